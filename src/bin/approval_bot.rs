@@ -0,0 +1,165 @@
+//! A reference approval bot: a daemon that approves the ops of the wallets it watches by a
+//! policy, as an approver of those wallets. It shows how an automated approver is meant to
+//! integrate with the program through the client module, and is not meant to hold keys that
+//! matter as it is.
+//!
+//! It takes the path of a JSON config file, and polls the program's op accounts over RPC:
+//!
+//! ```json
+//! {
+//!   "rpc_url": "http://localhost:8899",
+//!   "program_id": "<wallet program address>",
+//!   "keypair": "/path/to/approver-keypair.json",
+//!   "poll_interval_secs": 10,
+//!   "policy": {
+//!     "wallets": ["<wallet address>"],
+//!     "approved_op_codes": ["Transfer", "Wrap"],
+//!     "max_transfer_amount": 1000000000,
+//!     "deny_unmatched": false
+//!   }
+//! }
+//! ```
+
+use std::error::Error;
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde_json::Value;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+use strike_wallet::client::approval_bot::{pending_dispositions, ApprovalPolicy};
+use strike_wallet::model::multisig_op::MultisigOpCode;
+
+type BotResult<T> = Result<T, Box<dyn Error>>;
+
+struct Config {
+    rpc_url: String,
+    program_id: Pubkey,
+    approver: Keypair,
+    poll_interval: Duration,
+    policy: ApprovalPolicy,
+}
+
+fn field<'a>(value: &'a Value, name: &str) -> BotResult<&'a Value> {
+    value
+        .get(name)
+        .ok_or_else(|| format!("config is missing {}", name).into())
+}
+
+fn string_field<'a>(value: &'a Value, name: &str) -> BotResult<&'a str> {
+    field(value, name)?
+        .as_str()
+        .ok_or_else(|| format!("{} has to be a string", name).into())
+}
+
+fn pubkey(value: &Value) -> BotResult<Pubkey> {
+    let address = value.as_str().ok_or("addresses have to be strings")?;
+    Ok(Pubkey::from_str(address)?)
+}
+
+fn op_code(value: &Value) -> BotResult<MultisigOpCode> {
+    let name = value.as_str().ok_or("op codes have to be strings")?;
+    (0..=u8::MAX)
+        .filter_map(|code| MultisigOpCode::from_u8(code).ok())
+        .find(|op_code| format!("{:?}", op_code) == name)
+        .ok_or_else(|| format!("unknown op code {}", name).into())
+}
+
+fn parse_policy(value: &Value) -> BotResult<ApprovalPolicy> {
+    let list = |name| -> BotResult<&Vec<Value>> {
+        field(value, name)?
+            .as_array()
+            .ok_or_else(|| format!("{} has to be a list", name).into())
+    };
+    Ok(ApprovalPolicy {
+        wallets: list("wallets")?
+            .iter()
+            .map(pubkey)
+            .collect::<BotResult<_>>()?,
+        approved_op_codes: list("approved_op_codes")?
+            .iter()
+            .map(op_code)
+            .collect::<BotResult<_>>()?,
+        max_transfer_amount: value.get("max_transfer_amount").and_then(Value::as_u64),
+        deny_unmatched: value
+            .get("deny_unmatched")
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+    })
+}
+
+fn read_config(path: &str) -> BotResult<Config> {
+    let value: Value = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+    Ok(Config {
+        rpc_url: string_field(&value, "rpc_url")?.to_string(),
+        program_id: pubkey(field(&value, "program_id")?)?,
+        approver: read_keypair_file(string_field(&value, "keypair")?)?,
+        poll_interval: Duration::from_secs(
+            value
+                .get("poll_interval_secs")
+                .and_then(Value::as_u64)
+                .unwrap_or(10),
+        ),
+        policy: parse_policy(field(&value, "policy")?)?,
+    })
+}
+
+/// Registers the approver's disposition on every op waiting on it, one transaction per op, so
+/// that an op which can't take the disposition doesn't hold back the others.
+fn poll(rpc_client: &RpcClient, config: &Config) -> BotResult<()> {
+    let accounts = rpc_client.get_program_accounts(&config.program_id)?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+    let instructions = pending_dispositions(
+        &config.program_id,
+        &config.approver.pubkey(),
+        &config.policy,
+        accounts
+            .iter()
+            .map(|(address, account)| (*address, account.data.as_slice())),
+        now,
+    );
+    for instruction in instructions {
+        let multisig_op_account = instruction.accounts[0].pubkey;
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&config.approver.pubkey()),
+            &[&config.approver],
+            rpc_client.get_latest_blockhash()?,
+        );
+        match rpc_client.send_and_confirm_transaction(&transaction) {
+            Ok(signature) => println!(
+                "registered disposition on {} in {}",
+                multisig_op_account, signature
+            ),
+            Err(err) => eprintln!(
+                "failed to register disposition on {}: {}",
+                multisig_op_account, err
+            ),
+        }
+    }
+    Ok(())
+}
+
+fn main() -> BotResult<()> {
+    let path = std::env::args()
+        .nth(1)
+        .ok_or("usage: approval-bot <config.json>")?;
+    let config = read_config(&path)?;
+    let rpc_client =
+        RpcClient::new_with_commitment(config.rpc_url.clone(), CommitmentConfig::confirmed());
+    println!(
+        "approving as {} for {} wallets",
+        config.approver.pubkey(),
+        config.policy.wallets.len()
+    );
+    loop {
+        if let Err(err) = poll(&rpc_client, &config) {
+            eprintln!("poll failed: {}", err);
+        }
+        thread::sleep(config.poll_interval);
+    }
+}
@@ -0,0 +1,3 @@
+pub mod approval_bot;
+pub mod builders;
+pub mod transactions;
@@ -0,0 +1,124 @@
+//! The decisions of the reference approval bot, `src/bin/approval_bot.rs`, kept apart from its
+//! RPC plumbing so that end-to-end tests can run the bot against the accounts of a test
+//! validator as well.
+//!
+//! The bot is an approver of the wallets it watches. It reads the program's op accounts,
+//! applies its policy to the ops of those wallets that are waiting on it, and registers its
+//! disposition on them with `SetApprovalDisposition`.
+
+use solana_program::instruction::Instruction;
+use solana_program::pubkey::Pubkey;
+
+use crate::client::builders;
+use crate::model::multisig_op::{ApprovalDisposition, MultisigOp, MultisigOpCode};
+
+/// What the bot approves. Ops it doesn't approve are left to the other approvers, unless it is
+/// set to deny them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApprovalPolicy {
+    /// The wallets whose ops the bot decides on.
+    pub wallets: Vec<Pubkey>,
+    /// The types of op the bot approves. Ops whose type wasn't known when they were started,
+    /// such as dApp transactions supplied after their init, are never approved.
+    pub approved_op_codes: Vec<MultisigOpCode>,
+    /// The most a transfer-category op may move, in the units of what it moves, for the bot
+    /// to approve it, or None for no limit.
+    pub max_transfer_amount: Option<u64>,
+    /// Whether the bot denies the ops it doesn't approve.
+    pub deny_unmatched: bool,
+}
+
+impl ApprovalPolicy {
+    /// The disposition the policy registers on the op, or None if it leaves the op alone.
+    pub fn decide(&self, multisig_op: &MultisigOp) -> Option<ApprovalDisposition> {
+        let approved = multisig_op
+            .op_code
+            .map_or(false, |op_code| self.approved_op_codes.contains(&op_code))
+            && self
+                .max_transfer_amount
+                .map_or(true, |max| multisig_op.transfer_amount <= max);
+        if approved {
+            Some(ApprovalDisposition::APPROVE)
+        } else if self.deny_unmatched {
+            Some(ApprovalDisposition::DENY)
+        } else {
+            None
+        }
+    }
+}
+
+/// The dispositions the approver owes the ops among the given program accounts, as of `now`.
+/// Accounts that aren't ops are skipped, as are ops of other wallets, ops that are no longer
+/// pending or have expired, ops whose params aren't known yet, and ops the approver isn't an
+/// approver of or has registered a disposition on already.
+pub fn pending_dispositions<'a>(
+    program_id: &Pubkey,
+    approver: &Pubkey,
+    policy: &ApprovalPolicy,
+    accounts: impl IntoIterator<Item = (Pubkey, &'a [u8])>,
+    now: i64,
+) -> Vec<Instruction> {
+    accounts
+        .into_iter()
+        .filter_map(|(address, data)| {
+            let multisig_op = MultisigOp::unpack_sized(data).ok()?;
+            let params_hash = multisig_op.params_hash?;
+            let awaits_approver = multisig_op.disposition_records.iter().any(|record| {
+                record.approver == *approver && record.disposition == ApprovalDisposition::NONE
+            });
+            if !policy.wallets.contains(&multisig_op.wallet)
+                || !multisig_op.state.is_pending()
+                || multisig_op.expires_at < now
+                || !awaits_approver
+            {
+                return None;
+            }
+            let disposition = policy.decide(&multisig_op)?;
+            Some(builders::set_approval_disposition(
+                program_id,
+                &address,
+                approver,
+                disposition,
+                params_hash,
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::program_pack::Pack;
+
+    fn transfer(amount: u64) -> MultisigOp {
+        let mut multisig_op = MultisigOp::unpack_unchecked(&vec![0; MultisigOp::LEN]).unwrap();
+        multisig_op.op_code = Some(MultisigOpCode::Transfer);
+        multisig_op.transfer_amount = amount;
+        multisig_op
+    }
+
+    #[test]
+    fn test_decide() {
+        let mut policy = ApprovalPolicy {
+            wallets: vec![Pubkey::new_unique()],
+            approved_op_codes: vec![MultisigOpCode::Transfer],
+            max_transfer_amount: Some(100),
+            deny_unmatched: false,
+        };
+        assert_eq!(
+            policy.decide(&transfer(100)),
+            Some(ApprovalDisposition::APPROVE)
+        );
+        assert_eq!(policy.decide(&transfer(101)), None);
+
+        let mut unknown = transfer(1);
+        unknown.op_code = None;
+        assert_eq!(policy.decide(&unknown), None);
+
+        policy.deny_unmatched = true;
+        assert_eq!(
+            policy.decide(&transfer(101)),
+            Some(ApprovalDisposition::DENY)
+        );
+    }
+}
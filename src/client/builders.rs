@@ -0,0 +1,4278 @@
+//! Builders for every instruction of the wallet program, so that off-chain services can build
+//! inits, approvals and finalizes with the accounts in the order the handlers expect.
+//!
+//! Init builders charge no fee; use `with_fee` to set one on any init that supports it.
+
+use std::borrow::Borrow;
+use std::time::Duration;
+
+use bytes::BufMut;
+use solana_program::hash::Hash;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::{bpf_loader_upgradeable, ed25519_program, system_program, sysvar};
+
+use crate::instruction::ProgramInstruction::{Cleanup, Migrate};
+use crate::instruction::{
+    approval_disposition_signature_message, init_signature_message,
+    pack_supply_dapp_transaction_instructions, BalanceAccountAddressWhitelistUpdate,
+    BalanceAccountCreation, BalanceAccountPolicyUpdate, ConfigUpdate, FullRotation, RewardSplit,
+    TransferFinalizeStep,
+};
+use crate::model::approval_group::ApprovalGroup;
+use crate::model::balance_account::BalanceAccount;
+use crate::model::compliance_policy::CompliancePolicy;
+use crate::model::destination_receipt::DestinationReceipt;
+use crate::model::fee_policy::FeePolicy;
+use crate::model::finalize_submitter_policy::FinalizeSubmitterPolicy;
+use crate::model::insurance_attestation::InsuranceAttestation;
+use crate::model::internal_transfer_handshake::InternalTransferHandshake;
+use crate::model::merkle_address_book::MerkleAddressBook;
+use crate::model::price_oracle::PriceOracle;
+use crate::model::program_parameters::ProgramParameters;
+use crate::model::rent_return_allowlist::RentReturnAllowlist;
+use crate::model::session_key_registry::{SessionKeyRegistry, SessionKeyScope};
+use crate::model::stake_weight_governance::StakeWeightGovernance;
+use crate::model::token_account_registry::TokenAccountRegistry;
+use crate::model::wallet::WalletGuidHash;
+use crate::model::wallet_event_log::WalletEventLog;
+use crate::model::wallet_freeze::WalletFreeze;
+use crate::model::wallet_read_model::WalletReadModel;
+use crate::model::wallet_registry::WalletRegistration;
+use crate::model::wallet_replication::{MirroredUpdate, WalletReplication};
+use crate::model::wallet_stats::WalletStats;
+use crate::model::wrapper_book::{WrapperBook, WrapperEntry};
+use crate::pda::{PdaSeeds, TemporaryUnwrappingAccountSeeds};
+use crate::serialization_utils::pack_option;
+use crate::{
+    constants::spl_memo,
+    instruction::{
+        AddressBookExpiration, AddressBookImport, AddressBookUpdate, BalanceAccountWhitelistUpdate,
+        DAppBookUpdate, InitialWalletConfig, ProgramInstruction, WalletConfigPolicyUpdate,
+    },
+    lookup_table,
+    model::{
+        address_book::{
+            AddressBookEntry, AddressBookEntryKind, AddressBookEntryNameHash, AddressBookEntryRisk,
+            DAppBookEntry,
+        },
+        assistant::Assistant,
+        balance_account::{BalanceAccountGuidHash, BalanceAccountNameHash},
+        multisig_op::{
+            ApprovalDisposition, BooleanSetting, DispositionReason, MintAuthorityType,
+            SlotUpdateType, WrapDirection,
+        },
+        signer::Signer,
+    },
+    utils,
+    utils::{associated_token_address, SlotId},
+};
+
+pub fn init_wallet(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    assistant_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    wallet_guid_hash: WalletGuidHash,
+    initial_config: InitialWalletConfig,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new_readonly(*assistant_account, true),
+        AccountMeta::new(*rent_return_account, true),
+        AccountMeta::new_readonly(ProgramParameters::find_address(program_id).0, false),
+        AccountMeta::new(
+            WalletRegistration::find_address(&wallet_guid_hash, program_id).0,
+            false,
+        ),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: ProgramInstruction::InitWallet {
+            wallet_guid_hash,
+            initial_config,
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+#[cfg(feature = "demo")]
+pub fn init_demo_wallet(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    assistant_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    wallet_guid_hash: WalletGuidHash,
+    initial_config: InitialWalletConfig,
+    balance_accounts: Vec<crate::instruction::DemoBalanceAccount>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new_readonly(*assistant_account, true),
+        AccountMeta::new(*rent_return_account, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    for balance_account in &balance_accounts {
+        accounts.push(AccountMeta::new(
+            BalanceAccount::find_address(&wallet_guid_hash, &balance_account.guid_hash, program_id)
+                .0,
+            false,
+        ));
+    }
+    accounts.push(AccountMeta::new(
+        WalletRegistration::find_address(&wallet_guid_hash, program_id).0,
+        false,
+    ));
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: ProgramInstruction::InitDemoWallet {
+            wallet_guid_hash,
+            initial_config,
+            balance_accounts,
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+fn init_multisig_op(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    program_instruction: ProgramInstruction,
+) -> Instruction {
+    let mut accounts = vec![AccountMeta::new(*multisig_op_account, false)];
+    accounts.push(AccountMeta::new(*wallet_account, false));
+    accounts.push(AccountMeta::new_readonly(*initiator_account, true));
+    accounts.push(AccountMeta::new_readonly(sysvar::clock::id(), false));
+    accounts.push(AccountMeta::new_readonly(*rent_return_account, true));
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: program_instruction.borrow().pack(),
+    }
+}
+
+/// Supplies the program parameters to an init bounded by them, as its last account.
+fn with_program_parameters(mut instruction: Instruction) -> Instruction {
+    instruction.accounts.push(AccountMeta::new_readonly(
+        ProgramParameters::find_address(&instruction.program_id).0,
+        false,
+    ));
+    instruction
+}
+
+pub fn set_approval_disposition(
+    program_id: &Pubkey,
+    multisig_op_account: &Pubkey,
+    approver: &Pubkey,
+    disposition: ApprovalDisposition,
+    params_hash: Hash,
+) -> Instruction {
+    set_approval_disposition_with_reason(
+        program_id,
+        multisig_op_account,
+        approver,
+        disposition,
+        params_hash,
+        None,
+    )
+}
+
+/// A `SetApprovalDisposition` recording the approver's reason for the disposition on the op.
+pub fn set_approval_disposition_with_reason(
+    program_id: &Pubkey,
+    multisig_op_account: &Pubkey,
+    approver: &Pubkey,
+    disposition: ApprovalDisposition,
+    params_hash: Hash,
+    reason: Option<DispositionReason>,
+) -> Instruction {
+    let data = ProgramInstruction::SetApprovalDisposition {
+        disposition,
+        params_hash,
+        reason,
+    }
+    .borrow()
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new_readonly(*approver, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+const FEE_AMOUNT: u64 = 0;
+const FEE_ACCOUNT_GUID_HASH_NONE: Option<BalanceAccountGuidHash> = None;
+
+/// The tag, then the fee amount and optional fee account guid hash, which every init except
+/// `InitOpBundle` starts with.
+const INIT_FEE_PREFIX_LEN: usize = 1 + 8 + 1 + BalanceAccountGuidHash::LEN;
+
+/// Sets the fee charged by an init built with one of the builders below, returning an error if
+/// the instruction is not an init taking a fee.
+pub fn with_fee(
+    mut init: Instruction,
+    fee_amount: u64,
+    fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+) -> Result<Instruction, ProgramError> {
+    let program_instruction = ProgramInstruction::unpack(&init.data)?;
+    if !program_instruction.is_init()
+        || matches!(program_instruction, ProgramInstruction::InitOpBundle { .. })
+    {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let mut prefix = Vec::with_capacity(INIT_FEE_PREFIX_LEN);
+    prefix.push(init.data[0]);
+    prefix.put_u64_le(fee_amount);
+    pack_option(fee_account_guid_hash.as_ref(), &mut prefix);
+    init.data.splice(..INIT_FEE_PREFIX_LEN, prefix);
+    Ok(init)
+}
+
+pub fn init_balance_account_creation_instruction(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    slot_id: SlotId<BalanceAccount>,
+    account_guid_hash: BalanceAccountGuidHash,
+    name_hash: BalanceAccountNameHash,
+    approvals_required_for_transfer: u8,
+    approval_timeout_for_transfer: Duration,
+    approvers: Vec<SlotId<Signer>>,
+    signers_hash: Hash,
+    whitelist_enabled: BooleanSetting,
+    dapps_enabled: BooleanSetting,
+    address_book_slot_id: SlotId<AddressBookEntry>,
+) -> Instruction {
+    with_program_parameters(init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        rent_return_account,
+        ProgramInstruction::InitBalanceAccountCreation {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            account_guid_hash,
+            creation_params: BalanceAccountCreation {
+                slot_id,
+                name_hash,
+                approvals_required_for_transfer,
+                approval_timeout_for_transfer,
+                transfer_approvers: approvers.clone(),
+                signers_hash,
+                whitelist_enabled,
+                dapps_enabled,
+                address_book_slot_id,
+            },
+        },
+    ))
+}
+
+pub fn finalize_balance_account_creation(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    creation_params: BalanceAccountCreation,
+    fee_account_maybe: Option<&Pubkey>,
+) -> Instruction {
+    let data = ProgramInstruction::FinalizeBalanceAccountCreation {
+        account_guid_hash,
+        creation_params,
+    }
+    .borrow()
+    .pack();
+    let mut accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new(*rent_return_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    if let Some(fee_account) = fee_account_maybe {
+        accounts.push(AccountMeta::new(*fee_account, false));
+        accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Passes the balance account to a `FinalizeBalanceAccountCreation`, so that lamports sent to
+/// its address ahead of time are returned to the rent return account if the creation was
+/// denied or expired.
+pub fn with_balance_account_reclaim(
+    mut finalize: Instruction,
+    balance_account: &Pubkey,
+) -> Instruction {
+    finalize
+        .accounts
+        .push(AccountMeta::new(*balance_account, false));
+    finalize
+        .accounts
+        .push(AccountMeta::new_readonly(system_program::id(), false));
+    finalize
+}
+
+pub fn cleanup_uncreated_balance_account(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    balance_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    token_accounts: Vec<Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*wallet_account, false),
+        AccountMeta::new(*balance_account, false),
+        AccountMeta::new(*rent_return_account, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    if !token_accounts.is_empty() {
+        accounts.push(AccountMeta::new_readonly(spl_token::id(), false));
+        accounts.extend(
+            token_accounts
+                .iter()
+                .map(|token_account| AccountMeta::new(*token_account, false)),
+        );
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: ProgramInstruction::CleanupUncreatedBalanceAccount { account_guid_hash }.pack(),
+    }
+}
+
+pub fn init_dapp_book_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    update: DAppBookUpdate,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        rent_return_account,
+        ProgramInstruction::InitDAppBookUpdate {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            update,
+        },
+    )
+}
+
+pub fn finalize_dapp_book_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    update: DAppBookUpdate,
+    fee_account_maybe: Option<&Pubkey>,
+) -> Instruction {
+    let data = ProgramInstruction::FinalizeDAppBookUpdate { update }
+        .borrow()
+        .pack();
+    let mut accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new(*rent_return_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    if let Some(fee_account) = fee_account_maybe {
+        accounts.push(AccountMeta::new(*fee_account, false));
+        accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+pub fn init_balance_account_policy_update_instruction(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    update: BalanceAccountPolicyUpdate,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new_readonly(*initiator_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(*rent_return_account, true),
+            AccountMeta::new_readonly(ProgramParameters::find_address(program_id).0, false),
+        ],
+        data: ProgramInstruction::InitBalanceAccountPolicyUpdate {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            account_guid_hash,
+            update: update.clone(),
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+pub fn finalize_balance_account_policy_update_instruction(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    update: BalanceAccountPolicyUpdate,
+    fee_account_maybe: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new(*rent_return_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    if let Some(fee_account) = fee_account_maybe {
+        accounts.push(AccountMeta::new(*fee_account, false));
+        accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: ProgramInstruction::FinalizeBalanceAccountPolicyUpdate {
+            account_guid_hash,
+            update,
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+pub fn init_transfer(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    source_account: &Pubkey,
+    destination_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    amount: u64,
+    destination_name_hash: AddressBookEntryNameHash,
+    token_mint: &Pubkey,
+    fee_payer: &Pubkey,
+    destination_acknowledgement_required: bool,
+) -> Instruction {
+    init_transfer_with_memo(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        source_account,
+        destination_account,
+        account_guid_hash,
+        amount,
+        destination_name_hash,
+        token_mint,
+        fee_payer,
+        destination_acknowledgement_required,
+        None,
+    )
+}
+
+pub fn init_transfer_with_memo(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    source_account: &Pubkey,
+    destination_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    amount: u64,
+    destination_name_hash: AddressBookEntryNameHash,
+    token_mint: &Pubkey,
+    fee_payer: &Pubkey,
+    destination_acknowledgement_required: bool,
+    memo_hash: Option<Hash>,
+) -> Instruction {
+    init_transfer_with_options(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        source_account,
+        destination_account,
+        account_guid_hash,
+        amount,
+        destination_name_hash,
+        token_mint,
+        fee_payer,
+        destination_acknowledgement_required,
+        memo_hash,
+        false,
+    )
+}
+
+/// An `InitTransfer` that may also allow its finalize to create the destination's associated
+/// token account.
+pub fn init_transfer_with_options(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    source_account: &Pubkey,
+    destination_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    amount: u64,
+    destination_name_hash: AddressBookEntryNameHash,
+    token_mint: &Pubkey,
+    fee_payer: &Pubkey,
+    destination_acknowledgement_required: bool,
+    memo_hash: Option<Hash>,
+    allow_destination_ata_creation: bool,
+) -> Instruction {
+    let data = ProgramInstruction::InitTransfer {
+        fee_amount: FEE_AMOUNT,
+        fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+        account_guid_hash,
+        amount,
+        destination_name_hash,
+        destination_acknowledgement_required,
+        deduct_fee_from_amount: false,
+        allow_destination_ata_creation,
+        memo_hash,
+        destination_proof: None,
+    }
+    .borrow()
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new(*source_account, false),
+        AccountMeta::new_readonly(*destination_account, false),
+        AccountMeta::new_readonly(*initiator_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new(*fee_payer, true),
+        AccountMeta::new_readonly(*token_mint, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+pub fn finalize_transfer(
+    program_id: &Pubkey,
+    multisig_op_account: &Pubkey,
+    wallet_account: &Pubkey,
+    source_account: &Pubkey,
+    destination_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    amount: u64,
+    token_mint: &Pubkey,
+    token_program: Option<&Pubkey>,
+    fee_account_maybe: Option<&Pubkey>,
+    handshake_account_maybe: Option<&Pubkey>,
+) -> Instruction {
+    finalize_transfer_with_memo(
+        program_id,
+        multisig_op_account,
+        wallet_account,
+        source_account,
+        destination_account,
+        rent_return_account,
+        account_guid_hash,
+        amount,
+        token_mint,
+        token_program,
+        fee_account_maybe,
+        handshake_account_maybe,
+        None,
+    )
+}
+
+pub fn finalize_transfer_with_memo(
+    program_id: &Pubkey,
+    multisig_op_account: &Pubkey,
+    wallet_account: &Pubkey,
+    source_account: &Pubkey,
+    destination_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    amount: u64,
+    token_mint: &Pubkey,
+    token_program: Option<&Pubkey>,
+    fee_account_maybe: Option<&Pubkey>,
+    handshake_account_maybe: Option<&Pubkey>,
+    memo: Option<Vec<u8>>,
+) -> Instruction {
+    finalize_transfer_with_options(
+        program_id,
+        multisig_op_account,
+        wallet_account,
+        source_account,
+        destination_account,
+        rent_return_account,
+        account_guid_hash,
+        amount,
+        token_mint,
+        token_program,
+        fee_account_maybe,
+        handshake_account_maybe,
+        memo,
+        false,
+    )
+}
+
+/// A `FinalizeTransfer` of a transfer that may have been allowed to create the destination's
+/// associated token account.
+pub fn finalize_transfer_with_options(
+    program_id: &Pubkey,
+    multisig_op_account: &Pubkey,
+    wallet_account: &Pubkey,
+    source_account: &Pubkey,
+    destination_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    amount: u64,
+    token_mint: &Pubkey,
+    token_program: Option<&Pubkey>,
+    fee_account_maybe: Option<&Pubkey>,
+    handshake_account_maybe: Option<&Pubkey>,
+    memo: Option<Vec<u8>>,
+    allow_destination_ata_creation: bool,
+) -> Instruction {
+    finalize_transfer_step(
+        program_id,
+        multisig_op_account,
+        wallet_account,
+        source_account,
+        destination_account,
+        rent_return_account,
+        account_guid_hash,
+        amount,
+        token_mint,
+        token_program,
+        fee_account_maybe,
+        handshake_account_maybe,
+        memo,
+        allow_destination_ata_creation,
+        TransferFinalizeStep::VerifyAndExecute,
+    )
+}
+
+/// Like `finalize_transfer_with_options`, but submitted as the `VerifyTransfer` or
+/// `ExecuteTransfer` that takes only the given step of the finalize.
+pub fn finalize_transfer_step(
+    program_id: &Pubkey,
+    multisig_op_account: &Pubkey,
+    wallet_account: &Pubkey,
+    source_account: &Pubkey,
+    destination_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    amount: u64,
+    token_mint: &Pubkey,
+    token_program: Option<&Pubkey>,
+    fee_account_maybe: Option<&Pubkey>,
+    handshake_account_maybe: Option<&Pubkey>,
+    memo: Option<Vec<u8>>,
+    allow_destination_ata_creation: bool,
+    step: TransferFinalizeStep,
+) -> Instruction {
+    let memo_program_maybe = memo.as_ref().map(|_| spl_memo::id());
+    let destination_acknowledgement_required = handshake_account_maybe.is_some();
+    let data = match step {
+        TransferFinalizeStep::VerifyAndExecute => ProgramInstruction::FinalizeTransfer {
+            account_guid_hash,
+            amount,
+            token_mint: *token_mint,
+            destination_acknowledgement_required,
+            deduct_fee_from_amount: false,
+            allow_destination_ata_creation,
+            memo,
+        },
+        TransferFinalizeStep::Verify => ProgramInstruction::VerifyTransfer {
+            account_guid_hash,
+            amount,
+            token_mint: *token_mint,
+            destination_acknowledgement_required,
+            deduct_fee_from_amount: false,
+            allow_destination_ata_creation,
+            memo,
+        },
+        TransferFinalizeStep::Execute => ProgramInstruction::ExecuteTransfer {
+            account_guid_hash,
+            amount,
+            token_mint: *token_mint,
+            destination_acknowledgement_required,
+            deduct_fee_from_amount: false,
+            allow_destination_ata_creation,
+            memo,
+        },
+    }
+    .pack();
+    let mut accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new_readonly(*wallet_account, false),
+        AccountMeta::new(*source_account, false),
+        AccountMeta::new(*destination_account, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(*rent_return_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new(
+            DestinationReceipt::find_address(
+                wallet_account,
+                destination_account,
+                token_mint,
+                program_id,
+            )
+            .0,
+            false,
+        ),
+    ];
+    if *token_mint != system_program::id() {
+        // SPL
+        let token_program = token_program.unwrap();
+        accounts.extend_from_slice(&[
+            AccountMeta::new(
+                associated_token_address(source_account, &token_mint, token_program),
+                false,
+            ),
+            AccountMeta::new(
+                associated_token_address(destination_account, &token_mint, token_program),
+                false,
+            ),
+            AccountMeta::new_readonly(*token_program, false),
+            AccountMeta::new_readonly(*token_mint, false),
+        ]);
+        if allow_destination_ata_creation {
+            accounts.push(AccountMeta::new_readonly(
+                spl_associated_token_account::id(),
+                false,
+            ));
+        }
+    }
+
+    if let Some(handshake_account) = handshake_account_maybe {
+        accounts.push(AccountMeta::new(*handshake_account, false));
+    }
+
+    if let Some(memo_program) = memo_program_maybe {
+        accounts.push(AccountMeta::new_readonly(memo_program, false));
+    }
+
+    if let Some(fee_account) = fee_account_maybe {
+        accounts.push(AccountMeta::new(*fee_account, false));
+        accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+pub fn cancel_transfer(
+    program_id: &Pubkey,
+    multisig_op_account: &Pubkey,
+    wallet_account: &Pubkey,
+    destination_account: &Pubkey,
+    approver: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    amount: u64,
+    token_mint: &Pubkey,
+) -> Instruction {
+    let data = ProgramInstruction::CancelTransfer {
+        account_guid_hash,
+        amount,
+        token_mint: *token_mint,
+        destination_acknowledgement_required: false,
+        deduct_fee_from_amount: false,
+        allow_destination_ata_creation: false,
+        memo: None,
+    }
+    .borrow()
+    .pack();
+    let accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new_readonly(*wallet_account, false),
+        AccountMeta::new_readonly(*destination_account, false),
+        AccountMeta::new_readonly(*approver, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+pub fn acknowledge_internal_transfer(
+    program_id: &Pubkey,
+    multisig_op_account: &Pubkey,
+    source_wallet_account: &Pubkey,
+    destination_wallet_account: &Pubkey,
+    destination_account: &Pubkey,
+    acknowledger_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    params_hash: Hash,
+) -> Instruction {
+    let data = ProgramInstruction::AcknowledgeInternalTransfer {
+        account_guid_hash,
+        params_hash,
+    }
+    .borrow()
+    .pack();
+
+    let (handshake_account, _) = InternalTransferHandshake::find_address(
+        multisig_op_account,
+        destination_account,
+        program_id,
+    );
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*multisig_op_account, false),
+        AccountMeta::new(handshake_account, false),
+        AccountMeta::new_readonly(*source_wallet_account, false),
+        AccountMeta::new_readonly(*destination_wallet_account, false),
+        AccountMeta::new_readonly(*destination_account, false),
+        AccountMeta::new_readonly(*acknowledger_account, true),
+        AccountMeta::new(*rent_return_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+pub fn add_pending_address_book_entries(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    assistant_account: &Pubkey,
+    entries: Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+) -> Instruction {
+    let data = ProgramInstruction::AddPendingAddressBookEntries { entries }
+        .borrow()
+        .pack();
+
+    let accounts = vec![
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new_readonly(*assistant_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+pub fn ratify_pending_address_book_entries(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    approver_account: &Pubkey,
+    entries: Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+) -> Instruction {
+    let data = ProgramInstruction::RatifyPendingAddressBookEntries { entries }
+        .borrow()
+        .pack();
+
+    let accounts = vec![
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new_readonly(*approver_account, true),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+pub fn init_wrap_unwrap(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    balance_account: &Pubkey,
+    account_guid_hash: &BalanceAccountGuidHash,
+    wallet_guid_hash: &WalletGuidHash,
+    amount: u64,
+    direction: WrapDirection,
+    token_account_rent: u64,
+) -> Instruction {
+    let data = ProgramInstruction::InitWrapUnwrap {
+        fee_amount: if direction == WrapDirection::UNWRAP {
+            token_account_rent
+        } else {
+            FEE_AMOUNT
+        },
+        fee_account_guid_hash: if direction == WrapDirection::UNWRAP {
+            Some(account_guid_hash.clone())
+        } else {
+            FEE_ACCOUNT_GUID_HASH_NONE
+        },
+        account_guid_hash: *account_guid_hash,
+        amount,
+        direction,
+    }
+    .borrow()
+    .pack();
+
+    let wrapped_sol_account = spl_associated_token_account::get_associated_token_address(
+        balance_account,
+        &spl_token::native_mint::id(),
+    );
+
+    let mut accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new(*balance_account, false),
+        AccountMeta::new(wrapped_sol_account, false),
+        AccountMeta::new_readonly(spl_token::native_mint::id(), false),
+        AccountMeta::new_readonly(*initiator_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new(*rent_return_account, true),
+    ];
+    if direction == WrapDirection::UNWRAP {
+        accounts.push(AccountMeta::new(
+            TemporaryUnwrappingAccountSeeds {
+                wallet_guid_hash: &wallet_guid_hash,
+                multisig_op: multisig_op_account,
+            }
+            .find_address(program_id)
+            .0,
+            false,
+        ))
+    }
+    accounts.append(
+        vec![
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+            AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+        ]
+        .as_mut(),
+    );
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+pub fn finalize_wrap_unwrap(
+    program_id: &Pubkey,
+    multisig_op_account: &Pubkey,
+    wallet_account: &Pubkey,
+    balance_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    wallet_guid_hash: &WalletGuidHash,
+    account_guid_hash: &BalanceAccountGuidHash,
+    amount: u64,
+    direction: WrapDirection,
+    fee_account_maybe: Option<&Pubkey>,
+) -> Instruction {
+    let data = ProgramInstruction::FinalizeWrapUnwrap {
+        account_guid_hash: *account_guid_hash,
+        amount,
+        direction,
+    }
+    .borrow()
+    .pack();
+
+    let wrapped_sol_account = spl_associated_token_account::get_associated_token_address(
+        balance_account,
+        &spl_token::native_mint::id(),
+    );
+
+    let mut accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new_readonly(*wallet_account, false),
+        AccountMeta::new(*balance_account, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(*rent_return_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new(wrapped_sol_account, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(spl_token::native_mint::id(), false),
+        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+    ];
+
+    if direction == WrapDirection::UNWRAP {
+        accounts.push(AccountMeta::new(
+            TemporaryUnwrappingAccountSeeds {
+                wallet_guid_hash: &wallet_guid_hash,
+                multisig_op: multisig_op_account,
+            }
+            .find_address(program_id)
+            .0,
+            false,
+        ))
+    }
+
+    if let Some(fee_account) = fee_account_maybe {
+        accounts.push(AccountMeta::new(*fee_account, false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+pub fn init_update_signer(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    slot_update_type: SlotUpdateType,
+    slot_id: SlotId<Signer>,
+    signer: Signer,
+    fee_amount: Option<u64>,
+    fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+) -> Instruction {
+    with_program_parameters(init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        rent_return_account,
+        ProgramInstruction::InitUpdateSigner {
+            fee_amount: fee_amount.unwrap_or(FEE_AMOUNT),
+            fee_account_guid_hash,
+            slot_update_type,
+            slot_id,
+            signer,
+        },
+    ))
+}
+
+pub fn finalize_update_signer(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    slot_update_type: SlotUpdateType,
+    slot_id: SlotId<Signer>,
+    signer: Signer,
+    fee_account_maybe: Option<&Pubkey>,
+) -> Instruction {
+    let data = ProgramInstruction::FinalizeUpdateSigner {
+        slot_update_type,
+        slot_id,
+        signer,
+    }
+    .borrow()
+    .pack();
+
+    let mut accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new(*rent_return_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    if let Some(fee_account) = fee_account_maybe {
+        accounts.push(AccountMeta::new(*fee_account, false));
+        accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+pub fn init_update_assistant(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    slot_update_type: SlotUpdateType,
+    slot_id: SlotId<Assistant>,
+    assistant: Assistant,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        rent_return_account,
+        ProgramInstruction::InitUpdateAssistant {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            slot_update_type,
+            slot_id,
+            assistant,
+        },
+    )
+}
+
+pub fn finalize_update_assistant(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    slot_update_type: SlotUpdateType,
+    slot_id: SlotId<Assistant>,
+    assistant: Assistant,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new(*rent_return_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: ProgramInstruction::FinalizeUpdateAssistant {
+            slot_update_type,
+            slot_id,
+            assistant,
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+pub fn init_finalize_submitter_policy_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    policy: &FinalizeSubmitterPolicy,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        rent_return_account,
+        ProgramInstruction::InitFinalizeSubmitterPolicyUpdate {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            policy: policy.clone(),
+        },
+    )
+}
+
+pub fn finalize_finalize_submitter_policy_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    policy: &FinalizeSubmitterPolicy,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new(*rent_return_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: ProgramInstruction::FinalizeFinalizeSubmitterPolicyUpdate {
+            policy: policy.clone(),
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+pub fn init_reward_split_policy_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    splits: &Vec<RewardSplit>,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        rent_return_account,
+        ProgramInstruction::InitRewardSplitPolicyUpdate {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            account_guid_hash,
+            splits: splits.clone(),
+        },
+    )
+}
+
+pub fn finalize_reward_split_policy_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    splits: &Vec<RewardSplit>,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new(*rent_return_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: ProgramInstruction::FinalizeRewardSplitPolicyUpdate {
+            account_guid_hash,
+            splits: splits.clone(),
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+pub fn init_wallet_config_policy_update_instruction(
+    program_id: Pubkey,
+    wallet_account: Pubkey,
+    multisig_op_account: Pubkey,
+    initiator_account: Pubkey,
+    rent_return_account: Pubkey,
+    update: &WalletConfigPolicyUpdate,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(multisig_op_account, false),
+            AccountMeta::new(wallet_account, false),
+            AccountMeta::new_readonly(initiator_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(rent_return_account, true),
+            AccountMeta::new_readonly(ProgramParameters::find_address(&program_id).0, false),
+        ],
+        data: ProgramInstruction::InitWalletConfigPolicyUpdate {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            update: update.clone(),
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+pub fn finalize_wallet_config_policy_update_instruction(
+    program_id: Pubkey,
+    wallet_account: Pubkey,
+    multisig_op_account: Pubkey,
+    rent_return_account: Pubkey,
+    update: &WalletConfigPolicyUpdate,
+    fee_account_maybe: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(multisig_op_account, false),
+        AccountMeta::new(wallet_account, false),
+        AccountMeta::new(rent_return_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    if let Some(fee_account) = fee_account_maybe {
+        accounts.push(AccountMeta::new(*fee_account, false));
+        accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    }
+
+    Instruction {
+        program_id,
+        accounts,
+        data: ProgramInstruction::FinalizeWalletConfigPolicyUpdate {
+            update: update.clone(),
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+pub fn init_dapp_transaction(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    multisig_data_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: &BalanceAccountGuidHash,
+    dapp: DAppBookEntry,
+    instruction_count: u8,
+) -> Instruction {
+    let data = ProgramInstruction::InitDAppTransaction {
+        fee_amount: FEE_AMOUNT,
+        fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+        account_guid_hash: *account_guid_hash,
+        dapp,
+        instruction_count,
+    }
+    .borrow()
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*multisig_data_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new_readonly(*initiator_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(*rent_return_account, true),
+        AccountMeta::new_readonly(ProgramParameters::find_address(program_id).0, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+pub fn supply_dapp_transaction_instructions(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    multisig_data_account: &Pubkey,
+    initiator_account: &Pubkey,
+    starting_index: u8,
+    instructions: &Vec<Instruction>,
+) -> Instruction {
+    let mut data = Vec::<u8>::new();
+    pack_supply_dapp_transaction_instructions(starting_index, instructions, &mut data);
+    let accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*multisig_data_account, false),
+        AccountMeta::new_readonly(*initiator_account, true),
+        AccountMeta::new_readonly(*wallet_account, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+pub fn finalize_dapp_transaction(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    multisig_data_account: &Pubkey,
+    balance_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: &BalanceAccountGuidHash,
+    params_hash: &Hash,
+    instructions: &Vec<Instruction>,
+    fee_account_maybe: Option<&Pubkey>,
+) -> Instruction {
+    let data = ProgramInstruction::FinalizeDAppTransaction {
+        account_guid_hash: *account_guid_hash,
+        params_hash: *params_hash,
+    }
+    .borrow()
+    .pack();
+
+    // the accounts below are expected below in this order by finalize
+    let mut accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*multisig_data_account, false),
+        AccountMeta::new_readonly(*wallet_account, false),
+        AccountMeta::new(*balance_account, false),
+        AccountMeta::new(*rent_return_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    // we also need to include any accounts referenced by the dapp instructions, but we don't
+    // want to repeat keys
+    let mut keys_to_skip = vec![
+        *multisig_op_account,
+        *multisig_data_account,
+        *wallet_account,
+        *balance_account,
+        *rent_return_account,
+        sysvar::clock::id(),
+    ];
+
+    // add the optional fee account if it is supplied
+    if let Some(fee_account) = fee_account_maybe {
+        accounts.push(AccountMeta::new(*fee_account, false));
+        accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+        keys_to_skip.push(*fee_account);
+        keys_to_skip.push(system_program::id());
+    }
+
+    accounts.extend(utils::unique_account_metas(&instructions, &keys_to_skip));
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+pub fn init_account_settings_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    whitelist_status: Option<BooleanSetting>,
+    dapps_enabled: Option<BooleanSetting>,
+    archived: Option<BooleanSetting>,
+    system_owned_destinations_only: Option<BooleanSetting>,
+    fee_amount: Option<u64>,
+    fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        rent_return_account,
+        ProgramInstruction::InitAccountSettingsUpdate {
+            fee_amount: fee_amount.unwrap_or(FEE_AMOUNT),
+            fee_account_guid_hash: if fee_account_guid_hash.is_some() {
+                fee_account_guid_hash
+            } else {
+                FEE_ACCOUNT_GUID_HASH_NONE
+            },
+            account_guid_hash,
+            whitelist_enabled: whitelist_status,
+            dapps_enabled,
+            archived,
+            system_owned_destinations_only,
+        },
+    )
+}
+
+pub fn finalize_account_settings_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    whitelist_status: Option<BooleanSetting>,
+    dapps_enabled: Option<BooleanSetting>,
+    archived: Option<BooleanSetting>,
+    system_owned_destinations_only: Option<BooleanSetting>,
+    fee_account_maybe: Option<&Pubkey>,
+) -> Instruction {
+    let data = ProgramInstruction::FinalizeAccountSettingsUpdate {
+        account_guid_hash,
+        whitelist_enabled: whitelist_status,
+        dapps_enabled,
+        archived,
+        system_owned_destinations_only,
+    }
+    .borrow()
+    .pack();
+
+    let mut accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new(*rent_return_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    if let Some(fee_account) = fee_account_maybe {
+        accounts.push(AccountMeta::new(*fee_account, false));
+        accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+pub fn init_balance_account_name_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    account_name_hash: BalanceAccountNameHash,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        rent_return_account,
+        ProgramInstruction::InitBalanceAccountNameUpdate {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            account_guid_hash,
+            account_name_hash,
+        },
+    )
+}
+
+pub fn finalize_balance_account_name_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    account_name_hash: BalanceAccountNameHash,
+    fee_account_maybe: Option<&Pubkey>,
+) -> Instruction {
+    let data = ProgramInstruction::FinalizeBalanceAccountNameUpdate {
+        account_guid_hash,
+        account_name_hash,
+    }
+    .borrow()
+    .pack();
+
+    let mut accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new(*rent_return_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    if let Some(fee_account) = fee_account_maybe {
+        accounts.push(AccountMeta::new(*fee_account, false));
+        accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+pub fn init_balance_account_deletion(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        rent_return_account,
+        ProgramInstruction::InitBalanceAccountDeletion {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            account_guid_hash,
+        },
+    )
+}
+
+pub fn finalize_balance_account_deletion(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    balance_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new(*balance_account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(*rent_return_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: ProgramInstruction::FinalizeBalanceAccountDeletion { account_guid_hash }.pack(),
+    }
+}
+
+pub fn init_spending_limit_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    token_mint: Pubkey,
+    amount: u64,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        rent_return_account,
+        ProgramInstruction::InitSpendingLimitUpdate {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            account_guid_hash,
+            token_mint,
+            amount,
+        },
+    )
+}
+
+pub fn finalize_spending_limit_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    token_mint: Pubkey,
+    amount: u64,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new(*rent_return_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: ProgramInstruction::FinalizeSpendingLimitUpdate {
+            account_guid_hash,
+            token_mint,
+            amount,
+        }
+        .pack(),
+    }
+}
+
+pub fn nudge(program_id: &Pubkey, multisig_op_account: &Pubkey, idle_slots: u64) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*multisig_op_account, false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: ProgramInstruction::Nudge { idle_slots }.pack(),
+    }
+}
+
+pub fn cleanup_expired_op(
+    program_id: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    side_accounts: Vec<Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*rent_return_account, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+    accounts.extend(
+        side_accounts
+            .iter()
+            .map(|side_account| AccountMeta::new(*side_account, false)),
+    );
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: ProgramInstruction::CleanupExpiredOp {}.pack(),
+    }
+}
+
+pub fn suspend_dapp(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    approver: &Pubkey,
+    dapp: DAppBookEntry,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new_readonly(*approver, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: ProgramInstruction::SuspendDApp { dapp }.pack(),
+    }
+}
+
+pub fn register_token_accounts(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    balance_account: &Pubkey,
+    initiator_account: &Pubkey,
+    payer_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    token_accounts: Vec<Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*wallet_account, false),
+        AccountMeta::new(
+            TokenAccountRegistry::find_address(wallet_account, &account_guid_hash, program_id).0,
+            false,
+        ),
+        AccountMeta::new_readonly(*balance_account, false),
+        AccountMeta::new_readonly(*initiator_account, true),
+        AccountMeta::new(*payer_account, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    accounts.extend(
+        token_accounts
+            .iter()
+            .map(|token_account| AccountMeta::new_readonly(*token_account, false)),
+    );
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: ProgramInstruction::RegisterTokenAccounts { account_guid_hash }.pack(),
+    }
+}
+
+/// A `SetApprovalDisposition` signed by a session key on behalf of the approver who
+/// registered it.
+pub fn set_session_approval_disposition(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    session_key: &Pubkey,
+    disposition: ApprovalDisposition,
+    params_hash: Hash,
+) -> Instruction {
+    let mut instruction = set_approval_disposition(
+        program_id,
+        multisig_op_account,
+        session_key,
+        disposition,
+        params_hash,
+    );
+    instruction.accounts.push(AccountMeta::new_readonly(
+        SessionKeyRegistry::find_address(wallet_account, program_id).0,
+        false,
+    ));
+    instruction
+}
+
+pub fn register_session_key(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    approver: &Pubkey,
+    payer_account: &Pubkey,
+    session_key: &Pubkey,
+    scope: SessionKeyScope,
+    expires_at: i64,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*wallet_account, false),
+            AccountMeta::new(
+                SessionKeyRegistry::find_address(wallet_account, program_id).0,
+                false,
+            ),
+            AccountMeta::new_readonly(*approver, true),
+            AccountMeta::new(*payer_account, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: ProgramInstruction::RegisterSessionKey {
+            session_key: *session_key,
+            scope,
+            expires_at,
+        }
+        .pack(),
+    }
+}
+
+pub fn revoke_session_key(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    approver: &Pubkey,
+    session_key: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(
+                SessionKeyRegistry::find_address(wallet_account, program_id).0,
+                false,
+            ),
+            AccountMeta::new_readonly(*approver, true),
+        ],
+        data: ProgramInstruction::RevokeSessionKey {
+            session_key: *session_key,
+        }
+        .pack(),
+    }
+}
+
+pub fn init_address_book_update_instruction(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    add_address_book_entries: Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+    remove_address_book_entries: Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+    deprecate_address_book_entries: Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+    balance_account_whitelist_updates: Vec<BalanceAccountWhitelistUpdate>,
+    address_book_risks: Vec<(SlotId<AddressBookEntry>, AddressBookEntryRisk)>,
+    address_book_expirations: Vec<AddressBookExpiration>,
+    address_book_entry_kinds: Vec<(SlotId<AddressBookEntry>, AddressBookEntryKind)>,
+) -> Instruction {
+    // the accounts of entries with a declared kind, so their owners are checked too
+    let declared_entry_accounts: Vec<AccountMeta> = add_address_book_entries
+        .iter()
+        .filter(|(id, _)| {
+            address_book_entry_kinds
+                .iter()
+                .any(|(declared_id, _)| declared_id == id)
+        })
+        .map(|(_, entry)| AccountMeta::new_readonly(entry.address, false))
+        .collect();
+    let mut instruction = init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        rent_return_account,
+        ProgramInstruction::InitAddressBookUpdate {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            update: AddressBookUpdate {
+                add_address_book_entries: add_address_book_entries.clone(),
+                remove_address_book_entries: remove_address_book_entries.clone(),
+                deprecate_address_book_entries: deprecate_address_book_entries.clone(),
+                balance_account_whitelist_updates: balance_account_whitelist_updates.clone(),
+                address_book_risks,
+                address_book_expirations,
+                address_book_entry_kinds,
+            },
+        },
+    );
+    instruction.accounts.extend(declared_entry_accounts);
+    instruction
+}
+
+pub fn finalize_address_book_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    update: AddressBookUpdate,
+    fee_account_maybe: Option<&Pubkey>,
+) -> Instruction {
+    let data = ProgramInstruction::FinalizeAddressBookUpdate { update }
+        .borrow()
+        .pack();
+    let mut accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new(*rent_return_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    if let Some(fee_account) = fee_account_maybe {
+        accounts.push(AccountMeta::new(*fee_account, false));
+        accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+pub fn migrate_account(
+    program_id: &Pubkey,
+    source_account: &Pubkey,
+    destination_account: &Pubkey,
+    rent_return_account: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*source_account, false),
+        AccountMeta::new(*destination_account, false),
+        AccountMeta::new_readonly(*rent_return_account, true),
+    ];
+
+    let data = Migrate {}.borrow().pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+pub fn cleanup_account(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    cleanup_account: &Pubkey,
+    rent_return_account: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*wallet_account, false),
+        AccountMeta::new(*cleanup_account, false),
+        AccountMeta::new(*rent_return_account, false),
+    ];
+
+    let data = Cleanup {}.borrow().pack();
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+pub fn init_balance_account_address_whitelist_update_instruction(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    update: BalanceAccountAddressWhitelistUpdate,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new_readonly(*initiator_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(*rent_return_account, true),
+        ],
+        data: ProgramInstruction::InitBalanceAccountAddressWhitelistUpdate {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            account_guid_hash,
+            update: update.clone(),
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+pub fn finalize_balance_account_address_whitelist_update_instruction(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    update: BalanceAccountAddressWhitelistUpdate,
+    fee_account_maybe: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new(*rent_return_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    if let Some(fee_account) = fee_account_maybe {
+        accounts.push(AccountMeta::new(*fee_account, false));
+        accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: ProgramInstruction::FinalizeBalanceAccountAddressWhitelistUpdate {
+            account_guid_hash,
+            update,
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+pub fn init_sign_data_instruction(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    data: &Vec<u8>,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new_readonly(*initiator_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(*rent_return_account, true),
+        ],
+        data: ProgramInstruction::InitSignData {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            data: data.clone(),
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+pub fn finalize_sign_data_instruction(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    data: &Vec<u8>,
+    fee_account_maybe: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new(*rent_return_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    if let Some(fee_account) = fee_account_maybe {
+        accounts.push(AccountMeta::new(*fee_account, false));
+        accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: ProgramInstruction::FinalizeSignData { data: data.clone() }
+            .borrow()
+            .pack(),
+    }
+}
+
+pub fn init_vote_rewards_withdrawal(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    vote_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    amount: u64,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new_readonly(*vote_account, false),
+            AccountMeta::new_readonly(*initiator_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(*rent_return_account, true),
+        ],
+        data: ProgramInstruction::InitVoteRewardsWithdrawal {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            account_guid_hash,
+            amount,
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+pub fn finalize_vote_rewards_withdrawal(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    vote_account: &Pubkey,
+    authority_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    amount: u64,
+    split_accounts: &Vec<Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new_readonly(*wallet_account, false),
+        AccountMeta::new(*vote_account, false),
+        AccountMeta::new(*authority_account, false),
+        AccountMeta::new_readonly(solana_program::vote::program::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(*rent_return_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+    accounts.extend(
+        split_accounts
+            .iter()
+            .map(|split_account| AccountMeta::new(*split_account, false)),
+    );
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: ProgramInstruction::FinalizeVoteRewardsWithdrawal {
+            account_guid_hash,
+            amount,
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+pub fn remove_deprecated_address_book_entries(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    assistant_account: &Pubkey,
+    entries: Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+) -> Instruction {
+    let data = ProgramInstruction::RemoveDeprecatedAddressBookEntries { entries }
+        .borrow()
+        .pack();
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new_readonly(*assistant_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data,
+    }
+}
+
+pub fn activate_wallet_config_policy_instruction(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: ProgramInstruction::ActivateWalletConfigPolicy {}
+            .borrow()
+            .pack(),
+    }
+}
+
+pub fn simulate_wallet_config_policy_update_instruction(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    update: &WalletConfigPolicyUpdate,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![AccountMeta::new_readonly(*wallet_account, false)],
+        data: ProgramInstruction::SimulateWalletConfigPolicyUpdate {
+            update: update.clone(),
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+pub fn simulate_balance_account_policy_update_instruction(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    account_guid_hash: &BalanceAccountGuidHash,
+    update: &BalanceAccountPolicyUpdate,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![AccountMeta::new_readonly(*wallet_account, false)],
+        data: ProgramInstruction::SimulateBalanceAccountPolicyUpdate {
+            account_guid_hash: *account_guid_hash,
+            update: update.clone(),
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+pub fn simulate_config_update_instruction(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    initiator_account: &Pubkey,
+    update: &ConfigUpdate,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*wallet_account, false),
+        AccountMeta::new_readonly(*initiator_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(ProgramParameters::find_address(program_id).0, false),
+    ];
+    if let ConfigUpdate::AddressBook(update) = update {
+        // the accounts of entries with a declared kind, so their owners are checked too
+        accounts.extend(
+            update
+                .add_address_book_entries
+                .iter()
+                .filter(|(id, _)| {
+                    update
+                        .address_book_entry_kinds
+                        .iter()
+                        .any(|(declared_id, _)| declared_id == id)
+                })
+                .map(|(_, entry)| AccountMeta::new_readonly(entry.address, false)),
+        );
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: ProgramInstruction::SimulateConfigUpdate {
+            update: update.clone(),
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+pub fn init_mint_to(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    balance_account: &Pubkey,
+    mint: &Pubkey,
+    destination_token_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    amount: u64,
+    destination_name_hash: AddressBookEntryNameHash,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new_readonly(*balance_account, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*destination_token_account, false),
+            AccountMeta::new_readonly(*initiator_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(*rent_return_account, true),
+        ],
+        data: ProgramInstruction::InitMintTo {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            account_guid_hash,
+            amount,
+            destination_name_hash,
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+pub fn finalize_mint_to(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    balance_account: &Pubkey,
+    mint: &Pubkey,
+    destination_token_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    amount: u64,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new_readonly(*wallet_account, false),
+            AccountMeta::new_readonly(*balance_account, false),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new(*destination_token_account, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new(*rent_return_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: ProgramInstruction::FinalizeMintTo {
+            account_guid_hash,
+            amount,
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+pub fn init_spl_token_accounts_creation(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    mint: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    payer_account_guid_hash: BalanceAccountGuidHash,
+    account_guid_hashes: Vec<BalanceAccountGuidHash>,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*initiator_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(*rent_return_account, true),
+        ],
+        data: ProgramInstruction::InitSPLTokenAccountsCreation {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            payer_account_guid_hash,
+            account_guid_hashes,
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+/// Finalizes an SPL token accounts creation, creating the token accounts of the given balance
+/// accounts, which have to be the next of the op's balance accounts still without one. When
+/// `continuing`, the op has already been finalized and this creates a further batch.
+pub fn finalize_spl_token_accounts_creation(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    payer_account: &Pubkey,
+    mint: &Pubkey,
+    token_program_id: &Pubkey,
+    rent_return_account: &Pubkey,
+    payer_account_guid_hash: BalanceAccountGuidHash,
+    account_guid_hashes: Vec<BalanceAccountGuidHash>,
+    batch_balance_accounts: &[Pubkey],
+    continuing: bool,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new_readonly(*wallet_account, false),
+        AccountMeta::new(*payer_account, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(*rent_return_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+    for balance_account in batch_balance_accounts {
+        accounts.push(AccountMeta::new_readonly(*balance_account, false));
+        accounts.push(AccountMeta::new(
+            associated_token_address(balance_account, mint, token_program_id),
+            false,
+        ));
+    }
+    let batch_size = batch_balance_accounts.len() as u8;
+    let instruction = if continuing {
+        ProgramInstruction::ContinueSPLTokenAccountsCreation {
+            payer_account_guid_hash,
+            account_guid_hashes,
+            batch_size,
+        }
+    } else {
+        ProgramInstruction::FinalizeSPLTokenAccountsCreation {
+            payer_account_guid_hash,
+            account_guid_hashes,
+            batch_size,
+        }
+    };
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.pack(),
+    }
+}
+
+pub fn init_set_mint_authority(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    balance_account: &Pubkey,
+    mint: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    authority_type: MintAuthorityType,
+    new_authority: Option<Pubkey>,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new_readonly(*balance_account, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*initiator_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(*rent_return_account, true),
+        ],
+        data: ProgramInstruction::InitSetMintAuthority {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            account_guid_hash,
+            authority_type,
+            new_authority,
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+pub fn finalize_set_mint_authority(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    balance_account: &Pubkey,
+    mint: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    authority_type: MintAuthorityType,
+    new_authority: Option<Pubkey>,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new_readonly(*wallet_account, false),
+            AccountMeta::new_readonly(*balance_account, false),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new(*rent_return_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: ProgramInstruction::FinalizeSetMintAuthority {
+            account_guid_hash,
+            authority_type,
+            new_authority,
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+pub fn init_payment_channel(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    channel_account: &Pubkey,
+    destination: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    destination_name_hash: AddressBookEntryNameHash,
+    token_mint: Pubkey,
+    allowance: u64,
+    duration: Duration,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new_readonly(*channel_account, false),
+            AccountMeta::new_readonly(*destination, false),
+            AccountMeta::new_readonly(*initiator_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(*rent_return_account, true),
+        ],
+        data: ProgramInstruction::InitPaymentChannel {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            account_guid_hash,
+            destination_name_hash,
+            token_mint,
+            allowance,
+            duration,
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+pub fn finalize_payment_channel(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    channel_account: &Pubkey,
+    destination: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    token_mint: Pubkey,
+    allowance: u64,
+    duration: Duration,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new_readonly(*wallet_account, false),
+            AccountMeta::new(*channel_account, false),
+            AccountMeta::new_readonly(*destination, false),
+            AccountMeta::new(*rent_return_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: ProgramInstruction::FinalizePaymentChannel {
+            account_guid_hash,
+            token_mint,
+            allowance,
+            duration,
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+pub fn channel_payment(
+    program_id: &Pubkey,
+    channel_account: &Pubkey,
+    wallet_account: &Pubkey,
+    balance_account: &Pubkey,
+    destination: &Pubkey,
+    assistant_account: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*channel_account, false),
+            AccountMeta::new_readonly(*wallet_account, false),
+            AccountMeta::new(*balance_account, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(*assistant_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: ProgramInstruction::ChannelPayment { amount }
+            .borrow()
+            .pack(),
+    }
+}
+
+pub fn init_op_bundle(
+    program_id: &Pubkey,
+    bundle_account: &Pubkey,
+    wallet_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    multisig_op_accounts: &[Pubkey],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*bundle_account, false),
+        AccountMeta::new_readonly(*wallet_account, false),
+        AccountMeta::new_readonly(*initiator_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(*rent_return_account, true),
+    ];
+    accounts.extend(
+        multisig_op_accounts
+            .iter()
+            .map(|multisig_op_account| AccountMeta::new_readonly(*multisig_op_account, false)),
+    );
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: ProgramInstruction::InitOpBundle {}.borrow().pack(),
+    }
+}
+
+pub fn approve_op_bundle(
+    program_id: &Pubkey,
+    bundle_account: &Pubkey,
+    wallet_account: &Pubkey,
+    approver_account: &Pubkey,
+    bundle_hash: Hash,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*bundle_account, false),
+            AccountMeta::new_readonly(*wallet_account, false),
+            AccountMeta::new_readonly(*approver_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: ProgramInstruction::ApproveOpBundle { bundle_hash }
+            .borrow()
+            .pack(),
+    }
+}
+
+pub fn apply_op_bundle_approval(
+    program_id: &Pubkey,
+    bundle_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*bundle_account, false),
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: ProgramInstruction::ApplyOpBundleApproval {}.borrow().pack(),
+    }
+}
+
+pub fn close_op_bundle(
+    program_id: &Pubkey,
+    bundle_account: &Pubkey,
+    rent_return_account: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*bundle_account, false),
+            AccountMeta::new(*rent_return_account, true),
+        ],
+        data: ProgramInstruction::CloseOpBundle {}.borrow().pack(),
+    }
+}
+
+pub fn init_lookup_table_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    lookup_table: Option<Pubkey>,
+    new_addresses: Vec<Pubkey>,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new_readonly(*wallet_account, false),
+            AccountMeta::new_readonly(*initiator_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(*rent_return_account, true),
+        ],
+        data: ProgramInstruction::InitLookupTableUpdate {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            lookup_table,
+            new_addresses,
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+pub fn finalize_lookup_table_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    lookup_table_account: &Pubkey,
+    authority_account: &Pubkey,
+    lookup_table: Option<Pubkey>,
+    new_addresses: Vec<Pubkey>,
+    recent_slot: u64,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new_readonly(*wallet_account, false),
+            AccountMeta::new(*rent_return_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new(*lookup_table_account, false),
+            AccountMeta::new_readonly(*authority_account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(lookup_table::program::id(), false),
+        ],
+        data: ProgramInstruction::FinalizeLookupTableUpdate {
+            lookup_table,
+            new_addresses,
+            recent_slot,
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+pub fn init_address_book_import(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    source_wallet_account: &Pubkey,
+    source_approver_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    entries: Vec<(SlotId<AddressBookEntry>, SlotId<AddressBookEntry>)>,
+    balance_accounts: Vec<(BalanceAccountGuidHash, BalanceAccountGuidHash)>,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new_readonly(*wallet_account, false),
+            AccountMeta::new_readonly(*source_wallet_account, false),
+            AccountMeta::new_readonly(*source_approver_account, true),
+            AccountMeta::new_readonly(*initiator_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(*rent_return_account, true),
+        ],
+        data: ProgramInstruction::InitAddressBookImport {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            entries,
+            balance_accounts,
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+pub fn finalize_address_book_import(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    import: AddressBookImport,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new(*rent_return_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: ProgramInstruction::FinalizeAddressBookImport { import }
+            .borrow()
+            .pack(),
+    }
+}
+
+pub fn init_committed_transfer(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    amount: u64,
+    destination_commitment: Hash,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new_readonly(*wallet_account, false),
+            AccountMeta::new_readonly(*initiator_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(*rent_return_account, true),
+        ],
+        data: ProgramInstruction::InitCommittedTransfer {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            account_guid_hash,
+            amount,
+            destination_commitment,
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+pub fn finalize_committed_transfer(
+    program_id: &Pubkey,
+    multisig_op_account: &Pubkey,
+    wallet_account: &Pubkey,
+    source_account: &Pubkey,
+    destination_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    amount: u64,
+    destination_commitment: Hash,
+    destination_name_hash: AddressBookEntryNameHash,
+    salt: Hash,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new(*source_account, false),
+            AccountMeta::new(*destination_account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(*rent_return_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new(
+                DestinationReceipt::find_address(
+                    wallet_account,
+                    destination_account,
+                    &system_program::id(),
+                    program_id,
+                )
+                .0,
+                false,
+            ),
+        ],
+        data: ProgramInstruction::FinalizeCommittedTransfer {
+            account_guid_hash,
+            amount,
+            destination_commitment,
+            destination_name_hash,
+            salt,
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+pub fn init_compliance_policy_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    policy: Option<CompliancePolicy>,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        rent_return_account,
+        ProgramInstruction::InitCompliancePolicyUpdate {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            policy,
+        },
+    )
+}
+
+pub fn finalize_compliance_policy_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    policy: Option<CompliancePolicy>,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new(*rent_return_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: ProgramInstruction::FinalizeCompliancePolicyUpdate { policy }
+            .borrow()
+            .pack(),
+    }
+}
+
+pub fn compliance_approve(
+    program_id: &Pubkey,
+    multisig_op_account: &Pubkey,
+    service_account: &Pubkey,
+    params_hash: Hash,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new_readonly(*service_account, true),
+        ],
+        data: ProgramInstruction::ComplianceApprove { params_hash }
+            .borrow()
+            .pack(),
+    }
+}
+
+pub fn get_allowed_destinations(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    start_slot_id: SlotId<AddressBookEntry>,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![AccountMeta::new_readonly(*wallet_account, false)],
+        data: ProgramInstruction::GetAllowedDestinations {
+            account_guid_hash,
+            start_slot_id,
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+/// An ed25519 program instruction verifying the signature of the message by the public key, with
+/// the key, signature and message all in its own data.
+pub fn ed25519_signature_instruction(
+    public_key: &Pubkey,
+    signature: &[u8; 64],
+    message: &[u8],
+) -> Instruction {
+    const DATA_START: u16 = 16;
+    let public_key_offset = DATA_START;
+    let signature_offset = public_key_offset + 32;
+    let message_offset = signature_offset + 64;
+    let mut data = vec![1, 0];
+    for field in [
+        signature_offset,
+        u16::MAX,
+        public_key_offset,
+        u16::MAX,
+        message_offset,
+        message.len() as u16,
+        u16::MAX,
+    ] {
+        data.extend_from_slice(&field.to_le_bytes());
+    }
+    data.extend_from_slice(public_key.as_ref());
+    data.extend_from_slice(signature);
+    data.extend_from_slice(message);
+    Instruction {
+        program_id: ed25519_program::id(),
+        accounts: vec![],
+        data,
+    }
+}
+
+/// The message the initiator signs to submit the init through `init_with_signature`.
+pub fn init_with_signature_message(program_id: &Pubkey, init: &Instruction) -> Hash {
+    init_signature_message(
+        program_id,
+        &init
+            .accounts
+            .iter()
+            .map(|account_meta| account_meta.pubkey)
+            .collect::<Vec<_>>(),
+        &init.data,
+    )
+}
+
+/// Wraps the init instruction in an `InitWithSignature`, preceded by the ed25519 instruction
+/// verifying the initiator's signature of `init_with_signature_message`, so that the initiator
+/// doesn't sign the transaction.
+pub fn init_with_signature(
+    program_id: &Pubkey,
+    init: Instruction,
+    initiator: &Pubkey,
+    signature: &[u8; 64],
+) -> Vec<Instruction> {
+    let message = init_with_signature_message(program_id, &init);
+    let mut accounts = init
+        .accounts
+        .into_iter()
+        .map(|account_meta| AccountMeta {
+            is_signer: account_meta.is_signer && account_meta.pubkey != *initiator,
+            ..account_meta
+        })
+        .collect::<Vec<_>>();
+    accounts.push(AccountMeta::new_readonly(sysvar::instructions::id(), false));
+    vec![
+        ed25519_signature_instruction(initiator, signature, message.as_ref()),
+        Instruction {
+            program_id: *program_id,
+            accounts,
+            data: ProgramInstruction::InitWithSignature { init: init.data }
+                .borrow()
+                .pack(),
+        },
+    ]
+}
+
+/// A `SetApprovalDispositionBySignature`, preceded by the ed25519 instruction verifying the
+/// approver's signature of its `approval_disposition_signature_message`, which the approver can
+/// produce offline for anyone to submit.
+pub fn set_approval_disposition_by_signature(
+    program_id: &Pubkey,
+    multisig_op_account: &Pubkey,
+    approver: &Pubkey,
+    disposition: ApprovalDisposition,
+    params_hash: Hash,
+    expires_at: i64,
+    signature: &[u8; 64],
+) -> Vec<Instruction> {
+    let message = approval_disposition_signature_message(
+        program_id,
+        multisig_op_account,
+        disposition,
+        &params_hash,
+        expires_at,
+    );
+    vec![
+        ed25519_signature_instruction(approver, signature, message.as_ref()),
+        Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new(*multisig_op_account, false),
+                AccountMeta::new_readonly(*approver, false),
+                AccountMeta::new_readonly(sysvar::clock::id(), false),
+                AccountMeta::new_readonly(sysvar::instructions::id(), false),
+            ],
+            data: ProgramInstruction::SetApprovalDispositionBySignature {
+                disposition,
+                params_hash,
+                expires_at,
+            }
+            .pack(),
+        },
+    ]
+}
+
+/// A `SubmitAggregatedApproval`, preceded by the ed25519 instruction verifying the approval
+/// group's aggregated signature of the op's `approval_disposition_signature_message` for an
+/// approval against the group key.
+pub fn submit_aggregated_approval(
+    program_id: &Pubkey,
+    multisig_op_account: &Pubkey,
+    wallet_account: &Pubkey,
+    group_key: &Pubkey,
+    params_hash: Hash,
+    expires_at: i64,
+    signature: &[u8; 64],
+) -> Vec<Instruction> {
+    let message = approval_disposition_signature_message(
+        program_id,
+        multisig_op_account,
+        ApprovalDisposition::APPROVE,
+        &params_hash,
+        expires_at,
+    );
+    vec![
+        ed25519_signature_instruction(group_key, signature, message.as_ref()),
+        Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new(*multisig_op_account, false),
+                AccountMeta::new_readonly(*wallet_account, false),
+                AccountMeta::new_readonly(sysvar::clock::id(), false),
+                AccountMeta::new_readonly(sysvar::instructions::id(), false),
+            ],
+            data: ProgramInstruction::SubmitAggregatedApproval {
+                params_hash,
+                expires_at,
+            }
+            .pack(),
+        },
+    ]
+}
+
+pub fn init_with_compute_hint(init: Instruction, finalize_compute_units: u32) -> Instruction {
+    Instruction {
+        program_id: init.program_id,
+        accounts: init.accounts,
+        data: ProgramInstruction::InitWithComputeHint {
+            finalize_compute_units,
+            init: init.data,
+        }
+        .pack(),
+    }
+}
+
+pub fn init_wallet_replication_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    replication: Option<WalletReplication>,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        rent_return_account,
+        ProgramInstruction::InitWalletReplicationUpdate {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            replication,
+        },
+    )
+}
+
+pub fn finalize_wallet_replication_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    replication: Option<WalletReplication>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new(*wallet_account, false),
+        AccountMeta::new(*rent_return_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+    if let Some(WalletReplication::Primary { standby }) = replication {
+        accounts.push(AccountMeta::new_readonly(standby, false));
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: ProgramInstruction::FinalizeWalletReplicationUpdate { replication }
+            .borrow()
+            .pack(),
+    }
+}
+
+/// The accounts a primary's config finalizes need to mirror the change into its standby.
+pub fn standby_accounts(
+    program_id: &Pubkey,
+    primary_wallet_account: &Pubkey,
+    standby_wallet_account: &Pubkey,
+) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(*standby_wallet_account, false),
+        AccountMeta::new_readonly(
+            WalletReplication::mirror_authority(primary_wallet_account, program_id).0,
+            false,
+        ),
+        AccountMeta::new_readonly(*program_id, false),
+    ]
+}
+
+pub fn apply_mirrored_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    mirror_authority_account: &Pubkey,
+    update: MirroredUpdate,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new_readonly(*mirror_authority_account, false),
+        ],
+        data: ProgramInstruction::ApplyMirroredUpdate { update }
+            .borrow()
+            .pack(),
+    }
+}
+
+pub fn init_price_oracle_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    oracle: Option<PriceOracle>,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        rent_return_account,
+        ProgramInstruction::InitPriceOracleUpdate {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            oracle,
+        },
+    )
+}
+
+pub fn finalize_price_oracle_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    oracle: Option<PriceOracle>,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new(*rent_return_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: ProgramInstruction::FinalizePriceOracleUpdate { oracle }
+            .borrow()
+            .pack(),
+    }
+}
+
+pub fn init_merkle_address_book_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    root: Hash,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        rent_return_account,
+        ProgramInstruction::InitMerkleAddressBookUpdate {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            account_guid_hash,
+            root,
+        },
+    )
+}
+
+pub fn finalize_merkle_address_book_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    root: Hash,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new_readonly(*wallet_account, false),
+            AccountMeta::new(
+                MerkleAddressBook::find_address(wallet_account, &account_guid_hash, program_id).0,
+                false,
+            ),
+            AccountMeta::new(*rent_return_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: ProgramInstruction::FinalizeMerkleAddressBookUpdate {
+            account_guid_hash,
+            root,
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+pub fn export_wallet_read_model(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    payer_account: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*wallet_account, false),
+            AccountMeta::new(
+                WalletReadModel::find_address(wallet_account, program_id).0,
+                false,
+            ),
+            AccountMeta::new(*payer_account, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: ProgramInstruction::ExportWalletReadModel {}.pack(),
+    }
+}
+
+pub fn init_event_log(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    payer_account: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*wallet_account, false),
+            AccountMeta::new(
+                WalletEventLog::find_address(wallet_account, program_id).0,
+                false,
+            ),
+            AccountMeta::new(*payer_account, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: ProgramInstruction::InitEventLog {}.pack(),
+    }
+}
+
+/// Supplies the wallet's event log to an instruction about one of its ops, so that the op's
+/// events are appended to it. An init is given the log before it is wrapped in an
+/// `InitWithStakeWeights`.
+pub fn with_event_log(mut instruction: Instruction, wallet_account: &Pubkey) -> Instruction {
+    let event_log = WalletEventLog::find_address(wallet_account, &instruction.program_id).0;
+    instruction
+        .accounts
+        .push(AccountMeta::new(event_log, false));
+    instruction
+}
+
+pub fn init_wallet_stats(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    payer_account: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*wallet_account, false),
+            AccountMeta::new(
+                WalletStats::find_address(wallet_account, program_id).0,
+                false,
+            ),
+            AccountMeta::new(*payer_account, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: ProgramInstruction::InitWalletStats {}.pack(),
+    }
+}
+
+/// Supplies the wallet's stats to an instruction about one of its ops, so that the op is
+/// counted in them. An init is given the stats before it is wrapped in an
+/// `InitWithStakeWeights`.
+pub fn with_wallet_stats(mut instruction: Instruction, wallet_account: &Pubkey) -> Instruction {
+    let stats = WalletStats::find_address(wallet_account, &instruction.program_id).0;
+    instruction.accounts.push(AccountMeta::new(stats, false));
+    instruction
+}
+
+pub fn init_wallet_freeze(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    freeze: WalletFreeze,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        rent_return_account,
+        ProgramInstruction::InitWalletFreeze {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            freeze,
+        },
+    )
+}
+
+pub fn finalize_wallet_freeze(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    freeze: WalletFreeze,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new(*rent_return_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: ProgramInstruction::FinalizeWalletFreeze { freeze }
+            .borrow()
+            .pack(),
+    }
+}
+
+pub fn emergency_freeze(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    signers: &[Pubkey],
+) -> Instruction {
+    let mut accounts = vec![AccountMeta::new(*wallet_account, false)];
+    accounts.extend(
+        signers
+            .iter()
+            .map(|signer| AccountMeta::new_readonly(*signer, true)),
+    );
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: ProgramInstruction::EmergencyFreeze {}.pack(),
+    }
+}
+
+pub fn init_rent_return_allowlist_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    allowlist: RentReturnAllowlist,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        rent_return_account,
+        ProgramInstruction::InitRentReturnAllowlistUpdate {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            allowlist,
+        },
+    )
+}
+
+pub fn finalize_rent_return_allowlist_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    allowlist: RentReturnAllowlist,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new(*rent_return_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: ProgramInstruction::FinalizeRentReturnAllowlistUpdate { allowlist }
+            .borrow()
+            .pack(),
+    }
+}
+
+pub fn init_full_rotation(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    rotation: FullRotation,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        rent_return_account,
+        ProgramInstruction::InitFullRotation {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            rotation,
+        },
+    )
+}
+
+pub fn finalize_full_rotation(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    rotation: FullRotation,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new(*rent_return_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: ProgramInstruction::FinalizeFullRotation { rotation }
+            .borrow()
+            .pack(),
+    }
+}
+
+pub fn update_program_parameters(
+    program_id: &Pubkey,
+    upgrade_authority: &Pubkey,
+    parameters: ProgramParameters,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(ProgramParameters::find_address(program_id).0, false),
+            AccountMeta::new_readonly(
+                Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id())
+                    .0,
+                false,
+            ),
+            AccountMeta::new(*upgrade_authority, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: ProgramInstruction::UpdateProgramParameters { parameters }
+            .borrow()
+            .pack(),
+    }
+}
+
+pub fn init_signer_activation_delay_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    delay: Duration,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        rent_return_account,
+        ProgramInstruction::InitSignerActivationDelayUpdate {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            delay,
+        },
+    )
+}
+
+pub fn finalize_signer_activation_delay_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    delay: Duration,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new(*rent_return_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: ProgramInstruction::FinalizeSignerActivationDelayUpdate { delay }
+            .borrow()
+            .pack(),
+    }
+}
+
+pub fn init_fee_policy_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    fee_policy: FeePolicy,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        rent_return_account,
+        ProgramInstruction::InitFeePolicyUpdate {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            fee_policy,
+        },
+    )
+}
+
+pub fn finalize_fee_policy_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    fee_policy: FeePolicy,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new(*rent_return_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: ProgramInstruction::FinalizeFeePolicyUpdate { fee_policy }
+            .borrow()
+            .pack(),
+    }
+}
+
+pub fn init_stake_weight_governance_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    governance: Option<StakeWeightGovernance>,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        rent_return_account,
+        ProgramInstruction::InitStakeWeightGovernanceUpdate {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            governance,
+        },
+    )
+}
+
+pub fn finalize_stake_weight_governance_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    governance: Option<StakeWeightGovernance>,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new(*rent_return_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: ProgramInstruction::FinalizeStakeWeightGovernanceUpdate { governance }
+            .borrow()
+            .pack(),
+    }
+}
+
+pub fn init_approval_group_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    approval_group: Option<ApprovalGroup>,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        rent_return_account,
+        ProgramInstruction::InitApprovalGroupUpdate {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            approval_group,
+        },
+    )
+}
+
+pub fn finalize_approval_group_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    approval_group: Option<ApprovalGroup>,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new(*rent_return_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: ProgramInstruction::FinalizeApprovalGroupUpdate { approval_group }
+            .borrow()
+            .pack(),
+    }
+}
+
+pub fn init_enable_deposit_addresses(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    enabled: BooleanSetting,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        rent_return_account,
+        ProgramInstruction::InitEnableDepositAddresses {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            account_guid_hash,
+            enabled,
+        },
+    )
+}
+
+pub fn finalize_enable_deposit_addresses(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: BalanceAccountGuidHash,
+    enabled: BooleanSetting,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new(*rent_return_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: ProgramInstruction::FinalizeEnableDepositAddresses {
+            account_guid_hash,
+            enabled,
+        }
+        .pack(),
+    }
+}
+
+/// Wraps a transfer or wrap finalize in a `FinalizeWithDepositSweep` of the deposit addresses
+/// with the given indices, along with their token accounts of the mint (and its token program)
+/// for an SPL transfer.
+pub fn finalize_with_deposit_sweep(
+    finalize: Instruction,
+    wallet_guid_hash: &WalletGuidHash,
+    account_guid_hash: &BalanceAccountGuidHash,
+    deposit_address_indices: &[u8],
+    token: Option<(&Pubkey, &Pubkey)>,
+) -> Instruction {
+    let mut accounts = finalize.accounts;
+    for index in deposit_address_indices {
+        let (deposit_address, _) = BalanceAccount::find_deposit_address(
+            wallet_guid_hash,
+            account_guid_hash,
+            *index,
+            &finalize.program_id,
+        );
+        accounts.push(AccountMeta::new(deposit_address, false));
+        if let Some((token_mint, token_program)) = token {
+            accounts.push(AccountMeta::new(
+                associated_token_address(&deposit_address, token_mint, token_program),
+                false,
+            ));
+        }
+    }
+    Instruction {
+        program_id: finalize.program_id,
+        accounts,
+        data: ProgramInstruction::FinalizeWithDepositSweep {
+            deposit_address_indices: deposit_address_indices.to_vec(),
+            finalize: finalize.data,
+        }
+        .pack(),
+    }
+}
+
+pub fn init_compact_wallet(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        rent_return_account,
+        ProgramInstruction::InitCompactWallet {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+        },
+    )
+}
+
+pub fn finalize_compact_wallet(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_return_account: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new(*rent_return_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: ProgramInstruction::FinalizeCompactWallet {}.borrow().pack(),
+    }
+}
+
+pub fn init_insurance_attestor_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    attestor: Pubkey,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        rent_return_account,
+        ProgramInstruction::InitInsuranceAttestorUpdate {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            attestor,
+        },
+    )
+}
+
+pub fn finalize_insurance_attestor_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    attestor: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new_readonly(*wallet_account, false),
+            AccountMeta::new(
+                InsuranceAttestation::find_address(wallet_account, program_id).0,
+                false,
+            ),
+            AccountMeta::new(*rent_return_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: ProgramInstruction::FinalizeInsuranceAttestorUpdate { attestor }
+            .borrow()
+            .pack(),
+    }
+}
+
+pub fn submit_insurance_attestation(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    attestor: &Pubkey,
+    statement_hash: Hash,
+    coverage_expires_at: i64,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(
+                InsuranceAttestation::find_address(wallet_account, program_id).0,
+                false,
+            ),
+            AccountMeta::new_readonly(*attestor, true),
+        ],
+        data: ProgramInstruction::SubmitInsuranceAttestation {
+            statement_hash,
+            coverage_expires_at,
+        }
+        .pack(),
+    }
+}
+
+pub fn check_insurance_attestation(program_id: &Pubkey, wallet_account: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(
+                InsuranceAttestation::find_address(wallet_account, program_id).0,
+                false,
+            ),
+            AccountMeta::new(
+                WalletEventLog::find_address(wallet_account, program_id).0,
+                false,
+            ),
+        ],
+        data: ProgramInstruction::CheckInsuranceAttestation {}.pack(),
+    }
+}
+
+pub fn init_provisioning_wallet(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    assistant_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    creator_account: &Pubkey,
+    wallet_guid_hash: WalletGuidHash,
+    initial_config: InitialWalletConfig,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new_readonly(*assistant_account, true),
+            AccountMeta::new(*rent_return_account, true),
+            AccountMeta::new_readonly(*creator_account, true),
+            AccountMeta::new_readonly(ProgramParameters::find_address(program_id).0, false),
+            AccountMeta::new(
+                WalletRegistration::find_address(&wallet_guid_hash, program_id).0,
+                false,
+            ),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: ProgramInstruction::InitProvisioningWallet {
+            wallet_guid_hash,
+            initial_config,
+        }
+        .pack(),
+    }
+}
+
+pub fn update_provisioning_wallet(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    creator_account: &Pubkey,
+    config: InitialWalletConfig,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new_readonly(*creator_account, true),
+            AccountMeta::new_readonly(ProgramParameters::find_address(program_id).0, false),
+        ],
+        data: ProgramInstruction::UpdateProvisioningWallet { config }.pack(),
+    }
+}
+
+pub fn activate_wallet(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    config_approvers: &[Pubkey],
+) -> Instruction {
+    let mut accounts = vec![AccountMeta::new(*wallet_account, false)];
+    accounts.extend(
+        config_approvers
+            .iter()
+            .map(|approver| AccountMeta::new_readonly(*approver, true)),
+    );
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: ProgramInstruction::ActivateWallet {}.pack(),
+    }
+}
+
+pub fn migrate_pending_approvals(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    config_approvers: &[Pubkey],
+    multisig_op_accounts: &[Pubkey],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*wallet_account, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+    accounts.extend(
+        config_approvers
+            .iter()
+            .map(|approver| AccountMeta::new_readonly(*approver, true)),
+    );
+    accounts.extend(
+        multisig_op_accounts
+            .iter()
+            .map(|multisig_op_account| AccountMeta::new(*multisig_op_account, false)),
+    );
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: ProgramInstruction::MigratePendingApprovals {
+            approver_count: config_approvers.len() as u8,
+        }
+        .pack(),
+    }
+}
+
+pub fn init_wrapper_book_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    wrapper_book: WrapperBook,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        rent_return_account,
+        ProgramInstruction::InitWrapperBookUpdate {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            wrapper_book,
+        },
+    )
+}
+
+pub fn finalize_wrapper_book_update(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    wrapper_book: WrapperBook,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new(*rent_return_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: ProgramInstruction::FinalizeWrapperBookUpdate { wrapper_book }
+            .borrow()
+            .pack(),
+    }
+}
+
+pub fn init_adapter_wrap_unwrap(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    balance_account: &Pubkey,
+    account_guid_hash: &BalanceAccountGuidHash,
+    wrapper_mint: &Pubkey,
+    amount: u64,
+    direction: WrapDirection,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new_readonly(*balance_account, false),
+            AccountMeta::new_readonly(
+                spl_associated_token_account::get_associated_token_address(
+                    balance_account,
+                    wrapper_mint,
+                ),
+                false,
+            ),
+            AccountMeta::new_readonly(*initiator_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(*rent_return_account, true),
+        ],
+        data: ProgramInstruction::InitAdapterWrapUnwrap {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            account_guid_hash: *account_guid_hash,
+            wrapper_mint: *wrapper_mint,
+            amount,
+            direction,
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+pub fn finalize_adapter_wrap_unwrap(
+    program_id: &Pubkey,
+    multisig_op_account: &Pubkey,
+    wallet_account: &Pubkey,
+    balance_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: &BalanceAccountGuidHash,
+    wrapper: &WrapperEntry,
+    amount: u64,
+    direction: WrapDirection,
+    fee_account_maybe: Option<&Pubkey>,
+    adapter_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*multisig_op_account, false),
+        AccountMeta::new_readonly(*wallet_account, false),
+        AccountMeta::new(*balance_account, false),
+        AccountMeta::new(*rent_return_account, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new(
+            spl_associated_token_account::get_associated_token_address(
+                balance_account,
+                &wrapper.mint,
+            ),
+            false,
+        ),
+        AccountMeta::new(wrapper.mint, false),
+        AccountMeta::new_readonly(wrapper.adapter_program, false),
+    ];
+    if let Some(fee_account) = fee_account_maybe {
+        accounts.push(AccountMeta::new(*fee_account, false));
+    }
+    accounts.extend(adapter_accounts);
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: ProgramInstruction::FinalizeAdapterWrapUnwrap {
+            account_guid_hash: *account_guid_hash,
+            wrapper_mint: wrapper.mint,
+            amount,
+            direction,
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+pub fn init_address_book_renewal(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    initiator_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    slot_id: SlotId<AddressBookEntry>,
+    entry: AddressBookEntry,
+    expires_at: i64,
+) -> Instruction {
+    init_multisig_op(
+        program_id,
+        wallet_account,
+        multisig_op_account,
+        initiator_account,
+        rent_return_account,
+        ProgramInstruction::InitAddressBookRenewal {
+            fee_amount: FEE_AMOUNT,
+            fee_account_guid_hash: FEE_ACCOUNT_GUID_HASH_NONE,
+            slot_id,
+            entry,
+            expires_at,
+        },
+    )
+}
+
+pub fn finalize_address_book_renewal(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    slot_id: SlotId<AddressBookEntry>,
+    entry: AddressBookEntry,
+    expires_at: i64,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*multisig_op_account, false),
+            AccountMeta::new(*wallet_account, false),
+            AccountMeta::new(*rent_return_account, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: ProgramInstruction::FinalizeAddressBookRenewal {
+            slot_id,
+            entry,
+            expires_at,
+        }
+        .borrow()
+        .pack(),
+    }
+}
+
+/// Wraps an init for a stake weight governed wallet, snapshotting the governance token holdings
+/// of the given holders into the op it starts.
+pub fn init_with_stake_weights(
+    init: Instruction,
+    governance_mint: &Pubkey,
+    holders: &[Pubkey],
+) -> Instruction {
+    let mut accounts = init.accounts;
+    accounts.push(AccountMeta::new_readonly(*governance_mint, false));
+    accounts.extend(holders.iter().map(|holder| {
+        AccountMeta::new_readonly(
+            spl_associated_token_account::get_associated_token_address(holder, governance_mint),
+            false,
+        )
+    }));
+    Instruction {
+        program_id: init.program_id,
+        accounts,
+        data: ProgramInstruction::InitWithStakeWeights {
+            stake_account_count: holders.len() as u8,
+            init: init.data,
+        }
+        .pack(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::client::builders::{init_op_bundle, init_wallet_freeze, with_fee};
+    use crate::instruction::ProgramInstruction;
+    use crate::model::balance_account::BalanceAccountGuidHash;
+    use crate::model::wallet_freeze::WalletFreeze;
+    use solana_program::program_error::ProgramError;
+    use solana_program::pubkey::Pubkey;
+
+    #[test]
+    fn test_with_fee() {
+        let freeze = WalletFreeze {
+            frozen: true,
+            emergency_quorum: 2,
+        };
+        let init = init_wallet_freeze(
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            freeze,
+        );
+        let fee_account_guid_hash = BalanceAccountGuidHash::new(&[7; 32]);
+        let charged = with_fee(init.clone(), 1000, Some(fee_account_guid_hash)).unwrap();
+        assert_eq!(charged.accounts, init.accounts);
+        assert!(matches!(
+            ProgramInstruction::unpack(&charged.data).unwrap(),
+            ProgramInstruction::InitWalletFreeze {
+                fee_amount: 1000,
+                fee_account_guid_hash: Some(hash),
+                freeze: unpacked,
+            } if hash == fee_account_guid_hash && unpacked == freeze
+        ));
+
+        let bundle = init_op_bundle(
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &[Pubkey::new_unique()],
+        );
+        assert_eq!(
+            with_fee(bundle, 1000, None).unwrap_err(),
+            ProgramError::InvalidInstructionData
+        );
+    }
+}
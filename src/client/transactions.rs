@@ -0,0 +1,198 @@
+//! Messages for transactions that can reference more accounts than a legacy transaction can
+//! list, such as dApp finalizes and batches of transfer finalizes. These are compiled into v0
+//! messages that load their accounts from the wallet's lookup tables, and fall back to legacy
+//! messages when no tables are available. Signing is left to the caller's transaction type.
+
+use solana_program::hash::Hash;
+use solana_program::instruction::Instruction;
+use solana_program::message::{Message, VersionedMessage};
+use solana_program::pubkey::Pubkey;
+
+use crate::client::builders;
+use crate::lookup_table::{compile_message, LookupTable};
+use crate::model::balance_account::BalanceAccountGuidHash;
+
+/// The most bytes a serialized transaction can take, signatures included: the minimum IPv6
+/// MTU less the IP and UDP headers.
+pub const PACKET_DATA_SIZE: usize = 1280 - 40 - 8;
+
+const SIGNATURE_BYTES: usize = 64;
+
+/// Compiles the instructions into a v0 message loading every account it can from the lookup
+/// tables, or into a legacy message when there are no tables or the v0 message can't index
+/// all of its accounts.
+pub fn compile_transaction_message(
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    lookup_tables: &[LookupTable],
+    recent_blockhash: Hash,
+) -> VersionedMessage {
+    if !lookup_tables.is_empty() {
+        if let Some(message) = compile_message(payer, instructions, lookup_tables, recent_blockhash)
+        {
+            return VersionedMessage::V0(message);
+        }
+    }
+    VersionedMessage::Legacy(Message::new_with_blockhash(
+        instructions,
+        Some(payer),
+        &recent_blockhash,
+    ))
+}
+
+/// The size of a transaction of the message once signed.
+pub fn transaction_size(message: &VersionedMessage) -> usize {
+    let signatures = usize::from(message.header().num_required_signatures);
+    // the signature count is a compact u16, which takes one byte below 128
+    let count_bytes = if signatures < 0x80 { 1 } else { 2 };
+    count_bytes + signatures * SIGNATURE_BYTES + message.serialize().len()
+}
+
+pub fn fits_in_transaction(message: &VersionedMessage) -> bool {
+    transaction_size(message) <= PACKET_DATA_SIZE
+}
+
+/// Splits the instructions, typically transfer finalizes, into as few messages as fit in a
+/// transaction each, keeping their order. An instruction too large to fit even on its own
+/// gets a message of its own, which the cluster will reject.
+pub fn batch_transaction_messages(
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    lookup_tables: &[LookupTable],
+    recent_blockhash: Hash,
+) -> Vec<VersionedMessage> {
+    let mut messages: Vec<VersionedMessage> = Vec::new();
+    let mut batch_start = 0;
+    for batch_end in 1..=instructions.len() {
+        let message = compile_transaction_message(
+            payer,
+            &instructions[batch_start..batch_end],
+            lookup_tables,
+            recent_blockhash,
+        );
+        if !fits_in_transaction(&message) && batch_end - batch_start > 1 {
+            messages.push(compile_transaction_message(
+                payer,
+                &instructions[batch_start..batch_end - 1],
+                lookup_tables,
+                recent_blockhash,
+            ));
+            batch_start = batch_end - 1;
+        }
+    }
+    if batch_start < instructions.len() {
+        messages.push(compile_transaction_message(
+            payer,
+            &instructions[batch_start..],
+            lookup_tables,
+            recent_blockhash,
+        ));
+    }
+    messages
+}
+
+/// A message finalizing a dApp transaction, with the accounts of the dApp's instructions
+/// loaded from the lookup tables where they hold them. The rent return account pays for it.
+pub fn finalize_dapp_transaction_message(
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    multisig_op_account: &Pubkey,
+    multisig_data_account: &Pubkey,
+    balance_account: &Pubkey,
+    rent_return_account: &Pubkey,
+    account_guid_hash: &BalanceAccountGuidHash,
+    params_hash: &Hash,
+    instructions: &Vec<Instruction>,
+    fee_account_maybe: Option<&Pubkey>,
+    lookup_tables: &[LookupTable],
+    recent_blockhash: Hash,
+) -> VersionedMessage {
+    compile_transaction_message(
+        rent_return_account,
+        &[builders::finalize_dapp_transaction(
+            program_id,
+            wallet_account,
+            multisig_op_account,
+            multisig_data_account,
+            balance_account,
+            rent_return_account,
+            account_guid_hash,
+            params_hash,
+            instructions,
+            fee_account_maybe,
+        )],
+        lookup_tables,
+        recent_blockhash,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::instruction::AccountMeta;
+
+    fn transfer_like(program_id: &Pubkey, signer: &Pubkey) -> (Instruction, Vec<Pubkey>) {
+        let accounts: Vec<Pubkey> = (0..6).map(|_| Pubkey::new_unique()).collect();
+        let mut metas = vec![AccountMeta::new(*signer, true)];
+        metas.extend(accounts.iter().map(|key| AccountMeta::new(*key, false)));
+        (
+            Instruction::new_with_bytes(*program_id, &[0; 64], metas),
+            accounts,
+        )
+    }
+
+    #[test]
+    fn test_compile_transaction_message() {
+        let payer = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let (instruction, accounts) = transfer_like(&program_id, &payer);
+
+        // without tables the message is a legacy one listing every account
+        let message =
+            compile_transaction_message(&payer, &[instruction.clone()], &[], Hash::default());
+        assert!(matches!(message, VersionedMessage::Legacy(_)));
+        assert_eq!(message.static_account_keys().len(), 8);
+
+        let table = LookupTable {
+            address: Pubkey::new_unique(),
+            addresses: accounts,
+        };
+        let message =
+            compile_transaction_message(&payer, &[instruction], &[table], Hash::default());
+        assert!(matches!(message, VersionedMessage::V0(_)));
+        assert_eq!(message.static_account_keys(), &[payer, program_id]);
+        message.sanitize(true).unwrap();
+    }
+
+    #[test]
+    fn test_batch_transaction_messages() {
+        let payer = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let (instructions, accounts): (Vec<Instruction>, Vec<Vec<Pubkey>>) =
+            (0..10).map(|_| transfer_like(&program_id, &payer)).unzip();
+
+        // listing six accounts per instruction, only a few fit in a legacy transaction
+        let legacy = batch_transaction_messages(&payer, &instructions, &[], Hash::default());
+        assert!(legacy.len() > 1);
+        assert!(legacy.iter().all(fits_in_transaction));
+        assert_eq!(
+            legacy
+                .iter()
+                .map(|message| message.instructions().len())
+                .sum::<usize>(),
+            instructions.len()
+        );
+
+        // loading them from a table fits more instructions per transaction
+        let table = LookupTable {
+            address: Pubkey::new_unique(),
+            addresses: accounts.into_iter().flatten().collect(),
+        };
+        let v0 = batch_transaction_messages(&payer, &instructions, &[table], Hash::default());
+        assert!(v0.len() < legacy.len());
+        assert!(v0.iter().all(fits_in_transaction));
+        assert!(v0
+            .iter()
+            .all(|message| matches!(message, VersionedMessage::V0(_))));
+    }
+}
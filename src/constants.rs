@@ -2,3 +2,8 @@ pub use solana_program::pubkey::PUBKEY_BYTES;
 
 pub const HASH_LEN: usize = 32;
 pub const VERSION_LEN: usize = 4;
+
+/// The SPL memo program, with which transfers record their memo.
+pub mod spl_memo {
+    solana_program::declare_id!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+}
@@ -64,7 +64,7 @@ pub enum WalletError {
     /// Only one policy config change can be initiated at a time.
     #[error("Concurrent Operations Not Allowed")]
     ConcurrentOperationsNotAllowed,
-    /// Simulation of MultisigOp finalization completed normally.
+    /// Simulation of MultisigOp finalization or of a config update completed normally.
     #[error("Simulation Finished Successfully")]
     SimulationFinished,
     /// Cannot whitelist an address when Whitelisting is not enabled.
@@ -149,6 +149,388 @@ pub enum WalletError {
     /// Incorrect Initiator Account
     #[error("Incorrect Initiator Account")]
     IncorrectInitiatorAccount,
+    /// Internal transfer has not been acknowledged by the destination wallet
+    #[error("Destination Not Acknowledged")]
+    DestinationNotAcknowledged,
+    /// Account has already been initialized
+    #[error("Account Already Initialized")]
+    AccountAlreadyInitialized,
+    /// Account data does not have the size of the expected account type
+    #[error("Account Type Mismatch")]
+    AccountTypeMismatch,
+
+    // 45
+    /// Adding the pending address book entries would exceed the allowed number of pending entries
+    #[error("Too Many Pending Address Book Entries")]
+    TooManyPendingAddressBookEntries,
+    /// Address book entry has not been ratified by a config approver yet
+    #[error("Address Book Entry Pending")]
+    AddressBookEntryPending,
+    /// The fee cannot be deducted from the transferred amount
+    #[error("Invalid Fee Deduction")]
+    InvalidFeeDeduction,
+    /// The signer's capabilities don't allow the requested role
+    #[error("Signer Missing Capability")]
+    SignerMissingCapability,
+    /// The reward splits are empty, repeat or reference unknown balance accounts, or don't
+    /// add up to 100 percent
+    #[error("Invalid Reward Split")]
+    InvalidRewardSplit,
+
+    // 50
+    /// The assistant key has been disabled
+    #[error("Assistant Disabled")]
+    AssistantDisabled,
+    /// The assistant key initiated too many operations in the current window
+    #[error("Assistant Rate Limit Exceeded")]
+    AssistantRateLimitExceeded,
+    /// Too many pending address book entries were staged in the current window
+    #[error("Pending Address Book Rate Limit Exceeded")]
+    PendingAddressBookRateLimitExceeded,
+    /// The multisig data account is not the one the operation was started with
+    #[error("Incorrect Multisig Data Account")]
+    IncorrectMultisigDataAccount,
+    /// The address book entry has been deprecated and can't be used for new operations
+    #[error("Address Book Entry Deprecated")]
+    AddressBookEntryDeprecated,
+
+    // 55
+    /// The address book entry is not deprecated, or its grace period has not elapsed yet
+    #[error("Address Book Entry Not Removable")]
+    AddressBookEntryNotRemovable,
+    /// There is no staged config policy to activate
+    #[error("No Pending Config Policy")]
+    NoPendingConfigPolicy,
+    /// The staged config policy's timelock has not elapsed yet
+    #[error("Config Policy Timelocked")]
+    ConfigPolicyTimelocked,
+    /// The config policy timelock exceeds the maximum
+    #[error("Invalid Config Policy Timelock")]
+    InvalidConfigPolicyTimelock,
+    /// The dapp book entry's expiry has passed, and it must be renewed before it can be used
+    #[error("DApp Book Entry Expired")]
+    DAppBookEntryExpired,
+
+    // 60
+    /// The dapp book expiry is not in the future, or its slot is empty
+    #[error("Invalid DApp Book Expiry")]
+    InvalidDAppBookExpiry,
+    /// The operation was approved, but its finalize delay has not elapsed yet
+    #[error("Finalize Delay Not Elapsed")]
+    FinalizeDelayNotElapsed,
+    /// The finalize delay exceeds the maximum
+    #[error("Invalid Finalize Delay")]
+    InvalidFinalizeDelay,
+    /// The operation is not approved and waiting out its finalize delay
+    #[error("Operation Not Cancellable")]
+    OperationNotCancellable,
+    /// The finalize submitter policy names an unknown category setting, too many submitter
+    /// keys, or none while requiring one
+    #[error("Invalid Finalize Submitter Policy")]
+    InvalidFinalizeSubmitterPolicy,
+
+    // 65
+    /// The wallet's finalize submitter policy does not permit this account to finalize the
+    /// operation
+    #[error("Finalize Submitter Not Allowed")]
+    FinalizeSubmitterNotAllowed,
+    /// The mint amount exceeds the balance account's mint cap
+    #[error("Mint Cap Exceeded")]
+    MintCapExceeded,
+    /// The balance account does not hold the mint's authority of the given type
+    #[error("Invalid Mint Authority")]
+    InvalidMintAuthority,
+    /// The payment channel's allowance or window is zero, or its account is not the one the
+    /// operation was started with
+    #[error("Invalid Payment Channel")]
+    InvalidPaymentChannel,
+    /// The payment channel's window has passed
+    #[error("Payment Channel Expired")]
+    PaymentChannelExpired,
+
+    // 70
+    /// The payment exceeds what is left of the payment channel's allowance
+    #[error("Payment Channel Allowance Exceeded")]
+    PaymentChannelAllowanceExceeded,
+    /// The balance account is archived, so no new operations can be started against it
+    #[error("Balance Account Archived")]
+    BalanceAccountArchived,
+    /// The op bundle's members are not multisig ops pending approval from the same initiator,
+    /// or the multisig op is not a member of the bundle
+    #[error("Invalid Op Bundle")]
+    InvalidOpBundle,
+    /// One of the op bundle's members has expired, so the bundle can no longer be approved
+    #[error("Op Bundle Expired")]
+    OpBundleExpired,
+    /// A timestamp or index computed from the instruction's inputs is out of range
+    #[error("Arithmetic Overflow")]
+    ArithmeticOverflow,
+
+    // 75
+    /// The lookup table update adds too many or duplicate addresses, or no addresses to an
+    /// existing table
+    #[error("Invalid Lookup Table Update")]
+    InvalidLookupTableUpdate,
+    /// An imported address book entry is missing, pending or deprecated in the source wallet,
+    /// or the import selects the same slot or balance account twice
+    #[error("Invalid Address Book Import")]
+    InvalidAddressBookImport,
+    /// The destination revealed at finalize doesn't match the commitment the transfer was
+    /// initiated with
+    #[error("Destination Commitment Mismatch")]
+    DestinationCommitmentMismatch,
+    /// The multisig op account doesn't have room for the disposition records of the op's
+    /// approvers
+    #[error("Multisig Op Account Too Small")]
+    MultisigOpAccountTooSmall,
+    /// The operation was approved but still needs the approval of the wallet's compliance
+    /// service before it can be finalized
+    #[error("Compliance Approval Required")]
+    ComplianceApprovalRequired,
+    // 80
+    /// A balance account with the given GUID hash already exists in the wallet
+    #[error("Duplicate Balance Account Guid")]
+    DuplicateBalanceAccountGuid,
+    /// The wrapped init isn't preceded by an ed25519 instruction verifying the initiator's
+    /// signature over it
+    #[error("Invalid Init Signature")]
+    InvalidInitSignature,
+    /// The primary's standby wallet isn't among the finalize's accounts, or doesn't have the
+    /// primary's config
+    #[error("Standby Wallet Not In Sync")]
+    StandbyWalletNotInSync,
+    /// The update isn't signed by the mirror authority of the wallet's primary
+    #[error("Invalid Mirrored Update")]
+    InvalidMirroredUpdate,
+    /// A standby wallet only takes config changes from its primary
+    #[error("Wallet Is Standby")]
+    WalletIsStandby,
+    // 85
+    /// A reward split of another balance account pays into the balance account
+    #[error("Balance Account In Use")]
+    BalanceAccountInUse,
+    /// The update's vectors are not sorted by their key, or contain duplicates
+    #[error("Non Canonical Params")]
+    NonCanonicalParams,
+    /// The balance account only transfers SOL to accounts owned by the system program
+    #[error("Destination Not System Owned")]
+    DestinationNotSystemOwned,
+    /// The op isn't pending, or has seen approval activity too recently for a reminder
+    #[error("Operation Not Idle")]
+    OperationNotIdle,
+    /// The price oracle has no or too many feeds, more than one feed for a token mint, or
+    /// staleness or confidence limits out of range
+    #[error("Invalid Price Oracle")]
+    InvalidPriceOracle,
+    // 90
+    /// The whitelisted price account of the transfer's token mint is missing, or isn't a Pyth
+    /// price account with a positive price
+    #[error("Invalid Price Account")]
+    InvalidPriceAccount,
+    /// The price isn't trading, or was published longer ago than the oracle allows
+    #[error("Stale Price")]
+    StalePrice,
+    /// The price's confidence interval is wider than the oracle allows
+    #[error("Price Too Uncertain")]
+    PriceTooUncertain,
+    /// The token account isn't an SPL token account owned by the balance account
+    #[error("Invalid Token Account")]
+    InvalidTokenAccount,
+    /// The balance account has as many registered token accounts as it can have
+    #[error("Token Account Registry Full")]
+    TokenAccountRegistryFull,
+    // 95
+    /// An account is passed to a dApp transaction's finalize with writable or signer flags other
+    /// than the supplied instructions give it
+    #[error("DApp Account Permission Mismatch")]
+    DAppAccountPermissionMismatch,
+    /// A multisig op was moved to a state its lifecycle doesn't allow from its current one
+    #[error("Invalid State Transition")]
+    InvalidStateTransition,
+    /// An instruction of a dApp transaction is for a program that isn't in the wallet's dapp
+    /// book
+    #[error("DApp Program Not Allowed")]
+    DAppProgramNotAllowed,
+    /// The operation can't be cleaned up before its expiry
+    #[error("Operation Not Expired")]
+    OperationNotExpired,
+    /// New transfers, wraps and dApp transactions can't be initiated while the wallet is frozen
+    #[error("Wallet Frozen")]
+    WalletFrozen,
+    // 100
+    /// A wallet freeze update sets an emergency freeze quorum larger than the wallet's signers
+    #[error("Invalid Emergency Freeze Quorum")]
+    InvalidEmergencyFreezeQuorum,
+    /// An emergency freeze isn't signed by enough of the wallet's signers, or the wallet has no
+    /// emergency freeze quorum
+    #[error("Emergency Freeze Quorum Not Met")]
+    EmergencyFreezeQuorumNotMet,
+    /// A rent return allowlist has too many or repeated entries
+    #[error("Invalid Rent Return Allowlist")]
+    InvalidRentReturnAllowlist,
+    /// The rent of a multisig op is returned to an account not on the wallet's rent return
+    /// allowlist
+    #[error("Rent Return Not Allowed")]
+    RentReturnNotAllowed,
+    /// A full rotation replaces a key that isn't a signer, or leaves two signers with one key
+    #[error("Invalid Full Rotation")]
+    InvalidFullRotation,
+    // 105
+    /// Program parameters outside the floors and ceilings the program bounds them by
+    #[error("Invalid Program Parameters")]
+    InvalidProgramParameters,
+    /// A signer count, dApp instruction count or approval timeout beyond the program parameters
+    #[error("Program Parameter Limit Exceeded")]
+    ProgramParameterLimitExceeded,
+    /// A signer activation delay beyond the maximum the program allows
+    #[error("Invalid Signer Activation Delay")]
+    InvalidSignerActivationDelay,
+    /// A signer added to the wallet can't initiate or approve until its activation delay elapses
+    #[error("Signer Not Yet Active")]
+    SignerNotYetActive,
+    /// A multisig op's fee is more than the wallet's fee policy allows an op to collect
+    #[error("Fee Exceeds Cap")]
+    FeeExceedsCap,
+    // 110
+    /// A token account to close still holds tokens
+    #[error("Token Account Not Empty")]
+    TokenAccountNotEmpty,
+    /// A stake weight governance with no mint, or an approval threshold outside 1 to 10,000
+    /// basis points
+    #[error("Invalid Stake Weight Governance")]
+    InvalidStakeWeightGovernance,
+    /// A stake account given for a weight snapshot isn't its owner's associated token account
+    /// of the governance mint, or is given twice
+    #[error("Invalid Stake Account")]
+    InvalidStakeAccount,
+    /// Stake weights were given for an op of a wallet that isn't stake weight governed, or for
+    /// an op whose weights are already snapshotted
+    #[error("Stake Weights Not Expected")]
+    StakeWeightsNotExpected,
+    /// The event log supplied to an instruction isn't the log of the wallet of the op it is about
+    #[error("Invalid Event Log")]
+    InvalidEventLog,
+    /// A transfer to a high-risk destination needs a compliance approval, which a wallet
+    /// without a compliance policy can't give
+    #[error("Compliance Policy Required")]
+    CompliancePolicyRequired,
+    /// A wallet with the same GUID hash is already registered
+    #[error("Wallet Already Registered")]
+    WalletAlreadyRegistered,
+    /// The destination's address book entry has lapsed, and must be renewed before it can be
+    /// transferred to again
+    #[error("Address Book Entry Expired")]
+    AddressBookEntryExpired,
+    #[error("Invalid Address Book Expiry")]
+    InvalidAddressBookExpiry,
+    /// A session key that isn't registered, has lapsed, or is registered with an expiry outside
+    /// the allowed session duration
+    #[error("Invalid Session Key")]
+    InvalidSessionKey,
+    /// The wallet has as many live session keys as it can have
+    #[error("Session Key Registry Full")]
+    SessionKeyRegistryFull,
+    /// The op is outside the scope of the session key that signed for its approver
+    #[error("Session Key Out Of Scope")]
+    SessionKeyOutOfScope,
+    /// A disposition by signature isn't preceded by an ed25519 instruction verifying the
+    /// approver's signature of it, or is submitted after the signature's expiry
+    #[error("Invalid Approval Signature")]
+    InvalidApprovalSignature,
+    /// A balance account's approval tiers are too many, not in strictly ascending order of
+    /// minimum amount, or require approvals from anyone but its transfer approvers or more
+    /// approvals than a tier has approvers
+    #[error("Invalid Approval Tiers")]
+    InvalidApprovalTiers,
+    /// The stats supplied to an instruction aren't the stats of the wallet of the op it is about
+    #[error("Invalid Wallet Stats")]
+    InvalidWalletStats,
+    /// A transfer's destination token account doesn't exist, and the transfer wasn't approved
+    /// to create it
+    #[error("Destination Token Account Creation Not Allowed")]
+    DestinationTokenAccountCreationNotAllowed,
+    /// An address book entry's address doesn't look like what the entry was declared to be
+    #[error("Address Book Entry Kind Mismatch")]
+    AddressBookEntryKindMismatch,
+    /// An SPL token accounts creation is for no balance accounts, more than an op can track, or
+    /// the same balance account twice, or a batch of it is larger than what remains
+    #[error("Invalid SPL Token Accounts Creation")]
+    InvalidSPLTokenAccountsCreation,
+    /// An op is continued before it was finalized as approved
+    #[error("Operation Not Executed")]
+    OperationNotExecuted,
+    /// A dApp is tagged with a category the balance account may not transact with
+    #[error("DApp Category Not Allowed")]
+    DAppCategoryNotAllowed,
+    /// A dApp book update tags an empty dApp book slot with categories
+    #[error("Invalid DApp Categories")]
+    InvalidDAppCategories,
+    /// A config op is finalized after the wallet's state changed since it was started
+    #[error("Stale Operation")]
+    StaleOperation,
+    /// An approval group lacks a group key, a threshold or a scope
+    #[error("Invalid Approval Group")]
+    InvalidApprovalGroup,
+    /// The wallet's approval group can't approve the op, being out of its scope or short of its
+    /// quorum
+    #[error("Approval Group Not Allowed")]
+    ApprovalGroupNotAllowed,
+    /// Funds can't be swept from the deposit addresses of a balance account that hasn't enabled
+    /// them
+    #[error("Deposit Addresses Not Enabled")]
+    DepositAddressesNotEnabled,
+    /// A dApp transaction's instructions are more, or larger when serialized, than the limits
+    /// it was started with allow
+    #[error("DApp Instruction Too Large")]
+    DAppInstructionTooLarge,
+    /// A dApp book update limits the instruction count of an empty dApp book slot, or limits it
+    /// beyond what a dApp transaction can hold
+    #[error("Invalid DApp Instruction Limit")]
+    InvalidDAppInstructionLimit,
+    /// A replicated wallet's slots can't be compacted, since its signer slots have to line up
+    /// with its counterpart's
+    #[error("Wallet Is Replicated")]
+    WalletIsReplicated,
+    /// An insurance attestation was made by a key other than the wallet's registered attestor,
+    /// or without a statement
+    #[error("Invalid Insurance Attestor")]
+    InvalidInsuranceAttestor,
+    /// An insurance attestation's coverage expires in the past, or further ahead than an
+    /// attestation can cover
+    #[error("Invalid Coverage Expiry")]
+    InvalidCoverageExpiry,
+    /// No ops can be started against a wallet that is still being provisioned
+    #[error("Wallet Is Provisioning")]
+    WalletIsProvisioning,
+    /// A wallet that isn't being provisioned can't be edited by its creator or activated
+    #[error("Wallet Not Provisioning")]
+    WalletNotProvisioning,
+    /// A provisioning wallet's config can only be edited with the signature of its creator
+    #[error("Invalid Provisioning Creator")]
+    InvalidProvisioningCreator,
+    /// A wallet can only be activated with the signatures of all of its config approvers
+    #[error("Activation Not Signed By All Approvers")]
+    ActivationNotSignedByAllApprovers,
+    /// A wrapper book can't list a mint twice, list wrapped SOL, or exceed its capacity
+    #[error("Invalid Wrapper Book")]
+    InvalidWrapperBook,
+    /// Only mints in the wallet's wrapper book can be wrapped into through an adapter
+    #[error("Unknown Wrapper Mint")]
+    UnknownWrapperMint,
+    /// A wrapper adapter took more lamports or tokens from the balance account than requested
+    #[error("Wrapper Adapter Overdrawn")]
+    WrapperAdapterOverdrawn,
+    /// A token account the op would move tokens into or out of is frozen by its mint's freeze
+    /// authority
+    #[error("Token Account Frozen")]
+    TokenAccountFrozen,
+    /// Fewer than the config approval quorum of active config approvers signed an instruction
+    /// that takes their signatures in place of an op
+    #[error("Config Approver Quorum Not Met")]
+    ConfigApproverQuorumNotMet,
+    /// The params hash given for an op isn't the hash of the op's params
+    #[error("Operation Params Mismatch")]
+    OperationParamsMismatch,
 }
 
 impl From<WalletError> for ProgramError {
@@ -156,3 +538,57 @@ impl From<WalletError> for ProgramError {
         ProgramError::Custom(e as u32)
     }
 }
+
+/// The first code builtin program errors are logged under. Wallet error numbers stay far below
+/// it, so the two never collide.
+pub const BUILTIN_ERROR_CODE_BASE: u32 = 0xFFFF_0000;
+
+/// The code a failure is logged under: the wallet error number for wallet errors, and
+/// `BUILTIN_ERROR_CODE_BASE` plus the runtime's builtin error number (the one it shifts above
+/// the u32 range in its own encoding) for builtin program errors.
+pub fn error_code(error: &ProgramError) -> u32 {
+    match error {
+        ProgramError::Custom(code) => *code,
+        other => BUILTIN_ERROR_CODE_BASE + (u64::from(other.clone()) >> 32) as u32,
+    }
+}
+
+/// Logs a handler failure as `STRIKE_ERR code=<code> ctx=<handler> <message>` and evaluates to
+/// the failure as a `ProgramError`, so log-based alerting can match on the code and context
+/// instead of the message text.
+macro_rules! strike_err {
+    ($ctx:literal, $error:expr, $fmt:literal $(, $arg:expr)* $(,)?) => {{
+        let error: solana_program::program_error::ProgramError = $error.into();
+        solana_program::msg!(
+            concat!("STRIKE_ERR code={} ctx=", $ctx, " ", $fmt),
+            $crate::error::error_code(&error)
+            $(, $arg)*
+        );
+        error
+    }};
+}
+
+pub(crate) use strike_err;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_code() {
+        assert_eq!(error_code(&WalletError::AccountNotRecognized.into()), 0);
+        assert_eq!(error_code(&WalletError::OperationNotCancellable.into()), 63);
+        assert_eq!(
+            error_code(&ProgramError::InvalidArgument),
+            BUILTIN_ERROR_CODE_BASE + 2
+        );
+        assert_eq!(
+            error_code(&ProgramError::InvalidAccountData),
+            BUILTIN_ERROR_CODE_BASE + 4
+        );
+        assert_eq!(
+            strike_err!("test", WalletError::InvalidApprover, "approver {}", 1),
+            ProgramError::Custom(3)
+        );
+    }
+}
@@ -1,18 +1,65 @@
+pub mod address_book_import_handler;
+pub mod address_book_renewal_handler;
 pub mod address_book_update_handler;
 pub mod approval_disposition_handler;
+pub mod approval_group_update_handler;
 pub mod balance_account_address_whitelist_update_handler;
 pub mod balance_account_creation_handler;
+pub mod balance_account_deletion_handler;
 pub mod balance_account_name_update_handler;
 pub mod balance_account_policy_update_handler;
 pub mod balance_account_settings_update_handler;
+pub mod cleanup_expired_op_handler;
 pub mod cleanup_handler;
+pub mod compliance_policy_update_handler;
+pub mod config_update_simulation_handler;
 pub mod dapp_book_update_handler;
 pub mod dapp_transaction_handler;
+#[cfg(feature = "demo")]
+pub mod demo_wallet_handler;
+pub mod deposit_address_handler;
+pub mod fee_policy_update_handler;
+pub mod finalize_submitter_policy_update_handler;
+pub mod full_rotation_handler;
 pub mod init_wallet_handler;
+pub mod init_with_compute_hint_handler;
+pub mod init_with_signature_handler;
+pub mod init_with_stake_weights_handler;
+pub mod insurance_attestation_handler;
+pub mod internal_transfer_acknowledgement_handler;
+pub mod lookup_table_handler;
+pub mod merkle_address_book_update_handler;
 pub mod migrate_handler;
+pub mod mint_to_handler;
+pub mod nudge_handler;
+pub mod op_bundle_handler;
+pub mod payment_channel_handler;
+pub mod pending_address_book_entries_handler;
+pub mod price_oracle_update_handler;
+pub mod program_parameters_handler;
+pub mod rent_return_allowlist_update_handler;
+pub mod reward_split_policy_update_handler;
+pub mod session_key_handler;
+pub mod set_mint_authority_handler;
 pub mod sign_data_handler;
+pub mod signer_activation_delay_update_handler;
+pub mod spending_limit_update_handler;
+pub mod spl_token_accounts_creation_handler;
+pub mod stake_weight_governance_update_handler;
+pub mod token_account_registry_handler;
 pub mod transfer_handler;
+pub mod update_assistant_handler;
 pub mod update_signer_handler;
 pub mod utils;
+pub mod vote_rewards_withdrawal_handler;
+pub mod wallet_compaction_handler;
 pub mod wallet_config_policy_update_handler;
+pub mod wallet_event_log_handler;
+pub mod wallet_freeze_handler;
+pub mod wallet_provisioning_handler;
+pub mod wallet_read_model_handler;
+pub mod wallet_registry_handler;
+pub mod wallet_replication_handler;
+pub mod wallet_stats_handler;
 pub mod wrap_unwrap_handler;
+pub mod wrapper_book_update_handler;
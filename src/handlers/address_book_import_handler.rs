@@ -0,0 +1,125 @@
+use crate::error::{strike_err, WalletError};
+use crate::handlers::utils::{
+    finalize_multisig_op, get_clock_from_next_account, next_initiator_account_info,
+    next_program_account_info, next_signer_account_info, next_wallet_account_info,
+    start_multisig_config_op, FeeCollectionInfo,
+};
+use crate::instruction::AddressBookImport;
+use crate::model::address_book::AddressBookEntry;
+use crate::model::balance_account::BalanceAccountGuidHash;
+use crate::model::multisig_op::MultisigOpParams;
+use crate::model::wallet::Wallet;
+use crate::utils::SlotId;
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+
+pub fn init(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    fee_amount: u64,
+    fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+    entries: &[(SlotId<AddressBookEntry>, SlotId<AddressBookEntry>)],
+    balance_accounts: &[(BalanceAccountGuidHash, BalanceAccountGuidHash)],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let source_wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let source_approver_account_info = next_signer_account_info(accounts_iter)?;
+    let initiator_account_info =
+        next_initiator_account_info(accounts_iter, multisig_op_account_info, wallet_account_info)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+    let rent_return_account_info = next_signer_account_info(accounts_iter)?;
+
+    if source_wallet_account_info.key == wallet_account_info.key {
+        return Err(strike_err!(
+            "address_book_import_handler::init",
+            WalletError::InvalidAddressBookImport,
+            "Address book entries can't be imported from the same wallet"
+        ));
+    }
+
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    wallet.validate_config_initiator(initiator_account_info, clock.unix_timestamp)?;
+
+    // a key approving config changes of the source wallet which also signs for this one ties
+    // both wallets to the same organization
+    let source_wallet = Wallet::unpack(&source_wallet_account_info.data.borrow())?;
+    if !source_wallet
+        .get_active_config_approvers_keys(clock.unix_timestamp)
+        .contains(source_approver_account_info.key)
+        || !wallet
+            .get_signers_keys()
+            .contains(source_approver_account_info.key)
+    {
+        return Err(strike_err!(
+            "address_book_import_handler::init",
+            WalletError::InvalidApprover,
+            "The source approver must be a config approver of the source wallet and a signer of this wallet"
+        ));
+    }
+
+    let import = source_wallet.export_address_book_entries(
+        source_wallet_account_info.key,
+        entries,
+        balance_accounts,
+    )?;
+    import.validate_canonical()?;
+    wallet.validate_address_book_import(&import)?;
+
+    start_multisig_config_op(
+        multisig_op_account_info,
+        wallet_account_info,
+        &mut wallet,
+        clock,
+        MultisigOpParams::ImportAddressBookEntries {
+            wallet_address: *wallet_account_info.key,
+            import,
+        },
+        *initiator_account_info.key,
+        *rent_return_account_info.key,
+        fee_amount,
+        fee_account_guid_hash,
+    )
+}
+
+pub fn finalize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    import: &AddressBookImport,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let rent_return_account_info = next_signer_account_info(accounts_iter)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+    let fee_account_info_maybe = accounts_iter.next();
+
+    let wallet_guid_hash =
+        &Wallet::wallet_guid_hash_from_slice(&wallet_account_info.data.borrow())?;
+
+    finalize_multisig_op(
+        multisig_op_account_info,
+        wallet_account_info,
+        FeeCollectionInfo {
+            rent_return_account_info,
+            fee_account_info_maybe,
+            wallet_guid_hash,
+            program_id,
+        },
+        clock,
+        MultisigOpParams::ImportAddressBookEntries {
+            wallet_address: *wallet_account_info.key,
+            import: import.clone(),
+        },
+        || -> ProgramResult {
+            let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+            wallet.import_address_book_entries(import)?;
+            Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+            Ok(())
+        },
+        || -> ProgramResult { Ok(()) },
+    )
+}
@@ -1,16 +1,20 @@
+use crate::error::{strike_err, WalletError};
 use crate::handlers::utils::{
-    finalize_multisig_op, get_clock_from_next_account, next_program_account_info,
-    next_signer_account_info, next_wallet_account_info, start_multisig_config_op,
-    FeeCollectionInfo,
+    finalize_multisig_op, get_clock_from_next_account, next_initiator_account_info,
+    next_program_account_info, next_signer_account_info, next_wallet_account_info,
+    start_multisig_config_op, FeeCollectionInfo,
 };
 use crate::instruction::AddressBookUpdate;
+use crate::model::address_book::{AddressBookEntry, AddressBookEntryKind};
 use crate::model::balance_account::BalanceAccountGuidHash;
 use crate::model::multisig_op::MultisigOpParams;
 use crate::model::wallet::Wallet;
-use solana_program::account_info::{next_account_info, AccountInfo};
+use crate::utils::SlotId;
+use solana_program::account_info::AccountInfo;
 use solana_program::entrypoint::ProgramResult;
 use solana_program::program_pack::Pack;
 use solana_program::pubkey::Pubkey;
+use solana_program::system_program;
 
 pub fn init(
     program_id: &Pubkey,
@@ -22,17 +26,21 @@ pub fn init(
     let accounts_iter = &mut accounts.iter();
     let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
     let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
-    let initiator_account_info = next_account_info(accounts_iter)?;
+    let initiator_account_info =
+        next_initiator_account_info(accounts_iter, multisig_op_account_info, wallet_account_info)?;
     let clock = get_clock_from_next_account(accounts_iter)?;
     let rent_return_account_info = next_signer_account_info(accounts_iter)?;
 
-    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
-    wallet.validate_config_initiator(initiator_account_info)?;
-    wallet.validate_address_book_update(update)?;
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    wallet.validate_config_initiator(initiator_account_info, clock.unix_timestamp)?;
+    update.validate_canonical()?;
+    wallet.validate_address_book_update(update, clock.unix_timestamp)?;
+    validate_declared_entry_owners(update, accounts)?;
 
     start_multisig_config_op(
         &multisig_op_account_info,
-        &wallet,
+        wallet_account_info,
+        &mut wallet,
         clock,
         MultisigOpParams::AddressBookUpdate {
             wallet_address: *wallet_account_info.key,
@@ -45,6 +53,49 @@ pub fn init(
     )
 }
 
+/// Checks that the supplied accounts of entries being added with a declared kind are owned as
+/// accounts of their kind would be.
+pub fn validate_declared_entry_owners(
+    update: &AddressBookUpdate,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    for (id, kind) in &update.address_book_entry_kinds {
+        let entry = match update
+            .add_address_book_entries
+            .iter()
+            .find(|(added_id, _)| added_id == id)
+        {
+            Some((_, entry)) => entry,
+            None => continue,
+        };
+        let account_info = match accounts
+            .iter()
+            .find(|account_info| *account_info.key == entry.address)
+        {
+            Some(account_info) => account_info,
+            None => continue,
+        };
+        let owned_as_kind = match kind {
+            AddressBookEntryKind::Wallet => *account_info.owner == system_program::id(),
+            AddressBookEntryKind::TokenAccount { .. } => {
+                account_info.data_is_empty()
+                    || *account_info.owner == spl_token::id()
+                    || *account_info.owner == spl_token_2022::id()
+            }
+        };
+        if !owned_as_kind {
+            return Err(strike_err!(
+                "address_book_update_handler::init",
+                WalletError::AddressBookEntryKindMismatch,
+                "Address book entry {} is owned by {}",
+                entry.address,
+                account_info.owner
+            ));
+        }
+    }
+    Ok(())
+}
+
 pub fn finalize(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
@@ -59,9 +110,11 @@ pub fn finalize(
 
     let wallet_guid_hash =
         &Wallet::wallet_guid_hash_from_slice(&wallet_account_info.data.borrow())?;
+    let now = clock.unix_timestamp;
 
     finalize_multisig_op(
         &multisig_op_account_info,
+        wallet_account_info,
         FeeCollectionInfo {
             rent_return_account_info,
             fee_account_info_maybe,
@@ -75,10 +128,36 @@ pub fn finalize(
         },
         || -> ProgramResult {
             let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow_mut())?;
-            wallet.update_address_book(update)?;
+            wallet.update_address_book(update, now)?;
             Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
             Ok(())
         },
         || -> ProgramResult { Ok(()) },
     )
 }
+
+pub fn remove_deprecated(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    entries: &Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let assistant_account_info = next_signer_account_info(accounts_iter)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    if !wallet.is_enabled_assistant(assistant_account_info.key) {
+        return Err(strike_err!(
+            "address_book_update_handler::remove_deprecated",
+            WalletError::InvalidApprover,
+            "Deprecated address book entries can only be removed by an enabled assistant"
+        ));
+    }
+
+    // the deprecation was approved through an address book update, so removing the entries
+    // once their grace period has elapsed needs no further approval
+    wallet.remove_deprecated_address_book_entries(entries, clock.unix_timestamp)?;
+    Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+    Ok(())
+}
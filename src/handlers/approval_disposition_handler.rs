@@ -1,10 +1,18 @@
-use crate::error::WalletError;
-use crate::handlers::utils::{get_clock_from_next_account, next_program_account_info};
-use crate::model::multisig_op::{ApprovalDisposition, MultisigOp};
+use crate::error::{strike_err, WalletError};
+use crate::handlers::init_with_signature_handler::verified_signer;
+use crate::handlers::utils::{
+    get_clock_from_next_account, next_program_account_info, next_wallet_account_info,
+};
+use crate::instruction::approval_disposition_signature_message;
+use crate::model::multisig_op::{ApprovalDisposition, DispositionReason, MultisigOp};
+use crate::model::session_key_registry::{SessionKey, SessionKeyRegistry};
+use crate::model::wallet::Wallet;
+use crate::pda::{self, SessionKeyRegistrySeeds};
 use crate::version::{Versioned, VERSION};
 use solana_program::account_info::{next_account_info, AccountInfo};
 use solana_program::entrypoint::ProgramResult;
 use solana_program::hash::Hash;
+use solana_program::program_error::ProgramError;
 use solana_program::program_pack::Pack;
 use solana_program::pubkey::Pubkey;
 
@@ -13,33 +21,195 @@ pub fn handle(
     accounts: &[AccountInfo],
     disposition: ApprovalDisposition,
     params_hash: Hash,
+    reason: Option<DispositionReason>,
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
     let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
     let signer_account_info = next_account_info(accounts_iter)?;
     let clock = get_clock_from_next_account(accounts_iter)?;
 
-    if MultisigOp::version_from_slice(&multisig_op_account_info.data.borrow())? != VERSION {
-        return Err(WalletError::OperationVersionMismatch.into());
+    let mut multisig_op = unpack_op(multisig_op_account_info, params_hash)?;
+
+    // a signer that isn't one of the op's approvers may be a session key signing for one
+    match accounts_iter.next() {
+        Some(registry_account_info) if !multisig_op.is_approver(signer_account_info.key) => {
+            let session_key = session_key(
+                program_id,
+                registry_account_info,
+                &multisig_op,
+                signer_account_info.key,
+            )?;
+            multisig_op.validate_and_record_session_approval_disposition(
+                signer_account_info,
+                &session_key,
+                disposition,
+                reason,
+                &clock,
+            )?;
+        }
+        _ => multisig_op.validate_and_record_approval_disposition(
+            signer_account_info,
+            disposition,
+            reason,
+            &clock,
+        )?,
     }
+    MultisigOp::pack_sized(multisig_op, &mut multisig_op_account_info.data.borrow_mut())?;
 
-    let mut multisig_op = MultisigOp::unpack(&multisig_op_account_info.data.borrow())?;
+    Ok(())
+}
 
-    match multisig_op.params_hash {
-        None => return Err(WalletError::OperationNotInitialized.into()),
-        Some(v) => {
-            if params_hash != v {
-                return Err(WalletError::InvalidSignature.into());
-            }
-        }
+pub fn handle_by_signature(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    disposition: ApprovalDisposition,
+    params_hash: Hash,
+    expires_at: i64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let approver_account_info = next_account_info(accounts_iter)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+    let instructions_sysvar_account_info = next_account_info(accounts_iter)?;
+
+    if clock.unix_timestamp > expires_at {
+        return Err(strike_err!(
+            "approval_disposition_handler::handle_by_signature",
+            WalletError::InvalidApprovalSignature,
+            "The approval signature expired at {}",
+            expires_at
+        ));
+    }
+    let message = approval_disposition_signature_message(
+        program_id,
+        multisig_op_account_info.key,
+        disposition,
+        &params_hash,
+        expires_at,
+    );
+    let signer = verified_signer(
+        instructions_sysvar_account_info,
+        &message,
+        WalletError::InvalidApprovalSignature,
+    )?;
+    if signer != *approver_account_info.key {
+        return Err(strike_err!(
+            "approval_disposition_handler::handle_by_signature",
+            WalletError::InvalidApprovalSignature,
+            "The approval is signed by {} rather than the approver",
+            signer
+        ));
     }
 
+    // the approver's signature of the message stands in for their transaction signature
+    let mut approver_account_info = approver_account_info.clone();
+    approver_account_info.is_signer = true;
+    let mut multisig_op = unpack_op(multisig_op_account_info, params_hash)?;
     multisig_op.validate_and_record_approval_disposition(
-        &signer_account_info,
+        &approver_account_info,
         disposition,
+        None,
         &clock,
     )?;
-    MultisigOp::pack(multisig_op, &mut multisig_op_account_info.data.borrow_mut())?;
+    MultisigOp::pack_sized(multisig_op, &mut multisig_op_account_info.data.borrow_mut())
+}
 
-    Ok(())
+pub fn handle_aggregated(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    params_hash: Hash,
+    expires_at: i64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+    let instructions_sysvar_account_info = next_account_info(accounts_iter)?;
+
+    if clock.unix_timestamp > expires_at {
+        return Err(strike_err!(
+            "approval_disposition_handler::handle_aggregated",
+            WalletError::InvalidApprovalSignature,
+            "The aggregated approval expired at {}",
+            expires_at
+        ));
+    }
+    let approval_group = Wallet::unpack(&wallet_account_info.data.borrow())?
+        .approval_group
+        .ok_or_else(|| {
+            strike_err!(
+                "approval_disposition_handler::handle_aggregated",
+                WalletError::ApprovalGroupNotAllowed,
+                "The wallet has no approval group"
+            )
+        })?;
+    let message = approval_disposition_signature_message(
+        program_id,
+        multisig_op_account_info.key,
+        ApprovalDisposition::APPROVE,
+        &params_hash,
+        expires_at,
+    );
+    let signer = verified_signer(
+        instructions_sysvar_account_info,
+        &message,
+        WalletError::InvalidApprovalSignature,
+    )?;
+    if signer != approval_group.group_key {
+        return Err(strike_err!(
+            "approval_disposition_handler::handle_aggregated",
+            WalletError::InvalidApprovalSignature,
+            "The approval is signed by {} rather than the group key",
+            signer
+        ));
+    }
+
+    let mut multisig_op = unpack_op(multisig_op_account_info, params_hash)?;
+    if multisig_op.wallet != *wallet_account_info.key {
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+    multisig_op.record_aggregated_approval(&approval_group, &clock)?;
+    MultisigOp::pack_sized(multisig_op, &mut multisig_op_account_info.data.borrow_mut())
+}
+
+/// Unpacks the op the disposition is for, validating that it has the params the approver saw.
+fn unpack_op(
+    multisig_op_account_info: &AccountInfo,
+    params_hash: Hash,
+) -> Result<MultisigOp, ProgramError> {
+    if MultisigOp::version_from_slice(&multisig_op_account_info.data.borrow())? != VERSION {
+        return Err(WalletError::OperationVersionMismatch.into());
+    }
+
+    let multisig_op = MultisigOp::unpack_sized(&multisig_op_account_info.data.borrow())?;
+
+    match multisig_op.params_hash {
+        None => Err(WalletError::OperationNotInitialized.into()),
+        Some(v) if params_hash != v => Err(WalletError::InvalidSignature.into()),
+        Some(_) => Ok(multisig_op),
+    }
+}
+
+/// The session key registered for the signer in the session key registry of the op's wallet.
+fn session_key(
+    program_id: &Pubkey,
+    registry_account_info: &AccountInfo,
+    multisig_op: &MultisigOp,
+    signer: &Pubkey,
+) -> Result<SessionKey, ProgramError> {
+    pda::verify(
+        program_id,
+        &SessionKeyRegistrySeeds {
+            wallet: &multisig_op.wallet,
+        },
+        registry_account_info.key,
+        None,
+    )?;
+    if registry_account_info.owner != program_id {
+        return Err(WalletError::InvalidSessionKey.into());
+    }
+    SessionKeyRegistry::unpack(&registry_account_info.data.borrow())?
+        .get(signer)
+        .copied()
+        .ok_or_else(|| WalletError::InvalidSessionKey.into())
 }
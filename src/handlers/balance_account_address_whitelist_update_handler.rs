@@ -1,13 +1,15 @@
 use crate::handlers::utils::{
-    finalize_multisig_op, get_clock_from_next_account, next_program_account_info,
-    next_signer_account_info, next_wallet_account_info, start_multisig_config_op,
-    FeeCollectionInfo,
+    finalize_multisig_op, get_clock_from_next_account, next_initiator_account_info,
+    next_program_account_info, next_signer_account_info, next_wallet_account_info,
+    return_allowed_destinations, start_multisig_config_op, FeeCollectionInfo,
 };
 use crate::instruction::BalanceAccountAddressWhitelistUpdate;
+use crate::model::address_book::AddressBookEntry;
 use crate::model::balance_account::BalanceAccountGuidHash;
 use crate::model::multisig_op::MultisigOpParams;
 use crate::model::wallet::Wallet;
-use solana_program::account_info::{next_account_info, AccountInfo};
+use crate::utils::SlotId;
+use solana_program::account_info::AccountInfo;
 use solana_program::entrypoint::ProgramResult;
 use solana_program::program_pack::Pack;
 use solana_program::pubkey::Pubkey;
@@ -23,17 +25,21 @@ pub fn init(
     let accounts_iter = &mut accounts.iter();
     let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
     let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
-    let initiator_account_info = next_account_info(accounts_iter)?;
+    let initiator_account_info =
+        next_initiator_account_info(accounts_iter, multisig_op_account_info, wallet_account_info)?;
     let clock = get_clock_from_next_account(accounts_iter)?;
     let rent_return_account = next_signer_account_info(accounts_iter)?;
 
-    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
-    wallet.validate_config_initiator(initiator_account_info)?;
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    wallet.validate_config_initiator(initiator_account_info, clock.unix_timestamp)?;
+    wallet.get_active_balance_account(account_guid_hash)?;
+    update.validate_canonical()?;
     wallet.validate_balance_account_address_whitelist_update(account_guid_hash, update)?;
 
     start_multisig_config_op(
         &multisig_op_account_info,
-        &wallet,
+        wallet_account_info,
+        &mut wallet,
         clock,
         MultisigOpParams::UpdateBalanceAccountAddressWhitelist {
             wallet_address: *wallet_account_info.key,
@@ -67,6 +73,7 @@ pub fn finalize(
 
     finalize_multisig_op(
         &multisig_op_account_info,
+        wallet_account_info,
         FeeCollectionInfo {
             rent_return_account_info,
             fee_account_info_maybe,
@@ -90,3 +97,19 @@ pub fn finalize(
 
     Ok(())
 }
+
+pub fn get_allowed_destinations(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    account_guid_hash: &BalanceAccountGuidHash,
+    start_slot_id: SlotId<AddressBookEntry>,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+
+    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    return_allowed_destinations(
+        &wallet.get_allowed_destinations_page(account_guid_hash, start_slot_id)?,
+    );
+    Ok(())
+}
@@ -1,16 +1,22 @@
+use crate::error::{strike_err, WalletError};
+use crate::handlers::program_parameters_handler;
 use crate::handlers::utils::{
-    finalize_multisig_op, get_clock_from_next_account, next_program_account_info,
-    next_signer_account_info, next_wallet_account_info, start_multisig_config_op,
-    FeeCollectionInfo,
+    finalize_multisig_op, get_clock_from_next_account, next_initiator_account_info,
+    next_program_account_info, next_signer_account_info, next_wallet_account_info,
+    start_multisig_config_op, validate_balance_account_and_get_seed, FeeCollectionInfo,
 };
 use crate::instruction::BalanceAccountCreation;
-use crate::model::balance_account::BalanceAccountGuidHash;
+use crate::model::balance_account::{BalanceAccount, BalanceAccountGuidHash};
 use crate::model::multisig_op::MultisigOpParams;
-use crate::model::wallet::Wallet;
+use crate::model::wallet::{Wallet, WalletGuidHash};
+use crate::pda::{BalanceAccountSeeds, PdaSeeds};
 use solana_program::account_info::{next_account_info, AccountInfo};
 use solana_program::entrypoint::ProgramResult;
+use solana_program::program::invoke_signed;
 use solana_program::program_pack::Pack;
 use solana_program::pubkey::Pubkey;
+use solana_program::{system_instruction, system_program};
+use spl_token::state::Account as SPLAccount;
 
 pub fn init(
     program_id: &Pubkey,
@@ -23,17 +29,22 @@ pub fn init(
     let accounts_iter = &mut accounts.iter();
     let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
     let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
-    let initiator_account_info = next_account_info(accounts_iter)?;
+    let initiator_account_info =
+        next_initiator_account_info(accounts_iter, multisig_op_account_info, wallet_account_info)?;
     let clock = get_clock_from_next_account(accounts_iter)?;
     let rent_return_account_info = next_signer_account_info(accounts_iter)?;
 
-    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
-    wallet.validate_config_initiator(initiator_account_info)?;
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    wallet.validate_config_initiator(initiator_account_info, clock.unix_timestamp)?;
+    creation_params.validate_canonical()?;
     wallet.validate_balance_account_creation(account_guid_hash, creation_params, program_id)?;
+    program_parameters_handler::load(program_id, accounts)?
+        .validate_approval_timeout(&creation_params.approval_timeout_for_transfer)?;
 
     start_multisig_config_op(
         &multisig_op_account_info,
-        &wallet,
+        wallet_account_info,
+        &mut wallet,
         clock,
         MultisigOpParams::CreateBalanceAccount {
             account_guid_hash: *account_guid_hash,
@@ -65,6 +76,7 @@ pub fn finalize(
 
     finalize_multisig_op(
         &multisig_op_account_info,
+        wallet_account_info,
         FeeCollectionInfo {
             rent_return_account_info,
             fee_account_info_maybe,
@@ -83,6 +95,172 @@ pub fn finalize(
             Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
             Ok(())
         },
-        || -> ProgramResult { Ok(()) },
+        || -> ProgramResult {
+            // nothing was created, but lamports may have been sent to the balance account's
+            // address ahead of time, and would be stranded there
+            let (balance_account_key, _) =
+                BalanceAccount::find_address(wallet_guid_hash, account_guid_hash, program_id);
+            let optional_accounts = accounts.get(4..).unwrap_or_default();
+            let balance_account_info_maybe = optional_accounts
+                .iter()
+                .find(|account_info| *account_info.key == balance_account_key);
+            let system_program_account_info_maybe = optional_accounts
+                .iter()
+                .find(|account_info| *account_info.key == system_program::id());
+            if let (Some(balance_account_info), Some(system_program_account_info)) = (
+                balance_account_info_maybe,
+                system_program_account_info_maybe,
+            ) {
+                reclaim_uncreated_balance_account(
+                    program_id,
+                    wallet_guid_hash,
+                    account_guid_hash,
+                    balance_account_info,
+                    rent_return_account_info,
+                    system_program_account_info,
+                    None,
+                    &[],
+                )?;
+            }
+            Ok(())
+        },
+    )
+}
+
+pub fn cleanup(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    account_guid_hash: &BalanceAccountGuidHash,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let balance_account_info = next_account_info(accounts_iter)?;
+    let rent_return_account_info = next_signer_account_info(accounts_iter)?;
+    let system_program_account_info = next_account_info(accounts_iter)?;
+    let token_program_account_info_maybe = accounts_iter.next();
+    let token_account_infos = accounts_iter.as_slice();
+
+    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    if wallet.get_balance_account(account_guid_hash).is_ok() {
+        return Err(strike_err!(
+            "balance_account_creation_handler::cleanup",
+            WalletError::DuplicateBalanceAccountGuid,
+            "The balance account was created, so there is nothing to clean up"
+        ));
+    }
+    if *rent_return_account_info.key != wallet.rent_return {
+        return Err(WalletError::IncorrectRentReturnAccount.into());
+    }
+
+    reclaim_uncreated_balance_account(
+        program_id,
+        &wallet.wallet_guid_hash,
+        account_guid_hash,
+        balance_account_info,
+        rent_return_account_info,
+        system_program_account_info,
+        token_program_account_info_maybe,
+        token_account_infos,
     )
 }
+
+/// Closes the given token accounts, which have to be empty and owned by the balance account,
+/// then sweeps the lamports at the balance account's address, all into the rent return
+/// account. Only to be used for a balance account that isn't in the wallet.
+#[allow(clippy::too_many_arguments)]
+fn reclaim_uncreated_balance_account<'a>(
+    program_id: &Pubkey,
+    wallet_guid_hash: &WalletGuidHash,
+    account_guid_hash: &BalanceAccountGuidHash,
+    balance_account_info: &AccountInfo<'a>,
+    rent_return_account_info: &AccountInfo<'a>,
+    system_program_account_info: &AccountInfo<'a>,
+    token_program_account_info_maybe: Option<&AccountInfo<'a>>,
+    token_account_infos: &[AccountInfo<'a>],
+) -> ProgramResult {
+    let bump_seed = validate_balance_account_and_get_seed(
+        balance_account_info,
+        wallet_guid_hash,
+        account_guid_hash,
+        program_id,
+    )?;
+    let balance_account_seeds = BalanceAccountSeeds {
+        wallet_guid_hash,
+        account_guid_hash,
+    };
+    let bump_seed = [bump_seed];
+    let seeds = &balance_account_seeds.signer_seeds(&bump_seed)[..];
+    if *system_program_account_info.key != system_program::id() {
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+
+    if !token_account_infos.is_empty() {
+        let token_program_account_info =
+            token_program_account_info_maybe.ok_or(WalletError::AccountNotRecognized)?;
+        if *token_program_account_info.key != spl_token::id() {
+            return Err(WalletError::AccountNotRecognized.into());
+        }
+        for token_account_info in token_account_infos {
+            if *token_account_info.owner != spl_token::id() {
+                return Err(strike_err!(
+                    "balance_account_creation_handler::reclaim_uncreated_balance_account",
+                    WalletError::InvalidTokenAccount,
+                    "Token account {} isn't owned by the token program",
+                    token_account_info.key
+                ));
+            }
+            let token_account = SPLAccount::unpack(&token_account_info.data.borrow())?;
+            if token_account.owner != *balance_account_info.key {
+                return Err(strike_err!(
+                    "balance_account_creation_handler::reclaim_uncreated_balance_account",
+                    WalletError::InvalidTokenAccount,
+                    "Token account {} isn't owned by the balance account",
+                    token_account_info.key
+                ));
+            }
+            // wrapped SOL is returned as lamports when the account is closed
+            if token_account.amount > 0 && !token_account.is_native() {
+                return Err(strike_err!(
+                    "balance_account_creation_handler::reclaim_uncreated_balance_account",
+                    WalletError::TokenAccountNotEmpty,
+                    "Token account {} isn't empty",
+                    token_account_info.key
+                ));
+            }
+            invoke_signed(
+                &spl_token::instruction::close_account(
+                    &spl_token::id(),
+                    token_account_info.key,
+                    rent_return_account_info.key,
+                    balance_account_info.key,
+                    &[],
+                )?,
+                &[
+                    token_account_info.clone(),
+                    rent_return_account_info.clone(),
+                    balance_account_info.clone(),
+                    token_program_account_info.clone(),
+                ],
+                &[seeds],
+            )?;
+        }
+    }
+
+    let lamports = balance_account_info.lamports();
+    if lamports > 0 && *balance_account_info.owner == system_program::id() {
+        invoke_signed(
+            &system_instruction::transfer(
+                balance_account_info.key,
+                rent_return_account_info.key,
+                lamports,
+            ),
+            &[
+                balance_account_info.clone(),
+                rent_return_account_info.clone(),
+                system_program_account_info.clone(),
+            ],
+            &[seeds],
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,128 @@
+use crate::handlers::utils::{
+    finalize_multisig_op, get_clock_from_next_account, next_initiator_account_info,
+    next_program_account_info, next_signer_account_info, next_wallet_account_info,
+    start_multisig_config_op, validate_balance_account_and_get_seed, FeeCollectionInfo,
+};
+use crate::model::balance_account::BalanceAccountGuidHash;
+use crate::model::multisig_op::MultisigOpParams;
+use crate::model::wallet::Wallet;
+use crate::pda::{BalanceAccountSeeds, PdaSeeds};
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program::invoke_signed;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_instruction;
+
+pub fn init(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    fee_amount: u64,
+    fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+    account_guid_hash: &BalanceAccountGuidHash,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let initiator_account_info =
+        next_initiator_account_info(accounts_iter, multisig_op_account_info, wallet_account_info)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+    let rent_return_account_info = next_signer_account_info(accounts_iter)?;
+
+    let mut wallet: Wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+
+    // archived balance accounts can be deleted as well
+    wallet.validate_config_initiator(initiator_account_info, clock.unix_timestamp)?;
+    wallet.validate_balance_account_deletion(account_guid_hash, program_id)?;
+
+    start_multisig_config_op(
+        multisig_op_account_info,
+        wallet_account_info,
+        &mut wallet,
+        clock,
+        MultisigOpParams::DeleteBalanceAccount {
+            wallet_address: *wallet_account_info.key,
+            account_guid_hash: *account_guid_hash,
+        },
+        *initiator_account_info.key,
+        *rent_return_account_info.key,
+        fee_amount,
+        fee_account_guid_hash,
+    )?;
+
+    Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+pub fn finalize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    account_guid_hash: &BalanceAccountGuidHash,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let balance_account_info = next_account_info(accounts_iter)?;
+    let system_program_account_info = next_account_info(accounts_iter)?;
+    let rent_return_account_info = next_signer_account_info(accounts_iter)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+    let fee_account_info_maybe = accounts_iter.next();
+
+    let wallet_guid_hash =
+        &Wallet::wallet_guid_hash_from_slice(&wallet_account_info.data.borrow())?;
+
+    finalize_multisig_op(
+        multisig_op_account_info,
+        wallet_account_info,
+        FeeCollectionInfo {
+            rent_return_account_info,
+            fee_account_info_maybe,
+            wallet_guid_hash,
+            program_id,
+        },
+        clock,
+        MultisigOpParams::DeleteBalanceAccount {
+            wallet_address: *wallet_account_info.key,
+            account_guid_hash: *account_guid_hash,
+        },
+        || -> ProgramResult {
+            let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+            wallet.delete_balance_account(account_guid_hash, program_id)?;
+            Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+
+            // the rent return account is covered by the op's approvals, so it is safe to
+            // sweep the balance account into it, rent-exempt minimum included
+            let bump_seed = validate_balance_account_and_get_seed(
+                balance_account_info,
+                wallet_guid_hash,
+                account_guid_hash,
+                program_id,
+            )?;
+            let lamports = balance_account_info.lamports();
+            if lamports > 0 {
+                invoke_signed(
+                    &system_instruction::transfer(
+                        balance_account_info.key,
+                        rent_return_account_info.key,
+                        lamports,
+                    ),
+                    &[
+                        balance_account_info.clone(),
+                        rent_return_account_info.clone(),
+                        system_program_account_info.clone(),
+                    ],
+                    &[&BalanceAccountSeeds {
+                        wallet_guid_hash,
+                        account_guid_hash,
+                    }
+                    .signer_seeds(&[bump_seed])],
+                )?;
+            }
+            Ok(())
+        },
+        || -> ProgramResult { Ok(()) },
+    )?;
+
+    Ok(())
+}
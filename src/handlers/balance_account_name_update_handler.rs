@@ -1,12 +1,12 @@
 use crate::handlers::utils::{
-    finalize_multisig_op, get_clock_from_next_account, next_program_account_info,
-    next_signer_account_info, next_wallet_account_info, start_multisig_config_op,
-    FeeCollectionInfo,
+    finalize_multisig_op, get_clock_from_next_account, next_initiator_account_info,
+    next_program_account_info, next_signer_account_info, next_wallet_account_info,
+    start_multisig_config_op, FeeCollectionInfo,
 };
 use crate::model::balance_account::{BalanceAccountGuidHash, BalanceAccountNameHash};
 use crate::model::multisig_op::MultisigOpParams;
 use crate::model::wallet::Wallet;
-use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::account_info::AccountInfo;
 use solana_program::entrypoint::ProgramResult;
 use solana_program::program_pack::Pack;
 use solana_program::pubkey::Pubkey;
@@ -22,15 +22,16 @@ pub fn init(
     let accounts_iter = &mut accounts.iter();
     let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
     let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
-    let initiator_account_info = next_account_info(accounts_iter)?;
+    let initiator_account_info =
+        next_initiator_account_info(accounts_iter, multisig_op_account_info, wallet_account_info)?;
     let clock = get_clock_from_next_account(accounts_iter)?;
     let rent_return_account_info = next_signer_account_info(accounts_iter)?;
 
-    let wallet: Wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    let mut wallet: Wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
 
-    // ensure GUID references valid account for this wallet
-    wallet.validate_balance_account_guid_hash(account_guid_hash)?;
-    wallet.validate_config_initiator(initiator_account_info)?;
+    // ensure GUID references a valid, unarchived account for this wallet
+    wallet.get_active_balance_account(account_guid_hash)?;
+    wallet.validate_config_initiator(initiator_account_info, clock.unix_timestamp)?;
     wallet.validate_balance_account_name_update(
         account_guid_hash,
         account_name_hash,
@@ -39,7 +40,8 @@ pub fn init(
 
     start_multisig_config_op(
         &multisig_op_account_info,
-        &wallet,
+        wallet_account_info,
+        &mut wallet,
         clock,
         MultisigOpParams::UpdateBalanceAccountName {
             wallet_address: *wallet_account_info.key,
@@ -75,6 +77,7 @@ pub fn finalize(
 
     finalize_multisig_op(
         &multisig_op_account_info,
+        wallet_account_info,
         FeeCollectionInfo {
             rent_return_account_info,
             fee_account_info_maybe,
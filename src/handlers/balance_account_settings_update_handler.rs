@@ -1,47 +1,65 @@
 use crate::handlers::utils::{
-    finalize_multisig_op, get_clock_from_next_account, next_program_account_info,
-    next_signer_account_info, next_wallet_account_info, start_multisig_config_op,
-    FeeCollectionInfo,
+    finalize_multisig_op, get_clock_from_next_account, next_initiator_account_info,
+    next_program_account_info, next_signer_account_info, next_wallet_account_info,
+    start_multisig_config_op, FeeCollectionInfo,
 };
 use crate::model::balance_account::BalanceAccountGuidHash;
 use crate::model::multisig_op::{BooleanSetting, MultisigOpParams};
 use crate::model::wallet::Wallet;
-use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::account_info::AccountInfo;
 use solana_program::entrypoint::ProgramResult;
 use solana_program::program_pack::Pack;
 use solana_program::pubkey::Pubkey;
 
+/// The balance account settings to change, leaving those that are `None` as they are.
+#[derive(Clone, Copy)]
+pub struct BalanceAccountSettingsUpdate {
+    pub whitelist_enabled: Option<BooleanSetting>,
+    pub dapps_enabled: Option<BooleanSetting>,
+    pub archived: Option<BooleanSetting>,
+    pub system_owned_destinations_only: Option<BooleanSetting>,
+}
+
 pub fn init(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     fee_amount: u64,
     fee_account_guid_hash: Option<BalanceAccountGuidHash>,
     account_guid_hash: &BalanceAccountGuidHash,
-    whitelist_enabled: Option<BooleanSetting>,
-    dapps_enabled: Option<BooleanSetting>,
+    update: BalanceAccountSettingsUpdate,
 ) -> ProgramResult {
+    let BalanceAccountSettingsUpdate {
+        whitelist_enabled,
+        dapps_enabled,
+        archived,
+        system_owned_destinations_only,
+    } = update;
     let accounts_iter = &mut accounts.iter();
     let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
     let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
-    let initiator_account_info = next_account_info(accounts_iter)?;
+    let initiator_account_info =
+        next_initiator_account_info(accounts_iter, multisig_op_account_info, wallet_account_info)?;
     let clock = get_clock_from_next_account(accounts_iter)?;
     let rent_return_account_info = next_signer_account_info(accounts_iter)?;
 
-    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
-    wallet.validate_config_initiator(initiator_account_info)?;
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    wallet.validate_config_initiator(initiator_account_info, clock.unix_timestamp)?;
     if let Some(status) = whitelist_enabled {
         wallet.validate_whitelist_enabled_update(account_guid_hash, status)?;
     }
 
     start_multisig_config_op(
         &multisig_op_account_info,
-        &wallet,
+        wallet_account_info,
+        &mut wallet,
         clock,
         MultisigOpParams::UpdateBalanceAccountSettings {
             wallet_address: *wallet_account_info.key,
             account_guid_hash: *account_guid_hash,
             whitelist_enabled,
             dapps_enabled,
+            archived,
+            system_owned_destinations_only,
         },
         *initiator_account_info.key,
         *rent_return_account_info.key,
@@ -54,9 +72,14 @@ pub fn finalize(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     account_guid_hash: &BalanceAccountGuidHash,
-    whitelist_enabled: Option<BooleanSetting>,
-    dapps_enabled: Option<BooleanSetting>,
+    update: BalanceAccountSettingsUpdate,
 ) -> ProgramResult {
+    let BalanceAccountSettingsUpdate {
+        whitelist_enabled,
+        dapps_enabled,
+        archived,
+        system_owned_destinations_only,
+    } = update;
     let accounts_iter = &mut accounts.iter();
     let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
     let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
@@ -69,6 +92,7 @@ pub fn finalize(
 
     finalize_multisig_op(
         &multisig_op_account_info,
+        wallet_account_info,
         FeeCollectionInfo {
             rent_return_account_info,
             fee_account_info_maybe,
@@ -81,6 +105,8 @@ pub fn finalize(
             account_guid_hash: *account_guid_hash,
             whitelist_enabled,
             dapps_enabled,
+            archived,
+            system_owned_destinations_only,
         },
         || -> ProgramResult {
             let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow_mut())?;
@@ -90,6 +116,12 @@ pub fn finalize(
             if let Some(enabled) = dapps_enabled {
                 wallet.update_dapps_enabled(&account_guid_hash, enabled)?;
             }
+            if let Some(archived) = archived {
+                wallet.update_archived(account_guid_hash, archived)?;
+            }
+            if let Some(enabled) = system_owned_destinations_only {
+                wallet.update_system_owned_destinations_only(account_guid_hash, enabled)?;
+            }
             Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
             Ok(())
         },
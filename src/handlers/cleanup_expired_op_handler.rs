@@ -0,0 +1,61 @@
+use crate::error::WalletError;
+use crate::handlers::utils::{
+    collect_remaining_balance, get_clock_from_next_account, log_op_disposition,
+    next_program_account_info,
+};
+use crate::model::internal_transfer_handshake::InternalTransferHandshake;
+use crate::model::multisig_op::{MultisigOp, OperationDisposition};
+use crate::version::{Versioned, VERSION};
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+
+pub fn handle(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let rent_return_account_info = next_account_info(accounts_iter)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+
+    // ops of earlier versions are already finalized as expired by any finalize
+    if MultisigOp::version_from_slice(&multisig_op_account_info.data.borrow())? != VERSION {
+        return Err(WalletError::AccountVersionMismatch.into());
+    }
+    let mut multisig_op = MultisigOp::unpack_sized(&multisig_op_account_info.data.borrow())?;
+    if *rent_return_account_info.key != multisig_op.rent_return {
+        return Err(WalletError::IncorrectRentReturnAccount.into());
+    }
+    let multisig_data_account_info = match multisig_op.multisig_data_account {
+        Some(_) => {
+            let multisig_data_account_info = next_program_account_info(accounts_iter, program_id)?;
+            multisig_op.validate_multisig_data_account(multisig_data_account_info.key)?;
+            Some(multisig_data_account_info)
+        }
+        None => None,
+    };
+    multisig_op.expire(&clock)?;
+    log_op_disposition(OperationDisposition::EXPIRED);
+    MultisigOp::pack_sized(multisig_op, &mut multisig_op_account_info.data.borrow_mut())?;
+
+    // the side accounts the op's finalize would have closed go with it
+    if let Some(multisig_data_account_info) = multisig_data_account_info {
+        collect_remaining_balance(multisig_data_account_info, rent_return_account_info)?;
+    }
+    for handshake_account_info in accounts_iter.filter(|account_info| {
+        is_handshake_of(program_id, account_info, multisig_op_account_info.key)
+    }) {
+        collect_remaining_balance(handshake_account_info, rent_return_account_info)?;
+    }
+    collect_remaining_balance(multisig_op_account_info, rent_return_account_info)
+}
+
+/// Whether the account is a handshake acknowledging the op. Only the program can write to an
+/// account it owns, and it only creates handshakes at their PDA, so an account owned by the
+/// program and of the handshake's size that names the op is one.
+fn is_handshake_of(program_id: &Pubkey, account_info: &AccountInfo, multisig_op: &Pubkey) -> bool {
+    account_info.owner == program_id
+        && account_info.is_writable
+        && account_info.data_len() == InternalTransferHandshake::LEN
+        && InternalTransferHandshake::unpack(&account_info.data.borrow())
+            .map_or(false, |handshake| handshake.multisig_op == *multisig_op)
+}
@@ -0,0 +1,110 @@
+use crate::error::WalletError;
+use crate::handlers::utils::{
+    finalize_multisig_op, get_clock_from_next_account, next_initiator_account_info,
+    next_program_account_info, next_signer_account_info, next_wallet_account_info,
+    start_multisig_config_op, FeeCollectionInfo,
+};
+use crate::model::balance_account::BalanceAccountGuidHash;
+use crate::model::compliance_policy::CompliancePolicy;
+use crate::model::multisig_op::{MultisigOp, MultisigOpParams};
+use crate::model::wallet::Wallet;
+use crate::version::{Versioned, VERSION};
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::Hash;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+
+pub fn init(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    fee_amount: u64,
+    fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+    policy: &Option<CompliancePolicy>,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let initiator_account_info =
+        next_initiator_account_info(accounts_iter, multisig_op_account_info, wallet_account_info)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+    let rent_return_account_info = next_signer_account_info(accounts_iter)?;
+
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    wallet.validate_config_initiator(initiator_account_info, clock.unix_timestamp)?;
+
+    start_multisig_config_op(
+        multisig_op_account_info,
+        wallet_account_info,
+        &mut wallet,
+        clock,
+        MultisigOpParams::UpdateCompliancePolicy {
+            wallet_address: *wallet_account_info.key,
+            policy: *policy,
+        },
+        *initiator_account_info.key,
+        *rent_return_account_info.key,
+        fee_amount,
+        fee_account_guid_hash,
+    )
+}
+
+pub fn finalize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    policy: &Option<CompliancePolicy>,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let rent_return_account_info = next_signer_account_info(accounts_iter)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+    let fee_account_info_maybe = accounts_iter.next();
+
+    let wallet_guid_hash =
+        &Wallet::wallet_guid_hash_from_slice(&wallet_account_info.data.borrow())?;
+
+    finalize_multisig_op(
+        multisig_op_account_info,
+        wallet_account_info,
+        FeeCollectionInfo {
+            rent_return_account_info,
+            fee_account_info_maybe,
+            wallet_guid_hash,
+            program_id,
+        },
+        clock,
+        MultisigOpParams::UpdateCompliancePolicy {
+            wallet_address: *wallet_account_info.key,
+            policy: *policy,
+        },
+        || -> ProgramResult {
+            let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+            wallet.update_compliance_policy(policy);
+            Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+            Ok(())
+        },
+        || -> ProgramResult { Ok(()) },
+    )
+}
+
+pub fn approve(program_id: &Pubkey, accounts: &[AccountInfo], params_hash: Hash) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let service_account_info = next_account_info(accounts_iter)?;
+
+    if MultisigOp::version_from_slice(&multisig_op_account_info.data.borrow())? != VERSION {
+        return Err(WalletError::OperationVersionMismatch.into());
+    }
+    let mut multisig_op = MultisigOp::unpack_sized(&multisig_op_account_info.data.borrow())?;
+
+    // the service approves the op it screened, so a different op can't take its place
+    if multisig_op.params_hash != Some(params_hash) {
+        return Err(WalletError::InvalidSignature.into());
+    }
+
+    multisig_op.record_compliance_approval(service_account_info)?;
+    MultisigOp::pack_sized(multisig_op, &mut multisig_op_account_info.data.borrow_mut())?;
+
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use crate::error::WalletError;
+use crate::handlers::address_book_update_handler::validate_declared_entry_owners;
+use crate::handlers::program_parameters_handler;
+use crate::handlers::utils::{
+    get_clock_from_next_account, next_signer_account_info, next_wallet_account_info,
+    return_policy_diff,
+};
+use crate::instruction::ConfigUpdate;
+use crate::model::policy_diff::PolicySummary;
+use crate::model::wallet::Wallet;
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+
+/// Validates the update as its init would and logs the policy it would result in. Like a dApp
+/// transaction simulation, this always fails, so nothing it does is ever committed.
+pub fn simulate(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    update: &ConfigUpdate,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let initiator_account_info = next_signer_account_info(accounts_iter)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+
+    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    wallet.validate_config_initiator(initiator_account_info, clock.unix_timestamp)?;
+    let mut updated_wallet = wallet.clone();
+
+    match update {
+        ConfigUpdate::WalletConfigPolicy(update) => {
+            wallet.validate_not_standby()?;
+            update.validate_canonical()?;
+            wallet.validate_config_policy_update(update)?;
+            program_parameters_handler::load(program_id, accounts)?
+                .validate_approval_timeout(&update.approval_timeout_for_config)?;
+
+            let diff = wallet.config_policy_diff(update)?;
+            if diff.weakens_policy() && !wallet.config_policy_timelock.is_zero() {
+                msg!(
+                    "Update would be staged for {} seconds",
+                    wallet.config_policy_timelock.as_secs()
+                );
+            }
+            updated_wallet.update_config_policy(update)?;
+            log_policy_summary(
+                "Wallet config policy",
+                &updated_wallet.config_policy_summary(),
+            );
+            return_policy_diff(&diff);
+        }
+        ConfigUpdate::AddressBook(update) => {
+            update.validate_canonical()?;
+            wallet.validate_address_book_update(update, clock.unix_timestamp)?;
+            validate_declared_entry_owners(update, accounts)?;
+
+            updated_wallet.update_address_book(update, clock.unix_timestamp)?;
+            msg!(
+                "Address book: {} entries",
+                updated_wallet.address_book.filled_slots().len()
+            );
+            for (i, whitelist_update) in update.balance_account_whitelist_updates.iter().enumerate()
+            {
+                let balance_account =
+                    updated_wallet.get_balance_account(&whitelist_update.guid_hash)?;
+                msg!(
+                    "Whitelist update {}: {} allowed destinations",
+                    i,
+                    balance_account.allowed_destinations.count_enabled()
+                );
+            }
+        }
+        ConfigUpdate::BalanceAccountPolicy {
+            account_guid_hash,
+            update,
+        } => {
+            wallet.get_active_balance_account(account_guid_hash)?;
+            update.validate_canonical()?;
+            wallet.validate_balance_account_policy_update(account_guid_hash, update)?;
+            program_parameters_handler::load(program_id, accounts)?
+                .validate_approval_timeout(&update.approval_timeout_for_transfer)?;
+
+            updated_wallet.update_balance_account_policy(account_guid_hash, update)?;
+            log_policy_summary(
+                "Balance account policy",
+                &Wallet::balance_account_policy_summary(
+                    &updated_wallet.get_balance_account(account_guid_hash)?,
+                ),
+            );
+            return_policy_diff(&wallet.balance_account_policy_diff(account_guid_hash, update)?);
+        }
+    }
+
+    Err(WalletError::SimulationFinished.into())
+}
+
+fn log_policy_summary(name: &str, summary: &PolicySummary) {
+    msg!(
+        "{}: approvers {:?}, {} approvals required, approval timeout {}s, delay {}s",
+        name,
+        summary
+            .approvers
+            .iter_enabled()
+            .map(|id| id.value)
+            .collect::<Vec<usize>>(),
+        summary.approvals_required,
+        summary.approval_timeout.as_secs(),
+        summary.delay.as_secs()
+    );
+}
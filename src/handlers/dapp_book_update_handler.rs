@@ -1,13 +1,15 @@
+use crate::error::{strike_err, WalletError};
 use crate::handlers::utils::{
-    finalize_multisig_op, get_clock_from_next_account, next_program_account_info,
-    next_signer_account_info, next_wallet_account_info, start_multisig_config_op,
-    FeeCollectionInfo,
+    finalize_multisig_op, get_clock_from_next_account, next_initiator_account_info,
+    next_program_account_info, next_signer_account_info, next_wallet_account_info,
+    start_multisig_config_op, FeeCollectionInfo,
 };
 use crate::instruction::DAppBookUpdate;
+use crate::model::address_book::DAppBookEntry;
 use crate::model::balance_account::BalanceAccountGuidHash;
 use crate::model::multisig_op::MultisigOpParams;
 use crate::model::wallet::Wallet;
-use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::account_info::AccountInfo;
 use solana_program::entrypoint::ProgramResult;
 use solana_program::program_pack::Pack;
 use solana_program::pubkey::Pubkey;
@@ -22,18 +24,21 @@ pub fn init(
     let accounts_iter = &mut accounts.iter();
     let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
     let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
-    let initiator_account_info = next_account_info(accounts_iter)?;
+    let initiator_account_info =
+        next_initiator_account_info(accounts_iter, multisig_op_account_info, wallet_account_info)?;
     let clock = get_clock_from_next_account(accounts_iter)?;
     let rent_return_account_info = next_signer_account_info(accounts_iter)?;
 
-    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
 
-    wallet.validate_config_initiator(initiator_account_info)?;
-    wallet.validate_dapp_book_update(update)?;
+    wallet.validate_config_initiator(initiator_account_info, clock.unix_timestamp)?;
+    update.validate_canonical()?;
+    wallet.validate_dapp_book_update(update, clock.unix_timestamp)?;
 
     start_multisig_config_op(
         &multisig_op_account_info,
-        &wallet,
+        wallet_account_info,
+        &mut wallet,
         clock,
         MultisigOpParams::UpdateDAppBook {
             wallet_address: *wallet_account_info.key,
@@ -61,6 +66,7 @@ pub fn finalize(
     let rent_return_account_info = next_signer_account_info(accounts_iter)?;
     let clock = get_clock_from_next_account(accounts_iter)?;
     let fee_account_info_maybe = accounts_iter.next();
+    let now = clock.unix_timestamp;
 
     let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow_mut())?;
 
@@ -69,6 +75,7 @@ pub fn finalize(
 
     finalize_multisig_op(
         &multisig_op_account_info,
+        wallet_account_info,
         FeeCollectionInfo {
             rent_return_account_info,
             fee_account_info_maybe,
@@ -81,7 +88,7 @@ pub fn finalize(
             update: update.clone(),
         },
         || -> ProgramResult {
-            wallet.update_dapp_book(update)?;
+            wallet.update_dapp_book(update, now)?;
             Ok(())
         },
         || -> ProgramResult { Ok(()) },
@@ -91,3 +98,31 @@ pub fn finalize(
 
     Ok(())
 }
+
+pub fn suspend(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    dapp: &DAppBookEntry,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let approver_account_info = next_signer_account_info(accounts_iter)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    if !wallet
+        .get_active_config_approvers_keys(clock.unix_timestamp)
+        .contains(approver_account_info.key)
+    {
+        return Err(strike_err!(
+            "dapp_book_update_handler::suspend",
+            WalletError::InvalidApprover,
+            "Only a config approver can suspend a dapp"
+        ));
+    }
+
+    wallet.suspend_dapp(dapp, clock.unix_timestamp)?;
+    Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+
+    Ok(())
+}
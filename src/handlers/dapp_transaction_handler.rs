@@ -1,5 +1,6 @@
 use bitvec::macros::internal::funty::Fundamental;
 use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::clock::Clock;
 use solana_program::entrypoint::ProgramResult;
 use solana_program::hash::Hash;
 use solana_program::instruction::Instruction;
@@ -8,19 +9,28 @@ use solana_program::program::invoke_signed;
 use solana_program::program_error::ProgramError;
 use solana_program::program_pack::Pack;
 use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::Sysvar;
 use spl_token::state::Account as SPLAccount;
 
-use crate::error::WalletError;
+use crate::error::{strike_err, WalletError};
+use crate::handlers::program_parameters_handler;
 use crate::handlers::utils::{
     calculate_expires, collect_remaining_balance, get_clock_from_next_account, log_op_disposition,
-    next_program_account_info, next_signer_account_info, next_wallet_account_info,
-    validate_balance_account_and_get_seed,
+    next_initiator_account_info, next_program_account_info, next_signer_account_info,
+    next_wallet_account_info, op_approvers, record_assistant_initiation, return_receipt,
+    unpack_uninitialized, unpack_uninitialized_multisig_op, validate_balance_account_and_get_seed,
+    validate_finalize_submitter, validate_rent_return,
 };
 use crate::model::address_book::DAppBookEntry;
 use crate::model::balance_account::BalanceAccountGuidHash;
 use crate::model::dapp_multisig_data::DAppMultisigData;
-use crate::model::multisig_op::{ApprovalDisposition, MultisigOp, OperationDisposition};
+use crate::model::finalize_submitter_policy::FinalizeCategory;
+use crate::model::multisig_op::{
+    ApprovalDisposition, MultisigOp, MultisigOpCode, OperationDisposition,
+};
 use crate::model::wallet::Wallet;
+use crate::pda::{BalanceAccountSeeds, PdaSeeds};
+use crate::utils::unique_account_metas;
 use crate::version::{Versioned, VERSION};
 
 pub fn init(
@@ -36,30 +46,47 @@ pub fn init(
     let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
     let multisig_data_account_info = next_program_account_info(accounts_iter, program_id)?;
     let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
-    let initiator_account_info = next_account_info(accounts_iter)?;
+    let initiator_account_info =
+        next_initiator_account_info(accounts_iter, multisig_op_account_info, wallet_account_info)?;
     let clock = get_clock_from_next_account(accounts_iter)?;
     let rent_return_account_info = next_signer_account_info(accounts_iter)?;
 
-    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
-    let balance_account = wallet.get_balance_account(account_guid_hash)?;
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    wallet.validate_not_provisioning()?;
+    wallet.validate_not_frozen()?;
+    let balance_account = wallet.get_active_balance_account(account_guid_hash)?;
+    program_parameters_handler::load(program_id, accounts)?
+        .validate_dapp_instruction_count(instruction_count)?;
 
     if balance_account.are_dapps_disabled() {
         return Err(WalletError::DAppsDisabled.into());
     }
 
-    wallet.validate_transfer_initiator(initiator_account_info)?;
+    wallet.validate_transfer_initiator(initiator_account_info, clock.unix_timestamp)?;
 
     if !balance_account.is_whitelist_disabled() {
-        if !wallet.dapp_allowed(dapp) {
-            return Err(WalletError::DAppNotAllowed.into());
-        }
+        wallet.validate_dapp_allowed(dapp, clock.unix_timestamp)?;
     }
+    wallet.validate_dapp_category_allowed(&balance_account, &dapp.address)?;
+    wallet.fee_policy.validate_fee(fee_amount)?;
+
+    record_assistant_initiation(
+        wallet_account_info,
+        &mut wallet,
+        initiator_account_info.key,
+        &clock,
+    )?;
 
-    let mut multisig_op = MultisigOp::unpack_unchecked(&multisig_op_account_info.data.borrow())?;
+    let mut multisig_op = unpack_uninitialized_multisig_op(multisig_op_account_info)?;
+    let (approvers, approvals_required) = op_approvers(
+        &wallet,
+        wallet.get_active_transfer_approvers_keys(&balance_account, clock.unix_timestamp),
+        balance_account.approvals_required_for_transfer,
+    );
     multisig_op.init(
-        wallet.get_transfer_approvers_keys(&balance_account),
+        approvers,
         (*initiator_account_info.key, ApprovalDisposition::NONE),
-        balance_account.approvals_required_for_transfer,
+        approvals_required,
         clock.unix_timestamp,
         calculate_expires(
             clock.unix_timestamp,
@@ -69,16 +96,26 @@ pub fn init(
         *rent_return_account_info.key,
         fee_amount,
         fee_account_guid_hash,
+        wallet.op_nonce,
     )?;
-    MultisigOp::pack(multisig_op, &mut multisig_op_account_info.data.borrow_mut())?;
-
-    let mut multisig_data =
-        DAppMultisigData::unpack_unchecked(&multisig_data_account_info.data.borrow())?;
+    multisig_op.stake_weight_governance = wallet.stake_weight_governance;
+    multisig_op.wallet = *wallet_account_info.key;
+    multisig_op.category = Some(FinalizeCategory::DApp);
+    multisig_op.op_code = Some(MultisigOpCode::DAppTransaction);
+    multisig_op.record_activity(clock.slot);
+    multisig_op.set_expiry_slot(clock.slot, balance_account.approval_timeout_for_transfer);
+    multisig_op.bind_multisig_data_account(*multisig_data_account_info.key);
+    // the params hash is only returned once all instructions have been supplied
+    return_receipt(&multisig_op, &clock);
+    MultisigOp::pack_sized(multisig_op, &mut multisig_op_account_info.data.borrow_mut())?;
+
+    let mut multisig_data: DAppMultisigData = unpack_uninitialized(multisig_data_account_info)?;
     multisig_data.init(
         *wallet_account_info.key,
         *account_guid_hash,
         dapp,
         instruction_count,
+        wallet.dapp_instruction_limit(&dapp),
     )?;
     DAppMultisigData::pack(
         multisig_data,
@@ -98,6 +135,7 @@ pub fn supply_instructions(
     let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
     let multisig_data_account_info = next_program_account_info(accounts_iter, program_id)?;
     let initiator_account_info = next_account_info(accounts_iter)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
 
     if !initiator_account_info.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
@@ -107,22 +145,34 @@ pub fn supply_instructions(
         return Err(WalletError::OperationVersionMismatch.into());
     }
 
-    let mut multisig_op = MultisigOp::unpack(&multisig_op_account_info.data.borrow())?;
+    let mut multisig_op = MultisigOp::unpack_sized(&multisig_op_account_info.data.borrow())?;
     if multisig_op.initiator != *initiator_account_info.key {
         return Err(WalletError::IncorrectInitiatorAccount.into());
     }
+    multisig_op.validate_multisig_data_account(multisig_data_account_info.key)?;
 
     let params_hash = {
         let mut multisig_data =
             DAppMultisigData::unpack(&multisig_data_account_info.data.borrow())?;
+        if multisig_data.wallet_address != *wallet_account_info.key {
+            return Err(strike_err!(
+                "dapp_transaction_handler::supply_instructions",
+                WalletError::AccountNotRecognized,
+                "Wallet account is not the wallet of the dApp transaction"
+            ));
+        }
+        validate_instruction_programs(
+            &Wallet::unpack(&wallet_account_info.data.borrow())?,
+            &multisig_data.account_guid_hash,
+            &instructions,
+        )?;
 
-        for index in starting_index..starting_index + instructions.len().as_u8() {
-            multisig_data.add_instruction(
-                index,
-                &instructions
-                    .get(usize::from(index - starting_index))
-                    .unwrap(),
-            )?;
+        let ending_index = u8::try_from(instructions.len())
+            .ok()
+            .and_then(|count| starting_index.checked_add(count))
+            .ok_or(WalletError::DAppInstructionOverflow)?;
+        for (index, instruction) in (starting_index..ending_index).zip(instructions.iter()) {
+            multisig_data.add_instruction(index, instruction)?;
         }
 
         let params_hash = if multisig_data.all_instructions_supplied() {
@@ -139,28 +189,35 @@ pub fn supply_instructions(
         params_hash
     };
 
-    if let Some(_) = params_hash {
-        multisig_op.params_hash = params_hash;
+    if let Some(params_hash) = params_hash {
+        let clock = Clock::get()?;
+        multisig_op.set_params_hash(
+            params_hash,
+            initiator_account_info.key,
+            clock.unix_timestamp,
+        )?;
+        return_receipt(&multisig_op, &clock);
+        MultisigOp::pack_sized(multisig_op, &mut multisig_op_account_info.data.borrow_mut())?;
+    }
 
-        // record approval
-        if let Some(record) = multisig_op
-            .disposition_records
-            .iter_mut()
-            .find(|r| r.approver == *initiator_account_info.key)
-        {
-            if record.disposition == ApprovalDisposition::NONE {
-                record.disposition = ApprovalDisposition::APPROVE
-            }
-        }
-        if multisig_op.get_disposition_count(ApprovalDisposition::APPROVE)
-            == multisig_op.dispositions_required
-        {
-            multisig_op.operation_disposition = OperationDisposition::APPROVED
-        }
+    Ok(())
+}
 
-        MultisigOp::pack(multisig_op, &mut multisig_op_account_info.data.borrow_mut())?;
+fn validate_instruction_programs(
+    wallet: &Wallet,
+    account_guid_hash: &BalanceAccountGuidHash,
+    instructions: &[Instruction],
+) -> ProgramResult {
+    let balance_account = wallet.get_balance_account(account_guid_hash)?;
+    let now = Clock::get()?.unix_timestamp;
+    for instruction in instructions {
+        // a category policy applies even to accounts that may transact with dapps outside the
+        // dapp book
+        wallet.validate_dapp_category_allowed(&balance_account, &instruction.program_id)?;
+        if !balance_account.is_whitelist_disabled() {
+            wallet.validate_dapp_program_allowed(&instruction.program_id, now)?;
+        }
     }
-
     Ok(())
 }
 
@@ -262,10 +319,13 @@ pub fn finalize(
     let clock = get_clock_from_next_account(accounts_iter)?;
 
     if MultisigOp::version_from_slice(&multisig_op_account_info.data.borrow())? == VERSION {
-        let multisig_op = MultisigOp::unpack(&multisig_op_account_info.data.borrow())?;
+        let multisig_op = MultisigOp::unpack_sized(&multisig_op_account_info.data.borrow())?;
+        multisig_op.validate_multisig_data_account(multisig_data_account_info.key)?;
         let multisig_data = DAppMultisigData::unpack(&multisig_data_account_info.data.borrow())?;
+        multisig_data.validate_limits()?;
 
         let instructions = multisig_data.instructions()?;
+        validate_account_permissions(accounts_iter.as_slice(), &instructions)?;
         let (is_approved, is_final) = {
             const NOT_FINAL: u32 = WalletError::TransferDispositionNotFinal as u32;
             match multisig_op.approved(multisig_data.hash(&multisig_op)?, &clock, Some(params_hash))
@@ -286,8 +346,15 @@ pub fn finalize(
             program_id,
         )?;
 
-        if *rent_return_account_info.key != multisig_op.rent_return {
-            return Err(WalletError::IncorrectRentReturnAccount.into());
+        validate_rent_return(wallet_account_info, &multisig_op, rent_return_account_info)?;
+        // simulations are rolled back, so only final submissions are subject to the policy
+        if is_final {
+            validate_finalize_submitter(
+                wallet_account_info,
+                &multisig_op,
+                FinalizeCategory::DApp,
+                rent_return_account_info.key,
+            )?;
         }
 
         let starting_balances: Vec<u64> = if is_final {
@@ -308,11 +375,11 @@ pub fn finalize(
                 invoke_signed(
                     &instruction,
                     &accounts,
-                    &[&[
-                        wallet_guid_hash.to_bytes(),
-                        account_guid_hash.to_bytes(),
-                        &[bump_seed],
-                    ]],
+                    &[&BalanceAccountSeeds {
+                        wallet_guid_hash,
+                        account_guid_hash,
+                    }
+                    .signer_seeds(&[bump_seed])],
                 )?;
             }
         }
@@ -343,6 +410,34 @@ pub fn finalize(
     }
 }
 
+/// Each account the supplied instructions reference has to be passed with the same writable and
+/// signer flags they give it, so a submitter can't hand the approved instructions more than the
+/// approvers reviewed.
+fn validate_account_permissions(
+    account_infos: &[AccountInfo],
+    instructions: &Vec<Instruction>,
+) -> ProgramResult {
+    let account_metas = unique_account_metas(instructions, &Vec::new());
+    for account_info in account_infos {
+        if let Some(meta) = account_metas
+            .iter()
+            .find(|it| it.pubkey == *account_info.key)
+        {
+            if meta.is_writable != account_info.is_writable
+                || meta.is_signer != account_info.is_signer
+            {
+                return Err(strike_err!(
+                    "dapp_transaction_handler::finalize",
+                    WalletError::DAppAccountPermissionMismatch,
+                    "Account {} is passed with different permissions than supplied",
+                    account_info.key
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
 fn cleanup(
     multisig_op_account_info: &AccountInfo,
     multisig_data_account_info: &AccountInfo,
@@ -0,0 +1,74 @@
+use crate::handlers::utils::{
+    next_program_account_info, next_signer_account_info, unpack_uninitialized,
+    validate_balance_account_and_get_seed,
+};
+use crate::handlers::wallet_registry_handler;
+use crate::instruction::{DemoBalanceAccount, InitialWalletConfig};
+use crate::model::wallet::{Wallet, WalletGuidHash};
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program::invoke;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_instruction;
+
+pub fn handle(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    wallet_guid_hash: &WalletGuidHash,
+    initial_config: &InitialWalletConfig,
+    balance_accounts: &Vec<DemoBalanceAccount>,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let wallet_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let assistant_account_info = next_account_info(accounts_iter)?;
+    let rent_return_account_info = next_signer_account_info(accounts_iter)?;
+    let system_program_account_info = next_account_info(accounts_iter)?;
+
+    let mut wallet: Wallet = unpack_uninitialized(wallet_account_info)?;
+
+    wallet.init(
+        wallet_guid_hash,
+        *assistant_account_info.key,
+        *rent_return_account_info.key,
+        initial_config,
+    )?;
+
+    for demo_account in balance_accounts {
+        wallet.create_demo_balance_account(demo_account, program_id)?;
+
+        let balance_account_info = next_account_info(accounts_iter)?;
+        validate_balance_account_and_get_seed(
+            balance_account_info,
+            wallet_guid_hash,
+            &demo_account.guid_hash,
+            program_id,
+        )?;
+        if demo_account.lamports > 0 {
+            invoke(
+                &system_instruction::transfer(
+                    rent_return_account_info.key,
+                    balance_account_info.key,
+                    demo_account.lamports,
+                ),
+                &[
+                    rent_return_account_info.clone(),
+                    balance_account_info.clone(),
+                    system_program_account_info.clone(),
+                ],
+            )?;
+        }
+    }
+
+    Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+
+    wallet_registry_handler::register(
+        program_id,
+        accounts,
+        rent_return_account_info,
+        wallet_guid_hash,
+        wallet_account_info.key,
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,338 @@
+use crate::error::{strike_err, WalletError};
+use crate::handlers::utils::{
+    finalize_multisig_op, get_clock_from_next_account, next_initiator_account_info,
+    next_program_account_info, next_signer_account_info, next_wallet_account_info,
+    start_multisig_config_op, token_program_of, transfer_checked_instruction,
+    validate_balance_account_and_get_seed, FeeCollectionInfo,
+};
+use crate::instruction::ProgramInstruction;
+use crate::model::balance_account::{BalanceAccount, BalanceAccountGuidHash};
+use crate::model::multisig_op::{BooleanSetting, MultisigOpParams};
+use crate::model::wallet::Wallet;
+use crate::pda::{self, DepositAddressSeeds, PdaSeeds};
+use crate::utils::associated_token_address;
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program::invoke_signed;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::Sysvar;
+use solana_program::{system_instruction, system_program};
+use spl_token_2022::extension::StateWithExtensions;
+use spl_token_2022::state::Account as TokenAccount;
+
+pub fn init(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    fee_amount: u64,
+    fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+    account_guid_hash: &BalanceAccountGuidHash,
+    enabled: BooleanSetting,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let initiator_account_info =
+        next_initiator_account_info(accounts_iter, multisig_op_account_info, wallet_account_info)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+    let rent_return_account_info = next_signer_account_info(accounts_iter)?;
+
+    let mut wallet: Wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+
+    wallet.get_active_balance_account(account_guid_hash)?;
+    wallet.validate_config_initiator(initiator_account_info, clock.unix_timestamp)?;
+
+    start_multisig_config_op(
+        multisig_op_account_info,
+        wallet_account_info,
+        &mut wallet,
+        clock,
+        MultisigOpParams::EnableDepositAddresses {
+            wallet_address: *wallet_account_info.key,
+            account_guid_hash: *account_guid_hash,
+            enabled,
+        },
+        *initiator_account_info.key,
+        *rent_return_account_info.key,
+        fee_amount,
+        fee_account_guid_hash,
+    )?;
+
+    Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+pub fn finalize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    account_guid_hash: &BalanceAccountGuidHash,
+    enabled: BooleanSetting,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let rent_return_account_info = next_signer_account_info(accounts_iter)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+    let fee_account_info_maybe = accounts_iter.next();
+
+    let wallet_guid_hash =
+        &Wallet::wallet_guid_hash_from_slice(&wallet_account_info.data.borrow())?;
+
+    finalize_multisig_op(
+        multisig_op_account_info,
+        wallet_account_info,
+        FeeCollectionInfo {
+            rent_return_account_info,
+            fee_account_info_maybe,
+            wallet_guid_hash,
+            program_id,
+        },
+        clock,
+        MultisigOpParams::EnableDepositAddresses {
+            wallet_address: *wallet_account_info.key,
+            account_guid_hash: *account_guid_hash,
+            enabled,
+        },
+        || -> ProgramResult {
+            let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+            wallet.update_deposit_addresses(account_guid_hash, enabled)?;
+            Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+            Ok(())
+        },
+        || -> ProgramResult { Ok(()) },
+    )
+}
+
+/// The balance account the wrapped finalize moves funds out of, which the deposit addresses
+/// are swept into, and the token mint it moves (the default pubkey for SOL).
+pub struct SweptFinalize {
+    pub account_guid_hash: BalanceAccountGuidHash,
+    pub token_mint: Pubkey,
+}
+
+impl SweptFinalize {
+    fn is_spl(&self) -> bool {
+        self.token_mint != Pubkey::default()
+    }
+}
+
+/// Checks that the wrapped instruction finalizes a transfer or a wrap, returning what it moves
+/// out of which balance account.
+pub fn validate_finalize(finalize: &[u8]) -> Result<SweptFinalize, ProgramError> {
+    match ProgramInstruction::unpack(finalize)? {
+        ProgramInstruction::FinalizeTransfer {
+            account_guid_hash,
+            token_mint,
+            ..
+        }
+        | ProgramInstruction::ExecuteTransfer {
+            account_guid_hash,
+            token_mint,
+            ..
+        } => Ok(SweptFinalize {
+            account_guid_hash,
+            token_mint,
+        }),
+        ProgramInstruction::FinalizeWrapUnwrap {
+            account_guid_hash, ..
+        } => Ok(SweptFinalize {
+            account_guid_hash,
+            token_mint: Pubkey::default(),
+        }),
+        _ => Err(strike_err!(
+            "deposit_address_handler::validate_finalize",
+            ProgramError::InvalidInstructionData,
+            "Only transfer and wrap finalizes can sweep deposit addresses"
+        )),
+    }
+}
+
+/// Splits the accounts into those of the wrapped finalize and the deposit accounts, one per
+/// index, or two for an SPL transfer.
+pub fn split_accounts<'a, 'b>(
+    accounts: &'a [AccountInfo<'b>],
+    swept: &SweptFinalize,
+    index_count: usize,
+) -> Result<(&'a [AccountInfo<'b>], &'a [AccountInfo<'b>]), ProgramError> {
+    let deposit_accounts_len = if swept.is_spl() {
+        2 * index_count
+    } else {
+        index_count
+    };
+    let finalize_len = accounts
+        .len()
+        .checked_sub(deposit_accounts_len)
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+    Ok(accounts.split_at(finalize_len))
+}
+
+fn find_account<'a, 'b>(
+    accounts: &'a [AccountInfo<'b>],
+    key: &Pubkey,
+) -> Result<&'a AccountInfo<'b>, ProgramError> {
+    accounts
+        .iter()
+        .find(|account_info| account_info.key == key)
+        .ok_or(ProgramError::NotEnoughAccountKeys)
+}
+
+/// Sweeps what the deposit addresses with the given indices hold of what the wrapped finalize
+/// moves into its balance account, the third of its accounts.
+pub fn sweep<'a>(
+    program_id: &Pubkey,
+    finalize_accounts: &[AccountInfo<'a>],
+    deposit_accounts: &[AccountInfo<'a>],
+    deposit_address_indices: &[u8],
+    swept: &SweptFinalize,
+) -> ProgramResult {
+    let accounts_iter = &mut finalize_accounts.iter();
+    next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let balance_account_info = accounts_iter
+        .next()
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    validate_balance_account_and_get_seed(
+        balance_account_info,
+        &wallet.wallet_guid_hash,
+        &swept.account_guid_hash,
+        program_id,
+    )?;
+    if !wallet
+        .get_balance_account(&swept.account_guid_hash)?
+        .are_deposit_addresses_enabled()
+    {
+        return Err(WalletError::DepositAddressesNotEnabled.into());
+    }
+
+    let per_index = if swept.is_spl() { 2 } else { 1 };
+    for (index, accounts) in deposit_address_indices
+        .iter()
+        .zip(deposit_accounts.chunks_exact(per_index))
+    {
+        if usize::from(*index) >= BalanceAccount::MAX_DEPOSIT_ADDRESSES {
+            return Err(strike_err!(
+                "deposit_address_handler::sweep",
+                ProgramError::InvalidArgument,
+                "Deposit address index {} is out of range",
+                index
+            ));
+        }
+        let seeds = DepositAddressSeeds {
+            wallet_guid_hash: &wallet.wallet_guid_hash,
+            account_guid_hash: &swept.account_guid_hash,
+            index: *index,
+        };
+        let deposit_address_info = &accounts[0];
+        let bump_seed = pda::verify(program_id, &seeds, deposit_address_info.key, None)?;
+        if swept.is_spl() {
+            sweep_tokens(
+                finalize_accounts,
+                balance_account_info,
+                deposit_address_info,
+                &accounts[1],
+                &swept.token_mint,
+                &seeds.signer_seeds(&[bump_seed]),
+            )?;
+        } else {
+            sweep_lamports(
+                finalize_accounts,
+                balance_account_info,
+                deposit_address_info,
+                &seeds.signer_seeds(&[bump_seed]),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn sweep_lamports<'a>(
+    finalize_accounts: &[AccountInfo<'a>],
+    balance_account_info: &AccountInfo<'a>,
+    deposit_address_info: &AccountInfo<'a>,
+    signer_seeds: &[&[u8]],
+) -> ProgramResult {
+    let lamports = deposit_address_info.lamports();
+    if lamports == 0 {
+        return Ok(());
+    }
+    let system_program_account = find_account(finalize_accounts, &system_program::id())?;
+    invoke_signed(
+        &system_instruction::transfer(deposit_address_info.key, balance_account_info.key, lamports),
+        &[
+            deposit_address_info.clone(),
+            balance_account_info.clone(),
+            system_program_account.clone(),
+        ],
+        &[signer_seeds],
+    )?;
+    msg!(
+        "Swept {} lamports from deposit address {}",
+        lamports,
+        deposit_address_info.key
+    );
+    Ok(())
+}
+
+fn sweep_tokens<'a>(
+    finalize_accounts: &[AccountInfo<'a>],
+    balance_account_info: &AccountInfo<'a>,
+    deposit_address_info: &AccountInfo<'a>,
+    deposit_token_account_info: &AccountInfo<'a>,
+    token_mint: &Pubkey,
+    signer_seeds: &[&[u8]],
+) -> ProgramResult {
+    let token_mint_account_info = find_account(finalize_accounts, token_mint)?;
+    let token_program_id = token_program_of(token_mint_account_info)?;
+    if *deposit_token_account_info.key
+        != associated_token_address(deposit_address_info.key, token_mint, &token_program_id)
+    {
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+    if *deposit_token_account_info.owner != token_program_id {
+        // nothing was ever deposited there
+        return Ok(());
+    }
+    let amount =
+        StateWithExtensions::<TokenAccount>::unpack(&deposit_token_account_info.data.borrow())?
+            .base
+            .amount;
+    if amount == 0 {
+        return Ok(());
+    }
+    let balance_token_account_info = find_account(
+        finalize_accounts,
+        &associated_token_address(balance_account_info.key, token_mint, &token_program_id),
+    )?;
+    let token_program_account_info = find_account(finalize_accounts, &token_program_id)?;
+    let (transfer_instruction, _) = transfer_checked_instruction(
+        token_mint_account_info,
+        deposit_token_account_info.key,
+        balance_token_account_info.key,
+        deposit_address_info.key,
+        amount,
+        Clock::get()?.epoch,
+    )?;
+    invoke_signed(
+        &transfer_instruction,
+        &[
+            deposit_token_account_info.clone(),
+            balance_token_account_info.clone(),
+            deposit_address_info.clone(),
+            token_mint_account_info.clone(),
+            token_program_account_info.clone(),
+        ],
+        &[signer_seeds],
+    )?;
+    msg!(
+        "Swept {} tokens from deposit address {}",
+        amount,
+        deposit_address_info.key
+    );
+    Ok(())
+}
@@ -1,12 +1,12 @@
-use crate::handlers::utils::{next_program_account_info, next_signer_account_info};
+use crate::handlers::utils::{
+    next_program_account_info, next_signer_account_info, unpack_uninitialized,
+};
+use crate::handlers::{program_parameters_handler, wallet_registry_handler};
 use crate::instruction::InitialWalletConfig;
-use crate::model::signer::Signer;
 use crate::model::wallet::{Wallet, WalletGuidHash};
-use crate::version::VERSION;
 use solana_program::account_info::{next_account_info, AccountInfo};
 use solana_program::entrypoint::ProgramResult;
-use solana_program::program_error::ProgramError;
-use solana_program::program_pack::{IsInitialized, Pack};
+use solana_program::program_pack::Pack;
 use solana_program::pubkey::Pubkey;
 
 pub fn handle(
@@ -20,21 +20,26 @@ pub fn handle(
     let assistant_account_info = next_account_info(accounts_iter)?;
     let rent_return_account_info = next_signer_account_info(accounts_iter)?;
 
-    let mut wallet = Wallet::unpack_unchecked(&wallet_account_info.data.borrow())?;
+    let mut wallet: Wallet = unpack_uninitialized(wallet_account_info)?;
 
-    if wallet.is_initialized() {
-        return Err(ProgramError::AccountAlreadyInitialized);
-    }
-
-    wallet.is_initialized = true;
-    wallet.version = VERSION;
-    wallet.rent_return = *rent_return_account_info.key;
-    wallet.wallet_guid_hash = *wallet_guid_hash;
-    wallet.assistant = Signer {
-        key: *assistant_account_info.key,
-    };
-    wallet.initialize(initial_config)?;
+    wallet.init(
+        wallet_guid_hash,
+        *assistant_account_info.key,
+        *rent_return_account_info.key,
+        initial_config,
+    )?;
+    let parameters = program_parameters_handler::load(program_id, accounts)?;
+    parameters.validate_signer_count(wallet.signers.filled_slots().len())?;
+    parameters.validate_approval_timeout(&wallet.approval_timeout_for_config)?;
     Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
 
+    wallet_registry_handler::register(
+        program_id,
+        accounts,
+        rent_return_account_info,
+        wallet_guid_hash,
+        wallet_account_info.key,
+    )?;
+
     Ok(())
 }
@@ -0,0 +1,39 @@
+use crate::error::strike_err;
+use crate::handlers::utils::next_program_account_info;
+use crate::instruction::ProgramInstruction;
+use crate::model::multisig_op::MultisigOp;
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+
+/// Checks that the wrapped instruction starts a multisig op, either directly or wrapped in an
+/// `InitWithSignature`.
+pub fn validate_init(init: &[u8]) -> ProgramResult {
+    match ProgramInstruction::unpack(init)? {
+        ProgramInstruction::InitWithSignature { init } => validate_init(&init),
+        instruction
+            if instruction.is_init()
+                && !matches!(instruction, ProgramInstruction::InitOpBundle { .. }) =>
+        {
+            Ok(())
+        }
+        _ => Err(strike_err!(
+            "init_with_compute_hint_handler::validate_init",
+            ProgramError::InvalidInstructionData,
+            "Only init instructions starting a multisig op can be given a compute hint"
+        )),
+    }
+}
+
+/// Records the compute hint in the op the wrapped instruction just started.
+pub fn record_hint(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    finalize_compute_units: u32,
+) -> ProgramResult {
+    let multisig_op_account_info = next_program_account_info(&mut accounts.iter(), program_id)?;
+    let mut multisig_op = MultisigOp::unpack_sized(&multisig_op_account_info.data.borrow())?;
+    multisig_op.finalize_compute_units = Some(finalize_compute_units).filter(|units| *units > 0);
+    MultisigOp::pack_sized(multisig_op, &mut multisig_op_account_info.data.borrow_mut())
+}
@@ -0,0 +1,120 @@
+use crate::error::{strike_err, WalletError};
+use crate::instruction::{init_signature_message, ProgramInstruction};
+use arrayref::{array_ref, array_refs};
+use solana_program::account_info::AccountInfo;
+use solana_program::ed25519_program;
+use solana_program::hash::Hash;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
+use solana_program::sysvar::instructions::get_instruction_relative;
+
+const SIGNATURE_OFFSETS_START: usize = 2;
+const SIGNATURE_OFFSETS_LEN: usize = 14;
+
+/// Authenticates the initiator of the wrapped init instruction by the ed25519 instruction before
+/// this one, returning the wrapped instruction's accounts with the initiator marked as a signer.
+pub fn authenticate_initiator<'a>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo<'a>],
+    init: &[u8],
+) -> Result<Vec<AccountInfo<'a>>, ProgramError> {
+    let (instructions_sysvar_account_info, init_accounts) = accounts
+        .split_last()
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+    if !ProgramInstruction::unpack(init)?.is_init() {
+        return Err(strike_err!(
+            "init_with_signature_handler::authenticate_initiator",
+            ProgramError::InvalidInstructionData,
+            "Only init instructions can be initiated with a signature"
+        ));
+    }
+
+    let message = init_signature_message(
+        program_id,
+        &init_accounts
+            .iter()
+            .map(|account_info| *account_info.key)
+            .collect::<Vec<_>>(),
+        init,
+    );
+    let initiator = verified_signer(
+        instructions_sysvar_account_info,
+        &message,
+        WalletError::InvalidInitSignature,
+    )?;
+
+    let mut init_accounts = init_accounts.to_vec();
+    let mut found = false;
+    for account_info in init_accounts.iter_mut() {
+        if *account_info.key == initiator {
+            account_info.is_signer = true;
+            found = true;
+        }
+    }
+    if !found {
+        return Err(strike_err!(
+            "init_with_signature_handler::authenticate_initiator",
+            WalletError::InvalidInitSignature,
+            "Signer {} is not an account of the init",
+            initiator
+        ));
+    }
+    Ok(init_accounts)
+}
+
+/// The key whose signature of the message the ed25519 instruction before this one verified. The
+/// runtime fails the transaction if that verification fails, so only its data needs checking.
+/// Fails with `error` if there is no such instruction or it verifies another message.
+pub(crate) fn verified_signer(
+    instructions_sysvar_account_info: &AccountInfo,
+    message: &Hash,
+    error: WalletError,
+) -> Result<Pubkey, ProgramError> {
+    let instruction = get_instruction_relative(-1, instructions_sysvar_account_info)?;
+    let data = &instruction.data;
+    if instruction.program_id != ed25519_program::id()
+        || data.first() != Some(&1)
+        || data.len() < SIGNATURE_OFFSETS_START + SIGNATURE_OFFSETS_LEN
+    {
+        return Err(strike_err!(
+            "init_with_signature_handler::verified_signer",
+            error,
+            "Expected an ed25519 instruction verifying a single signature"
+        ));
+    }
+
+    let offsets = array_ref![data, SIGNATURE_OFFSETS_START, SIGNATURE_OFFSETS_LEN];
+    let (
+        _signature_offset,
+        signature_instruction_index,
+        public_key_offset,
+        public_key_instruction_index,
+        message_offset,
+        message_size,
+        message_instruction_index,
+    ) = array_refs![offsets, 2, 2, 2, 2, 2, 2, 2];
+    // the key and message have to be the ones in the ed25519 instruction itself, rather than in
+    // another instruction that could say anything
+    let in_own_data = [
+        signature_instruction_index,
+        public_key_instruction_index,
+        message_instruction_index,
+    ]
+    .iter()
+    .all(|index| u16::from_le_bytes(**index) == u16::MAX);
+    let public_key_offset = usize::from(u16::from_le_bytes(*public_key_offset));
+    let message_offset = usize::from(u16::from_le_bytes(*message_offset));
+    let message_size = usize::from(u16::from_le_bytes(*message_size));
+    let public_key = data.get(public_key_offset..public_key_offset + PUBKEY_BYTES);
+    let signed_message = data.get(message_offset..message_offset + message_size);
+    match public_key {
+        Some(public_key) if in_own_data && signed_message == Some(message.as_ref()) => {
+            Ok(Pubkey::new(public_key))
+        }
+        _ => Err(strike_err!(
+            "init_with_signature_handler::verified_signer",
+            error,
+            "The ed25519 instruction doesn't verify a signature of the message"
+        )),
+    }
+}
@@ -0,0 +1,114 @@
+use crate::error::{strike_err, WalletError};
+use crate::handlers::utils::{next_program_account_info, token_program_of};
+use crate::instruction::ProgramInstruction;
+use crate::model::multisig_op::MultisigOp;
+use crate::utils::associated_token_address;
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::Sysvar;
+use spl_token_2022::extension::StateWithExtensions;
+use spl_token_2022::state::{Account as TokenAccount, Mint};
+
+/// Checks that the wrapped instruction starts a multisig op, either directly or wrapped in an
+/// `InitWithSignature` or `InitWithComputeHint`.
+pub fn validate_init(init: &[u8]) -> ProgramResult {
+    match ProgramInstruction::unpack(init)? {
+        ProgramInstruction::InitWithSignature { init }
+        | ProgramInstruction::InitWithComputeHint { init, .. } => validate_init(&init),
+        instruction
+            if instruction.is_init()
+                && !matches!(instruction, ProgramInstruction::InitOpBundle { .. }) =>
+        {
+            Ok(())
+        }
+        _ => Err(strike_err!(
+            "init_with_stake_weights_handler::validate_init",
+            ProgramError::InvalidInstructionData,
+            "Only init instructions starting a multisig op can be given stake weights"
+        )),
+    }
+}
+
+/// Splits the accounts into those of the wrapped init and the governance mint followed by the
+/// stake accounts.
+pub fn split_accounts<'a, 'b>(
+    accounts: &'a [AccountInfo<'b>],
+    stake_account_count: u8,
+) -> Result<(&'a [AccountInfo<'b>], &'a [AccountInfo<'b>]), ProgramError> {
+    let snapshot_len = 1 + usize::from(stake_account_count);
+    let init_len = accounts
+        .len()
+        .checked_sub(snapshot_len)
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+    Ok(accounts.split_at(init_len))
+}
+
+/// Snapshots the governance token holdings of the stake accounts into the op the wrapped
+/// instruction just started, as the weights of its approvers.
+pub fn record_stake_weights(
+    program_id: &Pubkey,
+    init_accounts: &[AccountInfo],
+    snapshot_accounts: &[AccountInfo],
+) -> ProgramResult {
+    let multisig_op_account_info =
+        next_program_account_info(&mut init_accounts.iter(), program_id)?;
+    let mut multisig_op = MultisigOp::unpack_sized(&multisig_op_account_info.data.borrow())?;
+    let governance = multisig_op
+        .stake_weight_governance
+        .ok_or(WalletError::StakeWeightsNotExpected)?;
+
+    let (mint_account_info, stake_account_infos) = snapshot_accounts
+        .split_first()
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+    if *mint_account_info.key != governance.mint {
+        return Err(strike_err!(
+            "init_with_stake_weights_handler::record_stake_weights",
+            WalletError::InvalidStakeAccount,
+            "Account {} isn't the governance mint",
+            mint_account_info.key
+        ));
+    }
+    let token_program_id = token_program_of(mint_account_info)?;
+    let supply = StateWithExtensions::<Mint>::unpack(&mint_account_info.data.borrow())?
+        .base
+        .supply;
+
+    let weights = stake_account_infos
+        .iter()
+        .map(|stake_account_info| {
+            if *stake_account_info.owner != token_program_id {
+                return Err(strike_err!(
+                    "init_with_stake_weights_handler::record_stake_weights",
+                    WalletError::InvalidStakeAccount,
+                    "Stake account {} isn't owned by the governance mint's token program",
+                    stake_account_info.key
+                ));
+            }
+            let stake_account =
+                StateWithExtensions::<TokenAccount>::unpack(&stake_account_info.data.borrow())?
+                    .base;
+            // one account per holder, so that no stake is counted twice
+            if *stake_account_info.key
+                != associated_token_address(
+                    &stake_account.owner,
+                    &governance.mint,
+                    &token_program_id,
+                )
+            {
+                return Err(strike_err!(
+                    "init_with_stake_weights_handler::record_stake_weights",
+                    WalletError::InvalidStakeAccount,
+                    "Stake account {} isn't its owner's associated token account of the governance mint",
+                    stake_account_info.key
+                ));
+            }
+            Ok((stake_account.owner, stake_account.amount))
+        })
+        .collect::<Result<Vec<_>, ProgramError>>()?;
+
+    multisig_op.record_stake_weights(weights, supply, Clock::get()?.unix_timestamp)?;
+    MultisigOp::pack_sized(multisig_op, &mut multisig_op_account_info.data.borrow_mut())
+}
@@ -0,0 +1,177 @@
+use crate::error::{strike_err, WalletError};
+use crate::handlers::utils::{
+    create_pda_account, finalize_multisig_op, get_clock_from_next_account,
+    next_initiator_account_info, next_program_account_info, next_signer_account_info,
+    next_wallet_account_info, start_multisig_config_op, FeeCollectionInfo,
+};
+use crate::model::balance_account::BalanceAccountGuidHash;
+use crate::model::insurance_attestation::InsuranceAttestation;
+use crate::model::multisig_op::MultisigOpParams;
+use crate::model::wallet::Wallet;
+use crate::model::wallet_event_log::{WalletEventKind, WalletEventLog};
+use crate::pda::{self, InsuranceAttestationSeeds};
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::Hash;
+use solana_program::msg;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::Sysvar;
+
+pub fn init(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    fee_amount: u64,
+    fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+    attestor: &Pubkey,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let initiator_account_info =
+        next_initiator_account_info(accounts_iter, multisig_op_account_info, wallet_account_info)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+    let rent_return_account_info = next_signer_account_info(accounts_iter)?;
+
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    wallet.validate_config_initiator(initiator_account_info, clock.unix_timestamp)?;
+    if *attestor == Pubkey::default() {
+        return Err(strike_err!(
+            "insurance_attestation_handler::init",
+            WalletError::InvalidInsuranceAttestor,
+            "An insurance attestor needs a key"
+        ));
+    }
+
+    start_multisig_config_op(
+        multisig_op_account_info,
+        wallet_account_info,
+        &mut wallet,
+        clock,
+        MultisigOpParams::UpdateInsuranceAttestor {
+            wallet_address: *wallet_account_info.key,
+            attestor: *attestor,
+        },
+        *initiator_account_info.key,
+        *rent_return_account_info.key,
+        fee_amount,
+        fee_account_guid_hash,
+    )
+}
+
+pub fn finalize(program_id: &Pubkey, accounts: &[AccountInfo], attestor: &Pubkey) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let attestation_account_info = next_account_info(accounts_iter)?;
+    let rent_return_account_info = next_signer_account_info(accounts_iter)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+    let system_program_account = next_account_info(accounts_iter)?;
+    let fee_account_info_maybe = accounts_iter.next();
+
+    let wallet_guid_hash =
+        &Wallet::wallet_guid_hash_from_slice(&wallet_account_info.data.borrow())?;
+
+    let seeds = InsuranceAttestationSeeds {
+        wallet: wallet_account_info.key,
+    };
+    let bump_seed = pda::verify(program_id, &seeds, attestation_account_info.key, None)?;
+
+    finalize_multisig_op(
+        multisig_op_account_info,
+        wallet_account_info,
+        FeeCollectionInfo {
+            rent_return_account_info,
+            fee_account_info_maybe,
+            wallet_guid_hash,
+            program_id,
+        },
+        clock,
+        MultisigOpParams::UpdateInsuranceAttestor {
+            wallet_address: *wallet_account_info.key,
+            attestor: *attestor,
+        },
+        || -> ProgramResult {
+            let attestation = if attestation_account_info.owner == program_id {
+                InsuranceAttestation {
+                    attestor: *attestor,
+                    ..InsuranceAttestation::unpack(&attestation_account_info.data.borrow())?
+                }
+            } else {
+                create_pda_account(
+                    rent_return_account_info,
+                    attestation_account_info,
+                    system_program_account,
+                    InsuranceAttestation::LEN,
+                    program_id,
+                    &seeds,
+                    bump_seed,
+                )?;
+                InsuranceAttestation::new(*wallet_account_info.key, *attestor)
+            };
+            InsuranceAttestation::pack(attestation, &mut attestation_account_info.data.borrow_mut())
+        },
+        || -> ProgramResult { Ok(()) },
+    )
+}
+
+pub fn submit(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    statement_hash: &Hash,
+    coverage_expires_at: i64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let attestation_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let attestor_account_info = next_signer_account_info(accounts_iter)?;
+
+    let mut attestation = InsuranceAttestation::unpack(&attestation_account_info.data.borrow())?;
+    attestation.attest(
+        attestor_account_info.key,
+        statement_hash,
+        coverage_expires_at,
+        Clock::get()?.unix_timestamp,
+    )?;
+    msg!(
+        "Insurance attestation {} covers wallet {} until {}",
+        statement_hash,
+        attestation.wallet,
+        coverage_expires_at
+    );
+    InsuranceAttestation::pack(attestation, &mut attestation_account_info.data.borrow_mut())
+}
+
+pub fn check(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let attestation_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let event_log_account_info = next_program_account_info(accounts_iter, program_id)?;
+
+    let mut attestation = InsuranceAttestation::unpack(&attestation_account_info.data.borrow())?;
+    let mut event_log_data = event_log_account_info.data.borrow_mut();
+    if WalletEventLog::wallet_from_slice(&event_log_data)? != attestation.wallet {
+        return Err(strike_err!(
+            "insurance_attestation_handler::check",
+            WalletError::InvalidEventLog,
+            "Event log isn't the log of the attestation's wallet"
+        ));
+    }
+
+    let clock = Clock::get()?;
+    if !attestation.report_lapse(clock.unix_timestamp) {
+        return Ok(());
+    }
+    msg!(
+        "Insurance coverage of wallet {} lapsed at {}",
+        attestation.wallet,
+        attestation.coverage_expires_at
+    );
+    WalletEventLog::append_to_slice(
+        &mut event_log_data,
+        WalletEventKind::InsuranceAttestationLapsed,
+        *attestation_account_info.key,
+        clock.slot,
+        clock.unix_timestamp,
+    )?;
+    InsuranceAttestation::pack(attestation, &mut attestation_account_info.data.borrow_mut())
+}
@@ -0,0 +1,144 @@
+use crate::error::{strike_err, WalletError};
+use crate::handlers::utils::{
+    get_clock_from_next_account, next_program_account_info, next_signer_account_info,
+    next_wallet_account_info, validate_balance_account_and_get_seed, validate_rent_return,
+};
+use crate::model::balance_account::BalanceAccountGuidHash;
+use crate::model::internal_transfer_handshake::InternalTransferHandshake;
+use crate::model::multisig_op::{MultisigOp, MultisigOpCode, MultisigOpState};
+use crate::model::wallet::Wallet;
+use crate::pda::{self, InternalTransferHandshakeSeeds, PdaSeeds};
+use crate::version::{Versioned, VERSION};
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::Hash;
+use solana_program::program::invoke_signed;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::system_instruction;
+use solana_program::sysvar::Sysvar;
+
+pub fn handle(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    account_guid_hash: &BalanceAccountGuidHash,
+    params_hash: &Hash,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let handshake_account_info = next_account_info(accounts_iter)?;
+    let source_wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let destination_wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let destination_account = next_account_info(accounts_iter)?;
+    let acknowledger_account_info = next_signer_account_info(accounts_iter)?;
+    let rent_return_account_info = next_signer_account_info(accounts_iter)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+    let system_program_account = next_account_info(accounts_iter)?;
+
+    if MultisigOp::version_from_slice(&multisig_op_account_info.data.borrow())? != VERSION {
+        return Err(WalletError::OperationVersionMismatch.into());
+    }
+
+    let multisig_op = MultisigOp::unpack_sized(&multisig_op_account_info.data.borrow())?;
+
+    if multisig_op.state == MultisigOpState::Denied || multisig_op.is_expired(&clock) {
+        return Err(strike_err!(
+            "internal_transfer_acknowledgement_handler::handle",
+            WalletError::InvalidDisposition,
+            "Operation can no longer be acknowledged"
+        ));
+    }
+
+    validate_rent_return(
+        source_wallet_account_info,
+        &multisig_op,
+        rent_return_account_info,
+    )?;
+
+    // the acknowledger acknowledges the params they were shown, which the transfer's
+    // finalize checks against its own along with the destination the handshake is for
+    if multisig_op.op_code != Some(MultisigOpCode::Transfer)
+        || multisig_op.wallet != *source_wallet_account_info.key
+    {
+        return Err(strike_err!(
+            "internal_transfer_acknowledgement_handler::handle",
+            WalletError::OperationParamsMismatch,
+            "Operation is not a transfer from the source wallet"
+        ));
+    }
+    if multisig_op.params_hash != Some(*params_hash) {
+        return Err(strike_err!(
+            "internal_transfer_acknowledgement_handler::handle",
+            WalletError::OperationParamsMismatch,
+            "Operation's params hash is not {}",
+            params_hash
+        ));
+    }
+
+    let seeds = InternalTransferHandshakeSeeds {
+        multisig_op: multisig_op_account_info.key,
+        destination: destination_account.key,
+    };
+    let bump_seed = pda::verify(program_id, &seeds, handshake_account_info.key, None)?;
+
+    if !handshake_account_info.data_is_empty() {
+        return Err(WalletError::AccountAlreadyInitialized.into());
+    }
+
+    let destination_wallet = Wallet::unpack(&destination_wallet_account_info.data.borrow())?;
+    let destination_balance_account = destination_wallet.get_balance_account(account_guid_hash)?;
+
+    validate_balance_account_and_get_seed(
+        destination_account,
+        &destination_wallet.wallet_guid_hash,
+        account_guid_hash,
+        program_id,
+    )?;
+
+    if !destination_wallet
+        .get_active_config_approvers_keys(clock.unix_timestamp)
+        .contains(acknowledger_account_info.key)
+        && !destination_wallet
+            .get_active_transfer_approvers_keys(&destination_balance_account, clock.unix_timestamp)
+            .contains(acknowledger_account_info.key)
+    {
+        return Err(strike_err!(
+            "internal_transfer_acknowledgement_handler::handle",
+            WalletError::InvalidApprover,
+            "Acknowledger is not an approver in the destination wallet"
+        ));
+    }
+
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            rent_return_account_info.key,
+            handshake_account_info.key,
+            rent.minimum_balance(InternalTransferHandshake::LEN),
+            InternalTransferHandshake::LEN as u64,
+            program_id,
+        ),
+        &[
+            rent_return_account_info.clone(),
+            handshake_account_info.clone(),
+            system_program_account.clone(),
+        ],
+        &[&seeds.signer_seeds(&[bump_seed])],
+    )?;
+
+    InternalTransferHandshake::pack(
+        InternalTransferHandshake {
+            is_initialized: true,
+            multisig_op: *multisig_op_account_info.key,
+            destination_wallet: *destination_wallet_account_info.key,
+            destination_account_guid_hash: *account_guid_hash,
+            destination: *destination_account.key,
+            acknowledged_by: *acknowledger_account_info.key,
+            params_hash: *params_hash,
+        },
+        &mut handshake_account_info.data.borrow_mut(),
+    )?;
+
+    Ok(())
+}
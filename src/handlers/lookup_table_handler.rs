@@ -0,0 +1,172 @@
+use crate::error::{strike_err, WalletError};
+use crate::handlers::utils::{
+    finalize_multisig_op, get_clock_from_next_account, next_initiator_account_info,
+    next_program_account_info, next_signer_account_info, next_wallet_account_info,
+    start_multisig_config_op, FeeCollectionInfo,
+};
+use crate::lookup_table::{
+    create_lookup_table_instruction, extend_lookup_table_instruction, find_authority_address,
+    find_lookup_table_address, MAX_NEW_ADDRESSES,
+};
+use crate::model::balance_account::BalanceAccountGuidHash;
+use crate::model::multisig_op::MultisigOpParams;
+use crate::model::wallet::Wallet;
+use crate::pda::{LookupTableAuthoritySeeds, PdaSeeds};
+use itertools::Itertools;
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program::invoke_signed;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+
+pub fn init(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    fee_amount: u64,
+    fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+    lookup_table: Option<Pubkey>,
+    new_addresses: &[Pubkey],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let initiator_account_info =
+        next_initiator_account_info(accounts_iter, multisig_op_account_info, wallet_account_info)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+    let rent_return_account = next_signer_account_info(accounts_iter)?;
+
+    if new_addresses.len() > MAX_NEW_ADDRESSES
+        || new_addresses.iter().unique().count() != new_addresses.len()
+        || (lookup_table.is_some() && new_addresses.is_empty())
+    {
+        return Err(strike_err!(
+            "lookup_table_handler::init",
+            WalletError::InvalidLookupTableUpdate,
+            "Lookup table updates add up to {} distinct addresses, and at least one to an existing table",
+            MAX_NEW_ADDRESSES
+        ));
+    }
+
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    wallet.validate_config_initiator(initiator_account_info, clock.unix_timestamp)?;
+
+    start_multisig_config_op(
+        multisig_op_account_info,
+        wallet_account_info,
+        &mut wallet,
+        clock,
+        MultisigOpParams::UpdateLookupTable {
+            wallet_address: *wallet_account_info.key,
+            lookup_table,
+            new_addresses: new_addresses.to_vec(),
+        },
+        *initiator_account_info.key,
+        *rent_return_account.key,
+        fee_amount,
+        fee_account_guid_hash,
+    )
+}
+
+pub fn finalize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    lookup_table: Option<Pubkey>,
+    new_addresses: &[Pubkey],
+    recent_slot: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let rent_return_account_info = next_signer_account_info(accounts_iter)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+    let lookup_table_account_info = next_account_info(accounts_iter)?;
+    let authority_account_info = next_account_info(accounts_iter)?;
+    let system_program_account_info = next_account_info(accounts_iter)?;
+    let lookup_table_program_account_info = next_account_info(accounts_iter)?;
+    let fee_account_info_maybe = accounts_iter.next();
+
+    let (authority, authority_bump_seed) =
+        find_authority_address(wallet_account_info.key, program_id);
+    if *authority_account_info.key != authority {
+        return Err(WalletError::InvalidPDA.into());
+    }
+    // without a lookup table, one is created at the address derived from the recent slot
+    let (lookup_table_address, create_bump_seed) = match lookup_table {
+        Some(lookup_table) => (lookup_table, None),
+        None => {
+            let (address, bump_seed) = find_lookup_table_address(&authority, recent_slot);
+            (address, Some(bump_seed))
+        }
+    };
+    if *lookup_table_account_info.key != lookup_table_address {
+        return Err(strike_err!(
+            "lookup_table_handler::finalize",
+            WalletError::AccountNotRecognized,
+            "Expected lookup table {}",
+            lookup_table_address
+        ));
+    }
+
+    let wallet_guid_hash =
+        &Wallet::wallet_guid_hash_from_slice(&wallet_account_info.data.borrow())?;
+
+    finalize_multisig_op(
+        multisig_op_account_info,
+        wallet_account_info,
+        FeeCollectionInfo {
+            rent_return_account_info,
+            fee_account_info_maybe,
+            wallet_guid_hash,
+            program_id,
+        },
+        clock,
+        MultisigOpParams::UpdateLookupTable {
+            wallet_address: *wallet_account_info.key,
+            lookup_table,
+            new_addresses: new_addresses.to_vec(),
+        },
+        || -> ProgramResult {
+            let account_infos = &[
+                lookup_table_account_info.clone(),
+                authority_account_info.clone(),
+                rent_return_account_info.clone(),
+                system_program_account_info.clone(),
+                lookup_table_program_account_info.clone(),
+            ];
+            let authority_seeds = LookupTableAuthoritySeeds {
+                wallet: wallet_account_info.key,
+            };
+            let authority_bump_seed = [authority_bump_seed];
+            let authority_seeds = &authority_seeds.signer_seeds(&authority_bump_seed)[..];
+            if let Some(bump_seed) = create_bump_seed {
+                invoke_signed(
+                    &create_lookup_table_instruction(
+                        &lookup_table_address,
+                        &authority,
+                        rent_return_account_info.key,
+                        recent_slot,
+                        bump_seed,
+                    ),
+                    account_infos,
+                    &[authority_seeds],
+                )?;
+                msg!("Created lookup table {}", lookup_table_address);
+            }
+            if !new_addresses.is_empty() {
+                invoke_signed(
+                    &extend_lookup_table_instruction(
+                        &lookup_table_address,
+                        &authority,
+                        rent_return_account_info.key,
+                        new_addresses,
+                    ),
+                    account_infos,
+                    &[authority_seeds],
+                )?;
+            }
+            Ok(())
+        },
+        || -> ProgramResult { Ok(()) },
+    )
+}
@@ -0,0 +1,112 @@
+use crate::handlers::utils::{
+    create_pda_account, finalize_multisig_op, get_clock_from_next_account,
+    next_initiator_account_info, next_program_account_info, next_signer_account_info,
+    next_wallet_account_info, start_multisig_config_op, FeeCollectionInfo,
+};
+use crate::model::balance_account::BalanceAccountGuidHash;
+use crate::model::merkle_address_book::MerkleAddressBook;
+use crate::model::multisig_op::MultisigOpParams;
+use crate::model::wallet::Wallet;
+use crate::pda::{self, MerkleAddressBookSeeds};
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::Hash;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+
+pub fn init(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    fee_amount: u64,
+    fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+    account_guid_hash: &BalanceAccountGuidHash,
+    root: &Hash,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let initiator_account_info =
+        next_initiator_account_info(accounts_iter, multisig_op_account_info, wallet_account_info)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+    let rent_return_account_info = next_signer_account_info(accounts_iter)?;
+
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    wallet.validate_config_initiator(initiator_account_info, clock.unix_timestamp)?;
+    wallet.get_active_balance_account(account_guid_hash)?;
+
+    start_multisig_config_op(
+        multisig_op_account_info,
+        wallet_account_info,
+        &mut wallet,
+        clock,
+        MultisigOpParams::UpdateMerkleAddressBook {
+            wallet_address: *wallet_account_info.key,
+            account_guid_hash: *account_guid_hash,
+            root: *root,
+        },
+        *initiator_account_info.key,
+        *rent_return_account_info.key,
+        fee_amount,
+        fee_account_guid_hash,
+    )
+}
+
+pub fn finalize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    account_guid_hash: &BalanceAccountGuidHash,
+    root: &Hash,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let address_book_account_info = next_account_info(accounts_iter)?;
+    let rent_return_account_info = next_signer_account_info(accounts_iter)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+    let system_program_account = next_account_info(accounts_iter)?;
+    let fee_account_info_maybe = accounts_iter.next();
+
+    let wallet_guid_hash =
+        &Wallet::wallet_guid_hash_from_slice(&wallet_account_info.data.borrow())?;
+
+    let seeds = MerkleAddressBookSeeds {
+        wallet: wallet_account_info.key,
+        account_guid_hash,
+    };
+    let bump_seed = pda::verify(program_id, &seeds, address_book_account_info.key, None)?;
+
+    finalize_multisig_op(
+        multisig_op_account_info,
+        wallet_account_info,
+        FeeCollectionInfo {
+            rent_return_account_info,
+            fee_account_info_maybe,
+            wallet_guid_hash,
+            program_id,
+        },
+        clock,
+        MultisigOpParams::UpdateMerkleAddressBook {
+            wallet_address: *wallet_account_info.key,
+            account_guid_hash: *account_guid_hash,
+            root: *root,
+        },
+        || -> ProgramResult {
+            if address_book_account_info.owner != program_id {
+                create_pda_account(
+                    rent_return_account_info,
+                    address_book_account_info,
+                    system_program_account,
+                    MerkleAddressBook::LEN,
+                    program_id,
+                    &seeds,
+                    bump_seed,
+                )?;
+            }
+            MerkleAddressBook::pack(
+                MerkleAddressBook::new(*wallet_account_info.key, *account_guid_hash, *root),
+                &mut address_book_account_info.data.borrow_mut(),
+            )
+        },
+        || -> ProgramResult { Ok(()) },
+    )
+}
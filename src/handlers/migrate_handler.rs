@@ -1,41 +1,240 @@
-use crate::error::WalletError;
-use crate::handlers::utils::next_program_account_info;
-use crate::model::wallet::Wallet;
+use crate::constants::{HASH_LEN, VERSION_LEN};
+use crate::error::{strike_err, WalletError};
+use crate::handlers::utils::{next_program_account_info, unpack_uninitialized};
+use crate::model::address_book::{
+    AddressBook, AddressBookExpirations, AddressBookRisks, AddressBookUsage, DAppBook,
+    DAppBookCategories, DAppBookExpirations, DAppBookInstructionLimits,
+};
+use crate::model::assistant::Assistant;
+use crate::model::balance_account::{
+    ApprovalTiers, BalanceAccount, FinalizeDelayPolicy, RewardSplitPolicy, SpendingLimit,
+};
+use crate::model::fee_policy::FeePolicy;
+use crate::model::finalize_submitter_policy::FinalizeSubmitterPolicy;
+use crate::model::rent_return_allowlist::RentReturnAllowlist;
+use crate::model::retired_signers::RetiredSigners;
+use crate::model::signer::Signer;
+use crate::model::signer_activations::SignerActivations;
+use crate::model::wallet::{
+    Approvers, Assistants, BalanceAccounts, PendingAddressBookEntries, Signers, Wallet,
+    WalletGuidHash,
+};
+use crate::model::wallet_freeze::WalletFreeze;
+use crate::model::wrapper_book::WrapperBook;
+use crate::utils::{SlotId, Slots};
 use crate::version::{Versioned, VERSION};
+use arrayref::{array_ref, array_refs};
 use solana_program::account_info::{next_account_info, AccountInfo};
 use solana_program::entrypoint::ProgramResult;
 use solana_program::program_error::ProgramError;
-use solana_program::program_pack::Pack;
-use solana_program::pubkey::Pubkey;
+use solana_program::program_pack::{Pack, Sealed};
+use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
 use std::collections::BTreeMap;
+use std::time::Duration;
 
-type MigrationFunction = fn(&AccountInfo, &mut [u8], &Pubkey);
+type MigrationFunction = fn(&AccountInfo, &mut [u8], &Pubkey) -> ProgramResult;
 
 static MIGRATION_TEST_VERSION: u32 = VERSION;
 
 // this is a test migration which goes from the current version to version 0, and
 // simply copies all the data
-fn migration_test(source: &AccountInfo, destination: &mut [u8], rent_return: &Pubkey) {
-    let source_account = Wallet::unpack(&**source.data.borrow()).unwrap();
+fn migration_test(
+    source: &AccountInfo,
+    destination: &mut [u8],
+    rent_return: &Pubkey,
+) -> ProgramResult {
+    let source_account = Wallet::unpack(&source.data.borrow())?;
     let destination_account = Wallet {
         is_initialized: true,
         version: 0,
         rent_return: *rent_return,
-        wallet_guid_hash: source_account.wallet_guid_hash,
-        signers: source_account.signers,
-        assistant: source_account.assistant,
-        address_book: source_account.address_book,
-        approvals_required_for_config: source_account.approvals_required_for_config,
-        approval_timeout_for_config: source_account.approval_timeout_for_config,
-        config_approvers: source_account.config_approvers,
-        balance_accounts: source_account.balance_accounts,
-        dapp_book: source_account.dapp_book,
+        ..source_account
     };
-    Wallet::pack(destination_account, destination).unwrap();
+    Wallet::pack(destination_account, destination)
+}
+
+/// A signer key as stored by version 1 wallets, before signers had capabilities.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Ord, PartialOrd)]
+struct SignerKeyV1(Pubkey);
+
+impl Sealed for SignerKeyV1 {}
+
+impl Pack for SignerKeyV1 {
+    const LEN: usize = PUBKEY_BYTES;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        dst.copy_from_slice(self.0.as_ref());
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        Ok(SignerKeyV1(Pubkey::new_from_array(*array_ref![
+            src,
+            0,
+            PUBKEY_BYTES
+        ])))
+    }
+}
+
+type SignersV1 = Slots<SignerKeyV1, { Wallet::MAX_SIGNERS }>;
+
+/// A balance account as stored by version 1 wallets, before it had a reward split policy,
+/// which is the current layout without the trailing policy.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Ord, PartialOrd)]
+struct BalanceAccountV1(BalanceAccount);
+
+impl Sealed for BalanceAccountV1 {}
+
+impl Pack for BalanceAccountV1 {
+    const LEN: usize = BalanceAccount::LEN
+        - RewardSplitPolicy::LEN
+        - FinalizeDelayPolicy::LEN
+        - 8 // mint cap
+        - SpendingLimit::LEN
+        - ApprovalTiers::LEN
+        - 1; // disallowed dapp categories
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut buf = [0; BalanceAccount::LEN];
+        self.0.pack_into_slice(&mut buf);
+        dst.copy_from_slice(&buf[..BalanceAccountV1::LEN]);
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let mut buf = [0; BalanceAccount::LEN];
+        buf[..BalanceAccountV1::LEN].copy_from_slice(&src[..BalanceAccountV1::LEN]);
+        Ok(BalanceAccountV1(BalanceAccount::unpack_from_slice(&buf)?))
+    }
+}
+
+type BalanceAccountsV1 = Slots<BalanceAccountV1, { Wallet::MAX_BALANCE_ACCOUNTS }>;
+
+const WALLET_V1_LEN: usize = 1 + // is_initialized
+    VERSION_LEN + // version
+    PUBKEY_BYTES + // rent return
+    HASH_LEN + // wallet guid hash
+    SignersV1::LEN +
+    PUBKEY_BYTES + // assistant
+    AddressBook::LEN +
+    1 + // approvals_required_for_config
+    8 + // approval_timeout_for_config
+    Approvers::STORAGE_SIZE + // config approvers
+    DAppBook::LEN +
+    BalanceAccountsV1::LEN;
+
+// migrates a version 1 wallet, which had a single assistant, no pending address book
+// entries, no address book usage, no config policy timelock, no dapp book expirations, no
+// finalize submitter policy, no compliance policy, no replication and no price oracle, and
+// whose signers had no capabilities. Signers keep every capability they effectively had, the
+// assistant takes the first assistant slot without a rate limit, the timelock takes its
+// default, dapps never expire, anyone may keep submitting finalize transactions, no transfer
+// needs a compliance approval, the wallet is neither a primary nor a standby, thresholds stay
+// in token units, and balance accounts start out without a reward split policy, a finalize
+// delay, a mint cap, a spending limit, approval tiers or disallowed dapp categories.
+fn migrate_v1(source: &AccountInfo, destination: &mut [u8], rent_return: &Pubkey) -> ProgramResult {
+    let src = &**source.data.borrow();
+    if src.len() != WALLET_V1_LEN {
+        return Err(strike_err!(
+            "migrate_handler::migrate_v1",
+            ProgramError::InvalidAccountData,
+            "Version 1 wallet data length {} does not match {}",
+            src.len(),
+            WALLET_V1_LEN
+        ));
+    }
+    let src = array_ref![src, 0, WALLET_V1_LEN];
+    let (
+        _is_initialized,
+        _version,
+        _rent_return,
+        wallet_guid_hash,
+        signers_src,
+        assistant,
+        address_book_src,
+        approvals_required_for_config,
+        approval_timeout_for_config,
+        config_approvers_src,
+        dapp_book_src,
+        balance_accounts_src,
+    ) = array_refs![
+        src,
+        1,
+        VERSION_LEN,
+        PUBKEY_BYTES,
+        HASH_LEN,
+        SignersV1::LEN,
+        PUBKEY_BYTES,
+        AddressBook::LEN,
+        1,
+        8,
+        Approvers::STORAGE_SIZE,
+        DAppBook::LEN,
+        BalanceAccountsV1::LEN
+    ];
+
+    let destination_account = Wallet {
+        is_initialized: true,
+        version: VERSION,
+        rent_return: *rent_return,
+        wallet_guid_hash: WalletGuidHash::new(wallet_guid_hash),
+        signers: Signers::from_vec(
+            SignersV1::unpack_from_slice(signers_src)?
+                .filled_slots()
+                .into_iter()
+                .map(|(slot_id, key)| (SlotId::new(slot_id.value), Signer::new(key.0)))
+                .collect(),
+        ),
+        assistants: Assistants::from_vec(vec![(
+            SlotId::new(0),
+            Assistant::new(Pubkey::new_from_array(*assistant)),
+        )]),
+        address_book: AddressBook::unpack_from_slice(address_book_src)?,
+        pending_address_book_entries: PendingAddressBookEntries::zero(),
+        pending_address_book_window_start: 0,
+        pending_address_book_entries_in_window: 0,
+        address_book_usage: AddressBookUsage::new(),
+        approvals_required_for_config: approvals_required_for_config[0],
+        approval_timeout_for_config: Duration::from_secs(u64::from_le_bytes(
+            *approval_timeout_for_config,
+        )),
+        config_approvers: Approvers::new(*config_approvers_src),
+        config_policy_timelock: Wallet::DEFAULT_CONFIG_POLICY_TIMELOCK,
+        pending_config_policy: None,
+        balance_accounts: BalanceAccounts::from_vec(
+            BalanceAccountsV1::unpack_from_slice(balance_accounts_src)?
+                .filled_slots()
+                .into_iter()
+                .map(|(slot_id, balance_account)| (SlotId::new(slot_id.value), balance_account.0))
+                .collect(),
+        ),
+        dapp_book: DAppBook::unpack_from_slice(dapp_book_src)?,
+        dapp_book_expirations: DAppBookExpirations::default(),
+        finalize_submitter_policy: FinalizeSubmitterPolicy::default(),
+        compliance_policy: None,
+        replication: None,
+        price_oracle: None,
+        freeze: WalletFreeze::default(),
+        rent_return_allowlist: RentReturnAllowlist::default(),
+        signer_activations: SignerActivations::default(),
+        fee_policy: FeePolicy::default(),
+        stake_weight_governance: None,
+        address_book_risks: AddressBookRisks::new(),
+        address_book_expirations: AddressBookExpirations::new(),
+        dapp_book_categories: DAppBookCategories::default(),
+        dapp_book_instruction_limits: DAppBookInstructionLimits::default(),
+        op_nonce: 0,
+        approval_group: None,
+        provisioning: None,
+        wrapper_book: WrapperBook::default(),
+        retired_signers: RetiredSigners::default(),
+    };
+    Wallet::pack(destination_account, destination)
 }
 
 fn migrations() -> BTreeMap<u32, MigrationFunction> {
-    BTreeMap::from([(MIGRATION_TEST_VERSION, migration_test as MigrationFunction)])
+    BTreeMap::from([
+        (1, migrate_v1 as MigrationFunction),
+        (MIGRATION_TEST_VERSION, migration_test as MigrationFunction),
+    ])
 }
 
 pub fn handle(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
@@ -49,18 +248,123 @@ pub fn handle(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
         return Err(WalletError::AccountVersionMismatch.into());
     }
 
-    if Wallet::is_initialized_from_slice(&destination_account_info.data.borrow()) {
-        return Err(ProgramError::AccountAlreadyInitialized);
-    }
+    unpack_uninitialized::<Wallet>(destination_account_info)?;
 
     if let Some(migrator) = migrations().get(&source_version) {
         migrator(
             source_account_info,
             &mut destination_account_info.data.borrow_mut(),
             rent_return_account_info.key,
-        );
-        Ok(())
+        )
     } else {
         Err(WalletError::UnknownVersion.into())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::constants::HASH_LEN;
+    use crate::handlers::migrate_handler::{
+        migrate_v1, BalanceAccountV1, BalanceAccountsV1, SignerKeyV1, SignersV1, WALLET_V1_LEN,
+    };
+    use crate::model::address_book::{AddressBook, AddressBookEntry, AddressBookEntryNameHash};
+    use crate::model::assistant::Assistant;
+    use crate::model::balance_account::{BalanceAccount, BalanceAccountGuidHash};
+    use crate::model::signer::Signer;
+    use crate::model::wallet::{Approvers, Wallet, WalletGuidHash};
+    use crate::test_utils::AccountInfoFixture;
+    use crate::utils::{SlotId, Slots};
+    use crate::version::VERSION;
+    use solana_program::program_error::ProgramError;
+    use solana_program::program_pack::Pack;
+    use solana_program::pubkey::Pubkey;
+    use std::time::Duration;
+
+    #[test]
+    fn test_migrate_v1() {
+        let signer_keys = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let assistant_key = Pubkey::new_unique();
+        let wallet_guid_hash = WalletGuidHash::new(&[7; HASH_LEN]);
+        let address_book_entry = AddressBookEntry {
+            address: Pubkey::new_unique(),
+            name_hash: AddressBookEntryNameHash::new(&[9; HASH_LEN]),
+        };
+        let mut balance_account =
+            BalanceAccount::unpack_unchecked(&[0; BalanceAccount::LEN]).unwrap();
+        balance_account.guid_hash = BalanceAccountGuidHash::new(&[5; HASH_LEN]);
+        balance_account.approvals_required_for_transfer = 1;
+        balance_account.approval_timeout_for_transfer = Duration::from_secs(7200);
+        balance_account.transfer_approvers = Approvers::from_enabled_vec(vec![SlotId::new(3)]);
+
+        let mut v1 = vec![1];
+        v1.extend_from_slice(&1u32.to_le_bytes());
+        v1.extend_from_slice(Pubkey::new_unique().as_ref());
+        v1.extend_from_slice(wallet_guid_hash.to_bytes());
+        let mut signers = vec![0; SignersV1::LEN];
+        SignersV1::from_vec(vec![
+            (SlotId::new(0), SignerKeyV1(signer_keys[0])),
+            (SlotId::new(3), SignerKeyV1(signer_keys[1])),
+        ])
+        .pack_into_slice(&mut signers);
+        v1.extend_from_slice(&signers);
+        v1.extend_from_slice(assistant_key.as_ref());
+        let mut address_book = vec![0; AddressBook::LEN];
+        AddressBook::from_vec(vec![(SlotId::new(2), address_book_entry)])
+            .pack_into_slice(&mut address_book);
+        v1.extend_from_slice(&address_book);
+        v1.push(2);
+        v1.extend_from_slice(&3600u64.to_le_bytes());
+        v1.extend_from_slice(
+            Approvers::from_enabled_vec(vec![SlotId::new(0), SlotId::new(3)]).as_bytes(),
+        );
+        v1.resize(WALLET_V1_LEN - BalanceAccountsV1::LEN, 0);
+        let mut balance_accounts = vec![0; BalanceAccountsV1::LEN];
+        BalanceAccountsV1::from_vec(vec![(SlotId::new(1), BalanceAccountV1(balance_account))])
+            .pack_into_slice(&mut balance_accounts);
+        v1.extend_from_slice(&balance_accounts);
+
+        let rent_return = Pubkey::new_unique();
+        let mut source = AccountInfoFixture::unique().data(v1);
+        let mut destination = vec![0; Wallet::LEN];
+        migrate_v1(&source.account_info(), &mut destination, &rent_return).unwrap();
+
+        let wallet = Wallet::unpack(&destination).unwrap();
+        assert_eq!(wallet.version, VERSION);
+        assert_eq!(wallet.rent_return, rent_return);
+        assert_eq!(wallet.wallet_guid_hash, wallet_guid_hash);
+        assert_eq!(
+            wallet.signers,
+            Slots::from_vec(vec![
+                (SlotId::new(0), Signer::new(signer_keys[0])),
+                (SlotId::new(3), Signer::new(signer_keys[1])),
+            ])
+        );
+        assert_eq!(
+            wallet.assistants,
+            Slots::from_vec(vec![(SlotId::new(0), Assistant::new(assistant_key))])
+        );
+        assert_eq!(
+            wallet.address_book,
+            AddressBook::from_vec(vec![(SlotId::new(2), address_book_entry)])
+        );
+        assert_eq!(wallet.pending_address_book_entries.count_enabled(), 0);
+        assert_eq!(wallet.approvals_required_for_config, 2);
+        assert_eq!(
+            wallet.approval_timeout_for_config,
+            Duration::from_secs(3600)
+        );
+        assert_eq!(wallet.get_config_approvers_keys(), signer_keys.to_vec());
+        assert_eq!(
+            wallet.balance_accounts.filled_slots(),
+            vec![(SlotId::new(1), balance_account)]
+        );
+        assert!(!balance_account.reward_split_policy.is_set());
+
+        // anything other than a version 1 layout is rejected
+        let mut source = AccountInfoFixture::unique().data(vec![0; WALLET_V1_LEN - 1]);
+        assert_eq!(
+            migrate_v1(&source.account_info(), &mut destination, &rent_return),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+}
@@ -0,0 +1,204 @@
+use crate::error::{strike_err, WalletError};
+use crate::handlers::utils::{
+    finalize_multisig_op, get_clock_from_next_account, next_initiator_account_info,
+    next_program_account_info, next_signer_account_info, next_wallet_account_info,
+    start_multisig_transfer_op, validate_balance_account_and_get_seed, validate_mint_authority,
+    FeeCollectionInfo,
+};
+use crate::model::address_book::AddressBookEntryNameHash;
+use crate::model::balance_account::BalanceAccountGuidHash;
+use crate::model::multisig_op::{MintAuthorityType, MultisigOpParams};
+use crate::model::wallet::Wallet;
+use crate::pda::{BalanceAccountSeeds, PdaSeeds};
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program::invoke_signed;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use spl_token::instruction as spl_instruction;
+use spl_token::state::Account as SPLAccount;
+
+pub fn init(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    fee_amount: u64,
+    fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+    account_guid_hash: &BalanceAccountGuidHash,
+    amount: u64,
+    destination_name_hash: &AddressBookEntryNameHash,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let balance_account_info = next_account_info(accounts_iter)?;
+    let mint_account_info = next_account_info(accounts_iter)?;
+    let destination_token_account_info = next_account_info(accounts_iter)?;
+    let initiator_account_info =
+        next_initiator_account_info(accounts_iter, multisig_op_account_info, wallet_account_info)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+    let rent_return_account_info = next_signer_account_info(accounts_iter)?;
+
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    let balance_account = wallet.get_active_balance_account(account_guid_hash)?;
+    wallet.validate_transfer_initiator(initiator_account_info, clock.unix_timestamp)?;
+
+    validate_balance_account_and_get_seed(
+        balance_account_info,
+        &wallet.wallet_guid_hash,
+        account_guid_hash,
+        program_id,
+    )?;
+    validate_mint_authority(
+        mint_account_info,
+        MintAuthorityType::MintTokens,
+        balance_account_info.key,
+    )?;
+
+    if balance_account.mint_cap == 0 || amount > balance_account.mint_cap {
+        return Err(strike_err!(
+            "mint_to_handler::init",
+            WalletError::MintCapExceeded,
+            "Mint amount {} exceeds the balance account's mint cap of {}",
+            amount,
+            balance_account.mint_cap
+        ));
+    }
+
+    if *destination_token_account_info.owner != spl_token::id() {
+        return Err(strike_err!(
+            "mint_to_handler::init",
+            WalletError::InvalidDestinationTokenAccount,
+            "Destination is not an SPL token account"
+        ));
+    }
+    let destination_token_account =
+        SPLAccount::unpack(&destination_token_account_info.data.borrow())?;
+    if destination_token_account.mint != *mint_account_info.key {
+        return Err(strike_err!(
+            "mint_to_handler::init",
+            WalletError::InvalidDestinationTokenAccount,
+            "Destination token account is not for the mint"
+        ));
+    }
+
+    // the tokens go to whoever owns the destination token account, so that is what has to be
+    // whitelisted
+    let destination = destination_token_account.owner;
+    if !wallet.destination_allowed(
+        &balance_account,
+        &destination,
+        destination_name_hash,
+        clock.unix_timestamp,
+    )? {
+        return Err(strike_err!(
+            "mint_to_handler::init",
+            WalletError::DestinationNotAllowed,
+            "Destination account is not whitelisted"
+        ));
+    }
+    let destination_used =
+        wallet.record_destination_use(&destination, destination_name_hash, clock.slot);
+
+    let destination_jurisdiction =
+        wallet.destination_jurisdiction(&destination, destination_name_hash);
+    start_multisig_transfer_op(
+        multisig_op_account_info,
+        wallet_account_info,
+        &mut wallet,
+        &balance_account,
+        clock,
+        MultisigOpParams::MintTo {
+            wallet_address: *wallet_account_info.key,
+            account_guid_hash: *account_guid_hash,
+            mint: *mint_account_info.key,
+            destination_token_account: *destination_token_account_info.key,
+            amount,
+        },
+        destination_jurisdiction,
+        *initiator_account_info.key,
+        *rent_return_account_info.key,
+        fee_amount,
+        fee_account_guid_hash,
+        &[],
+    )?;
+
+    if destination_used {
+        Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+    }
+    Ok(())
+}
+
+pub fn finalize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    account_guid_hash: &BalanceAccountGuidHash,
+    amount: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let balance_account_info = next_account_info(accounts_iter)?;
+    let mint_account_info = next_account_info(accounts_iter)?;
+    let destination_token_account_info = next_account_info(accounts_iter)?;
+    let spl_token_program_account_info = next_account_info(accounts_iter)?;
+    let rent_return_account_info = next_signer_account_info(accounts_iter)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+    let fee_account_info_maybe = accounts_iter.next();
+
+    if *spl_token_program_account_info.key != spl_token::id() {
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+
+    let wallet_guid_hash =
+        &Wallet::wallet_guid_hash_from_slice(&wallet_account_info.data.borrow())?;
+
+    let bump_seed = validate_balance_account_and_get_seed(
+        balance_account_info,
+        wallet_guid_hash,
+        account_guid_hash,
+        program_id,
+    )?;
+
+    finalize_multisig_op(
+        multisig_op_account_info,
+        wallet_account_info,
+        FeeCollectionInfo {
+            rent_return_account_info,
+            fee_account_info_maybe,
+            wallet_guid_hash,
+            program_id,
+        },
+        clock,
+        MultisigOpParams::MintTo {
+            wallet_address: *wallet_account_info.key,
+            account_guid_hash: *account_guid_hash,
+            mint: *mint_account_info.key,
+            destination_token_account: *destination_token_account_info.key,
+            amount,
+        },
+        || -> ProgramResult {
+            invoke_signed(
+                &spl_instruction::mint_to(
+                    &spl_token::id(),
+                    mint_account_info.key,
+                    destination_token_account_info.key,
+                    balance_account_info.key,
+                    &[],
+                    amount,
+                )?,
+                &[
+                    mint_account_info.clone(),
+                    destination_token_account_info.clone(),
+                    balance_account_info.clone(),
+                    spl_token_program_account_info.clone(),
+                ],
+                &[&BalanceAccountSeeds {
+                    wallet_guid_hash,
+                    account_guid_hash,
+                }
+                .signer_seeds(&[bump_seed])],
+            )
+        },
+        || -> ProgramResult { Ok(()) },
+    )
+}
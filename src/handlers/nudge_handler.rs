@@ -0,0 +1,30 @@
+use crate::error::{strike_err, WalletError};
+use crate::handlers::utils::{get_clock_from_next_account, next_program_account_info};
+use crate::model::multisig_op::MultisigOp;
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::pubkey::Pubkey;
+
+pub fn handle(program_id: &Pubkey, accounts: &[AccountInfo], idle_slots: u64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+
+    let multisig_op = MultisigOp::unpack_sized(&multisig_op_account_info.data.borrow())?;
+    if !multisig_op.is_idle(idle_slots, &clock) {
+        return Err(strike_err!(
+            "nudge_handler::handle",
+            WalletError::OperationNotIdle,
+            "Op last saw approval activity at slot {}",
+            multisig_op.last_activity_slot
+        ));
+    }
+
+    msg!("ApprovalReminder: [{}]", multisig_op_account_info.key);
+    for approver in multisig_op.pending_approvers() {
+        msg!("PendingApprover: [{}]", approver);
+    }
+
+    Ok(())
+}
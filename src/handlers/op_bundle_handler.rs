@@ -0,0 +1,154 @@
+use crate::error::{strike_err, WalletError};
+use crate::handlers::utils::{
+    collect_remaining_balance, get_clock_from_next_account, next_initiator_account_info,
+    next_program_account_info, next_signer_account_info, next_wallet_account_info,
+    unpack_uninitialized,
+};
+use crate::model::multisig_op::{MultisigOp, OperationDisposition};
+use crate::model::op_bundle::{OpBundle, OpBundleMember};
+use crate::model::wallet::Wallet;
+use crate::version::{Versioned, VERSION};
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::Hash;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+
+fn unpack_multisig_op(multisig_op_account_info: &AccountInfo) -> Result<MultisigOp, ProgramError> {
+    if MultisigOp::version_from_slice(&multisig_op_account_info.data.borrow())? != VERSION {
+        return Err(WalletError::OperationVersionMismatch.into());
+    }
+    MultisigOp::unpack_sized(&multisig_op_account_info.data.borrow())
+}
+
+pub fn init(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let bundle_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let initiator_account_info =
+        next_initiator_account_info(accounts_iter, bundle_account_info, wallet_account_info)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+    let rent_return_account_info = next_signer_account_info(accounts_iter)?;
+
+    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    wallet.validate_config_initiator(initiator_account_info, clock.unix_timestamp)?;
+    unpack_uninitialized::<OpBundle>(bundle_account_info)?;
+
+    let mut bundle = OpBundle {
+        is_initialized: true,
+        wallet_address: *wallet_account_info.key,
+        rent_return: *rent_return_account_info.key,
+        expires_at: i64::MAX,
+        members: Vec::new(),
+        approvers: Vec::new(),
+    };
+    for multisig_op_account_info in accounts_iter {
+        if multisig_op_account_info.owner != program_id
+            || bundle.get_member(multisig_op_account_info.key).is_some()
+            || bundle.members.len() == OpBundle::MAX_MEMBERS
+        {
+            return Err(strike_err!(
+                "op_bundle_handler::init",
+                WalletError::InvalidOpBundle,
+                "Op bundle members must be up to {} distinct multisig ops",
+                OpBundle::MAX_MEMBERS
+            ));
+        }
+        let mut multisig_op = unpack_multisig_op(multisig_op_account_info)?;
+        let pending = multisig_op.initiator == *initiator_account_info.key
+            && multisig_op.update_operation_disposition(&clock)? == OperationDisposition::NONE;
+        let params_hash = match multisig_op.params_hash {
+            Some(params_hash) if pending => params_hash,
+            _ => {
+                return Err(strike_err!(
+                    "op_bundle_handler::init",
+                    WalletError::InvalidOpBundle,
+                    "Multisig op {} is not pending approval or has another initiator",
+                    multisig_op_account_info.key
+                ))
+            }
+        };
+        bundle.expires_at = bundle.expires_at.min(multisig_op.expires_at);
+        bundle.members.push(OpBundleMember {
+            multisig_op: *multisig_op_account_info.key,
+            params_hash,
+        });
+    }
+    if bundle.members.is_empty() {
+        return Err(strike_err!(
+            "op_bundle_handler::init",
+            WalletError::InvalidOpBundle,
+            "Op bundle has no members"
+        ));
+    }
+
+    msg!("Op bundle hash: {}", bundle.bundle_hash());
+    OpBundle::pack(bundle, &mut bundle_account_info.data.borrow_mut())
+}
+
+pub fn approve(program_id: &Pubkey, accounts: &[AccountInfo], bundle_hash: Hash) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let bundle_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let approver_account_info = next_signer_account_info(accounts_iter)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+
+    let mut bundle = OpBundle::unpack(&bundle_account_info.data.borrow())?;
+    if bundle.wallet_address != *wallet_account_info.key {
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+    if bundle.bundle_hash() != bundle_hash {
+        return Err(WalletError::InvalidSignature.into());
+    }
+    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    if !wallet
+        .get_signers_keys()
+        .contains(approver_account_info.key)
+    {
+        return Err(strike_err!(
+            "op_bundle_handler::approve",
+            WalletError::InvalidApprover,
+            "Op bundles can only be approved by signers of the wallet"
+        ));
+    }
+
+    bundle.record_approval(*approver_account_info.key, clock.unix_timestamp)?;
+    OpBundle::pack(bundle, &mut bundle_account_info.data.borrow_mut())
+}
+
+pub fn apply_approval(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let bundle_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+
+    let bundle = OpBundle::unpack(&bundle_account_info.data.borrow())?;
+    let mut multisig_op = unpack_multisig_op(multisig_op_account_info)?;
+    match bundle.get_member(multisig_op_account_info.key) {
+        Some(member) if Some(member.params_hash) == multisig_op.params_hash => {}
+        _ => {
+            return Err(strike_err!(
+                "op_bundle_handler::apply_approval",
+                WalletError::InvalidOpBundle,
+                "Multisig op is not a member of the op bundle"
+            ))
+        }
+    }
+
+    multisig_op.record_bundle_approvals(&bundle.approvers, &clock)?;
+    MultisigOp::pack_sized(multisig_op, &mut multisig_op_account_info.data.borrow_mut())
+}
+
+pub fn close(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let bundle_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let rent_return_account_info = next_signer_account_info(accounts_iter)?;
+
+    let bundle = OpBundle::unpack(&bundle_account_info.data.borrow())?;
+    if bundle.rent_return != *rent_return_account_info.key {
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+    collect_remaining_balance(bundle_account_info, rent_return_account_info)
+}
@@ -0,0 +1,289 @@
+use crate::constants::PUBKEY_BYTES;
+use crate::error::{strike_err, WalletError};
+use crate::handlers::utils::{
+    calculate_expires, finalize_multisig_op, get_clock_from_next_account,
+    next_initiator_account_info, next_program_account_info, next_signer_account_info,
+    next_wallet_account_info, start_multisig_transfer_op, transfer_sol_checked,
+    unpack_uninitialized, validate_balance_account_and_get_seed, FeeCollectionInfo,
+};
+use crate::model::address_book::AddressBookEntryNameHash;
+use crate::model::balance_account::BalanceAccountGuidHash;
+use crate::model::multisig_op::MultisigOpParams;
+use crate::model::payment_channel::PaymentChannel;
+use crate::model::wallet::Wallet;
+use crate::pda::{BalanceAccountSeeds, PdaSeeds};
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program::invoke_signed;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_program;
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::instruction as spl_instruction;
+use std::time::Duration;
+
+/// What a payment channel may pay out, and for how long once it is open.
+pub struct PaymentChannelTerms {
+    /// The default pubkey for SOL.
+    pub token_mint: Pubkey,
+    pub allowance: u64,
+    pub duration: Duration,
+}
+
+pub fn init(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    fee_amount: u64,
+    fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+    account_guid_hash: &BalanceAccountGuidHash,
+    destination_name_hash: &AddressBookEntryNameHash,
+    terms: PaymentChannelTerms,
+) -> ProgramResult {
+    let PaymentChannelTerms {
+        token_mint,
+        allowance,
+        duration,
+    } = terms;
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let channel_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let destination_account_info = next_account_info(accounts_iter)?;
+    let initiator_account_info =
+        next_initiator_account_info(accounts_iter, multisig_op_account_info, wallet_account_info)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+    let rent_return_account_info = next_signer_account_info(accounts_iter)?;
+
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    let balance_account = wallet.get_active_balance_account(account_guid_hash)?;
+    wallet.validate_transfer_initiator(initiator_account_info, clock.unix_timestamp)?;
+
+    if allowance == 0 || duration.is_zero() {
+        return Err(strike_err!(
+            "payment_channel_handler::init",
+            WalletError::InvalidPaymentChannel,
+            "Payment channel allowance and duration can't be 0"
+        ));
+    }
+    // fail now rather than after approval if the channel account can't be initialized
+    unpack_uninitialized::<PaymentChannel>(channel_account_info)?;
+
+    if !wallet.destination_allowed(
+        &balance_account,
+        destination_account_info.key,
+        destination_name_hash,
+        clock.unix_timestamp,
+    )? {
+        return Err(strike_err!(
+            "payment_channel_handler::init",
+            WalletError::DestinationNotAllowed,
+            "Destination account is not whitelisted"
+        ));
+    }
+    let destination_used = wallet.record_destination_use(
+        destination_account_info.key,
+        destination_name_hash,
+        clock.slot,
+    );
+
+    let destination_jurisdiction =
+        wallet.destination_jurisdiction(destination_account_info.key, destination_name_hash);
+    start_multisig_transfer_op(
+        multisig_op_account_info,
+        wallet_account_info,
+        &mut wallet,
+        &balance_account,
+        clock,
+        MultisigOpParams::OpenPaymentChannel {
+            wallet_address: *wallet_account_info.key,
+            account_guid_hash: *account_guid_hash,
+            channel_account: *channel_account_info.key,
+            destination: *destination_account_info.key,
+            token_mint,
+            allowance,
+            duration,
+        },
+        destination_jurisdiction,
+        *initiator_account_info.key,
+        *rent_return_account_info.key,
+        fee_amount,
+        fee_account_guid_hash,
+        accounts,
+    )?;
+
+    if destination_used {
+        Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+    }
+    Ok(())
+}
+
+pub fn finalize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    account_guid_hash: &BalanceAccountGuidHash,
+    token_mint: Pubkey,
+    allowance: u64,
+    duration: Duration,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let channel_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let destination_account_info = next_account_info(accounts_iter)?;
+    let rent_return_account_info = next_signer_account_info(accounts_iter)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+    let fee_account_info_maybe = accounts_iter.next();
+    let now = clock.unix_timestamp;
+
+    let wallet_guid_hash =
+        &Wallet::wallet_guid_hash_from_slice(&wallet_account_info.data.borrow())?;
+
+    finalize_multisig_op(
+        multisig_op_account_info,
+        wallet_account_info,
+        FeeCollectionInfo {
+            rent_return_account_info,
+            fee_account_info_maybe,
+            wallet_guid_hash,
+            program_id,
+        },
+        clock,
+        MultisigOpParams::OpenPaymentChannel {
+            wallet_address: *wallet_account_info.key,
+            account_guid_hash: *account_guid_hash,
+            channel_account: *channel_account_info.key,
+            destination: *destination_account_info.key,
+            token_mint,
+            allowance,
+            duration,
+        },
+        || -> ProgramResult {
+            unpack_uninitialized::<PaymentChannel>(channel_account_info)?;
+            PaymentChannel::pack(
+                PaymentChannel {
+                    is_initialized: true,
+                    wallet_address: *wallet_account_info.key,
+                    account_guid_hash: *account_guid_hash,
+                    destination: *destination_account_info.key,
+                    token_mint,
+                    allowance,
+                    spent: 0,
+                    expires_at: calculate_expires(now, duration)?,
+                },
+                &mut channel_account_info.data.borrow_mut(),
+            )
+        },
+        || -> ProgramResult { Ok(()) },
+    )
+}
+
+pub fn pay(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let channel_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let source_account_info = next_account_info(accounts_iter)?;
+    let destination_account_info = next_account_info(accounts_iter)?;
+    let assistant_account_info = next_signer_account_info(accounts_iter)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+    let system_program_account_info = next_account_info(accounts_iter)?;
+
+    if *system_program_account_info.key != system_program::id() {
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+
+    let mut channel = PaymentChannel::unpack(&channel_account_info.data.borrow())?;
+    if channel.wallet_address != *wallet_account_info.key
+        || channel.destination != *destination_account_info.key
+    {
+        return Err(strike_err!(
+            "payment_channel_handler::pay",
+            WalletError::AccountNotRecognized,
+            "Payment channel is not for this wallet and destination"
+        ));
+    }
+
+    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    wallet.get_active_balance_account(&channel.account_guid_hash)?;
+
+    // the allowance was approved up front, so drawing it down only takes the assistant
+    match wallet.get_assistant(assistant_account_info.key) {
+        Some((_, assistant)) if assistant.enabled => {}
+        Some(_) => {
+            return Err(strike_err!(
+                "payment_channel_handler::pay",
+                WalletError::AssistantDisabled,
+                "Assistant is disabled"
+            ))
+        }
+        None => {
+            return Err(strike_err!(
+                "payment_channel_handler::pay",
+                WalletError::IncorrectInitiatorAccount,
+                "Channel payments can only be made by the wallet's assistant"
+            ))
+        }
+    }
+
+    let bump_seed = validate_balance_account_and_get_seed(
+        source_account_info,
+        &wallet.wallet_guid_hash,
+        &channel.account_guid_hash,
+        program_id,
+    )?;
+
+    channel.record_payment(amount, clock.unix_timestamp)?;
+
+    if channel.token_mint.to_bytes() != [0; PUBKEY_BYTES] {
+        let source_token_account_info = next_account_info(accounts_iter)?;
+        let destination_token_account_info = next_account_info(accounts_iter)?;
+        let spl_token_program_account_info = next_account_info(accounts_iter)?;
+
+        if *spl_token_program_account_info.key != spl_token::id() {
+            return Err(WalletError::AccountNotRecognized.into());
+        }
+        if *source_token_account_info.key
+            != get_associated_token_address(source_account_info.key, &channel.token_mint)
+        {
+            return Err(WalletError::InvalidSourceTokenAccount.into());
+        }
+        if *destination_token_account_info.key
+            != get_associated_token_address(destination_account_info.key, &channel.token_mint)
+        {
+            return Err(WalletError::InvalidDestinationTokenAccount.into());
+        }
+
+        invoke_signed(
+            &spl_instruction::transfer(
+                &spl_token::id(),
+                source_token_account_info.key,
+                destination_token_account_info.key,
+                source_account_info.key,
+                &[],
+                amount,
+            )?,
+            &[
+                source_token_account_info.clone(),
+                destination_token_account_info.clone(),
+                source_account_info.clone(),
+                spl_token_program_account_info.clone(),
+            ],
+            &[&BalanceAccountSeeds {
+                wallet_guid_hash: &wallet.wallet_guid_hash,
+                account_guid_hash: &channel.account_guid_hash,
+            }
+            .signer_seeds(&[bump_seed])],
+        )?;
+    } else {
+        transfer_sol_checked(
+            &wallet.wallet_guid_hash,
+            source_account_info.clone(),
+            &channel.account_guid_hash,
+            bump_seed,
+            system_program_account_info.clone(),
+            destination_account_info.clone(),
+            amount,
+        )?;
+    }
+
+    PaymentChannel::pack(channel, &mut channel_account_info.data.borrow_mut())
+}
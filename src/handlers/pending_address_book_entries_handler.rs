@@ -0,0 +1,65 @@
+use crate::error::{strike_err, WalletError};
+use crate::handlers::utils::{
+    get_clock_from_next_account, next_signer_account_info, next_wallet_account_info,
+};
+use crate::model::address_book::AddressBookEntry;
+use crate::model::wallet::Wallet;
+use crate::utils::SlotId;
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::Sysvar;
+
+pub fn add(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    entries: &Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let assistant_account_info = next_signer_account_info(accounts_iter)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    if !wallet.is_enabled_assistant(assistant_account_info.key) {
+        return Err(strike_err!(
+            "pending_address_book_entries_handler::add",
+            WalletError::InvalidApprover,
+            "Pending address book entries can only be added by an enabled assistant"
+        ));
+    }
+
+    // each addition counts against a rate limited assistant's initiations
+    wallet.record_assistant_initiation(assistant_account_info.key, clock.unix_timestamp)?;
+    wallet.add_pending_address_book_entries(entries, clock.unix_timestamp)?;
+    Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+    Ok(())
+}
+
+pub fn ratify(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    entries: &Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let approver_account_info = next_signer_account_info(accounts_iter)?;
+
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    if !wallet
+        .get_active_config_approvers_keys(Clock::get()?.unix_timestamp)
+        .contains(approver_account_info.key)
+    {
+        return Err(strike_err!(
+            "pending_address_book_entries_handler::ratify",
+            WalletError::InvalidApprover,
+            "Pending address book entries can only be ratified by a config approver"
+        ));
+    }
+
+    wallet.ratify_pending_address_book_entries(entries)?;
+    Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use crate::error::WalletError;
+use crate::handlers::utils::{create_pda_account, next_signer_account_info};
+use crate::model::program_parameters::ProgramParameters;
+use crate::pda::{self, ProgramParametersSeeds};
+use arrayref::{array_ref, array_refs};
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::bpf_loader_upgradeable;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+
+/// The serialized `UpgradeableLoaderState::ProgramData` variant: its tag, the slot the program
+/// was last deployed in, and the optional upgrade authority.
+const PROGRAM_DATA_METADATA_LEN: usize = 4 + 8 + 1 + 32;
+const PROGRAM_DATA_TAG: u32 = 3;
+
+pub fn update(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    parameters: &ProgramParameters,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let parameters_account_info = next_account_info(accounts_iter)?;
+    let program_data_account_info = next_account_info(accounts_iter)?;
+    let upgrade_authority_account_info = next_signer_account_info(accounts_iter)?;
+    let system_program_account = next_account_info(accounts_iter)?;
+
+    let (program_data_address, _) =
+        Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+    if *program_data_account_info.key != program_data_address
+        || *program_data_account_info.owner != bpf_loader_upgradeable::id()
+    {
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+    if upgrade_authority(&program_data_account_info.data.borrow())?
+        != Some(*upgrade_authority_account_info.key)
+    {
+        return Err(WalletError::InvalidSignature.into());
+    }
+
+    parameters.validate()?;
+
+    let bump_seed = pda::verify(
+        program_id,
+        &ProgramParametersSeeds,
+        parameters_account_info.key,
+        None,
+    )?;
+    if parameters_account_info.owner != program_id {
+        create_pda_account(
+            upgrade_authority_account_info,
+            parameters_account_info,
+            system_program_account,
+            ProgramParameters::LEN,
+            program_id,
+            &ProgramParametersSeeds,
+            bump_seed,
+        )?;
+    }
+
+    ProgramParameters::pack(
+        ProgramParameters {
+            is_initialized: true,
+            ..*parameters
+        },
+        &mut parameters_account_info.data.borrow_mut(),
+    )
+}
+
+/// Loads the program parameters, which instructions bounded by them must be supplied. Until the
+/// parameters account is created, the ceilings in code apply.
+pub fn load(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> Result<ProgramParameters, ProgramError> {
+    let (parameters_address, _) = ProgramParameters::find_address(program_id);
+    let parameters_account_info = accounts
+        .iter()
+        .find(|account_info| *account_info.key == parameters_address)
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+    if parameters_account_info.owner != program_id {
+        return Ok(ProgramParameters::default());
+    }
+    ProgramParameters::unpack(&parameters_account_info.data.borrow())
+}
+
+fn upgrade_authority(program_data: &[u8]) -> Result<Option<Pubkey>, ProgramError> {
+    if program_data.len() < PROGRAM_DATA_METADATA_LEN {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let program_data = array_ref![program_data, 0, PROGRAM_DATA_METADATA_LEN];
+    let (tag, _slot, has_authority, authority) = array_refs![program_data, 4, 8, 1, 32];
+    if u32::from_le_bytes(*tag) != PROGRAM_DATA_TAG {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(match has_authority {
+        [0] => None,
+        _ => Some(Pubkey::new_from_array(*authority)),
+    })
+}
@@ -0,0 +1,84 @@
+use crate::error::{strike_err, WalletError};
+use crate::handlers::utils::{
+    create_pda_account, next_program_account_info, next_signer_account_info,
+    next_wallet_account_info,
+};
+use crate::model::session_key_registry::{SessionKey, SessionKeyRegistry, SessionKeyScope};
+use crate::model::wallet::Wallet;
+use crate::pda::{self, SessionKeyRegistrySeeds};
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::Sysvar;
+
+pub fn register(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    session_key: &Pubkey,
+    scope: &SessionKeyScope,
+    expires_at: i64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let registry_account_info = next_account_info(accounts_iter)?;
+    let approver_account_info = next_signer_account_info(accounts_iter)?;
+    let payer_account_info = next_signer_account_info(accounts_iter)?;
+    let system_program_account = next_account_info(accounts_iter)?;
+
+    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    if wallet.get_signer(approver_account_info.key).is_none() {
+        return Err(strike_err!(
+            "session_key_handler::register",
+            WalletError::InvalidApprover,
+            "Only signers of the wallet can register session keys"
+        ));
+    }
+
+    let seeds = SessionKeyRegistrySeeds {
+        wallet: wallet_account_info.key,
+    };
+    let bump_seed = pda::verify(program_id, &seeds, registry_account_info.key, None)?;
+
+    let mut registry = if registry_account_info.owner == program_id {
+        SessionKeyRegistry::unpack(&registry_account_info.data.borrow())?
+    } else {
+        create_pda_account(
+            payer_account_info,
+            registry_account_info,
+            system_program_account,
+            SessionKeyRegistry::LEN,
+            program_id,
+            &seeds,
+            bump_seed,
+        )?;
+        SessionKeyRegistry::new(*wallet_account_info.key)
+    };
+
+    registry.register(
+        SessionKey {
+            approver: *approver_account_info.key,
+            session_key: *session_key,
+            scope: *scope,
+            expires_at,
+        },
+        Clock::get()?.unix_timestamp,
+    )?;
+
+    SessionKeyRegistry::pack(registry, &mut registry_account_info.data.borrow_mut())
+}
+
+pub fn revoke(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    session_key: &Pubkey,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let registry_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let approver_account_info = next_signer_account_info(accounts_iter)?;
+
+    let mut registry = SessionKeyRegistry::unpack(&registry_account_info.data.borrow())?;
+    registry.revoke(approver_account_info.key, session_key)?;
+    SessionKeyRegistry::pack(registry, &mut registry_account_info.data.borrow_mut())
+}
@@ -0,0 +1,151 @@
+use crate::error::WalletError;
+use crate::handlers::utils::{
+    finalize_multisig_op, get_clock_from_next_account, next_initiator_account_info,
+    next_program_account_info, next_signer_account_info, next_wallet_account_info,
+    start_multisig_transfer_op, validate_balance_account_and_get_seed, validate_mint_authority,
+    FeeCollectionInfo,
+};
+use crate::model::balance_account::BalanceAccountGuidHash;
+use crate::model::multisig_op::{MintAuthorityType, MultisigOpParams};
+use crate::model::wallet::Wallet;
+use crate::pda::{BalanceAccountSeeds, PdaSeeds};
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program::invoke_signed;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use spl_token::instruction::{self as spl_instruction, AuthorityType};
+
+pub fn init(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    fee_amount: u64,
+    fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+    account_guid_hash: &BalanceAccountGuidHash,
+    authority_type: MintAuthorityType,
+    new_authority: Option<Pubkey>,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let balance_account_info = next_account_info(accounts_iter)?;
+    let mint_account_info = next_account_info(accounts_iter)?;
+    let initiator_account_info =
+        next_initiator_account_info(accounts_iter, multisig_op_account_info, wallet_account_info)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+    let rent_return_account_info = next_signer_account_info(accounts_iter)?;
+
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    let balance_account = wallet.get_active_balance_account(account_guid_hash)?;
+    wallet.validate_transfer_initiator(initiator_account_info, clock.unix_timestamp)?;
+
+    validate_balance_account_and_get_seed(
+        balance_account_info,
+        &wallet.wallet_guid_hash,
+        account_guid_hash,
+        program_id,
+    )?;
+    validate_mint_authority(mint_account_info, authority_type, balance_account_info.key)?;
+
+    start_multisig_transfer_op(
+        multisig_op_account_info,
+        wallet_account_info,
+        &mut wallet,
+        &balance_account,
+        clock,
+        MultisigOpParams::SetMintAuthority {
+            wallet_address: *wallet_account_info.key,
+            account_guid_hash: *account_guid_hash,
+            mint: *mint_account_info.key,
+            authority_type,
+            new_authority,
+        },
+        None,
+        *initiator_account_info.key,
+        *rent_return_account_info.key,
+        fee_amount,
+        fee_account_guid_hash,
+        &[],
+    )
+}
+
+pub fn finalize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    account_guid_hash: &BalanceAccountGuidHash,
+    authority_type: MintAuthorityType,
+    new_authority: Option<Pubkey>,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let balance_account_info = next_account_info(accounts_iter)?;
+    let mint_account_info = next_account_info(accounts_iter)?;
+    let spl_token_program_account_info = next_account_info(accounts_iter)?;
+    let rent_return_account_info = next_signer_account_info(accounts_iter)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+    let fee_account_info_maybe = accounts_iter.next();
+
+    if *spl_token_program_account_info.key != spl_token::id() {
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+
+    let wallet_guid_hash =
+        &Wallet::wallet_guid_hash_from_slice(&wallet_account_info.data.borrow())?;
+
+    let bump_seed = validate_balance_account_and_get_seed(
+        balance_account_info,
+        wallet_guid_hash,
+        account_guid_hash,
+        program_id,
+    )?;
+
+    finalize_multisig_op(
+        multisig_op_account_info,
+        wallet_account_info,
+        FeeCollectionInfo {
+            rent_return_account_info,
+            fee_account_info_maybe,
+            wallet_guid_hash,
+            program_id,
+        },
+        clock,
+        MultisigOpParams::SetMintAuthority {
+            wallet_address: *wallet_account_info.key,
+            account_guid_hash: *account_guid_hash,
+            mint: *mint_account_info.key,
+            authority_type,
+            new_authority,
+        },
+        || -> ProgramResult {
+            invoke_signed(
+                &spl_instruction::set_authority(
+                    &spl_token::id(),
+                    mint_account_info.key,
+                    new_authority.as_ref(),
+                    spl_authority_type(authority_type),
+                    balance_account_info.key,
+                    &[],
+                )?,
+                &[
+                    mint_account_info.clone(),
+                    balance_account_info.clone(),
+                    spl_token_program_account_info.clone(),
+                ],
+                &[&BalanceAccountSeeds {
+                    wallet_guid_hash,
+                    account_guid_hash,
+                }
+                .signer_seeds(&[bump_seed])],
+            )
+        },
+        || -> ProgramResult { Ok(()) },
+    )
+}
+
+fn spl_authority_type(authority_type: MintAuthorityType) -> AuthorityType {
+    match authority_type {
+        MintAuthorityType::MintTokens => AuthorityType::MintTokens,
+        MintAuthorityType::FreezeAccount => AuthorityType::FreezeAccount,
+    }
+}
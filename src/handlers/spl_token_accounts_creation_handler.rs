@@ -0,0 +1,253 @@
+use crate::error::{strike_err, WalletError};
+use crate::handlers::utils::{
+    create_associated_token_account_instruction, finalize_multisig_op_in_batches,
+    get_clock_from_next_account, next_initiator_account_info, next_program_account_info,
+    next_signer_account_info, next_wallet_account_info, start_multisig_transfer_op,
+    token_program_of, validate_balance_account_and_get_seed, FeeCollectionInfo,
+};
+use crate::model::balance_account::BalanceAccountGuidHash;
+use crate::model::multisig_op::{MultisigOp, MultisigOpParams};
+use crate::model::wallet::{Wallet, WalletGuidHash};
+use crate::pda::{BalanceAccountSeeds, PdaSeeds};
+use crate::utils::associated_token_address;
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program::invoke_signed;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::system_program;
+use solana_program::sysvar::Sysvar;
+use spl_token::state::Account as SPLAccount;
+
+pub fn init(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    fee_amount: u64,
+    fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+    payer_account_guid_hash: &BalanceAccountGuidHash,
+    account_guid_hashes: &[BalanceAccountGuidHash],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let token_mint_account_info = next_account_info(accounts_iter)?;
+    let initiator_account_info =
+        next_initiator_account_info(accounts_iter, multisig_op_account_info, wallet_account_info)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+    let rent_return_account_info = next_signer_account_info(accounts_iter)?;
+
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    let payer_balance_account = wallet.get_active_balance_account(payer_account_guid_hash)?;
+    wallet.validate_transfer_initiator(initiator_account_info, clock.unix_timestamp)?;
+    token_program_of(token_mint_account_info)?;
+
+    if account_guid_hashes.is_empty()
+        || account_guid_hashes.len() > MultisigOp::MAX_SPL_TOKEN_ACCOUNTS
+        || account_guid_hashes
+            .iter()
+            .enumerate()
+            .any(|(i, guid_hash)| account_guid_hashes[..i].contains(guid_hash))
+    {
+        return Err(strike_err!(
+            "spl_token_accounts_creation_handler::init",
+            WalletError::InvalidSPLTokenAccountsCreation,
+            "Token accounts have to be created for 1 to {} distinct balance accounts",
+            MultisigOp::MAX_SPL_TOKEN_ACCOUNTS
+        ));
+    }
+    for account_guid_hash in account_guid_hashes {
+        wallet.get_active_balance_account(account_guid_hash)?;
+    }
+
+    start_multisig_transfer_op(
+        multisig_op_account_info,
+        wallet_account_info,
+        &mut wallet,
+        &payer_balance_account,
+        clock,
+        MultisigOpParams::CreateSPLTokenAccounts {
+            wallet_address: *wallet_account_info.key,
+            payer_account_guid_hash: *payer_account_guid_hash,
+            account_guid_hashes: account_guid_hashes.to_vec(),
+            token_mint: *token_mint_account_info.key,
+        },
+        None,
+        *initiator_account_info.key,
+        *rent_return_account_info.key,
+        fee_amount,
+        fee_account_guid_hash,
+        &[],
+    )
+}
+
+/// Creates a batch of the op's token accounts, for `FinalizeSPLTokenAccountsCreation` when
+/// `continuing` is false, and for `ContinueSPLTokenAccountsCreation` when it is true.
+pub fn finalize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    payer_account_guid_hash: &BalanceAccountGuidHash,
+    account_guid_hashes: &[BalanceAccountGuidHash],
+    batch_size: u8,
+    continuing: bool,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let payer_account_info = next_account_info(accounts_iter)?;
+    let token_mint_account_info = next_account_info(accounts_iter)?;
+    let token_program_account_info = next_account_info(accounts_iter)?;
+    let associated_token_program_account_info = next_account_info(accounts_iter)?;
+    let system_program_account_info = next_account_info(accounts_iter)?;
+    let rent_return_account_info = next_signer_account_info(accounts_iter)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+    let batch_account_infos = (0..usize::from(batch_size) * 2)
+        .map(|_| next_account_info(accounts_iter))
+        .collect::<Result<Vec<_>, _>>()?;
+    let fee_account_info_maybe = accounts_iter.next();
+
+    if *token_program_account_info.key != token_program_of(token_mint_account_info)?
+        || *associated_token_program_account_info.key != spl_associated_token_account::id()
+        || *system_program_account_info.key != system_program::id()
+    {
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+
+    let wallet_guid_hash =
+        &Wallet::wallet_guid_hash_from_slice(&wallet_account_info.data.borrow())?;
+    let payer_bump_seed = validate_balance_account_and_get_seed(
+        payer_account_info,
+        wallet_guid_hash,
+        payer_account_guid_hash,
+        program_id,
+    )?;
+
+    finalize_multisig_op_in_batches(
+        multisig_op_account_info,
+        wallet_account_info,
+        FeeCollectionInfo {
+            rent_return_account_info,
+            fee_account_info_maybe,
+            wallet_guid_hash,
+            program_id,
+        },
+        clock,
+        MultisigOpParams::CreateSPLTokenAccounts {
+            wallet_address: *wallet_account_info.key,
+            payer_account_guid_hash: *payer_account_guid_hash,
+            account_guid_hashes: account_guid_hashes.to_vec(),
+            token_mint: *token_mint_account_info.key,
+        },
+        continuing,
+        |multisig_op| -> Result<bool, ProgramError> {
+            // the batch is made of the next balance accounts still without a token account
+            let pending: Vec<usize> = (0..account_guid_hashes.len())
+                .filter(|index| multisig_op.spl_token_accounts_created & (1 << index) == 0)
+                .collect();
+            if usize::from(batch_size) > pending.len() {
+                return Err(strike_err!(
+                    "spl_token_accounts_creation_handler::finalize",
+                    WalletError::InvalidSPLTokenAccountsCreation,
+                    "Batch of {} is larger than the {} token accounts left to create",
+                    batch_size,
+                    pending.len()
+                ));
+            }
+            for (index, account_infos) in pending.iter().zip(batch_account_infos.chunks(2)) {
+                create_token_account(
+                    program_id,
+                    wallet_guid_hash,
+                    &account_guid_hashes[*index],
+                    account_infos[0],
+                    account_infos[1],
+                    token_mint_account_info,
+                    token_program_account_info.key,
+                    payer_account_info,
+                    &BalanceAccountSeeds {
+                        wallet_guid_hash,
+                        account_guid_hash: payer_account_guid_hash,
+                    }
+                    .signer_seeds(&[payer_bump_seed]),
+                    accounts,
+                )?;
+                multisig_op.spl_token_accounts_created |= 1 << index;
+            }
+            msg!(
+                "Created token accounts for {} of {} balance accounts",
+                multisig_op.spl_token_accounts_created.count_ones(),
+                account_guid_hashes.len()
+            );
+            Ok(pending.len() == usize::from(batch_size))
+        },
+        || -> ProgramResult { Ok(()) },
+    )
+}
+
+/// Creates the balance account's associated token account for the mint, paid for by the payer,
+/// unless it already exists.
+#[allow(clippy::too_many_arguments)]
+fn create_token_account(
+    program_id: &Pubkey,
+    wallet_guid_hash: &WalletGuidHash,
+    account_guid_hash: &BalanceAccountGuidHash,
+    balance_account_info: &AccountInfo,
+    token_account_info: &AccountInfo,
+    token_mint_account_info: &AccountInfo,
+    token_program_id: &Pubkey,
+    payer_account_info: &AccountInfo,
+    payer_signer_seeds: &[&[u8]],
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    validate_balance_account_and_get_seed(
+        balance_account_info,
+        wallet_guid_hash,
+        account_guid_hash,
+        program_id,
+    )?;
+    if *token_account_info.key
+        != associated_token_address(
+            balance_account_info.key,
+            token_mint_account_info.key,
+            token_program_id,
+        )
+    {
+        return Err(strike_err!(
+            "spl_token_accounts_creation_handler::create_token_account",
+            WalletError::AccountNotRecognized,
+            "{} is not the associated token account of {}",
+            token_account_info.key,
+            balance_account_info.key
+        ));
+    }
+    if !token_account_info.data_is_empty() {
+        // only the associated token program can have created an account at its address
+        return Ok(());
+    }
+
+    let rent = Rent::get()?;
+    let required_lamports = rent
+        .minimum_balance(SPLAccount::LEN)
+        .saturating_add(rent.minimum_balance(0));
+    if payer_account_info.lamports() < required_lamports {
+        return Err(strike_err!(
+            "spl_token_accounts_creation_handler::create_token_account",
+            WalletError::InsufficientBalance,
+            "Payer has {} lamports, short of the {} needed to fund a token account",
+            payer_account_info.lamports(),
+            required_lamports
+        ));
+    }
+    invoke_signed(
+        &create_associated_token_account_instruction(
+            payer_account_info,
+            token_account_info,
+            balance_account_info,
+            token_mint_account_info,
+            token_program_id,
+        ),
+        accounts,
+        &[payer_signer_seeds],
+    )
+}
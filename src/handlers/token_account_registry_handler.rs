@@ -0,0 +1,92 @@
+use crate::error::{strike_err, WalletError};
+use crate::handlers::utils::{
+    create_pda_account, next_signer_account_info, next_wallet_account_info,
+    validate_balance_account_and_get_seed,
+};
+use crate::model::balance_account::BalanceAccountGuidHash;
+use crate::model::token_account_registry::{RegisteredTokenAccount, TokenAccountRegistry};
+use crate::model::wallet::Wallet;
+use crate::pda::{self, TokenAccountRegistrySeeds};
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::Sysvar;
+use spl_token_2022::extension::StateWithExtensions;
+use spl_token_2022::state::Account as TokenAccount;
+
+pub fn register(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    account_guid_hash: &BalanceAccountGuidHash,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let registry_account_info = next_account_info(accounts_iter)?;
+    let balance_account_info = next_account_info(accounts_iter)?;
+    let initiator_account_info = next_signer_account_info(accounts_iter)?;
+    let payer_account_info = next_signer_account_info(accounts_iter)?;
+    let system_program_account = next_account_info(accounts_iter)?;
+
+    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    wallet.validate_transfer_initiator(initiator_account_info, Clock::get()?.unix_timestamp)?;
+    wallet.get_balance_account(account_guid_hash)?;
+    validate_balance_account_and_get_seed(
+        balance_account_info,
+        &wallet.wallet_guid_hash,
+        account_guid_hash,
+        program_id,
+    )?;
+
+    let seeds = TokenAccountRegistrySeeds {
+        wallet: wallet_account_info.key,
+        account_guid_hash,
+    };
+    let bump_seed = pda::verify(program_id, &seeds, registry_account_info.key, None)?;
+
+    let mut registry = if registry_account_info.owner == program_id {
+        TokenAccountRegistry::unpack(&registry_account_info.data.borrow())?
+    } else {
+        create_pda_account(
+            payer_account_info,
+            registry_account_info,
+            system_program_account,
+            TokenAccountRegistry::LEN,
+            program_id,
+            &seeds,
+            bump_seed,
+        )?;
+        TokenAccountRegistry::new(*wallet_account_info.key, *account_guid_hash)
+    };
+
+    for token_account_info in accounts_iter {
+        if *token_account_info.owner != spl_token::id()
+            && *token_account_info.owner != spl_token_2022::id()
+        {
+            return Err(strike_err!(
+                "token_account_registry_handler::register",
+                WalletError::InvalidTokenAccount,
+                "Token account {} isn't owned by a token program",
+                token_account_info.key
+            ));
+        }
+        let token_account =
+            StateWithExtensions::<TokenAccount>::unpack(&token_account_info.data.borrow())?.base;
+        if token_account.owner != *balance_account_info.key {
+            return Err(strike_err!(
+                "token_account_registry_handler::register",
+                WalletError::InvalidTokenAccount,
+                "Token account {} isn't owned by the balance account",
+                token_account_info.key
+            ));
+        }
+        registry.register(RegisteredTokenAccount {
+            token_mint: token_account.mint,
+            token_account: *token_account_info.key,
+            frozen: token_account.is_frozen(),
+        })?;
+    }
+
+    TokenAccountRegistry::pack(registry, &mut registry_account_info.data.borrow_mut())
+}
@@ -1,122 +1,149 @@
-use crate::constants::PUBKEY_BYTES;
-use crate::error::WalletError;
+use crate::constants::{spl_memo, PUBKEY_BYTES};
+use crate::error::{strike_err, WalletError};
 use crate::handlers::utils::{
-    create_associated_token_account_instruction, finalize_multisig_op, get_clock_from_next_account,
+    collect_remaining_balance, create_associated_token_account_instruction, create_pda_account,
+    finalize_multisig_op, finalize_multisig_op_in_batches, frozen_token_account,
+    get_clock_from_next_account, log_fee_deduction, next_initiator_account_info,
     next_program_account_info, next_signer_account_info, next_wallet_account_info,
-    start_multisig_transfer_op, transfer_sol_checked, validate_balance_account_and_get_seed,
+    quarantine_if_frozen, start_multisig_transfer_op, token_program_of,
+    transfer_checked_instruction, transfer_sol_checked, validate_balance_account_and_get_seed,
     FeeCollectionInfo,
 };
-use crate::model::address_book::AddressBookEntryNameHash;
+use crate::instruction::TransferFinalizeStep;
+use crate::model::address_book::{AddressBookEntry, AddressBookEntryNameHash, JurisdictionCode};
 use crate::model::balance_account::BalanceAccountGuidHash;
-use crate::model::multisig_op::MultisigOpParams;
+use crate::model::destination_receipt::DestinationReceipt;
+use crate::model::internal_transfer_handshake::InternalTransferHandshake;
+use crate::model::merkle_address_book::MerkleAddressBook;
+use crate::model::multisig_op::{MultisigOp, MultisigOpParams};
 use crate::model::wallet::Wallet;
+use crate::pda::{
+    self, BalanceAccountSeeds, DestinationReceiptSeeds, InternalTransferHandshakeSeeds, PdaSeeds,
+};
+use crate::utils::associated_token_address;
+use crate::version::{Versioned, VERSION};
 use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::clock::Clock;
 use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::{hash, Hash};
+use solana_program::instruction::Instruction;
 use solana_program::msg;
 use solana_program::program::{invoke, invoke_signed};
 use solana_program::program_error::ProgramError;
 use solana_program::program_pack::Pack;
 use solana_program::pubkey::Pubkey;
 use solana_program::rent::Rent;
-use solana_program::system_program;
 use solana_program::sysvar::Sysvar;
-use spl_associated_token_account::get_associated_token_address;
-use spl_token::id as SPL_TOKEN_ID;
-use spl_token::instruction as spl_instruction;
+use solana_program::{system_instruction, system_program};
 use spl_token::state::Account as SPLAccount;
+use spl_token_2022::extension::StateWithExtensions;
+use spl_token_2022::state::Account as TokenAccount;
+
+/// How the finalize submitter of a transfer is reimbursed.
+pub struct TransferFee {
+    pub amount: u64,
+    pub account_guid_hash: Option<BalanceAccountGuidHash>,
+    /// When set, the fee is paid out of the source balance account of a SOL transfer: out of
+    /// the transferred amount if approved, and directly otherwise.
+    pub deduct_from_amount: bool,
+}
 
+#[allow(clippy::too_many_arguments)]
 pub fn init(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    fee_amount: u64,
-    fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+    fee: TransferFee,
     account_guid_hash: &BalanceAccountGuidHash,
     amount: u64,
     destination_name_hash: &AddressBookEntryNameHash,
+    destination_acknowledgement_required: bool,
+    allow_destination_ata_creation: bool,
+    memo_hash: Option<Hash>,
+    destination_proof: &Option<Vec<Hash>>,
 ) -> ProgramResult {
+    let TransferFee {
+        amount: fee_amount,
+        account_guid_hash: fee_account_guid_hash,
+        deduct_from_amount: deduct_fee_from_amount,
+    } = fee;
     let accounts_iter = &mut accounts.iter();
     let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
     let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
-    let source_account = next_account_info(accounts_iter)?;
+    // the source account, which only the finalize moves anything out of
+    next_account_info(accounts_iter)?;
     let destination_account = next_account_info(accounts_iter)?;
-    let initiator_account_info = next_account_info(accounts_iter)?;
+    let initiator_account_info =
+        next_initiator_account_info(accounts_iter, multisig_op_account_info, wallet_account_info)?;
     let clock = get_clock_from_next_account(accounts_iter)?;
     let rent_return_account_info = next_signer_account_info(accounts_iter)?;
     let token_mint = next_account_info(accounts_iter)?;
-    let destination_token_account = next_account_info(accounts_iter)?;
 
-    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
-    let balance_account = wallet.get_balance_account(account_guid_hash)?;
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    wallet.validate_not_frozen()?;
+    let balance_account = wallet.get_active_balance_account(account_guid_hash)?;
 
     if !wallet.destination_allowed(
         &balance_account,
         destination_account.key,
         destination_name_hash,
+        clock.unix_timestamp,
+    )? && !destination_proven(
+        program_id,
+        wallet_account_info.key,
+        account_guid_hash,
+        &AddressBookEntry {
+            address: *destination_account.key,
+            name_hash: *destination_name_hash,
+        },
+        destination_proof,
+        accounts,
     )? {
-        msg!("Destination account is not whitelisted");
-        return Err(WalletError::DestinationNotAllowed.into());
+        return Err(strike_err!(
+            "transfer_handler::init",
+            WalletError::DestinationNotAllowed,
+            "Destination account is not whitelisted"
+        ));
     }
 
-    wallet.validate_transfer_initiator(initiator_account_info)?;
-
-    if *token_mint.key != Pubkey::default() && *destination_token_account.owner == Pubkey::default()
-    {
-        // We need to create the associated token "destination" account. If it had
-        // been created already, it would be owned by the associated token program.
+    wallet.validate_transfer_initiator(initiator_account_info, clock.unix_timestamp)?;
 
-        // frst check if the source account has sufficient funds to create it
-        let rent = Rent::get()?;
-        if rent.is_exempt(source_account.lamports(), SPLAccount::LEN) {
-            match validate_balance_account_and_get_seed(
-                source_account,
-                &wallet.wallet_guid_hash,
-                account_guid_hash,
-                program_id,
-            ) {
-                Ok(bump_seed) => {
-                    // pay for associated token account with source account
-                    invoke_signed(
-                        &create_associated_token_account_instruction(
-                            source_account,
-                            destination_token_account,
-                            destination_account,
-                            token_mint,
-                        ),
-                        accounts,
-                        &[&[
-                            wallet.wallet_guid_hash.to_bytes(),
-                            account_guid_hash.to_bytes(),
-                            &[bump_seed],
-                        ]],
-                    )?;
-                }
-                Err(error) => {
-                    return if error == WalletError::InvalidPDA.into() {
-                        msg!("could not find BalanceAccount PDA for source GUID hash");
-                        Err(WalletError::InvalidSourceAccount.into())
-                    } else {
-                        msg!("unhandled error validating source BalanceAccount GUID hash");
-                        Err(ProgramError::InvalidArgument)
-                    }
-                }
-            }
-        } else {
-            // pay for associated token account with fee-payer account.
-            invoke(
-                &create_associated_token_account_instruction(
-                    rent_return_account_info,
-                    destination_token_account,
-                    destination_account,
-                    token_mint,
-                ),
-                accounts,
-            )?;
+    if deduct_fee_from_amount {
+        // the fee reimburses the finalize submitter in lamports, so it can only be taken out
+        // of a SOL transfer, and replaces collection from a separate fee account
+        if *token_mint.key != Pubkey::default() {
+            return Err(strike_err!(
+                "transfer_handler::init",
+                WalletError::InvalidFeeDeduction,
+                "Fee can only be deducted from SOL transfers"
+            ));
+        }
+        if fee_account_guid_hash.is_some() {
+            return Err(strike_err!(
+                "transfer_handler::init",
+                WalletError::InvalidFeeDeduction,
+                "Fee can't be deducted from the amount when a fee account is given"
+            ));
+        }
+        if fee_amount > amount {
+            return Err(strike_err!(
+                "transfer_handler::init",
+                WalletError::InvalidFeeDeduction,
+                "Fee {} can't exceed the transfer amount {}",
+                fee_amount,
+                amount
+            ));
         }
     }
 
+    let destination_used =
+        wallet.record_destination_use(destination_account.key, destination_name_hash, clock.slot);
+
+    let destination_jurisdiction =
+        wallet.destination_jurisdiction(destination_account.key, destination_name_hash);
     start_multisig_transfer_op(
         &multisig_op_account_info,
-        &wallet,
+        wallet_account_info,
+        &mut wallet,
         &balance_account,
         clock,
         MultisigOpParams::Transfer {
@@ -125,20 +152,68 @@ pub fn init(
             destination: *destination_account.key,
             amount,
             token_mint: *token_mint.key,
+            destination_acknowledgement_required,
+            deduct_fee_from_amount,
+            memo_hash,
+            allow_destination_ata_creation,
         },
+        destination_jurisdiction,
         *initiator_account_info.key,
         *rent_return_account_info.key,
         fee_amount,
         fee_account_guid_hash,
-    )
+        accounts,
+    )?;
+
+    if destination_used {
+        Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+    }
+    Ok(())
+}
+
+/// Whether `destination_proof` proves the destination is in the Merkle address book of the
+/// balance account, whose account is looked up among `accounts`.
+fn destination_proven(
+    program_id: &Pubkey,
+    wallet_address: &Pubkey,
+    account_guid_hash: &BalanceAccountGuidHash,
+    destination: &AddressBookEntry,
+    destination_proof: &Option<Vec<Hash>>,
+    accounts: &[AccountInfo],
+) -> Result<bool, ProgramError> {
+    let proof = match destination_proof {
+        Some(proof) => proof,
+        None => return Ok(false),
+    };
+    let (address, _) =
+        MerkleAddressBook::find_address(wallet_address, account_guid_hash, program_id);
+    match accounts
+        .iter()
+        .find(|account| *account.key == address && account.owner == program_id)
+    {
+        Some(address_book_account_info) => Ok(MerkleAddressBook::unpack(
+            &address_book_account_info.data.borrow(),
+        )?
+        .contains(destination, proof)),
+        None => {
+            msg!("Merkle address book account is missing");
+            Ok(false)
+        }
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn finalize(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     account_guid_hash: &BalanceAccountGuidHash,
     amount: u64,
     token_mint: Pubkey,
+    destination_acknowledgement_required: bool,
+    deduct_fee_from_amount: bool,
+    allow_destination_ata_creation: bool,
+    memo: &Option<Vec<u8>>,
+    step: TransferFinalizeStep,
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
     let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
@@ -148,6 +223,9 @@ pub fn finalize(
     let system_program_account = next_account_info(accounts_iter)?;
     let rent_return_account_info = next_signer_account_info(accounts_iter)?;
     let clock = get_clock_from_next_account(accounts_iter)?;
+    let receipt_account_info = next_account_info(accounts_iter)?;
+    let slot = clock.slot;
+    let epoch = clock.epoch;
 
     let is_spl = token_mint.to_bytes() != [0; PUBKEY_BYTES];
     let source_token_account = if is_spl {
@@ -165,7 +243,22 @@ pub fn finalize(
     } else {
         None
     };
-    let token_mint_authority = if is_spl {
+    let token_mint_account_info = if is_spl {
+        Some(next_account_info(accounts_iter)?)
+    } else {
+        None
+    };
+    let associated_token_program_account_info = if is_spl && allow_destination_ata_creation {
+        Some(next_account_info(accounts_iter)?)
+    } else {
+        None
+    };
+    let handshake_account_info = if destination_acknowledgement_required {
+        Some(next_account_info(accounts_iter)?)
+    } else {
+        None
+    };
+    let memo_program_account_info = if memo.is_some() {
         Some(next_account_info(accounts_iter)?)
     } else {
         None
@@ -176,6 +269,16 @@ pub fn finalize(
     if system_program_account.key != &system_program::id() {
         return Err(WalletError::AccountNotRecognized.into());
     }
+    if let Some(memo_program_account_info) = memo_program_account_info {
+        if *memo_program_account_info.key != spl_memo::id() {
+            return Err(WalletError::AccountNotRecognized.into());
+        }
+    }
+    if let Some(associated_token_program_account_info) = associated_token_program_account_info {
+        if *associated_token_program_account_info.key != spl_associated_token_account::id() {
+            return Err(WalletError::AccountNotRecognized.into());
+        }
+    }
 
     let wallet_guid_hash =
         &Wallet::wallet_guid_hash_from_slice(&wallet_account_info.data.borrow())?;
@@ -187,8 +290,98 @@ pub fn finalize(
         program_id,
     )?;
 
-    finalize_multisig_op(
-        &multisig_op_account_info,
+    let receipt_bump_seed = pda::verify(
+        program_id,
+        &DestinationReceiptSeeds {
+            wallet: wallet_account_info.key,
+            destination: destination_account.key,
+            token_mint: &token_mint,
+        },
+        receipt_account_info.key,
+        None,
+    )?;
+
+    let receipt_accounts = DestinationReceiptAccounts {
+        receipt_account_info,
+        bump_seed: receipt_bump_seed,
+        payer_account_info: rent_return_account_info,
+        system_program_account,
+    };
+
+    if let Some(handshake_account_info) = handshake_account_info {
+        pda::verify(
+            program_id,
+            &InternalTransferHandshakeSeeds {
+                multisig_op: multisig_op_account_info.key,
+                destination: destination_account.key,
+            },
+            handshake_account_info.key,
+            None,
+        )?;
+    }
+
+    let expected_params = MultisigOpParams::Transfer {
+        wallet_address: *wallet_account_info.key,
+        account_guid_hash: *account_guid_hash,
+        destination: *destination_account.key,
+        amount,
+        token_mint,
+        destination_acknowledgement_required,
+        deduct_fee_from_amount,
+        memo_hash: memo.as_ref().map(|memo| hash(memo)),
+        allow_destination_ata_creation,
+    };
+
+    approve_within_spending_limit(
+        multisig_op_account_info,
+        wallet_account_info,
+        &clock,
+        &expected_params,
+        account_guid_hash,
+        &token_mint,
+        amount,
+    )?;
+
+    // the token accounts are validated up front, so that only the transfer's own accounts
+    // being frozen can quarantine it
+    let token_program_id = if is_spl {
+        let token_mint_account_info = token_mint_account_info.unwrap();
+        if *token_mint_account_info.key != token_mint {
+            return Err(WalletError::InvalidTokenMintAccount.into());
+        }
+        let token_program_id = token_program_of(token_mint_account_info)?;
+        if *spl_token_program.unwrap().key != token_program_id {
+            return Err(WalletError::AccountNotRecognized.into());
+        }
+        if *source_token_account.unwrap().key
+            != associated_token_address(source_account.key, &token_mint, &token_program_id)
+        {
+            return Err(WalletError::InvalidSourceTokenAccount.into());
+        }
+        if *destination_token_account.unwrap().key
+            != associated_token_address(destination_account.key, &token_mint, &token_program_id)
+        {
+            return Err(WalletError::InvalidDestinationTokenAccount.into());
+        }
+        if quarantine_if_frozen(
+            multisig_op_account_info,
+            &clock,
+            &expected_params,
+            &[
+                source_token_account.unwrap(),
+                destination_token_account.unwrap(),
+            ],
+        )? {
+            return Ok(());
+        }
+        Some(token_program_id)
+    } else {
+        None
+    };
+
+    finalize_multisig_op_in_batches(
+        multisig_op_account_info,
+        wallet_account_info,
         FeeCollectionInfo {
             rent_return_account_info,
             fee_account_info_maybe,
@@ -196,73 +389,591 @@ pub fn finalize(
             program_id,
         },
         clock,
-        MultisigOpParams::Transfer {
-            wallet_address: *wallet_account_info.key,
-            account_guid_hash: *account_guid_hash,
-            destination: *destination_account.key,
-            amount,
-            token_mint,
-        },
-        || -> ProgramResult {
+        expected_params,
+        step == TransferFinalizeStep::Execute,
+        |multisig_op| -> Result<bool, ProgramError> {
+            if step == TransferFinalizeStep::Verify {
+                msg!("Transfer verified, and left to be executed");
+                return Ok(false);
+            }
+            // as the destination was classified when the transfer was initiated
+            let jurisdiction = multisig_op.destination_jurisdiction;
+            if let Some(handshake_account_info) = handshake_account_info {
+                if handshake_account_info.owner != program_id
+                    || handshake_account_info.data_is_empty()
+                {
+                    return Err(strike_err!(
+                        "transfer_handler::finalize",
+                        WalletError::DestinationNotAcknowledged,
+                        "Internal transfer has not been acknowledged"
+                    ));
+                }
+                let handshake =
+                    InternalTransferHandshake::unpack(&handshake_account_info.data.borrow())?;
+                if handshake.multisig_op != *multisig_op_account_info.key
+                    || handshake.destination != *destination_account.key
+                    || Some(handshake.params_hash) != multisig_op.params_hash
+                {
+                    return Err(strike_err!(
+                        "transfer_handler::finalize",
+                        WalletError::DestinationNotAcknowledged,
+                        "Internal transfer acknowledgement does not match this transfer"
+                    ));
+                }
+            }
+            if let Some(memo) = memo {
+                invoke(
+                    &Instruction {
+                        program_id: spl_memo::id(),
+                        accounts: vec![],
+                        data: memo.clone(),
+                    },
+                    &[memo_program_account_info.unwrap().clone()],
+                )?;
+            }
             if is_spl {
-                let source_token_account_key =
-                    get_associated_token_address(source_account.key, &token_mint);
-                if *source_token_account.unwrap().key != source_token_account_key {
-                    return Err(WalletError::InvalidSourceTokenAccount.into());
+                let token_mint_account_info = token_mint_account_info.unwrap();
+                let token_program_id = token_program_id.unwrap();
+                // a transfer verified before its token accounts were frozen can't be
+                // quarantined, and fails to execute until they are thawed
+                if let Some(token_account_info) = frozen_token_account(&[
+                    source_token_account.unwrap(),
+                    destination_token_account.unwrap(),
+                ])? {
+                    return Err(strike_err!(
+                        "transfer_handler::finalize",
+                        WalletError::TokenAccountFrozen,
+                        "Token account {} is frozen",
+                        token_account_info.key
+                    ));
                 }
-                let source_token_account_data =
-                    SPLAccount::unpack(&source_token_account.unwrap().data.borrow())?;
-                if source_token_account_data.amount < amount {
-                    msg!(
+                let source_token_account_key = *source_token_account.unwrap().key;
+                let source_token_amount = StateWithExtensions::<TokenAccount>::unpack(
+                    &source_token_account.unwrap().data.borrow(),
+                )?
+                .base
+                .amount;
+                if source_token_amount < amount {
+                    return Err(strike_err!(
+                        "transfer_handler::finalize",
+                        WalletError::InsufficientBalance,
                         "Source token account only has {} tokens of {} requested",
-                        source_token_account_data.amount,
+                        source_token_amount,
                         amount
-                    );
-                    return Err(WalletError::InsufficientBalance.into());
+                    ));
                 }
-                let destination_token_account_key =
-                    get_associated_token_address(&destination_account.key, &token_mint);
-                if *destination_token_account.unwrap().key != destination_token_account_key {
-                    return Err(WalletError::InvalidDestinationTokenAccount.into());
+                let destination_token_account_key = *destination_token_account.unwrap().key;
+                if destination_token_account.unwrap().data_is_empty() {
+                    if !allow_destination_ata_creation {
+                        return Err(strike_err!(
+                            "transfer_handler::finalize",
+                            WalletError::DestinationTokenAccountCreationNotAllowed,
+                            "Destination token account doesn't exist"
+                        ));
+                    }
+                    create_destination_token_account(
+                        multisig_op,
+                        source_account,
+                        rent_return_account_info,
+                        destination_token_account.unwrap(),
+                        destination_account,
+                        token_mint_account_info,
+                        &token_program_id,
+                        &BalanceAccountSeeds {
+                            wallet_guid_hash,
+                            account_guid_hash,
+                        }
+                        .signer_seeds(&[bump_seed]),
+                        accounts,
+                    )?;
                 }
 
+                let (transfer_instruction, transfer_fee) = transfer_checked_instruction(
+                    token_mint_account_info,
+                    &source_token_account_key,
+                    &destination_token_account_key,
+                    source_account.key,
+                    amount,
+                    epoch,
+                )?;
+                if transfer_fee > 0 {
+                    msg!("Token transfer fee withheld: {}", transfer_fee);
+                }
                 invoke_signed(
-                    &spl_instruction::transfer(
-                        &SPL_TOKEN_ID(),
-                        &source_token_account_key,
-                        &destination_token_account_key,
-                        source_account.key,
-                        &[],
-                        amount,
-                    )?,
+                    &transfer_instruction,
                     &[
                         source_token_account.unwrap().clone(),
                         destination_token_account.unwrap().clone(),
                         source_account.clone(),
                         destination_account.clone(),
-                        token_mint_authority.unwrap().clone(),
+                        token_mint_account_info.clone(),
                         spl_token_program.unwrap().clone(),
                     ],
-                    &[&[
-                        Wallet::wallet_guid_hash_from_slice(&wallet_account_info.data.borrow())?
-                            .to_bytes(),
-                        account_guid_hash.to_bytes(),
-                        &[bump_seed],
-                    ]],
+                    &[&BalanceAccountSeeds {
+                        wallet_guid_hash: &Wallet::wallet_guid_hash_from_slice(
+                            &wallet_account_info.data.borrow(),
+                        )?,
+                        account_guid_hash,
+                    }
+                    .signer_seeds(&[bump_seed])],
+                )?;
+                record_destination_receipt(
+                    program_id,
+                    &receipt_accounts,
+                    wallet_account_info.key,
+                    destination_account.key,
+                    &token_mint,
+                    amount,
+                    0,
+                    slot,
+                    jurisdiction,
                 )?;
-                Ok(())
             } else {
-                return transfer_sol_checked(
+                let balance_account = Wallet::unpack(&wallet_account_info.data.borrow())?
+                    .get_balance_account(account_guid_hash)?;
+                if balance_account.requires_system_owned_destinations()
+                    && *destination_account.owner != system_program::id()
+                {
+                    return Err(strike_err!(
+                        "transfer_handler::finalize",
+                        WalletError::DestinationNotSystemOwned,
+                        "Destination is owned by {}",
+                        destination_account.owner
+                    ));
+                }
+                let fee_amount = if deduct_fee_from_amount {
+                    multisig_op.fee_amount
+                } else {
+                    0
+                };
+                let amount_after_fee = amount
+                    .checked_sub(fee_amount)
+                    .ok_or(WalletError::AmountOverflow)?;
+                transfer_sol_checked(
                     wallet_guid_hash,
                     source_account.clone(),
                     account_guid_hash,
                     bump_seed,
                     system_program_account.clone(),
                     destination_account.clone(),
-                    amount,
-                );
+                    amount_after_fee,
+                )?;
+                if fee_amount > 0 {
+                    transfer_sol_checked(
+                        wallet_guid_hash,
+                        source_account.clone(),
+                        account_guid_hash,
+                        bump_seed,
+                        system_program_account.clone(),
+                        rent_return_account_info.clone(),
+                        fee_amount,
+                    )?;
+                    log_fee_deduction(fee_amount);
+                }
+                record_destination_receipt(
+                    program_id,
+                    &receipt_accounts,
+                    wallet_account_info.key,
+                    destination_account.key,
+                    &token_mint,
+                    amount_after_fee,
+                    fee_amount,
+                    slot,
+                    jurisdiction,
+                )?;
             }
+            Ok(true)
         },
+        // nothing was transferred, so there is nothing to deduct a fee from
         || -> ProgramResult { Ok(()) },
+    )?;
+
+    // the handshake is kept for the execution of a verified transfer
+    if let Some(handshake_account_info) = handshake_account_info {
+        if handshake_account_info.owner == program_id && multisig_op_account_info.data_is_empty() {
+            collect_remaining_balance(handshake_account_info, rent_return_account_info)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn cancel(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    account_guid_hash: &BalanceAccountGuidHash,
+    amount: u64,
+    token_mint: Pubkey,
+    destination_acknowledgement_required: bool,
+    deduct_fee_from_amount: bool,
+    allow_destination_ata_creation: bool,
+    memo: &Option<Vec<u8>>,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let destination_account = next_account_info(accounts_iter)?;
+    let approver_account_info = next_signer_account_info(accounts_iter)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+
+    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    if !wallet
+        .get_active_config_approvers_keys(clock.unix_timestamp)
+        .contains(approver_account_info.key)
+    {
+        return Err(strike_err!(
+            "transfer_handler::cancel",
+            WalletError::InvalidApprover,
+            "Only a config approver can cancel a transfer"
+        ));
+    }
+
+    if MultisigOp::version_from_slice(&multisig_op_account_info.data.borrow())? != VERSION {
+        return Err(WalletError::OperationVersionMismatch.into());
+    }
+    let mut multisig_op = MultisigOp::unpack_sized(&multisig_op_account_info.data.borrow())?;
+    let expected_params = MultisigOpParams::Transfer {
+        wallet_address: *wallet_account_info.key,
+        account_guid_hash: *account_guid_hash,
+        destination: *destination_account.key,
+        amount,
+        token_mint,
+        destination_acknowledgement_required,
+        deduct_fee_from_amount,
+        memo_hash: memo.as_ref().map(|memo| hash(memo)),
+        allow_destination_ata_creation,
+    };
+    if multisig_op.params_hash != Some(expected_params.hash(&multisig_op)) {
+        return Err(WalletError::InvalidSignature.into());
+    }
+
+    multisig_op.cancel(&clock)?;
+    MultisigOp::pack_sized(multisig_op, &mut multisig_op_account_info.data.borrow_mut())?;
+
+    Ok(())
+}
+
+pub fn init_committed(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    fee_amount: u64,
+    fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+    account_guid_hash: &BalanceAccountGuidHash,
+    amount: u64,
+    destination_commitment: &Hash,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let initiator_account_info =
+        next_initiator_account_info(accounts_iter, multisig_op_account_info, wallet_account_info)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+    let rent_return_account_info = next_signer_account_info(accounts_iter)?;
+
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    wallet.validate_not_frozen()?;
+    let balance_account = wallet.get_active_balance_account(account_guid_hash)?;
+    wallet.validate_transfer_initiator(initiator_account_info, clock.unix_timestamp)?;
+
+    // the destination is checked against the whitelist once it is revealed at finalize
+    start_multisig_transfer_op(
+        multisig_op_account_info,
+        wallet_account_info,
+        &mut wallet,
+        &balance_account,
+        clock,
+        MultisigOpParams::CommittedTransfer {
+            wallet_address: *wallet_account_info.key,
+            account_guid_hash: *account_guid_hash,
+            amount,
+            destination_commitment: *destination_commitment,
+        },
+        None,
+        *initiator_account_info.key,
+        *rent_return_account_info.key,
+        fee_amount,
+        fee_account_guid_hash,
+        accounts,
     )
 }
+
+/// Approves a pending transfer on its initiator's approval alone if it fits within what
+/// remains of the spending limit of its balance account, recording it against the limit.
+/// Anything else is left for the approval quorum.
+fn approve_within_spending_limit(
+    multisig_op_account_info: &AccountInfo,
+    wallet_account_info: &AccountInfo,
+    clock: &Clock,
+    expected_params: &MultisigOpParams,
+    account_guid_hash: &BalanceAccountGuidHash,
+    token_mint: &Pubkey,
+    amount: u64,
+) -> ProgramResult {
+    if MultisigOp::version_from_slice(&multisig_op_account_info.data.borrow())? != VERSION {
+        return Ok(());
+    }
+    let mut multisig_op = MultisigOp::unpack_sized(&multisig_op_account_info.data.borrow())?;
+    if multisig_op.params_hash != Some(expected_params.hash(&multisig_op))
+        || !multisig_op.approve_by_initiator(clock)?
+    {
+        return Ok(());
+    }
+
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    if !wallet.record_limited_spend(account_guid_hash, token_mint, amount, clock.unix_timestamp)? {
+        return Ok(());
+    }
+    msg!("Transfer is within the spending limit of its balance account");
+    Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+    MultisigOp::pack_sized(multisig_op, &mut multisig_op_account_info.data.borrow_mut())
+}
+
+pub fn finalize_committed(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    account_guid_hash: &BalanceAccountGuidHash,
+    amount: u64,
+    destination_commitment: &Hash,
+    destination_name_hash: &AddressBookEntryNameHash,
+    salt: &Hash,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let source_account = next_account_info(accounts_iter)?;
+    let destination_account = next_account_info(accounts_iter)?;
+    let system_program_account = next_account_info(accounts_iter)?;
+    let rent_return_account_info = next_signer_account_info(accounts_iter)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+    let receipt_account_info = next_account_info(accounts_iter)?;
+    let fee_account_info_maybe = accounts_iter.next();
+    let slot = clock.slot;
+    let now = clock.unix_timestamp;
+
+    if system_program_account.key != &system_program::id() {
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+
+    let wallet_guid_hash =
+        &Wallet::wallet_guid_hash_from_slice(&wallet_account_info.data.borrow())?;
+
+    let bump_seed = validate_balance_account_and_get_seed(
+        source_account,
+        wallet_guid_hash,
+        account_guid_hash,
+        program_id,
+    )?;
+
+    let receipt_bump_seed = pda::verify(
+        program_id,
+        &DestinationReceiptSeeds {
+            wallet: wallet_account_info.key,
+            destination: destination_account.key,
+            token_mint: &Pubkey::default(),
+        },
+        receipt_account_info.key,
+        None,
+    )?;
+
+    finalize_multisig_op(
+        multisig_op_account_info,
+        wallet_account_info,
+        FeeCollectionInfo {
+            rent_return_account_info,
+            fee_account_info_maybe,
+            wallet_guid_hash,
+            program_id,
+        },
+        clock,
+        MultisigOpParams::CommittedTransfer {
+            wallet_address: *wallet_account_info.key,
+            account_guid_hash: *account_guid_hash,
+            amount,
+            destination_commitment: *destination_commitment,
+        },
+        || -> ProgramResult {
+            let destination = AddressBookEntry {
+                address: *destination_account.key,
+                name_hash: *destination_name_hash,
+            };
+            if destination.commitment(salt) != *destination_commitment {
+                return Err(strike_err!(
+                    "transfer_handler::finalize_committed",
+                    WalletError::DestinationCommitmentMismatch,
+                    "Destination {} does not match the transfer's commitment",
+                    destination_account.key
+                ));
+            }
+            let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+            let balance_account = wallet.get_balance_account(account_guid_hash)?;
+            if !wallet.destination_allowed(
+                &balance_account,
+                destination_account.key,
+                destination_name_hash,
+                now,
+            )? {
+                return Err(strike_err!(
+                    "transfer_handler::finalize_committed",
+                    WalletError::DestinationNotAllowed,
+                    "Destination account is not whitelisted"
+                ));
+            }
+            if wallet
+                .destination_jurisdiction(destination_account.key, destination_name_hash)
+                .is_some()
+            {
+                return Err(strike_err!(
+                    "transfer_handler::finalize_committed",
+                    WalletError::DestinationNotAllowed,
+                    "High-risk destinations can't be transferred to by commitment"
+                ));
+            }
+
+            transfer_sol_checked(
+                wallet_guid_hash,
+                source_account.clone(),
+                account_guid_hash,
+                bump_seed,
+                system_program_account.clone(),
+                destination_account.clone(),
+                amount,
+            )?;
+            if wallet.record_destination_use(destination_account.key, destination_name_hash, slot) {
+                Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+            }
+            record_destination_receipt(
+                program_id,
+                &DestinationReceiptAccounts {
+                    receipt_account_info,
+                    bump_seed: receipt_bump_seed,
+                    payer_account_info: rent_return_account_info,
+                    system_program_account,
+                },
+                wallet_account_info.key,
+                destination_account.key,
+                &Pubkey::default(),
+                amount,
+                0,
+                slot,
+                None,
+            )
+        },
+        || -> ProgramResult { Ok(()) },
+    )
+}
+
+struct DestinationReceiptAccounts<'a, 'b> {
+    receipt_account_info: &'a AccountInfo<'b>,
+    bump_seed: u8,
+    payer_account_info: &'a AccountInfo<'b>,
+    system_program_account: &'a AccountInfo<'b>,
+}
+
+/// Adds a finalized transfer to the running totals of its destination, creating the receipt
+/// account on the first transfer.
+#[allow(clippy::too_many_arguments)]
+fn record_destination_receipt(
+    program_id: &Pubkey,
+    accounts: &DestinationReceiptAccounts,
+    wallet: &Pubkey,
+    destination: &Pubkey,
+    token_mint: &Pubkey,
+    amount: u64,
+    deducted_fee: u64,
+    slot: u64,
+    jurisdiction: Option<JurisdictionCode>,
+) -> ProgramResult {
+    let receipt_account_info = accounts.receipt_account_info;
+    let mut receipt = if receipt_account_info.owner == program_id {
+        if receipt_account_info.data_len() == DestinationReceipt::LEN_WITHOUT_JURISDICTION
+            || receipt_account_info.data_len() == DestinationReceipt::LEN_WITHOUT_DEDUCTED_FEE
+        {
+            // receipts created before jurisdictions or deducted fees were recorded are grown
+            // to make room, the new fields starting out zeroed
+            let required_lamports = Rent::get()?
+                .minimum_balance(DestinationReceipt::LEN)
+                .saturating_sub(receipt_account_info.lamports());
+            if required_lamports > 0 {
+                invoke(
+                    &system_instruction::transfer(
+                        accounts.payer_account_info.key,
+                        receipt_account_info.key,
+                        required_lamports,
+                    ),
+                    &[
+                        accounts.payer_account_info.clone(),
+                        receipt_account_info.clone(),
+                        accounts.system_program_account.clone(),
+                    ],
+                )?;
+            }
+            receipt_account_info.realloc(DestinationReceipt::LEN, true)?;
+        }
+        DestinationReceipt::unpack(&receipt_account_info.data.borrow())?
+    } else {
+        create_pda_account(
+            accounts.payer_account_info,
+            receipt_account_info,
+            accounts.system_program_account,
+            DestinationReceipt::LEN,
+            program_id,
+            &DestinationReceiptSeeds {
+                wallet,
+                destination,
+                token_mint,
+            },
+            accounts.bump_seed,
+        )?;
+        DestinationReceipt::new(*wallet, *destination, *token_mint)
+    };
+    receipt.record_transfer(amount, deducted_fee, slot, jurisdiction)?;
+    DestinationReceipt::pack(receipt, &mut receipt_account_info.data.borrow_mut())
+}
+
+/// Creates the destination's associated token account for a transfer approved to create it,
+/// paying its rent out of the source balance account if it can afford it and out of the rent
+/// return account otherwise. What the source paid is recorded in the op and logged, so that it
+/// can be accounted for alongside the transferred amount.
+#[allow(clippy::too_many_arguments)]
+fn create_destination_token_account(
+    multisig_op: &mut MultisigOp,
+    source_account: &AccountInfo,
+    rent_return_account_info: &AccountInfo,
+    destination_token_account: &AccountInfo,
+    destination_account: &AccountInfo,
+    token_mint_account_info: &AccountInfo,
+    token_program_id: &Pubkey,
+    source_signer_seeds: &[&[u8]],
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    if Rent::get()?.is_exempt(source_account.lamports(), SPLAccount::LEN) {
+        let lamports_before = source_account.lamports();
+        invoke_signed(
+            &create_associated_token_account_instruction(
+                source_account,
+                destination_token_account,
+                destination_account,
+                token_mint_account_info,
+                token_program_id,
+            ),
+            accounts,
+            &[source_signer_seeds],
+        )?;
+        let rent = lamports_before.saturating_sub(source_account.lamports());
+        multisig_op.destination_token_account_rent = rent;
+        msg!("DestinationTokenAccountRent: [{}]", rent);
+    } else {
+        invoke(
+            &create_associated_token_account_instruction(
+                rent_return_account_info,
+                destination_token_account,
+                destination_account,
+                token_mint_account_info,
+                token_program_id,
+            ),
+            accounts,
+        )?;
+    }
+    Ok(())
+}
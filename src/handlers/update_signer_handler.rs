@@ -1,15 +1,21 @@
+use crate::error::WalletError;
+use crate::handlers::program_parameters_handler;
 use crate::handlers::utils::{
-    finalize_multisig_op, get_clock_from_next_account, next_program_account_info,
-    next_signer_account_info, next_wallet_account_info, start_multisig_config_op,
-    FeeCollectionInfo,
+    finalize_multisig_op, get_clock_from_next_account, next_initiator_account_info,
+    next_program_account_info, next_signer_account_info, next_wallet_account_info,
+    start_multisig_config_op, FeeCollectionInfo,
 };
+use crate::handlers::wallet_replication_handler::mirror_to_standby;
 use crate::model::balance_account::BalanceAccountGuidHash;
-use crate::model::multisig_op::{MultisigOpParams, SlotUpdateType};
+use crate::model::multisig_op::{MultisigOp, MultisigOpParams, SlotUpdateType};
 use crate::model::signer::Signer;
 use crate::model::wallet::Wallet;
+use crate::model::wallet_replication::MirroredUpdate;
 use crate::utils::SlotId;
-use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::account_info::AccountInfo;
 use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
 use solana_program::program_pack::Pack;
 use solana_program::pubkey::Pubkey;
 
@@ -25,20 +31,35 @@ pub fn init(
     let accounts_iter = &mut accounts.iter();
     let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
     let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
-    let initiator_account_info = next_account_info(accounts_iter)?;
+    let initiator_account_info =
+        next_initiator_account_info(accounts_iter, multisig_op_account_info, wallet_account_info)?;
     let clock = get_clock_from_next_account(accounts_iter)?;
     let rent_return_account_info = next_signer_account_info(accounts_iter)?;
 
-    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
-    wallet.validate_config_initiator(initiator_account_info)?;
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    wallet.validate_update_signer_initiator(
+        initiator_account_info,
+        slot_update_type,
+        &signer,
+        clock.unix_timestamp,
+    )?;
+    wallet.validate_not_standby()?;
     match slot_update_type {
-        SlotUpdateType::SetIfEmpty => wallet.validate_add_signer((slot_id, signer))?,
+        SlotUpdateType::SetIfEmpty => {
+            wallet.validate_add_signer((slot_id, signer))?;
+            program_parameters_handler::load(program_id, accounts)?
+                .validate_signer_count(wallet.signers.filled_slots().len() + 1)?;
+        }
         SlotUpdateType::Clear => wallet.validate_remove_signer((slot_id, signer))?,
+        SlotUpdateType::Replace => {
+            wallet.validate_replace_signer((slot_id, signer), clock.unix_timestamp)?
+        }
     }
 
     start_multisig_config_op(
         &multisig_op_account_info,
-        &wallet,
+        wallet_account_info,
+        &mut wallet,
         clock,
         MultisigOpParams::UpdateSigner {
             wallet_address: *wallet_account_info.key,
@@ -66,12 +87,14 @@ pub fn finalize(
     let rent_return_account_info = next_signer_account_info(accounts_iter)?;
     let clock = get_clock_from_next_account(accounts_iter)?;
     let fee_account_info_maybe = accounts_iter.next();
+    let now = clock.unix_timestamp;
 
     let wallet_guid_hash =
         &Wallet::wallet_guid_hash_from_slice(&wallet_account_info.data.borrow())?;
 
     finalize_multisig_op(
         &multisig_op_account_info,
+        wallet_account_info,
         FeeCollectionInfo {
             rent_return_account_info,
             fee_account_info_maybe,
@@ -87,13 +110,82 @@ pub fn finalize(
         },
         || -> ProgramResult {
             let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow_mut())?;
+            wallet.validate_not_standby()?;
             match slot_update_type {
-                SlotUpdateType::SetIfEmpty => wallet.add_signer((slot_id, signer))?,
+                SlotUpdateType::SetIfEmpty => wallet.add_signer((slot_id, signer), now)?,
                 SlotUpdateType::Clear => wallet.remove_signer((slot_id, signer))?,
+                SlotUpdateType::Replace => wallet.replace_signer((slot_id, signer), now)?,
             }
+            let replication = wallet.replication;
             Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
-            Ok(())
+            mirror_to_standby(
+                program_id,
+                accounts,
+                wallet_account_info.key,
+                &replication,
+                MirroredUpdate::Signer {
+                    slot_update_type,
+                    slot_id,
+                    signer,
+                },
+            )
         },
         || -> ProgramResult { Ok(()) },
     )
 }
+
+pub fn migrate_pending_approvals(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    approver_count: u8,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+
+    let remaining_account_infos = accounts_iter.as_slice();
+    if remaining_account_infos.len() < usize::from(approver_count) {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let (approver_account_infos, multisig_op_account_infos) =
+        remaining_account_infos.split_at(usize::from(approver_count));
+
+    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    wallet.validate_config_approvers_signed(approver_account_infos, clock.unix_timestamp)?;
+
+    let accounts_iter = &mut multisig_op_account_infos.iter();
+    while accounts_iter.len() > 0 {
+        let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+        let mut multisig_op = MultisigOp::unpack_sized(&multisig_op_account_info.data.borrow())?;
+        if multisig_op.wallet != *wallet_account_info.key {
+            return Err(WalletError::AccountNotRecognized.into());
+        }
+
+        let migrations: Vec<(Pubkey, Pubkey)> = multisig_op
+            .disposition_records
+            .iter()
+            .filter_map(|record| {
+                wallet
+                    .successor_of(&record.approver, clock.unix_timestamp)
+                    .map(|successor| (record.approver, successor))
+            })
+            .collect();
+        let mut migrated = false;
+        for (retired, successor) in migrations {
+            if multisig_op.migrate_approver(&retired, &successor) {
+                msg!(
+                    "Migrated the disposition of {} on {} to {}",
+                    retired,
+                    multisig_op_account_info.key,
+                    successor
+                );
+                migrated = true;
+            }
+        }
+        if migrated {
+            multisig_op.update_operation_disposition(&clock)?;
+            MultisigOp::pack_sized(multisig_op, &mut multisig_op_account_info.data.borrow_mut())?;
+        }
+    }
+    Ok(())
+}
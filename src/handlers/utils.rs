@@ -2,30 +2,103 @@ use std::cmp::max;
 use std::slice::Iter;
 use std::time::Duration;
 
+use solana_program::program_option::COption;
 use solana_program::rent::Rent;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
-    clock::Clock,
+    clock::{Clock, Epoch},
     entrypoint::ProgramResult,
     instruction::{AccountMeta, Instruction},
     msg,
-    program::invoke_signed,
+    program::{invoke_signed, set_return_data},
     program_error::ProgramError,
-    program_pack::Pack,
+    program_pack::{IsInitialized, Pack},
     pubkey::Pubkey,
     system_instruction,
     sysvar::Sysvar,
 };
 use spl_associated_token_account;
+use spl_token::state::Mint;
+use spl_token_2022::extension::transfer_fee::instruction::transfer_checked_with_fee;
+use spl_token_2022::extension::transfer_fee::TransferFeeConfig;
+use spl_token_2022::extension::StateWithExtensions;
+use spl_token_2022::state::{Account as TokenAccount2022, Mint as Mint2022};
 
-use crate::error::WalletError;
+use crate::error::{strike_err, WalletError};
+use crate::model::address_book::{AllowedDestinationsPage, JurisdictionCode};
 use crate::model::balance_account::{BalanceAccount, BalanceAccountGuidHash};
+use crate::model::finalize_submitter_policy::{FinalizeCategory, FinalizeSubmitters};
 use crate::model::multisig_op::{
-    ApprovalDisposition, MultisigOp, MultisigOpParams, OperationDisposition,
+    ApprovalDisposition, MintAuthorityType, MultisigOp, MultisigOpParams, MultisigOpReceipt,
+    MultisigOpState, OperationDisposition,
 };
+use crate::model::policy_diff::PolicyDiff;
 use crate::model::wallet::{Wallet, WalletGuidHash};
+use crate::pda::{self, BalanceAccountSeeds, PdaSeeds};
 use crate::version::{Versioned, VERSION};
 
+/// The op an instruction is about, the first of its accounts, if it is an initialized op. It
+/// isn't for an instruction that starts the op, before it runs, nor once the op's account has
+/// been closed.
+pub fn unpack_subject_op(program_id: &Pubkey, accounts: &[AccountInfo]) -> Option<MultisigOp> {
+    accounts
+        .first()
+        .filter(|account_info| account_info.owner == program_id)
+        .and_then(|account_info| MultisigOp::unpack_from_slice(&account_info.data.borrow()).ok())
+        .filter(|multisig_op| multisig_op.is_initialized)
+}
+
+/// How an instruction changed the op it is about, going by the op as it was before and after
+/// the instruction.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct OpTransitions {
+    pub initiated: bool,
+    pub approved: bool,
+    pub denied: bool,
+    /// The op was finalized before it expired without having been denied, whether its account
+    /// was closed by the finalize or kept as executed. Transfers within a spending limit are
+    /// finalized without being approved.
+    pub finalized: bool,
+    /// The finalized op had been approved.
+    pub executed: bool,
+    /// The op was finalized or cleaned up after it expired, without having been denied.
+    pub expired: bool,
+}
+
+impl OpTransitions {
+    pub fn between(before: Option<&MultisigOp>, after: Option<&MultisigOp>, clock: &Clock) -> Self {
+        let before_state = before.map_or(MultisigOpState::Uninitialized, |op| op.state);
+        let mut transitions = OpTransitions::default();
+        if let Some(after) = after {
+            transitions.initiated = before.is_none();
+            transitions.approved = after.state == MultisigOpState::Approved
+                && before_state != MultisigOpState::Approved;
+            transitions.denied =
+                after.state == MultisigOpState::Denied && before_state != MultisigOpState::Denied;
+        }
+        if let Some(before) = before {
+            let after_state = after.map(|op| op.state);
+            // an op kept open as executed was finalized when it became executed
+            transitions.finalized = !matches!(
+                before.state,
+                MultisigOpState::Denied | MultisigOpState::Executed
+            ) && !before.is_expired(clock)
+                && after_state.map_or(true, |state| state == MultisigOpState::Executed);
+            transitions.executed =
+                transitions.finalized && before.state == MultisigOpState::Approved;
+            transitions.expired = matches!(
+                before.state,
+                MultisigOpState::PendingInstructions
+                    | MultisigOpState::Open
+                    | MultisigOpState::Approved
+                    | MultisigOpState::Quarantined
+            ) && before.is_expired(clock)
+                && after_state.map_or(true, |state| state == MultisigOpState::Expired);
+        }
+        transitions
+    }
+}
+
 pub struct FeeCollectionInfo<'a, 'b> {
     pub rent_return_account_info: &'a AccountInfo<'b>,
     pub fee_account_info_maybe: Option<&'a AccountInfo<'b>>,
@@ -51,12 +124,59 @@ pub fn next_program_account_info<'a, 'b, I: Iterator<Item = &'a AccountInfo<'b>>
 ) -> Result<I::Item, ProgramError> {
     let account_info = next_account_info(iter)?;
     if account_info.owner != program_id {
-        msg!("Account does not belong to the program");
-        return Err(ProgramError::IncorrectProgramId);
+        return Err(strike_err!(
+            "utils::next_program_account_info",
+            ProgramError::IncorrectProgramId,
+            "Account does not belong to the program"
+        ));
     }
     Ok(account_info)
 }
 
+/// Unpack a program account which is about to be initialized. The account must have exactly
+/// the size of the expected type (so that an account created for a different type cannot be
+/// reused), and must not have been initialized already. Callers are expected to have obtained
+/// the account via `next_program_account_info`, which verifies the owner.
+pub fn unpack_uninitialized<T: Pack + IsInitialized>(
+    account_info: &AccountInfo,
+) -> Result<T, ProgramError> {
+    if account_info.data_len() != T::LEN {
+        return Err(strike_err!(
+            "utils::unpack_uninitialized",
+            WalletError::AccountTypeMismatch,
+            "Account data length {} does not match {}",
+            account_info.data_len(),
+            T::LEN
+        ));
+    }
+    let value = T::unpack_unchecked(&account_info.data.borrow())?;
+    if value.is_initialized() {
+        return Err(WalletError::AccountAlreadyInitialized.into());
+    }
+    Ok(value)
+}
+
+/// Unpack a multisig op account which is about to be initialized. Unlike other program accounts,
+/// op accounts may be sized down to the number of approvers of the op, so any valid op size is
+/// accepted here and `MultisigOp::pack_sized` checks that the approvers fit once they are known.
+pub fn unpack_uninitialized_multisig_op(
+    account_info: &AccountInfo,
+) -> Result<MultisigOp, ProgramError> {
+    if MultisigOp::records_capacity(account_info.data_len()).is_none() {
+        return Err(strike_err!(
+            "utils::unpack_uninitialized_multisig_op",
+            WalletError::AccountTypeMismatch,
+            "Account data length {} is not a multisig op size",
+            account_info.data_len()
+        ));
+    }
+    let multisig_op = MultisigOp::unpack_from_slice(&account_info.data.borrow())?;
+    if multisig_op.is_initialized() {
+        return Err(WalletError::AccountAlreadyInitialized.into());
+    }
+    Ok(multisig_op)
+}
+
 pub fn next_wallet_account_info<'a, 'b, I: Iterator<Item = &'a AccountInfo<'b>>>(
     iter: &mut I,
     program_id: &Pubkey,
@@ -72,8 +192,11 @@ pub fn next_wallet_account_info<'a, 'b, I: Iterator<Item = &'a AccountInfo<'b>>>
 pub fn get_clock_from_next_account(iter: &mut Iter<AccountInfo>) -> Result<Clock, ProgramError> {
     let account_info = next_account_info(iter)?;
     if solana_program::sysvar::clock::id() != *account_info.key {
-        msg!("Invalid clock account");
-        return Err(WalletError::AccountNotRecognized.into());
+        return Err(strike_err!(
+            "utils::get_clock_from_next_account",
+            WalletError::AccountNotRecognized,
+            "Invalid clock account"
+        ));
     }
     Clock::from_account_info(&account_info)
 }
@@ -88,13 +211,46 @@ pub fn next_signer_account_info<'a, 'b, I: Iterator<Item = &'a AccountInfo<'b>>>
     Ok(account_info)
 }
 
-pub fn calculate_expires(start: i64, duration: Duration) -> Result<i64, ProgramError> {
-    let expires_at = start.checked_add(duration.as_secs() as i64);
-    if expires_at == None {
-        msg!("Invalid expires_at");
-        return Err(ProgramError::InvalidArgument);
+/// Reads the initiator of an op, which has to sign the transaction itself rather than be
+/// taken to have done so from its position among the accounts. The op and wallet accounts
+/// can't stand in for it, whatever keys the wallet knows.
+pub fn next_initiator_account_info<'a, 'b, I: Iterator<Item = &'a AccountInfo<'b>>>(
+    iter: &mut I,
+    multisig_op_account_info: &AccountInfo,
+    wallet_account_info: &AccountInfo,
+) -> Result<I::Item, ProgramError> {
+    let account_info = next_account_info(iter)?;
+    if !account_info.is_signer {
+        return Err(strike_err!(
+            "utils::next_initiator_account_info",
+            WalletError::InvalidSignature,
+            "Initiator {} didn't sign the transaction",
+            account_info.key
+        ));
+    }
+    if account_info.key == multisig_op_account_info.key
+        || account_info.key == wallet_account_info.key
+    {
+        return Err(strike_err!(
+            "utils::next_initiator_account_info",
+            WalletError::IncorrectInitiatorAccount,
+            "The initiator can't be the op or wallet account"
+        ));
     }
-    Ok(expires_at.unwrap())
+    Ok(account_info)
+}
+
+pub fn calculate_expires(start: i64, duration: Duration) -> Result<i64, ProgramError> {
+    i64::try_from(duration.as_secs())
+        .ok()
+        .and_then(|secs| start.checked_add(secs))
+        .ok_or_else(|| {
+            strike_err!(
+                "utils::calculate_expires",
+                WalletError::ArithmeticOverflow,
+                "Invalid expires_at"
+            )
+        })
 }
 
 /// validate the PDA of a BalanceAccount and return its bump seed.
@@ -104,30 +260,137 @@ pub fn validate_balance_account_and_get_seed(
     account_guid_hash: &BalanceAccountGuidHash,
     program_id: &Pubkey,
 ) -> Result<u8, ProgramError> {
-    let seeds = &[wallet_guid_hash.to_bytes(), account_guid_hash.to_bytes()];
-    match verify_pda(program_id, seeds, balance_account_info.key, None) {
-        Ok((_pda, bump_seed)) => Ok(bump_seed),
-        Err(_error) => Err(WalletError::InvalidPDA.into()),
+    pda::verify(
+        program_id,
+        &BalanceAccountSeeds {
+            wallet_guid_hash,
+            account_guid_hash,
+        },
+        balance_account_info.key,
+        None,
+    )
+}
+
+/// Counts the initiation against the initiating assistant's rate limit. The wallet account
+/// only has to be writable when the initiator is a rate limited assistant.
+pub fn record_assistant_initiation(
+    wallet_account_info: &AccountInfo,
+    wallet: &mut Wallet,
+    initiator: &Pubkey,
+    clock: &Clock,
+) -> ProgramResult {
+    if wallet.record_assistant_initiation(initiator, clock.unix_timestamp)? {
+        Wallet::pack(wallet.clone(), &mut wallet_account_info.data.borrow_mut())?;
     }
+    Ok(())
 }
 
+/// Starts a transfer-like op. When the wallet has a price oracle, the transfer's thresholds are
+/// in US cents, and the whitelisted price account of the transferred token mint has to be among
+/// `price_account_infos` for a transfer that a threshold applies to. A transfer to a
+/// destination in `destination_jurisdiction`, which is given for high-risk destinations, needs
+/// the compliance service's approval whatever its amount.
 pub fn start_multisig_transfer_op(
     multisig_op_account_info: &AccountInfo,
-    wallet: &Wallet,
+    wallet_account_info: &AccountInfo,
+    wallet: &mut Wallet,
     balance_account: &BalanceAccount,
     clock: Clock,
     params: MultisigOpParams,
+    destination_jurisdiction: Option<JurisdictionCode>,
     initiator: Pubkey,
     rent_return: Pubkey,
     fee_amount: u64,
     fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+    price_account_infos: &[AccountInfo],
 ) -> ProgramResult {
-    let mut multisig_op = MultisigOp::unpack_unchecked(&multisig_op_account_info.data.borrow())?;
+    wallet.validate_not_provisioning()?;
+    wallet.fee_policy.validate_fee(fee_amount)?;
+    record_assistant_initiation(wallet_account_info, wallet, &initiator, &clock)?;
+
+    let mut multisig_op = unpack_uninitialized_multisig_op(multisig_op_account_info)?;
 
+    let transferred = match params {
+        MultisigOpParams::Transfer {
+            amount, token_mint, ..
+        }
+        | MultisigOpParams::OpenPaymentChannel {
+            allowance: amount,
+            token_mint,
+            ..
+        } => Some((amount, token_mint)),
+        MultisigOpParams::CommittedTransfer { amount, .. } => Some((amount, Pubkey::default())),
+        _ => None,
+    };
+    // the amount compared against the thresholds, only valued when there is one to compare with
+    let threshold_amount = match transferred {
+        Some((amount, token_mint))
+            if wallet.compliance_policy.is_some()
+                || !balance_account.finalize_delay_policy.delay.is_zero() =>
+        {
+            Some(match &wallet.price_oracle {
+                Some(oracle) => oracle.value_in_cents(
+                    &token_mint,
+                    amount,
+                    price_account_infos,
+                    clock.unix_timestamp,
+                )?,
+                None => amount,
+            })
+        }
+        _ => None,
+    };
+    let compliance_service = match destination_jurisdiction {
+        Some(jurisdiction) => Some(
+            wallet
+                .compliance_policy
+                .ok_or_else(|| {
+                    strike_err!(
+                        "utils::start_multisig_transfer_op",
+                        WalletError::CompliancePolicyRequired,
+                        "Destination in high-risk jurisdiction {} needs a compliance approval",
+                        String::from_utf8_lossy(&jurisdiction.to_bytes())
+                    )
+                })?
+                .service,
+        ),
+        None => threshold_amount.and_then(|amount| {
+            wallet
+                .compliance_policy
+                .filter(|policy| policy.requires_approval(amount))
+                .map(|policy| policy.service)
+        }),
+    };
+    // a payment channel's allowance can be drawn down all at once, so opening one is delayed
+    // like a transfer of that amount, and a committed transfer is delayed like the transfer it
+    // reveals
+    let transfer_amount = match (&params, threshold_amount) {
+        (MultisigOpParams::Transfer { .. }, Some(amount))
+        | (MultisigOpParams::CommittedTransfer { .. }, Some(amount))
+        | (MultisigOpParams::OpenPaymentChannel { .. }, Some(amount)) => Some(amount),
+        _ => None,
+    };
+    let finalize_delay = transfer_amount
+        .map(|amount| balance_account.finalize_delay_policy.delay_for(amount))
+        .unwrap_or(Duration::ZERO);
+    // transfers in one of the account's approval tiers are approved by that tier's approvers
+    let (active_approvers, approvals_required_for_transfer) =
+        match transfer_amount.and_then(|amount| balance_account.approval_tiers.tier_for(amount)) {
+            Some(tier) => (
+                wallet.get_active_tier_approvers_keys(&tier, clock.unix_timestamp),
+                tier.approvals_required,
+            ),
+            None => (
+                wallet.get_active_transfer_approvers_keys(balance_account, clock.unix_timestamp),
+                balance_account.approvals_required_for_transfer,
+            ),
+        };
+    let (approvers, approvals_required) =
+        op_approvers(wallet, active_approvers, approvals_required_for_transfer);
     multisig_op.init(
-        wallet.get_transfer_approvers_keys(balance_account),
+        approvers,
         (initiator, ApprovalDisposition::APPROVE),
-        balance_account.approvals_required_for_transfer,
+        approvals_required,
         clock.unix_timestamp,
         calculate_expires(
             clock.unix_timestamp,
@@ -137,46 +400,254 @@ pub fn start_multisig_transfer_op(
         rent_return,
         fee_amount,
         fee_account_guid_hash,
+        wallet.op_nonce,
     )?;
-    MultisigOp::pack(multisig_op, &mut multisig_op_account_info.data.borrow_mut())?;
+    multisig_op.stake_weight_governance = wallet.stake_weight_governance;
+    multisig_op.wallet = *wallet_account_info.key;
+    multisig_op.destination_jurisdiction = destination_jurisdiction;
+    multisig_op.record_activity(clock.slot);
+    multisig_op.set_expiry_slot(clock.slot, balance_account.approval_timeout_for_transfer);
+    multisig_op.set_finalize_delay(finalize_delay);
+    if let Some(compliance_service) = compliance_service {
+        multisig_op.require_compliance_approval(compliance_service);
+    }
+    return_receipt(&multisig_op, &clock);
+    MultisigOp::pack_sized(multisig_op, &mut multisig_op_account_info.data.borrow_mut())?;
 
     Ok(())
 }
 
 pub fn start_multisig_config_op(
     multisig_op_account_info: &AccountInfo,
-    wallet: &Wallet,
+    wallet_account_info: &AccountInfo,
+    wallet: &mut Wallet,
+    clock: Clock,
+    params: MultisigOpParams,
+    initiator: Pubkey,
+    rent_return: Pubkey,
+    fee_amount: u64,
+    fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+) -> ProgramResult {
+    let approvals_required = wallet.approvals_required_for_config;
+    start_config_op(
+        multisig_op_account_info,
+        wallet_account_info,
+        wallet,
+        clock,
+        params,
+        initiator,
+        rent_return,
+        fee_amount,
+        fee_account_guid_hash,
+        approvals_required,
+    )
+}
+
+/// Starts a config op that every config approver has to approve, rather than the number the
+/// wallet's config policy requires. It can't be approved while any config approver is yet to
+/// activate.
+#[allow(clippy::too_many_arguments)]
+pub fn start_full_quorum_config_op(
+    multisig_op_account_info: &AccountInfo,
+    wallet_account_info: &AccountInfo,
+    wallet: &mut Wallet,
+    clock: Clock,
+    params: MultisigOpParams,
+    initiator: Pubkey,
+    rent_return: Pubkey,
+    fee_amount: u64,
+    fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+) -> ProgramResult {
+    let approvals_required = wallet.get_config_approvers_keys().len() as u8;
+    start_config_op(
+        multisig_op_account_info,
+        wallet_account_info,
+        wallet,
+        clock,
+        params,
+        initiator,
+        rent_return,
+        fee_amount,
+        fee_account_guid_hash,
+        approvals_required,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn start_config_op(
+    multisig_op_account_info: &AccountInfo,
+    wallet_account_info: &AccountInfo,
+    wallet: &mut Wallet,
     clock: Clock,
     params: MultisigOpParams,
     initiator: Pubkey,
     rent_return: Pubkey,
     fee_amount: u64,
     fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+    approvals_required: u8,
 ) -> ProgramResult {
-    let mut multisig_op = MultisigOp::unpack_unchecked(&multisig_op_account_info.data.borrow())?;
+    wallet.validate_not_provisioning()?;
+    wallet.fee_policy.validate_fee(fee_amount)?;
+    record_assistant_initiation(wallet_account_info, wallet, &initiator, &clock)?;
 
+    let mut multisig_op = unpack_uninitialized_multisig_op(multisig_op_account_info)?;
+
+    let (approvers, approvals_required) = op_approvers(
+        wallet,
+        wallet.get_active_config_approvers_keys(clock.unix_timestamp),
+        approvals_required,
+    );
     multisig_op.init(
-        wallet.get_config_approvers_keys(),
+        approvers,
         (initiator, ApprovalDisposition::APPROVE),
-        wallet.approvals_required_for_config,
+        approvals_required,
         clock.unix_timestamp,
         calculate_expires(clock.unix_timestamp, wallet.approval_timeout_for_config)?,
         Some(params),
         rent_return,
         fee_amount,
         fee_account_guid_hash,
+        wallet.op_nonce,
     )?;
-    MultisigOp::pack(multisig_op, &mut multisig_op_account_info.data.borrow_mut())?;
+    multisig_op.stake_weight_governance = wallet.stake_weight_governance;
+    multisig_op.wallet = *wallet_account_info.key;
+    multisig_op.record_activity(clock.slot);
+    multisig_op.set_expiry_slot(clock.slot, wallet.approval_timeout_for_config);
+    return_receipt(&multisig_op, &clock);
+    MultisigOp::pack_sized(multisig_op, &mut multisig_op_account_info.data.borrow_mut())?;
 
     Ok(())
 }
 
+/// The approvers of a new op and the approvals it requires. An op of a stake weight governed
+/// wallet starts out with no approvers and requiring an approval, so that it can't be approved
+/// until `InitWithStakeWeights` snapshots the governance token holders' weights into it.
+pub fn op_approvers(
+    wallet: &Wallet,
+    approvers: Vec<Pubkey>,
+    approvals_required: u8,
+) -> (Vec<Pubkey>, u8) {
+    if wallet.stake_weight_governance.is_some() {
+        (Vec::new(), 1)
+    } else {
+        (approvers, approvals_required)
+    }
+}
+
+/// Hands the op's params hash and expiry back to the submitting client as return data.
+pub fn return_receipt(multisig_op: &MultisigOp, clock: &Clock) {
+    let mut receipt = [0; MultisigOpReceipt::LEN];
+    multisig_op.receipt(clock).pack_into_slice(&mut receipt);
+    set_return_data(&receipt);
+}
+
+/// Hands a simulated policy update's diff back to the submitting client as return data.
+pub fn return_policy_diff(diff: &PolicyDiff) {
+    let mut buf = [0; PolicyDiff::LEN];
+    diff.pack_into_slice(&mut buf);
+    set_return_data(&buf);
+}
+
+/// Hands a page of a balance account's allowed destinations back to the submitting client as
+/// return data.
+pub fn return_allowed_destinations(page: &AllowedDestinationsPage) {
+    let mut buf = Vec::new();
+    page.pack(&mut buf);
+    set_return_data(&buf);
+}
+
 pub fn log_op_disposition(disposition: OperationDisposition) {
     msg!("OperationDisposition: [{}]", disposition.to_u8());
 }
 
+pub fn log_fee_deduction(fee_amount: u64) {
+    msg!("FeeDeductedFromAmount: [{}]", fee_amount);
+}
+
+/// Checks the account submitting an op's finalize transaction, which signs it as the op's rent
+/// return account, against the wallet's finalize submitter policy for the op's category.
+/// Checks that the account the op's rent is being returned to is the one it was initiated with,
+/// and is on the wallet's rent return allowlist.
+pub fn validate_rent_return(
+    wallet_account_info: &AccountInfo,
+    multisig_op: &MultisigOp,
+    rent_return_account_info: &AccountInfo,
+) -> ProgramResult {
+    if *rent_return_account_info.key != multisig_op.rent_return {
+        return Err(WalletError::IncorrectRentReturnAccount.into());
+    }
+    if !Wallet::rent_return_allowlist_from_slice(&wallet_account_info.data.borrow())?
+        .allows(&multisig_op.rent_return)
+    {
+        return Err(strike_err!(
+            "utils::validate_rent_return",
+            WalletError::RentReturnNotAllowed,
+            "Rent return account {} is not on the wallet's allowlist",
+            multisig_op.rent_return
+        ));
+    }
+    Ok(())
+}
+
+pub fn validate_finalize_submitter(
+    wallet_account_info: &AccountInfo,
+    multisig_op: &MultisigOp,
+    category: FinalizeCategory,
+    submitter: &Pubkey,
+) -> ProgramResult {
+    let policy = Wallet::finalize_submitter_policy_from_slice(&wallet_account_info.data.borrow())?;
+    let allowed = match policy.submitters(category) {
+        FinalizeSubmitters::Anyone => true,
+        FinalizeSubmitters::Assistant => {
+            Wallet::unpack(&wallet_account_info.data.borrow())?.is_enabled_assistant(submitter)
+        }
+        FinalizeSubmitters::Approvers => multisig_op.is_approver(submitter),
+        FinalizeSubmitters::Named => policy.named_submitters.contains(submitter),
+    };
+    if !allowed {
+        return Err(strike_err!(
+            "utils::validate_finalize_submitter",
+            WalletError::FinalizeSubmitterNotAllowed,
+            "{:?} finalize transactions can only be submitted by {:?}",
+            category,
+            policy.submitters(category)
+        ));
+    }
+    Ok(())
+}
+
+/// Checks that the given SPL mint's authority of the given type is held by `authority`.
+pub fn validate_mint_authority(
+    mint_account_info: &AccountInfo,
+    authority_type: MintAuthorityType,
+    authority: &Pubkey,
+) -> ProgramResult {
+    if *mint_account_info.owner != spl_token::id() {
+        return Err(strike_err!(
+            "utils::validate_mint_authority",
+            WalletError::AccountNotRecognized,
+            "Mint account is not owned by the SPL token program"
+        ));
+    }
+    let mint = Mint::unpack(&mint_account_info.data.borrow())?;
+    let current_authority = match authority_type {
+        MintAuthorityType::MintTokens => mint.mint_authority,
+        MintAuthorityType::FreezeAccount => mint.freeze_authority,
+    };
+    if current_authority != COption::Some(*authority) {
+        return Err(strike_err!(
+            "utils::validate_mint_authority",
+            WalletError::InvalidMintAuthority,
+            "Balance account does not hold the {:?} authority of the mint",
+            authority_type
+        ));
+    }
+    Ok(())
+}
+
 pub fn finalize_multisig_op<'a, F, G>(
     multisig_op_account_info: &AccountInfo,
+    wallet_account_info: &AccountInfo,
     fee_collection_info: FeeCollectionInfo,
     clock: Clock,
     expected_params: MultisigOpParams,
@@ -188,67 +659,29 @@ where
     G: FnMut() -> ProgramResult,
 {
     if MultisigOp::version_from_slice(&multisig_op_account_info.data.borrow())? == VERSION {
-        let multisig_op = MultisigOp::unpack(&multisig_op_account_info.data.borrow())?;
+        let multisig_op = MultisigOp::unpack_sized(&multisig_op_account_info.data.borrow())?;
 
-        if *fee_collection_info.rent_return_account_info.key != multisig_op.rent_return {
-            return Err(WalletError::IncorrectRentReturnAccount.into());
-        }
+        validate_rent_return(
+            wallet_account_info,
+            &multisig_op,
+            fee_collection_info.rent_return_account_info,
+        )?;
+        validate_finalize_submitter(
+            wallet_account_info,
+            &multisig_op,
+            expected_params.finalize_category(),
+            &multisig_op.rent_return,
+        )?;
 
         if multisig_op.approved(expected_params.hash(&multisig_op), &clock, None)? {
+            validate_op_nonce(wallet_account_info, &multisig_op, &expected_params)?;
             on_op_approved()?;
+            bump_op_nonce(wallet_account_info, &expected_params)?;
         } else {
             on_op_not_approved()?;
         }
 
-        if multisig_op.fee_amount > 0 {
-            // attempt to collect fees
-            if let Some(guid_hash) = multisig_op.fee_account_guid_hash {
-                if let Some(fee_account_info) = fee_collection_info.fee_account_info_maybe {
-                    let fee_collection = || -> Result<(), ProgramError> {
-                        let bump_seed = validate_balance_account_and_get_seed(
-                            fee_account_info,
-                            fee_collection_info.wallet_guid_hash,
-                            &guid_hash,
-                            fee_collection_info.program_id,
-                        )?;
-                        // this will transfer as much of the fee as possible without taking the
-                        // fee account below the minimum balance
-                        let rent = Rent::get()?;
-                        let balance_floor = rent.minimum_balance(0);
-                        let final_from_lamports = max(
-                            balance_floor,
-                            fee_account_info
-                                .lamports()
-                                .saturating_sub(multisig_op.fee_amount),
-                        );
-                        let amount = fee_account_info
-                            .lamports()
-                            .saturating_sub(final_from_lamports);
-
-                        invoke_signed(
-                            &system_instruction::transfer(
-                                fee_account_info.key,
-                                fee_collection_info.rent_return_account_info.key,
-                                amount,
-                            ),
-                            &[
-                                fee_account_info.clone(),
-                                fee_collection_info.rent_return_account_info.clone(),
-                            ],
-                            &[&[
-                                fee_collection_info.wallet_guid_hash.to_bytes(),
-                                guid_hash.to_bytes(),
-                                &[bump_seed],
-                            ]],
-                        )?;
-                        Ok(())
-                    };
-                    if let Err(err) = fee_collection() {
-                        msg!("Unable to collect fees: {:?}", err);
-                    }
-                }
-            }
-        }
+        collect_fee(&multisig_op, wallet_account_info, &fee_collection_info);
     } else {
         log_op_disposition(OperationDisposition::EXPIRED);
     }
@@ -261,6 +694,190 @@ where
     Ok(())
 }
 
+/// Fails the finalize of an approved config op that was started against an older wallet
+/// state than the current one, rather than applying an update made for that older state.
+fn validate_op_nonce(
+    wallet_account_info: &AccountInfo,
+    multisig_op: &MultisigOp,
+    expected_params: &MultisigOpParams,
+) -> ProgramResult {
+    if expected_params.finalize_category() != FinalizeCategory::Config {
+        return Ok(());
+    }
+    let op_nonce = Wallet::op_nonce_from_slice(&wallet_account_info.data.borrow())?;
+    if multisig_op.op_nonce != op_nonce {
+        return Err(strike_err!(
+            "utils::validate_op_nonce",
+            WalletError::StaleOperation,
+            "Operation was started at op nonce {}, but the wallet is at {}",
+            multisig_op.op_nonce,
+            op_nonce
+        ));
+    }
+    Ok(())
+}
+
+/// Bumps the wallet's op nonce once a config op has been applied to it. Config ops that
+/// finalize against a read-only wallet leave its state as it was, so they don't bump it.
+fn bump_op_nonce(
+    wallet_account_info: &AccountInfo,
+    expected_params: &MultisigOpParams,
+) -> ProgramResult {
+    if expected_params.finalize_category() == FinalizeCategory::Config
+        && wallet_account_info.is_writable
+    {
+        Wallet::bump_op_nonce_in_slice(&mut wallet_account_info.data.borrow_mut())?;
+    }
+    Ok(())
+}
+
+/// Attempts to collect the op's fee from its fee account into the rent return account,
+/// without failing the finalize if it can't be collected.
+fn collect_fee(
+    multisig_op: &MultisigOp,
+    wallet_account_info: &AccountInfo,
+    fee_collection_info: &FeeCollectionInfo,
+) {
+    if multisig_op.fee_amount > 0 {
+        // attempt to collect fees
+        if let Some(guid_hash) = multisig_op.fee_account_guid_hash {
+            if let Some(fee_account_info) = fee_collection_info.fee_account_info_maybe {
+                let fee_collection = || -> Result<(), ProgramError> {
+                    let bump_seed = validate_balance_account_and_get_seed(
+                        fee_account_info,
+                        fee_collection_info.wallet_guid_hash,
+                        &guid_hash,
+                        fee_collection_info.program_id,
+                    )?;
+                    // the wallet's cap may have been lowered since the op was initiated
+                    let fee_amount =
+                        Wallet::fee_policy_from_slice(&wallet_account_info.data.borrow())?
+                            .collectable(multisig_op.fee_amount);
+                    // this will transfer as much of the fee as possible without taking the
+                    // fee account below the minimum balance
+                    let rent = Rent::get()?;
+                    let balance_floor = rent.minimum_balance(0);
+                    let final_from_lamports = max(
+                        balance_floor,
+                        fee_account_info.lamports().saturating_sub(fee_amount),
+                    );
+                    let amount = fee_account_info
+                        .lamports()
+                        .saturating_sub(final_from_lamports);
+
+                    invoke_signed(
+                        &system_instruction::transfer(
+                            fee_account_info.key,
+                            fee_collection_info.rent_return_account_info.key,
+                            amount,
+                        ),
+                        &[
+                            fee_account_info.clone(),
+                            fee_collection_info.rent_return_account_info.clone(),
+                        ],
+                        &[&BalanceAccountSeeds {
+                            wallet_guid_hash: fee_collection_info.wallet_guid_hash,
+                            account_guid_hash: &guid_hash,
+                        }
+                        .signer_seeds(&[bump_seed])],
+                    )?;
+                    Ok(())
+                };
+                if let Err(err) = fee_collection() {
+                    strike_err!("utils::collect_fee", err, "Unable to collect fees");
+                }
+            }
+        }
+    }
+}
+
+/// Finalizes an op whose approved effect may take more than one transaction, like
+/// `finalize_multisig_op`, except that an approved op is kept open as executed until `on_batch`
+/// reports its effect complete. Later batches are applied by continuing the executed op, which
+/// doesn't check its approval again. The fee is collected when the op is closed.
+#[allow(clippy::too_many_arguments)]
+pub fn finalize_multisig_op_in_batches<F, G>(
+    multisig_op_account_info: &AccountInfo,
+    wallet_account_info: &AccountInfo,
+    fee_collection_info: FeeCollectionInfo,
+    clock: Clock,
+    expected_params: MultisigOpParams,
+    continuing: bool,
+    mut on_batch: F,
+    mut on_op_not_approved: G,
+) -> ProgramResult
+where
+    F: FnMut(&mut MultisigOp) -> Result<bool, ProgramError>,
+    G: FnMut() -> ProgramResult,
+{
+    if MultisigOp::version_from_slice(&multisig_op_account_info.data.borrow())? != VERSION {
+        if continuing {
+            return Err(WalletError::AccountVersionMismatch.into());
+        }
+        log_op_disposition(OperationDisposition::EXPIRED);
+        return collect_remaining_balance(
+            multisig_op_account_info,
+            fee_collection_info.rent_return_account_info,
+        );
+    }
+
+    let mut multisig_op = MultisigOp::unpack_sized(&multisig_op_account_info.data.borrow())?;
+    validate_rent_return(
+        wallet_account_info,
+        &multisig_op,
+        fee_collection_info.rent_return_account_info,
+    )?;
+    validate_finalize_submitter(
+        wallet_account_info,
+        &multisig_op,
+        expected_params.finalize_category(),
+        &multisig_op.rent_return,
+    )?;
+
+    let executed = multisig_op.state == MultisigOpState::Executed;
+    let done = if continuing {
+        if !executed {
+            return Err(strike_err!(
+                "utils::finalize_multisig_op_in_batches",
+                WalletError::OperationNotExecuted,
+                "Operation is {:?}",
+                multisig_op.state
+            ));
+        }
+        if multisig_op.params_hash != Some(expected_params.hash(&multisig_op)) {
+            return Err(WalletError::InvalidSignature.into());
+        }
+        on_batch(&mut multisig_op)?
+    } else if executed {
+        return Err(strike_err!(
+            "utils::finalize_multisig_op_in_batches",
+            WalletError::InvalidStateTransition,
+            "Operation was already finalized, and has to be continued"
+        ));
+    } else if multisig_op.approved(expected_params.hash(&multisig_op), &clock, None)? {
+        validate_op_nonce(wallet_account_info, &multisig_op, &expected_params)?;
+        multisig_op.transition(MultisigOpState::Executed)?;
+        let done = on_batch(&mut multisig_op)?;
+        bump_op_nonce(wallet_account_info, &expected_params)?;
+        done
+    } else {
+        on_op_not_approved()?;
+        true
+    };
+
+    if !done {
+        return MultisigOp::pack_sized(
+            multisig_op,
+            &mut multisig_op_account_info.data.borrow_mut(),
+        );
+    }
+    collect_fee(&multisig_op, wallet_account_info, &fee_collection_info);
+    collect_remaining_balance(
+        multisig_op_account_info,
+        fee_collection_info.rent_return_account_info,
+    )
+}
+
 pub fn transfer_sol_checked<'a>(
     wallet_guid_hash: &WalletGuidHash,
     balance_account: AccountInfo<'a>,
@@ -276,54 +893,85 @@ pub fn transfer_sol_checked<'a>(
         .ok_or(WalletError::AmountOverflow)?;
 
     if balance_account.lamports() < lamports_plus_rent {
-        msg!(
-            "Account only has {} lamports of {} requested while having to keep {} lamports for rent exemption",
+        return Err(strike_err!("utils::transfer_sol_checked", WalletError::InsufficientBalance, "Account only has {} lamports of {} requested while having to keep {} lamports for rent exemption",
             balance_account.lamports(),
             lamports,
-            balance_account_rent
-        );
-        return Err(WalletError::InsufficientBalance.into());
+            balance_account_rent));
     }
     let instruction = &system_instruction::transfer(balance_account.key, to.key, lamports);
     invoke_signed(
         instruction,
         &[balance_account, to, system_program_account],
-        &[&[
-            wallet_guid_hash.to_bytes(),
-            account_guid_hash.to_bytes(),
-            &[bump_seed],
-        ]],
+        &[&BalanceAccountSeeds {
+            wallet_guid_hash,
+            account_guid_hash,
+        }
+        .signer_seeds(&[bump_seed])],
     )
 }
 
-/// Validate a given PDA and optionally its corresponding bump seed. If valid,
-/// the key and bump_seed are returned in the result.
-pub fn verify_pda(
+/// Create a program owned PDA of the given size, paid for by `payer`. Unlike a plain
+/// `create_account`, this also works when lamports were already sent to the address, so that
+/// funding it up front can't block its creation.
+pub fn create_pda_account<'a>(
+    payer: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    system_program_account: &AccountInfo<'a>,
+    space: usize,
     program_id: &Pubkey,
-    seeds: &[&[u8]],
-    expected_key: &Pubkey,
-    expected_bump_seed: Option<u8>,
-) -> Result<(Pubkey, u8), ProgramError> {
-    let (key, bump_seed) = Pubkey::find_program_address(seeds, program_id);
-    if key != *expected_key {
-        return Err(WalletError::InvalidPDA.into());
-    }
-    // verify bump seed
-    if let Some(expected_bump_seed_value) = expected_bump_seed {
-        if bump_seed != expected_bump_seed_value {
-            return Err(WalletError::InvalidPDA.into());
-        }
+    seeds: &impl PdaSeeds,
+    bump_seed: u8,
+) -> ProgramResult {
+    let bump_seed = [bump_seed];
+    let seeds = &seeds.signer_seeds(&bump_seed)[..];
+    let accounts = &[
+        payer.clone(),
+        account.clone(),
+        system_program_account.clone(),
+    ];
+    let required_lamports = Rent::get()?
+        .minimum_balance(space)
+        .saturating_sub(account.lamports());
+    if account.lamports() == 0 {
+        return invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                account.key,
+                required_lamports,
+                space as u64,
+                program_id,
+            ),
+            accounts,
+            &[seeds],
+        );
+    }
+    if required_lamports > 0 {
+        invoke_signed(
+            &system_instruction::transfer(payer.key, account.key, required_lamports),
+            accounts,
+            &[],
+        )?;
     }
-    Ok((key, bump_seed))
+    invoke_signed(
+        &system_instruction::allocate(account.key, space as u64),
+        accounts,
+        &[seeds],
+    )?;
+    invoke_signed(
+        &system_instruction::assign(account.key, program_id),
+        accounts,
+        &[seeds],
+    )
 }
 
 /// Build an instruction to create an "associated token account" for the given
-/// balance account.
+/// balance account, under the token program owning the mint.
 pub fn create_associated_token_account_instruction(
     payer_account_info: &AccountInfo,
     associated_token_account_info: &AccountInfo,
     balance_account_info: &AccountInfo,
     token_mint_account_info: &AccountInfo,
+    token_program_id: &Pubkey,
 ) -> Instruction {
     Instruction {
         program_id: spl_associated_token_account::id(),
@@ -333,8 +981,375 @@ pub fn create_associated_token_account_instruction(
             AccountMeta::new_readonly(*balance_account_info.key, false),
             AccountMeta::new_readonly(*token_mint_account_info.key, false),
             AccountMeta::new_readonly(solana_program::system_program::id(), false),
-            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(*token_program_id, false),
         ],
         data: vec![0],
     }
 }
+
+/// The token program owning the given mint: the SPL token program, or Token-2022.
+pub fn token_program_of(mint_account_info: &AccountInfo) -> Result<Pubkey, ProgramError> {
+    if *mint_account_info.owner == spl_token::id()
+        || *mint_account_info.owner == spl_token_2022::id()
+    {
+        Ok(*mint_account_info.owner)
+    } else {
+        Err(strike_err!(
+            "utils::token_program_of",
+            WalletError::InvalidTokenMintAccount,
+            "Mint account is owned by {}",
+            mint_account_info.owner
+        ))
+    }
+}
+
+/// The first of the token accounts that has been frozen by its mint's freeze authority, which
+/// the token program would fail any transfer into or out of. An account that doesn't exist yet
+/// isn't frozen.
+pub fn frozen_token_account<'a, 'b>(
+    token_account_infos: &[&'a AccountInfo<'b>],
+) -> Result<Option<&'a AccountInfo<'b>>, ProgramError> {
+    for token_account_info in token_account_infos {
+        if !token_account_info.data_is_empty()
+            && StateWithExtensions::<TokenAccount2022>::unpack(&token_account_info.data.borrow())?
+                .base
+                .is_frozen()
+        {
+            return Ok(Some(token_account_info));
+        }
+    }
+    Ok(None)
+}
+
+/// Holds back the finalize of an approved op that would move tokens into or out of a frozen
+/// token account, rather than letting the token program fail it. The op is quarantined with
+/// its approvals kept, and the finalize goes through so that the quarantine sticks. A
+/// quarantined op is released by the next finalize once its token accounts have been thawed,
+/// and fails it with `TokenAccountFrozen` while they haven't. Returns whether the op was
+/// quarantined, in which case there is nothing more for the finalize to do. The token
+/// accounts have to have been validated as the op's own.
+pub fn quarantine_if_frozen(
+    multisig_op_account_info: &AccountInfo,
+    clock: &Clock,
+    expected_params: &MultisigOpParams,
+    token_account_infos: &[&AccountInfo],
+) -> Result<bool, ProgramError> {
+    if MultisigOp::version_from_slice(&multisig_op_account_info.data.borrow())? != VERSION {
+        return Ok(false);
+    }
+    let mut multisig_op = MultisigOp::unpack_sized(&multisig_op_account_info.data.borrow())?;
+    if multisig_op.params_hash != Some(expected_params.hash(&multisig_op)) {
+        return Ok(false);
+    }
+    let frozen = frozen_token_account(token_account_infos)?;
+    match (multisig_op.state, frozen) {
+        (MultisigOpState::Quarantined, Some(token_account_info)) => {
+            return Err(strike_err!(
+                "utils::quarantine_if_frozen",
+                WalletError::TokenAccountFrozen,
+                "Token account {} is still frozen",
+                token_account_info.key
+            ));
+        }
+        (MultisigOpState::Quarantined, None) => {
+            msg!("Token accounts have been thawed, releasing the operation");
+            multisig_op.release(clock)?;
+        }
+        (MultisigOpState::Approved, Some(token_account_info)) if !multisig_op.is_expired(clock) => {
+            strike_err!(
+                "utils::quarantine_if_frozen",
+                WalletError::TokenAccountFrozen,
+                "Token account {} is frozen, quarantining the operation",
+                token_account_info.key
+            );
+            multisig_op.quarantine()?;
+        }
+        _ => return Ok(false),
+    }
+    let quarantined = multisig_op.state == MultisigOpState::Quarantined;
+    MultisigOp::pack_sized(multisig_op, &mut multisig_op_account_info.data.borrow_mut())?;
+    Ok(quarantined)
+}
+
+/// Build a `TransferChecked` of `amount` tokens for the token program owning the mint, or a
+/// `TransferCheckedWithFee` if it's a Token-2022 mint with a transfer fee, along with the fee
+/// withheld from what the destination receives.
+pub fn transfer_checked_instruction(
+    mint_account_info: &AccountInfo,
+    source_token_account: &Pubkey,
+    destination_token_account: &Pubkey,
+    authority: &Pubkey,
+    amount: u64,
+    epoch: Epoch,
+) -> Result<(Instruction, u64), ProgramError> {
+    let token_program_id = token_program_of(mint_account_info)?;
+    let mint_data = mint_account_info.data.borrow();
+    let mint = StateWithExtensions::<Mint2022>::unpack(&mint_data)?;
+    if let Ok(transfer_fee_config) = mint.get_extension::<TransferFeeConfig>() {
+        let fee = transfer_fee_config
+            .calculate_epoch_fee(epoch, amount)
+            .ok_or(WalletError::AmountOverflow)?;
+        return Ok((
+            transfer_checked_with_fee(
+                &token_program_id,
+                source_token_account,
+                mint_account_info.key,
+                destination_token_account,
+                authority,
+                &[],
+                amount,
+                mint.base.decimals,
+                fee,
+            )?,
+            fee,
+        ));
+    }
+    Ok((
+        spl_token_2022::instruction::transfer_checked(
+            &token_program_id,
+            source_token_account,
+            mint_account_info.key,
+            destination_token_account,
+            authority,
+            &[],
+            amount,
+            mint.base.decimals,
+        )?,
+        0,
+    ))
+}
+
+/// Build a vote program instruction withdrawing `lamports` from the given vote account,
+/// whose withdraw authority must sign.
+pub fn vote_withdraw_instruction(
+    vote_account_info: &AccountInfo,
+    withdraw_authority_info: &AccountInfo,
+    recipient_account_info: &AccountInfo,
+    lamports: u64,
+) -> Instruction {
+    // VoteInstruction::Withdraw is variant 3 of the vote program's bincode-encoded enum
+    let mut data: Vec<u8> = Vec::with_capacity(4 + 8);
+    data.extend_from_slice(&3u32.to_le_bytes());
+    data.extend_from_slice(&lamports.to_le_bytes());
+    Instruction {
+        program_id: solana_program::vote::program::id(),
+        accounts: vec![
+            AccountMeta::new(*vote_account_info.key, false),
+            AccountMeta::new(*recipient_account_info.key, false),
+            AccountMeta::new_readonly(*withdraw_authority_info.key, true),
+        ],
+        data,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::WalletError;
+    use crate::handlers::utils::{
+        calculate_expires, collect_remaining_balance, next_program_account_info,
+        transfer_checked_instruction, unpack_uninitialized, unpack_uninitialized_multisig_op,
+    };
+    use crate::model::multisig_op::MultisigOp;
+    use crate::model::wallet::Wallet;
+    use crate::test_utils::AccountInfoFixture;
+    use crate::utils::associated_token_address;
+    use solana_program::program_error::ProgramError;
+    use solana_program::program_pack::Pack;
+    use solana_program::pubkey::Pubkey;
+    use spl_token_2022::extension::transfer_fee::TransferFeeConfig;
+    use spl_token_2022::extension::{ExtensionType, StateWithExtensionsMut};
+    use spl_token_2022::instruction::TokenInstruction;
+    use spl_token_2022::state::Mint as Mint2022;
+    use std::time::Duration;
+
+    #[test]
+    fn test_next_program_account_info_checks_owner() {
+        let program_id = Pubkey::new_unique();
+        let mut owned = AccountInfoFixture::unique().owner(program_id);
+        let mut foreign = AccountInfoFixture::unique();
+        let accounts = [owned.account_info(), foreign.account_info()];
+        let accounts_iter = &mut accounts.iter();
+
+        assert!(next_program_account_info(accounts_iter, &program_id).is_ok());
+        assert_eq!(
+            next_program_account_info(accounts_iter, &program_id).err(),
+            Some(ProgramError::IncorrectProgramId)
+        );
+    }
+
+    #[test]
+    fn test_unpack_uninitialized() {
+        let mut account = AccountInfoFixture::unique().zeroed_for::<Wallet>();
+        assert!(unpack_uninitialized::<Wallet>(&account.account_info()).is_ok());
+        assert_eq!(
+            unpack_uninitialized::<MultisigOp>(&account.account_info()).err(),
+            Some(WalletError::AccountTypeMismatch.into())
+        );
+
+        let mut data = vec![0; Wallet::LEN];
+        data[0] = 1;
+        let mut initialized = AccountInfoFixture::unique().data(data);
+        assert_eq!(
+            unpack_uninitialized::<Wallet>(&initialized.account_info()).err(),
+            Some(WalletError::AccountAlreadyInitialized.into())
+        );
+    }
+
+    #[test]
+    fn test_unpack_uninitialized_multisig_op() {
+        for len in [MultisigOp::LEN, MultisigOp::len_for(1)] {
+            let mut account = AccountInfoFixture::unique().data(vec![0; len]);
+            assert!(unpack_uninitialized_multisig_op(&account.account_info()).is_ok());
+        }
+        let mut account = AccountInfoFixture::unique().zeroed_for::<Wallet>();
+        assert_eq!(
+            unpack_uninitialized_multisig_op(&account.account_info()).err(),
+            Some(WalletError::AccountTypeMismatch.into())
+        );
+
+        let mut data = vec![0; MultisigOp::len_for(2)];
+        data[0] = 1;
+        let mut initialized = AccountInfoFixture::unique().data(data);
+        assert_eq!(
+            unpack_uninitialized_multisig_op(&initialized.account_info()).err(),
+            Some(WalletError::AccountAlreadyInitialized.into())
+        );
+    }
+
+    #[test]
+    fn test_calculate_expires_overflow() {
+        assert_eq!(calculate_expires(100, Duration::from_secs(50)), Ok(150));
+        assert_eq!(
+            calculate_expires(i64::MAX, Duration::from_secs(1)),
+            Err(WalletError::ArithmeticOverflow.into())
+        );
+        // durations beyond i64::MAX seconds must not wrap around to a past expiry
+        assert_eq!(
+            calculate_expires(0, Duration::from_secs(u64::MAX)),
+            Err(WalletError::ArithmeticOverflow.into())
+        );
+    }
+
+    #[test]
+    fn test_collect_remaining_balance_overflow() {
+        let mut from = AccountInfoFixture::unique().lamports(2);
+        let mut to = AccountInfoFixture::unique().lamports(u64::MAX - 1);
+        let (from_info, to_info) = (from.account_info(), to.account_info());
+        assert_eq!(
+            collect_remaining_balance(&from_info, &to_info),
+            Err(WalletError::AmountOverflow.into())
+        );
+        assert_eq!(from_info.lamports(), 2);
+        assert_eq!(to_info.lamports(), u64::MAX - 1);
+    }
+
+    #[test]
+    fn test_transfer_checked_instruction() {
+        let (source, destination, authority) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let mut legacy_mint_data = vec![0; spl_token::state::Mint::LEN];
+        spl_token::state::Mint {
+            decimals: 6,
+            is_initialized: true,
+            ..spl_token::state::Mint::default()
+        }
+        .pack_into_slice(&mut legacy_mint_data);
+        let mut legacy_mint = AccountInfoFixture::unique()
+            .owner(spl_token::id())
+            .data(legacy_mint_data);
+        let (instruction, fee) = transfer_checked_instruction(
+            &legacy_mint.account_info(),
+            &source,
+            &destination,
+            &authority,
+            1000,
+            0,
+        )
+        .unwrap();
+        assert_eq!(instruction.program_id, spl_token::id());
+        assert_eq!(instruction.accounts[1].pubkey, legacy_mint.key());
+        assert_eq!(
+            TokenInstruction::unpack(&instruction.data).unwrap(),
+            TokenInstruction::TransferChecked {
+                amount: 1000,
+                decimals: 6
+            }
+        );
+        assert_eq!(fee, 0);
+
+        // a Token-2022 mint charging 1% up to 5 tokens
+        let mut mint_data =
+            vec![
+                0;
+                ExtensionType::get_account_len::<Mint2022>(&[ExtensionType::TransferFeeConfig])
+            ];
+        let mut mint =
+            StateWithExtensionsMut::<Mint2022>::unpack_uninitialized(&mut mint_data).unwrap();
+        let transfer_fee_config = mint.init_extension::<TransferFeeConfig>().unwrap();
+        transfer_fee_config
+            .newer_transfer_fee
+            .transfer_fee_basis_points = 100.into();
+        transfer_fee_config.newer_transfer_fee.maximum_fee = 5.into();
+        mint.base = Mint2022 {
+            decimals: 2,
+            is_initialized: true,
+            ..Mint2022::default()
+        };
+        mint.pack_base();
+        mint.init_account_type().unwrap();
+        let mut fee_mint = AccountInfoFixture::unique()
+            .owner(spl_token_2022::id())
+            .data(mint_data);
+        let (instruction, fee) = transfer_checked_instruction(
+            &fee_mint.account_info(),
+            &source,
+            &destination,
+            &authority,
+            300,
+            0,
+        )
+        .unwrap();
+        assert_eq!(instruction.program_id, spl_token_2022::id());
+        assert_eq!(fee, 3);
+        let (_, fee) = transfer_checked_instruction(
+            &fee_mint.account_info(),
+            &source,
+            &destination,
+            &authority,
+            1000,
+            0,
+        )
+        .unwrap();
+        assert_eq!(fee, 5);
+
+        let mut foreign_mint = AccountInfoFixture::unique();
+        assert_eq!(
+            transfer_checked_instruction(
+                &foreign_mint.account_info(),
+                &source,
+                &destination,
+                &authority,
+                1000,
+                0,
+            )
+            .err(),
+            Some(WalletError::InvalidTokenMintAccount.into())
+        );
+    }
+
+    #[test]
+    fn test_associated_token_address() {
+        let (owner, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        assert_eq!(
+            associated_token_address(&owner, &mint, &spl_token::id()),
+            spl_associated_token_account::get_associated_token_address(&owner, &mint)
+        );
+        assert_ne!(
+            associated_token_address(&owner, &mint, &spl_token_2022::id()),
+            associated_token_address(&owner, &mint, &spl_token::id())
+        );
+    }
+}
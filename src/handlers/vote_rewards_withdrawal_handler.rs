@@ -0,0 +1,176 @@
+use crate::error::{strike_err, WalletError};
+use crate::handlers::utils::{
+    finalize_multisig_op, get_clock_from_next_account, next_initiator_account_info,
+    next_program_account_info, next_signer_account_info, next_wallet_account_info,
+    start_multisig_transfer_op, transfer_sol_checked, validate_balance_account_and_get_seed,
+    vote_withdraw_instruction, FeeCollectionInfo,
+};
+use crate::instruction::RewardSplit;
+use crate::model::balance_account::BalanceAccountGuidHash;
+use crate::model::multisig_op::MultisigOpParams;
+use crate::model::wallet::Wallet;
+use crate::pda::{BalanceAccountSeeds, PdaSeeds};
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program::invoke_signed;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_program;
+use solana_program::vote::program as vote_program;
+
+pub fn init(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    fee_amount: u64,
+    fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+    account_guid_hash: &BalanceAccountGuidHash,
+    amount: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let vote_account_info = next_account_info(accounts_iter)?;
+    let initiator_account_info =
+        next_initiator_account_info(accounts_iter, multisig_op_account_info, wallet_account_info)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+    let rent_return_account_info = next_signer_account_info(accounts_iter)?;
+
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    let balance_account = wallet.get_active_balance_account(account_guid_hash)?;
+    wallet.validate_transfer_initiator(initiator_account_info, clock.unix_timestamp)?;
+    // the withdrawal can only be finalized once the balance account has a policy to apply
+    wallet.get_reward_splits(&balance_account)?;
+
+    if *vote_account_info.owner != vote_program::id() {
+        return Err(strike_err!(
+            "vote_rewards_withdrawal_handler::init",
+            WalletError::AccountNotRecognized,
+            "Rewards can only be withdrawn from a vote account"
+        ));
+    }
+
+    start_multisig_transfer_op(
+        multisig_op_account_info,
+        wallet_account_info,
+        &mut wallet,
+        &balance_account,
+        clock,
+        MultisigOpParams::VoteRewardsWithdrawal {
+            wallet_address: *wallet_account_info.key,
+            account_guid_hash: *account_guid_hash,
+            vote_account: *vote_account_info.key,
+            amount,
+        },
+        None,
+        *initiator_account_info.key,
+        *rent_return_account_info.key,
+        fee_amount,
+        fee_account_guid_hash,
+        &[],
+    )
+}
+
+pub fn finalize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    account_guid_hash: &BalanceAccountGuidHash,
+    amount: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let vote_account_info = next_account_info(accounts_iter)?;
+    let authority_account_info = next_account_info(accounts_iter)?;
+    let vote_program_account_info = next_account_info(accounts_iter)?;
+    let system_program_account_info = next_account_info(accounts_iter)?;
+    let rent_return_account_info = next_signer_account_info(accounts_iter)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+
+    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    let splits = &wallet.get_reward_splits(&wallet.get_balance_account(account_guid_hash)?)?;
+    let split_account_infos = splits
+        .iter()
+        .map(|_| next_account_info(accounts_iter))
+        .collect::<Result<Vec<_>, _>>()?;
+    let fee_account_info_maybe = accounts_iter.next();
+
+    if *vote_program_account_info.key != vote_program::id()
+        || *system_program_account_info.key != system_program::id()
+    {
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+
+    let wallet_guid_hash = &wallet.wallet_guid_hash;
+
+    let bump_seed = validate_balance_account_and_get_seed(
+        authority_account_info,
+        wallet_guid_hash,
+        account_guid_hash,
+        program_id,
+    )?;
+    for (split, split_account_info) in splits.iter().zip(split_account_infos.iter()) {
+        validate_balance_account_and_get_seed(
+            split_account_info,
+            wallet_guid_hash,
+            &split.account_guid_hash,
+            program_id,
+        )?;
+    }
+
+    finalize_multisig_op(
+        multisig_op_account_info,
+        wallet_account_info,
+        FeeCollectionInfo {
+            rent_return_account_info,
+            fee_account_info_maybe,
+            wallet_guid_hash,
+            program_id,
+        },
+        clock,
+        MultisigOpParams::VoteRewardsWithdrawal {
+            wallet_address: *wallet_account_info.key,
+            account_guid_hash: *account_guid_hash,
+            vote_account: *vote_account_info.key,
+            amount,
+        },
+        || -> ProgramResult {
+            invoke_signed(
+                &vote_withdraw_instruction(
+                    vote_account_info,
+                    authority_account_info,
+                    authority_account_info,
+                    amount,
+                ),
+                &[
+                    vote_account_info.clone(),
+                    authority_account_info.clone(),
+                    vote_program_account_info.clone(),
+                ],
+                &[&BalanceAccountSeeds {
+                    wallet_guid_hash,
+                    account_guid_hash,
+                }
+                .signer_seeds(&[bump_seed])],
+            )?;
+
+            let shares = RewardSplit::split_amount(amount, splits);
+            for (share, split_account_info) in shares.into_iter().zip(split_account_infos.iter()) {
+                // the authority's own share is already in place
+                if split_account_info.key == authority_account_info.key || share == 0 {
+                    continue;
+                }
+                transfer_sol_checked(
+                    wallet_guid_hash,
+                    authority_account_info.clone(),
+                    account_guid_hash,
+                    bump_seed,
+                    system_program_account_info.clone(),
+                    (*split_account_info).clone(),
+                    share,
+                )?;
+            }
+            Ok(())
+        },
+        || -> ProgramResult { Ok(()) },
+    )
+}
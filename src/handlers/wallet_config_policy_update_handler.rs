@@ -1,13 +1,16 @@
+use crate::handlers::program_parameters_handler;
 use crate::handlers::utils::{
-    finalize_multisig_op, get_clock_from_next_account, next_program_account_info,
-    next_signer_account_info, next_wallet_account_info, start_multisig_config_op,
-    FeeCollectionInfo,
+    finalize_multisig_op, get_clock_from_next_account, next_initiator_account_info,
+    next_program_account_info, next_signer_account_info, next_wallet_account_info,
+    return_policy_diff, start_multisig_config_op, FeeCollectionInfo,
 };
+use crate::handlers::wallet_replication_handler::mirror_to_standby;
 use crate::instruction::WalletConfigPolicyUpdate;
 use crate::model::balance_account::BalanceAccountGuidHash;
 use crate::model::multisig_op::MultisigOpParams;
 use crate::model::wallet::Wallet;
-use solana_program::account_info::{next_account_info, AccountInfo};
+use crate::model::wallet_replication::MirroredUpdate;
+use solana_program::account_info::AccountInfo;
 use solana_program::entrypoint::ProgramResult;
 use solana_program::program_pack::Pack;
 use solana_program::pubkey::Pubkey;
@@ -22,18 +25,24 @@ pub fn init(
     let accounts_iter = &mut accounts.iter();
     let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
     let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
-    let initiator_account_info = next_account_info(accounts_iter)?;
+    let initiator_account_info =
+        next_initiator_account_info(accounts_iter, multisig_op_account_info, wallet_account_info)?;
     let clock = get_clock_from_next_account(accounts_iter)?;
     let rent_return_account_info = next_signer_account_info(accounts_iter)?;
 
-    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
 
-    wallet.validate_config_initiator(initiator_account_info)?;
+    wallet.validate_config_initiator(initiator_account_info, clock.unix_timestamp)?;
+    wallet.validate_not_standby()?;
+    update.validate_canonical()?;
     wallet.validate_config_policy_update(update)?;
+    program_parameters_handler::load(program_id, accounts)?
+        .validate_approval_timeout(&update.approval_timeout_for_config)?;
 
     start_multisig_config_op(
         &multisig_op_account_info,
-        &wallet,
+        wallet_account_info,
+        &mut wallet,
         clock,
         MultisigOpParams::UpdateWalletConfigPolicy {
             wallet_address: *wallet_account_info.key,
@@ -59,12 +68,14 @@ pub fn finalize(
     let rent_return_account_info = next_signer_account_info(accounts_iter)?;
     let clock = get_clock_from_next_account(accounts_iter)?;
     let fee_account_info_maybe = accounts_iter.next();
+    let now = clock.unix_timestamp;
 
     let wallet_guid_hash =
         &Wallet::wallet_guid_hash_from_slice(&wallet_account_info.data.borrow())?;
 
     finalize_multisig_op(
         &multisig_op_account_info,
+        wallet_account_info,
         FeeCollectionInfo {
             rent_return_account_info,
             fee_account_info_maybe,
@@ -78,12 +89,47 @@ pub fn finalize(
         },
         || -> ProgramResult {
             let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow_mut())?;
-            wallet.update_config_policy(update)?;
+            wallet.validate_not_standby()?;
+            wallet.schedule_config_policy_update(update, now)?;
+            let replication = wallet.replication;
             Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
-            Ok(())
+            mirror_to_standby(
+                program_id,
+                accounts,
+                wallet_account_info.key,
+                &replication,
+                MirroredUpdate::ConfigPolicy(update.clone()),
+            )
         },
         || -> ProgramResult { Ok(()) },
     )?;
 
     Ok(())
 }
+
+pub fn activate(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+
+    // the staged policy was approved when its update was finalized, so anyone may activate it
+    // once its timelock has elapsed
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    wallet.activate_pending_config_policy(clock.unix_timestamp)?;
+    Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+    Ok(())
+}
+
+pub fn simulate(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    update: &WalletConfigPolicyUpdate,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+
+    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    update.validate_canonical()?;
+    return_policy_diff(&wallet.config_policy_diff(update)?);
+    Ok(())
+}
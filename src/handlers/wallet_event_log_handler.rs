@@ -0,0 +1,178 @@
+use crate::constants::PUBKEY_BYTES;
+use crate::error::{strike_err, WalletError};
+use crate::handlers::utils::{
+    create_pda_account, next_signer_account_info, next_wallet_account_info, unpack_subject_op,
+    OpTransitions,
+};
+use crate::instruction::ProgramInstruction;
+use crate::model::multisig_op::MultisigOp;
+use crate::model::wallet::WalletCompaction;
+use crate::model::wallet_event_log::{WalletEventKind, WalletEventLog};
+use crate::pda::{self, WalletEventLogSeeds};
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::Sysvar;
+
+pub fn init(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let event_log_account_info = next_account_info(accounts_iter)?;
+    let payer_account_info = next_signer_account_info(accounts_iter)?;
+    let system_program_account = next_account_info(accounts_iter)?;
+
+    let seeds = WalletEventLogSeeds {
+        wallet: wallet_account_info.key,
+    };
+    let bump_seed = pda::verify(program_id, &seeds, event_log_account_info.key, None)?;
+    if event_log_account_info.owner == program_id {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    create_pda_account(
+        payer_account_info,
+        event_log_account_info,
+        system_program_account,
+        WalletEventLog::LEN,
+        program_id,
+        &seeds,
+        bump_seed,
+    )?;
+
+    WalletEventLog::pack(
+        WalletEventLog::new(*wallet_account_info.key),
+        &mut event_log_account_info.data.borrow_mut(),
+    )
+}
+
+/// The event log supplied to an instruction, if there is one. Only the program can write to an
+/// account it owns, and it only creates event logs at their PDA, so an account owned by the
+/// program and of the event log's size is one.
+fn event_log_account_info<'a, 'b>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'b>],
+) -> Option<&'a AccountInfo<'b>> {
+    accounts.iter().rev().find(|account_info| {
+        account_info.owner == program_id
+            && account_info.is_writable
+            && account_info.data_len() == WalletEventLog::LEN
+    })
+}
+
+/// The op an instruction is about, the first of its accounts, as it was before the
+/// instruction, for an instruction supplied an event log. The op isn't initialized yet for an
+/// instruction that starts it.
+pub struct LoggedOp {
+    before: Option<MultisigOp>,
+}
+
+pub fn snapshot(program_id: &Pubkey, accounts: &[AccountInfo]) -> Option<LoggedOp> {
+    event_log_account_info(program_id, accounts)?;
+    Some(LoggedOp {
+        before: unpack_subject_op(program_id, accounts),
+    })
+}
+
+/// Appends the events of the op an instruction was about to the event log supplied to it,
+/// going by how the instruction changed the op: whether it started, approved, denied,
+/// finalized or expired it. The op has to belong to the log's wallet.
+pub fn record(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+    logged_op: Option<LoggedOp>,
+) -> ProgramResult {
+    let before = match logged_op {
+        Some(logged_op) => logged_op.before,
+        None => return Ok(()),
+    };
+    let after = unpack_subject_op(program_id, accounts);
+    let clock = Clock::get()?;
+    let transitions = OpTransitions::between(before.as_ref(), after.as_ref(), &clock);
+
+    let mut events = Vec::new();
+    if transitions.initiated {
+        events.push(WalletEventKind::OpInitiated);
+    }
+    if transitions.approved {
+        events.push(WalletEventKind::OpApproved);
+    }
+    if transitions.denied {
+        events.push(WalletEventKind::OpDenied);
+    }
+    if transitions.executed {
+        events.push(WalletEventKind::OpExecuted);
+        if ProgramInstruction::unpack(instruction_data)?.is_config_finalize() {
+            events.push(WalletEventKind::ConfigChanged);
+        }
+    }
+    if transitions.expired {
+        events.push(WalletEventKind::OpExpired);
+    }
+    if events.is_empty() {
+        return Ok(());
+    }
+
+    let wallet = after.or(before).map(|op| op.wallet).unwrap_or_default();
+    let event_log_account_info =
+        event_log_account_info(program_id, accounts).ok_or(WalletError::AccountNotRecognized)?;
+    let mut event_log_data = event_log_account_info.data.borrow_mut();
+    if WalletEventLog::wallet_from_slice(&event_log_data)? != wallet {
+        return Err(strike_err!(
+            "wallet_event_log_handler::record",
+            WalletError::InvalidEventLog,
+            "Event log isn't the log of the op's wallet"
+        ));
+    }
+    for kind in events {
+        WalletEventLog::append_to_slice(
+            &mut event_log_data,
+            kind,
+            *accounts[0].key,
+            clock.slot,
+            clock.unix_timestamp,
+        )?;
+    }
+    Ok(())
+}
+
+/// Appends the remap table of a compaction to the event log supplied to the instruction, if
+/// there is one, so that indexers can follow the wallet's entries to their new slots without
+/// relying on the transaction logs.
+pub fn record_compaction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    wallet: &Pubkey,
+    compaction: &WalletCompaction,
+) -> ProgramResult {
+    let event_log_account_info = match event_log_account_info(program_id, accounts) {
+        Some(event_log_account_info) => event_log_account_info,
+        None => return Ok(()),
+    };
+    let mut event_log_data = event_log_account_info.data.borrow_mut();
+    if WalletEventLog::wallet_from_slice(&event_log_data)? != *wallet {
+        return Err(strike_err!(
+            "wallet_event_log_handler::record_compaction",
+            WalletError::InvalidEventLog,
+            "Event log isn't the log of the compacted wallet"
+        ));
+    }
+
+    let clock = Clock::get()?;
+    let mut remap = Vec::new();
+    compaction.pack(&mut remap);
+    for chunk in remap.chunks(PUBKEY_BYTES) {
+        let mut chunk_bytes = [0; PUBKEY_BYTES];
+        chunk_bytes[..chunk.len()].copy_from_slice(chunk);
+        WalletEventLog::append_to_slice(
+            &mut event_log_data,
+            WalletEventKind::SlotsRemapped,
+            Pubkey::new_from_array(chunk_bytes),
+            clock.slot,
+            clock.unix_timestamp,
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use crate::handlers::utils::{next_signer_account_info, next_wallet_account_info};
+use crate::handlers::{init_wallet_handler, program_parameters_handler};
+use crate::instruction::InitialWalletConfig;
+use crate::model::wallet::{Wallet, WalletGuidHash};
+use crate::model::wallet_provisioning::WalletProvisioning;
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+
+pub fn init(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    wallet_guid_hash: &WalletGuidHash,
+    initial_config: &InitialWalletConfig,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let wallet_account_info = next_account_info(accounts_iter)?;
+    let _assistant_account_info = next_account_info(accounts_iter)?;
+    let _rent_return_account_info = next_account_info(accounts_iter)?;
+    let creator_account_info = next_signer_account_info(accounts_iter)?;
+
+    init_wallet_handler::handle(program_id, accounts, wallet_guid_hash, initial_config)?;
+
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    wallet.provisioning = Some(WalletProvisioning {
+        creator: *creator_account_info.key,
+    });
+    Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())
+}
+
+pub fn update(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config: &InitialWalletConfig,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let creator_account_info = next_account_info(accounts_iter)?;
+
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    wallet.validate_provisioning_creator(creator_account_info)?;
+    wallet.reprovision(config)?;
+    let parameters = program_parameters_handler::load(program_id, accounts)?;
+    parameters.validate_signer_count(wallet.signers.filled_slots().len())?;
+    parameters.validate_approval_timeout(&wallet.approval_timeout_for_config)?;
+    Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())
+}
+
+pub fn activate(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    wallet.activate(accounts_iter.as_slice())?;
+    Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())
+}
@@ -0,0 +1,70 @@
+use crate::error::WalletError;
+use crate::handlers::utils::{
+    create_pda_account, next_signer_account_info, next_wallet_account_info,
+};
+use crate::model::wallet::Wallet;
+use crate::model::wallet_read_model::WalletReadModel;
+use crate::pda::{self, WalletReadModelSeeds};
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+
+pub fn export(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let read_model_account_info = next_account_info(accounts_iter)?;
+    let payer_account_info = next_signer_account_info(accounts_iter)?;
+    let system_program_account = next_account_info(accounts_iter)?;
+
+    let seeds = WalletReadModelSeeds {
+        wallet: wallet_account_info.key,
+    };
+    let bump_seed = pda::verify(program_id, &seeds, read_model_account_info.key, None)?;
+    if read_model_account_info.owner != program_id {
+        create_pda_account(
+            payer_account_info,
+            read_model_account_info,
+            system_program_account,
+            WalletReadModel::LEN,
+            program_id,
+            &seeds,
+            bump_seed,
+        )?;
+    }
+
+    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    WalletReadModel::pack(
+        WalletReadModel::of(wallet_account_info.key, &wallet),
+        &mut read_model_account_info.data.borrow_mut(),
+    )
+}
+
+/// Refreshes the read model supplied as the last account of a config finalize, if there is
+/// one. Only the program can write to an account it owns, and it only creates read models at
+/// their PDA, so an account owned by the program and of the read model's size is one.
+pub fn refresh(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let read_model_account_info = match accounts.last() {
+        Some(account_info)
+            if account_info.owner == program_id
+                && account_info.is_writable
+                && account_info.data_len() == WalletReadModel::LEN =>
+        {
+            account_info
+        }
+        _ => return Ok(()),
+    };
+    let read_model = WalletReadModel::unpack(&read_model_account_info.data.borrow())?;
+    let wallet_account_info = accounts
+        .iter()
+        .find(|account_info| {
+            *account_info.key == read_model.wallet && account_info.owner == program_id
+        })
+        .ok_or(WalletError::AccountNotRecognized)?;
+
+    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    WalletReadModel::pack(
+        WalletReadModel::of(wallet_account_info.key, &wallet),
+        &mut read_model_account_info.data.borrow_mut(),
+    )
+}
@@ -0,0 +1,59 @@
+use crate::error::{strike_err, WalletError};
+use crate::handlers::utils::create_pda_account;
+use crate::model::wallet::WalletGuidHash;
+use crate::model::wallet_registry::WalletRegistration;
+use crate::pda::WalletRegistrationSeeds;
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_program;
+
+/// Registers a wallet being initialized by creating its registration PDA, at the payer's
+/// expense. Fails if the wallet's GUID hash is registered already, which a wallet initialized
+/// with it first claims for good. The registration and system program accounts are looked up
+/// by key.
+pub fn register<'a>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo<'a>],
+    payer_account_info: &AccountInfo<'a>,
+    wallet_guid_hash: &WalletGuidHash,
+    wallet: &Pubkey,
+) -> ProgramResult {
+    let (registration_address, bump_seed) =
+        WalletRegistration::find_address(wallet_guid_hash, program_id);
+    let registration_account_info = accounts
+        .iter()
+        .find(|account_info| *account_info.key == registration_address)
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let system_program_account = accounts
+        .iter()
+        .find(|account_info| *account_info.key == system_program::id())
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    if registration_account_info.owner == program_id {
+        return Err(strike_err!(
+            "wallet_registry_handler::register",
+            WalletError::WalletAlreadyRegistered,
+            "A wallet with this GUID hash is already registered"
+        ));
+    }
+    create_pda_account(
+        payer_account_info,
+        registration_account_info,
+        system_program_account,
+        WalletRegistration::LEN,
+        program_id,
+        &WalletRegistrationSeeds { wallet_guid_hash },
+        bump_seed,
+    )?;
+    WalletRegistration::pack(
+        WalletRegistration {
+            is_initialized: true,
+            wallet_guid_hash: *wallet_guid_hash,
+            wallet: *wallet,
+        },
+        &mut registration_account_info.data.borrow_mut(),
+    )
+}
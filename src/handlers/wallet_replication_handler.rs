@@ -0,0 +1,194 @@
+use crate::error::{strike_err, WalletError};
+use crate::handlers::utils::{
+    finalize_multisig_op, get_clock_from_next_account, next_initiator_account_info,
+    next_program_account_info, next_signer_account_info, next_wallet_account_info,
+    start_multisig_config_op, FeeCollectionInfo,
+};
+use crate::instruction::ProgramInstruction;
+use crate::model::balance_account::BalanceAccountGuidHash;
+use crate::model::multisig_op::MultisigOpParams;
+use crate::model::wallet::Wallet;
+use crate::model::wallet_replication::{MirroredUpdate, WalletReplication};
+use crate::pda::{MirrorAuthoritySeeds, PdaSeeds};
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::program::invoke_signed;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::Sysvar;
+
+pub fn init(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    fee_amount: u64,
+    fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+    replication: &Option<WalletReplication>,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let initiator_account_info =
+        next_initiator_account_info(accounts_iter, multisig_op_account_info, wallet_account_info)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+    let rent_return_account_info = next_signer_account_info(accounts_iter)?;
+
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    wallet.validate_config_initiator(initiator_account_info, clock.unix_timestamp)?;
+    wallet.validate_replication_update(wallet_account_info.key, replication)?;
+
+    start_multisig_config_op(
+        multisig_op_account_info,
+        wallet_account_info,
+        &mut wallet,
+        clock,
+        MultisigOpParams::UpdateWalletReplication {
+            wallet_address: *wallet_account_info.key,
+            replication: *replication,
+        },
+        *initiator_account_info.key,
+        *rent_return_account_info.key,
+        fee_amount,
+        fee_account_guid_hash,
+    )
+}
+
+pub fn finalize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    replication: &Option<WalletReplication>,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let rent_return_account_info = next_signer_account_info(accounts_iter)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+    let fee_account_info_maybe = accounts_iter.next();
+
+    let wallet_guid_hash =
+        &Wallet::wallet_guid_hash_from_slice(&wallet_account_info.data.borrow())?;
+
+    finalize_multisig_op(
+        multisig_op_account_info,
+        wallet_account_info,
+        FeeCollectionInfo {
+            rent_return_account_info,
+            fee_account_info_maybe,
+            wallet_guid_hash,
+            program_id,
+        },
+        clock,
+        MultisigOpParams::UpdateWalletReplication {
+            wallet_address: *wallet_account_info.key,
+            replication: *replication,
+        },
+        || -> ProgramResult {
+            let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+            if let Some(WalletReplication::Primary { standby }) = replication {
+                let standby_account_info = standby_account_info(program_id, accounts, standby)?;
+                let standby_wallet = Wallet::unpack(&standby_account_info.data.borrow())?;
+                wallet.validate_standby(wallet_account_info.key, &standby_wallet)?;
+            }
+            wallet.update_replication(replication);
+            Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+            Ok(())
+        },
+        || -> ProgramResult { Ok(()) },
+    )
+}
+
+pub fn apply(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    update: &MirroredUpdate,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let mirror_authority_account_info = next_account_info(accounts_iter)?;
+
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    wallet.validate_mirror_authority(mirror_authority_account_info, program_id)?;
+    wallet.apply_mirrored_update(update, Clock::get()?.unix_timestamp)?;
+    Wallet::pack(wallet, &mut wallet_account_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Mirrors a config change a wallet has just finalized into its standby, if it's a primary,
+/// failing the finalize if the standby doesn't take it so the two never drift apart.
+pub fn mirror_to_standby(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    wallet_address: &Pubkey,
+    replication: &Option<WalletReplication>,
+    update: MirroredUpdate,
+) -> ProgramResult {
+    let standby = match replication {
+        Some(WalletReplication::Primary { standby }) => standby,
+        _ => return Ok(()),
+    };
+    let standby_account_info = standby_account_info(program_id, accounts, standby)?;
+    let (mirror_authority, bump_seed) =
+        WalletReplication::mirror_authority(wallet_address, program_id);
+    let mirror_authority_account_info = find_account_info(accounts, &mirror_authority)?;
+    let program_account_info = find_account_info(accounts, program_id)?;
+
+    invoke_signed(
+        &Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new(*standby, false),
+                AccountMeta::new_readonly(mirror_authority, true),
+            ],
+            data: ProgramInstruction::ApplyMirroredUpdate { update }.pack(),
+        },
+        &[
+            standby_account_info.clone(),
+            mirror_authority_account_info.clone(),
+            program_account_info.clone(),
+        ],
+        &[&MirrorAuthoritySeeds {
+            primary: wallet_address,
+        }
+        .signer_seeds(&[bump_seed])],
+    )
+}
+
+fn standby_account_info<'a, 'b>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'b>],
+    standby: &Pubkey,
+) -> Result<&'a AccountInfo<'b>, ProgramError> {
+    match accounts
+        .iter()
+        .find(|account_info| account_info.key == standby)
+    {
+        Some(account_info) => {
+            next_wallet_account_info(&mut std::iter::once(account_info), program_id)
+        }
+        None => Err(strike_err!(
+            "wallet_replication_handler::standby_account_info",
+            WalletError::StandbyWalletNotInSync,
+            "Standby wallet {} is not among the accounts",
+            standby
+        )),
+    }
+}
+
+fn find_account_info<'a, 'b>(
+    accounts: &'a [AccountInfo<'b>],
+    key: &Pubkey,
+) -> Result<&'a AccountInfo<'b>, ProgramError> {
+    accounts
+        .iter()
+        .find(|account_info| account_info.key == key)
+        .ok_or_else(|| {
+            strike_err!(
+                "wallet_replication_handler::find_account_info",
+                ProgramError::NotEnoughAccountKeys,
+                "Account {} is not among the accounts",
+                key
+            )
+        })
+}
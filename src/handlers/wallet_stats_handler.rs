@@ -0,0 +1,143 @@
+use crate::error::{strike_err, WalletError};
+use crate::handlers::utils::{
+    create_pda_account, next_signer_account_info, next_wallet_account_info, unpack_subject_op,
+    OpTransitions,
+};
+use crate::instruction::ProgramInstruction;
+use crate::model::multisig_op::MultisigOp;
+use crate::model::wallet_stats::{OpCounter, WalletStats};
+use crate::pda::{self, WalletStatsSeeds};
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::Sysvar;
+
+pub fn init(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let stats_account_info = next_account_info(accounts_iter)?;
+    let payer_account_info = next_signer_account_info(accounts_iter)?;
+    let system_program_account = next_account_info(accounts_iter)?;
+
+    let seeds = WalletStatsSeeds {
+        wallet: wallet_account_info.key,
+    };
+    let bump_seed = pda::verify(program_id, &seeds, stats_account_info.key, None)?;
+    if stats_account_info.owner == program_id {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    create_pda_account(
+        payer_account_info,
+        stats_account_info,
+        system_program_account,
+        WalletStats::LEN,
+        program_id,
+        &seeds,
+        bump_seed,
+    )?;
+
+    WalletStats::pack(
+        WalletStats::new(*wallet_account_info.key),
+        &mut stats_account_info.data.borrow_mut(),
+    )
+}
+
+/// The stats supplied to an instruction, if there are any. Like the event log, they are told
+/// apart from the instruction's other accounts by their owner and size, past the op account.
+fn stats_account_info<'a, 'b>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'b>],
+) -> Option<&'a AccountInfo<'b>> {
+    accounts.iter().skip(1).rev().find(|account_info| {
+        account_info.owner == program_id
+            && account_info.is_writable
+            && account_info.data_len() == WalletStats::LEN
+    })
+}
+
+/// The op an instruction is about as it was before the instruction, for an instruction
+/// supplied the wallet's stats.
+pub struct CountedOp {
+    before: Option<MultisigOp>,
+}
+
+pub fn snapshot(program_id: &Pubkey, accounts: &[AccountInfo]) -> Option<CountedOp> {
+    stats_account_info(program_id, accounts)?;
+    Some(CountedOp {
+        before: unpack_subject_op(program_id, accounts),
+    })
+}
+
+/// Counts how an instruction changed the op it was about in the stats supplied to it, under
+/// the op's type, and adds what an executed transfer moved out of the wallet to its outflow.
+/// The op has to belong to the stats' wallet.
+pub fn record(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+    counted_op: Option<CountedOp>,
+) -> ProgramResult {
+    let before = match counted_op {
+        Some(counted_op) => counted_op.before,
+        None => return Ok(()),
+    };
+    let after = unpack_subject_op(program_id, accounts);
+    let transitions = OpTransitions::between(before.as_ref(), after.as_ref(), &Clock::get()?);
+
+    let counters: Vec<OpCounter> = [
+        (transitions.initiated, OpCounter::Initiated),
+        (transitions.approved, OpCounter::Approved),
+        (transitions.denied, OpCounter::Denied),
+        (transitions.expired, OpCounter::Expired),
+    ]
+    .into_iter()
+    .filter(|(counted, _)| *counted)
+    .map(|(_, counter)| counter)
+    .collect();
+    // a transfer finalized in steps only moves funds when it is executed, which closes the op
+    let outflow = match ProgramInstruction::unpack(instruction_data)? {
+        ProgramInstruction::FinalizeTransfer {
+            amount, token_mint, ..
+        } if transitions.finalized => Some((
+            Some(token_mint).filter(|mint| *mint != Pubkey::default()),
+            amount,
+        )),
+        ProgramInstruction::ExecuteTransfer {
+            amount, token_mint, ..
+        } if after.is_none() => Some((
+            Some(token_mint).filter(|mint| *mint != Pubkey::default()),
+            amount,
+        )),
+        ProgramInstruction::FinalizeCommittedTransfer { amount, .. } if transitions.finalized => {
+            Some((None, amount))
+        }
+        _ => None,
+    };
+    let op = match after.or(before) {
+        Some(op) if !counters.is_empty() || outflow.is_some() => op,
+        _ => return Ok(()),
+    };
+
+    let stats_account_info =
+        stats_account_info(program_id, accounts).ok_or(WalletError::AccountNotRecognized)?;
+    let mut stats_data = stats_account_info.data.borrow_mut();
+    if WalletStats::wallet_from_slice(&stats_data)? != op.wallet {
+        return Err(strike_err!(
+            "wallet_stats_handler::record",
+            WalletError::InvalidWalletStats,
+            "Stats aren't the stats of the op's wallet"
+        ));
+    }
+    if let Some(op_code) = op.op_code {
+        for counter in counters {
+            WalletStats::increment_in_slice(&mut stats_data, op_code, counter)?;
+        }
+    }
+    if let Some((token_mint, amount)) = outflow {
+        WalletStats::add_outflow_in_slice(&mut stats_data, token_mint.as_ref(), amount)?;
+    }
+    Ok(())
+}
@@ -1,13 +1,14 @@
-use crate::error::WalletError;
+use crate::error::{strike_err, WalletError};
 use crate::handlers::utils::{
     create_associated_token_account_instruction, finalize_multisig_op, get_clock_from_next_account,
-    next_program_account_info, next_signer_account_info, next_wallet_account_info,
-    start_multisig_transfer_op, transfer_sol_checked, validate_balance_account_and_get_seed,
-    FeeCollectionInfo,
+    next_initiator_account_info, next_program_account_info, next_signer_account_info,
+    next_wallet_account_info, quarantine_if_frozen, start_multisig_transfer_op,
+    transfer_sol_checked, validate_balance_account_and_get_seed, FeeCollectionInfo,
 };
 use crate::model::balance_account::BalanceAccountGuidHash;
-use crate::model::multisig_op::{MultisigOpParams, WrapDirection};
+use crate::model::multisig_op::{MultisigOp, MultisigOpParams, WrapDirection};
 use crate::model::wallet::Wallet;
+use crate::pda::{BalanceAccountSeeds, PdaSeeds, TemporaryUnwrappingAccountSeeds};
 use solana_program::account_info::{next_account_info, AccountInfo};
 use solana_program::entrypoint::ProgramResult;
 use solana_program::msg;
@@ -16,11 +17,15 @@ use solana_program::program_error::ProgramError;
 use solana_program::program_pack::Pack;
 use solana_program::pubkey::Pubkey;
 use solana_program::rent::Rent;
+use solana_program::system_instruction;
 use solana_program::system_program;
 use solana_program::sysvar::Sysvar;
 use spl_associated_token_account::get_associated_token_address;
 use spl_associated_token_account::tools::account::create_pda_account;
 use spl_token::state::Account as SPLAccount;
+use spl_token_2022::extension::StateWithExtensions;
+use spl_token_2022::state::Account as TokenAccount2022;
+use std::fmt::Display;
 
 pub fn init(
     program_id: &Pubkey,
@@ -38,57 +43,65 @@ pub fn init(
     let wrapped_sol_account_info = next_account_info(accounts_iter)?;
     let native_mint_account_info = next_account_info(accounts_iter)?;
     if *native_mint_account_info.key != spl_token::native_mint::id() {
-        msg!("Invalid native mint account set");
-        return Err(ProgramError::InvalidAccountData);
+        return Err(strike_err!(
+            "wrap_unwrap_handler::init",
+            ProgramError::InvalidAccountData,
+            "Invalid native mint account set"
+        ));
     }
 
-    let initiator_account = next_account_info(accounts_iter)?;
+    let initiator_account =
+        next_initiator_account_info(accounts_iter, multisig_op_account_info, wallet_account_info)?;
     let clock = get_clock_from_next_account(accounts_iter)?;
     let rent_return_account_info = next_signer_account_info(accounts_iter)?;
 
-    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
-    let balance_account = wallet.get_balance_account(&account_guid_hash)?;
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    wallet.validate_not_frozen()?;
+    let balance_account = wallet.get_active_balance_account(account_guid_hash)?;
 
-    wallet.validate_transfer_initiator(initiator_account)?;
+    wallet.validate_transfer_initiator(initiator_account, clock.unix_timestamp)?;
 
-    if direction == WrapDirection::WRAP && *wrapped_sol_account_info.owner == Pubkey::default() {
-        // we need to create the wrapped SOL account (if it had been created already
-        // it would be owned by the Token program). Since this is an attempt to wrap
-        // SOL, it stands to reason they have some SOL in their account, so we assume
-        // they have enough to create this account (if they don't, it will just fail)
-        let bump_seed = validate_balance_account_and_get_seed(
+    if direction == WrapDirection::WRAP {
+        // the wrapped SOL account is only created when the wrap is finalized, so that a denied
+        // or expired wrap doesn't leave an empty token account behind. Its rent is set aside in
+        // the multisig op account until then, even if the account exists now, since it may be
+        // closed before finalize. The rent is returned along with the op account's own rent if
+        // it ends up not being needed.
+        validate_balance_account_and_get_seed(
             balance_account_info,
             &wallet.wallet_guid_hash,
             account_guid_hash,
             program_id,
         )?;
-        invoke_signed(
-            &create_associated_token_account_instruction(
-                balance_account_info,
-                wrapped_sol_account_info,
-                balance_account_info,
-                native_mint_account_info,
+        if *wrapped_sol_account_info.key
+            != get_associated_token_address(balance_account_info.key, native_mint_account_info.key)
+        {
+            return Err(WalletError::InvalidSourceTokenAccount.into());
+        }
+        let rent = Rent::get()?;
+        invoke(
+            &system_instruction::transfer(
+                rent_return_account_info.key,
+                multisig_op_account_info.key,
+                rent.minimum_balance(SPLAccount::LEN),
             ),
             accounts,
-            &[&[
-                wallet.wallet_guid_hash.to_bytes(),
-                account_guid_hash.to_bytes(),
-                &[bump_seed],
-            ]],
         )?;
     } else if direction == WrapDirection::UNWRAP {
         let temporary_unwrapping_account = next_account_info(accounts_iter)?;
         let system_program_account_info = next_account_info(accounts_iter)?;
-        let (temporary_unwrapping_account_pda, unwrapping_bump_seed) = Pubkey::find_program_address(
-            &[
-                &wallet.wallet_guid_hash.to_bytes(),
-                &multisig_op_account_info.key.to_bytes(),
-            ],
-            program_id,
-        );
+        let unwrapping_seeds = TemporaryUnwrappingAccountSeeds {
+            wallet_guid_hash: &wallet.wallet_guid_hash,
+            multisig_op: multisig_op_account_info.key,
+        };
+        let (temporary_unwrapping_account_pda, unwrapping_bump_seed) =
+            unwrapping_seeds.find_address(program_id);
         if temporary_unwrapping_account_pda != *temporary_unwrapping_account.key {
-            msg!("Wrong temporary unwrapping account");
-            return Err(ProgramError::InvalidAccountData);
+            return Err(strike_err!(
+                "wrap_unwrap_handler::init",
+                ProgramError::InvalidAccountData,
+                "Wrong temporary unwrapping account"
+            ));
         }
         let rent = Rent::get()?;
         create_pda_account(
@@ -98,11 +111,7 @@ pub fn init(
             &spl_token::id(),
             &system_program_account_info,
             temporary_unwrapping_account,
-            &[
-                &wallet.wallet_guid_hash.to_bytes(),
-                &multisig_op_account_info.key.to_bytes(),
-                &[unwrapping_bump_seed],
-            ],
+            &unwrapping_seeds.signer_seeds(&[unwrapping_bump_seed]),
         )?;
 
         let balance_account_bump_seed = validate_balance_account_and_get_seed(
@@ -120,17 +129,39 @@ pub fn init(
                 balance_account_info.key,
             )?,
             accounts,
-            &[&[
-                wallet.wallet_guid_hash.to_bytes(),
-                account_guid_hash.to_bytes(),
-                &[balance_account_bump_seed],
-            ]],
+            &[&BalanceAccountSeeds {
+                wallet_guid_hash: &wallet.wallet_guid_hash,
+                account_guid_hash,
+            }
+            .signer_seeds(&[balance_account_bump_seed])],
+        )?;
+    } else if direction == WrapDirection::UNWRAP_ALL {
+        // the wrapped SOL account is closed at finalize, so the amount is whatever it holds then
+        if amount != 0 {
+            return Err(strike_err!(
+                "wrap_unwrap_handler::init",
+                ProgramError::InvalidArgument,
+                "Unwrapping the whole balance takes an amount of 0, not {}",
+                amount
+            ));
+        }
+        validate_balance_account_and_get_seed(
+            balance_account_info,
+            &wallet.wallet_guid_hash,
+            account_guid_hash,
+            program_id,
         )?;
+        if *wrapped_sol_account_info.key
+            != get_associated_token_address(balance_account_info.key, native_mint_account_info.key)
+        {
+            return Err(WalletError::InvalidSourceTokenAccount.into());
+        }
     }
 
     start_multisig_transfer_op(
         &multisig_op_account_info,
-        &wallet,
+        wallet_account_info,
+        &mut wallet,
         &balance_account,
         clock,
         MultisigOpParams::Wrap {
@@ -139,10 +170,12 @@ pub fn init(
             amount,
             direction,
         },
+        None,
         *initiator_account.key,
         *rent_return_account_info.key,
         fee_amount,
         fee_account_guid_hash,
+        &[],
     )
 }
 
@@ -161,15 +194,16 @@ pub fn finalize(
     let rent_return_account_info = next_signer_account_info(accounts_iter)?;
     let clock = get_clock_from_next_account(accounts_iter)?;
     let wrapped_sol_account_info = next_account_info(accounts_iter)?;
-    // spl_token_program_info account
-    let _ = next_account_info(accounts_iter)?;
+    let spl_token_program_info = next_account_info(accounts_iter)?;
     let native_mint_account_info = next_account_info(accounts_iter)?;
     if *native_mint_account_info.key != spl_token::native_mint::id() {
-        msg!("Invalid native mint account set");
-        return Err(ProgramError::InvalidAccountData);
+        return Err(strike_err!(
+            "wrap_unwrap_handler::finalize",
+            ProgramError::InvalidAccountData,
+            "Invalid native mint account set"
+        ));
     }
-    // spl_associated_token_program_info account
-    let _ = next_account_info(accounts_iter)?;
+    let spl_associated_token_program_info = next_account_info(accounts_iter)?;
 
     let temporary_unwrapping_account = if direction == WrapDirection::UNWRAP {
         Some(next_account_info(accounts_iter)?)
@@ -178,16 +212,17 @@ pub fn finalize(
     };
     let unwrapping_bump_seed = if direction == WrapDirection::UNWRAP {
         let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
-        let (key, seed) = Pubkey::find_program_address(
-            &[
-                wallet.wallet_guid_hash.to_bytes(),
-                &multisig_op_account_info.key.to_bytes(),
-            ],
-            program_id,
-        );
+        let (key, seed) = TemporaryUnwrappingAccountSeeds {
+            wallet_guid_hash: &wallet.wallet_guid_hash,
+            multisig_op: multisig_op_account_info.key,
+        }
+        .find_address(program_id);
         if *temporary_unwrapping_account.unwrap().key != key {
-            msg!("Wrong temporary unwrapping account");
-            return Err(ProgramError::InvalidAccountData);
+            return Err(strike_err!(
+                "wrap_unwrap_handler::finalize",
+                ProgramError::InvalidAccountData,
+                "Wrong temporary unwrapping account"
+            ));
         }
         Some(seed)
     } else {
@@ -208,6 +243,14 @@ pub fn finalize(
         account_guid_hash,
         program_id,
     )?;
+    let balance_account_seeds = BalanceAccountSeeds {
+        wallet_guid_hash,
+        account_guid_hash,
+    };
+    let unwrapping_seeds = TemporaryUnwrappingAccountSeeds {
+        wallet_guid_hash,
+        multisig_op: multisig_op_account_info.key,
+    };
 
     let wrapped_sol_account_key =
         get_associated_token_address(balance_account_info.key, &spl_token::native_mint::id());
@@ -215,8 +258,24 @@ pub fn finalize(
         return Err(WalletError::InvalidSourceTokenAccount.into());
     }
 
+    let expected_params = MultisigOpParams::Wrap {
+        wallet_address: *wallet_account_info.key,
+        account_guid_hash: *account_guid_hash,
+        amount,
+        direction,
+    };
+    if quarantine_if_frozen(
+        multisig_op_account_info,
+        &clock,
+        &expected_params,
+        &[wrapped_sol_account_info],
+    )? {
+        return Ok(());
+    }
+
     finalize_multisig_op(
         &multisig_op_account_info,
+        wallet_account_info,
         FeeCollectionInfo {
             rent_return_account_info,
             fee_account_info_maybe,
@@ -224,14 +283,24 @@ pub fn finalize(
             program_id,
         },
         clock,
-        MultisigOpParams::Wrap {
-            wallet_address: *wallet_account_info.key,
-            account_guid_hash: *account_guid_hash,
-            amount,
-            direction,
-        },
+        expected_params,
         || -> ProgramResult {
             if direction == WrapDirection::WRAP {
+                if *wrapped_sol_account_info.owner == Pubkey::default() {
+                    create_wrapped_sol_account(
+                        multisig_op_account_info,
+                        balance_account_info,
+                        wrapped_sol_account_info,
+                        native_mint_account_info,
+                        &[
+                            system_program_account_info.clone(),
+                            spl_token_program_info.clone(),
+                            spl_associated_token_program_info.clone(),
+                        ],
+                        &balance_account_seeds.signer_seeds(&[bump_seed]),
+                    )?;
+                }
+
                 transfer_sol_checked(
                     wallet_guid_hash,
                     balance_account_info.clone(),
@@ -249,16 +318,41 @@ pub fn finalize(
                     )?,
                     &[wrapped_sol_account_info.clone()],
                 )?;
+            } else if direction == WrapDirection::UNWRAP_ALL {
+                let wrapped_sol_account_data =
+                    SPLAccount::unpack(&wrapped_sol_account_info.data.borrow())?;
+                msg!(
+                    "Unwrapping all {} lamports of the wrapped SOL account",
+                    wrapped_sol_account_data.amount
+                );
+
+                // closing a wrapped SOL account releases all of its lamports, the rent included,
+                // so there is no need for a temporary account
+                invoke_signed(
+                    &spl_token::instruction::close_account(
+                        &spl_token::id(),
+                        &wrapped_sol_account_key,
+                        balance_account_info.key,
+                        balance_account_info.key,
+                        &[],
+                    )?,
+                    &[
+                        wrapped_sol_account_info.clone(),
+                        balance_account_info.clone(),
+                    ],
+                    &[&balance_account_seeds.signer_seeds(&[bump_seed])],
+                )?;
             } else {
                 let wrapped_sol_account_data =
                     SPLAccount::unpack(&wrapped_sol_account_info.data.borrow())?;
                 if wrapped_sol_account_data.amount < amount {
-                    msg!(
+                    return Err(strike_err!(
+                        "wrap_unwrap_handler::finalize",
+                        WalletError::InsufficientBalance,
                         "Wrapped SOL account only has {} lamports of {} requested",
                         wrapped_sol_account_data.amount,
                         amount
-                    );
-                    return Err(WalletError::InsufficientBalance.into());
+                    ));
                 }
 
                 // the only way to transfer lamports out of a token account is to close it, so we first
@@ -278,11 +372,7 @@ pub fn finalize(
                         balance_account_info.clone(),
                         temporary_unwrapping_account.unwrap().clone(),
                     ],
-                    &[&[
-                        wallet_guid_hash.to_bytes(),
-                        account_guid_hash.to_bytes(),
-                        &[bump_seed],
-                    ]],
+                    &[&balance_account_seeds.signer_seeds(&[bump_seed])],
                 )?;
 
                 invoke_signed(
@@ -298,16 +388,8 @@ pub fn finalize(
                         temporary_unwrapping_account.unwrap().clone(),
                     ],
                     &[
-                        &[
-                            wallet_guid_hash.to_bytes(),
-                            account_guid_hash.to_bytes(),
-                            &[bump_seed],
-                        ],
-                        &[
-                            wallet_guid_hash.to_bytes(),
-                            &multisig_op_account_info.key.to_bytes(),
-                            &[unwrapping_bump_seed.unwrap()],
-                        ],
+                        &balance_account_seeds.signer_seeds(&[bump_seed]),
+                        &unwrapping_seeds.signer_seeds(&[unwrapping_bump_seed.unwrap()]),
                     ],
                 )?;
             }
@@ -328,16 +410,8 @@ pub fn finalize(
                         temporary_unwrapping_account.unwrap().clone(),
                     ],
                     &[
-                        &[
-                            wallet_guid_hash.to_bytes(),
-                            account_guid_hash.to_bytes(),
-                            &[bump_seed],
-                        ],
-                        &[
-                            wallet_guid_hash.to_bytes(),
-                            &multisig_op_account_info.key.to_bytes(),
-                            &[unwrapping_bump_seed.unwrap()],
-                        ],
+                        &balance_account_seeds.signer_seeds(&[bump_seed]),
+                        &unwrapping_seeds.signer_seeds(&[unwrapping_bump_seed.unwrap()]),
                     ],
                 )?;
             }
@@ -345,3 +419,407 @@ pub fn finalize(
         },
     )
 }
+
+#[allow(clippy::too_many_arguments)]
+pub fn init_adapter(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    fee_amount: u64,
+    fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+    account_guid_hash: &BalanceAccountGuidHash,
+    wrapper_mint: &Pubkey,
+    amount: u64,
+    direction: WrapDirection,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let balance_account_info = next_account_info(accounts_iter)?;
+    let wrapped_token_account_info = next_account_info(accounts_iter)?;
+    let initiator_account =
+        next_initiator_account_info(accounts_iter, multisig_op_account_info, wallet_account_info)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+    let rent_return_account_info = next_signer_account_info(accounts_iter)?;
+
+    let mut wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    wallet.validate_not_frozen()?;
+    let balance_account = wallet.get_active_balance_account(account_guid_hash)?;
+    wallet.validate_transfer_initiator(initiator_account, clock.unix_timestamp)?;
+    wallet.wrapper_book.find(wrapper_mint)?;
+    validate_adapter_direction(direction)?;
+
+    // the adapter wraps into and unwraps from the balance account's associated token account,
+    // which has to exist by finalize
+    validate_balance_account_and_get_seed(
+        balance_account_info,
+        &wallet.wallet_guid_hash,
+        account_guid_hash,
+        program_id,
+    )?;
+    if *wrapped_token_account_info.key
+        != get_associated_token_address(balance_account_info.key, wrapper_mint)
+    {
+        return Err(WalletError::InvalidSourceTokenAccount.into());
+    }
+
+    start_multisig_transfer_op(
+        multisig_op_account_info,
+        wallet_account_info,
+        &mut wallet,
+        &balance_account,
+        clock,
+        MultisigOpParams::AdapterWrap {
+            wallet_address: *wallet_account_info.key,
+            account_guid_hash: *account_guid_hash,
+            wrapper_mint: *wrapper_mint,
+            amount,
+            direction,
+        },
+        None,
+        *initiator_account.key,
+        *rent_return_account_info.key,
+        fee_amount,
+        fee_account_guid_hash,
+        &[],
+    )
+}
+
+pub fn finalize_adapter(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    account_guid_hash: &BalanceAccountGuidHash,
+    wrapper_mint: &Pubkey,
+    amount: u64,
+    direction: WrapDirection,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig_op_account_info = next_program_account_info(accounts_iter, program_id)?;
+    let wallet_account_info = next_wallet_account_info(accounts_iter, program_id)?;
+    let balance_account_info = next_account_info(accounts_iter)?;
+    let rent_return_account_info = next_signer_account_info(accounts_iter)?;
+    let clock = get_clock_from_next_account(accounts_iter)?;
+    let wrapped_token_account_info = next_account_info(accounts_iter)?;
+    let wrapper_mint_account_info = next_account_info(accounts_iter)?;
+    let adapter_program_info = next_account_info(accounts_iter)?;
+    // the adapter's own accounts follow the fee account, so it is only taken when there is one
+    let fee_account_info_maybe =
+        if MultisigOp::unpack_sized(&multisig_op_account_info.data.borrow())?
+            .fee_account_guid_hash
+            .is_some()
+        {
+            Some(next_account_info(accounts_iter)?)
+        } else {
+            None
+        };
+    let adapter_account_infos = accounts_iter.as_slice();
+
+    let wallet = Wallet::unpack(&wallet_account_info.data.borrow())?;
+    let wallet_guid_hash = &wallet.wallet_guid_hash;
+    let wrapper = wallet.wrapper_book.find(wrapper_mint)?;
+    if *wrapper_mint_account_info.key != wrapper.mint
+        || *adapter_program_info.key != wrapper.adapter_program
+    {
+        return Err(WalletError::AccountNotRecognized.into());
+    }
+    validate_adapter_direction(direction)?;
+    let expected_adapter_accounts = wrapper.expected_adapter_accounts();
+    if adapter_account_infos.len() != expected_adapter_accounts.len()
+        || adapter_account_infos
+            .iter()
+            .zip(&expected_adapter_accounts)
+            .any(|(account_info, expected)| account_info.key != expected)
+    {
+        return Err(strike_err!(
+            "wrap_unwrap_handler::finalize_adapter",
+            WalletError::AccountNotRecognized,
+            "Adapter {} takes the {} accounts listed in the wallet's wrapper book, and no others",
+            wrapper.adapter_program,
+            expected_adapter_accounts.len()
+        ));
+    }
+
+    let bump_seed = validate_balance_account_and_get_seed(
+        balance_account_info,
+        wallet_guid_hash,
+        account_guid_hash,
+        program_id,
+    )?;
+    let balance_account_seeds = BalanceAccountSeeds {
+        wallet_guid_hash,
+        account_guid_hash,
+    };
+    if *wrapped_token_account_info.key
+        != get_associated_token_address(balance_account_info.key, wrapper_mint)
+    {
+        return Err(WalletError::InvalidSourceTokenAccount.into());
+    }
+
+    let expected_params = MultisigOpParams::AdapterWrap {
+        wallet_address: *wallet_account_info.key,
+        account_guid_hash: *account_guid_hash,
+        wrapper_mint: *wrapper_mint,
+        amount,
+        direction,
+    };
+    if quarantine_if_frozen(
+        multisig_op_account_info,
+        &clock,
+        &expected_params,
+        &[wrapped_token_account_info],
+    )? {
+        return Ok(());
+    }
+
+    finalize_multisig_op(
+        multisig_op_account_info,
+        wallet_account_info,
+        FeeCollectionInfo {
+            rent_return_account_info,
+            fee_account_info_maybe,
+            wallet_guid_hash,
+            program_id,
+        },
+        clock,
+        expected_params,
+        || -> ProgramResult {
+            // what the adapter may take from the balance account: lamports for a wrap, and
+            // wrapper tokens for an unwrap
+            let available = if direction == WrapDirection::WRAP {
+                balance_account_info.lamports()
+            } else {
+                SPLAccount::unpack(&wrapped_token_account_info.data.borrow())?.amount
+            };
+            let required = if direction == WrapDirection::WRAP {
+                amount
+                    .checked_add(Rent::get()?.minimum_balance(0))
+                    .ok_or(WalletError::AmountOverflow)?
+            } else {
+                amount
+            };
+            if available < required {
+                return Err(strike_err!(
+                    "wrap_unwrap_handler::finalize_adapter",
+                    WalletError::InsufficientBalance,
+                    "Balance account only has {} of the {} needed",
+                    available,
+                    required
+                ));
+            }
+
+            let mut account_infos = vec![
+                balance_account_info.clone(),
+                wrapped_token_account_info.clone(),
+                wrapper_mint_account_info.clone(),
+            ];
+            account_infos.extend_from_slice(adapter_account_infos);
+            let holdings_before = AdapterHoldings::of(balance_account_info, &account_infos);
+            invoke_signed(
+                &wrapper.adapter_instruction(
+                    balance_account_info.key,
+                    wrapped_token_account_info.key,
+                    direction,
+                    amount,
+                    adapter_account_infos,
+                ),
+                &account_infos,
+                &[&balance_account_seeds.signer_seeds(&[bump_seed])],
+            )?;
+
+            let spent_from = if direction == WrapDirection::WRAP {
+                None
+            } else {
+                Some(wrapped_token_account_info.key)
+            };
+            holdings_before.validate_taken(
+                &AdapterHoldings::of(balance_account_info, &account_infos),
+                spent_from,
+                amount,
+                &wrapper.adapter_program,
+            )
+        },
+        || -> ProgramResult { Ok(()) },
+    )
+}
+
+/// What a balance account holds among the accounts passed on to a wrapper adapter, which the
+/// adapter could spend with the balance account's signature: its lamports, and every token
+/// account it owns.
+struct AdapterHoldings {
+    lamports: u64,
+    owner: Pubkey,
+    token_accounts: Vec<(Pubkey, TokenAccount2022)>,
+}
+
+impl AdapterHoldings {
+    fn of(balance_account_info: &AccountInfo, account_infos: &[AccountInfo]) -> AdapterHoldings {
+        let mut token_accounts: Vec<(Pubkey, TokenAccount2022)> = Vec::new();
+        for account_info in account_infos {
+            if (*account_info.owner != spl_token::id()
+                && *account_info.owner != spl_token_2022::id())
+                || token_accounts
+                    .iter()
+                    .any(|(key, _)| key == account_info.key)
+            {
+                continue;
+            }
+            if let Ok(token_account) =
+                StateWithExtensions::<TokenAccount2022>::unpack(&account_info.data.borrow())
+            {
+                if token_account.base.owner == *balance_account_info.key {
+                    token_accounts.push((*account_info.key, token_account.base));
+                }
+            }
+        }
+        AdapterHoldings {
+            lamports: balance_account_info.lamports(),
+            owner: *balance_account_info.owner,
+            token_accounts,
+        }
+    }
+
+    /// Validates that the adapter took no more than `amount` from what the op spends, the
+    /// balance account's lamports or the token account `spent_from`, took nothing from the
+    /// balance account's other holdings, and left the authorities over all of them as they
+    /// were.
+    fn validate_taken(
+        &self,
+        after: &AdapterHoldings,
+        spent_from: Option<&Pubkey>,
+        amount: u64,
+        adapter_program: &Pubkey,
+    ) -> ProgramResult {
+        let overdrawn = |taken: u64, allowed: u64, holding: &dyn Display| {
+            strike_err!(
+                "wrap_unwrap_handler::finalize_adapter",
+                WalletError::WrapperAdapterOverdrawn,
+                "Adapter {} took {} from {}, of the {} allowed",
+                adapter_program,
+                taken,
+                holding,
+                allowed
+            )
+        };
+
+        let allowed = if spent_from.is_none() { amount } else { 0 };
+        let taken = self.lamports.saturating_sub(after.lamports);
+        if taken > allowed {
+            return Err(overdrawn(taken, allowed, &"the balance account's lamports"));
+        }
+        if after.owner != self.owner {
+            return Err(strike_err!(
+                "wrap_unwrap_handler::finalize_adapter",
+                WalletError::WrapperAdapterOverdrawn,
+                "Adapter {} assigned the balance account to {}",
+                adapter_program,
+                after.owner
+            ));
+        }
+
+        for (key, before) in &self.token_accounts {
+            let after = after
+                .token_accounts
+                .iter()
+                .find(|(after_key, _)| after_key == key)
+                .map(|(_, after)| after);
+            let after = match after {
+                Some(after) => after,
+                None => {
+                    return Err(strike_err!(
+                        "wrap_unwrap_handler::finalize_adapter",
+                        WalletError::WrapperAdapterOverdrawn,
+                        "Adapter {} took token account {} from the balance account",
+                        adapter_program,
+                        key
+                    ))
+                }
+            };
+            let allowed = if spent_from == Some(key) { amount } else { 0 };
+            let taken = before.amount.saturating_sub(after.amount);
+            if taken > allowed {
+                return Err(overdrawn(taken, allowed, key));
+            }
+            let untouched = TokenAccount2022 {
+                amount: before.amount,
+                ..*after
+            } == *before;
+            if !untouched {
+                return Err(strike_err!(
+                    "wrap_unwrap_handler::finalize_adapter",
+                    WalletError::WrapperAdapterOverdrawn,
+                    "Adapter {} changed the authorities over token account {}",
+                    adapter_program,
+                    key
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Adapters only wrap and unwrap given amounts; unwrapping everything is particular to wrapped
+/// SOL.
+fn validate_adapter_direction(direction: WrapDirection) -> ProgramResult {
+    if direction == WrapDirection::UNWRAP_ALL {
+        return Err(strike_err!(
+            "wrap_unwrap_handler::validate_adapter_direction",
+            ProgramError::InvalidArgument,
+            "Wrapper adapters can't unwrap a whole balance"
+        ));
+    }
+    Ok(())
+}
+
+/// Creates the balance account's wrapped SOL account, paying its rent out of the lamports which
+/// were set aside in the multisig op account when the wrap was initiated.
+fn create_wrapped_sol_account<'a>(
+    multisig_op_account_info: &AccountInfo<'a>,
+    balance_account_info: &AccountInfo<'a>,
+    wrapped_sol_account_info: &AccountInfo<'a>,
+    native_mint_account_info: &AccountInfo<'a>,
+    program_account_infos: &[AccountInfo<'a>],
+    balance_account_seeds: &[&[u8]],
+) -> ProgramResult {
+    let rent = Rent::get()?;
+    let token_account_rent = rent.minimum_balance(SPLAccount::LEN);
+    let reserved_lamports = multisig_op_account_info
+        .lamports()
+        .saturating_sub(rent.minimum_balance(multisig_op_account_info.data_len()));
+    if reserved_lamports < token_account_rent {
+        return Err(strike_err!(
+            "wrap_unwrap_handler::create_wrapped_sol_account",
+            WalletError::InsufficientBalance,
+            "Multisig op account only has {} of {} lamports reserved for the wrapped SOL account",
+            reserved_lamports,
+            token_account_rent
+        ));
+    }
+
+    // the associated token program requires a system-owned payer, so the reserved rent is
+    // passed through the balance account
+    **multisig_op_account_info.lamports.borrow_mut() = multisig_op_account_info
+        .lamports()
+        .checked_sub(token_account_rent)
+        .ok_or(WalletError::AmountOverflow)?;
+    **balance_account_info.lamports.borrow_mut() = balance_account_info
+        .lamports()
+        .checked_add(token_account_rent)
+        .ok_or(WalletError::AmountOverflow)?;
+
+    let mut account_infos = vec![
+        balance_account_info.clone(),
+        wrapped_sol_account_info.clone(),
+        native_mint_account_info.clone(),
+    ];
+    account_infos.extend_from_slice(program_account_infos);
+    invoke_signed(
+        &create_associated_token_account_instruction(
+            balance_account_info,
+            wrapped_sol_account_info,
+            balance_account_info,
+            native_mint_account_info,
+            &spl_token::id(),
+        ),
+        &account_infos,
+        &[balance_account_seeds],
+    )
+}
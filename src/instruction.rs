@@ -3,29 +3,53 @@ use std::mem::size_of;
 use std::slice::Iter;
 use std::time::Duration;
 
+use arrayref::array_ref;
 use bitvec::macros::internal::funty::Fundamental;
 use bytes::BufMut;
-use solana_program::hash::Hash;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::{hash, Hash};
+use solana_program::msg;
 use solana_program::program_error::ProgramError;
 use solana_program::program_pack::Pack;
 use solana_program::{instruction::AccountMeta, instruction::Instruction, pubkey::Pubkey};
 
 use crate::constants::{HASH_LEN, PUBKEY_BYTES};
-use crate::model::address_book::{AddressBookEntry, AddressBookEntryNameHash, DAppBookEntry};
+use crate::error::WalletError;
+use crate::model::address_book::{
+    AddressBookEntry, AddressBookEntryKind, AddressBookEntryNameHash, AddressBookEntryRisk,
+    DAppBookEntry, DAppCategories,
+};
+use crate::model::approval_group::ApprovalGroup;
+use crate::model::assistant::Assistant;
 use crate::model::balance_account::{
-    BalanceAccount, BalanceAccountGuidHash, BalanceAccountNameHash,
+    ApprovalTier, BalanceAccount, BalanceAccountGuidHash, BalanceAccountNameHash,
+    FinalizeDelayPolicy,
 };
+use crate::model::compliance_policy::CompliancePolicy;
+use crate::model::fee_policy::FeePolicy;
+use crate::model::finalize_submitter_policy::FinalizeSubmitterPolicy;
+use crate::model::merkle_address_book::MerkleAddressBook;
 use crate::model::multisig_op::{
-    ApprovalDisposition, BooleanSetting, SlotUpdateType, WrapDirection,
+    ApprovalDisposition, BooleanSetting, DispositionReason, MintAuthorityType, SlotUpdateType,
+    WrapDirection,
 };
-use crate::model::signer::Signer;
+use crate::model::price_oracle::PriceOracle;
+use crate::model::program_parameters::ProgramParameters;
+use crate::model::rent_return_allowlist::RentReturnAllowlist;
+use crate::model::session_key_registry::SessionKeyScope;
+use crate::model::signer::{Signer, SignerCapabilities};
+use crate::model::stake_weight_governance::StakeWeightGovernance;
 use crate::model::wallet::WalletGuidHash;
+use crate::model::wallet_freeze::WalletFreeze;
+use crate::model::wallet_replication::{MirroredUpdate, WalletReplication};
+use crate::model::wrapper_book::WrapperBook;
 use crate::serialization_utils::{
-    append_duration, pack_option, read_account_guid_hash, read_account_name_hash,
-    read_address_book_entry_name_hash, read_duration, read_fixed_size_array, read_slice, read_u16,
-    read_u64, read_u8, unpack_option,
+    append_duration, append_optional_hash, append_optional_pubkey, pack_option,
+    read_account_guid_hash, read_account_name_hash, read_address_book_entry_name_hash,
+    read_duration, read_fixed_size_array, read_optional_hash, read_optional_pubkey, read_slice,
+    read_u16, read_u64, read_u8, unpack_option,
 };
-use crate::utils::SlotId;
+use crate::utils::{is_strictly_ascending_by_key, SlotId};
 
 // Instruction "tags" are sent as the first byte of each instruction
 // and are mapped to corresponding ProgramInstructions to execute:
@@ -62,22 +86,140 @@ pub const TAG_INIT_BALANCE_ACCOUNT_ADDRESS_WHITELIST_UPDATE: u8 = 33;
 pub const TAG_FINALIZE_BALANCE_ACCOUNT_ADDRESS_WHITELIST_UPDATE: u8 = 34;
 pub const TAG_INIT_SIGN_DATA: u8 = 35;
 pub const TAG_FINALIZE_SIGN_DATA: u8 = 36;
-
+pub const TAG_ACKNOWLEDGE_INTERNAL_TRANSFER: u8 = 37;
+pub const TAG_ADD_PENDING_ADDRESS_BOOK_ENTRIES: u8 = 38;
+pub const TAG_RATIFY_PENDING_ADDRESS_BOOK_ENTRIES: u8 = 39;
+pub const TAG_INIT_VOTE_REWARDS_WITHDRAWAL: u8 = 40;
+pub const TAG_FINALIZE_VOTE_REWARDS_WITHDRAWAL: u8 = 41;
+pub const TAG_INIT_UPDATE_ASSISTANT: u8 = 42;
+pub const TAG_FINALIZE_UPDATE_ASSISTANT: u8 = 43;
+pub const TAG_INIT_REWARD_SPLIT_POLICY_UPDATE: u8 = 44;
+pub const TAG_FINALIZE_REWARD_SPLIT_POLICY_UPDATE: u8 = 45;
+pub const TAG_REMOVE_DEPRECATED_ADDRESS_BOOK_ENTRIES: u8 = 46;
+pub const TAG_ACTIVATE_WALLET_CONFIG_POLICY: u8 = 47;
+pub const TAG_CANCEL_TRANSFER: u8 = 48;
+pub const TAG_INIT_FINALIZE_SUBMITTER_POLICY_UPDATE: u8 = 49;
+pub const TAG_FINALIZE_FINALIZE_SUBMITTER_POLICY_UPDATE: u8 = 50;
+pub const TAG_INIT_MINT_TO: u8 = 51;
+pub const TAG_FINALIZE_MINT_TO: u8 = 52;
+pub const TAG_INIT_SET_MINT_AUTHORITY: u8 = 53;
+pub const TAG_FINALIZE_SET_MINT_AUTHORITY: u8 = 54;
+pub const TAG_SIMULATE_WALLET_CONFIG_POLICY_UPDATE: u8 = 55;
+pub const TAG_SIMULATE_BALANCE_ACCOUNT_POLICY_UPDATE: u8 = 56;
+pub const TAG_INIT_PAYMENT_CHANNEL: u8 = 57;
+pub const TAG_FINALIZE_PAYMENT_CHANNEL: u8 = 58;
+pub const TAG_CHANNEL_PAYMENT: u8 = 59;
+pub const TAG_INIT_OP_BUNDLE: u8 = 60;
+pub const TAG_APPROVE_OP_BUNDLE: u8 = 61;
+pub const TAG_APPLY_OP_BUNDLE_APPROVAL: u8 = 62;
+pub const TAG_CLOSE_OP_BUNDLE: u8 = 63;
+pub const TAG_INIT_LOOKUP_TABLE_UPDATE: u8 = 64;
+pub const TAG_FINALIZE_LOOKUP_TABLE_UPDATE: u8 = 65;
+pub const TAG_INIT_ADDRESS_BOOK_IMPORT: u8 = 66;
+pub const TAG_FINALIZE_ADDRESS_BOOK_IMPORT: u8 = 67;
+pub const TAG_INIT_COMMITTED_TRANSFER: u8 = 68;
+pub const TAG_FINALIZE_COMMITTED_TRANSFER: u8 = 69;
+pub const TAG_INIT_COMPLIANCE_POLICY_UPDATE: u8 = 70;
+pub const TAG_FINALIZE_COMPLIANCE_POLICY_UPDATE: u8 = 71;
+pub const TAG_COMPLIANCE_APPROVE: u8 = 72;
+pub const TAG_GET_ALLOWED_DESTINATIONS: u8 = 73;
+pub const TAG_INIT_WITH_SIGNATURE: u8 = 74;
+pub const TAG_INIT_WALLET_REPLICATION_UPDATE: u8 = 75;
+pub const TAG_FINALIZE_WALLET_REPLICATION_UPDATE: u8 = 76;
+pub const TAG_APPLY_MIRRORED_UPDATE: u8 = 77;
+pub const TAG_INIT_BALANCE_ACCOUNT_DELETION: u8 = 78;
+pub const TAG_FINALIZE_BALANCE_ACCOUNT_DELETION: u8 = 79;
+#[cfg(feature = "demo")]
+pub const TAG_INIT_DEMO_WALLET: u8 = 80;
+pub const TAG_INIT_SPENDING_LIMIT_UPDATE: u8 = 81;
+pub const TAG_FINALIZE_SPENDING_LIMIT_UPDATE: u8 = 82;
+pub const TAG_NUDGE: u8 = 83;
+pub const TAG_SUSPEND_DAPP: u8 = 84;
+pub const TAG_INIT_PRICE_ORACLE_UPDATE: u8 = 85;
+pub const TAG_FINALIZE_PRICE_ORACLE_UPDATE: u8 = 86;
+pub const TAG_REGISTER_TOKEN_ACCOUNTS: u8 = 87;
+pub const TAG_INIT_MERKLE_ADDRESS_BOOK_UPDATE: u8 = 88;
+pub const TAG_FINALIZE_MERKLE_ADDRESS_BOOK_UPDATE: u8 = 89;
+pub const TAG_CLEANUP_EXPIRED_OP: u8 = 90;
+pub const TAG_INIT_WITH_COMPUTE_HINT: u8 = 91;
+pub const TAG_EXPORT_WALLET_READ_MODEL: u8 = 92;
+pub const TAG_INIT_WALLET_FREEZE: u8 = 93;
+pub const TAG_FINALIZE_WALLET_FREEZE: u8 = 94;
+pub const TAG_EMERGENCY_FREEZE: u8 = 95;
+pub const TAG_INIT_RENT_RETURN_ALLOWLIST_UPDATE: u8 = 96;
+pub const TAG_FINALIZE_RENT_RETURN_ALLOWLIST_UPDATE: u8 = 97;
+pub const TAG_INIT_FULL_ROTATION: u8 = 98;
+pub const TAG_FINALIZE_FULL_ROTATION: u8 = 99;
+pub const TAG_UPDATE_PROGRAM_PARAMETERS: u8 = 100;
+pub const TAG_INIT_SIGNER_ACTIVATION_DELAY_UPDATE: u8 = 101;
+pub const TAG_FINALIZE_SIGNER_ACTIVATION_DELAY_UPDATE: u8 = 102;
+pub const TAG_INIT_FEE_POLICY_UPDATE: u8 = 103;
+pub const TAG_FINALIZE_FEE_POLICY_UPDATE: u8 = 104;
+pub const TAG_CLEANUP_UNCREATED_BALANCE_ACCOUNT: u8 = 105;
+pub const TAG_INIT_STAKE_WEIGHT_GOVERNANCE_UPDATE: u8 = 106;
+pub const TAG_FINALIZE_STAKE_WEIGHT_GOVERNANCE_UPDATE: u8 = 107;
+pub const TAG_INIT_WITH_STAKE_WEIGHTS: u8 = 108;
+pub const TAG_INIT_EVENT_LOG: u8 = 109;
+pub const TAG_INIT_ADDRESS_BOOK_RENEWAL: u8 = 110;
+pub const TAG_FINALIZE_ADDRESS_BOOK_RENEWAL: u8 = 111;
+pub const TAG_REGISTER_SESSION_KEY: u8 = 112;
+pub const TAG_REVOKE_SESSION_KEY: u8 = 113;
+pub const TAG_SET_APPROVAL_DISPOSITION_BY_SIGNATURE: u8 = 114;
+pub const TAG_INIT_WALLET_STATS: u8 = 115;
+pub const TAG_INIT_SPL_TOKEN_ACCOUNTS_CREATION: u8 = 116;
+pub const TAG_FINALIZE_SPL_TOKEN_ACCOUNTS_CREATION: u8 = 117;
+pub const TAG_CONTINUE_SPL_TOKEN_ACCOUNTS_CREATION: u8 = 118;
+pub const TAG_VERIFY_TRANSFER: u8 = 119;
+pub const TAG_EXECUTE_TRANSFER: u8 = 120;
+pub const TAG_SIMULATE_CONFIG_UPDATE: u8 = 121;
+pub const TAG_INIT_APPROVAL_GROUP_UPDATE: u8 = 122;
+pub const TAG_FINALIZE_APPROVAL_GROUP_UPDATE: u8 = 123;
+pub const TAG_SUBMIT_AGGREGATED_APPROVAL: u8 = 124;
+pub const TAG_INIT_ENABLE_DEPOSIT_ADDRESSES: u8 = 125;
+pub const TAG_FINALIZE_ENABLE_DEPOSIT_ADDRESSES: u8 = 126;
+pub const TAG_FINALIZE_WITH_DEPOSIT_SWEEP: u8 = 127;
+pub const TAG_INIT_COMPACT_WALLET: u8 = 128;
+pub const TAG_FINALIZE_COMPACT_WALLET: u8 = 129;
+pub const TAG_INIT_INSURANCE_ATTESTOR_UPDATE: u8 = 130;
+pub const TAG_FINALIZE_INSURANCE_ATTESTOR_UPDATE: u8 = 131;
+pub const TAG_SUBMIT_INSURANCE_ATTESTATION: u8 = 132;
+pub const TAG_CHECK_INSURANCE_ATTESTATION: u8 = 133;
+pub const TAG_INIT_PROVISIONING_WALLET: u8 = 134;
+pub const TAG_UPDATE_PROVISIONING_WALLET: u8 = 135;
+pub const TAG_ACTIVATE_WALLET: u8 = 136;
+pub const TAG_INIT_WRAPPER_BOOK_UPDATE: u8 = 137;
+pub const TAG_FINALIZE_WRAPPER_BOOK_UPDATE: u8 = 138;
+pub const TAG_INIT_ADAPTER_WRAP_UNWRAP: u8 = 139;
+pub const TAG_FINALIZE_ADAPTER_WRAP_UNWRAP: u8 = 140;
+pub const TAG_MIGRATE_PENDING_APPROVALS: u8 = 141;
+
+/// Instructions that start a multisig op return its `MultisigOpReceipt` as return data, as
+/// does the `SupplyDAppTransactionInstructions` that completes a dApp transaction's params.
 #[derive(Debug)]
 pub enum ProgramInstruction {
     /// 0. `[writable]` The wallet account
     /// 1. `[signer]` The transaction assistant account
-    /// 2. `[signer]` The rent return account
+    /// 2. `[signer, writable]` The rent return account, which pays for the wallet's
+    ///    registration
+    /// 3. `[]` The program parameters account, bounding the signers, dApp instruction count
+    ///    and approval timeouts the op can set
+    /// 4. `[writable]` The wallet registration account (PDA of the wallet GUID hash)
+    /// 5. `[]` The system program
+    ///
+    /// The wallet is registered under its GUID hash, which fails if a wallet was registered
+    /// under it already. The registration and system program accounts are looked up by key.
     InitWallet {
         wallet_guid_hash: WalletGuidHash,
         initial_config: InitialWalletConfig,
     },
 
     /// 0. `[writable]` The multisig operation account
-    /// 1. `[]` The wallet account
+    /// 1. `[writable]` The wallet account
     /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
     /// 3. `[]` The sysvar clock account
     /// 4. `[signer]` The rent return account
+    /// 5. `[]` The program parameters account, bounding the signers, dApp instruction count
+    ///    and approval timeouts the op can set
     InitBalanceAccountCreation {
         fee_amount: u64,
         fee_account_guid_hash: Option<BalanceAccountGuidHash>,
@@ -88,71 +230,177 @@ pub enum ProgramInstruction {
     /// 0. `[writable]` The multisig operation account
     /// 1. `[writable]` The wallet account
     /// 2. `[signer, writable]` The rent return account
-    /// 3. `[writable]` The fee account, if fee_account_guid_hash was set in the init
-    /// 4. `[]` The system program (only needed if fee_account_guid_hash was set in the init)
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[writable]` The fee account, if fee_account_guid_hash was set in the init
+    /// 5. `[]` The system program (only needed if fee_account_guid_hash was set in the init)
+    /// 6. `[writable]` The balance account (optional)
+    /// 7. `[]` The system program (only needed if the balance account is passed)
+    ///
+    /// If the creation was denied or expired and the balance account is passed, lamports sent
+    /// to its address ahead of time are returned to the rent return account along with the
+    /// op's rent. Token accounts created for it are closed by `CleanupUncreatedBalanceAccount`.
     FinalizeBalanceAccountCreation {
         account_guid_hash: BalanceAccountGuidHash,
         creation_params: BalanceAccountCreation,
     },
 
     /// 0. `[writable]` The multisig operation account
-    /// 1. `[]` The wallet account
+    /// 1. `[writable]` The wallet account
     /// 2. `[writable]` The source account
     /// 3. `[]` The destination account
     /// 4. `[signer]` The initiator account (either the transaction assistant or an approver)
     /// 5. `[]` The sysvar clock account
-    /// 6. `[signer, writable]` The rent return account, also used if we need to create destination
-    ///     token account for an SPL transfer and the source account does not have enough funds
+    /// 6. `[signer, writable]` The rent return account
     /// 7. `[]` The token mint (for SPL transfers, use system account otherwise)
-    /// 8. `[writable]` The destination token account (only used for SPL transfers)
-    /// 9. `[]` The system program (only used for SPL transfers)
-    /// 10. `[]` The SPL token program (only used for SPL transfers)
-    /// 11. `[]` The Rent sysvar program (only used for SPL transfers)
-    /// 12. `[]` The SPL associated token program (only used for SPL transfers)
+    /// 8. `[]` The price account of the token mint (only used if the wallet has a price oracle)
+    /// 9. `[]` The Merkle address book account of the source balance account (only used if a
+    ///    destination proof is given)
+    ///
+    /// The destination token account of an SPL transfer isn't created when it is initiated. If
+    /// `allow_destination_ata_creation` is set, the finalize creates it if it doesn't exist yet,
+    /// and otherwise the finalize fails until it does.
+    ///
+    /// A destination that the source balance account doesn't whitelist can still be transferred
+    /// to if `destination_proof` proves it is in the balance account's Merkle address book.
+    ///
+    /// If `memo_hash` is set, the transfer can only be finalized with the memo it is the hash
+    /// of, which the finalize then records with the SPL memo program.
+    ///
+    /// A transfer to an address book entry classified high-risk needs the approval of the
+    /// wallet's compliance service whatever its amount, and can't be initiated if the wallet
+    /// has no compliance policy.
     InitTransfer {
         fee_amount: u64,
         fee_account_guid_hash: Option<BalanceAccountGuidHash>,
         account_guid_hash: BalanceAccountGuidHash,
         amount: u64,
         destination_name_hash: AddressBookEntryNameHash,
+        destination_acknowledgement_required: bool,
+        deduct_fee_from_amount: bool,
+        allow_destination_ata_creation: bool,
+        memo_hash: Option<Hash>,
+        destination_proof: Option<Vec<Hash>>,
     },
 
     /// 0. `[writable]` The multisig operation account
-    /// 1. `[signer]` The approver account
+    /// 1. `[signer]` The approver account, or a session key registered by the approver
     /// 2. `[]` The sysvar clock account
+    /// 3. `[]` The session key registry account of the op's wallet, if a session key signs
+    ///
+    /// The reason is recorded with the disposition on the op. It is left off the encoding
+    /// altogether when there is none, as it was before reasons could be given.
     SetApprovalDisposition {
         disposition: ApprovalDisposition,
         params_hash: Hash,
+        reason: Option<DispositionReason>,
     },
 
     /// 0. `[writable]` The multisig operation account
-    /// 1. `[]` The wallet account
+    /// 1. `[]` The wallet account (writable if the transfer is to be finalized within the
+    ///    spending limit of the source balance account)
     /// 2. `[writable]` The source account
     /// 3. `[writable]` The destination account
     /// 4. `[]` The system program
     /// 5. `[signer, writable]` The rent return account
     /// 6. `[]` The sysvar clock account
-    /// 7. `[writable]` The source token account, if this is an SPL transfer
-    /// 8. `[writable]` The destination token account, if this is an SPL transfer
-    /// 9. `[]` The SPL token program account, if this is an SPL transfer
-    /// 10. `[]` The token mint authority, if this is an SPL transfer
-    /// 11. `[writable]` The fee account, if fee_account_guid_hash was set in the init
-    /// 12. `[]` The system program (only needed if fee_account_guid_hash was set in the init)
+    /// 7. `[writable]` The destination receipt account, the PDA of `DestinationReceipt` for the
+    ///    wallet, destination and token mint, which is created on the first transfer
+    /// 8. `[writable]` The source token account, if this is an SPL transfer
+    /// 9. `[writable]` The destination token account, if this is an SPL transfer
+    /// 10. `[]` The token program owning the mint, either the SPL token program or Token-2022, if
+    ///     this is an SPL transfer
+    /// 11. `[]` The token mint account, if this is an SPL transfer
+    /// 12. `[]` The SPL associated token program, if this is an SPL transfer allowed to create
+    ///     the destination token account
+    /// 13. `[writable]` The internal transfer handshake account, if destination acknowledgement
+    ///     was required in the init
+    /// 14. `[]` The SPL memo program, if a memo hash was set in the init
+    /// 15. `[writable]` The fee account, if fee_account_guid_hash was set in the init
+    /// 16. `[]` The system program (only needed if fee_account_guid_hash was set in the init)
+    ///
+    /// An SPL transfer whose destination token account doesn't exist fails unless
+    /// allow_destination_ata_creation was set in the init, in which case the account is
+    /// created, with its rent paid by the source account if it can afford it and by the rent
+    /// return account otherwise. The rent the source account paid is recorded in the op as
+    /// `destination_token_account_rent` before it is closed, and logged.
+    ///
+    /// The memo has to be the one whose hash was set in the init, if any. It is recorded with
+    /// the SPL memo program when the transfer is made.
+    ///
+    /// If deduct_fee_from_amount was set in the init, the fee amount of the operation is paid
+    /// to the rent return account out of the transferred amount rather than from a fee account,
+    /// and the destination receives the remainder. The fee is added to the destination
+    /// receipt's `cumulative_deducted_fee`. A transfer that is denied or expires pays no fee.
+    ///
+    /// SPL transfers are made with `TransferChecked`, or `TransferCheckedWithFee` for Token-2022
+    /// mints with a transfer fee, in which case the fee is withheld from what the destination
+    /// receives.
+    ///
+    /// A transfer its initiator approved which fits within what remains of the spending limit
+    /// of the source balance account is finalized without waiting for the other approvers.
+    ///
+    /// SOL transfers out of a balance account with system_owned_destinations_only on fail
+    /// unless the destination is owned by the system program.
+    ///
+    /// The jurisdiction of a high-risk destination, as classified when the transfer was
+    /// initiated, is recorded in the destination receipt.
+    ///
+    /// `VerifyTransfer` and `ExecuteTransfer` take the two steps of this finalize in separate
+    /// transactions instead.
     FinalizeTransfer {
         account_guid_hash: BalanceAccountGuidHash,
         amount: u64,
         token_mint: Pubkey,
+        destination_acknowledgement_required: bool,
+        deduct_fee_from_amount: bool,
+        allow_destination_ata_creation: bool,
+        memo: Option<Vec<u8>>,
+    },
+
+    /// The accounts are those of `FinalizeTransfer`.
+    ///
+    /// Takes the first of the two steps `FinalizeTransfer` takes at once: checks the transfer's
+    /// dispositions and, if it was approved, marks the op executed and keeps it open until
+    /// `ExecuteTransfer` moves the funds. A transfer that wasn't approved is finalized as it
+    /// would be by `FinalizeTransfer`.
+    VerifyTransfer {
+        account_guid_hash: BalanceAccountGuidHash,
+        amount: u64,
+        token_mint: Pubkey,
+        destination_acknowledgement_required: bool,
+        deduct_fee_from_amount: bool,
+        allow_destination_ata_creation: bool,
+        memo: Option<Vec<u8>>,
+    },
+
+    /// The accounts are those of `FinalizeTransfer`.
+    ///
+    /// Makes the transfer of an op `VerifyTransfer` marked executed, without further approvals,
+    /// and closes the op. If the transfer fails, for instance because the destination token
+    /// account doesn't exist and can't be created, the op stays executed and the transfer can
+    /// be retried once the problem is fixed, however long after the op's expiry. Fees are
+    /// collected when the op is closed.
+    ExecuteTransfer {
+        account_guid_hash: BalanceAccountGuidHash,
+        amount: u64,
+        token_mint: Pubkey,
+        destination_acknowledgement_required: bool,
+        deduct_fee_from_amount: bool,
+        allow_destination_ata_creation: bool,
+        memo: Option<Vec<u8>>,
     },
 
     /// 0. `[writable]` The multisig operation account
-    /// 1. `[]` The wallet account
+    /// 1. `[writable]` The wallet account
     /// 2. `[writable]` The balance account
-    /// 3. `[writable]` The associated wrapped SOL account
+    /// 3. `[]` The associated wrapped SOL account
     /// 4. `[]` The native mint account
     /// 5. `[signer]` The initiator account (either the transaction assistant or an approver)
     /// 6. `[]` The sysvar clock account
-    /// 7. `[signer]` The rent return account
-    /// 8. `[writable]` A temporary wrapped SOL account (only for unwrap)
+    /// 7. `[signer, writable]` The rent return account. For a wrap, it also sets aside the rent of
+    ///    the wrapped SOL account, which is created at finalize if it doesn't exist by then. The
+    ///    rent is returned at finalize if it isn't needed.
+    /// 8. `[writable]` A temporary wrapped SOL account (only for unwrap, not for unwrap all)
     /// 9. `[]` The system program
     /// 10. `[]` The SPL token program
     /// 11. `[]` The Rent sysvar program
@@ -175,7 +423,7 @@ pub enum ProgramInstruction {
     /// 7. `[]` The SPL token account
     /// 8. `[]` The native mint account
     /// 9. `[]` The SPL associated token program
-    /// 10. `[writable]` A temporary wrapped SOL account, for use with unwrap
+    /// 10. `[writable]` A temporary wrapped SOL account, for use with unwrap (not unwrap all)
     /// 11. `[writable]` The fee account, if fee_account_guid_hash was set in the init
     FinalizeWrapUnwrap {
         account_guid_hash: BalanceAccountGuidHash,
@@ -184,10 +432,13 @@ pub enum ProgramInstruction {
     },
 
     /// 0. `[writable]` The multisig operation account
-    /// 1. `[]` The wallet account
-    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant, an approver, or
+    ///    the signer being removed if it holds the resign capability)
     /// 3. `[]` The sysvar clock account
     /// 4. `[signer]` The rent return account
+    /// 5. `[]` The program parameters account, bounding the signers, dApp instruction count
+    ///    and approval timeouts the op can set
     InitUpdateSigner {
         fee_amount: u64,
         fee_account_guid_hash: Option<BalanceAccountGuidHash>,
@@ -201,6 +452,12 @@ pub enum ProgramInstruction {
     /// 2. `[signer, writable]` The rent return account
     /// 3. `[writable]` The fee account, if fee_account_guid_hash was set in the init
     /// 4. `[]` The system program (only needed if fee_account_guid_hash was set in the init)
+    /// 5. `[writable]` The standby wallet account, if the wallet is a primary
+    /// 6. `[]` The wallet's mirror authority account, if the wallet is a primary
+    /// 7. `[]` This program's account, if the wallet is a primary
+    ///
+    /// The standby accounts are looked up by key, so they can follow the fee accounts or
+    /// take their place when there are none.
     FinalizeUpdateSigner {
         slot_update_type: SlotUpdateType,
         slot_id: SlotId<Signer>,
@@ -212,6 +469,8 @@ pub enum ProgramInstruction {
     /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
     /// 3. `[]` The sysvar clock account
     /// 4. `[signer]` The rent return account
+    /// 5. `[]` The program parameters account, bounding the signers, dApp instruction count
+    ///    and approval timeouts the op can set
     InitWalletConfigPolicyUpdate {
         fee_amount: u64,
         fee_account_guid_hash: Option<BalanceAccountGuidHash>,
@@ -223,14 +482,21 @@ pub enum ProgramInstruction {
     /// 2. `[signer, writable]` The rent return account
     /// 3. `[writable]` The fee account, if fee_account_guid_hash was set in the init
     /// 4. `[]` The system program (only needed if fee_account_guid_hash was set in the init)
+    /// 5. `[writable]` The standby wallet account, if the wallet is a primary
+    /// 6. `[]` The wallet's mirror authority account, if the wallet is a primary
+    /// 7. `[]` This program's account, if the wallet is a primary
+    ///
+    /// As with `FinalizeUpdateSigner`, the standby accounts are looked up by key.
     FinalizeWalletConfigPolicyUpdate { update: WalletConfigPolicyUpdate },
 
     /// 0. `[writable]` The multisig operation account
     /// 1. `[writable]` The multisig data account
-    /// 2. `[]` The wallet account
+    /// 2. `[writable]` The wallet account
     /// 3. `[signer]` The initiator account (either the transaction assistant or an approver)
     /// 4. `[]` The sysvar clock account
     /// 5. `[signer]` The rent return account
+    /// 6. `[]` The program parameters account, bounding the signers, dApp instruction count
+    ///    and approval timeouts the op can set
     InitDAppTransaction {
         fee_amount: u64,
         fee_account_guid_hash: Option<BalanceAccountGuidHash>,
@@ -242,6 +508,12 @@ pub enum ProgramInstruction {
     /// 0. `[writable]` The multisig operation account
     /// 1. `[writable]` The multisig data account
     /// 2. `[signer]` The initiator account
+    /// 3. `[]` The wallet account
+    ///
+    /// When the balance account whitelists destinations, each instruction has to be for a
+    /// program with an unexpired entry in the wallet's dapp book. Programs commonly invoked
+    /// alongside a dapp, such as the system and token programs, are allowed by giving them
+    /// dapp book entries of their own.
     SupplyDAppTransactionInstructions {
         instructions: Vec<Instruction>,
         starting_index: u8,
@@ -255,13 +527,15 @@ pub enum ProgramInstruction {
     /// 5. `[]` The sysvar clock account
     /// 6. `[writable]` The fee account, if fee_account_guid_hash was set in the init
     /// 7. `[]` The system program (only needed if fee_account_guid_hash was set in the init)
+    /// 8. Any number of the programs and accounts the supplied instructions reference, each
+    ///    writable and signer exactly as the instructions give it
     FinalizeDAppTransaction {
         account_guid_hash: BalanceAccountGuidHash,
         params_hash: Hash,
     },
 
     /// 0  `[writable]` The multisig operation account
-    /// 1. `[]` The wallet account
+    /// 1. `[writable]` The wallet account
     /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
     /// 3. `[]` The sysvar clock account
     /// 4. `[signer]` The initiator account
@@ -271,6 +545,8 @@ pub enum ProgramInstruction {
         account_guid_hash: BalanceAccountGuidHash,
         whitelist_enabled: Option<BooleanSetting>,
         dapps_enabled: Option<BooleanSetting>,
+        archived: Option<BooleanSetting>,
+        system_owned_destinations_only: Option<BooleanSetting>,
     },
 
     /// 0  `[writable]` The multisig operation account
@@ -282,10 +558,12 @@ pub enum ProgramInstruction {
         account_guid_hash: BalanceAccountGuidHash,
         whitelist_enabled: Option<BooleanSetting>,
         dapps_enabled: Option<BooleanSetting>,
+        archived: Option<BooleanSetting>,
+        system_owned_destinations_only: Option<BooleanSetting>,
     },
 
     /// 0. `[writable]` The multisig operation account
-    /// 1. `[]` The wallet account
+    /// 1. `[writable]` The wallet account
     /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
     /// 3. `[]` The sysvar clock account
     /// 4. `[signer]` The rent return account
@@ -304,10 +582,16 @@ pub enum ProgramInstruction {
     FinalizeDAppBookUpdate { update: DAppBookUpdate },
 
     /// 0. `[writable]` The multisig operation account
-    /// 1. `[]` The wallet account
+    /// 1. `[writable]` The wallet account
     /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
     /// 3. `[]` The sysvar clock account
     /// 4. `[signer]` The rent return account
+    /// 5. `[]` The accounts of any entries being added with a declared kind, optionally
+    ///
+    /// An entry declared a token account has to be the associated token account of its declared
+    /// owner and mint. If the account of an entry with a declared kind is supplied, it also has
+    /// to be owned as one of its kind would be: by the system program for a wallet (whether or
+    /// not it has been created), and by a token program for a token account that exists.
     InitAddressBookUpdate {
         fee_amount: u64,
         fee_account_guid_hash: Option<BalanceAccountGuidHash>,
@@ -323,7 +607,7 @@ pub enum ProgramInstruction {
     FinalizeAddressBookUpdate { update: AddressBookUpdate },
 
     /// 0. `[writable]` The multisig operation account
-    /// 1. `[]` The wallet account
+    /// 1. `[writable]` The wallet account
     /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
     /// 3. `[]` The sysvar clock account
     /// 4. `[signer]` The rent return account
@@ -350,6 +634,8 @@ pub enum ProgramInstruction {
     /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
     /// 3. `[]` The sysvar clock account
     /// 4. `[signer]` The rent return account
+    /// 5. `[]` The program parameters account, bounding the signers, dApp instruction count
+    ///    and approval timeouts the op can set
     InitBalanceAccountPolicyUpdate {
         fee_amount: u64,
         fee_account_guid_hash: Option<BalanceAccountGuidHash>,
@@ -402,7 +688,7 @@ pub enum ProgramInstruction {
     },
 
     /// 0. `[writable]` The multisig operation account
-    /// 1. `[]` The wallet account
+    /// 1. `[writable]` The wallet account
     /// 2. `[signer]` The initiator account
     /// 3. `[]` The sysvar clock account
     /// 4. `[signer]` The rent return account
@@ -419,473 +705,3757 @@ pub enum ProgramInstruction {
     /// 4. `[writable]` The fee account, if fee_account_guid_hash was set in the init
     /// 5. `[]` The system program (only needed if fee_account_guid_hash was set in the init)
     FinalizeSignData { data: Vec<u8> },
-}
 
-impl ProgramInstruction {
-    /// Serialize a ProgramInstruction to a byte vector.
-    pub fn pack(&self) -> Vec<u8> {
-        let mut buf = Vec::with_capacity(size_of::<Self>());
-        match self {
-            &ProgramInstruction::InitWallet {
-                wallet_guid_hash,
-                ref initial_config,
-            } => {
-                let mut initial_config_bytes: Vec<u8> = Vec::new();
-                initial_config.pack(&mut initial_config_bytes);
-                buf.push(TAG_INIT_WALLET);
-                buf.extend_from_slice(wallet_guid_hash.to_bytes());
-                buf.extend_from_slice(&initial_config_bytes);
-            }
-            &ProgramInstruction::InitBalanceAccountCreation {
-                fee_amount,
-                fee_account_guid_hash,
-                ref account_guid_hash,
-                ref creation_params,
-            } => {
-                let mut update_bytes: Vec<u8> = Vec::new();
-                creation_params.pack(&mut update_bytes);
-                buf.push(TAG_INIT_BALANCE_ACCOUNT_CREATION);
-                buf.put_u64_le(fee_amount);
-                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
-                buf.extend_from_slice(account_guid_hash.to_bytes());
-                buf.extend_from_slice(&update_bytes);
-            }
-            &ProgramInstruction::FinalizeBalanceAccountCreation {
-                ref account_guid_hash,
-                ref creation_params,
-            } => {
-                let mut update_bytes: Vec<u8> = Vec::new();
-                creation_params.pack(&mut update_bytes);
-                buf.push(TAG_FINALIZE_BALANCE_ACCOUNT_CREATION);
-                buf.extend_from_slice(account_guid_hash.to_bytes());
-                buf.extend_from_slice(&update_bytes);
-            }
-            &ProgramInstruction::InitTransfer {
-                fee_amount,
-                fee_account_guid_hash,
-                ref account_guid_hash,
-                ref amount,
-                ref destination_name_hash,
-            } => {
-                buf.push(TAG_INIT_TRANSFER);
-                buf.put_u64_le(fee_amount);
-                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
-                buf.extend_from_slice(account_guid_hash.to_bytes());
-                buf.extend_from_slice(&amount.to_le_bytes());
-                buf.extend_from_slice(destination_name_hash.to_bytes());
-            }
-            &ProgramInstruction::FinalizeTransfer {
-                ref account_guid_hash,
-                ref amount,
-                ref token_mint,
-            } => {
-                buf.push(TAG_FINALIZE_TRANSFER);
-                buf.extend_from_slice(account_guid_hash.to_bytes());
-                buf.extend_from_slice(&amount.to_le_bytes());
-                buf.extend_from_slice(&token_mint.to_bytes());
-                buf.push(0);
-            }
-            &ProgramInstruction::SetApprovalDisposition {
-                ref disposition,
-                ref params_hash,
-            } => {
-                buf.push(TAG_SET_APPROVAL_DISPOSITION);
-                buf.push(disposition.to_u8());
-                buf.extend_from_slice(params_hash.as_ref());
-            }
-            &ProgramInstruction::InitWrapUnwrap {
-                fee_amount,
-                fee_account_guid_hash,
-                ref account_guid_hash,
-                ref amount,
-                ref direction,
-            } => {
-                buf.push(TAG_INIT_WRAP_UNWRAP);
-                buf.put_u64_le(fee_amount);
-                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
-                buf.extend_from_slice(&account_guid_hash.to_bytes());
-                buf.extend_from_slice(&amount.to_le_bytes());
-                buf.push(direction.to_u8());
-            }
-            &ProgramInstruction::FinalizeWrapUnwrap {
-                ref account_guid_hash,
-                ref amount,
-                ref direction,
-            } => {
-                buf.push(TAG_FINALIZE_WRAP_UNWRAP);
-                buf.extend_from_slice(&account_guid_hash.to_bytes());
-                buf.extend_from_slice(&amount.to_le_bytes());
-                buf.push(direction.to_u8());
-            }
-            &ProgramInstruction::InitUpdateSigner {
-                fee_amount,
-                fee_account_guid_hash,
-                ref slot_update_type,
-                ref slot_id,
-                ref signer,
-            } => {
-                buf.push(TAG_INIT_UPDATE_SIGNER);
-                buf.put_u64_le(fee_amount);
-                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
-                buf.push(slot_update_type.to_u8());
-                buf.push(slot_id.value as u8);
-                buf.extend_from_slice(signer.key.as_ref());
-            }
-            &ProgramInstruction::FinalizeUpdateSigner {
-                ref slot_update_type,
-                ref slot_id,
-                ref signer,
-            } => {
-                buf.push(TAG_FINALIZE_UPDATE_SIGNER);
-                buf.push(slot_update_type.to_u8());
-                buf.push(slot_id.value as u8);
-                buf.extend_from_slice(signer.key.as_ref());
-            }
-            &ProgramInstruction::InitWalletConfigPolicyUpdate {
-                fee_amount,
-                fee_account_guid_hash,
-                ref update,
-            } => {
-                let mut update_bytes: Vec<u8> = Vec::new();
-                update.pack(&mut update_bytes);
-                buf.push(TAG_INIT_WALLET_CONFIG_POLICY_UPDATE);
-                buf.put_u64_le(fee_amount);
-                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
-                buf.extend_from_slice(&update_bytes);
-            }
-            &ProgramInstruction::FinalizeWalletConfigPolicyUpdate { ref update } => {
-                let mut update_bytes: Vec<u8> = Vec::new();
-                update.pack(&mut update_bytes);
-                buf.push(TAG_FINALIZE_WALLET_CONFIG_POLICY_UPDATE);
-                buf.extend_from_slice(&update_bytes);
-            }
-            &ProgramInstruction::InitDAppTransaction {
-                fee_amount,
-                fee_account_guid_hash,
-                ref account_guid_hash,
-                ref dapp,
-                instruction_count,
-            } => {
-                buf.push(TAG_INIT_DAPP_TRANSACTION);
-                buf.put_u64_le(fee_amount);
-                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
-                buf.extend_from_slice(&account_guid_hash.to_bytes());
-                let mut buf2 = vec![0; DAppBookEntry::LEN];
-                dapp.pack_into_slice(buf2.as_mut_slice());
-                buf.extend_from_slice(&buf2[..]);
-                buf.put_u8(instruction_count);
-            }
-            &ProgramInstruction::FinalizeDAppTransaction {
-                ref account_guid_hash,
-                ref params_hash,
-            } => {
-                buf.push(TAG_FINALIZE_DAPP_TRANSACTION);
-                buf.extend_from_slice(&account_guid_hash.to_bytes());
-                buf.extend_from_slice(&params_hash.to_bytes());
-            }
-            &ProgramInstruction::InitAccountSettingsUpdate {
-                fee_amount,
-                fee_account_guid_hash,
-                ref account_guid_hash,
-                ref whitelist_enabled,
-                ref dapps_enabled,
-            } => {
-                buf.push(TAG_INIT_ACCOUNT_SETTINGS_UPDATE);
-                buf.put_u64_le(fee_amount);
-                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
-                buf.extend_from_slice(&account_guid_hash.to_bytes());
-                pack_option(whitelist_enabled.as_ref(), &mut buf);
-                pack_option(dapps_enabled.as_ref(), &mut buf);
-            }
-            &ProgramInstruction::FinalizeAccountSettingsUpdate {
-                ref account_guid_hash,
-                ref whitelist_enabled,
-                ref dapps_enabled,
-            } => {
-                buf.push(TAG_FINALIZE_ACCOUNT_SETTINGS_UPDATE);
-                buf.extend_from_slice(&account_guid_hash.to_bytes());
-                pack_option(whitelist_enabled.as_ref(), &mut buf);
-                pack_option(dapps_enabled.as_ref(), &mut buf);
-            }
-            &ProgramInstruction::InitDAppBookUpdate {
-                fee_amount,
-                fee_account_guid_hash,
-                ref update,
-            } => {
-                buf.push(TAG_INIT_DAPP_BOOK_UPDATE);
-                buf.put_u64_le(fee_amount);
-                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
-                let mut update_bytes: Vec<u8> = Vec::new();
-                update.pack(&mut update_bytes);
-                buf.extend_from_slice(&update_bytes);
-            }
-            &ProgramInstruction::FinalizeDAppBookUpdate { ref update } => {
-                buf.push(TAG_FINALIZE_DAPP_BOOK_UPDATE);
-                let mut update_bytes: Vec<u8> = Vec::new();
-                update.pack(&mut update_bytes);
-                buf.extend_from_slice(&update_bytes);
-            }
-            &ProgramInstruction::InitAddressBookUpdate {
-                fee_amount,
-                fee_account_guid_hash,
-                ref update,
-            } => {
-                let mut update_bytes: Vec<u8> = Vec::new();
-                update.pack(&mut update_bytes);
-                buf.push(TAG_INIT_ADDRESS_BOOK_UPDATE);
-                buf.put_u64_le(fee_amount);
-                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
-                buf.extend_from_slice(&update_bytes);
-            }
-            &ProgramInstruction::FinalizeAddressBookUpdate { ref update } => {
+    /// 0. `[]` The multisig operation account of the transfer being acknowledged
+    /// 1. `[writable]` The internal transfer handshake account
+    /// 2. `[]` The source wallet account
+    /// 3. `[]` The destination wallet account
+    /// 4. `[]` The destination balance account
+    /// 5. `[signer]` The acknowledging account (an approver in the destination wallet)
+    /// 6. `[signer, writable]` The rent return account of the multisig operation
+    /// 7. `[]` The sysvar clock account
+    /// 8. `[]` The system program
+    ///
+    /// Acknowledges the transfer whose params hash the acknowledger was shown, which has to be
+    /// the one stored in the op. The handshake records the hash, and the transfer's finalize
+    /// only accepts a handshake recorded for its own params.
+    AcknowledgeInternalTransfer {
+        account_guid_hash: BalanceAccountGuidHash,
+        params_hash: Hash,
+    },
+
+    /// 0. `[writable]` The wallet account
+    /// 1. `[signer]` The transaction assistant account
+    /// 2. `[]` The sysvar clock account
+    AddPendingAddressBookEntries {
+        entries: Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+    },
+
+    /// 0. `[writable]` The wallet account
+    /// 1. `[signer]` The ratifying account (a config approver)
+    RatifyPendingAddressBookEntries {
+        entries: Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[]` The vote account, whose withdraw authority is the balance account
+    /// 3. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 4. `[]` The sysvar clock account
+    /// 5. `[signer]` The rent return account
+    ///
+    /// The withdrawn rewards are split according to the balance account's reward split policy.
+    InitVoteRewardsWithdrawal {
+        fee_amount: u64,
+        fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+        account_guid_hash: BalanceAccountGuidHash,
+        amount: u64,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The wallet account
+    /// 2. `[writable]` The vote account
+    /// 3. `[writable]` The balance account holding the vote account withdraw authority
+    /// 4. `[]` The vote program
+    /// 5. `[]` The system program
+    /// 6. `[signer, writable]` The rent return account
+    /// 7. `[]` The sysvar clock account
+    /// 8. `[writable]` The balance account of each split of the reward split policy, in
+    ///    balance account slot order
+    /// 9. `[writable]` The fee account, if fee_account_guid_hash was set in the init
+    FinalizeVoteRewardsWithdrawal {
+        account_guid_hash: BalanceAccountGuidHash,
+        amount: u64,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[signer]` The rent return account
+    ///
+    /// Setting a slot which already holds the given key updates its enable flag and rate limit.
+    InitUpdateAssistant {
+        fee_amount: u64,
+        fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+        slot_update_type: SlotUpdateType,
+        slot_id: SlotId<Assistant>,
+        assistant: Assistant,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer, writable]` The rent return account
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[writable]` The fee account, if fee_account_guid_hash was set in the init
+    /// 5. `[]` The system program (only needed if fee_account_guid_hash was set in the init)
+    FinalizeUpdateAssistant {
+        slot_update_type: SlotUpdateType,
+        slot_id: SlotId<Assistant>,
+        assistant: Assistant,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[signer]` The rent return account
+    InitRewardSplitPolicyUpdate {
+        fee_amount: u64,
+        fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+        account_guid_hash: BalanceAccountGuidHash,
+        splits: Vec<RewardSplit>,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer, writable]` The rent return account
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[writable]` The fee account, if fee_account_guid_hash was set in the init
+    /// 5. `[]` The system program (only needed if fee_account_guid_hash was set in the init)
+    FinalizeRewardSplitPolicyUpdate {
+        account_guid_hash: BalanceAccountGuidHash,
+        splits: Vec<RewardSplit>,
+    },
+
+    /// 0. `[writable]` The wallet account
+    /// 1. `[signer]` The transaction assistant account
+    /// 2. `[]` The sysvar clock account
+    RemoveDeprecatedAddressBookEntries {
+        entries: Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+    },
+
+    /// 0. `[writable]` The wallet account
+    /// 1. `[]` The sysvar clock account
+    ActivateWalletConfigPolicy {},
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The wallet account
+    /// 2. `[]` The destination account
+    /// 3. `[signer]` The config approver account
+    /// 4. `[]` The sysvar clock account
+    ///
+    /// Cancels an approved transfer whose finalize delay has not elapsed yet, taking the same
+    /// params as the `FinalizeTransfer` it would otherwise be finalized with. The transfer is
+    /// then finalized as denied.
+    CancelTransfer {
+        account_guid_hash: BalanceAccountGuidHash,
+        amount: u64,
+        token_mint: Pubkey,
+        destination_acknowledgement_required: bool,
+        deduct_fee_from_amount: bool,
+        allow_destination_ata_creation: bool,
+        memo: Option<Vec<u8>>,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[signer]` The rent return account
+    ///
+    /// The rent return account submits the finalize transaction, so it has to be permitted by
+    /// the policy in effect when the operation is finalized.
+    InitFinalizeSubmitterPolicyUpdate {
+        fee_amount: u64,
+        fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+        policy: FinalizeSubmitterPolicy,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer, writable]` The rent return account
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[writable]` The fee account, if fee_account_guid_hash was set in the init
+    /// 5. `[]` The system program (only needed if fee_account_guid_hash was set in the init)
+    FinalizeFinalizeSubmitterPolicyUpdate { policy: FinalizeSubmitterPolicy },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[]` The balance account holding the mint authority
+    /// 3. `[]` The token mint account
+    /// 4. `[]` The destination token account, whose owner must be an allowed destination of
+    ///    the balance account
+    /// 5. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 6. `[]` The sysvar clock account
+    /// 7. `[signer]` The rent return account
+    ///
+    /// The amount can't exceed the balance account's mint cap. As for `InitTransfer`, minting to
+    /// a high-risk destination needs the approval of the wallet's compliance service.
+    InitMintTo {
+        fee_amount: u64,
+        fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+        account_guid_hash: BalanceAccountGuidHash,
+        amount: u64,
+        destination_name_hash: AddressBookEntryNameHash,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The wallet account
+    /// 2. `[]` The balance account holding the mint authority
+    /// 3. `[writable]` The token mint account
+    /// 4. `[writable]` The destination token account
+    /// 5. `[]` The SPL token program
+    /// 6. `[signer, writable]` The rent return account
+    /// 7. `[]` The sysvar clock account
+    /// 8. `[writable]` The fee account, if fee_account_guid_hash was set in the init
+    FinalizeMintTo {
+        account_guid_hash: BalanceAccountGuidHash,
+        amount: u64,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[]` The balance account holding the mint authority
+    /// 3. `[]` The token mint account
+    /// 4. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 5. `[]` The sysvar clock account
+    /// 6. `[signer]` The rent return account
+    ///
+    /// A new authority of None revokes the authority for good.
+    InitSetMintAuthority {
+        fee_amount: u64,
+        fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+        account_guid_hash: BalanceAccountGuidHash,
+        authority_type: MintAuthorityType,
+        new_authority: Option<Pubkey>,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The wallet account
+    /// 2. `[]` The balance account holding the mint authority
+    /// 3. `[writable]` The token mint account
+    /// 4. `[]` The SPL token program
+    /// 5. `[signer, writable]` The rent return account
+    /// 6. `[]` The sysvar clock account
+    /// 7. `[writable]` The fee account, if fee_account_guid_hash was set in the init
+    FinalizeSetMintAuthority {
+        account_guid_hash: BalanceAccountGuidHash,
+        authority_type: MintAuthorityType,
+        new_authority: Option<Pubkey>,
+    },
+
+    /// 0. `[]` The wallet account
+    ///
+    /// Validates the update without applying it, returning the resulting PolicyDiff as
+    /// return data.
+    SimulateWalletConfigPolicyUpdate { update: WalletConfigPolicyUpdate },
+
+    /// 0. `[]` The wallet account
+    ///
+    /// Validates the update without applying it, returning the resulting PolicyDiff as
+    /// return data.
+    SimulateBalanceAccountPolicyUpdate {
+        account_guid_hash: BalanceAccountGuidHash,
+        update: BalanceAccountPolicyUpdate,
+    },
+
+    /// 0. `[]` The wallet account
+    /// 1. `[signer]` The initiator account the update would be initiated by
+    /// 2. `[]` The sysvar clock account
+    /// 3. `[]` The program parameters account, for policy updates
+    /// 4. `[]` The accounts of address book entries being added with a declared kind
+    ///
+    /// Runs the update through the validation its init would, logs the policy it would result
+    /// in, and then fails with `SimulationFinished`, so that clients can preview an update
+    /// before initiating it. A policy update also returns its PolicyDiff as return data.
+    SimulateConfigUpdate { update: ConfigUpdate },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[]` The payment channel account, owned by the program and not yet initialized
+    /// 3. `[]` The destination account
+    /// 4. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 5. `[]` The sysvar clock account
+    /// 6. `[signer]` The rent return account
+    /// 7. `[]` The price account of the token mint (only used if the wallet has a price oracle)
+    ///
+    /// The token mint is the default pubkey for SOL. The channel's window starts when the
+    /// operation is finalized. As for `InitTransfer`, a channel to a high-risk destination needs
+    /// the approval of the wallet's compliance service.
+    InitPaymentChannel {
+        fee_amount: u64,
+        fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+        account_guid_hash: BalanceAccountGuidHash,
+        destination_name_hash: AddressBookEntryNameHash,
+        token_mint: Pubkey,
+        allowance: u64,
+        duration: Duration,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The wallet account
+    /// 2. `[writable]` The payment channel account
+    /// 3. `[]` The destination account
+    /// 4. `[signer, writable]` The rent return account
+    /// 5. `[]` The sysvar clock account
+    /// 6. `[writable]` The fee account, if fee_account_guid_hash was set in the init
+    FinalizePaymentChannel {
+        account_guid_hash: BalanceAccountGuidHash,
+        token_mint: Pubkey,
+        allowance: u64,
+        duration: Duration,
+    },
+
+    /// 0. `[writable]` The payment channel account
+    /// 1. `[]` The wallet account
+    /// 2. `[writable]` The source balance account
+    /// 3. `[writable]` The destination account
+    /// 4. `[signer]` The transaction assistant account
+    /// 5. `[]` The sysvar clock account
+    /// 6. `[]` The system program
+    /// 7. `[writable]` The source token account, for SPL channels
+    /// 8. `[writable]` The destination token account, for SPL channels
+    /// 9. `[]` The SPL token program, for SPL channels
+    ChannelPayment { amount: u64 },
+
+    /// 0. `[writable]` The op bundle account
+    /// 1. `[]` The wallet account
+    /// 2. `[signer]` The initiator of the member ops
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[signer]` The rent return account
+    /// 5. `[]` The member multisig operation accounts, one after the other
+    InitOpBundle {},
+
+    /// 0. `[writable]` The op bundle account
+    /// 1. `[]` The wallet account
+    /// 2. `[signer]` The approver account
+    /// 3. `[]` The sysvar clock account
+    ApproveOpBundle { bundle_hash: Hash },
+
+    /// 0. `[]` The op bundle account
+    /// 1. `[writable]` The member multisig operation account
+    /// 2. `[]` The sysvar clock account
+    ApplyOpBundleApproval {},
+
+    /// 0. `[writable]` The op bundle account
+    /// 1. `[signer, writable]` The rent return account
+    CloseOpBundle {},
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[signer]` The rent return account
+    ///
+    /// Without a lookup table, a new one owned by the wallet's lookup table authority is
+    /// created when the operation is finalized.
+    InitLookupTableUpdate {
+        fee_amount: u64,
+        fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+        lookup_table: Option<Pubkey>,
+        new_addresses: Vec<Pubkey>,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The wallet account
+    /// 2. `[signer, writable]` The rent return account, which also pays for the lookup table
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[writable]` The lookup table account
+    /// 5. `[]` The wallet's lookup table authority PDA
+    /// 6. `[]` The system program
+    /// 7. `[]` The address lookup table program
+    /// 8. `[writable]` The fee account, if fee_account_guid_hash was set in the init
+    ///
+    /// The recent slot is only used when creating a lookup table, whose address is derived
+    /// from it. It has to be recent enough to still be in the slot hashes sysvar.
+    FinalizeLookupTableUpdate {
+        lookup_table: Option<Pubkey>,
+        new_addresses: Vec<Pubkey>,
+        recent_slot: u64,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The wallet account
+    /// 2. `[]` The source wallet account, whose address book entries are imported
+    /// 3. `[signer]` The source approver account, a config approver of the source wallet who
+    ///    is also a signer of this wallet
+    /// 4. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 5. `[]` The sysvar clock account
+    /// 6. `[signer]` The rent return account
+    ///
+    /// Entries are given as pairs of their slot in the source wallet and the slot they are
+    /// added at, and balance accounts as pairs of a source balance account and the balance
+    /// account which gets the imported entries that source account whitelists enabled.
+    InitAddressBookImport {
+        fee_amount: u64,
+        fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+        entries: Vec<(SlotId<AddressBookEntry>, SlotId<AddressBookEntry>)>,
+        balance_accounts: Vec<(BalanceAccountGuidHash, BalanceAccountGuidHash)>,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer, writable]` The rent return account
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[writable]` The fee account, if fee_account_guid_hash was set in the init
+    /// 5. `[]` The system program (only needed if fee_account_guid_hash was set in the init)
+    FinalizeAddressBookImport { import: AddressBookImport },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[signer]` The rent return account
+    /// 5. `[]` The price account of SOL (only used if the wallet has a price oracle)
+    ///
+    /// Initiates a SOL transfer to the destination whose `AddressBookEntry::commitment` is
+    /// given, without naming the destination until the transfer is finalized. Like any other
+    /// transfer, it is held for the balance account's finalize delay once approved if its
+    /// amount is above the delay threshold.
+    InitCommittedTransfer {
+        fee_amount: u64,
+        fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+        account_guid_hash: BalanceAccountGuidHash,
+        amount: u64,
+        destination_commitment: Hash,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[writable]` The source account
+    /// 3. `[writable]` The destination account
+    /// 4. `[]` The system program
+    /// 5. `[signer, writable]` The rent return account
+    /// 6. `[]` The sysvar clock account
+    /// 7. `[writable]` The destination receipt account, as for `FinalizeTransfer`
+    /// 8. `[writable]` The fee account, if fee_account_guid_hash was set in the init
+    /// 9. `[]` The system program (only needed if fee_account_guid_hash was set in the init)
+    ///
+    /// If the transfer was approved, the destination has to match the commitment and be
+    /// allowed by the balance account's whitelist. A denied or expired transfer is finalized
+    /// without revealing anything, so the destination account can be any account then. The
+    /// destination can't be an address book entry classified high-risk, as the compliance
+    /// approval such a transfer needs couldn't have been asked for when it was initiated.
+    FinalizeCommittedTransfer {
+        account_guid_hash: BalanceAccountGuidHash,
+        amount: u64,
+        destination_commitment: Hash,
+        destination_name_hash: AddressBookEntryNameHash,
+        salt: Hash,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[signer]` The rent return account
+    ///
+    /// Sets the compliance service whose approval transfers above the policy's threshold
+    /// need, or removes it when no policy is given. Transfers already initiated keep the
+    /// requirement they were initiated with.
+    InitCompliancePolicyUpdate {
+        fee_amount: u64,
+        fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+        policy: Option<CompliancePolicy>,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer, writable]` The rent return account
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[writable]` The fee account, if fee_account_guid_hash was set in the init
+    /// 5. `[]` The system program (only needed if fee_account_guid_hash was set in the init)
+    FinalizeCompliancePolicyUpdate { policy: Option<CompliancePolicy> },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[signer]` The compliance service account
+    ///
+    /// Records the compliance service's approval of an operation it has screened, which the
+    /// operation needs to be finalized if it was initiated above the policy's threshold.
+    ComplianceApprove { params_hash: Hash },
+
+    /// 0. `[]` The wallet account
+    ///
+    /// Returns the balance account's allowed destinations, from the given address book slot
+    /// on, as an AllowedDestinationsPage in the return data.
+    GetAllowedDestinations {
+        account_guid_hash: BalanceAccountGuidHash,
+        start_slot_id: SlotId<AddressBookEntry>,
+    },
+
+    /// 0..n-1. The accounts of the wrapped init instruction, the initiator not signing
+    /// n. `[]` The instructions sysvar account
+    ///
+    /// Runs the wrapped init instruction with its initiator authenticated by an ed25519
+    /// signature instead of a transaction signature, so that the initiator's key can live in an
+    /// HSM that never builds transactions. The instruction immediately before this one has to be
+    /// an ed25519 program instruction verifying the initiator's signature of the
+    /// `init_signature_message` of the wrapped instruction.
+    InitWithSignature { init: Vec<u8> },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[signer]` The rent return account
+    ///
+    /// Makes the wallet a primary mirroring its config changes into the given standby, a
+    /// standby taking them from the given primary, or ends its replication. A standby's
+    /// approvers end its replication to cut over to it.
+    InitWalletReplicationUpdate {
+        fee_amount: u64,
+        fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+        replication: Option<WalletReplication>,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer, writable]` The rent return account
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[writable]` The fee account, if fee_account_guid_hash was set in the init
+    /// 5. `[]` The system program (only needed if fee_account_guid_hash was set in the init)
+    /// 6. `[]` The standby wallet account, if the wallet is becoming a primary
+    ///
+    /// A wallet becomes a primary only once its standby has become its standby, and while the
+    /// standby has the same signers and config policy.
+    FinalizeWalletReplicationUpdate {
+        replication: Option<WalletReplication>,
+    },
+
+    /// 0. `[writable]` The standby wallet account
+    /// 1. `[signer]` The mirror authority account of the standby's primary
+    ///
+    /// Applies a config change its primary has just finalized to a standby. Only the program
+    /// can sign for a primary's mirror authority, so this is only ever invoked by the
+    /// primary's finalize.
+    ApplyMirroredUpdate { update: MirroredUpdate },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[signer]` The rent return account
+    InitBalanceAccountDeletion {
+        fee_amount: u64,
+        fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+        account_guid_hash: BalanceAccountGuidHash,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[writable]` The balance account
+    /// 3. `[]` The system program
+    /// 4. `[signer, writable]` The rent return account
+    /// 5. `[]` The sysvar clock account
+    /// 6. `[writable]` The fee account, if fee_account_guid_hash was set in the init
+    ///
+    /// Frees the balance account's slot and its address book entry, and sweeps all its
+    /// lamports into the rent return account. Its SPL token accounts are left as they are.
+    FinalizeBalanceAccountDeletion {
+        account_guid_hash: BalanceAccountGuidHash,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[signer]` The rent return account
+    ///
+    /// Sets the amount of token_mint (the default pubkey for SOL) which transfers out of the
+    /// balance account may add up to within 24 hours while only needing the approval of their
+    /// initiator. A zero amount removes the limit.
+    InitSpendingLimitUpdate {
+        fee_amount: u64,
+        fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+        account_guid_hash: BalanceAccountGuidHash,
+        token_mint: Pubkey,
+        amount: u64,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer, writable]` The rent return account
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[writable]` The fee account, if fee_account_guid_hash was set in the init
+    FinalizeSpendingLimitUpdate {
+        account_guid_hash: BalanceAccountGuidHash,
+        token_mint: Pubkey,
+        amount: u64,
+    },
+
+    /// 0. `[]` The multisig operation account
+    /// 1. `[]` The sysvar clock account
+    ///
+    /// Logs an approval reminder for the op, along with the approvers yet to register a
+    /// disposition, if it is pending and hasn't seen approval activity for at least idle_slots
+    /// slots. Anyone can submit it, so notification services can trigger reminders off it.
+    Nudge { idle_slots: u64 },
+
+    /// 0. `[writable]` The wallet account
+    /// 1. `[signer]` The config approver account
+    /// 2. `[]` The sysvar clock account
+    ///
+    /// Lets any single config approver expire a dapp book entry on the spot, so no new dapp
+    /// transactions can be started with it from balance accounts that have whitelisting
+    /// enabled, pending a dapp book update that renews or removes the entry.
+    SuspendDApp { dapp: DAppBookEntry },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[signer]` The rent return account
+    ///
+    /// Sets the price feeds transfers are valued with, which makes the compliance and finalize
+    /// delay thresholds US cents, or puts them back in units of the token transferred when no
+    /// oracle is given. Transfers already initiated keep the delay and compliance requirement
+    /// they were initiated with.
+    InitPriceOracleUpdate {
+        fee_amount: u64,
+        fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+        oracle: Option<PriceOracle>,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer, writable]` The rent return account
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[writable]` The fee account, if fee_account_guid_hash was set in the init
+    /// 5. `[]` The system program (only needed if fee_account_guid_hash was set in the init)
+    FinalizePriceOracleUpdate { oracle: Option<PriceOracle> },
+
+    /// 0. `[]` The wallet account
+    /// 1. `[writable]` The token account registry account (PDA of the wallet and balance account)
+    /// 2. `[]` The balance account
+    /// 3. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 4. `[signer, writable]` The payer account, which funds the registry on first use
+    /// 5. `[]` The system program
+    /// 6. `[]` Any number of token accounts to register, of either token program, each owned by
+    ///    the balance account
+    ///
+    /// Records token accounts owned by the balance account in its token account registry, so
+    /// clients and future sweep or consolidation flows have an authoritative list of them,
+    /// along with whether each is frozen. Registering a token account again refreshes its
+    /// freeze state.
+    RegisterTokenAccounts {
+        account_guid_hash: BalanceAccountGuidHash,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[signer]` The rent return account
+    ///
+    /// Sets the Merkle root of the balance account's Merkle address book, to any entry of which
+    /// a whitelisted transfer can be initiated with a proof of membership. A zero root clears it.
+    InitMerkleAddressBookUpdate {
+        fee_amount: u64,
+        fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+        account_guid_hash: BalanceAccountGuidHash,
+        root: Hash,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The wallet account
+    /// 2. `[writable]` The Merkle address book account (PDA of the wallet and balance account)
+    /// 3. `[signer, writable]` The rent return account, which funds the Merkle address book
+    ///    account the first time it is set
+    /// 4. `[]` The sysvar clock account
+    /// 5. `[]` The system program
+    /// 6. `[writable]` The fee account, if fee_account_guid_hash was set in the init
+    FinalizeMerkleAddressBookUpdate {
+        account_guid_hash: BalanceAccountGuidHash,
+        root: Hash,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The rent return account of the op
+    /// 2. `[]` The sysvar clock account
+    /// 3. `[writable]` The multisig data account of a dapp transaction op, or any number of
+    ///    internal transfer handshakes acknowledging a transfer op
+    ///
+    /// Marks an op that is past its expiry without having been finalized as expired, and
+    /// returns its rent, along with that of the side accounts its finalize would have closed,
+    /// to its rent return account. Anyone can submit it. Handshakes that aren't passed are left
+    /// open. A pending op holds no lock on the wallet or its balance accounts, so there is
+    /// nothing else to release, and other ops can be initiated whether or not an expired one
+    /// has been cleaned up.
+    CleanupExpiredOp {},
+
+    /// 0..n. The accounts of the wrapped init instruction
+    ///
+    /// Runs the wrapped init instruction (or an `InitWithSignature` wrapping one), then records
+    /// in the header of the op it started how many compute units its finalize is expected to
+    /// take, so that whoever submits the finalize knows what compute budget to request.
+    /// `InitOpBundle` can't be wrapped, as it doesn't start a multisig op.
+    InitWithComputeHint {
+        finalize_compute_units: u32,
+        init: Vec<u8>,
+    },
+
+    /// 0. `[]` The wallet account
+    /// 1. `[writable]` The wallet read model account (PDA of the wallet)
+    /// 2. `[signer, writable]` The payer, which funds the read model account the first time
+    ///    it is exported
+    /// 3. `[]` The system program
+    ///
+    /// Exports the wallet's read model: its config policy hash, balance account digests,
+    /// config approvers and whitelisted destinations. Anyone can submit it. Once exported, the
+    /// read model is also refreshed by any config finalize it is supplied to as the last
+    /// account.
+    ExportWalletReadModel {},
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[signer]` The rent return account
+    ///
+    /// Freezes or unfreezes the wallet, and sets how many signers can freeze it together with
+    /// an `EmergencyFreeze`. No transfers, wraps or dApp transactions can be initiated while
+    /// the wallet is frozen; ops already initiated are unaffected.
+    InitWalletFreeze {
+        fee_amount: u64,
+        fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+        freeze: WalletFreeze,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer, writable]` The rent return account
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[writable]` The fee account, if fee_account_guid_hash was set in the init
+    /// 5. `[]` The system program (only needed if fee_account_guid_hash was set in the init)
+    FinalizeWalletFreeze { freeze: WalletFreeze },
+
+    /// 0. `[writable]` The wallet account
+    /// 1. `[signer]` Any number of signers freezing the wallet
+    ///
+    /// Freezes the wallet without going through approvals, as long as at least its emergency
+    /// freeze quorum of signers sign. Unfreezing takes an approved `InitWalletFreeze`.
+    EmergencyFreeze {},
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[signer]` The rent return account
+    ///
+    /// Sets the accounts the rent of multisig ops may be returned to when they are finalized.
+    /// An empty allowlist allows any account. Ops initiated with a rent return account that
+    /// is no longer allowed can't be finalized, and are left to expire.
+    InitRentReturnAllowlistUpdate {
+        fee_amount: u64,
+        fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+        allowlist: RentReturnAllowlist,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer, writable]` The rent return account
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[writable]` The fee account, if fee_account_guid_hash was set in the init
+    /// 5. `[]` The system program (only needed if fee_account_guid_hash was set in the init)
+    FinalizeRentReturnAllowlistUpdate { allowlist: RentReturnAllowlist },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[signer]` The rent return account
+    ///
+    /// Replaces the wallet's assistants with the given one, its rent return key (on the rent
+    /// return allowlist too), and each of the given signer keys (as a named finalize submitter
+    /// too) with its new key in one op, which every config approver has to approve. Signer
+    /// slots are kept, so approver bitmaps carry over to the new keys. Ops initiated before
+    /// the rotation keep the approvers they were initiated with.
+    InitFullRotation {
+        fee_amount: u64,
+        fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+        rotation: FullRotation,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer, writable]` The rent return account
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[writable]` The fee account, if fee_account_guid_hash was set in the init
+    /// 5. `[]` The system program (only needed if fee_account_guid_hash was set in the init)
+    FinalizeFullRotation { rotation: FullRotation },
+
+    /// 0. `[writable]` The program parameters account
+    /// 1. `[]` The program's program data account
+    /// 2. `[signer, writable]` The program's upgrade authority, which pays for the account
+    /// 3. `[]` The system program
+    ///
+    /// Sets the program-global limits, within the floors and ceilings in code, creating the
+    /// parameters account at its PDA if needed.
+    UpdateProgramParameters { parameters: ProgramParameters },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[signer]` The rent return account
+    ///
+    /// Sets how long signers added to the wallet wait before they can initiate ops or count
+    /// toward quorums. A shortened delay only applies to signers added once the previous delay
+    /// would have elapsed from the update.
+    InitSignerActivationDelayUpdate {
+        fee_amount: u64,
+        fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+        delay: Duration,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer, writable]` The rent return account
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[writable]` The fee account, if fee_account_guid_hash was set in the init
+    /// 5. `[]` The system program (only needed if fee_account_guid_hash was set in the init)
+    FinalizeSignerActivationDelayUpdate { delay: Duration },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[signer]` The rent return account
+    ///
+    /// Sets the cap on the fee a multisig op may collect from its fee account. Ops initiated
+    /// with a larger fee are rejected, and ops already pending collect at most the new cap.
+    InitFeePolicyUpdate {
+        fee_amount: u64,
+        fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+        fee_policy: FeePolicy,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer, writable]` The rent return account
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[writable]` The fee account, if fee_account_guid_hash was set in the init
+    /// 5. `[]` The system program (only needed if fee_account_guid_hash was set in the init)
+    FinalizeFeePolicyUpdate { fee_policy: FeePolicy },
+
+    /// 0. `[]` The wallet account
+    /// 1. `[writable]` The balance account (PDA of the wallet and account guid hash)
+    /// 2. `[signer, writable]` The wallet's rent return account
+    /// 3. `[]` The system program
+    /// 4. `[]` The SPL token program (only needed if token accounts are passed)
+    /// 5. `[writable]` Any number of empty token accounts owned by the balance account
+    ///
+    /// Reclaims what was set up ahead of time for a balance account that was never created,
+    /// because its creation was denied or expired: the token accounts are closed and the
+    /// lamports at the balance account address swept, all into the wallet's rent return
+    /// account. Fails if a balance account with the guid hash exists in the wallet.
+    CleanupUncreatedBalanceAccount {
+        account_guid_hash: BalanceAccountGuidHash,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[signer]` The rent return account
+    ///
+    /// Makes the wallet stake weight governed, so that its ops are approved by the holders of
+    /// the governance token, weighted by their holdings, instead of by its signers; or, with no
+    /// governance, makes the signers approve them again. Ops already pending keep their approvers.
+    InitStakeWeightGovernanceUpdate {
+        fee_amount: u64,
+        fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+        governance: Option<StakeWeightGovernance>,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer, writable]` The rent return account
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[writable]` The fee account, if fee_account_guid_hash was set in the init
+    /// 5. `[]` The system program (only needed if fee_account_guid_hash was set in the init)
+    FinalizeStakeWeightGovernanceUpdate {
+        governance: Option<StakeWeightGovernance>,
+    },
+
+    /// 0..n-1. The accounts of the wrapped init instruction
+    /// n. `[]` The governance token mint
+    /// n+1.. `[]` The stake accounts, each a holder's associated token account of the mint
+    ///
+    /// Runs the wrapped init instruction (or an `InitWithSignature` or `InitWithComputeHint`
+    /// wrapping one) for a stake weight governed wallet, then snapshots the holdings of the
+    /// stake accounts into the op it started: their owners become its approvers, weighted by
+    /// the tokens they hold, and the op needs the wallet's approval threshold of the mint's
+    /// current supply to be approved. Ops of such wallets initiated without it can't be
+    /// approved. The op account has to have room for a disposition record per stake account.
+    InitWithStakeWeights {
+        stake_account_count: u8,
+        init: Vec<u8>,
+    },
+
+    /// 0. `[]` The wallet account
+    /// 1. `[writable]` The wallet event log account (PDA of the wallet)
+    /// 2. `[signer, writable]` The payer, which funds the event log account
+    /// 3. `[]` The system program
+    ///
+    /// Creates the wallet's event log. Anyone can submit it. Once created, the log gets the
+    /// events of an op (initiated, approved, denied, executed and, for config ops, config
+    /// changed, or expired) from any instruction about the op it is supplied to as a writable trailing
+    /// account, ahead of the stake accounts of an `InitWithStakeWeights`.
+    InitEventLog {},
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[signer]` The rent return account
+    ///
+    /// Pushes back the expiry of an address book entry that lapses, re-verifying it without
+    /// an address book update. The entry can be transferred to again once renewed.
+    InitAddressBookRenewal {
+        fee_amount: u64,
+        fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+        slot_id: SlotId<AddressBookEntry>,
+        entry: AddressBookEntry,
+        expires_at: i64,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer, writable]` The rent return account
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[writable]` The fee account, if fee_account_guid_hash was set in the init
+    /// 5. `[]` The system program (only needed if fee_account_guid_hash was set in the init)
+    FinalizeAddressBookRenewal {
+        slot_id: SlotId<AddressBookEntry>,
+        entry: AddressBookEntry,
+        expires_at: i64,
+    },
+
+    /// 0. `[]` The wallet account
+    /// 1. `[writable]` The session key registry account, the wallet's PDA
+    /// 2. `[signer]` The approver registering the session key
+    /// 3. `[signer, writable]` The payer, which funds the registry if it doesn't exist yet
+    /// 4. `[]` The system program
+    ///
+    /// Lets a signer of the wallet register a short-lived session key, which can then sign
+    /// `SetApprovalDisposition` on their behalf for the ops in its scope until it expires, at
+    /// most `SessionKeyRegistry::MAX_SESSION_DURATION` from now.
+    RegisterSessionKey {
+        session_key: Pubkey,
+        scope: SessionKeyScope,
+        expires_at: i64,
+    },
+
+    /// 0. `[writable]` The session key registry account
+    /// 1. `[signer]` The approver who registered the session key
+    ///
+    /// Revokes a session key before it expires.
+    RevokeSessionKey { session_key: Pubkey },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The approver account, which doesn't sign
+    /// 2. `[]` The sysvar clock account
+    /// 3. `[]` The instructions sysvar account
+    ///
+    /// Records the approver's disposition like `SetApprovalDisposition`, authenticated by an
+    /// ed25519 signature instead of a transaction signature, so that an approver with a cold key
+    /// can sign the `approval_disposition_signature_message` offline and have anyone submit it
+    /// until `expires_at`. The instruction immediately before this one has to be an ed25519
+    /// program instruction verifying the approver's signature of that message.
+    SetApprovalDispositionBySignature {
+        disposition: ApprovalDisposition,
+        params_hash: Hash,
+        expires_at: i64,
+    },
+
+    /// 0. `[]` The wallet account
+    /// 1. `[writable]` The wallet stats account (PDA of the wallet)
+    /// 2. `[signer, writable]` The payer, which funds the stats account
+    /// 3. `[]` The system program
+    ///
+    /// Creates the wallet's stats. Anyone can submit it. Once created, the stats count the ops
+    /// initiated, approved, denied and expired, by op type, and the SOL and tokens transferred
+    /// out, from any instruction about an op they are supplied to as a writable trailing
+    /// account, ahead of the stake accounts of an `InitWithStakeWeights`.
+    InitWalletStats {},
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[]` The token mint account
+    /// 3. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 4. `[]` The sysvar clock account
+    /// 5. `[signer]` The rent return account
+    ///
+    /// Starts an op creating the associated token accounts of up to
+    /// `MultisigOp::MAX_SPL_TOKEN_ACCOUNTS` balance accounts for the mint, paid for by the payer
+    /// balance account and approved by its transfer approvers.
+    InitSPLTokenAccountsCreation {
+        fee_amount: u64,
+        fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+        payer_account_guid_hash: BalanceAccountGuidHash,
+        account_guid_hashes: Vec<BalanceAccountGuidHash>,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The wallet account
+    /// 2. `[writable]` The payer balance account
+    /// 3. `[]` The token mint account
+    /// 4. `[]` The token program owning the mint
+    /// 5. `[]` The SPL associated token program
+    /// 6. `[]` The system program
+    /// 7. `[signer, writable]` The rent return account
+    /// 8. `[]` The sysvar clock account
+    /// 9. The balance account and its `[writable]` associated token account, for each of the
+    ///    next `batch_size` balance accounts of the op still without a token account, in the
+    ///    order of the op's balance accounts
+    /// 10. `[writable]` The fee account, if fee_account_guid_hash was set in the init, after
+    ///     the balance accounts
+    ///
+    /// Creates the first batch of token accounts of an approved op, which is then kept open as
+    /// executed until `ContinueSPLTokenAccountsCreation` has created the rest. A token account
+    /// which already exists is skipped. Fees are collected when the op is closed.
+    FinalizeSPLTokenAccountsCreation {
+        payer_account_guid_hash: BalanceAccountGuidHash,
+        account_guid_hashes: Vec<BalanceAccountGuidHash>,
+        batch_size: u8,
+    },
+
+    /// The accounts are those of `FinalizeSPLTokenAccountsCreation`.
+    ///
+    /// Creates the next batch of token accounts of an op already finalized as approved, without
+    /// further approvals, closing the op once all of its token accounts exist.
+    ContinueSPLTokenAccountsCreation {
+        payer_account_guid_hash: BalanceAccountGuidHash,
+        account_guid_hashes: Vec<BalanceAccountGuidHash>,
+        batch_size: u8,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[signer]` The rent return account
+    ///
+    /// Registers the group key of a threshold signing group of the wallet's approvers, whose
+    /// aggregated signatures `SubmitAggregatedApproval` takes in place of individual approvals;
+    /// or, with no group, stops taking them.
+    InitApprovalGroupUpdate {
+        fee_amount: u64,
+        fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+        approval_group: Option<ApprovalGroup>,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer, writable]` The rent return account
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[writable]` The fee account, if fee_account_guid_hash was set in the init
+    /// 5. `[]` The system program (only needed if fee_account_guid_hash was set in the init)
+    FinalizeApprovalGroupUpdate {
+        approval_group: Option<ApprovalGroup>,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The wallet account
+    /// 2. `[]` The sysvar clock account
+    /// 3. `[]` The instructions sysvar account
+    ///
+    /// Approves the op outright with a signature of its `approval_disposition_signature_message`
+    /// for an approval, aggregated off-chain from the shares of at least the threshold of the
+    /// wallet's approval group, which anyone can submit until `expires_at`. The instruction
+    /// immediately before this one has to be an ed25519 program instruction verifying the
+    /// signature against the group key. The group's threshold has to be at least the approvals
+    /// the op requires, and the op has to be in the group's scope.
+    SubmitAggregatedApproval { params_hash: Hash, expires_at: i64 },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[signer]` The rent return account
+    ///
+    /// Turns the balance account's deposit addresses on or off. A deposit address is a PDA of
+    /// the balance account and an index from 0 to 254 that funds can be sent to, for instance
+    /// to tell customers' deposits apart, and swept from into the balance account by a
+    /// `FinalizeWithDepositSweep`.
+    InitEnableDepositAddresses {
+        fee_amount: u64,
+        fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+        account_guid_hash: BalanceAccountGuidHash,
+        enabled: BooleanSetting,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer, writable]` The rent return account
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[writable]` The fee account, if fee_account_guid_hash was set in the init
+    /// 5. `[]` The system program (only needed if fee_account_guid_hash was set in the init)
+    FinalizeEnableDepositAddresses {
+        account_guid_hash: BalanceAccountGuidHash,
+        enabled: BooleanSetting,
+    },
+
+    /// 0..n-1. The accounts of the wrapped finalize instruction
+    /// n.. `[writable]` For each index, the deposit address of the balance account, followed
+    ///     by its associated token account of the mint for an SPL transfer
+    ///
+    /// Sweeps everything held by the given deposit addresses of the balance account a transfer
+    /// or wrap is out of into it, then runs the wrapped `FinalizeTransfer`, `ExecuteTransfer`
+    /// or `FinalizeWrapUnwrap`. SOL is swept for SOL transfers and wraps, and the transferred
+    /// token for SPL transfers. The balance account has to have its deposit addresses enabled.
+    FinalizeWithDepositSweep {
+        deposit_address_indices: Vec<u8>,
+        finalize: Vec<u8>,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[signer]` The rent return account
+    ///
+    /// Packs the wallet's signers, address book entries and dapp book entries into their lowest
+    /// slots, so that ops scan fewer empty slots after many removals. What is approved is the
+    /// remap of slots the wallet's state calls for when the op is started; since any config
+    /// change makes the op stale, it is the remap made when it is finalized.
+    InitCompactWallet {
+        fee_amount: u64,
+        fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer, writable]` The rent return account
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[writable]` The fee account, if fee_account_guid_hash was set in the init
+    /// 5. `[]` The system program (only needed if fee_account_guid_hash was set in the init)
+    ///
+    /// Moves the entries to their new slots, along with the approvers, allowed destinations
+    /// and everything else kept by slot, and logs the remap.
+    FinalizeCompactWallet {},
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[signer]` The rent return account
+    ///
+    /// Registers the insurer or auditor key that attests to the wallet's custody insurance
+    /// coverage, replacing the one registered before. The attestation made so far is kept.
+    InitInsuranceAttestorUpdate {
+        fee_amount: u64,
+        fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+        attestor: Pubkey,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The wallet account
+    /// 2. `[writable]` The insurance attestation account (PDA of the wallet)
+    /// 3. `[signer, writable]` The rent return account, which funds the insurance attestation
+    ///    account the first time an attestor is registered
+    /// 4. `[]` The sysvar clock account
+    /// 5. `[]` The system program
+    /// 6. `[writable]` The fee account, if fee_account_guid_hash was set in the init
+    FinalizeInsuranceAttestorUpdate { attestor: Pubkey },
+
+    /// 0. `[writable]` The insurance attestation account
+    /// 1. `[signer]` The wallet's registered attestor
+    ///
+    /// Records the hash of the statement the attestor signed off-chain and when the coverage it
+    /// attests to expires, at most `InsuranceAttestation::MAX_COVERAGE_PERIOD` from now.
+    SubmitInsuranceAttestation {
+        statement_hash: Hash,
+        coverage_expires_at: i64,
+    },
+
+    /// 0. `[writable]` The insurance attestation account
+    /// 1. `[writable]` The wallet's event log account
+    ///
+    /// Appends an `InsuranceAttestationLapsed` event to the wallet's event log if its coverage
+    /// has expired since it was last attested, once per lapse. Anyone can submit it.
+    CheckInsuranceAttestation {},
+
+    /// 0. `[writable]` The wallet account
+    /// 1. `[signer]` The transaction assistant account
+    /// 2. `[signer, writable]` The rent return account, which pays for the wallet's
+    ///    registration
+    /// 3. `[signer]` The creator account
+    /// 4. `[]` The program parameters account, bounding the signers and approval timeouts the
+    ///    config can set
+    /// 5. `[writable]` The wallet registration account (PDA of the wallet GUID hash)
+    /// 6. `[]` The system program
+    ///
+    /// Sets up and registers a wallet like `InitWallet`, but leaves it being provisioned: its
+    /// creator can replace its config with `UpdateProvisioningWallet` without approvals, and no
+    /// ops can be started against it until it is activated with `ActivateWallet`.
+    InitProvisioningWallet {
+        wallet_guid_hash: WalletGuidHash,
+        initial_config: InitialWalletConfig,
+    },
+
+    /// 0. `[writable]` The wallet account
+    /// 1. `[signer]` The creator account
+    /// 2. `[]` The program parameters account, bounding the signers and approval timeouts the
+    ///    config can set
+    ///
+    /// Replaces the signers and config policy of a wallet being provisioned with the given
+    /// config, as though the wallet had been initialized with it.
+    UpdateProvisioningWallet { config: InitialWalletConfig },
+
+    /// 0. `[writable]` The wallet account
+    /// 1. `[signer]` The wallet's config approvers, one account each
+    ///
+    /// Ends the provisioning of a wallet, putting its config under its config policy for good.
+    /// Every one of the wallet's config approvers has to sign.
+    ActivateWallet {},
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[signer]` The rent return account
+    ///
+    /// Sets the mints besides wrapped SOL that balance accounts can wrap into, each with the
+    /// adapter program that wraps and unwraps it.
+    InitWrapperBookUpdate {
+        fee_amount: u64,
+        fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+        wrapper_book: WrapperBook,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[signer, writable]` The rent return account
+    /// 3. `[]` The sysvar clock account
+    /// 4. `[writable]` The fee account, if fee_account_guid_hash was set in the init
+    /// 5. `[]` The system program (only needed if fee_account_guid_hash was set in the init)
+    FinalizeWrapperBookUpdate { wrapper_book: WrapperBook },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[writable]` The wallet account
+    /// 2. `[]` The balance account
+    /// 3. `[]` The balance account's associated token account of the wrapper mint
+    /// 4. `[signer]` The initiator account (either the transaction assistant or an approver)
+    /// 5. `[]` The sysvar clock account
+    /// 6. `[signer]` The rent return account
+    ///
+    /// Wraps lamports of a balance account into a mint in the wallet's wrapper book, or unwraps
+    /// its tokens back into lamports, through the mint's adapter program. Unwrapping all is
+    /// only supported for wrapped SOL, with `InitWrapUnwrap`.
+    InitAdapterWrapUnwrap {
+        fee_amount: u64,
+        fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+        account_guid_hash: BalanceAccountGuidHash,
+        wrapper_mint: Pubkey,
+        amount: u64,
+        direction: WrapDirection,
+    },
+
+    /// 0. `[writable]` The multisig operation account
+    /// 1. `[]` The wallet account
+    /// 2. `[writable]` The balance account
+    /// 3. `[signer, writable]` The rent return account
+    /// 4. `[]` The sysvar clock account
+    /// 5. `[writable]` The balance account's associated token account of the wrapper mint,
+    ///    which has to exist
+    /// 6. `[writable]` The wrapper mint
+    /// 7. `[]` The wrapper mint's adapter program
+    /// 8. `[writable]` The fee account, if fee_account_guid_hash was set in the init
+    /// 9. Any number of adapter accounts: those of the mint's wrapper book entry, in order,
+    ///    passed on to it
+    ///
+    /// The adapter is invoked with the balance account as signer, and may not take more
+    /// lamports (to wrap) or tokens (to unwrap) from it than the op's amount, nor anything from
+    /// the other token accounts of the balance account passed to it, nor change who has
+    /// authority over them.
+    FinalizeAdapterWrapUnwrap {
+        account_guid_hash: BalanceAccountGuidHash,
+        wrapper_mint: Pubkey,
+        amount: u64,
+        direction: WrapDirection,
+    },
+
+    /// 0. `[]` The wallet account
+    /// 1. `[]` The sysvar clock account
+    /// 2. `[signer]` The config approvers authorizing the migration, approver_count of them
+    /// 3. `[writable]` Any number of the wallet's pending multisig operation accounts, after
+    ///    the approvers
+    ///
+    /// Hands the disposition records of signer keys replaced with an `InitUpdateSigner` of
+    /// the `Replace` slot update type on to the keys that replaced them, once those are active,
+    /// so that rotating a key doesn't strand the approvals it gave ops still in flight, and the
+    /// retired key can no longer register dispositions on them. At least the config approval
+    /// quorum of active config approvers have to sign. Ops that are no longer pending are left
+    /// as they are, and the others are settled against their migrated dispositions.
+    MigratePendingApprovals { approver_count: u8 },
+
+    /// 0. `[writable]` The wallet account
+    /// 1. `[signer]` The transaction assistant account
+    /// 2. `[signer, writable]` The rent return account, which also funds the balance accounts
+    /// 3. `[]` The system program
+    /// 4+. `[writable]` The balance accounts, in the order they are given in
+    /// n. `[writable]` The wallet registration account, after the balance accounts
+    ///
+    /// Sets up and registers a wallet like `InitWallet`, then creates the balance accounts without going
+    /// through approvals and funds each with its lamports. Only built with the `demo` feature,
+    /// which can't be combined with `mainnet`.
+    #[cfg(feature = "demo")]
+    InitDemoWallet {
+        wallet_guid_hash: WalletGuidHash,
+        initial_config: InitialWalletConfig,
+        balance_accounts: Vec<DemoBalanceAccount>,
+    },
+}
+
+/// Which steps of a transfer's finalize an instruction takes. `FinalizeTransfer` verifies the
+/// transfer's dispositions and executes it at once, while `VerifyTransfer` and
+/// `ExecuteTransfer` take one step each, so that an execution which fails can be retried
+/// without approving the transfer again.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TransferFinalizeStep {
+    VerifyAndExecute,
+    Verify,
+    Execute,
+}
+
+impl ProgramInstruction {
+    /// Serialize a ProgramInstruction to a byte vector.
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(size_of::<Self>());
+        match self {
+            &ProgramInstruction::InitWallet {
+                wallet_guid_hash,
+                ref initial_config,
+            } => {
+                let mut initial_config_bytes: Vec<u8> = Vec::new();
+                initial_config.pack(&mut initial_config_bytes);
+                buf.push(TAG_INIT_WALLET);
+                buf.extend_from_slice(wallet_guid_hash.to_bytes());
+                buf.extend_from_slice(&initial_config_bytes);
+            }
+            &ProgramInstruction::InitBalanceAccountCreation {
+                fee_amount,
+                fee_account_guid_hash,
+                ref account_guid_hash,
+                ref creation_params,
+            } => {
+                let mut update_bytes: Vec<u8> = Vec::new();
+                creation_params.pack(&mut update_bytes);
+                buf.push(TAG_INIT_BALANCE_ACCOUNT_CREATION);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                buf.extend_from_slice(&update_bytes);
+            }
+            &ProgramInstruction::FinalizeBalanceAccountCreation {
+                ref account_guid_hash,
+                ref creation_params,
+            } => {
+                let mut update_bytes: Vec<u8> = Vec::new();
+                creation_params.pack(&mut update_bytes);
+                buf.push(TAG_FINALIZE_BALANCE_ACCOUNT_CREATION);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                buf.extend_from_slice(&update_bytes);
+            }
+            &ProgramInstruction::InitTransfer {
+                fee_amount,
+                fee_account_guid_hash,
+                ref account_guid_hash,
+                ref amount,
+                ref destination_name_hash,
+                destination_acknowledgement_required,
+                deduct_fee_from_amount,
+                allow_destination_ata_creation,
+                ref memo_hash,
+                ref destination_proof,
+            } => {
+                buf.push(TAG_INIT_TRANSFER);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf.extend_from_slice(destination_name_hash.to_bytes());
+                buf.push(destination_acknowledgement_required as u8);
+                buf.push(deduct_fee_from_amount as u8);
+                buf.push(allow_destination_ata_creation as u8);
+                append_optional_hash(memo_hash, &mut buf);
+                if let Some(proof) = destination_proof {
+                    append_merkle_proof(proof, &mut buf);
+                }
+            }
+            &ProgramInstruction::FinalizeTransfer {
+                ref account_guid_hash,
+                ref amount,
+                ref token_mint,
+                destination_acknowledgement_required,
+                deduct_fee_from_amount,
+                allow_destination_ata_creation,
+                ref memo,
+            } => {
+                buf.push(TAG_FINALIZE_TRANSFER);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf.extend_from_slice(&token_mint.to_bytes());
+                buf.push(destination_acknowledgement_required as u8);
+                buf.push(deduct_fee_from_amount as u8);
+                buf.push(allow_destination_ata_creation as u8);
+                append_optional_memo(memo, &mut buf);
+            }
+            &ProgramInstruction::SetApprovalDisposition {
+                ref disposition,
+                ref params_hash,
+                ref reason,
+            } => {
+                buf.push(TAG_SET_APPROVAL_DISPOSITION);
+                buf.push(disposition.to_u8());
+                buf.extend_from_slice(params_hash.as_ref());
+                if let Some(reason) = reason {
+                    buf.push(reason.code);
+                    append_optional_hash(&reason.hash, &mut buf);
+                }
+            }
+            &ProgramInstruction::InitWrapUnwrap {
+                fee_amount,
+                fee_account_guid_hash,
+                ref account_guid_hash,
+                ref amount,
+                ref direction,
+            } => {
+                buf.push(TAG_INIT_WRAP_UNWRAP);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                buf.extend_from_slice(&account_guid_hash.to_bytes());
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf.push(direction.to_u8());
+            }
+            &ProgramInstruction::FinalizeWrapUnwrap {
+                ref account_guid_hash,
+                ref amount,
+                ref direction,
+            } => {
+                buf.push(TAG_FINALIZE_WRAP_UNWRAP);
+                buf.extend_from_slice(&account_guid_hash.to_bytes());
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf.push(direction.to_u8());
+            }
+            &ProgramInstruction::InitUpdateSigner {
+                fee_amount,
+                fee_account_guid_hash,
+                ref slot_update_type,
+                ref slot_id,
+                ref signer,
+            } => {
+                buf.push(TAG_INIT_UPDATE_SIGNER);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                buf.push(slot_update_type.to_u8());
+                buf.push(slot_id.value as u8);
+                append_signer(signer, &mut buf);
+            }
+            &ProgramInstruction::FinalizeUpdateSigner {
+                ref slot_update_type,
+                ref slot_id,
+                ref signer,
+            } => {
+                buf.push(TAG_FINALIZE_UPDATE_SIGNER);
+                buf.push(slot_update_type.to_u8());
+                buf.push(slot_id.value as u8);
+                append_signer(signer, &mut buf);
+            }
+            &ProgramInstruction::InitWalletConfigPolicyUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                ref update,
+            } => {
+                let mut update_bytes: Vec<u8> = Vec::new();
+                update.pack(&mut update_bytes);
+                buf.push(TAG_INIT_WALLET_CONFIG_POLICY_UPDATE);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                buf.extend_from_slice(&update_bytes);
+            }
+            &ProgramInstruction::FinalizeWalletConfigPolicyUpdate { ref update } => {
+                let mut update_bytes: Vec<u8> = Vec::new();
+                update.pack(&mut update_bytes);
+                buf.push(TAG_FINALIZE_WALLET_CONFIG_POLICY_UPDATE);
+                buf.extend_from_slice(&update_bytes);
+            }
+            &ProgramInstruction::InitDAppTransaction {
+                fee_amount,
+                fee_account_guid_hash,
+                ref account_guid_hash,
+                ref dapp,
+                instruction_count,
+            } => {
+                buf.push(TAG_INIT_DAPP_TRANSACTION);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                buf.extend_from_slice(&account_guid_hash.to_bytes());
+                let mut buf2 = vec![0; DAppBookEntry::LEN];
+                dapp.pack_into_slice(buf2.as_mut_slice());
+                buf.extend_from_slice(&buf2[..]);
+                buf.put_u8(instruction_count);
+            }
+            &ProgramInstruction::FinalizeDAppTransaction {
+                ref account_guid_hash,
+                ref params_hash,
+            } => {
+                buf.push(TAG_FINALIZE_DAPP_TRANSACTION);
+                buf.extend_from_slice(&account_guid_hash.to_bytes());
+                buf.extend_from_slice(&params_hash.to_bytes());
+            }
+            &ProgramInstruction::InitAccountSettingsUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                ref account_guid_hash,
+                ref whitelist_enabled,
+                ref dapps_enabled,
+                ref archived,
+                ref system_owned_destinations_only,
+            } => {
+                buf.push(TAG_INIT_ACCOUNT_SETTINGS_UPDATE);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                buf.extend_from_slice(&account_guid_hash.to_bytes());
+                pack_option(whitelist_enabled.as_ref(), &mut buf);
+                pack_option(dapps_enabled.as_ref(), &mut buf);
+                pack_option(archived.as_ref(), &mut buf);
+                pack_option(system_owned_destinations_only.as_ref(), &mut buf);
+            }
+            &ProgramInstruction::FinalizeAccountSettingsUpdate {
+                ref account_guid_hash,
+                ref whitelist_enabled,
+                ref dapps_enabled,
+                ref archived,
+                ref system_owned_destinations_only,
+            } => {
+                buf.push(TAG_FINALIZE_ACCOUNT_SETTINGS_UPDATE);
+                buf.extend_from_slice(&account_guid_hash.to_bytes());
+                pack_option(whitelist_enabled.as_ref(), &mut buf);
+                pack_option(dapps_enabled.as_ref(), &mut buf);
+                pack_option(archived.as_ref(), &mut buf);
+                pack_option(system_owned_destinations_only.as_ref(), &mut buf);
+            }
+            &ProgramInstruction::InitDAppBookUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                ref update,
+            } => {
+                buf.push(TAG_INIT_DAPP_BOOK_UPDATE);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                let mut update_bytes: Vec<u8> = Vec::new();
+                update.pack(&mut update_bytes);
+                buf.extend_from_slice(&update_bytes);
+            }
+            &ProgramInstruction::FinalizeDAppBookUpdate { ref update } => {
+                buf.push(TAG_FINALIZE_DAPP_BOOK_UPDATE);
+                let mut update_bytes: Vec<u8> = Vec::new();
+                update.pack(&mut update_bytes);
+                buf.extend_from_slice(&update_bytes);
+            }
+            &ProgramInstruction::InitAddressBookUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                ref update,
+            } => {
+                let mut update_bytes: Vec<u8> = Vec::new();
+                update.pack(&mut update_bytes);
+                buf.push(TAG_INIT_ADDRESS_BOOK_UPDATE);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                buf.extend_from_slice(&update_bytes);
+            }
+            &ProgramInstruction::FinalizeAddressBookUpdate { ref update } => {
+                let mut update_bytes: Vec<u8> = Vec::new();
+                update.pack(&mut update_bytes);
+                buf.push(TAG_FINALIZE_ADDRESS_BOOK_UPDATE);
+                buf.extend_from_slice(&update_bytes);
+            }
+            &ProgramInstruction::InitBalanceAccountNameUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                ref account_guid_hash,
+                ref account_name_hash,
+            } => {
+                buf.push(TAG_INIT_BALANCE_ACCOUNT_NAME_UPDATE);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                buf.extend_from_slice(account_name_hash.to_bytes());
+            }
+            &ProgramInstruction::FinalizeBalanceAccountNameUpdate {
+                ref account_guid_hash,
+                ref account_name_hash,
+            } => {
+                buf.push(TAG_FINALIZE_BALANCE_ACCOUNT_NAME_UPDATE);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                buf.extend_from_slice(account_name_hash.to_bytes());
+            }
+            &ProgramInstruction::InitBalanceAccountPolicyUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                ref account_guid_hash,
+                ref update,
+            } => {
+                let mut update_bytes: Vec<u8> = Vec::new();
+                update.pack(&mut update_bytes);
+                buf.push(TAG_INIT_BALANCE_ACCOUNT_POLICY_UPDATE);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                buf.extend_from_slice(&update_bytes);
+            }
+            &ProgramInstruction::FinalizeBalanceAccountPolicyUpdate {
+                ref account_guid_hash,
+                ref update,
+            } => {
+                let mut update_bytes: Vec<u8> = Vec::new();
+                update.pack(&mut update_bytes);
+                buf.push(TAG_FINALIZE_BALANCE_ACCOUNT_POLICY_UPDATE);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                buf.extend_from_slice(&update_bytes);
+            }
+            &ProgramInstruction::SupplyDAppTransactionInstructions {
+                ref instructions,
+                starting_index,
+            } => {
+                pack_supply_dapp_transaction_instructions(starting_index, instructions, &mut buf);
+            }
+            &ProgramInstruction::Migrate {} => {
+                buf.push(TAG_MIGRATE);
+            }
+            &ProgramInstruction::Cleanup {} => {
+                buf.push(TAG_CLEANUP);
+            }
+            &ProgramInstruction::InitBalanceAccountAddressWhitelistUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                ref account_guid_hash,
+                ref update,
+            } => {
+                let mut update_bytes: Vec<u8> = Vec::new();
+                update.pack(&mut update_bytes);
+                buf.push(TAG_INIT_BALANCE_ACCOUNT_ADDRESS_WHITELIST_UPDATE);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                buf.extend_from_slice(&update_bytes);
+            }
+            &ProgramInstruction::FinalizeBalanceAccountAddressWhitelistUpdate {
+                ref account_guid_hash,
+                ref update,
+            } => {
+                let mut update_bytes: Vec<u8> = Vec::new();
+                update.pack(&mut update_bytes);
+                buf.push(TAG_FINALIZE_BALANCE_ACCOUNT_ADDRESS_WHITELIST_UPDATE);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                buf.extend_from_slice(&update_bytes);
+            }
+            &ProgramInstruction::InitSignData {
+                fee_amount,
+                fee_account_guid_hash,
+                ref data,
+            } => {
+                buf.push(TAG_INIT_SIGN_DATA);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                buf.put_u16_le(data.len().as_u16());
+                buf.extend_from_slice(data);
+            }
+            &ProgramInstruction::FinalizeSignData { ref data } => {
+                buf.push(TAG_FINALIZE_SIGN_DATA);
+                buf.put_u16_le(data.len().as_u16());
+                buf.extend_from_slice(data);
+            }
+            &ProgramInstruction::AcknowledgeInternalTransfer {
+                ref account_guid_hash,
+                params_hash,
+            } => {
+                buf.push(TAG_ACKNOWLEDGE_INTERNAL_TRANSFER);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                buf.extend_from_slice(params_hash.as_ref());
+            }
+            ProgramInstruction::AddPendingAddressBookEntries { entries } => {
+                buf.push(TAG_ADD_PENDING_ADDRESS_BOOK_ENTRIES);
+                append_address_book_entries(entries, &mut buf);
+            }
+            ProgramInstruction::RatifyPendingAddressBookEntries { entries } => {
+                buf.push(TAG_RATIFY_PENDING_ADDRESS_BOOK_ENTRIES);
+                append_address_book_entries(entries, &mut buf);
+            }
+            &ProgramInstruction::InitVoteRewardsWithdrawal {
+                fee_amount,
+                fee_account_guid_hash,
+                ref account_guid_hash,
+                amount,
+            } => {
+                buf.push(TAG_INIT_VOTE_REWARDS_WITHDRAWAL);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                buf.put_u64_le(amount);
+            }
+            &ProgramInstruction::FinalizeVoteRewardsWithdrawal {
+                ref account_guid_hash,
+                amount,
+            } => {
+                buf.push(TAG_FINALIZE_VOTE_REWARDS_WITHDRAWAL);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                buf.put_u64_le(amount);
+            }
+            &ProgramInstruction::InitUpdateAssistant {
+                fee_amount,
+                fee_account_guid_hash,
+                ref slot_update_type,
+                ref slot_id,
+                ref assistant,
+            } => {
+                buf.push(TAG_INIT_UPDATE_ASSISTANT);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                buf.push(slot_update_type.to_u8());
+                buf.push(slot_id.value as u8);
+                append_assistant(assistant, &mut buf);
+            }
+            ProgramInstruction::FinalizeUpdateAssistant {
+                slot_update_type,
+                slot_id,
+                assistant,
+            } => {
+                buf.push(TAG_FINALIZE_UPDATE_ASSISTANT);
+                buf.push(slot_update_type.to_u8());
+                buf.push(slot_id.value as u8);
+                append_assistant(assistant, &mut buf);
+            }
+            &ProgramInstruction::InitRewardSplitPolicyUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                ref account_guid_hash,
+                ref splits,
+            } => {
+                buf.push(TAG_INIT_REWARD_SPLIT_POLICY_UPDATE);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                append_reward_splits(splits, &mut buf);
+            }
+            ProgramInstruction::FinalizeRewardSplitPolicyUpdate {
+                account_guid_hash,
+                splits,
+            } => {
+                buf.push(TAG_FINALIZE_REWARD_SPLIT_POLICY_UPDATE);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                append_reward_splits(splits, &mut buf);
+            }
+            ProgramInstruction::RemoveDeprecatedAddressBookEntries { entries } => {
+                buf.push(TAG_REMOVE_DEPRECATED_ADDRESS_BOOK_ENTRIES);
+                append_address_book_entries(entries, &mut buf);
+            }
+            ProgramInstruction::ActivateWalletConfigPolicy {} => {
+                buf.push(TAG_ACTIVATE_WALLET_CONFIG_POLICY);
+            }
+            ProgramInstruction::CancelTransfer {
+                account_guid_hash,
+                amount,
+                token_mint,
+                destination_acknowledgement_required,
+                deduct_fee_from_amount,
+                allow_destination_ata_creation,
+                memo,
+            } => {
+                buf.push(TAG_CANCEL_TRANSFER);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf.extend_from_slice(&token_mint.to_bytes());
+                buf.push(*destination_acknowledgement_required as u8);
+                buf.push(*deduct_fee_from_amount as u8);
+                buf.push(*allow_destination_ata_creation as u8);
+                append_optional_memo(memo, &mut buf);
+            }
+            ProgramInstruction::VerifyTransfer {
+                account_guid_hash,
+                amount,
+                token_mint,
+                destination_acknowledgement_required,
+                deduct_fee_from_amount,
+                allow_destination_ata_creation,
+                memo,
+            } => {
+                buf.push(TAG_VERIFY_TRANSFER);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf.extend_from_slice(&token_mint.to_bytes());
+                buf.push(*destination_acknowledgement_required as u8);
+                buf.push(*deduct_fee_from_amount as u8);
+                buf.push(*allow_destination_ata_creation as u8);
+                append_optional_memo(memo, &mut buf);
+            }
+            ProgramInstruction::ExecuteTransfer {
+                account_guid_hash,
+                amount,
+                token_mint,
+                destination_acknowledgement_required,
+                deduct_fee_from_amount,
+                allow_destination_ata_creation,
+                memo,
+            } => {
+                buf.push(TAG_EXECUTE_TRANSFER);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf.extend_from_slice(&token_mint.to_bytes());
+                buf.push(*destination_acknowledgement_required as u8);
+                buf.push(*deduct_fee_from_amount as u8);
+                buf.push(*allow_destination_ata_creation as u8);
+                append_optional_memo(memo, &mut buf);
+            }
+            &ProgramInstruction::InitFinalizeSubmitterPolicyUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                ref policy,
+            } => {
+                buf.push(TAG_INIT_FINALIZE_SUBMITTER_POLICY_UPDATE);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                append_finalize_submitter_policy(policy, &mut buf);
+            }
+            ProgramInstruction::FinalizeFinalizeSubmitterPolicyUpdate { policy } => {
+                buf.push(TAG_FINALIZE_FINALIZE_SUBMITTER_POLICY_UPDATE);
+                append_finalize_submitter_policy(policy, &mut buf);
+            }
+            &ProgramInstruction::InitMintTo {
+                fee_amount,
+                fee_account_guid_hash,
+                ref account_guid_hash,
+                amount,
+                ref destination_name_hash,
+            } => {
+                buf.push(TAG_INIT_MINT_TO);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                buf.put_u64_le(amount);
+                buf.extend_from_slice(destination_name_hash.to_bytes());
+            }
+            &ProgramInstruction::FinalizeMintTo {
+                ref account_guid_hash,
+                amount,
+            } => {
+                buf.push(TAG_FINALIZE_MINT_TO);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                buf.put_u64_le(amount);
+            }
+            &ProgramInstruction::InitSetMintAuthority {
+                fee_amount,
+                fee_account_guid_hash,
+                ref account_guid_hash,
+                authority_type,
+                ref new_authority,
+            } => {
+                buf.push(TAG_INIT_SET_MINT_AUTHORITY);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                buf.push(authority_type.to_u8());
+                append_optional_pubkey(new_authority, &mut buf);
+            }
+            &ProgramInstruction::FinalizeSetMintAuthority {
+                ref account_guid_hash,
+                authority_type,
+                ref new_authority,
+            } => {
+                buf.push(TAG_FINALIZE_SET_MINT_AUTHORITY);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                buf.push(authority_type.to_u8());
+                append_optional_pubkey(new_authority, &mut buf);
+            }
+            ProgramInstruction::SimulateWalletConfigPolicyUpdate { update } => {
                 let mut update_bytes: Vec<u8> = Vec::new();
                 update.pack(&mut update_bytes);
-                buf.push(TAG_FINALIZE_ADDRESS_BOOK_UPDATE);
+                buf.push(TAG_SIMULATE_WALLET_CONFIG_POLICY_UPDATE);
+                buf.extend_from_slice(&update_bytes);
+            }
+            ProgramInstruction::SimulateBalanceAccountPolicyUpdate {
+                account_guid_hash,
+                update,
+            } => {
+                let mut update_bytes: Vec<u8> = Vec::new();
+                update.pack(&mut update_bytes);
+                buf.push(TAG_SIMULATE_BALANCE_ACCOUNT_POLICY_UPDATE);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
                 buf.extend_from_slice(&update_bytes);
             }
-            &ProgramInstruction::InitBalanceAccountNameUpdate {
+            ProgramInstruction::SimulateConfigUpdate { update } => {
+                buf.push(TAG_SIMULATE_CONFIG_UPDATE);
+                append_config_update(update, &mut buf);
+            }
+            &ProgramInstruction::InitPaymentChannel {
+                fee_amount,
+                fee_account_guid_hash,
+                ref account_guid_hash,
+                ref destination_name_hash,
+                ref token_mint,
+                allowance,
+                ref duration,
+            } => {
+                buf.push(TAG_INIT_PAYMENT_CHANNEL);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                buf.extend_from_slice(destination_name_hash.to_bytes());
+                buf.extend_from_slice(&token_mint.to_bytes());
+                buf.put_u64_le(allowance);
+                append_duration(duration, &mut buf);
+            }
+            &ProgramInstruction::FinalizePaymentChannel {
+                ref account_guid_hash,
+                ref token_mint,
+                allowance,
+                ref duration,
+            } => {
+                buf.push(TAG_FINALIZE_PAYMENT_CHANNEL);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                buf.extend_from_slice(&token_mint.to_bytes());
+                buf.put_u64_le(allowance);
+                append_duration(duration, &mut buf);
+            }
+            &ProgramInstruction::ChannelPayment { amount } => {
+                buf.push(TAG_CHANNEL_PAYMENT);
+                buf.put_u64_le(amount);
+            }
+            &ProgramInstruction::InitOpBundle {} => {
+                buf.push(TAG_INIT_OP_BUNDLE);
+            }
+            ProgramInstruction::ApproveOpBundle { bundle_hash } => {
+                buf.push(TAG_APPROVE_OP_BUNDLE);
+                buf.extend_from_slice(bundle_hash.as_ref());
+            }
+            &ProgramInstruction::ApplyOpBundleApproval {} => {
+                buf.push(TAG_APPLY_OP_BUNDLE_APPROVAL);
+            }
+            &ProgramInstruction::CloseOpBundle {} => {
+                buf.push(TAG_CLOSE_OP_BUNDLE);
+            }
+            &ProgramInstruction::InitLookupTableUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                ref lookup_table,
+                ref new_addresses,
+            } => {
+                buf.push(TAG_INIT_LOOKUP_TABLE_UPDATE);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                append_optional_pubkey(lookup_table, &mut buf);
+                append_pubkeys(new_addresses, &mut buf);
+            }
+            &ProgramInstruction::FinalizeLookupTableUpdate {
+                ref lookup_table,
+                ref new_addresses,
+                recent_slot,
+            } => {
+                buf.push(TAG_FINALIZE_LOOKUP_TABLE_UPDATE);
+                append_optional_pubkey(lookup_table, &mut buf);
+                append_pubkeys(new_addresses, &mut buf);
+                buf.put_u64_le(recent_slot);
+            }
+            &ProgramInstruction::InitAddressBookImport {
+                fee_amount,
+                fee_account_guid_hash,
+                ref entries,
+                ref balance_accounts,
+            } => {
+                buf.push(TAG_INIT_ADDRESS_BOOK_IMPORT);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                buf.push(entries.len() as u8);
+                for (source_slot_id, slot_id) in entries {
+                    buf.push(source_slot_id.value as u8);
+                    buf.push(slot_id.value as u8);
+                }
+                buf.push(balance_accounts.len() as u8);
+                for (source_guid_hash, guid_hash) in balance_accounts {
+                    buf.extend_from_slice(source_guid_hash.to_bytes());
+                    buf.extend_from_slice(guid_hash.to_bytes());
+                }
+            }
+            ProgramInstruction::FinalizeAddressBookImport { import } => {
+                buf.push(TAG_FINALIZE_ADDRESS_BOOK_IMPORT);
+                import.pack(&mut buf);
+            }
+            &ProgramInstruction::InitCommittedTransfer {
+                fee_amount,
+                fee_account_guid_hash,
+                ref account_guid_hash,
+                amount,
+                ref destination_commitment,
+            } => {
+                buf.push(TAG_INIT_COMMITTED_TRANSFER);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                buf.put_u64_le(amount);
+                buf.extend_from_slice(destination_commitment.as_ref());
+            }
+            &ProgramInstruction::FinalizeCommittedTransfer {
+                ref account_guid_hash,
+                amount,
+                ref destination_commitment,
+                ref destination_name_hash,
+                ref salt,
+            } => {
+                buf.push(TAG_FINALIZE_COMMITTED_TRANSFER);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                buf.put_u64_le(amount);
+                buf.extend_from_slice(destination_commitment.as_ref());
+                buf.extend_from_slice(destination_name_hash.to_bytes());
+                buf.extend_from_slice(salt.as_ref());
+            }
+            &ProgramInstruction::InitCompliancePolicyUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                ref policy,
+            } => {
+                buf.push(TAG_INIT_COMPLIANCE_POLICY_UPDATE);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                append_compliance_policy(policy, &mut buf);
+            }
+            ProgramInstruction::FinalizeCompliancePolicyUpdate { policy } => {
+                buf.push(TAG_FINALIZE_COMPLIANCE_POLICY_UPDATE);
+                append_compliance_policy(policy, &mut buf);
+            }
+            ProgramInstruction::ComplianceApprove { params_hash } => {
+                buf.push(TAG_COMPLIANCE_APPROVE);
+                buf.extend_from_slice(params_hash.as_ref());
+            }
+            ProgramInstruction::GetAllowedDestinations {
+                account_guid_hash,
+                start_slot_id,
+            } => {
+                buf.push(TAG_GET_ALLOWED_DESTINATIONS);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                buf.push(start_slot_id.value as u8);
+            }
+            ProgramInstruction::InitWithSignature { init } => {
+                buf.push(TAG_INIT_WITH_SIGNATURE);
+                buf.extend_from_slice(init);
+            }
+            &ProgramInstruction::InitWalletReplicationUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                ref replication,
+            } => {
+                buf.push(TAG_INIT_WALLET_REPLICATION_UPDATE);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                append_wallet_replication(replication, &mut buf);
+            }
+            ProgramInstruction::FinalizeWalletReplicationUpdate { replication } => {
+                buf.push(TAG_FINALIZE_WALLET_REPLICATION_UPDATE);
+                append_wallet_replication(replication, &mut buf);
+            }
+            ProgramInstruction::ApplyMirroredUpdate { update } => {
+                buf.push(TAG_APPLY_MIRRORED_UPDATE);
+                append_mirrored_update(update, &mut buf);
+            }
+            &ProgramInstruction::InitBalanceAccountDeletion {
+                fee_amount,
+                fee_account_guid_hash,
+                ref account_guid_hash,
+            } => {
+                buf.push(TAG_INIT_BALANCE_ACCOUNT_DELETION);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+            }
+            ProgramInstruction::FinalizeBalanceAccountDeletion { account_guid_hash } => {
+                buf.push(TAG_FINALIZE_BALANCE_ACCOUNT_DELETION);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+            }
+            &ProgramInstruction::InitSpendingLimitUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                ref account_guid_hash,
+                ref token_mint,
+                amount,
+            } => {
+                buf.push(TAG_INIT_SPENDING_LIMIT_UPDATE);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                buf.extend_from_slice(token_mint.as_ref());
+                buf.put_u64_le(amount);
+            }
+            &ProgramInstruction::FinalizeSpendingLimitUpdate {
+                ref account_guid_hash,
+                ref token_mint,
+                amount,
+            } => {
+                buf.push(TAG_FINALIZE_SPENDING_LIMIT_UPDATE);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                buf.extend_from_slice(token_mint.as_ref());
+                buf.put_u64_le(amount);
+            }
+            &ProgramInstruction::Nudge { idle_slots } => {
+                buf.push(TAG_NUDGE);
+                buf.put_u64_le(idle_slots);
+            }
+            ProgramInstruction::SuspendDApp { dapp } => {
+                buf.push(TAG_SUSPEND_DAPP);
+                let mut buf2 = vec![0; DAppBookEntry::LEN];
+                dapp.pack_into_slice(buf2.as_mut_slice());
+                buf.extend_from_slice(&buf2[..]);
+            }
+            &ProgramInstruction::InitPriceOracleUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                ref oracle,
+            } => {
+                buf.push(TAG_INIT_PRICE_ORACLE_UPDATE);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                append_price_oracle(oracle, &mut buf);
+            }
+            ProgramInstruction::FinalizePriceOracleUpdate { oracle } => {
+                buf.push(TAG_FINALIZE_PRICE_ORACLE_UPDATE);
+                append_price_oracle(oracle, &mut buf);
+            }
+            ProgramInstruction::RegisterTokenAccounts { account_guid_hash } => {
+                buf.push(TAG_REGISTER_TOKEN_ACCOUNTS);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+            }
+            &ProgramInstruction::InitMerkleAddressBookUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                ref account_guid_hash,
+                ref root,
+            } => {
+                buf.push(TAG_INIT_MERKLE_ADDRESS_BOOK_UPDATE);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                buf.extend_from_slice(root.as_ref());
+            }
+            ProgramInstruction::FinalizeMerkleAddressBookUpdate {
+                account_guid_hash,
+                root,
+            } => {
+                buf.push(TAG_FINALIZE_MERKLE_ADDRESS_BOOK_UPDATE);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                buf.extend_from_slice(root.as_ref());
+            }
+            ProgramInstruction::CleanupExpiredOp {} => {
+                buf.push(TAG_CLEANUP_EXPIRED_OP);
+            }
+            &ProgramInstruction::InitWithComputeHint {
+                finalize_compute_units,
+                ref init,
+            } => {
+                buf.push(TAG_INIT_WITH_COMPUTE_HINT);
+                buf.put_u32_le(finalize_compute_units);
+                buf.extend_from_slice(init);
+            }
+            ProgramInstruction::ExportWalletReadModel {} => {
+                buf.push(TAG_EXPORT_WALLET_READ_MODEL);
+            }
+            &ProgramInstruction::InitWalletFreeze {
+                fee_amount,
+                fee_account_guid_hash,
+                ref freeze,
+            } => {
+                buf.push(TAG_INIT_WALLET_FREEZE);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                append_wallet_freeze(freeze, &mut buf);
+            }
+            ProgramInstruction::FinalizeWalletFreeze { freeze } => {
+                buf.push(TAG_FINALIZE_WALLET_FREEZE);
+                append_wallet_freeze(freeze, &mut buf);
+            }
+            ProgramInstruction::EmergencyFreeze {} => {
+                buf.push(TAG_EMERGENCY_FREEZE);
+            }
+            &ProgramInstruction::InitRentReturnAllowlistUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                ref allowlist,
+            } => {
+                buf.push(TAG_INIT_RENT_RETURN_ALLOWLIST_UPDATE);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                append_pubkeys(&allowlist.entries, &mut buf);
+            }
+            ProgramInstruction::FinalizeRentReturnAllowlistUpdate { allowlist } => {
+                buf.push(TAG_FINALIZE_RENT_RETURN_ALLOWLIST_UPDATE);
+                append_pubkeys(&allowlist.entries, &mut buf);
+            }
+            &ProgramInstruction::InitFullRotation {
+                fee_amount,
+                fee_account_guid_hash,
+                ref rotation,
+            } => {
+                buf.push(TAG_INIT_FULL_ROTATION);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                rotation.pack(&mut buf);
+            }
+            ProgramInstruction::FinalizeFullRotation { rotation } => {
+                buf.push(TAG_FINALIZE_FULL_ROTATION);
+                rotation.pack(&mut buf);
+            }
+            ProgramInstruction::UpdateProgramParameters { parameters } => {
+                buf.push(TAG_UPDATE_PROGRAM_PARAMETERS);
+                let mut parameters_bytes = [0; ProgramParameters::LEN];
+                parameters.pack_into_slice(&mut parameters_bytes);
+                buf.extend_from_slice(&parameters_bytes);
+            }
+            &ProgramInstruction::InitSignerActivationDelayUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                ref delay,
+            } => {
+                buf.push(TAG_INIT_SIGNER_ACTIVATION_DELAY_UPDATE);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                append_duration(delay, &mut buf);
+            }
+            ProgramInstruction::FinalizeSignerActivationDelayUpdate { delay } => {
+                buf.push(TAG_FINALIZE_SIGNER_ACTIVATION_DELAY_UPDATE);
+                append_duration(delay, &mut buf);
+            }
+            &ProgramInstruction::InitFeePolicyUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                ref fee_policy,
+            } => {
+                buf.push(TAG_INIT_FEE_POLICY_UPDATE);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                append_fee_policy(fee_policy, &mut buf);
+            }
+            ProgramInstruction::FinalizeFeePolicyUpdate { fee_policy } => {
+                buf.push(TAG_FINALIZE_FEE_POLICY_UPDATE);
+                append_fee_policy(fee_policy, &mut buf);
+            }
+            ProgramInstruction::CleanupUncreatedBalanceAccount { account_guid_hash } => {
+                buf.push(TAG_CLEANUP_UNCREATED_BALANCE_ACCOUNT);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+            }
+            &ProgramInstruction::InitStakeWeightGovernanceUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                ref governance,
+            } => {
+                buf.push(TAG_INIT_STAKE_WEIGHT_GOVERNANCE_UPDATE);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                append_stake_weight_governance(governance, &mut buf);
+            }
+            ProgramInstruction::FinalizeStakeWeightGovernanceUpdate { governance } => {
+                buf.push(TAG_FINALIZE_STAKE_WEIGHT_GOVERNANCE_UPDATE);
+                append_stake_weight_governance(governance, &mut buf);
+            }
+            &ProgramInstruction::InitWithStakeWeights {
+                stake_account_count,
+                ref init,
+            } => {
+                buf.push(TAG_INIT_WITH_STAKE_WEIGHTS);
+                buf.push(stake_account_count);
+                buf.extend_from_slice(init);
+            }
+            ProgramInstruction::InitEventLog {} => {
+                buf.push(TAG_INIT_EVENT_LOG);
+            }
+            &ProgramInstruction::InitAddressBookRenewal {
+                fee_amount,
+                fee_account_guid_hash,
+                slot_id,
+                entry,
+                expires_at,
+            } => {
+                buf.push(TAG_INIT_ADDRESS_BOOK_RENEWAL);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                append_address_book_renewal(slot_id, &entry, expires_at, &mut buf);
+            }
+            &ProgramInstruction::FinalizeAddressBookRenewal {
+                slot_id,
+                entry,
+                expires_at,
+            } => {
+                buf.push(TAG_FINALIZE_ADDRESS_BOOK_RENEWAL);
+                append_address_book_renewal(slot_id, &entry, expires_at, &mut buf);
+            }
+            &ProgramInstruction::RegisterSessionKey {
+                session_key,
+                scope,
+                expires_at,
+            } => {
+                buf.push(TAG_REGISTER_SESSION_KEY);
+                buf.extend_from_slice(session_key.as_ref());
+                let mut scope_buf = [0; SessionKeyScope::LEN];
+                scope.pack_into_slice(&mut scope_buf);
+                buf.extend_from_slice(&scope_buf);
+                buf.extend_from_slice(&expires_at.to_le_bytes());
+            }
+            ProgramInstruction::RevokeSessionKey { session_key } => {
+                buf.push(TAG_REVOKE_SESSION_KEY);
+                buf.extend_from_slice(session_key.as_ref());
+            }
+            &ProgramInstruction::SetApprovalDispositionBySignature {
+                disposition,
+                params_hash,
+                expires_at,
+            } => {
+                buf.push(TAG_SET_APPROVAL_DISPOSITION_BY_SIGNATURE);
+                buf.push(disposition.to_u8());
+                buf.extend_from_slice(params_hash.as_ref());
+                buf.extend_from_slice(&expires_at.to_le_bytes());
+            }
+            ProgramInstruction::InitWalletStats {} => {
+                buf.push(TAG_INIT_WALLET_STATS);
+            }
+            &ProgramInstruction::InitSPLTokenAccountsCreation {
+                fee_amount,
+                fee_account_guid_hash,
+                ref payer_account_guid_hash,
+                ref account_guid_hashes,
+            } => {
+                buf.push(TAG_INIT_SPL_TOKEN_ACCOUNTS_CREATION);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                buf.extend_from_slice(payer_account_guid_hash.to_bytes());
+                pack_balance_account_guid_hash_vec(account_guid_hashes, &mut buf);
+            }
+            &ProgramInstruction::FinalizeSPLTokenAccountsCreation {
+                ref payer_account_guid_hash,
+                ref account_guid_hashes,
+                batch_size,
+            } => {
+                buf.push(TAG_FINALIZE_SPL_TOKEN_ACCOUNTS_CREATION);
+                buf.extend_from_slice(payer_account_guid_hash.to_bytes());
+                pack_balance_account_guid_hash_vec(account_guid_hashes, &mut buf);
+                buf.push(batch_size);
+            }
+            &ProgramInstruction::ContinueSPLTokenAccountsCreation {
+                ref payer_account_guid_hash,
+                ref account_guid_hashes,
+                batch_size,
+            } => {
+                buf.push(TAG_CONTINUE_SPL_TOKEN_ACCOUNTS_CREATION);
+                buf.extend_from_slice(payer_account_guid_hash.to_bytes());
+                pack_balance_account_guid_hash_vec(account_guid_hashes, &mut buf);
+                buf.push(batch_size);
+            }
+            &ProgramInstruction::InitApprovalGroupUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                ref approval_group,
+            } => {
+                buf.push(TAG_INIT_APPROVAL_GROUP_UPDATE);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                append_approval_group(approval_group, &mut buf);
+            }
+            ProgramInstruction::FinalizeApprovalGroupUpdate { approval_group } => {
+                buf.push(TAG_FINALIZE_APPROVAL_GROUP_UPDATE);
+                append_approval_group(approval_group, &mut buf);
+            }
+            &ProgramInstruction::SubmitAggregatedApproval {
+                params_hash,
+                expires_at,
+            } => {
+                buf.push(TAG_SUBMIT_AGGREGATED_APPROVAL);
+                buf.extend_from_slice(params_hash.as_ref());
+                buf.extend_from_slice(&expires_at.to_le_bytes());
+            }
+            &ProgramInstruction::InitEnableDepositAddresses {
                 fee_amount,
                 fee_account_guid_hash,
                 ref account_guid_hash,
-                ref account_name_hash,
+                enabled,
             } => {
-                buf.push(TAG_INIT_BALANCE_ACCOUNT_NAME_UPDATE);
+                buf.push(TAG_INIT_ENABLE_DEPOSIT_ADDRESSES);
                 buf.put_u64_le(fee_amount);
                 pack_option(fee_account_guid_hash.as_ref(), &mut buf);
                 buf.extend_from_slice(account_guid_hash.to_bytes());
-                buf.extend_from_slice(account_name_hash.to_bytes());
+                buf.push(enabled.to_u8());
             }
-            &ProgramInstruction::FinalizeBalanceAccountNameUpdate {
+            &ProgramInstruction::FinalizeEnableDepositAddresses {
                 ref account_guid_hash,
-                ref account_name_hash,
+                enabled,
             } => {
-                buf.push(TAG_FINALIZE_BALANCE_ACCOUNT_NAME_UPDATE);
+                buf.push(TAG_FINALIZE_ENABLE_DEPOSIT_ADDRESSES);
                 buf.extend_from_slice(account_guid_hash.to_bytes());
-                buf.extend_from_slice(account_name_hash.to_bytes());
+                buf.push(enabled.to_u8());
             }
-            &ProgramInstruction::InitBalanceAccountPolicyUpdate {
+            ProgramInstruction::FinalizeWithDepositSweep {
+                deposit_address_indices,
+                finalize,
+            } => {
+                buf.push(TAG_FINALIZE_WITH_DEPOSIT_SWEEP);
+                buf.push(deposit_address_indices.len() as u8);
+                buf.extend_from_slice(deposit_address_indices);
+                buf.extend_from_slice(finalize);
+            }
+            &ProgramInstruction::InitCompactWallet {
+                fee_amount,
+                fee_account_guid_hash,
+            } => {
+                buf.push(TAG_INIT_COMPACT_WALLET);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+            }
+            ProgramInstruction::FinalizeCompactWallet {} => {
+                buf.push(TAG_FINALIZE_COMPACT_WALLET);
+            }
+            &ProgramInstruction::InitInsuranceAttestorUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                attestor,
+            } => {
+                buf.push(TAG_INIT_INSURANCE_ATTESTOR_UPDATE);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                buf.extend_from_slice(attestor.as_ref());
+            }
+            ProgramInstruction::FinalizeInsuranceAttestorUpdate { attestor } => {
+                buf.push(TAG_FINALIZE_INSURANCE_ATTESTOR_UPDATE);
+                buf.extend_from_slice(attestor.as_ref());
+            }
+            &ProgramInstruction::SubmitInsuranceAttestation {
+                ref statement_hash,
+                coverage_expires_at,
+            } => {
+                buf.push(TAG_SUBMIT_INSURANCE_ATTESTATION);
+                buf.extend_from_slice(statement_hash.as_ref());
+                buf.extend_from_slice(&coverage_expires_at.to_le_bytes());
+            }
+            ProgramInstruction::CheckInsuranceAttestation {} => {
+                buf.push(TAG_CHECK_INSURANCE_ATTESTATION);
+            }
+            &ProgramInstruction::InitProvisioningWallet {
+                wallet_guid_hash,
+                ref initial_config,
+            } => {
+                buf.push(TAG_INIT_PROVISIONING_WALLET);
+                buf.extend_from_slice(wallet_guid_hash.to_bytes());
+                initial_config.pack(&mut buf);
+            }
+            ProgramInstruction::UpdateProvisioningWallet { config } => {
+                buf.push(TAG_UPDATE_PROVISIONING_WALLET);
+                config.pack(&mut buf);
+            }
+            ProgramInstruction::ActivateWallet {} => {
+                buf.push(TAG_ACTIVATE_WALLET);
+            }
+            &ProgramInstruction::InitWrapperBookUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                ref wrapper_book,
+            } => {
+                buf.push(TAG_INIT_WRAPPER_BOOK_UPDATE);
+                buf.put_u64_le(fee_amount);
+                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
+                wrapper_book.pack(&mut buf);
+            }
+            ProgramInstruction::FinalizeWrapperBookUpdate { wrapper_book } => {
+                buf.push(TAG_FINALIZE_WRAPPER_BOOK_UPDATE);
+                wrapper_book.pack(&mut buf);
+            }
+            &ProgramInstruction::InitAdapterWrapUnwrap {
                 fee_amount,
                 fee_account_guid_hash,
                 ref account_guid_hash,
-                ref update,
+                ref wrapper_mint,
+                amount,
+                direction,
             } => {
-                let mut update_bytes: Vec<u8> = Vec::new();
-                update.pack(&mut update_bytes);
-                buf.push(TAG_INIT_BALANCE_ACCOUNT_POLICY_UPDATE);
+                buf.push(TAG_INIT_ADAPTER_WRAP_UNWRAP);
                 buf.put_u64_le(fee_amount);
                 pack_option(fee_account_guid_hash.as_ref(), &mut buf);
                 buf.extend_from_slice(account_guid_hash.to_bytes());
-                buf.extend_from_slice(&update_bytes);
+                buf.extend_from_slice(wrapper_mint.as_ref());
+                buf.put_u64_le(amount);
+                buf.push(direction.to_u8());
+            }
+            &ProgramInstruction::FinalizeAdapterWrapUnwrap {
+                ref account_guid_hash,
+                ref wrapper_mint,
+                amount,
+                direction,
+            } => {
+                buf.push(TAG_FINALIZE_ADAPTER_WRAP_UNWRAP);
+                buf.extend_from_slice(account_guid_hash.to_bytes());
+                buf.extend_from_slice(wrapper_mint.as_ref());
+                buf.put_u64_le(amount);
+                buf.push(direction.to_u8());
+            }
+            ProgramInstruction::MigratePendingApprovals { approver_count } => {
+                buf.push(TAG_MIGRATE_PENDING_APPROVALS);
+                buf.push(*approver_count);
+            }
+            #[cfg(feature = "demo")]
+            ProgramInstruction::InitDemoWallet {
+                wallet_guid_hash,
+                initial_config,
+                balance_accounts,
+            } => {
+                buf.push(TAG_INIT_DEMO_WALLET);
+                buf.extend_from_slice(wallet_guid_hash.to_bytes());
+                buf.push(balance_accounts.len() as u8);
+                for balance_account in balance_accounts {
+                    balance_account.pack(&mut buf);
+                }
+                initial_config.pack(&mut buf);
+            }
+        }
+        buf
+    }
+
+    /// Deserialize a byte buffer to ProgramInstruction.
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        let (tag, rest) = input
+            .split_first()
+            .ok_or(ProgramError::InvalidInstructionData)?;
+
+        Ok(match *tag {
+            TAG_INIT_WALLET => Self::unpack_init_wallet_instruction(rest)?,
+
+            TAG_INIT_BALANCE_ACCOUNT_CREATION => {
+                Self::unpack_init_balance_account_creation_instruction(rest)?
+            }
+            TAG_FINALIZE_BALANCE_ACCOUNT_CREATION => {
+                Self::unpack_finalize_balance_account_creation_instruction(rest)?
+            }
+            TAG_INIT_TRANSFER => Self::unpack_init_transfer_for_approval_instruction(rest)?,
+
+            TAG_FINALIZE_TRANSFER => Self::unpack_finalize_transfer_instruction(rest)?,
+
+            TAG_SET_APPROVAL_DISPOSITION => {
+                Self::unpack_set_approval_disposition_instruction(rest)?
+            }
+            TAG_INIT_WRAP_UNWRAP => Self::unpack_init_wrap_unwrap_instruction(rest)?,
+
+            TAG_FINALIZE_WRAP_UNWRAP => Self::unpack_finalize_wrap_unwrap_instruction(rest)?,
+
+            TAG_INIT_UPDATE_SIGNER => Self::unpack_init_update_signer_instruction(rest)?,
+
+            TAG_FINALIZE_UPDATE_SIGNER => Self::unpack_finalize_update_signer_instruction(rest)?,
+
+            TAG_INIT_WALLET_CONFIG_POLICY_UPDATE => {
+                Self::unpack_init_wallet_config_policy_update_instruction(rest)?
+            }
+            TAG_FINALIZE_WALLET_CONFIG_POLICY_UPDATE => {
+                Self::unpack_finalize_wallet_config_policy_update_instruction(rest)?
+            }
+            TAG_INIT_DAPP_TRANSACTION => Self::unpack_init_dapp_transaction_instruction(rest)?,
+
+            TAG_FINALIZE_DAPP_TRANSACTION => {
+                Self::unpack_finalize_dapp_transaction_instruction(rest)?
+            }
+            TAG_INIT_ACCOUNT_SETTINGS_UPDATE => {
+                Self::unpack_init_account_settings_update_instruction(rest)?
+            }
+            TAG_FINALIZE_ACCOUNT_SETTINGS_UPDATE => {
+                Self::unpack_finalize_account_settings_update_instruction(rest)?
+            }
+            TAG_INIT_DAPP_BOOK_UPDATE => Self::unpack_init_dapp_book_update_instruction(rest)?,
+
+            TAG_FINALIZE_DAPP_BOOK_UPDATE => {
+                Self::unpack_finalize_dapp_book_update_instruction(rest)?
+            }
+            TAG_INIT_ADDRESS_BOOK_UPDATE => {
+                Self::unpack_init_address_book_update_instruction(rest)?
+            }
+            TAG_FINALIZE_ADDRESS_BOOK_UPDATE => {
+                Self::unpack_finalize_address_book_update_instruction(rest)?
+            }
+            TAG_INIT_BALANCE_ACCOUNT_NAME_UPDATE => {
+                Self::unpack_init_balance_account_name_update_instruction(rest)?
+            }
+            TAG_FINALIZE_BALANCE_ACCOUNT_NAME_UPDATE => {
+                Self::unpack_finalize_balance_account_name_update_instruction(rest)?
+            }
+            TAG_INIT_BALANCE_ACCOUNT_POLICY_UPDATE => {
+                Self::unpack_init_balance_account_policy_update_instruction(rest)?
+            }
+            TAG_FINALIZE_BALANCE_ACCOUNT_POLICY_UPDATE => {
+                Self::unpack_finalize_balance_account_policy_update_instruction(rest)?
+            }
+            TAG_SUPPLY_DAPP_INSTRUCTIONS => {
+                Self::unpack_supply_dapp_instructions_instruction(rest)?
+            }
+            TAG_MIGRATE => Self::Migrate {},
+            TAG_CLEANUP => Self::Cleanup {},
+            TAG_INIT_BALANCE_ACCOUNT_ADDRESS_WHITELIST_UPDATE => {
+                Self::unpack_init_balance_account_address_whitelist_update_instruction(rest)?
+            }
+            TAG_FINALIZE_BALANCE_ACCOUNT_ADDRESS_WHITELIST_UPDATE => {
+                Self::unpack_finalize_balance_account_address_whitelist_update_instruction(rest)?
+            }
+            TAG_INIT_SIGN_DATA => Self::unpack_init_sign_data_instruction(rest)?,
+            TAG_FINALIZE_SIGN_DATA => Self::unpack_finalize_sign_data_instruction(rest)?,
+            TAG_ACKNOWLEDGE_INTERNAL_TRANSFER => {
+                Self::unpack_acknowledge_internal_transfer_instruction(rest)?
+            }
+            TAG_ADD_PENDING_ADDRESS_BOOK_ENTRIES => Self::AddPendingAddressBookEntries {
+                entries: read_address_book_entries(&mut rest.iter())?,
+            },
+            TAG_RATIFY_PENDING_ADDRESS_BOOK_ENTRIES => Self::RatifyPendingAddressBookEntries {
+                entries: read_address_book_entries(&mut rest.iter())?,
+            },
+            TAG_INIT_VOTE_REWARDS_WITHDRAWAL => {
+                Self::unpack_init_vote_rewards_withdrawal_instruction(rest)?
+            }
+            TAG_FINALIZE_VOTE_REWARDS_WITHDRAWAL => {
+                Self::unpack_finalize_vote_rewards_withdrawal_instruction(rest)?
+            }
+            TAG_INIT_UPDATE_ASSISTANT => Self::unpack_init_update_assistant_instruction(rest)?,
+            TAG_FINALIZE_UPDATE_ASSISTANT => {
+                Self::unpack_finalize_update_assistant_instruction(rest)?
+            }
+            TAG_INIT_REWARD_SPLIT_POLICY_UPDATE => {
+                Self::unpack_init_reward_split_policy_update_instruction(rest)?
+            }
+            TAG_FINALIZE_REWARD_SPLIT_POLICY_UPDATE => {
+                Self::unpack_finalize_reward_split_policy_update_instruction(rest)?
+            }
+            TAG_REMOVE_DEPRECATED_ADDRESS_BOOK_ENTRIES => {
+                Self::RemoveDeprecatedAddressBookEntries {
+                    entries: read_address_book_entries(&mut rest.iter())?,
+                }
+            }
+            TAG_ACTIVATE_WALLET_CONFIG_POLICY => Self::ActivateWalletConfigPolicy {},
+            TAG_CANCEL_TRANSFER => match Self::unpack_finalize_transfer_instruction(rest)? {
+                Self::FinalizeTransfer {
+                    account_guid_hash,
+                    amount,
+                    token_mint,
+                    destination_acknowledgement_required,
+                    deduct_fee_from_amount,
+                    allow_destination_ata_creation,
+                    memo,
+                } => Self::CancelTransfer {
+                    account_guid_hash,
+                    amount,
+                    token_mint,
+                    destination_acknowledgement_required,
+                    deduct_fee_from_amount,
+                    allow_destination_ata_creation,
+                    memo,
+                },
+                _ => return Err(ProgramError::InvalidInstructionData),
+            },
+            TAG_VERIFY_TRANSFER => match Self::unpack_finalize_transfer_instruction(rest)? {
+                Self::FinalizeTransfer {
+                    account_guid_hash,
+                    amount,
+                    token_mint,
+                    destination_acknowledgement_required,
+                    deduct_fee_from_amount,
+                    allow_destination_ata_creation,
+                    memo,
+                } => Self::VerifyTransfer {
+                    account_guid_hash,
+                    amount,
+                    token_mint,
+                    destination_acknowledgement_required,
+                    deduct_fee_from_amount,
+                    allow_destination_ata_creation,
+                    memo,
+                },
+                _ => return Err(ProgramError::InvalidInstructionData),
+            },
+            TAG_EXECUTE_TRANSFER => match Self::unpack_finalize_transfer_instruction(rest)? {
+                Self::FinalizeTransfer {
+                    account_guid_hash,
+                    amount,
+                    token_mint,
+                    destination_acknowledgement_required,
+                    deduct_fee_from_amount,
+                    allow_destination_ata_creation,
+                    memo,
+                } => Self::ExecuteTransfer {
+                    account_guid_hash,
+                    amount,
+                    token_mint,
+                    destination_acknowledgement_required,
+                    deduct_fee_from_amount,
+                    allow_destination_ata_creation,
+                    memo,
+                },
+                _ => return Err(ProgramError::InvalidInstructionData),
+            },
+            TAG_INIT_FINALIZE_SUBMITTER_POLICY_UPDATE => {
+                Self::unpack_init_finalize_submitter_policy_update_instruction(rest)?
+            }
+            TAG_FINALIZE_FINALIZE_SUBMITTER_POLICY_UPDATE => {
+                Self::FinalizeFinalizeSubmitterPolicyUpdate {
+                    policy: read_finalize_submitter_policy(&mut rest.iter())?,
+                }
+            }
+            TAG_INIT_MINT_TO => Self::unpack_init_mint_to_instruction(rest)?,
+            TAG_FINALIZE_MINT_TO => Self::unpack_finalize_mint_to_instruction(rest)?,
+            TAG_INIT_SET_MINT_AUTHORITY => Self::unpack_init_set_mint_authority_instruction(rest)?,
+            TAG_FINALIZE_SET_MINT_AUTHORITY => {
+                Self::unpack_finalize_set_mint_authority_instruction(rest)?
+            }
+            TAG_SIMULATE_WALLET_CONFIG_POLICY_UPDATE => Self::SimulateWalletConfigPolicyUpdate {
+                update: WalletConfigPolicyUpdate::unpack(rest)?,
+            },
+            TAG_SIMULATE_BALANCE_ACCOUNT_POLICY_UPDATE => {
+                Self::unpack_simulate_balance_account_policy_update_instruction(rest)?
+            }
+            TAG_SIMULATE_CONFIG_UPDATE => Self::SimulateConfigUpdate {
+                update: unpack_config_update(rest)?,
+            },
+            TAG_INIT_PAYMENT_CHANNEL => Self::unpack_init_payment_channel_instruction(rest)?,
+            TAG_FINALIZE_PAYMENT_CHANNEL => {
+                Self::unpack_finalize_payment_channel_instruction(rest)?
+            }
+            TAG_CHANNEL_PAYMENT => Self::ChannelPayment {
+                amount: read_u64(&mut rest.iter()).ok_or(ProgramError::InvalidInstructionData)?,
+            },
+            TAG_INIT_OP_BUNDLE => Self::InitOpBundle {},
+            TAG_APPROVE_OP_BUNDLE => Self::ApproveOpBundle {
+                bundle_hash: Hash::new_from_array(
+                    rest.get(0..HASH_LEN)
+                        .and_then(|slice| slice.try_into().ok())
+                        .ok_or(ProgramError::InvalidInstructionData)?,
+                ),
+            },
+            TAG_APPLY_OP_BUNDLE_APPROVAL => Self::ApplyOpBundleApproval {},
+            TAG_CLOSE_OP_BUNDLE => Self::CloseOpBundle {},
+            TAG_INIT_LOOKUP_TABLE_UPDATE => {
+                Self::unpack_init_lookup_table_update_instruction(rest)?
+            }
+            TAG_FINALIZE_LOOKUP_TABLE_UPDATE => {
+                Self::unpack_finalize_lookup_table_update_instruction(rest)?
+            }
+            TAG_INIT_ADDRESS_BOOK_IMPORT => {
+                Self::unpack_init_address_book_import_instruction(rest)?
+            }
+            TAG_FINALIZE_ADDRESS_BOOK_IMPORT => Self::FinalizeAddressBookImport {
+                import: AddressBookImport::unpack(rest)?,
+            },
+            TAG_INIT_COMMITTED_TRANSFER => Self::unpack_init_committed_transfer_instruction(rest)?,
+            TAG_FINALIZE_COMMITTED_TRANSFER => {
+                Self::unpack_finalize_committed_transfer_instruction(rest)?
+            }
+            TAG_INIT_COMPLIANCE_POLICY_UPDATE => {
+                Self::unpack_init_compliance_policy_update_instruction(rest)?
+            }
+            TAG_FINALIZE_COMPLIANCE_POLICY_UPDATE => Self::FinalizeCompliancePolicyUpdate {
+                policy: read_compliance_policy(&mut rest.iter())?,
+            },
+            TAG_COMPLIANCE_APPROVE => Self::ComplianceApprove {
+                params_hash: Hash::new_from_array(
+                    rest.get(0..HASH_LEN)
+                        .and_then(|slice| slice.try_into().ok())
+                        .ok_or(ProgramError::InvalidInstructionData)?,
+                ),
+            },
+            TAG_GET_ALLOWED_DESTINATIONS => {
+                let iter = &mut rest.iter();
+                Self::GetAllowedDestinations {
+                    account_guid_hash: read_account_guid_hash(iter)
+                        .ok_or(ProgramError::InvalidInstructionData)?,
+                    start_slot_id: SlotId::new(usize::from(
+                        *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?,
+                    )),
+                }
+            }
+            TAG_INIT_WITH_SIGNATURE => Self::InitWithSignature {
+                init: rest.to_vec(),
+            },
+            TAG_INIT_WALLET_REPLICATION_UPDATE => {
+                let iter = &mut rest.iter();
+                let fee_amount = read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?;
+                let fee_account_guid_hash = unpack_option::<BalanceAccountGuidHash>(iter)?;
+                Self::InitWalletReplicationUpdate {
+                    fee_amount,
+                    fee_account_guid_hash,
+                    replication: read_wallet_replication(iter)?,
+                }
+            }
+            TAG_FINALIZE_WALLET_REPLICATION_UPDATE => Self::FinalizeWalletReplicationUpdate {
+                replication: read_wallet_replication(&mut rest.iter())?,
+            },
+            TAG_APPLY_MIRRORED_UPDATE => Self::ApplyMirroredUpdate {
+                update: unpack_mirrored_update(rest)?,
+            },
+            TAG_INIT_BALANCE_ACCOUNT_DELETION => {
+                let iter = &mut rest.iter();
+                Self::InitBalanceAccountDeletion {
+                    fee_amount: read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?,
+                    fee_account_guid_hash: unpack_option::<BalanceAccountGuidHash>(iter)?,
+                    account_guid_hash: read_account_guid_hash(iter)
+                        .ok_or(ProgramError::InvalidInstructionData)?,
+                }
+            }
+            TAG_FINALIZE_BALANCE_ACCOUNT_DELETION => Self::FinalizeBalanceAccountDeletion {
+                account_guid_hash: unpack_account_guid_hash(rest)?,
+            },
+            TAG_INIT_SPENDING_LIMIT_UPDATE => {
+                let iter = &mut rest.iter();
+                Self::InitSpendingLimitUpdate {
+                    fee_amount: read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?,
+                    fee_account_guid_hash: unpack_option::<BalanceAccountGuidHash>(iter)?,
+                    account_guid_hash: read_account_guid_hash(iter)
+                        .ok_or(ProgramError::InvalidInstructionData)?,
+                    token_mint: Pubkey::new_from_array(
+                        *read_fixed_size_array(iter).ok_or(ProgramError::InvalidInstructionData)?,
+                    ),
+                    amount: read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?,
+                }
+            }
+            TAG_FINALIZE_SPENDING_LIMIT_UPDATE => {
+                let iter = &mut rest.iter();
+                Self::FinalizeSpendingLimitUpdate {
+                    account_guid_hash: read_account_guid_hash(iter)
+                        .ok_or(ProgramError::InvalidInstructionData)?,
+                    token_mint: Pubkey::new_from_array(
+                        *read_fixed_size_array(iter).ok_or(ProgramError::InvalidInstructionData)?,
+                    ),
+                    amount: read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?,
+                }
+            }
+            TAG_NUDGE => Self::Nudge {
+                idle_slots: read_u64(&mut rest.iter())
+                    .ok_or(ProgramError::InvalidInstructionData)?,
+            },
+            TAG_SUSPEND_DAPP => Self::SuspendDApp {
+                dapp: DAppBookEntry::unpack_from_slice(
+                    read_slice(&mut rest.iter(), DAppBookEntry::LEN)
+                        .ok_or(ProgramError::InvalidInstructionData)?,
+                )?,
+            },
+            TAG_INIT_PRICE_ORACLE_UPDATE => {
+                Self::unpack_init_price_oracle_update_instruction(rest)?
+            }
+            TAG_FINALIZE_PRICE_ORACLE_UPDATE => Self::FinalizePriceOracleUpdate {
+                oracle: read_price_oracle(&mut rest.iter())?,
+            },
+            TAG_REGISTER_TOKEN_ACCOUNTS => Self::RegisterTokenAccounts {
+                account_guid_hash: read_account_guid_hash(&mut rest.iter())
+                    .ok_or(ProgramError::InvalidInstructionData)?,
+            },
+            TAG_INIT_MERKLE_ADDRESS_BOOK_UPDATE => {
+                Self::unpack_init_merkle_address_book_update_instruction(rest)?
+            }
+            TAG_FINALIZE_MERKLE_ADDRESS_BOOK_UPDATE => {
+                let iter = &mut rest.iter();
+                Self::FinalizeMerkleAddressBookUpdate {
+                    account_guid_hash: read_account_guid_hash(iter)
+                        .ok_or(ProgramError::InvalidInstructionData)?,
+                    root: Hash::new_from_array(
+                        *read_fixed_size_array(iter).ok_or(ProgramError::InvalidInstructionData)?,
+                    ),
+                }
+            }
+            TAG_CLEANUP_EXPIRED_OP => Self::CleanupExpiredOp {},
+            TAG_INIT_WITH_COMPUTE_HINT => {
+                let iter = &mut rest.iter();
+                Self::InitWithComputeHint {
+                    finalize_compute_units: u32::from_le_bytes(
+                        *read_fixed_size_array(iter).ok_or(ProgramError::InvalidInstructionData)?,
+                    ),
+                    init: iter.copied().collect(),
+                }
+            }
+            TAG_EXPORT_WALLET_READ_MODEL => Self::ExportWalletReadModel {},
+            TAG_INIT_WALLET_FREEZE => Self::unpack_init_wallet_freeze_instruction(rest)?,
+            TAG_FINALIZE_WALLET_FREEZE => Self::FinalizeWalletFreeze {
+                freeze: read_wallet_freeze(&mut rest.iter())?,
+            },
+            TAG_EMERGENCY_FREEZE => Self::EmergencyFreeze {},
+            TAG_INIT_RENT_RETURN_ALLOWLIST_UPDATE => {
+                Self::unpack_init_rent_return_allowlist_update_instruction(rest)?
+            }
+            TAG_FINALIZE_RENT_RETURN_ALLOWLIST_UPDATE => Self::FinalizeRentReturnAllowlistUpdate {
+                allowlist: RentReturnAllowlist {
+                    entries: read_pubkeys(&mut rest.iter())?,
+                },
+            },
+            TAG_INIT_FULL_ROTATION => {
+                let iter = &mut rest.iter();
+                Self::InitFullRotation {
+                    fee_amount: read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?,
+                    fee_account_guid_hash: unpack_option::<BalanceAccountGuidHash>(iter)?,
+                    rotation: FullRotation::unpack(iter)?,
+                }
+            }
+            TAG_FINALIZE_FULL_ROTATION => Self::FinalizeFullRotation {
+                rotation: FullRotation::unpack(&mut rest.iter())?,
+            },
+            TAG_UPDATE_PROGRAM_PARAMETERS => Self::UpdateProgramParameters {
+                parameters: ProgramParameters::unpack_from_slice(
+                    read_slice(&mut rest.iter(), ProgramParameters::LEN)
+                        .ok_or(ProgramError::InvalidInstructionData)?,
+                )
+                .map_err(|_| ProgramError::InvalidInstructionData)?,
+            },
+            TAG_INIT_SIGNER_ACTIVATION_DELAY_UPDATE => {
+                let iter = &mut rest.iter();
+                Self::InitSignerActivationDelayUpdate {
+                    fee_amount: read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?,
+                    fee_account_guid_hash: unpack_option::<BalanceAccountGuidHash>(iter)?,
+                    delay: read_duration(iter).ok_or(ProgramError::InvalidInstructionData)?,
+                }
+            }
+            TAG_FINALIZE_SIGNER_ACTIVATION_DELAY_UPDATE => {
+                Self::FinalizeSignerActivationDelayUpdate {
+                    delay: read_duration(&mut rest.iter())
+                        .ok_or(ProgramError::InvalidInstructionData)?,
+                }
+            }
+            TAG_INIT_FEE_POLICY_UPDATE => {
+                let iter = &mut rest.iter();
+                Self::InitFeePolicyUpdate {
+                    fee_amount: read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?,
+                    fee_account_guid_hash: unpack_option::<BalanceAccountGuidHash>(iter)?,
+                    fee_policy: read_fee_policy(iter)?,
+                }
+            }
+            TAG_FINALIZE_FEE_POLICY_UPDATE => Self::FinalizeFeePolicyUpdate {
+                fee_policy: read_fee_policy(&mut rest.iter())?,
+            },
+            TAG_CLEANUP_UNCREATED_BALANCE_ACCOUNT => Self::CleanupUncreatedBalanceAccount {
+                account_guid_hash: read_account_guid_hash(&mut rest.iter())
+                    .ok_or(ProgramError::InvalidInstructionData)?,
+            },
+            TAG_INIT_STAKE_WEIGHT_GOVERNANCE_UPDATE => {
+                let iter = &mut rest.iter();
+                Self::InitStakeWeightGovernanceUpdate {
+                    fee_amount: read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?,
+                    fee_account_guid_hash: unpack_option::<BalanceAccountGuidHash>(iter)?,
+                    governance: read_stake_weight_governance(iter)?,
+                }
+            }
+            TAG_FINALIZE_STAKE_WEIGHT_GOVERNANCE_UPDATE => {
+                Self::FinalizeStakeWeightGovernanceUpdate {
+                    governance: read_stake_weight_governance(&mut rest.iter())?,
+                }
+            }
+            TAG_INIT_WITH_STAKE_WEIGHTS => {
+                let (stake_account_count, init) = rest
+                    .split_first()
+                    .ok_or(ProgramError::InvalidInstructionData)?;
+                Self::InitWithStakeWeights {
+                    stake_account_count: *stake_account_count,
+                    init: init.to_vec(),
+                }
+            }
+            TAG_INIT_EVENT_LOG => Self::InitEventLog {},
+            TAG_INIT_ADDRESS_BOOK_RENEWAL => {
+                let iter = &mut rest.iter();
+                let fee_amount = read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?;
+                let fee_account_guid_hash = unpack_option::<BalanceAccountGuidHash>(iter)?;
+                let (slot_id, entry, expires_at) = read_address_book_renewal(iter)?;
+                Self::InitAddressBookRenewal {
+                    fee_amount,
+                    fee_account_guid_hash,
+                    slot_id,
+                    entry,
+                    expires_at,
+                }
+            }
+            TAG_FINALIZE_ADDRESS_BOOK_RENEWAL => {
+                let (slot_id, entry, expires_at) = read_address_book_renewal(&mut rest.iter())?;
+                Self::FinalizeAddressBookRenewal {
+                    slot_id,
+                    entry,
+                    expires_at,
+                }
             }
-            &ProgramInstruction::FinalizeBalanceAccountPolicyUpdate {
-                ref account_guid_hash,
-                ref update,
-            } => {
-                let mut update_bytes: Vec<u8> = Vec::new();
-                update.pack(&mut update_bytes);
-                buf.push(TAG_FINALIZE_BALANCE_ACCOUNT_POLICY_UPDATE);
-                buf.extend_from_slice(account_guid_hash.to_bytes());
-                buf.extend_from_slice(&update_bytes);
+            TAG_REGISTER_SESSION_KEY => {
+                let iter = &mut rest.iter();
+                let bytes = read_slice(iter, PUBKEY_BYTES + SessionKeyScope::LEN + 8)
+                    .ok_or(ProgramError::InvalidInstructionData)?;
+                Self::RegisterSessionKey {
+                    session_key: Pubkey::new_from_array(*array_ref![bytes, 0, PUBKEY_BYTES]),
+                    scope: SessionKeyScope::unpack_from_slice(&bytes[PUBKEY_BYTES..]),
+                    expires_at: i64::from_le_bytes(*array_ref![
+                        bytes,
+                        PUBKEY_BYTES + SessionKeyScope::LEN,
+                        8
+                    ]),
+                }
             }
-            &ProgramInstruction::SupplyDAppTransactionInstructions {
-                ref instructions,
-                starting_index,
-            } => {
-                pack_supply_dapp_transaction_instructions(starting_index, instructions, &mut buf);
+            TAG_SET_APPROVAL_DISPOSITION_BY_SIGNATURE => {
+                let iter = &mut rest.iter();
+                let bytes = read_slice(iter, 1 + HASH_LEN + 8)
+                    .ok_or(ProgramError::InvalidInstructionData)?;
+                Self::SetApprovalDispositionBySignature {
+                    disposition: ApprovalDisposition::from_u8(bytes[0]),
+                    params_hash: Hash::new_from_array(*array_ref![bytes, 1, HASH_LEN]),
+                    expires_at: i64::from_le_bytes(*array_ref![bytes, 1 + HASH_LEN, 8]),
+                }
             }
-            &ProgramInstruction::Migrate {} => {
-                buf.push(TAG_MIGRATE);
+            TAG_INIT_WALLET_STATS => Self::InitWalletStats {},
+            TAG_INIT_SPL_TOKEN_ACCOUNTS_CREATION => {
+                let iter = &mut rest.iter();
+                Self::InitSPLTokenAccountsCreation {
+                    fee_amount: read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?,
+                    fee_account_guid_hash: unpack_option::<BalanceAccountGuidHash>(iter)?,
+                    payer_account_guid_hash: read_account_guid_hash(iter)
+                        .ok_or(ProgramError::InvalidInstructionData)?,
+                    account_guid_hashes: read_account_guid_vec(iter)?,
+                }
             }
-            &ProgramInstruction::Cleanup {} => {
-                buf.push(TAG_CLEANUP);
+            TAG_FINALIZE_SPL_TOKEN_ACCOUNTS_CREATION => {
+                let (payer_account_guid_hash, account_guid_hashes, batch_size) =
+                    read_spl_token_accounts_creation_batch(&mut rest.iter())?;
+                Self::FinalizeSPLTokenAccountsCreation {
+                    payer_account_guid_hash,
+                    account_guid_hashes,
+                    batch_size,
+                }
             }
-            &ProgramInstruction::InitBalanceAccountAddressWhitelistUpdate {
-                fee_amount,
-                fee_account_guid_hash,
-                ref account_guid_hash,
-                ref update,
-            } => {
-                let mut update_bytes: Vec<u8> = Vec::new();
-                update.pack(&mut update_bytes);
-                buf.push(TAG_INIT_BALANCE_ACCOUNT_ADDRESS_WHITELIST_UPDATE);
-                buf.put_u64_le(fee_amount);
-                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
-                buf.extend_from_slice(account_guid_hash.to_bytes());
-                buf.extend_from_slice(&update_bytes);
+            TAG_CONTINUE_SPL_TOKEN_ACCOUNTS_CREATION => {
+                let (payer_account_guid_hash, account_guid_hashes, batch_size) =
+                    read_spl_token_accounts_creation_batch(&mut rest.iter())?;
+                Self::ContinueSPLTokenAccountsCreation {
+                    payer_account_guid_hash,
+                    account_guid_hashes,
+                    batch_size,
+                }
             }
-            &ProgramInstruction::FinalizeBalanceAccountAddressWhitelistUpdate {
-                ref account_guid_hash,
-                ref update,
-            } => {
-                let mut update_bytes: Vec<u8> = Vec::new();
-                update.pack(&mut update_bytes);
-                buf.push(TAG_FINALIZE_BALANCE_ACCOUNT_ADDRESS_WHITELIST_UPDATE);
-                buf.extend_from_slice(account_guid_hash.to_bytes());
-                buf.extend_from_slice(&update_bytes);
+            TAG_REVOKE_SESSION_KEY => Self::RevokeSessionKey {
+                session_key: Pubkey::new_from_array(
+                    *read_fixed_size_array(&mut rest.iter())
+                        .ok_or(ProgramError::InvalidInstructionData)?,
+                ),
+            },
+            TAG_INIT_APPROVAL_GROUP_UPDATE => {
+                let iter = &mut rest.iter();
+                Self::InitApprovalGroupUpdate {
+                    fee_amount: read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?,
+                    fee_account_guid_hash: unpack_option::<BalanceAccountGuidHash>(iter)?,
+                    approval_group: read_approval_group(iter)?,
+                }
             }
-            &ProgramInstruction::InitSignData {
-                fee_amount,
-                fee_account_guid_hash,
-                ref data,
-            } => {
-                buf.push(TAG_INIT_SIGN_DATA);
-                buf.put_u64_le(fee_amount);
-                pack_option(fee_account_guid_hash.as_ref(), &mut buf);
-                buf.put_u16_le(data.len().as_u16());
-                buf.extend_from_slice(data);
+            TAG_FINALIZE_APPROVAL_GROUP_UPDATE => Self::FinalizeApprovalGroupUpdate {
+                approval_group: read_approval_group(&mut rest.iter())?,
+            },
+            TAG_SUBMIT_AGGREGATED_APPROVAL => {
+                let iter = &mut rest.iter();
+                let bytes =
+                    read_slice(iter, HASH_LEN + 8).ok_or(ProgramError::InvalidInstructionData)?;
+                Self::SubmitAggregatedApproval {
+                    params_hash: Hash::new_from_array(*array_ref![bytes, 0, HASH_LEN]),
+                    expires_at: i64::from_le_bytes(*array_ref![bytes, HASH_LEN, 8]),
+                }
             }
-            &ProgramInstruction::FinalizeSignData { ref data } => {
-                buf.push(TAG_FINALIZE_SIGN_DATA);
-                buf.put_u16_le(data.len().as_u16());
-                buf.extend_from_slice(data);
+            TAG_INIT_ENABLE_DEPOSIT_ADDRESSES => {
+                let iter = &mut rest.iter();
+                Self::InitEnableDepositAddresses {
+                    fee_amount: read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?,
+                    fee_account_guid_hash: unpack_option::<BalanceAccountGuidHash>(iter)?,
+                    account_guid_hash: read_account_guid_hash(iter)
+                        .ok_or(ProgramError::InvalidInstructionData)?,
+                    enabled: BooleanSetting::from_u8(
+                        *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?,
+                    ),
+                }
+            }
+            TAG_FINALIZE_ENABLE_DEPOSIT_ADDRESSES => {
+                let iter = &mut rest.iter();
+                Self::FinalizeEnableDepositAddresses {
+                    account_guid_hash: read_account_guid_hash(iter)
+                        .ok_or(ProgramError::InvalidInstructionData)?,
+                    enabled: BooleanSetting::from_u8(
+                        *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?,
+                    ),
+                }
+            }
+            TAG_FINALIZE_WITH_DEPOSIT_SWEEP => {
+                let (count, rest) = rest
+                    .split_first()
+                    .ok_or(ProgramError::InvalidInstructionData)?;
+                if rest.len() < usize::from(*count) {
+                    return Err(ProgramError::InvalidInstructionData);
+                }
+                let (deposit_address_indices, finalize) = rest.split_at(usize::from(*count));
+                Self::FinalizeWithDepositSweep {
+                    deposit_address_indices: deposit_address_indices.to_vec(),
+                    finalize: finalize.to_vec(),
+                }
             }
+            TAG_INIT_COMPACT_WALLET => {
+                let iter = &mut rest.iter();
+                Self::InitCompactWallet {
+                    fee_amount: read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?,
+                    fee_account_guid_hash: unpack_option::<BalanceAccountGuidHash>(iter)?,
+                }
+            }
+            TAG_FINALIZE_COMPACT_WALLET => Self::FinalizeCompactWallet {},
+            TAG_INIT_INSURANCE_ATTESTOR_UPDATE => {
+                let iter = &mut rest.iter();
+                Self::InitInsuranceAttestorUpdate {
+                    fee_amount: read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?,
+                    fee_account_guid_hash: unpack_option::<BalanceAccountGuidHash>(iter)?,
+                    attestor: Pubkey::new_from_array(
+                        *read_fixed_size_array(iter).ok_or(ProgramError::InvalidInstructionData)?,
+                    ),
+                }
+            }
+            TAG_FINALIZE_INSURANCE_ATTESTOR_UPDATE => Self::FinalizeInsuranceAttestorUpdate {
+                attestor: Pubkey::new_from_array(
+                    *read_fixed_size_array(&mut rest.iter())
+                        .ok_or(ProgramError::InvalidInstructionData)?,
+                ),
+            },
+            TAG_SUBMIT_INSURANCE_ATTESTATION => {
+                let iter = &mut rest.iter();
+                let bytes =
+                    read_slice(iter, HASH_LEN + 8).ok_or(ProgramError::InvalidInstructionData)?;
+                Self::SubmitInsuranceAttestation {
+                    statement_hash: Hash::new_from_array(*array_ref![bytes, 0, HASH_LEN]),
+                    coverage_expires_at: i64::from_le_bytes(*array_ref![bytes, HASH_LEN, 8]),
+                }
+            }
+            TAG_CHECK_INSURANCE_ATTESTATION => Self::CheckInsuranceAttestation {},
+            TAG_INIT_PROVISIONING_WALLET => {
+                Self::unpack_init_provisioning_wallet_instruction(rest)?
+            }
+            TAG_UPDATE_PROVISIONING_WALLET => Self::UpdateProvisioningWallet {
+                config: InitialWalletConfig::unpack(rest)?,
+            },
+            TAG_ACTIVATE_WALLET => Self::ActivateWallet {},
+            TAG_INIT_WRAPPER_BOOK_UPDATE => {
+                Self::unpack_init_wrapper_book_update_instruction(rest)?
+            }
+            TAG_FINALIZE_WRAPPER_BOOK_UPDATE => Self::FinalizeWrapperBookUpdate {
+                wrapper_book: WrapperBook::unpack(&mut rest.iter())?,
+            },
+            TAG_INIT_ADAPTER_WRAP_UNWRAP => {
+                Self::unpack_init_adapter_wrap_unwrap_instruction(rest)?
+            }
+            TAG_FINALIZE_ADAPTER_WRAP_UNWRAP => {
+                Self::unpack_finalize_adapter_wrap_unwrap_instruction(rest)?
+            }
+            TAG_MIGRATE_PENDING_APPROVALS => Self::MigratePendingApprovals {
+                approver_count: *read_u8(&mut rest.iter())
+                    .ok_or(ProgramError::InvalidInstructionData)?,
+            },
+            #[cfg(feature = "demo")]
+            TAG_INIT_DEMO_WALLET => Self::unpack_init_demo_wallet_instruction(rest)?,
+            _ => return Err(ProgramError::InvalidInstructionData),
+        })
+    }
+
+    /// Whether the instruction starts an operation, and so can be wrapped in an
+    /// `InitWithSignature`.
+    pub fn is_init(&self) -> bool {
+        matches!(
+            self,
+            ProgramInstruction::InitBalanceAccountCreation { .. }
+                | ProgramInstruction::InitTransfer { .. }
+                | ProgramInstruction::InitWrapUnwrap { .. }
+                | ProgramInstruction::InitUpdateSigner { .. }
+                | ProgramInstruction::InitWalletConfigPolicyUpdate { .. }
+                | ProgramInstruction::InitDAppTransaction { .. }
+                | ProgramInstruction::InitAccountSettingsUpdate { .. }
+                | ProgramInstruction::InitDAppBookUpdate { .. }
+                | ProgramInstruction::InitAddressBookUpdate { .. }
+                | ProgramInstruction::InitBalanceAccountNameUpdate { .. }
+                | ProgramInstruction::InitBalanceAccountPolicyUpdate { .. }
+                | ProgramInstruction::InitBalanceAccountAddressWhitelistUpdate { .. }
+                | ProgramInstruction::InitSignData { .. }
+                | ProgramInstruction::InitVoteRewardsWithdrawal { .. }
+                | ProgramInstruction::InitUpdateAssistant { .. }
+                | ProgramInstruction::InitRewardSplitPolicyUpdate { .. }
+                | ProgramInstruction::InitFinalizeSubmitterPolicyUpdate { .. }
+                | ProgramInstruction::InitMintTo { .. }
+                | ProgramInstruction::InitSetMintAuthority { .. }
+                | ProgramInstruction::InitPaymentChannel { .. }
+                | ProgramInstruction::InitOpBundle { .. }
+                | ProgramInstruction::InitLookupTableUpdate { .. }
+                | ProgramInstruction::InitAddressBookImport { .. }
+                | ProgramInstruction::InitCommittedTransfer { .. }
+                | ProgramInstruction::InitCompliancePolicyUpdate { .. }
+                | ProgramInstruction::InitWalletReplicationUpdate { .. }
+                | ProgramInstruction::InitBalanceAccountDeletion { .. }
+                | ProgramInstruction::InitSpendingLimitUpdate { .. }
+                | ProgramInstruction::InitPriceOracleUpdate { .. }
+                | ProgramInstruction::InitMerkleAddressBookUpdate { .. }
+                | ProgramInstruction::InitWalletFreeze { .. }
+                | ProgramInstruction::InitRentReturnAllowlistUpdate { .. }
+                | ProgramInstruction::InitFullRotation { .. }
+                | ProgramInstruction::InitSignerActivationDelayUpdate { .. }
+                | ProgramInstruction::InitFeePolicyUpdate { .. }
+                | ProgramInstruction::InitStakeWeightGovernanceUpdate { .. }
+                | ProgramInstruction::InitAddressBookRenewal { .. }
+                | ProgramInstruction::InitSPLTokenAccountsCreation { .. }
+                | ProgramInstruction::InitApprovalGroupUpdate { .. }
+                | ProgramInstruction::InitEnableDepositAddresses { .. }
+                | ProgramInstruction::InitCompactWallet { .. }
+                | ProgramInstruction::InitInsuranceAttestorUpdate { .. }
+                | ProgramInstruction::InitWrapperBookUpdate { .. }
+                | ProgramInstruction::InitAdapterWrapUnwrap { .. }
+        )
+    }
+
+    /// Whether the instruction finalizes a change to the wallet's configuration, and so
+    /// refreshes the wallet read model when it is supplied as the last account.
+    pub fn is_config_finalize(&self) -> bool {
+        matches!(
+            self,
+            ProgramInstruction::FinalizeBalanceAccountCreation { .. }
+                | ProgramInstruction::FinalizeUpdateSigner { .. }
+                | ProgramInstruction::FinalizeWalletConfigPolicyUpdate { .. }
+                | ProgramInstruction::FinalizeAccountSettingsUpdate { .. }
+                | ProgramInstruction::FinalizeDAppBookUpdate { .. }
+                | ProgramInstruction::FinalizeAddressBookUpdate { .. }
+                | ProgramInstruction::FinalizeBalanceAccountNameUpdate { .. }
+                | ProgramInstruction::FinalizeBalanceAccountPolicyUpdate { .. }
+                | ProgramInstruction::FinalizeBalanceAccountAddressWhitelistUpdate { .. }
+                | ProgramInstruction::FinalizeUpdateAssistant { .. }
+                | ProgramInstruction::FinalizeRewardSplitPolicyUpdate { .. }
+                | ProgramInstruction::ActivateWalletConfigPolicy {}
+                | ProgramInstruction::FinalizeFinalizeSubmitterPolicyUpdate { .. }
+                | ProgramInstruction::FinalizeAddressBookImport { .. }
+                | ProgramInstruction::FinalizeCompliancePolicyUpdate { .. }
+                | ProgramInstruction::FinalizeWalletReplicationUpdate { .. }
+                | ProgramInstruction::FinalizeBalanceAccountDeletion { .. }
+                | ProgramInstruction::FinalizeSpendingLimitUpdate { .. }
+                | ProgramInstruction::FinalizePriceOracleUpdate { .. }
+                | ProgramInstruction::FinalizeWalletFreeze { .. }
+                | ProgramInstruction::FinalizeRentReturnAllowlistUpdate { .. }
+                | ProgramInstruction::FinalizeFullRotation { .. }
+                | ProgramInstruction::FinalizeSignerActivationDelayUpdate { .. }
+                | ProgramInstruction::FinalizeFeePolicyUpdate { .. }
+                | ProgramInstruction::FinalizeStakeWeightGovernanceUpdate { .. }
+                | ProgramInstruction::FinalizeAddressBookRenewal { .. }
+                | ProgramInstruction::FinalizeApprovalGroupUpdate { .. }
+                | ProgramInstruction::FinalizeEnableDepositAddresses { .. }
+                | ProgramInstruction::FinalizeCompactWallet {}
+                | ProgramInstruction::FinalizeInsuranceAttestorUpdate { .. }
+                | ProgramInstruction::FinalizeWrapperBookUpdate { .. }
+        )
+    }
+
+    fn unpack_init_wallet_instruction(bytes: &[u8]) -> Result<ProgramInstruction, ProgramError> {
+        Ok(Self::InitWallet {
+            wallet_guid_hash: unpack_wallet_guid_hash(bytes)?,
+            initial_config: InitialWalletConfig::unpack(
+                bytes
+                    .get(HASH_LEN..)
+                    .ok_or(ProgramError::InvalidInstructionData)?,
+            )?,
+        })
+    }
+
+    fn unpack_init_provisioning_wallet_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        Ok(Self::InitProvisioningWallet {
+            wallet_guid_hash: unpack_wallet_guid_hash(bytes)?,
+            initial_config: InitialWalletConfig::unpack(
+                bytes
+                    .get(HASH_LEN..)
+                    .ok_or(ProgramError::InvalidInstructionData)?,
+            )?,
+        })
+    }
+
+    #[cfg(feature = "demo")]
+    fn unpack_init_demo_wallet_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let iter = &mut bytes.iter();
+        let wallet_guid_hash = WalletGuidHash::new(
+            read_fixed_size_array::<HASH_LEN>(iter).ok_or(ProgramError::InvalidInstructionData)?,
+        );
+        let count = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let balance_accounts = (0..count)
+            .map(|_| DemoBalanceAccount::unpack(iter))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::InitDemoWallet {
+            wallet_guid_hash,
+            initial_config: InitialWalletConfig::unpack(iter.as_slice())?,
+            balance_accounts,
+        })
+    }
+
+    fn unpack_init_balance_account_creation_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let iter = &mut bytes.into_iter();
+        Ok(Self::InitBalanceAccountCreation {
+            fee_amount: read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?,
+            fee_account_guid_hash: unpack_option::<BalanceAccountGuidHash>(iter)?,
+            account_guid_hash: read_account_guid_hash(iter)
+                .ok_or(ProgramError::InvalidInstructionData)?,
+            creation_params: BalanceAccountCreation::unpack(iter.as_slice())?,
+        })
+    }
+
+    fn unpack_finalize_balance_account_creation_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        Ok(Self::FinalizeBalanceAccountCreation {
+            account_guid_hash: unpack_account_guid_hash(bytes)?,
+            creation_params: BalanceAccountCreation::unpack(
+                bytes
+                    .get(HASH_LEN..)
+                    .ok_or(ProgramError::InvalidInstructionData)?,
+            )?,
+        })
+    }
+
+    fn unpack_init_balance_account_policy_update_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let iter = &mut bytes.into_iter();
+        Ok(Self::InitBalanceAccountPolicyUpdate {
+            fee_amount: read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?,
+            fee_account_guid_hash: unpack_option::<BalanceAccountGuidHash>(iter)?,
+            account_guid_hash: read_account_guid_hash(iter)
+                .ok_or(ProgramError::InvalidInstructionData)?,
+            update: BalanceAccountPolicyUpdate::unpack(iter.as_slice())?,
+        })
+    }
+
+    fn unpack_finalize_balance_account_policy_update_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        Ok(Self::FinalizeBalanceAccountPolicyUpdate {
+            account_guid_hash: unpack_account_guid_hash(bytes)?,
+            update: BalanceAccountPolicyUpdate::unpack(
+                bytes
+                    .get(HASH_LEN..)
+                    .ok_or(ProgramError::InvalidInstructionData)?,
+            )?,
+        })
+    }
+
+    fn unpack_simulate_balance_account_policy_update_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        Ok(Self::SimulateBalanceAccountPolicyUpdate {
+            account_guid_hash: unpack_account_guid_hash(bytes)?,
+            update: BalanceAccountPolicyUpdate::unpack(
+                bytes
+                    .get(HASH_LEN..)
+                    .ok_or(ProgramError::InvalidInstructionData)?,
+            )?,
+        })
+    }
+
+    fn unpack_init_transfer_for_approval_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let iter = &mut bytes.into_iter();
+        let fee_amount = read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let fee_account_guid_hash = unpack_option::<BalanceAccountGuidHash>(iter)?;
+
+        let account_guid_hash =
+            read_account_guid_hash(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let amount = read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let destination_name_hash =
+            read_address_book_entry_name_hash(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let destination_acknowledgement_required =
+            unpack_bool(*read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?)?;
+        let deduct_fee_from_amount =
+            unpack_bool(*read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?)?;
+        let allow_destination_ata_creation =
+            unpack_bool(*read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?)?;
+        let memo_hash = read_optional_hash(iter)?;
+        let destination_proof = if iter.as_slice().is_empty() {
+            None
+        } else {
+            Some(read_merkle_proof(iter)?)
+        };
+
+        Ok(Self::InitTransfer {
+            fee_amount,
+            fee_account_guid_hash,
+            account_guid_hash,
+            amount,
+            destination_name_hash,
+            destination_acknowledgement_required,
+            deduct_fee_from_amount,
+            allow_destination_ata_creation,
+            memo_hash,
+            destination_proof,
+        })
+    }
+
+    fn unpack_acknowledge_internal_transfer_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let iter = &mut bytes.iter();
+        let account_guid_hash =
+            read_account_guid_hash(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let params_hash = Hash::new_from_array(
+            *read_fixed_size_array(iter).ok_or(ProgramError::InvalidInstructionData)?,
+        );
+
+        Ok(Self::AcknowledgeInternalTransfer {
+            account_guid_hash,
+            params_hash,
+        })
+    }
+
+    fn unpack_set_approval_disposition_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let (disposition, rest) = bytes
+            .split_first()
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        let params_hash = Hash::new_from_array(
+            rest.get(0..HASH_LEN)
+                .and_then(|slice| slice.try_into().ok())
+                .ok_or(ProgramError::InvalidInstructionData)?,
+        );
+        let iter = &mut rest[HASH_LEN..].iter();
+        let reason = match read_u8(iter) {
+            Some(code) => Some(DispositionReason {
+                code: *code,
+                hash: read_optional_hash(iter)?,
+            }),
+            None => None,
+        };
+        Ok(Self::SetApprovalDisposition {
+            disposition: ApprovalDisposition::from_u8(*disposition),
+            params_hash,
+            reason,
+        })
+    }
+
+    fn unpack_finalize_transfer_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        Ok(Self::FinalizeTransfer {
+            account_guid_hash: unpack_account_guid_hash(bytes)?,
+            amount: bytes
+                .get(HASH_LEN..HASH_LEN + 8)
+                .and_then(|slice| slice.try_into().ok())
+                .map(u64::from_le_bytes)
+                .ok_or(ProgramError::InvalidInstructionData)?,
+            token_mint: unpack_public_key(bytes, HASH_LEN + 8)?,
+            destination_acknowledgement_required: unpack_bool(
+                *bytes
+                    .get(HASH_LEN + 8 + PUBKEY_BYTES)
+                    .ok_or(ProgramError::InvalidInstructionData)?,
+            )?,
+            deduct_fee_from_amount: unpack_bool(
+                *bytes
+                    .get(HASH_LEN + 8 + PUBKEY_BYTES + 1)
+                    .ok_or(ProgramError::InvalidInstructionData)?,
+            )?,
+            allow_destination_ata_creation: unpack_bool(
+                *bytes
+                    .get(HASH_LEN + 8 + PUBKEY_BYTES + 2)
+                    .ok_or(ProgramError::InvalidInstructionData)?,
+            )?,
+            memo: read_optional_memo(
+                &mut bytes
+                    .get(HASH_LEN + 8 + PUBKEY_BYTES + 3..)
+                    .ok_or(ProgramError::InvalidInstructionData)?
+                    .iter(),
+            )?,
+        })
+    }
+
+    fn unpack_init_wrap_unwrap_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let iter = &mut bytes.into_iter();
+        let fee_amount = read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let fee_account_guid_hash = unpack_option::<BalanceAccountGuidHash>(iter)?;
+        let account_guid_hash =
+            read_account_guid_hash(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let amount = read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let direction = read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+
+        Ok(Self::InitWrapUnwrap {
+            fee_amount,
+            fee_account_guid_hash,
+            account_guid_hash,
+            amount,
+            direction: WrapDirection::from_u8(*direction),
+        })
+    }
+
+    fn unpack_finalize_wrap_unwrap_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        if let Some(direction) = bytes.get(40) {
+            Ok(Self::FinalizeWrapUnwrap {
+                account_guid_hash: unpack_account_guid_hash(bytes)?,
+                amount: bytes
+                    .get(HASH_LEN..HASH_LEN + 8)
+                    .and_then(|slice| slice.try_into().ok())
+                    .map(u64::from_le_bytes)
+                    .ok_or(ProgramError::InvalidInstructionData)?,
+                direction: WrapDirection::from_u8(*direction),
+            })
+        } else {
+            Err(ProgramError::InvalidInstructionData)
         }
-        buf
     }
 
-    /// Deserialize a byte buffer to ProgramInstruction.
-    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
-        let (tag, rest) = input
-            .split_first()
-            .ok_or(ProgramError::InvalidInstructionData)?;
+    fn unpack_init_adapter_wrap_unwrap_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let iter = &mut bytes.iter();
+        let fee_amount = read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let fee_account_guid_hash = unpack_option::<BalanceAccountGuidHash>(iter)?;
+        let (account_guid_hash, wrapper_mint, amount, direction) =
+            Self::unpack_adapter_wrap_unwrap(iter)?;
+        Ok(Self::InitAdapterWrapUnwrap {
+            fee_amount,
+            fee_account_guid_hash,
+            account_guid_hash,
+            wrapper_mint,
+            amount,
+            direction,
+        })
+    }
 
-        Ok(match *tag {
-            TAG_INIT_WALLET => Self::unpack_init_wallet_instruction(rest)?,
+    fn unpack_finalize_adapter_wrap_unwrap_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let (account_guid_hash, wrapper_mint, amount, direction) =
+            Self::unpack_adapter_wrap_unwrap(&mut bytes.iter())?;
+        Ok(Self::FinalizeAdapterWrapUnwrap {
+            account_guid_hash,
+            wrapper_mint,
+            amount,
+            direction,
+        })
+    }
 
-            TAG_INIT_BALANCE_ACCOUNT_CREATION => {
-                Self::unpack_init_balance_account_creation_instruction(rest)?
-            }
-            TAG_FINALIZE_BALANCE_ACCOUNT_CREATION => {
-                Self::unpack_finalize_balance_account_creation_instruction(rest)?
-            }
-            TAG_INIT_TRANSFER => Self::unpack_init_transfer_for_approval_instruction(rest)?,
+    fn unpack_adapter_wrap_unwrap(
+        iter: &mut Iter<u8>,
+    ) -> Result<(BalanceAccountGuidHash, Pubkey, u64, WrapDirection), ProgramError> {
+        let account_guid_hash =
+            read_account_guid_hash(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let wrapper_mint = Pubkey::new_from_array(
+            *read_fixed_size_array::<PUBKEY_BYTES>(iter)
+                .ok_or(ProgramError::InvalidInstructionData)?,
+        );
+        let amount = read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let direction = read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        Ok((
+            account_guid_hash,
+            wrapper_mint,
+            amount,
+            WrapDirection::from_u8(*direction),
+        ))
+    }
 
-            TAG_FINALIZE_TRANSFER => Self::unpack_finalize_transfer_instruction(rest)?,
+    fn unpack_init_update_signer_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let iter = &mut bytes.into_iter();
+        let fee_amount = read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let fee_account_guid_hash = unpack_option::<BalanceAccountGuidHash>(iter)?;
+        let slot_update_type = read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let slot_id = read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        Ok(Self::InitUpdateSigner {
+            fee_amount,
+            fee_account_guid_hash,
+            slot_update_type: SlotUpdateType::from_u8(*slot_update_type),
+            slot_id: SlotId::new(*slot_id as usize),
+            signer: unpack_signer(iter.as_slice())?,
+        })
+    }
 
-            TAG_SET_APPROVAL_DISPOSITION => {
-                Self::unpack_set_approval_disposition_instruction(rest)?
-            }
-            TAG_INIT_WRAP_UNWRAP => Self::unpack_init_wrap_unwrap_instruction(rest)?,
+    fn unpack_finalize_update_signer_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let (slot_update_type, rest) = bytes
+            .split_first()
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        let (slot_id, rest) = rest
+            .split_first()
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        Ok(Self::FinalizeUpdateSigner {
+            slot_update_type: SlotUpdateType::from_u8(*slot_update_type),
+            slot_id: SlotId::new(*slot_id as usize),
+            signer: unpack_signer(rest)?,
+        })
+    }
 
-            TAG_FINALIZE_WRAP_UNWRAP => Self::unpack_finalize_wrap_unwrap_instruction(rest)?,
+    fn unpack_init_update_assistant_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let iter = &mut bytes.iter();
+        let fee_amount = read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let fee_account_guid_hash = unpack_option::<BalanceAccountGuidHash>(iter)?;
+        let slot_update_type = read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let slot_id = read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        Ok(Self::InitUpdateAssistant {
+            fee_amount,
+            fee_account_guid_hash,
+            slot_update_type: SlotUpdateType::from_u8(*slot_update_type),
+            slot_id: SlotId::new(*slot_id as usize),
+            assistant: read_assistant(iter)?,
+        })
+    }
 
-            TAG_INIT_UPDATE_SIGNER => Self::unpack_init_update_signer_instruction(rest)?,
+    fn unpack_finalize_update_assistant_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let iter = &mut bytes.iter();
+        let slot_update_type = read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let slot_id = read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        Ok(Self::FinalizeUpdateAssistant {
+            slot_update_type: SlotUpdateType::from_u8(*slot_update_type),
+            slot_id: SlotId::new(*slot_id as usize),
+            assistant: read_assistant(iter)?,
+        })
+    }
 
-            TAG_FINALIZE_UPDATE_SIGNER => Self::unpack_finalize_update_signer_instruction(rest)?,
+    fn unpack_init_wallet_config_policy_update_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let iter = &mut bytes.into_iter();
+        let fee_amount = read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let fee_account_guid_hash = unpack_option::<BalanceAccountGuidHash>(iter)?;
+        Ok(Self::InitWalletConfigPolicyUpdate {
+            fee_amount,
+            fee_account_guid_hash,
+            update: WalletConfigPolicyUpdate::unpack(iter.as_slice())?,
+        })
+    }
 
-            TAG_INIT_WALLET_CONFIG_POLICY_UPDATE => {
-                Self::unpack_init_wallet_config_policy_update_instruction(rest)?
-            }
-            TAG_FINALIZE_WALLET_CONFIG_POLICY_UPDATE => {
-                Self::unpack_finalize_wallet_config_policy_update_instruction(rest)?
-            }
-            TAG_INIT_DAPP_TRANSACTION => Self::unpack_init_dapp_transaction_instruction(rest)?,
+    fn unpack_finalize_wallet_config_policy_update_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        Ok(Self::FinalizeWalletConfigPolicyUpdate {
+            update: WalletConfigPolicyUpdate::unpack(bytes)?,
+        })
+    }
 
-            TAG_FINALIZE_DAPP_TRANSACTION => {
-                Self::unpack_finalize_dapp_transaction_instruction(rest)?
-            }
-            TAG_INIT_ACCOUNT_SETTINGS_UPDATE => {
-                Self::unpack_init_account_settings_update_instruction(rest)?
-            }
-            TAG_FINALIZE_ACCOUNT_SETTINGS_UPDATE => {
-                Self::unpack_finalize_account_settings_update_instruction(rest)?
-            }
-            TAG_INIT_DAPP_BOOK_UPDATE => Self::unpack_init_dapp_book_update_instruction(rest)?,
+    fn unpack_init_dapp_transaction_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let iter = &mut bytes.into_iter();
+        let fee_amount = read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let fee_account_guid_hash = unpack_option::<BalanceAccountGuidHash>(iter)?;
+        let account_guid_hash = unpack_account_guid_hash(
+            read_slice(iter, HASH_LEN).ok_or(ProgramError::InvalidInstructionData)?,
+        )?;
+        let dapp = DAppBookEntry::unpack_from_slice(
+            read_slice(iter, DAppBookEntry::LEN).ok_or(ProgramError::InvalidInstructionData)?,
+        )?;
+        let instruction_count = read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        Ok(Self::InitDAppTransaction {
+            fee_amount,
+            fee_account_guid_hash,
+            account_guid_hash,
+            dapp,
+            instruction_count: *instruction_count,
+        })
+    }
 
-            TAG_FINALIZE_DAPP_BOOK_UPDATE => {
-                Self::unpack_finalize_dapp_book_update_instruction(rest)?
-            }
-            TAG_INIT_ADDRESS_BOOK_UPDATE => {
-                Self::unpack_init_address_book_update_instruction(rest)?
-            }
-            TAG_FINALIZE_ADDRESS_BOOK_UPDATE => {
-                Self::unpack_finalize_address_book_update_instruction(rest)?
-            }
-            TAG_INIT_BALANCE_ACCOUNT_NAME_UPDATE => {
-                Self::unpack_init_balance_account_name_update_instruction(rest)?
-            }
-            TAG_FINALIZE_BALANCE_ACCOUNT_NAME_UPDATE => {
-                Self::unpack_finalize_balance_account_name_update_instruction(rest)?
-            }
-            TAG_INIT_BALANCE_ACCOUNT_POLICY_UPDATE => {
-                Self::unpack_init_balance_account_policy_update_instruction(rest)?
-            }
-            TAG_FINALIZE_BALANCE_ACCOUNT_POLICY_UPDATE => {
-                Self::unpack_finalize_balance_account_policy_update_instruction(rest)?
-            }
-            TAG_SUPPLY_DAPP_INSTRUCTIONS => {
-                Self::unpack_supply_dapp_instructions_instruction(rest)?
-            }
-            TAG_MIGRATE => Self::Migrate {},
-            TAG_CLEANUP => Self::Cleanup {},
-            TAG_INIT_BALANCE_ACCOUNT_ADDRESS_WHITELIST_UPDATE => {
-                Self::unpack_init_balance_account_address_whitelist_update_instruction(rest)?
-            }
-            TAG_FINALIZE_BALANCE_ACCOUNT_ADDRESS_WHITELIST_UPDATE => {
-                Self::unpack_finalize_balance_account_address_whitelist_update_instruction(rest)?
-            }
-            TAG_INIT_SIGN_DATA => Self::unpack_init_sign_data_instruction(rest)?,
-            TAG_FINALIZE_SIGN_DATA => Self::unpack_finalize_sign_data_instruction(rest)?,
-            _ => return Err(ProgramError::InvalidInstructionData),
+    fn unpack_finalize_dapp_transaction_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let iter = &mut bytes.into_iter();
+        let account_guid_hash = unpack_account_guid_hash(
+            read_slice(iter, HASH_LEN).ok_or(ProgramError::InvalidInstructionData)?,
+        )?;
+        let params_hash =
+            Hash::new(read_slice(iter, HASH_LEN).ok_or(ProgramError::InvalidInstructionData)?);
+        Ok(Self::FinalizeDAppTransaction {
+            account_guid_hash,
+            params_hash,
         })
     }
 
-    fn unpack_init_wallet_instruction(bytes: &[u8]) -> Result<ProgramInstruction, ProgramError> {
-        Ok(Self::InitWallet {
-            wallet_guid_hash: unpack_wallet_guid_hash(bytes)?,
-            initial_config: InitialWalletConfig::unpack(
-                bytes
-                    .get(HASH_LEN..)
-                    .ok_or(ProgramError::InvalidInstructionData)?,
+    fn unpack_init_account_settings_update_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let iter = &mut bytes.into_iter();
+        Ok(Self::InitAccountSettingsUpdate {
+            fee_amount: read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?,
+            fee_account_guid_hash: unpack_option::<BalanceAccountGuidHash>(iter)?,
+            account_guid_hash: read_account_guid_hash(iter)
+                .ok_or(ProgramError::InvalidInstructionData)?,
+            whitelist_enabled: unpack_option::<BooleanSetting>(iter)?,
+            dapps_enabled: unpack_option::<BooleanSetting>(iter)?,
+            archived: unpack_option::<BooleanSetting>(iter)?,
+            system_owned_destinations_only: unpack_option::<BooleanSetting>(iter)?,
+        })
+    }
+
+    fn unpack_finalize_account_settings_update_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let iter = &mut bytes.into_iter();
+        Ok(Self::FinalizeAccountSettingsUpdate {
+            account_guid_hash: unpack_account_guid_hash(
+                read_slice(iter, HASH_LEN).ok_or(ProgramError::InvalidInstructionData)?,
             )?,
+            whitelist_enabled: unpack_option::<BooleanSetting>(iter)?,
+            dapps_enabled: unpack_option::<BooleanSetting>(iter)?,
+            archived: unpack_option::<BooleanSetting>(iter)?,
+            system_owned_destinations_only: unpack_option::<BooleanSetting>(iter)?,
+        })
+    }
+
+    fn unpack_init_dapp_book_update_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let iter = &mut bytes.into_iter();
+        let fee_amount = read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let fee_account_guid_hash = unpack_option::<BalanceAccountGuidHash>(iter)?;
+        Ok(Self::InitDAppBookUpdate {
+            fee_amount,
+            fee_account_guid_hash,
+            update: DAppBookUpdate::unpack(iter.as_slice())?,
+        })
+    }
+
+    fn unpack_finalize_dapp_book_update_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        Ok(Self::FinalizeDAppBookUpdate {
+            update: DAppBookUpdate::unpack(bytes)?,
         })
     }
 
-    fn unpack_init_balance_account_creation_instruction(
+    fn unpack_init_address_book_update_instruction(
         bytes: &[u8],
     ) -> Result<ProgramInstruction, ProgramError> {
         let iter = &mut bytes.into_iter();
-        Ok(Self::InitBalanceAccountCreation {
-            fee_amount: read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?,
-            fee_account_guid_hash: unpack_option::<BalanceAccountGuidHash>(iter)?,
-            account_guid_hash: read_account_guid_hash(iter)
-                .ok_or(ProgramError::InvalidInstructionData)?,
-            creation_params: BalanceAccountCreation::unpack(iter.as_slice())?,
+        let fee_amount = read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let fee_account_guid_hash = unpack_option::<BalanceAccountGuidHash>(iter)?;
+        Ok(Self::InitAddressBookUpdate {
+            fee_amount,
+            fee_account_guid_hash,
+            update: AddressBookUpdate::unpack(iter.as_slice())?,
         })
     }
 
-    fn unpack_finalize_balance_account_creation_instruction(
+    fn unpack_finalize_address_book_update_instruction(
         bytes: &[u8],
     ) -> Result<ProgramInstruction, ProgramError> {
-        Ok(Self::FinalizeBalanceAccountCreation {
-            account_guid_hash: unpack_account_guid_hash(bytes)?,
-            creation_params: BalanceAccountCreation::unpack(
-                bytes
-                    .get(HASH_LEN..)
-                    .ok_or(ProgramError::InvalidInstructionData)?,
-            )?,
+        Ok(Self::FinalizeAddressBookUpdate {
+            update: AddressBookUpdate::unpack(bytes)?,
         })
     }
 
-    fn unpack_init_balance_account_policy_update_instruction(
+    fn unpack_init_balance_account_name_update_instruction(
         bytes: &[u8],
     ) -> Result<ProgramInstruction, ProgramError> {
         let iter = &mut bytes.into_iter();
-        Ok(Self::InitBalanceAccountPolicyUpdate {
+        Ok(Self::InitBalanceAccountNameUpdate {
             fee_amount: read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?,
             fee_account_guid_hash: unpack_option::<BalanceAccountGuidHash>(iter)?,
             account_guid_hash: read_account_guid_hash(iter)
                 .ok_or(ProgramError::InvalidInstructionData)?,
-            update: BalanceAccountPolicyUpdate::unpack(iter.as_slice())?,
+            account_name_hash: read_account_name_hash(iter)
+                .ok_or(ProgramError::InvalidInstructionData)?,
         })
     }
 
-    fn unpack_finalize_balance_account_policy_update_instruction(
+    fn unpack_finalize_balance_account_name_update_instruction(
         bytes: &[u8],
     ) -> Result<ProgramInstruction, ProgramError> {
-        Ok(Self::FinalizeBalanceAccountPolicyUpdate {
+        Ok(Self::FinalizeBalanceAccountNameUpdate {
             account_guid_hash: unpack_account_guid_hash(bytes)?,
-            update: BalanceAccountPolicyUpdate::unpack(
+            account_name_hash: unpack_account_name_hash(
                 bytes
                     .get(HASH_LEN..)
                     .ok_or(ProgramError::InvalidInstructionData)?,
@@ -893,349 +4463,407 @@ impl ProgramInstruction {
         })
     }
 
-    fn unpack_init_transfer_for_approval_instruction(
+    fn unpack_supply_dapp_instructions_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let iter = &mut bytes.into_iter();
+        let starting_index = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        Ok(Self::SupplyDAppTransactionInstructions {
+            starting_index,
+            instructions: read_instructions(iter)?,
+        })
+    }
+
+    fn unpack_init_balance_account_address_whitelist_update_instruction(
         bytes: &[u8],
     ) -> Result<ProgramInstruction, ProgramError> {
         let iter = &mut bytes.into_iter();
         let fee_amount = read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?;
         let fee_account_guid_hash = unpack_option::<BalanceAccountGuidHash>(iter)?;
-
         let account_guid_hash =
             read_account_guid_hash(iter).ok_or(ProgramError::InvalidInstructionData)?;
-        let amount = read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?;
-        let destination_name_hash =
-            read_address_book_entry_name_hash(iter).ok_or(ProgramError::InvalidInstructionData)?;
-
-        Ok(Self::InitTransfer {
+        let update = BalanceAccountAddressWhitelistUpdate::unpack(iter.as_slice())?;
+        Ok(Self::InitBalanceAccountAddressWhitelistUpdate {
             fee_amount,
             fee_account_guid_hash,
             account_guid_hash,
-            amount,
-            destination_name_hash,
+            update,
         })
     }
 
-    fn unpack_set_approval_disposition_instruction(
+    fn unpack_finalize_balance_account_address_whitelist_update_instruction(
         bytes: &[u8],
     ) -> Result<ProgramInstruction, ProgramError> {
-        let (disposition, rest) = bytes
-            .split_first()
-            .ok_or(ProgramError::InvalidInstructionData)?;
-        Ok(Self::SetApprovalDisposition {
-            disposition: ApprovalDisposition::from_u8(*disposition),
-            params_hash: Hash::new_from_array(
-                rest.get(0..HASH_LEN)
-                    .and_then(|slice| slice.try_into().ok())
+        Ok(Self::FinalizeBalanceAccountAddressWhitelistUpdate {
+            account_guid_hash: unpack_account_guid_hash(bytes)?,
+            update: BalanceAccountAddressWhitelistUpdate::unpack(
+                bytes
+                    .get(HASH_LEN..)
                     .ok_or(ProgramError::InvalidInstructionData)?,
-            ),
+            )?,
         })
     }
 
-    fn unpack_finalize_transfer_instruction(
-        bytes: &[u8],
-    ) -> Result<ProgramInstruction, ProgramError> {
-        Ok(Self::FinalizeTransfer {
-            account_guid_hash: unpack_account_guid_hash(bytes)?,
-            amount: bytes
-                .get(HASH_LEN..HASH_LEN + 8)
-                .and_then(|slice| slice.try_into().ok())
-                .map(u64::from_le_bytes)
-                .ok_or(ProgramError::InvalidInstructionData)?,
-            token_mint: unpack_public_key(bytes, HASH_LEN + 8)?,
+    fn unpack_init_sign_data_instruction(bytes: &[u8]) -> Result<ProgramInstruction, ProgramError> {
+        let iter = &mut bytes.into_iter();
+        let fee_amount = read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let fee_account_guid_hash = unpack_option::<BalanceAccountGuidHash>(iter)?;
+        let data_len = read_u16(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let data = read_slice(iter, data_len.try_into().unwrap())
+            .ok_or(ProgramError::InvalidInstructionData)?
+            .to_vec();
+        Ok(Self::InitSignData {
+            fee_amount,
+            fee_account_guid_hash,
+            data,
         })
     }
 
-    fn unpack_init_wrap_unwrap_instruction(
+    fn unpack_finalize_sign_data_instruction(
         bytes: &[u8],
     ) -> Result<ProgramInstruction, ProgramError> {
         let iter = &mut bytes.into_iter();
+        let data_len = read_u16(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let data = read_slice(iter, data_len.try_into().unwrap())
+            .ok_or(ProgramError::InvalidInstructionData)?
+            .to_vec();
+        Ok(Self::FinalizeSignData { data })
+    }
+
+    fn unpack_init_vote_rewards_withdrawal_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let iter = &mut bytes.iter();
         let fee_amount = read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?;
         let fee_account_guid_hash = unpack_option::<BalanceAccountGuidHash>(iter)?;
-        let account_guid_hash =
-            read_account_guid_hash(iter).ok_or(ProgramError::InvalidInstructionData)?;
-        let amount = read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?;
-        let direction = read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
-
-        Ok(Self::InitWrapUnwrap {
+        Ok(Self::InitVoteRewardsWithdrawal {
             fee_amount,
             fee_account_guid_hash,
-            account_guid_hash,
-            amount,
-            direction: WrapDirection::from_u8(*direction),
+            account_guid_hash: read_account_guid_hash(iter)
+                .ok_or(ProgramError::InvalidInstructionData)?,
+            amount: read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?,
         })
     }
 
-    fn unpack_finalize_wrap_unwrap_instruction(
+    fn unpack_finalize_vote_rewards_withdrawal_instruction(
         bytes: &[u8],
     ) -> Result<ProgramInstruction, ProgramError> {
-        if let Some(direction) = bytes.get(40) {
-            Ok(Self::FinalizeWrapUnwrap {
-                account_guid_hash: unpack_account_guid_hash(bytes)?,
-                amount: bytes
-                    .get(HASH_LEN..HASH_LEN + 8)
-                    .and_then(|slice| slice.try_into().ok())
-                    .map(u64::from_le_bytes)
-                    .ok_or(ProgramError::InvalidInstructionData)?,
-                direction: WrapDirection::from_u8(*direction),
-            })
-        } else {
-            Err(ProgramError::InvalidInstructionData)
-        }
+        let iter = &mut bytes.iter();
+        Ok(Self::FinalizeVoteRewardsWithdrawal {
+            account_guid_hash: read_account_guid_hash(iter)
+                .ok_or(ProgramError::InvalidInstructionData)?,
+            amount: read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?,
+        })
     }
 
-    fn unpack_init_update_signer_instruction(
+    fn unpack_init_mint_to_instruction(bytes: &[u8]) -> Result<ProgramInstruction, ProgramError> {
+        let iter = &mut bytes.iter();
+        let fee_amount = read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let fee_account_guid_hash = unpack_option::<BalanceAccountGuidHash>(iter)?;
+        Ok(Self::InitMintTo {
+            fee_amount,
+            fee_account_guid_hash,
+            account_guid_hash: read_account_guid_hash(iter)
+                .ok_or(ProgramError::InvalidInstructionData)?,
+            amount: read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?,
+            destination_name_hash: read_address_book_entry_name_hash(iter)
+                .ok_or(ProgramError::InvalidInstructionData)?,
+        })
+    }
+
+    fn unpack_init_payment_channel_instruction(
         bytes: &[u8],
     ) -> Result<ProgramInstruction, ProgramError> {
-        let iter = &mut bytes.into_iter();
+        let iter = &mut bytes.iter();
         let fee_amount = read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?;
         let fee_account_guid_hash = unpack_option::<BalanceAccountGuidHash>(iter)?;
-        let slot_update_type = read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
-        let slot_id = read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
-        Ok(Self::InitUpdateSigner {
+        Ok(Self::InitPaymentChannel {
             fee_amount,
             fee_account_guid_hash,
-            slot_update_type: SlotUpdateType::from_u8(*slot_update_type),
-            slot_id: SlotId::new(*slot_id as usize),
-            signer: Signer::unpack_from_slice(iter.as_slice())?,
+            account_guid_hash: read_account_guid_hash(iter)
+                .ok_or(ProgramError::InvalidInstructionData)?,
+            destination_name_hash: read_address_book_entry_name_hash(iter)
+                .ok_or(ProgramError::InvalidInstructionData)?,
+            token_mint: Pubkey::new_from_array(
+                *read_fixed_size_array(iter).ok_or(ProgramError::InvalidInstructionData)?,
+            ),
+            allowance: read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?,
+            duration: read_duration(iter).ok_or(ProgramError::InvalidInstructionData)?,
         })
     }
 
-    fn unpack_finalize_update_signer_instruction(
+    fn unpack_finalize_payment_channel_instruction(
         bytes: &[u8],
     ) -> Result<ProgramInstruction, ProgramError> {
-        let (slot_update_type, rest) = bytes
-            .split_first()
-            .ok_or(ProgramError::InvalidInstructionData)?;
-        let (slot_id, rest) = rest
-            .split_first()
-            .ok_or(ProgramError::InvalidInstructionData)?;
-        Ok(Self::FinalizeUpdateSigner {
-            slot_update_type: SlotUpdateType::from_u8(*slot_update_type),
-            slot_id: SlotId::new(*slot_id as usize),
-            signer: Signer::unpack_from_slice(rest)?,
+        let iter = &mut bytes.iter();
+        Ok(Self::FinalizePaymentChannel {
+            account_guid_hash: read_account_guid_hash(iter)
+                .ok_or(ProgramError::InvalidInstructionData)?,
+            token_mint: Pubkey::new_from_array(
+                *read_fixed_size_array(iter).ok_or(ProgramError::InvalidInstructionData)?,
+            ),
+            allowance: read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?,
+            duration: read_duration(iter).ok_or(ProgramError::InvalidInstructionData)?,
         })
     }
 
-    fn unpack_init_wallet_config_policy_update_instruction(
+    fn unpack_init_lookup_table_update_instruction(
         bytes: &[u8],
     ) -> Result<ProgramInstruction, ProgramError> {
-        let iter = &mut bytes.into_iter();
+        let iter = &mut bytes.iter();
         let fee_amount = read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?;
         let fee_account_guid_hash = unpack_option::<BalanceAccountGuidHash>(iter)?;
-        Ok(Self::InitWalletConfigPolicyUpdate {
+        Ok(Self::InitLookupTableUpdate {
             fee_amount,
             fee_account_guid_hash,
-            update: WalletConfigPolicyUpdate::unpack(iter.as_slice())?,
+            lookup_table: read_optional_pubkey(iter)?,
+            new_addresses: read_pubkeys(iter)?,
         })
     }
 
-    fn unpack_finalize_wallet_config_policy_update_instruction(
+    fn unpack_finalize_lookup_table_update_instruction(
         bytes: &[u8],
     ) -> Result<ProgramInstruction, ProgramError> {
-        Ok(Self::FinalizeWalletConfigPolicyUpdate {
-            update: WalletConfigPolicyUpdate::unpack(bytes)?,
+        let iter = &mut bytes.iter();
+        Ok(Self::FinalizeLookupTableUpdate {
+            lookup_table: read_optional_pubkey(iter)?,
+            new_addresses: read_pubkeys(iter)?,
+            recent_slot: read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?,
         })
     }
 
-    fn unpack_init_dapp_transaction_instruction(
+    fn unpack_init_address_book_import_instruction(
         bytes: &[u8],
     ) -> Result<ProgramInstruction, ProgramError> {
-        let iter = &mut bytes.into_iter();
+        let iter = &mut bytes.iter();
         let fee_amount = read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?;
         let fee_account_guid_hash = unpack_option::<BalanceAccountGuidHash>(iter)?;
-        let account_guid_hash = unpack_account_guid_hash(
-            read_slice(iter, HASH_LEN).ok_or(ProgramError::InvalidInstructionData)?,
-        )?;
-        let dapp = DAppBookEntry::unpack_from_slice(
-            read_slice(iter, DAppBookEntry::LEN).ok_or(ProgramError::InvalidInstructionData)?,
-        )?;
-        let instruction_count = read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
-        Ok(Self::InitDAppTransaction {
+        let entries_count = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let entries = read_slice(iter, usize::from(entries_count) * 2)
+            .ok_or(ProgramError::InvalidInstructionData)?
+            .chunks_exact(2)
+            .map(|chunk| {
+                (
+                    SlotId::new(usize::from(chunk[0])),
+                    SlotId::new(usize::from(chunk[1])),
+                )
+            })
+            .collect();
+        let balance_accounts_count = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let mut balance_accounts = Vec::with_capacity(usize::from(balance_accounts_count));
+        for _ in 0..balance_accounts_count {
+            balance_accounts.push((read_account_guid(iter)?, read_account_guid(iter)?));
+        }
+        Ok(Self::InitAddressBookImport {
             fee_amount,
             fee_account_guid_hash,
-            account_guid_hash,
-            dapp,
-            instruction_count: *instruction_count,
+            entries,
+            balance_accounts,
         })
     }
 
-    fn unpack_finalize_dapp_transaction_instruction(
+    fn unpack_init_committed_transfer_instruction(
         bytes: &[u8],
     ) -> Result<ProgramInstruction, ProgramError> {
-        let iter = &mut bytes.into_iter();
-        let account_guid_hash = unpack_account_guid_hash(
-            read_slice(iter, HASH_LEN).ok_or(ProgramError::InvalidInstructionData)?,
-        )?;
-        let params_hash =
-            Hash::new(read_slice(iter, HASH_LEN).ok_or(ProgramError::InvalidInstructionData)?);
-        Ok(Self::FinalizeDAppTransaction {
-            account_guid_hash,
-            params_hash,
+        let iter = &mut bytes.iter();
+        let fee_amount = read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let fee_account_guid_hash = unpack_option::<BalanceAccountGuidHash>(iter)?;
+        Ok(Self::InitCommittedTransfer {
+            fee_amount,
+            fee_account_guid_hash,
+            account_guid_hash: read_account_guid_hash(iter)
+                .ok_or(ProgramError::InvalidInstructionData)?,
+            amount: read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?,
+            destination_commitment: Hash::new_from_array(
+                *read_fixed_size_array(iter).ok_or(ProgramError::InvalidInstructionData)?,
+            ),
         })
     }
 
-    fn unpack_init_account_settings_update_instruction(
+    fn unpack_finalize_committed_transfer_instruction(
         bytes: &[u8],
     ) -> Result<ProgramInstruction, ProgramError> {
-        let iter = &mut bytes.into_iter();
-        Ok(Self::InitAccountSettingsUpdate {
-            fee_amount: read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?,
-            fee_account_guid_hash: unpack_option::<BalanceAccountGuidHash>(iter)?,
+        let iter = &mut bytes.iter();
+        Ok(Self::FinalizeCommittedTransfer {
             account_guid_hash: read_account_guid_hash(iter)
                 .ok_or(ProgramError::InvalidInstructionData)?,
-            whitelist_enabled: unpack_option::<BooleanSetting>(iter)?,
-            dapps_enabled: unpack_option::<BooleanSetting>(iter)?,
+            amount: read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?,
+            destination_commitment: Hash::new_from_array(
+                *read_fixed_size_array(iter).ok_or(ProgramError::InvalidInstructionData)?,
+            ),
+            destination_name_hash: read_address_book_entry_name_hash(iter)
+                .ok_or(ProgramError::InvalidInstructionData)?,
+            salt: Hash::new_from_array(
+                *read_fixed_size_array(iter).ok_or(ProgramError::InvalidInstructionData)?,
+            ),
         })
     }
 
-    fn unpack_finalize_account_settings_update_instruction(
+    fn unpack_init_compliance_policy_update_instruction(
         bytes: &[u8],
     ) -> Result<ProgramInstruction, ProgramError> {
-        let iter = &mut bytes.into_iter();
-        Ok(Self::FinalizeAccountSettingsUpdate {
-            account_guid_hash: unpack_account_guid_hash(
-                read_slice(iter, HASH_LEN).ok_or(ProgramError::InvalidInstructionData)?,
-            )?,
-            whitelist_enabled: unpack_option::<BooleanSetting>(iter)?,
-            dapps_enabled: unpack_option::<BooleanSetting>(iter)?,
+        let iter = &mut bytes.iter();
+        let fee_amount = read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let fee_account_guid_hash = unpack_option::<BalanceAccountGuidHash>(iter)?;
+        Ok(Self::InitCompliancePolicyUpdate {
+            fee_amount,
+            fee_account_guid_hash,
+            policy: read_compliance_policy(iter)?,
         })
     }
 
-    fn unpack_init_dapp_book_update_instruction(
+    fn unpack_init_price_oracle_update_instruction(
         bytes: &[u8],
     ) -> Result<ProgramInstruction, ProgramError> {
-        let iter = &mut bytes.into_iter();
+        let iter = &mut bytes.iter();
+        let fee_amount = read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let fee_account_guid_hash = unpack_option::<BalanceAccountGuidHash>(iter)?;
+        Ok(Self::InitPriceOracleUpdate {
+            fee_amount,
+            fee_account_guid_hash,
+            oracle: read_price_oracle(iter)?,
+        })
+    }
+
+    fn unpack_init_wallet_freeze_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let iter = &mut bytes.iter();
         let fee_amount = read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?;
         let fee_account_guid_hash = unpack_option::<BalanceAccountGuidHash>(iter)?;
-        Ok(Self::InitDAppBookUpdate {
+        Ok(Self::InitWalletFreeze {
             fee_amount,
             fee_account_guid_hash,
-            update: DAppBookUpdate::unpack(iter.as_slice())?,
+            freeze: read_wallet_freeze(iter)?,
         })
     }
 
-    fn unpack_finalize_dapp_book_update_instruction(
+    fn unpack_init_rent_return_allowlist_update_instruction(
         bytes: &[u8],
     ) -> Result<ProgramInstruction, ProgramError> {
-        Ok(Self::FinalizeDAppBookUpdate {
-            update: DAppBookUpdate::unpack(bytes)?,
+        let iter = &mut bytes.iter();
+        let fee_amount = read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let fee_account_guid_hash = unpack_option::<BalanceAccountGuidHash>(iter)?;
+        Ok(Self::InitRentReturnAllowlistUpdate {
+            fee_amount,
+            fee_account_guid_hash,
+            allowlist: RentReturnAllowlist {
+                entries: read_pubkeys(iter)?,
+            },
         })
     }
 
-    fn unpack_init_address_book_update_instruction(
+    fn unpack_init_wrapper_book_update_instruction(
         bytes: &[u8],
     ) -> Result<ProgramInstruction, ProgramError> {
-        let iter = &mut bytes.into_iter();
+        let iter = &mut bytes.iter();
         let fee_amount = read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?;
         let fee_account_guid_hash = unpack_option::<BalanceAccountGuidHash>(iter)?;
-        Ok(Self::InitAddressBookUpdate {
+        Ok(Self::InitWrapperBookUpdate {
             fee_amount,
             fee_account_guid_hash,
-            update: AddressBookUpdate::unpack(iter.as_slice())?,
+            wrapper_book: WrapperBook::unpack(iter)?,
         })
     }
 
-    fn unpack_finalize_address_book_update_instruction(
+    fn unpack_init_merkle_address_book_update_instruction(
         bytes: &[u8],
     ) -> Result<ProgramInstruction, ProgramError> {
-        Ok(Self::FinalizeAddressBookUpdate {
-            update: AddressBookUpdate::unpack(bytes)?,
+        let iter = &mut bytes.iter();
+        let fee_amount = read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let fee_account_guid_hash = unpack_option::<BalanceAccountGuidHash>(iter)?;
+        Ok(Self::InitMerkleAddressBookUpdate {
+            fee_amount,
+            fee_account_guid_hash,
+            account_guid_hash: read_account_guid_hash(iter)
+                .ok_or(ProgramError::InvalidInstructionData)?,
+            root: Hash::new_from_array(
+                *read_fixed_size_array(iter).ok_or(ProgramError::InvalidInstructionData)?,
+            ),
         })
     }
 
-    fn unpack_init_balance_account_name_update_instruction(
+    fn unpack_finalize_mint_to_instruction(
         bytes: &[u8],
     ) -> Result<ProgramInstruction, ProgramError> {
-        let iter = &mut bytes.into_iter();
-        Ok(Self::InitBalanceAccountNameUpdate {
-            fee_amount: read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?,
-            fee_account_guid_hash: unpack_option::<BalanceAccountGuidHash>(iter)?,
+        let iter = &mut bytes.iter();
+        Ok(Self::FinalizeMintTo {
             account_guid_hash: read_account_guid_hash(iter)
                 .ok_or(ProgramError::InvalidInstructionData)?,
-            account_name_hash: read_account_name_hash(iter)
-                .ok_or(ProgramError::InvalidInstructionData)?,
+            amount: read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?,
         })
     }
 
-    fn unpack_finalize_balance_account_name_update_instruction(
+    fn unpack_init_set_mint_authority_instruction(
         bytes: &[u8],
     ) -> Result<ProgramInstruction, ProgramError> {
-        Ok(Self::FinalizeBalanceAccountNameUpdate {
-            account_guid_hash: unpack_account_guid_hash(bytes)?,
-            account_name_hash: unpack_account_name_hash(
-                bytes
-                    .get(HASH_LEN..)
-                    .ok_or(ProgramError::InvalidInstructionData)?,
-            )?,
+        let iter = &mut bytes.iter();
+        let fee_amount = read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let fee_account_guid_hash = unpack_option::<BalanceAccountGuidHash>(iter)?;
+        Ok(Self::InitSetMintAuthority {
+            fee_amount,
+            fee_account_guid_hash,
+            account_guid_hash: read_account_guid_hash(iter)
+                .ok_or(ProgramError::InvalidInstructionData)?,
+            authority_type: MintAuthorityType::from_u8(
+                *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?,
+            ),
+            new_authority: read_optional_pubkey(iter)?,
         })
     }
 
-    fn unpack_supply_dapp_instructions_instruction(
+    fn unpack_finalize_set_mint_authority_instruction(
         bytes: &[u8],
     ) -> Result<ProgramInstruction, ProgramError> {
-        let iter = &mut bytes.into_iter();
-        let starting_index = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
-        Ok(Self::SupplyDAppTransactionInstructions {
-            starting_index,
-            instructions: read_instructions(iter)?,
+        let iter = &mut bytes.iter();
+        Ok(Self::FinalizeSetMintAuthority {
+            account_guid_hash: read_account_guid_hash(iter)
+                .ok_or(ProgramError::InvalidInstructionData)?,
+            authority_type: MintAuthorityType::from_u8(
+                *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?,
+            ),
+            new_authority: read_optional_pubkey(iter)?,
         })
     }
 
-    fn unpack_init_balance_account_address_whitelist_update_instruction(
+    fn unpack_init_reward_split_policy_update_instruction(
         bytes: &[u8],
     ) -> Result<ProgramInstruction, ProgramError> {
-        let iter = &mut bytes.into_iter();
+        let iter = &mut bytes.iter();
         let fee_amount = read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?;
         let fee_account_guid_hash = unpack_option::<BalanceAccountGuidHash>(iter)?;
-        let account_guid_hash =
-            read_account_guid_hash(iter).ok_or(ProgramError::InvalidInstructionData)?;
-        let update = BalanceAccountAddressWhitelistUpdate::unpack(iter.as_slice())?;
-        Ok(Self::InitBalanceAccountAddressWhitelistUpdate {
+        Ok(Self::InitRewardSplitPolicyUpdate {
             fee_amount,
             fee_account_guid_hash,
-            account_guid_hash,
-            update,
+            account_guid_hash: read_account_guid_hash(iter)
+                .ok_or(ProgramError::InvalidInstructionData)?,
+            splits: read_reward_splits(iter)?,
         })
     }
 
-    fn unpack_finalize_balance_account_address_whitelist_update_instruction(
+    fn unpack_finalize_reward_split_policy_update_instruction(
         bytes: &[u8],
     ) -> Result<ProgramInstruction, ProgramError> {
-        Ok(Self::FinalizeBalanceAccountAddressWhitelistUpdate {
-            account_guid_hash: unpack_account_guid_hash(bytes)?,
-            update: BalanceAccountAddressWhitelistUpdate::unpack(
-                bytes
-                    .get(HASH_LEN..)
-                    .ok_or(ProgramError::InvalidInstructionData)?,
-            )?,
+        let iter = &mut bytes.iter();
+        Ok(Self::FinalizeRewardSplitPolicyUpdate {
+            account_guid_hash: read_account_guid_hash(iter)
+                .ok_or(ProgramError::InvalidInstructionData)?,
+            splits: read_reward_splits(iter)?,
         })
     }
 
-    fn unpack_init_sign_data_instruction(bytes: &[u8]) -> Result<ProgramInstruction, ProgramError> {
-        let iter = &mut bytes.into_iter();
+    fn unpack_init_finalize_submitter_policy_update_instruction(
+        bytes: &[u8],
+    ) -> Result<ProgramInstruction, ProgramError> {
+        let iter = &mut bytes.iter();
         let fee_amount = read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?;
         let fee_account_guid_hash = unpack_option::<BalanceAccountGuidHash>(iter)?;
-        let data_len = read_u16(iter).ok_or(ProgramError::InvalidInstructionData)?;
-        let data = read_slice(iter, data_len.try_into().unwrap())
-            .ok_or(ProgramError::InvalidInstructionData)?
-            .to_vec();
-        Ok(Self::InitSignData {
+        Ok(Self::InitFinalizeSubmitterPolicyUpdate {
             fee_amount,
             fee_account_guid_hash,
-            data,
+            policy: read_finalize_submitter_policy(iter)?,
         })
     }
-
-    fn unpack_finalize_sign_data_instruction(
-        bytes: &[u8],
-    ) -> Result<ProgramInstruction, ProgramError> {
-        let iter = &mut bytes.into_iter();
-        let data_len = read_u16(iter).ok_or(ProgramError::InvalidInstructionData)?;
-        let data = read_slice(iter, data_len.try_into().unwrap())
-            .ok_or(ProgramError::InvalidInstructionData)?
-            .to_vec();
-        Ok(Self::FinalizeSignData { data })
-    }
 }
 
 pub fn pack_supply_dapp_transaction_instructions(
@@ -1259,66 +4887,454 @@ pub struct InitialWalletConfig {
     pub config_approvers: Vec<SlotId<Signer>>,
 }
 
-impl InitialWalletConfig {
-    fn unpack(bytes: &[u8]) -> Result<InitialWalletConfig, ProgramError> {
-        if bytes.len() < 7 {
-            return Err(ProgramError::InvalidInstructionData);
-        }
-        let mut iter = bytes.iter();
-        let approvals_required_for_config =
-            *iter.next().ok_or(ProgramError::InvalidInstructionData)?;
-        let approval_timeout_for_config =
-            read_duration(&mut iter).ok_or(ProgramError::InvalidInstructionData)?;
-        let signers = read_signers(&mut iter)?;
-        let config_approvers = read_signer_slots(&mut iter)?;
+impl InitialWalletConfig {
+    fn unpack(bytes: &[u8]) -> Result<InitialWalletConfig, ProgramError> {
+        if bytes.len() < 7 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let mut iter = bytes.iter();
+        let approvals_required_for_config =
+            *iter.next().ok_or(ProgramError::InvalidInstructionData)?;
+        let approval_timeout_for_config =
+            read_duration(&mut iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let signer_keys = read_signer_keys(&mut iter)?;
+        let config_approvers = read_signer_slots(&mut iter)?;
+        let signers = read_signer_capabilities(&mut iter, signer_keys)?;
+
+        Ok(InitialWalletConfig {
+            approvals_required_for_config,
+            approval_timeout_for_config,
+            signers,
+            config_approvers,
+        })
+    }
+
+    pub fn pack(&self, dst: &mut Vec<u8>) {
+        dst.push(self.approvals_required_for_config);
+        append_duration(&self.approval_timeout_for_config, dst);
+        append_signer_keys(&self.signers, dst);
+        append_signer_slots(&self.config_approvers, dst);
+        for (_, signer) in self.signers.iter() {
+            dst.push(signer.capabilities.to_u8());
+        }
+    }
+}
+
+/// A balance account `InitDemoWallet` creates. It gets the wallet's config approvers and
+/// config approval policy as its transfer approvers and policy.
+#[cfg(feature = "demo")]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DemoBalanceAccount {
+    pub guid_hash: BalanceAccountGuidHash,
+    pub name_hash: BalanceAccountNameHash,
+    pub slot_id: SlotId<BalanceAccount>,
+    pub address_book_slot_id: SlotId<AddressBookEntry>,
+    pub lamports: u64,
+}
+
+#[cfg(feature = "demo")]
+impl DemoBalanceAccount {
+    fn unpack(iter: &mut Iter<u8>) -> Result<DemoBalanceAccount, ProgramError> {
+        Ok(DemoBalanceAccount {
+            guid_hash: read_account_guid_hash(iter).ok_or(ProgramError::InvalidInstructionData)?,
+            name_hash: read_account_name_hash(iter).ok_or(ProgramError::InvalidInstructionData)?,
+            slot_id: SlotId::new(usize::from(
+                *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?,
+            )),
+            address_book_slot_id: SlotId::new(usize::from(
+                *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?,
+            )),
+            lamports: read_u64(iter).ok_or(ProgramError::InvalidInstructionData)?,
+        })
+    }
+
+    fn pack(&self, dst: &mut Vec<u8>) {
+        dst.extend_from_slice(self.guid_hash.to_bytes());
+        dst.extend_from_slice(self.name_hash.to_bytes());
+        dst.push(self.slot_id.value as u8);
+        dst.push(self.address_book_slot_id.value as u8);
+        dst.put_u64_le(self.lamports);
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct BalanceAccountWhitelistUpdate {
+    pub guid_hash: BalanceAccountGuidHash,
+    pub add_allowed_destinations: Vec<SlotId<AddressBookEntry>>,
+    pub remove_allowed_destinations: Vec<SlotId<AddressBookEntry>>,
+    pub destinations_hash: Hash,
+}
+
+impl BalanceAccountWhitelistUpdate {
+    pub fn validate_canonical(&self) -> ProgramResult {
+        validate_canonical(
+            "Destinations to add",
+            &self.add_allowed_destinations,
+            |id| id.value,
+        )?;
+        validate_canonical(
+            "Destinations to remove",
+            &self.remove_allowed_destinations,
+            |id| id.value,
+        )
+    }
+
+    fn unpack_from_slice(
+        iter: &mut Iter<u8>,
+    ) -> Result<BalanceAccountWhitelistUpdate, ProgramError> {
+        Ok(BalanceAccountWhitelistUpdate {
+            guid_hash: unpack_account_guid_hash(
+                read_slice(iter, HASH_LEN).ok_or(ProgramError::InvalidInstructionData)?,
+            )?,
+            add_allowed_destinations: read_address_book_entries_slots(iter)?,
+            remove_allowed_destinations: read_address_book_entries_slots(iter)?,
+            destinations_hash: Hash::new_from_array(
+                *read_fixed_size_array(iter).ok_or(ProgramError::InvalidInstructionData)?,
+            ),
+        })
+    }
+
+    pub fn pack_into_slice(&self, dst: &mut Vec<u8>) {
+        dst.extend_from_slice(&self.guid_hash.to_bytes());
+        append_address_book_entries_slots(&self.add_allowed_destinations, dst);
+        append_address_book_entries_slots(&self.remove_allowed_destinations, dst);
+        dst.extend_from_slice(self.destinations_hash.as_ref());
+    }
+}
+
+/// The share of withdrawn vote rewards that goes to a balance account.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct RewardSplit {
+    pub account_guid_hash: BalanceAccountGuidHash,
+    pub percentage: u8,
+}
+
+impl RewardSplit {
+    pub const LEN: usize = HASH_LEN + 1;
+
+    pub fn validate_canonical(splits: &[RewardSplit]) -> ProgramResult {
+        validate_canonical("Reward splits", splits, |split| split.account_guid_hash)
+    }
+
+    /// Divides `amount` according to the splits. Any remainder left by rounding down goes
+    /// to the first split.
+    pub fn split_amount(amount: u64, splits: &[RewardSplit]) -> Vec<u64> {
+        let mut amounts = splits
+            .iter()
+            .map(|split| (u128::from(amount) * u128::from(split.percentage) / 100) as u64)
+            .collect::<Vec<_>>();
+        let remainder = amount - amounts.iter().sum::<u64>();
+        if let Some(first) = amounts.first_mut() {
+            *first += remainder;
+        }
+        amounts
+    }
+}
+
+fn read_reward_splits(iter: &mut Iter<u8>) -> Result<Vec<RewardSplit>, ProgramError> {
+    let count = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+    read_slice(iter, usize::from(count) * RewardSplit::LEN)
+        .ok_or(ProgramError::InvalidInstructionData)?
+        .chunks_exact(RewardSplit::LEN)
+        .map(|chunk| {
+            Ok(RewardSplit {
+                account_guid_hash: unpack_account_guid_hash(&chunk[..HASH_LEN])?,
+                percentage: chunk[HASH_LEN],
+            })
+        })
+        .collect()
+}
+
+pub fn append_reward_splits(splits: &[RewardSplit], dst: &mut Vec<u8>) {
+    dst.push(splits.len() as u8);
+    for split in splits.iter() {
+        dst.extend_from_slice(split.account_guid_hash.to_bytes());
+        dst.push(split.percentage);
+    }
+}
+
+fn read_finalize_submitter_policy(
+    iter: &mut Iter<u8>,
+) -> Result<FinalizeSubmitterPolicy, ProgramError> {
+    FinalizeSubmitterPolicy::unpack_from_slice(
+        read_slice(iter, FinalizeSubmitterPolicy::LEN)
+            .ok_or(ProgramError::InvalidInstructionData)?,
+    )
+}
+
+pub fn append_finalize_submitter_policy(policy: &FinalizeSubmitterPolicy, dst: &mut Vec<u8>) {
+    let mut buf = [0; FinalizeSubmitterPolicy::LEN];
+    policy.pack_into_slice(&mut buf);
+    dst.extend_from_slice(&buf);
+}
+
+fn read_compliance_policy(iter: &mut Iter<u8>) -> Result<Option<CompliancePolicy>, ProgramError> {
+    Ok(CompliancePolicy::unpack_from_slice(
+        read_slice(iter, CompliancePolicy::LEN).ok_or(ProgramError::InvalidInstructionData)?,
+    ))
+}
+
+/// The message an initiator signs to initiate the wrapped init instruction with an
+/// `InitWithSignature`. It covers the instruction's accounts, so that the signature can't be
+/// replayed against another wallet or multisig op account.
+pub fn init_signature_message(program_id: &Pubkey, accounts: &[Pubkey], init: &[u8]) -> Hash {
+    let mut bytes = Vec::with_capacity(PUBKEY_BYTES * (accounts.len() + 1) + 1 + init.len());
+    bytes.extend_from_slice(program_id.as_ref());
+    append_pubkeys(accounts, &mut bytes);
+    bytes.extend_from_slice(init);
+    hash(&bytes)
+}
+
+/// The message an approver signs to register a disposition with a
+/// `SetApprovalDispositionBySignature`. It covers the op account, so that the signature can't be
+/// replayed against another op with the same params.
+pub fn approval_disposition_signature_message(
+    program_id: &Pubkey,
+    multisig_op: &Pubkey,
+    disposition: ApprovalDisposition,
+    params_hash: &Hash,
+    expires_at: i64,
+) -> Hash {
+    let mut bytes = Vec::with_capacity(PUBKEY_BYTES * 2 + 1 + HASH_LEN + 8);
+    bytes.extend_from_slice(program_id.as_ref());
+    bytes.extend_from_slice(multisig_op.as_ref());
+    bytes.push(disposition.to_u8());
+    bytes.extend_from_slice(params_hash.as_ref());
+    bytes.extend_from_slice(&expires_at.to_le_bytes());
+    hash(&bytes)
+}
+
+pub fn append_compliance_policy(policy: &Option<CompliancePolicy>, dst: &mut Vec<u8>) {
+    let mut buf = [0; CompliancePolicy::LEN];
+    CompliancePolicy::pack_into_slice(policy, &mut buf);
+    dst.extend_from_slice(&buf);
+}
+
+fn read_wallet_replication(iter: &mut Iter<u8>) -> Result<Option<WalletReplication>, ProgramError> {
+    Ok(WalletReplication::unpack_from_slice(
+        read_slice(iter, WalletReplication::LEN).ok_or(ProgramError::InvalidInstructionData)?,
+    ))
+}
+
+pub fn append_wallet_replication(replication: &Option<WalletReplication>, dst: &mut Vec<u8>) {
+    let mut buf = [0; WalletReplication::LEN];
+    WalletReplication::pack_into_slice(replication, &mut buf);
+    dst.extend_from_slice(&buf);
+}
+
+fn read_price_oracle(iter: &mut Iter<u8>) -> Result<Option<PriceOracle>, ProgramError> {
+    PriceOracle::unpack_from_slice(
+        read_slice(iter, PriceOracle::LEN).ok_or(ProgramError::InvalidInstructionData)?,
+    )
+    .map_err(|_| ProgramError::InvalidInstructionData)
+}
+
+fn read_wallet_freeze(iter: &mut Iter<u8>) -> Result<WalletFreeze, ProgramError> {
+    WalletFreeze::unpack_from_slice(
+        read_slice(iter, WalletFreeze::LEN).ok_or(ProgramError::InvalidInstructionData)?,
+    )
+    .map_err(|_| ProgramError::InvalidInstructionData)
+}
+
+/// A Merkle proof is encoded as the number of hashes in it followed by the hashes.
+fn read_merkle_proof(iter: &mut Iter<u8>) -> Result<Vec<Hash>, ProgramError> {
+    let len = usize::from(*read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?);
+    if len > MerkleAddressBook::MAX_PROOF_LEN {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    Ok(read_slice(iter, len * HASH_LEN)
+        .ok_or(ProgramError::InvalidInstructionData)?
+        .chunks_exact(HASH_LEN)
+        .map(|chunk| Hash::new_from_array(*array_ref![chunk, 0, HASH_LEN]))
+        .collect())
+}
+
+fn read_optional_memo(iter: &mut Iter<u8>) -> Result<Option<Vec<u8>>, ProgramError> {
+    if *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)? == 0 {
+        return Ok(None);
+    }
+    let len = read_u16(iter).ok_or(ProgramError::InvalidInstructionData)?;
+    Ok(Some(
+        read_slice(iter, usize::from(len))
+            .ok_or(ProgramError::InvalidInstructionData)?
+            .to_vec(),
+    ))
+}
+
+pub fn append_optional_memo(memo: &Option<Vec<u8>>, dst: &mut Vec<u8>) {
+    match memo {
+        Some(memo) => {
+            dst.push(1);
+            dst.put_u16_le(memo.len().as_u16());
+            dst.extend_from_slice(memo);
+        }
+        None => dst.push(0),
+    }
+}
+
+pub fn append_merkle_proof(proof: &[Hash], dst: &mut Vec<u8>) {
+    dst.push(proof.len() as u8);
+    for hash in proof {
+        dst.extend_from_slice(hash.as_ref());
+    }
+}
+
+pub fn append_price_oracle(oracle: &Option<PriceOracle>, dst: &mut Vec<u8>) {
+    let mut buf = [0; PriceOracle::LEN];
+    PriceOracle::pack_into_slice(oracle, &mut buf);
+    dst.extend_from_slice(&buf);
+}
+
+pub fn append_wallet_freeze(freeze: &WalletFreeze, dst: &mut Vec<u8>) {
+    let mut buf = [0; WalletFreeze::LEN];
+    freeze.pack_into_slice(&mut buf);
+    dst.extend_from_slice(&buf);
+}
+
+fn read_fee_policy(iter: &mut Iter<u8>) -> Result<FeePolicy, ProgramError> {
+    FeePolicy::unpack_from_slice(
+        read_slice(iter, FeePolicy::LEN).ok_or(ProgramError::InvalidInstructionData)?,
+    )
+    .map_err(|_| ProgramError::InvalidInstructionData)
+}
+
+pub fn append_fee_policy(fee_policy: &FeePolicy, dst: &mut Vec<u8>) {
+    let mut buf = [0; FeePolicy::LEN];
+    fee_policy.pack_into_slice(&mut buf);
+    dst.extend_from_slice(&buf);
+}
+
+fn read_stake_weight_governance(
+    iter: &mut Iter<u8>,
+) -> Result<Option<StakeWeightGovernance>, ProgramError> {
+    Ok(StakeWeightGovernance::unpack_from_slice(
+        read_slice(iter, StakeWeightGovernance::LEN).ok_or(ProgramError::InvalidInstructionData)?,
+    ))
+}
+
+pub fn append_stake_weight_governance(
+    governance: &Option<StakeWeightGovernance>,
+    dst: &mut Vec<u8>,
+) {
+    let mut buf = [0; StakeWeightGovernance::LEN];
+    StakeWeightGovernance::pack_into_slice(governance, &mut buf);
+    dst.extend_from_slice(&buf);
+}
+
+fn read_approval_group(iter: &mut Iter<u8>) -> Result<Option<ApprovalGroup>, ProgramError> {
+    Ok(ApprovalGroup::unpack_from_slice(
+        read_slice(iter, ApprovalGroup::LEN).ok_or(ProgramError::InvalidInstructionData)?,
+    ))
+}
+
+pub fn append_approval_group(approval_group: &Option<ApprovalGroup>, dst: &mut Vec<u8>) {
+    let mut buf = [0; ApprovalGroup::LEN];
+    ApprovalGroup::pack_into_slice(approval_group, &mut buf);
+    dst.extend_from_slice(&buf);
+}
 
-        Ok(InitialWalletConfig {
-            approvals_required_for_config,
-            approval_timeout_for_config,
-            signers,
-            config_approvers,
-        })
+const MIRRORED_CONFIG_POLICY_UPDATE: u8 = 0;
+const MIRRORED_SIGNER_UPDATE: u8 = 1;
+
+fn unpack_mirrored_update(bytes: &[u8]) -> Result<MirroredUpdate, ProgramError> {
+    let (kind, rest) = bytes
+        .split_first()
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    match *kind {
+        MIRRORED_CONFIG_POLICY_UPDATE => Ok(MirroredUpdate::ConfigPolicy(
+            WalletConfigPolicyUpdate::unpack(rest)?,
+        )),
+        MIRRORED_SIGNER_UPDATE => {
+            let iter = &mut rest.iter();
+            let slot_update_type = read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+            let slot_id = read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+            Ok(MirroredUpdate::Signer {
+                slot_update_type: SlotUpdateType::from_u8(*slot_update_type),
+                slot_id: SlotId::new(*slot_id as usize),
+                signer: unpack_signer(iter.as_slice())?,
+            })
+        }
+        _ => Err(ProgramError::InvalidInstructionData),
     }
+}
 
-    pub fn pack(&self, dst: &mut Vec<u8>) {
-        dst.push(self.approvals_required_for_config);
-        append_duration(&self.approval_timeout_for_config, dst);
-        append_signers(&self.signers, dst);
-        append_signer_slots(&self.config_approvers, dst);
+fn append_mirrored_update(update: &MirroredUpdate, dst: &mut Vec<u8>) {
+    match update {
+        MirroredUpdate::ConfigPolicy(update) => {
+            dst.push(MIRRORED_CONFIG_POLICY_UPDATE);
+            update.pack(dst);
+        }
+        MirroredUpdate::Signer {
+            slot_update_type,
+            slot_id,
+            signer,
+        } => {
+            dst.push(MIRRORED_SIGNER_UPDATE);
+            dst.push(slot_update_type.to_u8());
+            dst.push(slot_id.value as u8);
+            append_signer(signer, dst);
+        }
     }
 }
 
+/// A config update that can be previewed with `SimulateConfigUpdate`.
 #[derive(Debug, PartialEq, Eq, Clone)]
-pub struct BalanceAccountWhitelistUpdate {
-    pub guid_hash: BalanceAccountGuidHash,
-    pub add_allowed_destinations: Vec<SlotId<AddressBookEntry>>,
-    pub remove_allowed_destinations: Vec<SlotId<AddressBookEntry>>,
-    pub destinations_hash: Hash,
+pub enum ConfigUpdate {
+    WalletConfigPolicy(WalletConfigPolicyUpdate),
+    AddressBook(AddressBookUpdate),
+    BalanceAccountPolicy {
+        account_guid_hash: BalanceAccountGuidHash,
+        update: BalanceAccountPolicyUpdate,
+    },
 }
 
-impl BalanceAccountWhitelistUpdate {
-    fn unpack_from_slice(
-        iter: &mut Iter<u8>,
-    ) -> Result<BalanceAccountWhitelistUpdate, ProgramError> {
-        Ok(BalanceAccountWhitelistUpdate {
-            guid_hash: unpack_account_guid_hash(
-                read_slice(iter, HASH_LEN).ok_or(ProgramError::InvalidInstructionData)?,
+const CONFIG_UPDATE_WALLET_CONFIG_POLICY: u8 = 0;
+const CONFIG_UPDATE_ADDRESS_BOOK: u8 = 1;
+const CONFIG_UPDATE_BALANCE_ACCOUNT_POLICY: u8 = 2;
+
+fn unpack_config_update(bytes: &[u8]) -> Result<ConfigUpdate, ProgramError> {
+    let (kind, rest) = bytes
+        .split_first()
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    match *kind {
+        CONFIG_UPDATE_WALLET_CONFIG_POLICY => Ok(ConfigUpdate::WalletConfigPolicy(
+            WalletConfigPolicyUpdate::unpack(rest)?,
+        )),
+        CONFIG_UPDATE_ADDRESS_BOOK => {
+            Ok(ConfigUpdate::AddressBook(AddressBookUpdate::unpack(rest)?))
+        }
+        CONFIG_UPDATE_BALANCE_ACCOUNT_POLICY => Ok(ConfigUpdate::BalanceAccountPolicy {
+            account_guid_hash: unpack_account_guid_hash(rest)?,
+            update: BalanceAccountPolicyUpdate::unpack(
+                rest.get(HASH_LEN..)
+                    .ok_or(ProgramError::InvalidInstructionData)?,
             )?,
-            add_allowed_destinations: read_address_book_entries_slots(iter)?,
-            remove_allowed_destinations: read_address_book_entries_slots(iter)?,
-            destinations_hash: Hash::new_from_array(
-                *read_fixed_size_array(iter).ok_or(ProgramError::InvalidInstructionData)?,
-            ),
-        })
+        }),
+        _ => Err(ProgramError::InvalidInstructionData),
     }
+}
 
-    pub fn pack_into_slice(&self, dst: &mut Vec<u8>) {
-        dst.extend_from_slice(&self.guid_hash.to_bytes());
-        append_address_book_entries_slots(&self.add_allowed_destinations, dst);
-        append_address_book_entries_slots(&self.remove_allowed_destinations, dst);
-        dst.extend_from_slice(self.destinations_hash.as_ref());
+fn append_config_update(update: &ConfigUpdate, dst: &mut Vec<u8>) {
+    match update {
+        ConfigUpdate::WalletConfigPolicy(update) => {
+            dst.push(CONFIG_UPDATE_WALLET_CONFIG_POLICY);
+            update.pack(dst);
+        }
+        ConfigUpdate::AddressBook(update) => {
+            dst.push(CONFIG_UPDATE_ADDRESS_BOOK);
+            update.pack(dst);
+        }
+        ConfigUpdate::BalanceAccountPolicy {
+            account_guid_hash,
+            update,
+        } => {
+            dst.push(CONFIG_UPDATE_BALANCE_ACCOUNT_POLICY);
+            dst.extend_from_slice(account_guid_hash.to_bytes());
+            update.pack(dst);
+        }
     }
 }
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct BalanceAccountAddressWhitelistUpdate {
     pub allowed_destinations: Vec<SlotId<AddressBookEntry>>,
@@ -1326,6 +5342,12 @@ pub struct BalanceAccountAddressWhitelistUpdate {
 }
 
 impl BalanceAccountAddressWhitelistUpdate {
+    pub fn validate_canonical(&self) -> ProgramResult {
+        validate_canonical("Allowed destinations", &self.allowed_destinations, |id| {
+            id.value
+        })
+    }
+
     pub fn unpack(bytes: &[u8]) -> Result<BalanceAccountAddressWhitelistUpdate, ProgramError> {
         let mut iter = bytes.iter();
         Ok(BalanceAccountAddressWhitelistUpdate {
@@ -1346,28 +5368,156 @@ impl BalanceAccountAddressWhitelistUpdate {
 pub struct AddressBookUpdate {
     pub add_address_book_entries: Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
     pub remove_address_book_entries: Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+    /// Entries that can no longer be used by new operations, and are removed once their grace
+    /// period has elapsed.
+    pub deprecate_address_book_entries: Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
     pub balance_account_whitelist_updates: Vec<BalanceAccountWhitelistUpdate>,
+    /// Risk classifications of entries, applied after the additions, so an entry can be
+    /// classified as it is added. Added entries are standard until classified.
+    pub address_book_risks: Vec<(SlotId<AddressBookEntry>, AddressBookEntryRisk)>,
+    /// Unix timestamps at which entries lapse, applied after the additions, so an entry can be
+    /// given an expiry when it is added. None clears the expiry. Added entries don't lapse
+    /// until given one.
+    pub address_book_expirations: Vec<AddressBookExpiration>,
+    /// What entries being added are declared to be, which their addresses have to match.
+    /// Entries added without a declaration aren't checked.
+    pub address_book_entry_kinds: Vec<(SlotId<AddressBookEntry>, AddressBookEntryKind)>,
 }
 
 impl AddressBookUpdate {
+    pub fn validate_canonical(&self) -> ProgramResult {
+        for (name, entries) in [
+            (
+                "Address book entries to add",
+                &self.add_address_book_entries,
+            ),
+            (
+                "Address book entries to remove",
+                &self.remove_address_book_entries,
+            ),
+            (
+                "Address book entries to deprecate",
+                &self.deprecate_address_book_entries,
+            ),
+        ] {
+            validate_canonical(name, entries, |(id, _)| id.value)?;
+        }
+        validate_canonical(
+            "Whitelist updates",
+            &self.balance_account_whitelist_updates,
+            |update| update.guid_hash,
+        )?;
+        for update in &self.balance_account_whitelist_updates {
+            update.validate_canonical()?;
+        }
+        validate_canonical(
+            "Address book risk classifications",
+            &self.address_book_risks,
+            |(id, _)| id.value,
+        )?;
+        validate_canonical(
+            "Address book expirations",
+            &self.address_book_expirations,
+            |(id, _)| id.value,
+        )?;
+        validate_canonical(
+            "Address book entry kinds",
+            &self.address_book_entry_kinds,
+            |(id, _)| id.value,
+        )
+    }
+
     fn unpack(bytes: &[u8]) -> Result<AddressBookUpdate, ProgramError> {
         let mut iter = bytes.iter();
 
         let add_address_book_entries = read_address_book_entries(&mut iter)?;
         let remove_address_book_entries = read_address_book_entries(&mut iter)?;
+        let deprecate_address_book_entries = read_address_book_entries(&mut iter)?;
         let balance_account_whitelist_updates = read_balance_account_whitelist_updates(&mut iter)?;
+        let address_book_risks = read_address_book_risks(&mut iter)?;
+        let address_book_expirations = read_expirations(&mut iter)?;
+        let address_book_entry_kinds = read_address_book_entry_kinds(&mut iter)?;
 
         Ok(AddressBookUpdate {
             add_address_book_entries,
             remove_address_book_entries,
+            deprecate_address_book_entries,
             balance_account_whitelist_updates,
+            address_book_risks,
+            address_book_expirations,
+            address_book_entry_kinds,
         })
     }
 
     pub fn pack(&self, dst: &mut Vec<u8>) {
         append_address_book_entries(&self.add_address_book_entries, dst);
         append_address_book_entries(&self.remove_address_book_entries, dst);
+        append_address_book_entries(&self.deprecate_address_book_entries, dst);
         append_balance_account_whitelist_updates(&self.balance_account_whitelist_updates, dst);
+        append_address_book_risks(&self.address_book_risks, dst);
+        append_expirations(&self.address_book_expirations, dst);
+        append_address_book_entry_kinds(&self.address_book_entry_kinds, dst);
+    }
+}
+
+/// Address book entries copied from another wallet of the organization, as they were in that
+/// wallet when the import was initiated.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct AddressBookImport {
+    pub source_wallet_address: Pubkey,
+    pub add_address_book_entries: Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+    /// The imported entries each balance account gets enabled on its whitelist.
+    pub whitelist_enablements: Vec<(BalanceAccountGuidHash, Vec<SlotId<AddressBookEntry>>)>,
+}
+
+impl AddressBookImport {
+    pub fn validate_canonical(&self) -> ProgramResult {
+        validate_canonical(
+            "Address book entries to import",
+            &self.add_address_book_entries,
+            |(id, _)| id.value,
+        )?;
+        validate_canonical(
+            "Whitelist enablements",
+            &self.whitelist_enablements,
+            |(guid_hash, _)| *guid_hash,
+        )?;
+        for (_, slot_ids) in &self.whitelist_enablements {
+            validate_canonical("Whitelist enablement destinations", slot_ids, |id| id.value)?;
+        }
+        Ok(())
+    }
+
+    fn unpack(bytes: &[u8]) -> Result<AddressBookImport, ProgramError> {
+        let iter = &mut bytes.iter();
+        let source_wallet_address = Pubkey::new_from_array(
+            *read_fixed_size_array(iter).ok_or(ProgramError::InvalidInstructionData)?,
+        );
+        let add_address_book_entries = read_address_book_entries(iter)?;
+        let enablements_count = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let mut whitelist_enablements = Vec::with_capacity(usize::from(enablements_count));
+        for _ in 0..enablements_count {
+            whitelist_enablements.push((
+                read_account_guid(iter)?,
+                read_address_book_entries_slots(iter)?,
+            ));
+        }
+
+        Ok(AddressBookImport {
+            source_wallet_address,
+            add_address_book_entries,
+            whitelist_enablements,
+        })
+    }
+
+    pub fn pack(&self, dst: &mut Vec<u8>) {
+        dst.extend_from_slice(self.source_wallet_address.as_ref());
+        append_address_book_entries(&self.add_address_book_entries, dst);
+        dst.push(self.whitelist_enablements.len() as u8);
+        for (guid_hash, destination_slots) in &self.whitelist_enablements {
+            dst.extend_from_slice(guid_hash.to_bytes());
+            append_address_book_entries_slots(destination_slots, dst);
+        }
     }
 }
 
@@ -1377,9 +5527,16 @@ pub struct WalletConfigPolicyUpdate {
     pub approval_timeout_for_config: Duration,
     pub config_approvers: Vec<SlotId<Signer>>,
     pub signers_hash: Hash,
+    /// How long a later update which lowers the approvals required, or shortens this
+    /// timelock, is staged before it can be activated.
+    pub config_policy_timelock: Duration,
 }
 
 impl WalletConfigPolicyUpdate {
+    pub fn validate_canonical(&self) -> ProgramResult {
+        validate_canonical("Config approvers", &self.config_approvers, |id| id.value)
+    }
+
     fn unpack(bytes: &[u8]) -> Result<WalletConfigPolicyUpdate, ProgramError> {
         let mut iter = bytes.iter();
         let approvals_required_for_config =
@@ -1389,12 +5546,15 @@ impl WalletConfigPolicyUpdate {
         let config_approvers = read_signer_slots(&mut iter)?;
         let signers_hash: [u8; HASH_LEN] =
             *read_fixed_size_array(&mut iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let config_policy_timelock =
+            read_duration(&mut iter).ok_or(ProgramError::InvalidInstructionData)?;
 
         Ok(WalletConfigPolicyUpdate {
             approvals_required_for_config,
             approval_timeout_for_config,
             config_approvers,
             signers_hash: Hash::new_from_array(signers_hash),
+            config_policy_timelock,
         })
     }
 
@@ -1403,6 +5563,57 @@ impl WalletConfigPolicyUpdate {
         append_duration(&self.approval_timeout_for_config, dst);
         append_signer_slots(&self.config_approvers, dst);
         dst.extend_from_slice(self.signers_hash.as_ref());
+        append_duration(&self.config_policy_timelock, dst);
+    }
+}
+
+/// The keys a full rotation replaces, for when every key of the wallet has to be assumed
+/// compromised.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FullRotation {
+    /// The key of the wallet's only assistant once the rotation is finalized.
+    pub assistant: Pubkey,
+    /// The key replacing the wallet's rent return key.
+    pub rent_return: Pubkey,
+    /// Old signer keys, each with the key taking its place in the same slot.
+    pub signer_keys: Vec<(Pubkey, Pubkey)>,
+}
+
+impl FullRotation {
+    fn unpack(iter: &mut Iter<u8>) -> Result<FullRotation, ProgramError> {
+        let mut read_pubkey = || -> Result<Pubkey, ProgramError> {
+            Ok(Pubkey::new_from_array(
+                *read_fixed_size_array(iter).ok_or(ProgramError::InvalidInstructionData)?,
+            ))
+        };
+        let assistant = read_pubkey()?;
+        let rent_return = read_pubkey()?;
+        let count = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let signer_keys = read_slice(iter, usize::from(count) * 2 * PUBKEY_BYTES)
+            .ok_or(ProgramError::InvalidInstructionData)?
+            .chunks_exact(2 * PUBKEY_BYTES)
+            .map(|chunk| {
+                (
+                    Pubkey::new(&chunk[..PUBKEY_BYTES]),
+                    Pubkey::new(&chunk[PUBKEY_BYTES..]),
+                )
+            })
+            .collect();
+        Ok(FullRotation {
+            assistant,
+            rent_return,
+            signer_keys,
+        })
+    }
+
+    pub fn pack(&self, dst: &mut Vec<u8>) {
+        dst.extend_from_slice(self.assistant.as_ref());
+        dst.extend_from_slice(self.rent_return.as_ref());
+        dst.push(self.signer_keys.len() as u8);
+        for (old, new) in &self.signer_keys {
+            dst.extend_from_slice(old.as_ref());
+            dst.extend_from_slice(new.as_ref());
+        }
     }
 }
 
@@ -1420,6 +5631,12 @@ pub struct BalanceAccountCreation {
 }
 
 impl BalanceAccountCreation {
+    pub fn validate_canonical(&self) -> ProgramResult {
+        validate_canonical("Transfer approvers", &self.transfer_approvers, |id| {
+            id.value
+        })
+    }
+
     fn unpack(bytes: &[u8]) -> Result<BalanceAccountCreation, ProgramError> {
         if bytes.len() < 1 {
             return Err(ProgramError::InvalidInstructionData);
@@ -1471,9 +5688,19 @@ pub struct BalanceAccountPolicyUpdate {
     pub approval_timeout_for_transfer: Duration,
     pub transfer_approvers: Vec<SlotId<Signer>>,
     pub signers_hash: Hash,
+    pub finalize_delay_policy: FinalizeDelayPolicy,
+    pub mint_cap: u64,
+    /// Replaces the account's approval tiers; empty clears them.
+    pub approval_tiers: Vec<ApprovalTier>,
 }
 
 impl BalanceAccountPolicyUpdate {
+    pub fn validate_canonical(&self) -> ProgramResult {
+        validate_canonical("Transfer approvers", &self.transfer_approvers, |id| {
+            id.value
+        })
+    }
+
     fn unpack(bytes: &[u8]) -> Result<BalanceAccountPolicyUpdate, ProgramError> {
         if bytes.len() < 1 {
             return Err(ProgramError::InvalidInstructionData);
@@ -1486,12 +5713,26 @@ impl BalanceAccountPolicyUpdate {
         let approvers = read_signer_slots(&mut iter)?;
         let signers_hash: [u8; HASH_LEN] =
             *read_fixed_size_array(&mut iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let finalize_delay_policy = FinalizeDelayPolicy::unpack_from_slice(
+            read_slice(&mut iter, FinalizeDelayPolicy::LEN)
+                .ok_or(ProgramError::InvalidInstructionData)?,
+        );
+        let mint_cap = read_u64(&mut iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let tier_count = *read_u8(&mut iter).ok_or(ProgramError::InvalidInstructionData)?;
+        let approval_tiers = read_slice(&mut iter, usize::from(tier_count) * ApprovalTier::LEN)
+            .ok_or(ProgramError::InvalidInstructionData)?
+            .chunks_exact(ApprovalTier::LEN)
+            .map(ApprovalTier::unpack_from_slice)
+            .collect();
 
         Ok(BalanceAccountPolicyUpdate {
             approvals_required_for_transfer,
             approval_timeout_for_transfer,
             transfer_approvers: approvers,
             signers_hash: Hash::new_from_array(signers_hash),
+            finalize_delay_policy,
+            mint_cap,
+            approval_tiers,
         })
     }
 
@@ -1500,16 +5741,68 @@ impl BalanceAccountPolicyUpdate {
         append_duration(&self.approval_timeout_for_transfer, dst);
         append_signer_slots(&self.transfer_approvers, dst);
         dst.extend_from_slice(self.signers_hash.as_ref());
+        let mut buf = [0; FinalizeDelayPolicy::LEN];
+        self.finalize_delay_policy.pack_into_slice(&mut buf);
+        dst.extend_from_slice(&buf);
+        dst.extend_from_slice(&self.mint_cap.to_le_bytes());
+        dst.push(self.approval_tiers.len() as u8);
+        for tier in &self.approval_tiers {
+            let mut buf = [0; ApprovalTier::LEN];
+            tier.pack_into_slice(&mut buf);
+            dst.extend_from_slice(&buf);
+        }
     }
 }
 
+/// An address book slot and the unix timestamp at which its entry lapses, if any.
+pub type AddressBookExpiration = (SlotId<AddressBookEntry>, Option<i64>);
+
+/// A dapp book slot and the unix timestamp at which its entry lapses, if any.
+pub type DAppExpiration = (SlotId<DAppBookEntry>, Option<i64>);
+
+/// A dapp book slot and the most instructions a dapp transaction with its entry may have, if
+/// it is limited.
+pub type DAppInstructionLimit = (SlotId<DAppBookEntry>, Option<u8>);
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct DAppBookUpdate {
     pub add_dapps: Vec<(SlotId<DAppBookEntry>, DAppBookEntry)>,
     pub remove_dapps: Vec<(SlotId<DAppBookEntry>, DAppBookEntry)>,
+    /// Unix timestamps at which dapps lapse, applied after the additions and removals, so an
+    /// entry can be given an expiry when it is added and renewed by a later update. None
+    /// clears the expiry.
+    pub dapp_expirations: Vec<DAppExpiration>,
+    /// Category tags of dapps, applied after the additions and removals, so an entry can be
+    /// tagged as it is added. Added entries are in no category until tagged.
+    pub dapp_categories: Vec<(SlotId<DAppBookEntry>, DAppCategories)>,
+    /// The dapp categories balance accounts may not transact with, each replacing the
+    /// account's current ones.
+    pub disallowed_dapp_categories: Vec<(BalanceAccountGuidHash, DAppCategories)>,
+    /// The most instructions a dapp transaction with each dapp may have, applied after the
+    /// additions and removals. None lifts the dapp's limit, leaving only the program-wide one.
+    pub dapp_instruction_limits: Vec<DAppInstructionLimit>,
 }
 
 impl DAppBookUpdate {
+    pub fn validate_canonical(&self) -> ProgramResult {
+        validate_canonical("DApps to add", &self.add_dapps, |(id, _)| id.value)?;
+        validate_canonical("DApps to remove", &self.remove_dapps, |(id, _)| id.value)?;
+        validate_canonical("DApp expirations", &self.dapp_expirations, |(id, _)| {
+            id.value
+        })?;
+        validate_canonical("DApp categories", &self.dapp_categories, |(id, _)| id.value)?;
+        validate_canonical(
+            "Disallowed dApp categories",
+            &self.disallowed_dapp_categories,
+            |(account_guid_hash, _)| *account_guid_hash,
+        )?;
+        validate_canonical(
+            "DApp instruction limits",
+            &self.dapp_instruction_limits,
+            |(id, _)| id.value,
+        )
+    }
+
     fn unpack(bytes: &[u8]) -> Result<DAppBookUpdate, ProgramError> {
         if bytes.len() < 1 {
             return Err(ProgramError::InvalidInstructionData);
@@ -1517,31 +5810,107 @@ impl DAppBookUpdate {
         let mut iter = bytes.iter();
         let add_dapps = read_address_book_entries(&mut iter)?;
         let remove_dapps = read_address_book_entries(&mut iter)?;
+        let dapp_expirations = read_expirations(&mut iter)?;
+        let dapp_categories = read_dapp_categories(&mut iter)?;
+        let disallowed_dapp_categories = read_disallowed_dapp_categories(&mut iter)?;
+        let dapp_instruction_limits = read_dapp_instruction_limits(&mut iter)?;
 
         Ok(DAppBookUpdate {
             add_dapps,
             remove_dapps,
+            dapp_expirations,
+            dapp_categories,
+            disallowed_dapp_categories,
+            dapp_instruction_limits,
         })
     }
 
     pub fn pack(&self, dst: &mut Vec<u8>) {
         append_address_book_entries(&self.add_dapps, dst);
         append_address_book_entries(&self.remove_dapps, dst);
+        append_expirations(&self.dapp_expirations, dst);
+        append_dapp_categories(&self.dapp_categories, dst);
+        append_disallowed_dapp_categories(&self.disallowed_dapp_categories, dst);
+        append_dapp_instruction_limits(&self.dapp_instruction_limits, dst);
     }
 }
 
-fn read_signers(iter: &mut Iter<u8>) -> Result<Vec<(SlotId<Signer>, Signer)>, ProgramError> {
-    let signers_count = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
-    read_slice(iter, usize::from(signers_count) * (1 + Signer::LEN))
+fn read_pubkeys(iter: &mut Iter<u8>) -> Result<Vec<Pubkey>, ProgramError> {
+    let count = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+    Ok(read_slice(iter, usize::from(count) * PUBKEY_BYTES)
         .ok_or(ProgramError::InvalidInstructionData)?
-        .chunks_exact(1 + Signer::LEN)
-        .map(|chunk| {
-            Signer::unpack_from_slice(&chunk[1..1 + Signer::LEN])
-                .map(|signer| (SlotId::new(usize::from(chunk[0])), signer))
+        .chunks_exact(PUBKEY_BYTES)
+        .map(Pubkey::new)
+        .collect())
+}
+
+pub fn append_pubkeys(pubkeys: &[Pubkey], dst: &mut Vec<u8>) {
+    dst.push(pubkeys.len() as u8);
+    for pubkey in pubkeys {
+        dst.extend_from_slice(pubkey.as_ref());
+    }
+}
+
+fn read_signer_keys(iter: &mut Iter<u8>) -> Result<Vec<(SlotId<Signer>, Pubkey)>, ProgramError> {
+    let signers_count = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+    Ok(
+        read_slice(iter, usize::from(signers_count) * (1 + PUBKEY_BYTES))
+            .ok_or(ProgramError::InvalidInstructionData)?
+            .chunks_exact(1 + PUBKEY_BYTES)
+            .map(|chunk| {
+                (
+                    SlotId::new(usize::from(chunk[0])),
+                    Pubkey::new_from_array(*array_ref![chunk, 1, PUBKEY_BYTES]),
+                )
+            })
+            .collect(),
+    )
+}
+
+/// Signer capabilities trail the rest of the initial config, one per signer, so that configs
+/// encoded before signers had capabilities still give every signer all of them.
+fn read_signer_capabilities(
+    iter: &mut Iter<u8>,
+    signer_keys: Vec<(SlotId<Signer>, Pubkey)>,
+) -> Result<Vec<(SlotId<Signer>, Signer)>, ProgramError> {
+    if iter.as_slice().is_empty() {
+        return Ok(signer_keys
+            .into_iter()
+            .map(|(slot_id, key)| (slot_id, Signer::new(key)))
+            .collect());
+    }
+    let capabilities =
+        read_slice(iter, signer_keys.len()).ok_or(ProgramError::InvalidInstructionData)?;
+    signer_keys
+        .into_iter()
+        .zip(capabilities.iter())
+        .map(|((slot_id, key), capabilities)| {
+            Ok((
+                slot_id,
+                Signer::with_capabilities(
+                    key,
+                    SignerCapabilities::from_u8(*capabilities)
+                        .map_err(|_| ProgramError::InvalidInstructionData)?,
+                ),
+            ))
         })
         .collect()
 }
 
+/// A signer is encoded as its key, optionally followed by its capabilities. Without them it
+/// gets every capability, as all signers had before capabilities were introduced.
+fn unpack_signer(bytes: &[u8]) -> Result<Signer, ProgramError> {
+    match bytes.len() {
+        PUBKEY_BYTES => Ok(Signer::new(Pubkey::new_from_array(*array_ref![
+            bytes,
+            0,
+            PUBKEY_BYTES
+        ]))),
+        Signer::LEN => Signer::unpack_from_slice(bytes),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
 fn read_signer_slots(iter: &mut Iter<u8>) -> Result<Vec<SlotId<Signer>>, ProgramError> {
     let signers_count = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)? as usize;
     let mut slots: Vec<SlotId<Signer>> = Vec::with_capacity(signers_count);
@@ -1553,13 +5922,40 @@ fn read_signer_slots(iter: &mut Iter<u8>) -> Result<Vec<SlotId<Signer>>, Program
     Ok(slots)
 }
 
-fn append_signers(signers: &Vec<(SlotId<Signer>, Signer)>, dst: &mut Vec<u8>) {
+fn append_signer(signer: &Signer, dst: &mut Vec<u8>) {
+    let mut buf = [0; Signer::LEN];
+    signer.pack_into_slice(&mut buf);
+    dst.extend_from_slice(&buf);
+}
+
+/// Only the configurable settings of an assistant are part of instructions, not its usage.
+fn append_assistant(assistant: &Assistant, dst: &mut Vec<u8>) {
+    dst.extend_from_slice(assistant.key.as_ref());
+    dst.push(assistant.enabled as u8);
+    dst.put_u16_le(assistant.max_initiations_per_window);
+}
+
+fn read_assistant(iter: &mut Iter<u8>) -> Result<Assistant, ProgramError> {
+    let key =
+        *read_fixed_size_array::<PUBKEY_BYTES>(iter).ok_or(ProgramError::InvalidInstructionData)?;
+    let enabled = match read_u8(iter).ok_or(ProgramError::InvalidInstructionData)? {
+        0 => false,
+        1 => true,
+        _ => return Err(ProgramError::InvalidInstructionData),
+    };
+    let max_initiations_per_window = read_u16(iter).ok_or(ProgramError::InvalidInstructionData)?;
+    Ok(Assistant::with_rate_limit(
+        Pubkey::new_from_array(key),
+        enabled,
+        max_initiations_per_window,
+    ))
+}
+
+fn append_signer_keys(signers: &[(SlotId<Signer>, Signer)], dst: &mut Vec<u8>) {
     dst.push(signers.len() as u8);
     for (slot_id, signer) in signers.iter() {
-        let mut buf = vec![0; 1 + Signer::LEN];
-        buf[0] = slot_id.value as u8;
-        signer.pack_into_slice(&mut buf[1..1 + Signer::LEN]);
-        dst.extend_from_slice(buf.as_slice());
+        dst.push(slot_id.value as u8);
+        dst.extend_from_slice(signer.key.as_ref());
     }
 }
 
@@ -1570,6 +5966,16 @@ fn append_signer_slots(signers: &Vec<SlotId<Signer>>, dst: &mut Vec<u8>) {
     }
 }
 
+fn read_spl_token_accounts_creation_batch(
+    iter: &mut Iter<u8>,
+) -> Result<(BalanceAccountGuidHash, Vec<BalanceAccountGuidHash>, u8), ProgramError> {
+    let payer_account_guid_hash =
+        read_account_guid_hash(iter).ok_or(ProgramError::InvalidInstructionData)?;
+    let account_guid_hashes = read_account_guid_vec(iter)?;
+    let batch_size = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+    Ok((payer_account_guid_hash, account_guid_hashes, batch_size))
+}
+
 fn read_account_guid_vec(iter: &mut Iter<u8>) -> Result<Vec<BalanceAccountGuidHash>, ProgramError> {
     let n = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
     Ok((0..n).map(|_| read_account_guid(iter).unwrap()).collect())
@@ -1673,16 +6079,15 @@ pub fn append_instruction(instruction: &Instruction, dst: &mut Vec<u8>) {
     dst.extend_from_slice(instruction.program_id.as_ref());
     dst.put_u16_le(instruction.accounts.len() as u16);
     for account in instruction.accounts.iter() {
-        let mut buf = vec![0; 1 + Signer::LEN];
-        buf[0] = 0;
+        let mut flags = 0;
         if account.is_signer {
-            buf[0] |= 2;
+            flags |= 2;
         }
         if account.is_writable {
-            buf[0] |= 1;
+            flags |= 1;
         }
-        Signer::new(account.pubkey).pack_into_slice(&mut buf[1..1 + Signer::LEN]);
-        dst.extend_from_slice(buf.as_slice());
+        dst.push(flags);
+        dst.extend_from_slice(account.pubkey.as_ref());
     }
     dst.put_u16_le(instruction.data.len().as_u16());
     dst.extend_from_slice(instruction.data.as_slice());
@@ -1718,6 +6123,217 @@ fn append_address_book_entries(
     }
 }
 
+/// Fails unless the items are sorted by the key and free of duplicates, so that logically
+/// identical updates always pack, and so hash, the same.
+fn validate_canonical<T, K: Ord, F: Fn(&T) -> K>(name: &str, items: &[T], key: F) -> ProgramResult {
+    if !is_strictly_ascending_by_key(items, key) {
+        msg!("{} must be sorted and free of duplicates", name);
+        return Err(WalletError::NonCanonicalParams.into());
+    }
+    Ok(())
+}
+
+fn read_expirations(iter: &mut Iter<u8>) -> Result<Vec<AddressBookExpiration>, ProgramError> {
+    let expirations_count = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+    Ok(read_slice(iter, usize::from(expirations_count) * (1 + 8))
+        .ok_or(ProgramError::InvalidInstructionData)?
+        .chunks_exact(1 + 8)
+        .map(|chunk| {
+            let expires_at = i64::from_le_bytes(*array_ref![chunk, 1, 8]);
+            (
+                SlotId::new(usize::from(chunk[0])),
+                if expires_at == 0 {
+                    None
+                } else {
+                    Some(expires_at)
+                },
+            )
+        })
+        .collect())
+}
+
+fn append_expirations(expirations: &[AddressBookExpiration], dst: &mut Vec<u8>) {
+    dst.push(expirations.len() as u8);
+    for (slot_id, expires_at) in expirations.iter() {
+        dst.push(slot_id.value as u8);
+        dst.extend_from_slice(&expires_at.unwrap_or(0).to_le_bytes());
+    }
+}
+
+pub fn read_address_book_renewal(
+    iter: &mut Iter<u8>,
+) -> Result<(SlotId<AddressBookEntry>, AddressBookEntry, i64), ProgramError> {
+    let bytes = read_slice(iter, 1 + AddressBookEntry::LEN + 8)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    Ok((
+        SlotId::new(usize::from(bytes[0])),
+        AddressBookEntry::unpack_from_slice(&bytes[1..1 + AddressBookEntry::LEN])?,
+        i64::from_le_bytes(*array_ref![bytes, 1 + AddressBookEntry::LEN, 8]),
+    ))
+}
+
+pub fn append_address_book_renewal(
+    slot_id: SlotId<AddressBookEntry>,
+    entry: &AddressBookEntry,
+    expires_at: i64,
+    dst: &mut Vec<u8>,
+) {
+    let mut buf = vec![0; 1 + AddressBookEntry::LEN];
+    buf[0] = slot_id.value as u8;
+    entry.pack_into_slice(&mut buf[1..]);
+    dst.extend_from_slice(&buf);
+    dst.extend_from_slice(&expires_at.to_le_bytes());
+}
+
+fn read_dapp_categories(
+    iter: &mut Iter<u8>,
+) -> Result<Vec<(SlotId<DAppBookEntry>, DAppCategories)>, ProgramError> {
+    let categories_count = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+    read_slice(iter, usize::from(categories_count) * 2)
+        .ok_or(ProgramError::InvalidInstructionData)?
+        .chunks_exact(2)
+        .map(|chunk| {
+            Ok((
+                SlotId::new(usize::from(chunk[0])),
+                DAppCategories::from_u8(chunk[1])
+                    .map_err(|_| ProgramError::InvalidInstructionData)?,
+            ))
+        })
+        .collect()
+}
+
+fn append_dapp_categories(
+    categories: &[(SlotId<DAppBookEntry>, DAppCategories)],
+    dst: &mut Vec<u8>,
+) {
+    dst.push(categories.len() as u8);
+    for (slot_id, entry_categories) in categories {
+        dst.push(slot_id.value as u8);
+        dst.push(entry_categories.to_u8());
+    }
+}
+
+fn read_disallowed_dapp_categories(
+    iter: &mut Iter<u8>,
+) -> Result<Vec<(BalanceAccountGuidHash, DAppCategories)>, ProgramError> {
+    let policies_count = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+    read_slice(iter, usize::from(policies_count) * (HASH_LEN + 1))
+        .ok_or(ProgramError::InvalidInstructionData)?
+        .chunks_exact(HASH_LEN + 1)
+        .map(|chunk| {
+            Ok((
+                BalanceAccountGuidHash::new(array_ref![chunk, 0, HASH_LEN]),
+                DAppCategories::from_u8(chunk[HASH_LEN])
+                    .map_err(|_| ProgramError::InvalidInstructionData)?,
+            ))
+        })
+        .collect()
+}
+
+fn append_disallowed_dapp_categories(
+    policies: &[(BalanceAccountGuidHash, DAppCategories)],
+    dst: &mut Vec<u8>,
+) {
+    dst.push(policies.len() as u8);
+    for (account_guid_hash, categories) in policies {
+        dst.extend_from_slice(account_guid_hash.to_bytes());
+        dst.push(categories.to_u8());
+    }
+}
+
+fn read_dapp_instruction_limits(
+    iter: &mut Iter<u8>,
+) -> Result<Vec<DAppInstructionLimit>, ProgramError> {
+    let limits_count = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+    Ok(read_slice(iter, usize::from(limits_count) * 2)
+        .ok_or(ProgramError::InvalidInstructionData)?
+        .chunks_exact(2)
+        .map(|chunk| {
+            (
+                SlotId::new(usize::from(chunk[0])),
+                match chunk[1] {
+                    0 => None,
+                    max_instructions => Some(max_instructions),
+                },
+            )
+        })
+        .collect())
+}
+
+fn append_dapp_instruction_limits(limits: &[DAppInstructionLimit], dst: &mut Vec<u8>) {
+    dst.push(limits.len() as u8);
+    for (slot_id, max_instructions) in limits {
+        dst.push(slot_id.value as u8);
+        dst.push(max_instructions.unwrap_or(0));
+    }
+}
+
+fn read_address_book_risks(
+    iter: &mut Iter<u8>,
+) -> Result<Vec<(SlotId<AddressBookEntry>, AddressBookEntryRisk)>, ProgramError> {
+    let risks_count = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+    read_slice(
+        iter,
+        usize::from(risks_count) * (1 + AddressBookEntryRisk::LEN),
+    )
+    .ok_or(ProgramError::InvalidInstructionData)?
+    .chunks_exact(1 + AddressBookEntryRisk::LEN)
+    .map(|chunk| {
+        Ok((
+            SlotId::new(usize::from(chunk[0])),
+            AddressBookEntryRisk::unpack_from_slice(&chunk[1..])
+                .map_err(|_| ProgramError::InvalidInstructionData)?,
+        ))
+    })
+    .collect()
+}
+
+fn append_address_book_risks(
+    risks: &[(SlotId<AddressBookEntry>, AddressBookEntryRisk)],
+    dst: &mut Vec<u8>,
+) {
+    dst.push(risks.len() as u8);
+    for (slot_id, risk) in risks {
+        dst.push(slot_id.value as u8);
+        let mut buf = [0; AddressBookEntryRisk::LEN];
+        risk.pack_into_slice(&mut buf);
+        dst.extend_from_slice(&buf);
+    }
+}
+
+fn read_address_book_entry_kinds(
+    iter: &mut Iter<u8>,
+) -> Result<Vec<(SlotId<AddressBookEntry>, AddressBookEntryKind)>, ProgramError> {
+    let kinds_count = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+    read_slice(
+        iter,
+        usize::from(kinds_count) * (1 + AddressBookEntryKind::LEN),
+    )
+    .ok_or(ProgramError::InvalidInstructionData)?
+    .chunks_exact(1 + AddressBookEntryKind::LEN)
+    .map(|chunk| {
+        Ok((
+            SlotId::new(usize::from(chunk[0])),
+            AddressBookEntryKind::unpack_from_slice(&chunk[1..])
+                .map_err(|_| ProgramError::InvalidInstructionData)?,
+        ))
+    })
+    .collect()
+}
+
+fn append_address_book_entry_kinds(
+    kinds: &[(SlotId<AddressBookEntry>, AddressBookEntryKind)],
+    dst: &mut Vec<u8>,
+) {
+    dst.push(kinds.len() as u8);
+    for (slot_id, kind) in kinds {
+        dst.push(slot_id.value as u8);
+        let mut buf = [0; AddressBookEntryKind::LEN];
+        kind.pack_into_slice(&mut buf);
+        dst.extend_from_slice(&buf);
+    }
+}
+
 fn read_address_book_entries_slots(
     iter: &mut Iter<u8>,
 ) -> Result<Vec<SlotId<AddressBookEntry>>, ProgramError> {
@@ -1781,6 +6397,15 @@ fn unpack_account_name_hash(bytes: &[u8]) -> Result<BalanceAccountNameHash, Prog
         .ok_or(ProgramError::InvalidInstructionData)
 }
 
+/// Deserialize a bool from a single byte, rejecting anything other than 0 or 1.
+fn unpack_bool(value: u8) -> Result<bool, ProgramError> {
+    match value {
+        0 => Ok(false),
+        1 => Ok(true),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
 /// Deserialize a Pubkey, starting from the given offset in `bytes` slice.
 fn unpack_public_key(bytes: &[u8], offset: usize) -> Result<Pubkey, ProgramError> {
     Ok(Pubkey::new_from_array(
@@ -1,11 +1,25 @@
+#[cfg(all(feature = "client", not(target_arch = "bpf")))]
+pub mod client;
 pub mod constants;
 pub mod error;
 pub mod instruction;
+pub mod lookup_table;
 pub mod model;
+pub mod pda;
+#[cfg(not(target_arch = "bpf"))]
+pub mod policy_lint;
 pub mod processor;
 pub mod serialization_utils;
+#[cfg(feature = "test-vectors")]
+pub mod test_vectors;
 pub mod utils;
 pub mod version;
 
+#[cfg(all(feature = "demo", feature = "mainnet"))]
+compile_error!("the demo feature must not be enabled in mainnet builds");
+
 mod entrypoint;
 mod handlers;
+
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
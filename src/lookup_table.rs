@@ -0,0 +1,395 @@
+//! Address lookup tables whose authority is a PDA of the wallet, so that they can only be
+//! created and extended through approved multisig ops, and the client side helpers that use
+//! them to fit large finalize and dApp transactions into a single transaction.
+
+use crate::constants::PUBKEY_BYTES;
+use crate::pda::{LookupTableAuthoritySeeds, PdaSeeds};
+use bytes::BufMut;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::pubkey::Pubkey;
+use solana_program::system_program;
+#[cfg(not(target_arch = "bpf"))]
+use {
+    solana_program::hash::Hash,
+    solana_program::instruction::CompiledInstruction,
+    solana_program::message::v0::{Message, MessageAddressTableLookup},
+    solana_program::message::MessageHeader,
+};
+
+/// The address lookup table program.
+pub mod program {
+    solana_program::declare_id!("AddressLookupTab1e1111111111111111111111111");
+}
+
+/// The most addresses a single lookup table update can add, which keeps its init and finalize
+/// instructions within the transaction size limit.
+pub const MAX_NEW_ADDRESSES: usize = 20;
+
+/// Size of the metadata the lookup table program stores ahead of the addresses.
+pub const LOOKUP_TABLE_META_SIZE: usize = 56;
+
+const CREATE_LOOKUP_TABLE: u32 = 0;
+const EXTEND_LOOKUP_TABLE: u32 = 2;
+
+/// Derive the PDA and "bump seed" of the authority of the given wallet's lookup tables.
+pub fn find_authority_address(wallet: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    LookupTableAuthoritySeeds { wallet }.find_address(program_id)
+}
+
+/// Derive the address and "bump seed" of the lookup table created by `authority` at
+/// `recent_slot`.
+pub fn find_lookup_table_address(authority: &Pubkey, recent_slot: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[authority.as_ref(), &recent_slot.to_le_bytes()],
+        &program::id(),
+    )
+}
+
+// the lookup table program takes bincode encoded instructions: a u32 variant index followed
+// by the fields, with vectors prefixed by their u64 length
+
+pub fn create_lookup_table_instruction(
+    lookup_table: &Pubkey,
+    authority: &Pubkey,
+    payer: &Pubkey,
+    recent_slot: u64,
+    bump_seed: u8,
+) -> Instruction {
+    let mut data: Vec<u8> = Vec::with_capacity(4 + 8 + 1);
+    data.put_u32_le(CREATE_LOOKUP_TABLE);
+    data.put_u64_le(recent_slot);
+    data.push(bump_seed);
+    Instruction {
+        program_id: program::id(),
+        accounts: vec![
+            AccountMeta::new(*lookup_table, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+pub fn extend_lookup_table_instruction(
+    lookup_table: &Pubkey,
+    authority: &Pubkey,
+    payer: &Pubkey,
+    new_addresses: &[Pubkey],
+) -> Instruction {
+    let mut data: Vec<u8> = Vec::with_capacity(4 + 8 + new_addresses.len() * PUBKEY_BYTES);
+    data.put_u32_le(EXTEND_LOOKUP_TABLE);
+    data.put_u64_le(new_addresses.len() as u64);
+    for address in new_addresses {
+        data.extend_from_slice(address.as_ref());
+    }
+    Instruction {
+        program_id: program::id(),
+        accounts: vec![
+            AccountMeta::new(*lookup_table, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+/// A lookup table and the addresses it holds, as read from the chain.
+#[cfg(not(target_arch = "bpf"))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LookupTable {
+    pub address: Pubkey,
+    pub addresses: Vec<Pubkey>,
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl LookupTable {
+    /// Reads the addresses out of the data of a lookup table account.
+    pub fn from_account_data(address: Pubkey, data: &[u8]) -> Option<Self> {
+        let addresses = data.get(LOOKUP_TABLE_META_SIZE..)?;
+        if addresses.len() % PUBKEY_BYTES != 0 {
+            return None;
+        }
+        Some(LookupTable {
+            address,
+            addresses: addresses
+                .chunks_exact(PUBKEY_BYTES)
+                .map(Pubkey::new)
+                .collect(),
+        })
+    }
+
+    fn position(&self, key: &Pubkey) -> Option<u8> {
+        self.addresses
+            .iter()
+            .position(|address| address == key)
+            .and_then(|position| u8::try_from(position).ok())
+    }
+}
+
+/// The accounts of the instructions that a lookup table can hold: those that never sign and
+/// are not invoked as programs, in the order they first appear.
+#[cfg(not(target_arch = "bpf"))]
+pub fn lookup_table_candidates(instructions: &[Instruction]) -> Vec<Pubkey> {
+    merged_account_metas(None, instructions)
+        .iter()
+        .filter(|meta| !meta.is_signer && !is_program_id(instructions, &meta.pubkey))
+        .map(|meta| meta.pubkey)
+        .collect()
+}
+
+/// Compiles the instructions into a v0 message which loads every account it can from the
+/// given lookup tables instead of listing it. Returns None if the message would reference
+/// more accounts than it can index.
+#[cfg(not(target_arch = "bpf"))]
+pub fn compile_message(
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    lookup_tables: &[LookupTable],
+    recent_blockhash: Hash,
+) -> Option<Message> {
+    let metas = merged_account_metas(Some(payer), instructions);
+    let table_index = |meta: &AccountMeta| {
+        if meta.is_signer || is_program_id(instructions, &meta.pubkey) {
+            None
+        } else {
+            lookup_tables
+                .iter()
+                .position(|table| table.position(&meta.pubkey).is_some())
+        }
+    };
+
+    // signers come first, and within signers and non-signers writable accounts come first
+    let mut account_keys: Vec<Pubkey> = Vec::new();
+    let mut counts = [0; 4];
+    for (count, (is_signer, is_writable)) in
+        counts
+            .iter_mut()
+            .zip([(true, true), (true, false), (false, true), (false, false)])
+    {
+        let keys = metas
+            .iter()
+            .filter(|meta| meta.is_signer == is_signer && meta.is_writable == is_writable)
+            .filter(|meta| table_index(meta).is_none())
+            .map(|meta| meta.pubkey);
+        let len_before = account_keys.len();
+        account_keys.extend(keys);
+        *count = account_keys.len() - len_before;
+    }
+    let [writable_signed, readonly_signed, _, readonly_unsigned] = counts;
+    let header = MessageHeader {
+        num_required_signatures: u8::try_from(writable_signed + readonly_signed).ok()?,
+        num_readonly_signed_accounts: u8::try_from(readonly_signed).ok()?,
+        num_readonly_unsigned_accounts: u8::try_from(readonly_unsigned).ok()?,
+    };
+
+    // loaded accounts are indexed after the static ones, writable from every table first
+    let mut writable_keys: Vec<Pubkey> = Vec::new();
+    let mut readonly_keys: Vec<Pubkey> = Vec::new();
+    let mut address_table_lookups: Vec<MessageAddressTableLookup> = Vec::new();
+    for (index, table) in lookup_tables.iter().enumerate() {
+        let mut lookup = MessageAddressTableLookup {
+            account_key: table.address,
+            writable_indexes: Vec::new(),
+            readonly_indexes: Vec::new(),
+        };
+        for meta in metas.iter().filter(|meta| table_index(meta) == Some(index)) {
+            let position = table.position(&meta.pubkey)?;
+            if meta.is_writable {
+                lookup.writable_indexes.push(position);
+                writable_keys.push(meta.pubkey);
+            } else {
+                lookup.readonly_indexes.push(position);
+                readonly_keys.push(meta.pubkey);
+            }
+        }
+        if !lookup.writable_indexes.is_empty() || !lookup.readonly_indexes.is_empty() {
+            address_table_lookups.push(lookup);
+        }
+    }
+
+    let all_keys: Vec<Pubkey> = account_keys
+        .iter()
+        .chain(writable_keys.iter())
+        .chain(readonly_keys.iter())
+        .copied()
+        .collect();
+    let key_index = |key: &Pubkey| {
+        all_keys
+            .iter()
+            .position(|candidate| candidate == key)
+            .and_then(|position| u8::try_from(position).ok())
+    };
+    let compiled_instructions = instructions
+        .iter()
+        .map(|instruction| {
+            Some(CompiledInstruction {
+                program_id_index: key_index(&instruction.program_id)?,
+                accounts: instruction
+                    .accounts
+                    .iter()
+                    .map(|meta| key_index(&meta.pubkey))
+                    .collect::<Option<Vec<u8>>>()?,
+                data: instruction.data.clone(),
+            })
+        })
+        .collect::<Option<Vec<CompiledInstruction>>>()?;
+
+    Some(Message {
+        header,
+        account_keys,
+        recent_blockhash,
+        instructions: compiled_instructions,
+        address_table_lookups,
+    })
+}
+
+#[cfg(not(target_arch = "bpf"))]
+fn is_program_id(instructions: &[Instruction], key: &Pubkey) -> bool {
+    instructions
+        .iter()
+        .any(|instruction| instruction.program_id == *key)
+}
+
+/// The accounts of the instructions, including their program ids, with the permissions of
+/// every use of an account merged, in the order they first appear after the payer.
+#[cfg(not(target_arch = "bpf"))]
+fn merged_account_metas(payer: Option<&Pubkey>, instructions: &[Instruction]) -> Vec<AccountMeta> {
+    let mut metas: Vec<AccountMeta> = payer
+        .map(|payer| vec![AccountMeta::new(*payer, true)])
+        .unwrap_or_default();
+    let program_metas = instructions
+        .iter()
+        .map(|instruction| AccountMeta::new_readonly(instruction.program_id, false));
+    let account_metas = instructions
+        .iter()
+        .flat_map(|instruction| instruction.accounts.iter().cloned());
+    for account in account_metas.chain(program_metas) {
+        match metas.iter_mut().find(|meta| meta.pubkey == account.pubkey) {
+            Some(meta) => {
+                meta.is_signer |= account.is_signer;
+                meta.is_writable |= account.is_writable;
+            }
+            None => metas.push(account),
+        }
+    }
+    metas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_table_instructions() {
+        let lookup_table = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+
+        let create = create_lookup_table_instruction(&lookup_table, &authority, &payer, 258, 7);
+        assert_eq!(create.data, vec![0, 0, 0, 0, 2, 1, 0, 0, 0, 0, 0, 0, 7]);
+
+        let new_addresses = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let extend =
+            extend_lookup_table_instruction(&lookup_table, &authority, &payer, &new_addresses);
+        assert_eq!(extend.data[..12], [2, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(extend.data[12..44], new_addresses[0].to_bytes());
+        assert_eq!(extend.data[44..], new_addresses[1].to_bytes());
+        assert_eq!(
+            extend.accounts[1],
+            AccountMeta::new_readonly(authority, true)
+        );
+    }
+
+    #[test]
+    fn test_compile_message() {
+        let payer = Pubkey::new_unique();
+        let signer = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let accounts: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let instructions = vec![
+            Instruction::new_with_bytes(
+                program_id,
+                &[1],
+                vec![
+                    AccountMeta::new_readonly(signer, true),
+                    AccountMeta::new(accounts[0], false),
+                    AccountMeta::new_readonly(accounts[1], false),
+                    AccountMeta::new_readonly(accounts[2], false),
+                ],
+            ),
+            Instruction::new_with_bytes(
+                program_id,
+                &[2],
+                vec![
+                    // writable in any instruction makes it writable in the message
+                    AccountMeta::new(accounts[2], false),
+                    AccountMeta::new_readonly(accounts[3], false),
+                ],
+            ),
+        ];
+        assert_eq!(lookup_table_candidates(&instructions), accounts);
+
+        // the program id and the signer can't be loaded even when the table holds them, and
+        // accounts[3] is not in any table
+        let tables = [
+            LookupTable {
+                address: Pubkey::new_unique(),
+                addresses: vec![program_id, accounts[1], signer, accounts[0]],
+            },
+            LookupTable {
+                address: Pubkey::new_unique(),
+                addresses: vec![accounts[0], accounts[2]],
+            },
+        ];
+        let message = compile_message(&payer, &instructions, &tables, Hash::new_unique()).unwrap();
+        message.sanitize(true).unwrap();
+        assert_eq!(
+            message.account_keys,
+            vec![payer, signer, accounts[3], program_id]
+        );
+        assert_eq!(message.header.num_required_signatures, 2);
+        assert_eq!(message.header.num_readonly_signed_accounts, 1);
+        assert_eq!(message.header.num_readonly_unsigned_accounts, 2);
+        assert_eq!(
+            message.address_table_lookups,
+            vec![
+                MessageAddressTableLookup {
+                    account_key: tables[0].address,
+                    writable_indexes: vec![3],
+                    readonly_indexes: vec![1],
+                },
+                MessageAddressTableLookup {
+                    account_key: tables[1].address,
+                    writable_indexes: vec![1],
+                    readonly_indexes: vec![],
+                },
+            ]
+        );
+
+        // the instructions index the static keys, then the writable and readonly loaded ones
+        let all_keys = [
+            payer,
+            signer,
+            accounts[3],
+            program_id,
+            accounts[0],
+            accounts[2],
+            accounts[1],
+        ];
+        for (compiled, instruction) in message.instructions.iter().zip(instructions.iter()) {
+            assert_eq!(
+                all_keys[usize::from(compiled.program_id_index)],
+                instruction.program_id
+            );
+            let keys: Vec<Pubkey> = compiled
+                .accounts
+                .iter()
+                .map(|index| all_keys[usize::from(*index)])
+                .collect();
+            let expected: Vec<Pubkey> = instruction.accounts.iter().map(|a| a.pubkey).collect();
+            assert_eq!(keys, expected);
+        }
+    }
+}
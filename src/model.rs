@@ -1,6 +1,36 @@
 pub mod address_book;
+pub mod approval_group;
+pub mod assistant;
 pub mod balance_account;
+pub mod compliance_policy;
 pub mod dapp_multisig_data;
+pub mod destination_receipt;
+pub mod fee_policy;
+pub mod finalize_submitter_policy;
+pub mod insurance_attestation;
+pub mod internal_transfer_handshake;
+pub mod merkle_address_book;
 pub mod multisig_op;
+pub mod op_bundle;
+pub mod payment_channel;
+pub mod pending_config_policy;
+pub mod policy_diff;
+pub mod price_oracle;
+pub mod program_parameters;
+pub mod rent_return_allowlist;
+pub mod retired_signers;
+pub mod session_key_registry;
 pub mod signer;
+pub mod signer_activations;
+pub mod stake_weight_governance;
+pub mod state_summary;
+pub mod token_account_registry;
 pub mod wallet;
+pub mod wallet_event_log;
+pub mod wallet_freeze;
+pub mod wallet_provisioning;
+pub mod wallet_read_model;
+pub mod wallet_registry;
+pub mod wallet_replication;
+pub mod wallet_stats;
+pub mod wrapper_book;
@@ -1,7 +1,8 @@
 use crate::constants::{HASH_LEN, PUBKEY_BYTES};
 use crate::model::wallet::Wallet;
-use crate::utils::Slots;
+use crate::utils::{associated_token_address, remap_slots, SlotId, SlotRemap, Slots};
 use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::hash::{hashv, Hash};
 use solana_program::program_error::ProgramError;
 use solana_program::program_pack::{Pack, Sealed};
 use solana_program::pubkey::Pubkey;
@@ -33,6 +34,19 @@ pub struct AddressBookEntry {
     pub name_hash: AddressBookEntryNameHash,
 }
 
+impl AddressBookEntry {
+    /// The commitment a transfer to this entry can be initiated with, so that the destination
+    /// is only revealed at finalize. The salt keeps it from being matched against the address
+    /// book, which anyone can read.
+    pub fn commitment(&self, salt: &Hash) -> Hash {
+        hashv(&[
+            self.address.as_ref(),
+            self.name_hash.to_bytes(),
+            salt.as_ref(),
+        ])
+    }
+}
+
 impl Sealed for AddressBookEntry {}
 
 impl Pack for AddressBookEntry {
@@ -57,5 +71,610 @@ impl Pack for AddressBookEntry {
     }
 }
 
+/// A page of the address book entries a balance account is whitelisted to transfer to, as
+/// returned by `GetAllowedDestinations`. Return data can't hold a full whitelist, so a client
+/// reads it a page at a time, each starting from the slot after the previous page's last entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AllowedDestinationsPage {
+    pub entries: Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+}
+
+impl AllowedDestinationsPage {
+    pub const MAX_ENTRIES: usize = 15;
+    const ENTRY_LEN: usize = 1 + // slot id
+        AddressBookEntry::LEN;
+
+    /// The slot to start the next page from, if the whitelist might continue past this one.
+    pub fn next_start_slot_id(&self) -> Option<SlotId<AddressBookEntry>> {
+        if self.entries.len() < AllowedDestinationsPage::MAX_ENTRIES {
+            return None;
+        }
+        self.entries.last().map(|(id, _)| SlotId::new(id.value + 1))
+    }
+
+    /// Packs the entry count followed by the entries, each with its slot id.
+    pub fn pack(&self, dst: &mut Vec<u8>) {
+        dst.push(self.entries.len() as u8);
+        for (id, entry) in &self.entries {
+            dst.push(id.value as u8);
+            let mut buf = [0; AddressBookEntry::LEN];
+            entry.pack_into_slice(&mut buf);
+            dst.extend_from_slice(&buf);
+        }
+    }
+
+    pub fn unpack(src: &[u8]) -> Result<AllowedDestinationsPage, ProgramError> {
+        let (count, rest) = src.split_first().ok_or(ProgramError::InvalidAccountData)?;
+        let count = usize::from(*count);
+        if count > AllowedDestinationsPage::MAX_ENTRIES
+            || rest.len() < count * AllowedDestinationsPage::ENTRY_LEN
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(AllowedDestinationsPage {
+            entries: rest
+                .chunks_exact(AllowedDestinationsPage::ENTRY_LEN)
+                .take(count)
+                .map(|chunk| {
+                    Ok((
+                        SlotId::new(usize::from(chunk[0])),
+                        AddressBookEntry::unpack_from_slice(&chunk[1..])?,
+                    ))
+                })
+                .collect::<Result<Vec<_>, ProgramError>>()?,
+        })
+    }
+}
+
+/// Tracks whether an address book entry is on its way out, and when it was last used.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AddressBookEntryUsage {
+    /// Unix timestamp at which the entry was deprecated, if it has been.
+    pub deprecated_at: Option<i64>,
+    /// The slot in which a transfer to the entry was last initiated, or 0 if none has been.
+    pub last_used_slot: u64,
+}
+
+impl AddressBookEntryUsage {
+    pub const LEN: usize = 8 + // deprecated_at
+        8; // last_used_slot
+
+    pub fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, AddressBookEntryUsage::LEN];
+        let (deprecated_at_dst, last_used_slot_dst) = mut_array_refs![dst, 8, 8];
+
+        *deprecated_at_dst = self.deprecated_at.unwrap_or(0).to_le_bytes();
+        *last_used_slot_dst = self.last_used_slot.to_le_bytes();
+    }
+
+    pub fn unpack_from_slice(src: &[u8]) -> Self {
+        let src = array_ref![src, 0, AddressBookEntryUsage::LEN];
+        let (deprecated_at, last_used_slot) = array_refs![src, 8, 8];
+
+        let deprecated_at = i64::from_le_bytes(*deprecated_at);
+        AddressBookEntryUsage {
+            deprecated_at: if deprecated_at == 0 {
+                None
+            } else {
+                Some(deprecated_at)
+            },
+            last_used_slot: u64::from_le_bytes(*last_used_slot),
+        }
+    }
+}
+
+/// The usage of each address book entry, by slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AddressBookUsage([AddressBookEntryUsage; Wallet::MAX_ADDRESS_BOOK_ENTRIES]);
+
+impl Default for AddressBookUsage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AddressBookUsage {
+    pub const LEN: usize = AddressBookEntryUsage::LEN * Wallet::MAX_ADDRESS_BOOK_ENTRIES;
+
+    pub fn new() -> Self {
+        Self([AddressBookEntryUsage::default(); Wallet::MAX_ADDRESS_BOOK_ENTRIES])
+    }
+
+    pub fn get(&self, id: SlotId<AddressBookEntry>) -> AddressBookEntryUsage {
+        self.0[id.value]
+    }
+
+    pub fn get_mut(&mut self, id: SlotId<AddressBookEntry>) -> &mut AddressBookEntryUsage {
+        &mut self.0[id.value]
+    }
+
+    pub fn is_deprecated(&self, id: SlotId<AddressBookEntry>) -> bool {
+        self.0[id.value].deprecated_at.is_some()
+    }
+
+    pub fn clear(&mut self, id: SlotId<AddressBookEntry>) {
+        self.0[id.value] = AddressBookEntryUsage::default();
+    }
+
+    pub fn remap(&mut self, remap: &SlotRemap<AddressBookEntry>) {
+        remap_slots(&mut self.0, remap);
+    }
+
+    pub fn pack_into_slice(&self, dst: &mut [u8]) {
+        dst.chunks_exact_mut(AddressBookEntryUsage::LEN)
+            .zip(self.0.iter())
+            .for_each(|(chunk, usage)| usage.pack_into_slice(chunk));
+    }
+
+    pub fn unpack_from_slice(src: &[u8]) -> Self {
+        let mut usage = Self::new();
+        src.chunks_exact(AddressBookEntryUsage::LEN)
+            .zip(usage.0.iter_mut())
+            .for_each(|(chunk, entry_usage)| {
+                *entry_usage = AddressBookEntryUsage::unpack_from_slice(chunk)
+            });
+        usage
+    }
+}
+
+/// An ISO 3166-1 alpha-2 country code, such as `*b"KP"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct JurisdictionCode([u8; 2]);
+
+impl JurisdictionCode {
+    pub const LEN: usize = 2;
+
+    pub fn new(code: [u8; 2]) -> Result<Self, ProgramError> {
+        if code.iter().all(u8::is_ascii_uppercase) {
+            Ok(Self(code))
+        } else {
+            Err(ProgramError::InvalidArgument)
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; 2] {
+        self.0
+    }
+
+    /// Packs the code, with zeroes standing for none.
+    pub fn pack_into_slice(code: &Option<JurisdictionCode>, dst: &mut [u8]) {
+        array_mut_ref![dst, 0, JurisdictionCode::LEN]
+            .copy_from_slice(&code.map_or([0; 2], |code| code.0));
+    }
+
+    pub fn unpack_from_slice(src: &[u8]) -> Result<Option<JurisdictionCode>, ProgramError> {
+        match array_ref![src, 0, JurisdictionCode::LEN] {
+            [0, 0] => Ok(None),
+            code => JurisdictionCode::new(*code)
+                .map(Some)
+                .map_err(|_| ProgramError::InvalidAccountData),
+        }
+    }
+}
+
+/// The risk classification of an address book entry. Transfers to a high-risk entry need the
+/// approval of the wallet's compliance service whatever their amount, and the entry's
+/// jurisdiction is recorded in the receipt of each one finalized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AddressBookEntryRisk {
+    Standard,
+    HighRisk { jurisdiction: JurisdictionCode },
+}
+
+impl Default for AddressBookEntryRisk {
+    fn default() -> Self {
+        AddressBookEntryRisk::Standard
+    }
+}
+
+impl AddressBookEntryRisk {
+    /// A high-risk entry is packed as its jurisdiction code, and a standard one as zeroes.
+    pub const LEN: usize = JurisdictionCode::LEN;
+
+    pub fn jurisdiction(&self) -> Option<JurisdictionCode> {
+        match self {
+            AddressBookEntryRisk::Standard => None,
+            AddressBookEntryRisk::HighRisk { jurisdiction } => Some(*jurisdiction),
+        }
+    }
+
+    pub fn pack_into_slice(&self, dst: &mut [u8]) {
+        JurisdictionCode::pack_into_slice(&self.jurisdiction(), dst)
+    }
+
+    pub fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        Ok(match JurisdictionCode::unpack_from_slice(src)? {
+            Some(jurisdiction) => AddressBookEntryRisk::HighRisk { jurisdiction },
+            None => AddressBookEntryRisk::Standard,
+        })
+    }
+}
+
+/// What an address book entry being added is declared to be, which its address is checked
+/// against to catch mis-entered addresses. Declarations are only checked, not kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AddressBookEntryKind {
+    /// An account held by a keypair, which can't be a PDA or a token account.
+    Wallet,
+    /// The associated token account of the owner for the mint, under either token program.
+    TokenAccount { owner: Pubkey, token_mint: Pubkey },
+}
+
+impl AddressBookEntryKind {
+    pub const LEN: usize = 1 + PUBKEY_BYTES * 2;
+
+    /// Whether the address is derived as an entry of this kind has to be. This is all that can
+    /// be told of a wallet address on-chain without its account.
+    pub fn matches_derivation(&self, address: &Pubkey) -> bool {
+        match self {
+            AddressBookEntryKind::Wallet => true,
+            AddressBookEntryKind::TokenAccount { owner, token_mint } => {
+                [spl_token::id(), spl_token_2022::id()]
+                    .iter()
+                    .any(|token_program_id| {
+                        associated_token_address(owner, token_mint, token_program_id) == *address
+                    })
+            }
+        }
+    }
+
+    /// Whether the address could be of this kind, also checking off-chain that a wallet address
+    /// is on the ed25519 curve, which PDAs never are.
+    #[cfg(not(target_arch = "bpf"))]
+    pub fn matches(&self, address: &Pubkey) -> bool {
+        match self {
+            AddressBookEntryKind::Wallet => address.is_on_curve(),
+            AddressBookEntryKind::TokenAccount { .. } => self.matches_derivation(address),
+        }
+    }
+
+    pub fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, AddressBookEntryKind::LEN];
+        let (tag_dst, owner_dst, token_mint_dst) =
+            mut_array_refs![dst, 1, PUBKEY_BYTES, PUBKEY_BYTES];
+        match self {
+            AddressBookEntryKind::Wallet => {
+                tag_dst[0] = 0;
+                owner_dst.fill(0);
+                token_mint_dst.fill(0);
+            }
+            AddressBookEntryKind::TokenAccount { owner, token_mint } => {
+                tag_dst[0] = 1;
+                owner_dst.copy_from_slice(owner.as_ref());
+                token_mint_dst.copy_from_slice(token_mint.as_ref());
+            }
+        }
+    }
+
+    pub fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, AddressBookEntryKind::LEN];
+        let (tag, owner, token_mint) = array_refs![src, 1, PUBKEY_BYTES, PUBKEY_BYTES];
+        match tag[0] {
+            0 => Ok(AddressBookEntryKind::Wallet),
+            1 => Ok(AddressBookEntryKind::TokenAccount {
+                owner: Pubkey::new_from_array(*owner),
+                token_mint: Pubkey::new_from_array(*token_mint),
+            }),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
+
+/// The risk classification of each address book entry, by slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AddressBookRisks([AddressBookEntryRisk; Wallet::MAX_ADDRESS_BOOK_ENTRIES]);
+
+impl Default for AddressBookRisks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AddressBookRisks {
+    pub const LEN: usize = AddressBookEntryRisk::LEN * Wallet::MAX_ADDRESS_BOOK_ENTRIES;
+
+    pub fn new() -> Self {
+        Self([AddressBookEntryRisk::Standard; Wallet::MAX_ADDRESS_BOOK_ENTRIES])
+    }
+
+    pub fn get(&self, id: SlotId<AddressBookEntry>) -> AddressBookEntryRisk {
+        self.0[id.value]
+    }
+
+    pub fn set(&mut self, id: SlotId<AddressBookEntry>, risk: AddressBookEntryRisk) {
+        self.0[id.value] = risk;
+    }
+
+    pub fn remap(&mut self, remap: &SlotRemap<AddressBookEntry>) {
+        remap_slots(&mut self.0, remap);
+    }
+
+    pub fn pack_into_slice(&self, dst: &mut [u8]) {
+        dst.chunks_exact_mut(AddressBookEntryRisk::LEN)
+            .zip(self.0.iter())
+            .for_each(|(chunk, risk)| risk.pack_into_slice(chunk));
+    }
+
+    pub fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let mut risks = Self::new();
+        for (chunk, risk) in src
+            .chunks_exact(AddressBookEntryRisk::LEN)
+            .zip(risks.0.iter_mut())
+        {
+            *risk = AddressBookEntryRisk::unpack_from_slice(chunk)?;
+        }
+        Ok(risks)
+    }
+}
+
+/// The unix timestamp at which each address book entry lapses, by slot. A lapsed entry stays in
+/// the address book, but can't be transferred to until it is renewed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AddressBookExpirations([Option<i64>; Wallet::MAX_ADDRESS_BOOK_ENTRIES]);
+
+impl Default for AddressBookExpirations {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AddressBookExpirations {
+    pub const LEN: usize = 8 * Wallet::MAX_ADDRESS_BOOK_ENTRIES;
+
+    pub fn new() -> Self {
+        Self([None; Wallet::MAX_ADDRESS_BOOK_ENTRIES])
+    }
+
+    pub fn get(&self, id: SlotId<AddressBookEntry>) -> Option<i64> {
+        self.0[id.value]
+    }
+
+    pub fn set(&mut self, id: SlotId<AddressBookEntry>, expires_at: Option<i64>) {
+        self.0[id.value] = expires_at;
+    }
+
+    pub fn is_expired(&self, id: SlotId<AddressBookEntry>, now: i64) -> bool {
+        match self.0[id.value] {
+            Some(expires_at) => now >= expires_at,
+            None => false,
+        }
+    }
+
+    pub fn remap(&mut self, remap: &SlotRemap<AddressBookEntry>) {
+        remap_slots(&mut self.0, remap);
+    }
+
+    /// Packs each expiry as a little-endian timestamp, with 0 standing for none.
+    pub fn pack_into_slice(&self, dst: &mut [u8]) {
+        dst.chunks_exact_mut(8)
+            .zip(self.0.iter())
+            .for_each(|(chunk, expires_at)| {
+                chunk.copy_from_slice(&expires_at.unwrap_or(0).to_le_bytes())
+            });
+    }
+
+    pub fn unpack_from_slice(src: &[u8]) -> Self {
+        let mut expirations = Self::new();
+        src.chunks_exact(8)
+            .zip(expirations.0.iter_mut())
+            .for_each(|(chunk, expires_at)| {
+                *expires_at = match i64::from_le_bytes(*array_ref![chunk, 0, 8]) {
+                    0 => None,
+                    timestamp => Some(timestamp),
+                }
+            });
+        expirations
+    }
+}
+
 pub type DAppBookEntry = AddressBookEntry;
 pub type DAppBookEntryNameHash = AddressBookEntryNameHash;
+
+/// Bitmask of the kinds of protocol a dapp book entry is tagged as, so that a balance account
+/// can be kept from transacting with a whole category of dapps without listing each one.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Ord, PartialOrd, Default)]
+pub struct DAppCategories(u8);
+
+impl DAppCategories {
+    pub const NONE: DAppCategories = DAppCategories(0);
+    pub const DEX: DAppCategories = DAppCategories(1);
+    pub const LENDING: DAppCategories = DAppCategories(2);
+    pub const STAKING: DAppCategories = DAppCategories(4);
+    pub const BRIDGE: DAppCategories = DAppCategories(8);
+    pub const ALL: DAppCategories = DAppCategories(15);
+
+    pub fn from_u8(value: u8) -> Result<DAppCategories, ProgramError> {
+        if value & !Self::ALL.0 != 0 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(DAppCategories(value))
+    }
+
+    pub fn to_u8(&self) -> u8 {
+        self.0
+    }
+
+    pub fn intersects(&self, other: DAppCategories) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    pub fn union(&self, other: DAppCategories) -> DAppCategories {
+        DAppCategories(self.0 | other.0)
+    }
+}
+
+/// The categories each dapp book entry is tagged with, by slot. An untagged entry is in no
+/// category, so no balance account's category policy applies to it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DAppBookCategories([DAppCategories; Wallet::MAX_DAPP_BOOK_ENTRIES]);
+
+impl DAppBookCategories {
+    pub const LEN: usize = Wallet::MAX_DAPP_BOOK_ENTRIES;
+
+    pub fn get(&self, id: SlotId<DAppBookEntry>) -> DAppCategories {
+        self.0[id.value]
+    }
+
+    pub fn set(&mut self, id: SlotId<DAppBookEntry>, categories: DAppCategories) {
+        self.0[id.value] = categories;
+    }
+
+    pub fn remap(&mut self, remap: &SlotRemap<DAppBookEntry>) {
+        remap_slots(&mut self.0, remap);
+    }
+
+    pub fn pack_into_slice(&self, dst: &mut [u8]) {
+        dst.iter_mut()
+            .zip(self.0.iter())
+            .for_each(|(byte, categories)| *byte = categories.to_u8());
+    }
+
+    pub fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let mut categories = Self::default();
+        for (byte, entry_categories) in src.iter().zip(categories.0.iter_mut()) {
+            *entry_categories = DAppCategories::from_u8(*byte)?;
+        }
+        Ok(categories)
+    }
+}
+
+/// The unix timestamp at which each dapp book entry lapses, by slot. An entry without one stays
+/// whitelisted until it is removed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DAppBookExpirations([Option<i64>; Wallet::MAX_DAPP_BOOK_ENTRIES]);
+
+impl DAppBookExpirations {
+    pub const LEN: usize = 8 * Wallet::MAX_DAPP_BOOK_ENTRIES;
+
+    pub fn get(&self, id: SlotId<DAppBookEntry>) -> Option<i64> {
+        self.0[id.value]
+    }
+
+    pub fn set(&mut self, id: SlotId<DAppBookEntry>, expires_at: Option<i64>) {
+        self.0[id.value] = expires_at;
+    }
+
+    pub fn is_expired(&self, id: SlotId<DAppBookEntry>, now: i64) -> bool {
+        match self.0[id.value] {
+            Some(expires_at) => now >= expires_at,
+            None => false,
+        }
+    }
+
+    pub fn remap(&mut self, remap: &SlotRemap<DAppBookEntry>) {
+        remap_slots(&mut self.0, remap);
+    }
+
+    /// Packs each expiry as a little-endian timestamp, with 0 standing for none.
+    pub fn pack_into_slice(&self, dst: &mut [u8]) {
+        dst.chunks_exact_mut(8)
+            .zip(self.0.iter())
+            .for_each(|(chunk, expires_at)| {
+                chunk.copy_from_slice(&expires_at.unwrap_or(0).to_le_bytes())
+            });
+    }
+
+    pub fn unpack_from_slice(src: &[u8]) -> Self {
+        let mut expirations = Self::default();
+        src.chunks_exact(8)
+            .zip(expirations.0.iter_mut())
+            .for_each(|(chunk, expires_at)| {
+                *expires_at = match i64::from_le_bytes(*array_ref![chunk, 0, 8]) {
+                    0 => None,
+                    timestamp => Some(timestamp),
+                }
+            });
+        expirations
+    }
+}
+
+/// The most instructions a dapp transaction with each dapp book entry may have, by slot. An
+/// entry without one is held only to the program-wide limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DAppBookInstructionLimits([Option<u8>; Wallet::MAX_DAPP_BOOK_ENTRIES]);
+
+impl DAppBookInstructionLimits {
+    pub const LEN: usize = Wallet::MAX_DAPP_BOOK_ENTRIES;
+
+    pub fn get(&self, id: SlotId<DAppBookEntry>) -> Option<u8> {
+        self.0[id.value]
+    }
+
+    pub fn set(&mut self, id: SlotId<DAppBookEntry>, max_instructions: Option<u8>) {
+        self.0[id.value] = max_instructions;
+    }
+
+    pub fn remap(&mut self, remap: &SlotRemap<DAppBookEntry>) {
+        remap_slots(&mut self.0, remap);
+    }
+
+    /// Packs each limit as a byte, with 0 standing for none.
+    pub fn pack_into_slice(&self, dst: &mut [u8]) {
+        dst.iter_mut()
+            .zip(self.0.iter())
+            .for_each(|(byte, max_instructions)| *byte = max_instructions.unwrap_or(0));
+    }
+
+    pub fn unpack_from_slice(src: &[u8]) -> Self {
+        let mut limits = Self::default();
+        src.iter()
+            .zip(limits.0.iter_mut())
+            .for_each(|(byte, max_instructions)| {
+                *max_instructions = match byte {
+                    0 => None,
+                    max => Some(*max),
+                }
+            });
+        limits
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::model::address_book::AddressBookEntryKind;
+    use crate::utils::associated_token_address;
+    use solana_program::pubkey::Pubkey;
+
+    #[test]
+    fn test_entry_kind_matches() {
+        // a key that could belong to a keypair
+        let owner = std::iter::repeat_with(Pubkey::new_unique)
+            .find(|key| key.is_on_curve())
+            .unwrap();
+        let token_mint = Pubkey::new_unique();
+        let token_account = AddressBookEntryKind::TokenAccount { owner, token_mint };
+        for token_program_id in [spl_token::id(), spl_token_2022::id()] {
+            let address = associated_token_address(&owner, &token_mint, &token_program_id);
+            assert!(token_account.matches_derivation(&address));
+            assert!(token_account.matches(&address));
+
+            // an associated token account is a PDA, so it can't be declared a wallet
+            assert!(AddressBookEntryKind::Wallet.matches_derivation(&address));
+            assert!(!AddressBookEntryKind::Wallet.matches(&address));
+        }
+        assert!(!token_account.matches_derivation(&owner));
+        assert!(AddressBookEntryKind::Wallet.matches(&owner));
+
+        // a token account of another mint doesn't match
+        let other_mint_address =
+            associated_token_address(&owner, &Pubkey::new_unique(), &spl_token::id());
+        assert!(!token_account.matches_derivation(&other_mint_address));
+    }
+
+    #[test]
+    fn test_entry_kind_pack() {
+        let mut buf = [0u8; AddressBookEntryKind::LEN];
+        for kind in [
+            AddressBookEntryKind::Wallet,
+            AddressBookEntryKind::TokenAccount {
+                owner: Pubkey::new_unique(),
+                token_mint: Pubkey::new_unique(),
+            },
+        ] {
+            kind.pack_into_slice(&mut buf);
+            assert_eq!(AddressBookEntryKind::unpack_from_slice(&buf).unwrap(), kind);
+        }
+        buf[0] = 2;
+        assert!(AddressBookEntryKind::unpack_from_slice(&buf).is_err());
+    }
+}
@@ -0,0 +1,138 @@
+use crate::error::WalletError;
+use crate::model::multisig_op::MultisigOp;
+use crate::model::session_key_registry::SessionKeyScope;
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
+
+/// A group of a wallet's approvers holding shares of a threshold ed25519 key. Any `threshold`
+/// of them can aggregate their signature shares off-chain into a single signature verifiable
+/// against the group key, which `SubmitAggregatedApproval` takes in place of that many
+/// approvals, so that a large quorum approves an op in one transaction.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ApprovalGroup {
+    /// The group public key the aggregated signatures verify against.
+    pub group_key: Pubkey,
+    /// The number of signature shares an aggregated signature takes.
+    pub threshold: u8,
+    /// The ops the group may approve, as for a session key.
+    pub scope: SessionKeyScope,
+}
+
+impl ApprovalGroup {
+    pub const LEN: usize = PUBKEY_BYTES + // group key
+        1 + // threshold
+        SessionKeyScope::LEN; // scope
+
+    pub fn validate(&self) -> ProgramResult {
+        if self.group_key == Pubkey::default() || self.threshold == 0 || !self.scope.is_valid() {
+            msg!("An approval group needs a group key, a threshold and a scope");
+            return Err(WalletError::InvalidApprovalGroup.into());
+        }
+        Ok(())
+    }
+
+    /// Validate that an aggregated signature of the group can approve the op: the op is in
+    /// the group's scope, and the group's threshold is at least the approvals it requires.
+    pub fn validate_covers(&self, multisig_op: &MultisigOp) -> ProgramResult {
+        if !self
+            .scope
+            .covers(multisig_op.category, multisig_op.transfer_amount)
+        {
+            msg!("The op is out of the approval group's scope");
+            return Err(WalletError::ApprovalGroupNotAllowed.into());
+        }
+        if multisig_op.stake_weight_governance.is_some()
+            || self.threshold < multisig_op.dispositions_required
+        {
+            msg!(
+                "The approval group's threshold of {} doesn't meet the op's quorum",
+                self.threshold
+            );
+            return Err(WalletError::ApprovalGroupNotAllowed.into());
+        }
+        Ok(())
+    }
+
+    /// Packs the group, if any; a default group key stands for none.
+    pub fn pack_into_slice(group: &Option<ApprovalGroup>, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, ApprovalGroup::LEN];
+        let group = match group {
+            Some(group) => group,
+            None => {
+                dst.fill(0);
+                return;
+            }
+        };
+        let (group_key_dst, threshold_dst, scope_dst) =
+            mut_array_refs![dst, PUBKEY_BYTES, 1, SessionKeyScope::LEN];
+        group_key_dst.copy_from_slice(group.group_key.as_ref());
+        threshold_dst[0] = group.threshold;
+        group.scope.pack_into_slice(scope_dst);
+    }
+
+    pub fn unpack_from_slice(src: &[u8]) -> Option<ApprovalGroup> {
+        let src = array_ref![src, 0, ApprovalGroup::LEN];
+        let (group_key, threshold, scope) = array_refs![src, PUBKEY_BYTES, 1, SessionKeyScope::LEN];
+        let group_key = Pubkey::new_from_array(*group_key);
+        if group_key == Pubkey::default() {
+            return None;
+        }
+        Some(ApprovalGroup {
+            group_key,
+            threshold: threshold[0],
+            scope: SessionKeyScope::unpack_from_slice(scope),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::WalletError;
+    use crate::model::approval_group::ApprovalGroup;
+    use crate::model::session_key_registry::SessionKeyScope;
+    use solana_program::pubkey::Pubkey;
+
+    #[test]
+    fn test_approval_group() {
+        let group = ApprovalGroup {
+            group_key: Pubkey::new_unique(),
+            threshold: 3,
+            scope: SessionKeyScope {
+                categories: SessionKeyScope::CONFIG | SessionKeyScope::TRANSFER,
+                max_transfer_amount: 1_000,
+            },
+        };
+        assert_eq!(group.validate(), Ok(()));
+
+        for invalid in [
+            ApprovalGroup {
+                group_key: Pubkey::default(),
+                ..group
+            },
+            ApprovalGroup {
+                threshold: 0,
+                ..group
+            },
+            ApprovalGroup {
+                scope: SessionKeyScope {
+                    categories: 0,
+                    max_transfer_amount: 0,
+                },
+                ..group
+            },
+        ] {
+            assert_eq!(
+                invalid.validate(),
+                Err(WalletError::InvalidApprovalGroup.into())
+            );
+        }
+
+        let mut buffer = [0xff; ApprovalGroup::LEN];
+        ApprovalGroup::pack_into_slice(&Some(group), &mut buffer);
+        assert_eq!(ApprovalGroup::unpack_from_slice(&buffer), Some(group));
+        ApprovalGroup::pack_into_slice(&None, &mut buffer);
+        assert_eq!(ApprovalGroup::unpack_from_slice(&buffer), None);
+    }
+}
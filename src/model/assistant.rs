@@ -0,0 +1,114 @@
+use crate::error::WalletError;
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{Pack, Sealed};
+use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
+
+/// An automation key which may initiate operations without being a signer of the wallet,
+/// as long as it is enabled and within its initiation rate limit.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Ord, PartialOrd)]
+pub struct Assistant {
+    pub key: Pubkey,
+    pub enabled: bool,
+    /// The number of operations the key may initiate per rate limit window, or 0 for no limit.
+    pub max_initiations_per_window: u16,
+    /// Unix timestamp at which the current rate limit window started.
+    pub window_start: i64,
+    pub initiations_in_window: u16,
+}
+
+impl Assistant {
+    pub const RATE_LIMIT_WINDOW_SECS: i64 = 60 * 60;
+
+    pub fn new(key: Pubkey) -> Self {
+        Assistant::with_rate_limit(key, true, 0)
+    }
+
+    pub fn with_rate_limit(key: Pubkey, enabled: bool, max_initiations_per_window: u16) -> Self {
+        Assistant {
+            key,
+            enabled,
+            max_initiations_per_window,
+            window_start: 0,
+            initiations_in_window: 0,
+        }
+    }
+
+    /// Returns a copy with the configurable settings of `other`, keeping this key's usage in
+    /// the current rate limit window.
+    pub fn reconfigured(&self, other: &Assistant) -> Self {
+        Assistant {
+            enabled: other.enabled,
+            max_initiations_per_window: other.max_initiations_per_window,
+            ..*self
+        }
+    }
+
+    pub fn is_rate_limited(&self) -> bool {
+        self.max_initiations_per_window > 0
+    }
+
+    pub fn record_initiation(&mut self, now: i64) -> ProgramResult {
+        if !self.is_rate_limited() {
+            return Ok(());
+        }
+        if now.saturating_sub(self.window_start) >= Self::RATE_LIMIT_WINDOW_SECS {
+            self.window_start = now;
+            self.initiations_in_window = 0;
+        }
+        if self.initiations_in_window >= self.max_initiations_per_window {
+            msg!(
+                "Assistant already initiated {} operations in the current window",
+                self.initiations_in_window
+            );
+            return Err(WalletError::AssistantRateLimitExceeded.into());
+        }
+        self.initiations_in_window += 1;
+        Ok(())
+    }
+}
+
+impl Sealed for Assistant {}
+
+impl Pack for Assistant {
+    const LEN: usize = PUBKEY_BYTES + // key
+        1 + // enabled
+        2 + // max_initiations_per_window
+        8 + // window_start
+        2; // initiations_in_window
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, Assistant::LEN];
+        let (
+            key_dst,
+            enabled_dst,
+            max_initiations_per_window_dst,
+            window_start_dst,
+            initiations_in_window_dst,
+        ) = mut_array_refs![dst, PUBKEY_BYTES, 1, 2, 8, 2];
+        key_dst.copy_from_slice(self.key.as_ref());
+        enabled_dst[0] = self.enabled as u8;
+        *max_initiations_per_window_dst = self.max_initiations_per_window.to_le_bytes();
+        *window_start_dst = self.window_start.to_le_bytes();
+        *initiations_in_window_dst = self.initiations_in_window.to_le_bytes();
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, Assistant::LEN];
+        let (key, enabled, max_initiations_per_window, window_start, initiations_in_window) =
+            array_refs![src, PUBKEY_BYTES, 1, 2, 8, 2];
+        Ok(Assistant {
+            key: Pubkey::new_from_array(*key),
+            enabled: match enabled {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            max_initiations_per_window: u16::from_le_bytes(*max_initiations_per_window),
+            window_start: i64::from_le_bytes(*window_start),
+            initiations_in_window: u16::from_le_bytes(*initiations_in_window),
+        })
+    }
+}
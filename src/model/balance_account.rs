@@ -1,12 +1,14 @@
 use crate::constants::HASH_LEN;
-use crate::model::address_book::{AddressBook, AddressBookEntry};
+use crate::model::address_book::{AddressBook, AddressBookEntry, DAppCategories};
 use crate::model::multisig_op::BooleanSetting;
-use crate::model::wallet::{Approvers, WalletGuidHash};
-use crate::utils::SlotFlags;
+use crate::model::signer::Signer;
+use crate::model::wallet::{Approvers, Wallet, WalletGuidHash};
+use crate::pda::{BalanceAccountSeeds, DepositAddressSeeds, PdaSeeds};
+use crate::utils::{SlotFlags, SlotId, SlotRemap};
 use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
 use solana_program::program_error::ProgramError;
 use solana_program::program_pack::{IsInitialized, Pack, Sealed};
-use solana_program::pubkey::Pubkey;
+use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
 use std::convert::TryFrom;
 use std::time::Duration;
 
@@ -14,6 +16,9 @@ pub type AllowedDestinations = SlotFlags<AddressBookEntry, { AddressBook::FLAGS_
 
 const WHITELIST_SETTING_BIT: u8 = 0;
 const DAPPS_SETTING_BIT: u8 = 1;
+const ARCHIVED_SETTING_BIT: u8 = 2;
+const SYSTEM_OWNED_DESTINATIONS_ONLY_SETTING_BIT: u8 = 3;
+const DEPOSIT_ADDRESSES_SETTING_BIT: u8 = 4;
 
 #[derive(Debug, Clone, Eq, PartialEq, Copy, Ord, PartialOrd, Default)]
 pub struct BalanceAccountGuidHash([u8; HASH_LEN]);
@@ -91,6 +96,268 @@ impl Pack for BalanceAccountNameHash {
     }
 }
 
+/// The percentage of withdrawn vote rewards each balance account slot of the wallet receives.
+/// A policy in which every percentage is zero has not been set.
+#[derive(Debug, Clone, Eq, PartialEq, Copy, Ord, PartialOrd)]
+pub struct RewardSplitPolicy([u8; Wallet::MAX_BALANCE_ACCOUNTS]);
+
+impl RewardSplitPolicy {
+    pub const LEN: usize = Wallet::MAX_BALANCE_ACCOUNTS;
+
+    pub fn new(percentages: [u8; Wallet::MAX_BALANCE_ACCOUNTS]) -> Self {
+        Self(percentages)
+    }
+
+    pub fn zero() -> Self {
+        Self::new([0; Wallet::MAX_BALANCE_ACCOUNTS])
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.0.iter().any(|percentage| *percentage > 0)
+    }
+
+    pub fn percentage(&self, slot_id: SlotId<BalanceAccount>) -> u8 {
+        self.0[slot_id.value]
+    }
+
+    pub fn set_percentage(&mut self, slot_id: SlotId<BalanceAccount>, percentage: u8) {
+        self.0[slot_id.value] = percentage;
+    }
+
+    pub fn as_bytes(&self) -> &[u8; Wallet::MAX_BALANCE_ACCOUNTS] {
+        &self.0
+    }
+}
+
+/// Transfers of more than `threshold` out of a balance account can only be finalized once
+/// `delay` has passed since they were approved, during which a config approver can cancel them.
+/// The threshold is in the smallest unit of whatever is being transferred. A zero delay
+/// disables it.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd)]
+pub struct FinalizeDelayPolicy {
+    pub threshold: u64,
+    pub delay: Duration,
+}
+
+impl FinalizeDelayPolicy {
+    pub const LEN: usize = 8 + // threshold
+        8; // delay
+
+    pub fn delay_for(&self, amount: u64) -> Duration {
+        if amount > self.threshold {
+            self.delay
+        } else {
+            Duration::ZERO
+        }
+    }
+
+    pub fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, FinalizeDelayPolicy::LEN];
+        let (threshold_dst, delay_dst) = mut_array_refs![dst, 8, 8];
+        *threshold_dst = self.threshold.to_le_bytes();
+        *delay_dst = self.delay.as_secs().to_le_bytes();
+    }
+
+    pub fn unpack_from_slice(src: &[u8]) -> Self {
+        let src = array_ref![src, 0, FinalizeDelayPolicy::LEN];
+        let (threshold, delay) = array_refs![src, 8, 8];
+        FinalizeDelayPolicy {
+            threshold: u64::from_le_bytes(*threshold),
+            delay: Duration::from_secs(u64::from_le_bytes(*delay)),
+        }
+    }
+}
+
+/// Transfers of `token_mint` out of a balance account (the default pubkey for SOL) adding up to
+/// no more than `amount` within a 24 hour window can be finalized on their initiator's approval
+/// alone. A window starts with the first such transfer after the previous one ended. A zero
+/// amount disables the limit.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd)]
+pub struct SpendingLimit {
+    pub token_mint: Pubkey,
+    pub amount: u64,
+    /// Unix timestamp at which the current window started.
+    pub window_start: i64,
+    pub spent_in_window: u64,
+}
+
+impl SpendingLimit {
+    pub const LEN: usize = PUBKEY_BYTES + // token mint
+        8 + // amount
+        8 + // window start
+        8; // spent in window
+
+    pub const WINDOW_SECS: i64 = 24 * 60 * 60;
+
+    pub fn new(token_mint: Pubkey, amount: u64) -> Self {
+        SpendingLimit {
+            token_mint,
+            amount,
+            window_start: 0,
+            spent_in_window: 0,
+        }
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.amount > 0
+    }
+
+    /// What is left of the limit for `token_mint` at `now`.
+    pub fn remaining(&self, token_mint: &Pubkey, now: i64) -> u64 {
+        if !self.is_set() || self.token_mint != *token_mint {
+            0
+        } else if now.saturating_sub(self.window_start) >= Self::WINDOW_SECS {
+            self.amount
+        } else {
+            self.amount.saturating_sub(self.spent_in_window)
+        }
+    }
+
+    /// Counts a transfer against the limit, provided it is within what is left of it.
+    pub fn record_spend(&mut self, token_mint: &Pubkey, amount: u64, now: i64) -> bool {
+        if !self.is_set()
+            || self.token_mint != *token_mint
+            || amount > self.remaining(token_mint, now)
+        {
+            return false;
+        }
+        if now.saturating_sub(self.window_start) >= Self::WINDOW_SECS {
+            self.window_start = now;
+            self.spent_in_window = 0;
+        }
+        self.spent_in_window += amount;
+        true
+    }
+
+    pub fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, SpendingLimit::LEN];
+        let (token_mint_dst, amount_dst, window_start_dst, spent_in_window_dst) =
+            mut_array_refs![dst, PUBKEY_BYTES, 8, 8, 8];
+        token_mint_dst.copy_from_slice(self.token_mint.as_ref());
+        *amount_dst = self.amount.to_le_bytes();
+        *window_start_dst = self.window_start.to_le_bytes();
+        *spent_in_window_dst = self.spent_in_window.to_le_bytes();
+    }
+
+    pub fn unpack_from_slice(src: &[u8]) -> Self {
+        let src = array_ref![src, 0, SpendingLimit::LEN];
+        let (token_mint, amount, window_start, spent_in_window) =
+            array_refs![src, PUBKEY_BYTES, 8, 8, 8];
+        SpendingLimit {
+            token_mint: Pubkey::new_from_array(*token_mint),
+            amount: u64::from_le_bytes(*amount),
+            window_start: i64::from_le_bytes(*window_start),
+            spent_in_window: u64::from_le_bytes(*spent_in_window),
+        }
+    }
+}
+
+/// Transfers of at least `min_amount` out of a balance account need `approvals_required`
+/// approvals from the tier's `approvers`, a subset of the account's transfer approvers, rather
+/// than the account's own number of approvals from all of them. Like the finalize delay
+/// threshold, the amount is in the smallest unit of whatever is being transferred.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+pub struct ApprovalTier {
+    pub min_amount: u64,
+    pub approvals_required: u8,
+    pub approvers: Approvers,
+}
+
+impl ApprovalTier {
+    pub const LEN: usize = 8 + // min amount
+        1 + // approvals required
+        Approvers::STORAGE_SIZE; // approvers
+
+    pub fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, ApprovalTier::LEN];
+        let (min_amount_dst, approvals_required_dst, approvers_dst) =
+            mut_array_refs![dst, 8, 1, Approvers::STORAGE_SIZE];
+        *min_amount_dst = self.min_amount.to_le_bytes();
+        approvals_required_dst[0] = self.approvals_required;
+        approvers_dst.copy_from_slice(self.approvers.as_bytes());
+    }
+
+    pub fn unpack_from_slice(src: &[u8]) -> Self {
+        let src = array_ref![src, 0, ApprovalTier::LEN];
+        let (min_amount, approvals_required, approvers) =
+            array_refs![src, 8, 1, Approvers::STORAGE_SIZE];
+        ApprovalTier {
+            min_amount: u64::from_le_bytes(*min_amount),
+            approvals_required: approvals_required[0],
+            approvers: Approvers::new(*approvers),
+        }
+    }
+}
+
+/// The approval tiers of a balance account, in ascending order of their minimum amounts. Slots
+/// past the last tier requiring no approvals are unused.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+pub struct ApprovalTiers([ApprovalTier; ApprovalTiers::MAX_TIERS]);
+
+impl ApprovalTiers {
+    pub const MAX_TIERS: usize = 3;
+    pub const LEN: usize = ApprovalTier::LEN * ApprovalTiers::MAX_TIERS;
+
+    pub fn none() -> Self {
+        ApprovalTiers(
+            [ApprovalTier {
+                min_amount: 0,
+                approvals_required: 0,
+                approvers: Approvers::zero(),
+            }; ApprovalTiers::MAX_TIERS],
+        )
+    }
+
+    /// The tiers, which are taken to be valid. There can be at most `MAX_TIERS`.
+    pub fn from_tiers(tiers: &[ApprovalTier]) -> Self {
+        let mut approval_tiers = ApprovalTiers::none();
+        approval_tiers.0[..tiers.len()].copy_from_slice(tiers);
+        approval_tiers
+    }
+
+    pub fn tiers(&self) -> Vec<ApprovalTier> {
+        self.0
+            .iter()
+            .take_while(|tier| tier.approvals_required > 0)
+            .copied()
+            .collect()
+    }
+
+    /// Moves the approvers of every tier along with the signer slots the remap moves.
+    pub fn remap_approvers(&mut self, remap: &SlotRemap<Signer>) {
+        for tier in self.0.iter_mut() {
+            tier.approvers.remap(remap);
+        }
+    }
+
+    /// The tier a transfer of the amount falls in, if any: the one with the highest minimum
+    /// amount the amount reaches.
+    pub fn tier_for(&self, amount: u64) -> Option<ApprovalTier> {
+        self.tiers()
+            .into_iter()
+            .rev()
+            .find(|tier| amount >= tier.min_amount)
+    }
+
+    pub fn pack_into_slice(&self, dst: &mut [u8]) {
+        for (tier, tier_dst) in self.0.iter().zip(dst.chunks_exact_mut(ApprovalTier::LEN)) {
+            tier.pack_into_slice(tier_dst);
+        }
+    }
+
+    pub fn unpack_from_slice(src: &[u8]) -> Self {
+        let mut approval_tiers = ApprovalTiers::none();
+        for (tier, tier_src) in approval_tiers
+            .0
+            .iter_mut()
+            .zip(src.chunks_exact(ApprovalTier::LEN))
+        {
+            *tier = ApprovalTier::unpack_from_slice(tier_src);
+        }
+        approval_tiers
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Ord, PartialOrd)]
 pub struct BalanceAccount {
     pub guid_hash: BalanceAccountGuidHash,
@@ -101,6 +368,25 @@ pub struct BalanceAccount {
     pub allowed_destinations: AllowedDestinations,
     pub whitelist_enabled: BooleanSetting,
     pub dapps_enabled: BooleanSetting,
+    /// An archived account keeps its configuration, but no new operations can be started
+    /// against it other than updating its settings.
+    pub archived: BooleanSetting,
+    /// When on, SOL can only be transferred to accounts owned by the system program, since
+    /// lamports sent to an account owned by another program may not be recoverable.
+    pub system_owned_destinations_only: BooleanSetting,
+    pub reward_split_policy: RewardSplitPolicy,
+    pub finalize_delay_policy: FinalizeDelayPolicy,
+    /// The most a single mint operation may mint with a mint authority held by the balance
+    /// account. Zero disables minting.
+    pub mint_cap: u64,
+    pub spending_limit: SpendingLimit,
+    pub approval_tiers: ApprovalTiers,
+    /// The categories of dapps the account may not transact with, whichever dapp book entry
+    /// they are whitelisted under.
+    pub disallowed_dapp_categories: DAppCategories,
+    /// When on, funds sent to the account's deposit addresses can be swept into it when a
+    /// transfer or wrap out of it is finalized.
+    pub deposit_addresses_enabled: BooleanSetting,
 }
 
 impl Sealed for BalanceAccount {}
@@ -112,7 +398,13 @@ impl Pack for BalanceAccount {
         8 + // approval_timeout_for_transfer
         Approvers::STORAGE_SIZE + // transfer approvers
         AllowedDestinations::STORAGE_SIZE +  // allowed destinations
-        1; // boolean settings
+        1 + // boolean settings
+        RewardSplitPolicy::LEN + // reward split policy
+        FinalizeDelayPolicy::LEN + // finalize delay policy
+        8 + // mint cap
+        SpendingLimit::LEN + // spending limit
+        ApprovalTiers::LEN + // approval tiers
+        1; // disallowed dapp categories
 
     fn pack_into_slice(&self, dst: &mut [u8]) {
         let dst = array_mut_ref![dst, 0, BalanceAccount::LEN];
@@ -124,6 +416,12 @@ impl Pack for BalanceAccount {
             approvers_dst,
             allowed_destinations_dst,
             boolean_settings_dst,
+            reward_split_policy_dst,
+            finalize_delay_policy_dst,
+            mint_cap_dst,
+            spending_limit_dst,
+            approval_tiers_dst,
+            disallowed_dapp_categories_dst,
         ) = mut_array_refs![
             dst,
             HASH_LEN,
@@ -132,6 +430,12 @@ impl Pack for BalanceAccount {
             8,
             Approvers::STORAGE_SIZE,
             AllowedDestinations::STORAGE_SIZE,
+            1,
+            RewardSplitPolicy::LEN,
+            FinalizeDelayPolicy::LEN,
+            8,
+            SpendingLimit::LEN,
+            ApprovalTiers::LEN,
             1
         ];
 
@@ -146,6 +450,18 @@ impl Pack for BalanceAccount {
         allowed_destinations_dst.copy_from_slice(self.allowed_destinations.as_bytes());
         boolean_settings_dst[0] |= self.whitelist_enabled.to_u8() << WHITELIST_SETTING_BIT;
         boolean_settings_dst[0] |= self.dapps_enabled.to_u8() << DAPPS_SETTING_BIT;
+        boolean_settings_dst[0] |= self.archived.to_u8() << ARCHIVED_SETTING_BIT;
+        boolean_settings_dst[0] |= self.system_owned_destinations_only.to_u8()
+            << SYSTEM_OWNED_DESTINATIONS_ONLY_SETTING_BIT;
+        boolean_settings_dst[0] |=
+            self.deposit_addresses_enabled.to_u8() << DEPOSIT_ADDRESSES_SETTING_BIT;
+        reward_split_policy_dst.copy_from_slice(self.reward_split_policy.as_bytes());
+        self.finalize_delay_policy
+            .pack_into_slice(finalize_delay_policy_dst);
+        *mint_cap_dst = self.mint_cap.to_le_bytes();
+        self.spending_limit.pack_into_slice(spending_limit_dst);
+        self.approval_tiers.pack_into_slice(approval_tiers_dst);
+        disallowed_dapp_categories_dst[0] = self.disallowed_dapp_categories.to_u8();
     }
 
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
@@ -158,6 +474,12 @@ impl Pack for BalanceAccount {
             approvers_src,
             allowed_destinations_src,
             boolean_settings_src,
+            reward_split_policy_src,
+            finalize_delay_policy_src,
+            mint_cap_src,
+            spending_limit_src,
+            approval_tiers_src,
+            disallowed_dapp_categories_src,
         ) = array_refs![
             src,
             HASH_LEN,
@@ -166,6 +488,12 @@ impl Pack for BalanceAccount {
             8,
             Approvers::STORAGE_SIZE,
             AllowedDestinations::STORAGE_SIZE,
+            1,
+            RewardSplitPolicy::LEN,
+            FinalizeDelayPolicy::LEN,
+            8,
+            SpendingLimit::LEN,
+            ApprovalTiers::LEN,
             1
         ];
 
@@ -184,11 +512,31 @@ impl Pack for BalanceAccount {
             dapps_enabled: BooleanSetting::from_u8(
                 boolean_settings_src[0] & (1 << DAPPS_SETTING_BIT),
             ),
+            archived: BooleanSetting::from_u8(
+                boolean_settings_src[0] & (1 << ARCHIVED_SETTING_BIT),
+            ),
+            system_owned_destinations_only: BooleanSetting::from_u8(
+                boolean_settings_src[0] & (1 << SYSTEM_OWNED_DESTINATIONS_ONLY_SETTING_BIT),
+            ),
+            deposit_addresses_enabled: BooleanSetting::from_u8(
+                boolean_settings_src[0] & (1 << DEPOSIT_ADDRESSES_SETTING_BIT),
+            ),
+            reward_split_policy: RewardSplitPolicy::new(*reward_split_policy_src),
+            finalize_delay_policy: FinalizeDelayPolicy::unpack_from_slice(
+                finalize_delay_policy_src,
+            ),
+            mint_cap: u64::from_le_bytes(*mint_cap_src),
+            spending_limit: SpendingLimit::unpack_from_slice(spending_limit_src),
+            approval_tiers: ApprovalTiers::unpack_from_slice(approval_tiers_src),
+            disallowed_dapp_categories: DAppCategories::from_u8(disallowed_dapp_categories_src[0])?,
         })
     }
 }
 
 impl BalanceAccount {
+    /// Deposit addresses have indices from 0 to one less than this.
+    pub const MAX_DEPOSIT_ADDRESSES: usize = 255;
+
     pub fn is_whitelist_disabled(&self) -> bool {
         return self.whitelist_enabled == BooleanSetting::Off;
     }
@@ -197,6 +545,18 @@ impl BalanceAccount {
         return self.dapps_enabled == BooleanSetting::Off;
     }
 
+    pub fn is_archived(&self) -> bool {
+        self.archived == BooleanSetting::On
+    }
+
+    pub fn requires_system_owned_destinations(&self) -> bool {
+        self.system_owned_destinations_only == BooleanSetting::On
+    }
+
+    pub fn are_deposit_addresses_enabled(&self) -> bool {
+        self.deposit_addresses_enabled == BooleanSetting::On
+    }
+
     pub fn has_whitelisted_destinations(&self) -> bool {
         return self.allowed_destinations.count_enabled() > 0;
     }
@@ -207,9 +567,26 @@ impl BalanceAccount {
         guid_hash: &BalanceAccountGuidHash,
         program_id: &Pubkey,
     ) -> (Pubkey, u8) {
-        Pubkey::find_program_address(
-            &[wallet_guid_hash.to_bytes(), guid_hash.to_bytes()],
-            program_id,
-        )
+        BalanceAccountSeeds {
+            wallet_guid_hash,
+            account_guid_hash: guid_hash,
+        }
+        .find_address(program_id)
+    }
+
+    /// Derive the PDA and "bump seed" of the deposit address with the given index of a
+    /// BalanceAccount.
+    pub fn find_deposit_address(
+        wallet_guid_hash: &WalletGuidHash,
+        guid_hash: &BalanceAccountGuidHash,
+        index: u8,
+        program_id: &Pubkey,
+    ) -> (Pubkey, u8) {
+        DepositAddressSeeds {
+            wallet_guid_hash,
+            account_guid_hash: guid_hash,
+            index,
+        }
+        .find_address(program_id)
     }
 }
@@ -0,0 +1,50 @@
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
+
+/// A compliance service whose counter-signature transfers of more than the threshold need
+/// before they can be finalized. The service gives it with `ComplianceApprove` once it has
+/// screened the transfer off-chain.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct CompliancePolicy {
+    pub service: Pubkey,
+    /// The amount a transfer has to exceed, in the units of the token transferred, for the
+    /// service's approval to be required.
+    pub threshold: u64,
+}
+
+impl CompliancePolicy {
+    pub const LEN: usize = PUBKEY_BYTES + // service
+        8; // threshold
+
+    pub fn requires_approval(&self, amount: u64) -> bool {
+        amount > self.threshold
+    }
+
+    /// Packs the policy, if any; a default service key stands for none.
+    pub fn pack_into_slice(policy: &Option<CompliancePolicy>, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, CompliancePolicy::LEN];
+        let policy = match policy {
+            Some(policy) => policy,
+            None => {
+                dst.fill(0);
+                return;
+            }
+        };
+        let (service_dst, threshold_dst) = mut_array_refs![dst, PUBKEY_BYTES, 8];
+        service_dst.copy_from_slice(policy.service.as_ref());
+        *threshold_dst = policy.threshold.to_le_bytes();
+    }
+
+    pub fn unpack_from_slice(src: &[u8]) -> Option<CompliancePolicy> {
+        let src = array_ref![src, 0, CompliancePolicy::LEN];
+        let (service, threshold) = array_refs![src, PUBKEY_BYTES, 8];
+        let service = Pubkey::new_from_array(*service);
+        if service == Pubkey::default() {
+            return None;
+        }
+        Some(CompliancePolicy {
+            service,
+            threshold: u64::from_le_bytes(*threshold),
+        })
+    }
+}
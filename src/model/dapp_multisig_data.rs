@@ -15,8 +15,10 @@ use solana_program::program_error::ProgramError;
 use solana_program::program_pack::{IsInitialized, Pack, Sealed};
 use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
 
-const INSTRUCTION_DATA_LEN: usize = 2500;
-const MAX_INSTRUCTION_COUNT: usize = 32;
+// the buffer used to be a byte longer, but never filled up to its last byte, which now holds
+// the instruction limit without changing the size of the account
+const INSTRUCTION_DATA_LEN: usize = 2499;
+pub const MAX_INSTRUCTION_COUNT: usize = 32;
 
 #[derive(Debug)]
 pub struct DAppMultisigData {
@@ -28,6 +30,9 @@ pub struct DAppMultisigData {
     instruction_offsets: [u16; MAX_INSTRUCTION_COUNT],
     instruction_data: Vec<u8>,
     position: usize,
+    /// The most instructions the dapp's book entry allowed when the transaction was started,
+    /// if it limited them.
+    pub max_instructions: Option<u8>,
 }
 
 impl DAppMultisigData {
@@ -37,6 +42,7 @@ impl DAppMultisigData {
         account_guid_hash: BalanceAccountGuidHash,
         dapp: DAppBookEntry,
         num_instructions: u8,
+        max_instructions: Option<u8>,
     ) -> ProgramResult {
         self.is_initialized = true;
         self.wallet_address = wallet_address;
@@ -45,6 +51,8 @@ impl DAppMultisigData {
         if num_instructions > MAX_INSTRUCTION_COUNT as u8 {
             return Err(WalletError::DAppInstructionOverflow.into());
         }
+        self.max_instructions = max_instructions;
+        self.validate_instruction_count(num_instructions)?;
         self.num_instructions = num_instructions;
         self.instruction_offsets = [0; MAX_INSTRUCTION_COUNT];
         self.instruction_data = vec![0; INSTRUCTION_DATA_LEN];
@@ -67,16 +75,46 @@ impl DAppMultisigData {
             // the offset is 1-based, so that an offset of 0 can mean "unset"
             self.instruction_offsets[usize::from(index)] = (1 + self.position).as_u16();
             let new_position = self.position + buffer.len();
-            if new_position >= INSTRUCTION_DATA_LEN {
-                msg!("Instruction data exceeded buffer size");
-                return Err(WalletError::DAppInstructionOverflow.into());
-            }
+            self.validate_instructions_len(new_position)?;
             self.instruction_data[self.position..new_position].copy_from_slice(&buffer);
             self.position = new_position;
         }
         Ok(())
     }
 
+    /// Checks the supplied instructions against the limits the transaction was started with,
+    /// so that a transaction that couldn't be executed fails before any of it is.
+    pub fn validate_limits(&self) -> ProgramResult {
+        self.validate_instruction_count(self.num_instructions)?;
+        self.validate_instructions_len(self.position)
+    }
+
+    fn validate_instruction_count(&self, num_instructions: u8) -> ProgramResult {
+        match self.max_instructions {
+            Some(max_instructions) if num_instructions > max_instructions => {
+                msg!(
+                    "Instruction count {} exceeds the dapp's limit of {}",
+                    num_instructions,
+                    max_instructions
+                );
+                Err(WalletError::DAppInstructionTooLarge.into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn validate_instructions_len(&self, instructions_len: usize) -> ProgramResult {
+        if instructions_len > INSTRUCTION_DATA_LEN {
+            msg!(
+                "Serialized instructions size {} exceeds {}",
+                instructions_len,
+                INSTRUCTION_DATA_LEN
+            );
+            return Err(WalletError::DAppInstructionTooLarge.into());
+        }
+        Ok(())
+    }
+
     pub fn all_instructions_supplied(&self) -> bool {
         all(0..self.num_instructions, |x| {
             self.instruction_offsets[usize::from(x)] != 0
@@ -135,14 +173,15 @@ impl IsInitialized for DAppMultisigData {
 }
 
 impl Pack for DAppMultisigData {
-    const LEN: usize = 1
-        + PUBKEY_BYTES
-        + 32
-        + DAppBookEntry::LEN
-        + 1
-        + 2 * MAX_INSTRUCTION_COUNT
-        + 2
-        + INSTRUCTION_DATA_LEN;
+    const LEN: usize = 1 // initialized
+        + PUBKEY_BYTES // wallet address
+        + 32 // account guid hash
+        + DAppBookEntry::LEN // dapp
+        + 1 // num instructions
+        + 2 * MAX_INSTRUCTION_COUNT // instruction offsets
+        + 2 // position
+        + INSTRUCTION_DATA_LEN // instruction data
+        + 1; // max instructions
 
     fn pack_into_slice(&self, dst: &mut [u8]) {
         let dst = array_mut_ref![dst, 0, DAppMultisigData::LEN];
@@ -155,6 +194,7 @@ impl Pack for DAppMultisigData {
             instruction_offsets_dst,
             position_dst,
             instruction_data_dst,
+            max_instructions_dst,
         ) = mut_array_refs![
             dst,
             1,
@@ -164,7 +204,8 @@ impl Pack for DAppMultisigData {
             1,
             2 * MAX_INSTRUCTION_COUNT,
             2,
-            INSTRUCTION_DATA_LEN
+            INSTRUCTION_DATA_LEN,
+            1
         ];
 
         let DAppMultisigData {
@@ -176,6 +217,7 @@ impl Pack for DAppMultisigData {
             instruction_offsets,
             position,
             instruction_data,
+            max_instructions,
         } = self;
 
         is_initialized_dst[0] = *is_initialized as u8;
@@ -192,6 +234,7 @@ impl Pack for DAppMultisigData {
             });
         instruction_data_dst.copy_from_slice(instruction_data);
         position_dst.copy_from_slice(&position.as_u16().to_le_bytes()[..2]);
+        max_instructions_dst[0] = max_instructions.unwrap_or(0);
     }
 
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
@@ -205,6 +248,7 @@ impl Pack for DAppMultisigData {
             instruction_offsets,
             position,
             instruction_data,
+            max_instructions,
         ) = array_refs![
             src,
             1,
@@ -214,7 +258,8 @@ impl Pack for DAppMultisigData {
             1,
             2 * MAX_INSTRUCTION_COUNT,
             2,
-            INSTRUCTION_DATA_LEN
+            INSTRUCTION_DATA_LEN,
+            1
         ];
 
         let is_initialized = match is_initialized {
@@ -246,19 +291,28 @@ impl Pack for DAppMultisigData {
             instruction_offsets: instruction_offsets_array,
             instruction_data: instruction_data[..].to_owned(),
             position: usize::from(u16::from_le_bytes(*position)),
+            max_instructions: match max_instructions[0] {
+                0 => None,
+                max_instructions => Some(max_instructions),
+            },
         })
     }
 }
 
+// the size existing dapp multisig data accounts were allocated with
+const _: () = assert!(DAppMultisigData::LEN == 2696);
+
 #[cfg(test)]
 mod test {
     use crate::constants::{HASH_LEN, PUBKEY_BYTES};
+    use crate::error::WalletError;
     use crate::model::address_book::{DAppBookEntry, DAppBookEntryNameHash};
     use crate::model::balance_account::BalanceAccountGuidHash;
     use crate::model::dapp_multisig_data::{DAppMultisigData, INSTRUCTION_DATA_LEN};
     use arrayref::array_ref;
     use sha2::Digest;
     use sha2::Sha256;
+    use solana_program::instruction::Instruction;
     use solana_program::program_pack::Pack;
     use solana_program::pubkey::Pubkey;
 
@@ -283,6 +337,7 @@ mod test {
             instruction_offsets: [0; 32],
             position: 0,
             instruction_data: vec![0; INSTRUCTION_DATA_LEN],
+            max_instructions: None,
         };
         let mut buffer = vec![0; DAppMultisigData::LEN];
         data.pack_into_slice(&mut buffer);
@@ -307,6 +362,7 @@ mod test {
             ],
             position: 23,
             instruction_data: vec![1; INSTRUCTION_DATA_LEN],
+            max_instructions: Some(3),
         };
         let mut buffer = vec![0; DAppMultisigData::LEN];
         data.pack_into_slice(&mut buffer);
@@ -333,5 +389,45 @@ mod test {
         assert_eq!(data.instruction_offsets, data2.instruction_offsets);
         assert_eq!(data.instruction_data, data2.instruction_data);
         assert_eq!(data.position, data2.position);
+        assert_eq!(data.max_instructions, data2.max_instructions);
+    }
+
+    #[test]
+    fn test_instruction_limits() {
+        let mut data = DAppMultisigData::unpack_from_slice(&[0; DAppMultisigData::LEN]).unwrap();
+        let dapp = DAppBookEntry {
+            address: Pubkey::new_unique(),
+            name_hash: DAppBookEntryNameHash::new(&hash_of(b"dapp-name")),
+        };
+        assert_eq!(
+            data.init(
+                Pubkey::new_unique(),
+                BalanceAccountGuidHash::new(&hash_of(b"account-guid")),
+                dapp,
+                3,
+                Some(2),
+            ),
+            Err(WalletError::DAppInstructionTooLarge.into())
+        );
+
+        data.init(
+            Pubkey::new_unique(),
+            BalanceAccountGuidHash::new(&hash_of(b"account-guid")),
+            dapp,
+            2,
+            Some(2),
+        )
+        .unwrap();
+        let instruction = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[7; INSTRUCTION_DATA_LEN / 2],
+            vec![],
+        );
+        data.add_instruction(0, &instruction).unwrap();
+        assert_eq!(
+            data.add_instruction(1, &instruction),
+            Err(WalletError::DAppInstructionTooLarge.into())
+        );
+        data.validate_limits().unwrap();
     }
 }
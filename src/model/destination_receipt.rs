@@ -0,0 +1,197 @@
+use crate::constants::PUBKEY_BYTES;
+use crate::error::WalletError;
+use crate::model::address_book::JurisdictionCode;
+use crate::pda::{DestinationReceiptSeeds, PdaSeeds};
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{IsInitialized, Pack, Sealed};
+use solana_program::pubkey::Pubkey;
+
+/// Running totals of what a wallet has transferred to a destination, in a single token. It
+/// lives in a PDA derived from the wallet, destination and token mint, so that whoever is
+/// reconciling transfers can look it up without scanning the wallet's history, and is
+/// created by the first transfer finalized to the destination.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct DestinationReceipt {
+    pub is_initialized: bool,
+    pub wallet: Pubkey,
+    pub destination: Pubkey,
+    /// The system program id for SOL transfers.
+    pub token_mint: Pubkey,
+    pub transfer_count: u64,
+    pub cumulative_amount: u64,
+    pub last_slot: u64,
+    /// The jurisdiction of the destination as of the latest transfer, if it was classified
+    /// high-risk then.
+    pub jurisdiction: Option<JurisdictionCode>,
+    /// The fees taken out of the transferred amounts to reimburse their finalize submitters,
+    /// which `cumulative_amount` is net of.
+    pub cumulative_deducted_fee: u64,
+}
+
+impl DestinationReceipt {
+    /// The size of receipts created before deducted fees were recorded, which are grown by the
+    /// next transfer recorded in them.
+    pub const LEN_WITHOUT_DEDUCTED_FEE: usize = DestinationReceipt::LEN - 8;
+    /// The size of receipts created before jurisdictions were recorded, which are grown the
+    /// same way.
+    pub const LEN_WITHOUT_JURISDICTION: usize =
+        DestinationReceipt::LEN_WITHOUT_DEDUCTED_FEE - JurisdictionCode::LEN;
+
+    pub fn new(wallet: Pubkey, destination: Pubkey, token_mint: Pubkey) -> Self {
+        DestinationReceipt {
+            is_initialized: true,
+            wallet,
+            destination,
+            token_mint,
+            transfer_count: 0,
+            cumulative_amount: 0,
+            last_slot: 0,
+            jurisdiction: None,
+            cumulative_deducted_fee: 0,
+        }
+    }
+
+    /// Derive the PDA and "bump seed" of the receipt for the given wallet, destination and
+    /// token mint.
+    pub fn find_address(
+        wallet: &Pubkey,
+        destination: &Pubkey,
+        token_mint: &Pubkey,
+        program_id: &Pubkey,
+    ) -> (Pubkey, u8) {
+        DestinationReceiptSeeds {
+            wallet,
+            destination,
+            token_mint,
+        }
+        .find_address(program_id)
+    }
+
+    /// Records a transfer of `amount` to the destination, net of the `deducted_fee` that was
+    /// taken out of it.
+    pub fn record_transfer(
+        &mut self,
+        amount: u64,
+        deducted_fee: u64,
+        slot: u64,
+        jurisdiction: Option<JurisdictionCode>,
+    ) -> ProgramResult {
+        self.cumulative_amount = self
+            .cumulative_amount
+            .checked_add(amount)
+            .ok_or(WalletError::AmountOverflow)?;
+        self.transfer_count = self
+            .transfer_count
+            .checked_add(1)
+            .ok_or(WalletError::AmountOverflow)?;
+        self.cumulative_deducted_fee = self
+            .cumulative_deducted_fee
+            .checked_add(deducted_fee)
+            .ok_or(WalletError::AmountOverflow)?;
+        self.last_slot = slot;
+        self.jurisdiction = jurisdiction;
+        Ok(())
+    }
+}
+
+impl Sealed for DestinationReceipt {}
+
+impl IsInitialized for DestinationReceipt {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for DestinationReceipt {
+    const LEN: usize = 1 + // is_initialized
+        PUBKEY_BYTES + // wallet
+        PUBKEY_BYTES + // destination
+        PUBKEY_BYTES + // token_mint
+        8 + // transfer_count
+        8 + // cumulative_amount
+        8 + // last_slot
+        JurisdictionCode::LEN + // jurisdiction
+        8; // cumulative_deducted_fee
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, DestinationReceipt::LEN];
+        let (
+            is_initialized_dst,
+            wallet_dst,
+            destination_dst,
+            token_mint_dst,
+            transfer_count_dst,
+            cumulative_amount_dst,
+            last_slot_dst,
+            jurisdiction_dst,
+            cumulative_deducted_fee_dst,
+        ) = mut_array_refs![
+            dst,
+            1,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            8,
+            8,
+            8,
+            JurisdictionCode::LEN,
+            8
+        ];
+
+        is_initialized_dst[0] = self.is_initialized as u8;
+        wallet_dst.copy_from_slice(self.wallet.as_ref());
+        destination_dst.copy_from_slice(self.destination.as_ref());
+        token_mint_dst.copy_from_slice(self.token_mint.as_ref());
+        *transfer_count_dst = self.transfer_count.to_le_bytes();
+        *cumulative_amount_dst = self.cumulative_amount.to_le_bytes();
+        *last_slot_dst = self.last_slot.to_le_bytes();
+        JurisdictionCode::pack_into_slice(&self.jurisdiction, jurisdiction_dst);
+        *cumulative_deducted_fee_dst = self.cumulative_deducted_fee.to_le_bytes();
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, DestinationReceipt::LEN];
+        let (
+            is_initialized_src,
+            wallet_src,
+            destination_src,
+            token_mint_src,
+            transfer_count_src,
+            cumulative_amount_src,
+            last_slot_src,
+            jurisdiction_src,
+            cumulative_deducted_fee_src,
+        ) = array_refs![
+            src,
+            1,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            8,
+            8,
+            8,
+            JurisdictionCode::LEN,
+            8
+        ];
+
+        let is_initialized = match is_initialized_src {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        Ok(DestinationReceipt {
+            is_initialized,
+            wallet: Pubkey::new_from_array(*wallet_src),
+            destination: Pubkey::new_from_array(*destination_src),
+            token_mint: Pubkey::new_from_array(*token_mint_src),
+            transfer_count: u64::from_le_bytes(*transfer_count_src),
+            cumulative_amount: u64::from_le_bytes(*cumulative_amount_src),
+            last_slot: u64::from_le_bytes(*last_slot_src),
+            jurisdiction: JurisdictionCode::unpack_from_slice(jurisdiction_src)?,
+            cumulative_deducted_fee: u64::from_le_bytes(*cumulative_deducted_fee_src),
+        })
+    }
+}
@@ -0,0 +1,94 @@
+use crate::error::WalletError;
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+
+/// The most lamports a multisig op may collect as its fee from the wallet's fee-paying balance
+/// account. Fees are checked against the cap when an op is initiated, and the cap in effect
+/// when it is finalized bounds what is collected.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct FeePolicy {
+    /// The cap on a single op's fee, none leaving fees uncapped.
+    pub max_fee_amount: Option<u64>,
+}
+
+impl FeePolicy {
+    pub const LEN: usize = 1 + // has cap
+        8; // max fee amount
+
+    pub fn validate_fee(&self, fee_amount: u64) -> ProgramResult {
+        match self.max_fee_amount {
+            Some(max_fee_amount) if fee_amount > max_fee_amount => {
+                msg!(
+                    "Fee of {} lamports exceeds the wallet's cap of {}",
+                    fee_amount,
+                    max_fee_amount
+                );
+                Err(WalletError::FeeExceedsCap.into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// How much of an op's fee can be collected under the current cap.
+    pub fn collectable(&self, fee_amount: u64) -> u64 {
+        match self.max_fee_amount {
+            Some(max_fee_amount) => fee_amount.min(max_fee_amount),
+            None => fee_amount,
+        }
+    }
+
+    pub fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, FeePolicy::LEN];
+        let (has_cap_dst, max_fee_amount_dst) = mut_array_refs![dst, 1, 8];
+        has_cap_dst[0] = self.max_fee_amount.is_some() as u8;
+        *max_fee_amount_dst = self.max_fee_amount.unwrap_or(0).to_le_bytes();
+    }
+
+    pub fn unpack_from_slice(src: &[u8]) -> Result<FeePolicy, ProgramError> {
+        let src = array_ref![src, 0, FeePolicy::LEN];
+        let (has_cap, max_fee_amount) = array_refs![src, 1, 8];
+        Ok(FeePolicy {
+            max_fee_amount: match has_cap {
+                [0] => None,
+                [1] => Some(u64::from_le_bytes(*max_fee_amount)),
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::WalletError;
+    use crate::model::fee_policy::FeePolicy;
+    use crate::model::wallet::Wallet;
+    use solana_program::program_pack::Pack;
+
+    #[test]
+    fn test_fee_policy() {
+        let uncapped = FeePolicy::default();
+        assert_eq!(uncapped.validate_fee(u64::MAX), Ok(()));
+        assert_eq!(uncapped.collectable(5_000), 5_000);
+
+        let capped = FeePolicy {
+            max_fee_amount: Some(1_000),
+        };
+        assert_eq!(capped.validate_fee(1_000), Ok(()));
+        assert_eq!(
+            capped.validate_fee(1_001),
+            Err(WalletError::FeeExceedsCap.into())
+        );
+        // a cap lowered after an op was initiated bounds what it collects
+        assert_eq!(capped.collectable(5_000), 1_000);
+
+        // it is read back from a packed wallet without unpacking the rest of it
+        let mut wallet = Wallet::unpack_unchecked(&vec![0; Wallet::LEN]).unwrap();
+        wallet.is_initialized = true;
+        wallet.fee_policy = capped;
+        let mut buffer = vec![0xff; Wallet::LEN];
+        wallet.pack_into_slice(&mut buffer);
+        assert_eq!(Wallet::fee_policy_from_slice(&buffer), Ok(capped));
+    }
+}
@@ -0,0 +1,170 @@
+use crate::error::WalletError;
+use crate::utils::is_strictly_ascending_by_key;
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
+
+/// Who may submit the finalize transaction of an operation, i.e. sign it as the operation's
+/// rent return account.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[repr(u8)]
+pub enum FinalizeSubmitters {
+    Anyone = 0,
+    /// An enabled assistant of the wallet.
+    Assistant = 1,
+    /// One of the operation's approvers.
+    Approvers = 2,
+    /// One of the policy's named submitter keys.
+    Named = 3,
+}
+
+impl FinalizeSubmitters {
+    pub fn from_u8(value: u8) -> Result<FinalizeSubmitters, ProgramError> {
+        match value {
+            0 => Ok(FinalizeSubmitters::Anyone),
+            1 => Ok(FinalizeSubmitters::Assistant),
+            2 => Ok(FinalizeSubmitters::Approvers),
+            3 => Ok(FinalizeSubmitters::Named),
+            _ => Err(WalletError::InvalidFinalizeSubmitterPolicy.into()),
+        }
+    }
+
+    pub fn to_u8(&self) -> u8 {
+        *self as u8
+    }
+}
+
+impl Default for FinalizeSubmitters {
+    fn default() -> Self {
+        FinalizeSubmitters::Anyone
+    }
+}
+
+/// The categories of operations the finalize submitter policy distinguishes.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FinalizeCategory {
+    /// Changes to the wallet's or a balance account's configuration.
+    Config,
+    /// Operations moving funds out of a balance account.
+    Transfer,
+    /// DApp transactions and data signed on behalf of a balance account.
+    DApp,
+}
+
+impl FinalizeCategory {
+    pub fn from_u8(value: u8) -> Result<FinalizeCategory, ProgramError> {
+        match value {
+            1 => Ok(FinalizeCategory::Config),
+            2 => Ok(FinalizeCategory::Transfer),
+            3 => Ok(FinalizeCategory::DApp),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+
+    pub fn to_u8(&self) -> u8 {
+        match self {
+            FinalizeCategory::Config => 1,
+            FinalizeCategory::Transfer => 2,
+            FinalizeCategory::DApp => 3,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct FinalizeSubmitterPolicy {
+    pub config: FinalizeSubmitters,
+    pub transfer: FinalizeSubmitters,
+    pub dapp: FinalizeSubmitters,
+    pub named_submitters: Vec<Pubkey>,
+}
+
+impl FinalizeSubmitterPolicy {
+    pub const MAX_NAMED_SUBMITTERS: usize = 4;
+    pub const LEN: usize = 1 + // config
+        1 + // transfer
+        1 + // dapp
+        1 + // named submitters count
+        PUBKEY_BYTES * FinalizeSubmitterPolicy::MAX_NAMED_SUBMITTERS; // named submitters
+
+    pub fn submitters(&self, category: FinalizeCategory) -> FinalizeSubmitters {
+        match category {
+            FinalizeCategory::Config => self.config,
+            FinalizeCategory::Transfer => self.transfer,
+            FinalizeCategory::DApp => self.dapp,
+        }
+    }
+
+    pub fn validate(&self) -> ProgramResult {
+        if self.named_submitters.len() > FinalizeSubmitterPolicy::MAX_NAMED_SUBMITTERS {
+            msg!(
+                "Named finalize submitters can't exceed {}",
+                FinalizeSubmitterPolicy::MAX_NAMED_SUBMITTERS
+            );
+            return Err(WalletError::InvalidFinalizeSubmitterPolicy.into());
+        }
+        let names_required =
+            [self.config, self.transfer, self.dapp].contains(&FinalizeSubmitters::Named);
+        if names_required && self.named_submitters.is_empty() {
+            msg!("At least one named finalize submitter is required");
+            return Err(WalletError::InvalidFinalizeSubmitterPolicy.into());
+        }
+        Ok(())
+    }
+
+    pub fn validate_canonical(&self) -> ProgramResult {
+        if !is_strictly_ascending_by_key(&self.named_submitters, |key| *key) {
+            msg!("Named finalize submitters must be sorted and free of duplicates");
+            return Err(WalletError::NonCanonicalParams.into());
+        }
+        Ok(())
+    }
+
+    pub fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, FinalizeSubmitterPolicy::LEN];
+        let (config_dst, transfer_dst, dapp_dst, count_dst, named_submitters_dst) = mut_array_refs![
+            dst,
+            1,
+            1,
+            1,
+            1,
+            PUBKEY_BYTES * FinalizeSubmitterPolicy::MAX_NAMED_SUBMITTERS
+        ];
+        config_dst[0] = self.config.to_u8();
+        transfer_dst[0] = self.transfer.to_u8();
+        dapp_dst[0] = self.dapp.to_u8();
+        count_dst[0] = self.named_submitters.len() as u8;
+        named_submitters_dst.fill(0);
+        named_submitters_dst
+            .chunks_exact_mut(PUBKEY_BYTES)
+            .zip(self.named_submitters.iter())
+            .for_each(|(chunk, key)| chunk.copy_from_slice(key.as_ref()));
+    }
+
+    pub fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, FinalizeSubmitterPolicy::LEN];
+        let (config, transfer, dapp, count, named_submitters_src) = array_refs![
+            src,
+            1,
+            1,
+            1,
+            1,
+            PUBKEY_BYTES * FinalizeSubmitterPolicy::MAX_NAMED_SUBMITTERS
+        ];
+        let count = usize::from(count[0]);
+        if count > FinalizeSubmitterPolicy::MAX_NAMED_SUBMITTERS {
+            return Err(WalletError::InvalidFinalizeSubmitterPolicy.into());
+        }
+        Ok(FinalizeSubmitterPolicy {
+            config: FinalizeSubmitters::from_u8(config[0])?,
+            transfer: FinalizeSubmitters::from_u8(transfer[0])?,
+            dapp: FinalizeSubmitters::from_u8(dapp[0])?,
+            named_submitters: named_submitters_src
+                .chunks_exact(PUBKEY_BYTES)
+                .take(count)
+                .map(Pubkey::new)
+                .collect(),
+        })
+    }
+}
@@ -0,0 +1,248 @@
+use crate::constants::{HASH_LEN, PUBKEY_BYTES};
+use crate::error::WalletError;
+use crate::pda::{InsuranceAttestationSeeds, PdaSeeds};
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::Hash;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{IsInitialized, Pack, Sealed};
+use solana_program::pubkey::Pubkey;
+use std::time::Duration;
+
+/// The custody insurance attestation of a wallet: the hash of the latest statement its insurer
+/// or auditor signed off-chain, and how long the coverage it attests to lasts. It lives in a
+/// PDA derived from the wallet, created when the wallet registers the attestor with an
+/// `UpdateInsuranceAttestor` op, so that the wallet's clients can prove continuous coverage
+/// from chain state alone.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct InsuranceAttestation {
+    pub is_initialized: bool,
+    pub wallet: Pubkey,
+    /// The key the wallet registered to attest to its coverage.
+    pub attestor: Pubkey,
+    /// The hash of the attestor's signed statement, zero until the first attestation.
+    pub statement_hash: Hash,
+    pub attested_at: i64,
+    pub coverage_expires_at: i64,
+    /// When the current run of unbroken coverage began. An attestation made while the wallet
+    /// is covered extends the run, and one made after the coverage lapsed starts a new one.
+    pub covered_since: i64,
+    /// Whether the lapse of the coverage has been reported to the wallet's event log, so that
+    /// each lapse is reported once.
+    pub lapse_reported: bool,
+}
+
+impl InsuranceAttestation {
+    /// The furthest ahead an attestation can put the coverage's expiry.
+    pub const MAX_COVERAGE_PERIOD: Duration = Duration::from_secs(366 * 24 * 60 * 60);
+
+    pub fn new(wallet: Pubkey, attestor: Pubkey) -> Self {
+        InsuranceAttestation {
+            is_initialized: true,
+            wallet,
+            attestor,
+            statement_hash: Hash::default(),
+            attested_at: 0,
+            coverage_expires_at: 0,
+            covered_since: 0,
+            lapse_reported: false,
+        }
+    }
+
+    /// Derive the PDA and "bump seed" of the attestation of the given wallet.
+    pub fn find_address(wallet: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        InsuranceAttestationSeeds { wallet }.find_address(program_id)
+    }
+
+    pub fn is_attested(&self) -> bool {
+        self.statement_hash != Hash::default()
+    }
+
+    pub fn is_covered(&self, now: i64) -> bool {
+        self.is_attested() && now < self.coverage_expires_at
+    }
+
+    /// Records the attestor's latest statement, which has to cover the wallet until some time
+    /// in the next `MAX_COVERAGE_PERIOD`.
+    pub fn attest(
+        &mut self,
+        attestor: &Pubkey,
+        statement_hash: &Hash,
+        coverage_expires_at: i64,
+        now: i64,
+    ) -> ProgramResult {
+        if *attestor != self.attestor {
+            msg!("{} isn't the wallet's registered attestor", attestor);
+            return Err(WalletError::InvalidInsuranceAttestor.into());
+        }
+        if *statement_hash == Hash::default() {
+            msg!("An attestation needs the hash of its statement");
+            return Err(WalletError::InvalidInsuranceAttestor.into());
+        }
+        if coverage_expires_at <= now
+            || coverage_expires_at - now
+                > InsuranceAttestation::MAX_COVERAGE_PERIOD.as_secs() as i64
+        {
+            return Err(WalletError::InvalidCoverageExpiry.into());
+        }
+        if !self.is_covered(now) {
+            self.covered_since = now;
+        }
+        self.statement_hash = *statement_hash;
+        self.attested_at = now;
+        self.coverage_expires_at = coverage_expires_at;
+        self.lapse_reported = false;
+        Ok(())
+    }
+
+    /// Marks the lapse of an attested coverage as reported, returning whether it had lapsed
+    /// without having been reported yet.
+    pub fn report_lapse(&mut self, now: i64) -> bool {
+        if !self.is_attested() || self.is_covered(now) || self.lapse_reported {
+            return false;
+        }
+        self.lapse_reported = true;
+        true
+    }
+}
+
+impl Sealed for InsuranceAttestation {}
+
+impl IsInitialized for InsuranceAttestation {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for InsuranceAttestation {
+    const LEN: usize = 1 + // is_initialized
+        PUBKEY_BYTES + // wallet
+        PUBKEY_BYTES + // attestor
+        HASH_LEN + // statement hash
+        8 + // attested at
+        8 + // coverage expires at
+        8 + // covered since
+        1; // lapse reported
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, InsuranceAttestation::LEN];
+        let (
+            is_initialized_dst,
+            wallet_dst,
+            attestor_dst,
+            statement_hash_dst,
+            attested_at_dst,
+            coverage_expires_at_dst,
+            covered_since_dst,
+            lapse_reported_dst,
+        ) = mut_array_refs![dst, 1, PUBKEY_BYTES, PUBKEY_BYTES, HASH_LEN, 8, 8, 8, 1];
+
+        is_initialized_dst[0] = self.is_initialized as u8;
+        wallet_dst.copy_from_slice(self.wallet.as_ref());
+        attestor_dst.copy_from_slice(self.attestor.as_ref());
+        statement_hash_dst.copy_from_slice(self.statement_hash.as_ref());
+        *attested_at_dst = self.attested_at.to_le_bytes();
+        *coverage_expires_at_dst = self.coverage_expires_at.to_le_bytes();
+        *covered_since_dst = self.covered_since.to_le_bytes();
+        lapse_reported_dst[0] = self.lapse_reported as u8;
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, InsuranceAttestation::LEN];
+        let (
+            is_initialized_src,
+            wallet_src,
+            attestor_src,
+            statement_hash_src,
+            attested_at_src,
+            coverage_expires_at_src,
+            covered_since_src,
+            lapse_reported_src,
+        ) = array_refs![src, 1, PUBKEY_BYTES, PUBKEY_BYTES, HASH_LEN, 8, 8, 8, 1];
+
+        let is_initialized = match is_initialized_src {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        Ok(InsuranceAttestation {
+            is_initialized,
+            wallet: Pubkey::new_from_array(*wallet_src),
+            attestor: Pubkey::new_from_array(*attestor_src),
+            statement_hash: Hash::new_from_array(*statement_hash_src),
+            attested_at: i64::from_le_bytes(*attested_at_src),
+            coverage_expires_at: i64::from_le_bytes(*coverage_expires_at_src),
+            covered_since: i64::from_le_bytes(*covered_since_src),
+            lapse_reported: lapse_reported_src[0] == 1,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::WalletError;
+    use crate::model::insurance_attestation::InsuranceAttestation;
+    use solana_program::hash::Hash;
+    use solana_program::program_pack::Pack;
+    use solana_program::pubkey::Pubkey;
+
+    const DAY: i64 = 24 * 60 * 60;
+
+    #[test]
+    fn test_attest() {
+        let attestor = Pubkey::new_unique();
+        let mut attestation = InsuranceAttestation::new(Pubkey::new_unique(), attestor);
+        assert!(!attestation.is_covered(0));
+
+        // only the registered attestor can attest, to a coverage in the allowed period
+        assert_eq!(
+            attestation.attest(&Pubkey::new_unique(), &Hash::new_unique(), 30 * DAY, 0),
+            Err(WalletError::InvalidInsuranceAttestor.into())
+        );
+        assert_eq!(
+            attestation.attest(&attestor, &Hash::default(), 30 * DAY, 0),
+            Err(WalletError::InvalidInsuranceAttestor.into())
+        );
+        for coverage_expires_at in [0, 367 * DAY] {
+            assert_eq!(
+                attestation.attest(&attestor, &Hash::new_unique(), coverage_expires_at, 0),
+                Err(WalletError::InvalidCoverageExpiry.into())
+            );
+        }
+
+        // attesting before the coverage expires extends the run of coverage
+        attestation
+            .attest(&attestor, &Hash::new_unique(), 30 * DAY, 0)
+            .unwrap();
+        attestation
+            .attest(&attestor, &Hash::new_unique(), 60 * DAY, 29 * DAY)
+            .unwrap();
+        assert!(attestation.is_covered(59 * DAY));
+        assert_eq!(attestation.covered_since, 0);
+        assert!(!attestation.report_lapse(59 * DAY));
+
+        // a lapse is reported once, and attesting after it starts a new run
+        assert!(attestation.report_lapse(60 * DAY));
+        assert!(!attestation.report_lapse(61 * DAY));
+        attestation
+            .attest(&attestor, &Hash::new_unique(), 90 * DAY, 62 * DAY)
+            .unwrap();
+        assert_eq!(attestation.covered_since, 62 * DAY);
+        assert!(!attestation.lapse_reported);
+    }
+
+    #[test]
+    fn test_pack_unpack_round_trip() {
+        let attestor = Pubkey::new_unique();
+        let mut attestation = InsuranceAttestation::new(Pubkey::new_unique(), attestor);
+        attestation
+            .attest(&attestor, &Hash::new_unique(), 1_000, 10)
+            .unwrap();
+        attestation.report_lapse(1_000);
+        let mut buffer = vec![0; InsuranceAttestation::LEN];
+        InsuranceAttestation::pack(attestation.clone(), &mut buffer).unwrap();
+        assert_eq!(InsuranceAttestation::unpack(&buffer).unwrap(), attestation);
+    }
+}
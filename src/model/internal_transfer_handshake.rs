@@ -0,0 +1,129 @@
+use crate::constants::{HASH_LEN, PUBKEY_BYTES};
+use crate::model::balance_account::BalanceAccountGuidHash;
+use crate::pda::{InternalTransferHandshakeSeeds, PdaSeeds};
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::hash::Hash;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{IsInitialized, Pack, Sealed};
+use solana_program::pubkey::Pubkey;
+
+/// Records an acknowledgement, by an approver of the destination wallet, of an
+/// internal transfer between two wallets managed by this program. It lives in a
+/// PDA derived from the transfer's multisig op account and destination, and is
+/// closed when the transfer is finalized.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct InternalTransferHandshake {
+    pub is_initialized: bool,
+    pub multisig_op: Pubkey,
+    pub destination_wallet: Pubkey,
+    pub destination_account_guid_hash: BalanceAccountGuidHash,
+    pub destination: Pubkey,
+    pub acknowledged_by: Pubkey,
+    /// The params hash of the transfer that was acknowledged.
+    pub params_hash: Hash,
+}
+
+impl InternalTransferHandshake {
+    /// Derive the PDA and "bump seed" of the handshake for the given multisig op and
+    /// destination account.
+    pub fn find_address(
+        multisig_op: &Pubkey,
+        destination: &Pubkey,
+        program_id: &Pubkey,
+    ) -> (Pubkey, u8) {
+        InternalTransferHandshakeSeeds {
+            multisig_op,
+            destination,
+        }
+        .find_address(program_id)
+    }
+}
+
+impl Sealed for InternalTransferHandshake {}
+
+impl IsInitialized for InternalTransferHandshake {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for InternalTransferHandshake {
+    const LEN: usize = 1 + // is_initialized
+        PUBKEY_BYTES + // multisig_op
+        PUBKEY_BYTES + // destination_wallet
+        HASH_LEN + // destination_account_guid_hash
+        PUBKEY_BYTES + // destination
+        PUBKEY_BYTES + // acknowledged_by
+        HASH_LEN; // params_hash
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, InternalTransferHandshake::LEN];
+        let (
+            is_initialized_dst,
+            multisig_op_dst,
+            destination_wallet_dst,
+            destination_account_guid_hash_dst,
+            destination_dst,
+            acknowledged_by_dst,
+            params_hash_dst,
+        ) = mut_array_refs![
+            dst,
+            1,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            HASH_LEN,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            HASH_LEN
+        ];
+
+        is_initialized_dst[0] = self.is_initialized as u8;
+        multisig_op_dst.copy_from_slice(self.multisig_op.as_ref());
+        destination_wallet_dst.copy_from_slice(self.destination_wallet.as_ref());
+        destination_account_guid_hash_dst
+            .copy_from_slice(self.destination_account_guid_hash.to_bytes());
+        destination_dst.copy_from_slice(self.destination.as_ref());
+        acknowledged_by_dst.copy_from_slice(self.acknowledged_by.as_ref());
+        params_hash_dst.copy_from_slice(self.params_hash.as_ref());
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, InternalTransferHandshake::LEN];
+        let (
+            is_initialized_src,
+            multisig_op_src,
+            destination_wallet_src,
+            destination_account_guid_hash_src,
+            destination_src,
+            acknowledged_by_src,
+            params_hash_src,
+        ) = array_refs![
+            src,
+            1,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            HASH_LEN,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            HASH_LEN
+        ];
+
+        let is_initialized = match is_initialized_src {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        Ok(InternalTransferHandshake {
+            is_initialized,
+            multisig_op: Pubkey::new_from_array(*multisig_op_src),
+            destination_wallet: Pubkey::new_from_array(*destination_wallet_src),
+            destination_account_guid_hash: BalanceAccountGuidHash::new(
+                destination_account_guid_hash_src,
+            ),
+            destination: Pubkey::new_from_array(*destination_src),
+            acknowledged_by: Pubkey::new_from_array(*acknowledged_by_src),
+            params_hash: Hash::new_from_array(*params_hash_src),
+        })
+    }
+}
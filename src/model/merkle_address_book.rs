@@ -0,0 +1,239 @@
+use crate::constants::{HASH_LEN, PUBKEY_BYTES};
+use crate::model::address_book::AddressBookEntry;
+use crate::model::balance_account::BalanceAccountGuidHash;
+use crate::pda::{MerkleAddressBookSeeds, PdaSeeds};
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::hash::{hashv, Hash};
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{IsInitialized, Pack, Sealed};
+use solana_program::pubkey::Pubkey;
+
+/// An address book of a balance account kept off-chain, of which only the Merkle root is
+/// stored. It lives in a PDA derived from the wallet and the balance account's GUID hash, so a
+/// whitelisted balance account can transfer to any of its entries, however many there are, on
+/// a proof of membership. A zero root has no entries.
+///
+/// Leaves are the hashes of the entries, and each node hashes its two children in sorted order,
+/// so a proof is just the sibling hashes on the path from the leaf to the root. A node without
+/// a sibling is carried up to the next level as it is.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MerkleAddressBook {
+    pub is_initialized: bool,
+    pub wallet: Pubkey,
+    pub account_guid_hash: BalanceAccountGuidHash,
+    pub root: Hash,
+}
+
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+impl MerkleAddressBook {
+    /// Enough for an address book of 2^32 entries.
+    pub const MAX_PROOF_LEN: usize = 32;
+
+    pub fn new(wallet: Pubkey, account_guid_hash: BalanceAccountGuidHash, root: Hash) -> Self {
+        MerkleAddressBook {
+            is_initialized: true,
+            wallet,
+            account_guid_hash,
+            root,
+        }
+    }
+
+    /// Derive the PDA and "bump seed" of the address book for the given wallet and balance
+    /// account.
+    pub fn find_address(
+        wallet: &Pubkey,
+        account_guid_hash: &BalanceAccountGuidHash,
+        program_id: &Pubkey,
+    ) -> (Pubkey, u8) {
+        MerkleAddressBookSeeds {
+            wallet,
+            account_guid_hash,
+        }
+        .find_address(program_id)
+    }
+
+    pub fn leaf(entry: &AddressBookEntry) -> Hash {
+        hashv(&[
+            LEAF_PREFIX,
+            entry.address.as_ref(),
+            entry.name_hash.to_bytes(),
+        ])
+    }
+
+    fn node(left: &Hash, right: &Hash) -> Hash {
+        let (first, second) = if left <= right {
+            (left, right)
+        } else {
+            (right, left)
+        };
+        hashv(&[NODE_PREFIX, first.as_ref(), second.as_ref()])
+    }
+
+    fn next_level(level: &[Hash]) -> Vec<Hash> {
+        level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => Self::node(left, right),
+                _ => pair[0],
+            })
+            .collect()
+    }
+
+    /// The root of the given entries, or a zero hash if there are none.
+    pub fn root_of(entries: &[AddressBookEntry]) -> Hash {
+        let mut level: Vec<Hash> = entries.iter().map(Self::leaf).collect();
+        if level.is_empty() {
+            return Hash::default();
+        }
+        while level.len() > 1 {
+            level = Self::next_level(&level);
+        }
+        level[0]
+    }
+
+    /// The proof of membership of the entry at `index` among the given entries.
+    pub fn proof_of(entries: &[AddressBookEntry], mut index: usize) -> Vec<Hash> {
+        let mut level: Vec<Hash> = entries.iter().map(Self::leaf).collect();
+        let mut proof = Vec::new();
+        while level.len() > 1 {
+            if let Some(sibling) = level.get(index ^ 1) {
+                proof.push(*sibling);
+            }
+            level = Self::next_level(&level);
+            index /= 2;
+        }
+        proof
+    }
+
+    pub fn contains(&self, entry: &AddressBookEntry, proof: &[Hash]) -> bool {
+        self.root != Hash::default()
+            && proof.len() <= Self::MAX_PROOF_LEN
+            && proof.iter().fold(Self::leaf(entry), |hash, sibling| {
+                Self::node(&hash, sibling)
+            }) == self.root
+    }
+}
+
+impl Sealed for MerkleAddressBook {}
+
+impl IsInitialized for MerkleAddressBook {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for MerkleAddressBook {
+    const LEN: usize = 1 + // is_initialized
+        PUBKEY_BYTES + // wallet
+        HASH_LEN + // account_guid_hash
+        HASH_LEN; // root
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, MerkleAddressBook::LEN];
+        let (is_initialized_dst, wallet_dst, account_guid_hash_dst, root_dst) =
+            mut_array_refs![dst, 1, PUBKEY_BYTES, HASH_LEN, HASH_LEN];
+
+        is_initialized_dst[0] = self.is_initialized as u8;
+        wallet_dst.copy_from_slice(self.wallet.as_ref());
+        account_guid_hash_dst.copy_from_slice(self.account_guid_hash.to_bytes());
+        root_dst.copy_from_slice(self.root.as_ref());
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, MerkleAddressBook::LEN];
+        let (is_initialized_src, wallet_src, account_guid_hash_src, root_src) =
+            array_refs![src, 1, PUBKEY_BYTES, HASH_LEN, HASH_LEN];
+
+        let is_initialized = match is_initialized_src {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        Ok(MerkleAddressBook {
+            is_initialized,
+            wallet: Pubkey::new_from_array(*wallet_src),
+            account_guid_hash: BalanceAccountGuidHash::new(account_guid_hash_src),
+            root: Hash::new_from_array(*root_src),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::model::address_book::{AddressBookEntry, AddressBookEntryNameHash};
+    use crate::model::balance_account::BalanceAccountGuidHash;
+    use crate::model::merkle_address_book::MerkleAddressBook;
+    use solana_program::hash::Hash;
+    use solana_program::program_pack::Pack;
+    use solana_program::pubkey::Pubkey;
+
+    fn entries(count: u8) -> Vec<AddressBookEntry> {
+        (0..count)
+            .map(|i| AddressBookEntry {
+                address: Pubkey::new_unique(),
+                name_hash: AddressBookEntryNameHash::new(&[i; 32]),
+            })
+            .collect()
+    }
+
+    fn book(root: Hash) -> MerkleAddressBook {
+        MerkleAddressBook::new(
+            Pubkey::new_unique(),
+            BalanceAccountGuidHash::new(&[1; 32]),
+            root,
+        )
+    }
+
+    #[test]
+    fn test_proof_of_membership() {
+        for count in 1..=9 {
+            let entries = entries(count);
+            let book = book(MerkleAddressBook::root_of(&entries));
+            for (index, entry) in entries.iter().enumerate() {
+                let proof = MerkleAddressBook::proof_of(&entries, index);
+                assert!(book.contains(entry, &proof), "{} of {}", index, count);
+
+                // a proof only holds for the entry it was made for
+                let other = AddressBookEntry {
+                    address: Pubkey::new_unique(),
+                    name_hash: entry.name_hash,
+                };
+                assert!(!book.contains(&other, &proof));
+                let renamed = AddressBookEntry {
+                    address: entry.address,
+                    name_hash: AddressBookEntryNameHash::new(&[0xff; 32]),
+                };
+                assert!(!book.contains(&renamed, &proof));
+            }
+        }
+    }
+
+    #[test]
+    fn test_proof_is_rejected() {
+        let entries = entries(4);
+        let book = book(MerkleAddressBook::root_of(&entries));
+        let mut proof = MerkleAddressBook::proof_of(&entries, 1);
+        assert!(!book.contains(&entries[1], &proof[1..]));
+        proof[0] = Hash::new_unique();
+        assert!(!book.contains(&entries[1], &proof));
+
+        // an inner node can't pass for an entry
+        assert!(!book.contains(&entries[0], &[]));
+
+        // nothing is a member of an empty address book
+        assert_eq!(MerkleAddressBook::root_of(&[]), Hash::default());
+        let empty = self::book(Hash::default());
+        assert!(!empty.contains(&entries[0], &[]));
+    }
+
+    #[test]
+    fn test_pack_unpack_round_trip() {
+        let book = book(Hash::new_unique());
+        let mut buffer = vec![0; MerkleAddressBook::LEN];
+        MerkleAddressBook::pack(book.clone(), &mut buffer).unwrap();
+        assert_eq!(MerkleAddressBook::unpack(&buffer).unwrap(), book);
+    }
+}
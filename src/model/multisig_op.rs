@@ -2,29 +2,46 @@ use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
 use bitvec::macros::internal::funty::Fundamental;
 use bytes::BufMut;
 use solana_program::account_info::AccountInfo;
-use solana_program::clock::Clock;
+use solana_program::clock::{Clock, DEFAULT_MS_PER_SLOT};
 use solana_program::entrypoint::ProgramResult;
-use solana_program::hash::{hash, Hash, HASH_BYTES};
+use solana_program::hash::{hash, hashv, Hash, HASH_BYTES};
 use solana_program::instruction::Instruction;
 use solana_program::msg;
 use solana_program::program_error::ProgramError;
 use solana_program::program_pack::{IsInitialized, Pack, Sealed};
 use solana_program::pubkey::Pubkey;
 
-use crate::constants::{HASH_LEN, PUBKEY_BYTES};
+use crate::constants::{HASH_LEN, PUBKEY_BYTES, VERSION_LEN};
 use crate::error::WalletError;
 use crate::handlers::utils::log_op_disposition;
 use crate::instruction::{
-    append_instruction, AddressBookUpdate, BalanceAccountAddressWhitelistUpdate,
-    BalanceAccountCreation, BalanceAccountPolicyUpdate, DAppBookUpdate, WalletConfigPolicyUpdate,
+    append_address_book_renewal, append_approval_group, append_compliance_policy,
+    append_fee_policy, append_finalize_submitter_policy, append_instruction, append_price_oracle,
+    append_pubkeys, append_reward_splits, append_stake_weight_governance, append_wallet_freeze,
+    append_wallet_replication, AddressBookImport, AddressBookUpdate,
+    BalanceAccountAddressWhitelistUpdate, BalanceAccountCreation, BalanceAccountPolicyUpdate,
+    DAppBookUpdate, FullRotation, RewardSplit, WalletConfigPolicyUpdate,
 };
-use crate::model::address_book::DAppBookEntry;
+use crate::model::address_book::{AddressBookEntry, DAppBookEntry, JurisdictionCode};
+use crate::model::approval_group::ApprovalGroup;
+use crate::model::assistant::Assistant;
 use crate::model::balance_account::{BalanceAccountGuidHash, BalanceAccountNameHash};
+use crate::model::compliance_policy::CompliancePolicy;
+use crate::model::fee_policy::FeePolicy;
+use crate::model::finalize_submitter_policy::{FinalizeCategory, FinalizeSubmitterPolicy};
+use crate::model::price_oracle::PriceOracle;
+use crate::model::rent_return_allowlist::RentReturnAllowlist;
+use crate::model::session_key_registry::SessionKey;
 use crate::model::signer::Signer;
-use crate::model::wallet::Wallet;
-use crate::serialization_utils::pack_option;
-use crate::utils::SlotId;
+use crate::model::stake_weight_governance::StakeWeightGovernance;
+use crate::model::wallet::{Wallet, WalletCompaction};
+use crate::model::wallet_freeze::WalletFreeze;
+use crate::model::wallet_replication::WalletReplication;
+use crate::model::wrapper_book::WrapperBook;
+use crate::serialization_utils::{append_optional_pubkey, pack_option};
+use crate::utils::{duration_secs, duration_slots, SlotId};
 use crate::version::{Versioned, VERSION};
+use std::time::Duration;
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum MultisigOpCode {
@@ -42,6 +59,35 @@ pub enum MultisigOpCode {
     CreateSPLTokenAccounts,
     UpdateBalanceAccountAddressWhitelist,
     SignData,
+    VoteRewardsWithdrawal,
+    UpdateAssistant,
+    UpdateRewardSplitPolicy,
+    UpdateFinalizeSubmitterPolicy,
+    MintTo,
+    SetMintAuthority,
+    OpenPaymentChannel,
+    UpdateLookupTable,
+    ImportAddressBookEntries,
+    CommittedTransfer,
+    UpdateCompliancePolicy,
+    UpdateWalletReplication,
+    DeleteBalanceAccount,
+    UpdateSpendingLimit,
+    UpdatePriceOracle,
+    UpdateMerkleAddressBook,
+    UpdateWalletFreeze,
+    UpdateRentReturnAllowlist,
+    FullRotation,
+    UpdateSignerActivationDelay,
+    UpdateFeePolicy,
+    UpdateStakeWeightGovernance,
+    RenewAddressBookEntry,
+    UpdateApprovalGroup,
+    EnableDepositAddresses,
+    CompactWallet,
+    UpdateInsuranceAttestor,
+    UpdateWrapperBook,
+    AdapterWrap,
 }
 
 impl From<MultisigOpCode> for u8 {
@@ -61,6 +107,86 @@ impl From<MultisigOpCode> for u8 {
             MultisigOpCode::CreateSPLTokenAccounts => 13,
             MultisigOpCode::UpdateBalanceAccountAddressWhitelist => 14,
             MultisigOpCode::SignData => 15,
+            MultisigOpCode::VoteRewardsWithdrawal => 16,
+            MultisigOpCode::UpdateAssistant => 17,
+            MultisigOpCode::UpdateRewardSplitPolicy => 18,
+            MultisigOpCode::UpdateFinalizeSubmitterPolicy => 19,
+            MultisigOpCode::MintTo => 20,
+            MultisigOpCode::SetMintAuthority => 21,
+            MultisigOpCode::OpenPaymentChannel => 22,
+            MultisigOpCode::UpdateLookupTable => 23,
+            MultisigOpCode::ImportAddressBookEntries => 24,
+            MultisigOpCode::CommittedTransfer => 25,
+            MultisigOpCode::UpdateCompliancePolicy => 26,
+            MultisigOpCode::UpdateWalletReplication => 27,
+            MultisigOpCode::DeleteBalanceAccount => 28,
+            MultisigOpCode::UpdateSpendingLimit => 29,
+            MultisigOpCode::UpdatePriceOracle => 30,
+            MultisigOpCode::UpdateMerkleAddressBook => 31,
+            MultisigOpCode::UpdateWalletFreeze => 32,
+            MultisigOpCode::UpdateRentReturnAllowlist => 33,
+            MultisigOpCode::FullRotation => 34,
+            MultisigOpCode::UpdateSignerActivationDelay => 35,
+            MultisigOpCode::UpdateFeePolicy => 36,
+            MultisigOpCode::UpdateStakeWeightGovernance => 37,
+            MultisigOpCode::RenewAddressBookEntry => 38,
+            MultisigOpCode::UpdateApprovalGroup => 39,
+            MultisigOpCode::EnableDepositAddresses => 40,
+            MultisigOpCode::CompactWallet => 41,
+            MultisigOpCode::UpdateInsuranceAttestor => 42,
+            MultisigOpCode::UpdateWrapperBook => 43,
+            MultisigOpCode::AdapterWrap => 44,
+        }
+    }
+}
+
+impl MultisigOpCode {
+    pub fn from_u8(value: u8) -> Result<MultisigOpCode, ProgramError> {
+        match value {
+            1 => Ok(MultisigOpCode::CreateBalanceAccount),
+            3 => Ok(MultisigOpCode::Transfer),
+            4 => Ok(MultisigOpCode::Wrap),
+            5 => Ok(MultisigOpCode::UpdateSigner),
+            6 => Ok(MultisigOpCode::UpdateWalletConfigPolicy),
+            7 => Ok(MultisigOpCode::DAppTransaction),
+            8 => Ok(MultisigOpCode::UpdateBalanceAccountSettings),
+            9 => Ok(MultisigOpCode::UpdateDAppBook),
+            10 => Ok(MultisigOpCode::AddressBookUpdate),
+            11 => Ok(MultisigOpCode::UpdateBalanceAccountName),
+            12 => Ok(MultisigOpCode::UpdateBalanceAccountPolicy),
+            13 => Ok(MultisigOpCode::CreateSPLTokenAccounts),
+            14 => Ok(MultisigOpCode::UpdateBalanceAccountAddressWhitelist),
+            15 => Ok(MultisigOpCode::SignData),
+            16 => Ok(MultisigOpCode::VoteRewardsWithdrawal),
+            17 => Ok(MultisigOpCode::UpdateAssistant),
+            18 => Ok(MultisigOpCode::UpdateRewardSplitPolicy),
+            19 => Ok(MultisigOpCode::UpdateFinalizeSubmitterPolicy),
+            20 => Ok(MultisigOpCode::MintTo),
+            21 => Ok(MultisigOpCode::SetMintAuthority),
+            22 => Ok(MultisigOpCode::OpenPaymentChannel),
+            23 => Ok(MultisigOpCode::UpdateLookupTable),
+            24 => Ok(MultisigOpCode::ImportAddressBookEntries),
+            25 => Ok(MultisigOpCode::CommittedTransfer),
+            26 => Ok(MultisigOpCode::UpdateCompliancePolicy),
+            27 => Ok(MultisigOpCode::UpdateWalletReplication),
+            28 => Ok(MultisigOpCode::DeleteBalanceAccount),
+            29 => Ok(MultisigOpCode::UpdateSpendingLimit),
+            30 => Ok(MultisigOpCode::UpdatePriceOracle),
+            31 => Ok(MultisigOpCode::UpdateMerkleAddressBook),
+            32 => Ok(MultisigOpCode::UpdateWalletFreeze),
+            33 => Ok(MultisigOpCode::UpdateRentReturnAllowlist),
+            34 => Ok(MultisigOpCode::FullRotation),
+            35 => Ok(MultisigOpCode::UpdateSignerActivationDelay),
+            36 => Ok(MultisigOpCode::UpdateFeePolicy),
+            37 => Ok(MultisigOpCode::UpdateStakeWeightGovernance),
+            38 => Ok(MultisigOpCode::RenewAddressBookEntry),
+            39 => Ok(MultisigOpCode::UpdateApprovalGroup),
+            40 => Ok(MultisigOpCode::EnableDepositAddresses),
+            41 => Ok(MultisigOpCode::CompactWallet),
+            42 => Ok(MultisigOpCode::UpdateInsuranceAttestor),
+            43 => Ok(MultisigOpCode::UpdateWrapperBook),
+            44 => Ok(MultisigOpCode::AdapterWrap),
+            _ => Err(ProgramError::InvalidAccountData),
         }
     }
 }
@@ -119,22 +245,161 @@ impl OperationDisposition {
     }
 }
 
+/// Where a multisig op is in its lifecycle. Ops move between states only through
+/// `MultisigOp::transition`, which rejects any move the lifecycle doesn't allow.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum MultisigOpState {
+    /// The op account hasn't been initialized.
+    Uninitialized,
+    /// The op was started before its params were known, as for a dApp transaction whose
+    /// instructions are still being supplied.
+    PendingInstructions,
+    /// The op is waiting on its approvers.
+    Open,
+    Approved,
+    Denied,
+    Expired,
+    /// The op has been finalized.
+    Executed,
+    /// The op is held back from both approval and finalization.
+    Quarantined,
+}
+
+impl MultisigOpState {
+    /// Whether the op can still be approved or denied by its approvers.
+    pub fn is_pending(&self) -> bool {
+        matches!(
+            self,
+            MultisigOpState::PendingInstructions | MultisigOpState::Open
+        )
+    }
+
+    pub fn can_transition_to(&self, to: MultisigOpState) -> bool {
+        use MultisigOpState::*;
+        match (self, to) {
+            (Uninitialized, PendingInstructions | Open | Approved) => true,
+            (PendingInstructions, Open | Approved | Denied | Expired) => true,
+            (Open, Approved | Denied | Expired | Quarantined) => true,
+            // an approved op is denied when cancelled during its finalize delay, and expires
+            // when it isn't finalized in time
+            (Approved, Denied | Expired | Executed | Quarantined) => true,
+            (Denied | Expired, Executed) => true,
+            (Quarantined, Open | Denied | Expired) => true,
+            _ => false,
+        }
+    }
+
+    /// The disposition reported for the op, NONE for any state that isn't a decision.
+    pub fn operation_disposition(&self) -> OperationDisposition {
+        match self {
+            MultisigOpState::Approved => OperationDisposition::APPROVED,
+            MultisigOpState::Denied => OperationDisposition::DENIED,
+            MultisigOpState::Expired => OperationDisposition::EXPIRED,
+            _ => OperationDisposition::NONE,
+        }
+    }
+
+    /// The packed state shares its byte with the operation disposition it replaces, so that
+    /// ops packed before it was introduced unpack to the equivalent state.
+    fn to_u8(self) -> u8 {
+        match self {
+            MultisigOpState::Uninitialized | MultisigOpState::Open => 0,
+            MultisigOpState::Approved => 1,
+            MultisigOpState::Denied => 2,
+            MultisigOpState::Expired => 3,
+            MultisigOpState::PendingInstructions => 4,
+            MultisigOpState::Executed => 5,
+            MultisigOpState::Quarantined => 6,
+        }
+    }
+
+    fn from_u8(
+        value: u8,
+        is_initialized: bool,
+        params_hash: Option<Hash>,
+    ) -> Result<MultisigOpState, ProgramError> {
+        Ok(match value {
+            _ if !is_initialized => MultisigOpState::Uninitialized,
+            0 if params_hash.is_none() => MultisigOpState::PendingInstructions,
+            0 => MultisigOpState::Open,
+            1 => MultisigOpState::Approved,
+            2 => MultisigOpState::Denied,
+            3 => MultisigOpState::Expired,
+            4 => MultisigOpState::PendingInstructions,
+            5 => MultisigOpState::Executed,
+            6 => MultisigOpState::Quarantined,
+            _ => return Err(ProgramError::InvalidAccountData),
+        })
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct ApprovalDispositionRecord {
     pub approver: Pubkey,
     pub disposition: ApprovalDisposition,
+    /// Why the approver registered the disposition, if they gave a reason.
+    pub reason: Option<DispositionReason>,
+    /// The governance tokens the approver held as the op was initiated, for ops of stake weight
+    /// governed wallets, and 0 for others.
+    pub stake_weight: u64,
+}
+
+/// The reason an approver gives along with a disposition, kept on the op so that it can be
+/// audited before the op is closed. The meaning of the code is up to the wallet's services,
+/// and the hash commits to a longer explanation kept off chain.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct DispositionReason {
+    pub code: u8,
+    pub hash: Option<Hash>,
+}
+
+impl DispositionReason {
+    pub const LEN: usize = 1 + // code
+        HASH_LEN; // hash
+
+    fn pack_into_slice(reason: &Option<DispositionReason>, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 1 + DispositionReason::LEN];
+        let (has_reason_dst, code_dst, hash_dst) = mut_array_refs![dst, 1, 1, HASH_LEN];
+        has_reason_dst[0] = reason.is_some() as u8;
+        code_dst[0] = reason.map_or(0, |reason| reason.code);
+        *hash_dst = reason
+            .and_then(|reason| reason.hash)
+            .map_or(EMPTY_HASH, |hash| hash.to_bytes());
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Option<DispositionReason>, ProgramError> {
+        let src = array_ref![src, 0, 1 + DispositionReason::LEN];
+        let (has_reason, code, hash) = array_refs![src, 1, 1, HASH_LEN];
+        Ok(match has_reason {
+            [0] => None,
+            [1] => Some(DispositionReason {
+                code: code[0],
+                hash: if *hash == EMPTY_HASH {
+                    None
+                } else {
+                    Some(Hash::new_from_array(*hash))
+                },
+            }),
+            _ => return Err(ProgramError::InvalidAccountData),
+        })
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[allow(non_camel_case_types)]
 pub enum WrapDirection {
     WRAP = 0,
     UNWRAP = 1,
+    /// Closes the wrapped SOL account, crediting its whole balance along with its rent to the
+    /// balance account. The op's amount has to be 0.
+    UNWRAP_ALL = 2,
 }
 
 impl WrapDirection {
     pub fn from_u8(value: u8) -> WrapDirection {
         match value {
             0 => WrapDirection::WRAP,
+            2 => WrapDirection::UNWRAP_ALL,
             _ => WrapDirection::UNWRAP,
         }
     }
@@ -143,6 +408,30 @@ impl WrapDirection {
         match self {
             WrapDirection::WRAP => 0,
             WrapDirection::UNWRAP => 1,
+            WrapDirection::UNWRAP_ALL => 2,
+        }
+    }
+}
+
+/// The authorities of an SPL mint a balance account can hold and hand off.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum MintAuthorityType {
+    MintTokens = 0,
+    FreezeAccount = 1,
+}
+
+impl MintAuthorityType {
+    pub fn from_u8(value: u8) -> MintAuthorityType {
+        match value {
+            0 => MintAuthorityType::MintTokens,
+            _ => MintAuthorityType::FreezeAccount,
+        }
+    }
+
+    pub fn to_u8(&self) -> u8 {
+        match self {
+            MintAuthorityType::MintTokens => 0,
+            MintAuthorityType::FreezeAccount => 1,
         }
     }
 }
@@ -151,12 +440,16 @@ impl WrapDirection {
 pub enum SlotUpdateType {
     SetIfEmpty = 0,
     Clear = 1,
+    /// Clears the slot and sets it to a new key in the same op, so that the new key takes over
+    /// the slot from the key it held.
+    Replace = 2,
 }
 
 impl SlotUpdateType {
     pub fn from_u8(value: u8) -> SlotUpdateType {
         match value {
             0 => SlotUpdateType::SetIfEmpty,
+            2 => SlotUpdateType::Replace,
             _ => SlotUpdateType::Clear,
         }
     }
@@ -165,6 +458,7 @@ impl SlotUpdateType {
         match self {
             SlotUpdateType::SetIfEmpty => 0,
             SlotUpdateType::Clear => 1,
+            SlotUpdateType::Replace => 2,
         }
     }
 }
@@ -223,23 +517,29 @@ impl Pack for BooleanSetting {
 }
 
 impl ApprovalDispositionRecord {
-    pub(crate) const LEN: usize = 1 + PUBKEY_BYTES;
+    pub(crate) const LEN: usize = PUBKEY_BYTES + 1 + 1 + DispositionReason::LEN + 8;
 
     pub fn pack_into_slice(&self, dst: &mut [u8]) {
         let dst = array_mut_ref![dst, 0, ApprovalDispositionRecord::LEN];
-        let (approver_dst, disposition_dst) = mut_array_refs![dst, PUBKEY_BYTES, 1];
+        let (approver_dst, disposition_dst, reason_dst, stake_weight_dst) =
+            mut_array_refs![dst, PUBKEY_BYTES, 1, 1 + DispositionReason::LEN, 8];
 
         approver_dst.copy_from_slice(&self.approver.to_bytes());
         disposition_dst[0] = self.disposition.to_u8();
+        DispositionReason::pack_into_slice(&self.reason, reason_dst);
+        *stake_weight_dst = self.stake_weight.to_le_bytes();
     }
 
     pub fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
         let src = array_ref![src, 0, ApprovalDispositionRecord::LEN];
-        let (approver_bytes, disposition_bytes) = array_refs![src, PUBKEY_BYTES, 1];
+        let (approver_bytes, disposition_bytes, reason_bytes, stake_weight_bytes) =
+            array_refs![src, PUBKEY_BYTES, 1, 1 + DispositionReason::LEN, 8];
 
         Ok(ApprovalDispositionRecord {
             approver: Pubkey::new(approver_bytes),
             disposition: ApprovalDisposition::from_u8(disposition_bytes[0]),
+            reason: DispositionReason::unpack_from_slice(reason_bytes)?,
+            stake_weight: u64::from_le_bytes(*stake_weight_bytes),
         })
     }
 }
@@ -248,21 +548,170 @@ impl ApprovalDispositionRecord {
 pub struct MultisigOp {
     pub is_initialized: bool,
     pub version: u32,
+    /// One record per approver, ordered by approver pubkey so that records can be binary
+    /// searched and the packed op is the same whatever order the approvers were given in.
     pub disposition_records: Vec<ApprovalDispositionRecord>,
     pub dispositions_required: u8,
     pub params_hash: Option<Hash>,
     pub started_at: i64,
     pub expires_at: i64,
-    pub operation_disposition: OperationDisposition,
+    /// The slot height past which the op is expired whatever the clock says, or None for ops
+    /// started before they were bounded by slot height.
+    pub expires_at_slot: Option<u64>,
+    pub state: MultisigOpState,
     pub initiator: Pubkey,
     pub rent_return: Pubkey,
     pub fee_amount: u64,
     pub fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+    pub multisig_data_account: Option<Pubkey>,
+    /// How long after being approved the op can be finalized.
+    pub finalize_delay: Duration,
+    /// Unix timestamp at which the op was approved, or 0 if it hasn't been.
+    pub approved_at: i64,
+    /// The compliance service which has to approve the op before it can be finalized, if any.
+    pub compliance_service: Option<Pubkey>,
+    pub compliance_approved: bool,
+    /// The slot of the op's most recent approval activity, either its initiation or the
+    /// latest disposition recorded for it.
+    pub last_activity_slot: u64,
+    /// The compute units the client that planned the op expects its finalize to take, so that
+    /// whoever submits the finalize knows what compute budget to request for it.
+    pub finalize_compute_units: Option<u32>,
+    /// The governance of the op's wallet, if it is stake weight governed, in which case the op
+    /// is approved by stake weight rather than by a number of approvals.
+    pub stake_weight_governance: Option<StakeWeightGovernance>,
+    /// The stake weight that has to approve (or deny) the op, or 0 until its weights are
+    /// snapshotted.
+    pub required_stake_weight: u64,
+    /// The wallet the op was started for.
+    pub wallet: Pubkey,
+    /// The jurisdiction of the destination of a transfer to a high-risk address book entry,
+    /// which is recorded in the destination receipt when the transfer is finalized.
+    pub destination_jurisdiction: Option<JurisdictionCode>,
+    /// The category of the op's params, if they were known when it was started, which bounds
+    /// what session keys may register dispositions for it.
+    pub category: Option<FinalizeCategory>,
+    /// The amount moved by an op of the transfer category, in the units of what it moves, or 0
+    /// for others.
+    pub transfer_amount: u64,
+    /// The type of the op, if its params were known when it was started.
+    pub op_code: Option<MultisigOpCode>,
+    /// The rent the source balance account of a transfer paid to create the destination's
+    /// associated token account when the transfer was finalized, or 0 if it didn't.
+    pub destination_token_account_rent: u64,
+    /// Which of the balance accounts of an op creating SPL token accounts have their token
+    /// account, by their index in the op's params, so that an approved op can create them over
+    /// several transactions.
+    pub spl_token_accounts_created: u32,
+    /// The wallet's op nonce when the op was started, which is part of the op's params hash,
+    /// so that a config op started against an older wallet state can't be finalized.
+    pub op_nonce: u64,
 }
 
 const EMPTY_HASH: [u8; HASH_BYTES] = [0; HASH_BYTES];
 
 impl MultisigOp {
+    /// The most balance accounts an op can create SPL token accounts for, one per bit of
+    /// `spl_token_accounts_created`.
+    pub const MAX_SPL_TOKEN_ACCOUNTS: usize = u32::BITS as usize;
+
+    // the offset of the version, which is read without unpacking the op
+    const VERSION_OFFSET: usize = 1;
+    // the offset of the finalize compute units, which sit in the header so that submitters can
+    // read them without unpacking the op
+    const FINALIZE_COMPUTE_UNITS_OFFSET: usize = MultisigOp::VERSION_OFFSET + VERSION_LEN + 1;
+
+    const HEADER_LEN: usize = 1 // initialized
+        + VERSION_LEN // version
+        + 1 // disposition count
+        + 4; // finalize compute units
+
+    const TRAILER_LEN: usize = 1 // dispositions required
+        + HASH_LEN // hash
+        + 8 // started at
+        + 8 // expires at
+        + 1 // operation disposition
+        + PUBKEY_BYTES // initiator
+        + PUBKEY_BYTES // rent return
+        + 8 // fee amount
+        + HASH_LEN // fee account
+        + PUBKEY_BYTES // multisig data account
+        + 8 // finalize delay
+        + 8 // approved at
+        + PUBKEY_BYTES // compliance service
+        + 1 // compliance approved
+        + 8 // last activity slot
+        + StakeWeightGovernance::LEN // stake weight governance
+        + 8 // required stake weight
+        + PUBKEY_BYTES // wallet
+        + JurisdictionCode::LEN // destination jurisdiction
+        + 1 // category
+        + 8 // transfer amount
+        + 1 // op code
+        + 8 // destination token account rent
+        + 4 // spl token accounts created
+        + 8 // expires at slot
+        + 8; // op nonce
+
+    /// The size of an op without room for any disposition records.
+    pub const FIXED_LEN: usize = MultisigOp::HEADER_LEN + MultisigOp::TRAILER_LEN;
+
+    /// The size of an op account with room for the disposition records of `approvers_count`
+    /// approvers. The disposition records sit between the header and the trailer, so an
+    /// account of `MultisigOp::LEN` has the layout ops had before they could be sized down.
+    pub const fn len_for(approvers_count: usize) -> usize {
+        MultisigOp::FIXED_LEN + ApprovalDispositionRecord::LEN * approvers_count
+    }
+
+    /// The number of disposition records an op account of the given size has room for, if
+    /// it is a valid op account size.
+    pub fn records_capacity(len: usize) -> Option<usize> {
+        let records_len = len.checked_sub(MultisigOp::FIXED_LEN)?;
+        let capacity = records_len / ApprovalDispositionRecord::LEN;
+        if records_len % ApprovalDispositionRecord::LEN != 0 || capacity > Wallet::MAX_SIGNERS {
+            None
+        } else {
+            Some(capacity)
+        }
+    }
+
+    pub fn finalize_compute_units_from_slice(src: &[u8]) -> Result<Option<u32>, ProgramError> {
+        if src.len() < MultisigOp::FINALIZE_COMPUTE_UNITS_OFFSET + 4 {
+            Err(ProgramError::InvalidAccountData)
+        } else {
+            let buf = array_ref!(src, MultisigOp::FINALIZE_COMPUTE_UNITS_OFFSET, 4);
+            Ok(Some(u32::from_le_bytes(*buf)).filter(|units| *units > 0))
+        }
+    }
+
+    /// Unpacks an initialized op from an account of any valid op size, unlike `Pack::unpack`
+    /// which only takes accounts of `MultisigOp::LEN`.
+    pub fn unpack_sized(src: &[u8]) -> Result<Self, ProgramError> {
+        let multisig_op = MultisigOp::unpack_from_slice(src)?;
+        if !multisig_op.is_initialized {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        Ok(multisig_op)
+    }
+
+    /// Packs an op into an account of any valid op size with room for its disposition records.
+    pub fn pack_sized(multisig_op: MultisigOp, dst: &mut [u8]) -> ProgramResult {
+        match MultisigOp::records_capacity(dst.len()) {
+            Some(capacity) if capacity >= multisig_op.disposition_records.len() => {
+                multisig_op.pack_into_slice(dst);
+                Ok(())
+            }
+            _ => {
+                msg!(
+                    "Op account of {} bytes can't hold {} disposition records",
+                    dst.len(),
+                    multisig_op.disposition_records.len()
+                );
+                Err(WalletError::MultisigOpAccountTooSmall.into())
+            }
+        }
+    }
+
     pub fn get_disposition_count(&self, disposition: ApprovalDisposition) -> u8 {
         self.disposition_records
             .iter()
@@ -270,9 +719,48 @@ impl MultisigOp {
             .count() as u8
     }
 
+    /// The stake weight of the approvers who registered the disposition.
+    pub fn get_disposition_stake_weight(&self, disposition: ApprovalDisposition) -> u64 {
+        self.disposition_records
+            .iter()
+            .filter(|&n| n.disposition == disposition)
+            .fold(0, |weight, record| {
+                weight.saturating_add(record.stake_weight)
+            })
+    }
+
+    /// Whether enough approvers registered the disposition to decide the op: by their stake
+    /// weight for an op of a stake weight governed wallet, and by their number for others.
+    fn quorum_reached(&self, disposition: ApprovalDisposition) -> bool {
+        if self.stake_weight_governance.is_some() {
+            self.required_stake_weight > 0
+                && self.get_disposition_stake_weight(disposition) >= self.required_stake_weight
+        } else {
+            self.get_disposition_count(disposition) == self.dispositions_required
+        }
+    }
+
+    pub fn is_approver(&self, approver: &Pubkey) -> bool {
+        self.disposition_record_index(approver).is_ok()
+    }
+
+    fn disposition_record_index(&self, approver: &Pubkey) -> Result<usize, usize> {
+        self.disposition_records
+            .binary_search_by_key(approver, |record| record.approver)
+    }
+
+    fn disposition_record_mut(
+        &mut self,
+        approver: &Pubkey,
+    ) -> Option<&mut ApprovalDispositionRecord> {
+        self.disposition_record_index(approver)
+            .ok()
+            .map(|index| &mut self.disposition_records[index])
+    }
+
     pub fn init(
         &mut self,
-        approvers: Vec<Pubkey>,
+        mut approvers: Vec<Pubkey>,
         initiator_disposition: (Pubkey, ApprovalDisposition),
         approvals_required: u8,
         started_at: i64,
@@ -281,7 +769,10 @@ impl MultisigOp {
         rent_return: Pubkey,
         fee_amount: u64,
         fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+        op_nonce: u64,
     ) -> ProgramResult {
+        approvers.sort_unstable();
+        approvers.dedup();
         self.disposition_records = approvers
             .iter()
             .map(|approver| ApprovalDispositionRecord {
@@ -291,6 +782,8 @@ impl MultisigOp {
                 } else {
                     ApprovalDisposition::NONE
                 },
+                reason: None,
+                stake_weight: 0,
             })
             .collect::<Vec<_>>();
         self.dispositions_required = approvals_required;
@@ -301,12 +794,35 @@ impl MultisigOp {
         self.rent_return = rent_return;
         self.fee_amount = fee_amount;
         self.fee_account_guid_hash = fee_account_guid_hash;
+        self.multisig_data_account = None;
+        self.finalize_delay = Duration::ZERO;
+        self.approved_at = 0;
+        self.compliance_service = None;
+        self.compliance_approved = false;
+        self.last_activity_slot = 0;
+        self.finalize_compute_units = None;
+        self.stake_weight_governance = None;
+        self.required_stake_weight = 0;
+        self.destination_jurisdiction = None;
+        self.category = params.as_ref().map(|p| p.finalize_category());
+        self.transfer_amount = params.as_ref().map_or(0, |p| p.transfer_amount());
+        self.op_code = params.as_ref().map(|p| p.op_code());
+        self.destination_token_account_rent = 0;
+        self.spl_token_accounts_created = 0;
+        self.expires_at_slot = None;
+        self.op_nonce = op_nonce;
         self.params_hash = params.map_or(None, |p| Some(p.hash(&self)));
 
-        if self.get_disposition_count(ApprovalDisposition::APPROVE) == self.dispositions_required {
-            self.operation_disposition = OperationDisposition::APPROVED
+        self.state = MultisigOpState::Uninitialized;
+
+        // an op requiring no approvals is approved as it's initiated, whatever the initiator's
+        // disposition, and can be finalized right away, finalize delay permitting
+        if self.dispositions_required == 0 || self.quorum_reached(ApprovalDisposition::APPROVE) {
+            self.approve(started_at)?
+        } else if self.params_hash.is_none() {
+            self.transition(MultisigOpState::PendingInstructions)?
         } else {
-            self.operation_disposition = OperationDisposition::NONE
+            self.transition(MultisigOpState::Open)?
         }
 
         self.version = VERSION;
@@ -314,10 +830,306 @@ impl MultisigOp {
         Ok(())
     }
 
+    /// Holds back finalization of the op until `finalize_delay` has passed since it was
+    /// approved. Its expiry is pushed back by as much, so that the delay doesn't eat into the
+    /// time left to finalize it.
+    pub fn set_finalize_delay(&mut self, finalize_delay: Duration) {
+        self.finalize_delay = finalize_delay;
+        if self.state == MultisigOpState::Approved {
+            self.extend_expiry(finalize_delay);
+        }
+    }
+
+    /// Bounds the op's expiry by slot height as well, `timeout` after the given slot at the
+    /// nominal slot duration, so that a clock drifting behind the slot height can't keep the
+    /// op open past its approval timeout.
+    pub fn set_expiry_slot(&mut self, slot: u64, timeout: Duration) {
+        self.expires_at_slot = Some(slot.saturating_add(duration_slots(timeout)));
+    }
+
+    fn extend_expiry(&mut self, by: Duration) {
+        self.expires_at = self.expires_at.saturating_add(duration_secs(by));
+        self.expires_at_slot = self
+            .expires_at_slot
+            .map(|slot| slot.saturating_add(duration_slots(by)));
+    }
+
+    /// Whether the op is past its expiry, by either the clock or the slot height.
+    pub fn is_expired(&self, clock: &Clock) -> bool {
+        clock.unix_timestamp > self.expires_at || self.past_expiry_slot(clock)
+    }
+
+    fn past_expiry_slot(&self, clock: &Clock) -> bool {
+        self.expires_at_slot.map_or(false, |slot| clock.slot > slot)
+    }
+
+    /// The op's expiry as a timestamp, taking its slot height bound as the time it is
+    /// expected to be reached at the nominal slot duration when that comes first, so that
+    /// clients count down to whichever bound expires the op.
+    pub fn effective_expires_at(&self, clock: &Clock) -> i64 {
+        match self.expires_at_slot {
+            Some(slot) => {
+                let millis_left = slot
+                    .saturating_sub(clock.slot)
+                    .saturating_mul(DEFAULT_MS_PER_SLOT);
+                let expires_at_slot_time = clock
+                    .unix_timestamp
+                    .saturating_add(i64::try_from(millis_left / 1000).unwrap_or(i64::MAX));
+                self.expires_at.min(expires_at_slot_time)
+            }
+            None => self.expires_at,
+        }
+    }
+
+    pub fn record_activity(&mut self, slot: u64) {
+        self.last_activity_slot = slot;
+    }
+
+    /// Whether the op is still waiting on approvers, none of whom has registered a disposition
+    /// for at least `idle_slots` slots.
+    pub fn is_idle(&self, idle_slots: u64, clock: &Clock) -> bool {
+        self.state.is_pending()
+            && clock.unix_timestamp < self.expires_at
+            && !self.past_expiry_slot(clock)
+            && clock.slot.saturating_sub(self.last_activity_slot) >= idle_slots
+    }
+
+    /// The approvers yet to register a disposition for the op.
+    pub fn pending_approvers(&self) -> Vec<Pubkey> {
+        self.disposition_records
+            .iter()
+            .filter(|record| record.disposition == ApprovalDisposition::NONE)
+            .map(|record| record.approver)
+            .collect()
+    }
+
+    /// Holds back an approved op whose finalize can't go through for the time being, keeping
+    /// its dispositions so that it doesn't have to be approved again once it is released.
+    pub fn quarantine(&mut self) -> ProgramResult {
+        self.transition(MultisigOpState::Quarantined)
+    }
+
+    /// Releases a quarantined op, which is approved again at once on the dispositions it kept.
+    /// It stays approved as of when it first was, so its finalize delay isn't started over.
+    pub fn release(&mut self, clock: &Clock) -> ProgramResult {
+        self.transition(MultisigOpState::Open)?;
+        if !self.is_expired(clock) && self.quorum_reached(ApprovalDisposition::APPROVE) {
+            return self.transition(MultisigOpState::Approved);
+        }
+        self.update_operation_disposition(clock)?;
+        Ok(())
+    }
+
+    /// Hands the disposition record of an approver key that was rotated out of the wallet on
+    /// to the key that replaced it, returning whether it did. A successor that already has a
+    /// record of its own keeps it, and the retired key's record is left as it is.
+    pub fn migrate_approver(&mut self, retired: &Pubkey, successor: &Pubkey) -> bool {
+        if !self.state.is_pending() || self.is_approver(successor) {
+            return false;
+        }
+        match self.disposition_record_index(retired) {
+            Ok(index) => {
+                self.disposition_records[index].approver = *successor;
+                self.disposition_records
+                    .sort_unstable_by_key(|record| record.approver);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Holds back finalization of the op, should it be approved, until the compliance service
+    /// has approved it as well.
+    pub fn require_compliance_approval(&mut self, compliance_service: Pubkey) {
+        self.compliance_service = Some(compliance_service);
+        self.compliance_approved = false;
+    }
+
+    pub fn record_compliance_approval(&mut self, service: &AccountInfo) -> ProgramResult {
+        if !service.is_signer {
+            return Err(WalletError::InvalidSignature.into());
+        }
+        if self.compliance_service != Some(*service.key) {
+            msg!("Account is not the op's compliance service");
+            return Err(WalletError::InvalidApprover.into());
+        }
+        self.compliance_approved = true;
+        Ok(())
+    }
+
+    /// Approves a pending op on the approval of its initiator alone, as for a transfer within
+    /// the spending limit of its balance account. Ops with a finalize delay are left pending,
+    /// since they couldn't be finalized as they are approved.
+    pub fn approve_by_initiator(&mut self, clock: &Clock) -> Result<bool, ProgramError> {
+        let initiator_approved = self
+            .disposition_record_index(&self.initiator)
+            .map(|index| self.disposition_records[index].disposition)
+            == Ok(ApprovalDisposition::APPROVE);
+        if !self.state.is_pending()
+            || self.is_expired(clock)
+            || self.finalize_delay > Duration::ZERO
+            || self.stake_weight_governance.is_some()
+            || !initiator_approved
+        {
+            return Ok(false);
+        }
+        self.approve(clock.unix_timestamp)?;
+        Ok(true)
+    }
+
+    /// Moves the op to the given state, failing if its lifecycle doesn't allow it.
+    pub fn transition(&mut self, to: MultisigOpState) -> ProgramResult {
+        if !self.state.can_transition_to(to) {
+            msg!(
+                "Invalid multisig op state transition {:?} -> {:?}",
+                self.state,
+                to
+            );
+            return Err(WalletError::InvalidStateTransition.into());
+        }
+        self.state = to;
+        Ok(())
+    }
+
+    pub fn operation_disposition(&self) -> OperationDisposition {
+        self.state.operation_disposition()
+    }
+
+    fn approve(&mut self, now: i64) -> ProgramResult {
+        self.transition(MultisigOpState::Approved)?;
+        self.approved_at = now;
+        self.extend_expiry(self.finalize_delay);
+        Ok(())
+    }
+
+    fn finalizable_at(&self) -> i64 {
+        self.approved_at
+            .saturating_add(duration_secs(self.finalize_delay))
+    }
+
+    /// Denies an approved op that is still waiting out its finalize delay, so that finalizing
+    /// it only returns its rent.
+    pub fn cancel(&mut self, clock: &Clock) -> ProgramResult {
+        if self.state != MultisigOpState::Approved || clock.unix_timestamp >= self.finalizable_at()
+        {
+            return Err(WalletError::OperationNotCancellable.into());
+        }
+        self.transition(MultisigOpState::Denied)
+    }
+
+    /// Expires an op that wasn't finalized before its expiry, so that its rent can be returned
+    /// without finalizing it.
+    pub fn expire(&mut self, clock: &Clock) -> ProgramResult {
+        if !self.is_expired(clock) {
+            msg!(
+                "Operation doesn't expire before {}",
+                self.effective_expires_at(clock)
+            );
+            return Err(WalletError::OperationNotExpired.into());
+        }
+        if self.state == MultisigOpState::Expired {
+            return Ok(());
+        }
+        self.transition(MultisigOpState::Expired)
+    }
+
+    /// Binds the op to the account its params are supplied into, so that account can't be
+    /// swapped for another one once the op has been started.
+    pub fn bind_multisig_data_account(&mut self, multisig_data_account: Pubkey) {
+        self.multisig_data_account = Some(multisig_data_account);
+    }
+
+    pub fn validate_multisig_data_account(&self, multisig_data_account: &Pubkey) -> ProgramResult {
+        if self.multisig_data_account != Some(*multisig_data_account) {
+            return Err(WalletError::IncorrectMultisigDataAccount.into());
+        }
+        Ok(())
+    }
+
+    /// Sets the params hash of an op whose params only became known after init, counting
+    /// it as approved by the initiator where it is one of the approvers.
+    pub fn set_params_hash(
+        &mut self,
+        params_hash: Hash,
+        initiator: &Pubkey,
+        now: i64,
+    ) -> ProgramResult {
+        self.params_hash = Some(params_hash);
+        if self.state == MultisigOpState::PendingInstructions {
+            self.transition(MultisigOpState::Open)?;
+        }
+
+        if let Some(record) = self.disposition_record_mut(initiator) {
+            if record.disposition == ApprovalDisposition::NONE {
+                record.disposition = ApprovalDisposition::APPROVE
+            }
+        }
+        if self.state == MultisigOpState::Open && self.quorum_reached(ApprovalDisposition::APPROVE)
+        {
+            self.approve(now)?;
+        }
+        Ok(())
+    }
+
+    /// Makes the given holders of the governance token the op's approvers, weighted by the
+    /// tokens they hold, and sets the weight that has to approve it from the mint's supply. Only
+    /// an op of a stake weight governed wallet takes weights, and only once, as it is initiated.
+    pub fn record_stake_weights(
+        &mut self,
+        mut weights: Vec<(Pubkey, u64)>,
+        supply: u64,
+        now: i64,
+    ) -> ProgramResult {
+        let governance = match self.stake_weight_governance {
+            Some(governance)
+                if self.required_stake_weight == 0 && self.disposition_records.is_empty() =>
+            {
+                governance
+            }
+            _ => return Err(WalletError::StakeWeightsNotExpected.into()),
+        };
+        weights.sort_unstable_by_key(|(holder, _)| *holder);
+        if weights.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+            msg!("A holder's stake can only be counted once");
+            return Err(WalletError::InvalidStakeAccount.into());
+        }
+        // the initiator approves the op as it does with signers, once its params are known
+        self.disposition_records = weights
+            .into_iter()
+            .filter(|(_, weight)| *weight > 0)
+            .map(|(holder, weight)| ApprovalDispositionRecord {
+                approver: holder,
+                disposition: if holder == self.initiator && self.params_hash.is_some() {
+                    ApprovalDisposition::APPROVE
+                } else {
+                    ApprovalDisposition::NONE
+                },
+                reason: None,
+                stake_weight: weight,
+            })
+            .collect();
+        self.required_stake_weight = governance.required_weight(supply);
+        if self.state == MultisigOpState::Open && self.quorum_reached(ApprovalDisposition::APPROVE)
+        {
+            self.approve(now)?;
+        }
+        Ok(())
+    }
+
+    pub fn receipt(&self, clock: &Clock) -> MultisigOpReceipt {
+        MultisigOpReceipt {
+            params_hash: self.params_hash,
+            expires_at: self.effective_expires_at(clock),
+        }
+    }
+
+    /// Records the approver's disposition, along with the reason they gave for it, if any. An
+    /// approver resubmitting the disposition they registered leaves its reason as it was.
     pub fn validate_and_record_approval_disposition(
         &mut self,
         approver: &AccountInfo,
         disposition: ApprovalDisposition,
+        reason: Option<DispositionReason>,
         clock: &Clock,
     ) -> ProgramResult {
         if disposition != ApprovalDisposition::APPROVE && disposition != ApprovalDisposition::DENY {
@@ -328,14 +1140,60 @@ impl MultisigOp {
         if !approver.is_signer {
             return Err(WalletError::InvalidSignature.into());
         }
+        self.record_approval_disposition(approver.key, disposition, reason, clock)
+    }
 
-        if let Some(record) = self
-            .disposition_records
-            .iter_mut()
-            .find(|r| r.approver == *approver.key)
+    /// Records the disposition of the approver a session key was registered by, which signed
+    /// for them, as long as the session is live and its scope covers the op.
+    pub fn validate_and_record_session_approval_disposition(
+        &mut self,
+        session_key_account: &AccountInfo,
+        session_key: &SessionKey,
+        disposition: ApprovalDisposition,
+        reason: Option<DispositionReason>,
+        clock: &Clock,
+    ) -> ProgramResult {
+        if disposition != ApprovalDisposition::APPROVE && disposition != ApprovalDisposition::DENY {
+            msg!("Invalid Disposition provided");
+            return Err(WalletError::InvalidDisposition.into());
+        }
+
+        if !session_key_account.is_signer || session_key.session_key != *session_key_account.key {
+            return Err(WalletError::InvalidSignature.into());
+        }
+        session_key.validate_covers(self, clock.unix_timestamp)?;
+        self.record_approval_disposition(&session_key.approver, disposition, reason, clock)
+    }
+
+    /// Approves the op on the strength of an aggregated signature of its params by the
+    /// wallet's approval group, which stands for as many approvals as the group's threshold.
+    pub fn record_aggregated_approval(
+        &mut self,
+        approval_group: &ApprovalGroup,
+        clock: &Clock,
+    ) -> ProgramResult {
+        approval_group.validate_covers(self)?;
+        if self.update_operation_disposition(clock)? != OperationDisposition::NONE
+            || self.state != MultisigOpState::Open
         {
+            msg!("Operation is {:?}", self.state);
+            return Err(WalletError::InvalidStateTransition.into());
+        }
+        self.record_activity(clock.slot);
+        self.approve(clock.unix_timestamp)
+    }
+
+    fn record_approval_disposition(
+        &mut self,
+        approver: &Pubkey,
+        disposition: ApprovalDisposition,
+        reason: Option<DispositionReason>,
+        clock: &Clock,
+    ) -> ProgramResult {
+        if let Some(record) = self.disposition_record_mut(approver) {
             if record.disposition == ApprovalDisposition::NONE {
-                record.disposition = disposition
+                record.disposition = disposition;
+                record.reason = reason;
             } else if record.disposition != disposition {
                 msg!("Approver already registered a different disposition");
                 return Err(WalletError::InvalidDisposition.into());
@@ -344,27 +1202,49 @@ impl MultisigOp {
             msg!("Approver is not a configured approver");
             return Err(WalletError::InvalidApprover.into());
         }
-        self.update_operation_disposition(clock);
+        self.record_activity(clock.slot);
+        self.update_operation_disposition(clock)?;
 
         Ok(())
     }
 
-    pub fn update_operation_disposition(&mut self, clock: &Clock) -> OperationDisposition {
-        if self.operation_disposition != OperationDisposition::NONE {
-            return self.operation_disposition;
+    /// Records the approvals of an op bundle the op is a member of, for those of the bundle's
+    /// approvers that are approvers of the op and haven't registered a disposition of their own.
+    pub fn record_bundle_approvals(
+        &mut self,
+        bundle_approvers: &[Pubkey],
+        clock: &Clock,
+    ) -> Result<OperationDisposition, ProgramError> {
+        for approver in bundle_approvers {
+            // once decided, the op takes no more dispositions
+            if self.update_operation_disposition(clock)? != OperationDisposition::NONE {
+                break;
+            }
+            if let Some(record) = self.disposition_record_mut(approver) {
+                if record.disposition == ApprovalDisposition::NONE {
+                    record.disposition = ApprovalDisposition::APPROVE;
+                    self.last_activity_slot = clock.slot;
+                }
+            }
+        }
+        self.update_operation_disposition(clock)
+    }
+
+    pub fn update_operation_disposition(
+        &mut self,
+        clock: &Clock,
+    ) -> Result<OperationDisposition, ProgramError> {
+        if !self.state.is_pending() {
+            return Ok(self.operation_disposition());
         }
-        if clock.unix_timestamp > self.expires_at {
-            self.operation_disposition = OperationDisposition::EXPIRED
-        } else if self.get_disposition_count(ApprovalDisposition::APPROVE)
-            == self.dispositions_required
-        {
-            self.operation_disposition = OperationDisposition::APPROVED
-        } else if self.get_disposition_count(ApprovalDisposition::DENY)
-            == self.dispositions_required
-        {
-            self.operation_disposition = OperationDisposition::DENIED
+        if self.is_expired(clock) {
+            self.transition(MultisigOpState::Expired)?
+        } else if self.quorum_reached(ApprovalDisposition::APPROVE) {
+            self.approve(clock.unix_timestamp)?
+        } else if self.quorum_reached(ApprovalDisposition::DENY) {
+            self.transition(MultisigOpState::Denied)?
         }
-        return self.operation_disposition;
+        Ok(self.operation_disposition())
     }
 
     pub fn approved(
@@ -394,16 +1274,33 @@ impl MultisigOp {
             }
         }
 
-        if self.operation_disposition == OperationDisposition::NONE
+        if self.operation_disposition() == OperationDisposition::NONE
             && clock.unix_timestamp < self.expires_at
+            && !self.past_expiry_slot(clock)
         {
             return Err(WalletError::TransferDispositionNotFinal.into());
         }
 
-        let mut operation_disposition = self.operation_disposition;
-        if clock.unix_timestamp > self.expires_at {
+        let mut operation_disposition = self.operation_disposition();
+        if self.is_expired(clock) {
             operation_disposition = OperationDisposition::EXPIRED
         }
+        if operation_disposition == OperationDisposition::APPROVED
+            && clock.unix_timestamp < self.finalizable_at()
+        {
+            msg!(
+                "Operation can't be finalized before {}",
+                self.finalizable_at()
+            );
+            return Err(WalletError::FinalizeDelayNotElapsed.into());
+        }
+        if operation_disposition == OperationDisposition::APPROVED
+            && self.compliance_service.is_some()
+            && !self.compliance_approved
+        {
+            msg!("Operation has to be approved by its compliance service");
+            return Err(WalletError::ComplianceApprovalRequired.into());
+        }
         log_op_disposition(operation_disposition);
 
         if operation_disposition == OperationDisposition::APPROVED {
@@ -416,12 +1313,11 @@ impl MultisigOp {
 
 impl Versioned for MultisigOp {
     fn version_from_slice(src: &[u8]) -> Result<u32, ProgramError> {
-        if src.len() < 5 {
+        if src.len() < MultisigOp::VERSION_OFFSET + VERSION_LEN {
             Err(ProgramError::InvalidAccountData)
         } else {
-            let mut buf: [u8; 4] = [0; 4];
-            buf.copy_from_slice(&src[1..=4]);
-            Ok(u32::from_le_bytes(buf))
+            let buf = array_ref!(src, MultisigOp::VERSION_OFFSET, VERSION_LEN);
+            Ok(u32::from_le_bytes(*buf))
         }
     }
 }
@@ -435,42 +1331,49 @@ impl IsInitialized for MultisigOp {
 }
 
 impl Pack for MultisigOp {
-    const LEN: usize = 1 // initialized
-        + 4 // version
-        + 1 // disposition count
-        + ApprovalDispositionRecord::LEN * Wallet::MAX_SIGNERS // dispositions
-        + 1 // dispositions required
-        + HASH_LEN // hash
-        + 8 // started at
-        + 8 // expires at
-        + 1 // operation disposition
-        + PUBKEY_BYTES // initiator
-        + PUBKEY_BYTES // rent return
-        + 8 // fee amount
-        + HASH_LEN; // fee account
+    const LEN: usize = MultisigOp::len_for(Wallet::MAX_SIGNERS);
 
     fn pack_into_slice(&self, dst: &mut [u8]) {
-        let dst = array_mut_ref![dst, 0, MultisigOp::LEN];
+        let records_len = dst.len() - MultisigOp::FIXED_LEN;
+        let (header_dst, dst) = dst.split_at_mut(MultisigOp::HEADER_LEN);
+        let (disposition_records_dst, trailer_dst) = dst.split_at_mut(records_len);
+        let header_dst = array_mut_ref![header_dst, 0, MultisigOp::HEADER_LEN];
         let (
             is_initialized_dst,
             version_dst,
             disposition_records_count_dst,
-            disposition_records_dst,
+            finalize_compute_units_dst,
+        ) = mut_array_refs![header_dst, 1, VERSION_LEN, 1, 4];
+        let trailer_dst = array_mut_ref![trailer_dst, 0, MultisigOp::TRAILER_LEN];
+        let (
             dispositions_required_dst,
             hash_dst,
             started_at_dst,
             expires_at_dst,
-            operation_disposition_dst,
+            state_dst,
             initiator_dst,
             rent_return_dst,
             fee_amount_dst,
             fee_account_guid_hash_dst,
+            multisig_data_account_dst,
+            finalize_delay_dst,
+            approved_at_dst,
+            compliance_service_dst,
+            compliance_approved_dst,
+            last_activity_slot_dst,
+            stake_weight_governance_dst,
+            required_stake_weight_dst,
+            wallet_dst,
+            destination_jurisdiction_dst,
+            category_dst,
+            transfer_amount_dst,
+            op_code_dst,
+            destination_token_account_rent_dst,
+            spl_token_accounts_created_dst,
+            expires_at_slot_dst,
+            op_nonce_dst,
         ) = mut_array_refs![
-            dst,
-            1,
-            4,
-            1,
-            ApprovalDispositionRecord::LEN * Wallet::MAX_SIGNERS,
+            trailer_dst,
             1,
             HASH_LEN,
             8,
@@ -479,8 +1382,25 @@ impl Pack for MultisigOp {
             PUBKEY_BYTES,
             PUBKEY_BYTES,
             8,
-            HASH_LEN
-        ];
+            HASH_LEN,
+            PUBKEY_BYTES,
+            8,
+            8,
+            PUBKEY_BYTES,
+            1,
+            8,
+            StakeWeightGovernance::LEN,
+            8,
+            PUBKEY_BYTES,
+            JurisdictionCode::LEN,
+            1,
+            8,
+            1,
+            8,
+            4,
+            8,
+            8
+        ];
 
         let MultisigOp {
             is_initialized,
@@ -490,11 +1410,29 @@ impl Pack for MultisigOp {
             params_hash,
             started_at,
             expires_at,
-            operation_disposition,
+            state,
             initiator,
             rent_return,
             fee_amount,
             fee_account_guid_hash,
+            multisig_data_account,
+            finalize_delay,
+            approved_at,
+            compliance_service,
+            compliance_approved,
+            last_activity_slot,
+            finalize_compute_units,
+            stake_weight_governance,
+            required_stake_weight,
+            wallet,
+            destination_jurisdiction,
+            category,
+            transfer_amount,
+            op_code,
+            destination_token_account_rent,
+            spl_token_accounts_created,
+            expires_at_slot,
+            op_nonce,
         } = self;
 
         is_initialized_dst[0] = *is_initialized as u8;
@@ -502,6 +1440,7 @@ impl Pack for MultisigOp {
         *version_dst = version.to_le_bytes();
 
         disposition_records_count_dst[0] = disposition_records.len() as u8;
+        *finalize_compute_units_dst = finalize_compute_units.unwrap_or(0).to_le_bytes();
         disposition_records_dst.fill(0);
         disposition_records_dst
             .chunks_exact_mut(ApprovalDispositionRecord::LEN)
@@ -520,7 +1459,7 @@ impl Pack for MultisigOp {
         *started_at_dst = started_at.to_le_bytes();
         *expires_at_dst = expires_at.to_le_bytes();
 
-        operation_disposition_dst[0] = operation_disposition.to_u8();
+        state_dst[0] = state.to_u8();
 
         initiator_dst.copy_from_slice(&initiator.to_bytes());
         rent_return_dst.copy_from_slice(&rent_return.to_bytes());
@@ -530,30 +1469,68 @@ impl Pack for MultisigOp {
         } else {
             fee_account_guid_hash_dst.copy_from_slice(&EMPTY_HASH)
         }
+        multisig_data_account_dst
+            .copy_from_slice(&multisig_data_account.unwrap_or_default().to_bytes());
+        *finalize_delay_dst = finalize_delay.as_secs().to_le_bytes();
+        *approved_at_dst = approved_at.to_le_bytes();
+        compliance_service_dst.copy_from_slice(&compliance_service.unwrap_or_default().to_bytes());
+        compliance_approved_dst[0] = *compliance_approved as u8;
+        *last_activity_slot_dst = last_activity_slot.to_le_bytes();
+        StakeWeightGovernance::pack_into_slice(
+            stake_weight_governance,
+            stake_weight_governance_dst,
+        );
+        *required_stake_weight_dst = required_stake_weight.to_le_bytes();
+        wallet_dst.copy_from_slice(wallet.as_ref());
+        JurisdictionCode::pack_into_slice(destination_jurisdiction, destination_jurisdiction_dst);
+        category_dst[0] = category.map_or(0, |category| category.to_u8());
+        *transfer_amount_dst = transfer_amount.to_le_bytes();
+        op_code_dst[0] = op_code.map_or(0, u8::from);
+        *destination_token_account_rent_dst = destination_token_account_rent.to_le_bytes();
+        *spl_token_accounts_created_dst = spl_token_accounts_created.to_le_bytes();
+        *expires_at_slot_dst = expires_at_slot.unwrap_or(0).to_le_bytes();
+        *op_nonce_dst = op_nonce.to_le_bytes();
     }
 
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-        let src = array_ref![src, 0, MultisigOp::LEN];
+        let records_capacity =
+            MultisigOp::records_capacity(src.len()).ok_or(ProgramError::InvalidAccountData)?;
+        let (header, src) = src.split_at(MultisigOp::HEADER_LEN);
+        let (disposition_record_bytes, trailer) =
+            src.split_at(records_capacity * ApprovalDispositionRecord::LEN);
+        let header = array_ref![header, 0, MultisigOp::HEADER_LEN];
+        let (is_initialized, version, disposition_records_count, finalize_compute_units) =
+            array_refs![header, 1, VERSION_LEN, 1, 4];
+        let trailer = array_ref![trailer, 0, MultisigOp::TRAILER_LEN];
         let (
-            is_initialized,
-            version,
-            disposition_records_count,
-            disposition_record_bytes,
             dispositions_required,
             params_hash,
             started_at,
             expires_at,
-            operation_disposition,
+            state,
             initiator,
             rent_return,
             fee_amount,
             fee_account_guid_hash,
+            multisig_data_account,
+            finalize_delay,
+            approved_at,
+            compliance_service,
+            compliance_approved,
+            last_activity_slot,
+            stake_weight_governance,
+            required_stake_weight,
+            wallet,
+            destination_jurisdiction,
+            category,
+            transfer_amount,
+            op_code,
+            destination_token_account_rent,
+            spl_token_accounts_created,
+            expires_at_slot,
+            op_nonce,
         ) = array_refs![
-            src,
-            1,
-            4,
-            1,
-            ApprovalDispositionRecord::LEN * Wallet::MAX_SIGNERS,
+            trailer,
             1,
             HASH_LEN,
             8,
@@ -562,7 +1539,24 @@ impl Pack for MultisigOp {
             PUBKEY_BYTES,
             PUBKEY_BYTES,
             8,
-            HASH_LEN
+            HASH_LEN,
+            PUBKEY_BYTES,
+            8,
+            8,
+            PUBKEY_BYTES,
+            1,
+            8,
+            StakeWeightGovernance::LEN,
+            8,
+            PUBKEY_BYTES,
+            JurisdictionCode::LEN,
+            1,
+            8,
+            1,
+            8,
+            4,
+            8,
+            8
         ];
         let is_initialized = match is_initialized {
             [0] => false,
@@ -571,7 +1565,10 @@ impl Pack for MultisigOp {
         };
 
         let disposition_records_count = usize::from(disposition_records_count[0]);
-        let mut disposition_records = Vec::with_capacity(Wallet::MAX_SIGNERS);
+        if disposition_records_count > records_capacity {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut disposition_records = Vec::with_capacity(disposition_records_count);
         disposition_record_bytes
             .chunks_exact(ApprovalDispositionRecord::LEN)
             .take(disposition_records_count)
@@ -579,20 +1576,24 @@ impl Pack for MultisigOp {
                 let record = ApprovalDispositionRecord::unpack_from_slice(chunk).unwrap();
                 disposition_records.push(record);
             });
+        // ops started before records were kept in order are put in order as they are unpacked
+        disposition_records.sort_unstable_by_key(|record| record.approver);
+
+        let params_hash = if *params_hash == EMPTY_HASH {
+            None
+        } else {
+            Some(Hash::new_from_array(*params_hash))
+        };
 
         Ok(MultisigOp {
             is_initialized,
             version: u32::from_le_bytes(*version),
             disposition_records,
             dispositions_required: dispositions_required[0],
-            params_hash: if *params_hash == EMPTY_HASH {
-                None
-            } else {
-                Some(Hash::new_from_array(*params_hash))
-            },
+            params_hash,
             started_at: i64::from_le_bytes(*started_at),
             expires_at: i64::from_le_bytes(*expires_at),
-            operation_disposition: OperationDisposition::from_u8(operation_disposition[0]),
+            state: MultisigOpState::from_u8(state[0], is_initialized, params_hash)?,
             initiator: Pubkey::new_from_array(*initiator),
             rent_return: Pubkey::new_from_array(*rent_return),
             fee_amount: u64::from_le_bytes(*fee_amount),
@@ -601,6 +1602,85 @@ impl Pack for MultisigOp {
             } else {
                 Some(BalanceAccountGuidHash::new(fee_account_guid_hash))
             },
+            multisig_data_account: if *multisig_data_account == EMPTY_HASH {
+                None
+            } else {
+                Some(Pubkey::new_from_array(*multisig_data_account))
+            },
+            finalize_delay: Duration::from_secs(u64::from_le_bytes(*finalize_delay)),
+            approved_at: i64::from_le_bytes(*approved_at),
+            compliance_service: if *compliance_service == EMPTY_HASH {
+                None
+            } else {
+                Some(Pubkey::new_from_array(*compliance_service))
+            },
+            compliance_approved: compliance_approved[0] == 1,
+            last_activity_slot: u64::from_le_bytes(*last_activity_slot),
+            finalize_compute_units: Some(u32::from_le_bytes(*finalize_compute_units))
+                .filter(|units| *units > 0),
+            stake_weight_governance: StakeWeightGovernance::unpack_from_slice(
+                stake_weight_governance,
+            ),
+            required_stake_weight: u64::from_le_bytes(*required_stake_weight),
+            wallet: Pubkey::new_from_array(*wallet),
+            destination_jurisdiction: JurisdictionCode::unpack_from_slice(
+                destination_jurisdiction,
+            )?,
+            category: match category[0] {
+                0 => None,
+                value => Some(FinalizeCategory::from_u8(value)?),
+            },
+            transfer_amount: u64::from_le_bytes(*transfer_amount),
+            op_code: match op_code[0] {
+                0 => None,
+                value => Some(MultisigOpCode::from_u8(value)?),
+            },
+            destination_token_account_rent: u64::from_le_bytes(*destination_token_account_rent),
+            spl_token_accounts_created: u32::from_le_bytes(*spl_token_accounts_created),
+            expires_at_slot: Some(u64::from_le_bytes(*expires_at_slot)).filter(|slot| *slot > 0),
+            op_nonce: u64::from_le_bytes(*op_nonce),
+        })
+    }
+}
+
+// the size clients allocate multisig op accounts with when they don't size them to the approvers
+const _: () = assert!(MultisigOp::LEN == 2167);
+
+/// Returned by instructions that start (or complete the params of) a multisig op, so the
+/// submitting client learns what approvers will be signing without fetching the op account.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct MultisigOpReceipt {
+    pub params_hash: Option<Hash>,
+    pub expires_at: i64,
+}
+
+impl Sealed for MultisigOpReceipt {}
+
+impl Pack for MultisigOpReceipt {
+    const LEN: usize = HASH_LEN + // params hash
+        8; // expires_at
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, MultisigOpReceipt::LEN];
+        let (params_hash_dst, expires_at_dst) = mut_array_refs![dst, HASH_LEN, 8];
+        if let Some(hash) = self.params_hash {
+            params_hash_dst.copy_from_slice(&hash.to_bytes())
+        } else {
+            params_hash_dst.copy_from_slice(&EMPTY_HASH)
+        }
+        *expires_at_dst = self.expires_at.to_le_bytes();
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, MultisigOpReceipt::LEN];
+        let (params_hash, expires_at) = array_refs![src, HASH_LEN, 8];
+        Ok(MultisigOpReceipt {
+            params_hash: if *params_hash == EMPTY_HASH {
+                None
+            } else {
+                Some(Hash::new_from_array(*params_hash))
+            },
+            expires_at: i64::from_le_bytes(*expires_at),
         })
     }
 }
@@ -614,6 +1694,13 @@ pub enum MultisigOpParams {
         destination: Pubkey,
         amount: u64,
         token_mint: Pubkey,
+        destination_acknowledgement_required: bool,
+        deduct_fee_from_amount: bool,
+        /// The hash of the memo the transfer is to be recorded with, if any.
+        memo_hash: Option<Hash>,
+        /// Whether the finalize may create the destination's associated token account for an
+        /// SPL transfer, paying its rent out of the source balance account if it can afford it.
+        allow_destination_ata_creation: bool,
     },
     Wrap {
         wallet_address: Pubkey,
@@ -665,6 +1752,8 @@ pub enum MultisigOpParams {
         account_guid_hash: BalanceAccountGuidHash,
         whitelist_enabled: Option<BooleanSetting>,
         dapps_enabled: Option<BooleanSetting>,
+        archived: Option<BooleanSetting>,
+        system_owned_destinations_only: Option<BooleanSetting>,
     },
     CreateSPLTokenAccounts {
         wallet_address: Pubkey,
@@ -681,9 +1770,269 @@ pub enum MultisigOpParams {
         wallet_address: Pubkey,
         data: Vec<u8>,
     },
+    VoteRewardsWithdrawal {
+        wallet_address: Pubkey,
+        account_guid_hash: BalanceAccountGuidHash,
+        vote_account: Pubkey,
+        amount: u64,
+    },
+    UpdateAssistant {
+        wallet_address: Pubkey,
+        slot_update_type: SlotUpdateType,
+        slot_id: SlotId<Assistant>,
+        assistant: Assistant,
+    },
+    UpdateRewardSplitPolicy {
+        wallet_address: Pubkey,
+        account_guid_hash: BalanceAccountGuidHash,
+        splits: Vec<RewardSplit>,
+    },
+    UpdateFinalizeSubmitterPolicy {
+        wallet_address: Pubkey,
+        policy: FinalizeSubmitterPolicy,
+    },
+    MintTo {
+        wallet_address: Pubkey,
+        account_guid_hash: BalanceAccountGuidHash,
+        mint: Pubkey,
+        destination_token_account: Pubkey,
+        amount: u64,
+    },
+    SetMintAuthority {
+        wallet_address: Pubkey,
+        account_guid_hash: BalanceAccountGuidHash,
+        mint: Pubkey,
+        authority_type: MintAuthorityType,
+        new_authority: Option<Pubkey>,
+    },
+    OpenPaymentChannel {
+        wallet_address: Pubkey,
+        account_guid_hash: BalanceAccountGuidHash,
+        channel_account: Pubkey,
+        destination: Pubkey,
+        token_mint: Pubkey,
+        allowance: u64,
+        duration: Duration,
+    },
+    UpdateLookupTable {
+        wallet_address: Pubkey,
+        lookup_table: Option<Pubkey>,
+        new_addresses: Vec<Pubkey>,
+    },
+    ImportAddressBookEntries {
+        wallet_address: Pubkey,
+        import: AddressBookImport,
+    },
+    /// A SOL transfer to a destination known only by its commitment until finalize.
+    CommittedTransfer {
+        wallet_address: Pubkey,
+        account_guid_hash: BalanceAccountGuidHash,
+        amount: u64,
+        destination_commitment: Hash,
+    },
+    UpdateCompliancePolicy {
+        wallet_address: Pubkey,
+        policy: Option<CompliancePolicy>,
+    },
+    UpdateWalletReplication {
+        wallet_address: Pubkey,
+        replication: Option<WalletReplication>,
+    },
+    DeleteBalanceAccount {
+        wallet_address: Pubkey,
+        account_guid_hash: BalanceAccountGuidHash,
+    },
+    UpdateSpendingLimit {
+        wallet_address: Pubkey,
+        account_guid_hash: BalanceAccountGuidHash,
+        token_mint: Pubkey,
+        amount: u64,
+    },
+    UpdatePriceOracle {
+        wallet_address: Pubkey,
+        oracle: Option<PriceOracle>,
+    },
+    UpdateMerkleAddressBook {
+        wallet_address: Pubkey,
+        account_guid_hash: BalanceAccountGuidHash,
+        root: Hash,
+    },
+    UpdateWalletFreeze {
+        wallet_address: Pubkey,
+        freeze: WalletFreeze,
+    },
+    UpdateRentReturnAllowlist {
+        wallet_address: Pubkey,
+        allowlist: RentReturnAllowlist,
+    },
+    FullRotation {
+        wallet_address: Pubkey,
+        rotation: FullRotation,
+    },
+    UpdateSignerActivationDelay {
+        wallet_address: Pubkey,
+        delay: Duration,
+    },
+    UpdateFeePolicy {
+        wallet_address: Pubkey,
+        fee_policy: FeePolicy,
+    },
+    UpdateStakeWeightGovernance {
+        wallet_address: Pubkey,
+        governance: Option<StakeWeightGovernance>,
+    },
+    RenewAddressBookEntry {
+        wallet_address: Pubkey,
+        slot_id: SlotId<AddressBookEntry>,
+        entry: AddressBookEntry,
+        expires_at: i64,
+    },
+    UpdateApprovalGroup {
+        wallet_address: Pubkey,
+        approval_group: Option<ApprovalGroup>,
+    },
+    EnableDepositAddresses {
+        wallet_address: Pubkey,
+        account_guid_hash: BalanceAccountGuidHash,
+        enabled: BooleanSetting,
+    },
+    CompactWallet {
+        wallet_address: Pubkey,
+        compaction: WalletCompaction,
+    },
+    UpdateInsuranceAttestor {
+        wallet_address: Pubkey,
+        attestor: Pubkey,
+    },
+    UpdateWrapperBook {
+        wallet_address: Pubkey,
+        wrapper_book: WrapperBook,
+    },
+    /// A wrap into, or unwrap from, a mint in the wallet's wrapper book through its adapter.
+    AdapterWrap {
+        wallet_address: Pubkey,
+        account_guid_hash: BalanceAccountGuidHash,
+        wrapper_mint: Pubkey,
+        amount: u64,
+        direction: WrapDirection,
+    },
 }
 
 impl MultisigOpParams {
+    pub fn finalize_category(&self) -> FinalizeCategory {
+        match self {
+            MultisigOpParams::Transfer { .. }
+            | MultisigOpParams::Wrap { .. }
+            | MultisigOpParams::AdapterWrap { .. }
+            | MultisigOpParams::VoteRewardsWithdrawal { .. }
+            | MultisigOpParams::MintTo { .. }
+            | MultisigOpParams::SetMintAuthority { .. }
+            | MultisigOpParams::CreateSPLTokenAccounts { .. }
+            | MultisigOpParams::OpenPaymentChannel { .. }
+            | MultisigOpParams::CommittedTransfer { .. } => FinalizeCategory::Transfer,
+            MultisigOpParams::DAppTransaction { .. } | MultisigOpParams::SignData { .. } => {
+                FinalizeCategory::DApp
+            }
+            _ => FinalizeCategory::Config,
+        }
+    }
+
+    pub fn op_code(&self) -> MultisigOpCode {
+        match self {
+            MultisigOpParams::Transfer { .. } => MultisigOpCode::Transfer,
+            MultisigOpParams::Wrap { .. } => MultisigOpCode::Wrap,
+            MultisigOpParams::UpdateSigner { .. } => MultisigOpCode::UpdateSigner,
+            MultisigOpParams::UpdateWalletConfigPolicy { .. } => {
+                MultisigOpCode::UpdateWalletConfigPolicy
+            }
+            MultisigOpParams::DAppTransaction { .. } => MultisigOpCode::DAppTransaction,
+            MultisigOpParams::UpdateDAppBook { .. } => MultisigOpCode::UpdateDAppBook,
+            MultisigOpParams::AddressBookUpdate { .. } => MultisigOpCode::AddressBookUpdate,
+            MultisigOpParams::CreateBalanceAccount { .. } => MultisigOpCode::CreateBalanceAccount,
+            MultisigOpParams::UpdateBalanceAccountPolicy { .. } => {
+                MultisigOpCode::UpdateBalanceAccountPolicy
+            }
+            MultisigOpParams::UpdateBalanceAccountName { .. } => {
+                MultisigOpCode::UpdateBalanceAccountName
+            }
+            MultisigOpParams::UpdateBalanceAccountSettings { .. } => {
+                MultisigOpCode::UpdateBalanceAccountSettings
+            }
+            MultisigOpParams::CreateSPLTokenAccounts { .. } => {
+                MultisigOpCode::CreateSPLTokenAccounts
+            }
+            MultisigOpParams::UpdateBalanceAccountAddressWhitelist { .. } => {
+                MultisigOpCode::UpdateBalanceAccountAddressWhitelist
+            }
+            MultisigOpParams::SignData { .. } => MultisigOpCode::SignData,
+            MultisigOpParams::VoteRewardsWithdrawal { .. } => MultisigOpCode::VoteRewardsWithdrawal,
+            MultisigOpParams::UpdateAssistant { .. } => MultisigOpCode::UpdateAssistant,
+            MultisigOpParams::UpdateRewardSplitPolicy { .. } => {
+                MultisigOpCode::UpdateRewardSplitPolicy
+            }
+            MultisigOpParams::UpdateFinalizeSubmitterPolicy { .. } => {
+                MultisigOpCode::UpdateFinalizeSubmitterPolicy
+            }
+            MultisigOpParams::MintTo { .. } => MultisigOpCode::MintTo,
+            MultisigOpParams::SetMintAuthority { .. } => MultisigOpCode::SetMintAuthority,
+            MultisigOpParams::OpenPaymentChannel { .. } => MultisigOpCode::OpenPaymentChannel,
+            MultisigOpParams::UpdateLookupTable { .. } => MultisigOpCode::UpdateLookupTable,
+            MultisigOpParams::ImportAddressBookEntries { .. } => {
+                MultisigOpCode::ImportAddressBookEntries
+            }
+            MultisigOpParams::CommittedTransfer { .. } => MultisigOpCode::CommittedTransfer,
+            MultisigOpParams::UpdateCompliancePolicy { .. } => {
+                MultisigOpCode::UpdateCompliancePolicy
+            }
+            MultisigOpParams::UpdateWalletReplication { .. } => {
+                MultisigOpCode::UpdateWalletReplication
+            }
+            MultisigOpParams::DeleteBalanceAccount { .. } => MultisigOpCode::DeleteBalanceAccount,
+            MultisigOpParams::UpdateSpendingLimit { .. } => MultisigOpCode::UpdateSpendingLimit,
+            MultisigOpParams::UpdatePriceOracle { .. } => MultisigOpCode::UpdatePriceOracle,
+            MultisigOpParams::UpdateMerkleAddressBook { .. } => {
+                MultisigOpCode::UpdateMerkleAddressBook
+            }
+            MultisigOpParams::UpdateWalletFreeze { .. } => MultisigOpCode::UpdateWalletFreeze,
+            MultisigOpParams::UpdateRentReturnAllowlist { .. } => {
+                MultisigOpCode::UpdateRentReturnAllowlist
+            }
+            MultisigOpParams::FullRotation { .. } => MultisigOpCode::FullRotation,
+            MultisigOpParams::UpdateSignerActivationDelay { .. } => {
+                MultisigOpCode::UpdateSignerActivationDelay
+            }
+            MultisigOpParams::UpdateFeePolicy { .. } => MultisigOpCode::UpdateFeePolicy,
+            MultisigOpParams::UpdateStakeWeightGovernance { .. } => {
+                MultisigOpCode::UpdateStakeWeightGovernance
+            }
+            MultisigOpParams::RenewAddressBookEntry { .. } => MultisigOpCode::RenewAddressBookEntry,
+            MultisigOpParams::UpdateApprovalGroup { .. } => MultisigOpCode::UpdateApprovalGroup,
+            MultisigOpParams::EnableDepositAddresses { .. } => {
+                MultisigOpCode::EnableDepositAddresses
+            }
+            MultisigOpParams::CompactWallet { .. } => MultisigOpCode::CompactWallet,
+            MultisigOpParams::UpdateInsuranceAttestor { .. } => {
+                MultisigOpCode::UpdateInsuranceAttestor
+            }
+            MultisigOpParams::UpdateWrapperBook { .. } => MultisigOpCode::UpdateWrapperBook,
+            MultisigOpParams::AdapterWrap { .. } => MultisigOpCode::AdapterWrap,
+        }
+    }
+
+    /// The amount an op of the transfer category moves, or 0 for others.
+    pub fn transfer_amount(&self) -> u64 {
+        match self {
+            MultisigOpParams::Transfer { amount, .. }
+            | MultisigOpParams::Wrap { amount, .. }
+            | MultisigOpParams::AdapterWrap { amount, .. }
+            | MultisigOpParams::VoteRewardsWithdrawal { amount, .. }
+            | MultisigOpParams::MintTo { amount, .. }
+            | MultisigOpParams::CommittedTransfer { amount, .. } => *amount,
+            MultisigOpParams::OpenPaymentChannel { allowance, .. } => *allowance,
+            _ => 0,
+        }
+    }
+
     fn hash_wallet_update_op(
         type_code: u8,
         wallet_address: &Pubkey,
@@ -726,8 +2075,12 @@ impl MultisigOpParams {
                 destination,
                 amount,
                 token_mint,
+                destination_acknowledgement_required,
+                deduct_fee_from_amount,
+                memo_hash,
+                allow_destination_ata_creation,
             } => {
-                const LEN: usize = 1 + PUBKEY_BYTES * 4 + 8 + COMMON_DATA_LEN;
+                const LEN: usize = 1 + PUBKEY_BYTES * 4 + 8 + 1 + 1 + COMMON_DATA_LEN;
                 let mut bytes: [u8; LEN] = [0; LEN];
                 let bytes_ref = array_mut_ref![bytes, 0, LEN];
                 let (
@@ -738,6 +2091,8 @@ impl MultisigOpParams {
                     destination_ref,
                     amount_ref,
                     token_mint_ref,
+                    destination_acknowledgement_required_ref,
+                    deduct_fee_from_amount_ref,
                 ) = mut_array_refs![
                     bytes_ref,
                     1,
@@ -746,7 +2101,9 @@ impl MultisigOpParams {
                     HASH_LEN,
                     PUBKEY_BYTES,
                     8,
-                    PUBKEY_BYTES
+                    PUBKEY_BYTES,
+                    1,
+                    1
                 ];
                 type_code_ref[0] = MultisigOpCode::Transfer.into();
                 common_data_ref.copy_from_slice(common_data_bytes.as_ref());
@@ -755,7 +2112,18 @@ impl MultisigOpParams {
                 destination_ref.copy_from_slice(destination.as_ref());
                 *amount_ref = amount.to_le_bytes();
                 token_mint_ref.copy_from_slice(token_mint.as_ref());
-                hash(&bytes)
+                destination_acknowledgement_required_ref[0] =
+                    *destination_acknowledgement_required as u8;
+                deduct_fee_from_amount_ref[0] = *deduct_fee_from_amount as u8;
+                // transfers without a memo, and not allowed to create the destination's token
+                // account, hash as they did before either was introduced
+                let memo_hash_bytes: &[u8] = memo_hash.as_ref().map_or(&[], |hash| hash.as_ref());
+                let ata_creation_bytes: &[u8] = if *allow_destination_ata_creation {
+                    &[1]
+                } else {
+                    &[]
+                };
+                hashv(&[&bytes, memo_hash_bytes, ata_creation_bytes])
             }
             MultisigOpParams::Wrap {
                 wallet_address,
@@ -789,13 +2157,14 @@ impl MultisigOpParams {
                 signer,
             } => {
                 let mut bytes: Vec<u8> =
-                    Vec::with_capacity(1 + 2 + PUBKEY_BYTES * 2 + COMMON_DATA_LEN);
+                    Vec::with_capacity(1 + 2 + PUBKEY_BYTES * 2 + 1 + COMMON_DATA_LEN);
                 bytes.push(MultisigOpCode::UpdateSigner.into());
                 bytes.extend_from_slice(common_data_bytes.as_slice());
                 bytes.extend_from_slice(&wallet_address.to_bytes());
                 bytes.push(slot_update_type.to_u8());
                 bytes.push(slot_id.value as u8);
                 bytes.extend_from_slice(signer.key.as_ref());
+                bytes.push(signer.capabilities.to_u8());
                 hash(&bytes)
             }
             MultisigOpParams::DAppTransaction {
@@ -907,15 +2276,20 @@ impl MultisigOpParams {
                 account_guid_hash,
                 whitelist_enabled,
                 dapps_enabled,
+                archived,
+                system_owned_destinations_only,
             } => {
-                let mut bytes: Vec<u8> =
-                    Vec::with_capacity(1 + PUBKEY_BYTES + HASH_LEN + 2 + 2 + COMMON_DATA_LEN);
+                let mut bytes: Vec<u8> = Vec::with_capacity(
+                    1 + PUBKEY_BYTES + HASH_LEN + 2 + 2 + 2 + 2 + COMMON_DATA_LEN,
+                );
                 bytes.push(MultisigOpCode::UpdateBalanceAccountSettings.into());
                 bytes.extend_from_slice(common_data_bytes.as_slice());
                 bytes.extend_from_slice(&wallet_address.to_bytes());
                 bytes.extend_from_slice(account_guid_hash.to_bytes());
                 pack_option(whitelist_enabled.as_ref(), &mut bytes);
                 pack_option(dapps_enabled.as_ref(), &mut bytes);
+                pack_option(archived.as_ref(), &mut bytes);
+                pack_option(system_owned_destinations_only.as_ref(), &mut bytes);
                 hash(&bytes)
             }
             MultisigOpParams::CreateSPLTokenAccounts {
@@ -971,22 +2345,1513 @@ impl MultisigOpParams {
                 bytes.extend_from_slice(data.as_slice());
                 hash(&bytes)
             }
-        }
-    }
-}
-
-const COMMON_DATA_LEN: usize = PUBKEY_BYTES + PUBKEY_BYTES + 8 + HASH_LEN;
-
-pub fn common_data(multisig_op: &MultisigOp) -> Vec<u8> {
-    let mut common_data_bytes: Vec<u8> = Vec::with_capacity(COMMON_DATA_LEN);
-    common_data_bytes.extend_from_slice(multisig_op.initiator.as_ref());
-    common_data_bytes.extend_from_slice(multisig_op.rent_return.as_ref());
-    common_data_bytes.put_u64_le(multisig_op.fee_amount);
-    common_data_bytes.extend_from_slice(
-        multisig_op
-            .fee_account_guid_hash
-            .unwrap_or(BalanceAccountGuidHash::zero())
-            .to_bytes(),
-    );
-    return common_data_bytes;
+            MultisigOpParams::VoteRewardsWithdrawal {
+                wallet_address,
+                account_guid_hash,
+                vote_account,
+                amount,
+            } => {
+                let mut bytes: Vec<u8> =
+                    Vec::with_capacity(1 + COMMON_DATA_LEN + PUBKEY_BYTES * 2 + HASH_LEN + 8);
+                bytes.push(MultisigOpCode::VoteRewardsWithdrawal.into());
+                bytes.extend_from_slice(common_data_bytes.as_slice());
+                bytes.extend_from_slice(&wallet_address.to_bytes());
+                bytes.extend_from_slice(account_guid_hash.to_bytes());
+                bytes.extend_from_slice(&vote_account.to_bytes());
+                bytes.put_u64_le(*amount);
+                hash(&bytes)
+            }
+            MultisigOpParams::UpdateAssistant {
+                wallet_address,
+                slot_update_type,
+                slot_id,
+                assistant,
+            } => {
+                let mut bytes: Vec<u8> =
+                    Vec::with_capacity(1 + COMMON_DATA_LEN + PUBKEY_BYTES * 2 + 2 + 1 + 2);
+                bytes.push(MultisigOpCode::UpdateAssistant.into());
+                bytes.extend_from_slice(common_data_bytes.as_slice());
+                bytes.extend_from_slice(&wallet_address.to_bytes());
+                bytes.push(slot_update_type.to_u8());
+                bytes.push(slot_id.value as u8);
+                bytes.extend_from_slice(assistant.key.as_ref());
+                bytes.push(assistant.enabled as u8);
+                bytes.put_u16_le(assistant.max_initiations_per_window);
+                hash(&bytes)
+            }
+            MultisigOpParams::UpdateRewardSplitPolicy {
+                wallet_address,
+                account_guid_hash,
+                ref splits,
+            } => {
+                let mut bytes: Vec<u8> = Vec::with_capacity(
+                    1 + COMMON_DATA_LEN
+                        + PUBKEY_BYTES
+                        + HASH_LEN
+                        + 1
+                        + splits.len() * RewardSplit::LEN,
+                );
+                bytes.push(MultisigOpCode::UpdateRewardSplitPolicy.into());
+                bytes.extend_from_slice(common_data_bytes.as_slice());
+                bytes.extend_from_slice(&wallet_address.to_bytes());
+                bytes.extend_from_slice(account_guid_hash.to_bytes());
+                append_reward_splits(splits, &mut bytes);
+                hash(&bytes)
+            }
+            MultisigOpParams::UpdateFinalizeSubmitterPolicy {
+                wallet_address,
+                policy,
+            } => {
+                let mut update_bytes: Vec<u8> = Vec::new();
+                append_finalize_submitter_policy(policy, &mut update_bytes);
+                Self::hash_wallet_update_op(
+                    MultisigOpCode::UpdateFinalizeSubmitterPolicy.into(),
+                    wallet_address,
+                    common_data_bytes,
+                    update_bytes,
+                )
+            }
+            MultisigOpParams::MintTo {
+                wallet_address,
+                account_guid_hash,
+                mint,
+                destination_token_account,
+                amount,
+            } => {
+                let mut bytes: Vec<u8> =
+                    Vec::with_capacity(1 + COMMON_DATA_LEN + PUBKEY_BYTES * 3 + HASH_LEN + 8);
+                bytes.push(MultisigOpCode::MintTo.into());
+                bytes.extend_from_slice(common_data_bytes.as_slice());
+                bytes.extend_from_slice(&wallet_address.to_bytes());
+                bytes.extend_from_slice(account_guid_hash.to_bytes());
+                bytes.extend_from_slice(&mint.to_bytes());
+                bytes.extend_from_slice(&destination_token_account.to_bytes());
+                bytes.put_u64_le(*amount);
+                hash(&bytes)
+            }
+            MultisigOpParams::SetMintAuthority {
+                wallet_address,
+                account_guid_hash,
+                mint,
+                authority_type,
+                new_authority,
+            } => {
+                let mut bytes: Vec<u8> =
+                    Vec::with_capacity(1 + COMMON_DATA_LEN + PUBKEY_BYTES * 3 + HASH_LEN + 2);
+                bytes.push(MultisigOpCode::SetMintAuthority.into());
+                bytes.extend_from_slice(common_data_bytes.as_slice());
+                bytes.extend_from_slice(&wallet_address.to_bytes());
+                bytes.extend_from_slice(account_guid_hash.to_bytes());
+                bytes.extend_from_slice(&mint.to_bytes());
+                bytes.push(authority_type.to_u8());
+                append_optional_pubkey(new_authority, &mut bytes);
+                hash(&bytes)
+            }
+            MultisigOpParams::OpenPaymentChannel {
+                wallet_address,
+                account_guid_hash,
+                channel_account,
+                destination,
+                token_mint,
+                allowance,
+                duration,
+            } => {
+                let mut bytes: Vec<u8> =
+                    Vec::with_capacity(1 + COMMON_DATA_LEN + PUBKEY_BYTES * 4 + HASH_LEN + 16);
+                bytes.push(MultisigOpCode::OpenPaymentChannel.into());
+                bytes.extend_from_slice(common_data_bytes.as_slice());
+                bytes.extend_from_slice(&wallet_address.to_bytes());
+                bytes.extend_from_slice(account_guid_hash.to_bytes());
+                bytes.extend_from_slice(&channel_account.to_bytes());
+                bytes.extend_from_slice(&destination.to_bytes());
+                bytes.extend_from_slice(&token_mint.to_bytes());
+                bytes.put_u64_le(*allowance);
+                bytes.put_u64_le(duration.as_secs());
+                hash(&bytes)
+            }
+            MultisigOpParams::UpdateLookupTable {
+                wallet_address,
+                lookup_table,
+                new_addresses,
+            } => {
+                let mut bytes: Vec<u8> = Vec::with_capacity(
+                    1 + COMMON_DATA_LEN
+                        + PUBKEY_BYTES * 2
+                        + 1
+                        + 1
+                        + PUBKEY_BYTES * new_addresses.len(),
+                );
+                bytes.push(MultisigOpCode::UpdateLookupTable.into());
+                bytes.extend_from_slice(common_data_bytes.as_slice());
+                bytes.extend_from_slice(&wallet_address.to_bytes());
+                append_optional_pubkey(lookup_table, &mut bytes);
+                append_pubkeys(new_addresses, &mut bytes);
+                hash(&bytes)
+            }
+            MultisigOpParams::ImportAddressBookEntries {
+                wallet_address,
+                import,
+            } => {
+                let mut import_bytes: Vec<u8> = Vec::new();
+                import.pack(&mut import_bytes);
+                Self::hash_wallet_update_op(
+                    MultisigOpCode::ImportAddressBookEntries.into(),
+                    wallet_address,
+                    common_data_bytes,
+                    import_bytes,
+                )
+            }
+            MultisigOpParams::CommittedTransfer {
+                wallet_address,
+                account_guid_hash,
+                amount,
+                destination_commitment,
+            } => {
+                let mut bytes: Vec<u8> = Vec::with_capacity(
+                    1 + COMMON_DATA_LEN + PUBKEY_BYTES + HASH_LEN + 8 + HASH_LEN,
+                );
+                bytes.push(MultisigOpCode::CommittedTransfer.into());
+                bytes.extend_from_slice(common_data_bytes.as_slice());
+                bytes.extend_from_slice(wallet_address.as_ref());
+                bytes.extend_from_slice(account_guid_hash.to_bytes());
+                bytes.put_u64_le(*amount);
+                bytes.extend_from_slice(destination_commitment.as_ref());
+                hash(&bytes)
+            }
+            MultisigOpParams::UpdateCompliancePolicy {
+                wallet_address,
+                policy,
+            } => {
+                let mut update_bytes: Vec<u8> = Vec::new();
+                append_compliance_policy(policy, &mut update_bytes);
+                Self::hash_wallet_update_op(
+                    MultisigOpCode::UpdateCompliancePolicy.into(),
+                    wallet_address,
+                    common_data_bytes,
+                    update_bytes,
+                )
+            }
+            MultisigOpParams::UpdateWalletReplication {
+                wallet_address,
+                replication,
+            } => {
+                let mut update_bytes: Vec<u8> = Vec::new();
+                append_wallet_replication(replication, &mut update_bytes);
+                Self::hash_wallet_update_op(
+                    MultisigOpCode::UpdateWalletReplication.into(),
+                    wallet_address,
+                    common_data_bytes,
+                    update_bytes,
+                )
+            }
+            MultisigOpParams::DeleteBalanceAccount {
+                wallet_address,
+                account_guid_hash,
+            } => Self::hash_wallet_update_op(
+                MultisigOpCode::DeleteBalanceAccount.into(),
+                wallet_address,
+                common_data_bytes,
+                account_guid_hash.to_bytes().to_vec(),
+            ),
+            MultisigOpParams::UpdateSpendingLimit {
+                wallet_address,
+                account_guid_hash,
+                token_mint,
+                amount,
+            } => {
+                let mut update_bytes: Vec<u8> = Vec::with_capacity(PUBKEY_BYTES + 8);
+                update_bytes.extend_from_slice(token_mint.as_ref());
+                update_bytes.put_u64_le(*amount);
+                Self::hash_balance_account_update_op(
+                    MultisigOpCode::UpdateSpendingLimit.into(),
+                    wallet_address,
+                    common_data_bytes,
+                    account_guid_hash,
+                    update_bytes,
+                )
+            }
+            MultisigOpParams::UpdatePriceOracle {
+                wallet_address,
+                oracle,
+            } => {
+                let mut update_bytes: Vec<u8> = Vec::new();
+                append_price_oracle(oracle, &mut update_bytes);
+                Self::hash_wallet_update_op(
+                    MultisigOpCode::UpdatePriceOracle.into(),
+                    wallet_address,
+                    common_data_bytes,
+                    update_bytes,
+                )
+            }
+            MultisigOpParams::UpdateMerkleAddressBook {
+                wallet_address,
+                account_guid_hash,
+                root,
+            } => Self::hash_balance_account_update_op(
+                MultisigOpCode::UpdateMerkleAddressBook.into(),
+                wallet_address,
+                common_data_bytes,
+                account_guid_hash,
+                root.to_bytes().to_vec(),
+            ),
+            MultisigOpParams::UpdateWalletFreeze {
+                wallet_address,
+                freeze,
+            } => {
+                let mut update_bytes: Vec<u8> = Vec::new();
+                append_wallet_freeze(freeze, &mut update_bytes);
+                Self::hash_wallet_update_op(
+                    MultisigOpCode::UpdateWalletFreeze.into(),
+                    wallet_address,
+                    common_data_bytes,
+                    update_bytes,
+                )
+            }
+            MultisigOpParams::UpdateRentReturnAllowlist {
+                wallet_address,
+                allowlist,
+            } => {
+                let mut update_bytes: Vec<u8> = Vec::new();
+                append_pubkeys(&allowlist.entries, &mut update_bytes);
+                Self::hash_wallet_update_op(
+                    MultisigOpCode::UpdateRentReturnAllowlist.into(),
+                    wallet_address,
+                    common_data_bytes,
+                    update_bytes,
+                )
+            }
+            MultisigOpParams::FullRotation {
+                wallet_address,
+                rotation,
+            } => {
+                let mut update_bytes: Vec<u8> = Vec::new();
+                rotation.pack(&mut update_bytes);
+                Self::hash_wallet_update_op(
+                    MultisigOpCode::FullRotation.into(),
+                    wallet_address,
+                    common_data_bytes,
+                    update_bytes,
+                )
+            }
+            MultisigOpParams::UpdateSignerActivationDelay {
+                wallet_address,
+                delay,
+            } => {
+                let mut update_bytes: Vec<u8> = Vec::new();
+                update_bytes.put_u64_le(delay.as_secs());
+                Self::hash_wallet_update_op(
+                    MultisigOpCode::UpdateSignerActivationDelay.into(),
+                    wallet_address,
+                    common_data_bytes,
+                    update_bytes,
+                )
+            }
+            MultisigOpParams::UpdateFeePolicy {
+                wallet_address,
+                fee_policy,
+            } => {
+                let mut update_bytes: Vec<u8> = Vec::new();
+                append_fee_policy(fee_policy, &mut update_bytes);
+                Self::hash_wallet_update_op(
+                    MultisigOpCode::UpdateFeePolicy.into(),
+                    wallet_address,
+                    common_data_bytes,
+                    update_bytes,
+                )
+            }
+            MultisigOpParams::UpdateStakeWeightGovernance {
+                wallet_address,
+                governance,
+            } => {
+                let mut update_bytes: Vec<u8> = Vec::new();
+                append_stake_weight_governance(governance, &mut update_bytes);
+                Self::hash_wallet_update_op(
+                    MultisigOpCode::UpdateStakeWeightGovernance.into(),
+                    wallet_address,
+                    common_data_bytes,
+                    update_bytes,
+                )
+            }
+            MultisigOpParams::RenewAddressBookEntry {
+                wallet_address,
+                slot_id,
+                entry,
+                expires_at,
+            } => {
+                let mut update_bytes: Vec<u8> = Vec::new();
+                append_address_book_renewal(*slot_id, entry, *expires_at, &mut update_bytes);
+                Self::hash_wallet_update_op(
+                    MultisigOpCode::RenewAddressBookEntry.into(),
+                    wallet_address,
+                    common_data_bytes,
+                    update_bytes,
+                )
+            }
+            MultisigOpParams::UpdateApprovalGroup {
+                wallet_address,
+                approval_group,
+            } => {
+                let mut update_bytes: Vec<u8> = Vec::new();
+                append_approval_group(approval_group, &mut update_bytes);
+                Self::hash_wallet_update_op(
+                    MultisigOpCode::UpdateApprovalGroup.into(),
+                    wallet_address,
+                    common_data_bytes,
+                    update_bytes,
+                )
+            }
+            MultisigOpParams::EnableDepositAddresses {
+                wallet_address,
+                account_guid_hash,
+                enabled,
+            } => Self::hash_balance_account_update_op(
+                MultisigOpCode::EnableDepositAddresses.into(),
+                wallet_address,
+                common_data_bytes,
+                account_guid_hash,
+                vec![enabled.to_u8()],
+            ),
+            MultisigOpParams::CompactWallet {
+                wallet_address,
+                compaction,
+            } => {
+                let mut update_bytes: Vec<u8> = Vec::new();
+                compaction.pack(&mut update_bytes);
+                Self::hash_wallet_update_op(
+                    MultisigOpCode::CompactWallet.into(),
+                    wallet_address,
+                    common_data_bytes,
+                    update_bytes,
+                )
+            }
+            MultisigOpParams::UpdateInsuranceAttestor {
+                wallet_address,
+                attestor,
+            } => Self::hash_wallet_update_op(
+                MultisigOpCode::UpdateInsuranceAttestor.into(),
+                wallet_address,
+                common_data_bytes,
+                attestor.to_bytes().to_vec(),
+            ),
+            MultisigOpParams::UpdateWrapperBook {
+                wallet_address,
+                wrapper_book,
+            } => {
+                let mut update_bytes: Vec<u8> = Vec::new();
+                wrapper_book.pack(&mut update_bytes);
+                Self::hash_wallet_update_op(
+                    MultisigOpCode::UpdateWrapperBook.into(),
+                    wallet_address,
+                    common_data_bytes,
+                    update_bytes,
+                )
+            }
+            MultisigOpParams::AdapterWrap {
+                wallet_address,
+                account_guid_hash,
+                wrapper_mint,
+                amount,
+                direction,
+            } => {
+                let mut update_bytes: Vec<u8> = Vec::with_capacity(PUBKEY_BYTES + 8 + 1);
+                update_bytes.extend_from_slice(wrapper_mint.as_ref());
+                update_bytes.put_u64_le(*amount);
+                update_bytes.push(direction.to_u8());
+                Self::hash_balance_account_update_op(
+                    MultisigOpCode::AdapterWrap.into(),
+                    wallet_address,
+                    common_data_bytes,
+                    account_guid_hash,
+                    update_bytes,
+                )
+            }
+        }
+    }
+}
+
+const COMMON_DATA_LEN: usize = PUBKEY_BYTES + PUBKEY_BYTES + 8 + HASH_LEN + 8;
+
+pub fn common_data(multisig_op: &MultisigOp) -> Vec<u8> {
+    let mut common_data_bytes: Vec<u8> = Vec::with_capacity(COMMON_DATA_LEN);
+    common_data_bytes.extend_from_slice(multisig_op.initiator.as_ref());
+    common_data_bytes.extend_from_slice(multisig_op.rent_return.as_ref());
+    common_data_bytes.put_u64_le(multisig_op.fee_amount);
+    common_data_bytes.extend_from_slice(
+        multisig_op
+            .fee_account_guid_hash
+            .unwrap_or(BalanceAccountGuidHash::zero())
+            .to_bytes(),
+    );
+    common_data_bytes.put_u64_le(multisig_op.op_nonce);
+    return common_data_bytes;
+}
+
+#[cfg(test)]
+mod test {
+    use crate::constants::HASH_LEN;
+    use crate::error::WalletError;
+    use crate::model::balance_account::BalanceAccountGuidHash;
+    use crate::model::multisig_op::{
+        ApprovalDisposition, DispositionReason, MultisigOp, MultisigOpParams, MultisigOpReceipt,
+        MultisigOpState, OperationDisposition,
+    };
+    use crate::model::stake_weight_governance::StakeWeightGovernance;
+    use crate::test_utils::{clock_at, AccountInfoFixture};
+    use crate::version::Versioned;
+    use solana_program::clock::Clock;
+    use solana_program::hash::Hash;
+    use solana_program::program_error::ProgramError;
+    use solana_program::program_pack::Pack;
+    use solana_program::pubkey::Pubkey;
+    use std::time::Duration;
+
+    const STARTED_AT: i64 = 1000;
+    const EXPIRES_AT: i64 = 2000;
+
+    fn signers(count: usize) -> Vec<AccountInfoFixture> {
+        (0..count)
+            .map(|_| AccountInfoFixture::unique().signer())
+            .collect()
+    }
+
+    fn params() -> MultisigOpParams {
+        MultisigOpParams::SignData {
+            wallet_address: Pubkey::new_unique(),
+            data: vec![1, 2, 3],
+        }
+    }
+
+    fn new_op(
+        approvers: &Vec<AccountInfoFixture>,
+        initiator_disposition: ApprovalDisposition,
+        approvals_required: u8,
+        params: &MultisigOpParams,
+    ) -> MultisigOp {
+        let mut op = MultisigOp::unpack_unchecked(&vec![0; MultisigOp::LEN]).unwrap();
+        op.init(
+            approvers.iter().map(|it| it.key()).collect(),
+            (approvers[0].key(), initiator_disposition),
+            approvals_required,
+            STARTED_AT,
+            EXPIRES_AT,
+            Some(params.clone()),
+            Pubkey::new_unique(),
+            0,
+            None,
+            0,
+        )
+        .unwrap();
+        op
+    }
+
+    fn record(
+        op: &mut MultisigOp,
+        approver: &mut AccountInfoFixture,
+        disposition: ApprovalDisposition,
+    ) -> Result<(), ProgramError> {
+        op.validate_and_record_approval_disposition(
+            &approver.account_info(),
+            disposition,
+            None,
+            &clock_at(STARTED_AT + 1),
+        )
+    }
+
+    #[test]
+    fn test_receipt() {
+        let approvers = signers(2);
+        let params = params();
+        let op = new_op(&approvers, ApprovalDisposition::APPROVE, 2, &params);
+        let receipt = op.receipt(&clock_at(STARTED_AT));
+        assert_eq!(
+            receipt,
+            MultisigOpReceipt {
+                params_hash: Some(params.hash(&op)),
+                expires_at: EXPIRES_AT,
+            }
+        );
+        receipt_round_trip(receipt);
+
+        // an op whose params are still being supplied has no params hash to return yet
+        let mut op = MultisigOp::unpack_unchecked(&vec![0; MultisigOp::LEN]).unwrap();
+        op.expires_at = EXPIRES_AT;
+        receipt_round_trip(op.receipt(&clock_at(STARTED_AT)));
+        assert_eq!(op.receipt(&clock_at(STARTED_AT)).params_hash, None);
+    }
+
+    #[test]
+    fn test_version_from_slice_matches_layout() {
+        let op = new_op(&signers(1), ApprovalDisposition::APPROVE, 1, &params());
+        let mut buffer = vec![0; MultisigOp::LEN];
+        op.pack_into_slice(&mut buffer);
+        assert_eq!(MultisigOp::version_from_slice(&buffer), Ok(op.version));
+        assert_eq!(
+            MultisigOp::version_from_slice(&buffer[..MultisigOp::VERSION_OFFSET]),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn test_finalize_compute_units_from_slice() {
+        let mut op = new_op(&signers(1), ApprovalDisposition::APPROVE, 1, &params());
+        let mut buffer = vec![0; MultisigOp::LEN];
+        op.pack_into_slice(&mut buffer);
+        assert_eq!(
+            MultisigOp::finalize_compute_units_from_slice(&buffer),
+            Ok(None)
+        );
+
+        op.finalize_compute_units = Some(300_000);
+        op.pack_into_slice(&mut buffer);
+        assert_eq!(
+            MultisigOp::finalize_compute_units_from_slice(&buffer),
+            Ok(Some(300_000))
+        );
+        assert_eq!(
+            MultisigOp::unpack(&buffer).unwrap().finalize_compute_units,
+            Some(300_000)
+        );
+        assert_eq!(
+            MultisigOp::finalize_compute_units_from_slice(&buffer[..MultisigOp::HEADER_LEN - 1]),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    fn receipt_round_trip(receipt: MultisigOpReceipt) {
+        let mut packed = vec![0; MultisigOpReceipt::LEN];
+        receipt.pack_into_slice(&mut packed);
+        assert_eq!(MultisigOpReceipt::unpack_from_slice(&packed), Ok(receipt));
+    }
+
+    #[test]
+    fn test_approved_once_required_approvals_are_reached() {
+        for approver_count in 1..=6 {
+            for approvals_required in 1..=approver_count as u8 {
+                let mut approvers = signers(approver_count);
+                let mut op = new_op(
+                    &approvers,
+                    ApprovalDisposition::APPROVE,
+                    approvals_required,
+                    &params(),
+                );
+                for (approvals, approver) in approvers.iter_mut().enumerate().skip(1) {
+                    let expected = if approvals as u8 >= approvals_required {
+                        OperationDisposition::APPROVED
+                    } else {
+                        OperationDisposition::NONE
+                    };
+                    assert_eq!(op.operation_disposition(), expected);
+                    record(&mut op, approver, ApprovalDisposition::APPROVE).unwrap();
+                }
+                assert_eq!(op.operation_disposition(), OperationDisposition::APPROVED);
+            }
+        }
+    }
+
+    #[test]
+    fn test_denied_once_required_denials_are_reached() {
+        for approver_count in 2..=6 {
+            for approvals_required in 1..approver_count as u8 {
+                let mut approvers = signers(approver_count);
+                let mut op = new_op(
+                    &approvers,
+                    ApprovalDisposition::NONE,
+                    approvals_required,
+                    &params(),
+                );
+                for approver in approvers.iter_mut().take(approvals_required as usize) {
+                    assert_eq!(op.operation_disposition(), OperationDisposition::NONE);
+                    record(&mut op, approver, ApprovalDisposition::DENY).unwrap();
+                }
+                assert_eq!(op.operation_disposition(), OperationDisposition::DENIED);
+
+                // a final disposition is never overturned by later approvals
+                for approver in approvers.iter_mut().skip(approvals_required as usize) {
+                    record(&mut op, approver, ApprovalDisposition::APPROVE).unwrap();
+                }
+                assert_eq!(op.operation_disposition(), OperationDisposition::DENIED);
+            }
+        }
+    }
+
+    #[test]
+    fn test_record_approval_disposition_validation() {
+        let mut approvers = signers(2);
+        let mut op = new_op(&approvers, ApprovalDisposition::APPROVE, 2, &params());
+
+        assert_eq!(
+            record(&mut op, &mut approvers[1], ApprovalDisposition::NONE),
+            Err(WalletError::InvalidDisposition.into())
+        );
+        assert_eq!(
+            record(
+                &mut op,
+                &mut AccountInfoFixture::new(approvers[1].key()),
+                ApprovalDisposition::APPROVE
+            ),
+            Err(WalletError::InvalidSignature.into())
+        );
+        assert_eq!(
+            record(
+                &mut op,
+                &mut AccountInfoFixture::unique().signer(),
+                ApprovalDisposition::APPROVE
+            ),
+            Err(WalletError::InvalidApprover.into())
+        );
+        assert_eq!(
+            record(&mut op, &mut approvers[0], ApprovalDisposition::DENY),
+            Err(WalletError::InvalidDisposition.into())
+        );
+        // repeating the same disposition is harmless
+        assert_eq!(
+            record(&mut op, &mut approvers[0], ApprovalDisposition::APPROVE),
+            Ok(())
+        );
+        assert_eq!(op.operation_disposition(), OperationDisposition::NONE);
+    }
+
+    #[test]
+    fn test_disposition_reason_is_recorded() {
+        let mut approvers = signers(3);
+        let mut op = new_op(&approvers, ApprovalDisposition::APPROVE, 3, &params());
+        let reason = DispositionReason {
+            code: 2,
+            hash: Some(Hash::new_from_array([7; HASH_LEN])),
+        };
+        op.validate_and_record_approval_disposition(
+            &approvers[1].account_info(),
+            ApprovalDisposition::DENY,
+            Some(reason),
+            &clock_at(STARTED_AT + 1),
+        )
+        .unwrap();
+        // resubmitting the disposition leaves its reason as it was
+        op.validate_and_record_approval_disposition(
+            &approvers[1].account_info(),
+            ApprovalDisposition::DENY,
+            None,
+            &clock_at(STARTED_AT + 1),
+        )
+        .unwrap();
+        let code_only = DispositionReason {
+            code: 5,
+            hash: None,
+        };
+        op.validate_and_record_approval_disposition(
+            &approvers[2].account_info(),
+            ApprovalDisposition::APPROVE,
+            Some(code_only),
+            &clock_at(STARTED_AT + 1),
+        )
+        .unwrap();
+
+        let mut buffer = vec![0; MultisigOp::LEN];
+        op.pack_into_slice(&mut buffer);
+        let unpacked = MultisigOp::unpack(&buffer).unwrap();
+        let reason_of = |approver: &AccountInfoFixture| {
+            unpacked
+                .disposition_records
+                .iter()
+                .find(|record| record.approver == approver.key())
+                .unwrap()
+                .reason
+        };
+        assert_eq!(reason_of(&approvers[0]), None);
+        assert_eq!(reason_of(&approvers[1]), Some(reason));
+        assert_eq!(reason_of(&approvers[2]), Some(code_only));
+    }
+
+    #[test]
+    fn test_update_operation_disposition_expires() {
+        let approvers = signers(2);
+        let mut op = new_op(&approvers, ApprovalDisposition::APPROVE, 2, &params());
+        assert_eq!(
+            op.update_operation_disposition(&clock_at(EXPIRES_AT)),
+            Ok(OperationDisposition::NONE)
+        );
+        assert_eq!(
+            op.update_operation_disposition(&clock_at(EXPIRES_AT + 1)),
+            Ok(OperationDisposition::EXPIRED)
+        );
+    }
+
+    #[test]
+    fn test_approved() {
+        let params = params();
+        let mut approvers = signers(2);
+        let mut op = new_op(&approvers, ApprovalDisposition::APPROVE, 2, &params);
+        let expected_hash = params.hash(&op);
+        let clock = clock_at(STARTED_AT + 1);
+
+        assert_eq!(
+            op.approved(expected_hash, &clock, None),
+            Err(WalletError::TransferDispositionNotFinal.into())
+        );
+        assert_eq!(
+            op.approved(Hash::new_unique(), &clock, None),
+            Err(WalletError::InvalidSignature.into())
+        );
+
+        record(&mut op, &mut approvers[1], ApprovalDisposition::APPROVE).unwrap();
+        assert_eq!(op.approved(expected_hash, &clock, None), Ok(true));
+        assert_eq!(
+            op.approved(expected_hash, &clock, Some(&Hash::new_unique())),
+            Err(WalletError::InvalidSignature.into())
+        );
+        // an approved operation that was not finalized in time is no longer approved
+        assert_eq!(
+            op.approved(expected_hash, &clock_at(EXPIRES_AT + 1), None),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn test_finalize_delay() {
+        const DELAY: i64 = 500;
+        let params = params();
+        let mut approvers = signers(2);
+        let mut op = new_op(&approvers, ApprovalDisposition::APPROVE, 2, &params);
+        op.set_finalize_delay(Duration::from_secs(DELAY as u64));
+        let expected_hash = params.hash(&op);
+        assert_eq!(op.expires_at, EXPIRES_AT);
+        assert_eq!(
+            op.cancel(&clock_at(STARTED_AT + 1)),
+            Err(WalletError::OperationNotCancellable.into())
+        );
+
+        // the delay runs from the approval, which also pushes back the expiry
+        record(&mut op, &mut approvers[1], ApprovalDisposition::APPROVE).unwrap();
+        assert_eq!(op.approved_at, STARTED_AT + 1);
+        assert_eq!(op.expires_at, EXPIRES_AT + DELAY);
+        assert_eq!(
+            op.approved(expected_hash, &clock_at(STARTED_AT + DELAY), None),
+            Err(WalletError::FinalizeDelayNotElapsed.into())
+        );
+        assert_eq!(
+            op.approved(expected_hash, &clock_at(STARTED_AT + 1 + DELAY), None),
+            Ok(true)
+        );
+        assert_eq!(
+            op.approved(expected_hash, &clock_at(EXPIRES_AT + DELAY), None),
+            Ok(true)
+        );
+
+        // it can be cancelled until the delay has elapsed
+        assert_eq!(
+            op.cancel(&clock_at(STARTED_AT + 1 + DELAY)),
+            Err(WalletError::OperationNotCancellable.into())
+        );
+        op.cancel(&clock_at(STARTED_AT + DELAY)).unwrap();
+        assert_eq!(
+            op.approved(expected_hash, &clock_at(STARTED_AT + 1 + DELAY), None),
+            Ok(false)
+        );
+
+        // an op approved by its initiator alone is delayed from the start
+        let mut op = new_op(&signers(1), ApprovalDisposition::APPROVE, 1, &params);
+        op.set_finalize_delay(Duration::from_secs(DELAY as u64));
+        assert_eq!(op.approved_at, STARTED_AT);
+        assert_eq!(op.expires_at, EXPIRES_AT + DELAY);
+
+        // a delay too long to represent saturates instead of wrapping to a past timestamp
+        let mut op = new_op(&signers(1), ApprovalDisposition::APPROVE, 1, &params);
+        op.set_finalize_delay(Duration::from_secs(u64::MAX));
+        assert_eq!(op.expires_at, i64::MAX);
+        assert_eq!(
+            op.approved(params.hash(&op), &clock_at(i64::MAX - 1), None),
+            Err(WalletError::FinalizeDelayNotElapsed.into())
+        );
+    }
+
+    #[test]
+    fn test_zero_approvals_required() {
+        let params = params();
+        for initiator_disposition in [ApprovalDisposition::NONE, ApprovalDisposition::APPROVE] {
+            let mut approvers = signers(2);
+            let mut op = new_op(&approvers, initiator_disposition, 0, &params);
+            assert_eq!(op.operation_disposition(), OperationDisposition::APPROVED);
+            assert_eq!(op.approved_at, STARTED_AT);
+            assert_eq!(
+                op.approved(params.hash(&op), &clock_at(STARTED_AT), None),
+                Ok(true)
+            );
+
+            // later dispositions are still recorded, but don't change the outcome
+            record(&mut op, &mut approvers[1], ApprovalDisposition::DENY).unwrap();
+            assert_eq!(op.operation_disposition(), OperationDisposition::APPROVED);
+        }
+
+        // a finalize delay still holds it back
+        let mut op = new_op(&signers(1), ApprovalDisposition::NONE, 0, &params);
+        op.set_finalize_delay(Duration::from_secs(10));
+        assert_eq!(
+            op.approved(params.hash(&op), &clock_at(STARTED_AT), None),
+            Err(WalletError::FinalizeDelayNotElapsed.into())
+        );
+        assert_eq!(
+            op.approved(params.hash(&op), &clock_at(STARTED_AT + 10), None),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn test_is_idle() {
+        let params = params();
+        let mut approvers = signers(3);
+        let mut op = new_op(&approvers, ApprovalDisposition::APPROVE, 3, &params);
+        op.record_activity(100);
+        let clock = |slot: u64| Clock {
+            slot,
+            ..clock_at(STARTED_AT + 1)
+        };
+        assert!(!op.is_idle(50, &clock(149)));
+        assert!(op.is_idle(50, &clock(150)));
+        let mut pending = vec![approvers[1].key(), approvers[2].key()];
+        pending.sort();
+        assert_eq!(op.pending_approvers(), pending);
+
+        // a disposition restarts the interval
+        op.validate_and_record_approval_disposition(
+            &approvers[1].account_info(),
+            ApprovalDisposition::APPROVE,
+            None,
+            &clock(150),
+        )
+        .unwrap();
+        assert_eq!(op.last_activity_slot, 150);
+        assert!(!op.is_idle(50, &clock(150)));
+        assert_eq!(op.pending_approvers(), vec![approvers[2].key()]);
+
+        // and an expired or decided op is past reminding anyone
+        assert!(!op.is_idle(
+            50,
+            &Clock {
+                slot: 200,
+                ..clock_at(EXPIRES_AT)
+            }
+        ));
+        record(&mut op, &mut approvers[2], ApprovalDisposition::APPROVE).unwrap();
+        assert!(!op.is_idle(0, &clock(200)));
+    }
+
+    #[test]
+    fn test_approve_by_initiator() {
+        let params = params();
+        let mut op = new_op(&signers(2), ApprovalDisposition::APPROVE, 2, &params);
+        assert_eq!(op.approve_by_initiator(&clock_at(STARTED_AT + 1)), Ok(true));
+        assert_eq!(
+            op.approved(params.hash(&op), &clock_at(STARTED_AT + 1), None),
+            Ok(true)
+        );
+        // once approved there is nothing left to approve
+        assert_eq!(
+            op.approve_by_initiator(&clock_at(STARTED_AT + 1)),
+            Ok(false)
+        );
+
+        // not without the initiator's own approval
+        let mut op = new_op(&signers(2), ApprovalDisposition::NONE, 2, &params);
+        assert_eq!(
+            op.approve_by_initiator(&clock_at(STARTED_AT + 1)),
+            Ok(false)
+        );
+        assert_eq!(op.operation_disposition(), OperationDisposition::NONE);
+
+        // nor once expired or while a finalize delay applies
+        let mut op = new_op(&signers(2), ApprovalDisposition::APPROVE, 2, &params);
+        assert_eq!(
+            op.approve_by_initiator(&clock_at(EXPIRES_AT + 1)),
+            Ok(false)
+        );
+        op.set_finalize_delay(Duration::from_secs(10));
+        assert_eq!(
+            op.approve_by_initiator(&clock_at(STARTED_AT + 1)),
+            Ok(false)
+        );
+        assert_eq!(op.operation_disposition(), OperationDisposition::NONE);
+    }
+
+    #[test]
+    fn test_params_hash_depends_on_common_data() {
+        let params = params();
+        let approvers = signers(1);
+        let op = new_op(&approvers, ApprovalDisposition::APPROVE, 1, &params);
+        let mut other_op = new_op(&approvers, ApprovalDisposition::APPROVE, 1, &params);
+        other_op.rent_return = op.rent_return;
+        assert_eq!(params.hash(&op), params.hash(&other_op));
+        other_op.fee_amount = 1;
+        assert_ne!(params.hash(&op), params.hash(&other_op));
+        other_op.fee_amount = op.fee_amount;
+        other_op.op_nonce = 1;
+        assert_ne!(params.hash(&op), params.hash(&other_op));
+    }
+
+    #[test]
+    fn test_pack_unpack_round_trip() {
+        let mut approvers = signers(3);
+        let mut op = new_op(&approvers, ApprovalDisposition::APPROVE, 2, &params());
+        op.set_finalize_delay(Duration::from_secs(300));
+        op.wallet = Pubkey::new_unique();
+        op.op_nonce = 7;
+        record(&mut op, &mut approvers[2], ApprovalDisposition::DENY).unwrap();
+
+        let mut buffer = vec![0; MultisigOp::LEN];
+        op.pack_into_slice(&mut buffer);
+        let unpacked = MultisigOp::unpack(&buffer).unwrap();
+        assert_eq!(unpacked.disposition_records, op.disposition_records);
+        assert_eq!(unpacked.params_hash, op.params_hash);
+        assert_eq!(unpacked.state, op.state);
+        assert_eq!(unpacked.expires_at, EXPIRES_AT);
+        assert_eq!(unpacked.finalize_delay, op.finalize_delay);
+        assert_eq!(unpacked.approved_at, op.approved_at);
+        assert_eq!(unpacked.wallet, op.wallet);
+        assert_eq!(unpacked.op_nonce, op.op_nonce);
+    }
+
+    #[test]
+    fn test_pack_unpack_sized() {
+        let mut approvers = signers(3);
+        let mut op = new_op(&approvers, ApprovalDisposition::APPROVE, 2, &params());
+        record(&mut op, &mut approvers[2], ApprovalDisposition::DENY).unwrap();
+
+        assert_eq!(MultisigOp::records_capacity(MultisigOp::LEN), Some(24));
+        assert_eq!(
+            MultisigOp::records_capacity(MultisigOp::len_for(3)),
+            Some(3)
+        );
+        assert_eq!(
+            MultisigOp::records_capacity(MultisigOp::len_for(3) + 1),
+            None
+        );
+        assert_eq!(
+            MultisigOp::records_capacity(MultisigOp::FIXED_LEN - 1),
+            None
+        );
+        assert_eq!(MultisigOp::records_capacity(MultisigOp::len_for(25)), None);
+
+        let disposition_records = op.disposition_records.clone();
+        let params_hash = op.params_hash;
+        let mut buffer = vec![0; MultisigOp::len_for(3)];
+        MultisigOp::pack_sized(op, &mut buffer).unwrap();
+        let unpacked = MultisigOp::unpack_sized(&buffer).unwrap();
+        assert_eq!(unpacked.disposition_records, disposition_records);
+        assert_eq!(unpacked.params_hash, params_hash);
+        assert_eq!(unpacked.expires_at, EXPIRES_AT);
+        assert_eq!(
+            MultisigOp::unpack_sized(&vec![0; MultisigOp::len_for(3)]).err(),
+            Some(ProgramError::UninitializedAccount)
+        );
+        assert_eq!(
+            MultisigOp::unpack_sized(&buffer[..MultisigOp::len_for(2)]).err(),
+            Some(ProgramError::InvalidAccountData)
+        );
+
+        // the approvers have to fit
+        assert_eq!(
+            MultisigOp::pack_sized(unpacked, &mut vec![0; MultisigOp::len_for(2)]),
+            Err(WalletError::MultisigOpAccountTooSmall.into())
+        );
+
+        // a full size account keeps the layout of a packed op
+        let mut sized = vec![0; MultisigOp::LEN];
+        MultisigOp::pack_sized(MultisigOp::unpack_sized(&buffer).unwrap(), &mut sized).unwrap();
+        let mut packed = vec![0; MultisigOp::LEN];
+        MultisigOp::pack(MultisigOp::unpack_sized(&buffer).unwrap(), &mut packed).unwrap();
+        assert_eq!(sized, packed);
+    }
+
+    #[test]
+    fn test_record_bundle_approvals() {
+        let mut approvers = signers(4);
+        let mut op = new_op(&approvers, ApprovalDisposition::NONE, 2, &params());
+        record(&mut op, &mut approvers[1], ApprovalDisposition::DENY).unwrap();
+        let clock = clock_at(STARTED_AT + 1);
+
+        // a bundle approval doesn't override the approver's own disposition
+        assert_eq!(
+            op.record_bundle_approvals(&[approvers[1].key(), Pubkey::new_unique()], &clock),
+            Ok(OperationDisposition::NONE)
+        );
+        assert_eq!(op.get_disposition_count(ApprovalDisposition::APPROVE), 0);
+
+        assert_eq!(
+            op.record_bundle_approvals(
+                &[approvers[0].key(), approvers[2].key(), approvers[3].key()],
+                &clock
+            ),
+            Ok(OperationDisposition::APPROVED)
+        );
+        assert_eq!(op.get_disposition_count(ApprovalDisposition::APPROVE), 2);
+    }
+
+    #[test]
+    fn test_disposition_records_are_ordered_and_deduplicated() {
+        let mut approvers = signers(3);
+        approvers.reverse();
+        let mut op = MultisigOp::unpack_unchecked(&vec![0; MultisigOp::LEN]).unwrap();
+        op.init(
+            approvers
+                .iter()
+                .chain(approvers.iter())
+                .map(|it| it.key())
+                .collect(),
+            (approvers[0].key(), ApprovalDisposition::APPROVE),
+            2,
+            STARTED_AT,
+            EXPIRES_AT,
+            Some(params()),
+            Pubkey::new_unique(),
+            0,
+            None,
+            0,
+        )
+        .unwrap();
+
+        let mut expected: Vec<Pubkey> = approvers.iter().map(|it| it.key()).collect();
+        expected.sort();
+        assert_eq!(
+            op.disposition_records
+                .iter()
+                .map(|r| r.approver)
+                .collect::<Vec<_>>(),
+            expected
+        );
+        assert!(op.is_approver(&approvers[1].key()));
+        assert!(!op.is_approver(&Pubkey::new_unique()));
+
+        record(&mut op, &mut approvers[2], ApprovalDisposition::APPROVE).unwrap();
+        assert_eq!(op.operation_disposition(), OperationDisposition::APPROVED);
+
+        // records packed out of order are put back in order
+        op.disposition_records.reverse();
+        let mut buffer = vec![0; MultisigOp::LEN];
+        op.pack_into_slice(&mut buffer);
+        let unpacked = MultisigOp::unpack(&buffer).unwrap();
+        assert_eq!(
+            unpacked
+                .disposition_records
+                .iter()
+                .map(|r| r.approver)
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_migrate_approver() {
+        let approvers = signers(3);
+        let mut op = MultisigOp::unpack_unchecked(&vec![0; MultisigOp::LEN]).unwrap();
+        op.init(
+            approvers.iter().map(|it| it.key()).collect(),
+            (approvers[0].key(), ApprovalDisposition::APPROVE),
+            3,
+            STARTED_AT,
+            EXPIRES_AT,
+            Some(params()),
+            Pubkey::new_unique(),
+            0,
+            None,
+            0,
+        )
+        .unwrap();
+
+        let successor = Pubkey::new_unique();
+        assert!(op.migrate_approver(&approvers[0].key(), &successor));
+        assert!(!op.is_approver(&approvers[0].key()));
+        assert_eq!(
+            op.disposition_records
+                .iter()
+                .find(|r| r.approver == successor)
+                .unwrap()
+                .disposition,
+            ApprovalDisposition::APPROVE
+        );
+        assert!(op
+            .disposition_records
+            .windows(2)
+            .all(|pair| pair[0].approver < pair[1].approver));
+
+        // a successor that is an approver already keeps its own record
+        assert!(!op.migrate_approver(&approvers[1].key(), &approvers[2].key()));
+        assert!(!op.migrate_approver(&Pubkey::new_unique(), &Pubkey::new_unique()));
+    }
+
+    #[test]
+    fn test_set_params_hash_approves_for_initiator() {
+        for approvals_required in 1..=2 {
+            let approvers = signers(2);
+            let mut op = new_op(
+                &approvers,
+                ApprovalDisposition::NONE,
+                approvals_required,
+                &params(),
+            );
+            op.params_hash = None;
+            op.state = MultisigOpState::PendingInstructions;
+
+            let params_hash = Hash::new_unique();
+            op.set_params_hash(params_hash, &approvers[0].key(), STARTED_AT)
+                .unwrap();
+            assert_eq!(op.params_hash, Some(params_hash));
+            assert_eq!(
+                op.disposition_records[0].disposition,
+                ApprovalDisposition::APPROVE
+            );
+            assert_eq!(
+                op.operation_disposition(),
+                if approvals_required == 1 {
+                    OperationDisposition::APPROVED
+                } else {
+                    OperationDisposition::NONE
+                }
+            );
+        }
+
+        // an initiator that isn't an approver, such as an assistant, approves nothing
+        let approvers = signers(2);
+        let mut op = new_op(&approvers, ApprovalDisposition::NONE, 1, &params());
+        op.set_params_hash(Hash::new_unique(), &Pubkey::new_unique(), STARTED_AT)
+            .unwrap();
+        assert_eq!(op.get_disposition_count(ApprovalDisposition::APPROVE), 0);
+        assert_eq!(op.operation_disposition(), OperationDisposition::NONE);
+    }
+
+    #[test]
+    fn test_stake_weight_quorum() {
+        let governance = StakeWeightGovernance {
+            mint: Pubkey::new_unique(),
+            approval_threshold_bps: 5_000,
+        };
+        let mut holders = signers(4);
+        let initiator = holders[0].key();
+        let governed_op = |governance: Option<StakeWeightGovernance>| {
+            let mut op = MultisigOp::unpack_unchecked(&vec![0; MultisigOp::LEN]).unwrap();
+            op.init(
+                vec![],
+                (initiator, ApprovalDisposition::APPROVE),
+                1,
+                STARTED_AT,
+                EXPIRES_AT,
+                Some(params()),
+                Pubkey::new_unique(),
+                0,
+                None,
+                0,
+            )
+            .unwrap();
+            op.stake_weight_governance = governance;
+            op
+        };
+        let weights = |holders: &Vec<AccountInfoFixture>| {
+            holders
+                .iter()
+                .zip([30, 15, 25, 0])
+                .map(|(holder, weight)| (holder.key(), weight))
+                .collect::<Vec<_>>()
+        };
+
+        // the initiator's stake approves the op, and the op is decided by weight, not by count
+        let mut op = governed_op(Some(governance));
+        assert_eq!(op.operation_disposition(), OperationDisposition::NONE);
+        op.record_stake_weights(weights(&holders), 100, STARTED_AT)
+            .unwrap();
+        assert_eq!(op.required_stake_weight, 50);
+        assert_eq!(op.disposition_records.len(), 3);
+        assert_eq!(
+            op.get_disposition_stake_weight(ApprovalDisposition::APPROVE),
+            30
+        );
+        record(&mut op, &mut holders[1], ApprovalDisposition::APPROVE).unwrap();
+        assert_eq!(op.operation_disposition(), OperationDisposition::NONE);
+        record(&mut op, &mut holders[2], ApprovalDisposition::APPROVE).unwrap();
+        assert_eq!(op.operation_disposition(), OperationDisposition::APPROVED);
+
+        // holders with no stake aren't approvers
+        let mut op = governed_op(Some(governance));
+        op.record_stake_weights(weights(&holders), 100, STARTED_AT)
+            .unwrap();
+        assert_eq!(
+            record(&mut op, &mut holders[3], ApprovalDisposition::APPROVE),
+            Err(WalletError::InvalidApprover.into())
+        );
+
+        // denials are weighed the same way
+        let mut op = governed_op(Some(governance));
+        op.params_hash = None;
+        op.record_stake_weights(weights(&holders), 100, STARTED_AT)
+            .unwrap();
+        assert_eq!(
+            op.get_disposition_stake_weight(ApprovalDisposition::APPROVE),
+            0
+        );
+        record(&mut op, &mut holders[0], ApprovalDisposition::DENY).unwrap();
+        record(&mut op, &mut holders[1], ApprovalDisposition::DENY).unwrap();
+        assert_eq!(op.operation_disposition(), OperationDisposition::NONE);
+        record(&mut op, &mut holders[2], ApprovalDisposition::DENY).unwrap();
+        assert_eq!(op.operation_disposition(), OperationDisposition::DENIED);
+
+        // a stake that covers the threshold approves the op as it is initiated
+        let mut op = governed_op(Some(governance));
+        op.record_stake_weights(weights(&holders), 60, STARTED_AT)
+            .unwrap();
+        assert_eq!(op.operation_disposition(), OperationDisposition::APPROVED);
+
+        // weights are only taken once, and only by ops of governed wallets
+        let mut op = governed_op(Some(governance));
+        op.record_stake_weights(weights(&holders), 100, STARTED_AT)
+            .unwrap();
+        assert_eq!(
+            op.record_stake_weights(weights(&holders), 100, STARTED_AT),
+            Err(WalletError::StakeWeightsNotExpected.into())
+        );
+        let mut op = governed_op(None);
+        assert_eq!(
+            op.record_stake_weights(weights(&holders), 100, STARTED_AT),
+            Err(WalletError::StakeWeightsNotExpected.into())
+        );
+
+        // each holder's stake is counted once
+        let mut op = governed_op(Some(governance));
+        let mut duplicated = weights(&holders);
+        duplicated.push(duplicated[1]);
+        assert_eq!(
+            op.record_stake_weights(duplicated, 100, STARTED_AT),
+            Err(WalletError::InvalidStakeAccount.into())
+        );
+    }
+
+    #[test]
+    fn test_state_transitions() {
+        use MultisigOpState::*;
+        let states = [
+            Uninitialized,
+            PendingInstructions,
+            Open,
+            Approved,
+            Denied,
+            Expired,
+            Executed,
+            Quarantined,
+        ];
+        let allowed = [
+            (Uninitialized, PendingInstructions),
+            (Uninitialized, Open),
+            (Uninitialized, Approved),
+            (PendingInstructions, Open),
+            (PendingInstructions, Approved),
+            (PendingInstructions, Denied),
+            (PendingInstructions, Expired),
+            (Open, Approved),
+            (Open, Denied),
+            (Open, Expired),
+            (Open, Quarantined),
+            (Approved, Denied),
+            (Approved, Expired),
+            (Approved, Executed),
+            (Approved, Quarantined),
+            (Denied, Executed),
+            (Expired, Executed),
+            (Quarantined, Open),
+            (Quarantined, Denied),
+            (Quarantined, Expired),
+        ];
+        for from in states {
+            for to in states {
+                assert_eq!(
+                    from.can_transition_to(to),
+                    allowed.contains(&(from, to)),
+                    "{:?} -> {:?}",
+                    from,
+                    to
+                );
+            }
+        }
+
+        let mut op = new_op(&signers(2), ApprovalDisposition::APPROVE, 2, &params());
+        assert_eq!(op.state, Open);
+        assert_eq!(
+            op.transition(Executed),
+            Err(WalletError::InvalidStateTransition.into())
+        );
+        assert_eq!(op.state, Open);
+        op.transition(Expired).unwrap();
+        assert_eq!(op.operation_disposition(), OperationDisposition::EXPIRED);
+        // a decided op takes no more dispositions
+        assert_eq!(
+            op.update_operation_disposition(&clock_at(STARTED_AT + 1)),
+            Ok(OperationDisposition::EXPIRED)
+        );
+    }
+
+    #[test]
+    fn test_init_state() {
+        let approvers = signers(2);
+        let mut op = MultisigOp::unpack_unchecked(&vec![0; MultisigOp::LEN]).unwrap();
+        assert_eq!(op.state, MultisigOpState::Uninitialized);
+        op.init(
+            approvers.iter().map(|it| it.key()).collect(),
+            (approvers[0].key(), ApprovalDisposition::APPROVE),
+            2,
+            STARTED_AT,
+            EXPIRES_AT,
+            None,
+            Pubkey::new_unique(),
+            0,
+            None,
+            0,
+        )
+        .unwrap();
+        assert_eq!(op.state, MultisigOpState::PendingInstructions);
+        assert_eq!(op.operation_disposition(), OperationDisposition::NONE);
+
+        // the state survives packing, as does the state of ops packed before it was explicit
+        let mut buffer = vec![0; MultisigOp::LEN];
+        op.pack_into_slice(&mut buffer);
+        assert_eq!(
+            MultisigOp::unpack(&buffer).unwrap().state,
+            MultisigOpState::PendingInstructions
+        );
+        op.state = MultisigOpState::Open;
+        op.pack_into_slice(&mut buffer);
+        assert_eq!(
+            MultisigOp::unpack(&buffer).unwrap().state,
+            MultisigOpState::PendingInstructions
+        );
+
+        let op = new_op(&approvers, ApprovalDisposition::APPROVE, 1, &params());
+        assert_eq!(op.state, MultisigOpState::Approved);
+    }
+
+    #[test]
+    fn test_expire() {
+        let approvers = signers(2);
+        let mut op = new_op(&approvers, ApprovalDisposition::APPROVE, 2, &params());
+        assert_eq!(
+            op.expire(&clock_at(EXPIRES_AT)),
+            Err(WalletError::OperationNotExpired.into())
+        );
+        assert_eq!(op.state, MultisigOpState::Open);
+        op.expire(&clock_at(EXPIRES_AT + 1)).unwrap();
+        assert_eq!(op.state, MultisigOpState::Expired);
+        op.expire(&clock_at(EXPIRES_AT + 1)).unwrap();
+
+        // an approved op that wasn't finalized in time expires too
+        let mut op = new_op(&approvers, ApprovalDisposition::APPROVE, 1, &params());
+        op.expire(&clock_at(EXPIRES_AT + 1)).unwrap();
+        assert_eq!(op.operation_disposition(), OperationDisposition::EXPIRED);
+
+        // but a denied op stays denied
+        let mut op = new_op(&approvers, ApprovalDisposition::APPROVE, 2, &params());
+        op.transition(MultisigOpState::Denied).unwrap();
+        assert_eq!(
+            op.expire(&clock_at(EXPIRES_AT + 1)),
+            Err(WalletError::InvalidStateTransition.into())
+        );
+    }
+
+    #[test]
+    fn test_quarantine_and_release() {
+        let approvers = signers(2);
+        let mut op = new_op(&approvers, ApprovalDisposition::APPROVE, 1, &params());
+        let approved_at = op.approved_at;
+        op.quarantine().unwrap();
+        assert_eq!(op.state, MultisigOpState::Quarantined);
+        assert_eq!(op.operation_disposition(), OperationDisposition::NONE);
+
+        // released on the dispositions it kept, as approved as it was
+        op.release(&clock_at(STARTED_AT + 100)).unwrap();
+        assert_eq!(op.state, MultisigOpState::Approved);
+        assert_eq!(op.approved_at, approved_at);
+
+        // one released past its expiry expires
+        op.quarantine().unwrap();
+        op.release(&clock_at(EXPIRES_AT + 1)).unwrap();
+        assert_eq!(op.state, MultisigOpState::Expired);
+
+        // but a denied op can't be
+        let mut op = new_op(&approvers, ApprovalDisposition::APPROVE, 2, &params());
+        op.transition(MultisigOpState::Denied).unwrap();
+        assert_eq!(
+            op.quarantine(),
+            Err(WalletError::InvalidStateTransition.into())
+        );
+    }
+
+    #[test]
+    fn test_expire_by_slot() {
+        let clock_at_slot = |unix_timestamp: i64, slot: u64| Clock {
+            slot,
+            ..clock_at(unix_timestamp)
+        };
+        let mut op = new_op(&signers(2), ApprovalDisposition::APPROVE, 2, &params());
+        assert_eq!(op.expires_at_slot, None);
+        assert!(!op.is_expired(&clock_at_slot(EXPIRES_AT, u64::MAX)));
+
+        // 10 seconds at the nominal slot duration is 25 slots
+        op.set_expiry_slot(100, Duration::from_secs(10));
+        assert_eq!(op.expires_at_slot, Some(125));
+        let mut buffer = vec![0; MultisigOp::LEN];
+        op.pack_into_slice(&mut buffer);
+        assert_eq!(
+            MultisigOp::unpack_from_slice(&buffer)
+                .unwrap()
+                .expires_at_slot,
+            Some(125)
+        );
+
+        // the slot bound expires the op while the clock says it is still open
+        assert!(!op.is_expired(&clock_at_slot(STARTED_AT, 125)));
+        assert!(op.is_expired(&clock_at_slot(STARTED_AT, 126)));
+        assert_eq!(
+            op.effective_expires_at(&clock_at_slot(STARTED_AT, 100)),
+            STARTED_AT + 10
+        );
+        assert_eq!(
+            op.effective_expires_at(&clock_at_slot(EXPIRES_AT - 5, 100)),
+            EXPIRES_AT
+        );
+        assert_eq!(
+            op.expire(&clock_at_slot(STARTED_AT, 125)),
+            Err(WalletError::OperationNotExpired.into())
+        );
+        op.expire(&clock_at_slot(STARTED_AT, 126)).unwrap();
+        assert_eq!(op.state, MultisigOpState::Expired);
+    }
+
+    #[test]
+    fn test_transfer_params_hash() {
+        let approvers = signers(2);
+        let transfer = |memo_hash: Option<Hash>, allow_destination_ata_creation: bool| {
+            MultisigOpParams::Transfer {
+                wallet_address: Pubkey::new_from_array([1; 32]),
+                account_guid_hash: BalanceAccountGuidHash::new(&[2; 32]),
+                destination: Pubkey::new_from_array([3; 32]),
+                amount: 100,
+                token_mint: Pubkey::new_from_array([4; 32]),
+                destination_acknowledgement_required: false,
+                deduct_fee_from_amount: false,
+                memo_hash,
+                allow_destination_ata_creation,
+            }
+        };
+        let op = new_op(
+            &approvers,
+            ApprovalDisposition::NONE,
+            2,
+            &transfer(None, false),
+        );
+        let memo_hash = Some(Hash::new_from_array([5; 32]));
+
+        // allowing the destination's token account to be created has to be approved
+        let hashes = vec![
+            transfer(None, false).hash(&op),
+            transfer(None, true).hash(&op),
+            transfer(memo_hash, false).hash(&op),
+            transfer(memo_hash, true).hash(&op),
+        ];
+        for (i, hash) in hashes.iter().enumerate() {
+            assert!(!hashes[i + 1..].contains(hash));
+        }
+    }
 }
@@ -0,0 +1,230 @@
+use crate::constants::{HASH_LEN, PUBKEY_BYTES};
+use crate::error::WalletError;
+use crate::model::wallet::Wallet;
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::{hash, Hash};
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{IsInitialized, Pack, Sealed};
+use solana_program::pubkey::Pubkey;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct OpBundleMember {
+    pub multisig_op: Pubkey,
+    pub params_hash: Hash,
+}
+
+impl OpBundleMember {
+    pub const LEN: usize = PUBKEY_BYTES + HASH_LEN;
+}
+
+/// A set of related multisig ops started by the same initiator, which approvers approve all at
+/// once by approving the bundle hash. The approvals are then applied to each member op, which
+/// counts them like approvals given to the op itself. It lives in an account created by the
+/// client and owned by the program.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct OpBundle {
+    pub is_initialized: bool,
+    pub wallet_address: Pubkey,
+    pub rent_return: Pubkey,
+    /// The earliest expiry of the member ops, after which the bundle can't be approved.
+    pub expires_at: i64,
+    pub members: Vec<OpBundleMember>,
+    /// The signers who approved the bundle, ordered by pubkey.
+    pub approvers: Vec<Pubkey>,
+}
+
+impl OpBundle {
+    pub const MAX_MEMBERS: usize = 8;
+
+    /// The hash approvers sign, committing them to the params of every member op.
+    pub fn bundle_hash(&self) -> Hash {
+        let mut bytes: Vec<u8> =
+            Vec::with_capacity(PUBKEY_BYTES + self.members.len() * OpBundleMember::LEN);
+        bytes.extend_from_slice(self.wallet_address.as_ref());
+        for member in &self.members {
+            bytes.extend_from_slice(member.multisig_op.as_ref());
+            bytes.extend_from_slice(member.params_hash.as_ref());
+        }
+        hash(&bytes)
+    }
+
+    pub fn get_member(&self, multisig_op: &Pubkey) -> Option<&OpBundleMember> {
+        self.members
+            .iter()
+            .find(|member| member.multisig_op == *multisig_op)
+    }
+
+    pub fn record_approval(&mut self, approver: Pubkey, now: i64) -> ProgramResult {
+        if now > self.expires_at {
+            msg!("Op bundle expired at {}", self.expires_at);
+            return Err(WalletError::OpBundleExpired.into());
+        }
+        if let Err(index) = self.approvers.binary_search(&approver) {
+            self.approvers.insert(index, approver);
+        }
+        Ok(())
+    }
+}
+
+impl Sealed for OpBundle {}
+
+impl IsInitialized for OpBundle {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for OpBundle {
+    const LEN: usize = 1 + // is_initialized
+        PUBKEY_BYTES + // wallet_address
+        PUBKEY_BYTES + // rent_return
+        8 + // expires_at
+        1 + // members count
+        OpBundleMember::LEN * OpBundle::MAX_MEMBERS + // members
+        1 + // approvers count
+        PUBKEY_BYTES * Wallet::MAX_SIGNERS; // approvers
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, OpBundle::LEN];
+        let (
+            is_initialized_dst,
+            wallet_address_dst,
+            rent_return_dst,
+            expires_at_dst,
+            members_count_dst,
+            members_dst,
+            approvers_count_dst,
+            approvers_dst,
+        ) = mut_array_refs![
+            dst,
+            1,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            8,
+            1,
+            OpBundleMember::LEN * OpBundle::MAX_MEMBERS,
+            1,
+            PUBKEY_BYTES * Wallet::MAX_SIGNERS
+        ];
+
+        is_initialized_dst[0] = self.is_initialized as u8;
+        wallet_address_dst.copy_from_slice(self.wallet_address.as_ref());
+        rent_return_dst.copy_from_slice(self.rent_return.as_ref());
+        *expires_at_dst = self.expires_at.to_le_bytes();
+
+        members_count_dst[0] = self.members.len() as u8;
+        members_dst.fill(0);
+        members_dst
+            .chunks_exact_mut(OpBundleMember::LEN)
+            .zip(self.members.iter())
+            .for_each(|(chunk, member)| {
+                chunk[..PUBKEY_BYTES].copy_from_slice(member.multisig_op.as_ref());
+                chunk[PUBKEY_BYTES..].copy_from_slice(member.params_hash.as_ref());
+            });
+
+        approvers_count_dst[0] = self.approvers.len() as u8;
+        approvers_dst.fill(0);
+        approvers_dst
+            .chunks_exact_mut(PUBKEY_BYTES)
+            .zip(self.approvers.iter())
+            .for_each(|(chunk, approver)| chunk.copy_from_slice(approver.as_ref()));
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, OpBundle::LEN];
+        let (
+            is_initialized,
+            wallet_address,
+            rent_return,
+            expires_at,
+            members_count,
+            members,
+            approvers_count,
+            approvers,
+        ) = array_refs![
+            src,
+            1,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            8,
+            1,
+            OpBundleMember::LEN * OpBundle::MAX_MEMBERS,
+            1,
+            PUBKEY_BYTES * Wallet::MAX_SIGNERS
+        ];
+        let is_initialized = match is_initialized {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        let members_count = usize::from(members_count[0]);
+        let approvers_count = usize::from(approvers_count[0]);
+        if members_count > OpBundle::MAX_MEMBERS || approvers_count > Wallet::MAX_SIGNERS {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(OpBundle {
+            is_initialized,
+            wallet_address: Pubkey::new_from_array(*wallet_address),
+            rent_return: Pubkey::new_from_array(*rent_return),
+            expires_at: i64::from_le_bytes(*expires_at),
+            members: members
+                .chunks_exact(OpBundleMember::LEN)
+                .take(members_count)
+                .map(|chunk| OpBundleMember {
+                    multisig_op: Pubkey::new(&chunk[..PUBKEY_BYTES]),
+                    params_hash: Hash::new(&chunk[PUBKEY_BYTES..]),
+                })
+                .collect(),
+            approvers: approvers
+                .chunks_exact(PUBKEY_BYTES)
+                .take(approvers_count)
+                .map(Pubkey::new)
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_op_bundle() {
+        let mut bundle = OpBundle {
+            is_initialized: true,
+            wallet_address: Pubkey::new_unique(),
+            rent_return: Pubkey::new_unique(),
+            expires_at: 1000,
+            members: (0..OpBundle::MAX_MEMBERS)
+                .map(|_| OpBundleMember {
+                    multisig_op: Pubkey::new_unique(),
+                    params_hash: Hash::new_unique(),
+                })
+                .collect(),
+            approvers: vec![],
+        };
+        let bundle_hash = bundle.bundle_hash();
+
+        let approvers = [Pubkey::new_unique(), Pubkey::new_unique()];
+        bundle.record_approval(approvers[1], 1000).unwrap();
+        bundle.record_approval(approvers[0], 10).unwrap();
+        bundle.record_approval(approvers[1], 20).unwrap();
+        assert_eq!(bundle.approvers, approvers.to_vec());
+        assert_eq!(
+            bundle.record_approval(Pubkey::new_unique(), 1001),
+            Err(WalletError::OpBundleExpired.into())
+        );
+        // approvals don't change what the approvers signed
+        assert_eq!(bundle.bundle_hash(), bundle_hash);
+
+        let mut buf = vec![0; OpBundle::LEN];
+        bundle.pack_into_slice(&mut buf);
+        assert_eq!(OpBundle::unpack_from_slice(&buf), Ok(bundle.clone()));
+
+        bundle.members.swap(0, 1);
+        assert_ne!(bundle.bundle_hash(), bundle_hash);
+    }
+}
@@ -0,0 +1,183 @@
+use crate::constants::{HASH_LEN, PUBKEY_BYTES};
+use crate::error::WalletError;
+use crate::model::balance_account::BalanceAccountGuidHash;
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{IsInitialized, Pack, Sealed};
+use solana_program::pubkey::Pubkey;
+
+/// A standing allowance to pay a single destination out of a balance account. It is opened by
+/// a transfer multisig op, after which the wallet's assistant draws it down with channel
+/// payments, without further approvals, until the allowance is spent or the channel expires.
+/// It lives in an account created by the client and owned by the program.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct PaymentChannel {
+    pub is_initialized: bool,
+    pub wallet_address: Pubkey,
+    pub account_guid_hash: BalanceAccountGuidHash,
+    pub destination: Pubkey,
+    /// The default pubkey for SOL.
+    pub token_mint: Pubkey,
+    pub allowance: u64,
+    pub spent: u64,
+    /// Unix timestamp from which no more payments can be made.
+    pub expires_at: i64,
+}
+
+impl PaymentChannel {
+    pub fn remaining(&self) -> u64 {
+        self.allowance.saturating_sub(self.spent)
+    }
+
+    pub fn record_payment(&mut self, amount: u64, now: i64) -> ProgramResult {
+        if now >= self.expires_at {
+            msg!("Payment channel expired at {}", self.expires_at);
+            return Err(WalletError::PaymentChannelExpired.into());
+        }
+        if amount > self.remaining() {
+            msg!(
+                "Payment of {} exceeds the channel's remaining allowance of {}",
+                amount,
+                self.remaining()
+            );
+            return Err(WalletError::PaymentChannelAllowanceExceeded.into());
+        }
+        self.spent = self
+            .spent
+            .checked_add(amount)
+            .ok_or(WalletError::AmountOverflow)?;
+        Ok(())
+    }
+}
+
+impl Sealed for PaymentChannel {}
+
+impl IsInitialized for PaymentChannel {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for PaymentChannel {
+    const LEN: usize = 1 + // is_initialized
+        PUBKEY_BYTES + // wallet_address
+        HASH_LEN + // account_guid_hash
+        PUBKEY_BYTES + // destination
+        PUBKEY_BYTES + // token_mint
+        8 + // allowance
+        8 + // spent
+        8; // expires_at
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, PaymentChannel::LEN];
+        let (
+            is_initialized_dst,
+            wallet_address_dst,
+            account_guid_hash_dst,
+            destination_dst,
+            token_mint_dst,
+            allowance_dst,
+            spent_dst,
+            expires_at_dst,
+        ) = mut_array_refs![
+            dst,
+            1,
+            PUBKEY_BYTES,
+            HASH_LEN,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            8,
+            8,
+            8
+        ];
+
+        is_initialized_dst[0] = self.is_initialized as u8;
+        wallet_address_dst.copy_from_slice(self.wallet_address.as_ref());
+        account_guid_hash_dst.copy_from_slice(self.account_guid_hash.to_bytes());
+        destination_dst.copy_from_slice(self.destination.as_ref());
+        token_mint_dst.copy_from_slice(self.token_mint.as_ref());
+        *allowance_dst = self.allowance.to_le_bytes();
+        *spent_dst = self.spent.to_le_bytes();
+        *expires_at_dst = self.expires_at.to_le_bytes();
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, PaymentChannel::LEN];
+        let (
+            is_initialized_src,
+            wallet_address_src,
+            account_guid_hash_src,
+            destination_src,
+            token_mint_src,
+            allowance_src,
+            spent_src,
+            expires_at_src,
+        ) = array_refs![
+            src,
+            1,
+            PUBKEY_BYTES,
+            HASH_LEN,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            8,
+            8,
+            8
+        ];
+
+        let is_initialized = match is_initialized_src {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        Ok(PaymentChannel {
+            is_initialized,
+            wallet_address: Pubkey::new_from_array(*wallet_address_src),
+            account_guid_hash: BalanceAccountGuidHash::new(account_guid_hash_src),
+            destination: Pubkey::new_from_array(*destination_src),
+            token_mint: Pubkey::new_from_array(*token_mint_src),
+            allowance: u64::from_le_bytes(*allowance_src),
+            spent: u64::from_le_bytes(*spent_src),
+            expires_at: i64::from_le_bytes(*expires_at_src),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_payment() {
+        let mut channel = PaymentChannel {
+            is_initialized: true,
+            wallet_address: Pubkey::new_unique(),
+            account_guid_hash: BalanceAccountGuidHash::new(&[1; HASH_LEN]),
+            destination: Pubkey::new_unique(),
+            token_mint: Pubkey::default(),
+            allowance: 100,
+            spent: 0,
+            expires_at: 1000,
+        };
+
+        channel.record_payment(60, 10).unwrap();
+        assert_eq!(
+            channel.record_payment(41, 20),
+            Err(WalletError::PaymentChannelAllowanceExceeded.into())
+        );
+        channel.record_payment(40, 20).unwrap();
+        assert_eq!(channel.remaining(), 0);
+
+        channel.spent = 0;
+        assert_eq!(
+            channel.record_payment(1, 1000),
+            Err(WalletError::PaymentChannelExpired.into())
+        );
+
+        let mut buf = [0; PaymentChannel::LEN];
+        channel.pack_into_slice(&mut buf);
+        assert_eq!(PaymentChannel::unpack_from_slice(&buf), Ok(channel));
+    }
+}
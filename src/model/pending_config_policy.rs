@@ -0,0 +1,95 @@
+use crate::constants::HASH_LEN;
+use crate::instruction::WalletConfigPolicyUpdate;
+use crate::model::wallet::Approvers;
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::hash::Hash;
+use std::time::Duration;
+
+/// A config policy update that weakens the wallet's controls, staged until its timelock has
+/// elapsed instead of taking effect when its operation is finalized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PendingConfigPolicy {
+    pub approvals_required_for_config: u8,
+    pub approval_timeout_for_config: Duration,
+    pub config_approvers: Approvers,
+    pub config_policy_timelock: Duration,
+    /// Hash of the config approvers' keys in slot order, checked again on activation in case
+    /// the signers changed in the meantime.
+    pub signers_hash: Hash,
+    /// Unix timestamp from which the policy may be activated.
+    pub activates_at: i64,
+}
+
+impl PendingConfigPolicy {
+    pub const LEN: usize = 1 + // approvals_required_for_config
+        8 + // approval_timeout_for_config
+        Approvers::STORAGE_SIZE + // config_approvers
+        8 + // config_policy_timelock
+        HASH_LEN + // signers_hash
+        8; // activates_at
+
+    pub fn to_update(&self) -> WalletConfigPolicyUpdate {
+        WalletConfigPolicyUpdate {
+            approvals_required_for_config: self.approvals_required_for_config,
+            approval_timeout_for_config: self.approval_timeout_for_config,
+            config_approvers: self.config_approvers.iter_enabled().collect(),
+            signers_hash: self.signers_hash,
+            config_policy_timelock: self.config_policy_timelock,
+        }
+    }
+
+    /// Packs the pending policy, if any; an activation timestamp of 0 stands for none.
+    pub fn pack_into_slice(pending: &Option<PendingConfigPolicy>, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, PendingConfigPolicy::LEN];
+        let pending = match pending {
+            Some(pending) => pending,
+            None => {
+                dst.fill(0);
+                return;
+            }
+        };
+        let (
+            approvals_required_for_config_dst,
+            approval_timeout_for_config_dst,
+            config_approvers_dst,
+            config_policy_timelock_dst,
+            signers_hash_dst,
+            activates_at_dst,
+        ) = mut_array_refs![dst, 1, 8, Approvers::STORAGE_SIZE, 8, HASH_LEN, 8];
+        approvals_required_for_config_dst[0] = pending.approvals_required_for_config;
+        *approval_timeout_for_config_dst =
+            pending.approval_timeout_for_config.as_secs().to_le_bytes();
+        config_approvers_dst.copy_from_slice(pending.config_approvers.as_bytes());
+        *config_policy_timelock_dst = pending.config_policy_timelock.as_secs().to_le_bytes();
+        signers_hash_dst.copy_from_slice(pending.signers_hash.as_ref());
+        *activates_at_dst = pending.activates_at.to_le_bytes();
+    }
+
+    pub fn unpack_from_slice(src: &[u8]) -> Option<PendingConfigPolicy> {
+        let src = array_ref![src, 0, PendingConfigPolicy::LEN];
+        let (
+            approvals_required_for_config,
+            approval_timeout_for_config,
+            config_approvers,
+            config_policy_timelock,
+            signers_hash,
+            activates_at,
+        ) = array_refs![src, 1, 8, Approvers::STORAGE_SIZE, 8, HASH_LEN, 8];
+        let activates_at = i64::from_le_bytes(*activates_at);
+        if activates_at == 0 {
+            return None;
+        }
+        Some(PendingConfigPolicy {
+            approvals_required_for_config: approvals_required_for_config[0],
+            approval_timeout_for_config: Duration::from_secs(u64::from_le_bytes(
+                *approval_timeout_for_config,
+            )),
+            config_approvers: Approvers::new(*config_approvers),
+            config_policy_timelock: Duration::from_secs(u64::from_le_bytes(
+                *config_policy_timelock,
+            )),
+            signers_hash: Hash::new_from_array(*signers_hash),
+            activates_at,
+        })
+    }
+}
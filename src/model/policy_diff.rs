@@ -0,0 +1,146 @@
+use crate::model::wallet::Approvers;
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{Pack, Sealed};
+use std::time::Duration;
+
+/// The parts of a wallet config or balance account policy that approvers review when an update
+/// to it is proposed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PolicySummary {
+    pub approvers: Approvers,
+    pub approvals_required: u8,
+    pub approval_timeout: Duration,
+    /// The config policy timelock for the wallet config policy, and the finalize delay for a
+    /// balance account policy.
+    pub delay: Duration,
+}
+
+/// What a policy update changes, returned by the policy update simulation instructions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PolicyDiff {
+    pub approvers_added: Approvers,
+    pub approvers_removed: Approvers,
+    pub approvals_required_before: u8,
+    pub approvals_required_after: u8,
+    pub approval_timeout_before: Duration,
+    pub approval_timeout_after: Duration,
+    pub delay_before: Duration,
+    pub delay_after: Duration,
+}
+
+impl PolicyDiff {
+    pub fn between(before: &PolicySummary, after: &PolicySummary) -> PolicyDiff {
+        PolicyDiff {
+            approvers_added: Approvers::from_enabled_vec(
+                after
+                    .approvers
+                    .iter_enabled()
+                    .filter(|id| !before.approvers.is_enabled(id))
+                    .collect(),
+            ),
+            approvers_removed: Approvers::from_enabled_vec(
+                before
+                    .approvers
+                    .iter_enabled()
+                    .filter(|id| !after.approvers.is_enabled(id))
+                    .collect(),
+            ),
+            approvals_required_before: before.approvals_required,
+            approvals_required_after: after.approvals_required,
+            approval_timeout_before: before.approval_timeout,
+            approval_timeout_after: after.approval_timeout,
+            delay_before: before.delay,
+            delay_after: after.delay,
+        }
+    }
+
+    /// Whether the update lowers the approvals required or shortens the delay.
+    pub fn weakens_policy(&self) -> bool {
+        self.approvals_required_after < self.approvals_required_before
+            || self.delay_after < self.delay_before
+    }
+}
+
+impl Sealed for PolicyDiff {}
+
+impl Pack for PolicyDiff {
+    const LEN: usize = Approvers::STORAGE_SIZE + // approvers added
+        Approvers::STORAGE_SIZE + // approvers removed
+        1 + // approvals required before
+        1 + // approvals required after
+        8 + // approval timeout before
+        8 + // approval timeout after
+        8 + // delay before
+        8; // delay after
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, PolicyDiff::LEN];
+        let (
+            approvers_added_dst,
+            approvers_removed_dst,
+            approvals_required_before_dst,
+            approvals_required_after_dst,
+            approval_timeout_before_dst,
+            approval_timeout_after_dst,
+            delay_before_dst,
+            delay_after_dst,
+        ) = mut_array_refs![
+            dst,
+            Approvers::STORAGE_SIZE,
+            Approvers::STORAGE_SIZE,
+            1,
+            1,
+            8,
+            8,
+            8,
+            8
+        ];
+        approvers_added_dst.copy_from_slice(self.approvers_added.as_bytes());
+        approvers_removed_dst.copy_from_slice(self.approvers_removed.as_bytes());
+        approvals_required_before_dst[0] = self.approvals_required_before;
+        approvals_required_after_dst[0] = self.approvals_required_after;
+        *approval_timeout_before_dst = self.approval_timeout_before.as_secs().to_le_bytes();
+        *approval_timeout_after_dst = self.approval_timeout_after.as_secs().to_le_bytes();
+        *delay_before_dst = self.delay_before.as_secs().to_le_bytes();
+        *delay_after_dst = self.delay_after.as_secs().to_le_bytes();
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, PolicyDiff::LEN];
+        let (
+            approvers_added,
+            approvers_removed,
+            approvals_required_before,
+            approvals_required_after,
+            approval_timeout_before,
+            approval_timeout_after,
+            delay_before,
+            delay_after,
+        ) = array_refs![
+            src,
+            Approvers::STORAGE_SIZE,
+            Approvers::STORAGE_SIZE,
+            1,
+            1,
+            8,
+            8,
+            8,
+            8
+        ];
+        Ok(PolicyDiff {
+            approvers_added: Approvers::new(*approvers_added),
+            approvers_removed: Approvers::new(*approvers_removed),
+            approvals_required_before: approvals_required_before[0],
+            approvals_required_after: approvals_required_after[0],
+            approval_timeout_before: Duration::from_secs(u64::from_le_bytes(
+                *approval_timeout_before,
+            )),
+            approval_timeout_after: Duration::from_secs(u64::from_le_bytes(
+                *approval_timeout_after,
+            )),
+            delay_before: Duration::from_secs(u64::from_le_bytes(*delay_before)),
+            delay_after: Duration::from_secs(u64::from_le_bytes(*delay_after)),
+        })
+    }
+}
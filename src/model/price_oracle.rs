@@ -0,0 +1,365 @@
+use crate::constants::PUBKEY_BYTES;
+use crate::error::WalletError;
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::account_info::AccountInfo;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use std::time::Duration;
+
+/// The Pyth price account whitelisted to value transfers of `token_mint` (the default pubkey
+/// for SOL), whose amounts have `decimals` decimal places.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+pub struct PriceFeed {
+    pub token_mint: Pubkey,
+    pub price_account: Pubkey,
+    pub decimals: u8,
+}
+
+impl PriceFeed {
+    pub const LEN: usize = PUBKEY_BYTES + // token mint
+        PUBKEY_BYTES + // price account
+        1; // decimals
+}
+
+/// With a price oracle the wallet's transfer thresholds (those of the compliance policy and of
+/// the balance accounts' finalize delay policies) are in US cents rather than in units of the
+/// token transferred, and transfers are valued with the price feed of their token mint when
+/// they are initiated.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PriceOracle {
+    pub feeds: Vec<PriceFeed>,
+    /// How old the price may be, by its publish time, for a transfer to be valued with it.
+    pub max_staleness: Duration,
+    /// How wide the price's confidence interval may be, in basis points of the price.
+    pub max_confidence_bps: u16,
+}
+
+impl PriceOracle {
+    pub const MAX_FEEDS: usize = 4;
+    pub const MAX_STALENESS: Duration = Duration::from_secs(60 * 60);
+    pub const LEN: usize = 1 + // feed count
+        PriceFeed::LEN * PriceOracle::MAX_FEEDS + // feeds
+        8 + // max staleness
+        2; // max confidence bps
+
+    pub fn validate(&self) -> Result<(), ProgramError> {
+        if self.feeds.is_empty() || self.feeds.len() > PriceOracle::MAX_FEEDS {
+            msg!(
+                "A price oracle needs between 1 and {} price feeds",
+                PriceOracle::MAX_FEEDS
+            );
+            return Err(WalletError::InvalidPriceOracle.into());
+        }
+        for (i, feed) in self.feeds.iter().enumerate() {
+            if self.feeds[..i]
+                .iter()
+                .any(|other| other.token_mint == feed.token_mint)
+            {
+                msg!(
+                    "Token mint {} has more than one price feed",
+                    feed.token_mint
+                );
+                return Err(WalletError::InvalidPriceOracle.into());
+            }
+        }
+        if self.max_staleness.is_zero() || self.max_staleness > PriceOracle::MAX_STALENESS {
+            msg!(
+                "Max staleness must be between 1 and {} seconds",
+                PriceOracle::MAX_STALENESS.as_secs()
+            );
+            return Err(WalletError::InvalidPriceOracle.into());
+        }
+        if self.max_confidence_bps == 0 || self.max_confidence_bps > 10_000 {
+            msg!("Max confidence must be between 1 and 10000 basis points");
+            return Err(WalletError::InvalidPriceOracle.into());
+        }
+        Ok(())
+    }
+
+    /// Values `amount` of `token_mint` in US cents, using the mint's price feed which has to be
+    /// among `account_infos`. The upper end of the price's confidence interval is used, so an
+    /// uncertain price errs towards applying the policies.
+    pub fn value_in_cents(
+        &self,
+        token_mint: &Pubkey,
+        amount: u64,
+        account_infos: &[AccountInfo],
+        now: i64,
+    ) -> Result<u64, ProgramError> {
+        let feed = match self
+            .feeds
+            .iter()
+            .find(|feed| feed.token_mint == *token_mint)
+        {
+            Some(feed) => feed,
+            None => {
+                msg!("No price feed for token mint {}", token_mint);
+                return Err(WalletError::InvalidPriceAccount.into());
+            }
+        };
+        let price_account_info = match account_infos
+            .iter()
+            .find(|account_info| *account_info.key == feed.price_account)
+        {
+            Some(account_info) => account_info,
+            None => {
+                msg!("Price account {} was not passed", feed.price_account);
+                return Err(WalletError::InvalidPriceAccount.into());
+            }
+        };
+        let price = PythPrice::unpack_from_slice(&price_account_info.data.borrow())?;
+        price.validate(now, self.max_staleness, self.max_confidence_bps)?;
+        Ok(price.value_in_cents(amount, feed.decimals))
+    }
+
+    /// Packs the oracle, if any; no feeds stand for none.
+    pub fn pack_into_slice(oracle: &Option<PriceOracle>, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, PriceOracle::LEN];
+        dst.fill(0);
+        let oracle = match oracle {
+            Some(oracle) => oracle,
+            None => return,
+        };
+        let (count_dst, feeds_dst, max_staleness_dst, max_confidence_bps_dst) =
+            mut_array_refs![dst, 1, PriceFeed::LEN * PriceOracle::MAX_FEEDS, 8, 2];
+        count_dst[0] = oracle.feeds.len() as u8;
+        for (feed, feed_dst) in oracle
+            .feeds
+            .iter()
+            .zip(feeds_dst.chunks_exact_mut(PriceFeed::LEN))
+        {
+            feed_dst[..PUBKEY_BYTES].copy_from_slice(feed.token_mint.as_ref());
+            feed_dst[PUBKEY_BYTES..PUBKEY_BYTES * 2].copy_from_slice(feed.price_account.as_ref());
+            feed_dst[PUBKEY_BYTES * 2] = feed.decimals;
+        }
+        *max_staleness_dst = oracle.max_staleness.as_secs().to_le_bytes();
+        *max_confidence_bps_dst = oracle.max_confidence_bps.to_le_bytes();
+    }
+
+    pub fn unpack_from_slice(src: &[u8]) -> Result<Option<PriceOracle>, ProgramError> {
+        let src = array_ref![src, 0, PriceOracle::LEN];
+        let (count, feeds_src, max_staleness, max_confidence_bps) =
+            array_refs![src, 1, PriceFeed::LEN * PriceOracle::MAX_FEEDS, 8, 2];
+        let count = usize::from(count[0]);
+        if count == 0 {
+            return Ok(None);
+        }
+        if count > PriceOracle::MAX_FEEDS {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(Some(PriceOracle {
+            feeds: feeds_src
+                .chunks_exact(PriceFeed::LEN)
+                .take(count)
+                .map(|feed_src| PriceFeed {
+                    token_mint: Pubkey::new(&feed_src[..PUBKEY_BYTES]),
+                    price_account: Pubkey::new(&feed_src[PUBKEY_BYTES..PUBKEY_BYTES * 2]),
+                    decimals: feed_src[PUBKEY_BYTES * 2],
+                })
+                .collect(),
+            max_staleness: Duration::from_secs(u64::from_le_bytes(*max_staleness)),
+            max_confidence_bps: u16::from_le_bytes(*max_confidence_bps),
+        }))
+    }
+}
+
+/// The aggregate price read from a Pyth (v2) price account, worth `price * 10^expo` US dollars
+/// give or take `conf * 10^expo`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct PythPrice {
+    pub price: i64,
+    pub conf: u64,
+    pub expo: i32,
+    pub publish_time: i64,
+}
+
+impl PythPrice {
+    const MAGIC: u32 = 0xa1b2c3d4;
+    const VERSION: u32 = 2;
+    const ACCOUNT_TYPE_PRICE: u32 = 3;
+    const STATUS_TRADING: u32 = 1;
+    // the end of the aggregate price, the last field read
+    const MIN_LEN: usize = 240;
+
+    pub fn unpack_from_slice(src: &[u8]) -> Result<PythPrice, ProgramError> {
+        if src.len() < PythPrice::MIN_LEN {
+            msg!("Price account data is too short");
+            return Err(WalletError::InvalidPriceAccount.into());
+        }
+        let read_u32 = |offset: usize| u32::from_le_bytes(*array_ref![src, offset, 4]);
+        let read_u64 = |offset: usize| u64::from_le_bytes(*array_ref![src, offset, 8]);
+        if read_u32(0) != PythPrice::MAGIC
+            || read_u32(4) != PythPrice::VERSION
+            || read_u32(8) != PythPrice::ACCOUNT_TYPE_PRICE
+        {
+            msg!("Account is not a Pyth price account");
+            return Err(WalletError::InvalidPriceAccount.into());
+        }
+        if read_u32(224) != PythPrice::STATUS_TRADING {
+            msg!("Price is not currently trading");
+            return Err(WalletError::StalePrice.into());
+        }
+        Ok(PythPrice {
+            price: read_u64(208) as i64,
+            conf: read_u64(216),
+            expo: read_u32(20) as i32,
+            publish_time: read_u64(96) as i64,
+        })
+    }
+
+    pub fn validate(
+        &self,
+        now: i64,
+        max_staleness: Duration,
+        max_confidence_bps: u16,
+    ) -> Result<(), ProgramError> {
+        if self.price <= 0 {
+            msg!("Price {} is not positive", self.price);
+            return Err(WalletError::InvalidPriceAccount.into());
+        }
+        if now.saturating_sub(self.publish_time) > max_staleness.as_secs() as i64 {
+            msg!(
+                "Price was published at {}, too long before {}",
+                self.publish_time,
+                now
+            );
+            return Err(WalletError::StalePrice.into());
+        }
+        if u128::from(self.conf) * 10_000 > self.price as u128 * u128::from(max_confidence_bps) {
+            msg!(
+                "Price confidence {} is too wide for price {}",
+                self.conf,
+                self.price
+            );
+            return Err(WalletError::PriceTooUncertain.into());
+        }
+        Ok(())
+    }
+
+    /// The value of `amount` with `decimals` decimal places in US cents at the upper end of the
+    /// confidence interval, rounded down and saturating at `u64::MAX`.
+    pub fn value_in_cents(&self, amount: u64, decimals: u8) -> u64 {
+        let value = u128::from(amount) * (self.price as u128 + u128::from(self.conf));
+        // cents are 2 decimal places of a dollar
+        let exponent = i64::from(self.expo) + 2 - i64::from(decimals);
+        let value = if exponent >= 0 {
+            10u128
+                .checked_pow(exponent as u32)
+                .and_then(|scale| value.checked_mul(scale))
+                .unwrap_or(u128::MAX)
+        } else {
+            10u128
+                .checked_pow(exponent.unsigned_abs() as u32)
+                .map_or(0, |scale| value / scale)
+        };
+        u64::try_from(value).unwrap_or(u64::MAX)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn pyth_price_data(price: i64, conf: u64, expo: i32, publish_time: i64) -> Vec<u8> {
+        let mut data = vec![0; 3312];
+        data[0..4].copy_from_slice(&PythPrice::MAGIC.to_le_bytes());
+        data[4..8].copy_from_slice(&PythPrice::VERSION.to_le_bytes());
+        data[8..12].copy_from_slice(&PythPrice::ACCOUNT_TYPE_PRICE.to_le_bytes());
+        data[20..24].copy_from_slice(&expo.to_le_bytes());
+        data[96..104].copy_from_slice(&publish_time.to_le_bytes());
+        data[208..216].copy_from_slice(&price.to_le_bytes());
+        data[216..224].copy_from_slice(&conf.to_le_bytes());
+        data[224..228].copy_from_slice(&PythPrice::STATUS_TRADING.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_pyth_price() {
+        // $25.00 give or take a cent
+        let data = pyth_price_data(2_500_000_000, 1_000_000, -8, 1000);
+        let price = PythPrice::unpack_from_slice(&data).unwrap();
+        assert_eq!(
+            price,
+            PythPrice {
+                price: 2_500_000_000,
+                conf: 1_000_000,
+                expo: -8,
+                publish_time: 1000,
+            }
+        );
+
+        // 2 SOL at the upper end of the interval
+        assert_eq!(price.value_in_cents(2_000_000_000, 9), 5002);
+        assert_eq!(price.value_in_cents(1, 0), 2501);
+        assert_eq!(price.value_in_cents(u64::MAX, 0), u64::MAX);
+
+        price.validate(1060, Duration::from_secs(60), 4).unwrap();
+        assert_eq!(
+            price.validate(1061, Duration::from_secs(60), 4),
+            Err(WalletError::StalePrice.into())
+        );
+        assert_eq!(
+            price.validate(1000, Duration::from_secs(60), 3),
+            Err(WalletError::PriceTooUncertain.into())
+        );
+
+        let mut data = pyth_price_data(2_500_000_000, 1_000_000, -8, 1000);
+        data[224..228].copy_from_slice(&0u32.to_le_bytes());
+        assert_eq!(
+            PythPrice::unpack_from_slice(&data),
+            Err(WalletError::StalePrice.into())
+        );
+        data[8..12].copy_from_slice(&2u32.to_le_bytes());
+        assert_eq!(
+            PythPrice::unpack_from_slice(&data),
+            Err(WalletError::InvalidPriceAccount.into())
+        );
+        assert_eq!(
+            PythPrice::unpack_from_slice(&data[..200]),
+            Err(WalletError::InvalidPriceAccount.into())
+        );
+    }
+
+    #[test]
+    fn test_price_oracle_pack() {
+        let oracle = PriceOracle {
+            feeds: vec![
+                PriceFeed {
+                    token_mint: Pubkey::default(),
+                    price_account: Pubkey::new_unique(),
+                    decimals: 9,
+                },
+                PriceFeed {
+                    token_mint: Pubkey::new_unique(),
+                    price_account: Pubkey::new_unique(),
+                    decimals: 6,
+                },
+            ],
+            max_staleness: Duration::from_secs(60),
+            max_confidence_bps: 100,
+        };
+        oracle.validate().unwrap();
+        let mut buf = [0; PriceOracle::LEN];
+        PriceOracle::pack_into_slice(&Some(oracle.clone()), &mut buf);
+        assert_eq!(
+            PriceOracle::unpack_from_slice(&buf),
+            Ok(Some(oracle.clone()))
+        );
+        PriceOracle::pack_into_slice(&None, &mut buf);
+        assert_eq!(PriceOracle::unpack_from_slice(&buf), Ok(None));
+
+        let mut duplicate = oracle.clone();
+        duplicate.feeds[1].token_mint = Pubkey::default();
+        assert_eq!(
+            duplicate.validate(),
+            Err(WalletError::InvalidPriceOracle.into())
+        );
+        let mut stale = oracle;
+        stale.max_staleness = Duration::ZERO;
+        assert_eq!(
+            stale.validate(),
+            Err(WalletError::InvalidPriceOracle.into())
+        );
+    }
+}
@@ -0,0 +1,201 @@
+use crate::error::WalletError;
+use crate::model::dapp_multisig_data::MAX_INSTRUCTION_COUNT;
+use crate::model::wallet::Wallet;
+use crate::pda::{PdaSeeds, ProgramParametersSeeds};
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{IsInitialized, Pack, Sealed};
+use solana_program::pubkey::Pubkey;
+use std::time::Duration;
+
+/// Limits the program's upgrade authority can tune without redeploying, kept in a program-global
+/// PDA. Each is bounded by a floor and a ceiling in code; the ceilings are what the accounts are
+/// laid out for, and are the limits until the parameters account is created.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ProgramParameters {
+    pub is_initialized: bool,
+    pub max_signers: u8,
+    pub max_dapp_instructions: u8,
+    pub max_approval_timeout: Duration,
+}
+
+impl ProgramParameters {
+    pub const MIN_MAX_SIGNERS: u8 = 2;
+    pub const MIN_MAX_DAPP_INSTRUCTIONS: u8 = 1;
+    pub const MIN_MAX_APPROVAL_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+    /// Derive the PDA and "bump seed" of the program parameters.
+    pub fn find_address(program_id: &Pubkey) -> (Pubkey, u8) {
+        ProgramParametersSeeds.find_address(program_id)
+    }
+
+    pub fn validate(&self) -> ProgramResult {
+        if self.max_signers < Self::MIN_MAX_SIGNERS
+            || usize::from(self.max_signers) > Wallet::MAX_SIGNERS
+            || self.max_dapp_instructions < Self::MIN_MAX_DAPP_INSTRUCTIONS
+            || usize::from(self.max_dapp_instructions) > MAX_INSTRUCTION_COUNT
+            || self.max_approval_timeout < Self::MIN_MAX_APPROVAL_TIMEOUT
+            || self.max_approval_timeout > Wallet::MAX_APPROVAL_TIMEOUT
+        {
+            return Err(WalletError::InvalidProgramParameters.into());
+        }
+        Ok(())
+    }
+
+    pub fn validate_signer_count(&self, signer_count: usize) -> ProgramResult {
+        if signer_count > usize::from(self.max_signers) {
+            msg!("A wallet can't have more than {} signers", self.max_signers);
+            return Err(WalletError::ProgramParameterLimitExceeded.into());
+        }
+        Ok(())
+    }
+
+    pub fn validate_dapp_instruction_count(&self, instruction_count: u8) -> ProgramResult {
+        if instruction_count > self.max_dapp_instructions {
+            msg!(
+                "A dApp transaction can't have more than {} instructions",
+                self.max_dapp_instructions
+            );
+            return Err(WalletError::ProgramParameterLimitExceeded.into());
+        }
+        Ok(())
+    }
+
+    pub fn validate_approval_timeout(&self, timeout: &Duration) -> ProgramResult {
+        if *timeout > self.max_approval_timeout {
+            msg!(
+                "Approval timeout can't be more than {} seconds",
+                self.max_approval_timeout.as_secs()
+            );
+            return Err(WalletError::ProgramParameterLimitExceeded.into());
+        }
+        Ok(())
+    }
+}
+
+impl Default for ProgramParameters {
+    fn default() -> Self {
+        ProgramParameters {
+            is_initialized: false,
+            max_signers: Wallet::MAX_SIGNERS as u8,
+            max_dapp_instructions: MAX_INSTRUCTION_COUNT as u8,
+            max_approval_timeout: Wallet::MAX_APPROVAL_TIMEOUT,
+        }
+    }
+}
+
+impl Sealed for ProgramParameters {}
+
+impl IsInitialized for ProgramParameters {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for ProgramParameters {
+    const LEN: usize = 1 + // is_initialized
+        1 + // max signers
+        1 + // max dApp instructions
+        8; // max approval timeout
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, ProgramParameters::LEN];
+        let (
+            is_initialized_dst,
+            max_signers_dst,
+            max_dapp_instructions_dst,
+            max_approval_timeout_dst,
+        ) = mut_array_refs![dst, 1, 1, 1, 8];
+        is_initialized_dst[0] = self.is_initialized as u8;
+        max_signers_dst[0] = self.max_signers;
+        max_dapp_instructions_dst[0] = self.max_dapp_instructions;
+        *max_approval_timeout_dst = self.max_approval_timeout.as_secs().to_le_bytes();
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, ProgramParameters::LEN];
+        let (is_initialized, max_signers, max_dapp_instructions, max_approval_timeout) =
+            array_refs![src, 1, 1, 1, 8];
+        Ok(ProgramParameters {
+            is_initialized: match is_initialized {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            max_signers: max_signers[0],
+            max_dapp_instructions: max_dapp_instructions[0],
+            max_approval_timeout: Duration::from_secs(u64::from_le_bytes(*max_approval_timeout)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::WalletError;
+    use crate::model::program_parameters::ProgramParameters;
+    use solana_program::program_error::ProgramError;
+    use std::time::Duration;
+
+    #[test]
+    fn test_program_parameter_bounds() {
+        let ceilings = ProgramParameters::default();
+        assert_eq!(ceilings.validate(), Ok(()));
+
+        let invalid = ProgramParameters {
+            max_signers: ceilings.max_signers + 1,
+            ..ceilings
+        };
+        assert_eq!(
+            invalid.validate(),
+            Err(ProgramError::Custom(
+                WalletError::InvalidProgramParameters as u32
+            ))
+        );
+        let invalid = ProgramParameters {
+            max_dapp_instructions: 0,
+            ..ceilings
+        };
+        assert_eq!(
+            invalid.validate(),
+            Err(ProgramError::Custom(
+                WalletError::InvalidProgramParameters as u32
+            ))
+        );
+        let invalid = ProgramParameters {
+            max_approval_timeout: Duration::from_secs(60),
+            ..ceilings
+        };
+        assert_eq!(
+            invalid.validate(),
+            Err(ProgramError::Custom(
+                WalletError::InvalidProgramParameters as u32
+            ))
+        );
+
+        let tuned = ProgramParameters {
+            is_initialized: true,
+            max_signers: 5,
+            max_dapp_instructions: 4,
+            max_approval_timeout: Duration::from_secs(60 * 60 * 24),
+        };
+        assert_eq!(tuned.validate(), Ok(()));
+        assert_eq!(tuned.validate_signer_count(5), Ok(()));
+        assert_eq!(
+            tuned.validate_signer_count(6),
+            Err(ProgramError::Custom(
+                WalletError::ProgramParameterLimitExceeded as u32
+            ))
+        );
+        assert_eq!(tuned.validate_dapp_instruction_count(4), Ok(()));
+        assert!(tuned.validate_dapp_instruction_count(5).is_err());
+        assert_eq!(
+            tuned.validate_approval_timeout(&Duration::from_secs(60 * 60 * 24)),
+            Ok(())
+        );
+        assert!(tuned
+            .validate_approval_timeout(&Duration::from_secs(60 * 60 * 24 + 1))
+            .is_err());
+    }
+}
@@ -0,0 +1,129 @@
+use crate::error::WalletError;
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
+
+/// The accounts the rent of the wallet's multisig ops may be returned to when they are
+/// finalized, so that whoever pays for an op's init can't name an account of their own to
+/// collect its rent and fee. An empty allowlist allows any account.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct RentReturnAllowlist {
+    pub entries: Vec<Pubkey>,
+}
+
+impl RentReturnAllowlist {
+    pub const MAX_ENTRIES: usize = 8;
+    pub const LEN: usize = 1 + // entry count
+        PUBKEY_BYTES * RentReturnAllowlist::MAX_ENTRIES; // entries
+
+    pub fn validate(&self) -> Result<(), ProgramError> {
+        if self.entries.len() > RentReturnAllowlist::MAX_ENTRIES {
+            msg!(
+                "A rent return allowlist can have at most {} entries",
+                RentReturnAllowlist::MAX_ENTRIES
+            );
+            return Err(WalletError::InvalidRentReturnAllowlist.into());
+        }
+        for (i, entry) in self.entries.iter().enumerate() {
+            if self.entries[..i].contains(entry) {
+                msg!("Rent return account {} is listed more than once", entry);
+                return Err(WalletError::InvalidRentReturnAllowlist.into());
+            }
+        }
+        Ok(())
+    }
+
+    pub fn allows(&self, rent_return: &Pubkey) -> bool {
+        self.entries.is_empty() || self.entries.contains(rent_return)
+    }
+
+    pub fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, RentReturnAllowlist::LEN];
+        let (count_dst, entries_dst) =
+            mut_array_refs![dst, 1, PUBKEY_BYTES * RentReturnAllowlist::MAX_ENTRIES];
+        count_dst[0] = self.entries.len() as u8;
+        entries_dst.fill(0);
+        for (entry, entry_dst) in self
+            .entries
+            .iter()
+            .zip(entries_dst.chunks_exact_mut(PUBKEY_BYTES))
+        {
+            entry_dst.copy_from_slice(entry.as_ref());
+        }
+    }
+
+    pub fn unpack_from_slice(src: &[u8]) -> Result<RentReturnAllowlist, ProgramError> {
+        let src = array_ref![src, 0, RentReturnAllowlist::LEN];
+        let (count, entries_src) =
+            array_refs![src, 1, PUBKEY_BYTES * RentReturnAllowlist::MAX_ENTRIES];
+        let count = usize::from(count[0]);
+        if count > RentReturnAllowlist::MAX_ENTRIES {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(RentReturnAllowlist {
+            entries: entries_src
+                .chunks_exact(PUBKEY_BYTES)
+                .take(count)
+                .map(Pubkey::new)
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::WalletError;
+    use crate::model::rent_return_allowlist::RentReturnAllowlist;
+    use crate::model::wallet::Wallet;
+    use solana_program::program_error::ProgramError;
+    use solana_program::program_pack::Pack;
+    use solana_program::pubkey::Pubkey;
+
+    #[test]
+    fn test_rent_return_allowlist() {
+        let allowed = Pubkey::new_unique();
+        assert!(RentReturnAllowlist::default().allows(&allowed));
+        let allowlist = RentReturnAllowlist {
+            entries: vec![allowed, Pubkey::new_unique()],
+        };
+        assert_eq!(allowlist.validate(), Ok(()));
+        assert!(allowlist.allows(&allowed));
+        assert!(!allowlist.allows(&Pubkey::new_unique()));
+
+        let invalid: ProgramError = WalletError::InvalidRentReturnAllowlist.into();
+        assert_eq!(
+            RentReturnAllowlist {
+                entries: vec![allowed, allowed],
+            }
+            .validate(),
+            Err(invalid.clone())
+        );
+        assert_eq!(
+            RentReturnAllowlist {
+                entries: (0..=RentReturnAllowlist::MAX_ENTRIES)
+                    .map(|_| Pubkey::new_unique())
+                    .collect(),
+            }
+            .validate(),
+            Err(invalid)
+        );
+
+        // it is read back from a packed wallet without unpacking the rest of it
+        let mut wallet = Wallet::unpack_unchecked(&vec![0; Wallet::LEN]).unwrap();
+        wallet.is_initialized = true;
+        wallet.update_rent_return_allowlist(&allowlist);
+        let mut buffer = vec![0xff; Wallet::LEN];
+        wallet.pack_into_slice(&mut buffer);
+        assert_eq!(
+            Wallet::rent_return_allowlist_from_slice(&buffer),
+            Ok(allowlist)
+        );
+        wallet.update_rent_return_allowlist(&RentReturnAllowlist::default());
+        wallet.pack_into_slice(&mut buffer);
+        assert_eq!(
+            Wallet::rent_return_allowlist_from_slice(&buffer),
+            Ok(RentReturnAllowlist::default())
+        );
+    }
+}
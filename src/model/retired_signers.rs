@@ -0,0 +1,93 @@
+use crate::model::signer::Signer;
+use crate::model::wallet::Wallet;
+use crate::utils::{remap_slots, SlotId, SlotRemap};
+use arrayref::array_mut_ref;
+use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
+
+/// The key each signer slot held before an `UpdateSigner` replacement last gave the slot to
+/// another key, so that the dispositions the replaced key recorded on ops still in flight can be
+/// handed on to its successor with `MigratePendingApprovals`. A slot that is cleared has no
+/// successor, so clearing it forgets the key it retired.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct RetiredSigners {
+    keys: [Pubkey; Wallet::MAX_SIGNERS],
+}
+
+impl RetiredSigners {
+    pub const LEN: usize = PUBKEY_BYTES * Wallet::MAX_SIGNERS;
+
+    /// The key last replaced in the slot, or the default key if none was.
+    pub fn retired_key(&self, slot_id: SlotId<Signer>) -> Pubkey {
+        self.keys[slot_id.value]
+    }
+
+    pub fn signer_replaced(&mut self, slot_id: SlotId<Signer>, key: Pubkey) {
+        self.keys[slot_id.value] = key;
+    }
+
+    pub fn slot_cleared(&mut self, slot_id: SlotId<Signer>) {
+        self.keys[slot_id.value] = Pubkey::default();
+    }
+
+    /// The slot the key was last replaced in, if any.
+    pub fn find_slot(&self, key: &Pubkey) -> Option<SlotId<Signer>> {
+        if *key == Pubkey::default() {
+            return None;
+        }
+        self.keys
+            .iter()
+            .position(|retired| retired == key)
+            .map(SlotId::new)
+    }
+
+    pub fn remap(&mut self, remap: &SlotRemap<Signer>) {
+        remap_slots(&mut self.keys, remap);
+    }
+
+    pub fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, RetiredSigners::LEN];
+        for (key, key_dst) in self.keys.iter().zip(dst.chunks_exact_mut(PUBKEY_BYTES)) {
+            key_dst.copy_from_slice(key.as_ref());
+        }
+    }
+
+    pub fn unpack_from_slice(src: &[u8]) -> RetiredSigners {
+        let mut keys = [Pubkey::default(); Wallet::MAX_SIGNERS];
+        for (key, key_src) in keys
+            .iter_mut()
+            .zip(src[..RetiredSigners::LEN].chunks_exact(PUBKEY_BYTES))
+        {
+            *key = Pubkey::new(key_src);
+        }
+        RetiredSigners { keys }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::model::retired_signers::RetiredSigners;
+    use crate::utils::SlotId;
+    use solana_program::pubkey::Pubkey;
+
+    #[test]
+    fn test_retired_signers() {
+        let mut retired = RetiredSigners::default();
+        let key = Pubkey::new_unique();
+        assert_eq!(retired.find_slot(&key), None);
+        assert_eq!(retired.find_slot(&Pubkey::default()), None);
+
+        retired.signer_replaced(SlotId::new(3), key);
+        assert_eq!(retired.retired_key(SlotId::new(3)), key);
+        assert_eq!(retired.find_slot(&key), Some(SlotId::new(3)));
+
+        retired.remap(&vec![(SlotId::new(3), SlotId::new(0))]);
+        assert_eq!(retired.find_slot(&key), Some(SlotId::new(0)));
+
+        let mut buffer = vec![0; RetiredSigners::LEN];
+        retired.pack_into_slice(&mut buffer);
+        assert_eq!(RetiredSigners::unpack_from_slice(&buffer), retired);
+
+        retired.slot_cleared(SlotId::new(0));
+        assert_eq!(retired.find_slot(&key), None);
+    }
+}
@@ -0,0 +1,392 @@
+use crate::constants::PUBKEY_BYTES;
+use crate::error::WalletError;
+use crate::model::finalize_submitter_policy::FinalizeCategory;
+use crate::model::multisig_op::MultisigOp;
+use crate::pda::{PdaSeeds, SessionKeyRegistrySeeds};
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{IsInitialized, Pack, Sealed};
+use solana_program::pubkey::Pubkey;
+use std::time::Duration;
+
+/// The ops a session key may register dispositions for: those of the given categories and, for
+/// transfers, moving at most `max_transfer_amount`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct SessionKeyScope {
+    /// A bitmask of `SessionKeyScope::CONFIG`, `SessionKeyScope::TRANSFER` and
+    /// `SessionKeyScope::DAPP`.
+    pub categories: u8,
+    pub max_transfer_amount: u64,
+}
+
+impl SessionKeyScope {
+    pub const CONFIG: u8 = 1;
+    pub const TRANSFER: u8 = 2;
+    pub const DAPP: u8 = 4;
+
+    pub const LEN: usize = 1 + 8;
+
+    fn category_bit(category: FinalizeCategory) -> u8 {
+        match category {
+            FinalizeCategory::Config => SessionKeyScope::CONFIG,
+            FinalizeCategory::Transfer => SessionKeyScope::TRANSFER,
+            FinalizeCategory::DApp => SessionKeyScope::DAPP,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.categories != 0
+            && self.categories
+                & !(SessionKeyScope::CONFIG | SessionKeyScope::TRANSFER | SessionKeyScope::DAPP)
+                == 0
+    }
+
+    /// Whether an op of the given category moving the given amount is in scope. An op whose
+    /// category isn't known never is.
+    pub fn covers(&self, category: Option<FinalizeCategory>, transfer_amount: u64) -> bool {
+        match category {
+            None => false,
+            Some(category) => {
+                self.categories & SessionKeyScope::category_bit(category) != 0
+                    && (category != FinalizeCategory::Transfer
+                        || transfer_amount <= self.max_transfer_amount)
+            }
+        }
+    }
+
+    pub fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, SessionKeyScope::LEN];
+        let (categories_dst, max_transfer_amount_dst) = mut_array_refs![dst, 1, 8];
+        categories_dst[0] = self.categories;
+        *max_transfer_amount_dst = self.max_transfer_amount.to_le_bytes();
+    }
+
+    pub fn unpack_from_slice(src: &[u8]) -> SessionKeyScope {
+        let src = array_ref![src, 0, SessionKeyScope::LEN];
+        let (categories, max_transfer_amount) = array_refs![src, 1, 8];
+        SessionKeyScope {
+            categories: categories[0],
+            max_transfer_amount: u64::from_le_bytes(*max_transfer_amount),
+        }
+    }
+}
+
+/// A short-lived key an approver registered to sign dispositions on their behalf, for the ops
+/// in its scope until it expires, so that the approver's own key never has to be on the device
+/// approving.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct SessionKey {
+    pub approver: Pubkey,
+    pub session_key: Pubkey,
+    pub scope: SessionKeyScope,
+    pub expires_at: i64,
+}
+
+impl SessionKey {
+    pub const LEN: usize = PUBKEY_BYTES + PUBKEY_BYTES + SessionKeyScope::LEN + 8;
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        now >= self.expires_at
+    }
+
+    /// Validate that the session key may register a disposition for the op.
+    pub fn validate_covers(&self, multisig_op: &MultisigOp, now: i64) -> ProgramResult {
+        if self.is_expired(now) {
+            msg!("Session key {} has expired", self.session_key);
+            return Err(WalletError::InvalidSessionKey.into());
+        }
+        if !self
+            .scope
+            .covers(multisig_op.category, multisig_op.transfer_amount)
+        {
+            return Err(WalletError::SessionKeyOutOfScope.into());
+        }
+        Ok(())
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, SessionKey::LEN];
+        let (approver_dst, session_key_dst, scope_dst, expires_at_dst) =
+            mut_array_refs![dst, PUBKEY_BYTES, PUBKEY_BYTES, SessionKeyScope::LEN, 8];
+        approver_dst.copy_from_slice(self.approver.as_ref());
+        session_key_dst.copy_from_slice(self.session_key.as_ref());
+        self.scope.pack_into_slice(scope_dst);
+        *expires_at_dst = self.expires_at.to_le_bytes();
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> SessionKey {
+        let src = array_ref![src, 0, SessionKey::LEN];
+        let (approver, session_key, scope, expires_at) =
+            array_refs![src, PUBKEY_BYTES, PUBKEY_BYTES, SessionKeyScope::LEN, 8];
+        SessionKey {
+            approver: Pubkey::new_from_array(*approver),
+            session_key: Pubkey::new_from_array(*session_key),
+            scope: SessionKeyScope::unpack_from_slice(scope),
+            expires_at: i64::from_le_bytes(*expires_at),
+        }
+    }
+}
+
+/// The session keys registered by a wallet's approvers. It lives in a PDA derived from the
+/// wallet and is created by the first registration; lapsed keys are dropped as new ones are
+/// registered.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SessionKeyRegistry {
+    pub is_initialized: bool,
+    pub wallet: Pubkey,
+    pub session_keys: Vec<SessionKey>,
+}
+
+impl SessionKeyRegistry {
+    pub const MAX_SESSION_KEYS: usize = 16;
+    /// The longest a session key can be registered for.
+    pub const MAX_SESSION_DURATION: Duration = Duration::from_secs(24 * 60 * 60);
+
+    pub fn new(wallet: Pubkey) -> Self {
+        SessionKeyRegistry {
+            is_initialized: true,
+            wallet,
+            session_keys: Vec::new(),
+        }
+    }
+
+    /// Derive the PDA and "bump seed" of the registry for the given wallet.
+    pub fn find_address(wallet: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        SessionKeyRegistrySeeds { wallet }.find_address(program_id)
+    }
+
+    pub fn get(&self, session_key: &Pubkey) -> Option<&SessionKey> {
+        self.session_keys
+            .iter()
+            .find(|it| it.session_key == *session_key)
+    }
+
+    /// Registers the session key, replacing its earlier registration by the same approver.
+    pub fn register(&mut self, session_key: SessionKey, now: i64) -> ProgramResult {
+        if !session_key.scope.is_valid()
+            || session_key.is_expired(now)
+            || session_key.expires_at - now
+                > SessionKeyRegistry::MAX_SESSION_DURATION.as_secs() as i64
+        {
+            return Err(WalletError::InvalidSessionKey.into());
+        }
+        if let Some(registered) = self.get(&session_key.session_key) {
+            if registered.approver != session_key.approver {
+                msg!(
+                    "Session key {} is registered by another approver",
+                    session_key.session_key
+                );
+                return Err(WalletError::InvalidSessionKey.into());
+            }
+        }
+        self.session_keys
+            .retain(|it| !it.is_expired(now) && it.session_key != session_key.session_key);
+        if self.session_keys.len() == SessionKeyRegistry::MAX_SESSION_KEYS {
+            msg!(
+                "A wallet can have at most {} live session keys",
+                SessionKeyRegistry::MAX_SESSION_KEYS
+            );
+            return Err(WalletError::SessionKeyRegistryFull.into());
+        }
+        self.session_keys.push(session_key);
+        Ok(())
+    }
+
+    /// Revokes a session key registered by the approver.
+    pub fn revoke(&mut self, approver: &Pubkey, session_key: &Pubkey) -> ProgramResult {
+        match self.get(session_key) {
+            Some(registered) if registered.approver == *approver => {
+                self.session_keys
+                    .retain(|it| it.session_key != *session_key);
+                Ok(())
+            }
+            _ => Err(WalletError::InvalidSessionKey.into()),
+        }
+    }
+}
+
+impl Sealed for SessionKeyRegistry {}
+
+impl IsInitialized for SessionKeyRegistry {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for SessionKeyRegistry {
+    const LEN: usize = 1 + // is_initialized
+        PUBKEY_BYTES + // wallet
+        1 + // session key count
+        SessionKey::LEN * SessionKeyRegistry::MAX_SESSION_KEYS;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, SessionKeyRegistry::LEN];
+        let (is_initialized_dst, wallet_dst, count_dst, session_keys_dst) = mut_array_refs![
+            dst,
+            1,
+            PUBKEY_BYTES,
+            1,
+            SessionKey::LEN * SessionKeyRegistry::MAX_SESSION_KEYS
+        ];
+
+        is_initialized_dst[0] = self.is_initialized as u8;
+        wallet_dst.copy_from_slice(self.wallet.as_ref());
+        count_dst[0] = self.session_keys.len() as u8;
+        session_keys_dst.fill(0);
+        for (session_key, session_key_dst) in self
+            .session_keys
+            .iter()
+            .zip(session_keys_dst.chunks_exact_mut(SessionKey::LEN))
+        {
+            session_key.pack_into_slice(session_key_dst);
+        }
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, SessionKeyRegistry::LEN];
+        let (is_initialized_src, wallet_src, count_src, session_keys_src) = array_refs![
+            src,
+            1,
+            PUBKEY_BYTES,
+            1,
+            SessionKey::LEN * SessionKeyRegistry::MAX_SESSION_KEYS
+        ];
+
+        let is_initialized = match is_initialized_src {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        let count = usize::from(count_src[0]);
+        if count > SessionKeyRegistry::MAX_SESSION_KEYS {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(SessionKeyRegistry {
+            is_initialized,
+            wallet: Pubkey::new_from_array(*wallet_src),
+            session_keys: session_keys_src
+                .chunks_exact(SessionKey::LEN)
+                .take(count)
+                .map(SessionKey::unpack_from_slice)
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_000_000;
+
+    fn session_key(approver: Pubkey, scope: SessionKeyScope, expires_at: i64) -> SessionKey {
+        SessionKey {
+            approver,
+            session_key: Pubkey::new_unique(),
+            scope,
+            expires_at,
+        }
+    }
+
+    #[test]
+    fn test_scope_covers() {
+        let scope = SessionKeyScope {
+            categories: SessionKeyScope::TRANSFER | SessionKeyScope::DAPP,
+            max_transfer_amount: 100,
+        };
+        assert!(scope.covers(Some(FinalizeCategory::Transfer), 100));
+        assert!(!scope.covers(Some(FinalizeCategory::Transfer), 101));
+        assert!(scope.covers(Some(FinalizeCategory::DApp), 0));
+        assert!(!scope.covers(Some(FinalizeCategory::Config), 0));
+        assert!(!scope.covers(None, 0));
+    }
+
+    #[test]
+    fn test_register_and_revoke() {
+        let approver = Pubkey::new_unique();
+        let scope = SessionKeyScope {
+            categories: SessionKeyScope::TRANSFER,
+            max_transfer_amount: 100,
+        };
+        let mut registry = SessionKeyRegistry::new(Pubkey::new_unique());
+
+        // expiries have to be in the future and within the maximum session duration
+        let max_expires_at = NOW + SessionKeyRegistry::MAX_SESSION_DURATION.as_secs() as i64;
+        for expires_at in [NOW, max_expires_at + 1] {
+            assert_eq!(
+                registry.register(session_key(approver, scope, expires_at), NOW),
+                Err(WalletError::InvalidSessionKey.into())
+            );
+        }
+        assert_eq!(
+            registry.register(
+                session_key(
+                    approver,
+                    SessionKeyScope {
+                        categories: 8,
+                        max_transfer_amount: 0
+                    },
+                    max_expires_at
+                ),
+                NOW
+            ),
+            Err(WalletError::InvalidSessionKey.into())
+        );
+
+        let key = session_key(approver, scope, max_expires_at);
+        registry.register(key, NOW).unwrap();
+        assert_eq!(registry.get(&key.session_key), Some(&key));
+
+        // another approver can't take over the session key
+        assert_eq!(
+            registry.register(
+                SessionKey {
+                    approver: Pubkey::new_unique(),
+                    ..key
+                },
+                NOW
+            ),
+            Err(WalletError::InvalidSessionKey.into())
+        );
+        assert_eq!(
+            registry.revoke(&Pubkey::new_unique(), &key.session_key),
+            Err(WalletError::InvalidSessionKey.into())
+        );
+
+        let round_tripped = {
+            let mut buf = vec![0; SessionKeyRegistry::LEN];
+            registry.pack_into_slice(&mut buf);
+            SessionKeyRegistry::unpack_from_slice(&buf).unwrap()
+        };
+        assert_eq!(round_tripped, registry);
+
+        registry.revoke(&approver, &key.session_key).unwrap();
+        assert_eq!(registry.get(&key.session_key), None);
+    }
+
+    #[test]
+    fn test_register_drops_lapsed_keys() {
+        let approver = Pubkey::new_unique();
+        let scope = SessionKeyScope {
+            categories: SessionKeyScope::CONFIG,
+            max_transfer_amount: 0,
+        };
+        let mut registry = SessionKeyRegistry::new(Pubkey::new_unique());
+        for _ in 0..SessionKeyRegistry::MAX_SESSION_KEYS {
+            registry
+                .register(session_key(approver, scope, NOW + 10), NOW)
+                .unwrap();
+        }
+        assert_eq!(
+            registry.register(session_key(approver, scope, NOW + 10), NOW),
+            Err(WalletError::SessionKeyRegistryFull.into())
+        );
+
+        let key = session_key(approver, scope, NOW + 20);
+        registry.register(key, NOW + 10).unwrap();
+        assert_eq!(registry.session_keys, vec![key]);
+    }
+}
@@ -1,32 +1,79 @@
-use arrayref::array_ref;
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
 use solana_program::program_error::ProgramError;
 use solana_program::program_pack::{Pack, Sealed};
 use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
 
+/// Bitmask of the roles a signer key may play in a wallet, so that organizations can
+/// separate duties (e.g. keys which may only approve, or only initiate).
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Ord, PartialOrd)]
+pub struct SignerCapabilities(u8);
+
+impl SignerCapabilities {
+    pub const APPROVE_CONFIG: SignerCapabilities = SignerCapabilities(1);
+    pub const APPROVE_TRANSFER: SignerCapabilities = SignerCapabilities(2);
+    pub const INITIATE: SignerCapabilities = SignerCapabilities(4);
+    /// Allows the signer to initiate its own removal from the wallet, even without `INITIATE`.
+    pub const RESIGN: SignerCapabilities = SignerCapabilities(8);
+    pub const ALL: SignerCapabilities = SignerCapabilities(15);
+
+    pub fn from_u8(value: u8) -> Result<SignerCapabilities, ProgramError> {
+        if value & !Self::ALL.0 != 0 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(SignerCapabilities(value))
+    }
+
+    pub fn to_u8(&self) -> u8 {
+        self.0
+    }
+
+    pub fn contains(&self, other: SignerCapabilities) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn union(&self, other: SignerCapabilities) -> SignerCapabilities {
+        SignerCapabilities(self.0 | other.0)
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Ord, PartialOrd)]
 pub struct Signer {
     pub key: Pubkey,
+    pub capabilities: SignerCapabilities,
 }
 
 impl Signer {
     pub fn new(key: Pubkey) -> Self {
-        Signer { key }
+        Signer::with_capabilities(key, SignerCapabilities::ALL)
+    }
+
+    pub fn with_capabilities(key: Pubkey, capabilities: SignerCapabilities) -> Self {
+        Signer { key, capabilities }
+    }
+
+    pub fn can(&self, capability: SignerCapabilities) -> bool {
+        self.capabilities.contains(capability)
     }
 }
 
 impl Sealed for Signer {}
 
 impl Pack for Signer {
-    const LEN: usize = PUBKEY_BYTES;
+    const LEN: usize = PUBKEY_BYTES + 1; // key + capabilities
 
     fn pack_into_slice(&self, dst: &mut [u8]) {
-        dst.copy_from_slice(self.key.as_ref());
+        let dst = array_mut_ref![dst, 0, Signer::LEN];
+        let (key_dst, capabilities_dst) = mut_array_refs![dst, PUBKEY_BYTES, 1];
+        key_dst.copy_from_slice(self.key.as_ref());
+        capabilities_dst[0] = self.capabilities.to_u8();
     }
 
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
         let src = array_ref![src, 0, Signer::LEN];
+        let (key, capabilities) = array_refs![src, PUBKEY_BYTES, 1];
         Ok(Signer {
-            key: Pubkey::new_from_array(*src),
+            key: Pubkey::new_from_array(*key),
+            capabilities: SignerCapabilities::from_u8(capabilities[0])?,
         })
     }
 }
@@ -0,0 +1,157 @@
+use crate::error::WalletError;
+use crate::model::signer::Signer;
+use crate::model::wallet::Wallet;
+use crate::utils::{duration_secs, remap_slots, SlotId, SlotRemap};
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use std::time::Duration;
+
+/// When each signer slot's signer may start initiating ops and counting toward quorums.
+/// Signers added to a wallet after its creation wait out its activation delay, so that a
+/// compromised quorum can't add keys of its own and use them right away.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct SignerActivations {
+    /// How long a signer added to the wallet waits before it is active.
+    pub delay: Duration,
+    /// The delay in effect before it was last shortened, which still applies to signers added
+    /// until it would have elapsed; otherwise shortening the delay and adding a signer would
+    /// take no longer than adding a signer without a delay.
+    pub superseded_delay: Duration,
+    pub superseded_delay_until: i64,
+    /// Unix timestamp from which each slot's signer is active, 0 for signers active from the
+    /// start.
+    activates_at: [i64; Wallet::MAX_SIGNERS],
+}
+
+impl SignerActivations {
+    pub const MAX_DELAY: Duration = Duration::from_secs(60 * 60 * 24 * 30);
+    pub const LEN: usize = 8 + // delay
+        8 + // superseded delay
+        8 + // superseded delay until
+        8 * Wallet::MAX_SIGNERS; // activates at
+
+    /// The delay a signer added now waits.
+    pub fn effective_delay(&self, now: i64) -> Duration {
+        if now < self.superseded_delay_until {
+            self.delay.max(self.superseded_delay)
+        } else {
+            self.delay
+        }
+    }
+
+    pub fn validate_delay(delay: &Duration) -> ProgramResult {
+        if *delay > SignerActivations::MAX_DELAY {
+            msg!(
+                "Signer activation delay can't exceed {} seconds",
+                SignerActivations::MAX_DELAY.as_secs()
+            );
+            return Err(WalletError::InvalidSignerActivationDelay.into());
+        }
+        Ok(())
+    }
+
+    pub fn update_delay(&mut self, delay: Duration, now: i64) -> ProgramResult {
+        SignerActivations::validate_delay(&delay)?;
+        let effective_delay = self.effective_delay(now);
+        if delay < effective_delay {
+            self.superseded_delay = effective_delay;
+            self.superseded_delay_until = now.saturating_add(duration_secs(effective_delay));
+        }
+        self.delay = delay;
+        Ok(())
+    }
+
+    pub fn activates_at(&self, slot_id: SlotId<Signer>) -> i64 {
+        self.activates_at[slot_id.value]
+    }
+
+    pub fn is_active(&self, slot_id: SlotId<Signer>, now: i64) -> bool {
+        now >= self.activates_at(slot_id)
+    }
+
+    pub fn signer_added(&mut self, slot_id: SlotId<Signer>, now: i64) {
+        self.activates_at[slot_id.value] =
+            now.saturating_add(duration_secs(self.effective_delay(now)));
+    }
+
+    pub fn signer_removed(&mut self, slot_id: SlotId<Signer>) {
+        self.activates_at[slot_id.value] = 0;
+    }
+
+    pub fn remap(&mut self, remap: &SlotRemap<Signer>) {
+        remap_slots(&mut self.activates_at, remap);
+    }
+
+    pub fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, SignerActivations::LEN];
+        let (delay_dst, superseded_delay_dst, superseded_delay_until_dst, activates_at_dst) =
+            mut_array_refs![dst, 8, 8, 8, 8 * Wallet::MAX_SIGNERS];
+        *delay_dst = self.delay.as_secs().to_le_bytes();
+        *superseded_delay_dst = self.superseded_delay.as_secs().to_le_bytes();
+        *superseded_delay_until_dst = self.superseded_delay_until.to_le_bytes();
+        for (activates_at, activates_at_dst) in self
+            .activates_at
+            .iter()
+            .zip(activates_at_dst.chunks_exact_mut(8))
+        {
+            activates_at_dst.copy_from_slice(&activates_at.to_le_bytes());
+        }
+    }
+
+    pub fn unpack_from_slice(src: &[u8]) -> SignerActivations {
+        let src = array_ref![src, 0, SignerActivations::LEN];
+        let (delay, superseded_delay, superseded_delay_until, activates_at_src) =
+            array_refs![src, 8, 8, 8, 8 * Wallet::MAX_SIGNERS];
+        let mut activates_at = [0; Wallet::MAX_SIGNERS];
+        for (activates_at, activates_at_src) in activates_at
+            .iter_mut()
+            .zip(activates_at_src.chunks_exact(8))
+        {
+            *activates_at = i64::from_le_bytes(*array_ref![activates_at_src, 0, 8]);
+        }
+        SignerActivations {
+            delay: Duration::from_secs(u64::from_le_bytes(*delay)),
+            superseded_delay: Duration::from_secs(u64::from_le_bytes(*superseded_delay)),
+            superseded_delay_until: i64::from_le_bytes(*superseded_delay_until),
+            activates_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::model::signer_activations::SignerActivations;
+    use crate::utils::SlotId;
+    use std::time::Duration;
+
+    #[test]
+    fn test_signer_activations() {
+        let mut activations = SignerActivations::default();
+        activations
+            .update_delay(Duration::from_secs(100), 0)
+            .unwrap();
+        activations.signer_added(SlotId::new(1), 1000);
+        assert!(activations.is_active(SlotId::new(0), 1000));
+        assert!(!activations.is_active(SlotId::new(1), 1099));
+        assert!(activations.is_active(SlotId::new(1), 1100));
+
+        // a shortened delay applies only once the previous one would have elapsed
+        activations.update_delay(Duration::ZERO, 2000).unwrap();
+        activations.signer_added(SlotId::new(2), 2050);
+        assert_eq!(activations.activates_at(SlotId::new(2)), 2150);
+        activations.signer_added(SlotId::new(3), 2100);
+        assert_eq!(activations.activates_at(SlotId::new(3)), 2100);
+
+        activations.signer_removed(SlotId::new(2));
+        assert!(activations.is_active(SlotId::new(2), 0));
+
+        let mut packed = [0; SignerActivations::LEN];
+        activations.pack_into_slice(&mut packed);
+        assert_eq!(SignerActivations::unpack_from_slice(&packed), activations);
+
+        assert!(activations
+            .update_delay(SignerActivations::MAX_DELAY + Duration::from_secs(1), 0)
+            .is_err());
+    }
+}
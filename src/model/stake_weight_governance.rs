@@ -0,0 +1,127 @@
+use crate::error::WalletError;
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
+
+/// Approval of a wallet's ops by holders of a governance token rather than by its signers, for
+/// DAO treasuries with no fixed set of approvers. Each op is approved (or denied) by the holders
+/// whose tokens were snapshotted as it was initiated, weighted by the tokens they held.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct StakeWeightGovernance {
+    /// The mint of the governance token.
+    pub mint: Pubkey,
+    /// The share of the mint's supply at initiation, in basis points, that has to approve an op.
+    pub approval_threshold_bps: u16,
+}
+
+impl StakeWeightGovernance {
+    pub const LEN: usize = PUBKEY_BYTES + // mint
+        2; // approval threshold
+
+    pub const MAX_BPS: u16 = 10_000;
+
+    pub fn validate(&self) -> ProgramResult {
+        if self.mint == Pubkey::default()
+            || self.approval_threshold_bps == 0
+            || self.approval_threshold_bps > StakeWeightGovernance::MAX_BPS
+        {
+            msg!(
+                "Governance needs a mint and a threshold of 1 to {} basis points",
+                StakeWeightGovernance::MAX_BPS
+            );
+            return Err(WalletError::InvalidStakeWeightGovernance.into());
+        }
+        Ok(())
+    }
+
+    /// The weight that has to approve an op initiated while the mint had the given supply,
+    /// rounded up, and at least one token.
+    pub fn required_weight(&self, supply: u64) -> u64 {
+        let bps = u128::from(self.approval_threshold_bps);
+        let max_bps = u128::from(StakeWeightGovernance::MAX_BPS);
+        let required = (u128::from(supply) * bps + max_bps - 1) / max_bps;
+        (required as u64).max(1)
+    }
+
+    /// Packs the governance, if any; a default mint stands for none.
+    pub fn pack_into_slice(governance: &Option<StakeWeightGovernance>, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, StakeWeightGovernance::LEN];
+        let governance = match governance {
+            Some(governance) => governance,
+            None => {
+                dst.fill(0);
+                return;
+            }
+        };
+        let (mint_dst, approval_threshold_bps_dst) = mut_array_refs![dst, PUBKEY_BYTES, 2];
+        mint_dst.copy_from_slice(governance.mint.as_ref());
+        *approval_threshold_bps_dst = governance.approval_threshold_bps.to_le_bytes();
+    }
+
+    pub fn unpack_from_slice(src: &[u8]) -> Option<StakeWeightGovernance> {
+        let src = array_ref![src, 0, StakeWeightGovernance::LEN];
+        let (mint, approval_threshold_bps) = array_refs![src, PUBKEY_BYTES, 2];
+        let mint = Pubkey::new_from_array(*mint);
+        if mint == Pubkey::default() {
+            return None;
+        }
+        Some(StakeWeightGovernance {
+            mint,
+            approval_threshold_bps: u16::from_le_bytes(*approval_threshold_bps),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::WalletError;
+    use crate::model::stake_weight_governance::StakeWeightGovernance;
+    use solana_program::pubkey::Pubkey;
+
+    #[test]
+    fn test_stake_weight_governance() {
+        let governance = StakeWeightGovernance {
+            mint: Pubkey::new_unique(),
+            approval_threshold_bps: 5_001,
+        };
+        assert_eq!(governance.validate(), Ok(()));
+        assert_eq!(governance.required_weight(10_000), 5_001);
+        assert_eq!(governance.required_weight(3), 2);
+        assert_eq!(governance.required_weight(0), 1);
+        let unanimous = StakeWeightGovernance {
+            approval_threshold_bps: 10_000,
+            ..governance
+        };
+        assert_eq!(unanimous.required_weight(u64::MAX), u64::MAX);
+
+        for invalid in [
+            StakeWeightGovernance {
+                mint: Pubkey::default(),
+                ..governance
+            },
+            StakeWeightGovernance {
+                approval_threshold_bps: 0,
+                ..governance
+            },
+            StakeWeightGovernance {
+                approval_threshold_bps: 10_001,
+                ..governance
+            },
+        ] {
+            assert_eq!(
+                invalid.validate(),
+                Err(WalletError::InvalidStakeWeightGovernance.into())
+            );
+        }
+
+        let mut buffer = [0xff; StakeWeightGovernance::LEN];
+        StakeWeightGovernance::pack_into_slice(&Some(governance), &mut buffer);
+        assert_eq!(
+            StakeWeightGovernance::unpack_from_slice(&buffer),
+            Some(governance)
+        );
+        StakeWeightGovernance::pack_into_slice(&None, &mut buffer);
+        assert_eq!(StakeWeightGovernance::unpack_from_slice(&buffer), None);
+    }
+}
@@ -0,0 +1,103 @@
+use crate::model::balance_account::BalanceAccount;
+use crate::model::wallet::Wallet;
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::hash::hash;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+
+pub type PolicyDigest = [u8; StateSummary::DIGEST_LEN];
+
+/// A compact summary of the wallet's state, packed into a fixed region at the end of the wallet
+/// account each time the wallet is packed, so indexers can tell what changed between two
+/// snapshots without unpacking the whole wallet.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct StateSummary {
+    pub signer_count: u8,
+    /// The config changes held pending in the wallet itself: address book entries awaiting
+    /// approval, plus a staged config policy. This is not a count of pending multisig ops,
+    /// which live in accounts of their own.
+    pub pending_config_change_count: u8,
+    /// A digest of each balance account (its policy, settings and whitelist included) by slot,
+    /// all zeroes for an empty slot.
+    pub balance_account_digests: [PolicyDigest; Wallet::MAX_BALANCE_ACCOUNTS],
+}
+
+impl StateSummary {
+    /// Bumped whenever the summary's layout or what goes into it changes.
+    pub const VERSION: u8 = 1;
+    pub const DIGEST_LEN: usize = 8;
+    pub const LEN: usize = 1 + // version
+        1 + // signer count
+        1 + // pending config change count
+        StateSummary::DIGEST_LEN * Wallet::MAX_BALANCE_ACCOUNTS; // balance account digests
+
+    pub fn of(wallet: &Wallet) -> StateSummary {
+        let mut balance_account_digests =
+            [[0; StateSummary::DIGEST_LEN]; Wallet::MAX_BALANCE_ACCOUNTS];
+        for (slot_id, balance_account) in wallet.balance_accounts.filled_slots() {
+            balance_account_digests[slot_id.value] = StateSummary::digest(&balance_account);
+        }
+        StateSummary {
+            signer_count: wallet.signers.filled_slots().len() as u8,
+            pending_config_change_count: (wallet.pending_address_book_entries.count_enabled()
+                + usize::from(wallet.pending_config_policy.is_some()))
+                as u8,
+            balance_account_digests,
+        }
+    }
+
+    fn digest(balance_account: &BalanceAccount) -> PolicyDigest {
+        let mut buf = [0; BalanceAccount::LEN];
+        balance_account.pack_into_slice(&mut buf);
+        let hash = hash(&buf).to_bytes();
+        *array_ref![hash, 0, StateSummary::DIGEST_LEN]
+    }
+
+    pub fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, StateSummary::LEN];
+        let (version_dst, signer_count_dst, pending_config_change_count_dst, digests_dst) = mut_array_refs![
+            dst,
+            1,
+            1,
+            1,
+            StateSummary::DIGEST_LEN * Wallet::MAX_BALANCE_ACCOUNTS
+        ];
+        version_dst[0] = StateSummary::VERSION;
+        signer_count_dst[0] = self.signer_count;
+        pending_config_change_count_dst[0] = self.pending_config_change_count;
+        for (digest, digest_dst) in self
+            .balance_account_digests
+            .iter()
+            .zip(digests_dst.chunks_exact_mut(StateSummary::DIGEST_LEN))
+        {
+            digest_dst.copy_from_slice(digest);
+        }
+    }
+
+    pub fn unpack_from_slice(src: &[u8]) -> Result<StateSummary, ProgramError> {
+        let src = array_ref![src, 0, StateSummary::LEN];
+        let (version, signer_count, pending_config_change_count, digests_src) = array_refs![
+            src,
+            1,
+            1,
+            1,
+            StateSummary::DIGEST_LEN * Wallet::MAX_BALANCE_ACCOUNTS
+        ];
+        if version[0] != StateSummary::VERSION {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut balance_account_digests =
+            [[0; StateSummary::DIGEST_LEN]; Wallet::MAX_BALANCE_ACCOUNTS];
+        for (digest, digest_src) in balance_account_digests
+            .iter_mut()
+            .zip(digests_src.chunks_exact(StateSummary::DIGEST_LEN))
+        {
+            digest.copy_from_slice(digest_src);
+        }
+        Ok(StateSummary {
+            signer_count: signer_count[0],
+            pending_config_change_count: pending_config_change_count[0],
+            balance_account_digests,
+        })
+    }
+}
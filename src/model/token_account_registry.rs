@@ -0,0 +1,171 @@
+use crate::constants::{HASH_LEN, PUBKEY_BYTES};
+use crate::error::WalletError;
+use crate::model::balance_account::BalanceAccountGuidHash;
+use crate::pda::{PdaSeeds, TokenAccountRegistrySeeds};
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{IsInitialized, Pack, Sealed};
+use solana_program::pubkey::Pubkey;
+
+/// An SPL token account owned by a balance account, along with its mint, and whether it was
+/// frozen by the mint's freeze authority when it was last registered.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct RegisteredTokenAccount {
+    pub token_mint: Pubkey,
+    pub token_account: Pubkey,
+    pub frozen: bool,
+}
+
+impl RegisteredTokenAccount {
+    pub const LEN: usize = PUBKEY_BYTES + PUBKEY_BYTES + 1;
+}
+
+/// The SPL token accounts of a balance account, in the order they were registered. It lives in
+/// a PDA derived from the wallet and the balance account's GUID hash, so clients get an
+/// authoritative list without scanning token accounts by owner, and is created by the first
+/// registration.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TokenAccountRegistry {
+    pub is_initialized: bool,
+    pub wallet: Pubkey,
+    pub account_guid_hash: BalanceAccountGuidHash,
+    pub token_accounts: Vec<RegisteredTokenAccount>,
+}
+
+impl TokenAccountRegistry {
+    pub const MAX_TOKEN_ACCOUNTS: usize = 32;
+
+    pub fn new(wallet: Pubkey, account_guid_hash: BalanceAccountGuidHash) -> Self {
+        TokenAccountRegistry {
+            is_initialized: true,
+            wallet,
+            account_guid_hash,
+            token_accounts: Vec::new(),
+        }
+    }
+
+    /// Derive the PDA and "bump seed" of the registry for the given wallet and balance account.
+    pub fn find_address(
+        wallet: &Pubkey,
+        account_guid_hash: &BalanceAccountGuidHash,
+        program_id: &Pubkey,
+    ) -> (Pubkey, u8) {
+        TokenAccountRegistrySeeds {
+            wallet,
+            account_guid_hash,
+        }
+        .find_address(program_id)
+    }
+
+    /// Adds the token account, or refreshes its freeze state if it is registered already.
+    pub fn register(&mut self, token_account: RegisteredTokenAccount) -> ProgramResult {
+        if let Some(registered) = self
+            .token_accounts
+            .iter_mut()
+            .find(|it| it.token_account == token_account.token_account)
+        {
+            registered.frozen = token_account.frozen;
+            return Ok(());
+        }
+        if self.token_accounts.len() == TokenAccountRegistry::MAX_TOKEN_ACCOUNTS {
+            msg!(
+                "A balance account can have at most {} registered token accounts",
+                TokenAccountRegistry::MAX_TOKEN_ACCOUNTS
+            );
+            return Err(WalletError::TokenAccountRegistryFull.into());
+        }
+        self.token_accounts.push(token_account);
+        Ok(())
+    }
+
+    pub fn token_accounts_for_mint(&self, token_mint: &Pubkey) -> Vec<Pubkey> {
+        self.token_accounts
+            .iter()
+            .filter(|it| it.token_mint == *token_mint)
+            .map(|it| it.token_account)
+            .collect()
+    }
+}
+
+impl Sealed for TokenAccountRegistry {}
+
+impl IsInitialized for TokenAccountRegistry {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for TokenAccountRegistry {
+    const LEN: usize = 1 + // is_initialized
+        PUBKEY_BYTES + // wallet
+        HASH_LEN + // account_guid_hash
+        1 + // token account count
+        RegisteredTokenAccount::LEN * TokenAccountRegistry::MAX_TOKEN_ACCOUNTS;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, TokenAccountRegistry::LEN];
+        let (is_initialized_dst, wallet_dst, account_guid_hash_dst, count_dst, token_accounts_dst) = mut_array_refs![
+            dst,
+            1,
+            PUBKEY_BYTES,
+            HASH_LEN,
+            1,
+            RegisteredTokenAccount::LEN * TokenAccountRegistry::MAX_TOKEN_ACCOUNTS
+        ];
+
+        is_initialized_dst[0] = self.is_initialized as u8;
+        wallet_dst.copy_from_slice(self.wallet.as_ref());
+        account_guid_hash_dst.copy_from_slice(self.account_guid_hash.to_bytes());
+        count_dst[0] = self.token_accounts.len() as u8;
+        token_accounts_dst.fill(0);
+        for (token_account, token_account_dst) in self
+            .token_accounts
+            .iter()
+            .zip(token_accounts_dst.chunks_exact_mut(RegisteredTokenAccount::LEN))
+        {
+            token_account_dst[..PUBKEY_BYTES].copy_from_slice(token_account.token_mint.as_ref());
+            token_account_dst[PUBKEY_BYTES..PUBKEY_BYTES * 2]
+                .copy_from_slice(token_account.token_account.as_ref());
+            token_account_dst[PUBKEY_BYTES * 2] = token_account.frozen as u8;
+        }
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, TokenAccountRegistry::LEN];
+        let (is_initialized_src, wallet_src, account_guid_hash_src, count_src, token_accounts_src) = array_refs![
+            src,
+            1,
+            PUBKEY_BYTES,
+            HASH_LEN,
+            1,
+            RegisteredTokenAccount::LEN * TokenAccountRegistry::MAX_TOKEN_ACCOUNTS
+        ];
+
+        let is_initialized = match is_initialized_src {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        let count = usize::from(count_src[0]);
+        if count > TokenAccountRegistry::MAX_TOKEN_ACCOUNTS {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(TokenAccountRegistry {
+            is_initialized,
+            wallet: Pubkey::new_from_array(*wallet_src),
+            account_guid_hash: BalanceAccountGuidHash::new(account_guid_hash_src),
+            token_accounts: token_accounts_src
+                .chunks_exact(RegisteredTokenAccount::LEN)
+                .take(count)
+                .map(|src| RegisteredTokenAccount {
+                    token_mint: Pubkey::new(&src[..PUBKEY_BYTES]),
+                    token_account: Pubkey::new(&src[PUBKEY_BYTES..PUBKEY_BYTES * 2]),
+                    frozen: src[PUBKEY_BYTES * 2] == 1,
+                })
+                .collect(),
+        })
+    }
+}
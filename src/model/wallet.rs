@@ -1,19 +1,47 @@
 use crate::constants::{HASH_LEN, VERSION_LEN};
-use crate::error::WalletError;
+use crate::error::{strike_err, WalletError};
+#[cfg(feature = "demo")]
+use crate::instruction::DemoBalanceAccount;
 use crate::instruction::{
-    AddressBookUpdate, BalanceAccountAddressWhitelistUpdate, BalanceAccountCreation,
-    BalanceAccountPolicyUpdate, DAppBookUpdate, InitialWalletConfig, WalletConfigPolicyUpdate,
+    AddressBookExpiration, AddressBookImport, AddressBookUpdate,
+    BalanceAccountAddressWhitelistUpdate, BalanceAccountCreation, BalanceAccountPolicyUpdate,
+    DAppBookUpdate, DAppExpiration, FullRotation, InitialWalletConfig, RewardSplit,
+    WalletConfigPolicyUpdate,
 };
 use crate::model::address_book::{
-    AddressBook, AddressBookEntry, AddressBookEntryNameHash, DAppBook, DAppBookEntry,
+    AddressBook, AddressBookEntry, AddressBookEntryNameHash, AddressBookEntryRisk,
+    AddressBookExpirations, AddressBookRisks, AddressBookUsage, AllowedDestinationsPage, DAppBook,
+    DAppBookCategories, DAppBookEntry, DAppBookExpirations, DAppBookInstructionLimits,
+    DAppCategories, JurisdictionCode,
 };
+use crate::model::approval_group::ApprovalGroup;
+use crate::model::assistant::Assistant;
 use crate::model::balance_account::{
-    AllowedDestinations, BalanceAccount, BalanceAccountGuidHash, BalanceAccountNameHash,
+    AllowedDestinations, ApprovalTier, ApprovalTiers, BalanceAccount, BalanceAccountGuidHash,
+    BalanceAccountNameHash, FinalizeDelayPolicy, RewardSplitPolicy, SpendingLimit,
 };
-use crate::model::multisig_op::BooleanSetting;
-use crate::model::signer::Signer;
-use crate::utils::{GetSlotIds, SlotFlags, SlotId, Slots};
-use crate::version::Versioned;
+use crate::model::compliance_policy::CompliancePolicy;
+use crate::model::dapp_multisig_data::MAX_INSTRUCTION_COUNT;
+use crate::model::fee_policy::FeePolicy;
+use crate::model::finalize_submitter_policy::FinalizeSubmitterPolicy;
+use crate::model::multisig_op::{BooleanSetting, SlotUpdateType};
+use crate::model::pending_config_policy::PendingConfigPolicy;
+use crate::model::policy_diff::{PolicyDiff, PolicySummary};
+use crate::model::price_oracle::PriceOracle;
+use crate::model::rent_return_allowlist::RentReturnAllowlist;
+use crate::model::retired_signers::RetiredSigners;
+use crate::model::signer::{Signer, SignerCapabilities};
+use crate::model::signer_activations::SignerActivations;
+use crate::model::stake_weight_governance::StakeWeightGovernance;
+use crate::model::state_summary::StateSummary;
+use crate::model::wallet_freeze::WalletFreeze;
+use crate::model::wallet_provisioning::WalletProvisioning;
+use crate::model::wallet_replication::{MirroredUpdate, WalletReplication};
+use crate::model::wrapper_book::WrapperBook;
+use crate::utils::{
+    duration_secs, is_strictly_ascending_by_key, GetSlotIds, SlotFlags, SlotId, SlotRemap, Slots,
+};
+use crate::version::{Versioned, VERSION};
 use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
 use itertools::Itertools;
 use solana_program::account_info::AccountInfo;
@@ -26,8 +54,11 @@ use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
 use std::time::Duration;
 
 pub type Signers = Slots<Signer, { Wallet::MAX_SIGNERS }>;
+pub type Assistants = Slots<Assistant, { Wallet::MAX_ASSISTANTS }>;
 pub type Approvers = SlotFlags<Signer, { Signers::FLAGS_STORAGE_SIZE }>;
 pub type BalanceAccounts = Slots<BalanceAccount, { Wallet::MAX_BALANCE_ACCOUNTS }>;
+pub type PendingAddressBookEntries =
+    SlotFlags<AddressBookEntry, { AddressBook::FLAGS_STORAGE_SIZE }>;
 
 #[derive(Debug, Clone, Eq, PartialEq, Copy, Ord, PartialOrd)]
 pub struct WalletGuidHash([u8; HASH_LEN]);
@@ -46,6 +77,52 @@ impl WalletGuidHash {
     }
 }
 
+/// The slot moves compacting a wallet makes, packing its signers, address book entries and
+/// dapp book entries into their lowest slots, in the order they are in.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct WalletCompaction {
+    pub signers: SlotRemap<Signer>,
+    pub address_book: SlotRemap<AddressBookEntry>,
+    pub dapp_book: SlotRemap<DAppBookEntry>,
+}
+
+impl WalletCompaction {
+    pub fn is_empty(&self) -> bool {
+        self.signers.is_empty() && self.address_book.is_empty() && self.dapp_book.is_empty()
+    }
+
+    /// Appends the canonical encoding of the remap tables: for each, the number of moves
+    /// followed by the slot each move is from and the slot it is to, a byte each.
+    pub fn pack(&self, dst: &mut Vec<u8>) {
+        fn append_remap<A>(remap: &SlotRemap<A>, dst: &mut Vec<u8>) {
+            dst.push(remap.len() as u8);
+            for (from, to) in remap {
+                dst.push(from.value as u8);
+                dst.push(to.value as u8);
+            }
+        }
+        append_remap(&self.signers, dst);
+        append_remap(&self.address_book, dst);
+        append_remap(&self.dapp_book, dst);
+    }
+
+    /// Logs the remap tables, so that indexers can follow the entries to their new slots.
+    pub fn log(&self) {
+        fn moves<A>(remap: &SlotRemap<A>) -> String {
+            remap
+                .iter()
+                .map(|(from, to)| format!("{}->{}", from.value, to.value))
+                .join(",")
+        }
+        msg!(
+            "Wallet compaction: signers [{}] address book [{}] dapp book [{}]",
+            moves(&self.signers),
+            moves(&self.address_book),
+            moves(&self.dapp_book)
+        );
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Wallet {
     pub is_initialized: bool,
@@ -53,13 +130,61 @@ pub struct Wallet {
     pub rent_return: Pubkey,
     pub wallet_guid_hash: WalletGuidHash,
     pub signers: Signers,
-    pub assistant: Signer,
+    pub assistants: Assistants,
     pub address_book: AddressBook,
+    pub pending_address_book_entries: PendingAddressBookEntries,
+    /// Unix timestamp at which the current pending address book entries window started.
+    pub pending_address_book_window_start: i64,
+    pub pending_address_book_entries_in_window: u8,
+    pub address_book_usage: AddressBookUsage,
     pub approvals_required_for_config: u8,
     pub approval_timeout_for_config: Duration,
     pub config_approvers: Approvers,
+    /// How long a config policy update that weakens the current policy is staged for.
+    pub config_policy_timelock: Duration,
+    pub pending_config_policy: Option<PendingConfigPolicy>,
     pub balance_accounts: BalanceAccounts,
     pub dapp_book: DAppBook,
+    pub dapp_book_expirations: DAppBookExpirations,
+    /// Who may submit the finalize transactions of each category of operations.
+    pub finalize_submitter_policy: FinalizeSubmitterPolicy,
+    /// The compliance service counter-signing large transfers, if any.
+    pub compliance_policy: Option<CompliancePolicy>,
+    /// The wallet's part in keeping a hot-standby wallet in sync, if any.
+    pub replication: Option<WalletReplication>,
+    /// The price feeds transfers are valued with when the wallet's thresholds are in USD.
+    pub price_oracle: Option<PriceOracle>,
+    /// Whether the wallet is frozen, and the quorum of signers that can freeze it at once.
+    pub freeze: WalletFreeze,
+    /// The accounts the rent of finalized multisig ops may be returned to.
+    pub rent_return_allowlist: RentReturnAllowlist,
+    /// The delay before signers added to the wallet are active, and when each slot's is.
+    pub signer_activations: SignerActivations,
+    /// The cap on the fees multisig ops may collect.
+    pub fee_policy: FeePolicy,
+    /// The governance token whose holders approve the wallet's ops instead of its signers, if
+    /// the wallet is stake weight governed.
+    pub stake_weight_governance: Option<StakeWeightGovernance>,
+    /// The risk classification of each address book entry, by slot.
+    pub address_book_risks: AddressBookRisks,
+    /// When each address book entry lapses, by slot.
+    pub address_book_expirations: AddressBookExpirations,
+    /// The categories each dapp book entry is tagged with, by slot.
+    pub dapp_book_categories: DAppBookCategories,
+    /// The most instructions a dapp transaction with each dapp book entry may have, by slot.
+    pub dapp_book_instruction_limits: DAppBookInstructionLimits,
+    /// Bumped by every config op that is finalized, so that config ops started against an
+    /// older wallet state can't be finalized.
+    pub op_nonce: u64,
+    /// The group of approvers whose aggregated threshold signatures approve ops in one go, if
+    /// any.
+    pub approval_group: Option<ApprovalGroup>,
+    /// The wallet's creator, while the wallet is still being provisioned.
+    pub provisioning: Option<WalletProvisioning>,
+    /// The mints besides wrapped SOL that balance accounts can wrap into, with their adapters.
+    pub wrapper_book: WrapperBook,
+    /// The key each signer slot held before its signer was last removed.
+    pub retired_signers: RetiredSigners,
 }
 
 impl Sealed for Wallet {}
@@ -73,10 +198,56 @@ impl IsInitialized for Wallet {
 impl Wallet {
     pub const MAX_BALANCE_ACCOUNTS: usize = 9;
     pub const MAX_SIGNERS: usize = 24;
+    pub const MAX_ASSISTANTS: usize = 4;
     pub const MAX_ADDRESS_BOOK_ENTRIES: usize = 88;
     pub const MIN_APPROVAL_TIMEOUT: Duration = Duration::from_secs(60);
     pub const MAX_APPROVAL_TIMEOUT: Duration = Duration::from_secs(60 * 60 * 24 * 365);
     pub const MAX_DAPP_BOOK_ENTRIES: usize = 20;
+    pub const MAX_PENDING_ADDRESS_BOOK_ENTRIES: usize = 8;
+    pub const MAX_PENDING_ADDRESS_BOOK_ENTRIES_PER_WINDOW: usize = 8;
+    pub const PENDING_ADDRESS_BOOK_WINDOW_SECS: i64 = 60 * 60 * 24;
+    pub const DEFAULT_CONFIG_POLICY_TIMELOCK: Duration = Duration::from_secs(60 * 60 * 24);
+    pub const MAX_CONFIG_POLICY_TIMELOCK: Duration = Duration::from_secs(60 * 60 * 24 * 30);
+
+    // offsets of the fields read straight from account data, without unpacking the wallet
+    const VERSION_OFFSET: usize = 1;
+    const RENT_RETURN_OFFSET: usize = Wallet::VERSION_OFFSET + VERSION_LEN;
+    const WALLET_GUID_HASH_OFFSET: usize = Wallet::RENT_RETURN_OFFSET + PUBKEY_BYTES;
+    // the offsets past the variable-length fields are counted from the front of the layout, so
+    // that fields added after them, ahead of the state summary, don't shift them
+    const FINALIZE_SUBMITTER_POLICY_OFFSET: usize = Wallet::WALLET_GUID_HASH_OFFSET
+        + HASH_LEN
+        + Signers::LEN
+        + Assistants::LEN
+        + AddressBook::LEN
+        + PendingAddressBookEntries::STORAGE_SIZE
+        + 8 // pending_address_book_window_start
+        + 1 // pending_address_book_entries_in_window
+        + AddressBookUsage::LEN
+        + 1 // approvals_required_for_config
+        + 8 // approval_timeout_for_config
+        + Approvers::STORAGE_SIZE
+        + 8 // config_policy_timelock
+        + PendingConfigPolicy::LEN
+        + DAppBook::LEN
+        + BalanceAccounts::LEN
+        + DAppBookExpirations::LEN;
+    const RENT_RETURN_ALLOWLIST_OFFSET: usize = Wallet::FINALIZE_SUBMITTER_POLICY_OFFSET
+        + FinalizeSubmitterPolicy::LEN
+        + CompliancePolicy::LEN
+        + WalletReplication::LEN
+        + PriceOracle::LEN
+        + WalletFreeze::LEN;
+    const FEE_POLICY_OFFSET: usize =
+        Wallet::RENT_RETURN_ALLOWLIST_OFFSET + RentReturnAllowlist::LEN + SignerActivations::LEN;
+    const OP_NONCE_OFFSET: usize = Wallet::FEE_POLICY_OFFSET
+        + FeePolicy::LEN
+        + StakeWeightGovernance::LEN
+        + AddressBookRisks::LEN
+        + AddressBookExpirations::LEN
+        + DAppBookCategories::LEN
+        + DAppBookInstructionLimits::LEN;
+    const STATE_SUMMARY_OFFSET: usize = Wallet::LEN - StateSummary::LEN;
 
     pub fn get_signers_keys(&self) -> Vec<Pubkey> {
         return self
@@ -88,20 +259,165 @@ impl Wallet {
     }
 
     pub fn get_config_approvers_keys(&self) -> Vec<Pubkey> {
-        self.get_approvers_keys(&self.config_approvers)
+        self.get_approvers_keys(&self.config_approvers, SignerCapabilities::APPROVE_CONFIG)
     }
 
     pub fn get_transfer_approvers_keys(&self, balance_account: &BalanceAccount) -> Vec<Pubkey> {
-        self.get_approvers_keys(&balance_account.transfer_approvers)
+        self.get_approvers_keys(
+            &balance_account.transfer_approvers,
+            SignerCapabilities::APPROVE_TRANSFER,
+        )
+    }
+
+    /// The config approvers that count toward quorums: those whose activation delay elapsed.
+    pub fn get_active_config_approvers_keys(&self, now: i64) -> Vec<Pubkey> {
+        self.get_active_approvers_keys(
+            &self.config_approvers,
+            SignerCapabilities::APPROVE_CONFIG,
+            now,
+        )
+    }
+
+    /// The transfer approvers that count toward quorums: those whose activation delay elapsed.
+    pub fn get_active_transfer_approvers_keys(
+        &self,
+        balance_account: &BalanceAccount,
+        now: i64,
+    ) -> Vec<Pubkey> {
+        self.get_active_approvers_keys(
+            &balance_account.transfer_approvers,
+            SignerCapabilities::APPROVE_TRANSFER,
+            now,
+        )
+    }
+
+    /// The active approvers of a balance account's approval tier that can approve transfers.
+    pub fn get_active_tier_approvers_keys(&self, tier: &ApprovalTier, now: i64) -> Vec<Pubkey> {
+        self.get_active_approvers_keys(&tier.approvers, SignerCapabilities::APPROVE_TRANSFER, now)
+    }
+
+    fn get_approvers_keys(
+        &self,
+        approvers: &Approvers,
+        capability: SignerCapabilities,
+    ) -> Vec<Pubkey> {
+        approvers
+            .iter_enabled()
+            .filter_map(|r| self.signers[r])
+            .filter(|signer| signer.can(capability))
+            .map(|signer| signer.key)
+            .collect_vec()
     }
 
-    fn get_approvers_keys(&self, approvers: &Approvers) -> Vec<Pubkey> {
+    fn get_active_approvers_keys(
+        &self,
+        approvers: &Approvers,
+        capability: SignerCapabilities,
+        now: i64,
+    ) -> Vec<Pubkey> {
         approvers
             .iter_enabled()
-            .filter_map(|r| self.signers[r].map(|signer| signer.key))
+            .filter(|r| self.signer_activations.is_active(*r, now))
+            .filter_map(|r| self.signers[r])
+            .filter(|signer| signer.can(capability))
+            .map(|signer| signer.key)
             .collect_vec()
     }
 
+    pub fn get_signer(&self, key: &Pubkey) -> Option<(SlotId<Signer>, Signer)> {
+        self.signers.find_by(|signer| signer.key == *key)
+    }
+
+    pub fn get_assistant(&self, key: &Pubkey) -> Option<(SlotId<Assistant>, Assistant)> {
+        self.assistants.find_by(|assistant| assistant.key == *key)
+    }
+
+    pub fn is_enabled_assistant(&self, key: &Pubkey) -> bool {
+        match self.get_assistant(key) {
+            Some((_, assistant)) => assistant.enabled,
+            None => false,
+        }
+    }
+
+    /// Counts an initiation against the rate limit of the initiating assistant, if any.
+    /// Returns whether the wallet was updated and needs to be written back.
+    pub fn record_assistant_initiation(
+        &mut self,
+        initiator: &Pubkey,
+        now: i64,
+    ) -> Result<bool, ProgramError> {
+        match self.get_assistant(initiator) {
+            Some((slot_id, mut assistant)) if assistant.is_rate_limited() => {
+                assistant.record_initiation(now)?;
+                self.assistants.replace(slot_id, assistant);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    pub fn validate_update_assistant(
+        &self,
+        slot_update_type: SlotUpdateType,
+        slot_id: SlotId<Assistant>,
+        assistant: Assistant,
+    ) -> ProgramResult {
+        let mut self_clone = self.clone();
+        self_clone.update_assistant(slot_update_type, slot_id, assistant)
+    }
+
+    /// Setting a slot which already holds the same key updates its enable flag and rate limit.
+    pub fn update_assistant(
+        &mut self,
+        slot_update_type: SlotUpdateType,
+        slot_id: SlotId<Assistant>,
+        assistant: Assistant,
+    ) -> ProgramResult {
+        if slot_id.value >= Wallet::MAX_ASSISTANTS {
+            msg!("Assistant slot {} is out of range", slot_id.value);
+            return Err(WalletError::SlotCannotBeInserted.into());
+        }
+        match (slot_update_type, self.assistants[slot_id]) {
+            (SlotUpdateType::SetIfEmpty, Some(existing)) if existing.key == assistant.key => {
+                self.assistants
+                    .replace(slot_id, existing.reconfigured(&assistant));
+            }
+            (SlotUpdateType::SetIfEmpty, None) => {
+                if self.get_assistant(&assistant.key).is_some() {
+                    msg!("Key is already an assistant of this wallet");
+                    return Err(WalletError::SlotCannotBeInserted.into());
+                }
+                self.assistants.insert(
+                    slot_id,
+                    Assistant::with_rate_limit(
+                        assistant.key,
+                        assistant.enabled,
+                        assistant.max_initiations_per_window,
+                    ),
+                );
+            }
+            (SlotUpdateType::SetIfEmpty, Some(_)) => {
+                msg!("Assistant slot {} is already taken", slot_id.value);
+                return Err(WalletError::SlotCannotBeInserted.into());
+            }
+            (SlotUpdateType::Clear, Some(existing)) if existing.key == assistant.key => {
+                self.assistants.remove(slot_id, existing);
+            }
+            (SlotUpdateType::Clear, _) => {
+                msg!(
+                    "Assistant slot {} does not hold the given key",
+                    slot_id.value
+                );
+                return Err(WalletError::SlotCannotBeRemoved.into());
+            }
+            (SlotUpdateType::Replace, _) => {
+                msg!("Assistant slots are cleared and set rather than replaced");
+                return Err(WalletError::SlotCannotBeInserted.into());
+            }
+        }
+        Ok(())
+    }
+
     pub fn get_allowed_destinations(
         &self,
         balance_account: &BalanceAccount,
@@ -113,6 +429,36 @@ impl Wallet {
             .collect_vec()
     }
 
+    /// A page of the balance account's allowed destinations, starting from the given address
+    /// book slot.
+    pub fn get_allowed_destinations_page(
+        &self,
+        account_guid_hash: &BalanceAccountGuidHash,
+        start_slot_id: SlotId<AddressBookEntry>,
+    ) -> Result<AllowedDestinationsPage, ProgramError> {
+        let balance_account = self.get_balance_account(account_guid_hash)?;
+        Ok(AllowedDestinationsPage {
+            entries: balance_account
+                .allowed_destinations
+                .iter_enabled()
+                .filter(|id| id.value >= start_slot_id.value)
+                .filter_map(|id| self.address_book[id].map(|entry| (id, entry)))
+                .take(AllowedDestinationsPage::MAX_ENTRIES)
+                .collect_vec(),
+        })
+    }
+
+    /// Resolves the allowed destinations of a balance account from the wallet account's data,
+    /// for clients that have fetched the account rather than calling `GetAllowedDestinations`.
+    pub fn allowed_destinations_from_slice(
+        src: &[u8],
+        account_guid_hash: &BalanceAccountGuidHash,
+    ) -> Result<Vec<AddressBookEntry>, ProgramError> {
+        let wallet = Wallet::unpack(src)?;
+        let balance_account = wallet.get_balance_account(account_guid_hash)?;
+        Ok(wallet.get_allowed_destinations(&balance_account))
+    }
+
     pub fn get_balance_account(
         &self,
         account_guid_hash: &BalanceAccountGuidHash,
@@ -121,6 +467,20 @@ impl Wallet {
             .map(|(_, balance_account)| balance_account)
     }
 
+    /// Like `get_balance_account`, but fails for an archived balance account, against which
+    /// no new operations can be started.
+    pub fn get_active_balance_account(
+        &self,
+        account_guid_hash: &BalanceAccountGuidHash,
+    ) -> Result<BalanceAccount, ProgramError> {
+        let balance_account = self.get_balance_account(account_guid_hash)?;
+        if balance_account.is_archived() {
+            msg!("Balance account is archived");
+            return Err(WalletError::BalanceAccountArchived.into());
+        }
+        Ok(balance_account)
+    }
+
     pub fn get_balance_account_with_slot_id(
         &self,
         account_guid_hash: &BalanceAccountGuidHash,
@@ -141,12 +501,120 @@ impl Wallet {
         }
     }
 
-    pub fn validate_config_initiator(&self, initiator: &AccountInfo) -> ProgramResult {
-        return self.validate_initiator(initiator, || self.get_signers_keys());
+    /// Verify that the splits name distinct balance accounts of this wallet, each receiving a
+    /// non-zero share, and that the shares add up to 100 percent.
+    pub fn validate_reward_splits(&self, splits: &[RewardSplit]) -> ProgramResult {
+        if splits.is_empty() {
+            msg!("At least one reward split is required");
+            return Err(WalletError::InvalidRewardSplit.into());
+        }
+        if !splits
+            .iter()
+            .map(|it| it.account_guid_hash.to_bytes())
+            .all_unique()
+        {
+            msg!("Reward splits must name distinct balance accounts");
+            return Err(WalletError::InvalidRewardSplit.into());
+        }
+        if splits.iter().any(|it| it.percentage == 0) {
+            msg!("Reward split percentages must be non-zero");
+            return Err(WalletError::InvalidRewardSplit.into());
+        }
+        let total: u32 = splits.iter().map(|it| u32::from(it.percentage)).sum();
+        if total != 100 {
+            msg!(
+                "Reward split percentages add up to {} instead of 100",
+                total
+            );
+            return Err(WalletError::InvalidRewardSplit.into());
+        }
+        for split in splits.iter() {
+            self.validate_balance_account_guid_hash(&split.account_guid_hash)?;
+        }
+        Ok(())
+    }
+
+    pub fn validate_reward_split_policy_update(
+        &self,
+        account_guid_hash: &BalanceAccountGuidHash,
+        splits: &[RewardSplit],
+    ) -> ProgramResult {
+        let mut self_clone = self.clone();
+        self_clone.update_reward_split_policy(account_guid_hash, splits)
+    }
+
+    pub fn update_reward_split_policy(
+        &mut self,
+        account_guid_hash: &BalanceAccountGuidHash,
+        splits: &[RewardSplit],
+    ) -> ProgramResult {
+        self.validate_reward_splits(splits)?;
+        let (slot_id, mut balance_account) =
+            self.get_balance_account_with_slot_id(account_guid_hash)?;
+        let mut reward_split_policy = RewardSplitPolicy::zero();
+        for split in splits.iter() {
+            let (split_slot_id, _) =
+                self.get_balance_account_with_slot_id(&split.account_guid_hash)?;
+            reward_split_policy.set_percentage(split_slot_id, split.percentage);
+        }
+        balance_account.reward_split_policy = reward_split_policy;
+        self.balance_accounts.replace(slot_id, balance_account);
+        Ok(())
+    }
+
+    /// The splits of the balance account's reward split policy, in balance account slot order.
+    pub fn get_reward_splits(
+        &self,
+        balance_account: &BalanceAccount,
+    ) -> Result<Vec<RewardSplit>, ProgramError> {
+        if !balance_account.reward_split_policy.is_set() {
+            msg!("Balance account has no reward split policy");
+            return Err(WalletError::InvalidRewardSplit.into());
+        }
+        Ok(self
+            .balance_accounts
+            .filled_slots()
+            .into_iter()
+            .filter_map(|(slot_id, split_account)| {
+                let percentage = balance_account.reward_split_policy.percentage(slot_id);
+                if percentage > 0 {
+                    Some(RewardSplit {
+                        account_guid_hash: split_account.guid_hash,
+                        percentage,
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect())
+    }
+
+    pub fn validate_config_initiator(&self, initiator: &AccountInfo, now: i64) -> ProgramResult {
+        self.validate_initiator(initiator, now, |signer| {
+            signer.can(SignerCapabilities::INITIATE)
+        })
+    }
+
+    pub fn validate_transfer_initiator(&self, initiator: &AccountInfo, now: i64) -> ProgramResult {
+        self.validate_initiator(initiator, now, |signer| {
+            signer.can(SignerCapabilities::INITIATE)
+        })
     }
 
-    pub fn validate_transfer_initiator(&self, initiator: &AccountInfo) -> ProgramResult {
-        return self.validate_initiator(initiator, || self.get_signers_keys());
+    /// A signer update can also be initiated by a signer resigning from its own slot.
+    pub fn validate_update_signer_initiator(
+        &self,
+        initiator: &AccountInfo,
+        slot_update_type: SlotUpdateType,
+        signer: &Signer,
+        now: i64,
+    ) -> ProgramResult {
+        self.validate_initiator(initiator, now, |initiator_signer| {
+            initiator_signer.can(SignerCapabilities::INITIATE)
+                || (slot_update_type == SlotUpdateType::Clear
+                    && initiator_signer == *signer
+                    && initiator_signer.can(SignerCapabilities::RESIGN))
+        })
     }
 
     /// Validates the state of a wallet.
@@ -171,6 +639,8 @@ impl Wallet {
         Ok(())
     }
 
+    /// Policies can't do without approvals: an op requiring none is approved as soon as it's
+    /// initiated, which would leave the initiator to act alone.
     pub fn validate_approvals_required(approvals_required: u8) -> ProgramResult {
         if approvals_required == 0 {
             msg!("Approvals required can't be 0");
@@ -180,19 +650,40 @@ impl Wallet {
         Ok(())
     }
 
-    fn validate_initiator<F: FnOnce() -> Vec<Pubkey>>(
+    fn validate_initiator<F: FnOnce(Signer) -> bool>(
         &self,
         initiator: &AccountInfo,
-        get_initiators: F,
+        now: i64,
+        may_initiate: F,
     ) -> ProgramResult {
         if !initiator.is_signer {
             return Err(WalletError::InvalidSignature.into());
         }
-        if initiator.key == &self.assistant.key || get_initiators().contains(initiator.key) {
-            Ok(())
-        } else {
-            msg!("Transactions can only be initiated by an authorized account");
-            Err(WalletError::InvalidApprover.into())
+        if let Some((_, assistant)) = self.get_assistant(initiator.key) {
+            return if assistant.enabled {
+                Ok(())
+            } else {
+                msg!("Assistant is disabled");
+                Err(WalletError::AssistantDisabled.into())
+            };
+        }
+        match self.get_signer(initiator.key) {
+            Some((slot_id, _)) if !self.signer_activations.is_active(slot_id, now) => {
+                msg!(
+                    "Signer can't initiate before it activates at {}",
+                    self.signer_activations.activates_at(slot_id)
+                );
+                Err(WalletError::SignerNotYetActive.into())
+            }
+            Some((_, signer)) if may_initiate(signer) => Ok(()),
+            Some(_) => {
+                msg!("Signer is not allowed to initiate this transaction");
+                Err(WalletError::SignerMissingCapability.into())
+            }
+            None => {
+                msg!("Transactions can only be initiated by an authorized account");
+                Err(WalletError::InvalidApprover.into())
+            }
         }
     }
 
@@ -201,15 +692,64 @@ impl Wallet {
         balance_account: &BalanceAccount,
         address: &Pubkey,
         name_hash: &AddressBookEntryNameHash,
+        now: i64,
     ) -> Result<bool, ProgramError> {
-        Ok(balance_account.is_whitelist_disabled()
-            || match self.address_book.find_id(&AddressBookEntry {
+        if balance_account.is_whitelist_disabled() {
+            return Ok(true);
+        }
+        match self.address_book.find_id(&AddressBookEntry {
+            address: *address,
+            name_hash: *name_hash,
+        }) {
+            Some(entry_ref) if self.address_book_usage.is_deprecated(entry_ref) => {
+                msg!("Destination is deprecated and can only be used by pending operations");
+                Err(WalletError::AddressBookEntryDeprecated.into())
+            }
+            Some(entry_ref)
+                if balance_account.allowed_destinations.is_enabled(&entry_ref)
+                    && self.address_book_expirations.is_expired(entry_ref, now) =>
+            {
+                msg!("Destination's address book entry expired, and must be renewed");
+                Err(WalletError::AddressBookEntryExpired.into())
+            }
+            Some(entry_ref) => Ok(balance_account.allowed_destinations.is_enabled(&entry_ref)),
+            None => Ok(false),
+        }
+    }
+
+    /// The jurisdiction of the destination's address book entry, if the entry is classified
+    /// high-risk. A destination that isn't in the address book has no classification.
+    pub fn destination_jurisdiction(
+        &self,
+        address: &Pubkey,
+        name_hash: &AddressBookEntryNameHash,
+    ) -> Option<JurisdictionCode> {
+        self.address_book
+            .find_id(&AddressBookEntry {
                 address: *address,
                 name_hash: *name_hash,
-            }) {
-                Some(entry_ref) => balance_account.allowed_destinations.is_enabled(&entry_ref),
-                None => false,
             })
+            .and_then(|entry_ref| self.address_book_risks.get(entry_ref).jurisdiction())
+    }
+
+    /// Records a transfer to the destination in the usage of its address book entry, if it
+    /// has one. Returns whether the wallet changed.
+    pub fn record_destination_use(
+        &mut self,
+        address: &Pubkey,
+        name_hash: &AddressBookEntryNameHash,
+        slot: u64,
+    ) -> bool {
+        match self.address_book.find_id(&AddressBookEntry {
+            address: *address,
+            name_hash: *name_hash,
+        }) {
+            Some(entry_ref) => {
+                self.address_book_usage.get_mut(entry_ref).last_used_slot = slot;
+                true
+            }
+            None => false,
+        }
     }
 
     pub fn validate_remove_signer(
@@ -226,11 +766,216 @@ impl Wallet {
     }
 
     pub fn remove_signer(&mut self, signer_to_remove: (SlotId<Signer>, Signer)) -> ProgramResult {
-        self.remove_signers(&vec![signer_to_remove])
+        self.remove_signers(&vec![signer_to_remove])?;
+        self.signer_activations.signer_removed(signer_to_remove.0);
+        self.retired_signers.slot_cleared(signer_to_remove.0);
+        Ok(())
+    }
+
+    pub fn validate_replace_signer(
+        &self,
+        replacement: (SlotId<Signer>, Signer),
+        now: i64,
+    ) -> ProgramResult {
+        let mut self_clone = self.clone();
+        self_clone.replace_signer(replacement, now)
+    }
+
+    /// Gives a filled slot to a new signer, under the same rules as removing the slot's signer
+    /// and adding the new one, and records the replaced key so that its dispositions on pending
+    /// ops can be handed on to the new signer once it is active.
+    pub fn replace_signer(
+        &mut self,
+        replacement: (SlotId<Signer>, Signer),
+        now: i64,
+    ) -> ProgramResult {
+        let slot_id = replacement.0;
+        let replaced = match self
+            .signers
+            .filled_slots()
+            .into_iter()
+            .find(|(id, _)| *id == slot_id)
+        {
+            Some(replaced) => replaced,
+            None => {
+                msg!(
+                    "Signer slot {} is empty, so it can't be replaced",
+                    slot_id.value
+                );
+                return Err(WalletError::SlotCannotBeRemoved.into());
+            }
+        };
+        self.remove_signers(&vec![replaced])?;
+        self.add_signers(&vec![replacement])?;
+        self.signer_activations.signer_added(slot_id, now);
+        self.retired_signers
+            .signer_replaced(slot_id, replaced.1.key);
+        Ok(())
+    }
+
+    /// The active signer that replaced a retired key in its slot, to which the retired key's
+    /// dispositions on pending ops can be handed on. There is none if the slot was cleared
+    /// since, or the key is still one of the wallet's signers.
+    pub fn successor_of(&self, retired: &Pubkey, now: i64) -> Option<Pubkey> {
+        if self.get_signer(retired).is_some() {
+            return None;
+        }
+        let slot_id = self.retired_signers.find_slot(retired)?;
+        self.signers[slot_id]
+            .filter(|_| self.signer_activations.is_active(slot_id, now))
+            .map(|signer| signer.key)
+    }
+
+    /// Validates that at least the config approval quorum of active config approvers signed,
+    /// each counted once however many times it is passed.
+    pub fn validate_config_approvers_signed(
+        &self,
+        approvers: &[AccountInfo],
+        now: i64,
+    ) -> ProgramResult {
+        let config_approvers = self.get_active_config_approvers_keys(now);
+        let signed = approvers
+            .iter()
+            .filter(|account_info| account_info.is_signer)
+            .filter(|account_info| config_approvers.contains(account_info.key))
+            .map(|account_info| account_info.key)
+            .unique()
+            .count();
+        if signed < usize::from(self.approvals_required_for_config) {
+            msg!(
+                "{} config approvers signed, but {} are required",
+                signed,
+                self.approvals_required_for_config
+            );
+            return Err(WalletError::ConfigApproverQuorumNotMet.into());
+        }
+        Ok(())
+    }
+
+    /// Adds a signer to the wallet, which only becomes active once the signer activation delay
+    /// elapses.
+    pub fn add_signer(
+        &mut self,
+        signer_to_add: (SlotId<Signer>, Signer),
+        now: i64,
+    ) -> ProgramResult {
+        self.add_signers(&vec![signer_to_add])?;
+        self.signer_activations.signer_added(signer_to_add.0, now);
+        Ok(())
+    }
+
+    pub fn update_signer_activation_delay(&mut self, delay: Duration, now: i64) -> ProgramResult {
+        self.signer_activations.update_delay(delay, now)
+    }
+
+    pub fn update_fee_policy(&mut self, fee_policy: &FeePolicy) {
+        self.fee_policy = *fee_policy;
+    }
+
+    pub fn validate_stake_weight_governance_update(
+        governance: &Option<StakeWeightGovernance>,
+    ) -> ProgramResult {
+        match governance {
+            Some(governance) => governance.validate(),
+            None => Ok(()),
+        }
+    }
+
+    pub fn update_stake_weight_governance(&mut self, governance: &Option<StakeWeightGovernance>) {
+        self.stake_weight_governance = *governance;
+    }
+
+    pub fn validate_approval_group_update(group: &Option<ApprovalGroup>) -> ProgramResult {
+        match group {
+            Some(group) => group.validate(),
+            None => Ok(()),
+        }
     }
 
-    pub fn add_signer(&mut self, signer_to_add: (SlotId<Signer>, Signer)) -> ProgramResult {
-        self.add_signers(&vec![signer_to_add])
+    pub fn update_approval_group(&mut self, group: &Option<ApprovalGroup>) {
+        self.approval_group = *group;
+    }
+
+    /// The slot moves compacting the wallet would make.
+    pub fn compaction(&self) -> WalletCompaction {
+        WalletCompaction {
+            signers: self.signers.compaction_remap(),
+            address_book: self.address_book.compaction_remap(),
+            dapp_book: self.dapp_book.compaction_remap(),
+        }
+    }
+
+    /// A replicated wallet can't be compacted, since the signer slots mirrored between it and
+    /// its counterpart would no longer line up.
+    pub fn validate_compaction(&self) -> ProgramResult {
+        if self.replication.is_some() {
+            msg!("A replicated wallet's slots can't be compacted");
+            return Err(WalletError::WalletIsReplicated.into());
+        }
+        Ok(())
+    }
+
+    /// Moves the wallet's signers, address book entries and dapp book entries to the slots the
+    /// compaction moves them to, along with everything kept by their slots.
+    pub fn compact(&mut self, compaction: &WalletCompaction) -> ProgramResult {
+        self.validate_compaction()?;
+
+        self.signers.remap(&compaction.signers);
+        self.config_approvers.remap(&compaction.signers);
+        if let Some(pending_config_policy) = self.pending_config_policy.as_mut() {
+            pending_config_policy
+                .config_approvers
+                .remap(&compaction.signers);
+        }
+        self.signer_activations.remap(&compaction.signers);
+        self.retired_signers.remap(&compaction.signers);
+
+        self.address_book.remap(&compaction.address_book);
+        self.pending_address_book_entries
+            .remap(&compaction.address_book);
+        self.address_book_usage.remap(&compaction.address_book);
+        self.address_book_risks.remap(&compaction.address_book);
+        self.address_book_expirations
+            .remap(&compaction.address_book);
+
+        for (slot_id, mut balance_account) in self.balance_accounts.filled_slots() {
+            balance_account
+                .transfer_approvers
+                .remap(&compaction.signers);
+            balance_account
+                .approval_tiers
+                .remap_approvers(&compaction.signers);
+            balance_account
+                .allowed_destinations
+                .remap(&compaction.address_book);
+            self.balance_accounts.replace(slot_id, balance_account);
+        }
+
+        self.dapp_book.remap(&compaction.dapp_book);
+        self.dapp_book_expirations.remap(&compaction.dapp_book);
+        self.dapp_book_categories.remap(&compaction.dapp_book);
+        self.dapp_book_instruction_limits
+            .remap(&compaction.dapp_book);
+
+        Ok(())
+    }
+
+    /// Sets up a freshly created wallet account, with the given assistant in the first slot.
+    pub fn init(
+        &mut self,
+        wallet_guid_hash: &WalletGuidHash,
+        assistant: Pubkey,
+        rent_return: Pubkey,
+        initial_config: &InitialWalletConfig,
+    ) -> ProgramResult {
+        self.is_initialized = true;
+        self.version = VERSION;
+        self.rent_return = rent_return;
+        self.wallet_guid_hash = *wallet_guid_hash;
+        self.assistants
+            .insert(SlotId::new(0), Assistant::new(assistant));
+        self.config_policy_timelock = Wallet::DEFAULT_CONFIG_POLICY_TIMELOCK;
+        self.initialize(initial_config)
     }
 
     pub fn initialize(&mut self, initial_config: &InitialWalletConfig) -> ProgramResult {
@@ -271,13 +1016,99 @@ impl Wallet {
         Ok(())
     }
 
-    pub fn validate_address_book_update(&self, update: &AddressBookUpdate) -> ProgramResult {
+    pub fn validate_address_book_update(
+        &self,
+        update: &AddressBookUpdate,
+        now: i64,
+    ) -> ProgramResult {
         let mut self_clone = self.clone();
-        self_clone.update_address_book(update)
+        self_clone.update_address_book(update, now)
+    }
+
+    /// Adds address book entries on behalf of the assistant, without multisig approval. The
+    /// entries are marked as pending and can't be whitelisted until they have been ratified.
+    /// Besides the cap on outstanding pending entries, only a limited number of entries can be
+    /// staged per window, whether or not earlier ones have since been ratified.
+    pub fn add_pending_address_book_entries(
+        &mut self,
+        entries_to_add: &Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+        now: i64,
+    ) -> ProgramResult {
+        let slot_ids = entries_to_add.slot_ids();
+        if slot_ids.iter().unique().count() != slot_ids.len()
+            || slot_ids.iter().any(|id| {
+                id.value >= Wallet::MAX_ADDRESS_BOOK_ENTRIES || self.address_book[**id].is_some()
+            })
+        {
+            msg!("Failed to add pending address book entries: at least one of the provided slots is already taken");
+            return Err(WalletError::SlotCannotBeInserted.into());
+        }
+
+        let pending_count_after_update =
+            self.pending_address_book_entries.count_enabled() + entries_to_add.len();
+        if pending_count_after_update > Wallet::MAX_PENDING_ADDRESS_BOOK_ENTRIES {
+            msg!(
+                "Pending address book entries {} can't exceed {}",
+                pending_count_after_update,
+                Wallet::MAX_PENDING_ADDRESS_BOOK_ENTRIES
+            );
+            return Err(WalletError::TooManyPendingAddressBookEntries.into());
+        }
+
+        let window_elapsed = now.saturating_sub(self.pending_address_book_window_start)
+            >= Wallet::PENDING_ADDRESS_BOOK_WINDOW_SECS;
+        let staged_in_window = if window_elapsed {
+            entries_to_add.len()
+        } else {
+            usize::from(self.pending_address_book_entries_in_window) + entries_to_add.len()
+        };
+        if staged_in_window > Wallet::MAX_PENDING_ADDRESS_BOOK_ENTRIES_PER_WINDOW {
+            msg!(
+                "Pending address book entries staged in the current window {} can't exceed {}",
+                staged_in_window,
+                Wallet::MAX_PENDING_ADDRESS_BOOK_ENTRIES_PER_WINDOW
+            );
+            return Err(WalletError::PendingAddressBookRateLimitExceeded.into());
+        }
+
+        if window_elapsed {
+            self.pending_address_book_window_start = now;
+        }
+        self.pending_address_book_entries_in_window = staged_in_window as u8;
+        self.address_book.insert_many(entries_to_add);
+        self.pending_address_book_entries.enable_many(&slot_ids);
+        for (id, _) in entries_to_add {
+            self.address_book_usage.clear(*id);
+            self.address_book_risks
+                .set(*id, AddressBookEntryRisk::Standard);
+            self.address_book_expirations.set(*id, None);
+        }
+        Ok(())
     }
 
-    pub fn update_address_book(&mut self, update: &AddressBookUpdate) -> ProgramResult {
+    pub fn ratify_pending_address_book_entries(
+        &mut self,
+        entries_to_ratify: &Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+    ) -> ProgramResult {
+        if !self.address_book.contains(entries_to_ratify)
+            || !entries_to_ratify
+                .iter()
+                .all(|(id, _)| self.pending_address_book_entries.is_enabled(id))
+        {
+            msg!("Failed to ratify address book entries: at least one of the provided entries is not pending");
+            return Err(WalletError::UnknownAddressBookEntry.into());
+        }
+        for (id, _) in entries_to_ratify {
+            self.pending_address_book_entries.disable(id);
+        }
+        Ok(())
+    }
+
+    pub fn update_address_book(&mut self, update: &AddressBookUpdate, now: i64) -> ProgramResult {
+        Wallet::validate_address_book_entry_kinds(update)?;
         self.add_address_book_entries(&update.add_address_book_entries)?;
+        self.set_address_book_risks(&update.address_book_risks)?;
+        self.set_address_book_expirations(&update.address_book_expirations, now)?;
         for balance_account_whitelist_update in update.balance_account_whitelist_updates.clone() {
             let (slot_id, mut balance_account) =
                 self.get_balance_account_with_slot_id(&balance_account_whitelist_update.guid_hash)?;
@@ -297,14 +1128,184 @@ impl Wallet {
             self.balance_accounts.replace(slot_id, balance_account);
         }
         self.remove_address_book_entries(&update.remove_address_book_entries)?;
+        self.deprecate_address_book_entries(&update.deprecate_address_book_entries, now)?;
         Ok(())
     }
-    pub fn validate_balance_account_address_whitelist_update(
-        &self,
-        account_guid_hash: &BalanceAccountGuidHash,
-        update: &BalanceAccountAddressWhitelistUpdate,
+
+    /// Pushes back the expiry of an address book entry that lapses, whether or not it already
+    /// has. The entry must still be in its slot, and the new expiry must be in the future and
+    /// later than the current one.
+    pub fn renew_address_book_entry(
+        &mut self,
+        id: SlotId<AddressBookEntry>,
+        entry: &AddressBookEntry,
+        expires_at: i64,
+        now: i64,
     ) -> ProgramResult {
-        let mut self_clone = self.clone();
+        if !self.address_book.contains(&vec![(id, *entry)]) {
+            msg!("Failed to renew address book entry: the entry is not present in the config");
+            return Err(WalletError::UnknownAddressBookEntry.into());
+        }
+        match self.address_book_expirations.get(id) {
+            Some(current_expires_at) if expires_at > current_expires_at && expires_at > now => {
+                self.address_book_expirations.set(id, Some(expires_at));
+                Ok(())
+            }
+            Some(_) => {
+                msg!(
+                    "Failed to renew address book entry: {} is not later than the current expiry and now",
+                    expires_at
+                );
+                Err(WalletError::InvalidAddressBookExpiry.into())
+            }
+            None => {
+                msg!("Failed to renew address book entry: the entry doesn't lapse");
+                Err(WalletError::InvalidAddressBookExpiry.into())
+            }
+        }
+    }
+
+    /// Selects address book entries of this wallet to be imported into another wallet of the
+    /// organization, along with which of them the given balance accounts whitelist. Pending and
+    /// deprecated entries can't be exported.
+    pub fn export_address_book_entries(
+        &self,
+        wallet_address: &Pubkey,
+        entries: &[(SlotId<AddressBookEntry>, SlotId<AddressBookEntry>)],
+        balance_accounts: &[(BalanceAccountGuidHash, BalanceAccountGuidHash)],
+    ) -> Result<AddressBookImport, ProgramError> {
+        if entries
+            .iter()
+            .map(|(source_id, _)| source_id)
+            .unique()
+            .count()
+            != entries.len()
+            || entries.iter().map(|(_, id)| id).unique().count() != entries.len()
+            || balance_accounts
+                .iter()
+                .map(|(_, guid_hash)| guid_hash)
+                .sorted()
+                .dedup()
+                .count()
+                != balance_accounts.len()
+        {
+            msg!("Failed to export address book entries: a slot or balance account is selected twice");
+            return Err(WalletError::InvalidAddressBookImport.into());
+        }
+
+        let mut add_address_book_entries = Vec::with_capacity(entries.len());
+        for (source_id, id) in entries {
+            let source_entry = if source_id.value < Wallet::MAX_ADDRESS_BOOK_ENTRIES {
+                self.address_book[*source_id]
+            } else {
+                None
+            };
+            let entry = match source_entry {
+                Some(entry)
+                    if !self.pending_address_book_entries.is_enabled(source_id)
+                        && !self.address_book_usage.is_deprecated(*source_id) =>
+                {
+                    entry
+                }
+                _ => {
+                    msg!(
+                        "Failed to export address book entries: slot {} is empty, pending or deprecated",
+                        source_id.value
+                    );
+                    return Err(WalletError::InvalidAddressBookImport.into());
+                }
+            };
+            add_address_book_entries.push((*id, entry));
+        }
+
+        let mut whitelist_enablements = Vec::with_capacity(balance_accounts.len());
+        for (source_guid_hash, guid_hash) in balance_accounts {
+            let (_, source_balance_account) =
+                self.get_balance_account_with_slot_id(source_guid_hash)?;
+            let destination_slots = entries
+                .iter()
+                .filter(|(source_id, _)| {
+                    source_balance_account
+                        .allowed_destinations
+                        .is_enabled(source_id)
+                })
+                .map(|(_, id)| *id)
+                .collect_vec();
+            whitelist_enablements.push((*guid_hash, destination_slots));
+        }
+
+        Ok(AddressBookImport {
+            source_wallet_address: *wallet_address,
+            add_address_book_entries,
+            whitelist_enablements,
+        })
+    }
+
+    pub fn validate_address_book_import(&self, import: &AddressBookImport) -> ProgramResult {
+        let mut self_clone = self.clone();
+        self_clone.import_address_book_entries(import)
+    }
+
+    /// Adds address book entries exported from another wallet, enabling them on the whitelists
+    /// of the balance accounts they were whitelisted for there. Like entries added through an
+    /// address book update, they don't need to be ratified.
+    pub fn import_address_book_entries(&mut self, import: &AddressBookImport) -> ProgramResult {
+        self.add_address_book_entries(&import.add_address_book_entries)?;
+        for (guid_hash, destination_slots) in &import.whitelist_enablements {
+            let (slot_id, mut balance_account) =
+                self.get_balance_account_with_slot_id(guid_hash)?;
+            self.enable_transfer_destinations_by_slot(&mut balance_account, destination_slots)?;
+            self.balance_accounts.replace(slot_id, balance_account);
+        }
+        Ok(())
+    }
+
+    /// Removes deprecated address book entries whose grace period has elapsed, also taking
+    /// them off the whitelists they are still on. The grace period is the longest transfer
+    /// approval timeout of the wallet's balance accounts, by which time any transfer initiated
+    /// before the deprecation has expired.
+    pub fn remove_deprecated_address_book_entries(
+        &mut self,
+        entries_to_remove: &Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+        now: i64,
+    ) -> ProgramResult {
+        if !self.address_book.can_be_removed(entries_to_remove) {
+            msg!("Failed to remove deprecated address book entries: at least one of the provided entries is not present in the config");
+            return Err(WalletError::SlotCannotBeRemoved.into());
+        }
+        let grace_period = self
+            .balance_accounts
+            .filled_slots()
+            .iter()
+            .map(|(_, balance_account)| {
+                duration_secs(balance_account.approval_timeout_for_transfer)
+            })
+            .max()
+            .unwrap_or(0);
+        for (id, _) in entries_to_remove {
+            match self.address_book_usage.get(*id).deprecated_at {
+                Some(deprecated_at) if now.saturating_sub(deprecated_at) >= grace_period => {}
+                _ => {
+                    msg!("Failed to remove deprecated address book entries: at least one entry is not deprecated or still in its grace period");
+                    return Err(WalletError::AddressBookEntryNotRemovable.into());
+                }
+            }
+        }
+        let slot_ids = entries_to_remove.slot_ids();
+        for (slot_id, mut balance_account) in self.balance_accounts.filled_slots() {
+            for id in &slot_ids {
+                balance_account.allowed_destinations.disable(id);
+            }
+            self.balance_accounts.replace(slot_id, balance_account);
+        }
+        self.remove_address_book_entries(entries_to_remove)
+    }
+    pub fn validate_balance_account_address_whitelist_update(
+        &self,
+        account_guid_hash: &BalanceAccountGuidHash,
+        update: &BalanceAccountAddressWhitelistUpdate,
+    ) -> ProgramResult {
+        let mut self_clone = self.clone();
         self_clone.update_balance_account_address_whitelist(account_guid_hash, update)
     }
 
@@ -329,14 +1330,88 @@ impl Wallet {
         &self,
         update: &WalletConfigPolicyUpdate,
     ) -> ProgramResult {
+        self.config_policy_diff(update).map(|_| ())
+    }
+
+    /// Applies the update to a copy of the wallet, returning what it would change.
+    pub fn config_policy_diff(
+        &self,
+        update: &WalletConfigPolicyUpdate,
+    ) -> Result<PolicyDiff, ProgramError> {
         let mut self_clone = self.clone();
-        self_clone.update_config_policy(update)
+        self_clone.update_config_policy(update)?;
+        Ok(PolicyDiff::between(
+            &self.config_policy_summary(),
+            &self_clone.config_policy_summary(),
+        ))
+    }
+
+    pub fn config_policy_summary(&self) -> PolicySummary {
+        PolicySummary {
+            approvers: self.config_approvers,
+            approvals_required: self.approvals_required_for_config,
+            approval_timeout: self.approval_timeout_for_config,
+            delay: self.config_policy_timelock,
+        }
+    }
+
+    /// Applies the update right away, unless it lowers the approvals required for config or
+    /// shortens the timelock, in which case it is staged until the current timelock elapses.
+    pub fn schedule_config_policy_update(
+        &mut self,
+        update: &WalletConfigPolicyUpdate,
+        now: i64,
+    ) -> ProgramResult {
+        let weakens_policy = self.config_policy_diff(update)?.weakens_policy();
+        if !weakens_policy || self.config_policy_timelock.is_zero() {
+            return self.update_config_policy(update);
+        }
+
+        let mut config_approvers = update.config_approvers.clone();
+        config_approvers.sort_by_key(|id| id.value);
+        let activates_at = now.saturating_add(duration_secs(self.config_policy_timelock));
+        msg!("Config policy update staged until {}", activates_at);
+        self.pending_config_policy = Some(PendingConfigPolicy {
+            approvals_required_for_config: update.approvals_required_for_config,
+            approval_timeout_for_config: update.approval_timeout_for_config,
+            config_approvers: Approvers::from_enabled_vec(config_approvers.clone()),
+            config_policy_timelock: update.config_policy_timelock,
+            signers_hash: self.signers_hash(&config_approvers)?,
+            activates_at,
+        });
+        Ok(())
+    }
+
+    pub fn activate_pending_config_policy(&mut self, now: i64) -> ProgramResult {
+        let pending = match self.pending_config_policy {
+            Some(pending) => pending,
+            None => return Err(WalletError::NoPendingConfigPolicy.into()),
+        };
+        if now < pending.activates_at {
+            msg!(
+                "Config policy can't be activated before {}",
+                pending.activates_at
+            );
+            return Err(WalletError::ConfigPolicyTimelocked.into());
+        }
+        // re-validated against the signers as they are now
+        self.update_config_policy(&pending.to_update())
     }
 
     pub fn update_config_policy(&mut self, update: &WalletConfigPolicyUpdate) -> ProgramResult {
         Wallet::validate_approval_timeout(&update.approval_timeout_for_config)?;
+        if update.config_policy_timelock > Wallet::MAX_CONFIG_POLICY_TIMELOCK {
+            msg!(
+                "Config policy timelock can't exceed {} seconds",
+                Wallet::MAX_CONFIG_POLICY_TIMELOCK.as_secs()
+            );
+            return Err(WalletError::InvalidConfigPolicyTimelock.into());
+        }
         self.approval_timeout_for_config = update.approval_timeout_for_config;
         self.approvals_required_for_config = update.approvals_required_for_config;
+        self.config_policy_timelock = update.config_policy_timelock;
+        // an update taking effect supersedes any staged one
+        self.pending_config_policy = None;
 
         self.config_approvers.disable_all();
         self.enable_config_approvers_by_slots(&update.config_approvers)?;
@@ -360,20 +1435,398 @@ impl Wallet {
         Ok(())
     }
 
-    pub fn validate_dapp_book_update(&self, update: &DAppBookUpdate) -> ProgramResult {
+    pub fn update_compliance_policy(&mut self, policy: &Option<CompliancePolicy>) {
+        self.compliance_policy = *policy;
+    }
+
+    pub fn validate_replication_update(
+        &self,
+        wallet_address: &Pubkey,
+        replication: &Option<WalletReplication>,
+    ) -> ProgramResult {
+        let other = match replication {
+            Some(WalletReplication::Primary { standby }) => standby,
+            Some(WalletReplication::Standby { primary }) => primary,
+            None => return Ok(()),
+        };
+        if other == wallet_address {
+            msg!("A wallet can't replicate itself");
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(())
+    }
+
+    pub fn update_replication(&mut self, replication: &Option<WalletReplication>) {
+        self.replication = *replication;
+    }
+
+    pub fn update_price_oracle(&mut self, oracle: &Option<PriceOracle>) {
+        self.price_oracle = oracle.clone();
+    }
+
+    pub fn validate_freeze(&self, freeze: &WalletFreeze) -> ProgramResult {
+        let signer_count = self.signers.filled_slots().len();
+        if usize::from(freeze.emergency_quorum) > signer_count {
+            msg!(
+                "Emergency freeze quorum {} exceeds the wallet's {} signers",
+                freeze.emergency_quorum,
+                signer_count
+            );
+            return Err(WalletError::InvalidEmergencyFreezeQuorum.into());
+        }
+        Ok(())
+    }
+
+    pub fn update_freeze(&mut self, freeze: &WalletFreeze) {
+        self.freeze = *freeze;
+    }
+
+    pub fn update_rent_return_allowlist(&mut self, allowlist: &RentReturnAllowlist) {
+        self.rent_return_allowlist = allowlist.clone();
+    }
+
+    pub fn update_wrapper_book(&mut self, wrapper_book: &WrapperBook) {
+        self.wrapper_book = wrapper_book.clone();
+    }
+
+    pub fn validate_full_rotation(&self, rotation: &FullRotation) -> ProgramResult {
+        self.clone().apply_full_rotation(rotation)
+    }
+
+    /// Replaces the assistants, the rent return key and the rotated signer keys. Each new
+    /// signer key takes the slot of the key it replaces, keeping its capabilities.
+    pub fn apply_full_rotation(&mut self, rotation: &FullRotation) -> ProgramResult {
+        let rotated_slots = rotation
+            .signer_keys
+            .iter()
+            .map(|(old, new)| {
+                self.signers
+                    .find_by(|signer| signer.key == *old)
+                    .map(|(slot_id, signer)| {
+                        (
+                            slot_id,
+                            Signer {
+                                key: *new,
+                                ..signer
+                            },
+                        )
+                    })
+                    .ok_or_else(|| {
+                        msg!("{} is not a signer of the wallet", old);
+                        ProgramError::from(WalletError::InvalidFullRotation)
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        for (slot_id, signer) in rotated_slots {
+            self.signers.replace(slot_id, signer);
+        }
+        let signer_keys = self.get_signers_keys();
+        if signer_keys.iter().unique().count() != signer_keys.len()
+            || rotation
+                .signer_keys
+                .iter()
+                .map(|(old, _)| old)
+                .unique()
+                .count()
+                != rotation.signer_keys.len()
+        {
+            msg!("A signer key is rotated twice, or to the key of another signer");
+            return Err(WalletError::InvalidFullRotation.into());
+        }
+
+        let policy = &mut self.finalize_submitter_policy;
+        for named_submitter in policy.named_submitters.iter_mut() {
+            if let Some((_, new)) = rotation
+                .signer_keys
+                .iter()
+                .find(|(old, _)| old == named_submitter)
+            {
+                *named_submitter = *new;
+            }
+        }
+        policy.named_submitters.sort();
+        policy.named_submitters.dedup();
+
+        let allowlist = &mut self.rent_return_allowlist.entries;
+        if allowlist.contains(&self.rent_return) {
+            allowlist.retain(|entry| *entry != self.rent_return);
+            if !allowlist.contains(&rotation.rent_return) {
+                allowlist.push(rotation.rent_return);
+            }
+        }
+        self.rent_return = rotation.rent_return;
+
+        self.assistants =
+            Assistants::from_vec(vec![(SlotId::new(0), Assistant::new(rotation.assistant))]);
+        Ok(())
+    }
+
+    /// New transfers, wraps and dApp transactions can't be initiated while the wallet is
+    /// frozen.
+    pub fn validate_not_frozen(&self) -> ProgramResult {
+        if self.freeze.frozen {
+            msg!("The wallet is frozen");
+            return Err(WalletError::WalletFrozen.into());
+        }
+        Ok(())
+    }
+
+    /// Freezes the wallet if at least its emergency quorum of active signers signed, each
+    /// counted once however many times it is passed.
+    pub fn emergency_freeze(&mut self, signers: &[AccountInfo], now: i64) -> ProgramResult {
+        let signed = signers
+            .iter()
+            .filter(|account_info| account_info.is_signer)
+            .filter(|account_info| {
+                self.get_signer(account_info.key)
+                    .map_or(false, |(slot_id, _)| {
+                        self.signer_activations.is_active(slot_id, now)
+                    })
+            })
+            .map(|account_info| account_info.key)
+            .unique()
+            .count();
+        if self.freeze.emergency_quorum == 0 || signed < usize::from(self.freeze.emergency_quorum) {
+            msg!(
+                "{} signers signed an emergency freeze needing {}",
+                signed,
+                self.freeze.emergency_quorum
+            );
+            return Err(WalletError::EmergencyFreezeQuorumNotMet.into());
+        }
+        self.freeze.frozen = true;
+        Ok(())
+    }
+
+    /// No ops can be started against a wallet until it is activated.
+    pub fn validate_not_provisioning(&self) -> ProgramResult {
+        if self.provisioning.is_some() {
+            msg!("The wallet is still being provisioned");
+            return Err(WalletError::WalletIsProvisioning.into());
+        }
+        Ok(())
+    }
+
+    /// Validates that the wallet is being provisioned and that its creator signed.
+    pub fn validate_provisioning_creator(&self, creator: &AccountInfo) -> ProgramResult {
+        let provisioning = self.provisioning.ok_or_else(|| {
+            msg!("The wallet isn't being provisioned");
+            ProgramError::from(WalletError::WalletNotProvisioning)
+        })?;
+        if !creator.is_signer || *creator.key != provisioning.creator {
+            msg!("{} isn't the creator of the wallet", creator.key);
+            return Err(WalletError::InvalidProvisioningCreator.into());
+        }
+        Ok(())
+    }
+
+    /// Replaces the signers and config policy of a wallet being provisioned, as though it had
+    /// been initialized with the given config.
+    pub fn reprovision(&mut self, initial_config: &InitialWalletConfig) -> ProgramResult {
+        self.signers = Signers::new();
+        self.config_approvers = Approvers::zero();
+        self.approval_timeout_for_config = Duration::ZERO;
+        self.initialize(initial_config)
+    }
+
+    /// Puts a wallet being provisioned under its config policy for good, once every one of its
+    /// config approvers signed.
+    pub fn activate(&mut self, signers: &[AccountInfo]) -> ProgramResult {
+        if self.provisioning.is_none() {
+            msg!("The wallet isn't being provisioned");
+            return Err(WalletError::WalletNotProvisioning.into());
+        }
+        let signed = signers
+            .iter()
+            .filter(|account_info| account_info.is_signer)
+            .map(|account_info| *account_info.key)
+            .collect_vec();
+        let unsigned = self
+            .get_config_approvers_keys()
+            .into_iter()
+            .filter(|approver| !signed.contains(approver))
+            .count();
+        if unsigned > 0 {
+            msg!(
+                "{} config approvers have yet to sign the activation",
+                unsigned
+            );
+            return Err(WalletError::ActivationNotSignedByAllApprovers.into());
+        }
+        self.provisioning = None;
+        Ok(())
+    }
+
+    /// Config changes of a standby only come mirrored from its primary, so it stays in sync;
+    /// its approvers end the replication first to make changes of their own.
+    pub fn validate_not_standby(&self) -> ProgramResult {
+        if let Some(WalletReplication::Standby { primary }) = self.replication {
+            msg!(
+                "Config changes of the wallet are mirrored from its primary {}",
+                primary
+            );
+            return Err(WalletError::WalletIsStandby.into());
+        }
+        Ok(())
+    }
+
+    /// Checks a wallet is ready to become this wallet's standby: it has to have agreed to
+    /// mirror this wallet, and have the same signers and config policy to begin with.
+    pub fn validate_standby(&self, wallet_address: &Pubkey, standby: &Wallet) -> ProgramResult {
+        if standby.replication
+            != Some(WalletReplication::Standby {
+                primary: *wallet_address,
+            })
+        {
+            msg!("Standby wallet doesn't mirror {}", wallet_address);
+            return Err(WalletError::StandbyWalletNotInSync.into());
+        }
+        if standby.signers != self.signers
+            || standby.approvals_required_for_config != self.approvals_required_for_config
+            || standby.approval_timeout_for_config != self.approval_timeout_for_config
+            || standby.config_approvers != self.config_approvers
+            || standby.config_policy_timelock != self.config_policy_timelock
+            || standby.pending_config_policy != self.pending_config_policy
+        {
+            msg!("Standby wallet's signers or config policy differ from the primary's");
+            return Err(WalletError::StandbyWalletNotInSync.into());
+        }
+        Ok(())
+    }
+
+    /// Checks the mirrored update is signed for by the mirror authority of this wallet's
+    /// primary, which only the program can sign for as it finalizes a change of the primary.
+    pub fn validate_mirror_authority(
+        &self,
+        authority: &AccountInfo,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let primary = match self.replication {
+            Some(WalletReplication::Standby { primary }) => primary,
+            _ => {
+                msg!("Wallet isn't a standby");
+                return Err(WalletError::InvalidMirroredUpdate.into());
+            }
+        };
+        if !authority.is_signer
+            || *authority.key != WalletReplication::mirror_authority(&primary, program_id).0
+        {
+            msg!("Update isn't signed by the mirror authority of {}", primary);
+            return Err(WalletError::InvalidMirroredUpdate.into());
+        }
+        Ok(())
+    }
+
+    pub fn apply_mirrored_update(&mut self, update: &MirroredUpdate, now: i64) -> ProgramResult {
+        match update {
+            MirroredUpdate::ConfigPolicy(update) => self.schedule_config_policy_update(update, now),
+            MirroredUpdate::Signer {
+                slot_update_type,
+                slot_id,
+                signer,
+            } => match slot_update_type {
+                SlotUpdateType::SetIfEmpty => self.add_signer((*slot_id, *signer), now),
+                SlotUpdateType::Clear => self.remove_signer((*slot_id, *signer)),
+                SlotUpdateType::Replace => self.replace_signer((*slot_id, *signer), now),
+            },
+        }
+    }
+
+    pub fn update_finalize_submitter_policy(
+        &mut self,
+        policy: &FinalizeSubmitterPolicy,
+    ) -> ProgramResult {
+        policy.validate()?;
+        self.finalize_submitter_policy = policy.clone();
+        Ok(())
+    }
+
+    pub fn validate_dapp_book_update(&self, update: &DAppBookUpdate, now: i64) -> ProgramResult {
         let mut self_clone = self.clone();
-        self_clone.update_dapp_book(update)
+        self_clone.update_dapp_book(update, now)
     }
 
-    pub fn update_dapp_book(&mut self, update: &DAppBookUpdate) -> ProgramResult {
+    pub fn update_dapp_book(&mut self, update: &DAppBookUpdate, now: i64) -> ProgramResult {
         self.add_dapp_book_entries(&update.add_dapps)?;
         self.remove_dapp_book_entries(&update.remove_dapps)?;
+        self.set_dapp_book_expirations(&update.dapp_expirations, now)?;
+        self.set_dapp_book_categories(&update.dapp_categories)?;
+        self.set_dapp_book_instruction_limits(&update.dapp_instruction_limits)?;
+        self.set_disallowed_dapp_categories(&update.disallowed_dapp_categories)?;
+
+        Ok(())
+    }
 
+    /// Expires a dapp book entry right away, so no new dapp transactions can be started with
+    /// it until a dapp book update renews or removes it. An entry already expired is left as is.
+    pub fn suspend_dapp(&mut self, dapp: &DAppBookEntry, now: i64) -> ProgramResult {
+        let id = self
+            .dapp_book
+            .find_id(dapp)
+            .ok_or(WalletError::DAppNotAllowed)?;
+        if !self.dapp_book_expirations.is_expired(id, now) {
+            self.dapp_book_expirations.set(id, Some(now));
+        }
         Ok(())
     }
 
-    pub fn dapp_allowed(&self, dapp: DAppBookEntry) -> bool {
-        self.dapp_book.find_id(&dapp).is_some()
+    pub fn validate_dapp_allowed(&self, dapp: DAppBookEntry, now: i64) -> ProgramResult {
+        match self.dapp_book.find_id(&dapp) {
+            None => Err(WalletError::DAppNotAllowed.into()),
+            Some(id) if self.dapp_book_expirations.is_expired(id, now) => {
+                msg!("DApp book entry expired or was suspended, and must be renewed");
+                Err(WalletError::DAppBookEntryExpired.into())
+            }
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// The most instructions a dapp transaction with the dapp may have, if its dapp book entry
+    /// limits them.
+    pub fn dapp_instruction_limit(&self, dapp: &DAppBookEntry) -> Option<u8> {
+        self.dapp_book
+            .find_id(dapp)
+            .and_then(|id| self.dapp_book_instruction_limits.get(id))
+    }
+
+    /// Validates that the program has an unexpired dapp book entry, under any name.
+    pub fn validate_dapp_program_allowed(&self, program_id: &Pubkey, now: i64) -> ProgramResult {
+        if self.dapp_book.filled_slots().iter().any(|(id, entry)| {
+            entry.address == *program_id && !self.dapp_book_expirations.is_expired(*id, now)
+        }) {
+            Ok(())
+        } else {
+            Err(strike_err!(
+                "wallet::validate_dapp_program_allowed",
+                WalletError::DAppProgramNotAllowed,
+                "Program {} is not in the dapp book",
+                program_id
+            ))
+        }
+    }
+
+    /// Validates that the program isn't tagged, under any of its dapp book entries, with a
+    /// category the balance account may not transact with.
+    pub fn validate_dapp_category_allowed(
+        &self,
+        balance_account: &BalanceAccount,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        if self.dapp_book.filled_slots().iter().any(|(id, entry)| {
+            entry.address == *program_id
+                && self
+                    .dapp_book_categories
+                    .get(*id)
+                    .intersects(balance_account.disallowed_dapp_categories)
+        }) {
+            msg!(
+                "Program {} is in a dapp category the balance account may not transact with",
+                program_id
+            );
+            Err(WalletError::DAppCategoryNotAllowed.into())
+        } else {
+            Ok(())
+        }
     }
 
     pub fn validate_balance_account_creation(
@@ -392,6 +1845,11 @@ impl Wallet {
         creation_params: &BalanceAccountCreation,
         program_id: &Pubkey,
     ) -> ProgramResult {
+        // a second account with the same guid hash would be shadowed by the first in lookups
+        if self.get_balance_account(account_guid_hash).is_ok() {
+            msg!("A balance account with this guid hash already exists");
+            return Err(WalletError::DuplicateBalanceAccountGuid.into());
+        }
         Wallet::validate_approvals_required(creation_params.approvals_required_for_transfer)?;
         Wallet::validate_approval_timeout(&creation_params.approval_timeout_for_transfer)?;
         if creation_params.approvals_required_for_transfer
@@ -414,6 +1872,15 @@ impl Wallet {
             allowed_destinations: AllowedDestinations::zero(),
             whitelist_enabled: creation_params.whitelist_enabled,
             dapps_enabled: creation_params.dapps_enabled,
+            archived: BooleanSetting::Off,
+            system_owned_destinations_only: BooleanSetting::Off,
+            reward_split_policy: RewardSplitPolicy::zero(),
+            finalize_delay_policy: FinalizeDelayPolicy::default(),
+            mint_cap: 0,
+            spending_limit: SpendingLimit::default(),
+            approval_tiers: ApprovalTiers::none(),
+            disallowed_dapp_categories: DAppCategories::NONE,
+            deposit_addresses_enabled: BooleanSetting::Off,
         };
         self.enable_transfer_approvers_by_slot(
             &mut balance_account,
@@ -428,13 +1895,8 @@ impl Wallet {
         self.balance_accounts
             .insert(creation_params.slot_id, balance_account);
 
-        let (source_account_pda, _) = Pubkey::find_program_address(
-            &[
-                self.wallet_guid_hash.to_bytes(),
-                account_guid_hash.to_bytes(),
-            ],
-            program_id,
-        );
+        let (source_account_pda, _) =
+            BalanceAccount::find_address(&self.wallet_guid_hash, account_guid_hash, program_id);
 
         self.add_address_book_entries(&vec![(
             creation_params.address_book_slot_id,
@@ -446,13 +1908,61 @@ impl Wallet {
         Ok(())
     }
 
+    /// Creates a balance account for `InitDemoWallet`, approved for transfers by the config
+    /// approvers under the config approval policy.
+    #[cfg(feature = "demo")]
+    pub fn create_demo_balance_account(
+        &mut self,
+        demo_account: &DemoBalanceAccount,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let transfer_approvers = self.config_approvers.iter_enabled().collect_vec();
+        let creation_params = BalanceAccountCreation {
+            slot_id: demo_account.slot_id,
+            name_hash: demo_account.name_hash,
+            approvals_required_for_transfer: self.approvals_required_for_config,
+            approval_timeout_for_transfer: self.approval_timeout_for_config,
+            signers_hash: self.signers_hash(&transfer_approvers)?,
+            transfer_approvers,
+            whitelist_enabled: BooleanSetting::Off,
+            dapps_enabled: BooleanSetting::Off,
+            address_book_slot_id: demo_account.address_book_slot_id,
+        };
+        self.create_balance_account(&demo_account.guid_hash, &creation_params, program_id)
+    }
+
     pub fn validate_balance_account_policy_update(
         &self,
         account_guid_hash: &BalanceAccountGuidHash,
         update: &BalanceAccountPolicyUpdate,
     ) -> ProgramResult {
+        self.balance_account_policy_diff(account_guid_hash, update)
+            .map(|_| ())
+    }
+
+    /// Applies the update to a copy of the wallet, returning what it would change.
+    pub fn balance_account_policy_diff(
+        &self,
+        account_guid_hash: &BalanceAccountGuidHash,
+        update: &BalanceAccountPolicyUpdate,
+    ) -> Result<PolicyDiff, ProgramError> {
         let mut self_clone = self.clone();
-        self_clone.update_balance_account_policy(account_guid_hash, update)
+        self_clone.update_balance_account_policy(account_guid_hash, update)?;
+        Ok(PolicyDiff::between(
+            &Wallet::balance_account_policy_summary(&self.get_balance_account(account_guid_hash)?),
+            &Wallet::balance_account_policy_summary(
+                &self_clone.get_balance_account(account_guid_hash)?,
+            ),
+        ))
+    }
+
+    pub fn balance_account_policy_summary(balance_account: &BalanceAccount) -> PolicySummary {
+        PolicySummary {
+            approvers: balance_account.transfer_approvers,
+            approvals_required: balance_account.approvals_required_for_transfer,
+            approval_timeout: balance_account.approval_timeout_for_transfer,
+            delay: balance_account.finalize_delay_policy.delay,
+        }
     }
 
     pub fn validate_whitelist_enabled_update(
@@ -484,59 +1994,175 @@ impl Wallet {
         Ok(())
     }
 
-    pub fn update_dapps_enabled(
+    /// Turns sweeping funds from the balance account's deposit addresses into it on or off.
+    pub fn update_deposit_addresses(
         &mut self,
         account_guid_hash: &BalanceAccountGuidHash,
         enabled: BooleanSetting,
     ) -> ProgramResult {
         let (slot_id, mut balance_account) =
             self.get_balance_account_with_slot_id(account_guid_hash)?;
-        balance_account.dapps_enabled = enabled;
+        balance_account.deposit_addresses_enabled = enabled;
         self.balance_accounts.replace(slot_id, balance_account);
         Ok(())
     }
 
-    pub fn validate_balance_account_name_update(
-        &self,
+    /// Replaces the spending limit of the balance account, starting it on a fresh window.
+    pub fn update_spending_limit(
+        &mut self,
         account_guid_hash: &BalanceAccountGuidHash,
-        account_name_hash: &BalanceAccountNameHash,
-        program_id: &Pubkey,
+        token_mint: &Pubkey,
+        amount: u64,
     ) -> ProgramResult {
-        let mut self_clone = self.clone();
-        self_clone.update_balance_account_name_hash(
-            account_guid_hash,
-            account_name_hash,
-            program_id,
-        )
+        let (slot_id, mut balance_account) =
+            self.get_balance_account_with_slot_id(account_guid_hash)?;
+        balance_account.spending_limit = SpendingLimit::new(*token_mint, amount);
+        self.balance_accounts.replace(slot_id, balance_account);
+        Ok(())
     }
 
-    pub fn update_balance_account_name_hash(
+    /// Counts a transfer against the spending limit of the balance account if it is within what
+    /// is left of it, returning whether it was.
+    pub fn record_limited_spend(
         &mut self,
         account_guid_hash: &BalanceAccountGuidHash,
-        account_name_hash: &BalanceAccountNameHash,
-        program_id: &Pubkey,
-    ) -> ProgramResult {
+        token_mint: &Pubkey,
+        amount: u64,
+        now: i64,
+    ) -> Result<bool, ProgramError> {
         let (slot_id, mut balance_account) =
             self.get_balance_account_with_slot_id(account_guid_hash)?;
-        self.update_address_book_name_hash(account_guid_hash, account_name_hash, program_id)?;
-        balance_account.name_hash = account_name_hash.clone();
+        if !balance_account
+            .spending_limit
+            .record_spend(token_mint, amount, now)
+        {
+            return Ok(false);
+        }
         self.balance_accounts.replace(slot_id, balance_account);
-        Ok(())
+        Ok(true)
     }
 
-    pub fn update_address_book_name_hash(
+    pub fn update_dapps_enabled(
         &mut self,
         account_guid_hash: &BalanceAccountGuidHash,
-        account_name_hash: &BalanceAccountNameHash,
-        program_id: &Pubkey,
+        enabled: BooleanSetting,
     ) -> ProgramResult {
-        let (source_account_pda, _) = Pubkey::find_program_address(
-            &[
-                self.wallet_guid_hash.to_bytes(),
-                account_guid_hash.to_bytes(),
-            ],
+        let (slot_id, mut balance_account) =
+            self.get_balance_account_with_slot_id(account_guid_hash)?;
+        balance_account.dapps_enabled = enabled;
+        self.balance_accounts.replace(slot_id, balance_account);
+        Ok(())
+    }
+
+    pub fn update_system_owned_destinations_only(
+        &mut self,
+        account_guid_hash: &BalanceAccountGuidHash,
+        enabled: BooleanSetting,
+    ) -> ProgramResult {
+        let (slot_id, mut balance_account) =
+            self.get_balance_account_with_slot_id(account_guid_hash)?;
+        balance_account.system_owned_destinations_only = enabled;
+        self.balance_accounts.replace(slot_id, balance_account);
+        Ok(())
+    }
+
+    pub fn update_archived(
+        &mut self,
+        account_guid_hash: &BalanceAccountGuidHash,
+        archived: BooleanSetting,
+    ) -> ProgramResult {
+        let (slot_id, mut balance_account) =
+            self.get_balance_account_with_slot_id(account_guid_hash)?;
+        balance_account.archived = archived;
+        self.balance_accounts.replace(slot_id, balance_account);
+        Ok(())
+    }
+
+    pub fn validate_balance_account_deletion(
+        &self,
+        account_guid_hash: &BalanceAccountGuidHash,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let mut self_clone = self.clone();
+        self_clone.delete_balance_account(account_guid_hash, program_id)
+    }
+
+    /// Frees the balance account's slot, along with the address book entry of its PDA, which
+    /// is no longer whitelisted by any other balance account.
+    pub fn delete_balance_account(
+        &mut self,
+        account_guid_hash: &BalanceAccountGuidHash,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let (slot_id, balance_account) =
+            self.get_balance_account_with_slot_id(account_guid_hash)?;
+        // reward split policies refer to balance accounts by slot, so a split paying into this
+        // one would pay into whichever account takes its slot next
+        if self
+            .balance_accounts
+            .filled_slots()
+            .iter()
+            .any(|(other_slot_id, other_account)| {
+                *other_slot_id != slot_id
+                    && other_account.reward_split_policy.percentage(slot_id) > 0
+            })
+        {
+            msg!("Balance account is paid into by a reward split of another balance account");
+            return Err(WalletError::BalanceAccountInUse.into());
+        }
+        self.balance_accounts.remove(slot_id, balance_account);
+
+        let (balance_account_pda, _) =
+            BalanceAccount::find_address(&self.wallet_guid_hash, account_guid_hash, program_id);
+        if let Some(entry) = self
+            .address_book
+            .find_by(|entry| entry.address == balance_account_pda)
+        {
+            for (other_slot_id, mut other_account) in self.balance_accounts.filled_slots() {
+                other_account.allowed_destinations.disable(&entry.0);
+                self.balance_accounts.replace(other_slot_id, other_account);
+            }
+            self.remove_address_book_entries(&vec![entry])?;
+        }
+        Ok(())
+    }
+
+    pub fn validate_balance_account_name_update(
+        &self,
+        account_guid_hash: &BalanceAccountGuidHash,
+        account_name_hash: &BalanceAccountNameHash,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let mut self_clone = self.clone();
+        self_clone.update_balance_account_name_hash(
+            account_guid_hash,
+            account_name_hash,
             program_id,
-        );
+        )
+    }
+
+    pub fn update_balance_account_name_hash(
+        &mut self,
+        account_guid_hash: &BalanceAccountGuidHash,
+        account_name_hash: &BalanceAccountNameHash,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let (slot_id, mut balance_account) =
+            self.get_balance_account_with_slot_id(account_guid_hash)?;
+        self.update_address_book_name_hash(account_guid_hash, account_name_hash, program_id)?;
+        balance_account.name_hash = account_name_hash.clone();
+        self.balance_accounts.replace(slot_id, balance_account);
+        Ok(())
+    }
+
+    pub fn update_address_book_name_hash(
+        &mut self,
+        account_guid_hash: &BalanceAccountGuidHash,
+        account_name_hash: &BalanceAccountNameHash,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let (source_account_pda, _) =
+            BalanceAccount::find_address(&self.wallet_guid_hash, account_guid_hash, program_id);
         let (slot_id, mut address_book_entry) =
             self.get_address_book_entry_with_slot_id(&source_account_pda)?;
         address_book_entry.name_hash =
@@ -569,6 +2195,16 @@ impl Wallet {
         balance_account.approval_timeout_for_transfer = update.approval_timeout_for_transfer;
         balance_account.approvals_required_for_transfer = update.approvals_required_for_transfer;
 
+        if update.finalize_delay_policy.delay > Wallet::MAX_APPROVAL_TIMEOUT {
+            msg!(
+                "Finalize delay can't be more than {} seconds",
+                Wallet::MAX_APPROVAL_TIMEOUT.as_secs(),
+            );
+            return Err(WalletError::InvalidFinalizeDelay.into());
+        }
+        balance_account.finalize_delay_policy = update.finalize_delay_policy;
+        balance_account.mint_cap = update.mint_cap;
+
         self.validate_signers_hash(&update.transfer_approvers, &update.signers_hash)?;
 
         let approvers_count_after_update = balance_account.transfer_approvers.count_enabled();
@@ -593,10 +2229,51 @@ impl Wallet {
             return Err(WalletError::NoApproversEnabled.into());
         }
 
+        Wallet::validate_approval_tiers(&balance_account, &update.approval_tiers)?;
+        balance_account.approval_tiers = ApprovalTiers::from_tiers(&update.approval_tiers);
+
         self.balance_accounts.replace(slot_id, balance_account);
         Ok(())
     }
 
+    fn validate_approval_tiers(
+        balance_account: &BalanceAccount,
+        tiers: &[ApprovalTier],
+    ) -> ProgramResult {
+        if tiers.len() > ApprovalTiers::MAX_TIERS {
+            msg!(
+                "A balance account can have at most {} approval tiers",
+                ApprovalTiers::MAX_TIERS
+            );
+            return Err(WalletError::InvalidApprovalTiers.into());
+        }
+        if !is_strictly_ascending_by_key(tiers, |tier| tier.min_amount) {
+            msg!("Approval tiers must be in strictly ascending order of minimum amount");
+            return Err(WalletError::InvalidApprovalTiers.into());
+        }
+        for tier in tiers {
+            if tier
+                .approvers
+                .iter_enabled()
+                .any(|id| !balance_account.transfer_approvers.is_enabled(&id))
+            {
+                msg!("Approval tier approvers must be transfer approvers of the balance account");
+                return Err(WalletError::InvalidApprovalTiers.into());
+            }
+            if tier.approvals_required == 0
+                || usize::from(tier.approvals_required) > tier.approvers.count_enabled()
+            {
+                msg!(
+                    "Approval tier requires {} approvals of {} approvers",
+                    tier.approvals_required,
+                    tier.approvers.count_enabled()
+                );
+                return Err(WalletError::InvalidApprovalTiers.into());
+            }
+        }
+        Ok(())
+    }
+
     fn add_signers(&mut self, signers_to_add: &Vec<(SlotId<Signer>, Signer)>) -> ProgramResult {
         if !self.signers.can_be_inserted(signers_to_add) {
             msg!("Failed to add signers: at least one slot cannot be inserted");
@@ -639,6 +2316,14 @@ impl Wallet {
             return Err(WalletError::SlotCannotBeInserted.into());
         }
         self.address_book.insert_many(entries_to_add);
+        // entries added through a multisig op don't need to be ratified separately
+        for (id, _) in entries_to_add {
+            self.pending_address_book_entries.disable(id);
+            self.address_book_usage.clear(*id);
+            self.address_book_risks
+                .set(*id, AddressBookEntryRisk::Standard);
+            self.address_book_expirations.set(*id, None);
+        }
         Ok(())
     }
 
@@ -658,6 +2343,32 @@ impl Wallet {
             }
         }
         self.address_book.remove_many(entries_to_remove);
+        for (id, _) in entries_to_remove {
+            self.pending_address_book_entries.disable(id);
+            self.address_book_usage.clear(*id);
+            self.address_book_risks
+                .set(*id, AddressBookEntryRisk::Standard);
+            self.address_book_expirations.set(*id, None);
+        }
+        Ok(())
+    }
+
+    fn deprecate_address_book_entries(
+        &mut self,
+        entries_to_deprecate: &Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+        now: i64,
+    ) -> ProgramResult {
+        if !self.address_book.contains(entries_to_deprecate) {
+            msg!("Failed to deprecate address book entries: at least one of the provided entries is not present in the config");
+            return Err(WalletError::UnknownAddressBookEntry.into());
+        }
+        for (id, _) in entries_to_deprecate {
+            if self.address_book_usage.is_deprecated(*id) {
+                msg!("Failed to deprecate address book entries: at least one entry is already deprecated");
+                return Err(WalletError::AddressBookEntryDeprecated.into());
+            }
+            self.address_book_usage.get_mut(*id).deprecated_at = Some(now);
+        }
         Ok(())
     }
 
@@ -670,6 +2381,11 @@ impl Wallet {
             return Err(WalletError::SlotCannotBeInserted.into());
         }
         self.dapp_book.insert_many(entries_to_add);
+        for (id, _) in entries_to_add {
+            self.dapp_book_expirations.set(*id, None);
+            self.dapp_book_categories.set(*id, DAppCategories::NONE);
+            self.dapp_book_instruction_limits.set(*id, None);
+        }
         Ok(())
     }
 
@@ -682,6 +2398,162 @@ impl Wallet {
             return Err(WalletError::SlotCannotBeRemoved.into());
         }
         self.dapp_book.remove_many(entries_to_remove);
+        for (id, _) in entries_to_remove {
+            self.dapp_book_expirations.set(*id, None);
+            self.dapp_book_categories.set(*id, DAppCategories::NONE);
+            self.dapp_book_instruction_limits.set(*id, None);
+        }
+        Ok(())
+    }
+
+    /// Checks the kinds entries are declared as against the entries, which have to be among
+    /// those the update adds.
+    fn validate_address_book_entry_kinds(update: &AddressBookUpdate) -> ProgramResult {
+        for (id, kind) in &update.address_book_entry_kinds {
+            let entry = match update
+                .add_address_book_entries
+                .iter()
+                .find(|(added_id, _)| added_id == id)
+            {
+                Some((_, entry)) => entry,
+                None => {
+                    msg!(
+                        "Failed to check address book entry kind: slot {} isn't being added",
+                        id.value
+                    );
+                    return Err(WalletError::UnknownAddressBookEntry.into());
+                }
+            };
+            if !kind.matches_derivation(&entry.address) {
+                msg!(
+                    "Address book entry {} isn't derived as a {:?} would be",
+                    entry.address,
+                    kind
+                );
+                return Err(WalletError::AddressBookEntryKindMismatch.into());
+            }
+        }
+        Ok(())
+    }
+
+    fn set_address_book_risks(
+        &mut self,
+        risks: &[(SlotId<AddressBookEntry>, AddressBookEntryRisk)],
+    ) -> ProgramResult {
+        for (id, risk) in risks {
+            if id.value >= Wallet::MAX_ADDRESS_BOOK_ENTRIES || self.address_book[*id].is_none() {
+                msg!(
+                    "Failed to classify address book entry: slot {} is empty",
+                    id.value
+                );
+                return Err(WalletError::UnknownAddressBookEntry.into());
+            }
+            self.address_book_risks.set(*id, *risk);
+        }
+        Ok(())
+    }
+
+    fn set_address_book_expirations(
+        &mut self,
+        expirations: &[AddressBookExpiration],
+        now: i64,
+    ) -> ProgramResult {
+        for (id, expires_at) in expirations {
+            if id.value >= Wallet::MAX_ADDRESS_BOOK_ENTRIES || self.address_book[*id].is_none() {
+                msg!(
+                    "Failed to set address book expiry: slot {} is empty",
+                    id.value
+                );
+                return Err(WalletError::InvalidAddressBookExpiry.into());
+            }
+            if let Some(expires_at) = expires_at {
+                if *expires_at <= now {
+                    msg!(
+                        "Failed to set address book expiry: {} is not in the future",
+                        expires_at
+                    );
+                    return Err(WalletError::InvalidAddressBookExpiry.into());
+                }
+            }
+            self.address_book_expirations.set(*id, *expires_at);
+        }
+        Ok(())
+    }
+
+    fn set_dapp_book_expirations(
+        &mut self,
+        expirations: &[DAppExpiration],
+        now: i64,
+    ) -> ProgramResult {
+        for (id, expires_at) in expirations {
+            if id.value >= Wallet::MAX_DAPP_BOOK_ENTRIES || self.dapp_book[*id].is_none() {
+                msg!("Failed to set dapp book expiry: slot {} is empty", id.value);
+                return Err(WalletError::InvalidDAppBookExpiry.into());
+            }
+            if let Some(expires_at) = expires_at {
+                if *expires_at <= now {
+                    msg!(
+                        "Failed to set dapp book expiry: {} is not in the future",
+                        expires_at
+                    );
+                    return Err(WalletError::InvalidDAppBookExpiry.into());
+                }
+            }
+            self.dapp_book_expirations.set(*id, *expires_at);
+        }
+        Ok(())
+    }
+
+    fn set_dapp_book_categories(
+        &mut self,
+        categories: &[(SlotId<DAppBookEntry>, DAppCategories)],
+    ) -> ProgramResult {
+        for (id, entry_categories) in categories {
+            if id.value >= Wallet::MAX_DAPP_BOOK_ENTRIES || self.dapp_book[*id].is_none() {
+                msg!("Failed to tag dapp book entry: slot {} is empty", id.value);
+                return Err(WalletError::InvalidDAppCategories.into());
+            }
+            self.dapp_book_categories.set(*id, *entry_categories);
+        }
+        Ok(())
+    }
+
+    fn set_dapp_book_instruction_limits(
+        &mut self,
+        limits: &[(SlotId<DAppBookEntry>, Option<u8>)],
+    ) -> ProgramResult {
+        for (id, max_instructions) in limits {
+            if id.value >= Wallet::MAX_DAPP_BOOK_ENTRIES || self.dapp_book[*id].is_none() {
+                msg!(
+                    "Failed to limit dapp book entry: slot {} is empty",
+                    id.value
+                );
+                return Err(WalletError::InvalidDAppInstructionLimit.into());
+            }
+            if usize::from(max_instructions.unwrap_or(0)) > MAX_INSTRUCTION_COUNT {
+                msg!(
+                    "DApp instruction limit {:?} exceeds {}",
+                    max_instructions,
+                    MAX_INSTRUCTION_COUNT
+                );
+                return Err(WalletError::InvalidDAppInstructionLimit.into());
+            }
+            self.dapp_book_instruction_limits
+                .set(*id, *max_instructions);
+        }
+        Ok(())
+    }
+
+    fn set_disallowed_dapp_categories(
+        &mut self,
+        policies: &[(BalanceAccountGuidHash, DAppCategories)],
+    ) -> ProgramResult {
+        for (account_guid_hash, categories) in policies {
+            let (slot_id, mut balance_account) =
+                self.get_balance_account_with_slot_id(account_guid_hash)?;
+            balance_account.disallowed_dapp_categories = *categories;
+            self.balance_accounts.replace(slot_id, balance_account);
+        }
         Ok(())
     }
 
@@ -693,6 +2565,7 @@ impl Wallet {
             msg!("One of the specified config approver slots is not a signer slot");
             return Err(WalletError::UnknownSigner.into());
         }
+        self.validate_signers_capability(signer_slots, SignerCapabilities::APPROVE_CONFIG)?;
         self.config_approvers
             .enable_many(&signer_slots.iter().map(|signer| signer).collect_vec());
         Ok(())
@@ -707,12 +2580,29 @@ impl Wallet {
             msg!("Failed to enable transfer approvers: one of the given transfer approvers is not configured as signer");
             return Err(WalletError::UnknownSigner.into());
         }
+        self.validate_signers_capability(signer_slots, SignerCapabilities::APPROVE_TRANSFER)?;
         balance_account
             .transfer_approvers
             .enable_many(&signer_slots.iter().map(|signer| signer).collect_vec());
         Ok(())
     }
 
+    fn validate_signers_capability(
+        &self,
+        signer_slots: &[SlotId<Signer>],
+        capability: SignerCapabilities,
+    ) -> ProgramResult {
+        if signer_slots
+            .iter()
+            .filter_map(|id| self.signers[*id])
+            .any(|signer| !signer.can(capability))
+        {
+            msg!("One of the specified approvers lacks the capability to approve");
+            return Err(WalletError::SignerMissingCapability.into());
+        }
+        Ok(())
+    }
+
     fn enable_transfer_destinations_by_slot(
         &mut self,
         balance_account: &mut BalanceAccount,
@@ -722,6 +2612,20 @@ impl Wallet {
             msg!("Failed to enable transfer destinations: address book does not contain one of the given destinations");
             return Err(WalletError::UnknownAddressBookEntry.into());
         }
+        if self
+            .pending_address_book_entries
+            .any_enabled(&destination_slots.iter().collect_vec())
+        {
+            msg!("Failed to enable transfer destinations: one of the given destinations has not been ratified");
+            return Err(WalletError::AddressBookEntryPending.into());
+        }
+        if destination_slots
+            .iter()
+            .any(|id| self.address_book_usage.is_deprecated(*id))
+        {
+            msg!("Failed to enable transfer destinations: one of the given destinations is deprecated");
+            return Err(WalletError::AddressBookEntryDeprecated.into());
+        }
         if !destination_slots.is_empty() && balance_account.is_whitelist_disabled() {
             msg!("Cannot add destinations when whitelisting status is Off");
             return Err(WalletError::WhitelistDisabled.into());
@@ -758,9 +2662,9 @@ impl Wallet {
     }
 
     pub fn rent_return_from_slice(src: &[u8]) -> Result<Pubkey, ProgramError> {
-        if src.len() >= 1 + VERSION_LEN + PUBKEY_BYTES {
+        if src.len() >= Wallet::RENT_RETURN_OFFSET + PUBKEY_BYTES {
             if src[0] == 1 {
-                let buf = array_ref!(src, 1 + VERSION_LEN, PUBKEY_BYTES);
+                let buf = array_ref!(src, Wallet::RENT_RETURN_OFFSET, PUBKEY_BYTES);
                 Ok(Pubkey::new_from_array(*buf))
             } else {
                 Err(ProgramError::UninitializedAccount)
@@ -771,9 +2675,9 @@ impl Wallet {
     }
 
     pub fn wallet_guid_hash_from_slice(src: &[u8]) -> Result<WalletGuidHash, ProgramError> {
-        if src.len() >= 1 + VERSION_LEN + PUBKEY_BYTES + HASH_LEN {
+        if src.len() >= Wallet::WALLET_GUID_HASH_OFFSET + HASH_LEN {
             if src[0] == 1 {
-                let buf = array_ref!(src, 1 + VERSION_LEN + PUBKEY_BYTES, HASH_LEN);
+                let buf = array_ref!(src, Wallet::WALLET_GUID_HASH_OFFSET, HASH_LEN);
                 Ok(WalletGuidHash::new(buf))
             } else {
                 Err(ProgramError::UninitializedAccount)
@@ -783,11 +2687,91 @@ impl Wallet {
         }
     }
 
-    fn validate_signers_hash(
-        &self,
-        signer_slots: &Vec<SlotId<Signer>>,
-        provided_hash: &Hash,
-    ) -> ProgramResult {
+    pub fn finalize_submitter_policy_from_slice(
+        src: &[u8],
+    ) -> Result<FinalizeSubmitterPolicy, ProgramError> {
+        if src.len() >= Wallet::LEN {
+            if src[0] == 1 {
+                FinalizeSubmitterPolicy::unpack_from_slice(
+                    &src[Wallet::FINALIZE_SUBMITTER_POLICY_OFFSET..],
+                )
+            } else {
+                Err(ProgramError::UninitializedAccount)
+            }
+        } else {
+            Err(ProgramError::InvalidAccountData)
+        }
+    }
+
+    /// Reads the rent return allowlist without unpacking the rest of the wallet.
+    pub fn rent_return_allowlist_from_slice(
+        src: &[u8],
+    ) -> Result<RentReturnAllowlist, ProgramError> {
+        if src.len() >= Wallet::LEN {
+            if src[0] == 1 {
+                RentReturnAllowlist::unpack_from_slice(&src[Wallet::RENT_RETURN_ALLOWLIST_OFFSET..])
+            } else {
+                Err(ProgramError::UninitializedAccount)
+            }
+        } else {
+            Err(ProgramError::InvalidAccountData)
+        }
+    }
+
+    /// Reads the fee policy without unpacking the rest of the wallet.
+    pub fn fee_policy_from_slice(src: &[u8]) -> Result<FeePolicy, ProgramError> {
+        if src.len() >= Wallet::LEN {
+            if src[0] == 1 {
+                FeePolicy::unpack_from_slice(&src[Wallet::FEE_POLICY_OFFSET..])
+            } else {
+                Err(ProgramError::UninitializedAccount)
+            }
+        } else {
+            Err(ProgramError::InvalidAccountData)
+        }
+    }
+
+    /// Reads the op nonce without unpacking the rest of the wallet.
+    pub fn op_nonce_from_slice(src: &[u8]) -> Result<u64, ProgramError> {
+        if src.len() >= Wallet::LEN {
+            if src[0] == 1 {
+                Ok(u64::from_le_bytes(*array_ref!(
+                    src,
+                    Wallet::OP_NONCE_OFFSET,
+                    8
+                )))
+            } else {
+                Err(ProgramError::UninitializedAccount)
+            }
+        } else {
+            Err(ProgramError::InvalidAccountData)
+        }
+    }
+
+    /// Bumps the op nonce in place, without unpacking the rest of the wallet, once a config op
+    /// has been finalized.
+    pub fn bump_op_nonce_in_slice(dst: &mut [u8]) -> ProgramResult {
+        let op_nonce = Wallet::op_nonce_from_slice(dst)?
+            .checked_add(1)
+            .ok_or(WalletError::AmountOverflow)?;
+        array_mut_ref![dst, Wallet::OP_NONCE_OFFSET, 8].copy_from_slice(&op_nonce.to_le_bytes());
+        Ok(())
+    }
+
+    /// Reads the state summary without unpacking the rest of the wallet.
+    pub fn state_summary_from_slice(src: &[u8]) -> Result<StateSummary, ProgramError> {
+        if src.len() >= Wallet::LEN {
+            if src[0] == 1 {
+                StateSummary::unpack_from_slice(&src[Wallet::STATE_SUMMARY_OFFSET..])
+            } else {
+                Err(ProgramError::UninitializedAccount)
+            }
+        } else {
+            Err(ProgramError::InvalidAccountData)
+        }
+    }
+
+    fn signers_hash(&self, signer_slots: &Vec<SlotId<Signer>>) -> Result<Hash, ProgramError> {
         let mut bytes: Vec<u8> = Vec::new();
         for id in signer_slots {
             if let Some(signer) = self.signers[*id] {
@@ -796,7 +2780,15 @@ impl Wallet {
                 return Err(WalletError::UnknownSigner.into());
             }
         }
-        if hash(&bytes) != *provided_hash {
+        Ok(hash(&bytes))
+    }
+
+    fn validate_signers_hash(
+        &self,
+        signer_slots: &Vec<SlotId<Signer>>,
+        provided_hash: &Hash,
+    ) -> ProgramResult {
+        if self.signers_hash(signer_slots)? != *provided_hash {
             msg!("Signers hash did not match");
             return Err(WalletError::InvalidSignersHash.into());
         }
@@ -852,9 +2844,9 @@ impl Wallet {
 
 impl Versioned for Wallet {
     fn version_from_slice(src: &[u8]) -> Result<u32, ProgramError> {
-        if src.len() >= 1 + VERSION_LEN {
+        if src.len() >= Wallet::VERSION_OFFSET + VERSION_LEN {
             if src[0] == 1 {
-                let buf = array_ref!(src, 1, VERSION_LEN);
+                let buf = array_ref!(src, Wallet::VERSION_OFFSET, VERSION_LEN);
                 Ok(u32::from_le_bytes(*buf))
             } else {
                 Err(ProgramError::UninitializedAccount)
@@ -871,13 +2863,39 @@ impl Pack for Wallet {
         PUBKEY_BYTES + // rent return
         HASH_LEN + // wallet guid hash
         Signers::LEN +
-        Signer::LEN + // assistant
+        Assistants::LEN +
         AddressBook::LEN +
+        PendingAddressBookEntries::STORAGE_SIZE + // pending address book entries
+        8 + // pending_address_book_window_start
+        1 + // pending_address_book_entries_in_window
+        AddressBookUsage::LEN + // address book usage
         1 + // approvals_required_for_config
         8 + // approval_timeout_for_config
         Approvers::STORAGE_SIZE + // config approvers
+        8 + // config_policy_timelock
+        PendingConfigPolicy::LEN + // pending config policy
         DAppBook::LEN +
-        BalanceAccounts::LEN;
+        BalanceAccounts::LEN +
+        DAppBookExpirations::LEN + // dapp book expirations
+        FinalizeSubmitterPolicy::LEN + // finalize submitter policy
+        CompliancePolicy::LEN + // compliance policy
+        WalletReplication::LEN + // replication
+        PriceOracle::LEN + // price oracle
+        WalletFreeze::LEN + // freeze
+        RentReturnAllowlist::LEN + // rent return allowlist
+        SignerActivations::LEN + // signer activations
+        FeePolicy::LEN + // fee policy
+        StakeWeightGovernance::LEN + // stake weight governance
+        AddressBookRisks::LEN + // address book risks
+        AddressBookExpirations::LEN + // address book expirations
+        DAppBookCategories::LEN + // dapp book categories
+        DAppBookInstructionLimits::LEN + // dapp book instruction limits
+        8 + // op nonce
+        ApprovalGroup::LEN + // approval group
+        WalletProvisioning::LEN + // provisioning
+        WrapperBook::LEN + // wrapper book
+        RetiredSigners::LEN + // retired signers
+        StateSummary::LEN; // state summary
 
     fn pack_into_slice(&self, dst: &mut [u8]) {
         let dst = array_mut_ref![dst, 0, Wallet::LEN];
@@ -887,13 +2905,39 @@ impl Pack for Wallet {
             rent_return_dst,
             wallet_guid_hash_dst,
             signers_dst,
-            assistant_account_dst,
+            assistants_dst,
             address_book_dst,
+            pending_address_book_entries_dst,
+            pending_address_book_window_start_dst,
+            pending_address_book_entries_in_window_dst,
+            address_book_usage_dst,
             approvals_required_for_config_dst,
             approval_timeout_for_config_dst,
             config_approvers_dst,
+            config_policy_timelock_dst,
+            pending_config_policy_dst,
             dapp_book_dst,
             balance_accounts_dst,
+            dapp_book_expirations_dst,
+            finalize_submitter_policy_dst,
+            compliance_policy_dst,
+            replication_dst,
+            price_oracle_dst,
+            freeze_dst,
+            rent_return_allowlist_dst,
+            signer_activations_dst,
+            fee_policy_dst,
+            stake_weight_governance_dst,
+            address_book_risks_dst,
+            address_book_expirations_dst,
+            dapp_book_categories_dst,
+            dapp_book_instruction_limits_dst,
+            op_nonce_dst,
+            approval_group_dst,
+            provisioning_dst,
+            wrapper_book_dst,
+            retired_signers_dst,
+            state_summary_dst,
         ) = mut_array_refs![
             dst,
             1,
@@ -901,13 +2945,39 @@ impl Pack for Wallet {
             PUBKEY_BYTES,
             HASH_LEN,
             Signers::LEN,
-            Signer::LEN,
+            Assistants::LEN,
             AddressBook::LEN,
+            PendingAddressBookEntries::STORAGE_SIZE,
+            8,
+            1,
+            AddressBookUsage::LEN,
             1,
             8,
             Approvers::STORAGE_SIZE,
+            8,
+            PendingConfigPolicy::LEN,
             DAppBook::LEN,
-            BalanceAccounts::LEN
+            BalanceAccounts::LEN,
+            DAppBookExpirations::LEN,
+            FinalizeSubmitterPolicy::LEN,
+            CompliancePolicy::LEN,
+            WalletReplication::LEN,
+            PriceOracle::LEN,
+            WalletFreeze::LEN,
+            RentReturnAllowlist::LEN,
+            SignerActivations::LEN,
+            FeePolicy::LEN,
+            StakeWeightGovernance::LEN,
+            AddressBookRisks::LEN,
+            AddressBookExpirations::LEN,
+            DAppBookCategories::LEN,
+            DAppBookInstructionLimits::LEN,
+            8,
+            ApprovalGroup::LEN,
+            WalletProvisioning::LEN,
+            WrapperBook::LEN,
+            RetiredSigners::LEN,
+            StateSummary::LEN
         ];
 
         is_initialized_dst[0] = self.is_initialized as u8;
@@ -915,13 +2985,56 @@ impl Pack for Wallet {
         rent_return_dst.copy_from_slice(self.rent_return.as_ref());
         wallet_guid_hash_dst.copy_from_slice(&self.wallet_guid_hash.0);
         self.signers.pack_into_slice(signers_dst);
-        self.assistant.pack_into_slice(assistant_account_dst);
+        self.assistants.pack_into_slice(assistants_dst);
         self.address_book.pack_into_slice(address_book_dst);
+        pending_address_book_entries_dst
+            .copy_from_slice(self.pending_address_book_entries.as_bytes());
+        *pending_address_book_window_start_dst =
+            self.pending_address_book_window_start.to_le_bytes();
+        pending_address_book_entries_in_window_dst[0] = self.pending_address_book_entries_in_window;
+        self.address_book_usage
+            .pack_into_slice(address_book_usage_dst);
         approvals_required_for_config_dst[0] = self.approvals_required_for_config;
         *approval_timeout_for_config_dst = self.approval_timeout_for_config.as_secs().to_le_bytes();
         config_approvers_dst.copy_from_slice(self.config_approvers.as_bytes());
+        *config_policy_timelock_dst = self.config_policy_timelock.as_secs().to_le_bytes();
+        PendingConfigPolicy::pack_into_slice(
+            &self.pending_config_policy,
+            pending_config_policy_dst,
+        );
         self.dapp_book.pack_into_slice(dapp_book_dst);
         self.balance_accounts.pack_into_slice(balance_accounts_dst);
+        self.dapp_book_expirations
+            .pack_into_slice(dapp_book_expirations_dst);
+        self.finalize_submitter_policy
+            .pack_into_slice(finalize_submitter_policy_dst);
+        CompliancePolicy::pack_into_slice(&self.compliance_policy, compliance_policy_dst);
+        WalletReplication::pack_into_slice(&self.replication, replication_dst);
+        PriceOracle::pack_into_slice(&self.price_oracle, price_oracle_dst);
+        self.freeze.pack_into_slice(freeze_dst);
+        self.rent_return_allowlist
+            .pack_into_slice(rent_return_allowlist_dst);
+        self.signer_activations
+            .pack_into_slice(signer_activations_dst);
+        self.fee_policy.pack_into_slice(fee_policy_dst);
+        StakeWeightGovernance::pack_into_slice(
+            &self.stake_weight_governance,
+            stake_weight_governance_dst,
+        );
+        self.address_book_risks
+            .pack_into_slice(address_book_risks_dst);
+        self.address_book_expirations
+            .pack_into_slice(address_book_expirations_dst);
+        self.dapp_book_categories
+            .pack_into_slice(dapp_book_categories_dst);
+        self.dapp_book_instruction_limits
+            .pack_into_slice(dapp_book_instruction_limits_dst);
+        *op_nonce_dst = self.op_nonce.to_le_bytes();
+        ApprovalGroup::pack_into_slice(&self.approval_group, approval_group_dst);
+        WalletProvisioning::pack_into_slice(&self.provisioning, provisioning_dst);
+        self.wrapper_book.pack_into_slice(wrapper_book_dst);
+        self.retired_signers.pack_into_slice(retired_signers_dst);
+        StateSummary::of(self).pack_into_slice(state_summary_dst);
     }
 
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
@@ -932,13 +3045,39 @@ impl Pack for Wallet {
             rent_return,
             wallet_guid_hash,
             signers_src,
-            assistant,
+            assistants_src,
             address_book_src,
+            pending_address_book_entries_src,
+            pending_address_book_window_start,
+            pending_address_book_entries_in_window,
+            address_book_usage_src,
             approvals_required_for_config,
             approval_timeout_for_config,
             config_approvers_src,
+            config_policy_timelock,
+            pending_config_policy_src,
             dapp_book_src,
             balance_accounts_src,
+            dapp_book_expirations_src,
+            finalize_submitter_policy_src,
+            compliance_policy_src,
+            replication_src,
+            price_oracle_src,
+            freeze_src,
+            rent_return_allowlist_src,
+            signer_activations_src,
+            fee_policy_src,
+            stake_weight_governance_src,
+            address_book_risks_src,
+            address_book_expirations_src,
+            dapp_book_categories_src,
+            dapp_book_instruction_limits_src,
+            op_nonce_src,
+            approval_group_src,
+            provisioning_src,
+            wrapper_book_src,
+            retired_signers_src,
+            _state_summary_src,
         ) = array_refs![
             src,
             1,
@@ -946,13 +3085,39 @@ impl Pack for Wallet {
             PUBKEY_BYTES,
             HASH_LEN,
             Signers::LEN,
-            Signer::LEN,
+            Assistants::LEN,
             AddressBook::LEN,
+            PendingAddressBookEntries::STORAGE_SIZE,
+            8,
+            1,
+            AddressBookUsage::LEN,
             1,
             8,
             Approvers::STORAGE_SIZE,
+            8,
+            PendingConfigPolicy::LEN,
             DAppBook::LEN,
-            BalanceAccounts::LEN
+            BalanceAccounts::LEN,
+            DAppBookExpirations::LEN,
+            FinalizeSubmitterPolicy::LEN,
+            CompliancePolicy::LEN,
+            WalletReplication::LEN,
+            PriceOracle::LEN,
+            WalletFreeze::LEN,
+            RentReturnAllowlist::LEN,
+            SignerActivations::LEN,
+            FeePolicy::LEN,
+            StakeWeightGovernance::LEN,
+            AddressBookRisks::LEN,
+            AddressBookExpirations::LEN,
+            DAppBookCategories::LEN,
+            DAppBookInstructionLimits::LEN,
+            8,
+            ApprovalGroup::LEN,
+            WalletProvisioning::LEN,
+            WrapperBook::LEN,
+            RetiredSigners::LEN,
+            StateSummary::LEN
         ];
 
         Ok(Wallet {
@@ -965,15 +3130,2545 @@ impl Pack for Wallet {
             rent_return: Pubkey::new_from_array(*rent_return),
             wallet_guid_hash: WalletGuidHash::new(wallet_guid_hash),
             signers: Signers::unpack_from_slice(signers_src)?,
-            assistant: Signer::unpack_from_slice(assistant)?,
+            assistants: Assistants::unpack_from_slice(assistants_src)?,
             address_book: AddressBook::unpack_from_slice(address_book_src)?,
+            pending_address_book_entries: PendingAddressBookEntries::new(
+                *pending_address_book_entries_src,
+            ),
+            pending_address_book_window_start: i64::from_le_bytes(
+                *pending_address_book_window_start,
+            ),
+            pending_address_book_entries_in_window: pending_address_book_entries_in_window[0],
+            address_book_usage: AddressBookUsage::unpack_from_slice(address_book_usage_src),
             approvals_required_for_config: approvals_required_for_config[0],
             approval_timeout_for_config: Duration::from_secs(u64::from_le_bytes(
                 *approval_timeout_for_config,
             )),
             config_approvers: Approvers::new(*config_approvers_src),
+            config_policy_timelock: Duration::from_secs(u64::from_le_bytes(
+                *config_policy_timelock,
+            )),
+            pending_config_policy: PendingConfigPolicy::unpack_from_slice(
+                pending_config_policy_src,
+            ),
             balance_accounts: BalanceAccounts::unpack_from_slice(balance_accounts_src)?,
             dapp_book: DAppBook::unpack_from_slice(dapp_book_src)?,
+            dapp_book_expirations: DAppBookExpirations::unpack_from_slice(
+                dapp_book_expirations_src,
+            ),
+            finalize_submitter_policy: FinalizeSubmitterPolicy::unpack_from_slice(
+                finalize_submitter_policy_src,
+            )?,
+            compliance_policy: CompliancePolicy::unpack_from_slice(compliance_policy_src),
+            replication: WalletReplication::unpack_from_slice(replication_src),
+            price_oracle: PriceOracle::unpack_from_slice(price_oracle_src)?,
+            freeze: WalletFreeze::unpack_from_slice(freeze_src)?,
+            rent_return_allowlist: RentReturnAllowlist::unpack_from_slice(
+                rent_return_allowlist_src,
+            )?,
+            signer_activations: SignerActivations::unpack_from_slice(signer_activations_src),
+            fee_policy: FeePolicy::unpack_from_slice(fee_policy_src)?,
+            stake_weight_governance: StakeWeightGovernance::unpack_from_slice(
+                stake_weight_governance_src,
+            ),
+            address_book_risks: AddressBookRisks::unpack_from_slice(address_book_risks_src)?,
+            address_book_expirations: AddressBookExpirations::unpack_from_slice(
+                address_book_expirations_src,
+            ),
+            dapp_book_categories: DAppBookCategories::unpack_from_slice(dapp_book_categories_src)?,
+            dapp_book_instruction_limits: DAppBookInstructionLimits::unpack_from_slice(
+                dapp_book_instruction_limits_src,
+            ),
+            op_nonce: u64::from_le_bytes(*op_nonce_src),
+            approval_group: ApprovalGroup::unpack_from_slice(approval_group_src),
+            provisioning: WalletProvisioning::unpack_from_slice(provisioning_src),
+            wrapper_book: WrapperBook::unpack_from_slice(wrapper_book_src)?,
+            retired_signers: RetiredSigners::unpack_from_slice(retired_signers_src),
         })
     }
 }
+
+// the size of existing wallet accounts; a layout change that alters it needs a migration
+const _: () = assert!(Wallet::LEN == 15300);
+
+// the offsets past the variable-length fields have to land on their fields in the pack layout:
+// each of them plus the fields packed after it has to make up the whole wallet
+const _: () = assert!(
+    Wallet::FINALIZE_SUBMITTER_POLICY_OFFSET
+        + FinalizeSubmitterPolicy::LEN
+        + CompliancePolicy::LEN
+        + WalletReplication::LEN
+        + PriceOracle::LEN
+        + WalletFreeze::LEN
+        + RentReturnAllowlist::LEN
+        + SignerActivations::LEN
+        + FeePolicy::LEN
+        + StakeWeightGovernance::LEN
+        + AddressBookRisks::LEN
+        + AddressBookExpirations::LEN
+        + DAppBookCategories::LEN
+        + DAppBookInstructionLimits::LEN
+        + 8 // op nonce
+        + ApprovalGroup::LEN
+        + WalletProvisioning::LEN
+        + WrapperBook::LEN
+        + RetiredSigners::LEN
+        + StateSummary::LEN
+        == Wallet::LEN
+);
+const _: () = assert!(
+    Wallet::RENT_RETURN_ALLOWLIST_OFFSET
+        + RentReturnAllowlist::LEN
+        + SignerActivations::LEN
+        + FeePolicy::LEN
+        + StakeWeightGovernance::LEN
+        + AddressBookRisks::LEN
+        + AddressBookExpirations::LEN
+        + DAppBookCategories::LEN
+        + DAppBookInstructionLimits::LEN
+        + 8 // op nonce
+        + ApprovalGroup::LEN
+        + WalletProvisioning::LEN
+        + WrapperBook::LEN
+        + RetiredSigners::LEN
+        + StateSummary::LEN
+        == Wallet::LEN
+);
+const _: () = assert!(
+    Wallet::FEE_POLICY_OFFSET
+        + FeePolicy::LEN
+        + StakeWeightGovernance::LEN
+        + AddressBookRisks::LEN
+        + AddressBookExpirations::LEN
+        + DAppBookCategories::LEN
+        + DAppBookInstructionLimits::LEN
+        + 8 // op nonce
+        + ApprovalGroup::LEN
+        + WalletProvisioning::LEN
+        + WrapperBook::LEN
+        + RetiredSigners::LEN
+        + StateSummary::LEN
+        == Wallet::LEN
+);
+const _: () = assert!(
+    Wallet::OP_NONCE_OFFSET
+        + 8 // op nonce
+        + ApprovalGroup::LEN
+        + WalletProvisioning::LEN
+        + WrapperBook::LEN
+        + RetiredSigners::LEN
+        + StateSummary::LEN
+        == Wallet::LEN
+);
+
+#[cfg(test)]
+mod test {
+    use crate::constants::HASH_LEN;
+    use crate::error::WalletError;
+    use crate::instruction::{
+        AddressBookUpdate, BalanceAccountCreation, BalanceAccountPolicyUpdate, DAppBookUpdate,
+        FullRotation, InitialWalletConfig, RewardSplit, WalletConfigPolicyUpdate,
+    };
+    use crate::model::address_book::{
+        AddressBookEntry, AddressBookEntryNameHash, AddressBookEntryRisk, AllowedDestinationsPage,
+        DAppCategories, JurisdictionCode,
+    };
+    use crate::model::assistant::Assistant;
+    use crate::model::balance_account::{
+        ApprovalTier, ApprovalTiers, BalanceAccount, BalanceAccountGuidHash,
+        BalanceAccountNameHash, FinalizeDelayPolicy, RewardSplitPolicy, SpendingLimit,
+    };
+    use crate::model::dapp_multisig_data::MAX_INSTRUCTION_COUNT;
+    use crate::model::fee_policy::FeePolicy;
+    use crate::model::finalize_submitter_policy::{FinalizeSubmitterPolicy, FinalizeSubmitters};
+    use crate::model::multisig_op::{BooleanSetting, SlotUpdateType};
+    use crate::model::policy_diff::PolicyDiff;
+    use crate::model::rent_return_allowlist::RentReturnAllowlist;
+    use crate::model::signer::{Signer, SignerCapabilities};
+    use crate::model::signer_activations::SignerActivations;
+    use crate::model::stake_weight_governance::StakeWeightGovernance;
+    use crate::model::state_summary::StateSummary;
+    use crate::model::wallet::{Approvers, Wallet, WalletCompaction, WalletGuidHash};
+    use crate::model::wallet_freeze::WalletFreeze;
+    use crate::model::wallet_provisioning::WalletProvisioning;
+    use crate::model::wallet_replication::{MirroredUpdate, WalletReplication};
+    use crate::model::wrapper_book::{WrapperBook, WrapperEntry};
+    use crate::test_utils::AccountInfoFixture;
+    use crate::utils::SlotId;
+    use crate::version::{Versioned, VERSION};
+    use itertools::Itertools;
+    use solana_program::hash::hash;
+    use solana_program::program::MAX_RETURN_DATA;
+    use solana_program::program_error::ProgramError;
+    use solana_program::program_pack::Pack;
+    use solana_program::pubkey::Pubkey;
+    use std::time::Duration;
+
+    fn new_wallet() -> Wallet {
+        Wallet::unpack_unchecked(&vec![0; Wallet::LEN]).unwrap()
+    }
+
+    fn signers(count: usize) -> Vec<(SlotId<Signer>, Signer)> {
+        (0..count)
+            .map(|i| (SlotId::new(i), Signer::new(Pubkey::new_unique())))
+            .collect()
+    }
+
+    fn initial_config(
+        signers: &Vec<(SlotId<Signer>, Signer)>,
+        config_approvers: usize,
+        approvals_required: u8,
+    ) -> InitialWalletConfig {
+        InitialWalletConfig {
+            approvals_required_for_config: approvals_required,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: signers.clone(),
+            config_approvers: (0..config_approvers).map(SlotId::new).collect(),
+        }
+    }
+
+    fn initialized_wallet(signers: &Vec<(SlotId<Signer>, Signer)>) -> Wallet {
+        let mut wallet = new_wallet();
+        wallet
+            .init(
+                &WalletGuidHash::new(&[1; HASH_LEN]),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                &initial_config(signers, signers.len(), 1),
+            )
+            .unwrap();
+        wallet
+    }
+
+    fn address_book_entry(i: u8) -> AddressBookEntry {
+        AddressBookEntry {
+            address: Pubkey::new_unique(),
+            name_hash: AddressBookEntryNameHash::new(&hash(&[i]).to_bytes()),
+        }
+    }
+
+    fn err(error: WalletError) -> Result<(), ProgramError> {
+        Err(error.into())
+    }
+
+    #[test]
+    fn test_init() {
+        let signers = signers(2);
+        let wallet_guid_hash = WalletGuidHash::new(&[7; HASH_LEN]);
+        let assistant = Pubkey::new_unique();
+        let rent_return = Pubkey::new_unique();
+        let mut wallet = new_wallet();
+        wallet
+            .init(
+                &wallet_guid_hash,
+                assistant,
+                rent_return,
+                &initial_config(&signers, 2, 2),
+            )
+            .unwrap();
+
+        assert!(wallet.is_initialized);
+        assert_eq!(wallet.version, VERSION);
+        assert_eq!(wallet.rent_return, rent_return);
+        assert_eq!(wallet.wallet_guid_hash, wallet_guid_hash);
+        assert_eq!(
+            wallet.assistants.filled_slots(),
+            vec![(SlotId::new(0), Assistant::new(assistant))]
+        );
+        assert_eq!(
+            wallet.get_config_approvers_keys(),
+            signers.iter().map(|(_, it)| it.key).collect::<Vec<_>>()
+        );
+        assert_eq!(wallet.approvals_required_for_config, 2);
+    }
+
+    #[test]
+    fn test_initialize_approvals_required_bounds() {
+        let signers = signers(4);
+        for approvers in 1..=signers.len() {
+            for approvals_required in 0..=signers.len() as u8 + 1 {
+                let result = new_wallet().initialize(&initial_config(
+                    &signers,
+                    approvers,
+                    approvals_required,
+                ));
+                if approvals_required == 0 || usize::from(approvals_required) > approvers {
+                    assert_eq!(result, err(WalletError::InvalidApproverCount));
+                } else {
+                    assert_eq!(result, Ok(()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_initialize_requires_known_config_approvers() {
+        let signers = signers(2);
+        let mut config = initial_config(&signers, 2, 1);
+        config.config_approvers.push(SlotId::new(2));
+        assert_eq!(
+            new_wallet().initialize(&config),
+            err(WalletError::UnknownSigner)
+        );
+    }
+
+    #[test]
+    fn test_approval_timeout_bounds() {
+        for secs in [
+            0,
+            1,
+            59,
+            60,
+            3600,
+            60 * 60 * 24 * 365,
+            60 * 60 * 24 * 365 + 1,
+        ] {
+            let timeout = Duration::from_secs(secs);
+            let result = Wallet::validate_approval_timeout(&timeout);
+            if timeout < Wallet::MIN_APPROVAL_TIMEOUT || timeout > Wallet::MAX_APPROVAL_TIMEOUT {
+                assert_eq!(result, err(WalletError::InvalidApprovalTimeout));
+            } else {
+                assert_eq!(result, Ok(()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_validate_initiator() {
+        let signers = signers(2);
+        let wallet = initialized_wallet(&signers);
+
+        let mut assistant =
+            AccountInfoFixture::new(wallet.assistants[SlotId::new(0)].unwrap().key).signer();
+        let mut approver = AccountInfoFixture::new(signers[1].1.key).signer();
+        let mut unsigned_approver = AccountInfoFixture::new(signers[1].1.key);
+        let mut stranger = AccountInfoFixture::unique().signer();
+
+        for initiator in [&mut assistant, &mut approver] {
+            let initiator = initiator.account_info();
+            assert_eq!(wallet.validate_config_initiator(&initiator, 0), Ok(()));
+            assert_eq!(wallet.validate_transfer_initiator(&initiator, 0), Ok(()));
+        }
+        assert_eq!(
+            wallet.validate_config_initiator(&unsigned_approver.account_info(), 0),
+            err(WalletError::InvalidSignature)
+        );
+        assert_eq!(
+            wallet.validate_transfer_initiator(&stranger.account_info(), 0),
+            err(WalletError::InvalidApprover)
+        );
+    }
+
+    #[test]
+    fn test_update_assistants() {
+        let mut wallet = initialized_wallet(&signers(1));
+        let original = wallet.assistants[SlotId::new(0)].unwrap();
+        let automation = Assistant::with_rate_limit(Pubkey::new_unique(), true, 2);
+
+        assert_eq!(
+            wallet.update_assistant(SlotUpdateType::SetIfEmpty, SlotId::new(0), automation),
+            err(WalletError::SlotCannotBeInserted)
+        );
+        assert_eq!(
+            wallet.update_assistant(SlotUpdateType::SetIfEmpty, SlotId::new(1), original),
+            err(WalletError::SlotCannotBeInserted)
+        );
+        assert_eq!(
+            wallet.update_assistant(
+                SlotUpdateType::SetIfEmpty,
+                SlotId::new(Wallet::MAX_ASSISTANTS),
+                automation
+            ),
+            err(WalletError::SlotCannotBeInserted)
+        );
+        assert_eq!(
+            wallet.update_assistant(SlotUpdateType::SetIfEmpty, SlotId::new(1), automation),
+            Ok(())
+        );
+
+        // reconfiguring a key keeps its usage in the current window
+        assert_eq!(
+            wallet.record_assistant_initiation(&automation.key, 100),
+            Ok(true)
+        );
+        let disabled = Assistant::with_rate_limit(automation.key, false, 5);
+        assert_eq!(
+            wallet.update_assistant(SlotUpdateType::SetIfEmpty, SlotId::new(1), disabled),
+            Ok(())
+        );
+        let updated = wallet.assistants[SlotId::new(1)].unwrap();
+        assert!(!updated.enabled);
+        assert_eq!(updated.max_initiations_per_window, 5);
+        assert_eq!(updated.initiations_in_window, 1);
+
+        assert_eq!(
+            wallet.update_assistant(SlotUpdateType::Clear, SlotId::new(1), original),
+            err(WalletError::SlotCannotBeRemoved)
+        );
+        assert_eq!(
+            wallet.update_assistant(SlotUpdateType::Clear, SlotId::new(1), disabled),
+            Ok(())
+        );
+        assert_eq!(wallet.assistants[SlotId::new(1)], None);
+    }
+
+    #[test]
+    fn test_disabled_assistant_cannot_initiate() {
+        let mut wallet = initialized_wallet(&signers(1));
+        let key = Pubkey::new_unique();
+        wallet
+            .assistants
+            .insert(SlotId::new(1), Assistant::with_rate_limit(key, false, 0));
+        let mut assistant = AccountInfoFixture::new(key).signer();
+        assert_eq!(
+            wallet.validate_config_initiator(&assistant.account_info(), 0),
+            err(WalletError::AssistantDisabled)
+        );
+        assert_eq!(
+            wallet.validate_transfer_initiator(&assistant.account_info(), 0),
+            err(WalletError::AssistantDisabled)
+        );
+    }
+
+    #[test]
+    fn test_assistant_rate_limit() {
+        let mut wallet = initialized_wallet(&signers(1));
+        let unlimited = wallet.assistants[SlotId::new(0)].unwrap().key;
+        let limited = Pubkey::new_unique();
+        wallet
+            .assistants
+            .insert(SlotId::new(1), Assistant::with_rate_limit(limited, true, 3));
+
+        for _ in 0..10 {
+            assert_eq!(wallet.record_assistant_initiation(&unlimited, 0), Ok(false));
+        }
+        assert_eq!(
+            wallet.record_assistant_initiation(&Pubkey::new_unique(), 0),
+            Ok(false)
+        );
+
+        let window = Assistant::RATE_LIMIT_WINDOW_SECS;
+        let now = 1_700_000_000;
+        for start in [now, now + window, now + 3 * window] {
+            for offset in 0..3 {
+                assert_eq!(
+                    wallet.record_assistant_initiation(&limited, start + offset),
+                    Ok(true)
+                );
+            }
+            assert_eq!(
+                wallet.record_assistant_initiation(&limited, start + window - 1),
+                Err(WalletError::AssistantRateLimitExceeded.into())
+            );
+        }
+    }
+
+    #[test]
+    fn test_config_approvers_require_capability() {
+        let mut signers = signers(2);
+        signers[1].1 = Signer::with_capabilities(signers[1].1.key, SignerCapabilities::INITIATE);
+        assert_eq!(
+            new_wallet().initialize(&initial_config(&signers, 2, 1)),
+            err(WalletError::SignerMissingCapability)
+        );
+        assert_eq!(
+            new_wallet().initialize(&initial_config(&signers, 1, 1)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_full_rotation() {
+        let signers = signers(3);
+        let mut wallet = initialized_wallet(&signers);
+        let old_rent_return = wallet.rent_return;
+        wallet.update_rent_return_allowlist(&RentReturnAllowlist {
+            entries: vec![old_rent_return],
+        });
+        let rotation = FullRotation {
+            assistant: Pubkey::new_unique(),
+            rent_return: Pubkey::new_unique(),
+            signer_keys: vec![
+                (signers[0].1.key, Pubkey::new_unique()),
+                (signers[2].1.key, Pubkey::new_unique()),
+            ],
+        };
+
+        // rotating a key that isn't a signer's, one key twice, or onto another signer's key
+        let invalid = err(WalletError::InvalidFullRotation);
+        for signer_keys in [
+            vec![(Pubkey::new_unique(), Pubkey::new_unique())],
+            vec![
+                (signers[0].1.key, Pubkey::new_unique()),
+                (signers[0].1.key, Pubkey::new_unique()),
+            ],
+            vec![(signers[0].1.key, signers[1].1.key)],
+        ] {
+            assert_eq!(
+                wallet.validate_full_rotation(&FullRotation {
+                    signer_keys,
+                    ..rotation.clone()
+                }),
+                invalid
+            );
+        }
+
+        let config_approvers = wallet.config_approvers;
+        assert_eq!(wallet.validate_full_rotation(&rotation), Ok(()));
+        assert_eq!(wallet.apply_full_rotation(&rotation), Ok(()));
+        assert_eq!(
+            wallet.get_signers_keys(),
+            vec![
+                rotation.signer_keys[0].1,
+                signers[1].1.key,
+                rotation.signer_keys[1].1
+            ]
+        );
+        // the new keys take over the slots, and so the approver bitmaps, of the old ones
+        assert_eq!(wallet.config_approvers, config_approvers);
+        assert_eq!(
+            wallet.get_config_approvers_keys(),
+            wallet.get_signers_keys()
+        );
+        assert_eq!(
+            wallet.assistants.filled_slots(),
+            vec![(SlotId::new(0), Assistant::new(rotation.assistant))]
+        );
+        assert_eq!(wallet.rent_return, rotation.rent_return);
+        assert_eq!(
+            wallet.rent_return_allowlist.entries,
+            vec![rotation.rent_return]
+        );
+    }
+
+    #[test]
+    fn test_emergency_freeze() {
+        let signers = signers(3);
+        let mut wallet = initialized_wallet(&signers);
+
+        assert_eq!(
+            wallet.validate_freeze(&WalletFreeze {
+                frozen: false,
+                emergency_quorum: 4,
+            }),
+            err(WalletError::InvalidEmergencyFreezeQuorum)
+        );
+        let mut first = AccountInfoFixture::new(signers[0].1.key).signer();
+        let mut first_again = AccountInfoFixture::new(signers[0].1.key).signer();
+        let mut second = AccountInfoFixture::new(signers[1].1.key).signer();
+        let mut unsigned_third = AccountInfoFixture::new(signers[2].1.key);
+        let mut stranger = AccountInfoFixture::unique().signer();
+
+        // no quorum disables it
+        assert_eq!(
+            wallet.emergency_freeze(&[first.account_info(), second.account_info()], 0),
+            err(WalletError::EmergencyFreezeQuorumNotMet)
+        );
+
+        let freeze = WalletFreeze {
+            frozen: false,
+            emergency_quorum: 2,
+        };
+        assert_eq!(wallet.validate_freeze(&freeze), Ok(()));
+        wallet.update_freeze(&freeze);
+        // repeated, unsigned and unknown signers don't count towards it
+        assert_eq!(
+            wallet.emergency_freeze(
+                &[
+                    first.account_info(),
+                    first_again.account_info(),
+                    unsigned_third.account_info(),
+                    stranger.account_info(),
+                ],
+                0
+            ),
+            err(WalletError::EmergencyFreezeQuorumNotMet)
+        );
+        assert_eq!(wallet.validate_not_frozen(), Ok(()));
+
+        assert_eq!(
+            wallet.emergency_freeze(&[first.account_info(), second.account_info()], 0),
+            Ok(())
+        );
+        assert_eq!(wallet.validate_not_frozen(), err(WalletError::WalletFrozen));
+        assert_eq!(wallet.freeze.emergency_quorum, 2);
+    }
+
+    #[test]
+    fn test_provisioning() {
+        let signers = signers(3);
+        let mut wallet = initialized_wallet(&signers);
+        let creator = Pubkey::new_unique();
+        wallet.provisioning = Some(WalletProvisioning { creator });
+        assert_eq!(
+            wallet.validate_not_provisioning(),
+            err(WalletError::WalletIsProvisioning)
+        );
+
+        // only the creator can edit the config, which is replaced as a whole
+        let mut creator_account = AccountInfoFixture::new(creator).signer();
+        let mut unsigned_creator = AccountInfoFixture::new(creator);
+        let mut stranger = AccountInfoFixture::unique().signer();
+        assert_eq!(
+            wallet.validate_provisioning_creator(&unsigned_creator.account_info()),
+            err(WalletError::InvalidProvisioningCreator)
+        );
+        assert_eq!(
+            wallet.validate_provisioning_creator(&stranger.account_info()),
+            err(WalletError::InvalidProvisioningCreator)
+        );
+        assert_eq!(
+            wallet.validate_provisioning_creator(&creator_account.account_info()),
+            Ok(())
+        );
+        let new_signers = vec![signers[1].clone(), signers[2].clone()];
+        wallet
+            .reprovision(&InitialWalletConfig {
+                approvals_required_for_config: 2,
+                approval_timeout_for_config: Duration::from_secs(3600),
+                signers: new_signers.clone(),
+                config_approvers: vec![signers[1].0, signers[2].0],
+            })
+            .unwrap();
+        assert_eq!(wallet.signers.filled_slots(), new_signers);
+        assert_eq!(
+            wallet.get_config_approvers_keys(),
+            vec![signers[1].1.key, signers[2].1.key]
+        );
+        assert_eq!(wallet.approvals_required_for_config, 2);
+
+        // activation takes every config approver's signature
+        let mut first_approver = AccountInfoFixture::new(signers[1].1.key).signer();
+        let mut second_approver = AccountInfoFixture::new(signers[2].1.key).signer();
+        let mut former_signer = AccountInfoFixture::new(signers[0].1.key).signer();
+        assert_eq!(
+            wallet.activate(&[first_approver.account_info(), former_signer.account_info()]),
+            err(WalletError::ActivationNotSignedByAllApprovers)
+        );
+        assert_eq!(
+            wallet.activate(&[
+                first_approver.account_info(),
+                second_approver.account_info()
+            ]),
+            Ok(())
+        );
+        assert_eq!(wallet.validate_not_provisioning(), Ok(()));
+        assert_eq!(
+            wallet.validate_provisioning_creator(&creator_account.account_info()),
+            err(WalletError::WalletNotProvisioning)
+        );
+        assert_eq!(
+            wallet.activate(&[
+                first_approver.account_info(),
+                second_approver.account_info()
+            ]),
+            err(WalletError::WalletNotProvisioning)
+        );
+    }
+
+    #[test]
+    fn test_initiator_capabilities() {
+        let mut signers = signers(3);
+        signers.push((
+            SlotId::new(3),
+            Signer::with_capabilities(
+                Pubkey::new_unique(),
+                SignerCapabilities::APPROVE_CONFIG.union(SignerCapabilities::APPROVE_TRANSFER),
+            ),
+        ));
+        signers.push((
+            SlotId::new(4),
+            Signer::with_capabilities(Pubkey::new_unique(), SignerCapabilities::RESIGN),
+        ));
+        let mut wallet = new_wallet();
+        wallet
+            .assistants
+            .insert(SlotId::new(0), Assistant::new(Pubkey::new_unique()));
+        wallet.initialize(&initial_config(&signers, 4, 1)).unwrap();
+
+        let mut approve_only = AccountInfoFixture::new(signers[3].1.key).signer();
+        let mut resign_only = AccountInfoFixture::new(signers[4].1.key).signer();
+        assert_eq!(
+            wallet.validate_config_initiator(&approve_only.account_info(), 0),
+            err(WalletError::SignerMissingCapability)
+        );
+        assert_eq!(
+            wallet.validate_transfer_initiator(&resign_only.account_info(), 0),
+            err(WalletError::SignerMissingCapability)
+        );
+
+        // a signer holding RESIGN may only initiate its own removal
+        assert_eq!(
+            wallet.validate_update_signer_initiator(
+                &resign_only.account_info(),
+                SlotUpdateType::Clear,
+                &signers[4].1,
+                0
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            wallet.validate_update_signer_initiator(
+                &resign_only.account_info(),
+                SlotUpdateType::Clear,
+                &signers[2].1,
+                0
+            ),
+            err(WalletError::SignerMissingCapability)
+        );
+        assert_eq!(
+            wallet.validate_update_signer_initiator(
+                &approve_only.account_info(),
+                SlotUpdateType::Clear,
+                &signers[3].1,
+                0
+            ),
+            err(WalletError::SignerMissingCapability)
+        );
+    }
+
+    #[test]
+    fn test_signer_activation_delay() {
+        let signers = signers(3);
+        let mut wallet = initialized_wallet(&signers[..2].to_vec());
+        assert_eq!(
+            wallet.update_signer_activation_delay(
+                SignerActivations::MAX_DELAY + Duration::from_secs(1),
+                0
+            ),
+            err(WalletError::InvalidSignerActivationDelay)
+        );
+        wallet
+            .update_signer_activation_delay(Duration::from_secs(100), 0)
+            .unwrap();
+        wallet.add_signer(signers[2], 1000).unwrap();
+        wallet.config_approvers.enable(&signers[2].0);
+
+        // signers present at creation are active right away, added ones once the delay elapses
+        let mut added = AccountInfoFixture::new(signers[2].1.key).signer();
+        assert_eq!(
+            wallet.validate_config_initiator(&added.account_info(), 1099),
+            err(WalletError::SignerNotYetActive)
+        );
+        assert_eq!(
+            wallet.get_active_config_approvers_keys(1099),
+            vec![signers[0].1.key, signers[1].1.key]
+        );
+        assert_eq!(
+            wallet.validate_config_initiator(&added.account_info(), 1100),
+            Ok(())
+        );
+        assert_eq!(wallet.get_active_config_approvers_keys(1100).len(), 3);
+
+        // a removed and re-added signer waits out the delay again
+        wallet.config_approvers.disable(&signers[2].0);
+        wallet.remove_signer(signers[2]).unwrap();
+        wallet.add_signer(signers[2], 2000).unwrap();
+        assert_eq!(
+            wallet.validate_transfer_initiator(&added.account_info(), 2050),
+            err(WalletError::SignerNotYetActive)
+        );
+    }
+
+    #[test]
+    fn test_successor_of_retired_signer() {
+        let signers = signers(3);
+        let mut wallet = initialized_wallet(&signers);
+        wallet
+            .update_signer_activation_delay(Duration::from_secs(100), 0)
+            .unwrap();
+        let retired = signers[1].1.key;
+        assert_eq!(wallet.successor_of(&retired, 0), None);
+
+        // a key added to a cleared slot doesn't succeed the key cleared from it
+        wallet.config_approvers.disable(&signers[1].0);
+        wallet.remove_signer(signers[1]).unwrap();
+        wallet
+            .add_signer((signers[1].0, Signer::new(Pubkey::new_unique())), 0)
+            .unwrap();
+        assert_eq!(wallet.successor_of(&retired, 1000), None);
+
+        // a key replacing another in its slot succeeds it once it is active
+        let replaced = wallet.signers[signers[1].0].unwrap().key;
+        let successor = Signer::new(Pubkey::new_unique());
+        assert_eq!(
+            wallet.replace_signer((SlotId::new(5), successor), 1000),
+            err(WalletError::SlotCannotBeRemoved)
+        );
+        assert_eq!(
+            wallet.replace_signer((signers[0].0, successor), 1000),
+            err(WalletError::SignerIsConfigApprover)
+        );
+        wallet
+            .replace_signer((signers[1].0, successor), 1000)
+            .unwrap();
+        assert_eq!(wallet.get_signer(&replaced), None);
+        assert_eq!(wallet.successor_of(&replaced, 1099), None);
+        assert_eq!(wallet.successor_of(&replaced, 1100), Some(successor.key));
+        assert_eq!(wallet.successor_of(&signers[0].1.key, 1100), None);
+
+        // clearing the successor's slot forgets the key it replaced
+        wallet.remove_signer((signers[1].0, successor)).unwrap();
+        assert_eq!(wallet.successor_of(&replaced, 1100), None);
+    }
+
+    #[test]
+    fn test_validate_config_approvers_signed() {
+        let signers = signers(3);
+        let mut wallet = initialized_wallet(&signers);
+        wallet.approvals_required_for_config = 2;
+        wallet.config_approvers.disable(&signers[2].0);
+        let mut first = AccountInfoFixture::new(signers[0].1.key).signer();
+        let mut first_again = AccountInfoFixture::new(signers[0].1.key).signer();
+        let mut unsigned_second = AccountInfoFixture::new(signers[1].1.key);
+        let mut second = AccountInfoFixture::new(signers[1].1.key).signer();
+        let mut not_approver = AccountInfoFixture::new(signers[2].1.key).signer();
+
+        // repeated, unsigned and non-approving signers don't count towards the quorum
+        assert_eq!(
+            wallet.validate_config_approvers_signed(
+                &[
+                    first.account_info(),
+                    first_again.account_info(),
+                    unsigned_second.account_info(),
+                    not_approver.account_info(),
+                ],
+                0
+            ),
+            err(WalletError::ConfigApproverQuorumNotMet)
+        );
+        assert_eq!(
+            wallet.validate_config_approvers_signed(
+                &[first.account_info(), second.account_info()],
+                0
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_config_policy_update() {
+        let signers = signers(3);
+        let mut wallet = initialized_wallet(&signers);
+        let mut update = WalletConfigPolicyUpdate {
+            approvals_required_for_config: 2,
+            approval_timeout_for_config: Duration::from_secs(7200),
+            config_approvers: vec![SlotId::new(0), SlotId::new(2)],
+            signers_hash: hash(&[]),
+            config_policy_timelock: Wallet::DEFAULT_CONFIG_POLICY_TIMELOCK,
+        };
+        update.signers_hash = hash(
+            &update
+                .config_approvers
+                .iter()
+                .flat_map(|id| signers[id.value].1.key.to_bytes())
+                .collect::<Vec<_>>(),
+        );
+
+        let mut too_many_required = update.clone();
+        too_many_required.approvals_required_for_config = 3;
+        assert_eq!(
+            wallet.validate_config_policy_update(&too_many_required),
+            err(WalletError::InvalidApproverCount)
+        );
+
+        let mut none_required = update.clone();
+        none_required.approvals_required_for_config = 0;
+        assert_eq!(
+            wallet.validate_config_policy_update(&none_required),
+            err(WalletError::InvalidApproverCount)
+        );
+
+        let mut wrong_hash = update.clone();
+        wrong_hash.signers_hash = hash(&[]);
+        assert_eq!(
+            wallet.validate_config_policy_update(&wrong_hash),
+            err(WalletError::InvalidSignersHash)
+        );
+
+        // failed validation leaves the wallet untouched
+        assert_eq!(wallet.get_config_approvers_keys().len(), 3);
+
+        wallet.update_config_policy(&update).unwrap();
+        assert_eq!(
+            wallet.get_config_approvers_keys(),
+            vec![signers[0].1.key, signers[2].1.key]
+        );
+        assert_eq!(wallet.approvals_required_for_config, 2);
+        assert_eq!(
+            wallet.approval_timeout_for_config,
+            Duration::from_secs(7200)
+        );
+    }
+
+    #[test]
+    fn test_config_policy_timelock() {
+        let signers = signers(3);
+        let mut wallet = initialized_wallet(&signers);
+        assert_eq!(
+            wallet.config_policy_timelock,
+            Wallet::DEFAULT_CONFIG_POLICY_TIMELOCK
+        );
+        let update = |approvals_required: u8, approvers: Vec<usize>, timelock: u64| {
+            let config_approvers: Vec<SlotId<Signer>> =
+                approvers.into_iter().map(SlotId::new).collect();
+            WalletConfigPolicyUpdate {
+                approvals_required_for_config: approvals_required,
+                approval_timeout_for_config: Duration::from_secs(3600),
+                signers_hash: hash(
+                    &config_approvers
+                        .iter()
+                        .flat_map(|id| signers[id.value].1.key.to_bytes())
+                        .collect::<Vec<_>>(),
+                ),
+                config_approvers,
+                config_policy_timelock: Duration::from_secs(timelock),
+            }
+        };
+        let timelock = Wallet::DEFAULT_CONFIG_POLICY_TIMELOCK.as_secs();
+
+        // raising the approvals required takes effect right away
+        wallet
+            .schedule_config_policy_update(&update(3, vec![0, 1, 2], timelock), 100)
+            .unwrap();
+        assert_eq!(wallet.approvals_required_for_config, 3);
+        assert_eq!(wallet.pending_config_policy, None);
+
+        // lowering it is staged until the timelock elapses
+        let lowered = update(1, vec![2, 0], timelock);
+        wallet.schedule_config_policy_update(&lowered, 100).unwrap();
+        assert_eq!(wallet.approvals_required_for_config, 3);
+        assert_eq!(
+            wallet.pending_config_policy.unwrap().activates_at,
+            100 + timelock as i64
+        );
+        assert_eq!(
+            wallet.activate_pending_config_policy(99 + timelock as i64),
+            err(WalletError::ConfigPolicyTimelocked)
+        );
+        wallet
+            .activate_pending_config_policy(100 + timelock as i64)
+            .unwrap();
+        assert_eq!(wallet.approvals_required_for_config, 1);
+        assert_eq!(
+            wallet.get_config_approvers_keys(),
+            vec![signers[0].1.key, signers[2].1.key]
+        );
+        assert_eq!(
+            wallet.activate_pending_config_policy(100 + timelock as i64),
+            err(WalletError::NoPendingConfigPolicy)
+        );
+
+        // shortening the timelock is staged as well, and superseded by an update taking effect
+        wallet
+            .schedule_config_policy_update(&update(1, vec![0, 2], 0), 200)
+            .unwrap();
+        assert!(wallet.pending_config_policy.is_some());
+        wallet
+            .schedule_config_policy_update(&update(2, vec![0, 1, 2], timelock), 300)
+            .unwrap();
+        assert_eq!(wallet.approvals_required_for_config, 2);
+        assert_eq!(wallet.pending_config_policy, None);
+
+        // an update which fails validation is not staged
+        let mut wrong_hash = update(1, vec![0], timelock);
+        wrong_hash.signers_hash = hash(&[]);
+        assert_eq!(
+            wallet.schedule_config_policy_update(&wrong_hash, 400),
+            err(WalletError::InvalidSignersHash)
+        );
+        assert_eq!(
+            wallet.schedule_config_policy_update(
+                &update(
+                    2,
+                    vec![0, 1],
+                    Wallet::MAX_CONFIG_POLICY_TIMELOCK.as_secs() + 1
+                ),
+                400
+            ),
+            err(WalletError::InvalidConfigPolicyTimelock)
+        );
+        assert_eq!(wallet.pending_config_policy, None);
+    }
+
+    #[test]
+    fn test_policy_diff() {
+        let signers = signers(3);
+        let mut wallet = initialized_wallet(&signers);
+        let signers_hash = |slots: &Vec<SlotId<Signer>>| {
+            hash(
+                &slots
+                    .iter()
+                    .flat_map(|id| signers[id.value].1.key.to_bytes())
+                    .collect::<Vec<_>>(),
+            )
+        };
+        let approvers = |slots: Vec<usize>| {
+            Approvers::from_enabled_vec(slots.into_iter().map(SlotId::new).collect())
+        };
+
+        let config_approvers = vec![SlotId::new(0), SlotId::new(2)];
+        let before = wallet.clone();
+        let diff = wallet
+            .config_policy_diff(&WalletConfigPolicyUpdate {
+                approvals_required_for_config: 2,
+                approval_timeout_for_config: Duration::from_secs(7200),
+                signers_hash: signers_hash(&config_approvers),
+                config_approvers,
+                config_policy_timelock: Duration::ZERO,
+            })
+            .unwrap();
+        assert_eq!(wallet, before);
+        assert_eq!(
+            diff,
+            PolicyDiff {
+                approvers_added: approvers(vec![]),
+                approvers_removed: approvers(vec![1]),
+                approvals_required_before: 1,
+                approvals_required_after: 2,
+                approval_timeout_before: Duration::from_secs(3600),
+                approval_timeout_after: Duration::from_secs(7200),
+                delay_before: Wallet::DEFAULT_CONFIG_POLICY_TIMELOCK,
+                delay_after: Duration::ZERO,
+            }
+        );
+        // dropping the timelock weakens the policy even though more approvals are required
+        assert!(diff.weakens_policy());
+
+        let mut buf = [0; PolicyDiff::LEN];
+        diff.pack_into_slice(&mut buf);
+        assert_eq!(PolicyDiff::unpack_from_slice(&buf), Ok(diff));
+
+        let mut balance_account =
+            BalanceAccount::unpack_unchecked(&vec![0; BalanceAccount::LEN]).unwrap();
+        balance_account.guid_hash = balance_account_guid_hash(0);
+        balance_account.approvals_required_for_transfer = 1;
+        balance_account.transfer_approvers.enable(&SlotId::new(0));
+        wallet
+            .balance_accounts
+            .insert(SlotId::new(0), balance_account);
+        let transfer_approvers = vec![SlotId::new(1), SlotId::new(2)];
+        let mut update = BalanceAccountPolicyUpdate {
+            approvals_required_for_transfer: 2,
+            approval_timeout_for_transfer: Duration::from_secs(3600),
+            signers_hash: signers_hash(&transfer_approvers),
+            transfer_approvers,
+            finalize_delay_policy: FinalizeDelayPolicy {
+                threshold: 0,
+                delay: Duration::from_secs(60),
+            },
+            mint_cap: 0,
+            approval_tiers: vec![],
+        };
+        let diff = wallet
+            .balance_account_policy_diff(&balance_account_guid_hash(0), &update)
+            .unwrap();
+        assert_eq!(
+            diff,
+            PolicyDiff {
+                approvers_added: approvers(vec![1, 2]),
+                approvers_removed: approvers(vec![0]),
+                approvals_required_before: 1,
+                approvals_required_after: 2,
+                approval_timeout_before: Duration::ZERO,
+                approval_timeout_after: Duration::from_secs(3600),
+                delay_before: Duration::ZERO,
+                delay_after: Duration::from_secs(60),
+            }
+        );
+        assert!(!diff.weakens_policy());
+
+        assert_eq!(
+            wallet.balance_account_policy_diff(&balance_account_guid_hash(1), &update),
+            Err(WalletError::BalanceAccountNotFound.into())
+        );
+        update.approvals_required_for_transfer = 3;
+        assert_eq!(
+            wallet.balance_account_policy_diff(&balance_account_guid_hash(0), &update),
+            Err(WalletError::InvalidApproverCount.into())
+        );
+        update.approvals_required_for_transfer = 0;
+        assert_eq!(
+            wallet.balance_account_policy_diff(&balance_account_guid_hash(0), &update),
+            Err(WalletError::InvalidApproverCount.into())
+        );
+    }
+
+    #[test]
+    fn test_remove_config_approver_signer() {
+        let signers = signers(2);
+        let mut wallet = initialized_wallet(&signers);
+        assert_eq!(
+            wallet.remove_signer(signers[0]),
+            err(WalletError::SignerIsConfigApprover)
+        );
+        assert_eq!(
+            wallet.add_signer((signers[0].0, Signer::new(Pubkey::new_unique())), 0),
+            err(WalletError::SlotCannotBeInserted)
+        );
+    }
+
+    #[test]
+    fn test_pending_address_book_entries() {
+        let mut wallet = initialized_wallet(&signers(1));
+        let now = 1_700_000_000;
+        let entries: Vec<(SlotId<AddressBookEntry>, AddressBookEntry)> = (0
+            ..=Wallet::MAX_PENDING_ADDRESS_BOOK_ENTRIES)
+            .map(|i| (SlotId::new(i), address_book_entry(i as u8)))
+            .collect();
+
+        assert_eq!(
+            wallet.add_pending_address_book_entries(&entries, now),
+            err(WalletError::TooManyPendingAddressBookEntries)
+        );
+        assert_eq!(
+            wallet.add_pending_address_book_entries(&vec![entries[0], entries[0]], now),
+            err(WalletError::SlotCannotBeInserted)
+        );
+
+        let pending = entries[..Wallet::MAX_PENDING_ADDRESS_BOOK_ENTRIES].to_vec();
+        wallet
+            .add_pending_address_book_entries(&pending, now)
+            .unwrap();
+        assert_eq!(
+            wallet.pending_address_book_entries.count_enabled(),
+            Wallet::MAX_PENDING_ADDRESS_BOOK_ENTRIES
+        );
+
+        // an entry that was never staged can't be ratified
+        let unknown = vec![entries[Wallet::MAX_PENDING_ADDRESS_BOOK_ENTRIES]];
+        assert_eq!(
+            wallet.ratify_pending_address_book_entries(&unknown),
+            err(WalletError::UnknownAddressBookEntry)
+        );
+
+        wallet
+            .ratify_pending_address_book_entries(&pending[..2].to_vec())
+            .unwrap();
+        assert_eq!(
+            wallet.pending_address_book_entries.count_enabled(),
+            Wallet::MAX_PENDING_ADDRESS_BOOK_ENTRIES - 2
+        );
+        assert_eq!(
+            wallet.ratify_pending_address_book_entries(&pending[..1].to_vec()),
+            err(WalletError::UnknownAddressBookEntry)
+        );
+
+        // ratifying entries frees up pending capacity, but not the staging rate limit
+        let more = vec![entries[Wallet::MAX_PENDING_ADDRESS_BOOK_ENTRIES]];
+        assert_eq!(
+            wallet.add_pending_address_book_entries(&more, now + 1),
+            err(WalletError::PendingAddressBookRateLimitExceeded)
+        );
+        wallet
+            .add_pending_address_book_entries(&more, now + Wallet::PENDING_ADDRESS_BOOK_WINDOW_SECS)
+            .unwrap();
+        assert_eq!(wallet.pending_address_book_entries_in_window, 1);
+        assert_eq!(
+            wallet.pending_address_book_window_start,
+            now + Wallet::PENDING_ADDRESS_BOOK_WINDOW_SECS
+        );
+    }
+
+    #[test]
+    fn test_address_book_risks() {
+        let mut wallet = initialized_wallet(&signers(1));
+        let entry = address_book_entry(0);
+        let other_entry = address_book_entry(1);
+        let jurisdiction = JurisdictionCode::new(*b"KP").unwrap();
+        let update = |add, remove, risks| AddressBookUpdate {
+            add_address_book_entries: add,
+            remove_address_book_entries: remove,
+            deprecate_address_book_entries: vec![],
+            balance_account_whitelist_updates: vec![],
+            address_book_risks: risks,
+            address_book_expirations: vec![],
+            address_book_entry_kinds: vec![],
+        };
+        wallet
+            .update_address_book(
+                &update(
+                    vec![(SlotId::new(0), entry), (SlotId::new(1), other_entry)],
+                    vec![],
+                    vec![(
+                        SlotId::new(0),
+                        AddressBookEntryRisk::HighRisk { jurisdiction },
+                    )],
+                ),
+                1,
+            )
+            .unwrap();
+        assert_eq!(
+            wallet.destination_jurisdiction(&entry.address, &entry.name_hash),
+            Some(jurisdiction)
+        );
+        assert_eq!(
+            wallet.destination_jurisdiction(&other_entry.address, &other_entry.name_hash),
+            None
+        );
+        // the name hash is part of the entry
+        assert_eq!(
+            wallet.destination_jurisdiction(&entry.address, &other_entry.name_hash),
+            None
+        );
+
+        // only entries that are present can be classified
+        assert_eq!(
+            wallet.validate_address_book_update(
+                &update(
+                    vec![],
+                    vec![],
+                    vec![(
+                        SlotId::new(2),
+                        AddressBookEntryRisk::HighRisk { jurisdiction },
+                    )],
+                ),
+                1,
+            ),
+            err(WalletError::UnknownAddressBookEntry)
+        );
+
+        // an entry added to a slot does not inherit the classification of the one removed from it
+        wallet
+            .update_address_book(&update(vec![], vec![(SlotId::new(0), entry)], vec![]), 1)
+            .unwrap();
+        let new_entry = address_book_entry(2);
+        wallet
+            .update_address_book(
+                &update(vec![(SlotId::new(0), new_entry)], vec![], vec![]),
+                1,
+            )
+            .unwrap();
+        assert_eq!(
+            wallet.address_book_risks.get(SlotId::new(0)),
+            AddressBookEntryRisk::Standard
+        );
+        assert_eq!(
+            wallet.destination_jurisdiction(&new_entry.address, &new_entry.name_hash),
+            None
+        );
+
+        assert_eq!(
+            JurisdictionCode::new(*b"kp"),
+            Err(ProgramError::InvalidArgument)
+        );
+    }
+
+    #[test]
+    fn test_address_book_expirations() {
+        let mut wallet = initialized_wallet(&signers(1));
+        let entry = address_book_entry(0);
+        let other_entry = address_book_entry(1);
+        let update = |add, remove, expirations| AddressBookUpdate {
+            add_address_book_entries: add,
+            remove_address_book_entries: remove,
+            deprecate_address_book_entries: vec![],
+            balance_account_whitelist_updates: vec![],
+            address_book_risks: vec![],
+            address_book_expirations: expirations,
+            address_book_entry_kinds: vec![],
+        };
+        wallet
+            .update_address_book(
+                &update(
+                    vec![(SlotId::new(0), entry), (SlotId::new(1), other_entry)],
+                    vec![],
+                    vec![(SlotId::new(0), Some(200))],
+                ),
+                100,
+            )
+            .unwrap();
+        let mut balance_account =
+            BalanceAccount::unpack_unchecked(&vec![0; BalanceAccount::LEN]).unwrap();
+        balance_account.whitelist_enabled = BooleanSetting::On;
+        balance_account.allowed_destinations.enable(&SlotId::new(0));
+        balance_account.allowed_destinations.enable(&SlotId::new(1));
+
+        let allowed = |wallet: &Wallet, entry: AddressBookEntry, now| {
+            wallet.destination_allowed(&balance_account, &entry.address, &entry.name_hash, now)
+        };
+        assert_eq!(allowed(&wallet, entry, 199), Ok(true));
+        assert_eq!(
+            allowed(&wallet, entry, 200),
+            Err(WalletError::AddressBookEntryExpired.into())
+        );
+        assert_eq!(allowed(&wallet, other_entry, i64::MAX), Ok(true));
+
+        // an expiry must be in the future, and for an entry that is present
+        for expirations in [
+            vec![(SlotId::new(0), Some(300))],
+            vec![(SlotId::new(2), Some(400))],
+        ] {
+            assert_eq!(
+                wallet.validate_address_book_update(&update(vec![], vec![], expirations), 300),
+                err(WalletError::InvalidAddressBookExpiry)
+            );
+        }
+
+        // a renewal must push back the expiry of the entry in the slot
+        assert_eq!(
+            wallet.renew_address_book_entry(SlotId::new(0), &other_entry, 400, 300),
+            err(WalletError::UnknownAddressBookEntry)
+        );
+        assert_eq!(
+            wallet.renew_address_book_entry(SlotId::new(0), &entry, 300, 300),
+            err(WalletError::InvalidAddressBookExpiry)
+        );
+        assert_eq!(
+            wallet.renew_address_book_entry(SlotId::new(1), &other_entry, 400, 300),
+            err(WalletError::InvalidAddressBookExpiry)
+        );
+        wallet
+            .renew_address_book_entry(SlotId::new(0), &entry, 400, 300)
+            .unwrap();
+        assert_eq!(allowed(&wallet, entry, 300), Ok(true));
+
+        // an entry added to a slot does not inherit the expiry of the one removed from it
+        wallet
+            .update_address_book(&update(vec![], vec![(SlotId::new(0), entry)], vec![]), 300)
+            .unwrap();
+        let new_entry = address_book_entry(2);
+        wallet
+            .update_address_book(
+                &update(vec![(SlotId::new(0), new_entry)], vec![], vec![]),
+                300,
+            )
+            .unwrap();
+        assert_eq!(wallet.address_book_expirations.get(SlotId::new(0)), None);
+    }
+
+    fn balance_account_guid_hash(i: u8) -> BalanceAccountGuidHash {
+        BalanceAccountGuidHash::new(&hash(&[i]).to_bytes())
+    }
+
+    fn split(i: u8, percentage: u8) -> RewardSplit {
+        RewardSplit {
+            account_guid_hash: balance_account_guid_hash(i),
+            percentage,
+        }
+    }
+
+    #[test]
+    fn test_validate_reward_splits() {
+        let mut wallet = initialized_wallet(&signers(2));
+        for i in 0..3 {
+            let mut balance_account =
+                BalanceAccount::unpack_unchecked(&vec![0; BalanceAccount::LEN]).unwrap();
+            balance_account.guid_hash = balance_account_guid_hash(i);
+            wallet
+                .balance_accounts
+                .insert(SlotId::new(usize::from(i)), balance_account);
+        }
+
+        assert_eq!(wallet.validate_reward_splits(&vec![split(0, 100)]), Ok(()));
+        assert_eq!(
+            wallet.validate_reward_splits(&vec![split(0, 50), split(1, 30), split(2, 20)]),
+            Ok(())
+        );
+
+        for invalid in [
+            vec![],
+            vec![split(0, 99)],
+            vec![split(0, 60), split(1, 60)],
+            vec![split(0, 50), split(0, 50)],
+            vec![split(0, 100), split(1, 0)],
+        ] {
+            assert_eq!(
+                wallet.validate_reward_splits(&invalid),
+                err(WalletError::InvalidRewardSplit)
+            );
+        }
+
+        assert_eq!(
+            wallet.validate_reward_splits(&vec![split(0, 50), split(3, 50)]),
+            err(WalletError::BalanceAccountNotFound)
+        );
+    }
+
+    #[test]
+    fn test_create_balance_account_with_duplicate_guid_hash() {
+        let mut wallet = initialized_wallet(&signers(2));
+        let program_id = Pubkey::new_unique();
+        let account_guid_hash = balance_account_guid_hash(0);
+        let creation_params =
+            |slot_id: usize, address_book_slot_id: usize| BalanceAccountCreation {
+                slot_id: SlotId::new(slot_id),
+                name_hash: BalanceAccountNameHash::new(&[1; HASH_LEN]),
+                approvals_required_for_transfer: 1,
+                approval_timeout_for_transfer: Duration::from_secs(3600),
+                transfer_approvers: vec![SlotId::new(0)],
+                signers_hash: wallet.signers_hash(&vec![SlotId::new(0)]).unwrap(),
+                whitelist_enabled: BooleanSetting::Off,
+                dapps_enabled: BooleanSetting::Off,
+                address_book_slot_id: SlotId::new(address_book_slot_id),
+            };
+        let first = creation_params(0, 0);
+        let second = creation_params(1, 1);
+        wallet
+            .create_balance_account(&account_guid_hash, &first, &program_id)
+            .unwrap();
+
+        // another slot doesn't make the guid hash any less taken
+        let before = wallet.clone();
+        assert_eq!(
+            wallet.validate_balance_account_creation(&account_guid_hash, &second, &program_id),
+            err(WalletError::DuplicateBalanceAccountGuid)
+        );
+        assert_eq!(
+            wallet.create_balance_account(&account_guid_hash, &second, &program_id),
+            err(WalletError::DuplicateBalanceAccountGuid)
+        );
+        assert_eq!(wallet, before);
+        wallet
+            .create_balance_account(&balance_account_guid_hash(1), &second, &program_id)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_delete_balance_account() {
+        let mut wallet = initialized_wallet(&signers(2));
+        let program_id = Pubkey::new_unique();
+        let signers_hash = wallet.signers_hash(&vec![SlotId::new(0)]).unwrap();
+        for i in 0..2 {
+            wallet
+                .create_balance_account(
+                    &balance_account_guid_hash(i),
+                    &BalanceAccountCreation {
+                        slot_id: SlotId::new(usize::from(i)),
+                        name_hash: BalanceAccountNameHash::new(&[i; HASH_LEN]),
+                        approvals_required_for_transfer: 1,
+                        approval_timeout_for_transfer: Duration::from_secs(3600),
+                        transfer_approvers: vec![SlotId::new(0)],
+                        signers_hash,
+                        whitelist_enabled: BooleanSetting::On,
+                        dapps_enabled: BooleanSetting::Off,
+                        address_book_slot_id: SlotId::new(usize::from(i)),
+                    },
+                    &program_id,
+                )
+                .unwrap();
+        }
+        // the second account whitelists the first one, and pays half its rewards into it
+        let (slot_id, mut other_account) = wallet
+            .get_balance_account_with_slot_id(&balance_account_guid_hash(1))
+            .unwrap();
+        wallet
+            .enable_transfer_destinations_by_slot(&mut other_account, &vec![SlotId::new(0)])
+            .unwrap();
+        wallet.balance_accounts.replace(slot_id, other_account);
+        wallet
+            .update_reward_split_policy(
+                &balance_account_guid_hash(1),
+                &vec![split(0, 50), split(1, 50)],
+            )
+            .unwrap();
+
+        assert_eq!(
+            wallet.validate_balance_account_deletion(&balance_account_guid_hash(0), &program_id),
+            err(WalletError::BalanceAccountInUse)
+        );
+        wallet
+            .update_reward_split_policy(&balance_account_guid_hash(1), &vec![split(1, 100)])
+            .unwrap();
+        wallet
+            .delete_balance_account(&balance_account_guid_hash(0), &program_id)
+            .unwrap();
+
+        assert_eq!(
+            wallet.validate_balance_account_guid_hash(&balance_account_guid_hash(0)),
+            err(WalletError::BalanceAccountNotFound)
+        );
+        assert_eq!(wallet.address_book[SlotId::new(0)], None);
+        assert!(wallet.address_book[SlotId::new(1)].is_some());
+        assert!(!wallet
+            .get_balance_account(&balance_account_guid_hash(1))
+            .unwrap()
+            .allowed_destinations
+            .any_enabled(&vec![&SlotId::new(0)]));
+        assert_eq!(
+            wallet.delete_balance_account(&balance_account_guid_hash(0), &program_id),
+            err(WalletError::BalanceAccountNotFound)
+        );
+    }
+
+    #[test]
+    fn test_allowed_destinations_page() {
+        let mut wallet = initialized_wallet(&signers(1));
+        let mut balance_account =
+            BalanceAccount::unpack_unchecked(&vec![0; BalanceAccount::LEN]).unwrap();
+        balance_account.guid_hash = balance_account_guid_hash(0);
+        // every third slot is whitelisted, and slot 30 is whitelisted but has been emptied
+        for i in 0..Wallet::MAX_ADDRESS_BOOK_ENTRIES {
+            if i != 30 {
+                wallet
+                    .address_book
+                    .insert(SlotId::new(i), address_book_entry(i as u8));
+            }
+            if i % 3 == 0 {
+                balance_account.allowed_destinations.enable(&SlotId::new(i));
+            }
+        }
+        wallet
+            .balance_accounts
+            .insert(SlotId::new(0), balance_account);
+
+        let mut entries = Vec::new();
+        let mut start_slot_id = Some(SlotId::new(0));
+        while let Some(start) = start_slot_id {
+            let page = wallet
+                .get_allowed_destinations_page(&balance_account_guid_hash(0), start)
+                .unwrap();
+            let mut packed = Vec::new();
+            page.pack(&mut packed);
+            assert!(packed.len() <= MAX_RETURN_DATA);
+            assert_eq!(AllowedDestinationsPage::unpack(&packed), Ok(page.clone()));
+            start_slot_id = page.next_start_slot_id();
+            entries.extend(page.entries);
+        }
+        assert_eq!(entries.len(), 29);
+        assert_eq!(
+            entries.iter().map(|(_, entry)| *entry).collect_vec(),
+            wallet.get_allowed_destinations(&balance_account)
+        );
+        assert!(entries
+            .iter()
+            .all(|(id, _)| id.value % 3 == 0 && id.value != 30));
+
+        let mut data = vec![0; Wallet::LEN];
+        wallet.pack_into_slice(&mut data);
+        assert_eq!(
+            Wallet::allowed_destinations_from_slice(&data, &balance_account_guid_hash(0)),
+            Ok(wallet.get_allowed_destinations(&balance_account))
+        );
+        assert_eq!(
+            Wallet::allowed_destinations_from_slice(&data, &balance_account_guid_hash(1)),
+            Err(WalletError::BalanceAccountNotFound.into())
+        );
+    }
+
+    #[test]
+    fn test_wallet_replication() {
+        let signers = signers(2);
+        let mut primary = initialized_wallet(&signers);
+        let mut standby = initialized_wallet(&signers);
+        let (primary_address, standby_address) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let program_id = Pubkey::new_unique();
+
+        assert_eq!(
+            primary.validate_replication_update(
+                &primary_address,
+                &Some(WalletReplication::Primary {
+                    standby: primary_address
+                })
+            ),
+            Err(ProgramError::InvalidArgument)
+        );
+
+        // the standby has to agree to mirror the primary first
+        assert_eq!(
+            primary.validate_standby(&primary_address, &standby),
+            err(WalletError::StandbyWalletNotInSync)
+        );
+        standby.update_replication(&Some(WalletReplication::Standby {
+            primary: primary_address,
+        }));
+        primary
+            .validate_standby(&primary_address, &standby)
+            .unwrap();
+        assert_eq!(
+            standby.validate_not_standby(),
+            err(WalletError::WalletIsStandby)
+        );
+
+        // and have the same config to begin with
+        let mut diverged = standby.clone();
+        diverged.approval_timeout_for_config = Duration::from_secs(7200);
+        assert_eq!(
+            primary.validate_standby(&primary_address, &diverged),
+            err(WalletError::StandbyWalletNotInSync)
+        );
+        primary.update_replication(&Some(WalletReplication::Primary {
+            standby: standby_address,
+        }));
+        primary.validate_not_standby().unwrap();
+
+        // only the primary's mirror authority, signing, can update the standby
+        let (authority, _) = WalletReplication::mirror_authority(&primary_address, &program_id);
+        assert_eq!(
+            standby.validate_mirror_authority(
+                &AccountInfoFixture::new(authority).account_info(),
+                &program_id
+            ),
+            err(WalletError::InvalidMirroredUpdate)
+        );
+        let (other_authority, _) =
+            WalletReplication::mirror_authority(&standby_address, &program_id);
+        assert_eq!(
+            standby.validate_mirror_authority(
+                &AccountInfoFixture::new(other_authority)
+                    .signer()
+                    .account_info(),
+                &program_id
+            ),
+            err(WalletError::InvalidMirroredUpdate)
+        );
+        let mut authority = AccountInfoFixture::new(authority).signer();
+        standby
+            .validate_mirror_authority(&authority.account_info(), &program_id)
+            .unwrap();
+        assert_eq!(
+            primary.validate_mirror_authority(&authority.account_info(), &program_id),
+            err(WalletError::InvalidMirroredUpdate)
+        );
+
+        // mirrored updates apply to the standby as they did to the primary
+        let signer = (SlotId::new(2), Signer::new(Pubkey::new_unique()));
+        primary.add_signer(signer, 0).unwrap();
+        standby
+            .apply_mirrored_update(
+                &MirroredUpdate::Signer {
+                    slot_update_type: SlotUpdateType::SetIfEmpty,
+                    slot_id: signer.0,
+                    signer: signer.1,
+                },
+                0,
+            )
+            .unwrap();
+        let update = WalletConfigPolicyUpdate {
+            approvals_required_for_config: 1,
+            approval_timeout_for_config: Duration::from_secs(7200),
+            config_approvers: vec![SlotId::new(0), SlotId::new(2)],
+            signers_hash: primary
+                .signers_hash(&vec![SlotId::new(0), SlotId::new(2)])
+                .unwrap(),
+            config_policy_timelock: Wallet::DEFAULT_CONFIG_POLICY_TIMELOCK,
+        };
+        primary.schedule_config_policy_update(&update, 100).unwrap();
+        standby
+            .apply_mirrored_update(&MirroredUpdate::ConfigPolicy(update), 100)
+            .unwrap();
+        assert_eq!(standby.signers, primary.signers);
+        assert_eq!(standby.config_approvers, primary.config_approvers);
+        assert_eq!(
+            standby.approval_timeout_for_config,
+            primary.approval_timeout_for_config
+        );
+        assert_eq!(standby.pending_config_policy, primary.pending_config_policy);
+    }
+
+    #[test]
+    fn test_update_reward_split_policy() {
+        let mut wallet = initialized_wallet(&signers(2));
+        for i in 0..3 {
+            let mut balance_account =
+                BalanceAccount::unpack_unchecked(&vec![0; BalanceAccount::LEN]).unwrap();
+            balance_account.guid_hash = balance_account_guid_hash(i);
+            wallet
+                .balance_accounts
+                .insert(SlotId::new(usize::from(i)), balance_account);
+        }
+        let account_guid_hash = balance_account_guid_hash(1);
+        let balance_account = wallet.get_balance_account(&account_guid_hash).unwrap();
+        assert_eq!(
+            wallet.get_reward_splits(&balance_account),
+            Err(WalletError::InvalidRewardSplit.into())
+        );
+
+        let before = wallet.clone();
+        assert_eq!(
+            wallet.validate_reward_split_policy_update(&account_guid_hash, &vec![split(0, 99)]),
+            err(WalletError::InvalidRewardSplit)
+        );
+        assert_eq!(
+            wallet.validate_reward_split_policy_update(&account_guid_hash, &vec![split(2, 100)]),
+            Ok(())
+        );
+        assert_eq!(wallet, before);
+
+        wallet
+            .update_reward_split_policy(&account_guid_hash, &vec![split(2, 70), split(0, 30)])
+            .unwrap();
+        let balance_account = wallet.get_balance_account(&account_guid_hash).unwrap();
+        assert_eq!(
+            balance_account.reward_split_policy,
+            RewardSplitPolicy::new([30, 0, 70, 0, 0, 0, 0, 0, 0])
+        );
+        // splits come back in slot order
+        assert_eq!(
+            wallet.get_reward_splits(&balance_account),
+            Ok(vec![split(0, 30), split(2, 70)])
+        );
+    }
+
+    #[test]
+    fn test_spending_limit() {
+        let mut wallet = initialized_wallet(&signers(2));
+        let mut balance_account =
+            BalanceAccount::unpack_unchecked(&vec![0; BalanceAccount::LEN]).unwrap();
+        balance_account.guid_hash = balance_account_guid_hash(0);
+        wallet
+            .balance_accounts
+            .insert(SlotId::new(0), balance_account);
+        let account_guid_hash = balance_account_guid_hash(0);
+        let sol = Pubkey::default();
+        let now = 1_000_000;
+
+        // without a limit every transfer goes through the approval quorum
+        assert_eq!(
+            wallet.record_limited_spend(&account_guid_hash, &sol, 1, now),
+            Ok(false)
+        );
+
+        wallet
+            .update_spending_limit(&account_guid_hash, &sol, 100)
+            .unwrap();
+        assert_eq!(
+            wallet.record_limited_spend(&account_guid_hash, &sol, 60, now),
+            Ok(true)
+        );
+        assert_eq!(
+            wallet.record_limited_spend(&account_guid_hash, &sol, 60, now + 1),
+            Ok(false)
+        );
+        assert_eq!(
+            wallet.record_limited_spend(&account_guid_hash, &sol, 40, now + 1),
+            Ok(true)
+        );
+        // the limit only covers its own mint
+        assert_eq!(
+            wallet.record_limited_spend(&account_guid_hash, &Pubkey::new_unique(), 0, now + 1),
+            Ok(false)
+        );
+
+        // a new window starts once the previous one is over
+        let later = now + SpendingLimit::WINDOW_SECS;
+        assert_eq!(
+            wallet.record_limited_spend(&account_guid_hash, &sol, 100, later),
+            Ok(true)
+        );
+        let spending_limit = wallet
+            .get_balance_account(&account_guid_hash)
+            .unwrap()
+            .spending_limit;
+        assert_eq!(spending_limit.window_start, later);
+        assert_eq!(spending_limit.remaining(&sol, later), 0);
+
+        // a zero amount removes the limit
+        wallet
+            .update_spending_limit(&account_guid_hash, &sol, 0)
+            .unwrap();
+        assert_eq!(
+            wallet.record_limited_spend(&account_guid_hash, &sol, 0, later),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn test_approval_tiers() {
+        let signers = signers(3);
+        let mut wallet = initialized_wallet(&signers);
+        let mut balance_account =
+            BalanceAccount::unpack_unchecked(&vec![0; BalanceAccount::LEN]).unwrap();
+        balance_account.guid_hash = balance_account_guid_hash(0);
+        wallet
+            .balance_accounts
+            .insert(SlotId::new(0), balance_account);
+        let transfer_approvers = vec![SlotId::new(0), SlotId::new(1), SlotId::new(2)];
+        let tier = |min_amount: u64, approvals_required: u8, slots: Vec<usize>| ApprovalTier {
+            min_amount,
+            approvals_required,
+            approvers: Approvers::from_enabled_vec(slots.into_iter().map(SlotId::new).collect()),
+        };
+        let mut update = BalanceAccountPolicyUpdate {
+            approvals_required_for_transfer: 1,
+            approval_timeout_for_transfer: Duration::from_secs(3600),
+            signers_hash: hash(
+                &transfer_approvers
+                    .iter()
+                    .flat_map(|id| signers[id.value].1.key.to_bytes())
+                    .collect::<Vec<_>>(),
+            ),
+            transfer_approvers,
+            finalize_delay_policy: FinalizeDelayPolicy::default(),
+            mint_cap: 0,
+            approval_tiers: vec![tier(1000, 2, vec![0, 1]), tier(10_000, 3, vec![0, 1, 2])],
+        };
+        let account_guid_hash = balance_account_guid_hash(0);
+        wallet
+            .update_balance_account_policy(&account_guid_hash, &update)
+            .unwrap();
+        let approval_tiers = wallet
+            .get_balance_account(&account_guid_hash)
+            .unwrap()
+            .approval_tiers;
+        assert_eq!(approval_tiers.tier_for(999), None);
+        assert_eq!(
+            approval_tiers.tier_for(1000),
+            Some(update.approval_tiers[0])
+        );
+        assert_eq!(
+            approval_tiers.tier_for(9999),
+            Some(update.approval_tiers[0])
+        );
+        assert_eq!(
+            approval_tiers.tier_for(u64::MAX),
+            Some(update.approval_tiers[1])
+        );
+
+        let invalid_tiers = vec![
+            // out of order
+            vec![tier(10_000, 2, vec![0, 1]), tier(1000, 1, vec![0])],
+            // more approvals than approvers
+            vec![tier(1000, 3, vec![0, 1])],
+            // no approvals
+            vec![tier(1000, 0, vec![0, 1])],
+            // too many
+            vec![
+                tier(1, 1, vec![0]),
+                tier(2, 1, vec![0]),
+                tier(3, 1, vec![0]),
+                tier(4, 1, vec![0]),
+            ],
+        ];
+        for approval_tiers in invalid_tiers {
+            update.approval_tiers = approval_tiers;
+            assert_eq!(
+                wallet.update_balance_account_policy(&account_guid_hash, &update),
+                Err(WalletError::InvalidApprovalTiers.into())
+            );
+        }
+
+        // tier approvers have to be transfer approvers of the account
+        update.transfer_approvers = vec![SlotId::new(0), SlotId::new(1)];
+        update.signers_hash = hash(
+            &update
+                .transfer_approvers
+                .iter()
+                .flat_map(|id| signers[id.value].1.key.to_bytes())
+                .collect::<Vec<_>>(),
+        );
+        update.approval_tiers = vec![tier(1000, 1, vec![2])];
+        assert_eq!(
+            wallet.update_balance_account_policy(&account_guid_hash, &update),
+            Err(WalletError::InvalidApprovalTiers.into())
+        );
+
+        // and an update without tiers clears them
+        update.approval_tiers = vec![];
+        wallet
+            .update_balance_account_policy(&account_guid_hash, &update)
+            .unwrap();
+        assert_eq!(
+            wallet
+                .get_balance_account(&account_guid_hash)
+                .unwrap()
+                .approval_tiers,
+            ApprovalTiers::none()
+        );
+    }
+
+    #[test]
+    fn test_split_reward_amount() {
+        let splits = vec![split(0, 50), split(1, 30), split(2, 20)];
+        for amount in [0, 1, 7, 100, 999, 1_000_000_007, u64::MAX] {
+            let amounts = RewardSplit::split_amount(amount, &splits);
+            assert_eq!(
+                amounts.iter().map(|it| u128::from(*it)).sum::<u128>(),
+                u128::from(amount)
+            );
+            for (split, share) in splits.iter().zip(amounts.iter()).skip(1) {
+                assert_eq!(
+                    u128::from(*share),
+                    u128::from(amount) * u128::from(split.percentage) / 100
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_finalize_submitter_policy() {
+        let mut wallet = initialized_wallet(&signers(2));
+        let mut buffer = vec![0; Wallet::LEN];
+        Wallet::pack(wallet.clone(), &mut buffer).unwrap();
+        assert_eq!(
+            Wallet::finalize_submitter_policy_from_slice(&buffer),
+            Ok(FinalizeSubmitterPolicy::default())
+        );
+
+        // named submitters are required if any category is limited to them, up to the maximum
+        for named_submitters in [
+            vec![],
+            (0..=FinalizeSubmitterPolicy::MAX_NAMED_SUBMITTERS)
+                .map(|_| Pubkey::new_unique())
+                .collect(),
+        ] {
+            assert_eq!(
+                wallet.update_finalize_submitter_policy(&FinalizeSubmitterPolicy {
+                    config: FinalizeSubmitters::Anyone,
+                    transfer: FinalizeSubmitters::Named,
+                    dapp: FinalizeSubmitters::Anyone,
+                    named_submitters,
+                }),
+                Err(WalletError::InvalidFinalizeSubmitterPolicy.into())
+            );
+        }
+
+        let policy = FinalizeSubmitterPolicy {
+            config: FinalizeSubmitters::Approvers,
+            transfer: FinalizeSubmitters::Named,
+            dapp: FinalizeSubmitters::Assistant,
+            named_submitters: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+        };
+        wallet.update_finalize_submitter_policy(&policy).unwrap();
+        Wallet::pack(wallet.clone(), &mut buffer).unwrap();
+        assert_eq!(
+            Wallet::finalize_submitter_policy_from_slice(&buffer),
+            Ok(policy.clone())
+        );
+        assert_eq!(Wallet::unpack(&buffer).unwrap(), wallet);
+        assert_eq!(
+            Wallet::finalize_submitter_policy_from_slice(&buffer[..Wallet::LEN - 1]),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn test_dapp_book_expirations() {
+        let mut wallet = initialized_wallet(&signers(2));
+        let dapp = address_book_entry(0);
+        let other_dapp = address_book_entry(1);
+        wallet
+            .update_dapp_book(
+                &DAppBookUpdate {
+                    add_dapps: vec![(SlotId::new(0), dapp), (SlotId::new(1), other_dapp)],
+                    remove_dapps: vec![],
+                    dapp_expirations: vec![(SlotId::new(0), Some(200))],
+                    dapp_categories: vec![],
+                    disallowed_dapp_categories: vec![],
+                    dapp_instruction_limits: vec![],
+                },
+                100,
+            )
+            .unwrap();
+
+        assert_eq!(wallet.validate_dapp_allowed(dapp, 199), Ok(()));
+        assert_eq!(
+            wallet.validate_dapp_allowed(dapp, 200),
+            Err(WalletError::DAppBookEntryExpired.into())
+        );
+        assert_eq!(wallet.validate_dapp_allowed(other_dapp, i64::MAX), Ok(()));
+        assert_eq!(
+            wallet.validate_dapp_allowed(address_book_entry(2), 100),
+            Err(WalletError::DAppNotAllowed.into())
+        );
+
+        // an expiry must be in the future, and for an entry that is present
+        for expirations in [
+            vec![(SlotId::new(0), Some(300))],
+            vec![(SlotId::new(2), Some(400))],
+        ] {
+            assert_eq!(
+                wallet.validate_dapp_book_update(
+                    &DAppBookUpdate {
+                        add_dapps: vec![],
+                        remove_dapps: vec![],
+                        dapp_expirations: expirations,
+                        dapp_categories: vec![],
+                        disallowed_dapp_categories: vec![],
+                        dapp_instruction_limits: vec![],
+                    },
+                    300,
+                ),
+                Err(WalletError::InvalidDAppBookExpiry.into())
+            );
+        }
+
+        // renewing extends the expiry
+        wallet
+            .update_dapp_book(
+                &DAppBookUpdate {
+                    add_dapps: vec![],
+                    remove_dapps: vec![],
+                    dapp_expirations: vec![(SlotId::new(0), Some(400))],
+                    dapp_categories: vec![],
+                    disallowed_dapp_categories: vec![],
+                    dapp_instruction_limits: vec![],
+                },
+                300,
+            )
+            .unwrap();
+        assert_eq!(wallet.validate_dapp_allowed(dapp, 300), Ok(()));
+
+        // an entry added to a slot does not inherit the expiry of the one removed from it
+        let new_dapp = address_book_entry(3);
+        wallet
+            .update_dapp_book(
+                &DAppBookUpdate {
+                    add_dapps: vec![],
+                    remove_dapps: vec![(SlotId::new(0), dapp)],
+                    dapp_expirations: vec![],
+                    dapp_categories: vec![],
+                    disallowed_dapp_categories: vec![],
+                    dapp_instruction_limits: vec![],
+                },
+                300,
+            )
+            .unwrap();
+        wallet
+            .update_dapp_book(
+                &DAppBookUpdate {
+                    add_dapps: vec![(SlotId::new(0), new_dapp)],
+                    remove_dapps: vec![],
+                    dapp_expirations: vec![],
+                    dapp_categories: vec![],
+                    disallowed_dapp_categories: vec![],
+                    dapp_instruction_limits: vec![],
+                },
+                300,
+            )
+            .unwrap();
+        assert_eq!(wallet.validate_dapp_allowed(new_dapp, 500), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_dapp_program_allowed() {
+        let mut wallet = initialized_wallet(&signers(2));
+        let dapp = address_book_entry(0);
+        let renamed_dapp = AddressBookEntry {
+            address: dapp.address,
+            name_hash: address_book_entry(1).name_hash,
+        };
+        wallet
+            .update_dapp_book(
+                &DAppBookUpdate {
+                    add_dapps: vec![(SlotId::new(0), dapp), (SlotId::new(1), renamed_dapp)],
+                    remove_dapps: vec![],
+                    dapp_expirations: vec![(SlotId::new(0), Some(200))],
+                    dapp_categories: vec![],
+                    disallowed_dapp_categories: vec![],
+                    dapp_instruction_limits: vec![],
+                },
+                100,
+            )
+            .unwrap();
+
+        // a program is allowed as long as any of its entries is unexpired
+        assert_eq!(
+            wallet.validate_dapp_program_allowed(&dapp.address, 200),
+            Ok(())
+        );
+        wallet.suspend_dapp(&renamed_dapp, 150).unwrap();
+        assert_eq!(
+            wallet.validate_dapp_program_allowed(&dapp.address, 199),
+            Ok(())
+        );
+        assert_eq!(
+            wallet.validate_dapp_program_allowed(&dapp.address, 200),
+            Err(WalletError::DAppProgramNotAllowed.into())
+        );
+        assert_eq!(
+            wallet.validate_dapp_program_allowed(&Pubkey::new_unique(), 100),
+            Err(WalletError::DAppProgramNotAllowed.into())
+        );
+    }
+
+    #[test]
+    fn test_dapp_book_categories() {
+        let mut wallet = initialized_wallet(&signers(2));
+        let mut balance_account =
+            BalanceAccount::unpack_unchecked(&vec![0; BalanceAccount::LEN]).unwrap();
+        balance_account.guid_hash = balance_account_guid_hash(0);
+        wallet
+            .balance_accounts
+            .insert(SlotId::new(0), balance_account);
+        let dex = address_book_entry(0);
+        let bridge = address_book_entry(1);
+        let untagged = address_book_entry(2);
+        let update = |dapp_categories, disallowed_dapp_categories| DAppBookUpdate {
+            add_dapps: vec![],
+            remove_dapps: vec![],
+            dapp_expirations: vec![],
+            dapp_categories,
+            disallowed_dapp_categories,
+            dapp_instruction_limits: vec![],
+        };
+        wallet
+            .update_dapp_book(
+                &DAppBookUpdate {
+                    add_dapps: vec![
+                        (SlotId::new(0), dex),
+                        (SlotId::new(1), bridge),
+                        (SlotId::new(2), untagged),
+                    ],
+                    ..update(
+                        vec![
+                            (SlotId::new(0), DAppCategories::DEX),
+                            (
+                                SlotId::new(1),
+                                DAppCategories::BRIDGE.union(DAppCategories::STAKING),
+                            ),
+                        ],
+                        vec![(balance_account_guid_hash(0), DAppCategories::BRIDGE)],
+                    )
+                },
+                100,
+            )
+            .unwrap();
+
+        let balance_account = wallet
+            .get_balance_account(&balance_account_guid_hash(0))
+            .unwrap();
+        assert_eq!(
+            balance_account.disallowed_dapp_categories,
+            DAppCategories::BRIDGE
+        );
+        for dapp in [dex, untagged] {
+            assert_eq!(
+                wallet.validate_dapp_category_allowed(&balance_account, &dapp.address),
+                Ok(())
+            );
+        }
+        assert_eq!(
+            wallet.validate_dapp_category_allowed(&balance_account, &bridge.address),
+            Err(WalletError::DAppCategoryNotAllowed.into())
+        );
+
+        // a program is in the categories of each of its entries, whatever its name
+        let renamed_bridge = AddressBookEntry {
+            address: bridge.address,
+            name_hash: address_book_entry(3).name_hash,
+        };
+        wallet
+            .update_dapp_book(
+                &DAppBookUpdate {
+                    add_dapps: vec![(SlotId::new(3), renamed_bridge)],
+                    ..update(vec![], vec![])
+                },
+                100,
+            )
+            .unwrap();
+        assert_eq!(
+            wallet.validate_dapp_category_allowed(&balance_account, &renamed_bridge.address),
+            Err(WalletError::DAppCategoryNotAllowed.into())
+        );
+
+        // removing an entry clears its categories
+        wallet
+            .update_dapp_book(
+                &DAppBookUpdate {
+                    remove_dapps: vec![(SlotId::new(1), bridge)],
+                    ..update(vec![], vec![])
+                },
+                100,
+            )
+            .unwrap();
+        assert_eq!(
+            wallet.dapp_book_categories.get(SlotId::new(1)),
+            DAppCategories::NONE
+        );
+        assert_eq!(
+            wallet.validate_dapp_category_allowed(&balance_account, &bridge.address),
+            Ok(())
+        );
+
+        // only entries that are present can be tagged, and only known accounts given a policy
+        assert_eq!(
+            wallet.validate_dapp_book_update(
+                &update(vec![(SlotId::new(1), DAppCategories::DEX)], vec![]),
+                100
+            ),
+            err(WalletError::InvalidDAppCategories)
+        );
+        assert_eq!(
+            wallet.validate_dapp_book_update(
+                &update(
+                    vec![],
+                    vec![(balance_account_guid_hash(1), DAppCategories::ALL)]
+                ),
+                100
+            ),
+            err(WalletError::BalanceAccountNotFound)
+        );
+
+        let mut buffer = vec![0; Wallet::LEN];
+        wallet.pack_into_slice(&mut buffer);
+        assert_eq!(Wallet::unpack_from_slice(&buffer).unwrap(), wallet);
+    }
+
+    #[test]
+    fn test_dapp_book_instruction_limits() {
+        let mut wallet = initialized_wallet(&signers(2));
+        let limited = address_book_entry(0);
+        let unlimited = address_book_entry(1);
+        let update = |dapp_instruction_limits| DAppBookUpdate {
+            add_dapps: vec![],
+            remove_dapps: vec![],
+            dapp_expirations: vec![],
+            dapp_categories: vec![],
+            disallowed_dapp_categories: vec![],
+            dapp_instruction_limits,
+        };
+        wallet
+            .update_dapp_book(
+                &DAppBookUpdate {
+                    add_dapps: vec![(SlotId::new(0), limited), (SlotId::new(1), unlimited)],
+                    ..update(vec![(SlotId::new(0), Some(4))])
+                },
+                100,
+            )
+            .unwrap();
+        assert_eq!(wallet.dapp_instruction_limit(&limited), Some(4));
+        assert_eq!(wallet.dapp_instruction_limit(&unlimited), None);
+
+        let mut buffer = vec![0; Wallet::LEN];
+        wallet.pack_into_slice(&mut buffer);
+        assert_eq!(Wallet::unpack_from_slice(&buffer).unwrap(), wallet);
+
+        // removing an entry clears its limit
+        wallet
+            .update_dapp_book(
+                &DAppBookUpdate {
+                    remove_dapps: vec![(SlotId::new(0), limited)],
+                    ..update(vec![])
+                },
+                100,
+            )
+            .unwrap();
+        assert_eq!(
+            wallet.dapp_book_instruction_limits.get(SlotId::new(0)),
+            None
+        );
+
+        // only entries that are present can be limited, and no further than a dapp
+        // transaction can hold
+        assert_eq!(
+            wallet.validate_dapp_book_update(&update(vec![(SlotId::new(0), Some(4))]), 100),
+            err(WalletError::InvalidDAppInstructionLimit)
+        );
+        assert_eq!(
+            wallet.validate_dapp_book_update(
+                &update(vec![(
+                    SlotId::new(1),
+                    Some(MAX_INSTRUCTION_COUNT as u8 + 1)
+                )]),
+                100
+            ),
+            err(WalletError::InvalidDAppInstructionLimit)
+        );
+    }
+
+    #[test]
+    fn test_suspend_dapp() {
+        let mut wallet = initialized_wallet(&signers(2));
+        let dapp = address_book_entry(0);
+        wallet
+            .update_dapp_book(
+                &DAppBookUpdate {
+                    add_dapps: vec![(SlotId::new(0), dapp)],
+                    remove_dapps: vec![],
+                    dapp_expirations: vec![],
+                    dapp_categories: vec![],
+                    disallowed_dapp_categories: vec![],
+                    dapp_instruction_limits: vec![],
+                },
+                100,
+            )
+            .unwrap();
+
+        wallet.suspend_dapp(&dapp, 150).unwrap();
+        assert_eq!(
+            wallet.validate_dapp_allowed(dapp, 150),
+            Err(WalletError::DAppBookEntryExpired.into())
+        );
+        // suspending again doesn't push the expiry forward
+        wallet.suspend_dapp(&dapp, 160).unwrap();
+        assert_eq!(wallet.dapp_book_expirations.get(SlotId::new(0)), Some(150));
+        assert_eq!(
+            wallet.suspend_dapp(&address_book_entry(1), 160),
+            Err(WalletError::DAppNotAllowed.into())
+        );
+
+        // until a dapp book update lifts it
+        wallet
+            .update_dapp_book(
+                &DAppBookUpdate {
+                    add_dapps: vec![],
+                    remove_dapps: vec![],
+                    dapp_expirations: vec![(SlotId::new(0), None)],
+                    dapp_categories: vec![],
+                    disallowed_dapp_categories: vec![],
+                    dapp_instruction_limits: vec![],
+                },
+                170,
+            )
+            .unwrap();
+        assert_eq!(wallet.validate_dapp_allowed(dapp, 170), Ok(()));
+    }
+
+    #[test]
+    fn test_compaction() {
+        let signers = signers(4);
+        let mut wallet = new_wallet();
+        wallet
+            .init(
+                &WalletGuidHash::new(&[1; HASH_LEN]),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                &InitialWalletConfig {
+                    config_approvers: vec![SlotId::new(0), SlotId::new(3)],
+                    ..initial_config(&signers, 0, 1)
+                },
+            )
+            .unwrap();
+        wallet.remove_signer(signers[1]).unwrap();
+        wallet.remove_signer(signers[2]).unwrap();
+
+        let (entry, other_entry) = (address_book_entry(0), address_book_entry(2));
+        wallet
+            .update_address_book(
+                &AddressBookUpdate {
+                    add_address_book_entries: vec![
+                        (SlotId::new(0), entry),
+                        (SlotId::new(2), other_entry),
+                    ],
+                    remove_address_book_entries: vec![],
+                    deprecate_address_book_entries: vec![],
+                    balance_account_whitelist_updates: vec![],
+                    address_book_risks: vec![],
+                    address_book_expirations: vec![(SlotId::new(2), Some(200))],
+                    address_book_entry_kinds: vec![],
+                },
+                100,
+            )
+            .unwrap();
+        let dapp = address_book_entry(5);
+        wallet
+            .update_dapp_book(
+                &DAppBookUpdate {
+                    add_dapps: vec![(SlotId::new(4), dapp)],
+                    remove_dapps: vec![],
+                    dapp_expirations: vec![],
+                    dapp_categories: vec![],
+                    disallowed_dapp_categories: vec![],
+                    dapp_instruction_limits: vec![(SlotId::new(4), Some(3))],
+                },
+                100,
+            )
+            .unwrap();
+        let mut balance_account =
+            BalanceAccount::unpack_unchecked(&vec![0; BalanceAccount::LEN]).unwrap();
+        balance_account.transfer_approvers.enable(&SlotId::new(3));
+        balance_account.allowed_destinations.enable(&SlotId::new(2));
+        wallet
+            .balance_accounts
+            .insert(SlotId::new(0), balance_account);
+
+        let compaction = wallet.compaction();
+        assert_eq!(
+            compaction,
+            WalletCompaction {
+                signers: vec![(SlotId::new(3), SlotId::new(1))],
+                address_book: vec![(SlotId::new(2), SlotId::new(1))],
+                dapp_book: vec![(SlotId::new(4), SlotId::new(0))],
+            }
+        );
+        wallet.compact(&compaction).unwrap();
+        assert!(wallet.compaction().is_empty());
+
+        // everything kept by a slot moves along with its entry
+        assert_eq!(
+            wallet.signers.filled_slots(),
+            vec![
+                (SlotId::new(0), signers[0].1),
+                (SlotId::new(1), signers[3].1)
+            ]
+        );
+        assert!(wallet.config_approvers.is_enabled(&SlotId::new(1)));
+        assert!(!wallet.config_approvers.is_enabled(&SlotId::new(3)));
+        assert_eq!(
+            wallet.address_book.filled_slots(),
+            vec![(SlotId::new(0), entry), (SlotId::new(1), other_entry)]
+        );
+        assert_eq!(
+            wallet.address_book_expirations.get(SlotId::new(1)),
+            Some(200)
+        );
+        assert_eq!(wallet.address_book_expirations.get(SlotId::new(2)), None);
+        assert_eq!(
+            wallet.dapp_book.filled_slots(),
+            vec![(SlotId::new(0), dapp)]
+        );
+        assert_eq!(wallet.dapp_instruction_limit(&dapp), Some(3));
+        let balance_account = wallet.balance_accounts[SlotId::new(0)].unwrap();
+        assert!(balance_account
+            .transfer_approvers
+            .is_enabled(&SlotId::new(1)));
+        assert!(balance_account
+            .allowed_destinations
+            .is_enabled(&SlotId::new(1)));
+
+        let mut buffer = vec![0; Wallet::LEN];
+        wallet.pack_into_slice(&mut buffer);
+        assert_eq!(Wallet::unpack(&buffer).unwrap(), wallet);
+
+        // a replicated wallet's slots have to stay in line with its counterpart's
+        wallet.update_replication(&Some(WalletReplication::Standby {
+            primary: Pubkey::new_unique(),
+        }));
+        assert_eq!(
+            wallet.validate_compaction(),
+            err(WalletError::WalletIsReplicated)
+        );
+    }
+
+    #[test]
+    fn test_pack_unpack_round_trip() {
+        let mut wallet = initialized_wallet(&signers(5));
+        wallet.is_initialized = true;
+        wallet
+            .add_pending_address_book_entries(&vec![(SlotId::new(3), address_book_entry(3))], 1)
+            .unwrap();
+        wallet
+            .update_dapp_book(
+                &DAppBookUpdate {
+                    add_dapps: vec![(SlotId::new(2), address_book_entry(2))],
+                    remove_dapps: vec![],
+                    dapp_expirations: vec![(SlotId::new(2), Some(1000))],
+                    dapp_categories: vec![],
+                    disallowed_dapp_categories: vec![],
+                    dapp_instruction_limits: vec![],
+                },
+                1,
+            )
+            .unwrap();
+        wallet.update_fee_policy(&FeePolicy {
+            max_fee_amount: Some(5_000),
+        });
+        wallet.update_stake_weight_governance(&Some(StakeWeightGovernance {
+            mint: Pubkey::new_unique(),
+            approval_threshold_bps: 6_000,
+        }));
+        wallet.address_book_risks.set(
+            SlotId::new(3),
+            AddressBookEntryRisk::HighRisk {
+                jurisdiction: JurisdictionCode::new(*b"IR").unwrap(),
+            },
+        );
+        wallet.provisioning = Some(WalletProvisioning {
+            creator: Pubkey::new_unique(),
+        });
+        wallet.wrapper_book = WrapperBook {
+            entries: vec![WrapperEntry {
+                mint: Pubkey::new_unique(),
+                adapter_program: Pubkey::new_unique(),
+                adapter_accounts: [Pubkey::new_unique(); WrapperEntry::MAX_ADAPTER_ACCOUNTS],
+            }],
+        };
+
+        let mut buffer = vec![0; Wallet::LEN];
+        wallet.pack_into_slice(&mut buffer);
+        assert_eq!(Wallet::unpack(&buffer).unwrap(), wallet);
+    }
+
+    #[test]
+    fn test_fixed_offset_readers_match_layout() {
+        let mut wallet = initialized_wallet(&signers(2));
+        wallet.is_initialized = true;
+        // fields after the op nonce are populated, so that a bump landing on them is caught
+        wallet.wrapper_book = WrapperBook {
+            entries: vec![WrapperEntry {
+                mint: Pubkey::new_unique(),
+                adapter_program: Pubkey::new_unique(),
+                adapter_accounts: [Pubkey::new_unique(); WrapperEntry::MAX_ADAPTER_ACCOUNTS],
+            }],
+        };
+        wallet
+            .retired_signers
+            .signer_replaced(SlotId::new(0), Pubkey::new_unique());
+        let mut buffer = vec![0; Wallet::LEN];
+        wallet.pack_into_slice(&mut buffer);
+
+        assert_eq!(Wallet::version_from_slice(&buffer), Ok(VERSION));
+        assert_eq!(
+            Wallet::rent_return_from_slice(&buffer),
+            Ok(wallet.rent_return)
+        );
+        assert_eq!(
+            Wallet::wallet_guid_hash_from_slice(&buffer),
+            Ok(wallet.wallet_guid_hash)
+        );
+        assert_eq!(Wallet::op_nonce_from_slice(&buffer), Ok(0));
+
+        Wallet::bump_op_nonce_in_slice(&mut buffer).unwrap();
+        wallet.op_nonce = 1;
+        assert_eq!(Wallet::op_nonce_from_slice(&buffer), Ok(1));
+        assert_eq!(Wallet::unpack_from_slice(&buffer).unwrap(), wallet);
+
+        buffer[0] = 0;
+        assert_eq!(
+            Wallet::version_from_slice(&buffer),
+            Err(ProgramError::UninitializedAccount)
+        );
+        assert_eq!(
+            Wallet::wallet_guid_hash_from_slice(&buffer[..Wallet::WALLET_GUID_HASH_OFFSET]),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn test_state_summary() {
+        let mut wallet = initialized_wallet(&signers(3));
+        wallet.is_initialized = true;
+        for i in 0..2 {
+            let mut balance_account =
+                BalanceAccount::unpack_unchecked(&vec![0; BalanceAccount::LEN]).unwrap();
+            balance_account.guid_hash = balance_account_guid_hash(i);
+            wallet
+                .balance_accounts
+                .insert(SlotId::new(usize::from(i)), balance_account);
+        }
+        let mut buffer = vec![0; Wallet::LEN];
+        wallet.pack_into_slice(&mut buffer);
+        let summary = Wallet::state_summary_from_slice(&buffer).unwrap();
+        assert_eq!(summary.signer_count, 3);
+        assert_eq!(summary.pending_config_change_count, 0);
+        assert_ne!(
+            summary.balance_account_digests[0],
+            [0; StateSummary::DIGEST_LEN]
+        );
+        assert_ne!(
+            summary.balance_account_digests[0],
+            summary.balance_account_digests[1]
+        );
+        assert_eq!(
+            summary.balance_account_digests[2],
+            [0; StateSummary::DIGEST_LEN]
+        );
+
+        // a policy change shows in the digest of its balance account only
+        wallet
+            .update_spending_limit(&balance_account_guid_hash(1), &Pubkey::default(), 100)
+            .unwrap();
+        wallet
+            .add_pending_address_book_entries(&vec![(SlotId::new(3), address_book_entry(3))], 1)
+            .unwrap();
+        wallet.pack_into_slice(&mut buffer);
+        let updated = Wallet::state_summary_from_slice(&buffer).unwrap();
+        assert_eq!(updated.pending_config_change_count, 1);
+        assert_eq!(
+            updated.balance_account_digests[0],
+            summary.balance_account_digests[0]
+        );
+        assert_ne!(
+            updated.balance_account_digests[1],
+            summary.balance_account_digests[1]
+        );
+        assert_eq!(updated, StateSummary::of(&wallet));
+
+        buffer[Wallet::STATE_SUMMARY_OFFSET] = StateSummary::VERSION + 1;
+        assert_eq!(
+            Wallet::state_summary_from_slice(&buffer),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+}
@@ -0,0 +1,298 @@
+use crate::constants::PUBKEY_BYTES;
+use crate::pda::{PdaSeeds, WalletEventLogSeeds};
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{IsInitialized, Pack, Sealed};
+use solana_program::pubkey::Pubkey;
+
+/// What happened to an op of the wallet.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum WalletEventKind {
+    OpInitiated,
+    OpApproved,
+    OpDenied,
+    /// The approved op was finalized.
+    OpExecuted,
+    /// The approved op was a config op, and finalizing it changed the wallet's config.
+    ConfigChanged,
+    /// The wallet's insurance attestation lapsed without being renewed.
+    InsuranceAttestationLapsed,
+    /// Compacting the wallet moved entries to other slots. The remap table, as packed by
+    /// `WalletCompaction::pack`, is carried in the `op` field of as many consecutive events of
+    /// this kind as it takes, 32 bytes each, the last padded with zeros.
+    SlotsRemapped,
+    /// The op was finalized or cleaned up after it expired.
+    OpExpired,
+}
+
+impl WalletEventKind {
+    pub fn to_u8(&self) -> u8 {
+        match self {
+            WalletEventKind::OpInitiated => 0,
+            WalletEventKind::OpApproved => 1,
+            WalletEventKind::OpDenied => 2,
+            WalletEventKind::OpExecuted => 3,
+            WalletEventKind::ConfigChanged => 4,
+            WalletEventKind::InsuranceAttestationLapsed => 5,
+            WalletEventKind::SlotsRemapped => 6,
+            WalletEventKind::OpExpired => 7,
+        }
+    }
+
+    pub fn from_u8(value: u8) -> Result<WalletEventKind, ProgramError> {
+        Ok(match value {
+            0 => WalletEventKind::OpInitiated,
+            1 => WalletEventKind::OpApproved,
+            2 => WalletEventKind::OpDenied,
+            3 => WalletEventKind::OpExecuted,
+            4 => WalletEventKind::ConfigChanged,
+            5 => WalletEventKind::InsuranceAttestationLapsed,
+            6 => WalletEventKind::SlotsRemapped,
+            7 => WalletEventKind::OpExpired,
+            _ => return Err(ProgramError::InvalidAccountData),
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct WalletEvent {
+    /// The event's position among all the events ever appended to the log, starting at 0, so
+    /// that indexers can tell how many events were overwritten since they last read it.
+    pub sequence: u64,
+    pub kind: WalletEventKind,
+    /// The op account the event is about, the attestation account for an attestation lapse,
+    /// or a chunk of the remap table for a slot remap.
+    pub op: Pubkey,
+    pub slot: u64,
+    pub unix_timestamp: i64,
+}
+
+impl WalletEvent {
+    pub const LEN: usize = 8 + // sequence
+        1 + // kind
+        PUBKEY_BYTES + // op
+        8 + // slot
+        8; // unix timestamp
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, WalletEvent::LEN];
+        let (sequence_dst, kind_dst, op_dst, slot_dst, unix_timestamp_dst) =
+            mut_array_refs![dst, 8, 1, PUBKEY_BYTES, 8, 8];
+        *sequence_dst = self.sequence.to_le_bytes();
+        kind_dst[0] = self.kind.to_u8();
+        op_dst.copy_from_slice(self.op.as_ref());
+        *slot_dst = self.slot.to_le_bytes();
+        *unix_timestamp_dst = self.unix_timestamp.to_le_bytes();
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, WalletEvent::LEN];
+        let (sequence, kind, op, slot, unix_timestamp) = array_refs![src, 8, 1, PUBKEY_BYTES, 8, 8];
+        Ok(WalletEvent {
+            sequence: u64::from_le_bytes(*sequence),
+            kind: WalletEventKind::from_u8(kind[0])?,
+            op: Pubkey::new_from_array(*op),
+            slot: u64::from_le_bytes(*slot),
+            unix_timestamp: i64::from_le_bytes(*unix_timestamp),
+        })
+    }
+}
+
+/// A log of a wallet's op events, kept in a PDA derived from the wallet so indexers don't have
+/// to rely on transaction logs alone, which can be truncated. It is created by `InitEventLog`,
+/// and events are appended to it by any instruction it is supplied to as a trailing account.
+/// Events are kept in a ring buffer of `WalletEventLog::CAPACITY` slots, the event with
+/// sequence `n` in slot `n % CAPACITY`, so once the log is full each event overwrites the
+/// oldest one.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct WalletEventLog {
+    pub is_initialized: bool,
+    pub wallet: Pubkey,
+    /// The sequence of the next event to be appended, which is also the number of events ever
+    /// appended.
+    pub next_sequence: u64,
+    /// The retained events, oldest first.
+    pub events: Vec<WalletEvent>,
+}
+
+impl WalletEventLog {
+    pub const CAPACITY: usize = 128;
+
+    const HEADER_LEN: usize = 1 + // is_initialized
+        PUBKEY_BYTES + // wallet
+        8; // next sequence
+
+    pub fn new(wallet: Pubkey) -> Self {
+        WalletEventLog {
+            is_initialized: true,
+            wallet,
+            next_sequence: 0,
+            events: Vec::new(),
+        }
+    }
+
+    /// Derive the PDA and "bump seed" of the event log of the given wallet.
+    pub fn find_address(wallet: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        WalletEventLogSeeds { wallet }.find_address(program_id)
+    }
+
+    pub fn wallet_from_slice(src: &[u8]) -> Result<Pubkey, ProgramError> {
+        if src.len() != WalletEventLog::LEN || src[0] != 1 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(Pubkey::new_from_array(*array_ref![src, 1, PUBKEY_BYTES]))
+    }
+
+    /// Appends an event to a packed log in place, writing only its slot and the header rather
+    /// than repacking every event, and returns the event's sequence.
+    pub fn append_to_slice(
+        dst: &mut [u8],
+        kind: WalletEventKind,
+        op: Pubkey,
+        slot: u64,
+        unix_timestamp: i64,
+    ) -> Result<u64, ProgramError> {
+        WalletEventLog::wallet_from_slice(dst)?;
+        let next_sequence_dst = array_mut_ref![dst, 1 + PUBKEY_BYTES, 8];
+        let sequence = u64::from_le_bytes(*next_sequence_dst);
+        *next_sequence_dst = sequence
+            .checked_add(1)
+            .ok_or(ProgramError::InvalidAccountData)?
+            .to_le_bytes();
+        let offset = WalletEventLog::HEADER_LEN
+            + WalletEvent::LEN * (sequence % WalletEventLog::CAPACITY as u64) as usize;
+        WalletEvent {
+            sequence,
+            kind,
+            op,
+            slot,
+            unix_timestamp,
+        }
+        .pack_into_slice(&mut dst[offset..offset + WalletEvent::LEN]);
+        Ok(sequence)
+    }
+}
+
+impl Sealed for WalletEventLog {}
+
+impl IsInitialized for WalletEventLog {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for WalletEventLog {
+    const LEN: usize = WalletEventLog::HEADER_LEN + WalletEvent::LEN * WalletEventLog::CAPACITY;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, WalletEventLog::LEN];
+        let (is_initialized_dst, wallet_dst, next_sequence_dst, events_dst) = mut_array_refs![
+            dst,
+            1,
+            PUBKEY_BYTES,
+            8,
+            WalletEvent::LEN * WalletEventLog::CAPACITY
+        ];
+        is_initialized_dst[0] = self.is_initialized as u8;
+        wallet_dst.copy_from_slice(self.wallet.as_ref());
+        *next_sequence_dst = self.next_sequence.to_le_bytes();
+        events_dst.fill(0);
+        for event in &self.events {
+            let offset =
+                WalletEvent::LEN * (event.sequence % WalletEventLog::CAPACITY as u64) as usize;
+            event.pack_into_slice(&mut events_dst[offset..offset + WalletEvent::LEN]);
+        }
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, WalletEventLog::LEN];
+        let (is_initialized, wallet, next_sequence, events_src) = array_refs![
+            src,
+            1,
+            PUBKEY_BYTES,
+            8,
+            WalletEvent::LEN * WalletEventLog::CAPACITY
+        ];
+        let is_initialized = match is_initialized {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        let next_sequence = u64::from_le_bytes(*next_sequence);
+        let oldest = next_sequence.saturating_sub(WalletEventLog::CAPACITY as u64);
+        let events = (oldest..next_sequence)
+            .map(|sequence| {
+                let offset =
+                    WalletEvent::LEN * (sequence % WalletEventLog::CAPACITY as u64) as usize;
+                WalletEvent::unpack_from_slice(&events_src[offset..offset + WalletEvent::LEN])
+            })
+            .collect::<Result<Vec<_>, ProgramError>>()?;
+
+        Ok(WalletEventLog {
+            is_initialized,
+            wallet: Pubkey::new_from_array(*wallet),
+            next_sequence,
+            events,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::model::wallet_event_log::{WalletEventKind, WalletEventLog};
+    use solana_program::program_error::ProgramError;
+    use solana_program::program_pack::Pack;
+    use solana_program::pubkey::Pubkey;
+
+    #[test]
+    fn test_append_wraps_around() {
+        let wallet = Pubkey::new_unique();
+        let mut buffer = vec![0; WalletEventLog::LEN];
+        assert_eq!(
+            WalletEventLog::append_to_slice(
+                &mut buffer,
+                WalletEventKind::OpInitiated,
+                Pubkey::new_unique(),
+                1,
+                1
+            ),
+            Err(ProgramError::InvalidAccountData)
+        );
+        WalletEventLog::new(wallet).pack_into_slice(&mut buffer);
+        assert_eq!(WalletEventLog::wallet_from_slice(&buffer), Ok(wallet));
+
+        let ops: Vec<Pubkey> = (0..WalletEventLog::CAPACITY + 3)
+            .map(|_| Pubkey::new_unique())
+            .collect();
+        for (i, op) in ops.iter().enumerate() {
+            assert_eq!(
+                WalletEventLog::append_to_slice(
+                    &mut buffer,
+                    WalletEventKind::OpApproved,
+                    *op,
+                    i as u64,
+                    i as i64 * 10,
+                ),
+                Ok(i as u64)
+            );
+            let log = WalletEventLog::unpack(&buffer).unwrap();
+            assert_eq!(log.next_sequence, i as u64 + 1);
+            assert_eq!(log.events.len(), (i + 1).min(WalletEventLog::CAPACITY));
+            assert_eq!(log.events.last().unwrap().op, *op);
+        }
+
+        // the oldest events were overwritten, and the rest are still in order
+        let log = WalletEventLog::unpack(&buffer).unwrap();
+        assert_eq!(log.events[0].sequence, 3);
+        assert_eq!(log.events[0].op, ops[3]);
+        assert_eq!(log.events[0].unix_timestamp, 30);
+        assert!(log
+            .events
+            .windows(2)
+            .all(|pair| pair[1].sequence == pair[0].sequence + 1));
+
+        let mut repacked = vec![0; WalletEventLog::LEN];
+        log.pack_into_slice(&mut repacked);
+        assert_eq!(repacked, buffer);
+    }
+}
@@ -0,0 +1,37 @@
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::program_error::ProgramError;
+
+/// Whether the wallet is frozen, and how many of its signers can freeze it together without
+/// going through approvals. No transfers, wraps or dApp transactions can be initiated while the
+/// wallet is frozen; it is only unfrozen through an approved freeze update.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct WalletFreeze {
+    pub frozen: bool,
+    /// How many signers have to sign an `EmergencyFreeze` together, zero disabling it.
+    pub emergency_quorum: u8,
+}
+
+impl WalletFreeze {
+    pub const LEN: usize = 1 + // frozen
+        1; // emergency quorum
+
+    pub fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, WalletFreeze::LEN];
+        let (frozen_dst, emergency_quorum_dst) = mut_array_refs![dst, 1, 1];
+        frozen_dst[0] = self.frozen as u8;
+        emergency_quorum_dst[0] = self.emergency_quorum;
+    }
+
+    pub fn unpack_from_slice(src: &[u8]) -> Result<WalletFreeze, ProgramError> {
+        let src = array_ref![src, 0, WalletFreeze::LEN];
+        let (frozen, emergency_quorum) = array_refs![src, 1, 1];
+        Ok(WalletFreeze {
+            frozen: match frozen {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            emergency_quorum: emergency_quorum[0],
+        })
+    }
+}
@@ -0,0 +1,29 @@
+use arrayref::array_ref;
+use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
+
+/// A wallet still being provisioned. Until all of its config approvers sign an
+/// `ActivateWallet`, its creator can edit its config without approvals, and no ops can be
+/// started against it.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct WalletProvisioning {
+    /// The key that created the wallet with `InitProvisioningWallet`.
+    pub creator: Pubkey,
+}
+
+impl WalletProvisioning {
+    pub const LEN: usize = PUBKEY_BYTES; // creator
+
+    /// Packs the provisioning state, if any; a default creator key stands for none.
+    pub fn pack_into_slice(provisioning: &Option<WalletProvisioning>, dst: &mut [u8]) {
+        let creator = provisioning.map_or(Pubkey::default(), |provisioning| provisioning.creator);
+        dst[..WalletProvisioning::LEN].copy_from_slice(creator.as_ref());
+    }
+
+    pub fn unpack_from_slice(src: &[u8]) -> Option<WalletProvisioning> {
+        let creator = Pubkey::new_from_array(*array_ref![src, 0, WalletProvisioning::LEN]);
+        if creator == Pubkey::default() {
+            return None;
+        }
+        Some(WalletProvisioning { creator })
+    }
+}
@@ -0,0 +1,287 @@
+use crate::constants::{HASH_LEN, PUBKEY_BYTES};
+use crate::model::multisig_op::BooleanSetting;
+use crate::model::state_summary::{PolicyDigest, StateSummary};
+use crate::model::wallet::Wallet;
+use crate::pda::{PdaSeeds, WalletReadModelSeeds};
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::hash::{hashv, Hash};
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{IsInitialized, Pack, Sealed};
+use solana_program::pubkey::Pubkey;
+
+/// An address book entry that at least one balance account may transfer to.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct EnabledDestination {
+    pub address: Pubkey,
+    /// The slots of the balance accounts whitelisting the address, one bit per slot.
+    pub balance_account_slots: u16,
+}
+
+impl EnabledDestination {
+    pub const LEN: usize = PUBKEY_BYTES + 2;
+}
+
+/// A minimal read model of a wallet, kept in a PDA derived from the wallet so integrations
+/// checking whitelists or approvers can read it instead of the whole wallet account. It is
+/// refreshed by `ExportWalletReadModel`, and by any config finalize it is supplied to as the
+/// last account. Its counts come before the entries they count, so the used part can be read
+/// on its own.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct WalletReadModel {
+    pub is_initialized: bool,
+    pub wallet: Pubkey,
+    /// A hash of the approvals required for config, the approval timeout and timelock, and
+    /// the config approvers' keys.
+    pub config_policy_hash: Hash,
+    /// The balance account digests of the wallet's state summary, by slot.
+    pub balance_account_digests: [PolicyDigest; Wallet::MAX_BALANCE_ACCOUNTS],
+    /// The slots of the balance accounts with their whitelist on, one bit per slot.
+    pub whitelist_enabled_slots: u16,
+    pub config_approvers: Vec<Pubkey>,
+    pub destinations: Vec<EnabledDestination>,
+}
+
+impl WalletReadModel {
+    /// Derive the PDA and "bump seed" of the read model of the given wallet.
+    pub fn find_address(wallet: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        WalletReadModelSeeds { wallet }.find_address(program_id)
+    }
+
+    pub fn of(wallet_address: &Pubkey, wallet: &Wallet) -> WalletReadModel {
+        let config_approvers = wallet.get_config_approvers_keys();
+        let mut config_policy_bytes =
+            Vec::with_capacity(17 + config_approvers.len() * PUBKEY_BYTES);
+        config_policy_bytes.push(wallet.approvals_required_for_config);
+        config_policy_bytes
+            .extend_from_slice(&wallet.approval_timeout_for_config.as_secs().to_le_bytes());
+        config_policy_bytes
+            .extend_from_slice(&wallet.config_policy_timelock.as_secs().to_le_bytes());
+        for approver in &config_approvers {
+            config_policy_bytes.extend_from_slice(approver.as_ref());
+        }
+
+        let balance_accounts = wallet.balance_accounts.filled_slots();
+        let whitelist_enabled_slots = balance_accounts
+            .iter()
+            .filter(|(_, balance_account)| balance_account.whitelist_enabled == BooleanSetting::On)
+            .fold(0, |slots, (slot_id, _)| slots | 1 << slot_id.value);
+        let destinations = wallet
+            .address_book
+            .filled_slots()
+            .into_iter()
+            .filter_map(|(entry_slot_id, entry)| {
+                let balance_account_slots = balance_accounts
+                    .iter()
+                    .filter(|(_, balance_account)| {
+                        balance_account
+                            .allowed_destinations
+                            .is_enabled(&entry_slot_id)
+                    })
+                    .fold(0, |slots, (slot_id, _)| slots | 1 << slot_id.value);
+                (balance_account_slots != 0).then(|| EnabledDestination {
+                    address: entry.address,
+                    balance_account_slots,
+                })
+            })
+            .collect();
+
+        WalletReadModel {
+            is_initialized: true,
+            wallet: *wallet_address,
+            config_policy_hash: hashv(&[&config_policy_bytes]),
+            balance_account_digests: StateSummary::of(wallet).balance_account_digests,
+            whitelist_enabled_slots,
+            config_approvers,
+            destinations,
+        }
+    }
+}
+
+impl Sealed for WalletReadModel {}
+
+impl IsInitialized for WalletReadModel {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for WalletReadModel {
+    const LEN: usize = 1 + // is_initialized
+        PUBKEY_BYTES + // wallet
+        HASH_LEN + // config policy hash
+        StateSummary::DIGEST_LEN * Wallet::MAX_BALANCE_ACCOUNTS + // balance account digests
+        2 + // whitelist enabled slots
+        1 + // config approver count
+        1 + // destination count
+        PUBKEY_BYTES * Wallet::MAX_SIGNERS + // config approvers
+        EnabledDestination::LEN * Wallet::MAX_ADDRESS_BOOK_ENTRIES; // destinations
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, WalletReadModel::LEN];
+        let (
+            is_initialized_dst,
+            wallet_dst,
+            config_policy_hash_dst,
+            digests_dst,
+            whitelist_enabled_slots_dst,
+            approver_count_dst,
+            destination_count_dst,
+            approvers_dst,
+            destinations_dst,
+        ) = mut_array_refs![
+            dst,
+            1,
+            PUBKEY_BYTES,
+            HASH_LEN,
+            StateSummary::DIGEST_LEN * Wallet::MAX_BALANCE_ACCOUNTS,
+            2,
+            1,
+            1,
+            PUBKEY_BYTES * Wallet::MAX_SIGNERS,
+            EnabledDestination::LEN * Wallet::MAX_ADDRESS_BOOK_ENTRIES
+        ];
+
+        is_initialized_dst[0] = self.is_initialized as u8;
+        wallet_dst.copy_from_slice(self.wallet.as_ref());
+        config_policy_hash_dst.copy_from_slice(self.config_policy_hash.as_ref());
+        for (digest, digest_dst) in self
+            .balance_account_digests
+            .iter()
+            .zip(digests_dst.chunks_exact_mut(StateSummary::DIGEST_LEN))
+        {
+            digest_dst.copy_from_slice(digest);
+        }
+        *whitelist_enabled_slots_dst = self.whitelist_enabled_slots.to_le_bytes();
+        approver_count_dst[0] = self.config_approvers.len() as u8;
+        destination_count_dst[0] = self.destinations.len() as u8;
+        approvers_dst.fill(0);
+        for (approver, approver_dst) in self
+            .config_approvers
+            .iter()
+            .zip(approvers_dst.chunks_exact_mut(PUBKEY_BYTES))
+        {
+            approver_dst.copy_from_slice(approver.as_ref());
+        }
+        destinations_dst.fill(0);
+        for (destination, destination_dst) in self
+            .destinations
+            .iter()
+            .zip(destinations_dst.chunks_exact_mut(EnabledDestination::LEN))
+        {
+            destination_dst[..PUBKEY_BYTES].copy_from_slice(destination.address.as_ref());
+            destination_dst[PUBKEY_BYTES..]
+                .copy_from_slice(&destination.balance_account_slots.to_le_bytes());
+        }
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, WalletReadModel::LEN];
+        let (
+            is_initialized_src,
+            wallet_src,
+            config_policy_hash_src,
+            digests_src,
+            whitelist_enabled_slots_src,
+            approver_count_src,
+            destination_count_src,
+            approvers_src,
+            destinations_src,
+        ) = array_refs![
+            src,
+            1,
+            PUBKEY_BYTES,
+            HASH_LEN,
+            StateSummary::DIGEST_LEN * Wallet::MAX_BALANCE_ACCOUNTS,
+            2,
+            1,
+            1,
+            PUBKEY_BYTES * Wallet::MAX_SIGNERS,
+            EnabledDestination::LEN * Wallet::MAX_ADDRESS_BOOK_ENTRIES
+        ];
+
+        let is_initialized = match is_initialized_src {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        let approver_count = usize::from(approver_count_src[0]);
+        let destination_count = usize::from(destination_count_src[0]);
+        if approver_count > Wallet::MAX_SIGNERS
+            || destination_count > Wallet::MAX_ADDRESS_BOOK_ENTRIES
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut balance_account_digests =
+            [[0; StateSummary::DIGEST_LEN]; Wallet::MAX_BALANCE_ACCOUNTS];
+        for (digest, digest_src) in balance_account_digests
+            .iter_mut()
+            .zip(digests_src.chunks_exact(StateSummary::DIGEST_LEN))
+        {
+            digest.copy_from_slice(digest_src);
+        }
+
+        Ok(WalletReadModel {
+            is_initialized,
+            wallet: Pubkey::new_from_array(*wallet_src),
+            config_policy_hash: Hash::new_from_array(*config_policy_hash_src),
+            balance_account_digests,
+            whitelist_enabled_slots: u16::from_le_bytes(*whitelist_enabled_slots_src),
+            config_approvers: approvers_src
+                .chunks_exact(PUBKEY_BYTES)
+                .take(approver_count)
+                .map(Pubkey::new)
+                .collect(),
+            destinations: destinations_src
+                .chunks_exact(EnabledDestination::LEN)
+                .take(destination_count)
+                .map(|src| EnabledDestination {
+                    address: Pubkey::new(&src[..PUBKEY_BYTES]),
+                    balance_account_slots: u16::from_le_bytes([
+                        src[PUBKEY_BYTES],
+                        src[PUBKEY_BYTES + 1],
+                    ]),
+                })
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::model::wallet::Wallet;
+    use crate::model::wallet_read_model::{EnabledDestination, WalletReadModel};
+    use solana_program::hash::Hash;
+    use solana_program::program_pack::Pack;
+    use solana_program::pubkey::Pubkey;
+
+    #[test]
+    fn test_pack_unpack_round_trip() {
+        let mut balance_account_digests = [[0; 8]; Wallet::MAX_BALANCE_ACCOUNTS];
+        balance_account_digests[2] = [7; 8];
+        let read_model = WalletReadModel {
+            is_initialized: true,
+            wallet: Pubkey::new_unique(),
+            config_policy_hash: Hash::new_unique(),
+            balance_account_digests,
+            whitelist_enabled_slots: 0b100,
+            config_approvers: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+            destinations: vec![EnabledDestination {
+                address: Pubkey::new_unique(),
+                balance_account_slots: 0b101,
+            }],
+        };
+        let mut buffer = vec![0xff; WalletReadModel::LEN];
+        WalletReadModel::pack(read_model.clone(), &mut buffer).unwrap();
+        assert_eq!(WalletReadModel::unpack(&buffer).unwrap(), read_model);
+
+        // an emptied read model leaves nothing of its earlier entries behind
+        let emptied = WalletReadModel {
+            config_approvers: vec![],
+            destinations: vec![],
+            ..read_model
+        };
+        WalletReadModel::pack(emptied.clone(), &mut buffer).unwrap();
+        assert_eq!(WalletReadModel::unpack(&buffer).unwrap(), emptied);
+        assert!(buffer[WalletReadModel::LEN - 300..].iter().all(|b| *b == 0));
+    }
+}
@@ -0,0 +1,98 @@
+use crate::constants::{HASH_LEN, PUBKEY_BYTES};
+use crate::model::wallet::WalletGuidHash;
+use crate::pda::{PdaSeeds, WalletRegistrationSeeds};
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{IsInitialized, Pack, Sealed};
+use solana_program::pubkey::Pubkey;
+
+/// The registration of a wallet created by this program, in a PDA derived from the wallet's
+/// GUID hash. Every wallet init creates one, so the program's wallets can be enumerated by
+/// fetching its accounts of this size, and a wallet can be looked up by its GUID hash without
+/// scanning at all. Since the addresses of balance accounts are derived from the GUID hash,
+/// the registration existing is what keeps a second wallet from being initialized with it.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct WalletRegistration {
+    pub is_initialized: bool,
+    pub wallet_guid_hash: WalletGuidHash,
+    pub wallet: Pubkey,
+}
+
+impl WalletRegistration {
+    /// Derive the PDA and "bump seed" of the registration of the wallet with the GUID hash.
+    pub fn find_address(wallet_guid_hash: &WalletGuidHash, program_id: &Pubkey) -> (Pubkey, u8) {
+        WalletRegistrationSeeds { wallet_guid_hash }.find_address(program_id)
+    }
+}
+
+impl Sealed for WalletRegistration {}
+
+impl IsInitialized for WalletRegistration {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for WalletRegistration {
+    const LEN: usize = 1 + // is_initialized
+        HASH_LEN + // wallet guid hash
+        PUBKEY_BYTES; // wallet
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, WalletRegistration::LEN];
+        let (is_initialized_dst, wallet_guid_hash_dst, wallet_dst) =
+            mut_array_refs![dst, 1, HASH_LEN, PUBKEY_BYTES];
+
+        is_initialized_dst[0] = self.is_initialized as u8;
+        wallet_guid_hash_dst.copy_from_slice(self.wallet_guid_hash.to_bytes());
+        wallet_dst.copy_from_slice(self.wallet.as_ref());
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, WalletRegistration::LEN];
+        let (is_initialized, wallet_guid_hash, wallet) =
+            array_refs![src, 1, HASH_LEN, PUBKEY_BYTES];
+
+        Ok(WalletRegistration {
+            is_initialized: match is_initialized {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            wallet_guid_hash: WalletGuidHash::new(wallet_guid_hash),
+            wallet: Pubkey::new_from_array(*wallet),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_unpack() {
+        let registration = WalletRegistration {
+            is_initialized: true,
+            wallet_guid_hash: WalletGuidHash::new(&[1; HASH_LEN]),
+            wallet: Pubkey::new_unique(),
+        };
+        let mut buffer = vec![0; WalletRegistration::LEN];
+        WalletRegistration::pack(registration, &mut buffer).unwrap();
+        assert_eq!(WalletRegistration::unpack(&buffer).unwrap(), registration);
+
+        buffer[0] = 2;
+        assert_eq!(
+            WalletRegistration::unpack_unchecked(&buffer),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn test_addresses_are_per_wallet() {
+        let program_id = Pubkey::new_unique();
+        assert_ne!(
+            WalletRegistration::find_address(&WalletGuidHash::new(&[1; HASH_LEN]), &program_id),
+            WalletRegistration::find_address(&WalletGuidHash::new(&[2; HASH_LEN]), &program_id)
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use crate::instruction::WalletConfigPolicyUpdate;
+use crate::model::multisig_op::SlotUpdateType;
+use crate::model::signer::Signer;
+use crate::pda::{MirrorAuthoritySeeds, PdaSeeds};
+use crate::utils::SlotId;
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
+
+/// The part a wallet plays in keeping a hot-standby wallet's config in sync with its own. A
+/// primary mirrors its config changes into its standby as it finalizes them, and a standby only
+/// takes config changes from its primary, so that it can take over from it on a cutover.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum WalletReplication {
+    Primary { standby: Pubkey },
+    Standby { primary: Pubkey },
+}
+
+impl WalletReplication {
+    pub const LEN: usize = 1 + // role
+        PUBKEY_BYTES; // other wallet
+
+    const PRIMARY: u8 = 1;
+    const STANDBY: u8 = 2;
+
+    /// Derive the address and "bump seed" a primary signs the updates it mirrors into its
+    /// standby with.
+    pub fn mirror_authority(primary: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        MirrorAuthoritySeeds { primary }.find_address(program_id)
+    }
+
+    /// Packs the replication, if any; a zero role stands for none.
+    pub fn pack_into_slice(replication: &Option<WalletReplication>, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, WalletReplication::LEN];
+        let (role_dst, wallet_dst) = mut_array_refs![dst, 1, PUBKEY_BYTES];
+        let (role, wallet) = match replication {
+            Some(WalletReplication::Primary { standby }) => (Self::PRIMARY, *standby),
+            Some(WalletReplication::Standby { primary }) => (Self::STANDBY, *primary),
+            None => (0, Pubkey::default()),
+        };
+        role_dst[0] = role;
+        wallet_dst.copy_from_slice(wallet.as_ref());
+    }
+
+    pub fn unpack_from_slice(src: &[u8]) -> Option<WalletReplication> {
+        let src = array_ref![src, 0, WalletReplication::LEN];
+        let (role, wallet) = array_refs![src, 1, PUBKEY_BYTES];
+        let wallet = Pubkey::new_from_array(*wallet);
+        match role[0] {
+            Self::PRIMARY => Some(WalletReplication::Primary { standby: wallet }),
+            Self::STANDBY => Some(WalletReplication::Standby { primary: wallet }),
+            _ => None,
+        }
+    }
+}
+
+/// A config change a primary has finalized, as applied to its standby.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum MirroredUpdate {
+    ConfigPolicy(WalletConfigPolicyUpdate),
+    Signer {
+        slot_update_type: SlotUpdateType,
+        slot_id: SlotId<Signer>,
+        signer: Signer,
+    },
+}
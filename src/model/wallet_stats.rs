@@ -0,0 +1,283 @@
+use crate::constants::PUBKEY_BYTES;
+use crate::model::multisig_op::MultisigOpCode;
+use crate::pda::{PdaSeeds, WalletStatsSeeds};
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{IsInitialized, Pack, Sealed};
+use solana_program::pubkey::Pubkey;
+
+/// An op counter kept for every type of op.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum OpCounter {
+    Initiated,
+    Approved,
+    Denied,
+    /// The op expired before being finalized, whether or not it had been approved.
+    Expired,
+}
+
+impl OpCounter {
+    fn index(&self) -> usize {
+        match self {
+            OpCounter::Initiated => 0,
+            OpCounter::Approved => 1,
+            OpCounter::Denied => 2,
+            OpCounter::Expired => 3,
+        }
+    }
+}
+
+/// The number of ops of one type that were initiated, approved, denied and expired.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct OpTypeStats {
+    pub initiated: u64,
+    pub approved: u64,
+    pub denied: u64,
+    pub expired: u64,
+}
+
+impl OpTypeStats {
+    pub const LEN: usize = 8 * 4;
+
+    pub fn get(&self, counter: OpCounter) -> u64 {
+        match counter {
+            OpCounter::Initiated => self.initiated,
+            OpCounter::Approved => self.approved,
+            OpCounter::Denied => self.denied,
+            OpCounter::Expired => self.expired,
+        }
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, OpTypeStats::LEN];
+        let (initiated_dst, approved_dst, denied_dst, expired_dst) =
+            mut_array_refs![dst, 8, 8, 8, 8];
+        *initiated_dst = self.initiated.to_le_bytes();
+        *approved_dst = self.approved.to_le_bytes();
+        *denied_dst = self.denied.to_le_bytes();
+        *expired_dst = self.expired.to_le_bytes();
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Self {
+        let src = array_ref![src, 0, OpTypeStats::LEN];
+        let (initiated, approved, denied, expired) = array_refs![src, 8, 8, 8, 8];
+        OpTypeStats {
+            initiated: u64::from_le_bytes(*initiated),
+            approved: u64::from_le_bytes(*approved),
+            denied: u64::from_le_bytes(*denied),
+            expired: u64::from_le_bytes(*expired),
+        }
+    }
+}
+
+/// Aggregate counters of a wallet's ops and outflows, kept in a PDA derived from the wallet so
+/// dashboards can read authoritative totals without replaying its history. It is created by
+/// `InitWalletStats`, and counted by any instruction it is supplied to as a trailing account.
+/// Counters saturate rather than overflow.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct WalletStats {
+    pub is_initialized: bool,
+    pub wallet: Pubkey,
+    /// Indexed by op code, with room for op types added later.
+    pub op_stats: Vec<OpTypeStats>,
+    /// Lamports transferred out of the wallet's balance accounts.
+    pub sol_outflow: u64,
+    /// Tokens transferred out of the wallet's balance accounts, in the smallest units of their
+    /// mints, summed over every mint.
+    pub spl_outflow: u64,
+}
+
+impl WalletStats {
+    pub const OP_TYPES: usize = 64;
+
+    const OP_STATS_OFFSET: usize = 1 + PUBKEY_BYTES;
+    const SOL_OUTFLOW_OFFSET: usize =
+        WalletStats::OP_STATS_OFFSET + OpTypeStats::LEN * WalletStats::OP_TYPES;
+    const SPL_OUTFLOW_OFFSET: usize = WalletStats::SOL_OUTFLOW_OFFSET + 8;
+
+    pub fn new(wallet: Pubkey) -> Self {
+        WalletStats {
+            is_initialized: true,
+            wallet,
+            op_stats: vec![OpTypeStats::default(); WalletStats::OP_TYPES],
+            sol_outflow: 0,
+            spl_outflow: 0,
+        }
+    }
+
+    /// Derive the PDA and "bump seed" of the stats of the given wallet.
+    pub fn find_address(wallet: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        WalletStatsSeeds { wallet }.find_address(program_id)
+    }
+
+    pub fn op_type_stats(&self, op_code: MultisigOpCode) -> OpTypeStats {
+        self.op_stats[usize::from(u8::from(op_code))]
+    }
+
+    pub fn wallet_from_slice(src: &[u8]) -> Result<Pubkey, ProgramError> {
+        if src.len() != WalletStats::LEN || src[0] != 1 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(Pubkey::new_from_array(*array_ref![src, 1, PUBKEY_BYTES]))
+    }
+
+    /// Increments a counter of packed stats in place, without unpacking the other counters.
+    pub fn increment_in_slice(
+        dst: &mut [u8],
+        op_code: MultisigOpCode,
+        counter: OpCounter,
+    ) -> Result<(), ProgramError> {
+        WalletStats::wallet_from_slice(dst)?;
+        let offset = WalletStats::OP_STATS_OFFSET
+            + OpTypeStats::LEN * usize::from(u8::from(op_code))
+            + 8 * counter.index();
+        WalletStats::add_in_slice(dst, offset, 1);
+        Ok(())
+    }
+
+    /// Adds an outflow to packed stats in place, to the SOL outflow if no token mint is given.
+    pub fn add_outflow_in_slice(
+        dst: &mut [u8],
+        token_mint: Option<&Pubkey>,
+        amount: u64,
+    ) -> Result<(), ProgramError> {
+        WalletStats::wallet_from_slice(dst)?;
+        let offset = match token_mint {
+            None => WalletStats::SOL_OUTFLOW_OFFSET,
+            Some(_) => WalletStats::SPL_OUTFLOW_OFFSET,
+        };
+        WalletStats::add_in_slice(dst, offset, amount);
+        Ok(())
+    }
+
+    fn add_in_slice(dst: &mut [u8], offset: usize, amount: u64) {
+        let counter_dst = array_mut_ref![dst, offset, 8];
+        *counter_dst = u64::from_le_bytes(*counter_dst)
+            .saturating_add(amount)
+            .to_le_bytes();
+    }
+}
+
+impl Sealed for WalletStats {}
+
+impl IsInitialized for WalletStats {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for WalletStats {
+    const LEN: usize = WalletStats::SPL_OUTFLOW_OFFSET + 8;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, WalletStats::LEN];
+        let (is_initialized_dst, wallet_dst, op_stats_dst, sol_outflow_dst, spl_outflow_dst) = mut_array_refs![
+            dst,
+            1,
+            PUBKEY_BYTES,
+            OpTypeStats::LEN * WalletStats::OP_TYPES,
+            8,
+            8
+        ];
+        is_initialized_dst[0] = self.is_initialized as u8;
+        wallet_dst.copy_from_slice(self.wallet.as_ref());
+        for (stats, stats_dst) in self
+            .op_stats
+            .iter()
+            .zip(op_stats_dst.chunks_exact_mut(OpTypeStats::LEN))
+        {
+            stats.pack_into_slice(stats_dst);
+        }
+        *sol_outflow_dst = self.sol_outflow.to_le_bytes();
+        *spl_outflow_dst = self.spl_outflow.to_le_bytes();
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, WalletStats::LEN];
+        let (is_initialized, wallet, op_stats, sol_outflow, spl_outflow) = array_refs![
+            src,
+            1,
+            PUBKEY_BYTES,
+            OpTypeStats::LEN * WalletStats::OP_TYPES,
+            8,
+            8
+        ];
+        let is_initialized = match is_initialized {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        Ok(WalletStats {
+            is_initialized,
+            wallet: Pubkey::new_from_array(*wallet),
+            op_stats: op_stats
+                .chunks_exact(OpTypeStats::LEN)
+                .map(OpTypeStats::unpack_from_slice)
+                .collect(),
+            sol_outflow: u64::from_le_bytes(*sol_outflow),
+            spl_outflow: u64::from_le_bytes(*spl_outflow),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::model::multisig_op::MultisigOpCode;
+    use crate::model::wallet_stats::{OpCounter, WalletStats};
+    use solana_program::program_error::ProgramError;
+    use solana_program::program_pack::Pack;
+    use solana_program::pubkey::Pubkey;
+
+    #[test]
+    fn test_count_in_place() {
+        let wallet = Pubkey::new_unique();
+        let mut buffer = vec![0; WalletStats::LEN];
+        assert_eq!(
+            WalletStats::increment_in_slice(
+                &mut buffer,
+                MultisigOpCode::Transfer,
+                OpCounter::Initiated
+            ),
+            Err(ProgramError::InvalidAccountData)
+        );
+        WalletStats::new(wallet).pack_into_slice(&mut buffer);
+
+        for counter in [
+            OpCounter::Initiated,
+            OpCounter::Approved,
+            OpCounter::Initiated,
+        ] {
+            WalletStats::increment_in_slice(&mut buffer, MultisigOpCode::Transfer, counter)
+                .unwrap();
+        }
+        WalletStats::increment_in_slice(
+            &mut buffer,
+            MultisigOpCode::RenewAddressBookEntry,
+            OpCounter::Expired,
+        )
+        .unwrap();
+        WalletStats::add_outflow_in_slice(&mut buffer, None, 100).unwrap();
+        WalletStats::add_outflow_in_slice(&mut buffer, None, u64::MAX).unwrap();
+        WalletStats::add_outflow_in_slice(&mut buffer, Some(&Pubkey::new_unique()), 7).unwrap();
+
+        let stats = WalletStats::unpack(&buffer).unwrap();
+        assert_eq!(stats.wallet, wallet);
+        let transfers = stats.op_type_stats(MultisigOpCode::Transfer);
+        assert_eq!(transfers.get(OpCounter::Initiated), 2);
+        assert_eq!(transfers.get(OpCounter::Approved), 1);
+        assert_eq!(transfers.get(OpCounter::Denied), 0);
+        assert_eq!(
+            stats
+                .op_type_stats(MultisigOpCode::RenewAddressBookEntry)
+                .expired,
+            1
+        );
+        assert_eq!(stats.sol_outflow, u64::MAX);
+        assert_eq!(stats.spl_outflow, 7);
+
+        let mut repacked = vec![0; WalletStats::LEN];
+        stats.pack_into_slice(&mut repacked);
+        assert_eq!(repacked, buffer);
+    }
+}
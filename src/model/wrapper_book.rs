@@ -0,0 +1,293 @@
+use crate::error::WalletError;
+use crate::model::multisig_op::WrapDirection;
+use crate::serialization_utils::{read_slice, read_u8};
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use bytes::BufMut;
+use solana_program::account_info::AccountInfo;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::{Pubkey, PUBKEY_BYTES};
+use std::slice::Iter;
+
+/// A mint the wallet's balance accounts can wrap SOL into other than wrapped SOL, and the
+/// adapter program that wraps and unwraps it. An adapter is invoked with the instruction built
+/// by `adapter_instruction`, signed by the balance account.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct WrapperEntry {
+    pub mint: Pubkey,
+    pub adapter_program: Pubkey,
+    /// The accounts the adapter takes besides the balance account, its token account and the
+    /// mint, in order, followed by the default key in each unused place. No others can be
+    /// passed on to the adapter.
+    pub adapter_accounts: [Pubkey; WrapperEntry::MAX_ADAPTER_ACCOUNTS],
+}
+
+impl WrapperEntry {
+    pub const MAX_ADAPTER_ACCOUNTS: usize = 4;
+    pub const LEN: usize = PUBKEY_BYTES + // mint
+        PUBKEY_BYTES + // adapter program
+        PUBKEY_BYTES * WrapperEntry::MAX_ADAPTER_ACCOUNTS; // adapter accounts
+
+    /// The accounts that have to be passed on to the adapter.
+    pub fn expected_adapter_accounts(&self) -> Vec<Pubkey> {
+        self.adapter_accounts
+            .iter()
+            .copied()
+            .take_while(|account| *account != Pubkey::default())
+            .collect()
+    }
+
+    fn pack(&self, dst: &mut [u8]) {
+        dst[..PUBKEY_BYTES].copy_from_slice(self.mint.as_ref());
+        dst[PUBKEY_BYTES..PUBKEY_BYTES * 2].copy_from_slice(self.adapter_program.as_ref());
+        for (account, account_dst) in self
+            .adapter_accounts
+            .iter()
+            .zip(dst[PUBKEY_BYTES * 2..WrapperEntry::LEN].chunks_exact_mut(PUBKEY_BYTES))
+        {
+            account_dst.copy_from_slice(account.as_ref());
+        }
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> WrapperEntry {
+        let mut adapter_accounts = [Pubkey::default(); WrapperEntry::MAX_ADAPTER_ACCOUNTS];
+        for (account, account_src) in adapter_accounts
+            .iter_mut()
+            .zip(src[PUBKEY_BYTES * 2..WrapperEntry::LEN].chunks_exact(PUBKEY_BYTES))
+        {
+            *account = Pubkey::new(account_src);
+        }
+        WrapperEntry {
+            mint: Pubkey::new(&src[..PUBKEY_BYTES]),
+            adapter_program: Pubkey::new(&src[PUBKEY_BYTES..PUBKEY_BYTES * 2]),
+            adapter_accounts,
+        }
+    }
+
+    /// The instruction asking the adapter to wrap `amount` lamports of the balance account into
+    /// the mint, or to unwrap `amount` of the mint's tokens back into lamports. Its data is the
+    /// direction followed by the amount as a little-endian u64, and its accounts are:
+    ///
+    /// 0. `[signer, writable]` The balance account
+    /// 1. `[writable]` The balance account's associated token account of the mint
+    /// 2. `[writable]` The mint
+    /// 3. Any number of adapter accounts: the entry's, as they are passed to the finalize
+    pub fn adapter_instruction(
+        &self,
+        balance_account: &Pubkey,
+        wrapped_token_account: &Pubkey,
+        direction: WrapDirection,
+        amount: u64,
+        adapter_account_infos: &[AccountInfo],
+    ) -> Instruction {
+        let mut data = Vec::with_capacity(1 + 8);
+        data.push(direction.to_u8());
+        data.put_u64_le(amount);
+        let mut accounts = vec![
+            AccountMeta::new(*balance_account, true),
+            AccountMeta::new(*wrapped_token_account, false),
+            AccountMeta::new(self.mint, false),
+        ];
+        accounts.extend(
+            adapter_account_infos
+                .iter()
+                .map(|account_info| AccountMeta {
+                    pubkey: *account_info.key,
+                    is_signer: account_info.is_signer,
+                    is_writable: account_info.is_writable,
+                }),
+        );
+        Instruction {
+            program_id: self.adapter_program,
+            accounts,
+            data,
+        }
+    }
+}
+
+/// The wrapper mints the wallet's balance accounts can wrap into besides wrapped SOL, which
+/// is wrapped by the token program itself and so needs no entry.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct WrapperBook {
+    pub entries: Vec<WrapperEntry>,
+}
+
+impl WrapperBook {
+    pub const MAX_ENTRIES: usize = 4;
+    pub const LEN: usize = 1 + // entry count
+        WrapperEntry::LEN * WrapperBook::MAX_ENTRIES; // entries
+
+    pub fn validate(&self) -> Result<(), ProgramError> {
+        if self.entries.len() > WrapperBook::MAX_ENTRIES {
+            msg!(
+                "A wrapper book can have at most {} entries",
+                WrapperBook::MAX_ENTRIES
+            );
+            return Err(WalletError::InvalidWrapperBook.into());
+        }
+        for (i, entry) in self.entries.iter().enumerate() {
+            if entry.mint == Pubkey::default()
+                || entry.adapter_program == Pubkey::default()
+                || entry.mint == spl_token::native_mint::id()
+            {
+                msg!(
+                    "Wrapper mint {} needs an adapter, and can't be wrapped SOL",
+                    entry.mint
+                );
+                return Err(WalletError::InvalidWrapperBook.into());
+            }
+            let expected_adapter_accounts = entry.expected_adapter_accounts();
+            if entry.adapter_accounts[expected_adapter_accounts.len()..]
+                .iter()
+                .any(|account| *account != Pubkey::default())
+            {
+                msg!(
+                    "Adapter accounts of wrapper mint {} can't have gaps",
+                    entry.mint
+                );
+                return Err(WalletError::InvalidWrapperBook.into());
+            }
+            if self.entries[..i]
+                .iter()
+                .any(|other| other.mint == entry.mint)
+            {
+                msg!("Wrapper mint {} is listed more than once", entry.mint);
+                return Err(WalletError::InvalidWrapperBook.into());
+            }
+        }
+        Ok(())
+    }
+
+    pub fn find(&self, mint: &Pubkey) -> Result<&WrapperEntry, ProgramError> {
+        self.entries
+            .iter()
+            .find(|entry| entry.mint == *mint)
+            .ok_or_else(|| {
+                msg!("{} isn't in the wallet's wrapper book", mint);
+                WalletError::UnknownWrapperMint.into()
+            })
+    }
+
+    pub fn pack(&self, dst: &mut Vec<u8>) {
+        dst.push(self.entries.len() as u8);
+        for entry in &self.entries {
+            let mut entry_bytes = [0; WrapperEntry::LEN];
+            entry.pack(&mut entry_bytes);
+            dst.extend_from_slice(&entry_bytes);
+        }
+    }
+
+    pub fn unpack(iter: &mut Iter<u8>) -> Result<WrapperBook, ProgramError> {
+        let count = *read_u8(iter).ok_or(ProgramError::InvalidInstructionData)?;
+        Ok(WrapperBook {
+            entries: read_slice(iter, usize::from(count) * WrapperEntry::LEN)
+                .ok_or(ProgramError::InvalidInstructionData)?
+                .chunks_exact(WrapperEntry::LEN)
+                .map(WrapperEntry::unpack_from_slice)
+                .collect(),
+        })
+    }
+
+    pub fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, WrapperBook::LEN];
+        let (count_dst, entries_dst) =
+            mut_array_refs![dst, 1, WrapperEntry::LEN * WrapperBook::MAX_ENTRIES];
+        count_dst[0] = self.entries.len() as u8;
+        entries_dst.fill(0);
+        for (entry, entry_dst) in self
+            .entries
+            .iter()
+            .zip(entries_dst.chunks_exact_mut(WrapperEntry::LEN))
+        {
+            entry.pack(entry_dst);
+        }
+    }
+
+    pub fn unpack_from_slice(src: &[u8]) -> Result<WrapperBook, ProgramError> {
+        let src = array_ref![src, 0, WrapperBook::LEN];
+        let (count, entries_src) =
+            array_refs![src, 1, WrapperEntry::LEN * WrapperBook::MAX_ENTRIES];
+        let count = usize::from(count[0]);
+        if count > WrapperBook::MAX_ENTRIES {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(WrapperBook {
+            entries: entries_src
+                .chunks_exact(WrapperEntry::LEN)
+                .take(count)
+                .map(WrapperEntry::unpack_from_slice)
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::WalletError;
+    use crate::model::wrapper_book::{WrapperBook, WrapperEntry};
+    use solana_program::program_error::ProgramError;
+    use solana_program::pubkey::Pubkey;
+
+    fn entry() -> WrapperEntry {
+        let mut adapter_accounts = [Pubkey::default(); WrapperEntry::MAX_ADAPTER_ACCOUNTS];
+        adapter_accounts[0] = Pubkey::new_unique();
+        WrapperEntry {
+            mint: Pubkey::new_unique(),
+            adapter_program: Pubkey::new_unique(),
+            adapter_accounts,
+        }
+    }
+
+    #[test]
+    fn test_wrapper_book() {
+        let first = entry();
+        let book = WrapperBook {
+            entries: vec![first, entry()],
+        };
+        assert_eq!(book.validate(), Ok(()));
+        assert_eq!(book.find(&first.mint), Ok(&first));
+        assert_eq!(
+            first.expected_adapter_accounts(),
+            vec![first.adapter_accounts[0]]
+        );
+        let mut gap = first;
+        gap.adapter_accounts[2] = Pubkey::new_unique();
+        assert_eq!(
+            book.find(&Pubkey::new_unique()),
+            Err(WalletError::UnknownWrapperMint.into())
+        );
+
+        let invalid: ProgramError = WalletError::InvalidWrapperBook.into();
+        let invalid_books = [
+            vec![first, first],
+            vec![WrapperEntry {
+                mint: spl_token::native_mint::id(),
+                ..first
+            }],
+            vec![WrapperEntry {
+                adapter_program: Pubkey::default(),
+                ..first
+            }],
+            vec![gap],
+            (0..=WrapperBook::MAX_ENTRIES).map(|_| entry()).collect(),
+        ];
+        for entries in invalid_books {
+            assert_eq!(WrapperBook { entries }.validate(), Err(invalid.clone()));
+        }
+    }
+
+    #[test]
+    fn test_pack_unpack_round_trip() {
+        let book = WrapperBook {
+            entries: vec![entry(), entry()],
+        };
+        let mut buffer = vec![0xff; WrapperBook::LEN];
+        book.pack_into_slice(&mut buffer);
+        assert_eq!(WrapperBook::unpack_from_slice(&buffer), Ok(book.clone()));
+
+        let mut bytes = Vec::new();
+        book.pack(&mut bytes);
+        assert_eq!(WrapperBook::unpack(&mut bytes.iter()), Ok(book));
+    }
+}
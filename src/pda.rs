@@ -0,0 +1,309 @@
+//! The program's PDAs. Each has a seeds type here, which is the only place its seeds are
+//! spelled out, so that the models deriving an address, the handlers validating an account
+//! against it and the handlers signing for it can't disagree on them.
+
+use crate::error::WalletError;
+use crate::model::balance_account::BalanceAccountGuidHash;
+use crate::model::wallet::WalletGuidHash;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+
+const DESTINATION_RECEIPT_SEED: &[u8] = b"destination_receipt";
+const INSURANCE_ATTESTATION_SEED: &[u8] = b"insurance_attestation";
+const INTERNAL_TRANSFER_HANDSHAKE_SEED: &[u8] = b"internal_transfer_handshake";
+const LOOKUP_TABLE_AUTHORITY_SEED: &[u8] = b"lookup_table_authority";
+const MERKLE_ADDRESS_BOOK_SEED: &[u8] = b"merkle_address_book";
+const MIRROR_AUTHORITY_SEED: &[u8] = b"mirror";
+const PROGRAM_PARAMETERS_SEED: &[u8] = b"program_parameters";
+const SESSION_KEY_REGISTRY_SEED: &[u8] = b"session_key_registry";
+const TOKEN_ACCOUNT_REGISTRY_SEED: &[u8] = b"token_account_registry";
+const WALLET_EVENT_LOG_SEED: &[u8] = b"wallet_event_log";
+const WALLET_READ_MODEL_SEED: &[u8] = b"wallet_read_model";
+const WALLET_REGISTRATION_SEED: &[u8] = b"wallet_registration";
+const WALLET_STATS_SEED: &[u8] = b"wallet_stats";
+
+/// The seeds of one of the program's PDAs, without its bump seed.
+pub trait PdaSeeds {
+    fn seeds(&self) -> Vec<&[u8]>;
+
+    /// Derive the PDA and "bump seed".
+    fn find_address(&self, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&self.seeds(), program_id)
+    }
+
+    /// The seeds followed by the bump seed, to sign for the PDA with.
+    fn signer_seeds<'a>(&'a self, bump_seed: &'a [u8; 1]) -> Vec<&'a [u8]> {
+        let mut seeds = self.seeds();
+        seeds.push(bump_seed);
+        seeds
+    }
+}
+
+/// Validate that `key` is the PDA of the given seeds and, if given, that its bump seed is the
+/// expected one. If valid, returns the bump seed.
+pub fn verify(
+    program_id: &Pubkey,
+    seeds: &impl PdaSeeds,
+    key: &Pubkey,
+    expected_bump_seed: Option<u8>,
+) -> Result<u8, ProgramError> {
+    let (address, bump_seed) = seeds.find_address(program_id);
+    if address != *key {
+        return Err(WalletError::InvalidPDA.into());
+    }
+    if let Some(expected_bump_seed) = expected_bump_seed {
+        if bump_seed != expected_bump_seed {
+            return Err(WalletError::InvalidPDA.into());
+        }
+    }
+    Ok(bump_seed)
+}
+
+/// A balance account, which holds the funds and signs for them.
+pub struct BalanceAccountSeeds<'a> {
+    pub wallet_guid_hash: &'a WalletGuidHash,
+    pub account_guid_hash: &'a BalanceAccountGuidHash,
+}
+
+impl PdaSeeds for BalanceAccountSeeds<'_> {
+    fn seeds(&self) -> Vec<&[u8]> {
+        vec![
+            self.wallet_guid_hash.to_bytes(),
+            self.account_guid_hash.to_bytes(),
+        ]
+    }
+}
+
+/// A deposit address of a balance account, which funds are swept from into the balance account.
+pub struct DepositAddressSeeds<'a> {
+    pub wallet_guid_hash: &'a WalletGuidHash,
+    pub account_guid_hash: &'a BalanceAccountGuidHash,
+    pub index: u8,
+}
+
+impl PdaSeeds for DepositAddressSeeds<'_> {
+    fn seeds(&self) -> Vec<&[u8]> {
+        vec![
+            self.wallet_guid_hash.to_bytes(),
+            self.account_guid_hash.to_bytes(),
+            std::slice::from_ref(&self.index),
+        ]
+    }
+}
+
+/// The wrapped SOL account an unwrap op empties into the balance account, which lives only for
+/// the length of the finalize.
+pub struct TemporaryUnwrappingAccountSeeds<'a> {
+    pub wallet_guid_hash: &'a WalletGuidHash,
+    pub multisig_op: &'a Pubkey,
+}
+
+impl PdaSeeds for TemporaryUnwrappingAccountSeeds<'_> {
+    fn seeds(&self) -> Vec<&[u8]> {
+        vec![self.wallet_guid_hash.to_bytes(), self.multisig_op.as_ref()]
+    }
+}
+
+/// The running totals of a wallet's transfers of a token to a destination.
+pub struct DestinationReceiptSeeds<'a> {
+    pub wallet: &'a Pubkey,
+    pub destination: &'a Pubkey,
+    pub token_mint: &'a Pubkey,
+}
+
+impl PdaSeeds for DestinationReceiptSeeds<'_> {
+    fn seeds(&self) -> Vec<&[u8]> {
+        vec![
+            DESTINATION_RECEIPT_SEED,
+            self.wallet.as_ref(),
+            self.destination.as_ref(),
+            self.token_mint.as_ref(),
+        ]
+    }
+}
+
+/// The custody insurance attestation of a wallet.
+pub struct InsuranceAttestationSeeds<'a> {
+    pub wallet: &'a Pubkey,
+}
+
+impl PdaSeeds for InsuranceAttestationSeeds<'_> {
+    fn seeds(&self) -> Vec<&[u8]> {
+        vec![INSURANCE_ATTESTATION_SEED, self.wallet.as_ref()]
+    }
+}
+
+/// The acknowledgement of an internal transfer by its destination wallet.
+pub struct InternalTransferHandshakeSeeds<'a> {
+    pub multisig_op: &'a Pubkey,
+    pub destination: &'a Pubkey,
+}
+
+impl PdaSeeds for InternalTransferHandshakeSeeds<'_> {
+    fn seeds(&self) -> Vec<&[u8]> {
+        vec![
+            INTERNAL_TRANSFER_HANDSHAKE_SEED,
+            self.multisig_op.as_ref(),
+            self.destination.as_ref(),
+        ]
+    }
+}
+
+/// The authority of a wallet's address lookup tables.
+pub struct LookupTableAuthoritySeeds<'a> {
+    pub wallet: &'a Pubkey,
+}
+
+impl PdaSeeds for LookupTableAuthoritySeeds<'_> {
+    fn seeds(&self) -> Vec<&[u8]> {
+        vec![LOOKUP_TABLE_AUTHORITY_SEED, self.wallet.as_ref()]
+    }
+}
+
+/// The merkle address book of a balance account.
+pub struct MerkleAddressBookSeeds<'a> {
+    pub wallet: &'a Pubkey,
+    pub account_guid_hash: &'a BalanceAccountGuidHash,
+}
+
+impl PdaSeeds for MerkleAddressBookSeeds<'_> {
+    fn seeds(&self) -> Vec<&[u8]> {
+        vec![
+            MERKLE_ADDRESS_BOOK_SEED,
+            self.wallet.as_ref(),
+            self.account_guid_hash.to_bytes(),
+        ]
+    }
+}
+
+/// The address a primary wallet signs the updates it mirrors into its standby with.
+pub struct MirrorAuthoritySeeds<'a> {
+    pub primary: &'a Pubkey,
+}
+
+impl PdaSeeds for MirrorAuthoritySeeds<'_> {
+    fn seeds(&self) -> Vec<&[u8]> {
+        vec![MIRROR_AUTHORITY_SEED, self.primary.as_ref()]
+    }
+}
+
+/// The program-global parameters the upgrade authority can tune.
+pub struct ProgramParametersSeeds;
+
+impl PdaSeeds for ProgramParametersSeeds {
+    fn seeds(&self) -> Vec<&[u8]> {
+        vec![PROGRAM_PARAMETERS_SEED]
+    }
+}
+
+/// The session keys registered by a wallet's approvers.
+pub struct SessionKeyRegistrySeeds<'a> {
+    pub wallet: &'a Pubkey,
+}
+
+impl PdaSeeds for SessionKeyRegistrySeeds<'_> {
+    fn seeds(&self) -> Vec<&[u8]> {
+        vec![SESSION_KEY_REGISTRY_SEED, self.wallet.as_ref()]
+    }
+}
+
+/// The token accounts registered for a balance account.
+pub struct TokenAccountRegistrySeeds<'a> {
+    pub wallet: &'a Pubkey,
+    pub account_guid_hash: &'a BalanceAccountGuidHash,
+}
+
+impl PdaSeeds for TokenAccountRegistrySeeds<'_> {
+    fn seeds(&self) -> Vec<&[u8]> {
+        vec![
+            TOKEN_ACCOUNT_REGISTRY_SEED,
+            self.wallet.as_ref(),
+            self.account_guid_hash.to_bytes(),
+        ]
+    }
+}
+
+/// The ring buffer of a wallet's op events.
+pub struct WalletEventLogSeeds<'a> {
+    pub wallet: &'a Pubkey,
+}
+
+impl PdaSeeds for WalletEventLogSeeds<'_> {
+    fn seeds(&self) -> Vec<&[u8]> {
+        vec![WALLET_EVENT_LOG_SEED, self.wallet.as_ref()]
+    }
+}
+
+/// The read model a wallet's state is exported into.
+pub struct WalletReadModelSeeds<'a> {
+    pub wallet: &'a Pubkey,
+}
+
+impl PdaSeeds for WalletReadModelSeeds<'_> {
+    fn seeds(&self) -> Vec<&[u8]> {
+        vec![WALLET_READ_MODEL_SEED, self.wallet.as_ref()]
+    }
+}
+
+/// The registration of a wallet, by its GUID hash.
+pub struct WalletRegistrationSeeds<'a> {
+    pub wallet_guid_hash: &'a WalletGuidHash,
+}
+
+impl PdaSeeds for WalletRegistrationSeeds<'_> {
+    fn seeds(&self) -> Vec<&[u8]> {
+        vec![WALLET_REGISTRATION_SEED, self.wallet_guid_hash.to_bytes()]
+    }
+}
+
+/// The aggregate op and outflow counters of a wallet.
+pub struct WalletStatsSeeds<'a> {
+    pub wallet: &'a Pubkey,
+}
+
+impl PdaSeeds for WalletStatsSeeds<'_> {
+    fn seeds(&self) -> Vec<&[u8]> {
+        vec![WALLET_STATS_SEED, self.wallet.as_ref()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify() {
+        let program_id = Pubkey::new_unique();
+        let wallet = Pubkey::new_unique();
+        let seeds = WalletEventLogSeeds { wallet: &wallet };
+        let (address, bump_seed) = seeds.find_address(&program_id);
+        assert_eq!(
+            address,
+            Pubkey::create_program_address(&seeds.signer_seeds(&[bump_seed]), &program_id).unwrap()
+        );
+
+        assert_eq!(verify(&program_id, &seeds, &address, None), Ok(bump_seed));
+        assert_eq!(
+            verify(&program_id, &seeds, &address, Some(bump_seed)),
+            Ok(bump_seed)
+        );
+        assert_eq!(
+            verify(
+                &program_id,
+                &seeds,
+                &address,
+                Some(bump_seed.wrapping_sub(1))
+            ),
+            Err(WalletError::InvalidPDA.into())
+        );
+        assert_eq!(
+            verify(
+                &program_id,
+                &WalletReadModelSeeds { wallet: &wallet },
+                &address,
+                None
+            ),
+            Err(WalletError::InvalidPDA.into())
+        );
+    }
+}
@@ -0,0 +1,278 @@
+//! Off-chain checks of a wallet's policies for configurations that are allowed by the program
+//! but are likely to be mistakes, so front-ends can warn about them before they are approved.
+
+use crate::model::balance_account::{BalanceAccount, BalanceAccountGuidHash};
+use crate::model::wallet::{Approvers, Wallet};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Critical,
+}
+
+/// The policy a finding is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyScope {
+    WalletConfig,
+    BalanceAccount(BalanceAccountGuidHash),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FindingKind {
+    /// The policy has a single approver, whose approval alone is enough.
+    SingleApproverQuorum,
+    /// An enabled assistant can initiate operations that a single approval then lets through.
+    AssistantWithSingleApproval,
+    /// The account can transfer to any destination while holding at least the configured
+    /// high balance threshold.
+    WhitelistDisabledOnHighBalance { balance: u64 },
+    /// Operations expire sooner than the configured minimum approval timeout.
+    ShortApprovalTimeout { timeout: Duration },
+}
+
+impl FindingKind {
+    pub fn severity(&self) -> Severity {
+        match self {
+            FindingKind::SingleApproverQuorum | FindingKind::AssistantWithSingleApproval => {
+                Severity::Critical
+            }
+            FindingKind::WhitelistDisabledOnHighBalance { .. }
+            | FindingKind::ShortApprovalTimeout { .. } => Severity::Warning,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Finding {
+    pub scope: PolicyScope,
+    pub kind: FindingKind,
+}
+
+impl Finding {
+    pub fn severity(&self) -> Severity {
+        self.kind.severity()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PolicyLintConfig {
+    /// The balance from which an account without a whitelist is reported, in whatever unit
+    /// the balances passed to `lint_wallet` are in.
+    pub high_balance_threshold: u64,
+    pub min_approval_timeout: Duration,
+}
+
+impl PolicyLintConfig {
+    pub const DEFAULT_MIN_APPROVAL_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+    pub fn new(high_balance_threshold: u64) -> Self {
+        PolicyLintConfig {
+            high_balance_threshold,
+            min_approval_timeout: PolicyLintConfig::DEFAULT_MIN_APPROVAL_TIMEOUT,
+        }
+    }
+}
+
+/// Reports the risky configurations of the wallet config policy and of the policies of its
+/// balance accounts that aren't archived. Accounts missing from `balances` are not checked
+/// for a whitelist.
+pub fn lint_wallet(
+    wallet: &Wallet,
+    balances: &BTreeMap<BalanceAccountGuidHash, u64>,
+    config: &PolicyLintConfig,
+) -> Vec<Finding> {
+    let has_enabled_assistant = wallet
+        .assistants
+        .filled_slots()
+        .iter()
+        .any(|(_, assistant)| assistant.enabled);
+    let mut findings = Vec::new();
+
+    lint_approval_policy(
+        PolicyScope::WalletConfig,
+        &wallet.config_approvers,
+        wallet.approvals_required_for_config,
+        wallet.approval_timeout_for_config,
+        has_enabled_assistant,
+        config,
+        &mut findings,
+    );
+
+    for (_, balance_account) in wallet.balance_accounts.filled_slots() {
+        if !balance_account.is_archived() {
+            lint_balance_account(
+                &balance_account,
+                balances.get(&balance_account.guid_hash).copied(),
+                has_enabled_assistant,
+                config,
+                &mut findings,
+            );
+        }
+    }
+
+    findings
+}
+
+fn lint_balance_account(
+    balance_account: &BalanceAccount,
+    balance: Option<u64>,
+    has_enabled_assistant: bool,
+    config: &PolicyLintConfig,
+    findings: &mut Vec<Finding>,
+) {
+    let scope = PolicyScope::BalanceAccount(balance_account.guid_hash);
+    lint_approval_policy(
+        scope,
+        &balance_account.transfer_approvers,
+        balance_account.approvals_required_for_transfer,
+        balance_account.approval_timeout_for_transfer,
+        has_enabled_assistant,
+        config,
+        findings,
+    );
+
+    if let Some(balance) = balance {
+        if balance_account.is_whitelist_disabled() && balance >= config.high_balance_threshold {
+            findings.push(Finding {
+                scope,
+                kind: FindingKind::WhitelistDisabledOnHighBalance { balance },
+            });
+        }
+    }
+}
+
+fn lint_approval_policy(
+    scope: PolicyScope,
+    approvers: &Approvers,
+    approvals_required: u8,
+    approval_timeout: Duration,
+    has_enabled_assistant: bool,
+    config: &PolicyLintConfig,
+    findings: &mut Vec<Finding>,
+) {
+    if approvals_required == 1 {
+        if approvers.count_enabled() == 1 {
+            findings.push(Finding {
+                scope,
+                kind: FindingKind::SingleApproverQuorum,
+            });
+        }
+        // an assistant's initiation doesn't count as an approval, but one approver is enough
+        if has_enabled_assistant {
+            findings.push(Finding {
+                scope,
+                kind: FindingKind::AssistantWithSingleApproval,
+            });
+        }
+    }
+    if approval_timeout < config.min_approval_timeout {
+        findings.push(Finding {
+            scope,
+            kind: FindingKind::ShortApprovalTimeout {
+                timeout: approval_timeout,
+            },
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::HASH_LEN;
+    use crate::instruction::InitialWalletConfig;
+    use crate::model::address_book::DAppCategories;
+    use crate::model::balance_account::{
+        AllowedDestinations, ApprovalTiers, BalanceAccountNameHash, FinalizeDelayPolicy,
+        RewardSplitPolicy, SpendingLimit,
+    };
+    use crate::model::multisig_op::BooleanSetting;
+    use crate::model::signer::Signer;
+    use crate::model::wallet::WalletGuidHash;
+    use crate::utils::SlotId;
+    use solana_program::program_pack::Pack;
+    use solana_program::pubkey::Pubkey;
+
+    fn balance_account(guid_byte: u8, approvals_required: u8, timeout: Duration) -> BalanceAccount {
+        BalanceAccount {
+            guid_hash: BalanceAccountGuidHash::new(&[guid_byte; HASH_LEN]),
+            name_hash: BalanceAccountNameHash::zero(),
+            approvals_required_for_transfer: approvals_required,
+            approval_timeout_for_transfer: timeout,
+            transfer_approvers: Approvers::from_enabled_vec(vec![SlotId::new(0), SlotId::new(1)]),
+            allowed_destinations: AllowedDestinations::zero(),
+            whitelist_enabled: BooleanSetting::Off,
+            dapps_enabled: BooleanSetting::Off,
+            archived: BooleanSetting::Off,
+            system_owned_destinations_only: BooleanSetting::Off,
+            reward_split_policy: RewardSplitPolicy::zero(),
+            finalize_delay_policy: FinalizeDelayPolicy::default(),
+            mint_cap: 0,
+            spending_limit: SpendingLimit::default(),
+            approval_tiers: ApprovalTiers::none(),
+            disallowed_dapp_categories: DAppCategories::NONE,
+            deposit_addresses_enabled: BooleanSetting::Off,
+        }
+    }
+
+    #[test]
+    fn test_lint_wallet() {
+        let mut wallet = Wallet::unpack_unchecked(&vec![0; Wallet::LEN]).unwrap();
+        wallet
+            .init(
+                &WalletGuidHash::new(&[1; HASH_LEN]),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                &InitialWalletConfig {
+                    approvals_required_for_config: 1,
+                    approval_timeout_for_config: Duration::from_secs(3600),
+                    signers: vec![
+                        (SlotId::new(0), Signer::new(Pubkey::new_unique())),
+                        (SlotId::new(1), Signer::new(Pubkey::new_unique())),
+                    ],
+                    config_approvers: vec![SlotId::new(0)],
+                },
+            )
+            .unwrap();
+
+        let risky = balance_account(2, 2, Duration::from_secs(120));
+        let mut archived = balance_account(3, 1, Duration::from_secs(120));
+        archived.archived = BooleanSetting::On;
+        wallet.balance_accounts.insert(SlotId::new(0), risky);
+        wallet.balance_accounts.insert(SlotId::new(1), archived);
+
+        let balances = BTreeMap::from([(risky.guid_hash, 1000), (archived.guid_hash, 1000)]);
+        let findings = lint_wallet(&wallet, &balances, &PolicyLintConfig::new(1000));
+        let account_scope = PolicyScope::BalanceAccount(risky.guid_hash);
+        assert_eq!(
+            findings,
+            vec![
+                Finding {
+                    scope: PolicyScope::WalletConfig,
+                    kind: FindingKind::SingleApproverQuorum,
+                },
+                Finding {
+                    scope: PolicyScope::WalletConfig,
+                    kind: FindingKind::AssistantWithSingleApproval,
+                },
+                Finding {
+                    scope: account_scope,
+                    kind: FindingKind::ShortApprovalTimeout {
+                        timeout: Duration::from_secs(120),
+                    },
+                },
+                Finding {
+                    scope: account_scope,
+                    kind: FindingKind::WhitelistDisabledOnHighBalance { balance: 1000 },
+                },
+            ]
+        );
+        assert_eq!(findings[0].severity(), Severity::Critical);
+        assert_eq!(findings[3].severity(), Severity::Warning);
+
+        // without a known balance the missing whitelist is not reported
+        let findings = lint_wallet(&wallet, &BTreeMap::new(), &PolicyLintConfig::new(1000));
+        assert_eq!(findings.len(), 3);
+    }
+}
@@ -1,13 +1,33 @@
+use crate::handlers::balance_account_settings_update_handler::BalanceAccountSettingsUpdate;
+#[cfg(feature = "demo")]
+use crate::handlers::demo_wallet_handler;
+use crate::handlers::payment_channel_handler::PaymentChannelTerms;
+use crate::handlers::transfer_handler::TransferFee;
 use crate::handlers::{
-    address_book_update_handler, approval_disposition_handler,
+    address_book_import_handler, address_book_renewal_handler, address_book_update_handler,
+    approval_disposition_handler, approval_group_update_handler,
     balance_account_address_whitelist_update_handler, balance_account_creation_handler,
-    balance_account_name_update_handler, balance_account_policy_update_handler,
-    balance_account_settings_update_handler, cleanup_handler, dapp_book_update_handler,
-    dapp_transaction_handler, init_wallet_handler, migrate_handler, sign_data_handler,
-    transfer_handler, update_signer_handler, wallet_config_policy_update_handler,
-    wrap_unwrap_handler,
+    balance_account_deletion_handler, balance_account_name_update_handler,
+    balance_account_policy_update_handler, balance_account_settings_update_handler,
+    cleanup_expired_op_handler, cleanup_handler, compliance_policy_update_handler,
+    config_update_simulation_handler, dapp_book_update_handler, dapp_transaction_handler,
+    deposit_address_handler, fee_policy_update_handler, finalize_submitter_policy_update_handler,
+    full_rotation_handler, init_wallet_handler, init_with_compute_hint_handler,
+    init_with_signature_handler, init_with_stake_weights_handler, insurance_attestation_handler,
+    internal_transfer_acknowledgement_handler, lookup_table_handler,
+    merkle_address_book_update_handler, migrate_handler, mint_to_handler, nudge_handler,
+    op_bundle_handler, payment_channel_handler, pending_address_book_entries_handler,
+    price_oracle_update_handler, program_parameters_handler, rent_return_allowlist_update_handler,
+    reward_split_policy_update_handler, session_key_handler, set_mint_authority_handler,
+    sign_data_handler, signer_activation_delay_update_handler, spending_limit_update_handler,
+    spl_token_accounts_creation_handler, stake_weight_governance_update_handler,
+    token_account_registry_handler, transfer_handler, update_assistant_handler,
+    update_signer_handler, vote_rewards_withdrawal_handler, wallet_compaction_handler,
+    wallet_config_policy_update_handler, wallet_event_log_handler, wallet_freeze_handler,
+    wallet_provisioning_handler, wallet_read_model_handler, wallet_replication_handler,
+    wallet_stats_handler, wrap_unwrap_handler, wrapper_book_update_handler,
 };
-use crate::instruction::ProgramInstruction;
+use crate::instruction::{ProgramInstruction, TransferFinalizeStep};
 use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
 
 pub struct Processor;
@@ -17,8 +37,23 @@ impl Processor {
         program_id: &Pubkey,
         accounts: &[AccountInfo],
         instruction_data: &[u8],
+    ) -> ProgramResult {
+        let logged_op = wallet_event_log_handler::snapshot(program_id, accounts);
+        let counted_op = wallet_stats_handler::snapshot(program_id, accounts);
+        Self::dispatch(program_id, accounts, instruction_data)?;
+        wallet_event_log_handler::record(program_id, accounts, instruction_data, logged_op)?;
+        wallet_stats_handler::record(program_id, accounts, instruction_data, counted_op)
+    }
+
+    /// Processes the instruction, and the init it wraps for instructions wrapping one, so that
+    /// events are logged once for the outermost instruction.
+    fn dispatch(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        instruction_data: &[u8],
     ) -> ProgramResult {
         let instruction = ProgramInstruction::unpack(instruction_data)?;
+        let is_config_finalize = instruction.is_config_finalize();
 
         match instruction {
             ProgramInstruction::InitWallet {
@@ -125,36 +160,113 @@ impl Processor {
                 account_guid_hash,
                 amount,
                 destination_name_hash,
+                destination_acknowledgement_required,
+                deduct_fee_from_amount,
+                allow_destination_ata_creation,
+                memo_hash,
+                destination_proof,
             } => transfer_handler::init(
                 program_id,
                 &accounts,
-                fee_amount,
-                fee_account_guid_hash,
+                TransferFee {
+                    amount: fee_amount,
+                    account_guid_hash: fee_account_guid_hash,
+                    deduct_from_amount: deduct_fee_from_amount,
+                },
                 &account_guid_hash,
                 amount,
                 &destination_name_hash,
+                destination_acknowledgement_required,
+                allow_destination_ata_creation,
+                memo_hash,
+                &destination_proof,
             ),
 
             ProgramInstruction::FinalizeTransfer {
                 account_guid_hash,
                 amount,
                 token_mint,
+                destination_acknowledgement_required,
+                deduct_fee_from_amount,
+                allow_destination_ata_creation,
+                memo,
             } => transfer_handler::finalize(
                 program_id,
                 &accounts,
                 &account_guid_hash,
                 amount,
                 token_mint,
+                destination_acknowledgement_required,
+                deduct_fee_from_amount,
+                allow_destination_ata_creation,
+                &memo,
+                TransferFinalizeStep::VerifyAndExecute,
+            ),
+
+            ProgramInstruction::VerifyTransfer {
+                account_guid_hash,
+                amount,
+                token_mint,
+                destination_acknowledgement_required,
+                deduct_fee_from_amount,
+                allow_destination_ata_creation,
+                memo,
+            } => transfer_handler::finalize(
+                program_id,
+                accounts,
+                &account_guid_hash,
+                amount,
+                token_mint,
+                destination_acknowledgement_required,
+                deduct_fee_from_amount,
+                allow_destination_ata_creation,
+                &memo,
+                TransferFinalizeStep::Verify,
+            ),
+
+            ProgramInstruction::ExecuteTransfer {
+                account_guid_hash,
+                amount,
+                token_mint,
+                destination_acknowledgement_required,
+                deduct_fee_from_amount,
+                allow_destination_ata_creation,
+                memo,
+            } => transfer_handler::finalize(
+                program_id,
+                accounts,
+                &account_guid_hash,
+                amount,
+                token_mint,
+                destination_acknowledgement_required,
+                deduct_fee_from_amount,
+                allow_destination_ata_creation,
+                &memo,
+                TransferFinalizeStep::Execute,
             ),
 
             ProgramInstruction::SetApprovalDisposition {
                 disposition,
                 params_hash,
+                reason,
             } => approval_disposition_handler::handle(
                 program_id,
                 &accounts,
                 disposition,
                 params_hash,
+                reason,
+            ),
+
+            ProgramInstruction::SetApprovalDispositionBySignature {
+                disposition,
+                params_hash,
+                expires_at,
+            } => approval_disposition_handler::handle_by_signature(
+                program_id,
+                accounts,
+                disposition,
+                params_hash,
+                expires_at,
             ),
 
             ProgramInstruction::InitWrapUnwrap {
@@ -255,26 +367,38 @@ impl Processor {
                 account_guid_hash,
                 whitelist_enabled,
                 dapps_enabled,
+                archived,
+                system_owned_destinations_only,
             } => balance_account_settings_update_handler::init(
                 program_id,
                 &accounts,
                 fee_amount,
                 fee_account_guid_hash,
                 &account_guid_hash,
-                whitelist_enabled,
-                dapps_enabled,
+                BalanceAccountSettingsUpdate {
+                    whitelist_enabled,
+                    dapps_enabled,
+                    archived,
+                    system_owned_destinations_only,
+                },
             ),
 
             ProgramInstruction::FinalizeAccountSettingsUpdate {
                 account_guid_hash,
                 whitelist_enabled,
                 dapps_enabled,
+                archived,
+                system_owned_destinations_only,
             } => balance_account_settings_update_handler::finalize(
                 program_id,
                 &accounts,
                 &account_guid_hash,
-                whitelist_enabled,
-                dapps_enabled,
+                BalanceAccountSettingsUpdate {
+                    whitelist_enabled,
+                    dapps_enabled,
+                    archived,
+                    system_owned_destinations_only,
+                },
             ),
 
             ProgramInstruction::InitDAppBookUpdate {
@@ -351,6 +475,928 @@ impl Processor {
             ProgramInstruction::FinalizeSignData { ref data } => {
                 sign_data_handler::finalize(program_id, accounts, data)
             }
+
+            ProgramInstruction::AcknowledgeInternalTransfer {
+                account_guid_hash,
+                params_hash,
+            } => internal_transfer_acknowledgement_handler::handle(
+                program_id,
+                accounts,
+                &account_guid_hash,
+                &params_hash,
+            ),
+
+            ProgramInstruction::AddPendingAddressBookEntries { entries } => {
+                pending_address_book_entries_handler::add(program_id, accounts, &entries)
+            }
+
+            ProgramInstruction::RatifyPendingAddressBookEntries { entries } => {
+                pending_address_book_entries_handler::ratify(program_id, accounts, &entries)
+            }
+
+            ProgramInstruction::RemoveDeprecatedAddressBookEntries { entries } => {
+                address_book_update_handler::remove_deprecated(program_id, accounts, &entries)
+            }
+
+            ProgramInstruction::ActivateWalletConfigPolicy {} => {
+                wallet_config_policy_update_handler::activate(program_id, accounts)
+            }
+
+            ProgramInstruction::CancelTransfer {
+                account_guid_hash,
+                amount,
+                token_mint,
+                destination_acknowledgement_required,
+                deduct_fee_from_amount,
+                allow_destination_ata_creation,
+                memo,
+            } => transfer_handler::cancel(
+                program_id,
+                accounts,
+                &account_guid_hash,
+                amount,
+                token_mint,
+                destination_acknowledgement_required,
+                deduct_fee_from_amount,
+                allow_destination_ata_creation,
+                &memo,
+            ),
+
+            ProgramInstruction::InitVoteRewardsWithdrawal {
+                fee_amount,
+                fee_account_guid_hash,
+                account_guid_hash,
+                amount,
+            } => vote_rewards_withdrawal_handler::init(
+                program_id,
+                accounts,
+                fee_amount,
+                fee_account_guid_hash,
+                &account_guid_hash,
+                amount,
+            ),
+
+            ProgramInstruction::FinalizeVoteRewardsWithdrawal {
+                account_guid_hash,
+                amount,
+            } => vote_rewards_withdrawal_handler::finalize(
+                program_id,
+                accounts,
+                &account_guid_hash,
+                amount,
+            ),
+
+            ProgramInstruction::InitUpdateAssistant {
+                fee_amount,
+                fee_account_guid_hash,
+                slot_update_type,
+                slot_id,
+                assistant,
+            } => update_assistant_handler::init(
+                program_id,
+                accounts,
+                fee_amount,
+                fee_account_guid_hash,
+                slot_update_type,
+                slot_id,
+                assistant,
+            ),
+
+            ProgramInstruction::FinalizeUpdateAssistant {
+                slot_update_type,
+                slot_id,
+                assistant,
+            } => update_assistant_handler::finalize(
+                program_id,
+                accounts,
+                slot_update_type,
+                slot_id,
+                assistant,
+            ),
+
+            ProgramInstruction::InitRewardSplitPolicyUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                account_guid_hash,
+                ref splits,
+            } => reward_split_policy_update_handler::init(
+                program_id,
+                accounts,
+                fee_amount,
+                fee_account_guid_hash,
+                &account_guid_hash,
+                splits,
+            ),
+
+            ProgramInstruction::FinalizeRewardSplitPolicyUpdate {
+                account_guid_hash,
+                ref splits,
+            } => reward_split_policy_update_handler::finalize(
+                program_id,
+                accounts,
+                &account_guid_hash,
+                splits,
+            ),
+
+            ProgramInstruction::InitFinalizeSubmitterPolicyUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                ref policy,
+            } => finalize_submitter_policy_update_handler::init(
+                program_id,
+                accounts,
+                fee_amount,
+                fee_account_guid_hash,
+                policy,
+            ),
+
+            ProgramInstruction::FinalizeFinalizeSubmitterPolicyUpdate { ref policy } => {
+                finalize_submitter_policy_update_handler::finalize(program_id, accounts, policy)
+            }
+
+            ProgramInstruction::InitMintTo {
+                fee_amount,
+                fee_account_guid_hash,
+                ref account_guid_hash,
+                amount,
+                ref destination_name_hash,
+            } => mint_to_handler::init(
+                program_id,
+                accounts,
+                fee_amount,
+                fee_account_guid_hash,
+                account_guid_hash,
+                amount,
+                destination_name_hash,
+            ),
+
+            ProgramInstruction::FinalizeMintTo {
+                ref account_guid_hash,
+                amount,
+            } => mint_to_handler::finalize(program_id, accounts, account_guid_hash, amount),
+
+            ProgramInstruction::InitSetMintAuthority {
+                fee_amount,
+                fee_account_guid_hash,
+                ref account_guid_hash,
+                authority_type,
+                new_authority,
+            } => set_mint_authority_handler::init(
+                program_id,
+                accounts,
+                fee_amount,
+                fee_account_guid_hash,
+                account_guid_hash,
+                authority_type,
+                new_authority,
+            ),
+
+            ProgramInstruction::FinalizeSetMintAuthority {
+                ref account_guid_hash,
+                authority_type,
+                new_authority,
+            } => set_mint_authority_handler::finalize(
+                program_id,
+                accounts,
+                account_guid_hash,
+                authority_type,
+                new_authority,
+            ),
+
+            ProgramInstruction::SimulateWalletConfigPolicyUpdate { update } => {
+                wallet_config_policy_update_handler::simulate(program_id, accounts, &update)
+            }
+
+            ProgramInstruction::SimulateBalanceAccountPolicyUpdate {
+                account_guid_hash,
+                update,
+            } => balance_account_policy_update_handler::simulate(
+                program_id,
+                accounts,
+                &account_guid_hash,
+                &update,
+            ),
+
+            ProgramInstruction::SimulateConfigUpdate { update } => {
+                config_update_simulation_handler::simulate(program_id, accounts, &update)
+            }
+
+            ProgramInstruction::InitPaymentChannel {
+                fee_amount,
+                fee_account_guid_hash,
+                ref account_guid_hash,
+                ref destination_name_hash,
+                token_mint,
+                allowance,
+                duration,
+            } => payment_channel_handler::init(
+                program_id,
+                accounts,
+                fee_amount,
+                fee_account_guid_hash,
+                account_guid_hash,
+                destination_name_hash,
+                PaymentChannelTerms {
+                    token_mint,
+                    allowance,
+                    duration,
+                },
+            ),
+
+            ProgramInstruction::FinalizePaymentChannel {
+                ref account_guid_hash,
+                token_mint,
+                allowance,
+                duration,
+            } => payment_channel_handler::finalize(
+                program_id,
+                accounts,
+                account_guid_hash,
+                token_mint,
+                allowance,
+                duration,
+            ),
+
+            ProgramInstruction::ChannelPayment { amount } => {
+                payment_channel_handler::pay(program_id, accounts, amount)
+            }
+
+            ProgramInstruction::InitOpBundle {} => op_bundle_handler::init(program_id, accounts),
+
+            ProgramInstruction::ApproveOpBundle { bundle_hash } => {
+                op_bundle_handler::approve(program_id, accounts, bundle_hash)
+            }
+
+            ProgramInstruction::ApplyOpBundleApproval {} => {
+                op_bundle_handler::apply_approval(program_id, accounts)
+            }
+
+            ProgramInstruction::CloseOpBundle {} => op_bundle_handler::close(program_id, accounts),
+
+            ProgramInstruction::InitLookupTableUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                lookup_table,
+                ref new_addresses,
+            } => lookup_table_handler::init(
+                program_id,
+                accounts,
+                fee_amount,
+                fee_account_guid_hash,
+                lookup_table,
+                new_addresses,
+            ),
+
+            ProgramInstruction::FinalizeLookupTableUpdate {
+                lookup_table,
+                ref new_addresses,
+                recent_slot,
+            } => lookup_table_handler::finalize(
+                program_id,
+                accounts,
+                lookup_table,
+                new_addresses,
+                recent_slot,
+            ),
+
+            ProgramInstruction::InitAddressBookImport {
+                fee_amount,
+                fee_account_guid_hash,
+                ref entries,
+                ref balance_accounts,
+            } => address_book_import_handler::init(
+                program_id,
+                accounts,
+                fee_amount,
+                fee_account_guid_hash,
+                entries,
+                balance_accounts,
+            ),
+
+            ProgramInstruction::FinalizeAddressBookImport { ref import } => {
+                address_book_import_handler::finalize(program_id, accounts, import)
+            }
+
+            ProgramInstruction::InitCommittedTransfer {
+                fee_amount,
+                fee_account_guid_hash,
+                account_guid_hash,
+                amount,
+                destination_commitment,
+            } => transfer_handler::init_committed(
+                program_id,
+                accounts,
+                fee_amount,
+                fee_account_guid_hash,
+                &account_guid_hash,
+                amount,
+                &destination_commitment,
+            ),
+
+            ProgramInstruction::FinalizeCommittedTransfer {
+                account_guid_hash,
+                amount,
+                destination_commitment,
+                destination_name_hash,
+                salt,
+            } => transfer_handler::finalize_committed(
+                program_id,
+                accounts,
+                &account_guid_hash,
+                amount,
+                &destination_commitment,
+                &destination_name_hash,
+                &salt,
+            ),
+
+            ProgramInstruction::InitCompliancePolicyUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                policy,
+            } => compliance_policy_update_handler::init(
+                program_id,
+                accounts,
+                fee_amount,
+                fee_account_guid_hash,
+                &policy,
+            ),
+
+            ProgramInstruction::FinalizeCompliancePolicyUpdate { policy } => {
+                compliance_policy_update_handler::finalize(program_id, accounts, &policy)
+            }
+
+            ProgramInstruction::ComplianceApprove { params_hash } => {
+                compliance_policy_update_handler::approve(program_id, accounts, params_hash)
+            }
+
+            ProgramInstruction::GetAllowedDestinations {
+                account_guid_hash,
+                start_slot_id,
+            } => balance_account_address_whitelist_update_handler::get_allowed_destinations(
+                program_id,
+                accounts,
+                &account_guid_hash,
+                start_slot_id,
+            ),
+
+            ProgramInstruction::InitWithSignature { init } => {
+                let accounts = init_with_signature_handler::authenticate_initiator(
+                    program_id, accounts, &init,
+                )?;
+                Self::dispatch(program_id, &accounts, &init)
+            }
+
+            ProgramInstruction::InitWalletReplicationUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                replication,
+            } => wallet_replication_handler::init(
+                program_id,
+                accounts,
+                fee_amount,
+                fee_account_guid_hash,
+                &replication,
+            ),
+
+            ProgramInstruction::FinalizeWalletReplicationUpdate { replication } => {
+                wallet_replication_handler::finalize(program_id, accounts, &replication)
+            }
+
+            ProgramInstruction::ApplyMirroredUpdate { update } => {
+                wallet_replication_handler::apply(program_id, accounts, &update)
+            }
+
+            ProgramInstruction::InitBalanceAccountDeletion {
+                fee_amount,
+                fee_account_guid_hash,
+                account_guid_hash,
+            } => balance_account_deletion_handler::init(
+                program_id,
+                accounts,
+                fee_amount,
+                fee_account_guid_hash,
+                &account_guid_hash,
+            ),
+
+            ProgramInstruction::FinalizeBalanceAccountDeletion { account_guid_hash } => {
+                balance_account_deletion_handler::finalize(program_id, accounts, &account_guid_hash)
+            }
+
+            ProgramInstruction::InitSpendingLimitUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                account_guid_hash,
+                token_mint,
+                amount,
+            } => spending_limit_update_handler::init(
+                program_id,
+                accounts,
+                fee_amount,
+                fee_account_guid_hash,
+                &account_guid_hash,
+                &token_mint,
+                amount,
+            ),
+
+            ProgramInstruction::FinalizeSpendingLimitUpdate {
+                account_guid_hash,
+                token_mint,
+                amount,
+            } => spending_limit_update_handler::finalize(
+                program_id,
+                accounts,
+                &account_guid_hash,
+                &token_mint,
+                amount,
+            ),
+
+            ProgramInstruction::Nudge { idle_slots } => {
+                nudge_handler::handle(program_id, accounts, idle_slots)
+            }
+
+            ProgramInstruction::SuspendDApp { dapp } => {
+                dapp_book_update_handler::suspend(program_id, accounts, &dapp)
+            }
+
+            ProgramInstruction::InitPriceOracleUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                oracle,
+            } => price_oracle_update_handler::init(
+                program_id,
+                accounts,
+                fee_amount,
+                fee_account_guid_hash,
+                &oracle,
+            ),
+
+            ProgramInstruction::FinalizePriceOracleUpdate { oracle } => {
+                price_oracle_update_handler::finalize(program_id, accounts, &oracle)
+            }
+
+            ProgramInstruction::RegisterTokenAccounts { account_guid_hash } => {
+                token_account_registry_handler::register(program_id, accounts, &account_guid_hash)
+            }
+
+            ProgramInstruction::InitMerkleAddressBookUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                account_guid_hash,
+                root,
+            } => merkle_address_book_update_handler::init(
+                program_id,
+                accounts,
+                fee_amount,
+                fee_account_guid_hash,
+                &account_guid_hash,
+                &root,
+            ),
+
+            ProgramInstruction::FinalizeMerkleAddressBookUpdate {
+                account_guid_hash,
+                root,
+            } => merkle_address_book_update_handler::finalize(
+                program_id,
+                accounts,
+                &account_guid_hash,
+                &root,
+            ),
+
+            ProgramInstruction::CleanupExpiredOp {} => {
+                cleanup_expired_op_handler::handle(program_id, accounts)
+            }
+
+            ProgramInstruction::InitWithComputeHint {
+                finalize_compute_units,
+                init,
+            } => {
+                init_with_compute_hint_handler::validate_init(&init)?;
+                Self::dispatch(program_id, accounts, &init)?;
+                init_with_compute_hint_handler::record_hint(
+                    program_id,
+                    accounts,
+                    finalize_compute_units,
+                )
+            }
+
+            ProgramInstruction::ExportWalletReadModel {} => {
+                wallet_read_model_handler::export(program_id, accounts)
+            }
+
+            ProgramInstruction::InitWalletFreeze {
+                fee_amount,
+                fee_account_guid_hash,
+                freeze,
+            } => wallet_freeze_handler::init(
+                program_id,
+                accounts,
+                fee_amount,
+                fee_account_guid_hash,
+                &freeze,
+            ),
+
+            ProgramInstruction::FinalizeWalletFreeze { freeze } => {
+                wallet_freeze_handler::finalize(program_id, accounts, &freeze)
+            }
+
+            ProgramInstruction::EmergencyFreeze {} => {
+                wallet_freeze_handler::emergency_freeze(program_id, accounts)
+            }
+
+            ProgramInstruction::InitRentReturnAllowlistUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                allowlist,
+            } => rent_return_allowlist_update_handler::init(
+                program_id,
+                accounts,
+                fee_amount,
+                fee_account_guid_hash,
+                &allowlist,
+            ),
+
+            ProgramInstruction::FinalizeRentReturnAllowlistUpdate { allowlist } => {
+                rent_return_allowlist_update_handler::finalize(program_id, accounts, &allowlist)
+            }
+
+            ProgramInstruction::InitFullRotation {
+                fee_amount,
+                fee_account_guid_hash,
+                rotation,
+            } => full_rotation_handler::init(
+                program_id,
+                accounts,
+                fee_amount,
+                fee_account_guid_hash,
+                &rotation,
+            ),
+
+            ProgramInstruction::FinalizeFullRotation { rotation } => {
+                full_rotation_handler::finalize(program_id, accounts, &rotation)
+            }
+
+            ProgramInstruction::UpdateProgramParameters { parameters } => {
+                program_parameters_handler::update(program_id, accounts, &parameters)
+            }
+
+            ProgramInstruction::InitSignerActivationDelayUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                delay,
+            } => signer_activation_delay_update_handler::init(
+                program_id,
+                accounts,
+                fee_amount,
+                fee_account_guid_hash,
+                &delay,
+            ),
+
+            ProgramInstruction::FinalizeSignerActivationDelayUpdate { delay } => {
+                signer_activation_delay_update_handler::finalize(program_id, accounts, &delay)
+            }
+
+            ProgramInstruction::InitFeePolicyUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                fee_policy,
+            } => fee_policy_update_handler::init(
+                program_id,
+                accounts,
+                fee_amount,
+                fee_account_guid_hash,
+                &fee_policy,
+            ),
+
+            ProgramInstruction::FinalizeFeePolicyUpdate { fee_policy } => {
+                fee_policy_update_handler::finalize(program_id, accounts, &fee_policy)
+            }
+
+            ProgramInstruction::CleanupUncreatedBalanceAccount { account_guid_hash } => {
+                balance_account_creation_handler::cleanup(program_id, accounts, &account_guid_hash)
+            }
+
+            ProgramInstruction::InitStakeWeightGovernanceUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                governance,
+            } => stake_weight_governance_update_handler::init(
+                program_id,
+                accounts,
+                fee_amount,
+                fee_account_guid_hash,
+                &governance,
+            ),
+
+            ProgramInstruction::FinalizeStakeWeightGovernanceUpdate { governance } => {
+                stake_weight_governance_update_handler::finalize(program_id, accounts, &governance)
+            }
+
+            ProgramInstruction::InitWithStakeWeights {
+                stake_account_count,
+                init,
+            } => {
+                init_with_stake_weights_handler::validate_init(&init)?;
+                let (init_accounts, snapshot_accounts) =
+                    init_with_stake_weights_handler::split_accounts(accounts, stake_account_count)?;
+                Self::dispatch(program_id, init_accounts, &init)?;
+                init_with_stake_weights_handler::record_stake_weights(
+                    program_id,
+                    init_accounts,
+                    snapshot_accounts,
+                )
+            }
+
+            ProgramInstruction::InitEventLog {} => {
+                wallet_event_log_handler::init(program_id, accounts)
+            }
+
+            ProgramInstruction::InitWalletStats {} => {
+                wallet_stats_handler::init(program_id, accounts)
+            }
+
+            ProgramInstruction::InitSPLTokenAccountsCreation {
+                fee_amount,
+                fee_account_guid_hash,
+                ref payer_account_guid_hash,
+                ref account_guid_hashes,
+            } => spl_token_accounts_creation_handler::init(
+                program_id,
+                accounts,
+                fee_amount,
+                fee_account_guid_hash,
+                payer_account_guid_hash,
+                account_guid_hashes,
+            ),
+
+            ProgramInstruction::FinalizeSPLTokenAccountsCreation {
+                ref payer_account_guid_hash,
+                ref account_guid_hashes,
+                batch_size,
+            } => spl_token_accounts_creation_handler::finalize(
+                program_id,
+                accounts,
+                payer_account_guid_hash,
+                account_guid_hashes,
+                batch_size,
+                false,
+            ),
+
+            ProgramInstruction::ContinueSPLTokenAccountsCreation {
+                ref payer_account_guid_hash,
+                ref account_guid_hashes,
+                batch_size,
+            } => spl_token_accounts_creation_handler::finalize(
+                program_id,
+                accounts,
+                payer_account_guid_hash,
+                account_guid_hashes,
+                batch_size,
+                true,
+            ),
+
+            ProgramInstruction::InitApprovalGroupUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                approval_group,
+            } => approval_group_update_handler::init(
+                program_id,
+                accounts,
+                fee_amount,
+                fee_account_guid_hash,
+                &approval_group,
+            ),
+
+            ProgramInstruction::FinalizeApprovalGroupUpdate { approval_group } => {
+                approval_group_update_handler::finalize(program_id, accounts, &approval_group)
+            }
+
+            ProgramInstruction::SubmitAggregatedApproval {
+                params_hash,
+                expires_at,
+            } => approval_disposition_handler::handle_aggregated(
+                program_id,
+                accounts,
+                params_hash,
+                expires_at,
+            ),
+
+            ProgramInstruction::InitEnableDepositAddresses {
+                fee_amount,
+                fee_account_guid_hash,
+                account_guid_hash,
+                enabled,
+            } => deposit_address_handler::init(
+                program_id,
+                accounts,
+                fee_amount,
+                fee_account_guid_hash,
+                &account_guid_hash,
+                enabled,
+            ),
+
+            ProgramInstruction::FinalizeEnableDepositAddresses {
+                account_guid_hash,
+                enabled,
+            } => {
+                deposit_address_handler::finalize(program_id, accounts, &account_guid_hash, enabled)
+            }
+
+            ProgramInstruction::FinalizeWithDepositSweep {
+                deposit_address_indices,
+                finalize,
+            } => {
+                let swept = deposit_address_handler::validate_finalize(&finalize)?;
+                let (finalize_accounts, deposit_accounts) =
+                    deposit_address_handler::split_accounts(
+                        accounts,
+                        &swept,
+                        deposit_address_indices.len(),
+                    )?;
+                deposit_address_handler::sweep(
+                    program_id,
+                    finalize_accounts,
+                    deposit_accounts,
+                    &deposit_address_indices,
+                    &swept,
+                )?;
+                Self::dispatch(program_id, finalize_accounts, &finalize)
+            }
+
+            ProgramInstruction::InitCompactWallet {
+                fee_amount,
+                fee_account_guid_hash,
+            } => wallet_compaction_handler::init(
+                program_id,
+                accounts,
+                fee_amount,
+                fee_account_guid_hash,
+            ),
+
+            ProgramInstruction::FinalizeCompactWallet {} => {
+                wallet_compaction_handler::finalize(program_id, accounts)
+            }
+
+            ProgramInstruction::InitInsuranceAttestorUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                attestor,
+            } => insurance_attestation_handler::init(
+                program_id,
+                accounts,
+                fee_amount,
+                fee_account_guid_hash,
+                &attestor,
+            ),
+
+            ProgramInstruction::FinalizeInsuranceAttestorUpdate { attestor } => {
+                insurance_attestation_handler::finalize(program_id, accounts, &attestor)
+            }
+
+            ProgramInstruction::SubmitInsuranceAttestation {
+                statement_hash,
+                coverage_expires_at,
+            } => insurance_attestation_handler::submit(
+                program_id,
+                accounts,
+                &statement_hash,
+                coverage_expires_at,
+            ),
+
+            ProgramInstruction::CheckInsuranceAttestation {} => {
+                insurance_attestation_handler::check(program_id, accounts)
+            }
+
+            ProgramInstruction::InitProvisioningWallet {
+                wallet_guid_hash,
+                initial_config,
+            } => wallet_provisioning_handler::init(
+                program_id,
+                accounts,
+                &wallet_guid_hash,
+                &initial_config,
+            ),
+
+            ProgramInstruction::UpdateProvisioningWallet { config } => {
+                wallet_provisioning_handler::update(program_id, accounts, &config)
+            }
+
+            ProgramInstruction::ActivateWallet {} => {
+                wallet_provisioning_handler::activate(program_id, accounts)
+            }
+
+            ProgramInstruction::InitWrapperBookUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                wrapper_book,
+            } => wrapper_book_update_handler::init(
+                program_id,
+                accounts,
+                fee_amount,
+                fee_account_guid_hash,
+                &wrapper_book,
+            ),
+
+            ProgramInstruction::FinalizeWrapperBookUpdate { wrapper_book } => {
+                wrapper_book_update_handler::finalize(program_id, accounts, &wrapper_book)
+            }
+
+            ProgramInstruction::InitAdapterWrapUnwrap {
+                fee_amount,
+                fee_account_guid_hash,
+                account_guid_hash,
+                wrapper_mint,
+                amount,
+                direction,
+            } => wrap_unwrap_handler::init_adapter(
+                program_id,
+                accounts,
+                fee_amount,
+                fee_account_guid_hash,
+                &account_guid_hash,
+                &wrapper_mint,
+                amount,
+                direction,
+            ),
+
+            ProgramInstruction::FinalizeAdapterWrapUnwrap {
+                account_guid_hash,
+                wrapper_mint,
+                amount,
+                direction,
+            } => wrap_unwrap_handler::finalize_adapter(
+                program_id,
+                accounts,
+                &account_guid_hash,
+                &wrapper_mint,
+                amount,
+                direction,
+            ),
+
+            ProgramInstruction::MigratePendingApprovals { approver_count } => {
+                update_signer_handler::migrate_pending_approvals(
+                    program_id,
+                    accounts,
+                    approver_count,
+                )
+            }
+
+            ProgramInstruction::InitAddressBookRenewal {
+                fee_amount,
+                fee_account_guid_hash,
+                slot_id,
+                entry,
+                expires_at,
+            } => address_book_renewal_handler::init(
+                program_id,
+                accounts,
+                fee_amount,
+                fee_account_guid_hash,
+                slot_id,
+                &entry,
+                expires_at,
+            ),
+
+            ProgramInstruction::FinalizeAddressBookRenewal {
+                slot_id,
+                entry,
+                expires_at,
+            } => address_book_renewal_handler::finalize(
+                program_id, accounts, slot_id, &entry, expires_at,
+            ),
+
+            ProgramInstruction::RegisterSessionKey {
+                session_key,
+                scope,
+                expires_at,
+            } => session_key_handler::register(
+                program_id,
+                accounts,
+                &session_key,
+                &scope,
+                expires_at,
+            ),
+
+            ProgramInstruction::RevokeSessionKey { session_key } => {
+                session_key_handler::revoke(program_id, accounts, &session_key)
+            }
+
+            #[cfg(feature = "demo")]
+            ProgramInstruction::InitDemoWallet {
+                wallet_guid_hash,
+                initial_config,
+                balance_accounts,
+            } => demo_wallet_handler::handle(
+                program_id,
+                accounts,
+                &wallet_guid_hash,
+                &initial_config,
+                &balance_accounts,
+            ),
+        }?;
+
+        if is_config_finalize {
+            wallet_read_model_handler::refresh(program_id, accounts)?;
         }
+        Ok(())
     }
 }
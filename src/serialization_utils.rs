@@ -2,11 +2,13 @@ use std::convert::TryInto;
 use std::slice::Iter;
 use std::time::Duration;
 
-use crate::constants::HASH_LEN;
+use crate::constants::{HASH_LEN, PUBKEY_BYTES};
 use crate::model::address_book::AddressBookEntryNameHash;
 use crate::model::balance_account::{BalanceAccountGuidHash, BalanceAccountNameHash};
+use solana_program::hash::Hash;
 use solana_program::program_error::ProgramError;
 use solana_program::program_pack::{IsInitialized, Pack};
+use solana_program::pubkey::Pubkey;
 
 pub fn pack_option<T>(option: Option<&T>, dst: &mut Vec<u8>)
 where
@@ -139,3 +141,35 @@ pub fn append_optional_duration(maybe_duration: &Option<Duration>, dst: &mut Vec
         dst.extend_from_slice(&buf);
     }
 }
+
+pub fn read_optional_pubkey(iter: &mut Iter<u8>) -> Result<Option<Pubkey>, ProgramError> {
+    let has_value = iter.next().ok_or(ProgramError::InvalidInstructionData)?;
+    let value_data =
+        read_fixed_size_array::<PUBKEY_BYTES>(iter).ok_or(ProgramError::InvalidInstructionData)?;
+    Ok(if *has_value == 0 {
+        None
+    } else {
+        Some(Pubkey::new_from_array(*value_data))
+    })
+}
+
+pub fn append_optional_pubkey(maybe_pubkey: &Option<Pubkey>, dst: &mut Vec<u8>) {
+    dst.push(maybe_pubkey.is_some() as u8);
+    dst.extend_from_slice(maybe_pubkey.unwrap_or_default().as_ref());
+}
+
+pub fn read_optional_hash(iter: &mut Iter<u8>) -> Result<Option<Hash>, ProgramError> {
+    let has_value = iter.next().ok_or(ProgramError::InvalidInstructionData)?;
+    let value_data =
+        read_fixed_size_array::<HASH_LEN>(iter).ok_or(ProgramError::InvalidInstructionData)?;
+    Ok(if *has_value == 0 {
+        None
+    } else {
+        Some(Hash::new_from_array(*value_data))
+    })
+}
+
+pub fn append_optional_hash(maybe_hash: &Option<Hash>, dst: &mut Vec<u8>) {
+    dst.push(maybe_hash.is_some() as u8);
+    dst.extend_from_slice(maybe_hash.unwrap_or_default().as_ref());
+}
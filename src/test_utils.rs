@@ -0,0 +1,106 @@
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+
+/// Owns the backing storage of an account so that `AccountInfo`s can be handed to model and
+/// handler code in native unit tests, without going through the BPF program test harness.
+pub struct AccountInfoFixture {
+    key: Pubkey,
+    owner: Pubkey,
+    lamports: u64,
+    data: Vec<u8>,
+    is_signer: bool,
+}
+
+impl AccountInfoFixture {
+    pub fn new(key: Pubkey) -> Self {
+        AccountInfoFixture {
+            key,
+            owner: Pubkey::default(),
+            lamports: 0,
+            data: vec![],
+            is_signer: false,
+        }
+    }
+
+    pub fn unique() -> Self {
+        Self::new(Pubkey::new_unique())
+    }
+
+    pub fn signer(mut self) -> Self {
+        self.is_signer = true;
+        self
+    }
+
+    pub fn owner(mut self, owner: Pubkey) -> Self {
+        self.owner = owner;
+        self
+    }
+
+    pub fn lamports(mut self, lamports: u64) -> Self {
+        self.lamports = lamports;
+        self
+    }
+
+    pub fn data(mut self, data: Vec<u8>) -> Self {
+        self.data = data;
+        self
+    }
+
+    /// Sizes the account data for `T`, zero-filled as it would be right after creation.
+    pub fn zeroed_for<T: Pack>(self) -> Self {
+        self.data(vec![0; T::LEN])
+    }
+
+    pub fn key(&self) -> Pubkey {
+        self.key
+    }
+
+    pub fn account_info(&mut self) -> AccountInfo<'_> {
+        AccountInfo::new(
+            &self.key,
+            self.is_signer,
+            false,
+            &mut self.lamports,
+            &mut self.data,
+            &self.owner,
+            false,
+            0,
+        )
+    }
+}
+
+pub fn clock_at(unix_timestamp: i64) -> Clock {
+    Clock {
+        unix_timestamp,
+        ..Clock::default()
+    }
+}
+
+/// A small deterministic xorshift generator, so that property tests are reproducible from
+/// their seed without pulling in a randomness dependency.
+pub struct TestRng(u64);
+
+impl TestRng {
+    pub fn new(seed: u64) -> Self {
+        // xorshift gets stuck at zero
+        TestRng(seed.max(1))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A value in `0..bound`.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+
+    pub fn chance(&mut self, numerator: u64, denominator: u64) -> bool {
+        self.below(denominator) < numerator
+    }
+}
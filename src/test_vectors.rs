@@ -0,0 +1,1179 @@
+//! Canonical encodings of every `ProgramInstruction` variant, with fixed field values, for
+//! clients in other languages to check their encoders against. The vectors are checked in at
+//! `tests/fixtures/instruction_vectors.json`; regenerate them after changing an encoding with
+//!
+//! ```text
+//! STRIKE_UPDATE_TEST_VECTORS=1 cargo test --lib --features test-vectors test_vectors
+//! ```
+
+use crate::instruction::{
+    AddressBookImport, AddressBookUpdate, BalanceAccountAddressWhitelistUpdate,
+    BalanceAccountCreation, BalanceAccountPolicyUpdate, BalanceAccountWhitelistUpdate,
+    ConfigUpdate, DAppBookUpdate, FullRotation, InitialWalletConfig, ProgramInstruction,
+    RewardSplit, WalletConfigPolicyUpdate,
+};
+use crate::model::address_book::{
+    AddressBookEntry, AddressBookEntryKind, AddressBookEntryNameHash, AddressBookEntryRisk,
+    DAppCategories, JurisdictionCode,
+};
+use crate::model::approval_group::ApprovalGroup;
+use crate::model::assistant::Assistant;
+use crate::model::balance_account::{
+    ApprovalTier, BalanceAccountGuidHash, BalanceAccountNameHash, FinalizeDelayPolicy,
+};
+use crate::model::compliance_policy::CompliancePolicy;
+use crate::model::fee_policy::FeePolicy;
+use crate::model::finalize_submitter_policy::{FinalizeSubmitterPolicy, FinalizeSubmitters};
+use crate::model::multisig_op::{
+    ApprovalDisposition, BooleanSetting, DispositionReason, MintAuthorityType, SlotUpdateType,
+    WrapDirection,
+};
+use crate::model::price_oracle::{PriceFeed, PriceOracle};
+use crate::model::program_parameters::ProgramParameters;
+use crate::model::rent_return_allowlist::RentReturnAllowlist;
+use crate::model::session_key_registry::SessionKeyScope;
+use crate::model::signer::Signer;
+use crate::model::stake_weight_governance::StakeWeightGovernance;
+use crate::model::wallet::{Approvers, WalletGuidHash};
+use crate::model::wallet_freeze::WalletFreeze;
+use crate::model::wallet_replication::{MirroredUpdate, WalletReplication};
+use crate::model::wrapper_book::{WrapperBook, WrapperEntry};
+use crate::utils::SlotId;
+use solana_program::hash::Hash;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::pubkey::Pubkey;
+use std::fmt::Write;
+use std::time::Duration;
+
+pub struct InstructionVector {
+    pub name: &'static str,
+    pub instruction: ProgramInstruction,
+}
+
+impl InstructionVector {
+    fn new(instruction: ProgramInstruction) -> InstructionVector {
+        InstructionVector {
+            name: variant_name(&instruction),
+            instruction,
+        }
+    }
+}
+
+/// The variant's name. The match is exhaustive so that a new variant can't be added without
+/// coming here, where its vector belongs too.
+pub fn variant_name(instruction: &ProgramInstruction) -> &'static str {
+    match instruction {
+        ProgramInstruction::InitWallet { .. } => "InitWallet",
+        ProgramInstruction::InitBalanceAccountCreation { .. } => "InitBalanceAccountCreation",
+        ProgramInstruction::FinalizeBalanceAccountCreation { .. } => {
+            "FinalizeBalanceAccountCreation"
+        }
+        ProgramInstruction::InitTransfer { .. } => "InitTransfer",
+        ProgramInstruction::SetApprovalDisposition { .. } => "SetApprovalDisposition",
+        ProgramInstruction::FinalizeTransfer { .. } => "FinalizeTransfer",
+        ProgramInstruction::InitWrapUnwrap { .. } => "InitWrapUnwrap",
+        ProgramInstruction::FinalizeWrapUnwrap { .. } => "FinalizeWrapUnwrap",
+        ProgramInstruction::InitUpdateSigner { .. } => "InitUpdateSigner",
+        ProgramInstruction::FinalizeUpdateSigner { .. } => "FinalizeUpdateSigner",
+        ProgramInstruction::InitWalletConfigPolicyUpdate { .. } => "InitWalletConfigPolicyUpdate",
+        ProgramInstruction::FinalizeWalletConfigPolicyUpdate { .. } => {
+            "FinalizeWalletConfigPolicyUpdate"
+        }
+        ProgramInstruction::InitDAppTransaction { .. } => "InitDAppTransaction",
+        ProgramInstruction::SupplyDAppTransactionInstructions { .. } => {
+            "SupplyDAppTransactionInstructions"
+        }
+        ProgramInstruction::FinalizeDAppTransaction { .. } => "FinalizeDAppTransaction",
+        ProgramInstruction::InitAccountSettingsUpdate { .. } => "InitAccountSettingsUpdate",
+        ProgramInstruction::FinalizeAccountSettingsUpdate { .. } => "FinalizeAccountSettingsUpdate",
+        ProgramInstruction::InitDAppBookUpdate { .. } => "InitDAppBookUpdate",
+        ProgramInstruction::FinalizeDAppBookUpdate { .. } => "FinalizeDAppBookUpdate",
+        ProgramInstruction::InitAddressBookUpdate { .. } => "InitAddressBookUpdate",
+        ProgramInstruction::FinalizeAddressBookUpdate { .. } => "FinalizeAddressBookUpdate",
+        ProgramInstruction::InitBalanceAccountNameUpdate { .. } => "InitBalanceAccountNameUpdate",
+        ProgramInstruction::FinalizeBalanceAccountNameUpdate { .. } => {
+            "FinalizeBalanceAccountNameUpdate"
+        }
+        ProgramInstruction::InitBalanceAccountPolicyUpdate { .. } => {
+            "InitBalanceAccountPolicyUpdate"
+        }
+        ProgramInstruction::FinalizeBalanceAccountPolicyUpdate { .. } => {
+            "FinalizeBalanceAccountPolicyUpdate"
+        }
+        ProgramInstruction::Migrate {} => "Migrate",
+        ProgramInstruction::Cleanup {} => "Cleanup",
+        ProgramInstruction::InitBalanceAccountAddressWhitelistUpdate { .. } => {
+            "InitBalanceAccountAddressWhitelistUpdate"
+        }
+        ProgramInstruction::FinalizeBalanceAccountAddressWhitelistUpdate { .. } => {
+            "FinalizeBalanceAccountAddressWhitelistUpdate"
+        }
+        ProgramInstruction::InitSignData { .. } => "InitSignData",
+        ProgramInstruction::FinalizeSignData { .. } => "FinalizeSignData",
+        ProgramInstruction::AcknowledgeInternalTransfer { .. } => "AcknowledgeInternalTransfer",
+        ProgramInstruction::AddPendingAddressBookEntries { .. } => "AddPendingAddressBookEntries",
+        ProgramInstruction::RatifyPendingAddressBookEntries { .. } => {
+            "RatifyPendingAddressBookEntries"
+        }
+        ProgramInstruction::InitVoteRewardsWithdrawal { .. } => "InitVoteRewardsWithdrawal",
+        ProgramInstruction::FinalizeVoteRewardsWithdrawal { .. } => "FinalizeVoteRewardsWithdrawal",
+        ProgramInstruction::InitUpdateAssistant { .. } => "InitUpdateAssistant",
+        ProgramInstruction::FinalizeUpdateAssistant { .. } => "FinalizeUpdateAssistant",
+        ProgramInstruction::InitRewardSplitPolicyUpdate { .. } => "InitRewardSplitPolicyUpdate",
+        ProgramInstruction::FinalizeRewardSplitPolicyUpdate { .. } => {
+            "FinalizeRewardSplitPolicyUpdate"
+        }
+        ProgramInstruction::RemoveDeprecatedAddressBookEntries { .. } => {
+            "RemoveDeprecatedAddressBookEntries"
+        }
+        ProgramInstruction::ActivateWalletConfigPolicy {} => "ActivateWalletConfigPolicy",
+        ProgramInstruction::CancelTransfer { .. } => "CancelTransfer",
+        ProgramInstruction::InitFinalizeSubmitterPolicyUpdate { .. } => {
+            "InitFinalizeSubmitterPolicyUpdate"
+        }
+        ProgramInstruction::FinalizeFinalizeSubmitterPolicyUpdate { .. } => {
+            "FinalizeFinalizeSubmitterPolicyUpdate"
+        }
+        ProgramInstruction::InitMintTo { .. } => "InitMintTo",
+        ProgramInstruction::FinalizeMintTo { .. } => "FinalizeMintTo",
+        ProgramInstruction::InitSetMintAuthority { .. } => "InitSetMintAuthority",
+        ProgramInstruction::FinalizeSetMintAuthority { .. } => "FinalizeSetMintAuthority",
+        ProgramInstruction::SimulateWalletConfigPolicyUpdate { .. } => {
+            "SimulateWalletConfigPolicyUpdate"
+        }
+        ProgramInstruction::SimulateBalanceAccountPolicyUpdate { .. } => {
+            "SimulateBalanceAccountPolicyUpdate"
+        }
+        ProgramInstruction::InitPaymentChannel { .. } => "InitPaymentChannel",
+        ProgramInstruction::FinalizePaymentChannel { .. } => "FinalizePaymentChannel",
+        ProgramInstruction::ChannelPayment { .. } => "ChannelPayment",
+        ProgramInstruction::InitOpBundle {} => "InitOpBundle",
+        ProgramInstruction::ApproveOpBundle { .. } => "ApproveOpBundle",
+        ProgramInstruction::ApplyOpBundleApproval {} => "ApplyOpBundleApproval",
+        ProgramInstruction::CloseOpBundle {} => "CloseOpBundle",
+        ProgramInstruction::InitLookupTableUpdate { .. } => "InitLookupTableUpdate",
+        ProgramInstruction::FinalizeLookupTableUpdate { .. } => "FinalizeLookupTableUpdate",
+        ProgramInstruction::InitAddressBookImport { .. } => "InitAddressBookImport",
+        ProgramInstruction::FinalizeAddressBookImport { .. } => "FinalizeAddressBookImport",
+        ProgramInstruction::InitCommittedTransfer { .. } => "InitCommittedTransfer",
+        ProgramInstruction::FinalizeCommittedTransfer { .. } => "FinalizeCommittedTransfer",
+        ProgramInstruction::InitCompliancePolicyUpdate { .. } => "InitCompliancePolicyUpdate",
+        ProgramInstruction::FinalizeCompliancePolicyUpdate { .. } => {
+            "FinalizeCompliancePolicyUpdate"
+        }
+        ProgramInstruction::ComplianceApprove { .. } => "ComplianceApprove",
+        ProgramInstruction::GetAllowedDestinations { .. } => "GetAllowedDestinations",
+        ProgramInstruction::InitWithSignature { .. } => "InitWithSignature",
+        ProgramInstruction::InitWalletReplicationUpdate { .. } => "InitWalletReplicationUpdate",
+        ProgramInstruction::FinalizeWalletReplicationUpdate { .. } => {
+            "FinalizeWalletReplicationUpdate"
+        }
+        ProgramInstruction::ApplyMirroredUpdate { .. } => "ApplyMirroredUpdate",
+        ProgramInstruction::InitBalanceAccountDeletion { .. } => "InitBalanceAccountDeletion",
+        ProgramInstruction::FinalizeBalanceAccountDeletion { .. } => {
+            "FinalizeBalanceAccountDeletion"
+        }
+        ProgramInstruction::InitSpendingLimitUpdate { .. } => "InitSpendingLimitUpdate",
+        ProgramInstruction::FinalizeSpendingLimitUpdate { .. } => "FinalizeSpendingLimitUpdate",
+        ProgramInstruction::Nudge { .. } => "Nudge",
+        ProgramInstruction::SuspendDApp { .. } => "SuspendDApp",
+        ProgramInstruction::InitPriceOracleUpdate { .. } => "InitPriceOracleUpdate",
+        ProgramInstruction::FinalizePriceOracleUpdate { .. } => "FinalizePriceOracleUpdate",
+        ProgramInstruction::RegisterTokenAccounts { .. } => "RegisterTokenAccounts",
+        ProgramInstruction::InitMerkleAddressBookUpdate { .. } => "InitMerkleAddressBookUpdate",
+        ProgramInstruction::FinalizeMerkleAddressBookUpdate { .. } => {
+            "FinalizeMerkleAddressBookUpdate"
+        }
+        ProgramInstruction::CleanupExpiredOp {} => "CleanupExpiredOp",
+        ProgramInstruction::InitWithComputeHint { .. } => "InitWithComputeHint",
+        ProgramInstruction::ExportWalletReadModel {} => "ExportWalletReadModel",
+        ProgramInstruction::InitWalletFreeze { .. } => "InitWalletFreeze",
+        ProgramInstruction::FinalizeWalletFreeze { .. } => "FinalizeWalletFreeze",
+        ProgramInstruction::EmergencyFreeze {} => "EmergencyFreeze",
+        ProgramInstruction::InitRentReturnAllowlistUpdate { .. } => "InitRentReturnAllowlistUpdate",
+        ProgramInstruction::FinalizeRentReturnAllowlistUpdate { .. } => {
+            "FinalizeRentReturnAllowlistUpdate"
+        }
+        ProgramInstruction::InitFullRotation { .. } => "InitFullRotation",
+        ProgramInstruction::FinalizeFullRotation { .. } => "FinalizeFullRotation",
+        ProgramInstruction::UpdateProgramParameters { .. } => "UpdateProgramParameters",
+        ProgramInstruction::InitSignerActivationDelayUpdate { .. } => {
+            "InitSignerActivationDelayUpdate"
+        }
+        ProgramInstruction::FinalizeSignerActivationDelayUpdate { .. } => {
+            "FinalizeSignerActivationDelayUpdate"
+        }
+        ProgramInstruction::InitFeePolicyUpdate { .. } => "InitFeePolicyUpdate",
+        ProgramInstruction::FinalizeFeePolicyUpdate { .. } => "FinalizeFeePolicyUpdate",
+        ProgramInstruction::CleanupUncreatedBalanceAccount { .. } => {
+            "CleanupUncreatedBalanceAccount"
+        }
+        ProgramInstruction::InitStakeWeightGovernanceUpdate { .. } => {
+            "InitStakeWeightGovernanceUpdate"
+        }
+        ProgramInstruction::FinalizeStakeWeightGovernanceUpdate { .. } => {
+            "FinalizeStakeWeightGovernanceUpdate"
+        }
+        ProgramInstruction::InitWithStakeWeights { .. } => "InitWithStakeWeights",
+        ProgramInstruction::InitEventLog {} => "InitEventLog",
+        ProgramInstruction::InitAddressBookRenewal { .. } => "InitAddressBookRenewal",
+        ProgramInstruction::FinalizeAddressBookRenewal { .. } => "FinalizeAddressBookRenewal",
+        ProgramInstruction::RegisterSessionKey { .. } => "RegisterSessionKey",
+        ProgramInstruction::RevokeSessionKey { .. } => "RevokeSessionKey",
+        ProgramInstruction::SetApprovalDispositionBySignature { .. } => {
+            "SetApprovalDispositionBySignature"
+        }
+        ProgramInstruction::InitWalletStats {} => "InitWalletStats",
+        ProgramInstruction::InitSPLTokenAccountsCreation { .. } => "InitSPLTokenAccountsCreation",
+        ProgramInstruction::FinalizeSPLTokenAccountsCreation { .. } => {
+            "FinalizeSPLTokenAccountsCreation"
+        }
+        ProgramInstruction::ContinueSPLTokenAccountsCreation { .. } => {
+            "ContinueSPLTokenAccountsCreation"
+        }
+        ProgramInstruction::VerifyTransfer { .. } => "VerifyTransfer",
+        ProgramInstruction::ExecuteTransfer { .. } => "ExecuteTransfer",
+        ProgramInstruction::SimulateConfigUpdate { .. } => "SimulateConfigUpdate",
+        ProgramInstruction::InitApprovalGroupUpdate { .. } => "InitApprovalGroupUpdate",
+        ProgramInstruction::FinalizeApprovalGroupUpdate { .. } => "FinalizeApprovalGroupUpdate",
+        ProgramInstruction::SubmitAggregatedApproval { .. } => "SubmitAggregatedApproval",
+        ProgramInstruction::InitEnableDepositAddresses { .. } => "InitEnableDepositAddresses",
+        ProgramInstruction::FinalizeEnableDepositAddresses { .. } => {
+            "FinalizeEnableDepositAddresses"
+        }
+        ProgramInstruction::FinalizeWithDepositSweep { .. } => "FinalizeWithDepositSweep",
+        ProgramInstruction::InitCompactWallet { .. } => "InitCompactWallet",
+        ProgramInstruction::FinalizeCompactWallet { .. } => "FinalizeCompactWallet",
+        ProgramInstruction::InitInsuranceAttestorUpdate { .. } => "InitInsuranceAttestorUpdate",
+        ProgramInstruction::FinalizeInsuranceAttestorUpdate { .. } => {
+            "FinalizeInsuranceAttestorUpdate"
+        }
+        ProgramInstruction::SubmitInsuranceAttestation { .. } => "SubmitInsuranceAttestation",
+        ProgramInstruction::CheckInsuranceAttestation { .. } => "CheckInsuranceAttestation",
+        ProgramInstruction::InitProvisioningWallet { .. } => "InitProvisioningWallet",
+        ProgramInstruction::UpdateProvisioningWallet { .. } => "UpdateProvisioningWallet",
+        ProgramInstruction::ActivateWallet { .. } => "ActivateWallet",
+        ProgramInstruction::InitWrapperBookUpdate { .. } => "InitWrapperBookUpdate",
+        ProgramInstruction::FinalizeWrapperBookUpdate { .. } => "FinalizeWrapperBookUpdate",
+        ProgramInstruction::InitAdapterWrapUnwrap { .. } => "InitAdapterWrapUnwrap",
+        ProgramInstruction::FinalizeAdapterWrapUnwrap { .. } => "FinalizeAdapterWrapUnwrap",
+        ProgramInstruction::MigratePendingApprovals { .. } => "MigratePendingApprovals",
+        #[cfg(feature = "demo")]
+        ProgramInstruction::InitDemoWallet { .. } => "InitDemoWallet",
+    }
+}
+
+fn key(n: u8) -> Pubkey {
+    Pubkey::new_from_array([n; 32])
+}
+
+fn hash(n: u8) -> Hash {
+    Hash::new_from_array([n; 32])
+}
+
+fn guid(n: u8) -> BalanceAccountGuidHash {
+    BalanceAccountGuidHash::new(&[n; 32])
+}
+
+fn entry(n: u8) -> (SlotId<AddressBookEntry>, AddressBookEntry) {
+    (
+        SlotId::new(usize::from(n)),
+        AddressBookEntry {
+            address: key(n),
+            name_hash: AddressBookEntryNameHash::new(&[n; 32]),
+        },
+    )
+}
+
+fn creation_params() -> BalanceAccountCreation {
+    BalanceAccountCreation {
+        slot_id: SlotId::new(1),
+        name_hash: BalanceAccountNameHash::new(&[4; 32]),
+        approvals_required_for_transfer: 2,
+        approval_timeout_for_transfer: Duration::from_secs(3600),
+        transfer_approvers: vec![SlotId::new(0), SlotId::new(1)],
+        signers_hash: hash(5),
+        whitelist_enabled: BooleanSetting::On,
+        dapps_enabled: BooleanSetting::Off,
+        address_book_slot_id: SlotId::new(2),
+    }
+}
+
+fn config_policy_update() -> WalletConfigPolicyUpdate {
+    WalletConfigPolicyUpdate {
+        approvals_required_for_config: 2,
+        approval_timeout_for_config: Duration::from_secs(3600),
+        config_approvers: vec![SlotId::new(0), SlotId::new(2)],
+        signers_hash: hash(6),
+        config_policy_timelock: Duration::from_secs(86400),
+    }
+}
+
+fn policy_update() -> BalanceAccountPolicyUpdate {
+    BalanceAccountPolicyUpdate {
+        approvals_required_for_transfer: 2,
+        approval_timeout_for_transfer: Duration::from_secs(7200),
+        transfer_approvers: vec![SlotId::new(1), SlotId::new(3)],
+        signers_hash: hash(7),
+        finalize_delay_policy: FinalizeDelayPolicy {
+            threshold: 1_000_000,
+            delay: Duration::from_secs(600),
+        },
+        mint_cap: 5_000,
+        approval_tiers: vec![ApprovalTier {
+            min_amount: 100_000,
+            approvals_required: 2,
+            approvers: Approvers::from_enabled_vec(vec![SlotId::new(1), SlotId::new(3)]),
+        }],
+    }
+}
+
+fn dapp_book_update() -> DAppBookUpdate {
+    DAppBookUpdate {
+        add_dapps: vec![entry(8)],
+        remove_dapps: vec![entry(9)],
+        dapp_expirations: vec![
+            (SlotId::new(8), Some(1_700_000_000)),
+            (SlotId::new(9), None),
+        ],
+        dapp_categories: vec![(
+            SlotId::new(8),
+            DAppCategories::DEX.union(DAppCategories::BRIDGE),
+        )],
+        disallowed_dapp_categories: vec![(guid(3), DAppCategories::LENDING)],
+        dapp_instruction_limits: vec![(SlotId::new(8), Some(4))],
+    }
+}
+
+fn address_book_update() -> AddressBookUpdate {
+    AddressBookUpdate {
+        add_address_book_entries: vec![entry(10)],
+        remove_address_book_entries: vec![entry(11)],
+        deprecate_address_book_entries: vec![entry(12)],
+        balance_account_whitelist_updates: vec![BalanceAccountWhitelistUpdate {
+            guid_hash: guid(3),
+            add_allowed_destinations: vec![SlotId::new(10)],
+            remove_allowed_destinations: vec![SlotId::new(11)],
+            destinations_hash: hash(13),
+        }],
+        address_book_risks: vec![
+            (
+                SlotId::new(10),
+                AddressBookEntryRisk::HighRisk {
+                    jurisdiction: JurisdictionCode::new(*b"KP").unwrap(),
+                },
+            ),
+            (SlotId::new(12), AddressBookEntryRisk::Standard),
+        ],
+        address_book_expirations: vec![
+            (SlotId::new(10), Some(1_700_000_000)),
+            (SlotId::new(12), None),
+        ],
+        address_book_entry_kinds: vec![(SlotId::new(10), AddressBookEntryKind::Wallet)],
+    }
+}
+
+fn whitelist_update() -> BalanceAccountAddressWhitelistUpdate {
+    BalanceAccountAddressWhitelistUpdate {
+        allowed_destinations: vec![SlotId::new(10), SlotId::new(12)],
+        destinations_hash: hash(14),
+    }
+}
+
+fn reward_splits() -> Vec<RewardSplit> {
+    vec![
+        RewardSplit {
+            account_guid_hash: guid(15),
+            percentage: 70,
+        },
+        RewardSplit {
+            account_guid_hash: guid(16),
+            percentage: 30,
+        },
+    ]
+}
+
+fn finalize_submitter_policy() -> FinalizeSubmitterPolicy {
+    FinalizeSubmitterPolicy {
+        config: FinalizeSubmitters::Approvers,
+        transfer: FinalizeSubmitters::Named,
+        dapp: FinalizeSubmitters::Anyone,
+        named_submitters: vec![key(17)],
+    }
+}
+
+fn price_oracle() -> PriceOracle {
+    PriceOracle {
+        feeds: vec![PriceFeed {
+            token_mint: key(18),
+            price_account: key(19),
+            decimals: 6,
+        }],
+        max_staleness: Duration::from_secs(60),
+        max_confidence_bps: 100,
+    }
+}
+
+fn full_rotation() -> FullRotation {
+    FullRotation {
+        assistant: key(20),
+        rent_return: key(21),
+        signer_keys: vec![(key(22), key(23))],
+    }
+}
+
+fn init_sign_data() -> ProgramInstruction {
+    ProgramInstruction::InitSignData {
+        fee_amount: 0,
+        fee_account_guid_hash: None,
+        data: vec![1, 2, 3],
+    }
+}
+
+/// One vector per variant (but the demo-only `InitDemoWallet`), with inits charging a fee to a
+/// fee account so that the optional fee fields are exercised.
+pub fn instruction_vectors() -> Vec<InstructionVector> {
+    let fee_amount = 1_000;
+    let fee_account_guid_hash = Some(guid(2));
+    let account_guid_hash = guid(1);
+    let signer = Signer::new(key(24));
+    let wrapper_book = WrapperBook {
+        entries: vec![WrapperEntry {
+            mint: key(44),
+            adapter_program: key(45),
+            adapter_accounts: [key(67), key(68), Pubkey::default(), Pubkey::default()],
+        }],
+    };
+    [
+        ProgramInstruction::InitWallet {
+            wallet_guid_hash: WalletGuidHash::new(&[25; 32]),
+            initial_config: InitialWalletConfig {
+                approvals_required_for_config: 2,
+                approval_timeout_for_config: Duration::from_secs(3600),
+                signers: vec![
+                    (SlotId::new(0), Signer::new(key(26))),
+                    (SlotId::new(1), Signer::new(key(27))),
+                ],
+                config_approvers: vec![SlotId::new(0), SlotId::new(1)],
+            },
+        },
+        ProgramInstruction::InitBalanceAccountCreation {
+            fee_amount,
+            fee_account_guid_hash,
+            account_guid_hash,
+            creation_params: creation_params(),
+        },
+        ProgramInstruction::FinalizeBalanceAccountCreation {
+            account_guid_hash,
+            creation_params: creation_params(),
+        },
+        ProgramInstruction::InitTransfer {
+            fee_amount,
+            fee_account_guid_hash,
+            account_guid_hash,
+            amount: 123_456,
+            destination_name_hash: AddressBookEntryNameHash::new(&[28; 32]),
+            destination_acknowledgement_required: true,
+            deduct_fee_from_amount: false,
+            allow_destination_ata_creation: true,
+            memo_hash: Some(hash(29)),
+            destination_proof: Some(vec![hash(30), hash(31)]),
+        },
+        ProgramInstruction::SetApprovalDisposition {
+            disposition: ApprovalDisposition::DENY,
+            params_hash: hash(32),
+            reason: Some(DispositionReason {
+                code: 3,
+                hash: Some(hash(32)),
+            }),
+        },
+        ProgramInstruction::FinalizeTransfer {
+            account_guid_hash,
+            amount: 123_456,
+            token_mint: key(33),
+            destination_acknowledgement_required: true,
+            deduct_fee_from_amount: false,
+            allow_destination_ata_creation: true,
+            memo: Some(b"invoice 42".to_vec()),
+        },
+        ProgramInstruction::InitWrapUnwrap {
+            fee_amount,
+            fee_account_guid_hash,
+            account_guid_hash,
+            amount: 5_000,
+            direction: WrapDirection::WRAP,
+        },
+        ProgramInstruction::FinalizeWrapUnwrap {
+            account_guid_hash,
+            amount: 5_000,
+            direction: WrapDirection::UNWRAP,
+        },
+        ProgramInstruction::InitUpdateSigner {
+            fee_amount,
+            fee_account_guid_hash,
+            slot_update_type: SlotUpdateType::SetIfEmpty,
+            slot_id: SlotId::new(3),
+            signer,
+        },
+        ProgramInstruction::FinalizeUpdateSigner {
+            slot_update_type: SlotUpdateType::Clear,
+            slot_id: SlotId::new(3),
+            signer,
+        },
+        ProgramInstruction::InitWalletConfigPolicyUpdate {
+            fee_amount,
+            fee_account_guid_hash,
+            update: config_policy_update(),
+        },
+        ProgramInstruction::FinalizeWalletConfigPolicyUpdate {
+            update: config_policy_update(),
+        },
+        ProgramInstruction::InitDAppTransaction {
+            fee_amount,
+            fee_account_guid_hash,
+            account_guid_hash,
+            dapp: entry(34).1,
+            instruction_count: 2,
+        },
+        ProgramInstruction::SupplyDAppTransactionInstructions {
+            instructions: vec![Instruction {
+                program_id: key(35),
+                accounts: vec![
+                    AccountMeta::new(key(36), true),
+                    AccountMeta::new_readonly(key(37), false),
+                ],
+                data: vec![9, 8, 7],
+            }],
+            starting_index: 1,
+        },
+        ProgramInstruction::FinalizeDAppTransaction {
+            account_guid_hash,
+            params_hash: hash(38),
+        },
+        ProgramInstruction::InitAccountSettingsUpdate {
+            fee_amount,
+            fee_account_guid_hash,
+            account_guid_hash,
+            whitelist_enabled: Some(BooleanSetting::On),
+            dapps_enabled: None,
+            archived: Some(BooleanSetting::Off),
+            system_owned_destinations_only: None,
+        },
+        ProgramInstruction::FinalizeAccountSettingsUpdate {
+            account_guid_hash,
+            whitelist_enabled: Some(BooleanSetting::On),
+            dapps_enabled: None,
+            archived: Some(BooleanSetting::Off),
+            system_owned_destinations_only: None,
+        },
+        ProgramInstruction::InitDAppBookUpdate {
+            fee_amount,
+            fee_account_guid_hash,
+            update: dapp_book_update(),
+        },
+        ProgramInstruction::FinalizeDAppBookUpdate {
+            update: dapp_book_update(),
+        },
+        ProgramInstruction::InitAddressBookUpdate {
+            fee_amount,
+            fee_account_guid_hash,
+            update: address_book_update(),
+        },
+        ProgramInstruction::FinalizeAddressBookUpdate {
+            update: address_book_update(),
+        },
+        ProgramInstruction::InitBalanceAccountNameUpdate {
+            fee_amount,
+            fee_account_guid_hash,
+            account_guid_hash,
+            account_name_hash: BalanceAccountNameHash::new(&[39; 32]),
+        },
+        ProgramInstruction::FinalizeBalanceAccountNameUpdate {
+            account_guid_hash,
+            account_name_hash: BalanceAccountNameHash::new(&[39; 32]),
+        },
+        ProgramInstruction::InitBalanceAccountPolicyUpdate {
+            fee_amount,
+            fee_account_guid_hash,
+            account_guid_hash,
+            update: policy_update(),
+        },
+        ProgramInstruction::FinalizeBalanceAccountPolicyUpdate {
+            account_guid_hash,
+            update: policy_update(),
+        },
+        ProgramInstruction::Migrate {},
+        ProgramInstruction::Cleanup {},
+        ProgramInstruction::InitBalanceAccountAddressWhitelistUpdate {
+            fee_amount,
+            fee_account_guid_hash,
+            account_guid_hash,
+            update: whitelist_update(),
+        },
+        ProgramInstruction::FinalizeBalanceAccountAddressWhitelistUpdate {
+            account_guid_hash,
+            update: whitelist_update(),
+        },
+        ProgramInstruction::InitSignData {
+            fee_amount,
+            fee_account_guid_hash,
+            data: b"sign me".to_vec(),
+        },
+        ProgramInstruction::FinalizeSignData {
+            data: b"sign me".to_vec(),
+        },
+        ProgramInstruction::AcknowledgeInternalTransfer {
+            account_guid_hash,
+            params_hash: hash(40),
+        },
+        ProgramInstruction::AddPendingAddressBookEntries {
+            entries: vec![entry(42)],
+        },
+        ProgramInstruction::RatifyPendingAddressBookEntries {
+            entries: vec![entry(42)],
+        },
+        ProgramInstruction::InitVoteRewardsWithdrawal {
+            fee_amount,
+            fee_account_guid_hash,
+            account_guid_hash,
+            amount: 888,
+        },
+        ProgramInstruction::FinalizeVoteRewardsWithdrawal {
+            account_guid_hash,
+            amount: 888,
+        },
+        ProgramInstruction::InitUpdateAssistant {
+            fee_amount,
+            fee_account_guid_hash,
+            slot_update_type: SlotUpdateType::SetIfEmpty,
+            slot_id: SlotId::new(1),
+            assistant: Assistant::new(key(43)),
+        },
+        ProgramInstruction::FinalizeUpdateAssistant {
+            slot_update_type: SlotUpdateType::SetIfEmpty,
+            slot_id: SlotId::new(1),
+            assistant: Assistant::new(key(43)),
+        },
+        ProgramInstruction::InitRewardSplitPolicyUpdate {
+            fee_amount,
+            fee_account_guid_hash,
+            account_guid_hash,
+            splits: reward_splits(),
+        },
+        ProgramInstruction::FinalizeRewardSplitPolicyUpdate {
+            account_guid_hash,
+            splits: reward_splits(),
+        },
+        ProgramInstruction::RemoveDeprecatedAddressBookEntries {
+            entries: vec![entry(12)],
+        },
+        ProgramInstruction::ActivateWalletConfigPolicy {},
+        ProgramInstruction::CancelTransfer {
+            account_guid_hash,
+            amount: 123_456,
+            token_mint: key(33),
+            destination_acknowledgement_required: false,
+            deduct_fee_from_amount: true,
+            allow_destination_ata_creation: false,
+            memo: None,
+        },
+        ProgramInstruction::InitFinalizeSubmitterPolicyUpdate {
+            fee_amount,
+            fee_account_guid_hash,
+            policy: finalize_submitter_policy(),
+        },
+        ProgramInstruction::FinalizeFinalizeSubmitterPolicyUpdate {
+            policy: finalize_submitter_policy(),
+        },
+        ProgramInstruction::InitMintTo {
+            fee_amount,
+            fee_account_guid_hash,
+            account_guid_hash,
+            amount: 999,
+            destination_name_hash: AddressBookEntryNameHash::new(&[44; 32]),
+        },
+        ProgramInstruction::FinalizeMintTo {
+            account_guid_hash,
+            amount: 999,
+        },
+        ProgramInstruction::InitSetMintAuthority {
+            fee_amount,
+            fee_account_guid_hash,
+            account_guid_hash,
+            authority_type: MintAuthorityType::MintTokens,
+            new_authority: Some(key(45)),
+        },
+        ProgramInstruction::FinalizeSetMintAuthority {
+            account_guid_hash,
+            authority_type: MintAuthorityType::FreezeAccount,
+            new_authority: None,
+        },
+        ProgramInstruction::SimulateWalletConfigPolicyUpdate {
+            update: config_policy_update(),
+        },
+        ProgramInstruction::SimulateBalanceAccountPolicyUpdate {
+            account_guid_hash,
+            update: policy_update(),
+        },
+        ProgramInstruction::InitPaymentChannel {
+            fee_amount,
+            fee_account_guid_hash,
+            account_guid_hash,
+            destination_name_hash: AddressBookEntryNameHash::new(&[46; 32]),
+            token_mint: key(47),
+            allowance: 10_000,
+            duration: Duration::from_secs(86400),
+        },
+        ProgramInstruction::FinalizePaymentChannel {
+            account_guid_hash,
+            token_mint: key(47),
+            allowance: 10_000,
+            duration: Duration::from_secs(86400),
+        },
+        ProgramInstruction::ChannelPayment { amount: 250 },
+        ProgramInstruction::InitOpBundle {},
+        ProgramInstruction::ApproveOpBundle {
+            bundle_hash: hash(48),
+        },
+        ProgramInstruction::ApplyOpBundleApproval {},
+        ProgramInstruction::CloseOpBundle {},
+        ProgramInstruction::InitLookupTableUpdate {
+            fee_amount,
+            fee_account_guid_hash,
+            lookup_table: None,
+            new_addresses: vec![key(49), key(50)],
+        },
+        ProgramInstruction::FinalizeLookupTableUpdate {
+            lookup_table: Some(key(51)),
+            new_addresses: vec![key(49), key(50)],
+            recent_slot: 4_242,
+        },
+        ProgramInstruction::InitAddressBookImport {
+            fee_amount,
+            fee_account_guid_hash,
+            entries: vec![(SlotId::new(3), SlotId::new(5))],
+            balance_accounts: vec![(guid(52), account_guid_hash)],
+        },
+        ProgramInstruction::FinalizeAddressBookImport {
+            import: AddressBookImport {
+                source_wallet_address: key(53),
+                add_address_book_entries: vec![entry(5)],
+                whitelist_enablements: vec![(account_guid_hash, vec![SlotId::new(5)])],
+            },
+        },
+        ProgramInstruction::InitCommittedTransfer {
+            fee_amount,
+            fee_account_guid_hash,
+            account_guid_hash,
+            amount: 31_337,
+            destination_commitment: hash(54),
+        },
+        ProgramInstruction::FinalizeCommittedTransfer {
+            account_guid_hash,
+            amount: 31_337,
+            destination_commitment: hash(54),
+            destination_name_hash: AddressBookEntryNameHash::new(&[55; 32]),
+            salt: hash(56),
+        },
+        ProgramInstruction::InitCompliancePolicyUpdate {
+            fee_amount,
+            fee_account_guid_hash,
+            policy: Some(CompliancePolicy {
+                service: key(57),
+                threshold: 1_000_000,
+            }),
+        },
+        ProgramInstruction::FinalizeCompliancePolicyUpdate { policy: None },
+        ProgramInstruction::ComplianceApprove {
+            params_hash: hash(58),
+        },
+        ProgramInstruction::GetAllowedDestinations {
+            account_guid_hash,
+            start_slot_id: SlotId::new(15),
+        },
+        ProgramInstruction::InitWithSignature {
+            init: init_sign_data().pack(),
+        },
+        ProgramInstruction::InitWalletReplicationUpdate {
+            fee_amount,
+            fee_account_guid_hash,
+            replication: Some(WalletReplication::Primary { standby: key(59) }),
+        },
+        ProgramInstruction::FinalizeWalletReplicationUpdate {
+            replication: Some(WalletReplication::Standby { primary: key(60) }),
+        },
+        ProgramInstruction::ApplyMirroredUpdate {
+            update: MirroredUpdate::Signer {
+                slot_update_type: SlotUpdateType::SetIfEmpty,
+                slot_id: SlotId::new(4),
+                signer,
+            },
+        },
+        ProgramInstruction::InitBalanceAccountDeletion {
+            fee_amount,
+            fee_account_guid_hash,
+            account_guid_hash,
+        },
+        ProgramInstruction::FinalizeBalanceAccountDeletion { account_guid_hash },
+        ProgramInstruction::InitSpendingLimitUpdate {
+            fee_amount,
+            fee_account_guid_hash,
+            account_guid_hash,
+            token_mint: key(61),
+            amount: 50_000,
+        },
+        ProgramInstruction::FinalizeSpendingLimitUpdate {
+            account_guid_hash,
+            token_mint: key(61),
+            amount: 50_000,
+        },
+        ProgramInstruction::Nudge { idle_slots: 150 },
+        ProgramInstruction::SuspendDApp { dapp: entry(34).1 },
+        ProgramInstruction::InitPriceOracleUpdate {
+            fee_amount,
+            fee_account_guid_hash,
+            oracle: Some(price_oracle()),
+        },
+        ProgramInstruction::FinalizePriceOracleUpdate {
+            oracle: Some(price_oracle()),
+        },
+        ProgramInstruction::RegisterTokenAccounts { account_guid_hash },
+        ProgramInstruction::InitMerkleAddressBookUpdate {
+            fee_amount,
+            fee_account_guid_hash,
+            account_guid_hash,
+            root: hash(62),
+        },
+        ProgramInstruction::FinalizeMerkleAddressBookUpdate {
+            account_guid_hash,
+            root: hash(62),
+        },
+        ProgramInstruction::CleanupExpiredOp {},
+        ProgramInstruction::InitWithComputeHint {
+            finalize_compute_units: 400_000,
+            init: init_sign_data().pack(),
+        },
+        ProgramInstruction::ExportWalletReadModel {},
+        ProgramInstruction::InitWalletFreeze {
+            fee_amount,
+            fee_account_guid_hash,
+            freeze: WalletFreeze {
+                frozen: true,
+                emergency_quorum: 2,
+            },
+        },
+        ProgramInstruction::FinalizeWalletFreeze {
+            freeze: WalletFreeze {
+                frozen: false,
+                emergency_quorum: 2,
+            },
+        },
+        ProgramInstruction::EmergencyFreeze {},
+        ProgramInstruction::InitRentReturnAllowlistUpdate {
+            fee_amount,
+            fee_account_guid_hash,
+            allowlist: RentReturnAllowlist {
+                entries: vec![key(63), key(64)],
+            },
+        },
+        ProgramInstruction::FinalizeRentReturnAllowlistUpdate {
+            allowlist: RentReturnAllowlist {
+                entries: vec![key(63), key(64)],
+            },
+        },
+        ProgramInstruction::InitFullRotation {
+            fee_amount,
+            fee_account_guid_hash,
+            rotation: full_rotation(),
+        },
+        ProgramInstruction::FinalizeFullRotation {
+            rotation: full_rotation(),
+        },
+        ProgramInstruction::UpdateProgramParameters {
+            parameters: ProgramParameters {
+                is_initialized: true,
+                max_signers: 12,
+                max_dapp_instructions: 16,
+                max_approval_timeout: Duration::from_secs(60 * 60 * 24 * 30),
+            },
+        },
+        ProgramInstruction::InitSignerActivationDelayUpdate {
+            fee_amount,
+            fee_account_guid_hash,
+            delay: Duration::from_secs(60 * 60 * 48),
+        },
+        ProgramInstruction::FinalizeSignerActivationDelayUpdate {
+            delay: Duration::from_secs(60 * 60 * 48),
+        },
+        ProgramInstruction::InitFeePolicyUpdate {
+            fee_amount,
+            fee_account_guid_hash,
+            fee_policy: FeePolicy {
+                max_fee_amount: Some(5_000_000),
+            },
+        },
+        ProgramInstruction::FinalizeFeePolicyUpdate {
+            fee_policy: FeePolicy {
+                max_fee_amount: Some(5_000_000),
+            },
+        },
+        ProgramInstruction::CleanupUncreatedBalanceAccount { account_guid_hash },
+        ProgramInstruction::InitStakeWeightGovernanceUpdate {
+            fee_amount,
+            fee_account_guid_hash,
+            governance: Some(StakeWeightGovernance {
+                mint: key(65),
+                approval_threshold_bps: 5_001,
+            }),
+        },
+        ProgramInstruction::FinalizeStakeWeightGovernanceUpdate { governance: None },
+        ProgramInstruction::InitWithStakeWeights {
+            stake_account_count: 2,
+            init: ProgramInstruction::InitWalletFreeze {
+                fee_amount,
+                fee_account_guid_hash,
+                freeze: WalletFreeze {
+                    frozen: true,
+                    emergency_quorum: 2,
+                },
+            }
+            .pack(),
+        },
+        ProgramInstruction::InitEventLog {},
+        ProgramInstruction::InitAddressBookRenewal {
+            fee_amount,
+            fee_account_guid_hash,
+            slot_id: entry(10).0,
+            entry: entry(10).1,
+            expires_at: 1_700_000_000,
+        },
+        ProgramInstruction::FinalizeAddressBookRenewal {
+            slot_id: entry(10).0,
+            entry: entry(10).1,
+            expires_at: 1_700_000_000,
+        },
+        ProgramInstruction::RegisterSessionKey {
+            session_key: key(30),
+            scope: SessionKeyScope {
+                categories: SessionKeyScope::TRANSFER | SessionKeyScope::DAPP,
+                max_transfer_amount: 1_000_000,
+            },
+            expires_at: 1_700_000_000,
+        },
+        ProgramInstruction::RevokeSessionKey {
+            session_key: key(30),
+        },
+        ProgramInstruction::SetApprovalDispositionBySignature {
+            disposition: ApprovalDisposition::APPROVE,
+            params_hash: hash(32),
+            expires_at: 1_700_000_000,
+        },
+        ProgramInstruction::InitWalletStats {},
+        ProgramInstruction::InitSPLTokenAccountsCreation {
+            fee_amount: 1_000,
+            fee_account_guid_hash: Some(guid(2)),
+            payer_account_guid_hash: guid(1),
+            account_guid_hashes: vec![guid(3), guid(4)],
+        },
+        ProgramInstruction::FinalizeSPLTokenAccountsCreation {
+            payer_account_guid_hash: guid(1),
+            account_guid_hashes: vec![guid(3), guid(4)],
+            batch_size: 1,
+        },
+        ProgramInstruction::ContinueSPLTokenAccountsCreation {
+            payer_account_guid_hash: guid(1),
+            account_guid_hashes: vec![guid(3), guid(4)],
+            batch_size: 1,
+        },
+        ProgramInstruction::VerifyTransfer {
+            account_guid_hash,
+            amount: 123_456,
+            token_mint: key(33),
+            destination_acknowledgement_required: true,
+            deduct_fee_from_amount: false,
+            allow_destination_ata_creation: true,
+            memo: Some(b"invoice 42".to_vec()),
+        },
+        ProgramInstruction::ExecuteTransfer {
+            account_guid_hash,
+            amount: 123_456,
+            token_mint: key(33),
+            destination_acknowledgement_required: true,
+            deduct_fee_from_amount: false,
+            allow_destination_ata_creation: true,
+            memo: Some(b"invoice 42".to_vec()),
+        },
+        ProgramInstruction::SimulateConfigUpdate {
+            update: ConfigUpdate::AddressBook(address_book_update()),
+        },
+        ProgramInstruction::InitApprovalGroupUpdate {
+            fee_amount,
+            fee_account_guid_hash,
+            approval_group: Some(ApprovalGroup {
+                group_key: key(66),
+                threshold: 5,
+                scope: SessionKeyScope {
+                    categories: SessionKeyScope::CONFIG | SessionKeyScope::TRANSFER,
+                    max_transfer_amount: 1_000_000,
+                },
+            }),
+        },
+        ProgramInstruction::FinalizeApprovalGroupUpdate {
+            approval_group: None,
+        },
+        ProgramInstruction::SubmitAggregatedApproval {
+            params_hash: hash(63),
+            expires_at: 1_700_000_000,
+        },
+        ProgramInstruction::InitEnableDepositAddresses {
+            fee_amount,
+            fee_account_guid_hash,
+            account_guid_hash: guid(1),
+            enabled: BooleanSetting::On,
+        },
+        ProgramInstruction::FinalizeEnableDepositAddresses {
+            account_guid_hash: guid(1),
+            enabled: BooleanSetting::Off,
+        },
+        ProgramInstruction::FinalizeWithDepositSweep {
+            deposit_address_indices: vec![0, 7, 254],
+            finalize: ProgramInstruction::FinalizeWrapUnwrap {
+                account_guid_hash: guid(1),
+                amount: 500_000,
+                direction: WrapDirection::WRAP,
+            }
+            .pack(),
+        },
+        ProgramInstruction::InitCompactWallet {
+            fee_amount,
+            fee_account_guid_hash,
+        },
+        ProgramInstruction::FinalizeCompactWallet {},
+        ProgramInstruction::InitInsuranceAttestorUpdate {
+            fee_amount,
+            fee_account_guid_hash,
+            attestor: key(40),
+        },
+        ProgramInstruction::FinalizeInsuranceAttestorUpdate { attestor: key(40) },
+        ProgramInstruction::SubmitInsuranceAttestation {
+            statement_hash: hash(64),
+            coverage_expires_at: 1_731_000_000,
+        },
+        ProgramInstruction::CheckInsuranceAttestation {},
+        ProgramInstruction::InitProvisioningWallet {
+            wallet_guid_hash: WalletGuidHash::new(&[41; 32]),
+            initial_config: InitialWalletConfig {
+                approvals_required_for_config: 1,
+                approval_timeout_for_config: Duration::from_secs(3600),
+                signers: vec![(SlotId::new(0), Signer::new(key(42)))],
+                config_approvers: vec![SlotId::new(0)],
+            },
+        },
+        ProgramInstruction::UpdateProvisioningWallet {
+            config: InitialWalletConfig {
+                approvals_required_for_config: 2,
+                approval_timeout_for_config: Duration::from_secs(7200),
+                signers: vec![
+                    (SlotId::new(0), Signer::new(key(42))),
+                    (SlotId::new(1), Signer::new(key(43))),
+                ],
+                config_approvers: vec![SlotId::new(0), SlotId::new(1)],
+            },
+        },
+        ProgramInstruction::ActivateWallet {},
+        ProgramInstruction::InitWrapperBookUpdate {
+            fee_amount,
+            fee_account_guid_hash,
+            wrapper_book: wrapper_book.clone(),
+        },
+        ProgramInstruction::FinalizeWrapperBookUpdate { wrapper_book },
+        ProgramInstruction::InitAdapterWrapUnwrap {
+            fee_amount,
+            fee_account_guid_hash,
+            account_guid_hash,
+            wrapper_mint: key(44),
+            amount: 500_000,
+            direction: WrapDirection::WRAP,
+        },
+        ProgramInstruction::FinalizeAdapterWrapUnwrap {
+            account_guid_hash,
+            wrapper_mint: key(44),
+            amount: 500_000,
+            direction: WrapDirection::UNWRAP,
+        },
+        ProgramInstruction::MigratePendingApprovals { approver_count: 2 },
+    ]
+    .into_iter()
+    .map(InstructionVector::new)
+    .collect()
+}
+
+/// The vectors as a JSON array of objects with the variant's name, its tag, its encoding in
+/// hex, and the values it was built from in their Rust debug form.
+pub fn to_json(vectors: &[InstructionVector]) -> String {
+    let mut json = String::from("[\n");
+    for (i, vector) in vectors.iter().enumerate() {
+        let data = vector.instruction.pack();
+        let hex = data.iter().fold(String::new(), |mut hex, byte| {
+            write!(hex, "{:02x}", byte).unwrap();
+            hex
+        });
+        let fields = format!("{:?}", vector.instruction)
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"");
+        write!(
+            json,
+            "  {{\n    \"name\": \"{}\",\n    \"tag\": {},\n    \"data\": \"{}\",\n    \"fields\": \"{}\"\n  }}{}\n",
+            vector.name,
+            data[0],
+            hex,
+            fields,
+            if i + 1 < vectors.len() { "," } else { "" }
+        )
+        .unwrap();
+    }
+    json.push_str("]\n");
+    json
+}
+
+#[cfg(test)]
+mod test {
+    use crate::instruction::ProgramInstruction;
+    use crate::test_vectors::{instruction_vectors, to_json};
+    use std::collections::HashSet;
+
+    const FIXTURE: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/instruction_vectors.json"
+    );
+
+    #[test]
+    fn test_vectors() {
+        let vectors = instruction_vectors();
+
+        let mut names = HashSet::new();
+        let mut tags = HashSet::new();
+        for vector in &vectors {
+            assert!(names.insert(vector.name), "{} repeated", vector.name);
+            let data = vector.instruction.pack();
+            assert!(tags.insert(data[0]), "{} shares its tag", vector.name);
+            assert_eq!(
+                ProgramInstruction::unpack(&data).unwrap().pack(),
+                data,
+                "{} doesn't round trip",
+                vector.name
+            );
+        }
+
+        let json = to_json(&vectors);
+        if std::env::var("STRIKE_UPDATE_TEST_VECTORS").is_ok() {
+            std::fs::write(FIXTURE, json).unwrap();
+        } else {
+            assert_eq!(
+                std::fs::read_to_string(FIXTURE).unwrap(),
+                json,
+                "the instruction encoding changed; regenerate the vectors if that's intended"
+            );
+        }
+    }
+}
@@ -2,10 +2,12 @@ use std::fmt::Debug;
 use std::iter::Map;
 use std::marker::PhantomData;
 use std::ops::Index;
+use std::time::Duration;
 
 use bitvec::prelude::*;
 use bitvec::slice::IterOnes;
 use itertools::Itertools;
+use solana_program::clock::DEFAULT_MS_PER_SLOT;
 use solana_program::instruction::{AccountMeta, Instruction};
 use solana_program::program_error::ProgramError;
 use solana_program::program_pack::{Pack, Sealed};
@@ -27,6 +29,18 @@ impl<A> SlotId<A> {
     }
 }
 
+/// The moves compacting slots makes, as pairs of the slot an item was in and the slot it is
+/// moved to, in ascending order. Each item moves to a lower slot that is empty by then.
+pub type SlotRemap<A> = Vec<(SlotId<A>, SlotId<A>)>;
+
+/// Moves the items kept by slot the way the remap moves the slots' items, the item of the slot
+/// moved to, which is empty, taking the place of the moved one.
+pub fn remap_slots<A, T>(items: &mut [T], remap: &SlotRemap<A>) {
+    for (from, to) in remap {
+        items.swap(from.value, to.value);
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Slots<A, const SIZE: usize> {
     array: Box<[Option<A>; SIZE]>,
@@ -139,6 +153,22 @@ impl<A: Copy + PartialEq + Ord, const SIZE: usize> Slots<A, SIZE> {
             .map(|(pos, value_opt)| (SlotId::new(pos), value_opt.unwrap()))
     }
 
+    /// The moves that pack the filled slots into the lowest ones, keeping them in order.
+    pub fn compaction_remap(&self) -> SlotRemap<A> {
+        self.array
+            .iter()
+            .enumerate()
+            .filter(|(_, value_opt)| value_opt.is_some())
+            .enumerate()
+            .filter(|(to, (from, _))| to != from)
+            .map(|(to, (from, _))| (SlotId::new(from), SlotId::new(to)))
+            .collect_vec()
+    }
+
+    pub fn remap(&mut self, remap: &SlotRemap<A>) {
+        remap_slots(self.array.as_mut_slice(), remap);
+    }
+
     pub fn filled_slots(&self) -> Vec<(SlotId<A>, A)> {
         self.array
             .iter()
@@ -178,12 +208,21 @@ impl<A: Pack + Copy + PartialEq + Ord, const SIZE: usize> Pack for Slots<A, SIZE
     }
 }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
 pub struct SlotFlags<A, const STORAGE_SIZE: usize> {
     bit_arr: BitArray<[u8; STORAGE_SIZE]>,
     item_type: PhantomData<A>,
 }
 
+// the bit array's own Debug output includes its address, so only the enabled slots are shown
+impl<A, const STORAGE_SIZE: usize> Debug for SlotFlags<A, STORAGE_SIZE> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("SlotFlags")
+            .field(&self.bit_arr.iter_ones().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
 pub type IterEnabledIds<'a, A> = Map<IterOnes<'a, u8, Lsb0>, fn(usize) -> SlotId<A>>;
 
 impl<A, const STORAGE_SIZE: usize> SlotFlags<A, STORAGE_SIZE> {
@@ -245,6 +284,12 @@ impl<A, const STORAGE_SIZE: usize> SlotFlags<A, STORAGE_SIZE> {
     pub fn as_bytes(&self) -> &[u8] {
         self.bit_arr.as_raw_slice()
     }
+
+    pub fn remap(&mut self, remap: &SlotRemap<A>) {
+        for (from, to) in remap {
+            self.bit_arr.swap(from.value, to.value);
+        }
+    }
 }
 
 pub trait GetSlotIds<A> {
@@ -257,6 +302,24 @@ impl<A> GetSlotIds<A> for Vec<(SlotId<A>, A)> {
     }
 }
 
+/// The duration in whole seconds, saturating at `i64::MAX` so it can be added to a unix
+/// timestamp.
+pub fn duration_secs(duration: Duration) -> i64 {
+    i64::try_from(duration.as_secs()).unwrap_or(i64::MAX)
+}
+
+/// The number of slots the duration spans at the nominal slot duration, rounded up and
+/// saturating at `u64::MAX` so it can be added to a slot height.
+pub fn duration_slots(duration: Duration) -> u64 {
+    let ms_per_slot = u128::from(DEFAULT_MS_PER_SLOT);
+    u64::try_from((duration.as_millis() + ms_per_slot - 1) / ms_per_slot).unwrap_or(u64::MAX)
+}
+
+/// Whether the items are strictly ascending by the key, that is sorted and free of duplicates.
+pub fn is_strictly_ascending_by_key<T, K: Ord, F: Fn(&T) -> K>(items: &[T], key: F) -> bool {
+    items.windows(2).all(|pair| key(&pair[0]) < key(&pair[1]))
+}
+
 pub fn unique_account_metas(
     instructions: &Vec<Instruction>,
     keys_to_skip: &Vec<Pubkey>,
@@ -292,3 +355,21 @@ pub fn unique_account_metas(
         .sorted_by(|a, b| a.pubkey.to_bytes().cmp(&b.pubkey.to_bytes()))
         .collect()
 }
+
+/// The associated token account of `owner` for the mint under the given token program, which
+/// for the SPL token program is the one `get_associated_token_address` gives.
+pub fn associated_token_address(
+    owner: &Pubkey,
+    token_mint: &Pubkey,
+    token_program_id: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            owner.as_ref(),
+            token_program_id.as_ref(),
+            token_mint.as_ref(),
+        ],
+        &spl_associated_token_account::id(),
+    )
+    .0
+}
@@ -1,6 +1,6 @@
 use solana_program::program_error::ProgramError;
 
-pub static VERSION: u32 = 1;
+pub static VERSION: u32 = 2;
 
 pub trait Versioned {
     fn version_from_slice(src: &[u8]) -> Result<u32, ProgramError>;
@@ -0,0 +1,150 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use std::time::Duration;
+
+use solana_program::clock::Clock;
+use solana_program::instruction::InstructionError::Custom;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::AddressBookUpdate;
+use strike_wallet::model::multisig_op::BooleanSetting;
+use strike_wallet::utils::SlotId;
+
+async fn renew_destination(context: &mut BalanceAccountTestContext, expires_at: i64) {
+    let mut test_context = context.to_test_context();
+    let rent_return = test_context.payer.pubkey();
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let (slot_id, entry) = (SlotId::new(0), context.allowed_destination);
+    init_multisig_op(
+        &mut test_context,
+        multisig_op_account,
+        init_address_book_renewal(
+            &context.program_id,
+            &context.wallet_account.pubkey(),
+            &multisig_op_pubkey,
+            &context.approvers[0].pubkey(),
+            &rent_return,
+            slot_id,
+            entry,
+            expires_at,
+        ),
+        &context.approvers[0],
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut test_context,
+        &multisig_op_pubkey,
+        vec![&context.approvers[0], &context.approvers[1]],
+    )
+    .await;
+    finalize_multisig_op(
+        &mut test_context,
+        multisig_op_pubkey,
+        finalize_address_book_renewal(
+            &context.program_id,
+            &context.wallet_account.pubkey(),
+            &multisig_op_pubkey,
+            &rent_return,
+            slot_id,
+            entry,
+            expires_at,
+        ),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_address_book_entry_expiry() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let initiator = Keypair::from_bytes(&context.approvers[2].to_bytes()).unwrap();
+    account_settings_update(
+        &mut context,
+        Some(BooleanSetting::On),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+    .await;
+    modify_balance_account_address_whitelist(
+        &mut context,
+        vec![(SlotId::new(0), context.allowed_destination)],
+        None,
+    )
+    .await;
+
+    let now = context
+        .pt_context
+        .banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap()
+        .unix_timestamp;
+    update_address_book(
+        &mut context,
+        AddressBookUpdate {
+            add_address_book_entries: vec![],
+            remove_address_book_entries: vec![],
+            deprecate_address_book_entries: vec![],
+            balance_account_whitelist_updates: vec![],
+            address_book_risks: vec![],
+            address_book_expirations: vec![(SlotId::new(0), Some(now + 3600))],
+            address_book_entry_kinds: vec![],
+        },
+        None,
+    )
+    .await;
+    assert_eq!(
+        get_wallet(
+            &mut context.pt_context.banks_client,
+            &context.wallet_account.pubkey()
+        )
+        .await
+        .address_book_expirations
+        .get(SlotId::new(0)),
+        Some(now + 3600)
+    );
+
+    let amount = context.rent.minimum_balance(0);
+    let (_, result) =
+        setup_transfer_test(&mut context, &initiator, &balance_account, None, amount).await;
+    result.unwrap();
+
+    // once the entry lapses, it can't be transferred to
+    advance_clock(&mut context.pt_context, Duration::from_secs(3600)).await;
+    let (_, result) =
+        setup_transfer_test(&mut context, &initiator, &balance_account, None, amount).await;
+    assert_instruction_error(
+        result,
+        1,
+        Custom(WalletError::AddressBookEntryExpired as u32),
+    );
+
+    // until it is renewed
+    renew_destination(&mut context, now + 7200).await;
+    assert_eq!(
+        get_wallet(
+            &mut context.pt_context.banks_client,
+            &context.wallet_account.pubkey()
+        )
+        .await
+        .address_book_expirations
+        .get(SlotId::new(0)),
+        Some(now + 7200)
+    );
+    let (_, result) =
+        setup_transfer_test(&mut context, &initiator, &balance_account, None, amount).await;
+    result.unwrap();
+}
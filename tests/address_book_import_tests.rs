@@ -0,0 +1,341 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use std::time::Duration;
+
+use solana_program::instruction::InstructionError::Custom;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_instruction;
+use solana_program_test::{tokio, BanksClientError};
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::{Transaction, TransactionError};
+use uuid::Uuid;
+
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::{AddressBookImport, BalanceAccountCreation, InitialWalletConfig};
+use strike_wallet::model::address_book::{AddressBookEntry, AddressBookEntryNameHash};
+use strike_wallet::model::balance_account::{BalanceAccountGuidHash, BalanceAccountNameHash};
+use strike_wallet::model::multisig_op::{BooleanSetting, MultisigOp};
+use strike_wallet::model::wallet::WalletGuidHash;
+use strike_wallet::utils::SlotId;
+
+/// Creates a second wallet with the same signers as the test wallet, along with a whitelisted
+/// balance account, returning the wallet and the balance account's guid hash.
+async fn setup_destination_wallet(
+    context: &mut BalanceAccountTestContext,
+) -> (Keypair, BalanceAccountGuidHash) {
+    let wallet_account = Keypair::new();
+    let assistant_account = Keypair::new();
+    common::utils::init_wallet(
+        &mut context.pt_context.banks_client,
+        &context.pt_context.payer,
+        context.pt_context.last_blockhash,
+        &context.program_id,
+        &wallet_account,
+        &assistant_account,
+        WalletGuidHash::new(&hash_of(Uuid::new_v4().as_bytes())),
+        InitialWalletConfig {
+            approvals_required_for_config: 2,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: context
+                .approvers
+                .iter()
+                .enumerate()
+                .map(|(i, approver)| (SlotId::new(i), approver.pubkey_as_signer()))
+                .collect(),
+            config_approvers: vec![SlotId::new(0), SlotId::new(1)],
+        },
+    )
+    .await
+    .unwrap();
+
+    let mut test_context = context.to_test_context();
+    let mut wallet_context = WalletTestContext {
+        payer: Keypair::from_bytes(&test_context.payer.to_bytes()).unwrap(),
+        program_id: context.program_id,
+        banks_client: test_context.banks_client.clone(),
+        rent: context.rent,
+        wallet_account: Keypair::from_bytes(&wallet_account.to_bytes()).unwrap(),
+        assistant_account,
+        recent_blockhash: test_context.recent_blockhash,
+        wallet_guid_hash: WalletGuidHash::zero(),
+    };
+    let balance_account_guid_hash = random_balance_account_guid_hash();
+    let creation_params = BalanceAccountCreation {
+        slot_id: SlotId::new(0),
+        name_hash: BalanceAccountNameHash::new(&hash_of(b"Imported Account Name")),
+        approvals_required_for_transfer: 1,
+        approval_timeout_for_transfer: Duration::from_secs(120),
+        transfer_approvers: vec![SlotId::new(0)],
+        signers_hash: hash_signers(&vec![context.approvers[0].pubkey_as_signer()]),
+        whitelist_enabled: BooleanSetting::On,
+        dapps_enabled: BooleanSetting::Off,
+        address_book_slot_id: SlotId::new(40),
+    };
+    let initiator = Keypair::from_bytes(&context.approvers[2].to_bytes()).unwrap();
+    let multisig_op_account = init_balance_account_creation(
+        &mut wallet_context,
+        &initiator,
+        balance_account_guid_hash,
+        creation_params.clone(),
+    )
+    .await
+    .unwrap();
+    let rent_return = test_context.payer.pubkey();
+    approve_n_of_n_multisig_op(
+        &mut test_context,
+        &multisig_op_account,
+        vec![&context.approvers[0], &context.approvers[1]],
+    )
+    .await;
+    finalize_multisig_op(
+        &mut test_context,
+        multisig_op_account,
+        common::instructions::finalize_balance_account_creation(
+            &context.program_id,
+            &wallet_account.pubkey(),
+            &multisig_op_account,
+            &rent_return,
+            balance_account_guid_hash,
+            creation_params,
+            None,
+        ),
+    )
+    .await;
+
+    (wallet_account, balance_account_guid_hash)
+}
+
+async fn init_import(
+    context: &mut BalanceAccountTestContext,
+    wallet_account: &Pubkey,
+    source_approver: &Keypair,
+    entries: Vec<(SlotId<AddressBookEntry>, SlotId<AddressBookEntry>)>,
+    balance_accounts: Vec<(BalanceAccountGuidHash, BalanceAccountGuidHash)>,
+) -> Result<Pubkey, BanksClientError> {
+    let multisig_op_account = Keypair::new();
+    let initiator = &context.approvers[2];
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                system_instruction::create_account(
+                    &context.pt_context.payer.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    context.rent.minimum_balance(MultisigOp::LEN),
+                    MultisigOp::LEN as u64,
+                    &context.program_id,
+                ),
+                init_address_book_import(
+                    &context.program_id,
+                    wallet_account,
+                    &multisig_op_account.pubkey(),
+                    &context.wallet_account.pubkey(),
+                    &source_approver.pubkey(),
+                    &initiator.pubkey(),
+                    &context.pt_context.payer.pubkey(),
+                    entries,
+                    balance_accounts,
+                ),
+            ],
+            Some(&context.pt_context.payer.pubkey()),
+            &[
+                &context.pt_context.payer,
+                &multisig_op_account,
+                source_approver,
+                initiator,
+            ],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .map(|_| multisig_op_account.pubkey())
+}
+
+#[tokio::test]
+async fn test_address_book_import() {
+    let (mut context, _) = setup_balance_account_tests_and_finalize(Some(80_000)).await;
+    let source_entries = get_wallet(
+        &mut context.pt_context.banks_client,
+        &context.wallet_account.pubkey(),
+    )
+    .await
+    .address_book
+    .filled_slots();
+    account_settings_update(
+        &mut context,
+        Some(BooleanSetting::On),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+    .await;
+    let allowed_destination = (SlotId::new(0), context.allowed_destination);
+    modify_balance_account_address_whitelist(&mut context, vec![allowed_destination], None).await;
+
+    let (wallet_account, balance_account_guid_hash) = setup_destination_wallet(&mut context).await;
+    let source_approver = Keypair::from_bytes(&context.approvers[0].to_bytes()).unwrap();
+    let entries = vec![
+        (SlotId::new(0), SlotId::new(10)),
+        (SlotId::new(1), SlotId::new(11)),
+    ];
+    let balance_accounts = vec![(context.balance_account_guid_hash, balance_account_guid_hash)];
+    let multisig_op_account = init_import(
+        &mut context,
+        &wallet_account.pubkey(),
+        &source_approver,
+        entries,
+        balance_accounts,
+    )
+    .await
+    .unwrap();
+
+    let mut test_context = context.to_test_context();
+    let rent_return = test_context.payer.pubkey();
+    approve_n_of_n_multisig_op(
+        &mut test_context,
+        &multisig_op_account,
+        vec![&context.approvers[0], &context.approvers[1]],
+    )
+    .await;
+    let import = AddressBookImport {
+        source_wallet_address: context.wallet_account.pubkey(),
+        add_address_book_entries: vec![
+            (SlotId::new(10), source_entries[0].1),
+            (SlotId::new(11), source_entries[1].1),
+        ],
+        whitelist_enablements: vec![(balance_account_guid_hash, vec![SlotId::new(10)])],
+    };
+    finalize_multisig_op(
+        &mut test_context,
+        multisig_op_account,
+        finalize_address_book_import(
+            &context.program_id,
+            &wallet_account.pubkey(),
+            &multisig_op_account,
+            &rent_return,
+            import,
+        ),
+    )
+    .await;
+
+    let wallet = get_wallet(
+        &mut context.pt_context.banks_client,
+        &wallet_account.pubkey(),
+    )
+    .await;
+    assert_eq!(
+        wallet.address_book[SlotId::new(10)],
+        Some(source_entries[0].1)
+    );
+    assert_eq!(
+        wallet.address_book[SlotId::new(11)],
+        Some(source_entries[1].1)
+    );
+    assert_eq!(wallet.pending_address_book_entries.count_enabled(), 0);
+    let balance_account = wallet
+        .get_balance_account(&balance_account_guid_hash)
+        .unwrap();
+    assert_eq!(
+        wallet.get_allowed_destinations(&balance_account),
+        vec![context.allowed_destination]
+    );
+}
+
+#[tokio::test]
+async fn test_address_book_import_failures() {
+    let (mut context, _) = setup_balance_account_tests_and_finalize(Some(80_000)).await;
+    let (wallet_account, _) = setup_destination_wallet(&mut context).await;
+
+    // the source approver has to approve config changes of the source wallet
+    let approver = Keypair::from_bytes(&context.approvers[2].to_bytes()).unwrap();
+    assert_eq!(
+        init_import(
+            &mut context,
+            &wallet_account.pubkey(),
+            &approver,
+            vec![(SlotId::new(0), SlotId::new(10))],
+            vec![],
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::InvalidApprover as u32)),
+    );
+
+    let approver = Keypair::from_bytes(&context.approvers[0].to_bytes()).unwrap();
+
+    // entries must be present in the source wallet
+    assert_eq!(
+        init_import(
+            &mut context,
+            &wallet_account.pubkey(),
+            &approver,
+            vec![(SlotId::new(5), SlotId::new(10))],
+            vec![],
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::InvalidAddressBookImport as u32)),
+    );
+
+    // and can't be imported into a taken slot
+    assert_eq!(
+        init_import(
+            &mut context,
+            &wallet_account.pubkey(),
+            &approver,
+            vec![(SlotId::new(0), SlotId::new(40))],
+            vec![],
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::SlotCannotBeInserted as u32)),
+    );
+
+    // pending entries can't be imported
+    let pending_entry = AddressBookEntry {
+        address: Keypair::new().pubkey(),
+        name_hash: AddressBookEntryNameHash::new(&hash_of(b"Pending Name")),
+    };
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[add_pending_address_book_entries(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                &context.assistant_account.pubkey(),
+                vec![(SlotId::new(2), pending_entry)],
+            )],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer, &context.assistant_account],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(
+        init_import(
+            &mut context,
+            &wallet_account.pubkey(),
+            &approver,
+            vec![(SlotId::new(2), SlotId::new(10))],
+            vec![],
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::InvalidAddressBookImport as u32)),
+    );
+}
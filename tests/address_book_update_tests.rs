@@ -6,15 +6,25 @@ pub use common::instructions::*;
 pub use common::utils::*;
 use std::option::Option::None;
 
+use itertools::Itertools;
+use solana_program::clock::Clock;
+use solana_program::instruction::Instruction;
 use solana_program::instruction::InstructionError::Custom;
-use solana_program_test::tokio;
+use solana_program::{system_instruction, system_program};
+use solana_program_test::{tokio, BanksClientError};
 use solana_sdk::signature::Keypair;
 use solana_sdk::signer::Signer;
+use solana_sdk::transaction::Transaction;
 use strike_wallet::error::WalletError;
 use strike_wallet::instruction::AddressBookUpdate;
+use strike_wallet::model::address_book::{
+    AddressBookEntry, AddressBookEntryKind, AddressBookEntryNameHash, AddressBookEntryUsage,
+};
 use strike_wallet::model::multisig_op::{
     ApprovalDisposition, ApprovalDispositionRecord, BooleanSetting, OperationDisposition,
 };
+use strike_wallet::model::wallet::Wallet;
+use strike_wallet::utils::{associated_token_address, SlotId};
 
 #[tokio::test]
 async fn test_address_book_update() {
@@ -48,6 +58,8 @@ async fn test_address_book_update() {
         None,
         None,
         None,
+        None,
+        None,
     )
     .await;
 
@@ -131,6 +143,8 @@ async fn test_address_book_failures() {
         None,
         None,
         None,
+        None,
+        None,
     )
     .await;
     modify_balance_account_address_whitelist(
@@ -213,7 +227,11 @@ async fn test_address_book_update_initiator_approval() {
         AddressBookUpdate {
             add_address_book_entries: vec![],
             remove_address_book_entries: wallet.address_book.filled_slots(),
+            deprecate_address_book_entries: vec![],
             balance_account_whitelist_updates: vec![],
+            address_book_risks: vec![],
+            address_book_expirations: vec![],
+            address_book_entry_kinds: vec![],
         },
     )
     .await
@@ -226,10 +244,14 @@ async fn test_address_book_update_initiator_approval() {
             ApprovalDispositionRecord {
                 approver: context.approvers[0].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                reason: None,
+                stake_weight: 0,
             },
             ApprovalDispositionRecord {
                 approver: context.approvers[1].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                reason: None,
+                stake_weight: 0,
             },
         ],
         OperationDisposition::NONE,
@@ -242,7 +264,11 @@ async fn test_address_book_update_initiator_approval() {
         AddressBookUpdate {
             add_address_book_entries: vec![],
             remove_address_book_entries: wallet.address_book.filled_slots(),
+            deprecate_address_book_entries: vec![],
             balance_account_whitelist_updates: vec![],
+            address_book_risks: vec![],
+            address_book_expirations: vec![],
+            address_book_entry_kinds: vec![],
         },
     )
     .await
@@ -255,12 +281,447 @@ async fn test_address_book_update_initiator_approval() {
             ApprovalDispositionRecord {
                 approver: context.approvers[0].pubkey(),
                 disposition: ApprovalDisposition::APPROVE,
+                reason: None,
+                stake_weight: 0,
             },
             ApprovalDispositionRecord {
                 approver: context.approvers[1].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                reason: None,
+                stake_weight: 0,
             },
         ],
         OperationDisposition::NONE,
     );
 }
+
+async fn process_pending_address_book_instruction(
+    context: &mut BalanceAccountTestContext,
+    instruction: Instruction,
+    signer: &Keypair,
+) -> Result<(), BanksClientError> {
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer, signer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+}
+
+async fn add_pending_entries(
+    context: &mut BalanceAccountTestContext,
+    signer: &Keypair,
+    entries: Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+) -> Result<(), BanksClientError> {
+    let instruction = add_pending_address_book_entries(
+        &context.program_id,
+        &context.wallet_account.pubkey(),
+        &signer.pubkey(),
+        entries,
+    );
+    process_pending_address_book_instruction(context, instruction, signer).await
+}
+
+async fn ratify_pending_entries(
+    context: &mut BalanceAccountTestContext,
+    signer: &Keypair,
+    entries: Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+) -> Result<(), BanksClientError> {
+    let instruction = ratify_pending_address_book_entries(
+        &context.program_id,
+        &context.wallet_account.pubkey(),
+        &signer.pubkey(),
+        entries,
+    );
+    process_pending_address_book_instruction(context, instruction, signer).await
+}
+
+fn new_address_book_entries(
+    first_slot: usize,
+    count: usize,
+) -> Vec<(SlotId<AddressBookEntry>, AddressBookEntry)> {
+    (first_slot..first_slot + count)
+        .map(|slot| {
+            (
+                SlotId::new(slot),
+                AddressBookEntry {
+                    address: Keypair::new().pubkey(),
+                    name_hash: AddressBookEntryNameHash::new(&hash_of(&slot.to_le_bytes())),
+                },
+            )
+        })
+        .collect_vec()
+}
+
+#[tokio::test]
+async fn test_pending_address_book_entries() {
+    let (mut context, _) = setup_balance_account_tests_and_finalize(Some(40000)).await;
+    account_settings_update(
+        &mut context,
+        Some(BooleanSetting::On),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+    .await;
+
+    let assistant = Keypair::from_base58_string(&context.assistant_account.to_base58_string());
+    let config_approver = Keypair::from_base58_string(&context.approvers[0].to_base58_string());
+    let non_config_approver = Keypair::from_base58_string(&context.approvers[2].to_base58_string());
+    let entries = new_address_book_entries(10, 2);
+
+    // only the assistant can add pending entries
+    assert_instruction_error(
+        add_pending_entries(&mut context, &config_approver, entries.clone()).await,
+        0,
+        Custom(WalletError::InvalidApprover as u32),
+    );
+    add_pending_entries(&mut context, &assistant, entries.clone())
+        .await
+        .unwrap();
+
+    let wallet = get_wallet(
+        &mut context.pt_context.banks_client,
+        &context.wallet_account.pubkey(),
+    )
+    .await;
+    assert!(wallet.address_book.contains(&entries));
+    assert_eq!(wallet.pending_address_book_entries.count_enabled(), 2);
+
+    // pending entries can't be whitelisted
+    modify_balance_account_address_whitelist(
+        &mut context,
+        vec![entries[0]],
+        Some(Custom(WalletError::AddressBookEntryPending as u32)),
+    )
+    .await;
+
+    // only a config approver can ratify pending entries
+    assert_instruction_error(
+        ratify_pending_entries(&mut context, &non_config_approver, vec![entries[0]]).await,
+        0,
+        Custom(WalletError::InvalidApprover as u32),
+    );
+    ratify_pending_entries(&mut context, &config_approver, vec![entries[0]])
+        .await
+        .unwrap();
+
+    // an entry which is not pending can't be ratified
+    let other_config_approver =
+        Keypair::from_base58_string(&context.approvers[1].to_base58_string());
+    assert_instruction_error(
+        ratify_pending_entries(&mut context, &other_config_approver, vec![entries[0]]).await,
+        0,
+        Custom(WalletError::UnknownAddressBookEntry as u32),
+    );
+
+    // a ratified entry can be whitelisted
+    modify_balance_account_address_whitelist(&mut context, vec![entries[0]], None).await;
+
+    // the number of pending entries is capped
+    assert_instruction_error(
+        add_pending_entries(
+            &mut context,
+            &assistant,
+            new_address_book_entries(20, Wallet::MAX_PENDING_ADDRESS_BOOK_ENTRIES),
+        )
+        .await,
+        0,
+        Custom(WalletError::TooManyPendingAddressBookEntries as u32),
+    );
+    let more_entries = new_address_book_entries(
+        20,
+        Wallet::MAX_PENDING_ADDRESS_BOOK_ENTRIES_PER_WINDOW - entries.len(),
+    );
+    add_pending_entries(&mut context, &assistant, more_entries.clone())
+        .await
+        .unwrap();
+
+    // as is the number of entries staged per window, even though there is pending capacity left
+    assert_instruction_error(
+        add_pending_entries(&mut context, &assistant, new_address_book_entries(40, 1)).await,
+        0,
+        Custom(WalletError::PendingAddressBookRateLimitExceeded as u32),
+    );
+
+    // pending entries can't replace existing entries
+    assert_instruction_error(
+        add_pending_entries(
+            &mut context,
+            &assistant,
+            vec![(entries[0].0, new_address_book_entries(0, 1)[0].1)],
+        )
+        .await,
+        0,
+        Custom(WalletError::SlotCannotBeInserted as u32),
+    );
+
+    // ratify the remaining entries in a batch
+    let mut remaining_entries = vec![entries[1]];
+    remaining_entries.extend(more_entries);
+    ratify_pending_entries(&mut context, &config_approver, remaining_entries.clone())
+        .await
+        .unwrap();
+
+    let wallet = get_wallet(
+        &mut context.pt_context.banks_client,
+        &context.wallet_account.pubkey(),
+    )
+    .await;
+    assert!(wallet.address_book.contains(&remaining_entries));
+    assert_eq!(wallet.pending_address_book_entries.count_enabled(), 0);
+}
+
+async fn remove_deprecated_entries(
+    context: &mut BalanceAccountTestContext,
+    signer: &Keypair,
+    entries: Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
+) -> Result<(), BanksClientError> {
+    let instruction = remove_deprecated_address_book_entries(
+        &context.program_id,
+        &context.wallet_account.pubkey(),
+        &signer.pubkey(),
+        entries,
+    );
+    process_pending_address_book_instruction(context, instruction, signer).await
+}
+
+#[tokio::test]
+async fn test_address_book_entry_deprecation() {
+    let (mut context, balance_account) =
+        setup_balance_account_tests_and_finalize(Some(40000)).await;
+    account_settings_update(
+        &mut context,
+        Some(BooleanSetting::On),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+    .await;
+    let entry = (SlotId::new(0), context.allowed_destination);
+    modify_balance_account_address_whitelist(&mut context, vec![entry], None).await;
+
+    let initiator = Keypair::from_base58_string(&context.approvers[2].to_base58_string());
+    let amount = context.rent.minimum_balance(0);
+    let (pending_transfer, result) =
+        setup_transfer_test(&mut context, &initiator, &balance_account, None, amount).await;
+    result.unwrap();
+    let wallet = get_wallet(
+        &mut context.pt_context.banks_client,
+        &context.wallet_account.pubkey(),
+    )
+    .await;
+    let clock = context
+        .pt_context
+        .banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap();
+    assert_eq!(
+        wallet.address_book_usage.get(entry.0).last_used_slot,
+        clock.slot
+    );
+
+    update_address_book(
+        &mut context,
+        AddressBookUpdate {
+            add_address_book_entries: vec![],
+            remove_address_book_entries: vec![],
+            deprecate_address_book_entries: vec![entry],
+            balance_account_whitelist_updates: vec![],
+            address_book_risks: vec![],
+            address_book_expirations: vec![],
+            address_book_entry_kinds: vec![],
+        },
+        None,
+    )
+    .await;
+
+    // a deprecated entry can't be used by new transfers, or be whitelisted again
+    let (_, result) =
+        setup_transfer_test(&mut context, &initiator, &balance_account, None, amount).await;
+    assert_instruction_error(
+        result,
+        1,
+        Custom(WalletError::AddressBookEntryDeprecated as u32),
+    );
+    modify_balance_account_address_whitelist(
+        &mut context,
+        vec![entry],
+        Some(Custom(WalletError::AddressBookEntryDeprecated as u32)),
+    )
+    .await;
+
+    // but the transfer initiated before the deprecation still goes through
+    approve_or_deny_n_of_n_multisig_op(
+        &mut context.pt_context.banks_client,
+        &context.program_id,
+        &pending_transfer.pubkey(),
+        vec![&context.approvers[0], &context.approvers[1]],
+        &context.pt_context.payer,
+        context.pt_context.last_blockhash,
+        ApprovalDisposition::APPROVE,
+        OperationDisposition::APPROVED,
+    )
+    .await;
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                system_instruction::transfer(
+                    &context.pt_context.payer.pubkey(),
+                    &balance_account,
+                    amount * 2,
+                ),
+                finalize_transfer(
+                    &context.program_id,
+                    &pending_transfer.pubkey(),
+                    &context.wallet_account.pubkey(),
+                    &balance_account,
+                    &context.destination.pubkey(),
+                    &context.pt_context.payer.pubkey(),
+                    context.balance_account_guid_hash,
+                    amount,
+                    &system_program::id(),
+                    None,
+                    None,
+                    None,
+                ),
+            ],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .get_balance(context.destination.pubkey())
+            .await
+            .unwrap(),
+        amount
+    );
+
+    // the entry can be removed once transfers initiated before the deprecation have expired,
+    // unlike entries which were never deprecated
+    let assistant = Keypair::from_base58_string(&context.assistant_account.to_base58_string());
+    let other_entry = (SlotId::new(1), wallet.address_book[SlotId::new(1)].unwrap());
+    assert_instruction_error(
+        remove_deprecated_entries(&mut context, &assistant, vec![entry, other_entry]).await,
+        0,
+        Custom(WalletError::AddressBookEntryNotRemovable as u32),
+    );
+    let mut clock = context
+        .pt_context
+        .banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap();
+    clock.unix_timestamp += 120;
+    context.pt_context.set_sysvar(&clock);
+    assert_instruction_error(
+        remove_deprecated_entries(&mut context, &initiator, vec![entry]).await,
+        0,
+        Custom(WalletError::InvalidApprover as u32),
+    );
+    assert_instruction_error(
+        remove_deprecated_entries(&mut context, &assistant, vec![other_entry]).await,
+        0,
+        Custom(WalletError::AddressBookEntryNotRemovable as u32),
+    );
+    remove_deprecated_entries(&mut context, &assistant, vec![entry])
+        .await
+        .unwrap();
+
+    let wallet = get_wallet(
+        &mut context.pt_context.banks_client,
+        &context.wallet_account.pubkey(),
+    )
+    .await;
+    assert_eq!(wallet.address_book[entry.0], None);
+    assert_eq!(
+        wallet.address_book_usage.get(entry.0),
+        AddressBookEntryUsage::default()
+    );
+    let balance_account = wallet
+        .get_balance_account(&context.balance_account_guid_hash)
+        .unwrap();
+    assert!(!balance_account.allowed_destinations.is_enabled(&entry.0));
+}
+
+#[tokio::test]
+async fn test_address_book_update_entry_kinds() {
+    let (mut context, _) = setup_balance_account_tests_and_finalize(Some(64000)).await;
+    let initiator_account = Keypair::from_base58_string(&context.approvers[2].to_base58_string());
+    let owner = Keypair::new().pubkey();
+    let token_mint = Keypair::new().pubkey();
+    let token_account_kind = AddressBookEntryKind::TokenAccount { owner, token_mint };
+    let slot_id = SlotId::new(40);
+    let update = |address, kind| AddressBookUpdate {
+        add_address_book_entries: vec![(
+            slot_id,
+            AddressBookEntry {
+                address,
+                name_hash: AddressBookEntryNameHash::new(&[40; 32]),
+            },
+        )],
+        remove_address_book_entries: vec![],
+        deprecate_address_book_entries: vec![],
+        balance_account_whitelist_updates: vec![],
+        address_book_risks: vec![],
+        address_book_expirations: vec![],
+        address_book_entry_kinds: vec![(slot_id, kind)],
+    };
+
+    // the owner's own address isn't its token account
+    assert_instruction_error(
+        init_address_book_update(
+            &mut context,
+            &initiator_account,
+            update(owner, token_account_kind),
+        )
+        .await,
+        1,
+        Custom(WalletError::AddressBookEntryKindMismatch as u32),
+    );
+
+    // an account owned by a program can't be declared a wallet
+    let wallet_account = context.wallet_account.pubkey();
+    assert_instruction_error(
+        init_address_book_update(
+            &mut context,
+            &initiator_account,
+            update(wallet_account, AddressBookEntryKind::Wallet),
+        )
+        .await,
+        1,
+        Custom(WalletError::AddressBookEntryKindMismatch as u32),
+    );
+
+    // a token account which doesn't exist yet is accepted under either token program
+    for token_program_id in [spl_token::id(), spl_token_2022::id()] {
+        let address = associated_token_address(&owner, &token_mint, &token_program_id);
+        init_address_book_update(
+            &mut context,
+            &initiator_account,
+            update(address, token_account_kind),
+        )
+        .await
+        .unwrap();
+    }
+}
@@ -0,0 +1,117 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use solana_program::clock::Clock;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::Transaction;
+use strike_wallet::client::approval_bot::{pending_dispositions, ApprovalPolicy};
+use strike_wallet::model::multisig_op::{ApprovalDisposition, MultisigOpCode};
+
+#[tokio::test]
+async fn test_approval_bot_applies_its_policy() {
+    let (mut context, balance_account) =
+        setup_balance_account_tests_and_finalize(Some(60_000)).await;
+    let initiator = Keypair::from_bytes(&context.approvers[2].to_bytes()).unwrap();
+    let (small_transfer, result) =
+        setup_transfer_test(&mut context, &initiator, &balance_account, None, 123).await;
+    result.unwrap();
+    let (large_transfer, result) =
+        setup_transfer_test(&mut context, &initiator, &balance_account, None, 5000).await;
+    result.unwrap();
+
+    let bot = Keypair::from_bytes(&context.approvers[0].to_bytes()).unwrap();
+    let policy = ApprovalPolicy {
+        wallets: vec![context.wallet_account.pubkey()],
+        approved_op_codes: vec![MultisigOpCode::Transfer],
+        max_transfer_amount: Some(1000),
+        deny_unmatched: true,
+    };
+    let mut accounts = Vec::new();
+    for address in [
+        context.wallet_account.pubkey(),
+        small_transfer.pubkey(),
+        large_transfer.pubkey(),
+    ] {
+        let account = context
+            .pt_context
+            .banks_client
+            .get_account(address)
+            .await
+            .unwrap()
+            .unwrap();
+        accounts.push((address, account.data));
+    }
+    let now = context
+        .pt_context
+        .banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap()
+        .unix_timestamp;
+    let program_id = context.program_id;
+    let pending = |accounts: &Vec<(_, Vec<u8>)>| {
+        pending_dispositions(
+            &program_id,
+            &bot.pubkey(),
+            &policy,
+            accounts
+                .iter()
+                .map(|(address, data)| (*address, data.as_slice())),
+            now,
+        )
+    };
+
+    // one disposition per op of the wallet, and none for the wallet account itself
+    let instructions = pending(&accounts);
+    assert_eq!(instructions.len(), 2);
+    for instruction in instructions {
+        context
+            .pt_context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[instruction],
+                Some(&context.pt_context.payer.pubkey()),
+                &[&context.pt_context.payer, &bot],
+                context.pt_context.last_blockhash,
+            ))
+            .await
+            .unwrap();
+    }
+
+    for (multisig_op_account, disposition) in [
+        (small_transfer.pubkey(), ApprovalDisposition::APPROVE),
+        (large_transfer.pubkey(), ApprovalDisposition::DENY),
+    ] {
+        let multisig_op =
+            get_multisig_op_data(&mut context.pt_context.banks_client, multisig_op_account).await;
+        assert_eq!(
+            multisig_op
+                .disposition_records
+                .iter()
+                .find(|record| record.approver == bot.pubkey())
+                .unwrap()
+                .disposition,
+            disposition
+        );
+    }
+
+    // having registered its dispositions, the bot owes the ops nothing more
+    let mut accounts = Vec::new();
+    for address in [small_transfer.pubkey(), large_transfer.pubkey()] {
+        if let Some(account) = context
+            .pt_context
+            .banks_client
+            .get_account(address)
+            .await
+            .unwrap()
+        {
+            accounts.push((address, account.data));
+        }
+    }
+    assert!(pending(&accounts).is_empty());
+}
@@ -0,0 +1,127 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use solana_program::clock::Clock;
+use solana_program::instruction::{Instruction, InstructionError::Custom};
+use solana_program_test::{tokio, BanksClientError};
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::Transaction;
+
+use strike_wallet::error::WalletError;
+use strike_wallet::model::multisig_op::ApprovalDisposition;
+
+async fn process(
+    context: &mut BalanceAccountTestContext,
+    instructions: Vec<Instruction>,
+) -> Result<(), BanksClientError> {
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+}
+
+#[tokio::test]
+async fn test_approval_disposition_by_signature() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let initiator = Keypair::from_bytes(&context.approvers[2].to_bytes()).unwrap();
+    let approver = Keypair::from_bytes(&context.approvers[0].to_bytes()).unwrap();
+    let (multisig_op_account, result) =
+        setup_transfer_test(&mut context, &initiator, &balance_account, None, 123).await;
+    result.unwrap();
+    let multisig_op = multisig_op_account.pubkey();
+    let params_hash = get_multisig_op_data(&mut context.pt_context.banks_client, multisig_op)
+        .await
+        .params_hash
+        .unwrap();
+    let now = context
+        .pt_context
+        .banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap()
+        .unix_timestamp;
+
+    // a lapsed signature isn't accepted
+    let instructions = set_approval_disposition_by_signature(
+        &context.program_id,
+        &multisig_op,
+        &approver,
+        ApprovalDisposition::APPROVE,
+        params_hash,
+        now - 1,
+    );
+    assert_instruction_error(
+        process(&mut context, instructions).await,
+        1,
+        Custom(WalletError::InvalidApprovalSignature as u32),
+    );
+
+    // nor one verified for another disposition than the one submitted
+    let mut instructions = set_approval_disposition_by_signature(
+        &context.program_id,
+        &multisig_op,
+        &approver,
+        ApprovalDisposition::DENY,
+        params_hash,
+        now + 3600,
+    );
+    instructions[1] = set_approval_disposition_by_signature(
+        &context.program_id,
+        &multisig_op,
+        &approver,
+        ApprovalDisposition::APPROVE,
+        params_hash,
+        now + 3600,
+    )[1]
+    .clone();
+    assert_instruction_error(
+        process(&mut context, instructions).await,
+        1,
+        Custom(WalletError::InvalidApprovalSignature as u32),
+    );
+
+    // nor one by someone else than the approver
+    let mut instructions = set_approval_disposition_by_signature(
+        &context.program_id,
+        &multisig_op,
+        &Keypair::new(),
+        ApprovalDisposition::APPROVE,
+        params_hash,
+        now + 3600,
+    );
+    instructions[1].accounts[1].pubkey = approver.pubkey();
+    assert_instruction_error(
+        process(&mut context, instructions).await,
+        1,
+        Custom(WalletError::InvalidApprovalSignature as u32),
+    );
+
+    // the approver's offline signature is submitted without them signing the transaction
+    let instructions = set_approval_disposition_by_signature(
+        &context.program_id,
+        &multisig_op,
+        &approver,
+        ApprovalDisposition::APPROVE,
+        params_hash,
+        now + 3600,
+    );
+    process(&mut context, instructions).await.unwrap();
+    assert!(
+        get_multisig_op_data(&mut context.pt_context.banks_client, multisig_op)
+            .await
+            .disposition_records
+            .iter()
+            .any(|record| record.approver == approver.pubkey()
+                && record.disposition == ApprovalDisposition::APPROVE)
+    );
+}
@@ -0,0 +1,197 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use solana_program::clock::Clock;
+use solana_program::hash::Hash;
+use solana_program::instruction::{Instruction, InstructionError::Custom};
+use solana_program::pubkey::Pubkey;
+use solana_program_test::{tokio, BanksClientError};
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::Transaction;
+
+use strike_wallet::error::WalletError;
+use strike_wallet::model::approval_group::ApprovalGroup;
+use strike_wallet::model::multisig_op::MultisigOpState;
+use strike_wallet::model::session_key_registry::SessionKeyScope;
+
+async fn process(
+    context: &mut BalanceAccountTestContext,
+    instructions: Vec<Instruction>,
+) -> Result<(), BanksClientError> {
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+}
+
+async fn set_approval_group(
+    context: &mut BalanceAccountTestContext,
+    approval_group: ApprovalGroup,
+) {
+    let wallet = context.wallet_account.pubkey();
+    let rent_return = context.pt_context.payer.pubkey();
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let mut test_context = context.to_test_context();
+    init_multisig_op(
+        &mut test_context,
+        multisig_op_account,
+        init_approval_group_update(
+            &context.program_id,
+            &wallet,
+            &multisig_op_pubkey,
+            &context.approvers[0].pubkey(),
+            &rent_return,
+            Some(approval_group),
+        ),
+        &context.approvers[0],
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut test_context,
+        &multisig_op_pubkey,
+        vec![&context.approvers[0], &context.approvers[1]],
+    )
+    .await;
+    finalize_multisig_op(
+        &mut test_context,
+        multisig_op_pubkey,
+        finalize_approval_group_update(
+            &context.program_id,
+            &wallet,
+            &multisig_op_pubkey,
+            &rent_return,
+            Some(approval_group),
+        ),
+    )
+    .await;
+    assert_eq!(
+        get_wallet(&mut context.pt_context.banks_client, &wallet)
+            .await
+            .approval_group,
+        Some(approval_group)
+    );
+}
+
+/// Starts a transfer, returning its op account, params hash and a signature expiry an hour out.
+async fn start_transfer(
+    context: &mut BalanceAccountTestContext,
+    balance_account: &Pubkey,
+) -> (Pubkey, Hash, i64) {
+    let initiator = Keypair::from_bytes(&context.approvers[2].to_bytes()).unwrap();
+    let (multisig_op_account, result) =
+        setup_transfer_test(context, &initiator, balance_account, None, 123).await;
+    result.unwrap();
+    let multisig_op = multisig_op_account.pubkey();
+    let params_hash = get_multisig_op_data(&mut context.pt_context.banks_client, multisig_op)
+        .await
+        .params_hash
+        .unwrap();
+    let now = context
+        .pt_context
+        .banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap()
+        .unix_timestamp;
+    (multisig_op, params_hash, now + 3600)
+}
+
+#[tokio::test]
+async fn test_aggregated_approval() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let group = Keypair::new();
+    set_approval_group(
+        &mut context,
+        ApprovalGroup {
+            group_key: group.pubkey(),
+            threshold: 2,
+            scope: SessionKeyScope {
+                categories: SessionKeyScope::TRANSFER,
+                max_transfer_amount: 1_000,
+            },
+        },
+    )
+    .await;
+    let wallet = context.wallet_account.pubkey();
+    let (multisig_op, params_hash, expires_at) =
+        start_transfer(&mut context, &balance_account).await;
+
+    // a signature by any other key than the group's isn't accepted
+    let instructions = submit_aggregated_approval(
+        &context.program_id,
+        &multisig_op,
+        &wallet,
+        &Keypair::new(),
+        params_hash,
+        expires_at,
+    );
+    assert_instruction_error(
+        process(&mut context, instructions).await,
+        1,
+        Custom(WalletError::InvalidApprovalSignature as u32),
+    );
+
+    // the group's single signature approves the op
+    let instructions = submit_aggregated_approval(
+        &context.program_id,
+        &multisig_op,
+        &wallet,
+        &group,
+        params_hash,
+        expires_at,
+    );
+    process(&mut context, instructions).await.unwrap();
+    assert_eq!(
+        get_multisig_op_data(&mut context.pt_context.banks_client, multisig_op)
+            .await
+            .state,
+        MultisigOpState::Approved
+    );
+}
+
+#[tokio::test]
+async fn test_aggregated_approval_below_quorum_is_rejected() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let group = Keypair::new();
+    set_approval_group(
+        &mut context,
+        ApprovalGroup {
+            group_key: group.pubkey(),
+            threshold: 1,
+            scope: SessionKeyScope {
+                categories: SessionKeyScope::TRANSFER,
+                max_transfer_amount: 1_000,
+            },
+        },
+    )
+    .await;
+    let wallet = context.wallet_account.pubkey();
+    let (multisig_op, params_hash, expires_at) =
+        start_transfer(&mut context, &balance_account).await;
+
+    let instructions = submit_aggregated_approval(
+        &context.program_id,
+        &multisig_op,
+        &wallet,
+        &group,
+        params_hash,
+        expires_at,
+    );
+    assert_instruction_error(
+        process(&mut context, instructions).await,
+        1,
+        Custom(WalletError::ApprovalGroupNotAllowed as u32),
+    );
+}
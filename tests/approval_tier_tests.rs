@@ -0,0 +1,118 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use std::time::Duration;
+
+use solana_program::instruction::InstructionError::Custom;
+use solana_program::pubkey::Pubkey;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::BalanceAccountPolicyUpdate;
+use strike_wallet::model::balance_account::{ApprovalTier, FinalizeDelayPolicy};
+use strike_wallet::model::wallet::Approvers;
+use strike_wallet::utils::SlotId;
+
+fn policy_update(
+    context: &BalanceAccountTestContext,
+    tiers: Vec<ApprovalTier>,
+) -> BalanceAccountPolicyUpdate {
+    BalanceAccountPolicyUpdate {
+        approvals_required_for_transfer: 1,
+        approval_timeout_for_transfer: Duration::from_secs(3600),
+        transfer_approvers: vec![SlotId::new(0), SlotId::new(1)],
+        signers_hash: hash_signers(&vec![
+            context.approvers[0].pubkey_as_signer(),
+            context.approvers[1].pubkey_as_signer(),
+        ]),
+        finalize_delay_policy: FinalizeDelayPolicy::default(),
+        mint_cap: 0,
+        approval_tiers: tiers,
+    }
+}
+
+fn tier(min_amount: u64, approvals_required: u8, slots: Vec<usize>) -> ApprovalTier {
+    ApprovalTier {
+        min_amount,
+        approvals_required,
+        approvers: Approvers::from_enabled_vec(slots.into_iter().map(SlotId::new).collect()),
+    }
+}
+
+#[tokio::test]
+async fn test_transfer_approvals_by_amount_tier() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let initiator = Keypair::from_bytes(&context.approvers[2].to_bytes()).unwrap();
+    let update = policy_update(
+        &context,
+        vec![tier(1000, 1, vec![1]), tier(10_000, 2, vec![0, 1])],
+    );
+    update_balance_account_policy(&mut context, update, None).await;
+
+    let expected = vec![
+        // below the lowest tier either transfer approver can approve
+        (
+            999,
+            vec![context.approvers[0].pubkey(), context.approvers[1].pubkey()],
+            1,
+        ),
+        // the first tier only lets the second approver approve
+        (5000, vec![context.approvers[1].pubkey()], 1),
+        // and the second tier needs both of them
+        (
+            10_000,
+            vec![context.approvers[0].pubkey(), context.approvers[1].pubkey()],
+            2,
+        ),
+    ];
+    for (amount, approvers, dispositions_required) in expected {
+        let (multisig_op_account, result) =
+            setup_transfer_test(&mut context, &initiator, &balance_account, None, amount).await;
+        result.unwrap();
+        let multisig_op = get_multisig_op_data(
+            &mut context.pt_context.banks_client,
+            multisig_op_account.pubkey(),
+        )
+        .await;
+        assert_eq!(
+            multisig_op
+                .disposition_records
+                .iter()
+                .map(|record| record.approver)
+                .collect::<Vec<Pubkey>>(),
+            approvers
+        );
+        assert_eq!(multisig_op.dispositions_required, dispositions_required);
+    }
+}
+
+#[tokio::test]
+async fn test_approval_tiers_must_be_valid() {
+    let (mut context, _) = setup_balance_account_tests_and_finalize(None).await;
+
+    // the third signer isn't a transfer approver of the account
+    let update = policy_update(&context, vec![tier(1000, 1, vec![2])]);
+    update_balance_account_policy(
+        &mut context,
+        update,
+        Some(Custom(WalletError::InvalidApprovalTiers as u32)),
+    )
+    .await;
+
+    // tiers have to be in ascending order of amount
+    let update = policy_update(
+        &context,
+        vec![tier(10_000, 1, vec![1]), tier(1000, 1, vec![0])],
+    );
+    update_balance_account_policy(
+        &mut context,
+        update,
+        Some(Custom(WalletError::InvalidApprovalTiers as u32)),
+    )
+    .await;
+}
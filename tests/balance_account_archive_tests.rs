@@ -0,0 +1,85 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use solana_program::instruction::InstructionError::Custom;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::TransactionError;
+use std::borrow::BorrowMut;
+use strike_wallet::error::WalletError;
+use strike_wallet::model::multisig_op::BooleanSetting;
+use strike_wallet::utils::SlotId;
+
+async fn verify_archived(context: &mut BalanceAccountTestContext, expected_archived: bool) {
+    let wallet = get_wallet(
+        &mut context.pt_context.banks_client,
+        &context.wallet_account.pubkey(),
+    )
+    .await;
+    let account = wallet
+        .get_balance_account(&context.balance_account_guid_hash)
+        .unwrap();
+
+    assert_eq!(account.is_archived(), expected_archived);
+}
+
+#[tokio::test]
+async fn test_archived_balance_account() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let initiator = &Keypair::from_base58_string(&context.approvers[2].to_base58_string());
+
+    // accounts are not archived by default
+    verify_archived(&mut context, false).await;
+
+    account_settings_update(
+        &mut context,
+        None,
+        None,
+        Some(BooleanSetting::On),
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+    .await;
+    verify_archived(&mut context, true).await;
+
+    // no new transfers or config changes can be started against an archived account
+    let (_, result) =
+        setup_transfer_test(context.borrow_mut(), initiator, &balance_account, None, 123).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::BalanceAccountArchived as u32)),
+    );
+    let destination_to_add = context.allowed_destination;
+    modify_balance_account_address_whitelist(
+        &mut context,
+        vec![(SlotId::new(0), destination_to_add)],
+        Some(Custom(WalletError::BalanceAccountArchived as u32)),
+    )
+    .await;
+
+    // but its settings can still be updated, so it can be brought back
+    account_settings_update(
+        &mut context,
+        None,
+        None,
+        Some(BooleanSetting::Off),
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+    .await;
+    verify_archived(&mut context, false).await;
+
+    let (_, result) =
+        setup_transfer_test(context.borrow_mut(), initiator, &balance_account, None, 123).await;
+    result.unwrap();
+}
@@ -19,7 +19,9 @@ use solana_sdk::account::{AccountSharedData, ReadableAccount, WritableAccount};
 use std::collections::HashSet;
 use strike_wallet::error::WalletError;
 use strike_wallet::instruction::{BalanceAccountCreation, InitialWalletConfig};
-use strike_wallet::model::balance_account::{BalanceAccountGuidHash, BalanceAccountNameHash};
+use strike_wallet::model::balance_account::{
+    BalanceAccount, BalanceAccountGuidHash, BalanceAccountNameHash,
+};
 use strike_wallet::model::multisig_op::{
     ApprovalDisposition, ApprovalDispositionRecord, BooleanSetting, MultisigOp,
     OperationDisposition,
@@ -28,10 +30,11 @@ use strike_wallet::model::wallet::Wallet;
 use strike_wallet::utils::SlotId;
 use uuid::Uuid;
 use {
-    solana_program_test::tokio,
+    solana_program_test::{tokio, BanksClientError},
     solana_sdk::{
         pubkey::Pubkey,
         signature::{Keypair, Signer as SdkSigner},
+        system_instruction,
         transaction::Transaction,
     },
 };
@@ -292,7 +295,7 @@ async fn test_balance_account_creation_initiator_approval() {
     let approvers = vec![Keypair::new(), Keypair::new(), Keypair::new()];
 
     let mut context = setup_wallet_test(
-        30_000,
+        40_000,
         InitialWalletConfig {
             approvals_required_for_config: 2,
             approval_timeout_for_config: Duration::from_secs(3600),
@@ -332,10 +335,14 @@ async fn test_balance_account_creation_initiator_approval() {
             ApprovalDispositionRecord {
                 approver: approvers[0].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                reason: None,
+                stake_weight: 0,
             },
             ApprovalDispositionRecord {
                 approver: approvers[1].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                reason: None,
+                stake_weight: 0,
             },
         ],
         OperationDisposition::NONE,
@@ -367,10 +374,14 @@ async fn test_balance_account_creation_initiator_approval() {
             ApprovalDispositionRecord {
                 approver: approvers[0].pubkey(),
                 disposition: ApprovalDisposition::APPROVE,
+                reason: None,
+                stake_weight: 0,
             },
             ApprovalDispositionRecord {
                 approver: approvers[1].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                reason: None,
+                stake_weight: 0,
             },
         ],
         OperationDisposition::NONE,
@@ -478,3 +489,335 @@ async fn test_multisig_op_version_mismatch() {
         .get_balance_account(&context.balance_account_guid_hash)
         .is_err());
 }
+
+async fn init_duplicate_balance_account_creation(
+    context: &mut BalanceAccountTestContext,
+    creation_params: &BalanceAccountCreation,
+) -> Result<Pubkey, BanksClientError> {
+    let multisig_op_account = Keypair::new();
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                system_instruction::create_account(
+                    &context.pt_context.payer.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    context.rent.minimum_balance(MultisigOp::LEN),
+                    MultisigOp::LEN as u64,
+                    &context.program_id,
+                ),
+                init_balance_account_creation_instruction(
+                    &context.program_id,
+                    &context.wallet_account.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    &context.initiator_account.pubkey(),
+                    &context.pt_context.payer.pubkey(),
+                    creation_params.slot_id,
+                    context.balance_account_guid_hash,
+                    creation_params.name_hash,
+                    creation_params.approvals_required_for_transfer,
+                    creation_params.approval_timeout_for_transfer,
+                    creation_params.transfer_approvers.clone(),
+                    creation_params.signers_hash,
+                    creation_params.whitelist_enabled,
+                    creation_params.dapps_enabled,
+                    creation_params.address_book_slot_id,
+                ),
+            ],
+            Some(&context.pt_context.payer.pubkey()),
+            &[
+                &context.pt_context.payer,
+                &multisig_op_account,
+                &context.initiator_account,
+            ],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .map(|_| multisig_op_account.pubkey())
+}
+
+#[tokio::test]
+async fn test_balance_account_creation_duplicate_guid() {
+    let mut context = setup_balance_account_tests(None, false).await;
+
+    // a second creation of the same guid hash can be started while the first is pending
+    let mut creation_params = context.expected_creation_params.clone();
+    creation_params.slot_id = SlotId::new(1);
+    creation_params.address_book_slot_id = SlotId::new(33);
+    let duplicate_op_account =
+        init_duplicate_balance_account_creation(&mut context, &creation_params)
+            .await
+            .unwrap();
+    for multisig_op_account in [context.multisig_op_account.pubkey(), duplicate_op_account] {
+        approve_or_deny_n_of_n_multisig_op(
+            context.pt_context.banks_client.borrow_mut(),
+            &context.program_id,
+            &multisig_op_account,
+            vec![&context.approvers[0], &context.approvers[1]],
+            &context.pt_context.payer,
+            context.pt_context.last_blockhash,
+            ApprovalDisposition::APPROVE,
+            OperationDisposition::APPROVED,
+        )
+        .await;
+    }
+    utils::finalize_balance_account_creation(context.borrow_mut()).await;
+
+    // but not finalized once the first has been
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[finalize_balance_account_creation(
+                    &context.program_id,
+                    &context.wallet_account.pubkey(),
+                    &duplicate_op_account,
+                    &context.pt_context.payer.pubkey(),
+                    context.balance_account_guid_hash,
+                    creation_params.clone(),
+                    None,
+                )],
+                Some(&context.pt_context.payer.pubkey()),
+                &[&context.pt_context.payer],
+                context.pt_context.last_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            Custom(WalletError::DuplicateBalanceAccountGuid as u32)
+        ),
+    );
+
+    // nor started again
+    assert_eq!(
+        init_duplicate_balance_account_creation(&mut context, &creation_params)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            1,
+            Custom(WalletError::DuplicateBalanceAccountGuid as u32)
+        ),
+    );
+    let wallet = get_wallet(
+        &mut context.pt_context.banks_client,
+        &context.wallet_account.pubkey(),
+    )
+    .await;
+    assert_eq!(wallet.balance_accounts.filled_slots().len(), 1);
+}
+
+fn balance_account_address(context: &BalanceAccountTestContext) -> Pubkey {
+    BalanceAccount::find_address(
+        &context.wallet_guid_hash,
+        &context.balance_account_guid_hash,
+        &context.program_id,
+    )
+    .0
+}
+
+async fn fund(context: &mut BalanceAccountTestContext, address: &Pubkey, lamports: u64) {
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(
+                &context.pt_context.payer.pubkey(),
+                address,
+                lamports,
+            )],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_denied_balance_account_creation_returns_prefunded_lamports() {
+    let mut context = setup_balance_account_tests(None, false).await;
+    let balance_account = balance_account_address(&context);
+    fund(&mut context, &balance_account, 1_000_000).await;
+
+    approve_or_deny_n_of_n_multisig_op(
+        context.pt_context.banks_client.borrow_mut(),
+        &context.program_id,
+        &context.multisig_op_account.pubkey(),
+        vec![&context.approvers[0], &context.approvers[1]],
+        &context.pt_context.payer,
+        context.pt_context.last_blockhash,
+        ApprovalDisposition::DENY,
+        OperationDisposition::DENIED,
+    )
+    .await;
+
+    let finalize = with_balance_account_reclaim(
+        finalize_balance_account_creation(
+            &context.program_id,
+            &context.wallet_account.pubkey(),
+            &context.multisig_op_account.pubkey(),
+            &context.pt_context.payer.pubkey(),
+            context.balance_account_guid_hash,
+            context.expected_creation_params.clone(),
+            None,
+        ),
+        &balance_account,
+    );
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[finalize],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .get_balance(balance_account)
+            .await
+            .unwrap(),
+        0
+    );
+    let wallet = get_wallet(
+        &mut context.pt_context.banks_client,
+        &context.wallet_account.pubkey(),
+    )
+    .await;
+    assert!(wallet
+        .get_balance_account(&context.balance_account_guid_hash)
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_cleanup_uncreated_balance_account() {
+    let mut context = setup_balance_account_tests(None, false).await;
+    let balance_account = balance_account_address(&context);
+    let rent_return = context.pt_context.payer.pubkey();
+
+    // a token account holding tokens can't be closed
+    let spl_context = setup_spl_transfer_test(&mut context, &balance_account, false).await;
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[cleanup_uncreated_balance_account(
+                    &context.program_id,
+                    &context.wallet_account.pubkey(),
+                    &balance_account,
+                    &rent_return,
+                    context.balance_account_guid_hash,
+                    vec![spl_context.source_token_address],
+                )],
+                Some(&rent_return),
+                &[&context.pt_context.payer],
+                context.pt_context.last_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::TokenAccountNotEmpty as u32)),
+    );
+
+    // an empty one is closed, and the balance account's lamports swept
+    let wrapped_sol_account = spl_associated_token_account::get_associated_token_address(
+        &balance_account,
+        &spl_token::native_mint::id(),
+    );
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                spl_associated_token_account::instruction::create_associated_token_account(
+                    &rent_return,
+                    &balance_account,
+                    &spl_token::native_mint::id(),
+                ),
+            ],
+            Some(&rent_return),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+    fund(&mut context, &balance_account, 1_000_000).await;
+
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[cleanup_uncreated_balance_account(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                &balance_account,
+                &rent_return,
+                context.balance_account_guid_hash,
+                vec![wrapped_sol_account],
+            )],
+            Some(&rent_return),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    assert!(context
+        .pt_context
+        .banks_client
+        .get_account(wrapped_sol_account)
+        .await
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .get_balance(balance_account)
+            .await
+            .unwrap(),
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_cleanup_fails_for_created_balance_account() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let rent_return = context.pt_context.payer.pubkey();
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[cleanup_uncreated_balance_account(
+                    &context.program_id,
+                    &context.wallet_account.pubkey(),
+                    &balance_account,
+                    &rent_return,
+                    context.balance_account_guid_hash,
+                    vec![],
+                )],
+                Some(&rent_return),
+                &[&context.pt_context.payer],
+                context.pt_context.last_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            Custom(WalletError::DuplicateBalanceAccountGuid as u32)
+        ),
+    );
+}
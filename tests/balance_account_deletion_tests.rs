@@ -0,0 +1,142 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use solana_program::instruction::InstructionError::Custom;
+use solana_program::system_instruction;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::Transaction;
+use strike_wallet::error::WalletError;
+
+#[tokio::test]
+async fn test_balance_account_deletion() {
+    let (context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let mut test_context = context.to_test_context();
+    let program_id = context.program_id;
+    let wallet_address = context.wallet_account.pubkey();
+    let rent_return = test_context.payer.pubkey();
+
+    test_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(
+                &rent_return,
+                &balance_account,
+                1_000_000,
+            )],
+            Some(&rent_return),
+            &[&test_context.payer],
+            test_context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+    let balance_account_lamports = test_context
+        .banks_client
+        .get_balance(balance_account)
+        .await
+        .unwrap();
+
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    init_multisig_op(
+        &mut test_context,
+        multisig_op_account,
+        init_balance_account_deletion(
+            &program_id,
+            &wallet_address,
+            &multisig_op_pubkey,
+            &context.assistant_account.pubkey(),
+            &rent_return,
+            context.balance_account_guid_hash,
+        ),
+        &context.assistant_account,
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut test_context,
+        &multisig_op_pubkey,
+        vec![&context.approvers[0], &context.approvers[1]],
+    )
+    .await;
+
+    let op_account_lamports = test_context
+        .banks_client
+        .get_balance(multisig_op_pubkey)
+        .await
+        .unwrap();
+    let starting_rent_return_lamports = test_context
+        .banks_client
+        .get_balance(rent_return)
+        .await
+        .unwrap();
+    test_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[finalize_balance_account_deletion(
+                &program_id,
+                &wallet_address,
+                &multisig_op_pubkey,
+                &balance_account,
+                &rent_return,
+                context.balance_account_guid_hash,
+            )],
+            Some(&rent_return),
+            &[&test_context.payer],
+            test_context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // the balance account is swept, rent-exempt minimum included, into the rent return account
+    assert!(test_context
+        .banks_client
+        .get_account(balance_account)
+        .await
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        test_context
+            .banks_client
+            .get_balance(rent_return)
+            .await
+            .unwrap(),
+        starting_rent_return_lamports + op_account_lamports + balance_account_lamports - 5000
+    );
+
+    // and both its slot and its address book entry are freed
+    let wallet = get_wallet(&mut test_context.banks_client, &wallet_address).await;
+    assert_eq!(
+        wallet.validate_balance_account_guid_hash(&context.balance_account_guid_hash),
+        Err(WalletError::BalanceAccountNotFound.into())
+    );
+    assert_eq!(
+        wallet.address_book[context.balance_account_address_book_entry.0],
+        None
+    );
+
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    assert_instruction_error(
+        init_multisig_op(
+            &mut test_context,
+            multisig_op_account,
+            init_balance_account_deletion(
+                &program_id,
+                &wallet_address,
+                &multisig_op_pubkey,
+                &context.assistant_account.pubkey(),
+                &rent_return,
+                context.balance_account_guid_hash,
+            ),
+            &context.assistant_account,
+        )
+        .await,
+        1,
+        Custom(WalletError::BalanceAccountNotFound as u32),
+    );
+}
@@ -11,7 +11,10 @@ use solana_sdk::signature::Keypair;
 use solana_sdk::transaction::TransactionError;
 use std::borrow::BorrowMut;
 use strike_wallet::error::WalletError;
-use strike_wallet::model::multisig_op::{ApprovalDisposition, OperationDisposition};
+use strike_wallet::instruction::TransferFinalizeStep;
+use strike_wallet::model::multisig_op::{
+    ApprovalDisposition, MultisigOpState, OperationDisposition,
+};
 use {
     solana_program::system_instruction,
     solana_program_test::tokio,
@@ -34,6 +37,8 @@ async fn test_wrap_unwrap() {
 
     let amount = 123;
 
+    // the balance account can't cover the amount, so the wrap fails at finalize and the
+    // wrapped SOL account is never created
     assert_eq!(
         process_wrap(
             &mut context,
@@ -42,14 +47,16 @@ async fn test_wrap_unwrap() {
             amount,
             token_account_rent,
             wrapped_sol_account,
+            ApprovalDisposition::APPROVE,
         )
         .await
         .unwrap_err()
         .unwrap(),
-        TransactionError::InstructionError(1, Custom(1)),
+        TransactionError::InstructionError(0, Custom(WalletError::InsufficientBalance as u32)),
     );
 
-    // move enough into balance account to fund wrapped sol token rent and balance account minimum, but NOT what we want to transfer
+    // move enough into balance account to fund the amount and balance account minimum; the
+    // wrapped SOL token rent is paid out of the multisig op account
     context
         .pt_context
         .banks_client
@@ -57,7 +64,7 @@ async fn test_wrap_unwrap() {
             &[system_instruction::transfer(
                 &context.pt_context.payer.pubkey(),
                 &balance_account,
-                token_account_rent + balance_account_rent,
+                balance_account_rent + amount,
             )],
             Some(&context.pt_context.payer.pubkey()),
             &[&context.pt_context.payer],
@@ -66,49 +73,25 @@ async fn test_wrap_unwrap() {
         .await
         .unwrap();
 
-    assert_eq!(
-        process_wrap(
-            &mut context,
-            multisig_account_rent,
-            balance_account,
-            amount,
-            token_account_rent,
-            wrapped_sol_account,
-        )
-        .await
-        .unwrap_err()
-        .unwrap(),
-        TransactionError::InstructionError(0, Custom(WalletError::InsufficientBalance as u32)),
-    );
-
-    // balance account should have balance account minimum SOL now
-    assert_eq!(
-        context
-            .pt_context
-            .banks_client
-            .get_balance(balance_account)
-            .await
-            .unwrap(),
-        balance_account_rent
-    );
-
-    // move enough into balance account to fund the amount
-    context
+    // a denied wrap doesn't leave a wrapped SOL account behind
+    process_wrap(
+        &mut context,
+        multisig_account_rent,
+        balance_account,
+        amount,
+        token_account_rent,
+        wrapped_sol_account,
+        ApprovalDisposition::DENY,
+    )
+    .await
+    .unwrap();
+    assert!(context
         .pt_context
         .banks_client
-        .process_transaction(Transaction::new_signed_with_payer(
-            &[system_instruction::transfer(
-                &context.pt_context.payer.pubkey(),
-                &balance_account,
-                amount,
-            )],
-            Some(&context.pt_context.payer.pubkey()),
-            &[&context.pt_context.payer],
-            context.pt_context.last_blockhash,
-        ))
+        .get_account(wrapped_sol_account)
         .await
-        .unwrap();
-
+        .unwrap()
+        .is_none());
     assert_eq!(
         context
             .pt_context
@@ -126,6 +109,7 @@ async fn test_wrap_unwrap() {
         amount,
         token_account_rent,
         wrapped_sol_account,
+        ApprovalDisposition::APPROVE,
     )
     .await
     .unwrap();
@@ -140,6 +124,11 @@ async fn test_wrap_unwrap() {
         token_account_rent + amount
     );
 
+    assert_eq!(
+        get_token_balance(&mut context, &wrapped_sol_account).await,
+        amount
+    );
+
     assert_eq!(
         context
             .pt_context
@@ -201,6 +190,52 @@ async fn test_wrap_unwrap() {
         balance_account_rent + unwrap_amount
     );
 
+    // wrapping into the existing account doesn't spend the rent reserved for creating it
+    process_wrap(
+        &mut context,
+        multisig_account_rent,
+        balance_account,
+        unwrap_amount,
+        token_account_rent,
+        wrapped_sol_account,
+        ApprovalDisposition::APPROVE,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .get_balance(wrapped_sol_account)
+            .await
+            .unwrap(),
+        token_account_rent + amount
+    );
+    assert_eq!(
+        get_token_balance(&mut context, &wrapped_sol_account).await,
+        amount
+    );
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .get_balance(balance_account)
+            .await
+            .unwrap(),
+        balance_account_rent
+    );
+
+    process_unwrapping(
+        &mut context,
+        multisig_account_rent,
+        token_account_rent,
+        balance_account,
+        unwrap_amount,
+        ApprovalDisposition::APPROVE,
+    )
+    .await
+    .unwrap();
+
     let failed_unwrap_amount = 15;
     process_unwrapping(
         &mut context,
@@ -229,6 +264,32 @@ async fn test_wrap_unwrap() {
             .unwrap(),
         balance_account_rent + unwrap_amount
     );
+
+    // unwrapping all closes the wrapped SOL account, returning its rent along with the rest
+    process_unwrapping_all(
+        &mut context,
+        multisig_account_rent,
+        balance_account,
+        ApprovalDisposition::APPROVE,
+    )
+    .await
+    .unwrap();
+    assert!(context
+        .pt_context
+        .banks_client
+        .get_account(wrapped_sol_account)
+        .await
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .get_balance(balance_account)
+            .await
+            .unwrap(),
+        balance_account_rent + amount + token_account_rent
+    );
 }
 
 #[tokio::test]
@@ -280,13 +341,16 @@ async fn test_transfer_spl(
             .unwrap();
     }
 
+    // the transfer is only allowed to create the destination token account if it's missing
+    let allow_destination_ata_creation = !create_destination_token_account;
     let initiator = &Keypair::from_base58_string(&context.approvers[2].to_base58_string());
-    let (multisig_op_account, result) = setup_transfer_test(
+    let (multisig_op_account, result) = setup_transfer_test_with_ata_creation(
         context.borrow_mut(),
         initiator,
         &balance_account,
         Some(&spl_context.mint.pubkey()),
         123,
+        allow_destination_ata_creation,
     )
     .await;
     result.unwrap();
@@ -307,16 +371,29 @@ async fn test_transfer_spl(
         get_token_balance(&mut context, &spl_context.source_token_address).await,
         1000
     );
+    // initiating the transfer doesn't create the destination token account
     assert_eq!(
-        get_token_balance(&mut context, &spl_context.destination_token_address).await,
-        0
+        context
+            .pt_context
+            .banks_client
+            .get_account(spl_context.destination_token_address)
+            .await
+            .unwrap()
+            .is_some(),
+        create_destination_token_account
     );
+    let source_lamports = context
+        .pt_context
+        .banks_client
+        .get_balance(balance_account)
+        .await
+        .unwrap();
 
     context
         .pt_context
         .banks_client
         .process_transaction(Transaction::new_signed_with_payer(
-            &[finalize_transfer(
+            &[finalize_transfer_with_options(
                 &context.program_id,
                 &multisig_op_account.pubkey(),
                 &context.wallet_account.pubkey(),
@@ -326,8 +403,11 @@ async fn test_transfer_spl(
                 context.balance_account_guid_hash,
                 123,
                 &spl_context.mint.pubkey(),
-                Some(&spl_context.mint_authority.pubkey()),
+                Some(&spl_token::id()),
                 None,
+                None,
+                None,
+                allow_destination_ata_creation,
             )],
             Some(&context.pt_context.payer.pubkey()),
             &[&context.pt_context.payer],
@@ -344,6 +424,97 @@ async fn test_transfer_spl(
         get_token_balance(&mut context, &spl_context.destination_token_address).await,
         123
     );
+    // the source account pays for the destination token account if the finalize creates it
+    // and the source can afford it
+    let token_account_rent = context
+        .pt_context
+        .banks_client
+        .get_rent()
+        .await
+        .unwrap()
+        .minimum_balance(spl_token::state::Account::LEN);
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .get_balance(balance_account)
+            .await
+            .unwrap(),
+        if allow_destination_ata_creation && fund_source_account_to_pay_for_token {
+            source_lamports - token_account_rent
+        } else {
+            source_lamports
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_transfer_spl_destination_token_account_creation_not_allowed() {
+    let (mut context, balance_account) =
+        setup_balance_account_tests_and_finalize(Some(60_000)).await;
+    let spl_context = setup_spl_transfer_test(&mut context, &balance_account, true).await;
+
+    let initiator = &Keypair::from_base58_string(&context.approvers[2].to_base58_string());
+    let (multisig_op_account, result) = setup_transfer_test(
+        context.borrow_mut(),
+        initiator,
+        &balance_account,
+        Some(&spl_context.mint.pubkey()),
+        123,
+    )
+    .await;
+    result.unwrap();
+
+    approve_or_deny_n_of_n_multisig_op(
+        context.pt_context.banks_client.borrow_mut(),
+        &context.program_id,
+        &multisig_op_account.pubkey(),
+        vec![&context.approvers[0], &context.approvers[1]],
+        &context.pt_context.payer,
+        context.pt_context.last_blockhash,
+        ApprovalDisposition::APPROVE,
+        OperationDisposition::APPROVED,
+    )
+    .await;
+
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[finalize_transfer(
+                    &context.program_id,
+                    &multisig_op_account.pubkey(),
+                    &context.wallet_account.pubkey(),
+                    &balance_account,
+                    &context.allowed_destination.address,
+                    &context.pt_context.payer.pubkey(),
+                    context.balance_account_guid_hash,
+                    123,
+                    &spl_context.mint.pubkey(),
+                    Some(&spl_token::id()),
+                    None,
+                    None,
+                )],
+                Some(&context.pt_context.payer.pubkey()),
+                &[&context.pt_context.payer],
+                context.pt_context.last_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            Custom(WalletError::DestinationTokenAccountCreationNotAllowed as u32)
+        ),
+    );
+    assert!(context
+        .pt_context
+        .banks_client
+        .get_account(spl_context.destination_token_address)
+        .await
+        .unwrap()
+        .is_none());
 }
 
 #[tokio::test]
@@ -379,10 +550,13 @@ async fn test_transfer_spl_insufficient_balance() {
         get_token_balance(&mut context, &spl_context.source_token_address).await,
         1000
     );
-    assert_eq!(
-        get_token_balance(&mut context, &spl_context.destination_token_address).await,
-        0
-    );
+    assert!(context
+        .pt_context
+        .banks_client
+        .get_account(spl_context.destination_token_address)
+        .await
+        .unwrap()
+        .is_none());
 
     assert_eq!(
         context
@@ -399,7 +573,8 @@ async fn test_transfer_spl_insufficient_balance() {
                     context.balance_account_guid_hash,
                     1230,
                     &spl_context.mint.pubkey(),
-                    Some(&spl_context.mint_authority.pubkey()),
+                    Some(&spl_token::id()),
+                    None,
                     None,
                 )],
                 Some(&context.pt_context.payer.pubkey()),
@@ -416,8 +591,327 @@ async fn test_transfer_spl_insufficient_balance() {
         get_token_balance(&mut context, &spl_context.source_token_address).await,
         1000
     );
+    assert!(context
+        .pt_context
+        .banks_client
+        .get_account(spl_context.destination_token_address)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_transfer_spl_executed_after_failed_execution() {
+    let (mut context, balance_account) =
+        setup_balance_account_tests_and_finalize(Some(60_000)).await;
+    let spl_context = setup_spl_transfer_test(&mut context, &balance_account, true).await;
+
+    let initiator = &Keypair::from_base58_string(&context.approvers[2].to_base58_string());
+    let (multisig_op_account, result) = setup_transfer_test(
+        context.borrow_mut(),
+        initiator,
+        &balance_account,
+        Some(&spl_context.mint.pubkey()),
+        123,
+    )
+    .await;
+    result.unwrap();
+
+    approve_or_deny_n_of_n_multisig_op(
+        context.pt_context.banks_client.borrow_mut(),
+        &context.program_id,
+        &multisig_op_account.pubkey(),
+        vec![&context.approvers[0], &context.approvers[1]],
+        &context.pt_context.payer,
+        context.pt_context.last_blockhash,
+        ApprovalDisposition::APPROVE,
+        OperationDisposition::APPROVED,
+    )
+    .await;
+
+    let finalize_step = |context: &BalanceAccountTestContext, step| {
+        Transaction::new_signed_with_payer(
+            &[finalize_transfer_step(
+                &context.program_id,
+                &multisig_op_account.pubkey(),
+                &context.wallet_account.pubkey(),
+                &balance_account,
+                &context.allowed_destination.address,
+                &context.pt_context.payer.pubkey(),
+                context.balance_account_guid_hash,
+                123,
+                &spl_context.mint.pubkey(),
+                Some(&spl_token::id()),
+                None,
+                None,
+                None,
+                false,
+                step,
+            )],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        )
+    };
+
+    // the transfer can't be executed before it is verified
+    assert_instruction_error(
+        context
+            .pt_context
+            .banks_client
+            .process_transaction(finalize_step(&context, TransferFinalizeStep::Execute))
+            .await,
+        0,
+        Custom(WalletError::OperationNotExecuted as u32),
+    );
+
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(finalize_step(&context, TransferFinalizeStep::Verify))
+        .await
+        .unwrap();
+    assert_eq!(
+        get_multisig_op_data(
+            &mut context.pt_context.banks_client,
+            multisig_op_account.pubkey()
+        )
+        .await
+        .state,
+        MultisigOpState::Executed
+    );
+
+    // the destination token account is missing, so the execution fails, and leaves the op
+    // executed for it to be retried
+    assert_instruction_error(
+        context
+            .pt_context
+            .banks_client
+            .process_transaction(finalize_step(&context, TransferFinalizeStep::Execute))
+            .await,
+        0,
+        Custom(WalletError::DestinationTokenAccountCreationNotAllowed as u32),
+    );
+    assert_eq!(
+        get_multisig_op_data(
+            &mut context.pt_context.banks_client,
+            multisig_op_account.pubkey()
+        )
+        .await
+        .state,
+        MultisigOpState::Executed
+    );
+    assert_eq!(
+        get_token_balance(&mut context, &spl_context.source_token_address).await,
+        1000
+    );
+
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                spl_associated_token_account::instruction::create_associated_token_account(
+                    &context.pt_context.payer.pubkey(),
+                    &context.destination.pubkey(),
+                    &spl_context.mint.pubkey(),
+                ),
+            ],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(finalize_step(&context, TransferFinalizeStep::Execute))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        get_token_balance(&mut context, &spl_context.source_token_address).await,
+        1000 - 123
+    );
+    assert_eq!(
+        get_token_balance(&mut context, &spl_context.destination_token_address).await,
+        123
+    );
+    assert!(context
+        .pt_context
+        .banks_client
+        .get_account(multisig_op_account.pubkey())
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_transfer_spl_quarantined_while_frozen() {
+    let (mut context, balance_account) =
+        setup_balance_account_tests_and_finalize(Some(60_000)).await;
+    let spl_context = setup_spl_transfer_test(&mut context, &balance_account, true).await;
+
+    let initiator = &Keypair::from_base58_string(&context.approvers[2].to_base58_string());
+    let (multisig_op_account, result) = setup_transfer_test(
+        context.borrow_mut(),
+        initiator,
+        &balance_account,
+        Some(&spl_context.mint.pubkey()),
+        123,
+    )
+    .await;
+    result.unwrap();
+
+    approve_or_deny_n_of_n_multisig_op(
+        context.pt_context.banks_client.borrow_mut(),
+        &context.program_id,
+        &multisig_op_account.pubkey(),
+        vec![&context.approvers[0], &context.approvers[1]],
+        &context.pt_context.payer,
+        context.pt_context.last_blockhash,
+        ApprovalDisposition::APPROVE,
+        OperationDisposition::APPROVED,
+    )
+    .await;
+
+    let freeze_or_thaw = |context: &BalanceAccountTestContext, freeze: bool| {
+        let accounts = (
+            &spl_token::id(),
+            &spl_context.source_token_address,
+            &spl_context.mint.pubkey(),
+            &spl_context.mint_authority.pubkey(),
+        );
+        Transaction::new_signed_with_payer(
+            &[if freeze {
+                spl_token::instruction::freeze_account(
+                    accounts.0,
+                    accounts.1,
+                    accounts.2,
+                    accounts.3,
+                    &[],
+                )
+            } else {
+                spl_token::instruction::thaw_account(
+                    accounts.0,
+                    accounts.1,
+                    accounts.2,
+                    accounts.3,
+                    &[],
+                )
+            }
+            .unwrap()],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer, &spl_context.mint_authority],
+            context.pt_context.last_blockhash,
+        )
+    };
+    let finalize = |context: &BalanceAccountTestContext| {
+        Transaction::new_signed_with_payer(
+            &[finalize_transfer(
+                &context.program_id,
+                &multisig_op_account.pubkey(),
+                &context.wallet_account.pubkey(),
+                &balance_account,
+                &context.allowed_destination.address,
+                &context.pt_context.payer.pubkey(),
+                context.balance_account_guid_hash,
+                123,
+                &spl_context.mint.pubkey(),
+                Some(&spl_token::id()),
+                None,
+                None,
+            )],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        )
+    };
+
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                spl_associated_token_account::instruction::create_associated_token_account(
+                    &context.pt_context.payer.pubkey(),
+                    &context.destination.pubkey(),
+                    &spl_context.mint.pubkey(),
+                ),
+            ],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(freeze_or_thaw(&context, true))
+        .await
+        .unwrap();
+
+    // the finalize quarantines the transfer instead of failing, keeping its approvals
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(finalize(&context))
+        .await
+        .unwrap();
+    let multisig_op = get_multisig_op_data(
+        &mut context.pt_context.banks_client,
+        multisig_op_account.pubkey(),
+    )
+    .await;
+    assert_eq!(multisig_op.state, MultisigOpState::Quarantined);
+    assert!(multisig_op.get_disposition_count(ApprovalDisposition::APPROVE) >= 2);
+    assert_eq!(
+        get_token_balance(&mut context, &spl_context.source_token_address).await,
+        1000
+    );
+
+    // and it can't be finalized while the account stays frozen
+    context.pt_context.last_blockhash =
+        context.pt_context.get_new_latest_blockhash().await.unwrap();
+    assert_instruction_error(
+        context
+            .pt_context
+            .banks_client
+            .process_transaction(finalize(&context))
+            .await,
+        0,
+        Custom(WalletError::TokenAccountFrozen as u32),
+    );
+
+    // once thawed, it is released and finalized without being approved again
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(freeze_or_thaw(&context, false))
+        .await
+        .unwrap();
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(finalize(&context))
+        .await
+        .unwrap();
+    assert_eq!(
+        get_token_balance(&mut context, &spl_context.source_token_address).await,
+        1000 - 123
+    );
     assert_eq!(
         get_token_balance(&mut context, &spl_context.destination_token_address).await,
-        0
+        123
     );
+    assert!(context
+        .pt_context
+        .banks_client
+        .get_account(multisig_op_account.pubkey())
+        .await
+        .unwrap()
+        .is_none());
 }
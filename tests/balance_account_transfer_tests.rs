@@ -7,18 +7,27 @@ pub use common::utils::*;
 
 use std::borrow::BorrowMut;
 use std::option::Option::None;
+use std::time::Duration;
 
-use solana_program::hash::Hash;
+use solana_program::clock::Clock;
+use solana_program::hash::{hash, Hash};
 use solana_program::instruction::InstructionError::Custom;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
 use solana_program::system_program;
+use solana_program_test::BanksClientError;
 use solana_sdk::signature::Keypair;
 use solana_sdk::transaction::TransactionError;
 
 use common::instructions::finalize_transfer;
 use strike_wallet::error::WalletError;
+use strike_wallet::instruction::{BalanceAccountPolicyUpdate, ProgramInstruction};
 use strike_wallet::model::address_book::AddressBookEntryNameHash;
+use strike_wallet::model::balance_account::{BalanceAccountGuidHash, FinalizeDelayPolicy};
+use strike_wallet::model::destination_receipt::DestinationReceipt;
 use strike_wallet::model::multisig_op::{
-    ApprovalDisposition, ApprovalDispositionRecord, BooleanSetting, OperationDisposition,
+    ApprovalDisposition, ApprovalDispositionRecord, BooleanSetting, MultisigOp,
+    OperationDisposition,
 };
 use strike_wallet::utils::SlotId;
 use {
@@ -93,6 +102,29 @@ async fn test_transfer_sol() {
         0
     );
 
+    // lamports sent to the receipt address up front don't keep it from being created
+    let (receipt_address, _) = DestinationReceipt::find_address(
+        &context.wallet_account.pubkey(),
+        &context.destination.pubkey(),
+        &system_program::id(),
+        &context.program_id,
+    );
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(
+                &context.pt_context.payer.pubkey(),
+                &receipt_address,
+                balance_account_rent,
+            )],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+
     context
         .pt_context
         .banks_client
@@ -109,6 +141,7 @@ async fn test_transfer_sol() {
                 &system_program::id(),
                 None,
                 None,
+                None,
             )],
             Some(&context.pt_context.payer.pubkey()),
             &[&context.pt_context.payer],
@@ -135,6 +168,168 @@ async fn test_transfer_sol() {
             .unwrap(),
         balance_account_rent
     );
+
+    let clock = context
+        .pt_context
+        .banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap();
+    let receipt = get_destination_receipt(
+        &mut context.pt_context.banks_client,
+        &context.program_id,
+        &context.wallet_account.pubkey(),
+        &context.destination.pubkey(),
+        &system_program::id(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        receipt,
+        DestinationReceipt {
+            is_initialized: true,
+            wallet: context.wallet_account.pubkey(),
+            destination: context.destination.pubkey(),
+            token_mint: system_program::id(),
+            transfer_count: 1,
+            cumulative_amount: balance_account_rent,
+            last_slot: clock.slot,
+            jurisdiction: None,
+            cumulative_deducted_fee: 0,
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_transfer_sol_with_memo() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let initiator = &Keypair::from_base58_string(&context.approvers[2].to_base58_string());
+
+    let rent = context.pt_context.banks_client.get_rent().await.unwrap();
+    let balance_account_rent = rent.minimum_balance(0);
+    let multisig_op_account = Keypair::new();
+    let memo = b"invoice 1234".to_vec();
+
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                system_instruction::create_account(
+                    &context.pt_context.payer.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    rent.minimum_balance(MultisigOp::LEN),
+                    MultisigOp::LEN as u64,
+                    &context.program_id,
+                ),
+                init_transfer_with_memo(
+                    &context.program_id,
+                    &context.wallet_account.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    &initiator.pubkey(),
+                    &balance_account,
+                    &context.destination.pubkey(),
+                    context.balance_account_guid_hash,
+                    balance_account_rent,
+                    context.destination_name_hash,
+                    &system_program::id(),
+                    &context.pt_context.payer.pubkey(),
+                    false,
+                    Some(hash(&memo)),
+                ),
+                system_instruction::transfer(
+                    &context.pt_context.payer.pubkey(),
+                    &balance_account,
+                    balance_account_rent * 2,
+                ),
+            ],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer, &multisig_op_account, initiator],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    approve_or_deny_n_of_n_multisig_op(
+        context.pt_context.banks_client.borrow_mut(),
+        &context.program_id,
+        &multisig_op_account.pubkey(),
+        vec![&context.approvers[0], &context.approvers[1]],
+        &context.pt_context.payer,
+        context.pt_context.last_blockhash,
+        ApprovalDisposition::APPROVE,
+        OperationDisposition::APPROVED,
+    )
+    .await;
+
+    // the transfer can only be finalized with the memo it was initiated with
+    for wrong_memo in [None, Some(b"invoice 1235".to_vec())] {
+        assert_eq!(
+            context
+                .pt_context
+                .banks_client
+                .process_transaction(Transaction::new_signed_with_payer(
+                    &[finalize_transfer_with_memo(
+                        &context.program_id,
+                        &multisig_op_account.pubkey(),
+                        &context.wallet_account.pubkey(),
+                        &balance_account,
+                        &context.destination.pubkey(),
+                        &context.pt_context.payer.pubkey(),
+                        context.balance_account_guid_hash,
+                        balance_account_rent,
+                        &system_program::id(),
+                        None,
+                        None,
+                        None,
+                        wrong_memo,
+                    )],
+                    Some(&context.pt_context.payer.pubkey()),
+                    &[&context.pt_context.payer],
+                    context.pt_context.last_blockhash,
+                ))
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(0, Custom(WalletError::InvalidSignature as u32)),
+        );
+    }
+
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[finalize_transfer_with_memo(
+                &context.program_id,
+                &multisig_op_account.pubkey(),
+                &context.wallet_account.pubkey(),
+                &balance_account,
+                &context.destination.pubkey(),
+                &context.pt_context.payer.pubkey(),
+                context.balance_account_guid_hash,
+                balance_account_rent,
+                &system_program::id(),
+                None,
+                None,
+                None,
+                Some(memo),
+            )],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .get_balance(context.destination.pubkey())
+            .await
+            .unwrap(),
+        balance_account_rent
+    );
 }
 
 #[tokio::test]
@@ -218,6 +413,7 @@ async fn test_transfer_sol_denied() {
                 &system_program::id(),
                 None,
                 None,
+                None,
             )],
             Some(&context.pt_context.payer.pubkey()),
             &[&context.pt_context.payer],
@@ -245,73 +441,96 @@ async fn test_transfer_sol_denied() {
             .unwrap(),
         0
     );
+
+    // and nothing is recorded for the destination
+    assert_eq!(
+        get_destination_receipt(
+            &mut context.pt_context.banks_client,
+            &context.program_id,
+            &context.wallet_account.pubkey(),
+            &context.destination.pubkey(),
+            &system_program::id(),
+        )
+        .await,
+        None
+    );
 }
 
 #[tokio::test]
-async fn test_transfer_wrong_destination_name_hash() {
+async fn test_transfer_sol_finalize_delay_and_cancel() {
     let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
     let initiator = &Keypair::from_base58_string(&context.approvers[2].to_base58_string());
 
-    account_settings_update(
-        &mut context,
-        Some(BooleanSetting::On),
-        None,
-        None,
-        None,
-        None,
-        None,
-    )
-    .await;
-    let destination_to_add = context.allowed_destination;
-    modify_balance_account_address_whitelist(
+    let signers_hash = hash_signers(&vec![
+        context.approvers[0].pubkey_as_signer(),
+        context.approvers[1].pubkey_as_signer(),
+    ]);
+    update_balance_account_policy(
         &mut context,
-        vec![(SlotId::new(0), destination_to_add)],
-        None,
-    )
-    .await;
-
-    context.destination_name_hash = AddressBookEntryNameHash::zero();
-
-    let (_, result) = setup_transfer_test(
-        context.borrow_mut(),
-        &initiator,
-        &balance_account,
+        BalanceAccountPolicyUpdate {
+            approvals_required_for_transfer: 2,
+            approval_timeout_for_transfer: Duration::from_secs(120),
+            transfer_approvers: vec![SlotId::new(0), SlotId::new(1)],
+            signers_hash,
+            finalize_delay_policy: FinalizeDelayPolicy {
+                threshold: 0,
+                delay: Duration::from_secs(3600),
+            },
+            mint_cap: 0,
+            approval_tiers: vec![],
+        },
         None,
-        123,
     )
     .await;
-    assert_eq!(
-        result.unwrap_err().unwrap(),
-        TransactionError::InstructionError(1, Custom(WalletError::DestinationNotAllowed as u32)),
-    )
-}
 
-#[tokio::test]
-async fn test_transfer_requires_multisig() {
-    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
-    let initiator = &Keypair::from_base58_string(&context.approvers[2].to_base58_string());
+    let rent = context.pt_context.banks_client.get_rent().await.unwrap();
+    let balance_account_rent = rent.minimum_balance(0);
     let (multisig_op_account, result) = setup_transfer_test(
         context.borrow_mut(),
         &initiator,
         &balance_account,
         None,
-        123,
+        balance_account_rent,
     )
     .await;
     result.unwrap();
 
-    approve_or_deny_1_of_2_multisig_op(
+    approve_or_deny_n_of_n_multisig_op(
         context.pt_context.banks_client.borrow_mut(),
         &context.program_id,
         &multisig_op_account.pubkey(),
-        &context.approvers[0],
+        vec![&context.approvers[0], &context.approvers[1]],
         &context.pt_context.payer,
-        &context.approvers[1].pubkey(),
         context.pt_context.last_blockhash,
         ApprovalDisposition::APPROVE,
+        OperationDisposition::APPROVED,
+    )
+    .await;
+
+    let multisig_op = get_multisig_op_data(
+        &mut context.pt_context.banks_client,
+        multisig_op_account.pubkey(),
     )
     .await;
+    assert_eq!(Duration::from_secs(3600), multisig_op.finalize_delay);
 
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(
+                &context.pt_context.payer.pubkey(),
+                &balance_account,
+                balance_account_rent * 2,
+            )],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // the transfer cannot be finalized until the delay has elapsed
     assert_eq!(
         context
             .pt_context
@@ -325,10 +544,11 @@ async fn test_transfer_requires_multisig() {
                     &context.destination.pubkey(),
                     &context.pt_context.payer.pubkey(),
                     context.balance_account_guid_hash,
-                    123,
+                    balance_account_rent,
                     &system_program::id(),
                     None,
                     None,
+                    None,
                 )],
                 Some(&context.pt_context.payer.pubkey()),
                 &[&context.pt_context.payer],
@@ -337,83 +557,182 @@ async fn test_transfer_requires_multisig() {
             .await
             .unwrap_err()
             .unwrap(),
-        TransactionError::InstructionError(
-            0,
-            Custom(WalletError::TransferDispositionNotFinal as u32)
-        ),
+        TransactionError::InstructionError(0, Custom(WalletError::FinalizeDelayNotElapsed as u32)),
     );
-}
-
-#[tokio::test]
-async fn test_approval_fails_if_incorrect_params_hash() {
-    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
-    let initiator = &Keypair::from_base58_string(&context.approvers[2].to_base58_string());
-    let (multisig_op_account, result) = setup_transfer_test(
-        context.borrow_mut(),
-        &initiator,
-        &balance_account,
-        None,
-        123,
-    )
-    .await;
-    result.unwrap();
 
+    // only a config approver can cancel it
     assert_eq!(
         context
             .pt_context
             .banks_client
             .process_transaction(Transaction::new_signed_with_payer(
-                &[set_approval_disposition(
+                &[cancel_transfer(
                     &context.program_id,
                     &multisig_op_account.pubkey(),
-                    &context.approvers[1].pubkey(),
-                    ApprovalDisposition::APPROVE,
-                    Hash::new_from_array([0; 32])
+                    &context.wallet_account.pubkey(),
+                    &context.destination.pubkey(),
+                    &initiator.pubkey(),
+                    context.balance_account_guid_hash,
+                    balance_account_rent,
+                    &system_program::id(),
                 )],
                 Some(&context.pt_context.payer.pubkey()),
-                &[&context.pt_context.payer, &context.approvers[1]],
+                &[&context.pt_context.payer, initiator],
                 context.pt_context.last_blockhash,
             ))
             .await
             .unwrap_err()
             .unwrap(),
-        TransactionError::InstructionError(0, Custom(WalletError::InvalidSignature as u32)),
+        TransactionError::InstructionError(0, Custom(WalletError::InvalidApprover as u32)),
     );
-}
-
-#[tokio::test]
-async fn test_transfer_insufficient_balance() {
-    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
-    let initiator = &Keypair::from_base58_string(&context.approvers[2].to_base58_string());
-
-    let rent = context.pt_context.banks_client.get_rent().await.unwrap();
-    let balance_account_rent = rent.minimum_balance(0);
-
-    let (multisig_op_account, result) = setup_transfer_test(
-        context.borrow_mut(),
-        &initiator,
-        &balance_account,
-        None,
-        123,
-    )
-    .await;
-    result.unwrap();
 
-    approve_or_deny_n_of_n_multisig_op(
-        context.pt_context.banks_client.borrow_mut(),
-        &context.program_id,
-        &multisig_op_account.pubkey(),
-        vec![&context.approvers[0], &context.approvers[1]],
-        &context.pt_context.payer,
-        context.pt_context.last_blockhash,
-        ApprovalDisposition::APPROVE,
-        OperationDisposition::APPROVED,
-    )
-    .await;
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[cancel_transfer(
+                &context.program_id,
+                &multisig_op_account.pubkey(),
+                &context.wallet_account.pubkey(),
+                &context.destination.pubkey(),
+                &context.approvers[0].pubkey(),
+                context.balance_account_guid_hash,
+                balance_account_rent,
+                &system_program::id(),
+            )],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer, &context.approvers[0]],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
 
-    // can't go to zero
     assert_eq!(
-        context
+        get_multisig_op_data(
+            &mut context.pt_context.banks_client,
+            multisig_op_account.pubkey()
+        )
+        .await
+        .operation_disposition(),
+        OperationDisposition::DENIED
+    );
+
+    // finalizing the cancelled transfer closes the op without moving funds
+    advance_clock(&mut context.pt_context, Duration::from_secs(1)).await;
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[finalize_transfer(
+                &context.program_id,
+                &multisig_op_account.pubkey(),
+                &context.wallet_account.pubkey(),
+                &balance_account,
+                &context.destination.pubkey(),
+                &context.pt_context.payer.pubkey(),
+                context.balance_account_guid_hash,
+                balance_account_rent,
+                &system_program::id(),
+                None,
+                None,
+                None,
+            )],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .get_balance(balance_account)
+            .await
+            .unwrap(),
+        balance_account_rent * 2
+    );
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .get_balance(context.destination.pubkey())
+            .await
+            .unwrap(),
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_transfer_wrong_destination_name_hash() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let initiator = &Keypair::from_base58_string(&context.approvers[2].to_base58_string());
+
+    account_settings_update(
+        &mut context,
+        Some(BooleanSetting::On),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+    .await;
+    let destination_to_add = context.allowed_destination;
+    modify_balance_account_address_whitelist(
+        &mut context,
+        vec![(SlotId::new(0), destination_to_add)],
+        None,
+    )
+    .await;
+
+    context.destination_name_hash = AddressBookEntryNameHash::zero();
+
+    let (_, result) = setup_transfer_test(
+        context.borrow_mut(),
+        &initiator,
+        &balance_account,
+        None,
+        123,
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::DestinationNotAllowed as u32)),
+    )
+}
+
+#[tokio::test]
+async fn test_transfer_requires_multisig() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let initiator = &Keypair::from_base58_string(&context.approvers[2].to_base58_string());
+    let (multisig_op_account, result) = setup_transfer_test(
+        context.borrow_mut(),
+        &initiator,
+        &balance_account,
+        None,
+        123,
+    )
+    .await;
+    result.unwrap();
+
+    approve_or_deny_1_of_2_multisig_op(
+        context.pt_context.banks_client.borrow_mut(),
+        &context.program_id,
+        &multisig_op_account.pubkey(),
+        &context.approvers[0],
+        &context.pt_context.payer,
+        &context.approvers[1].pubkey(),
+        context.pt_context.last_blockhash,
+        ApprovalDisposition::APPROVE,
+    )
+    .await;
+
+    assert_eq!(
+        context
             .pt_context
             .banks_client
             .process_transaction(Transaction::new_signed_with_payer(
@@ -429,6 +748,108 @@ async fn test_transfer_insufficient_balance() {
                     &system_program::id(),
                     None,
                     None,
+                    None,
+                )],
+                Some(&context.pt_context.payer.pubkey()),
+                &[&context.pt_context.payer],
+                context.pt_context.last_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            Custom(WalletError::TransferDispositionNotFinal as u32)
+        ),
+    );
+}
+
+#[tokio::test]
+async fn test_approval_fails_if_incorrect_params_hash() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let initiator = &Keypair::from_base58_string(&context.approvers[2].to_base58_string());
+    let (multisig_op_account, result) = setup_transfer_test(
+        context.borrow_mut(),
+        &initiator,
+        &balance_account,
+        None,
+        123,
+    )
+    .await;
+    result.unwrap();
+
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[set_approval_disposition(
+                    &context.program_id,
+                    &multisig_op_account.pubkey(),
+                    &context.approvers[1].pubkey(),
+                    ApprovalDisposition::APPROVE,
+                    Hash::new_from_array([0; 32])
+                )],
+                Some(&context.pt_context.payer.pubkey()),
+                &[&context.pt_context.payer, &context.approvers[1]],
+                context.pt_context.last_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::InvalidSignature as u32)),
+    );
+}
+
+#[tokio::test]
+async fn test_transfer_insufficient_balance() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let initiator = &Keypair::from_base58_string(&context.approvers[2].to_base58_string());
+
+    let rent = context.pt_context.banks_client.get_rent().await.unwrap();
+    let balance_account_rent = rent.minimum_balance(0);
+
+    let (multisig_op_account, result) = setup_transfer_test(
+        context.borrow_mut(),
+        &initiator,
+        &balance_account,
+        None,
+        123,
+    )
+    .await;
+    result.unwrap();
+
+    approve_or_deny_n_of_n_multisig_op(
+        context.pt_context.banks_client.borrow_mut(),
+        &context.program_id,
+        &multisig_op_account.pubkey(),
+        vec![&context.approvers[0], &context.approvers[1]],
+        &context.pt_context.payer,
+        context.pt_context.last_blockhash,
+        ApprovalDisposition::APPROVE,
+        OperationDisposition::APPROVED,
+    )
+    .await;
+
+    // can't go to zero
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[finalize_transfer(
+                    &context.program_id,
+                    &multisig_op_account.pubkey(),
+                    &context.wallet_account.pubkey(),
+                    &balance_account,
+                    &context.destination.pubkey(),
+                    &context.pt_context.payer.pubkey(),
+                    context.balance_account_guid_hash,
+                    123,
+                    &system_program::id(),
+                    None,
+                    None,
+                    None,
                 )],
                 Some(&context.pt_context.payer.pubkey()),
                 &[&context.pt_context.payer],
@@ -474,6 +895,7 @@ async fn test_transfer_insufficient_balance() {
                     &system_program::id(),
                     None,
                     None,
+                    None,
                 )],
                 Some(&context.pt_context.payer.pubkey()),
                 &[&context.pt_context.payer],
@@ -486,6 +908,107 @@ async fn test_transfer_insufficient_balance() {
     );
 }
 
+#[tokio::test]
+async fn test_transfer_sol_to_program_owned_destination() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let initiator = &Keypair::from_base58_string(&context.approvers[2].to_base58_string());
+
+    account_settings_update(
+        &mut context,
+        None,
+        None,
+        None,
+        Some(BooleanSetting::On),
+        None,
+        None,
+        None,
+        None,
+    )
+    .await;
+
+    let rent = context.pt_context.banks_client.get_rent().await.unwrap();
+    let balance_account_rent = rent.minimum_balance(0);
+    let (multisig_op_account, result) = setup_transfer_test(
+        context.borrow_mut(),
+        initiator,
+        &balance_account,
+        None,
+        balance_account_rent,
+    )
+    .await;
+    result.unwrap();
+
+    approve_or_deny_n_of_n_multisig_op(
+        context.pt_context.banks_client.borrow_mut(),
+        &context.program_id,
+        &multisig_op_account.pubkey(),
+        vec![&context.approvers[0], &context.approvers[1]],
+        &context.pt_context.payer,
+        context.pt_context.last_blockhash,
+        ApprovalDisposition::APPROVE,
+        OperationDisposition::APPROVED,
+    )
+    .await;
+
+    // the destination is an account owned by this program rather than a system account
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                system_instruction::transfer(
+                    &context.pt_context.payer.pubkey(),
+                    &balance_account,
+                    balance_account_rent * 2,
+                ),
+                system_instruction::create_account(
+                    &context.pt_context.payer.pubkey(),
+                    &context.destination.pubkey(),
+                    balance_account_rent,
+                    0,
+                    &context.program_id,
+                ),
+            ],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer, &context.destination],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[finalize_transfer(
+                    &context.program_id,
+                    &multisig_op_account.pubkey(),
+                    &context.wallet_account.pubkey(),
+                    &balance_account,
+                    &context.destination.pubkey(),
+                    &context.pt_context.payer.pubkey(),
+                    context.balance_account_guid_hash,
+                    balance_account_rent,
+                    &system_program::id(),
+                    None,
+                    None,
+                    None,
+                )],
+                Some(&context.pt_context.payer.pubkey()),
+                &[&context.pt_context.payer],
+                context.pt_context.last_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            Custom(WalletError::DestinationNotSystemOwned as u32)
+        ),
+    );
+}
+
 #[tokio::test]
 async fn test_transfer_unwhitelisted_address() {
     let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
@@ -498,6 +1021,8 @@ async fn test_transfer_unwhitelisted_address() {
         None,
         None,
         None,
+        None,
+        None,
     )
     .await;
 
@@ -535,10 +1060,14 @@ async fn test_transfer_initiator_approval() {
             ApprovalDispositionRecord {
                 approver: context.approvers[0].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                reason: None,
+                stake_weight: 0,
             },
             ApprovalDispositionRecord {
                 approver: context.approvers[1].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                reason: None,
+                stake_weight: 0,
             },
         ],
         OperationDisposition::NONE,
@@ -562,12 +1091,317 @@ async fn test_transfer_initiator_approval() {
             ApprovalDispositionRecord {
                 approver: context.approvers[0].pubkey(),
                 disposition: ApprovalDisposition::APPROVE,
+                reason: None,
+                stake_weight: 0,
             },
             ApprovalDispositionRecord {
                 approver: context.approvers[1].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                reason: None,
+                stake_weight: 0,
             },
         ],
         OperationDisposition::NONE,
     );
 }
+
+async fn init_transfer_with_fee_deducted(
+    context: &mut BalanceAccountTestContext,
+    initiator: &Keypair,
+    balance_account: &Pubkey,
+    amount: u64,
+    fee_amount: u64,
+    fee_account_guid_hash: Option<BalanceAccountGuidHash>,
+) -> (Keypair, Result<(), BanksClientError>) {
+    let rent = context.pt_context.banks_client.get_rent().await.unwrap();
+    let multisig_op_account = Keypair::new();
+    let mut instruction = init_transfer(
+        &context.program_id,
+        &context.wallet_account.pubkey(),
+        &multisig_op_account.pubkey(),
+        &initiator.pubkey(),
+        balance_account,
+        &context.destination.pubkey(),
+        context.balance_account_guid_hash,
+        amount,
+        context.destination_name_hash,
+        &system_program::id(),
+        &context.pt_context.payer.pubkey(),
+        false,
+    );
+    instruction.data = ProgramInstruction::InitTransfer {
+        fee_amount,
+        fee_account_guid_hash,
+        account_guid_hash: context.balance_account_guid_hash,
+        amount,
+        destination_name_hash: context.destination_name_hash,
+        destination_acknowledgement_required: false,
+        deduct_fee_from_amount: true,
+        allow_destination_ata_creation: false,
+        memo_hash: None,
+        destination_proof: None,
+    }
+    .pack();
+
+    let result = context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                system_instruction::create_account(
+                    &context.pt_context.payer.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    rent.minimum_balance(MultisigOp::LEN),
+                    MultisigOp::LEN as u64,
+                    &context.program_id,
+                ),
+                instruction,
+            ],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer, &multisig_op_account, initiator],
+            context.pt_context.last_blockhash,
+        ))
+        .await;
+
+    (multisig_op_account, result)
+}
+
+async fn finalize_transfer_with_fee_deducted(
+    context: &mut BalanceAccountTestContext,
+    multisig_op_account: &Keypair,
+    balance_account: &Pubkey,
+    amount: u64,
+) {
+    let mut instruction = finalize_transfer(
+        &context.program_id,
+        &multisig_op_account.pubkey(),
+        &context.wallet_account.pubkey(),
+        balance_account,
+        &context.destination.pubkey(),
+        &context.pt_context.payer.pubkey(),
+        context.balance_account_guid_hash,
+        amount,
+        &system_program::id(),
+        None,
+        None,
+        None,
+    );
+    instruction.data = ProgramInstruction::FinalizeTransfer {
+        account_guid_hash: context.balance_account_guid_hash,
+        amount,
+        token_mint: system_program::id(),
+        destination_acknowledgement_required: false,
+        deduct_fee_from_amount: true,
+        allow_destination_ata_creation: false,
+        memo: None,
+    }
+    .pack();
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_transfer_sol_with_fee_deducted_from_amount() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let initiator = &Keypair::from_base58_string(&context.approvers[2].to_base58_string());
+
+    let rent = context.pt_context.banks_client.get_rent().await.unwrap();
+    let balance_account_rent = rent.minimum_balance(0);
+    let fee_amount = 5000;
+    let amount = balance_account_rent + fee_amount;
+
+    // the fee can't exceed the amount, and can't also be collected from a fee account
+    let (_, result) = init_transfer_with_fee_deducted(
+        context.borrow_mut(),
+        initiator,
+        &balance_account,
+        fee_amount - 1,
+        fee_amount,
+        None,
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::InvalidFeeDeduction as u32)),
+    );
+    let (_, result) = init_transfer_with_fee_deducted(
+        context.borrow_mut(),
+        initiator,
+        &balance_account,
+        amount,
+        fee_amount,
+        Some(BalanceAccountGuidHash::new(&hash_of(b"fee-account-guid"))),
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::InvalidFeeDeduction as u32)),
+    );
+
+    let (multisig_op_account, result) = init_transfer_with_fee_deducted(
+        context.borrow_mut(),
+        initiator,
+        &balance_account,
+        amount,
+        fee_amount,
+        None,
+    )
+    .await;
+    result.unwrap();
+
+    approve_or_deny_n_of_n_multisig_op(
+        context.pt_context.banks_client.borrow_mut(),
+        &context.program_id,
+        &multisig_op_account.pubkey(),
+        vec![&context.approvers[0], &context.approvers[1]],
+        &context.pt_context.payer,
+        context.pt_context.last_blockhash,
+        ApprovalDisposition::APPROVE,
+        OperationDisposition::APPROVED,
+    )
+    .await;
+
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(
+                &context.pt_context.payer.pubkey(),
+                &balance_account,
+                balance_account_rent + amount,
+            )],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    finalize_transfer_with_fee_deducted(
+        context.borrow_mut(),
+        &multisig_op_account,
+        &balance_account,
+        amount,
+    )
+    .await;
+
+    // the full amount leaves the balance account, but the destination only receives the
+    // amount net of the fee
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .get_balance(balance_account)
+            .await
+            .unwrap(),
+        balance_account_rent
+    );
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .get_balance(context.destination.pubkey())
+            .await
+            .unwrap(),
+        amount - fee_amount
+    );
+    let receipt = get_destination_receipt(
+        &mut context.pt_context.banks_client,
+        &context.program_id,
+        &context.wallet_account.pubkey(),
+        &context.destination.pubkey(),
+        &system_program::id(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(receipt.transfer_count, 1);
+    assert_eq!(receipt.cumulative_amount, amount - fee_amount);
+    assert_eq!(receipt.cumulative_deducted_fee, fee_amount);
+}
+
+#[tokio::test]
+async fn test_denied_transfer_sol_with_fee_deducted_from_amount_pays_no_fee() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let initiator = &Keypair::from_base58_string(&context.approvers[2].to_base58_string());
+
+    let rent = context.pt_context.banks_client.get_rent().await.unwrap();
+    let balance_account_rent = rent.minimum_balance(0);
+    let fee_amount = 5000;
+    let amount = balance_account_rent + fee_amount;
+
+    let (multisig_op_account, result) = init_transfer_with_fee_deducted(
+        context.borrow_mut(),
+        initiator,
+        &balance_account,
+        amount,
+        fee_amount,
+        None,
+    )
+    .await;
+    result.unwrap();
+
+    approve_or_deny_n_of_n_multisig_op(
+        context.pt_context.banks_client.borrow_mut(),
+        &context.program_id,
+        &multisig_op_account.pubkey(),
+        vec![&context.approvers[0], &context.approvers[1]],
+        &context.pt_context.payer,
+        context.pt_context.last_blockhash,
+        ApprovalDisposition::DENY,
+        OperationDisposition::DENIED,
+    )
+    .await;
+
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(
+                &context.pt_context.payer.pubkey(),
+                &balance_account,
+                balance_account_rent + amount,
+            )],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    finalize_transfer_with_fee_deducted(
+        context.borrow_mut(),
+        &multisig_op_account,
+        &balance_account,
+        amount,
+    )
+    .await;
+
+    // nothing was transferred, so there was nothing to deduct the fee from
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .get_balance(balance_account)
+            .await
+            .unwrap(),
+        balance_account_rent + amount
+    );
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .get_balance(context.destination.pubkey())
+            .await
+            .unwrap(),
+        0
+    );
+}
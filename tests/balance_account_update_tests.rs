@@ -17,7 +17,9 @@ use common::instructions::{
 use std::collections::HashSet;
 use strike_wallet::error::WalletError;
 use strike_wallet::instruction::BalanceAccountPolicyUpdate;
-use strike_wallet::model::balance_account::{BalanceAccountGuidHash, BalanceAccountNameHash};
+use strike_wallet::model::balance_account::{
+    BalanceAccountGuidHash, BalanceAccountNameHash, FinalizeDelayPolicy,
+};
 use strike_wallet::model::multisig_op::{
     ApprovalDisposition, ApprovalDispositionRecord, OperationDisposition,
 };
@@ -55,6 +57,9 @@ async fn test_balance_account_policy_update() {
         approval_timeout_for_transfer: Duration::from_secs(7200),
         transfer_approvers: vec![SlotId::new(1), SlotId::new(2)],
         signers_hash,
+        finalize_delay_policy: FinalizeDelayPolicy::default(),
+        mint_cap: 0,
+        approval_tiers: vec![],
     };
     let multisig_op_account = update_balance_account_policy(&mut context, update, None)
         .await
@@ -120,6 +125,9 @@ async fn test_balance_account_policy_update() {
             approval_timeout_for_transfer: Duration::from_secs(6200),
             transfer_approvers: vec![SlotId::new(1), SlotId::new(2)],
             signers_hash,
+            finalize_delay_policy: FinalizeDelayPolicy::default(),
+            mint_cap: 0,
+            approval_tiers: vec![],
         },
         None,
     )
@@ -144,6 +152,9 @@ async fn test_balance_account_policy_update() {
             approval_timeout_for_transfer: Duration::from_secs(6200),
             transfer_approvers: vec![SlotId::new(1), SlotId::new(2)],
             signers_hash,
+            finalize_delay_policy: FinalizeDelayPolicy::default(),
+            mint_cap: 0,
+            approval_tiers: vec![],
         },
         None,
     )
@@ -178,6 +189,9 @@ async fn test_balance_account_policy_update_initiator_approval() {
             approval_timeout_for_transfer: Duration::from_secs(7200),
             transfer_approvers: vec![SlotId::new(1), SlotId::new(2)],
             signers_hash,
+            finalize_delay_policy: FinalizeDelayPolicy::default(),
+            mint_cap: 0,
+            approval_tiers: vec![],
         },
     )
     .await
@@ -190,10 +204,14 @@ async fn test_balance_account_policy_update_initiator_approval() {
             ApprovalDispositionRecord {
                 approver: context.approvers[0].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                reason: None,
+                stake_weight: 0,
             },
             ApprovalDispositionRecord {
                 approver: context.approvers[1].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                reason: None,
+                stake_weight: 0,
             },
         ],
         OperationDisposition::NONE,
@@ -215,6 +233,9 @@ async fn test_balance_account_policy_update_initiator_approval() {
             approval_timeout_for_transfer: Duration::from_secs(7200),
             transfer_approvers: vec![SlotId::new(1), SlotId::new(2)],
             signers_hash: signers_hash_new,
+            finalize_delay_policy: FinalizeDelayPolicy::default(),
+            mint_cap: 0,
+            approval_tiers: vec![],
         },
     )
     .await
@@ -227,10 +248,14 @@ async fn test_balance_account_policy_update_initiator_approval() {
             ApprovalDispositionRecord {
                 approver: context.approvers[0].pubkey(),
                 disposition: ApprovalDisposition::APPROVE,
+                reason: None,
+                stake_weight: 0,
             },
             ApprovalDispositionRecord {
                 approver: context.approvers[1].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                reason: None,
+                stake_weight: 0,
             },
         ],
         OperationDisposition::NONE,
@@ -262,6 +287,9 @@ async fn test_balance_account_policy_update_is_denied() {
         approval_timeout_for_transfer: Duration::from_secs(7200),
         transfer_approvers: vec![SlotId::new(1), SlotId::new(2)],
         signers_hash,
+        finalize_delay_policy: FinalizeDelayPolicy::default(),
+        mint_cap: 0,
+        approval_tiers: vec![],
     };
 
     let balance_account_update_transaction = Transaction::new_signed_with_payer(
@@ -436,6 +464,9 @@ async fn invalid_balance_account_policy_updates() {
                     approval_timeout_for_transfer: Duration::from_secs(7200),
                     transfer_approvers: vec![SlotId::new(1), SlotId::new(2)],
                     signers_hash,
+                    finalize_delay_policy: FinalizeDelayPolicy::default(),
+                    mint_cap: 0,
+                    approval_tiers: vec![],
                 },
             ),
             Custom(WalletError::BalanceAccountNotFound as u32),
@@ -463,6 +494,9 @@ async fn invalid_balance_account_policy_updates() {
                     approval_timeout_for_transfer: Duration::from_secs(7200),
                     transfer_approvers: vec![SlotId::new(1), SlotId::new(2)],
                     signers_hash,
+                    finalize_delay_policy: FinalizeDelayPolicy::default(),
+                    mint_cap: 0,
+                    approval_tiers: vec![],
                 },
             ),
             Custom(WalletError::InvalidApproverCount as u32),
@@ -490,6 +524,9 @@ async fn invalid_balance_account_policy_updates() {
                     approval_timeout_for_transfer: Duration::from_secs(7200),
                     transfer_approvers: vec![SlotId::new(1), SlotId::new(3)],
                     signers_hash,
+                    finalize_delay_policy: FinalizeDelayPolicy::default(),
+                    mint_cap: 0,
+                    approval_tiers: vec![],
                 },
             ),
             Custom(WalletError::UnknownSigner as u32),
@@ -517,6 +554,9 @@ async fn invalid_balance_account_policy_updates() {
                     approval_timeout_for_transfer: Duration::from_secs(7200),
                     transfer_approvers: vec![SlotId::new(0), SlotId::new(1)],
                     signers_hash,
+                    finalize_delay_policy: FinalizeDelayPolicy::default(),
+                    mint_cap: 0,
+                    approval_tiers: vec![],
                 },
             ),
             Custom(WalletError::InvalidSignersHash as u32),
@@ -568,10 +608,14 @@ async fn test_update_balance_account_name_initiator_approval() {
             ApprovalDispositionRecord {
                 approver: context.approvers[0].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                reason: None,
+                stake_weight: 0,
             },
             ApprovalDispositionRecord {
                 approver: context.approvers[1].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                reason: None,
+                stake_weight: 0,
             },
         ],
         OperationDisposition::NONE,
@@ -593,10 +637,14 @@ async fn test_update_balance_account_name_initiator_approval() {
             ApprovalDispositionRecord {
                 approver: context.approvers[0].pubkey(),
                 disposition: ApprovalDisposition::APPROVE,
+                reason: None,
+                stake_weight: 0,
             },
             ApprovalDispositionRecord {
                 approver: context.approvers[1].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                reason: None,
+                stake_weight: 0,
             },
         ],
         OperationDisposition::NONE,
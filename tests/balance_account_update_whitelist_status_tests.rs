@@ -7,11 +7,12 @@ pub use common::utils::*;
 
 use solana_program::instruction::InstructionError::Custom;
 use solana_program_test::tokio;
-use solana_sdk::signature::Keypair;
-use solana_sdk::transaction::TransactionError;
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::{Transaction, TransactionError};
 use std::borrow::BorrowMut;
 use std::option::Option::None;
 use strike_wallet::error::WalletError;
+use strike_wallet::model::wallet::Wallet;
 use strike_wallet::model::{balance_account::BalanceAccountGuidHash, multisig_op::BooleanSetting};
 use strike_wallet::utils::SlotId;
 
@@ -46,6 +47,8 @@ async fn test_whitelist_status() {
         None,
         None,
         None,
+        None,
+        None,
     )
     .await;
     verify_whitelist_status(&mut context, BooleanSetting::On, 0).await;
@@ -61,6 +64,8 @@ async fn test_whitelist_status() {
         &mut context,
         Some(BooleanSetting::Off),
         None,
+        None,
+        None,
         Some(Custom(WalletError::WhitelistedAddressInUse as u32)),
         None,
         None,
@@ -91,6 +96,8 @@ async fn test_whitelist_status() {
         None,
         None,
         None,
+        None,
+        None,
     )
     .await;
     verify_whitelist_status(&mut context, BooleanSetting::Off, 0).await;
@@ -109,6 +116,8 @@ async fn test_whitelist_status() {
         None,
         None,
         None,
+        None,
+        None,
     )
     .await;
     verify_whitelist_status(&mut context, BooleanSetting::On, 0).await;
@@ -127,6 +136,8 @@ async fn test_modify_whitelist_when_account_guid_invalid() {
         None,
         None,
         None,
+        None,
+        None,
     )
     .await;
     verify_whitelist_status(&mut context, BooleanSetting::On, 0).await;
@@ -143,3 +154,76 @@ async fn test_modify_whitelist_when_account_guid_invalid() {
     )
     .await;
 }
+
+#[tokio::test]
+async fn test_get_allowed_destinations() {
+    let (mut context, _) = setup_balance_account_tests_and_finalize(None).await;
+    account_settings_update(
+        &mut context,
+        Some(BooleanSetting::On),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+    .await;
+    let allowed_destination = context.allowed_destination;
+    modify_balance_account_address_whitelist(
+        &mut context,
+        vec![(SlotId::new(0), allowed_destination)],
+        None,
+    )
+    .await;
+
+    // clients holding the wallet account's data can resolve the whitelist themselves
+    let wallet_account = context
+        .pt_context
+        .banks_client
+        .get_account(context.wallet_account.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        Wallet::allowed_destinations_from_slice(
+            &wallet_account.data,
+            &context.balance_account_guid_hash
+        )
+        .unwrap(),
+        vec![allowed_destination]
+    );
+
+    let view = |account_guid_hash| {
+        Transaction::new_signed_with_payer(
+            &[get_allowed_destinations(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                account_guid_hash,
+                SlotId::new(0),
+            )],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        )
+    };
+    let known = view(context.balance_account_guid_hash);
+    let unknown = view(BalanceAccountGuidHash::zero());
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(known)
+        .await
+        .unwrap();
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .process_transaction(unknown)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::BalanceAccountNotFound as u32)),
+    );
+}
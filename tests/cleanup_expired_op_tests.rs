@@ -0,0 +1,101 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use std::borrow::BorrowMut;
+use std::time::Duration;
+
+use solana_program::instruction::InstructionError::Custom;
+use solana_program::pubkey::Pubkey;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::Transaction;
+use strike_wallet::error::WalletError;
+
+#[tokio::test]
+async fn test_cleanup_expired_op() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let initiator = &Keypair::from_base58_string(&context.approvers[2].to_base58_string());
+    let (multisig_op_account, result) =
+        setup_transfer_test(context.borrow_mut(), initiator, &balance_account, None, 123).await;
+    result.unwrap();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let rent_return = context.pt_context.payer.pubkey();
+
+    let cleanup_transaction = |context: &BalanceAccountTestContext, rent_return: &Pubkey| {
+        Transaction::new_signed_with_payer(
+            &[cleanup_expired_op(
+                &context.program_id,
+                &multisig_op_pubkey,
+                rent_return,
+                Vec::new(),
+            )],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        )
+    };
+
+    // the op can't be cleaned up while it can still be approved
+    assert_instruction_error(
+        context
+            .pt_context
+            .banks_client
+            .process_transaction(cleanup_transaction(&context, &rent_return))
+            .await,
+        0,
+        Custom(WalletError::OperationNotExpired as u32),
+    );
+
+    advance_clock(&mut context.pt_context, Duration::from_secs(121)).await;
+
+    // its rent only goes back to its rent return account
+    assert_instruction_error(
+        context
+            .pt_context
+            .banks_client
+            .process_transaction(cleanup_transaction(&context, &Pubkey::new_unique()))
+            .await,
+        0,
+        Custom(WalletError::IncorrectRentReturnAccount as u32),
+    );
+
+    let op_rent = context
+        .pt_context
+        .banks_client
+        .get_balance(multisig_op_pubkey)
+        .await
+        .unwrap();
+    let rent_return_balance = context
+        .pt_context
+        .banks_client
+        .get_balance(rent_return)
+        .await
+        .unwrap();
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(cleanup_transaction(&context, &rent_return))
+        .await
+        .unwrap();
+
+    assert!(context
+        .pt_context
+        .banks_client
+        .get_account(multisig_op_pubkey)
+        .await
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .get_balance(rent_return)
+            .await
+            .unwrap(),
+        rent_return_balance + op_rent - 5000
+    );
+}
@@ -0,0 +1,404 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use std::time::Duration;
+
+use solana_program::hash::Hash;
+use solana_program::instruction::InstructionError::Custom;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_instruction;
+use solana_program_test::{tokio, BanksClientError};
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::{Transaction, TransactionError};
+
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::BalanceAccountPolicyUpdate;
+use strike_wallet::model::address_book::{AddressBookEntry, AddressBookEntryNameHash};
+use strike_wallet::model::balance_account::FinalizeDelayPolicy;
+use strike_wallet::model::multisig_op::{
+    ApprovalDisposition, BooleanSetting, MultisigOp, MultisigOpParams, OperationDisposition,
+};
+use strike_wallet::utils::SlotId;
+
+async fn setup_whitelisted_destination() -> (BalanceAccountTestContext, Pubkey) {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    account_settings_update(
+        &mut context,
+        Some(BooleanSetting::On),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+    .await;
+    let allowed_destination = (SlotId::new(0), context.allowed_destination);
+    modify_balance_account_address_whitelist(&mut context, vec![allowed_destination], None).await;
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(
+                &context.pt_context.payer.pubkey(),
+                &balance_account,
+                10_000_000,
+            )],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+    (context, balance_account)
+}
+
+async fn init_and_dispose(
+    context: &mut BalanceAccountTestContext,
+    amount: u64,
+    destination_commitment: Hash,
+    disposition: ApprovalDisposition,
+) -> Pubkey {
+    let multisig_op_account = Keypair::new();
+    let initiator = &context.approvers[2];
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                system_instruction::create_account(
+                    &context.pt_context.payer.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    context.rent.minimum_balance(MultisigOp::LEN),
+                    MultisigOp::LEN as u64,
+                    &context.program_id,
+                ),
+                init_committed_transfer(
+                    &context.program_id,
+                    &context.wallet_account.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    &initiator.pubkey(),
+                    &context.pt_context.payer.pubkey(),
+                    context.balance_account_guid_hash,
+                    amount,
+                    destination_commitment,
+                ),
+            ],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer, &multisig_op_account, initiator],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    let multisig_op = get_multisig_op_data(
+        &mut context.pt_context.banks_client,
+        multisig_op_account.pubkey(),
+    )
+    .await;
+    assert_eq!(
+        multisig_op.params_hash.unwrap(),
+        MultisigOpParams::CommittedTransfer {
+            wallet_address: context.wallet_account.pubkey(),
+            account_guid_hash: context.balance_account_guid_hash,
+            amount,
+            destination_commitment,
+        }
+        .hash(&multisig_op)
+    );
+
+    approve_or_deny_n_of_n_multisig_op(
+        &mut context.pt_context.banks_client,
+        &context.program_id,
+        &multisig_op_account.pubkey(),
+        vec![&context.approvers[0], &context.approvers[1]],
+        &context.pt_context.payer,
+        context.pt_context.last_blockhash,
+        disposition,
+        if disposition == ApprovalDisposition::APPROVE {
+            OperationDisposition::APPROVED
+        } else {
+            OperationDisposition::DENIED
+        },
+    )
+    .await;
+    multisig_op_account.pubkey()
+}
+
+async fn finalize(
+    context: &mut BalanceAccountTestContext,
+    multisig_op_account: &Pubkey,
+    balance_account: &Pubkey,
+    destination: &AddressBookEntry,
+    amount: u64,
+    destination_commitment: Hash,
+    salt: Hash,
+) -> Result<(), BanksClientError> {
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[finalize_committed_transfer(
+                &context.program_id,
+                multisig_op_account,
+                &context.wallet_account.pubkey(),
+                balance_account,
+                &destination.address,
+                &context.pt_context.payer.pubkey(),
+                context.balance_account_guid_hash,
+                amount,
+                destination_commitment,
+                destination.name_hash,
+                salt,
+            )],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+}
+
+#[tokio::test]
+async fn test_committed_transfer() {
+    let (mut context, balance_account) = setup_whitelisted_destination().await;
+    let destination = context.allowed_destination;
+    let salt = Hash::new_unique();
+    let commitment = destination.commitment(&salt);
+
+    let multisig_op_account = init_and_dispose(
+        &mut context,
+        123_000,
+        commitment,
+        ApprovalDisposition::APPROVE,
+    )
+    .await;
+    finalize(
+        &mut context,
+        &multisig_op_account,
+        &balance_account,
+        &destination,
+        123_000,
+        commitment,
+        salt,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .get_balance(destination.address)
+            .await
+            .unwrap(),
+        123_000
+    );
+}
+
+#[tokio::test]
+async fn test_committed_transfer_reveal_failures() {
+    let (mut context, balance_account) = setup_whitelisted_destination().await;
+    let destination = context.allowed_destination;
+    let salt = Hash::new_unique();
+    let commitment = destination.commitment(&salt);
+    let multisig_op_account = init_and_dispose(
+        &mut context,
+        123_000,
+        commitment,
+        ApprovalDisposition::APPROVE,
+    )
+    .await;
+
+    // the reveal has to match the commitment
+    let other_destination = AddressBookEntry {
+        address: Keypair::new().pubkey(),
+        name_hash: destination.name_hash,
+    };
+    for (revealed, revealed_salt) in [(destination, Hash::new_unique()), (other_destination, salt)]
+    {
+        assert_eq!(
+            finalize(
+                &mut context,
+                &multisig_op_account,
+                &balance_account,
+                &revealed,
+                123_000,
+                commitment,
+                revealed_salt,
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+            TransactionError::InstructionError(
+                0,
+                Custom(WalletError::DestinationCommitmentMismatch as u32)
+            ),
+        );
+    }
+
+    // and be whitelisted once revealed
+    let unlisted_destination = AddressBookEntry {
+        address: Keypair::new().pubkey(),
+        name_hash: AddressBookEntryNameHash::new(&hash_of(b"Unlisted Name")),
+    };
+    let commitment = unlisted_destination.commitment(&salt);
+    let multisig_op_account = init_and_dispose(
+        &mut context,
+        456_000,
+        commitment,
+        ApprovalDisposition::APPROVE,
+    )
+    .await;
+    assert_eq!(
+        finalize(
+            &mut context,
+            &multisig_op_account,
+            &balance_account,
+            &unlisted_destination,
+            456_000,
+            commitment,
+            salt,
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::DestinationNotAllowed as u32)),
+    );
+}
+
+#[tokio::test]
+async fn test_denied_committed_transfer_is_not_revealed() {
+    let (mut context, balance_account) = setup_whitelisted_destination().await;
+    let salt = Hash::new_unique();
+    let commitment = context.allowed_destination.commitment(&salt);
+    let multisig_op_account =
+        init_and_dispose(&mut context, 123_000, commitment, ApprovalDisposition::DENY).await;
+
+    // a denied transfer is finalized without revealing its destination
+    let placeholder = AddressBookEntry {
+        address: Keypair::new().pubkey(),
+        name_hash: AddressBookEntryNameHash::zero(),
+    };
+    finalize(
+        &mut context,
+        &multisig_op_account,
+        &balance_account,
+        &placeholder,
+        123_000,
+        commitment,
+        Hash::default(),
+    )
+    .await
+    .unwrap();
+
+    assert!(context
+        .pt_context
+        .banks_client
+        .get_account(multisig_op_account)
+        .await
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .get_balance(context.allowed_destination.address)
+            .await
+            .unwrap(),
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_committed_transfer_finalize_delay() {
+    let (mut context, balance_account) = setup_whitelisted_destination().await;
+    let signers_hash = hash_signers(&vec![
+        context.approvers[0].pubkey_as_signer(),
+        context.approvers[1].pubkey_as_signer(),
+    ]);
+    update_balance_account_policy(
+        &mut context,
+        BalanceAccountPolicyUpdate {
+            approvals_required_for_transfer: 2,
+            approval_timeout_for_transfer: Duration::from_secs(120),
+            transfer_approvers: vec![SlotId::new(0), SlotId::new(1)],
+            signers_hash,
+            finalize_delay_policy: FinalizeDelayPolicy {
+                threshold: 100_000,
+                delay: Duration::from_secs(3600),
+            },
+            mint_cap: 0,
+            approval_tiers: vec![],
+        },
+        None,
+    )
+    .await;
+    let destination = context.allowed_destination;
+    let salt = Hash::new_unique();
+    let commitment = destination.commitment(&salt);
+
+    // committing to the destination doesn't get a transfer above the threshold out of the delay
+    let multisig_op_account = init_and_dispose(
+        &mut context,
+        123_000,
+        commitment,
+        ApprovalDisposition::APPROVE,
+    )
+    .await;
+    assert_eq!(
+        get_multisig_op_data(&mut context.pt_context.banks_client, multisig_op_account)
+            .await
+            .finalize_delay,
+        Duration::from_secs(3600)
+    );
+    assert_eq!(
+        finalize(
+            &mut context,
+            &multisig_op_account,
+            &balance_account,
+            &destination,
+            123_000,
+            commitment,
+            salt,
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::FinalizeDelayNotElapsed as u32)),
+    );
+
+    // while one within it can be finalized as soon as it is approved
+    let multisig_op_account = init_and_dispose(
+        &mut context,
+        100_000,
+        commitment,
+        ApprovalDisposition::APPROVE,
+    )
+    .await;
+    finalize(
+        &mut context,
+        &multisig_op_account,
+        &balance_account,
+        &destination,
+        100_000,
+        commitment,
+        salt,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .get_balance(destination.address)
+            .await
+            .unwrap(),
+        100_000
+    );
+}
@@ -2,8 +2,9 @@ use crate::common::instructions;
 use crate::common::instructions::{
     finalize_account_settings_update, finalize_balance_account_name_update, finalize_update_signer,
     finalize_wallet_config_policy_update_instruction, init_account_settings_update,
-    init_balance_account_creation_instruction, init_balance_account_name_update, init_transfer,
-    init_wallet_config_policy_update_instruction, set_approval_disposition,
+    init_balance_account_creation_instruction, init_balance_account_name_update,
+    init_transfer_with_options, init_wallet_config_policy_update_instruction,
+    set_approval_disposition,
 };
 use crate::{
     finalize_address_book_update, finalize_balance_account_address_whitelist_update_instruction,
@@ -15,12 +16,14 @@ use arrayref::array_ref;
 use itertools::Itertools;
 use sha2::{Digest, Sha256};
 use solana_program::bpf_loader_upgradeable::{create_buffer, write};
+use solana_program::clock::Clock;
 use solana_program::hash::hash;
 use solana_program::instruction::{Instruction, InstructionError};
 use solana_program::rent::Rent;
 use solana_program::system_program;
+use solana_program_test::tokio::time::sleep;
 use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
-use solana_sdk::account::ReadableAccount;
+use solana_sdk::account::{AccountSharedData, ReadableAccount};
 use solana_sdk::transaction::TransactionError;
 use std::borrow::BorrowMut;
 use std::collections::HashSet;
@@ -28,7 +31,8 @@ use std::fmt::Debug;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use strike_wallet::instruction::{
     AddressBookUpdate, BalanceAccountAddressWhitelistUpdate, BalanceAccountCreation,
-    BalanceAccountPolicyUpdate, DAppBookUpdate, InitialWalletConfig, WalletConfigPolicyUpdate,
+    BalanceAccountPolicyUpdate, ConfigUpdate, DAppBookUpdate, InitialWalletConfig,
+    WalletConfigPolicyUpdate,
 };
 use strike_wallet::model::address_book::{
     AddressBookEntry, AddressBookEntryNameHash, DAppBookEntry, DAppBookEntryNameHash,
@@ -36,12 +40,14 @@ use strike_wallet::model::address_book::{
 use strike_wallet::model::balance_account::{
     BalanceAccount, BalanceAccountGuidHash, BalanceAccountNameHash,
 };
+use strike_wallet::model::destination_receipt::DestinationReceipt;
 use strike_wallet::model::multisig_op::{
     ApprovalDisposition, ApprovalDispositionRecord, BooleanSetting, MultisigOp, MultisigOpParams,
     OperationDisposition, SlotUpdateType, WrapDirection,
 };
 use strike_wallet::model::signer::Signer;
 use strike_wallet::model::wallet::{Signers, WalletGuidHash};
+use strike_wallet::model::wallet_registry::WalletRegistration;
 use strike_wallet::utils::SlotId;
 use strike_wallet::version::VERSION;
 use uuid::Uuid;
@@ -113,6 +119,83 @@ pub async fn setup_test(max_compute_units: u64) -> TestContext {
     }
 }
 
+/// Like `setup_test`, but keeps the `ProgramTestContext` around for tests that need to move
+/// the clock.
+pub async fn setup_test_with_context(max_compute_units: u64) -> (TestContext, ProgramTestContext) {
+    let program_id = Keypair::new().pubkey();
+    let mut pt = ProgramTest::new("strike_wallet", program_id, processor!(Processor::process));
+    pt.set_compute_max_units(max_compute_units);
+    let mut pt_context = pt.start_with_context().await;
+    let rent = pt_context.banks_client.get_rent().await.unwrap();
+
+    let test_context = TestContext {
+        program_id,
+        banks_client: pt_context.banks_client.clone(),
+        rent,
+        payer: Keypair::from_bytes(&pt_context.payer.to_bytes()).unwrap(),
+        recent_blockhash: pt_context.last_blockhash,
+    };
+    (test_context, pt_context)
+}
+
+/// Moves the clock forward, and returns a new blockhash so that a transaction repeated after
+/// the clock moved isn't answered with the result cached for its earlier attempt.
+pub async fn advance_clock(pt_context: &mut ProgramTestContext, duration: Duration) -> Hash {
+    let mut clock = pt_context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp += duration.as_secs() as i64;
+    pt_context.set_sysvar(&clock);
+    refresh_blockhash(pt_context).await
+}
+
+/// Waits for a blockhash other than the context's last one and makes it the last one.
+async fn refresh_blockhash(pt_context: &mut ProgramTestContext) -> Hash {
+    let last_blockhash = pt_context.last_blockhash;
+    loop {
+        let blockhash = pt_context
+            .banks_client
+            .get_latest_blockhash()
+            .await
+            .unwrap();
+        if blockhash != last_blockhash {
+            pt_context.last_blockhash = blockhash;
+            return blockhash;
+        }
+        sleep(Duration::from_millis(10)).await;
+    }
+}
+
+/// The state of a set of accounts, taken so that failure-path tests can branch from an
+/// expensive setup repeatedly instead of rebuilding it each time.
+pub struct AccountsSnapshot {
+    accounts: Vec<(Pubkey, Option<AccountSharedData>)>,
+}
+
+impl AccountsSnapshot {
+    pub async fn take(pt_context: &mut ProgramTestContext, addresses: &[Pubkey]) -> Self {
+        let mut accounts = Vec::with_capacity(addresses.len());
+        for address in addresses {
+            let account = pt_context
+                .banks_client
+                .get_account(*address)
+                .await
+                .unwrap()
+                .map(AccountSharedData::from);
+            accounts.push((*address, account));
+        }
+        AccountsSnapshot { accounts }
+    }
+
+    /// Puts the accounts back as they were when the snapshot was taken, closing those that
+    /// didn't exist yet. The clock is left as it is. Returns a new blockhash, so that repeating
+    /// a transaction from before the restore isn't answered with its cached result.
+    pub async fn restore(&self, pt_context: &mut ProgramTestContext) -> Hash {
+        for (address, account) in &self.accounts {
+            pt_context.set_account(address, &account.clone().unwrap_or_default());
+        }
+        refresh_blockhash(pt_context).await
+    }
+}
+
 pub fn create_program_owned_account_instruction(
     test_context: &TestContext,
     account_address: &Pubkey,
@@ -215,6 +298,23 @@ pub async fn get_multisig_op_data(
     .unwrap();
 }
 
+pub async fn get_destination_receipt(
+    banks_client: &mut BanksClient,
+    program_id: &Pubkey,
+    wallet_account: &Pubkey,
+    destination: &Pubkey,
+    token_mint: &Pubkey,
+) -> Option<DestinationReceipt> {
+    let (address, _) =
+        DestinationReceipt::find_address(wallet_account, destination, token_mint, program_id);
+    banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .filter(|account| account.owner == *program_id)
+        .map(|account| DestinationReceipt::unpack(account.data()).unwrap())
+}
+
 pub async fn init_wallet_config_policy_update(
     test_context: &mut TestContext,
     wallet_account: Pubkey,
@@ -282,6 +382,68 @@ pub async fn update_wallet_config_policy(
     .await;
 }
 
+pub async fn activate_wallet_config_policy(
+    test_context: &mut TestContext,
+    wallet_account: Pubkey,
+) -> Result<(), BanksClientError> {
+    test_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[instructions::activate_wallet_config_policy_instruction(
+                &test_context.program_id,
+                &wallet_account,
+            )],
+            Some(&test_context.payer.pubkey()),
+            &[&test_context.payer],
+            test_context.recent_blockhash,
+        ))
+        .await
+}
+
+pub async fn simulate_wallet_config_policy_update(
+    test_context: &mut TestContext,
+    wallet_account: Pubkey,
+    update: &WalletConfigPolicyUpdate,
+) -> Result<(), BanksClientError> {
+    test_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                instructions::simulate_wallet_config_policy_update_instruction(
+                    &test_context.program_id,
+                    &wallet_account,
+                    update,
+                ),
+            ],
+            Some(&test_context.payer.pubkey()),
+            &[&test_context.payer],
+            test_context.recent_blockhash,
+        ))
+        .await
+}
+
+pub async fn simulate_config_update(
+    test_context: &mut TestContext,
+    wallet_account: Pubkey,
+    initiator_account: &Keypair,
+    update: &ConfigUpdate,
+) -> Result<(), BanksClientError> {
+    test_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[instructions::simulate_config_update_instruction(
+                &test_context.program_id,
+                &wallet_account,
+                &initiator_account.pubkey(),
+                update,
+            )],
+            Some(&test_context.payer.pubkey()),
+            &[&test_context.payer, initiator_account],
+            test_context.recent_blockhash,
+        ))
+        .await
+}
+
 pub fn assert_instruction_error<R: Debug>(
     res: Result<R, BanksClientError>,
     expected_instruction_index: u8,
@@ -483,16 +645,20 @@ pub async fn update_signer(
             ApprovalDispositionRecord {
                 approver: approvers[0].pubkey(),
                 disposition: ApprovalDisposition::APPROVE,
+                reason: None,
+                stake_weight: 0,
             },
             ApprovalDispositionRecord {
                 approver: approvers[1].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                reason: None,
+                stake_weight: 0,
             },
         ])
     );
     assert_eq!(multisig_op.dispositions_required, 2);
     assert_eq!(
-        multisig_op.operation_disposition,
+        multisig_op.operation_disposition(),
         OperationDisposition::NONE
     );
 
@@ -539,7 +705,7 @@ pub async fn update_signer(
     )
     .unwrap();
     assert_eq!(
-        multisig_op.operation_disposition,
+        multisig_op.operation_disposition(),
         OperationDisposition::APPROVED
     );
 
@@ -602,6 +768,8 @@ pub async fn account_settings_update(
     context: &mut BalanceAccountTestContext,
     whitelist_status: Option<BooleanSetting>,
     dapps_enabled: Option<BooleanSetting>,
+    archived: Option<BooleanSetting>,
+    system_owned_destinations_only: Option<BooleanSetting>,
     expected_error: Option<InstructionError>,
     fee_amount: Option<u64>,
     fee_account_guid_hash: Option<BalanceAccountGuidHash>,
@@ -628,6 +796,8 @@ pub async fn account_settings_update(
                 context.balance_account_guid_hash,
                 whitelist_status,
                 dapps_enabled,
+                archived,
+                system_owned_destinations_only,
                 fee_amount,
                 fee_account_guid_hash,
             ),
@@ -681,16 +851,20 @@ pub async fn account_settings_update(
             ApprovalDispositionRecord {
                 approver: context.approvers[0].pubkey(),
                 disposition: ApprovalDisposition::APPROVE,
+                reason: None,
+                stake_weight: 0,
             },
             ApprovalDispositionRecord {
                 approver: context.approvers[1].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                reason: None,
+                stake_weight: 0,
             },
         ])
     );
     assert_eq!(multisig_op.dispositions_required, 2);
     assert_eq!(
-        multisig_op.operation_disposition,
+        multisig_op.operation_disposition(),
         OperationDisposition::NONE
     );
     assert_eq!(multisig_op.initiator, context.approvers[0].pubkey());
@@ -712,6 +886,8 @@ pub async fn account_settings_update(
             account_guid_hash: context.balance_account_guid_hash,
             whitelist_enabled: whitelist_status,
             dapps_enabled,
+            archived,
+            system_owned_destinations_only,
         }
         .hash(&multisig_op)
     );
@@ -741,6 +917,8 @@ pub async fn account_settings_update(
             context.balance_account_guid_hash,
             whitelist_status,
             dapps_enabled,
+            archived,
+            system_owned_destinations_only,
             fee_account_maybe.as_ref(),
         )],
         Some(&context.pt_context.payer.pubkey()),
@@ -889,11 +1067,9 @@ pub async fn verify_balance_account_name_hash(
     assert_eq!(account.name_hash, *expected_name_hash);
 
     // make sure address book entry has the new name
-    let (source_account, _) = Pubkey::find_program_address(
-        &[
-            context.wallet_guid_hash.to_bytes(),
-            context.balance_account_guid_hash.to_bytes(),
-        ],
+    let (source_account, _) = BalanceAccount::find_address(
+        &context.wallet_guid_hash,
+        &context.balance_account_guid_hash,
         &context.program_id,
     );
     let entry = wallet
@@ -954,12 +1130,14 @@ pub async fn approve_or_deny_n_of_n_multisig_op(
             .map(|approver| ApprovalDispositionRecord {
                 approver: approver.pubkey(),
                 disposition,
+                reason: None,
+                stake_weight: 0,
             })
             .collect_vec()
             .to_set()
     );
     assert_eq!(
-        multisig_op.operation_disposition,
+        multisig_op.operation_disposition(),
         expected_operation_disposition
     )
 }
@@ -1046,10 +1224,14 @@ pub async fn approve_or_deny_1_of_2_multisig_op(
             ApprovalDispositionRecord {
                 approver: approver.pubkey(),
                 disposition,
+                reason: None,
+                stake_weight: 0,
             },
             ApprovalDispositionRecord {
                 approver: *other_approver,
                 disposition: ApprovalDisposition::NONE,
+                reason: None,
+                stake_weight: 0,
             },
         ])
     );
@@ -1084,7 +1266,7 @@ pub struct BalanceAccountTestContext {
 }
 
 impl BalanceAccountTestContext {
-    fn to_test_context(&self) -> TestContext {
+    pub fn to_test_context(&self) -> TestContext {
         let new_payer = Keypair::from_bytes(&self.pt_context.payer.to_bytes()[..]).unwrap();
         TestContext {
             program_id: self.program_id,
@@ -1094,6 +1276,23 @@ impl BalanceAccountTestContext {
             recent_blockhash: self.pt_context.last_blockhash,
         }
     }
+
+    /// Snapshots the wallet, the balance account and the current multisig op, along with any
+    /// other accounts given, such as token accounts.
+    pub async fn snapshot(&mut self, other_accounts: &[Pubkey]) -> AccountsSnapshot {
+        let (balance_account, _) = BalanceAccount::find_address(
+            &self.wallet_guid_hash,
+            &self.balance_account_guid_hash,
+            &self.program_id,
+        );
+        let mut addresses = vec![
+            self.wallet_account.pubkey(),
+            balance_account,
+            self.multisig_op_account.pubkey(),
+        ];
+        addresses.extend_from_slice(other_accounts);
+        AccountsSnapshot::take(&mut self.pt_context, &addresses).await
+    }
 }
 
 pub async fn init_balance_account_creation(
@@ -1212,13 +1411,8 @@ pub async fn setup_balance_account_tests(
     }
 
     let slot_for_balance_account_address = SlotId::new(32);
-    let (source_account_pda, _) = Pubkey::find_program_address(
-        &[
-            wallet_guid_hash.to_bytes(),
-            balance_account_guid_hash.to_bytes(),
-        ],
-        &program_id,
-    );
+    let (source_account_pda, _) =
+        BalanceAccount::find_address(&wallet_guid_hash, &balance_account_guid_hash, &program_id);
 
     let init_transaction = Transaction::new_signed_with_payer(
         &[
@@ -1285,10 +1479,14 @@ pub async fn setup_balance_account_tests(
             ApprovalDispositionRecord {
                 approver: approvers[0].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                reason: None,
+                stake_weight: 0,
             },
             ApprovalDispositionRecord {
                 approver: approvers[1].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                reason: None,
+                stake_weight: 0,
             },
         ])
     );
@@ -1518,11 +1716,9 @@ pub async fn setup_balance_account_tests_and_finalize(
     .await;
 
     finalize_balance_account_creation(context.borrow_mut()).await;
-    let (source_account, _) = Pubkey::find_program_address(
-        &[
-            context.wallet_guid_hash.to_bytes(),
-            context.balance_account_guid_hash.to_bytes(),
-        ],
+    let (source_account, _) = BalanceAccount::find_address(
+        &context.wallet_guid_hash,
+        &context.balance_account_guid_hash,
         &context.program_id,
     );
 
@@ -1549,6 +1745,10 @@ pub async fn setup_balance_account_tests_and_finalize(
     let update = DAppBookUpdate {
         add_dapps: vec![(SlotId::new(0), context.allowed_dapp)],
         remove_dapps: vec![],
+        dapp_expirations: vec![],
+        dapp_categories: vec![],
+        disallowed_dapp_categories: vec![],
+        dapp_instruction_limits: vec![],
     };
 
     let multisig_op_account = init_dapp_book_update(
@@ -1584,6 +1784,25 @@ pub async fn setup_transfer_test(
     balance_account: &Pubkey,
     token_mint: Option<&Pubkey>,
     amount: u64,
+) -> (Keypair, Result<(), BanksClientError>) {
+    setup_transfer_test_with_ata_creation(
+        context,
+        initiator_account,
+        balance_account,
+        token_mint,
+        amount,
+        false,
+    )
+    .await
+}
+
+pub async fn setup_transfer_test_with_ata_creation(
+    context: &mut BalanceAccountTestContext,
+    initiator_account: &Keypair,
+    balance_account: &Pubkey,
+    token_mint: Option<&Pubkey>,
+    amount: u64,
+    allow_destination_ata_creation: bool,
 ) -> (Keypair, Result<(), BanksClientError>) {
     let rent = context.pt_context.banks_client.get_rent().await.unwrap();
     let multisig_account_rent = rent.minimum_balance(MultisigOp::LEN);
@@ -1602,7 +1821,7 @@ pub async fn setup_transfer_test(
                     MultisigOp::LEN as u64,
                     &context.program_id,
                 ),
-                init_transfer(
+                init_transfer_with_options(
                     &context.program_id,
                     &context.wallet_account.pubkey(),
                     &multisig_op_account.pubkey(),
@@ -1614,6 +1833,9 @@ pub async fn setup_transfer_test(
                     context.destination_name_hash,
                     token_mint.unwrap_or(&system_program::id()),
                     &context.pt_context.payer.pubkey(),
+                    false,
+                    None,
+                    allow_destination_ata_creation,
                 ),
             ],
             Some(&context.pt_context.payer.pubkey()),
@@ -1668,7 +1890,11 @@ pub async fn init_address_book_update(
                 &context.pt_context.payer.pubkey(),
                 update.add_address_book_entries,
                 update.remove_address_book_entries,
+                update.deprecate_address_book_entries,
                 update.balance_account_whitelist_updates,
+                update.address_book_risks,
+                update.address_book_expirations,
+                update.address_book_entry_kinds,
             ),
         ],
         Some(&context.pt_context.payer.pubkey()),
@@ -1740,16 +1966,30 @@ pub async fn modify_address_book(
     entries_to_remove: Vec<(SlotId<AddressBookEntry>, AddressBookEntry)>,
     expected_error: Option<InstructionError>,
 ) {
-    // add a whitelisted destination
+    update_address_book(
+        context,
+        AddressBookUpdate {
+            add_address_book_entries: entries_to_add,
+            remove_address_book_entries: entries_to_remove,
+            deprecate_address_book_entries: vec![],
+            balance_account_whitelist_updates: vec![],
+            address_book_risks: vec![],
+            address_book_expirations: vec![],
+            address_book_entry_kinds: vec![],
+        },
+        expected_error,
+    )
+    .await
+}
+
+pub async fn update_address_book(
+    context: &mut BalanceAccountTestContext,
+    update: AddressBookUpdate,
+    expected_error: Option<InstructionError>,
+) {
     let initiator_account =
         Keypair::from_base58_string(&context.initiator_account.to_base58_string());
 
-    let update = AddressBookUpdate {
-        add_address_book_entries: entries_to_add.clone(),
-        remove_address_book_entries: entries_to_remove.clone(),
-        balance_account_whitelist_updates: vec![],
-    };
-
     let init_result = init_address_book_update(context, &initiator_account, update.clone()).await;
 
     let multisig_op_account = match expected_error {
@@ -2215,6 +2455,22 @@ pub async fn get_wallet(banks_client: &mut BanksClient, account: &Pubkey) -> Wal
     .unwrap()
 }
 
+pub async fn get_wallet_registration(
+    banks_client: &mut BanksClient,
+    program_id: &Pubkey,
+    wallet_guid_hash: &WalletGuidHash,
+) -> WalletRegistration {
+    WalletRegistration::unpack(
+        banks_client
+            .get_account(WalletRegistration::find_address(wallet_guid_hash, program_id).0)
+            .await
+            .unwrap()
+            .unwrap()
+            .data(),
+    )
+    .unwrap()
+}
+
 pub fn assert_multisig_op_timestamps(
     multisig_op: &MultisigOp,
     initialized_at: SystemTime,
@@ -2250,7 +2506,7 @@ pub fn assert_initialized_multisig_op(
         multisig_op.disposition_records.to_set(),
         expected_dispositions.to_set()
     );
-    assert_eq!(multisig_op.operation_disposition, expected_op_disposition);
+    assert_eq!(multisig_op.operation_disposition(), expected_op_disposition);
     assert_eq!(
         multisig_op.params_hash.unwrap(),
         expected_params.hash(&multisig_op)
@@ -2275,7 +2531,7 @@ pub fn assert_multisig_op_dispositions(
         multisig_op.disposition_records.to_set(),
         expected_dispositions.to_set()
     );
-    assert_eq!(multisig_op.operation_disposition, expected_op_disposition);
+    assert_eq!(multisig_op.operation_disposition(), expected_op_disposition);
 }
 
 pub async fn verify_multisig_op_init_fails(
@@ -2324,8 +2580,16 @@ pub async fn process_wrap(
     amount: u64,
     token_account_rent: u64,
     wrapped_sol_account: Pubkey,
+    disposition: ApprovalDisposition,
 ) -> Result<(), BanksClientError> {
     let multisig_op_account = Keypair::new();
+    let wrapped_sol_account_exists = context
+        .pt_context
+        .banks_client
+        .get_account(wrapped_sol_account)
+        .await
+        .unwrap()
+        .is_some();
 
     let init_result = context
         .pt_context
@@ -2367,19 +2631,26 @@ pub async fn process_wrap(
         return init_result;
     }
 
+    // the wrapped SOL account is not created until finalize, and its rent is held by the op
+    // account whether or not it exists already
     assert_eq!(
         context
             .pt_context
             .banks_client
-            .get_balance(wrapped_sol_account)
+            .get_account(wrapped_sol_account)
             .await
-            .unwrap(),
-        token_account_rent
+            .unwrap()
+            .is_some(),
+        wrapped_sol_account_exists
     );
-
     assert_eq!(
-        get_token_balance(context.borrow_mut(), &wrapped_sol_account).await,
-        0
+        context
+            .pt_context
+            .banks_client
+            .get_balance(multisig_op_account.pubkey())
+            .await
+            .unwrap(),
+        multisig_account_rent + token_account_rent
     );
 
     approve_or_deny_n_of_n_multisig_op(
@@ -2389,8 +2660,12 @@ pub async fn process_wrap(
         vec![&context.approvers[0], &context.approvers[1]],
         &context.pt_context.payer,
         context.pt_context.last_blockhash,
-        ApprovalDisposition::APPROVE,
-        OperationDisposition::APPROVED,
+        disposition,
+        if disposition == ApprovalDisposition::APPROVE {
+            OperationDisposition::APPROVED
+        } else {
+            OperationDisposition::DENIED
+        },
     )
     .await;
 
@@ -2424,6 +2699,45 @@ pub async fn process_unwrapping(
     balance_account: Pubkey,
     unwrap_amount: u64,
     disposition: ApprovalDisposition,
+) -> Result<(), BanksClientError> {
+    process_unwrapping_in_direction(
+        context,
+        multisig_account_rent,
+        token_account_rent,
+        balance_account,
+        unwrap_amount,
+        WrapDirection::UNWRAP,
+        disposition,
+    )
+    .await
+}
+
+pub async fn process_unwrapping_all(
+    context: &mut BalanceAccountTestContext,
+    multisig_account_rent: u64,
+    balance_account: Pubkey,
+    disposition: ApprovalDisposition,
+) -> Result<(), BanksClientError> {
+    process_unwrapping_in_direction(
+        context,
+        multisig_account_rent,
+        0,
+        balance_account,
+        0,
+        WrapDirection::UNWRAP_ALL,
+        disposition,
+    )
+    .await
+}
+
+async fn process_unwrapping_in_direction(
+    context: &mut BalanceAccountTestContext,
+    multisig_account_rent: u64,
+    token_account_rent: u64,
+    balance_account: Pubkey,
+    unwrap_amount: u64,
+    direction: WrapDirection,
+    disposition: ApprovalDisposition,
 ) -> Result<(), BanksClientError> {
     let unwrap_multisig_op_account = Keypair::new();
 
@@ -2449,7 +2763,7 @@ pub async fn process_unwrapping(
                     &context.balance_account_guid_hash,
                     &context.wallet_guid_hash,
                     unwrap_amount,
-                    WrapDirection::UNWRAP,
+                    direction,
                     token_account_rent,
                 ),
             ],
@@ -2493,8 +2807,12 @@ pub async fn process_unwrapping(
                 &context.wallet_guid_hash,
                 &context.balance_account_guid_hash,
                 unwrap_amount,
-                WrapDirection::UNWRAP,
-                Some(&balance_account),
+                direction,
+                if direction == WrapDirection::UNWRAP {
+                    Some(&balance_account)
+                } else {
+                    None
+                },
             )],
             Some(&context.pt_context.payer.pubkey()),
             &[&context.pt_context.payer],
@@ -0,0 +1,255 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use solana_program::hash::Hash;
+use solana_program::instruction::InstructionError::Custom;
+use solana_program::program_pack::Pack;
+use solana_program::system_instruction;
+use solana_program_test::{tokio, BanksClientError};
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::{Transaction, TransactionError};
+
+use strike_wallet::error::WalletError;
+use strike_wallet::model::compliance_policy::CompliancePolicy;
+use strike_wallet::model::multisig_op::{ApprovalDisposition, MultisigOp, OperationDisposition};
+
+async fn update_compliance_policy(
+    context: &mut BalanceAccountTestContext,
+    policy: Option<CompliancePolicy>,
+) {
+    let mut test_context = context.to_test_context();
+    let rent_return = test_context.payer.pubkey();
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    init_multisig_op(
+        &mut test_context,
+        multisig_op_account,
+        init_compliance_policy_update(
+            &context.program_id,
+            &context.wallet_account.pubkey(),
+            &multisig_op_pubkey,
+            &context.approvers[0].pubkey(),
+            &rent_return,
+            policy,
+        ),
+        &context.approvers[0],
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut test_context,
+        &multisig_op_pubkey,
+        vec![&context.approvers[0], &context.approvers[1]],
+    )
+    .await;
+    finalize_multisig_op(
+        &mut test_context,
+        multisig_op_pubkey,
+        finalize_compliance_policy_update(
+            &context.program_id,
+            &context.wallet_account.pubkey(),
+            &multisig_op_pubkey,
+            &rent_return,
+            policy,
+        ),
+    )
+    .await;
+}
+
+async fn init_transfer_op(
+    context: &mut BalanceAccountTestContext,
+    amount: u64,
+    destination_commitment: Hash,
+) -> MultisigOp {
+    let multisig_op_account = Keypair::new();
+    let initiator = &context.approvers[2];
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                system_instruction::create_account(
+                    &context.pt_context.payer.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    context.rent.minimum_balance(MultisigOp::LEN),
+                    MultisigOp::LEN as u64,
+                    &context.program_id,
+                ),
+                init_committed_transfer(
+                    &context.program_id,
+                    &context.wallet_account.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    &initiator.pubkey(),
+                    &context.pt_context.payer.pubkey(),
+                    context.balance_account_guid_hash,
+                    amount,
+                    destination_commitment,
+                ),
+            ],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer, &multisig_op_account, initiator],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+    context.multisig_op_account = multisig_op_account;
+    get_multisig_op_data(
+        &mut context.pt_context.banks_client,
+        context.multisig_op_account.pubkey(),
+    )
+    .await
+}
+
+async fn approve_compliance(
+    context: &mut BalanceAccountTestContext,
+    service: &Keypair,
+    params_hash: Hash,
+) -> Result<(), BanksClientError> {
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[compliance_approve(
+                &context.program_id,
+                &context.multisig_op_account.pubkey(),
+                &service.pubkey(),
+                params_hash,
+            )],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer, service],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+}
+
+#[tokio::test]
+async fn test_compliance_approval() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let service = Keypair::new();
+    let policy = Some(CompliancePolicy {
+        service: service.pubkey(),
+        threshold: 100_000,
+    });
+    update_compliance_policy(&mut context, policy).await;
+    assert_eq!(
+        get_wallet(
+            &mut context.pt_context.banks_client,
+            &context.wallet_account.pubkey()
+        )
+        .await
+        .compliance_policy,
+        policy
+    );
+    let snapshot = context.snapshot(&[]).await;
+
+    // transfers up to the threshold don't need the service's approval
+    let multisig_op = init_transfer_op(&mut context, 100_000, Hash::new_unique()).await;
+    assert_eq!(multisig_op.compliance_service, None);
+
+    let destination = context.allowed_destination;
+    let salt = Hash::new_unique();
+    let commitment = destination.commitment(&salt);
+    let multisig_op = init_transfer_op(&mut context, 100_001, commitment).await;
+    assert_eq!(multisig_op.compliance_service, Some(service.pubkey()));
+    assert!(!multisig_op.compliance_approved);
+    let params_hash = multisig_op.params_hash.unwrap();
+    approve_or_deny_n_of_n_multisig_op(
+        &mut context.pt_context.banks_client,
+        &context.program_id,
+        &context.multisig_op_account.pubkey(),
+        vec![&context.approvers[0], &context.approvers[1]],
+        &context.pt_context.payer,
+        context.pt_context.last_blockhash,
+        ApprovalDisposition::APPROVE,
+        OperationDisposition::APPROVED,
+    )
+    .await;
+
+    // approved by the approvers, the transfer still waits for the service
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[finalize_committed_transfer(
+                    &context.program_id,
+                    &context.multisig_op_account.pubkey(),
+                    &context.wallet_account.pubkey(),
+                    &balance_account,
+                    &destination.address,
+                    &context.pt_context.payer.pubkey(),
+                    context.balance_account_guid_hash,
+                    100_001,
+                    commitment,
+                    destination.name_hash,
+                    salt,
+                )],
+                Some(&context.pt_context.payer.pubkey()),
+                &[&context.pt_context.payer],
+                context.pt_context.last_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            Custom(WalletError::ComplianceApprovalRequired as u32)
+        ),
+    );
+
+    // only the service can approve, and only the op it screened
+    let approver = Keypair::from_bytes(&context.approvers[0].to_bytes()).unwrap();
+    assert_eq!(
+        approve_compliance(&mut context, &approver, params_hash)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::InvalidApprover as u32)),
+    );
+    assert_eq!(
+        approve_compliance(&mut context, &service, Hash::new_unique())
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::InvalidSignature as u32)),
+    );
+    approve_compliance(&mut context, &service, params_hash)
+        .await
+        .unwrap();
+    assert!(
+        get_multisig_op_data(
+            &mut context.pt_context.banks_client,
+            context.multisig_op_account.pubkey(),
+        )
+        .await
+        .compliance_approved
+    );
+
+    // removing the policy lets later transfers through without the service
+    update_compliance_policy(&mut context, None).await;
+    let multisig_op = init_transfer_op(&mut context, 100_001, Hash::new_unique()).await;
+    assert_eq!(multisig_op.compliance_service, None);
+
+    // while rolling back to the snapshot brings the policy back
+    snapshot.restore(&mut context.pt_context).await;
+    let multisig_op = init_transfer_op(&mut context, 100_001, Hash::new_unique()).await;
+    assert_eq!(multisig_op.compliance_service, Some(service.pubkey()));
+}
+
+#[tokio::test]
+async fn test_compliance_approve_without_policy() {
+    let (mut context, _) = setup_balance_account_tests_and_finalize(None).await;
+    let multisig_op = init_transfer_op(&mut context, 100_001, Hash::new_unique()).await;
+    let service = Keypair::new();
+    assert_eq!(
+        approve_compliance(&mut context, &service, multisig_op.params_hash.unwrap())
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::InvalidApprover as u32)),
+    );
+}
@@ -59,9 +59,18 @@ async fn test_dapp_book_update() {
         },
     );
 
+    let expires_at = started_at
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+        + 86400;
     let add_dapp = DAppBookUpdate {
         add_dapps: vec![dapp_slot],
         remove_dapps: vec![],
+        dapp_expirations: vec![(SlotId::new(0), Some(expires_at))],
+        dapp_categories: vec![],
+        disallowed_dapp_categories: vec![],
+        dapp_instruction_limits: vec![],
     };
 
     let multisig_op_account = utils::init_dapp_book_update(
@@ -81,6 +90,8 @@ async fn test_dapp_book_update() {
         &vec![ApprovalDispositionRecord {
             approver: approvers[0].pubkey(),
             disposition: ApprovalDisposition::NONE,
+            reason: None,
+            stake_weight: 0,
         }],
         OperationDisposition::NONE,
         &MultisigOpParams::UpdateDAppBook {
@@ -101,17 +112,21 @@ async fn test_dapp_book_update() {
     )
     .await;
 
+    let wallet = get_wallet(&mut context.banks_client, &wallet_account.pubkey()).await;
+    assert_eq!(Slots::from_vec(vec![dapp_slot]), wallet.dapp_book);
     assert_eq!(
-        Slots::from_vec(vec![dapp_slot]),
-        get_wallet(&mut context.banks_client, &wallet_account.pubkey())
-            .await
-            .dapp_book
+        Some(expires_at),
+        wallet.dapp_book_expirations.get(SlotId::new(0))
     );
 
     // now remove it
     let remove_dapp = DAppBookUpdate {
         add_dapps: vec![],
         remove_dapps: vec![dapp_slot],
+        dapp_expirations: vec![],
+        dapp_categories: vec![],
+        disallowed_dapp_categories: vec![],
+        dapp_instruction_limits: vec![],
     };
 
     let remove_multisig_op_account = utils::init_dapp_book_update(
@@ -131,6 +146,8 @@ async fn test_dapp_book_update() {
         &vec![ApprovalDispositionRecord {
             approver: approvers[0].pubkey(),
             disposition: ApprovalDisposition::APPROVE,
+            reason: None,
+            stake_weight: 0,
         }],
         OperationDisposition::APPROVED,
         &MultisigOpParams::UpdateDAppBook {
@@ -149,17 +166,14 @@ async fn test_dapp_book_update() {
     )
     .await;
 
-    assert_eq!(
-        Slots::new(),
-        get_wallet(&mut context.banks_client, &wallet_account.pubkey())
-            .await
-            .dapp_book
-    );
+    let wallet = get_wallet(&mut context.banks_client, &wallet_account.pubkey()).await;
+    assert_eq!(Slots::new(), wallet.dapp_book);
+    assert_eq!(None, wallet.dapp_book_expirations.get(SlotId::new(0)));
 }
 
 #[tokio::test]
 async fn test_dapp_book_update_initiator_approval() {
-    let mut context = setup_test(30_000).await;
+    let mut context = setup_test(40_000).await;
 
     let wallet_account = Keypair::new();
     let assistant_account = Keypair::new();
@@ -206,6 +220,10 @@ async fn test_dapp_book_update_initiator_approval() {
                 },
             )],
             remove_dapps: vec![],
+            dapp_expirations: vec![],
+            dapp_categories: vec![],
+            disallowed_dapp_categories: vec![],
+            dapp_instruction_limits: vec![],
         },
     )
     .await
@@ -218,10 +236,14 @@ async fn test_dapp_book_update_initiator_approval() {
             ApprovalDispositionRecord {
                 approver: approvers[0].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                reason: None,
+                stake_weight: 0,
             },
             ApprovalDispositionRecord {
                 approver: approvers[1].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                reason: None,
+                stake_weight: 0,
             },
         ],
         OperationDisposition::NONE,
@@ -240,6 +262,10 @@ async fn test_dapp_book_update_initiator_approval() {
                 },
             )],
             remove_dapps: vec![],
+            dapp_expirations: vec![],
+            dapp_categories: vec![],
+            disallowed_dapp_categories: vec![],
+            dapp_instruction_limits: vec![],
         },
     )
     .await
@@ -252,10 +278,14 @@ async fn test_dapp_book_update_initiator_approval() {
             ApprovalDispositionRecord {
                 approver: approvers[0].pubkey(),
                 disposition: ApprovalDisposition::APPROVE,
+                reason: None,
+                stake_weight: 0,
             },
             ApprovalDispositionRecord {
                 approver: approvers[1].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                reason: None,
+                stake_weight: 0,
             },
         ],
         OperationDisposition::NONE,
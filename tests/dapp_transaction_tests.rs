@@ -2,8 +2,10 @@
 
 use std::borrow::BorrowMut;
 use std::option::Option::None;
+use std::time::Duration;
 
 use bitvec::macros::internal::funty::Fundamental;
+use solana_program::clock::Clock;
 use solana_program::hash::{hash, Hash};
 use solana_program::instruction::Instruction;
 use solana_program::instruction::InstructionError::Custom;
@@ -22,9 +24,11 @@ use common::instructions::{
 };
 pub use common::utils::*;
 use strike_wallet::error::WalletError;
-use strike_wallet::model::address_book::{DAppBookEntry, DAppBookEntryNameHash};
+use strike_wallet::instruction::DAppBookUpdate;
+use strike_wallet::model::address_book::{DAppBookEntry, DAppBookEntryNameHash, DAppCategories};
 use strike_wallet::model::dapp_multisig_data::DAppMultisigData;
 use strike_wallet::model::multisig_op::{ApprovalDisposition, BooleanSetting, MultisigOp};
+use strike_wallet::utils::SlotId;
 
 use crate::common::utils;
 use crate::utils::BalanceAccountTestContext;
@@ -69,6 +73,7 @@ async fn inner_instructions(
             context.destination_name_hash,
             &system_program::id(),
             &context.pt_context.payer.pubkey(),
+            false,
         ),
     ]
 }
@@ -91,6 +96,8 @@ async fn setup_dapp_test() -> DAppTest {
         None,
         None,
         None,
+        None,
+        None,
     )
     .await;
 
@@ -316,6 +323,79 @@ async fn test_dapp_transaction_bad_signature() {
     );
 }
 
+#[tokio::test]
+async fn test_dapp_transaction_escalated_account_permissions() {
+    let dapp_test = setup_dapp_test().await;
+
+    let mut context = dapp_test.context;
+
+    let params_hash = utils::get_operation_hash(
+        context.pt_context.banks_client.borrow_mut(),
+        dapp_test.multisig_op_account.pubkey(),
+    )
+    .await;
+    let approver = &context.approvers[0];
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[set_approval_disposition(
+                &context.program_id,
+                &dapp_test.multisig_op_account.pubkey(),
+                &approver.pubkey(),
+                ApprovalDisposition::APPROVE,
+                params_hash,
+            )],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer, approver],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // the inner instructions only read the destination, so it can't be passed writable
+    let mut finalize = finalize_dapp_transaction(
+        &context.program_id,
+        &context.wallet_account.pubkey(),
+        &dapp_test.multisig_op_account.pubkey(),
+        &dapp_test.multisig_data_account.pubkey(),
+        &dapp_test.balance_account,
+        &context.pt_context.payer.pubkey(),
+        &context.balance_account_guid_hash,
+        &dapp_test.params_hash,
+        &dapp_test.inner_instructions,
+        None,
+    );
+    finalize
+        .accounts
+        .iter_mut()
+        .find(|meta| meta.pubkey == context.destination.pubkey())
+        .unwrap()
+        .is_writable = true;
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[finalize],
+                Some(&context.pt_context.payer.pubkey()),
+                &[
+                    &context.pt_context.payer,
+                    &context.initiator_account,
+                    &dapp_test.inner_multisig_op_account,
+                ],
+                context.pt_context.last_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            Custom(WalletError::DAppAccountPermissionMismatch as u32)
+        ),
+    );
+}
+
 #[tokio::test]
 async fn test_dapp_transaction() {
     let dapp_test = setup_dapp_test().await;
@@ -472,6 +552,64 @@ async fn test_dapp_transaction_denied() {
     }
 }
 
+#[tokio::test]
+async fn test_expired_dapp_transaction_cleanup() {
+    let dapp_test = setup_dapp_test().await;
+    let mut context = dapp_test.context;
+    let multisig_op_pubkey = dapp_test.multisig_op_account.pubkey();
+    let multisig_data_pubkey = dapp_test.multisig_data_account.pubkey();
+    let rent_return = context.pt_context.payer.pubkey();
+    let blockhash = advance_clock(&mut context.pt_context, Duration::from_secs(121)).await;
+
+    // the multisig data account has to go with the op
+    assert_instruction_error(
+        context
+            .pt_context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[cleanup_expired_op(
+                    &context.program_id,
+                    &multisig_op_pubkey,
+                    &rent_return,
+                    vec![context.wallet_account.pubkey()],
+                )],
+                Some(&rent_return),
+                &[&context.pt_context.payer],
+                blockhash,
+            ))
+            .await,
+        0,
+        Custom(WalletError::IncorrectMultisigDataAccount as u32),
+    );
+
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[cleanup_expired_op(
+                &context.program_id,
+                &multisig_op_pubkey,
+                &rent_return,
+                vec![multisig_data_pubkey],
+            )],
+            Some(&rent_return),
+            &[&context.pt_context.payer],
+            blockhash,
+        ))
+        .await
+        .unwrap();
+
+    for account in vec![multisig_op_pubkey, multisig_data_pubkey] {
+        assert!(context
+            .pt_context
+            .banks_client
+            .get_account(account)
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
+
 #[tokio::test]
 async fn test_dapp_transaction_with_spl_transfers() {
     let (mut context, balance_account) =
@@ -485,6 +623,8 @@ async fn test_dapp_transaction_with_spl_transfers() {
         None,
         None,
         None,
+        None,
+        None,
     )
     .await;
 
@@ -779,37 +919,49 @@ async fn test_dapp_transaction_without_dapps_enabled() {
 }
 
 #[tokio::test]
-async fn test_dapp_transaction_unwhitelisted() {
-    let (mut context, balance_account) =
-        utils::setup_balance_account_tests_and_finalize(None).await;
+async fn test_dapp_transaction_reinitialization() {
+    let mut dapp_test = setup_dapp_test().await;
+    let context = &mut dapp_test.context;
+    let dapp = DAppBookEntry {
+        address: context.program_id.clone(),
+        name_hash: DAppBookEntryNameHash::new(&hash_of(b"Strike Wallet")),
+    };
+    let instruction_count = dapp_test.inner_instructions.len().as_u8();
 
-    account_settings_update(
-        &mut context,
-        Some(BooleanSetting::On),
-        Some(BooleanSetting::On),
-        None,
-        None,
-        None,
-        None,
-    )
-    .await;
+    // the op and data accounts of a pending transaction cannot be initialized again
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[init_dapp_transaction(
+                    &context.program_id,
+                    &context.wallet_account.pubkey(),
+                    &dapp_test.multisig_op_account.pubkey(),
+                    &dapp_test.multisig_data_account.pubkey(),
+                    &context.initiator_account.pubkey(),
+                    &context.pt_context.payer.pubkey(),
+                    &context.balance_account_guid_hash,
+                    dapp,
+                    instruction_count,
+                )],
+                Some(&context.pt_context.payer.pubkey()),
+                &[&context.pt_context.payer, &context.initiator_account],
+                context.pt_context.last_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            Custom(WalletError::AccountAlreadyInitialized as u32)
+        ),
+    );
 
+    // an account sized for a multisig op cannot be used as the dapp multisig data account
     let rent = context.pt_context.banks_client.get_rent().await.unwrap();
-    let multisig_op_account_rent = rent.minimum_balance(MultisigOp::LEN);
     let multisig_op_account = Keypair::new();
-    let multisig_data_account_rent = rent.minimum_balance(DAppMultisigData::LEN);
     let multisig_data_account = Keypair::new();
-    let dapp = DAppBookEntry {
-        address: context.program_id.clone(),
-        name_hash: DAppBookEntryNameHash::new(&hash_of(b"Strike Wallet")),
-    };
-    let inner_instructions = inner_instructions(
-        &mut context,
-        &multisig_op_account.pubkey(),
-        &balance_account,
-        123,
-    )
-    .await;
     assert_eq!(
         context
             .pt_context
@@ -819,15 +971,15 @@ async fn test_dapp_transaction_unwhitelisted() {
                     system_instruction::create_account(
                         &context.pt_context.payer.pubkey(),
                         &multisig_op_account.pubkey(),
-                        multisig_op_account_rent,
+                        rent.minimum_balance(MultisigOp::LEN),
                         MultisigOp::LEN as u64,
                         &context.program_id,
                     ),
                     system_instruction::create_account(
                         &context.pt_context.payer.pubkey(),
                         &multisig_data_account.pubkey(),
-                        multisig_data_account_rent,
-                        DAppMultisigData::LEN as u64,
+                        rent.minimum_balance(MultisigOp::LEN),
+                        MultisigOp::LEN as u64,
                         &context.program_id,
                     ),
                     init_dapp_transaction(
@@ -839,7 +991,7 @@ async fn test_dapp_transaction_unwhitelisted() {
                         &context.pt_context.payer.pubkey(),
                         &context.balance_account_guid_hash,
                         dapp,
-                        inner_instructions.len().as_u8(),
+                        instruction_count,
                     ),
                 ],
                 Some(&context.pt_context.payer.pubkey()),
@@ -854,37 +1006,23 @@ async fn test_dapp_transaction_unwhitelisted() {
             .await
             .unwrap_err()
             .unwrap(),
-        TransactionError::InstructionError(2, Custom(WalletError::DAppNotAllowed as u32)),
+        TransactionError::InstructionError(2, Custom(WalletError::AccountTypeMismatch as u32)),
     );
 }
 
 #[tokio::test]
-async fn test_dapp_transaction_whitelisted() {
-    let (mut context, balance_account) =
-        utils::setup_balance_account_tests_and_finalize(None).await;
-
-    account_settings_update(
-        &mut context,
-        Some(BooleanSetting::On),
-        Some(BooleanSetting::On),
-        None,
-        None,
-        None,
-        None,
-    )
-    .await;
+async fn test_dapp_transaction_substituted_multisig_data_account() {
+    let mut dapp_test = setup_dapp_test().await;
+    let context = &mut dapp_test.context;
+    let dapp = DAppBookEntry {
+        address: context.program_id.clone(),
+        name_hash: DAppBookEntryNameHash::new(&hash_of(b"Strike Wallet")),
+    };
 
-    let multisig_op_account_rent = context.rent.minimum_balance(MultisigOp::LEN);
-    let multisig_op_account = Keypair::new();
-    let multisig_data_account_rent = context.rent.minimum_balance(DAppMultisigData::LEN);
-    let multisig_data_account = Keypair::new();
-    let inner_instructions = inner_instructions(
-        &mut context,
-        &multisig_op_account.pubkey(),
-        &balance_account,
-        123,
-    )
-    .await;
+    // a data account bound to another op, carrying instructions of its own
+    let rent = context.pt_context.banks_client.get_rent().await.unwrap();
+    let other_multisig_op_account = Keypair::new();
+    let other_multisig_data_account = Keypair::new();
     context
         .pt_context
         .banks_client
@@ -892,52 +1030,105 @@ async fn test_dapp_transaction_whitelisted() {
             &[
                 system_instruction::create_account(
                     &context.pt_context.payer.pubkey(),
-                    &multisig_op_account.pubkey(),
-                    multisig_op_account_rent,
+                    &other_multisig_op_account.pubkey(),
+                    rent.minimum_balance(MultisigOp::LEN),
                     MultisigOp::LEN as u64,
                     &context.program_id,
                 ),
                 system_instruction::create_account(
                     &context.pt_context.payer.pubkey(),
-                    &multisig_data_account.pubkey(),
-                    multisig_data_account_rent,
+                    &other_multisig_data_account.pubkey(),
+                    rent.minimum_balance(DAppMultisigData::LEN),
                     DAppMultisigData::LEN as u64,
                     &context.program_id,
                 ),
                 init_dapp_transaction(
                     &context.program_id,
                     &context.wallet_account.pubkey(),
-                    &multisig_op_account.pubkey(),
-                    &multisig_data_account.pubkey(),
+                    &other_multisig_op_account.pubkey(),
+                    &other_multisig_data_account.pubkey(),
                     &context.initiator_account.pubkey(),
                     &context.pt_context.payer.pubkey(),
                     &context.balance_account_guid_hash,
-                    context.allowed_dapp,
-                    inner_instructions.len().as_u8(),
+                    dapp,
+                    1,
                 ),
             ],
             Some(&context.pt_context.payer.pubkey()),
             &[
                 &context.pt_context.payer,
-                &multisig_op_account,
-                &multisig_data_account,
+                &other_multisig_op_account,
+                &other_multisig_data_account,
                 &context.initiator_account,
             ],
             context.pt_context.last_blockhash,
         ))
         .await
         .unwrap();
+
+    assert_eq!(
+        supply_instructions(
+            context,
+            &dapp_test.multisig_op_account,
+            &other_multisig_data_account,
+            0,
+            &vec![dapp_test.inner_instructions[0].clone()],
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            Custom(WalletError::IncorrectMultisigDataAccount as u32)
+        ),
+    );
+
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[finalize_dapp_transaction(
+                    &context.program_id,
+                    &context.wallet_account.pubkey(),
+                    &dapp_test.multisig_op_account.pubkey(),
+                    &other_multisig_data_account.pubkey(),
+                    &dapp_test.balance_account,
+                    &context.pt_context.payer.pubkey(),
+                    &context.balance_account_guid_hash,
+                    &dapp_test.params_hash,
+                    &dapp_test.inner_instructions,
+                    None,
+                )],
+                Some(&context.pt_context.payer.pubkey()),
+                &[
+                    &context.pt_context.payer,
+                    &context.initiator_account,
+                    &dapp_test.inner_multisig_op_account,
+                ],
+                context.pt_context.last_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            Custom(WalletError::IncorrectMultisigDataAccount as u32)
+        ),
+    );
 }
 
 #[tokio::test]
-async fn test_supply_instruction_errors() {
+async fn test_dapp_transaction_unwhitelisted() {
     let (mut context, balance_account) =
-        utils::setup_balance_account_tests_and_finalize(Some(100000)).await;
+        utils::setup_balance_account_tests_and_finalize(None).await;
 
     account_settings_update(
         &mut context,
-        Some(BooleanSetting::Off),
         Some(BooleanSetting::On),
+        Some(BooleanSetting::On),
+        None,
+        None,
         None,
         None,
         None,
@@ -945,19 +1136,652 @@ async fn test_supply_instruction_errors() {
     )
     .await;
 
-    let multisig_op_account_rent = context.rent.minimum_balance(MultisigOp::LEN);
+    let rent = context.pt_context.banks_client.get_rent().await.unwrap();
+    let multisig_op_account_rent = rent.minimum_balance(MultisigOp::LEN);
     let multisig_op_account = Keypair::new();
-    let multisig_data_account_rent = context.rent.minimum_balance(DAppMultisigData::LEN);
+    let multisig_data_account_rent = rent.minimum_balance(DAppMultisigData::LEN);
     let multisig_data_account = Keypair::new();
-    let inner_multisig_op_account = Keypair::new();
     let dapp = DAppBookEntry {
         address: context.program_id.clone(),
         name_hash: DAppBookEntryNameHash::new(&hash_of(b"Strike Wallet")),
     };
-
     let inner_instructions = inner_instructions(
         &mut context,
-        &inner_multisig_op_account.pubkey(),
+        &multisig_op_account.pubkey(),
+        &balance_account,
+        123,
+    )
+    .await;
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[
+                    system_instruction::create_account(
+                        &context.pt_context.payer.pubkey(),
+                        &multisig_op_account.pubkey(),
+                        multisig_op_account_rent,
+                        MultisigOp::LEN as u64,
+                        &context.program_id,
+                    ),
+                    system_instruction::create_account(
+                        &context.pt_context.payer.pubkey(),
+                        &multisig_data_account.pubkey(),
+                        multisig_data_account_rent,
+                        DAppMultisigData::LEN as u64,
+                        &context.program_id,
+                    ),
+                    init_dapp_transaction(
+                        &context.program_id,
+                        &context.wallet_account.pubkey(),
+                        &multisig_op_account.pubkey(),
+                        &multisig_data_account.pubkey(),
+                        &context.initiator_account.pubkey(),
+                        &context.pt_context.payer.pubkey(),
+                        &context.balance_account_guid_hash,
+                        dapp,
+                        inner_instructions.len().as_u8(),
+                    ),
+                ],
+                Some(&context.pt_context.payer.pubkey()),
+                &[
+                    &context.pt_context.payer,
+                    &multisig_op_account,
+                    &multisig_data_account,
+                    &context.initiator_account,
+                ],
+                context.pt_context.last_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(2, Custom(WalletError::DAppNotAllowed as u32)),
+    );
+}
+
+#[tokio::test]
+async fn test_dapp_transaction_whitelisted() {
+    let (mut context, balance_account) =
+        utils::setup_balance_account_tests_and_finalize(None).await;
+
+    account_settings_update(
+        &mut context,
+        Some(BooleanSetting::On),
+        Some(BooleanSetting::On),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+    .await;
+
+    let multisig_op_account_rent = context.rent.minimum_balance(MultisigOp::LEN);
+    let multisig_op_account = Keypair::new();
+    let multisig_data_account_rent = context.rent.minimum_balance(DAppMultisigData::LEN);
+    let multisig_data_account = Keypair::new();
+    let inner_instructions = inner_instructions(
+        &mut context,
+        &multisig_op_account.pubkey(),
+        &balance_account,
+        123,
+    )
+    .await;
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                system_instruction::create_account(
+                    &context.pt_context.payer.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    multisig_op_account_rent,
+                    MultisigOp::LEN as u64,
+                    &context.program_id,
+                ),
+                system_instruction::create_account(
+                    &context.pt_context.payer.pubkey(),
+                    &multisig_data_account.pubkey(),
+                    multisig_data_account_rent,
+                    DAppMultisigData::LEN as u64,
+                    &context.program_id,
+                ),
+                init_dapp_transaction(
+                    &context.program_id,
+                    &context.wallet_account.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    &multisig_data_account.pubkey(),
+                    &context.initiator_account.pubkey(),
+                    &context.pt_context.payer.pubkey(),
+                    &context.balance_account_guid_hash,
+                    context.allowed_dapp,
+                    inner_instructions.len().as_u8(),
+                ),
+            ],
+            Some(&context.pt_context.payer.pubkey()),
+            &[
+                &context.pt_context.payer,
+                &multisig_op_account,
+                &multisig_data_account,
+                &context.initiator_account,
+            ],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // instructions for the allowed dapp's program can be supplied
+    supply_instructions(
+        &mut context,
+        &multisig_op_account,
+        &multisig_data_account,
+        0,
+        &vec![Instruction::new_with_bytes(
+            context.allowed_dapp.address,
+            &[],
+            vec![],
+        )],
+    )
+    .await
+    .unwrap();
+
+    // but not instructions for a program that is not in the dapp book
+    assert_eq!(
+        supply_instructions(
+            &mut context,
+            &multisig_op_account,
+            &multisig_data_account,
+            1,
+            &vec![inner_instructions[1].clone()]
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::DAppProgramNotAllowed as u32)),
+    );
+}
+
+#[tokio::test]
+async fn test_dapp_transaction_expired() {
+    let (mut context, balance_account) =
+        utils::setup_balance_account_tests_and_finalize(None).await;
+
+    account_settings_update(
+        &mut context,
+        Some(BooleanSetting::On),
+        Some(BooleanSetting::On),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+    .await;
+
+    // give the whitelisted dapp an expiry, then let it lapse
+    let now = context
+        .pt_context
+        .banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap()
+        .unix_timestamp;
+    let update = DAppBookUpdate {
+        add_dapps: vec![],
+        remove_dapps: vec![],
+        dapp_expirations: vec![(SlotId::new(0), Some(now + 3600))],
+        dapp_categories: vec![],
+        disallowed_dapp_categories: vec![],
+        dapp_instruction_limits: vec![],
+    };
+    let mut test_context = context.to_test_context();
+    let multisig_op_account = utils::init_dapp_book_update(
+        &mut test_context,
+        context.wallet_account.pubkey(),
+        &context.assistant_account,
+        update.clone(),
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut test_context,
+        &multisig_op_account,
+        vec![&context.approvers[0], &context.approvers[1]],
+    )
+    .await;
+    utils::finalize_dapp_book_update(
+        &mut test_context,
+        context.wallet_account.pubkey(),
+        multisig_op_account,
+        update,
+    )
+    .await;
+
+    advance_clock(&mut context.pt_context, Duration::from_secs(3600)).await;
+
+    let multisig_op_account_rent = context.rent.minimum_balance(MultisigOp::LEN);
+    let multisig_op_account = Keypair::new();
+    let multisig_data_account_rent = context.rent.minimum_balance(DAppMultisigData::LEN);
+    let multisig_data_account = Keypair::new();
+    let inner_instructions = inner_instructions(
+        &mut context,
+        &multisig_op_account.pubkey(),
+        &balance_account,
+        123,
+    )
+    .await;
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[
+                    system_instruction::create_account(
+                        &context.pt_context.payer.pubkey(),
+                        &multisig_op_account.pubkey(),
+                        multisig_op_account_rent,
+                        MultisigOp::LEN as u64,
+                        &context.program_id,
+                    ),
+                    system_instruction::create_account(
+                        &context.pt_context.payer.pubkey(),
+                        &multisig_data_account.pubkey(),
+                        multisig_data_account_rent,
+                        DAppMultisigData::LEN as u64,
+                        &context.program_id,
+                    ),
+                    init_dapp_transaction(
+                        &context.program_id,
+                        &context.wallet_account.pubkey(),
+                        &multisig_op_account.pubkey(),
+                        &multisig_data_account.pubkey(),
+                        &context.initiator_account.pubkey(),
+                        &context.pt_context.payer.pubkey(),
+                        &context.balance_account_guid_hash,
+                        context.allowed_dapp,
+                        inner_instructions.len().as_u8(),
+                    ),
+                ],
+                Some(&context.pt_context.payer.pubkey()),
+                &[
+                    &context.pt_context.payer,
+                    &multisig_op_account,
+                    &multisig_data_account,
+                    &context.initiator_account,
+                ],
+                context.pt_context.last_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(2, Custom(WalletError::DAppBookEntryExpired as u32)),
+    );
+}
+
+#[tokio::test]
+async fn test_dapp_transaction_disallowed_category() {
+    let (mut context, balance_account) =
+        utils::setup_balance_account_tests_and_finalize(None).await;
+
+    account_settings_update(
+        &mut context,
+        Some(BooleanSetting::On),
+        Some(BooleanSetting::On),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+    .await;
+
+    // tag the whitelisted dapp as a DEX, and keep the balance account from using DEXes
+    let update = DAppBookUpdate {
+        add_dapps: vec![],
+        remove_dapps: vec![],
+        dapp_expirations: vec![],
+        dapp_categories: vec![(SlotId::new(0), DAppCategories::DEX)],
+        disallowed_dapp_categories: vec![(
+            context.balance_account_guid_hash,
+            DAppCategories::DEX.union(DAppCategories::LENDING),
+        )],
+        dapp_instruction_limits: vec![],
+    };
+    let mut test_context = context.to_test_context();
+    let multisig_op_account = utils::init_dapp_book_update(
+        &mut test_context,
+        context.wallet_account.pubkey(),
+        &context.assistant_account,
+        update.clone(),
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut test_context,
+        &multisig_op_account,
+        vec![&context.approvers[0], &context.approvers[1]],
+    )
+    .await;
+    utils::finalize_dapp_book_update(
+        &mut test_context,
+        context.wallet_account.pubkey(),
+        multisig_op_account,
+        update,
+    )
+    .await;
+
+    let multisig_op_account_rent = context.rent.minimum_balance(MultisigOp::LEN);
+    let multisig_op_account = Keypair::new();
+    let multisig_data_account_rent = context.rent.minimum_balance(DAppMultisigData::LEN);
+    let multisig_data_account = Keypair::new();
+    let inner_instructions = inner_instructions(
+        &mut context,
+        &multisig_op_account.pubkey(),
+        &balance_account,
+        123,
+    )
+    .await;
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[
+                    system_instruction::create_account(
+                        &context.pt_context.payer.pubkey(),
+                        &multisig_op_account.pubkey(),
+                        multisig_op_account_rent,
+                        MultisigOp::LEN as u64,
+                        &context.program_id,
+                    ),
+                    system_instruction::create_account(
+                        &context.pt_context.payer.pubkey(),
+                        &multisig_data_account.pubkey(),
+                        multisig_data_account_rent,
+                        DAppMultisigData::LEN as u64,
+                        &context.program_id,
+                    ),
+                    init_dapp_transaction(
+                        &context.program_id,
+                        &context.wallet_account.pubkey(),
+                        &multisig_op_account.pubkey(),
+                        &multisig_data_account.pubkey(),
+                        &context.initiator_account.pubkey(),
+                        &context.pt_context.payer.pubkey(),
+                        &context.balance_account_guid_hash,
+                        context.allowed_dapp,
+                        inner_instructions.len().as_u8(),
+                    ),
+                ],
+                Some(&context.pt_context.payer.pubkey()),
+                &[
+                    &context.pt_context.payer,
+                    &multisig_op_account,
+                    &multisig_data_account,
+                    &context.initiator_account,
+                ],
+                context.pt_context.last_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(2, Custom(WalletError::DAppCategoryNotAllowed as u32)),
+    );
+}
+
+#[tokio::test]
+async fn test_dapp_transaction_over_instruction_limit() {
+    let (mut context, balance_account) =
+        utils::setup_balance_account_tests_and_finalize(None).await;
+
+    account_settings_update(
+        &mut context,
+        Some(BooleanSetting::On),
+        Some(BooleanSetting::On),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+    .await;
+
+    // limit the whitelisted dapp to fewer instructions than the transaction has
+    let update = DAppBookUpdate {
+        add_dapps: vec![],
+        remove_dapps: vec![],
+        dapp_expirations: vec![],
+        dapp_categories: vec![],
+        disallowed_dapp_categories: vec![],
+        dapp_instruction_limits: vec![(SlotId::new(0), Some(1))],
+    };
+    let mut test_context = context.to_test_context();
+    let multisig_op_account = utils::init_dapp_book_update(
+        &mut test_context,
+        context.wallet_account.pubkey(),
+        &context.assistant_account,
+        update.clone(),
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut test_context,
+        &multisig_op_account,
+        vec![&context.approvers[0], &context.approvers[1]],
+    )
+    .await;
+    utils::finalize_dapp_book_update(
+        &mut test_context,
+        context.wallet_account.pubkey(),
+        multisig_op_account,
+        update,
+    )
+    .await;
+
+    let multisig_op_account_rent = context.rent.minimum_balance(MultisigOp::LEN);
+    let multisig_op_account = Keypair::new();
+    let multisig_data_account_rent = context.rent.minimum_balance(DAppMultisigData::LEN);
+    let multisig_data_account = Keypair::new();
+    let inner_instructions = inner_instructions(
+        &mut context,
+        &multisig_op_account.pubkey(),
+        &balance_account,
+        123,
+    )
+    .await;
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[
+                    system_instruction::create_account(
+                        &context.pt_context.payer.pubkey(),
+                        &multisig_op_account.pubkey(),
+                        multisig_op_account_rent,
+                        MultisigOp::LEN as u64,
+                        &context.program_id,
+                    ),
+                    system_instruction::create_account(
+                        &context.pt_context.payer.pubkey(),
+                        &multisig_data_account.pubkey(),
+                        multisig_data_account_rent,
+                        DAppMultisigData::LEN as u64,
+                        &context.program_id,
+                    ),
+                    init_dapp_transaction(
+                        &context.program_id,
+                        &context.wallet_account.pubkey(),
+                        &multisig_op_account.pubkey(),
+                        &multisig_data_account.pubkey(),
+                        &context.initiator_account.pubkey(),
+                        &context.pt_context.payer.pubkey(),
+                        &context.balance_account_guid_hash,
+                        context.allowed_dapp,
+                        inner_instructions.len().as_u8(),
+                    ),
+                ],
+                Some(&context.pt_context.payer.pubkey()),
+                &[
+                    &context.pt_context.payer,
+                    &multisig_op_account,
+                    &multisig_data_account,
+                    &context.initiator_account,
+                ],
+                context.pt_context.last_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(2, Custom(WalletError::DAppInstructionTooLarge as u32)),
+    );
+}
+
+#[tokio::test]
+async fn test_dapp_transaction_suspended() {
+    let (mut context, balance_account) =
+        utils::setup_balance_account_tests_and_finalize(None).await;
+
+    account_settings_update(
+        &mut context,
+        Some(BooleanSetting::On),
+        Some(BooleanSetting::On),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+    .await;
+
+    let suspend_transaction = |context: &BalanceAccountTestContext, approver: &Keypair| {
+        Transaction::new_signed_with_payer(
+            &[suspend_dapp(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                &approver.pubkey(),
+                context.allowed_dapp,
+            )],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer, approver],
+            context.pt_context.last_blockhash,
+        )
+    };
+
+    // only a config approver can suspend a dapp
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .process_transaction(suspend_transaction(&context, &context.assistant_account))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::InvalidApprover as u32)),
+    );
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(suspend_transaction(&context, &context.approvers[0]))
+        .await
+        .unwrap();
+
+    let multisig_op_account_rent = context.rent.minimum_balance(MultisigOp::LEN);
+    let multisig_op_account = Keypair::new();
+    let multisig_data_account_rent = context.rent.minimum_balance(DAppMultisigData::LEN);
+    let multisig_data_account = Keypair::new();
+    let inner_instructions = inner_instructions(
+        &mut context,
+        &multisig_op_account.pubkey(),
+        &balance_account,
+        123,
+    )
+    .await;
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[
+                    system_instruction::create_account(
+                        &context.pt_context.payer.pubkey(),
+                        &multisig_op_account.pubkey(),
+                        multisig_op_account_rent,
+                        MultisigOp::LEN as u64,
+                        &context.program_id,
+                    ),
+                    system_instruction::create_account(
+                        &context.pt_context.payer.pubkey(),
+                        &multisig_data_account.pubkey(),
+                        multisig_data_account_rent,
+                        DAppMultisigData::LEN as u64,
+                        &context.program_id,
+                    ),
+                    init_dapp_transaction(
+                        &context.program_id,
+                        &context.wallet_account.pubkey(),
+                        &multisig_op_account.pubkey(),
+                        &multisig_data_account.pubkey(),
+                        &context.initiator_account.pubkey(),
+                        &context.pt_context.payer.pubkey(),
+                        &context.balance_account_guid_hash,
+                        context.allowed_dapp,
+                        inner_instructions.len().as_u8(),
+                    ),
+                ],
+                Some(&context.pt_context.payer.pubkey()),
+                &[
+                    &context.pt_context.payer,
+                    &multisig_op_account,
+                    &multisig_data_account,
+                    &context.initiator_account,
+                ],
+                context.pt_context.last_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(2, Custom(WalletError::DAppBookEntryExpired as u32)),
+    );
+}
+
+#[tokio::test]
+async fn test_supply_instruction_errors() {
+    let (mut context, balance_account) =
+        utils::setup_balance_account_tests_and_finalize(Some(100000)).await;
+
+    account_settings_update(
+        &mut context,
+        Some(BooleanSetting::Off),
+        Some(BooleanSetting::On),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+    .await;
+
+    let multisig_op_account_rent = context.rent.minimum_balance(MultisigOp::LEN);
+    let multisig_op_account = Keypair::new();
+    let multisig_data_account_rent = context.rent.minimum_balance(DAppMultisigData::LEN);
+    let multisig_data_account = Keypair::new();
+    let inner_multisig_op_account = Keypair::new();
+    let dapp = DAppBookEntry {
+        address: context.program_id.clone(),
+        name_hash: DAppBookEntryNameHash::new(&hash_of(b"Strike Wallet")),
+    };
+
+    let inner_instructions = inner_instructions(
+        &mut context,
+        &inner_multisig_op_account.pubkey(),
         &balance_account,
         123,
     )
@@ -1074,6 +1898,7 @@ async fn supply_instructions(
         .process_transaction(Transaction::new_signed_with_payer(
             &[supply_dapp_transaction_instructions(
                 &context.program_id,
+                &context.wallet_account.pubkey(),
                 &multisig_op_account.pubkey(),
                 &multisig_data_account.pubkey(),
                 &context.initiator_account.pubkey(),
@@ -1100,6 +1925,8 @@ async fn test_multisig_op_version_mismatch() {
         None,
         None,
         None,
+        None,
+        None,
     )
     .await;
 
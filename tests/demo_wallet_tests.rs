@@ -0,0 +1,123 @@
+#![cfg(all(feature = "test-bpf", feature = "demo"))]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use solana_program::instruction::Instruction;
+use solana_program::instruction::InstructionError::Custom;
+use solana_program::system_instruction;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::{Transaction, TransactionError};
+use std::time::Duration;
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::{DemoBalanceAccount, InitialWalletConfig};
+use strike_wallet::model::balance_account::{
+    BalanceAccount, BalanceAccountGuidHash, BalanceAccountNameHash,
+};
+use strike_wallet::model::wallet::{Wallet, WalletGuidHash};
+use strike_wallet::utils::SlotId;
+use uuid::Uuid;
+
+fn demo_balance_account(slot: usize, lamports: u64) -> DemoBalanceAccount {
+    DemoBalanceAccount {
+        guid_hash: BalanceAccountGuidHash::new(&hash_of(Uuid::new_v4().as_bytes())),
+        name_hash: BalanceAccountNameHash::new(&hash_of(Uuid::new_v4().as_bytes())),
+        slot_id: SlotId::new(slot),
+        address_book_slot_id: SlotId::new(slot),
+        lamports,
+    }
+}
+
+#[tokio::test]
+async fn test_init_demo_wallet() {
+    let mut context = setup_test(40_000).await;
+
+    let wallet_account = Keypair::new();
+    let assistant_account = Keypair::new();
+    let wallet_guid_hash = WalletGuidHash::new(&hash_of(Uuid::new_v4().as_bytes()));
+    let approvers = vec![Keypair::new(), Keypair::new()];
+    let initial_config = InitialWalletConfig {
+        approvals_required_for_config: 2,
+        approval_timeout_for_config: Duration::from_secs(3600),
+        signers: vec![
+            (SlotId::new(0), approvers[0].pubkey_as_signer()),
+            (SlotId::new(1), approvers[1].pubkey_as_signer()),
+        ],
+        config_approvers: vec![SlotId::new(0), SlotId::new(1)],
+    };
+    let balance_accounts = vec![
+        demo_balance_account(0, 1_000_000),
+        demo_balance_account(1, 0),
+    ];
+
+    let demo_wallet_transaction = |context: &TestContext, instruction: Instruction| {
+        Transaction::new_signed_with_payer(
+            &[
+                system_instruction::create_account(
+                    &context.payer.pubkey(),
+                    &wallet_account.pubkey(),
+                    context.rent.minimum_balance(Wallet::LEN),
+                    Wallet::LEN as u64,
+                    &context.program_id,
+                ),
+                instruction,
+            ],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &wallet_account, &assistant_account],
+            context.recent_blockhash,
+        )
+    };
+    let instruction = init_demo_wallet(
+        &context.program_id,
+        &wallet_account.pubkey(),
+        &assistant_account.pubkey(),
+        &context.payer.pubkey(),
+        wallet_guid_hash,
+        initial_config,
+        balance_accounts.clone(),
+    );
+
+    // the balance accounts have to be passed in the order they are given in
+    let mut mismatched = instruction.clone();
+    mismatched.accounts.swap(4, 5);
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(demo_wallet_transaction(&context, mismatched))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::InvalidPDA as u32)),
+    );
+
+    context
+        .banks_client
+        .process_transaction(demo_wallet_transaction(&context, instruction))
+        .await
+        .unwrap();
+
+    let wallet = get_wallet(&mut context.banks_client, &wallet_account.pubkey()).await;
+    assert_eq!(wallet.approvals_required_for_config, 2);
+    for demo_account in balance_accounts.iter() {
+        let balance_account = wallet.get_balance_account(&demo_account.guid_hash).unwrap();
+        assert_eq!(balance_account.name_hash, demo_account.name_hash);
+        assert_eq!(balance_account.approvals_required_for_transfer, 2);
+        assert_eq!(
+            wallet.get_transfer_approvers_keys(&balance_account),
+            vec![approvers[0].pubkey(), approvers[1].pubkey()]
+        );
+
+        let (address, _) = BalanceAccount::find_address(
+            &wallet_guid_hash,
+            &demo_account.guid_hash,
+            &context.program_id,
+        );
+        assert_eq!(
+            context.banks_client.get_balance(address).await.unwrap(),
+            demo_account.lamports
+        );
+    }
+}
@@ -0,0 +1,189 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use solana_program::instruction::{Instruction, InstructionError::Custom};
+use solana_program::pubkey::Pubkey;
+use solana_program::{system_instruction, system_program};
+use solana_program_test::{tokio, BanksClientError};
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::Transaction;
+
+use strike_wallet::error::WalletError;
+use strike_wallet::model::balance_account::BalanceAccount;
+use strike_wallet::model::multisig_op::{
+    ApprovalDisposition, BooleanSetting, OperationDisposition,
+};
+
+async fn process(
+    context: &mut BalanceAccountTestContext,
+    instructions: &[Instruction],
+) -> Result<(), BanksClientError> {
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            instructions,
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+}
+
+async fn get_balance(context: &mut BalanceAccountTestContext, account: Pubkey) -> u64 {
+    context
+        .pt_context
+        .banks_client
+        .get_balance(account)
+        .await
+        .unwrap()
+}
+
+async fn enable_deposit_addresses(context: &mut BalanceAccountTestContext) {
+    let wallet = context.wallet_account.pubkey();
+    let rent_return = context.pt_context.payer.pubkey();
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let mut test_context = context.to_test_context();
+    init_multisig_op(
+        &mut test_context,
+        multisig_op_account,
+        init_enable_deposit_addresses(
+            &context.program_id,
+            &wallet,
+            &multisig_op_pubkey,
+            &context.approvers[0].pubkey(),
+            &rent_return,
+            context.balance_account_guid_hash,
+            BooleanSetting::On,
+        ),
+        &context.approvers[0],
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut test_context,
+        &multisig_op_pubkey,
+        vec![&context.approvers[0], &context.approvers[1]],
+    )
+    .await;
+    finalize_multisig_op(
+        &mut test_context,
+        multisig_op_pubkey,
+        finalize_enable_deposit_addresses(
+            &context.program_id,
+            &wallet,
+            &multisig_op_pubkey,
+            &rent_return,
+            context.balance_account_guid_hash,
+            BooleanSetting::On,
+        ),
+    )
+    .await;
+    assert!(get_wallet(&mut context.pt_context.banks_client, &wallet)
+        .await
+        .get_balance_account(&context.balance_account_guid_hash)
+        .unwrap()
+        .are_deposit_addresses_enabled());
+}
+
+#[tokio::test]
+async fn test_transfer_sweeps_deposit_addresses() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let initiator = Keypair::from_bytes(&context.approvers[2].to_bytes()).unwrap();
+    let rent = context
+        .pt_context
+        .banks_client
+        .get_rent()
+        .await
+        .unwrap()
+        .minimum_balance(0);
+    let (deposit_address, _) = BalanceAccount::find_deposit_address(
+        &context.wallet_guid_hash,
+        &context.balance_account_guid_hash,
+        3,
+        &context.program_id,
+    );
+
+    // the balance account only has its rent, and the transfer is funded by a deposit
+    let payer = context.pt_context.payer.pubkey();
+    process(
+        &mut context,
+        &[
+            system_instruction::transfer(&payer, &balance_account, rent),
+            system_instruction::transfer(&payer, &deposit_address, rent * 2),
+        ],
+    )
+    .await
+    .unwrap();
+
+    let (multisig_op_account, result) =
+        setup_transfer_test(&mut context, &initiator, &balance_account, None, rent).await;
+    result.unwrap();
+    approve_or_deny_n_of_n_multisig_op(
+        &mut context.pt_context.banks_client,
+        &context.program_id,
+        &multisig_op_account.pubkey(),
+        vec![&context.approvers[0], &context.approvers[1]],
+        &context.pt_context.payer,
+        context.pt_context.last_blockhash,
+        ApprovalDisposition::APPROVE,
+        OperationDisposition::APPROVED,
+    )
+    .await;
+
+    let finalize = finalize_with_deposit_sweep(
+        finalize_transfer(
+            &context.program_id,
+            &multisig_op_account.pubkey(),
+            &context.wallet_account.pubkey(),
+            &balance_account,
+            &context.destination.pubkey(),
+            &payer,
+            context.balance_account_guid_hash,
+            rent,
+            &system_program::id(),
+            None,
+            None,
+            None,
+        ),
+        &context.wallet_guid_hash,
+        &context.balance_account_guid_hash,
+        &[3],
+        None,
+    );
+
+    // nothing is swept from a balance account's deposit addresses until they are enabled
+    assert_instruction_error(
+        process(&mut context, &[finalize.clone()]).await,
+        0,
+        Custom(WalletError::DepositAddressesNotEnabled as u32),
+    );
+
+    enable_deposit_addresses(&mut context).await;
+
+    // nor from the deposit address of another index than the one given
+    let mut mismatched = finalize.clone();
+    mismatched.accounts.last_mut().unwrap().pubkey = BalanceAccount::find_deposit_address(
+        &context.wallet_guid_hash,
+        &context.balance_account_guid_hash,
+        4,
+        &context.program_id,
+    )
+    .0;
+    assert_instruction_error(
+        process(&mut context, &[mismatched]).await,
+        0,
+        Custom(WalletError::InvalidPDA as u32),
+    );
+
+    process(&mut context, &[finalize]).await.unwrap();
+    assert_eq!(get_balance(&mut context, deposit_address).await, 0);
+    assert_eq!(get_balance(&mut context, balance_account).await, rent * 2);
+    let destination = context.destination.pubkey();
+    assert_eq!(get_balance(&mut context, destination).await, rent);
+}
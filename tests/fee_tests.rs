@@ -5,16 +5,20 @@ mod common;
 pub use common::instructions::*;
 pub use common::utils::*;
 
+use solana_program::instruction::InstructionError::Custom;
 use solana_program::system_instruction;
 use solana_sdk::signer::Signer;
 use solana_sdk::transaction::Transaction;
 use std::borrow::BorrowMut;
 use std::option::Option::None;
 use std::time::Duration;
+use strike_wallet::error::WalletError;
 use strike_wallet::instruction::InitialWalletConfig;
 use strike_wallet::model::balance_account::{BalanceAccount, BalanceAccountGuidHash};
+use strike_wallet::model::fee_policy::FeePolicy;
 use strike_wallet::model::multisig_op::{BooleanSetting, SlotUpdateType};
 use strike_wallet::model::wallet::Signers;
+use strike_wallet::model::wallet_freeze::WalletFreeze;
 use strike_wallet::utils::SlotId;
 use {solana_program_test::tokio, solana_sdk::signature::Keypair};
 
@@ -89,6 +93,8 @@ async fn test_fee_collection() {
         Some(BooleanSetting::On),
         None,
         None,
+        None,
+        None,
         Some(5_000_000),
         fee_account_guid_hash,
         None,
@@ -111,6 +117,8 @@ async fn test_fee_collection() {
         Some(BooleanSetting::Off),
         None,
         None,
+        None,
+        None,
         Some(5_000_000),
         fee_account_guid_hash,
         Some(4109120),
@@ -130,6 +138,8 @@ async fn test_fee_collection() {
         Some(BooleanSetting::On),
         None,
         None,
+        None,
+        None,
         Some(5_000_000),
         fee_account_guid_hash,
         Some(0),
@@ -143,3 +153,106 @@ async fn test_fee_collection() {
         .unwrap();
     assert_eq!(balance, 890880);
 }
+
+async fn update_fee_policy(context: &mut BalanceAccountTestContext, fee_policy: FeePolicy) {
+    let wallet = context.wallet_account.pubkey();
+    let rent_return = context.pt_context.payer.pubkey();
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let mut test_context = context.to_test_context();
+    init_multisig_op(
+        &mut test_context,
+        multisig_op_account,
+        init_fee_policy_update(
+            &context.program_id,
+            &wallet,
+            &multisig_op_pubkey,
+            &context.approvers[0].pubkey(),
+            &rent_return,
+            fee_policy,
+        ),
+        &context.approvers[0],
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut test_context,
+        &multisig_op_pubkey,
+        vec![&context.approvers[0], &context.approvers[1]],
+    )
+    .await;
+    finalize_multisig_op(
+        &mut test_context,
+        multisig_op_pubkey,
+        finalize_fee_policy_update(
+            &context.program_id,
+            &wallet,
+            &multisig_op_pubkey,
+            &rent_return,
+            fee_policy,
+        ),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_fee_cap() {
+    let mut context = setup_fee_tests().await;
+    let fee_policy = FeePolicy {
+        max_fee_amount: Some(1_000_000),
+    };
+    update_fee_policy(&mut context, fee_policy).await;
+    assert_eq!(
+        get_wallet(
+            &mut context.pt_context.banks_client,
+            &context.wallet_account.pubkey()
+        )
+        .await
+        .fee_policy,
+        fee_policy
+    );
+
+    let init_freeze_with_fee = |multisig_op_account: &Keypair, fee_amount: u64| {
+        with_fee(
+            init_wallet_freeze(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                &multisig_op_account.pubkey(),
+                &context.approvers[0].pubkey(),
+                &context.pt_context.payer.pubkey(),
+                WalletFreeze {
+                    frozen: false,
+                    emergency_quorum: 2,
+                },
+            ),
+            fee_amount,
+            Some(context.balance_account_guid_hash),
+        )
+        .unwrap()
+    };
+
+    // an op can't be initiated with a fee above the cap
+    let multisig_op_account = Keypair::new();
+    let init = init_freeze_with_fee(&multisig_op_account, 1_000_001);
+    verify_multisig_op_init_fails(
+        &mut context.pt_context.banks_client,
+        context.pt_context.last_blockhash,
+        &context.pt_context.payer,
+        &context.approvers[0],
+        &multisig_op_account,
+        init,
+        Custom(WalletError::FeeExceedsCap as u32),
+    )
+    .await;
+
+    let multisig_op_account = Keypair::new();
+    let init = init_freeze_with_fee(&multisig_op_account, 1_000_000);
+    init_multisig_op(
+        &mut context.to_test_context(),
+        multisig_op_account,
+        init,
+        &context.approvers[0],
+    )
+    .await
+    .unwrap();
+}
@@ -0,0 +1,225 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use std::borrow::BorrowMut;
+use std::time::Duration;
+
+use solana_program::instruction::InstructionError::Custom;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_instruction;
+use solana_program_test::BanksClientError;
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::InitialWalletConfig;
+use strike_wallet::model::finalize_submitter_policy::{
+    FinalizeSubmitterPolicy, FinalizeSubmitters,
+};
+use strike_wallet::model::multisig_op::{ApprovalDisposition, MultisigOp, OperationDisposition};
+use strike_wallet::utils::SlotId;
+use {
+    solana_program::program_pack::Pack,
+    solana_program_test::tokio,
+    solana_sdk::{
+        signature::{Keypair, Signer as SdkSigner},
+        transaction::Transaction,
+    },
+};
+
+async fn init_and_approve_policy_update(
+    context: &mut WalletTestContext,
+    approvers: &Vec<Keypair>,
+    rent_return: &Keypair,
+    policy: &FinalizeSubmitterPolicy,
+) -> Pubkey {
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::create_account(
+                &context.payer.pubkey(),
+                &multisig_op_pubkey,
+                context.rent.minimum_balance(MultisigOp::LEN),
+                MultisigOp::LEN as u64,
+                &context.program_id,
+            ),
+            init_finalize_submitter_policy_update(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                &multisig_op_pubkey,
+                &approvers[0].pubkey(),
+                &rent_return.pubkey(),
+                policy,
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[
+            &context.payer,
+            &multisig_op_account,
+            &approvers[0],
+            rent_return,
+        ],
+        context.recent_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    approve_or_deny_n_of_n_multisig_op(
+        context.banks_client.borrow_mut(),
+        &context.program_id,
+        &multisig_op_pubkey,
+        approvers.iter().collect(),
+        &context.payer,
+        context.recent_blockhash,
+        ApprovalDisposition::APPROVE,
+        OperationDisposition::APPROVED,
+    )
+    .await;
+
+    multisig_op_pubkey
+}
+
+async fn finalize_policy_update(
+    context: &mut WalletTestContext,
+    multisig_op_account: &Pubkey,
+    rent_return: &Keypair,
+    policy: &FinalizeSubmitterPolicy,
+) -> Result<(), BanksClientError> {
+    let transaction = Transaction::new_signed_with_payer(
+        &[finalize_finalize_submitter_policy_update(
+            &context.program_id,
+            &context.wallet_account.pubkey(),
+            multisig_op_account,
+            &rent_return.pubkey(),
+            policy,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, rent_return],
+        context.recent_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await
+}
+
+#[tokio::test]
+async fn test_finalize_submitter_policy() {
+    let approvers = vec![Keypair::new(), Keypair::new()];
+    let mut context = setup_wallet_test(
+        40_000,
+        InitialWalletConfig {
+            approvals_required_for_config: 2,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: vec![
+                (SlotId::new(0), approvers[0].pubkey_as_signer()),
+                (SlotId::new(1), approvers[1].pubkey_as_signer()),
+            ],
+            config_approvers: vec![SlotId::new(0), SlotId::new(1)],
+        },
+    )
+    .await;
+    let payer = Keypair::from_bytes(&context.payer.to_bytes()).unwrap();
+
+    // new wallets let anyone finalize
+    let wallet = get_wallet(&mut context.banks_client, &context.wallet_account.pubkey()).await;
+    assert_eq!(
+        wallet.finalize_submitter_policy,
+        FinalizeSubmitterPolicy::default()
+    );
+
+    // restrict config ops to a named submitter
+    let submitter = Keypair::new();
+    let named_policy = FinalizeSubmitterPolicy {
+        config: FinalizeSubmitters::Named,
+        transfer: FinalizeSubmitters::Approvers,
+        dapp: FinalizeSubmitters::Anyone,
+        named_submitters: vec![submitter.pubkey()],
+    };
+    let op = init_and_approve_policy_update(&mut context, &approvers, &payer, &named_policy).await;
+    finalize_policy_update(&mut context, &op, &payer, &named_policy)
+        .await
+        .unwrap();
+    let wallet = get_wallet(&mut context.banks_client, &context.wallet_account.pubkey()).await;
+    assert_eq!(wallet.finalize_submitter_policy, named_policy);
+
+    // the payer is no longer allowed to finalize config ops
+    let default_policy = FinalizeSubmitterPolicy::default();
+    let op =
+        init_and_approve_policy_update(&mut context, &approvers, &payer, &default_policy).await;
+    assert_eq!(
+        finalize_policy_update(&mut context, &op, &payer, &default_policy)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        solana_sdk::transaction::TransactionError::InstructionError(
+            0,
+            Custom(WalletError::FinalizeSubmitterNotAllowed as u32)
+        ),
+    );
+
+    // but the named submitter is
+    let op =
+        init_and_approve_policy_update(&mut context, &approvers, &submitter, &default_policy).await;
+    finalize_policy_update(&mut context, &op, &submitter, &default_policy)
+        .await
+        .unwrap();
+    let wallet = get_wallet(&mut context.banks_client, &context.wallet_account.pubkey()).await;
+    assert_eq!(wallet.finalize_submitter_policy, default_policy);
+}
+
+#[tokio::test]
+async fn test_finalize_submitter_policy_requires_named_submitters() {
+    let approvers = vec![Keypair::new()];
+    let mut context = setup_wallet_test(
+        40_000,
+        InitialWalletConfig {
+            approvals_required_for_config: 1,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: vec![(SlotId::new(0), approvers[0].pubkey_as_signer())],
+            config_approvers: vec![SlotId::new(0)],
+        },
+    )
+    .await;
+
+    let multisig_op_account = Keypair::new();
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::create_account(
+                &context.payer.pubkey(),
+                &multisig_op_account.pubkey(),
+                context.rent.minimum_balance(MultisigOp::LEN),
+                MultisigOp::LEN as u64,
+                &context.program_id,
+            ),
+            init_finalize_submitter_policy_update(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                &multisig_op_account.pubkey(),
+                &approvers[0].pubkey(),
+                &context.payer.pubkey(),
+                &FinalizeSubmitterPolicy {
+                    transfer: FinalizeSubmitters::Named,
+                    ..FinalizeSubmitterPolicy::default()
+                },
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &multisig_op_account, &approvers[0]],
+        context.recent_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        solana_sdk::transaction::TransactionError::InstructionError(
+            1,
+            Custom(WalletError::InvalidFinalizeSubmitterPolicy as u32)
+        ),
+    );
+}
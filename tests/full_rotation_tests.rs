@@ -0,0 +1,87 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+
+use strike_wallet::instruction::FullRotation;
+use strike_wallet::model::assistant::Assistant;
+use strike_wallet::utils::SlotId;
+
+#[tokio::test]
+async fn test_full_rotation() {
+    let (mut context, _) = setup_balance_account_tests_and_finalize(None).await;
+    let wallet_address = context.wallet_account.pubkey();
+    let rent_return = context.pt_context.payer.pubkey();
+    let wallet = get_wallet(&mut context.pt_context.banks_client, &wallet_address).await;
+
+    let new_approvers = vec![Keypair::new(), Keypair::new()];
+    let rotation = FullRotation {
+        assistant: Keypair::new().pubkey(),
+        rent_return: Keypair::new().pubkey(),
+        signer_keys: vec![
+            (context.approvers[0].pubkey(), new_approvers[0].pubkey()),
+            (context.approvers[1].pubkey(), new_approvers[1].pubkey()),
+        ],
+    };
+
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let mut test_context = context.to_test_context();
+    init_multisig_op(
+        &mut test_context,
+        multisig_op_account,
+        init_full_rotation(
+            &context.program_id,
+            &wallet_address,
+            &multisig_op_pubkey,
+            &context.approvers[0].pubkey(),
+            &rent_return,
+            rotation.clone(),
+        ),
+        &context.approvers[0],
+    )
+    .await
+    .unwrap();
+    // every config approver has to approve it
+    assert_eq!(
+        get_multisig_op_data(&mut test_context.banks_client, multisig_op_pubkey)
+            .await
+            .dispositions_required,
+        wallet.get_config_approvers_keys().len() as u8
+    );
+    approve_n_of_n_multisig_op(
+        &mut test_context,
+        &multisig_op_pubkey,
+        vec![&context.approvers[0], &context.approvers[1]],
+    )
+    .await;
+    finalize_multisig_op(
+        &mut test_context,
+        multisig_op_pubkey,
+        finalize_full_rotation(
+            &context.program_id,
+            &wallet_address,
+            &multisig_op_pubkey,
+            &rent_return,
+            rotation.clone(),
+        ),
+    )
+    .await;
+
+    let rotated = get_wallet(&mut context.pt_context.banks_client, &wallet_address).await;
+    assert_eq!(
+        rotated.get_config_approvers_keys(),
+        vec![new_approvers[0].pubkey(), new_approvers[1].pubkey()]
+    );
+    assert_eq!(rotated.config_approvers, wallet.config_approvers);
+    assert_eq!(
+        rotated.assistants.filled_slots(),
+        vec![(SlotId::new(0), Assistant::new(rotation.assistant))]
+    );
+    assert_eq!(rotated.rent_return, rotation.rent_return);
+}
@@ -0,0 +1,218 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use solana_program::instruction::InstructionError::Custom;
+use solana_program::system_instruction;
+use solana_program::system_program;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::Transaction;
+
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::AddressBookUpdate;
+use strike_wallet::model::address_book::{AddressBookEntryRisk, JurisdictionCode};
+use strike_wallet::model::compliance_policy::CompliancePolicy;
+use strike_wallet::model::multisig_op::{ApprovalDisposition, OperationDisposition};
+use strike_wallet::utils::SlotId;
+
+async fn classify_destination(context: &mut BalanceAccountTestContext, risk: AddressBookEntryRisk) {
+    update_address_book(
+        context,
+        AddressBookUpdate {
+            add_address_book_entries: vec![],
+            remove_address_book_entries: vec![],
+            deprecate_address_book_entries: vec![],
+            balance_account_whitelist_updates: vec![],
+            address_book_risks: vec![(SlotId::new(0), risk)],
+            address_book_expirations: vec![],
+            address_book_entry_kinds: vec![],
+        },
+        None,
+    )
+    .await;
+}
+
+async fn update_compliance_policy(
+    context: &mut BalanceAccountTestContext,
+    policy: Option<CompliancePolicy>,
+) {
+    let mut test_context = context.to_test_context();
+    let rent_return = test_context.payer.pubkey();
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    init_multisig_op(
+        &mut test_context,
+        multisig_op_account,
+        init_compliance_policy_update(
+            &context.program_id,
+            &context.wallet_account.pubkey(),
+            &multisig_op_pubkey,
+            &context.approvers[0].pubkey(),
+            &rent_return,
+            policy,
+        ),
+        &context.approvers[0],
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut test_context,
+        &multisig_op_pubkey,
+        vec![&context.approvers[0], &context.approvers[1]],
+    )
+    .await;
+    finalize_multisig_op(
+        &mut test_context,
+        multisig_op_pubkey,
+        finalize_compliance_policy_update(
+            &context.program_id,
+            &context.wallet_account.pubkey(),
+            &multisig_op_pubkey,
+            &rent_return,
+            policy,
+        ),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_transfer_to_high_risk_destination() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let initiator = Keypair::from_bytes(&context.approvers[2].to_bytes()).unwrap();
+    let jurisdiction = JurisdictionCode::new(*b"KP").unwrap();
+    classify_destination(
+        &mut context,
+        AddressBookEntryRisk::HighRisk { jurisdiction },
+    )
+    .await;
+    assert_eq!(
+        get_wallet(
+            &mut context.pt_context.banks_client,
+            &context.wallet_account.pubkey()
+        )
+        .await
+        .address_book_risks
+        .get(SlotId::new(0)),
+        AddressBookEntryRisk::HighRisk { jurisdiction }
+    );
+
+    // there's no compliance service to approve the transfer
+    let amount = context.rent.minimum_balance(0);
+    let (_, result) =
+        setup_transfer_test(&mut context, &initiator, &balance_account, None, amount).await;
+    assert_instruction_error(
+        result,
+        1,
+        Custom(WalletError::CompliancePolicyRequired as u32),
+    );
+
+    // the service has to approve it even though it is well under the threshold
+    let service = Keypair::new();
+    update_compliance_policy(
+        &mut context,
+        Some(CompliancePolicy {
+            service: service.pubkey(),
+            threshold: u64::MAX,
+        }),
+    )
+    .await;
+    let (multisig_op_account, result) =
+        setup_transfer_test(&mut context, &initiator, &balance_account, None, amount).await;
+    result.unwrap();
+    let multisig_op = get_multisig_op_data(
+        &mut context.pt_context.banks_client,
+        multisig_op_account.pubkey(),
+    )
+    .await;
+    assert_eq!(multisig_op.compliance_service, Some(service.pubkey()));
+    assert_eq!(multisig_op.destination_jurisdiction, Some(jurisdiction));
+
+    approve_or_deny_n_of_n_multisig_op(
+        &mut context.pt_context.banks_client,
+        &context.program_id,
+        &multisig_op_account.pubkey(),
+        vec![&context.approvers[0], &context.approvers[1]],
+        &context.pt_context.payer,
+        context.pt_context.last_blockhash,
+        ApprovalDisposition::APPROVE,
+        OperationDisposition::APPROVED,
+    )
+    .await;
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                system_instruction::transfer(
+                    &context.pt_context.payer.pubkey(),
+                    &balance_account,
+                    amount * 2,
+                ),
+                compliance_approve(
+                    &context.program_id,
+                    &multisig_op_account.pubkey(),
+                    &service.pubkey(),
+                    multisig_op.params_hash.unwrap(),
+                ),
+            ],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer, &service],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // the entry's classification changing after initiation doesn't change what is recorded
+    classify_destination(&mut context, AddressBookEntryRisk::Standard).await;
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[finalize_transfer(
+                &context.program_id,
+                &multisig_op_account.pubkey(),
+                &context.wallet_account.pubkey(),
+                &balance_account,
+                &context.destination.pubkey(),
+                &context.pt_context.payer.pubkey(),
+                context.balance_account_guid_hash,
+                amount,
+                &system_program::id(),
+                None,
+                None,
+                None,
+            )],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+    let receipt = get_destination_receipt(
+        &mut context.pt_context.banks_client,
+        &context.program_id,
+        &context.wallet_account.pubkey(),
+        &context.destination.pubkey(),
+        &system_program::id(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(receipt.transfer_count, 1);
+    assert_eq!(receipt.jurisdiction, Some(jurisdiction));
+
+    // transfers to the now standard entry need no compliance approval
+    let (multisig_op_account, result) =
+        setup_transfer_test(&mut context, &initiator, &balance_account, None, amount).await;
+    result.unwrap();
+    let multisig_op = get_multisig_op_data(
+        &mut context.pt_context.banks_client,
+        multisig_op_account.pubkey(),
+    )
+    .await;
+    assert_eq!(multisig_op.compliance_service, None);
+    assert_eq!(multisig_op.destination_jurisdiction, None);
+}
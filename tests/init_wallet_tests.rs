@@ -8,15 +8,26 @@ pub use common::utils::*;
 use std::time::Duration;
 
 use solana_program::instruction::InstructionError::Custom;
-use solana_sdk::transaction::TransactionError;
+use solana_program::program_pack::Pack;
+use solana_program::system_instruction;
+use solana_sdk::transaction::{Transaction, TransactionError};
 
-use crate::common::utils;
+use crate::common::{instructions, utils};
 use itertools::Itertools;
 use strike_wallet::error::WalletError;
 use strike_wallet::instruction::InitialWalletConfig;
-use strike_wallet::model::address_book::{AddressBook, DAppBook};
+use strike_wallet::model::address_book::{
+    AddressBook, AddressBookUsage, DAppBook, DAppBookExpirations,
+};
+use strike_wallet::model::assistant::Assistant;
+use strike_wallet::model::finalize_submitter_policy::FinalizeSubmitterPolicy;
+use strike_wallet::model::multisig_op::MultisigOp;
 use strike_wallet::model::signer::Signer;
-use strike_wallet::model::wallet::{Approvers, BalanceAccounts, Signers, Wallet, WalletGuidHash};
+use strike_wallet::model::wallet::{
+    Approvers, Assistants, BalanceAccounts, PendingAddressBookEntries, Signers, Wallet,
+    WalletGuidHash,
+};
+use strike_wallet::model::wallet_registry::WalletRegistration;
 use strike_wallet::utils::SlotId;
 use strike_wallet::version::VERSION;
 use uuid::Uuid;
@@ -42,7 +53,7 @@ async fn init_wallet() {
 
     let program_id = Keypair::new().pubkey();
     let mut pt = ProgramTest::new("strike_wallet", program_id, processor!(Processor::process));
-    pt.set_compute_max_units(25_000);
+    pt.set_compute_max_units(35_000);
     let (mut banks_client, payer, recent_blockhash) = pt.start().await;
     let wallet_account = Keypair::new();
     let assistant_account = Keypair::new();
@@ -79,8 +90,15 @@ async fn init_wallet() {
             rent_return: payer.pubkey().clone(),
             wallet_guid_hash,
             signers: Signers::from_vec(signers),
-            assistant: assistant_account.pubkey_as_signer(),
+            assistants: Assistants::from_vec(vec![(
+                SlotId::new(0),
+                Assistant::new(assistant_account.pubkey())
+            )]),
             address_book: AddressBook::new(),
+            pending_address_book_entries: PendingAddressBookEntries::zero(),
+            pending_address_book_window_start: 0,
+            pending_address_book_entries_in_window: 0,
+            address_book_usage: AddressBookUsage::new(),
             approvals_required_for_config,
             approval_timeout_for_config,
             config_approvers: Approvers::from_enabled_vec(
@@ -89,10 +107,30 @@ async fn init_wallet() {
                     .map(|(slot_id, _)| slot_id)
                     .collect_vec()
             ),
+            config_policy_timelock: Wallet::DEFAULT_CONFIG_POLICY_TIMELOCK,
+            pending_config_policy: None,
             balance_accounts: BalanceAccounts::new(),
             dapp_book: DAppBook::from_vec(vec![]),
+            dapp_book_expirations: DAppBookExpirations::default(),
+            finalize_submitter_policy: FinalizeSubmitterPolicy::default(),
+            compliance_policy: None,
+            replication: None,
+            price_oracle: None,
         }
     );
+
+    // the state summary is readable without unpacking the wallet
+    let summary = Wallet::state_summary_from_slice(
+        &banks_client
+            .get_account(wallet_account.pubkey())
+            .await
+            .unwrap()
+            .unwrap()
+            .data,
+    )
+    .unwrap();
+    assert_eq!(summary.signer_count, 3);
+    assert_eq!(summary.pending_config_change_count, 0);
 }
 
 #[tokio::test]
@@ -157,3 +195,165 @@ async fn invalid_wallet_initialization() {
         TransactionError::InstructionError(1, Custom(WalletError::UnknownSigner as u32)),
     );
 }
+
+#[tokio::test]
+async fn wallet_reinitialization() {
+    let program_id = Keypair::new().pubkey();
+    let mut pt = ProgramTest::new("strike_wallet", program_id, processor!(Processor::process));
+    pt.set_compute_max_units(35_000);
+    let (mut banks_client, payer, recent_blockhash) = pt.start().await;
+    let wallet_account = Keypair::new();
+    let assistant_account = Keypair::new();
+    let signers = vec![(SlotId::new(0), Signer::new(Pubkey::new_unique()))];
+    let initial_config = InitialWalletConfig {
+        approvals_required_for_config: 1,
+        approval_timeout_for_config: Duration::from_secs(3600),
+        signers: signers.clone(),
+        config_approvers: vec![SlotId::new(0)],
+    };
+
+    utils::init_wallet(
+        &mut banks_client,
+        &payer,
+        recent_blockhash,
+        &program_id,
+        &wallet_account,
+        &assistant_account,
+        WalletGuidHash::new(&hash_of(Uuid::new_v4().as_bytes())),
+        initial_config.clone(),
+    )
+    .await
+    .unwrap();
+
+    // verify an initialized wallet cannot be initialized again
+    assert_eq!(
+        banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[instructions::init_wallet(
+                    &program_id,
+                    &wallet_account.pubkey(),
+                    &assistant_account.pubkey(),
+                    &payer.pubkey(),
+                    WalletGuidHash::new(&hash_of(Uuid::new_v4().as_bytes())),
+                    initial_config.clone(),
+                )],
+                Some(&payer.pubkey()),
+                &[&payer, &assistant_account],
+                recent_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            Custom(WalletError::AccountAlreadyInitialized as u32)
+        ),
+    );
+
+    // verify an account sized for another account type cannot be initialized as a wallet
+    let multisig_op_account = Keypair::new();
+    let rent = banks_client.get_rent().await.unwrap();
+    assert_eq!(
+        banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[
+                    system_instruction::create_account(
+                        &payer.pubkey(),
+                        &multisig_op_account.pubkey(),
+                        rent.minimum_balance(MultisigOp::LEN),
+                        MultisigOp::LEN as u64,
+                        &program_id,
+                    ),
+                    instructions::init_wallet(
+                        &program_id,
+                        &multisig_op_account.pubkey(),
+                        &assistant_account.pubkey(),
+                        &payer.pubkey(),
+                        WalletGuidHash::new(&hash_of(Uuid::new_v4().as_bytes())),
+                        initial_config,
+                    ),
+                ],
+                Some(&payer.pubkey()),
+                &[&payer, &multisig_op_account, &assistant_account],
+                recent_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::AccountTypeMismatch as u32)),
+    );
+}
+
+#[tokio::test]
+async fn test_wallet_registry() {
+    let program_id = Keypair::new().pubkey();
+    let pt = ProgramTest::new("strike_wallet", program_id, processor!(Processor::process));
+    let (mut banks_client, payer, recent_blockhash) = pt.start().await;
+    let initial_config = InitialWalletConfig {
+        approvals_required_for_config: 1,
+        approval_timeout_for_config: Duration::from_secs(3600),
+        signers: vec![(SlotId::new(0), Signer::new(Pubkey::new_unique()))],
+        config_approvers: vec![SlotId::new(0)],
+    };
+
+    let mut registered = Vec::new();
+    for _ in 0..2 {
+        let wallet_account = Keypair::new();
+        let wallet_guid_hash = WalletGuidHash::new(&hash_of(Uuid::new_v4().as_bytes()));
+        utils::init_wallet(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            &program_id,
+            &wallet_account,
+            &Keypair::new(),
+            wallet_guid_hash,
+            initial_config.clone(),
+        )
+        .await
+        .unwrap();
+        registered.push(WalletRegistration {
+            is_initialized: true,
+            wallet_guid_hash,
+            wallet: wallet_account.pubkey(),
+        });
+    }
+    for registration in &registered {
+        assert_eq!(
+            get_wallet_registration(
+                &mut banks_client,
+                &program_id,
+                &registration.wallet_guid_hash
+            )
+            .await,
+            *registration
+        );
+    }
+
+    // a wallet can't be initialized with the GUID hash of a registered wallet
+    assert_eq!(
+        utils::init_wallet(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            &program_id,
+            &Keypair::new(),
+            &Keypair::new(),
+            registered[0].wallet_guid_hash,
+            initial_config,
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::WalletAlreadyRegistered as u32)),
+    );
+    assert_eq!(
+        get_wallet_registration(
+            &mut banks_client,
+            &program_id,
+            &registered[0].wallet_guid_hash
+        )
+        .await,
+        registered[0]
+    );
+}
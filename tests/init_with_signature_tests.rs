@@ -0,0 +1,255 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use std::time::Duration;
+
+use solana_program::instruction::Instruction;
+use solana_program::instruction::InstructionError::{Custom, InvalidInstructionData};
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_instruction;
+use solana_program_test::{tokio, BanksClientError};
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::Transaction;
+
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::{InitialWalletConfig, ProgramInstruction};
+use strike_wallet::model::multisig_op::{MultisigOp, MultisigOpParams};
+use strike_wallet::utils::SlotId;
+
+async fn setup() -> (WalletTestContext, Vec<Keypair>) {
+    let approvers = vec![Keypair::new(), Keypair::new(), Keypair::new()];
+    let context = setup_wallet_test(
+        40_000,
+        InitialWalletConfig {
+            approvals_required_for_config: 2,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: approvers
+                .iter()
+                .enumerate()
+                .map(|(i, approver)| (SlotId::new(i), approver.pubkey_as_signer()))
+                .collect(),
+            config_approvers: vec![SlotId::new(0), SlotId::new(1)],
+        },
+    )
+    .await;
+    (context, approvers)
+}
+
+/// Submits the instructions after creating the multisig op account, with only the payer and
+/// the op account signing.
+async fn process_init(
+    context: &mut WalletTestContext,
+    multisig_op_account: &Keypair,
+    instructions: Vec<Instruction>,
+) -> Result<(), BanksClientError> {
+    let mut all_instructions = vec![system_instruction::create_account(
+        &context.payer.pubkey(),
+        &multisig_op_account.pubkey(),
+        context.rent.minimum_balance(MultisigOp::LEN),
+        MultisigOp::LEN as u64,
+        &context.program_id,
+    )];
+    all_instructions.extend(instructions);
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &all_instructions,
+            Some(&context.payer.pubkey()),
+            &[&context.payer, multisig_op_account],
+            context.recent_blockhash,
+        ))
+        .await
+}
+
+fn init_sign_data(
+    context: &WalletTestContext,
+    multisig_op_account: &Pubkey,
+    initiator: &Pubkey,
+    data: &Vec<u8>,
+) -> Instruction {
+    init_sign_data_instruction(
+        &context.program_id,
+        &context.wallet_account.pubkey(),
+        multisig_op_account,
+        initiator,
+        &context.payer.pubkey(),
+        data,
+    )
+}
+
+#[tokio::test]
+async fn test_init_with_signature() {
+    let (mut context, approvers) = setup().await;
+    let program_id = context.program_id;
+    let initiator = &approvers[2];
+    let multisig_op_account = Keypair::new();
+    let data = vec![1, 2, 3, 4];
+    let init = init_sign_data(
+        &context,
+        &multisig_op_account.pubkey(),
+        &initiator.pubkey(),
+        &data,
+    );
+
+    process_init(
+        &mut context,
+        &multisig_op_account,
+        init_with_signature(&program_id, init, initiator),
+    )
+    .await
+    .unwrap();
+
+    let multisig_op =
+        get_multisig_op_data(&mut context.banks_client, multisig_op_account.pubkey()).await;
+    assert_eq!(multisig_op.initiator, initiator.pubkey());
+    assert_eq!(
+        multisig_op.params_hash.unwrap(),
+        MultisigOpParams::SignData {
+            wallet_address: context.wallet_account.pubkey(),
+            data,
+        }
+        .hash(&multisig_op)
+    );
+}
+
+#[tokio::test]
+async fn test_init_with_signature_failures() {
+    let (mut context, approvers) = setup().await;
+    let program_id = context.program_id;
+    let initiator = &approvers[2];
+    let multisig_op_account = Keypair::new();
+    let init = init_sign_data(
+        &context,
+        &multisig_op_account.pubkey(),
+        &initiator.pubkey(),
+        &vec![1, 2, 3, 4],
+    );
+
+    // the signature has to cover the init as submitted
+    let mut instructions = init_with_signature(&program_id, init.clone(), initiator);
+    instructions[1].data = ProgramInstruction::InitWithSignature {
+        init: init_sign_data(
+            &context,
+            &multisig_op_account.pubkey(),
+            &initiator.pubkey(),
+            &vec![5, 6, 7, 8],
+        )
+        .data,
+    }
+    .pack();
+    assert_instruction_error(
+        process_init(&mut context, &multisig_op_account, instructions).await,
+        2,
+        Custom(WalletError::InvalidInitSignature as u32),
+    );
+
+    // and be verified by the instruction right before the wrapper
+    let instructions = init_with_signature(&program_id, init.clone(), initiator);
+    assert_instruction_error(
+        process_init(
+            &mut context,
+            &multisig_op_account,
+            vec![instructions[1].clone()],
+        )
+        .await,
+        1,
+        Custom(WalletError::InvalidInitSignature as u32),
+    );
+
+    // the signer still has to be allowed to initiate
+    let outsider = Keypair::new();
+    let init_by_outsider = init_sign_data(
+        &context,
+        &multisig_op_account.pubkey(),
+        &outsider.pubkey(),
+        &vec![1, 2, 3, 4],
+    );
+    assert_instruction_error(
+        process_init(
+            &mut context,
+            &multisig_op_account,
+            init_with_signature(&program_id, init_by_outsider, &outsider),
+        )
+        .await,
+        2,
+        Custom(WalletError::InvalidApprover as u32),
+    );
+
+    // and only inits can be wrapped
+    let not_an_init = Instruction {
+        data: ProgramInstruction::FinalizeSignData {
+            data: vec![1, 2, 3, 4],
+        }
+        .pack(),
+        ..init
+    };
+    assert_instruction_error(
+        process_init(
+            &mut context,
+            &multisig_op_account,
+            init_with_signature(&program_id, not_an_init, initiator),
+        )
+        .await,
+        2,
+        InvalidInstructionData,
+    );
+}
+
+#[tokio::test]
+async fn test_init_with_compute_hint() {
+    let (mut context, approvers) = setup().await;
+    let program_id = context.program_id;
+    let initiator = &approvers[2];
+    let multisig_op_account = Keypair::new();
+    let init = init_sign_data(
+        &context,
+        &multisig_op_account.pubkey(),
+        &initiator.pubkey(),
+        &vec![1, 2, 3, 4],
+    );
+
+    // only inits starting a multisig op can be given a compute hint
+    let mut instructions = init_with_signature(&program_id, init.clone(), initiator);
+    instructions[1] = init_with_compute_hint(
+        Instruction {
+            data: ProgramInstruction::FinalizeSignData {
+                data: vec![1, 2, 3, 4],
+            }
+            .pack(),
+            ..instructions[1].clone()
+        },
+        250_000,
+    );
+    assert_instruction_error(
+        process_init(&mut context, &multisig_op_account, instructions).await,
+        2,
+        InvalidInstructionData,
+    );
+
+    let mut instructions = init_with_signature(&program_id, init, initiator);
+    instructions[1] = init_with_compute_hint(instructions[1].clone(), 250_000);
+    process_init(&mut context, &multisig_op_account, instructions)
+        .await
+        .unwrap();
+
+    let multisig_op_data = context
+        .banks_client
+        .get_account(multisig_op_account.pubkey())
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    assert_eq!(
+        MultisigOp::finalize_compute_units_from_slice(&multisig_op_data),
+        Ok(Some(250_000))
+    );
+    let multisig_op =
+        get_multisig_op_data(&mut context.banks_client, multisig_op_account.pubkey()).await;
+    assert_eq!(multisig_op.initiator, initiator.pubkey());
+    assert_eq!(multisig_op.finalize_compute_units, Some(250_000));
+}
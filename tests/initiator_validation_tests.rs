@@ -0,0 +1,267 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use solana_program::hash::Hash;
+use solana_program::instruction::Instruction;
+use solana_program::instruction::InstructionError::Custom;
+use solana_program::pubkey::Pubkey;
+use solana_program::{system_instruction, system_program};
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::{Transaction, TransactionError};
+
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::{BalanceAccountAddressWhitelistUpdate, DAppBookUpdate};
+use strike_wallet::model::balance_account::BalanceAccountNameHash;
+use strike_wallet::model::finalize_submitter_policy::FinalizeSubmitterPolicy;
+use strike_wallet::model::multisig_op::{MultisigOp, WrapDirection};
+
+const INIT_CASES: [&str; 11] = [
+    "transfer",
+    "wrap",
+    "dapp book update",
+    "address book update",
+    "address whitelist update",
+    "balance account name update",
+    "balance account deletion",
+    "account settings update",
+    "spending limit update",
+    "finalize submitter policy update",
+    "sign data",
+];
+
+/// Builds the init instruction of the given case with the given multisig op account and
+/// initiator.
+fn build_init(
+    case: &str,
+    context: &BalanceAccountTestContext,
+    balance_account: &Pubkey,
+    op: &Pubkey,
+    initiator: &Pubkey,
+) -> Instruction {
+    match case {
+        "transfer" => init_transfer(
+            &context.program_id,
+            &context.wallet_account.pubkey(),
+            op,
+            initiator,
+            balance_account,
+            &context.destination.pubkey(),
+            context.balance_account_guid_hash,
+            1,
+            context.destination_name_hash,
+            &system_program::id(),
+            &context.pt_context.payer.pubkey(),
+            false,
+        ),
+        "wrap" => init_wrap_unwrap(
+            &context.program_id,
+            &context.wallet_account.pubkey(),
+            op,
+            initiator,
+            &context.pt_context.payer.pubkey(),
+            balance_account,
+            &context.balance_account_guid_hash,
+            &context.wallet_guid_hash,
+            1,
+            WrapDirection::WRAP,
+            0,
+        ),
+        "dapp book update" => init_dapp_book_update(
+            &context.program_id,
+            &context.wallet_account.pubkey(),
+            op,
+            initiator,
+            &context.pt_context.payer.pubkey(),
+            DAppBookUpdate {
+                add_dapps: vec![],
+                remove_dapps: vec![],
+                dapp_expirations: vec![],
+                dapp_categories: vec![],
+                disallowed_dapp_categories: vec![],
+                dapp_instruction_limits: vec![],
+            },
+        ),
+        "address book update" => init_address_book_update_instruction(
+            &context.program_id,
+            &context.wallet_account.pubkey(),
+            op,
+            initiator,
+            &context.pt_context.payer.pubkey(),
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+        ),
+        "address whitelist update" => init_balance_account_address_whitelist_update_instruction(
+            &context.program_id,
+            &context.wallet_account.pubkey(),
+            op,
+            initiator,
+            &context.pt_context.payer.pubkey(),
+            context.balance_account_guid_hash,
+            BalanceAccountAddressWhitelistUpdate {
+                allowed_destinations: vec![],
+                destinations_hash: Hash::new_from_array([0; 32]),
+            },
+        ),
+        "balance account name update" => init_balance_account_name_update(
+            &context.program_id,
+            &context.wallet_account.pubkey(),
+            op,
+            initiator,
+            &context.pt_context.payer.pubkey(),
+            context.balance_account_guid_hash,
+            BalanceAccountNameHash::new(&[1; 32]),
+        ),
+        "balance account deletion" => init_balance_account_deletion(
+            &context.program_id,
+            &context.wallet_account.pubkey(),
+            op,
+            initiator,
+            &context.pt_context.payer.pubkey(),
+            context.balance_account_guid_hash,
+        ),
+        "account settings update" => init_account_settings_update(
+            &context.program_id,
+            &context.wallet_account.pubkey(),
+            op,
+            initiator,
+            &context.pt_context.payer.pubkey(),
+            context.balance_account_guid_hash,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        ),
+        "spending limit update" => init_spending_limit_update(
+            &context.program_id,
+            &context.wallet_account.pubkey(),
+            op,
+            initiator,
+            &context.pt_context.payer.pubkey(),
+            context.balance_account_guid_hash,
+            system_program::id(),
+            1,
+        ),
+        "finalize submitter policy update" => init_finalize_submitter_policy_update(
+            &context.program_id,
+            &context.wallet_account.pubkey(),
+            op,
+            initiator,
+            &context.pt_context.payer.pubkey(),
+            &FinalizeSubmitterPolicy::default(),
+        ),
+        "sign data" => init_sign_data_instruction(
+            &context.program_id,
+            &context.wallet_account.pubkey(),
+            op,
+            initiator,
+            &context.pt_context.payer.pubkey(),
+            &vec![1, 2, 3],
+        ),
+        _ => panic!("unknown case {}", case),
+    }
+}
+
+async fn init_op(
+    context: &mut BalanceAccountTestContext,
+    op: &Keypair,
+    init: Instruction,
+    extra_signers: &[&Keypair],
+) -> TransactionError {
+    let rent = context.pt_context.banks_client.get_rent().await.unwrap();
+    let mut signers = vec![&context.pt_context.payer, op];
+    signers.extend_from_slice(extra_signers);
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                system_instruction::create_account(
+                    &context.pt_context.payer.pubkey(),
+                    &op.pubkey(),
+                    rent.minimum_balance(MultisigOp::LEN),
+                    MultisigOp::LEN as u64,
+                    &context.program_id,
+                ),
+                init,
+            ],
+            Some(&context.pt_context.payer.pubkey()),
+            &signers,
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap_err()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_init_rejects_forged_initiator() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let initiator = Keypair::from_base58_string(&context.initiator_account.to_base58_string());
+    let wallet_account = Keypair::from_base58_string(&context.wallet_account.to_base58_string());
+
+    for case in INIT_CASES {
+        // the multisig op account standing in for the initiator
+        let op = Keypair::new();
+        let init = build_init(case, &context, &balance_account, &op.pubkey(), &op.pubkey());
+        assert_eq!(
+            init_op(&mut context, &op, init, &[]).await,
+            TransactionError::InstructionError(
+                1,
+                Custom(WalletError::IncorrectInitiatorAccount as u32)
+            ),
+            "{}",
+            case
+        );
+
+        // the wallet account standing in for the initiator
+        let op = Keypair::new();
+        let init = build_init(
+            case,
+            &context,
+            &balance_account,
+            &op.pubkey(),
+            &wallet_account.pubkey(),
+        );
+        assert_eq!(
+            init_op(&mut context, &op, init, &[&wallet_account]).await,
+            TransactionError::InstructionError(
+                1,
+                Custom(WalletError::IncorrectInitiatorAccount as u32)
+            ),
+            "{}",
+            case
+        );
+
+        // the initiator in place, but not signing
+        let op = Keypair::new();
+        let mut init = build_init(
+            case,
+            &context,
+            &balance_account,
+            &op.pubkey(),
+            &initiator.pubkey(),
+        );
+        init.accounts
+            .iter_mut()
+            .filter(|meta| meta.pubkey == initiator.pubkey())
+            .for_each(|meta| meta.is_signer = false);
+        assert_eq!(
+            init_op(&mut context, &op, init, &[]).await,
+            TransactionError::InstructionError(1, Custom(WalletError::InvalidSignature as u32)),
+            "{}",
+            case
+        );
+    }
+}
@@ -0,0 +1,202 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use std::time::Duration;
+
+use solana_program::clock::Clock;
+use solana_program::hash::Hash;
+use solana_program::instruction::{Instruction, InstructionError::Custom};
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program_test::{tokio, BanksClientError};
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::{Transaction, TransactionError};
+
+use strike_wallet::error::WalletError;
+use strike_wallet::model::insurance_attestation::InsuranceAttestation;
+use strike_wallet::model::wallet_event_log::{WalletEventKind, WalletEventLog};
+
+const DAY: u64 = 24 * 60 * 60;
+
+async fn process(
+    context: &mut BalanceAccountTestContext,
+    instruction: Instruction,
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let mut all_signers = vec![&context.pt_context.payer];
+    all_signers.extend_from_slice(signers);
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&context.pt_context.payer.pubkey()),
+            &all_signers,
+            context.pt_context.last_blockhash,
+        ))
+        .await
+}
+
+async fn register_attestor(context: &mut BalanceAccountTestContext, attestor: &Pubkey) {
+    let wallet = context.wallet_account.pubkey();
+    let rent_return = context.pt_context.payer.pubkey();
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let mut test_context = context.to_test_context();
+    init_multisig_op(
+        &mut test_context,
+        multisig_op_account,
+        init_insurance_attestor_update(
+            &context.program_id,
+            &wallet,
+            &multisig_op_pubkey,
+            &context.approvers[0].pubkey(),
+            &rent_return,
+            *attestor,
+        ),
+        &context.approvers[0],
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut test_context,
+        &multisig_op_pubkey,
+        vec![&context.approvers[0], &context.approvers[1]],
+    )
+    .await;
+    finalize_multisig_op(
+        &mut test_context,
+        multisig_op_pubkey,
+        finalize_insurance_attestor_update(
+            &context.program_id,
+            &wallet,
+            &multisig_op_pubkey,
+            &rent_return,
+            *attestor,
+        ),
+    )
+    .await;
+}
+
+async fn get_account<T: Pack>(context: &mut BalanceAccountTestContext, address: Pubkey) -> T {
+    T::unpack(
+        context
+            .pt_context
+            .banks_client
+            .get_account(address)
+            .await
+            .unwrap()
+            .unwrap()
+            .data
+            .as_slice(),
+    )
+    .unwrap()
+}
+
+async fn now(context: &mut BalanceAccountTestContext) -> i64 {
+    context
+        .pt_context
+        .banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap()
+        .unix_timestamp
+}
+
+#[tokio::test]
+async fn test_insurance_attestation_lapse_is_logged() {
+    let (mut context, _) = setup_balance_account_tests_and_finalize(None).await;
+    let program_id = context.program_id;
+    let wallet = context.wallet_account.pubkey();
+    let payer = context.pt_context.payer.pubkey();
+    let attestor = Keypair::new();
+    register_attestor(&mut context, &attestor.pubkey()).await;
+
+    let attestation_address = InsuranceAttestation::find_address(&wallet, &program_id).0;
+    let attestation: InsuranceAttestation = get_account(&mut context, attestation_address).await;
+    assert_eq!(attestation.wallet, wallet);
+    assert_eq!(attestation.attestor, attestor.pubkey());
+    assert!(!attestation.is_attested());
+
+    // only the registered attestor can attest
+    let statement_hash = Hash::new_unique();
+    let coverage_expires_at = now(&mut context).await + 30 * DAY as i64;
+    let impostor = Keypair::new();
+    assert_eq!(
+        process(
+            &mut context,
+            submit_insurance_attestation(
+                &program_id,
+                &wallet,
+                &impostor.pubkey(),
+                statement_hash,
+                coverage_expires_at,
+            ),
+            &[&impostor],
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::InvalidInsuranceAttestor as u32)),
+    );
+    process(
+        &mut context,
+        submit_insurance_attestation(
+            &program_id,
+            &wallet,
+            &attestor.pubkey(),
+            statement_hash,
+            coverage_expires_at,
+        ),
+        &[&attestor],
+    )
+    .await
+    .unwrap();
+    let attestation: InsuranceAttestation = get_account(&mut context, attestation_address).await;
+    assert_eq!(attestation.statement_hash, statement_hash);
+    assert_eq!(attestation.coverage_expires_at, coverage_expires_at);
+
+    // nothing is logged while the wallet is covered
+    process(
+        &mut context,
+        init_event_log(&program_id, &wallet, &payer),
+        &[],
+    )
+    .await
+    .unwrap();
+    let event_log_address = WalletEventLog::find_address(&wallet, &program_id).0;
+    process(
+        &mut context,
+        check_insurance_attestation(&program_id, &wallet),
+        &[],
+    )
+    .await
+    .unwrap();
+    let event_log: WalletEventLog = get_account(&mut context, event_log_address).await;
+    assert!(event_log.events.is_empty());
+
+    // and its lapse is logged once
+    for _ in 0..2 {
+        advance_clock(&mut context.pt_context, Duration::from_secs(31 * DAY)).await;
+        process(
+            &mut context,
+            check_insurance_attestation(&program_id, &wallet),
+            &[],
+        )
+        .await
+        .unwrap();
+    }
+    let event_log: WalletEventLog = get_account(&mut context, event_log_address).await;
+    assert_eq!(event_log.events.len(), 1);
+    assert_eq!(
+        event_log.events[0].kind,
+        WalletEventKind::InsuranceAttestationLapsed
+    );
+    assert_eq!(event_log.events[0].op, attestation_address);
+    let attestation: InsuranceAttestation = get_account(&mut context, attestation_address).await;
+    assert!(attestation.lapse_reported);
+}
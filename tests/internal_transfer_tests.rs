@@ -0,0 +1,422 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use solana_program::hash::Hash;
+use solana_program::instruction::InstructionError::Custom;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_program;
+use solana_program::sysvar;
+use solana_program_test::BanksClientError;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::signature::Keypair;
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::ProgramInstruction;
+use strike_wallet::model::address_book::AddressBookEntryNameHash;
+use strike_wallet::model::balance_account::BalanceAccountGuidHash;
+use strike_wallet::model::destination_receipt::DestinationReceipt;
+use strike_wallet::model::internal_transfer_handshake::InternalTransferHandshake;
+use strike_wallet::model::multisig_op::OperationDisposition;
+use strike_wallet::model::wallet::WalletGuidHash;
+use uuid::Uuid;
+use {
+    solana_program::program_pack::Pack,
+    solana_program_test::tokio,
+    solana_sdk::{signature::Signer as SdkSigner, transaction::Transaction},
+};
+
+struct InternalTransferTestContext {
+    test_context: TestContext,
+    source_wallet: Keypair,
+    source_approvers: Vec<Keypair>,
+    source_account: (BalanceAccountGuidHash, Pubkey),
+    destination_wallet: Keypair,
+    destination_approvers: Vec<Keypair>,
+    destination_accounts: Vec<(BalanceAccountGuidHash, Pubkey)>,
+    multisig_op_account: Pubkey,
+    amount: u64,
+}
+
+async fn setup_internal_transfer_test() -> InternalTransferTestContext {
+    let mut test_context = setup_test(60_000).await;
+
+    let source_wallet = Keypair::new();
+    let source_wallet_guid_hash = WalletGuidHash::new(&hash_of(Uuid::new_v4().as_bytes()));
+    let source_assistant = Keypair::new();
+    let source_approvers = vec![Keypair::new(), Keypair::new()];
+    create_wallet(
+        &mut test_context,
+        &source_wallet,
+        &source_wallet_guid_hash,
+        &source_assistant,
+        &source_approvers,
+    )
+    .await;
+    let rent = test_context.banks_client.get_rent().await.unwrap();
+    let amount = 1000;
+    let (source_guid_hash, (source_pda, _)) = create_balance_accounts(
+        &mut test_context,
+        &source_wallet.pubkey(),
+        &source_wallet_guid_hash,
+        &source_assistant,
+        &source_approvers,
+        1,
+        Some(rent.minimum_balance(0) + amount),
+    )
+    .await[0];
+
+    let destination_wallet = Keypair::new();
+    let destination_wallet_guid_hash = WalletGuidHash::new(&hash_of(Uuid::new_v4().as_bytes()));
+    let destination_assistant = Keypair::new();
+    let destination_approvers = vec![Keypair::new(), Keypair::new()];
+    create_wallet(
+        &mut test_context,
+        &destination_wallet,
+        &destination_wallet_guid_hash,
+        &destination_assistant,
+        &destination_approvers,
+    )
+    .await;
+    let destination_accounts: Vec<(BalanceAccountGuidHash, Pubkey)> = create_balance_accounts(
+        &mut test_context,
+        &destination_wallet.pubkey(),
+        &destination_wallet_guid_hash,
+        &destination_assistant,
+        &destination_approvers,
+        2,
+        Some(rent.minimum_balance(0)),
+    )
+    .await
+    .iter()
+    .map(|(guid_hash, (pda, _))| (*guid_hash, *pda))
+    .collect();
+
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let instruction = init_transfer(
+        &test_context.program_id,
+        &source_wallet.pubkey(),
+        &multisig_op_pubkey,
+        &source_approvers[0].pubkey(),
+        &source_pda,
+        &destination_accounts[0].1,
+        source_guid_hash,
+        amount,
+        AddressBookEntryNameHash::zero(),
+        &system_program::id(),
+        &test_context.payer.pubkey(),
+        true,
+    );
+    init_multisig_op(
+        &mut test_context,
+        multisig_op_account,
+        instruction,
+        &source_approvers[0],
+    )
+    .await
+    .unwrap();
+
+    // the initiator is the only approver required, so the transfer is approved at init
+    assert_eq!(
+        get_multisig_op_data(&mut test_context.banks_client, multisig_op_pubkey)
+            .await
+            .operation_disposition(),
+        OperationDisposition::APPROVED
+    );
+
+    InternalTransferTestContext {
+        test_context,
+        source_wallet,
+        source_approvers,
+        source_account: (source_guid_hash, source_pda),
+        destination_wallet,
+        destination_approvers,
+        destination_accounts,
+        multisig_op_account: multisig_op_pubkey,
+        amount,
+    }
+}
+
+async fn acknowledge(
+    context: &mut InternalTransferTestContext,
+    acknowledger: &Keypair,
+    destination_account: (BalanceAccountGuidHash, Pubkey),
+) -> Result<(), BanksClientError> {
+    let params_hash = transfer_params_hash(context).await;
+    acknowledge_params_hash(context, acknowledger, destination_account, params_hash).await
+}
+
+async fn transfer_params_hash(context: &mut InternalTransferTestContext) -> Hash {
+    get_multisig_op_data(
+        &mut context.test_context.banks_client,
+        context.multisig_op_account,
+    )
+    .await
+    .params_hash
+    .unwrap()
+}
+
+async fn acknowledge_params_hash(
+    context: &mut InternalTransferTestContext,
+    acknowledger: &Keypair,
+    destination_account: (BalanceAccountGuidHash, Pubkey),
+    params_hash: Hash,
+) -> Result<(), BanksClientError> {
+    context
+        .test_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[acknowledge_internal_transfer(
+                &context.test_context.program_id,
+                &context.multisig_op_account,
+                &context.source_wallet.pubkey(),
+                &context.destination_wallet.pubkey(),
+                &destination_account.1,
+                &acknowledger.pubkey(),
+                &context.test_context.payer.pubkey(),
+                destination_account.0,
+                params_hash,
+            )],
+            Some(&context.test_context.payer.pubkey()),
+            &[&context.test_context.payer, acknowledger],
+            context.test_context.recent_blockhash,
+        ))
+        .await
+}
+
+async fn finalize(context: &mut InternalTransferTestContext) -> Result<(), BanksClientError> {
+    let (handshake_account, _) = InternalTransferHandshake::find_address(
+        &context.multisig_op_account,
+        &context.destination_accounts[0].1,
+        &context.test_context.program_id,
+    );
+    context
+        .test_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[finalize_transfer(
+                &context.test_context.program_id,
+                &context.multisig_op_account,
+                &context.source_wallet.pubkey(),
+                &context.source_account.1,
+                &context.destination_accounts[0].1,
+                &context.test_context.payer.pubkey(),
+                context.source_account.0,
+                context.amount,
+                &system_program::id(),
+                None,
+                None,
+                Some(&handshake_account),
+            )],
+            Some(&context.test_context.payer.pubkey()),
+            &[&context.test_context.payer],
+            context.test_context.recent_blockhash,
+        ))
+        .await
+}
+
+#[tokio::test]
+async fn test_internal_transfer_requires_destination_acknowledgement() {
+    let mut context = setup_internal_transfer_test().await;
+
+    assert_instruction_error(
+        finalize(&mut context).await,
+        0,
+        Custom(WalletError::DestinationNotAcknowledged as u32),
+    );
+
+    let destination_approver =
+        Keypair::from_bytes(&context.destination_approvers[1].to_bytes()).unwrap();
+    let destination_account = context.destination_accounts[0];
+    acknowledge(&mut context, &destination_approver, destination_account)
+        .await
+        .unwrap();
+
+    let (handshake_account, _) = InternalTransferHandshake::find_address(
+        &context.multisig_op_account,
+        &context.destination_accounts[0].1,
+        &context.test_context.program_id,
+    );
+    let handshake = InternalTransferHandshake::unpack(
+        &context
+            .test_context
+            .banks_client
+            .get_account(handshake_account)
+            .await
+            .unwrap()
+            .unwrap()
+            .data,
+    )
+    .unwrap();
+    let params_hash = transfer_params_hash(&mut context).await;
+    assert_eq!(
+        handshake,
+        InternalTransferHandshake {
+            is_initialized: true,
+            multisig_op: context.multisig_op_account,
+            destination_wallet: context.destination_wallet.pubkey(),
+            destination_account_guid_hash: destination_account.0,
+            destination: destination_account.1,
+            acknowledged_by: destination_approver.pubkey(),
+            params_hash,
+        }
+    );
+
+    let destination_balance_before = context
+        .test_context
+        .banks_client
+        .get_balance(destination_account.1)
+        .await
+        .unwrap();
+
+    finalize(&mut context).await.unwrap();
+
+    assert_eq!(
+        context
+            .test_context
+            .banks_client
+            .get_balance(destination_account.1)
+            .await
+            .unwrap(),
+        destination_balance_before + context.amount
+    );
+
+    // both the multisig op and the handshake account are closed
+    assert!(context
+        .test_context
+        .banks_client
+        .get_account(context.multisig_op_account)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(context
+        .test_context
+        .banks_client
+        .get_account(handshake_account)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_internal_transfer_acknowledgement_requires_destination_approver() {
+    let mut context = setup_internal_transfer_test().await;
+
+    let source_approver = Keypair::from_bytes(&context.source_approvers[1].to_bytes()).unwrap();
+    let destination_account = context.destination_accounts[0];
+    assert_instruction_error(
+        acknowledge(&mut context, &source_approver, destination_account).await,
+        0,
+        Custom(WalletError::InvalidApprover as u32),
+    );
+
+    // the destination account must belong to the acknowledged balance account
+    let destination_approver =
+        Keypair::from_bytes(&context.destination_approvers[0].to_bytes()).unwrap();
+    let mismatched_account = (
+        context.destination_accounts[1].0,
+        context.destination_accounts[0].1,
+    );
+    assert_instruction_error(
+        acknowledge(&mut context, &destination_approver, mismatched_account).await,
+        0,
+        Custom(WalletError::InvalidPDA as u32),
+    );
+}
+
+#[tokio::test]
+async fn test_internal_transfer_acknowledgement_must_match_transfer() {
+    let mut context = setup_internal_transfer_test().await;
+
+    // the destination wallet can't acknowledge a transfer other than the one that was
+    // initiated
+    let destination_approver =
+        Keypair::from_bytes(&context.destination_approvers[0].to_bytes()).unwrap();
+    let destination_account = context.destination_accounts[0];
+    assert_instruction_error(
+        acknowledge_params_hash(
+            &mut context,
+            &destination_approver,
+            destination_account,
+            Hash::new_unique(),
+        )
+        .await,
+        0,
+        Custom(WalletError::OperationParamsMismatch as u32),
+    );
+
+    // and acknowledging receipt into a different balance account than the one the transfer
+    // was initiated for doesn't acknowledge the transfer
+    let other_destination_account = context.destination_accounts[1];
+    acknowledge(
+        &mut context,
+        &destination_approver,
+        other_destination_account,
+    )
+    .await
+    .unwrap();
+    assert_instruction_error(
+        finalize(&mut context).await,
+        0,
+        Custom(WalletError::DestinationNotAcknowledged as u32),
+    );
+
+    // the rejected attempts leave the transfer open for the right acknowledgement
+    acknowledge(&mut context, &destination_approver, destination_account)
+        .await
+        .unwrap();
+    finalize(&mut context).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_internal_transfer_flag_is_part_of_params_hash() {
+    let mut context = setup_internal_transfer_test().await;
+
+    // finalizing without supplying the handshake account computes a different params hash
+    let data = ProgramInstruction::FinalizeTransfer {
+        account_guid_hash: context.source_account.0,
+        amount: context.amount,
+        token_mint: system_program::id(),
+        destination_acknowledgement_required: false,
+        deduct_fee_from_amount: false,
+        allow_destination_ata_creation: false,
+        memo: None,
+    }
+    .pack();
+    let result = context
+        .test_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[Instruction {
+                program_id: context.test_context.program_id,
+                accounts: vec![
+                    AccountMeta::new(context.multisig_op_account, false),
+                    AccountMeta::new_readonly(context.source_wallet.pubkey(), false),
+                    AccountMeta::new(context.source_account.1, false),
+                    AccountMeta::new(context.destination_accounts[0].1, false),
+                    AccountMeta::new_readonly(system_program::id(), false),
+                    AccountMeta::new(context.test_context.payer.pubkey(), true),
+                    AccountMeta::new_readonly(sysvar::clock::id(), false),
+                    AccountMeta::new(
+                        DestinationReceipt::find_address(
+                            &context.source_wallet.pubkey(),
+                            &context.destination_accounts[0].1,
+                            &system_program::id(),
+                            &context.test_context.program_id,
+                        )
+                        .0,
+                        false,
+                    ),
+                ],
+                data,
+            }],
+            Some(&context.test_context.payer.pubkey()),
+            &[&context.test_context.payer],
+            context.test_context.recent_blockhash,
+        ))
+        .await;
+    assert_instruction_error(result, 0, Custom(WalletError::InvalidSignature as u32));
+}
@@ -0,0 +1,183 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use std::borrow::BorrowMut;
+use std::time::Duration;
+
+use crate::common::utils;
+use solana_program::instruction::InstructionError::Custom;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_instruction;
+use solana_program::sysvar::slot_hashes::SlotHashes;
+use solana_sdk::signature::Signer;
+use solana_sdk::transaction::TransactionError;
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::InitialWalletConfig;
+use strike_wallet::lookup_table::{
+    find_authority_address, find_lookup_table_address, LookupTable, MAX_NEW_ADDRESSES,
+};
+use strike_wallet::model::multisig_op::{ApprovalDisposition, MultisigOp, OperationDisposition};
+use strike_wallet::model::wallet::WalletGuidHash;
+use strike_wallet::utils::SlotId;
+use uuid::Uuid;
+use {
+    solana_program_test::tokio,
+    solana_sdk::{signature::Keypair, transaction::Transaction},
+};
+
+#[tokio::test]
+async fn test_lookup_table_update() {
+    let mut context = setup_test(30_000).await;
+
+    let wallet_account = Keypair::new();
+    let assistant_account = Keypair::new();
+    let approvers = vec![Keypair::new(), Keypair::new(), Keypair::new()];
+
+    utils::init_wallet(
+        &mut context.banks_client,
+        &context.payer,
+        context.recent_blockhash,
+        &context.program_id,
+        &wallet_account,
+        &assistant_account,
+        WalletGuidHash::new(&hash_of(Uuid::new_v4().as_bytes())),
+        InitialWalletConfig {
+            approvals_required_for_config: 2,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: vec![
+                (SlotId::new(0), approvers[0].pubkey_as_signer()),
+                (SlotId::new(1), approvers[1].pubkey_as_signer()),
+                (SlotId::new(2), approvers[2].pubkey_as_signer()),
+            ],
+            config_approvers: vec![SlotId::new(0), SlotId::new(1)],
+        },
+    )
+    .await
+    .unwrap();
+
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let multisig_op_account = Keypair::new();
+    let init_transaction = |new_addresses: Vec<Pubkey>| {
+        Transaction::new_signed_with_payer(
+            &[
+                system_instruction::create_account(
+                    &context.payer.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    rent.minimum_balance(MultisigOp::LEN),
+                    MultisigOp::LEN as u64,
+                    &context.program_id,
+                ),
+                init_lookup_table_update(
+                    &context.program_id,
+                    &wallet_account.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    &approvers[2].pubkey(),
+                    &context.payer.pubkey(),
+                    None,
+                    new_addresses,
+                ),
+            ],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &multisig_op_account, &approvers[2]],
+            context.recent_blockhash,
+        )
+    };
+
+    let too_many_addresses = init_transaction(
+        (0..=MAX_NEW_ADDRESSES)
+            .map(|_| Pubkey::new_unique())
+            .collect(),
+    );
+    let new_addresses = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+    let create_table = init_transaction(new_addresses.clone());
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(too_many_addresses)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::InvalidLookupTableUpdate as u32)),
+    );
+    context
+        .banks_client
+        .process_transaction(create_table)
+        .await
+        .unwrap();
+
+    approve_or_deny_n_of_n_multisig_op(
+        context.banks_client.borrow_mut(),
+        &context.program_id,
+        &multisig_op_account.pubkey(),
+        vec![&approvers[0], &approvers[1]],
+        &context.payer,
+        context.recent_blockhash,
+        ApprovalDisposition::APPROVE,
+        OperationDisposition::APPROVED,
+    )
+    .await;
+
+    let (recent_slot, _) = *context
+        .banks_client
+        .get_sysvar::<SlotHashes>()
+        .await
+        .unwrap()
+        .first()
+        .unwrap();
+    let (authority, _) = find_authority_address(&wallet_account.pubkey(), &context.program_id);
+    let (lookup_table, _) = find_lookup_table_address(&authority, recent_slot);
+    let finalize = |authority: &Pubkey| {
+        Transaction::new_signed_with_payer(
+            &[finalize_lookup_table_update(
+                &context.program_id,
+                &wallet_account.pubkey(),
+                &multisig_op_account.pubkey(),
+                &context.payer.pubkey(),
+                &lookup_table,
+                authority,
+                None,
+                new_addresses.clone(),
+                recent_slot,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        )
+    };
+    let wrong_authority = finalize(&Pubkey::new_unique());
+    let create = finalize(&authority);
+
+    // the table must be controlled by the wallet's authority PDA
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(wrong_authority)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::InvalidPDA as u32)),
+    );
+    context
+        .banks_client
+        .process_transaction(create)
+        .await
+        .unwrap();
+
+    let lookup_table_account = context
+        .banks_client
+        .get_account(lookup_table)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        LookupTable::from_account_data(lookup_table, &lookup_table_account.data)
+            .unwrap()
+            .addresses,
+        new_addresses
+    );
+}
@@ -0,0 +1,228 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use solana_program::hash::Hash;
+use solana_program::instruction::{AccountMeta, InstructionError::Custom};
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_program;
+use solana_program_test::{tokio, BanksClientError};
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::Transaction;
+
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::ProgramInstruction;
+use strike_wallet::model::address_book::{AddressBookEntry, AddressBookEntryNameHash};
+use strike_wallet::model::merkle_address_book::MerkleAddressBook;
+use strike_wallet::model::multisig_op::{BooleanSetting, MultisigOp};
+
+async fn update_merkle_address_book(context: &mut BalanceAccountTestContext, root: Hash) {
+    let wallet = context.wallet_account.pubkey();
+    let rent_return = context.pt_context.payer.pubkey();
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let mut test_context = context.to_test_context();
+    init_multisig_op(
+        &mut test_context,
+        multisig_op_account,
+        init_merkle_address_book_update(
+            &context.program_id,
+            &wallet,
+            &multisig_op_pubkey,
+            &context.approvers[0].pubkey(),
+            &rent_return,
+            context.balance_account_guid_hash,
+            root,
+        ),
+        &context.approvers[0],
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut test_context,
+        &multisig_op_pubkey,
+        vec![&context.approvers[0], &context.approvers[1]],
+    )
+    .await;
+    // the rent return account funds the address book account on the first update, so this
+    // can't go through finalize_multisig_op, which checks it only gets the op's rent back
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[finalize_merkle_address_book_update(
+                &context.program_id,
+                &wallet,
+                &multisig_op_pubkey,
+                &rent_return,
+                context.balance_account_guid_hash,
+                root,
+            )],
+            Some(&rent_return),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+    assert!(context
+        .pt_context
+        .banks_client
+        .get_account(multisig_op_pubkey)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+async fn init_transfer_with_proof(
+    context: &mut BalanceAccountTestContext,
+    balance_account: &Pubkey,
+    destination_proof: Option<Vec<Hash>>,
+) -> Result<(), BanksClientError> {
+    let multisig_op_account = Keypair::new();
+    let initiator = Keypair::from_bytes(&context.approvers[0].to_bytes()).unwrap();
+    let mut instruction = init_transfer(
+        &context.program_id,
+        &context.wallet_account.pubkey(),
+        &multisig_op_account.pubkey(),
+        &initiator.pubkey(),
+        balance_account,
+        &context.destination.pubkey(),
+        context.balance_account_guid_hash,
+        123,
+        context.destination_name_hash,
+        &system_program::id(),
+        &context.pt_context.payer.pubkey(),
+        false,
+    );
+    instruction.data = ProgramInstruction::InitTransfer {
+        fee_amount: 0,
+        fee_account_guid_hash: None,
+        account_guid_hash: context.balance_account_guid_hash,
+        amount: 123,
+        destination_name_hash: context.destination_name_hash,
+        destination_acknowledgement_required: false,
+        deduct_fee_from_amount: false,
+        allow_destination_ata_creation: false,
+        memo_hash: None,
+        destination_proof,
+    }
+    .pack();
+    instruction.accounts.push(AccountMeta::new_readonly(
+        MerkleAddressBook::find_address(
+            &context.wallet_account.pubkey(),
+            &context.balance_account_guid_hash,
+            &context.program_id,
+        )
+        .0,
+        false,
+    ));
+
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                solana_program::system_instruction::create_account(
+                    &context.pt_context.payer.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    context.rent.minimum_balance(MultisigOp::LEN),
+                    MultisigOp::LEN as u64,
+                    &context.program_id,
+                ),
+                instruction,
+            ],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer, &multisig_op_account, &initiator],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+}
+
+#[tokio::test]
+async fn test_transfer_to_merkle_address_book_entry() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    account_settings_update(
+        &mut context,
+        Some(BooleanSetting::On),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+    .await;
+
+    let mut entries: Vec<AddressBookEntry> = (0..5u8)
+        .map(|i| AddressBookEntry {
+            address: Keypair::new().pubkey(),
+            name_hash: AddressBookEntryNameHash::new(&[i; 32]),
+        })
+        .collect();
+    entries.push(AddressBookEntry {
+        address: context.destination.pubkey(),
+        name_hash: context.destination_name_hash,
+    });
+    let root = MerkleAddressBook::root_of(&entries);
+    let proof = MerkleAddressBook::proof_of(&entries, entries.len() - 1);
+
+    // the destination isn't whitelisted, and there is no Merkle address book yet
+    assert_instruction_error(
+        init_transfer_with_proof(&mut context, &balance_account, Some(proof.clone())).await,
+        1,
+        Custom(WalletError::DestinationNotAllowed as u32),
+    );
+
+    update_merkle_address_book(&mut context, root).await;
+    let (address_book_address, _) = MerkleAddressBook::find_address(
+        &context.wallet_account.pubkey(),
+        &context.balance_account_guid_hash,
+        &context.program_id,
+    );
+    let address_book = MerkleAddressBook::unpack(
+        &context
+            .pt_context
+            .banks_client
+            .get_account(address_book_address)
+            .await
+            .unwrap()
+            .unwrap()
+            .data,
+    )
+    .unwrap();
+    assert_eq!(address_book.root, root);
+
+    // a proof for another entry, or no proof at all, doesn't do
+    assert_instruction_error(
+        init_transfer_with_proof(
+            &mut context,
+            &balance_account,
+            Some(MerkleAddressBook::proof_of(&entries, 0)),
+        )
+        .await,
+        1,
+        Custom(WalletError::DestinationNotAllowed as u32),
+    );
+    assert_instruction_error(
+        init_transfer_with_proof(&mut context, &balance_account, None).await,
+        1,
+        Custom(WalletError::DestinationNotAllowed as u32),
+    );
+
+    init_transfer_with_proof(&mut context, &balance_account, Some(proof.clone()))
+        .await
+        .unwrap();
+
+    // clearing the root takes the entries out of the whitelist again
+    update_merkle_address_book(&mut context, Hash::default()).await;
+    assert_instruction_error(
+        init_transfer_with_proof(&mut context, &balance_account, Some(proof)).await,
+        1,
+        Custom(WalletError::DestinationNotAllowed as u32),
+    );
+}
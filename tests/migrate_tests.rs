@@ -5,7 +5,6 @@ use std::time::Duration;
 use itertools::Itertools;
 use solana_program::bpf_loader_upgradeable::{deploy_with_max_program_len, upgrade};
 use solana_program::hash::Hash;
-use solana_program::instruction::InstructionError;
 use solana_program::instruction::InstructionError::{Custom, UninitializedAccount};
 use solana_program::program_pack::Pack;
 use solana_program::pubkey::PUBKEY_BYTES;
@@ -19,9 +18,16 @@ pub use common::instructions::*;
 pub use common::utils::*;
 use strike_wallet::error::WalletError;
 use strike_wallet::instruction::InitialWalletConfig;
-use strike_wallet::model::address_book::{AddressBook, DAppBook};
+use strike_wallet::model::address_book::{
+    AddressBook, AddressBookUsage, DAppBook, DAppBookExpirations,
+};
+use strike_wallet::model::assistant::Assistant;
+use strike_wallet::model::finalize_submitter_policy::FinalizeSubmitterPolicy;
 use strike_wallet::model::signer::Signer;
-use strike_wallet::model::wallet::{Approvers, BalanceAccounts, Signers, Wallet, WalletGuidHash};
+use strike_wallet::model::wallet::{
+    Approvers, Assistants, BalanceAccounts, PendingAddressBookEntries, Signers, Wallet,
+    WalletGuidHash,
+};
 use strike_wallet::utils::SlotId;
 use {
     solana_program_test::{tokio, ProgramTest},
@@ -52,7 +58,7 @@ async fn migrate_account() {
     let program_account = Keypair::new();
     let program_id = program_account.pubkey();
     let mut pt = ProgramTest::default();
-    pt.set_compute_max_units(25_000);
+    pt.set_compute_max_units(35_000);
     let mut pt_context = pt.start_with_context().await;
 
     // deploy program as upgradeable
@@ -177,8 +183,15 @@ async fn migrate_account() {
             rent_return: pt_context.payer.pubkey().clone(),
             wallet_guid_hash,
             signers: Signers::from_vec(signers),
-            assistant: assistant_account.pubkey_as_signer(),
+            assistants: Assistants::from_vec(vec![(
+                SlotId::new(0),
+                Assistant::new(assistant_account.pubkey())
+            )]),
             address_book: AddressBook::new(),
+            pending_address_book_entries: PendingAddressBookEntries::zero(),
+            pending_address_book_window_start: 0,
+            pending_address_book_entries_in_window: 0,
+            address_book_usage: AddressBookUsage::new(),
             approvals_required_for_config,
             approval_timeout_for_config,
             config_approvers: Approvers::from_enabled_vec(
@@ -187,8 +200,15 @@ async fn migrate_account() {
                     .map(|(slot_id, _)| slot_id)
                     .collect_vec()
             ),
+            config_policy_timelock: Wallet::DEFAULT_CONFIG_POLICY_TIMELOCK,
+            pending_config_policy: None,
             balance_accounts: BalanceAccounts::new(),
             dapp_book: DAppBook::from_vec(vec![]),
+            dapp_book_expirations: DAppBookExpirations::default(),
+            finalize_submitter_policy: FinalizeSubmitterPolicy::default(),
+            compliance_policy: None,
+            replication: None,
+            price_oracle: None,
         }
     );
 
@@ -251,7 +271,7 @@ async fn test_migrate_errors() {
 
     let program_id = Keypair::new().pubkey();
     let mut pt = ProgramTest::new("strike_wallet", program_id, processor!(Processor::process));
-    pt.set_compute_max_units(25_000);
+    pt.set_compute_max_units(35_000);
     let mut pt_context = pt.start_with_context().await;
     let wallet_account = Keypair::new();
     let assistant_account = Keypair::new();
@@ -374,7 +394,44 @@ async fn test_migrate_errors() {
         .await
         .unwrap_err()
         .unwrap(),
-        TransactionError::InstructionError(0, InstructionError::AccountAlreadyInitialized)
+        TransactionError::InstructionError(
+            0,
+            Custom(WalletError::AccountAlreadyInitialized as u32)
+        )
+    );
+
+    // the destination must be sized for the current wallet layout
+    let undersized_wallet_account = Keypair::new();
+    pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[system_instruction::create_account(
+                &pt_context.payer.pubkey(),
+                &undersized_wallet_account.pubkey(),
+                rent.minimum_balance(Wallet::LEN - 1),
+                (Wallet::LEN - 1) as u64,
+                &program_id,
+            )],
+            Some(&pt_context.payer.pubkey()),
+            &[&pt_context.payer, &undersized_wallet_account],
+            pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    let blockhash = wait_for_new_blockhash(&mut pt_context).await;
+    assert_eq!(
+        process_migrate_account_transaction(
+            &mut pt_context,
+            &program_id,
+            &wallet_account,
+            &undersized_wallet_account,
+            blockhash
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::AccountTypeMismatch as u32))
     );
 }
 
@@ -387,7 +444,7 @@ async fn test_cleanup_errors() {
 
     let program_id = Keypair::new().pubkey();
     let mut pt = ProgramTest::new("strike_wallet", program_id, processor!(Processor::process));
-    pt.set_compute_max_units(25_000);
+    pt.set_compute_max_units(35_000);
     let mut pt_context = pt.start_with_context().await;
     let wallet_account = Keypair::new();
     let assistant_account = Keypair::new();
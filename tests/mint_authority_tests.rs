@@ -0,0 +1,291 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use std::borrow::BorrowMut;
+use std::time::Duration;
+
+use solana_program::instruction::Instruction;
+use solana_program::instruction::InstructionError::Custom;
+use solana_program::program_option::COption;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program_test::BanksClientError;
+use solana_sdk::signature::Keypair;
+use solana_sdk::transaction::TransactionError;
+
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::BalanceAccountPolicyUpdate;
+use strike_wallet::model::balance_account::FinalizeDelayPolicy;
+use strike_wallet::model::multisig_op::{
+    ApprovalDisposition, MintAuthorityType, MultisigOp, OperationDisposition,
+};
+use strike_wallet::utils::SlotId;
+use {
+    solana_program::system_instruction,
+    solana_program_test::tokio,
+    solana_sdk::{signature::Signer as SdkSigner, transaction::Transaction},
+};
+
+async fn setup_mint(
+    context: &mut BalanceAccountTestContext,
+    authority: &Pubkey,
+) -> (Pubkey, Pubkey) {
+    let rent = context.pt_context.banks_client.get_rent().await.unwrap();
+    let mint = Keypair::new();
+    let destination_token_address = spl_associated_token_account::get_associated_token_address(
+        &context.destination.pubkey(),
+        &mint.pubkey(),
+    );
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                system_instruction::create_account(
+                    &context.pt_context.payer.pubkey(),
+                    &mint.pubkey(),
+                    rent.minimum_balance(spl_token::state::Mint::LEN),
+                    spl_token::state::Mint::LEN as u64,
+                    &spl_token::id(),
+                ),
+                spl_token::instruction::initialize_mint(
+                    &spl_token::id(),
+                    &mint.pubkey(),
+                    authority,
+                    Some(authority),
+                    6,
+                )
+                .unwrap(),
+                spl_associated_token_account::instruction::create_associated_token_account(
+                    &context.pt_context.payer.pubkey(),
+                    &context.destination.pubkey(),
+                    &mint.pubkey(),
+                ),
+            ],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer, &mint],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+    (mint.pubkey(), destination_token_address)
+}
+
+async fn init_op(
+    context: &mut BalanceAccountTestContext,
+    instruction: impl Fn(&Pubkey) -> Instruction,
+) -> Result<Pubkey, BanksClientError> {
+    let multisig_op_account = Keypair::new();
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                system_instruction::create_account(
+                    &context.pt_context.payer.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    context.rent.minimum_balance(MultisigOp::LEN),
+                    MultisigOp::LEN as u64,
+                    &context.program_id,
+                ),
+                instruction(&multisig_op_account.pubkey()),
+            ],
+            Some(&context.pt_context.payer.pubkey()),
+            &[
+                &context.pt_context.payer,
+                &multisig_op_account,
+                &context.approvers[2],
+            ],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .map(|_| multisig_op_account.pubkey())
+}
+
+async fn approve_and_finalize(
+    context: &mut BalanceAccountTestContext,
+    multisig_op_account: &Pubkey,
+    finalize_instruction: Instruction,
+) {
+    approve_or_deny_n_of_n_multisig_op(
+        context.pt_context.banks_client.borrow_mut(),
+        &context.program_id,
+        multisig_op_account,
+        vec![&context.approvers[0], &context.approvers[1]],
+        &context.pt_context.payer,
+        context.pt_context.last_blockhash,
+        ApprovalDisposition::APPROVE,
+        OperationDisposition::APPROVED,
+    )
+    .await;
+
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[finalize_instruction],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+}
+
+async fn get_mint(
+    context: &mut BalanceAccountTestContext,
+    mint: &Pubkey,
+) -> spl_token::state::Mint {
+    spl_token::state::Mint::unpack_from_slice(
+        context
+            .pt_context
+            .banks_client
+            .get_account(*mint)
+            .await
+            .unwrap()
+            .unwrap()
+            .data
+            .as_slice(),
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_mint_to_and_set_mint_authority() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let (mint, destination_token_account) = setup_mint(&mut context, &balance_account).await;
+    let program_id = context.program_id;
+    let wallet_account = context.wallet_account.pubkey();
+    let initiator = context.approvers[2].pubkey();
+    let payer = context.pt_context.payer.pubkey();
+    let account_guid_hash = context.balance_account_guid_hash;
+    let destination_name_hash = context.destination_name_hash;
+
+    let mint_to = |amount: u64| {
+        move |multisig_op_account: &Pubkey| {
+            init_mint_to(
+                &program_id,
+                &wallet_account,
+                multisig_op_account,
+                &balance_account,
+                &mint,
+                &destination_token_account,
+                &initiator,
+                &payer,
+                account_guid_hash,
+                amount,
+                destination_name_hash,
+            )
+        }
+    };
+
+    // balance accounts can't mint until they are given a mint cap
+    assert_eq!(
+        init_op(&mut context, mint_to(500))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::MintCapExceeded as u32)),
+    );
+
+    let signers_hash = hash_signers(&vec![
+        context.approvers[0].pubkey_as_signer(),
+        context.approvers[1].pubkey_as_signer(),
+    ]);
+    update_balance_account_policy(
+        &mut context,
+        BalanceAccountPolicyUpdate {
+            approvals_required_for_transfer: 2,
+            approval_timeout_for_transfer: Duration::from_secs(3600),
+            transfer_approvers: vec![SlotId::new(0), SlotId::new(1)],
+            signers_hash,
+            finalize_delay_policy: FinalizeDelayPolicy::default(),
+            mint_cap: 1000,
+            approval_tiers: vec![],
+        },
+        None,
+    )
+    .await;
+
+    assert_eq!(
+        init_op(&mut context, mint_to(1001))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::MintCapExceeded as u32)),
+    );
+
+    let multisig_op_account = init_op(&mut context, mint_to(500)).await.unwrap();
+    approve_and_finalize(
+        &mut context,
+        &multisig_op_account,
+        finalize_mint_to(
+            &program_id,
+            &wallet_account,
+            &multisig_op_account,
+            &balance_account,
+            &mint,
+            &destination_token_account,
+            &payer,
+            account_guid_hash,
+            500,
+        ),
+    )
+    .await;
+    assert_eq!(
+        get_token_balance(&mut context, &destination_token_account).await,
+        500
+    );
+    assert_eq!(get_mint(&mut context, &mint).await.supply, 500);
+
+    // hand the mint authority off
+    let new_authority = Keypair::new().pubkey();
+    let multisig_op_account = init_op(&mut context, |multisig_op_account: &Pubkey| {
+        init_set_mint_authority(
+            &program_id,
+            &wallet_account,
+            multisig_op_account,
+            &balance_account,
+            &mint,
+            &initiator,
+            &payer,
+            account_guid_hash,
+            MintAuthorityType::MintTokens,
+            Some(new_authority),
+        )
+    })
+    .await
+    .unwrap();
+    approve_and_finalize(
+        &mut context,
+        &multisig_op_account,
+        finalize_set_mint_authority(
+            &program_id,
+            &wallet_account,
+            &multisig_op_account,
+            &balance_account,
+            &mint,
+            &payer,
+            account_guid_hash,
+            MintAuthorityType::MintTokens,
+            Some(new_authority),
+        ),
+    )
+    .await;
+    let mint_state = get_mint(&mut context, &mint).await;
+    assert_eq!(mint_state.mint_authority, COption::Some(new_authority));
+    assert_eq!(mint_state.freeze_authority, COption::Some(balance_account));
+
+    // after which the balance account can no longer mint
+    assert_eq!(
+        init_op(&mut context, mint_to(500))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::InvalidMintAuthority as u32)),
+    );
+}
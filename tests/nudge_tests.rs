@@ -0,0 +1,80 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use std::borrow::BorrowMut;
+
+use solana_program::instruction::InstructionError::Custom;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::Transaction;
+use strike_wallet::error::WalletError;
+use strike_wallet::model::multisig_op::{ApprovalDisposition, OperationDisposition};
+
+#[tokio::test]
+async fn test_nudge() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let initiator = &Keypair::from_base58_string(&context.approvers[2].to_base58_string());
+    let (multisig_op_account, result) =
+        setup_transfer_test(context.borrow_mut(), initiator, &balance_account, None, 123).await;
+    result.unwrap();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+
+    let mut test_context = context.to_test_context();
+    let nudge_transaction = |test_context: &TestContext, idle_slots: u64| {
+        Transaction::new_signed_with_payer(
+            &[nudge(
+                &test_context.program_id,
+                &multisig_op_pubkey,
+                idle_slots,
+            )],
+            Some(&test_context.payer.pubkey()),
+            &[&test_context.payer],
+            test_context.recent_blockhash,
+        )
+    };
+
+    // too soon after the op was initiated
+    assert_instruction_error(
+        test_context
+            .banks_client
+            .process_transaction(nudge_transaction(&test_context, u64::MAX))
+            .await,
+        0,
+        Custom(WalletError::OperationNotIdle as u32),
+    );
+    test_context
+        .banks_client
+        .process_transaction(nudge_transaction(&test_context, 0))
+        .await
+        .unwrap();
+
+    // nothing to remind anyone of once the op is approved
+    approve_or_deny_n_of_n_multisig_op(
+        context.pt_context.banks_client.borrow_mut(),
+        &context.program_id,
+        &multisig_op_pubkey,
+        vec![&context.approvers[0], &context.approvers[1]],
+        &context.pt_context.payer,
+        context.pt_context.last_blockhash,
+        ApprovalDisposition::APPROVE,
+        OperationDisposition::APPROVED,
+    )
+    .await;
+    test_context.recent_blockhash = test_context
+        .banks_client
+        .get_latest_blockhash()
+        .await
+        .unwrap();
+    assert_instruction_error(
+        test_context
+            .banks_client
+            .process_transaction(nudge_transaction(&test_context, 0))
+            .await,
+        0,
+        Custom(WalletError::OperationNotIdle as u32),
+    );
+}
@@ -0,0 +1,179 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use std::borrow::BorrowMut;
+
+use solana_program::hash::Hash;
+use solana_program::instruction::InstructionError::Custom;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_instruction;
+use solana_program_test::{tokio, BanksClientError};
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::{Transaction, TransactionError};
+
+use strike_wallet::error::WalletError;
+use strike_wallet::model::multisig_op::OperationDisposition;
+use strike_wallet::model::op_bundle::OpBundle;
+
+async fn approve_bundle(
+    context: &mut BalanceAccountTestContext,
+    bundle_account: &Pubkey,
+    approver: &Keypair,
+    bundle_hash: Hash,
+) -> Result<(), BanksClientError> {
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[approve_op_bundle(
+                &context.program_id,
+                bundle_account,
+                &context.wallet_account.pubkey(),
+                &approver.pubkey(),
+                bundle_hash,
+            )],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer, approver],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+}
+
+#[tokio::test]
+async fn test_op_bundle_approval() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let initiator = &Keypair::from_base58_string(&context.approvers[2].to_base58_string());
+    let (transfer_op_1, result) =
+        setup_transfer_test(context.borrow_mut(), initiator, &balance_account, None, 123).await;
+    result.unwrap();
+    let (transfer_op_2, result) =
+        setup_transfer_test(context.borrow_mut(), initiator, &balance_account, None, 456).await;
+    result.unwrap();
+
+    let program_id = context.program_id;
+    let payer = context.pt_context.payer.pubkey();
+    let bundle_account = Keypair::new();
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                system_instruction::create_account(
+                    &payer,
+                    &bundle_account.pubkey(),
+                    context.rent.minimum_balance(OpBundle::LEN),
+                    OpBundle::LEN as u64,
+                    &program_id,
+                ),
+                init_op_bundle(
+                    &program_id,
+                    &bundle_account.pubkey(),
+                    &context.wallet_account.pubkey(),
+                    &initiator.pubkey(),
+                    &payer,
+                    &[transfer_op_1.pubkey(), transfer_op_2.pubkey()],
+                ),
+            ],
+            Some(&payer),
+            &[&context.pt_context.payer, &bundle_account, initiator],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    let bundle = OpBundle::unpack(
+        context
+            .pt_context
+            .banks_client
+            .get_account(bundle_account.pubkey())
+            .await
+            .unwrap()
+            .unwrap()
+            .data
+            .as_slice(),
+    )
+    .unwrap();
+    assert_eq!(bundle.members.len(), 2);
+    let bundle_hash = bundle.bundle_hash();
+
+    // approvers must sign the hash of the bundle as it was initialized
+    let approver = Keypair::from_base58_string(&context.approvers[0].to_base58_string());
+    assert_eq!(
+        approve_bundle(
+            &mut context,
+            &bundle_account.pubkey(),
+            &approver,
+            Hash::new_unique(),
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::InvalidSignature as u32)),
+    );
+    for approver in [0, 1] {
+        let approver = Keypair::from_base58_string(&context.approvers[approver].to_base58_string());
+        approve_bundle(
+            &mut context,
+            &bundle_account.pubkey(),
+            &approver,
+            bundle_hash,
+        )
+        .await
+        .unwrap();
+    }
+
+    for multisig_op_account in [&transfer_op_1, &transfer_op_2] {
+        context
+            .pt_context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[apply_op_bundle_approval(
+                    &program_id,
+                    &bundle_account.pubkey(),
+                    &multisig_op_account.pubkey(),
+                )],
+                Some(&payer),
+                &[&context.pt_context.payer],
+                context.pt_context.last_blockhash,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(
+            get_multisig_op_data(
+                &mut context.pt_context.banks_client,
+                multisig_op_account.pubkey()
+            )
+            .await
+            .operation_disposition(),
+            OperationDisposition::APPROVED
+        );
+    }
+
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[close_op_bundle(
+                &program_id,
+                &bundle_account.pubkey(),
+                &payer,
+            )],
+            Some(&payer),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+    assert!(context
+        .pt_context
+        .banks_client
+        .get_account(bundle_account.pubkey())
+        .await
+        .unwrap()
+        .is_none());
+}
@@ -0,0 +1,276 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use std::borrow::BorrowMut;
+use std::time::Duration;
+
+use solana_program::instruction::InstructionError::Custom;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program_test::BanksClientError;
+use solana_sdk::signature::Keypair;
+use solana_sdk::transaction::TransactionError;
+
+use strike_wallet::error::WalletError;
+use strike_wallet::model::multisig_op::{ApprovalDisposition, MultisigOp, OperationDisposition};
+use strike_wallet::model::payment_channel::PaymentChannel;
+use {
+    solana_program::system_instruction,
+    solana_program_test::tokio,
+    solana_sdk::{signature::Signer as SdkSigner, transaction::Transaction},
+};
+
+async fn pay(
+    context: &mut BalanceAccountTestContext,
+    channel_account: &Pubkey,
+    balance_account: &Pubkey,
+    payer: &Keypair,
+    amount: u64,
+) -> Result<(), BanksClientError> {
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[channel_payment(
+                &context.program_id,
+                channel_account,
+                &context.wallet_account.pubkey(),
+                balance_account,
+                &context.destination.pubkey(),
+                &payer.pubkey(),
+                amount,
+            )],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer, payer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+}
+
+async fn get_balance(context: &mut BalanceAccountTestContext, account: &Pubkey) -> u64 {
+    context
+        .pt_context
+        .banks_client
+        .get_balance(*account)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_sol_payment_channel() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let unit = context.rent.minimum_balance(0);
+    let allowance = unit * 3;
+    let duration = Duration::from_secs(3600);
+    let program_id = context.program_id;
+    let wallet_account = context.wallet_account.pubkey();
+    let destination = context.destination.pubkey();
+    let payer = context.pt_context.payer.pubkey();
+    let account_guid_hash = context.balance_account_guid_hash;
+
+    let multisig_op_account = Keypair::new();
+    let channel_account = Keypair::new();
+    let init = |allowance: u64| {
+        init_payment_channel(
+            &program_id,
+            &wallet_account,
+            &multisig_op_account.pubkey(),
+            &channel_account.pubkey(),
+            &destination,
+            &context.approvers[2].pubkey(),
+            &payer,
+            account_guid_hash,
+            context.destination_name_hash,
+            Pubkey::default(),
+            allowance,
+            duration,
+        )
+    };
+    let init_zero = init(0);
+    let init_allowance = init(allowance);
+    let init_op = |instruction| {
+        Transaction::new_signed_with_payer(
+            &[
+                system_instruction::create_account(
+                    &payer,
+                    &multisig_op_account.pubkey(),
+                    context.rent.minimum_balance(MultisigOp::LEN),
+                    MultisigOp::LEN as u64,
+                    &program_id,
+                ),
+                system_instruction::create_account(
+                    &payer,
+                    &channel_account.pubkey(),
+                    context.rent.minimum_balance(PaymentChannel::LEN),
+                    PaymentChannel::LEN as u64,
+                    &program_id,
+                ),
+                instruction,
+            ],
+            Some(&payer),
+            &[
+                &context.pt_context.payer,
+                &multisig_op_account,
+                &channel_account,
+                &context.approvers[2],
+            ],
+            context.pt_context.last_blockhash,
+        )
+    };
+    let zero_allowance = init_op(init_zero);
+    let open = init_op(init_allowance);
+
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .process_transaction(zero_allowance)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(2, Custom(WalletError::InvalidPaymentChannel as u32)),
+    );
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(open)
+        .await
+        .unwrap();
+
+    approve_or_deny_n_of_n_multisig_op(
+        context.pt_context.banks_client.borrow_mut(),
+        &program_id,
+        &multisig_op_account.pubkey(),
+        vec![&context.approvers[0], &context.approvers[1]],
+        &context.pt_context.payer,
+        context.pt_context.last_blockhash,
+        ApprovalDisposition::APPROVE,
+        OperationDisposition::APPROVED,
+    )
+    .await;
+
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[finalize_payment_channel(
+                &program_id,
+                &wallet_account,
+                &multisig_op_account.pubkey(),
+                &channel_account.pubkey(),
+                &destination,
+                &payer,
+                account_guid_hash,
+                Pubkey::default(),
+                allowance,
+                duration,
+            )],
+            Some(&payer),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    let channel = PaymentChannel::unpack_from_slice(
+        context
+            .pt_context
+            .banks_client
+            .get_account(channel_account.pubkey())
+            .await
+            .unwrap()
+            .unwrap()
+            .data
+            .as_slice(),
+    )
+    .unwrap();
+    assert_eq!(channel.allowance, allowance);
+    assert_eq!(channel.spent, 0);
+    assert_eq!(channel.destination, destination);
+
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(
+                &payer,
+                &balance_account,
+                unit * 5,
+            )],
+            Some(&payer),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // only the assistant draws down the channel
+    let approver = Keypair::from_bytes(&context.approvers[0].to_bytes()).unwrap();
+    assert_eq!(
+        pay(
+            &mut context,
+            &channel_account.pubkey(),
+            &balance_account,
+            &approver,
+            unit
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            Custom(WalletError::IncorrectInitiatorAccount as u32)
+        ),
+    );
+
+    let assistant = Keypair::from_bytes(&context.assistant_account.to_bytes()).unwrap();
+    pay(
+        &mut context,
+        &channel_account.pubkey(),
+        &balance_account,
+        &assistant,
+        unit * 2,
+    )
+    .await
+    .unwrap();
+    assert_eq!(get_balance(&mut context, &destination).await, unit * 2);
+    assert_eq!(get_balance(&mut context, &balance_account).await, unit * 3);
+
+    assert_eq!(
+        pay(
+            &mut context,
+            &channel_account.pubkey(),
+            &balance_account,
+            &assistant,
+            unit + 1
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            Custom(WalletError::PaymentChannelAllowanceExceeded as u32)
+        ),
+    );
+
+    // what is left of the allowance can't be drawn once the window has passed
+    context.pt_context.last_blockhash = advance_clock(&mut context.pt_context, duration).await;
+    assert_eq!(
+        pay(
+            &mut context,
+            &channel_account.pubkey(),
+            &balance_account,
+            &assistant,
+            unit
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::PaymentChannelExpired as u32)),
+    );
+    assert_eq!(get_balance(&mut context, &destination).await, unit * 2);
+}
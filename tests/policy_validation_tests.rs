@@ -0,0 +1,325 @@
+#![cfg(feature = "test-utils")]
+
+use std::time::Duration;
+
+use solana_program::hash::{hash, Hash};
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::{InitialWalletConfig, RewardSplit, WalletConfigPolicyUpdate};
+use strike_wallet::model::balance_account::{BalanceAccount, BalanceAccountGuidHash};
+use strike_wallet::model::multisig_op::SlotUpdateType;
+use strike_wallet::model::signer::{Signer, SignerCapabilities};
+use strike_wallet::model::wallet::{Wallet, WalletGuidHash};
+use strike_wallet::test_utils::{AccountInfoFixture, TestRng};
+use strike_wallet::utils::SlotId;
+
+const ITERATIONS: u64 = 500;
+
+fn err(error: WalletError) -> Result<(), ProgramError> {
+    Err(error.into())
+}
+
+fn random_capabilities(rng: &mut TestRng) -> SignerCapabilities {
+    SignerCapabilities::from_u8(rng.below(16) as u8).unwrap()
+}
+
+fn random_slots(rng: &mut TestRng, count: usize) -> Vec<usize> {
+    (0..count).filter(|_| rng.chance(1, 2)).collect()
+}
+
+fn signers_hash(wallet: &Wallet, slots: &[SlotId<Signer>]) -> Hash {
+    hash(
+        &slots
+            .iter()
+            .flat_map(|id| wallet.signers[*id].unwrap().key.to_bytes())
+            .collect::<Vec<_>>(),
+    )
+}
+
+/// A wallet with `signer_count` signers of arbitrary capabilities, in which every signer
+/// that may approve config is a config approver.
+fn random_wallet(rng: &mut TestRng, signer_count: usize) -> (Wallet, AccountInfoFixture) {
+    let mut signers: Vec<(SlotId<Signer>, Signer)> = (0..signer_count)
+        .map(|i| {
+            (
+                SlotId::new(i),
+                Signer::with_capabilities(Pubkey::new_unique(), random_capabilities(rng)),
+            )
+        })
+        .collect();
+    // keep at least one config approver around, as an initialized wallet must have one
+    signers[0].1.capabilities = signers[0]
+        .1
+        .capabilities
+        .union(SignerCapabilities::APPROVE_CONFIG);
+
+    let assistant = AccountInfoFixture::unique().signer();
+    let mut wallet = Wallet::unpack_unchecked(&vec![0; Wallet::LEN]).unwrap();
+    wallet
+        .init(
+            &WalletGuidHash::new(&hash(&rng.next_u64().to_le_bytes()).to_bytes()),
+            assistant.key(),
+            Pubkey::new_unique(),
+            &InitialWalletConfig {
+                approvals_required_for_config: 1,
+                approval_timeout_for_config: Duration::from_secs(3600),
+                config_approvers: signers
+                    .iter()
+                    .filter(|(_, signer)| signer.can(SignerCapabilities::APPROVE_CONFIG))
+                    .map(|(id, _)| *id)
+                    .collect(),
+                signers,
+            },
+        )
+        .unwrap();
+    (wallet, assistant)
+}
+
+#[test]
+fn test_validate_approval_timeout() {
+    let mut rng = TestRng::new(1);
+    let max = Wallet::MAX_APPROVAL_TIMEOUT.as_secs();
+    for _ in 0..ITERATIONS {
+        let timeout = Duration::from_secs(rng.below(max * 2));
+        let expected =
+            if timeout >= Wallet::MIN_APPROVAL_TIMEOUT && timeout <= Wallet::MAX_APPROVAL_TIMEOUT {
+                Ok(())
+            } else {
+                err(WalletError::InvalidApprovalTimeout)
+            };
+        assert_eq!(Wallet::validate_approval_timeout(&timeout), expected);
+    }
+    for timeout in [Wallet::MIN_APPROVAL_TIMEOUT, Wallet::MAX_APPROVAL_TIMEOUT] {
+        assert_eq!(Wallet::validate_approval_timeout(&timeout), Ok(()));
+    }
+}
+
+#[test]
+fn test_validate_config_policy_update() {
+    let mut rng = TestRng::new(2);
+    for _ in 0..ITERATIONS {
+        // leave a free slot to reference
+        let signer_count = 1 + rng.below(Wallet::MAX_SIGNERS as u64 - 1) as usize;
+        let (wallet, _) = random_wallet(&mut rng, signer_count);
+
+        // sometimes reference a slot past the configured signers
+        let config_approvers: Vec<SlotId<Signer>> = random_slots(&mut rng, signer_count + 1)
+            .into_iter()
+            .map(SlotId::new)
+            .collect();
+        let approvals_required = rng.below(config_approvers.len() as u64 + 2) as u8;
+        let approval_timeout =
+            Duration::from_secs(Wallet::MIN_APPROVAL_TIMEOUT.as_secs() - 1 + rng.below(7200));
+        let unknown_signer = config_approvers
+            .iter()
+            .any(|id| wallet.signers[*id].is_none());
+        let hash_matches = rng.chance(9, 10);
+        let max_timelock = Wallet::MAX_CONFIG_POLICY_TIMELOCK.as_secs();
+        let config_policy_timelock = Duration::from_secs(if rng.chance(1, 10) {
+            max_timelock + 1
+        } else {
+            rng.below(max_timelock + 1)
+        });
+        let update = WalletConfigPolicyUpdate {
+            approvals_required_for_config: approvals_required,
+            approval_timeout_for_config: approval_timeout,
+            signers_hash: if hash_matches && !unknown_signer {
+                signers_hash(&wallet, &config_approvers)
+            } else {
+                Hash::new_unique()
+            },
+            config_approvers: config_approvers.clone(),
+            config_policy_timelock,
+        };
+
+        let expected = if approval_timeout < Wallet::MIN_APPROVAL_TIMEOUT {
+            err(WalletError::InvalidApprovalTimeout)
+        } else if config_policy_timelock > Wallet::MAX_CONFIG_POLICY_TIMELOCK {
+            err(WalletError::InvalidConfigPolicyTimelock)
+        } else if unknown_signer {
+            err(WalletError::UnknownSigner)
+        } else if config_approvers.iter().any(|id| {
+            !wallet.signers[*id]
+                .unwrap()
+                .can(SignerCapabilities::APPROVE_CONFIG)
+        }) {
+            err(WalletError::SignerMissingCapability)
+        } else if !hash_matches {
+            err(WalletError::InvalidSignersHash)
+        } else if approvals_required == 0
+            || usize::from(approvals_required) > config_approvers.len()
+        {
+            err(WalletError::InvalidApproverCount)
+        } else {
+            Ok(())
+        };
+
+        let before = wallet.clone();
+        assert_eq!(wallet.validate_config_policy_update(&update), expected);
+        assert_eq!(wallet, before);
+
+        // validation agrees with the update it guards, which leaves a usable config policy
+        let mut updated = wallet.clone();
+        assert_eq!(updated.update_config_policy(&update), expected);
+        if expected.is_ok() {
+            assert_eq!(
+                updated.get_config_approvers_keys(),
+                config_approvers
+                    .iter()
+                    .map(|id| wallet.signers[*id].unwrap().key)
+                    .collect::<Vec<_>>()
+            );
+            assert!(usize::from(updated.approvals_required_for_config) <= config_approvers.len());
+        }
+
+        // an update which shortens the timelock is staged rather than applied right away
+        let mut scheduled = wallet.clone();
+        assert_eq!(
+            scheduled.schedule_config_policy_update(&update, 1000),
+            expected
+        );
+        if expected.is_ok() {
+            if config_policy_timelock < wallet.config_policy_timelock {
+                assert_eq!(
+                    scheduled.pending_config_policy.unwrap().activates_at,
+                    1000 + wallet.config_policy_timelock.as_secs() as i64
+                );
+                scheduled.pending_config_policy = None;
+                assert_eq!(scheduled, wallet);
+            } else {
+                assert_eq!(scheduled, updated);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_validate_reward_splits() {
+    let mut rng = TestRng::new(3);
+    let guid_hash = |i: u64| BalanceAccountGuidHash::new(&hash(&i.to_le_bytes()).to_bytes());
+    let (mut wallet, _) = random_wallet(&mut rng, 1);
+    let account_count = Wallet::MAX_BALANCE_ACCOUNTS as u64;
+    for i in 0..account_count {
+        let mut balance_account =
+            BalanceAccount::unpack_unchecked(&[0; BalanceAccount::LEN]).unwrap();
+        balance_account.guid_hash = guid_hash(i);
+        wallet
+            .balance_accounts
+            .insert(SlotId::new(i as usize), balance_account);
+    }
+
+    for _ in 0..ITERATIONS {
+        // mostly splits that add up to 100, as those are the interesting ones
+        let split_count = rng.below(5);
+        let mut remaining = 100u64;
+        let mut splits: Vec<RewardSplit> = (0..split_count)
+            .map(|i| {
+                let percentage = if i + 1 == split_count && rng.chance(4, 5) {
+                    remaining
+                } else {
+                    rng.below(remaining + 1)
+                };
+                remaining -= percentage;
+                RewardSplit {
+                    // one past the wallet's balance accounts is unknown
+                    account_guid_hash: guid_hash(rng.below(account_count + 1)),
+                    percentage: percentage as u8,
+                }
+            })
+            .collect();
+        if rng.chance(1, 10) && !splits.is_empty() {
+            splits[0].percentage = splits[0].percentage.wrapping_add(1);
+        }
+
+        let total: u32 = splits.iter().map(|it| u32::from(it.percentage)).sum();
+        let distinct = splits.iter().enumerate().all(|(i, split)| {
+            splits[..i]
+                .iter()
+                .all(|other| other.account_guid_hash != split.account_guid_hash)
+        });
+        let expected = if splits.is_empty()
+            || !distinct
+            || splits.iter().any(|it| it.percentage == 0)
+            || total != 100
+        {
+            err(WalletError::InvalidRewardSplit)
+        } else if splits
+            .iter()
+            .any(|it| it.account_guid_hash == guid_hash(account_count))
+        {
+            err(WalletError::BalanceAccountNotFound)
+        } else {
+            Ok(())
+        };
+        assert_eq!(wallet.validate_reward_splits(&splits), expected);
+    }
+}
+
+#[test]
+fn test_validate_initiators() {
+    let mut rng = TestRng::new(4);
+    for _ in 0..ITERATIONS {
+        let signer_count = 1 + rng.below(8) as usize;
+        let (wallet, mut assistant) = random_wallet(&mut rng, signer_count);
+        let (slot_id, signer) =
+            wallet.signers.filled_slots()[rng.below(signer_count as u64) as usize];
+        let signed = rng.chance(9, 10);
+        let mut initiator_fixture = AccountInfoFixture::new(signer.key);
+        if signed {
+            initiator_fixture = initiator_fixture.signer();
+        }
+        let initiator = initiator_fixture.account_info();
+
+        let expected = |may_initiate: bool| {
+            if !signed {
+                err(WalletError::InvalidSignature)
+            } else if may_initiate {
+                Ok(())
+            } else {
+                err(WalletError::SignerMissingCapability)
+            }
+        };
+        let can_initiate = signer.can(SignerCapabilities::INITIATE);
+        assert_eq!(
+            wallet.validate_config_initiator(&initiator),
+            expected(can_initiate)
+        );
+        assert_eq!(
+            wallet.validate_transfer_initiator(&initiator),
+            expected(can_initiate)
+        );
+
+        // a signer may resign from its own slot without being able to initiate otherwise
+        assert_eq!(
+            wallet.validate_update_signer_initiator(&initiator, SlotUpdateType::Clear, &signer),
+            expected(can_initiate || signer.can(SignerCapabilities::RESIGN))
+        );
+        let other = Signer::new(Pubkey::new_unique());
+        assert_eq!(
+            wallet.validate_update_signer_initiator(&initiator, SlotUpdateType::Clear, &other),
+            expected(can_initiate)
+        );
+        assert_eq!(
+            wallet.validate_update_signer_initiator(
+                &initiator,
+                SlotUpdateType::SetIfEmpty,
+                &signer
+            ),
+            expected(can_initiate)
+        );
+        assert!(wallet.signers[slot_id].is_some());
+
+        // the enabled assistant can always initiate, and strangers never can
+        assert_eq!(
+            wallet.validate_config_initiator(&assistant.account_info()),
+            Ok(())
+        );
+        assert_eq!(
+            wallet
+                .validate_transfer_initiator(&AccountInfoFixture::unique().signer().account_info()),
+            err(WalletError::InvalidApprover)
+        );
+    }
+}
@@ -0,0 +1,288 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use std::time::Duration;
+
+use solana_program::clock::Clock;
+use solana_program::hash::Hash;
+use solana_program::instruction::{AccountMeta, Instruction, InstructionError::Custom};
+use solana_program::pubkey::Pubkey;
+use solana_program::system_instruction;
+use solana_program_test::{tokio, BanksClientError};
+use solana_sdk::account::{AccountSharedData, WritableAccount};
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::Transaction;
+
+use strike_wallet::error::WalletError;
+use strike_wallet::model::compliance_policy::CompliancePolicy;
+use strike_wallet::model::multisig_op::MultisigOp;
+use strike_wallet::model::price_oracle::{PriceFeed, PriceOracle};
+
+// a Pyth v2 price account with just the fields the program reads filled in
+fn pyth_price_data(price: i64, conf: u64, expo: i32, publish_time: i64) -> Vec<u8> {
+    let mut data = vec![0; 3312];
+    data[0..4].copy_from_slice(&0xa1b2c3d4u32.to_le_bytes());
+    data[4..8].copy_from_slice(&2u32.to_le_bytes());
+    data[8..12].copy_from_slice(&3u32.to_le_bytes());
+    data[20..24].copy_from_slice(&expo.to_le_bytes());
+    data[96..104].copy_from_slice(&publish_time.to_le_bytes());
+    data[208..216].copy_from_slice(&price.to_le_bytes());
+    data[216..224].copy_from_slice(&conf.to_le_bytes());
+    data[224..228].copy_from_slice(&1u32.to_le_bytes());
+    data
+}
+
+async fn set_price(context: &mut BalanceAccountTestContext, price_account: &Pubkey, price: i64) {
+    let clock = context
+        .pt_context
+        .banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap();
+    let data = pyth_price_data(price, 100_000, -8, clock.unix_timestamp);
+    let mut account = AccountSharedData::new(
+        context.rent.minimum_balance(data.len()),
+        data.len(),
+        &Pubkey::new_unique(),
+    );
+    account.set_data(data);
+    context.pt_context.set_account(price_account, &account);
+}
+
+async fn update_wallet_config(
+    context: &mut BalanceAccountTestContext,
+    multisig_op_account: Keypair,
+    init: Instruction,
+    finalize: Instruction,
+) {
+    let mut test_context = context.to_test_context();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    init_multisig_op(
+        &mut test_context,
+        multisig_op_account,
+        init,
+        &context.approvers[0],
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut test_context,
+        &multisig_op_pubkey,
+        vec![&context.approvers[0], &context.approvers[1]],
+    )
+    .await;
+    finalize_multisig_op(&mut test_context, multisig_op_pubkey, finalize).await;
+}
+
+async fn update_price_oracle(context: &mut BalanceAccountTestContext, oracle: Option<PriceOracle>) {
+    let wallet = context.wallet_account.pubkey();
+    let rent_return = context.pt_context.payer.pubkey();
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let init = init_price_oracle_update(
+        &context.program_id,
+        &wallet,
+        &multisig_op_pubkey,
+        &context.approvers[0].pubkey(),
+        &rent_return,
+        oracle.clone(),
+    );
+    let finalize = finalize_price_oracle_update(
+        &context.program_id,
+        &wallet,
+        &multisig_op_pubkey,
+        &rent_return,
+        oracle,
+    );
+    update_wallet_config(context, multisig_op_account, init, finalize).await;
+}
+
+async fn update_compliance_policy(
+    context: &mut BalanceAccountTestContext,
+    policy: Option<CompliancePolicy>,
+) {
+    let wallet = context.wallet_account.pubkey();
+    let rent_return = context.pt_context.payer.pubkey();
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let init = init_compliance_policy_update(
+        &context.program_id,
+        &wallet,
+        &multisig_op_pubkey,
+        &context.approvers[0].pubkey(),
+        &rent_return,
+        policy,
+    );
+    let finalize = finalize_compliance_policy_update(
+        &context.program_id,
+        &wallet,
+        &multisig_op_pubkey,
+        &rent_return,
+        policy,
+    );
+    update_wallet_config(context, multisig_op_account, init, finalize).await;
+}
+
+async fn init_transfer_op(
+    context: &mut BalanceAccountTestContext,
+    amount: u64,
+    price_account: Option<&Pubkey>,
+) -> Result<MultisigOp, BanksClientError> {
+    let multisig_op_account = Keypair::new();
+    let initiator = &context.approvers[2];
+    let mut instruction = init_committed_transfer(
+        &context.program_id,
+        &context.wallet_account.pubkey(),
+        &multisig_op_account.pubkey(),
+        &initiator.pubkey(),
+        &context.pt_context.payer.pubkey(),
+        context.balance_account_guid_hash,
+        amount,
+        Hash::new_unique(),
+    );
+    if let Some(price_account) = price_account {
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(*price_account, false));
+    }
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                system_instruction::create_account(
+                    &context.pt_context.payer.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    context.rent.minimum_balance(MultisigOp::LEN),
+                    MultisigOp::LEN as u64,
+                    &context.program_id,
+                ),
+                instruction,
+            ],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer, &multisig_op_account, initiator],
+            context.pt_context.last_blockhash,
+        ))
+        .await?;
+    Ok(get_multisig_op_data(
+        &mut context.pt_context.banks_client,
+        multisig_op_account.pubkey(),
+    )
+    .await)
+}
+
+#[tokio::test]
+async fn test_usd_compliance_threshold() {
+    let (mut context, _) = setup_balance_account_tests_and_finalize(None).await;
+    let service = Keypair::new();
+    let price_account = Pubkey::new_unique();
+    let oracle = Some(PriceOracle {
+        feeds: vec![PriceFeed {
+            token_mint: Pubkey::default(),
+            price_account,
+            decimals: 9,
+        }],
+        max_staleness: Duration::from_secs(60),
+        max_confidence_bps: 100,
+    });
+    update_price_oracle(&mut context, oracle.clone()).await;
+    assert_eq!(
+        get_wallet(
+            &mut context.pt_context.banks_client,
+            &context.wallet_account.pubkey()
+        )
+        .await
+        .price_oracle,
+        oracle
+    );
+    // $100
+    update_compliance_policy(
+        &mut context,
+        Some(CompliancePolicy {
+            service: service.pubkey(),
+            threshold: 10_000,
+        }),
+    )
+    .await;
+
+    // at $25 a SOL, 3 SOL stay under the threshold while 5 SOL exceed it
+    set_price(&mut context, &price_account, 2_500_000_000).await;
+    let multisig_op = init_transfer_op(&mut context, 3_000_000_000, Some(&price_account))
+        .await
+        .unwrap();
+    assert_eq!(multisig_op.compliance_service, None);
+    let multisig_op = init_transfer_op(&mut context, 5_000_000_000, Some(&price_account))
+        .await
+        .unwrap();
+    assert_eq!(multisig_op.compliance_service, Some(service.pubkey()));
+
+    // while at $50 a SOL the same 3 SOL need the service's approval
+    set_price(&mut context, &price_account, 5_000_000_000).await;
+    let multisig_op = init_transfer_op(&mut context, 3_000_000_000, Some(&price_account))
+        .await
+        .unwrap();
+    assert_eq!(multisig_op.compliance_service, Some(service.pubkey()));
+
+    // the price account has to be passed, and its price has to be recent
+    assert_instruction_error(
+        init_transfer_op(&mut context, 3_000_000_000, None)
+            .await
+            .map(|_| ()),
+        1,
+        Custom(WalletError::InvalidPriceAccount as u32),
+    );
+    advance_clock(&mut context.pt_context, Duration::from_secs(61)).await;
+    assert_instruction_error(
+        init_transfer_op(&mut context, 3_000_000_000, Some(&price_account))
+            .await
+            .map(|_| ()),
+        1,
+        Custom(WalletError::StalePrice as u32),
+    );
+
+    // without the oracle the threshold is in lamports again
+    update_price_oracle(&mut context, None).await;
+    let multisig_op = init_transfer_op(&mut context, 10_000, None).await.unwrap();
+    assert_eq!(multisig_op.compliance_service, None);
+    let multisig_op = init_transfer_op(&mut context, 10_001, None).await.unwrap();
+    assert_eq!(multisig_op.compliance_service, Some(service.pubkey()));
+}
+
+#[tokio::test]
+async fn test_invalid_price_oracle() {
+    let (mut context, _) = setup_balance_account_tests_and_finalize(None).await;
+    let mut test_context = context.to_test_context();
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let feed = PriceFeed {
+        token_mint: Pubkey::default(),
+        price_account: Pubkey::new_unique(),
+        decimals: 9,
+    };
+    assert_instruction_error(
+        init_multisig_op(
+            &mut test_context,
+            multisig_op_account,
+            init_price_oracle_update(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                &multisig_op_pubkey,
+                &context.approvers[0].pubkey(),
+                &context.pt_context.payer.pubkey(),
+                Some(PriceOracle {
+                    feeds: vec![feed, feed],
+                    max_staleness: Duration::from_secs(60),
+                    max_confidence_bps: 100,
+                }),
+            ),
+            &context.approvers[0],
+        )
+        .await,
+        1,
+        Custom(WalletError::InvalidPriceOracle as u32),
+    );
+}
@@ -0,0 +1,179 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use std::time::Duration;
+
+use solana_program::bpf_loader_upgradeable;
+use solana_program::instruction::InstructionError::Custom;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program_test::{tokio, ProgramTestContext};
+use solana_sdk::account::{Account, AccountSharedData};
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::Transaction;
+
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::InitialWalletConfig;
+use strike_wallet::model::program_parameters::ProgramParameters;
+use strike_wallet::model::signer::Signer;
+use strike_wallet::model::wallet::WalletGuidHash;
+use strike_wallet::utils::SlotId;
+use uuid::Uuid;
+
+/// Stands in for the program data account the upgradeable loader keeps for a deployed program.
+fn set_program_data(pt_context: &mut ProgramTestContext, program_id: &Pubkey, authority: &Pubkey) {
+    let (program_data_address, _) =
+        Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+    let mut data = Vec::new();
+    data.extend_from_slice(&3u32.to_le_bytes());
+    data.extend_from_slice(&0u64.to_le_bytes());
+    data.push(1);
+    data.extend_from_slice(authority.as_ref());
+    pt_context.set_account(
+        &program_data_address,
+        &AccountSharedData::from(Account {
+            lamports: 1_000_000_000,
+            data,
+            owner: bpf_loader_upgradeable::id(),
+            executable: false,
+            rent_epoch: 0,
+        }),
+    );
+}
+
+async fn update_parameters(
+    pt_context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    authority: &Keypair,
+    parameters: ProgramParameters,
+) -> Result<(), solana_program_test::BanksClientError> {
+    let blockhash = pt_context.get_new_latest_blockhash().await.unwrap();
+    pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[update_program_parameters(
+                program_id,
+                &authority.pubkey(),
+                parameters,
+            )],
+            Some(&pt_context.payer.pubkey()),
+            &[&pt_context.payer, authority],
+            blockhash,
+        ))
+        .await
+}
+
+#[tokio::test]
+async fn test_program_parameters() {
+    let (context, mut pt_context) = setup_test_with_context(60_000).await;
+    let upgrade_authority = Keypair::new();
+    set_program_data(
+        &mut pt_context,
+        &context.program_id,
+        &upgrade_authority.pubkey(),
+    );
+
+    let parameters = ProgramParameters {
+        is_initialized: true,
+        max_signers: 2,
+        max_dapp_instructions: 4,
+        max_approval_timeout: Duration::from_secs(60 * 60 * 24),
+    };
+
+    // only the upgrade authority can set them
+    assert_instruction_error(
+        update_parameters(
+            &mut pt_context,
+            &context.program_id,
+            &Keypair::new(),
+            parameters,
+        )
+        .await,
+        0,
+        Custom(WalletError::InvalidSignature as u32),
+    );
+
+    // and only within the bounds in code
+    assert_instruction_error(
+        update_parameters(
+            &mut pt_context,
+            &context.program_id,
+            &upgrade_authority,
+            ProgramParameters {
+                max_signers: 100,
+                ..parameters
+            },
+        )
+        .await,
+        0,
+        Custom(WalletError::InvalidProgramParameters as u32),
+    );
+
+    update_parameters(
+        &mut pt_context,
+        &context.program_id,
+        &upgrade_authority,
+        parameters,
+    )
+    .await
+    .unwrap();
+    let (parameters_address, _) = ProgramParameters::find_address(&context.program_id);
+    assert_eq!(
+        ProgramParameters::unpack(
+            &pt_context
+                .banks_client
+                .get_account(parameters_address)
+                .await
+                .unwrap()
+                .unwrap()
+                .data
+        )
+        .unwrap(),
+        parameters
+    );
+
+    // a wallet can't be set up beyond them
+    let signers = vec![
+        (SlotId::new(0), Signer::new(Pubkey::new_unique())),
+        (SlotId::new(1), Signer::new(Pubkey::new_unique())),
+        (SlotId::new(2), Signer::new(Pubkey::new_unique())),
+    ];
+    let initial_config = |signers: Vec<(SlotId<Signer>, Signer)>| InitialWalletConfig {
+        approvals_required_for_config: 1,
+        approval_timeout_for_config: Duration::from_secs(3600),
+        config_approvers: vec![signers[0].0],
+        signers,
+    };
+    let blockhash = pt_context.get_new_latest_blockhash().await.unwrap();
+    assert_instruction_error(
+        common::utils::init_wallet(
+            &mut pt_context.banks_client,
+            &context.payer,
+            blockhash,
+            &context.program_id,
+            &Keypair::new(),
+            &Keypair::new(),
+            WalletGuidHash::new(&hash_of(Uuid::new_v4().as_bytes())),
+            initial_config(signers.clone()),
+        )
+        .await,
+        1,
+        Custom(WalletError::ProgramParameterLimitExceeded as u32),
+    );
+    common::utils::init_wallet(
+        &mut pt_context.banks_client,
+        &context.payer,
+        blockhash,
+        &context.program_id,
+        &Keypair::new(),
+        &Keypair::new(),
+        WalletGuidHash::new(&hash_of(Uuid::new_v4().as_bytes())),
+        initial_config(signers[..2].to_vec()),
+    )
+    .await
+    .unwrap();
+}
@@ -0,0 +1,159 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use solana_program::instruction::InstructionError::Custom;
+use solana_program::pubkey::Pubkey;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::{Transaction, TransactionError};
+
+use strike_wallet::error::WalletError;
+use strike_wallet::model::multisig_op::MultisigOp;
+use strike_wallet::model::rent_return_allowlist::RentReturnAllowlist;
+
+/// Initiates and approves an allowlist update whose rent is returned to `rent_return`, and
+/// returns its multisig op account.
+async fn init_and_approve_update(
+    context: &mut BalanceAccountTestContext,
+    rent_return: &Keypair,
+    allowlist: RentReturnAllowlist,
+) -> Pubkey {
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let init = init_rent_return_allowlist_update(
+        &context.program_id,
+        &context.wallet_account.pubkey(),
+        &multisig_op_pubkey,
+        &context.approvers[0].pubkey(),
+        &rent_return.pubkey(),
+        allowlist,
+    );
+    let mut test_context = context.to_test_context();
+    test_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                create_program_owned_account_instruction(
+                    &test_context,
+                    &multisig_op_pubkey,
+                    MultisigOp::LEN,
+                ),
+                init,
+            ],
+            Some(&test_context.payer.pubkey()),
+            &[
+                &test_context.payer,
+                &multisig_op_account,
+                &context.approvers[0],
+                rent_return,
+            ],
+            test_context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut test_context,
+        &multisig_op_pubkey,
+        vec![&context.approvers[0], &context.approvers[1]],
+    )
+    .await;
+    multisig_op_pubkey
+}
+
+async fn finalize_update(
+    context: &mut BalanceAccountTestContext,
+    multisig_op_account: &Pubkey,
+    rent_return: &Keypair,
+    allowlist: RentReturnAllowlist,
+) -> Result<(), TransactionError> {
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[finalize_rent_return_allowlist_update(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                multisig_op_account,
+                &rent_return.pubkey(),
+                allowlist,
+            )],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer, rent_return],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+#[tokio::test]
+async fn test_rent_return_allowlist() {
+    let (mut context, _) = setup_balance_account_tests_and_finalize(None).await;
+    let rent_collector = Keypair::new();
+    let other_rent_collector = Keypair::new();
+    let allowlist = RentReturnAllowlist {
+        entries: vec![rent_collector.pubkey()],
+    };
+
+    // with no allowlist any rent return account is allowed
+    let multisig_op_account =
+        init_and_approve_update(&mut context, &other_rent_collector, allowlist.clone()).await;
+    finalize_update(
+        &mut context,
+        &multisig_op_account,
+        &other_rent_collector,
+        allowlist.clone(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        get_wallet(
+            &mut context.pt_context.banks_client,
+            &context.wallet_account.pubkey()
+        )
+        .await
+        .rent_return_allowlist,
+        allowlist
+    );
+
+    // an op initiated with a rent return account off the list can't be finalized
+    let cleared = RentReturnAllowlist::default();
+    let multisig_op_account =
+        init_and_approve_update(&mut context, &other_rent_collector, cleared.clone()).await;
+    assert_eq!(
+        finalize_update(
+            &mut context,
+            &multisig_op_account,
+            &other_rent_collector,
+            cleared.clone()
+        )
+        .await,
+        Err(TransactionError::InstructionError(
+            0,
+            Custom(WalletError::RentReturnNotAllowed as u32)
+        ))
+    );
+
+    let multisig_op_account =
+        init_and_approve_update(&mut context, &rent_collector, cleared.clone()).await;
+    finalize_update(
+        &mut context,
+        &multisig_op_account,
+        &rent_collector,
+        cleared.clone(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        get_wallet(
+            &mut context.pt_context.banks_client,
+            &context.wallet_account.pubkey()
+        )
+        .await
+        .rent_return_allowlist,
+        cleared
+    );
+}
@@ -0,0 +1,173 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use solana_program::clock::Clock;
+use solana_program::instruction::{Instruction, InstructionError::Custom};
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program_test::{tokio, BanksClientError};
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::Transaction;
+
+use strike_wallet::error::WalletError;
+use strike_wallet::model::multisig_op::ApprovalDisposition;
+use strike_wallet::model::session_key_registry::{SessionKeyRegistry, SessionKeyScope};
+
+async fn process(
+    context: &mut BalanceAccountTestContext,
+    instruction: Instruction,
+    signer: &Keypair,
+) -> Result<(), BanksClientError> {
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer, signer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+}
+
+async fn session_approve(
+    context: &mut BalanceAccountTestContext,
+    multisig_op: &Pubkey,
+    session_key: &Keypair,
+) -> Result<(), BanksClientError> {
+    let params_hash = get_multisig_op_data(&mut context.pt_context.banks_client, *multisig_op)
+        .await
+        .params_hash
+        .unwrap();
+    let instruction = set_session_approval_disposition(
+        &context.program_id,
+        &context.wallet_account.pubkey(),
+        multisig_op,
+        &session_key.pubkey(),
+        ApprovalDisposition::APPROVE,
+        params_hash,
+    );
+    process(context, instruction, session_key).await
+}
+
+async fn get_registry(context: &mut BalanceAccountTestContext) -> SessionKeyRegistry {
+    let (registry_address, _) =
+        SessionKeyRegistry::find_address(&context.wallet_account.pubkey(), &context.program_id);
+    SessionKeyRegistry::unpack(
+        &context
+            .pt_context
+            .banks_client
+            .get_account(registry_address)
+            .await
+            .unwrap()
+            .unwrap()
+            .data,
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_session_key_approval() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let initiator = Keypair::from_bytes(&context.approvers[2].to_bytes()).unwrap();
+    let approver = Keypair::from_bytes(&context.approvers[0].to_bytes()).unwrap();
+    let session_key = Keypair::new();
+    let now = context
+        .pt_context
+        .banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap()
+        .unix_timestamp;
+
+    let register = register_session_key(
+        &context.program_id,
+        &context.wallet_account.pubkey(),
+        &approver.pubkey(),
+        &context.pt_context.payer.pubkey(),
+        &session_key.pubkey(),
+        SessionKeyScope {
+            categories: SessionKeyScope::TRANSFER,
+            max_transfer_amount: 1000,
+        },
+        now + 3600,
+    );
+    process(&mut context, register, &approver).await.unwrap();
+    let registry = get_registry(&mut context).await;
+    assert_eq!(
+        registry.get(&session_key.pubkey()).unwrap().approver,
+        approver.pubkey()
+    );
+
+    // a transfer above the session's limit can't be approved with it
+    let (multisig_op_account, result) =
+        setup_transfer_test(&mut context, &initiator, &balance_account, None, 1001).await;
+    result.unwrap();
+    assert_instruction_error(
+        session_approve(&mut context, &multisig_op_account.pubkey(), &session_key).await,
+        0,
+        Custom(WalletError::SessionKeyOutOfScope as u32),
+    );
+
+    // one within it is approved on the approver's behalf
+    let (multisig_op_account, result) =
+        setup_transfer_test(&mut context, &initiator, &balance_account, None, 1000).await;
+    result.unwrap();
+    session_approve(&mut context, &multisig_op_account.pubkey(), &session_key)
+        .await
+        .unwrap();
+    let multisig_op = get_multisig_op_data(
+        &mut context.pt_context.banks_client,
+        multisig_op_account.pubkey(),
+    )
+    .await;
+    assert!(multisig_op
+        .disposition_records
+        .iter()
+        .any(|record| record.approver == approver.pubkey()
+            && record.disposition == ApprovalDisposition::APPROVE));
+
+    // once revoked, the session key can't approve anymore
+    let revoke = revoke_session_key(
+        &context.program_id,
+        &context.wallet_account.pubkey(),
+        &approver.pubkey(),
+        &session_key.pubkey(),
+    );
+    process(&mut context, revoke, &approver).await.unwrap();
+    let (multisig_op_account, result) =
+        setup_transfer_test(&mut context, &initiator, &balance_account, None, 10).await;
+    result.unwrap();
+    assert_instruction_error(
+        session_approve(&mut context, &multisig_op_account.pubkey(), &session_key).await,
+        0,
+        Custom(WalletError::InvalidSessionKey as u32),
+    );
+}
+
+#[tokio::test]
+async fn test_only_signers_register_session_keys() {
+    let (mut context, _) = setup_balance_account_tests_and_finalize(None).await;
+    let outsider = Keypair::new();
+    let register = register_session_key(
+        &context.program_id,
+        &context.wallet_account.pubkey(),
+        &outsider.pubkey(),
+        &context.pt_context.payer.pubkey(),
+        &Keypair::new().pubkey(),
+        SessionKeyScope {
+            categories: SessionKeyScope::CONFIG,
+            max_transfer_amount: 0,
+        },
+        i64::MAX,
+    );
+    assert_instruction_error(
+        process(&mut context, register, &outsider).await,
+        0,
+        Custom(WalletError::InvalidApprover as u32),
+    );
+}
@@ -29,7 +29,7 @@ use {
 
 #[tokio::test]
 async fn test_sign_data() {
-    let mut context = setup_test(20_000).await;
+    let mut context = setup_test(30_000).await;
 
     let wallet_account = Keypair::new();
     let assistant_account = Keypair::new();
@@ -115,10 +115,14 @@ async fn test_sign_data() {
             ApprovalDispositionRecord {
                 approver: approvers[0].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                reason: None,
+                stake_weight: 0,
             },
             ApprovalDispositionRecord {
                 approver: approvers[1].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                reason: None,
+                stake_weight: 0,
             },
         ])
     );
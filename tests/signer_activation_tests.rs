@@ -0,0 +1,183 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use std::time::Duration;
+
+use solana_program::instruction::{Instruction, InstructionError::Custom};
+use solana_program::pubkey::Pubkey;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+
+use strike_wallet::error::WalletError;
+use strike_wallet::model::multisig_op::SlotUpdateType;
+use strike_wallet::model::wallet_freeze::WalletFreeze;
+use strike_wallet::utils::SlotId;
+
+async fn approve_and_finalize_config_op(
+    context: &mut BalanceAccountTestContext,
+    init: Instruction,
+    multisig_op_account: Keypair,
+    finalize: Instruction,
+) {
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let mut test_context = context.to_test_context();
+    init_multisig_op(
+        &mut test_context,
+        multisig_op_account,
+        init,
+        &context.approvers[0],
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut test_context,
+        &multisig_op_pubkey,
+        vec![&context.approvers[0], &context.approvers[1]],
+    )
+    .await;
+    finalize_multisig_op(&mut test_context, multisig_op_pubkey, finalize).await;
+}
+
+async fn update_signer_activation_delay(context: &mut BalanceAccountTestContext, delay: Duration) {
+    let wallet = context.wallet_account.pubkey();
+    let rent_return = context.pt_context.payer.pubkey();
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let init = init_signer_activation_delay_update(
+        &context.program_id,
+        &wallet,
+        &multisig_op_pubkey,
+        &context.approvers[0].pubkey(),
+        &rent_return,
+        delay,
+    );
+    let finalize = finalize_signer_activation_delay_update(
+        &context.program_id,
+        &wallet,
+        &multisig_op_pubkey,
+        &rent_return,
+        delay,
+    );
+    approve_and_finalize_config_op(context, init, multisig_op_account, finalize).await;
+}
+
+async fn add_signer(context: &mut BalanceAccountTestContext, slot_id: usize, signer: &Keypair) {
+    let wallet = context.wallet_account.pubkey();
+    let rent_return = context.pt_context.payer.pubkey();
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let init = init_update_signer(
+        &context.program_id,
+        &wallet,
+        &multisig_op_pubkey,
+        &context.approvers[0].pubkey(),
+        &rent_return,
+        SlotUpdateType::SetIfEmpty,
+        SlotId::new(slot_id),
+        signer.pubkey_as_signer(),
+        None,
+        None,
+    );
+    let finalize = finalize_update_signer(
+        &context.program_id,
+        &wallet,
+        &multisig_op_pubkey,
+        &rent_return,
+        SlotUpdateType::SetIfEmpty,
+        SlotId::new(slot_id),
+        signer.pubkey_as_signer(),
+        None,
+    );
+    approve_and_finalize_config_op(context, init, multisig_op_account, finalize).await;
+}
+
+fn init_wallet_freeze_instruction(
+    context: &BalanceAccountTestContext,
+    initiator: &Pubkey,
+    multisig_op_account: &Keypair,
+) -> Instruction {
+    init_wallet_freeze(
+        &context.program_id,
+        &context.wallet_account.pubkey(),
+        &multisig_op_account.pubkey(),
+        initiator,
+        &context.pt_context.payer.pubkey(),
+        WalletFreeze {
+            frozen: false,
+            emergency_quorum: 2,
+        },
+    )
+}
+
+#[tokio::test]
+async fn test_added_signer_waits_out_activation_delay() {
+    let (mut context, _) = setup_balance_account_tests_and_finalize(None).await;
+    let delay = Duration::from_secs(60 * 60 * 24);
+
+    update_signer_activation_delay(&mut context, delay).await;
+    assert_eq!(
+        get_wallet(
+            &mut context.pt_context.banks_client,
+            &context.wallet_account.pubkey()
+        )
+        .await
+        .signer_activations
+        .delay,
+        delay
+    );
+
+    let new_signer = Keypair::new();
+    add_signer(&mut context, 3, &new_signer).await;
+
+    let multisig_op_account = Keypair::new();
+    verify_multisig_op_init_fails(
+        &mut context.pt_context.banks_client,
+        context.pt_context.last_blockhash,
+        &context.pt_context.payer,
+        &new_signer,
+        &multisig_op_account,
+        init_wallet_freeze_instruction(&context, &new_signer.pubkey(), &multisig_op_account),
+        Custom(WalletError::SignerNotYetActive as u32),
+    )
+    .await;
+
+    advance_clock(&mut context.pt_context, delay).await;
+    let multisig_op_account = Keypair::new();
+    let init = init_wallet_freeze_instruction(&context, &new_signer.pubkey(), &multisig_op_account);
+    init_multisig_op(
+        &mut context.to_test_context(),
+        multisig_op_account,
+        init,
+        &new_signer,
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_signer_activation_delay_bounds() {
+    let (mut context, _) = setup_balance_account_tests_and_finalize(None).await;
+    let multisig_op_account = Keypair::new();
+    let initiator = Keypair::from_base58_string(&context.approvers[0].to_base58_string());
+    verify_multisig_op_init_fails(
+        &mut context.pt_context.banks_client,
+        context.pt_context.last_blockhash,
+        &context.pt_context.payer,
+        &initiator,
+        &multisig_op_account,
+        init_signer_activation_delay_update(
+            &context.program_id,
+            &context.wallet_account.pubkey(),
+            &multisig_op_account.pubkey(),
+            &initiator.pubkey(),
+            &context.pt_context.payer.pubkey(),
+            Duration::from_secs(60 * 60 * 24 * 31),
+        ),
+        Custom(WalletError::InvalidSignerActivationDelay as u32),
+    )
+    .await;
+}
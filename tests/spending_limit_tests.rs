@@ -0,0 +1,232 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use std::borrow::BorrowMut;
+
+use solana_program::instruction::{Instruction, InstructionError::Custom};
+use solana_program::pubkey::Pubkey;
+use solana_program::system_instruction;
+use solana_program::system_program;
+use solana_program_test::{tokio, BanksClientError};
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::Transaction;
+use strike_wallet::error::WalletError;
+use strike_wallet::model::balance_account::SpendingLimit;
+use strike_wallet::model::multisig_op::{ApprovalDisposition, OperationDisposition};
+
+async fn finalize_sol_transfer(
+    context: &mut BalanceAccountTestContext,
+    multisig_op_account: &Pubkey,
+    balance_account: &Pubkey,
+    amount: u64,
+) -> Result<(), BanksClientError> {
+    let mut instruction: Instruction = finalize_transfer(
+        &context.program_id,
+        multisig_op_account,
+        &context.wallet_account.pubkey(),
+        balance_account,
+        &context.destination.pubkey(),
+        &context.pt_context.payer.pubkey(),
+        context.balance_account_guid_hash,
+        amount,
+        &system_program::id(),
+        None,
+        None,
+        None,
+    );
+    // the spending limit is recorded in the wallet
+    instruction.accounts[1].is_writable = true;
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+}
+
+#[tokio::test]
+async fn test_transfers_within_spending_limit() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let mut test_context = context.to_test_context();
+    let program_id = context.program_id;
+    let wallet_address = context.wallet_account.pubkey();
+    let rent_return = test_context.payer.pubkey();
+    let rent = test_context.banks_client.get_rent().await.unwrap();
+    let balance_account_rent = rent.minimum_balance(0);
+    let limit = balance_account_rent * 3;
+
+    test_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(
+                &rent_return,
+                &balance_account,
+                balance_account_rent * 5,
+            )],
+            Some(&rent_return),
+            &[&test_context.payer],
+            test_context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    init_multisig_op(
+        &mut test_context,
+        multisig_op_account,
+        init_spending_limit_update(
+            &program_id,
+            &wallet_address,
+            &multisig_op_pubkey,
+            &context.assistant_account.pubkey(),
+            &rent_return,
+            context.balance_account_guid_hash,
+            system_program::id(),
+            limit,
+        ),
+        &context.assistant_account,
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut test_context,
+        &multisig_op_pubkey,
+        vec![&context.approvers[0], &context.approvers[1]],
+    )
+    .await;
+    test_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[finalize_spending_limit_update(
+                &program_id,
+                &wallet_address,
+                &multisig_op_pubkey,
+                &rent_return,
+                context.balance_account_guid_hash,
+                system_program::id(),
+                limit,
+            )],
+            Some(&rent_return),
+            &[&test_context.payer],
+            test_context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    let wallet = get_wallet(&mut test_context.banks_client, &wallet_address).await;
+    assert_eq!(
+        wallet
+            .get_balance_account(&context.balance_account_guid_hash)
+            .unwrap()
+            .spending_limit,
+        SpendingLimit::new(system_program::id(), limit)
+    );
+
+    // a transfer within the limit is finalized on its initiator's approval alone
+    let initiator = &Keypair::from_base58_string(&context.approvers[0].to_base58_string());
+    let (multisig_op_account, result) = setup_transfer_test(
+        context.borrow_mut(),
+        initiator,
+        &balance_account,
+        None,
+        balance_account_rent,
+    )
+    .await;
+    result.unwrap();
+    finalize_sol_transfer(
+        &mut context,
+        &multisig_op_account.pubkey(),
+        &balance_account,
+        balance_account_rent,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .get_balance(context.destination.pubkey())
+            .await
+            .unwrap(),
+        balance_account_rent
+    );
+    let wallet = get_wallet(&mut context.pt_context.banks_client, &wallet_address).await;
+    assert_eq!(
+        wallet
+            .get_balance_account(&context.balance_account_guid_hash)
+            .unwrap()
+            .spending_limit
+            .spent_in_window,
+        balance_account_rent
+    );
+
+    // while one exceeding what is left of it still needs the approval quorum
+    let (multisig_op_account, result) = setup_transfer_test(
+        context.borrow_mut(),
+        initiator,
+        &balance_account,
+        None,
+        limit,
+    )
+    .await;
+    result.unwrap();
+    assert_instruction_error(
+        finalize_sol_transfer(
+            &mut context,
+            &multisig_op_account.pubkey(),
+            &balance_account,
+            limit,
+        )
+        .await,
+        0,
+        Custom(WalletError::TransferDispositionNotFinal as u32),
+    );
+    approve_or_deny_n_of_n_multisig_op(
+        context.pt_context.banks_client.borrow_mut(),
+        &context.program_id,
+        &multisig_op_account.pubkey(),
+        vec![&context.approvers[1]],
+        &context.pt_context.payer,
+        context.pt_context.last_blockhash,
+        ApprovalDisposition::APPROVE,
+        OperationDisposition::APPROVED,
+    )
+    .await;
+    finalize_sol_transfer(
+        &mut context,
+        &multisig_op_account.pubkey(),
+        &balance_account,
+        limit,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        context
+            .pt_context
+            .banks_client
+            .get_balance(context.destination.pubkey())
+            .await
+            .unwrap(),
+        balance_account_rent + limit
+    );
+
+    // and doesn't count against the limit
+    let wallet = get_wallet(&mut context.pt_context.banks_client, &wallet_address).await;
+    assert_eq!(
+        wallet
+            .get_balance_account(&context.balance_account_guid_hash)
+            .unwrap()
+            .spending_limit
+            .spent_in_window,
+        balance_account_rent
+    );
+}
@@ -0,0 +1,236 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use solana_program::instruction::{Instruction, InstructionError::Custom};
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_instruction;
+use solana_program_test::{tokio, BanksClientError};
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::Transaction;
+use strike_wallet::error::WalletError;
+use strike_wallet::model::balance_account::BalanceAccountGuidHash;
+use strike_wallet::model::multisig_op::MultisigOpState;
+use strike_wallet::model::wallet::WalletGuidHash;
+use uuid::Uuid;
+
+struct SPLTokenAccountsCreationTestContext {
+    test_context: TestContext,
+    wallet: Keypair,
+    approvers: Vec<Keypair>,
+    balance_accounts: Vec<(BalanceAccountGuidHash, Pubkey)>,
+    mint: Pubkey,
+}
+
+async fn setup_spl_token_accounts_creation_test() -> SPLTokenAccountsCreationTestContext {
+    let mut test_context = setup_test(200_000).await;
+
+    let wallet = Keypair::new();
+    let wallet_guid_hash = WalletGuidHash::new(&hash_of(Uuid::new_v4().as_bytes()));
+    let assistant = Keypair::new();
+    let approvers = vec![Keypair::new()];
+    create_wallet(
+        &mut test_context,
+        &wallet,
+        &wallet_guid_hash,
+        &assistant,
+        &approvers,
+    )
+    .await;
+    let rent = test_context.banks_client.get_rent().await.unwrap();
+    let balance_accounts = create_balance_accounts(
+        &mut test_context,
+        &wallet.pubkey(),
+        &wallet_guid_hash,
+        &assistant,
+        &approvers,
+        3,
+        Some(rent.minimum_balance(0) + rent.minimum_balance(spl_token::state::Account::LEN) * 3),
+    )
+    .await
+    .iter()
+    .map(|(guid_hash, (pda, _))| (*guid_hash, *pda))
+    .collect();
+
+    let mint = Keypair::new();
+    test_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                system_instruction::create_account(
+                    &test_context.payer.pubkey(),
+                    &mint.pubkey(),
+                    rent.minimum_balance(spl_token::state::Mint::LEN),
+                    spl_token::state::Mint::LEN as u64,
+                    &spl_token::id(),
+                ),
+                spl_token::instruction::initialize_mint(
+                    &spl_token::id(),
+                    &mint.pubkey(),
+                    &test_context.payer.pubkey(),
+                    None,
+                    6,
+                )
+                .unwrap(),
+            ],
+            Some(&test_context.payer.pubkey()),
+            &[&test_context.payer, &mint],
+            test_context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    SPLTokenAccountsCreationTestContext {
+        test_context,
+        wallet,
+        approvers,
+        balance_accounts,
+        mint: mint.pubkey(),
+    }
+}
+
+fn finalize_instruction(
+    context: &SPLTokenAccountsCreationTestContext,
+    multisig_op_account: &Pubkey,
+    batch: &[usize],
+    continuing: bool,
+) -> Instruction {
+    finalize_spl_token_accounts_creation(
+        &context.test_context.program_id,
+        &context.wallet.pubkey(),
+        multisig_op_account,
+        &context.balance_accounts[0].1,
+        &context.mint,
+        &spl_token::id(),
+        &context.test_context.payer.pubkey(),
+        context.balance_accounts[0].0,
+        context
+            .balance_accounts
+            .iter()
+            .map(|(guid_hash, _)| *guid_hash)
+            .collect(),
+        &batch
+            .iter()
+            .map(|index| context.balance_accounts[*index].1)
+            .collect::<Vec<_>>(),
+        continuing,
+    )
+}
+
+async fn process(
+    context: &mut SPLTokenAccountsCreationTestContext,
+    instruction: Instruction,
+) -> Result<(), BanksClientError> {
+    let test_context = &mut context.test_context;
+    test_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&test_context.payer.pubkey()),
+            &[&test_context.payer],
+            test_context.recent_blockhash,
+        ))
+        .await
+}
+
+async fn token_account_exists(
+    context: &mut SPLTokenAccountsCreationTestContext,
+    balance_account: &Pubkey,
+) -> bool {
+    let token_account =
+        get_associated_token_account_addresses(&vec![*balance_account], &context.mint)[0];
+    context
+        .test_context
+        .banks_client
+        .get_account(token_account)
+        .await
+        .unwrap()
+        .is_some()
+}
+
+#[tokio::test]
+async fn test_spl_token_accounts_creation_in_batches() {
+    let mut context = setup_spl_token_accounts_creation_test().await;
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let instruction = init_spl_token_accounts_creation(
+        &context.test_context.program_id,
+        &context.wallet.pubkey(),
+        &multisig_op_pubkey,
+        &context.mint,
+        &context.approvers[0].pubkey(),
+        &context.test_context.payer.pubkey(),
+        context.balance_accounts[0].0,
+        context
+            .balance_accounts
+            .iter()
+            .map(|(guid_hash, _)| *guid_hash)
+            .collect(),
+    );
+    init_multisig_op(
+        &mut context.test_context,
+        multisig_op_account,
+        instruction,
+        &context.approvers[0],
+    )
+    .await
+    .unwrap();
+
+    // the op can't be continued before it is finalized
+    let instruction = finalize_instruction(&context, &multisig_op_pubkey, &[0], true);
+    assert_instruction_error(
+        process(&mut context, instruction).await,
+        0,
+        Custom(WalletError::OperationNotExecuted as u32),
+    );
+
+    // the first batch is created by the finalize, which keeps the op open
+    let instruction = finalize_instruction(&context, &multisig_op_pubkey, &[0], false);
+    process(&mut context, instruction).await.unwrap();
+    let multisig_op =
+        get_multisig_op_data(&mut context.test_context.banks_client, multisig_op_pubkey).await;
+    assert_eq!(multisig_op.state, MultisigOpState::Executed);
+    assert_eq!(multisig_op.spl_token_accounts_created, 0b001);
+    let balance_account = context.balance_accounts[0].1;
+    assert!(token_account_exists(&mut context, &balance_account).await);
+
+    // a batch can't be larger than what is left
+    let instruction = finalize_instruction(&context, &multisig_op_pubkey, &[1, 2, 0], true);
+    assert_instruction_error(
+        process(&mut context, instruction).await,
+        0,
+        Custom(WalletError::InvalidSPLTokenAccountsCreation as u32),
+    );
+
+    // a token account created in the meantime is skipped
+    let balance_account = context.balance_accounts[2].1;
+    let test_context = &mut context.test_context;
+    test_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                spl_associated_token_account::instruction::create_associated_token_account(
+                    &test_context.payer.pubkey(),
+                    &balance_account,
+                    &context.mint,
+                ),
+            ],
+            Some(&test_context.payer.pubkey()),
+            &[&test_context.payer],
+            test_context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // and the op is closed once the rest have been created, without further approvals
+    let instruction = finalize_instruction(&context, &multisig_op_pubkey, &[1, 2], true);
+    finalize_multisig_op(&mut context.test_context, multisig_op_pubkey, instruction).await;
+    for index in 0..3 {
+        let balance_account = context.balance_accounts[index].1;
+        assert!(token_account_exists(&mut context, &balance_account).await);
+    }
+}
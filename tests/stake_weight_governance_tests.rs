@@ -0,0 +1,341 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use solana_program::instruction::{Instruction, InstructionError::Custom};
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_instruction;
+use solana_program_test::{tokio, BanksClientError};
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::Transaction;
+
+use strike_wallet::error::WalletError;
+use strike_wallet::model::multisig_op::{
+    ApprovalDisposition, MultisigOp, MultisigOpState, OperationDisposition,
+};
+use strike_wallet::model::stake_weight_governance::StakeWeightGovernance;
+
+/// Creates a governance token and mints the given holdings into its holders' associated token
+/// accounts.
+async fn create_governance_token(
+    context: &mut BalanceAccountTestContext,
+    holdings: &[(Pubkey, u64)],
+) -> Pubkey {
+    let mint = Keypair::new();
+    let mint_authority = Keypair::new();
+    let mut instructions = vec![
+        system_instruction::create_account(
+            &context.pt_context.payer.pubkey(),
+            &mint.pubkey(),
+            context.rent.minimum_balance(spl_token::state::Mint::LEN),
+            spl_token::state::Mint::LEN as u64,
+            &spl_token::id(),
+        ),
+        spl_token::instruction::initialize_mint(
+            &spl_token::id(),
+            &mint.pubkey(),
+            &mint_authority.pubkey(),
+            None,
+            0,
+        )
+        .unwrap(),
+    ];
+    for (holder, amount) in holdings {
+        instructions.push(
+            spl_associated_token_account::instruction::create_associated_token_account(
+                &context.pt_context.payer.pubkey(),
+                holder,
+                &mint.pubkey(),
+            ),
+        );
+        instructions.push(
+            spl_token::instruction::mint_to(
+                &spl_token::id(),
+                &mint.pubkey(),
+                &spl_associated_token_account::get_associated_token_address(holder, &mint.pubkey()),
+                &mint_authority.pubkey(),
+                &[],
+                *amount,
+            )
+            .unwrap(),
+        );
+    }
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer, &mint, &mint_authority],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+    mint.pubkey()
+}
+
+fn governance_update_instructions(
+    context: &BalanceAccountTestContext,
+    multisig_op_account: &Pubkey,
+    governance: Option<StakeWeightGovernance>,
+) -> (Instruction, Instruction) {
+    let wallet = context.wallet_account.pubkey();
+    let rent_return = context.pt_context.payer.pubkey();
+    (
+        init_stake_weight_governance_update(
+            &context.program_id,
+            &wallet,
+            multisig_op_account,
+            &context.approvers[0].pubkey(),
+            &rent_return,
+            governance,
+        ),
+        finalize_stake_weight_governance_update(
+            &context.program_id,
+            &wallet,
+            multisig_op_account,
+            &rent_return,
+            governance,
+        ),
+    )
+}
+
+async fn enable_governance(
+    context: &mut BalanceAccountTestContext,
+    governance: StakeWeightGovernance,
+) {
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let (init, finalize) =
+        governance_update_instructions(context, &multisig_op_pubkey, Some(governance));
+    let mut test_context = context.to_test_context();
+    init_multisig_op(
+        &mut test_context,
+        multisig_op_account,
+        init,
+        &context.approvers[0],
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut test_context,
+        &multisig_op_pubkey,
+        vec![&context.approvers[0], &context.approvers[1]],
+    )
+    .await;
+    finalize_multisig_op(&mut test_context, multisig_op_pubkey, finalize).await;
+    assert_eq!(
+        get_wallet(
+            &mut context.pt_context.banks_client,
+            &context.wallet_account.pubkey()
+        )
+        .await
+        .stake_weight_governance,
+        Some(governance)
+    );
+}
+
+async fn get_multisig_op(context: &mut BalanceAccountTestContext, account: &Pubkey) -> MultisigOp {
+    MultisigOp::unpack_from_slice(
+        context
+            .pt_context
+            .banks_client
+            .get_account(*account)
+            .await
+            .unwrap()
+            .unwrap()
+            .data
+            .as_slice(),
+    )
+    .unwrap()
+}
+
+async fn set_disposition(
+    context: &mut BalanceAccountTestContext,
+    multisig_op_account: &Pubkey,
+    approver: &Keypair,
+    disposition: ApprovalDisposition,
+) -> Result<(), BanksClientError> {
+    let params_hash =
+        get_operation_hash(&mut context.pt_context.banks_client, *multisig_op_account).await;
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[set_approval_disposition(
+                &context.program_id,
+                multisig_op_account,
+                &approver.pubkey(),
+                disposition,
+                params_hash,
+            )],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer, approver],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+}
+
+#[tokio::test]
+async fn test_governed_op_is_approved_by_stake_weight() {
+    let (mut context, _) = setup_balance_account_tests_and_finalize(None).await;
+    let holders = vec![Keypair::new(), Keypair::new()];
+    let initiator = context.approvers[0].pubkey();
+    let mint = create_governance_token(
+        &mut context,
+        &[
+            (initiator, 40),
+            (holders[0].pubkey(), 35),
+            (holders[1].pubkey(), 25),
+        ],
+    )
+    .await;
+    let governance = StakeWeightGovernance {
+        mint,
+        approval_threshold_bps: 5_001,
+    };
+    enable_governance(&mut context, governance).await;
+
+    // the holders hand control back to the wallet's signers
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let (init, finalize) = governance_update_instructions(&context, &multisig_op_pubkey, None);
+    init_multisig_op(
+        &mut context.to_test_context(),
+        multisig_op_account,
+        init_with_stake_weights(
+            init,
+            &mint,
+            &[initiator, holders[0].pubkey(), holders[1].pubkey()],
+        ),
+        &context.approvers[0],
+    )
+    .await
+    .unwrap();
+
+    let multisig_op = get_multisig_op(&mut context, &multisig_op_pubkey).await;
+    assert_eq!(multisig_op.required_stake_weight, 51);
+    assert_eq!(multisig_op.disposition_records.len(), 3);
+    assert_eq!(
+        multisig_op.get_disposition_stake_weight(ApprovalDisposition::APPROVE),
+        40
+    );
+    assert_eq!(
+        multisig_op.operation_disposition(),
+        OperationDisposition::NONE
+    );
+
+    // a signer of the wallet with no stake has no say
+    let signer = Keypair::from_bytes(&context.approvers[1].to_bytes()).unwrap();
+    assert_instruction_error(
+        set_disposition(
+            &mut context,
+            &multisig_op_pubkey,
+            &signer,
+            ApprovalDisposition::APPROVE,
+        )
+        .await,
+        0,
+        Custom(WalletError::InvalidApprover as u32),
+    );
+
+    set_disposition(
+        &mut context,
+        &multisig_op_pubkey,
+        &holders[1],
+        ApprovalDisposition::APPROVE,
+    )
+    .await
+    .unwrap();
+    let multisig_op = get_multisig_op(&mut context, &multisig_op_pubkey).await;
+    assert_eq!(multisig_op.state, MultisigOpState::Approved);
+
+    finalize_multisig_op(&mut context.to_test_context(), multisig_op_pubkey, finalize).await;
+    assert_eq!(
+        get_wallet(
+            &mut context.pt_context.banks_client,
+            &context.wallet_account.pubkey()
+        )
+        .await
+        .stake_weight_governance,
+        None
+    );
+}
+
+#[tokio::test]
+async fn test_governed_op_needs_stake_weights() {
+    let (mut context, _) = setup_balance_account_tests_and_finalize(None).await;
+    let initiator = context.approvers[0].pubkey();
+    let mint = create_governance_token(&mut context, &[(initiator, 100)]).await;
+
+    // weights are only taken by ops of governed wallets
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let (init, _) = governance_update_instructions(&context, &multisig_op_pubkey, None);
+    assert_instruction_error(
+        init_multisig_op(
+            &mut context.to_test_context(),
+            multisig_op_account,
+            init_with_stake_weights(init, &mint, &[initiator]),
+            &context.approvers[0],
+        )
+        .await,
+        1,
+        Custom(WalletError::StakeWeightsNotExpected as u32),
+    );
+
+    enable_governance(
+        &mut context,
+        StakeWeightGovernance {
+            mint,
+            approval_threshold_bps: 5_001,
+        },
+    )
+    .await;
+
+    // an op initiated without weights has no one who can approve it
+    let signer = Keypair::from_bytes(&context.approvers[0].to_bytes()).unwrap();
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let (init, _) = governance_update_instructions(&context, &multisig_op_pubkey, None);
+    init_multisig_op(
+        &mut context.to_test_context(),
+        multisig_op_account,
+        init,
+        &context.approvers[0],
+    )
+    .await
+    .unwrap();
+    assert_instruction_error(
+        set_disposition(
+            &mut context,
+            &multisig_op_pubkey,
+            &signer,
+            ApprovalDisposition::APPROVE,
+        )
+        .await,
+        0,
+        Custom(WalletError::InvalidApprover as u32),
+    );
+
+    // the governance mint can't be swapped for another
+    let other_mint = create_governance_token(&mut context, &[(initiator, 100)]).await;
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let (init, _) = governance_update_instructions(&context, &multisig_op_pubkey, None);
+    assert_instruction_error(
+        init_multisig_op(
+            &mut context.to_test_context(),
+            multisig_op_account,
+            init_with_stake_weights(init, &other_mint, &[initiator]),
+            &context.approvers[0],
+        )
+        .await,
+        1,
+        Custom(WalletError::InvalidStakeAccount as u32),
+    );
+}
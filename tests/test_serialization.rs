@@ -1,12 +1,40 @@
 // #![cfg(feature = "test-bpf")]
 
+use solana_program::hash::hash;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
 use solana_program_test::tokio;
 use solana_sdk::signature::{Keypair, Signer};
+use std::time::Duration;
 use strike_wallet::constants::HASH_LEN;
 use strike_wallet::instruction::{
-    pack_balance_account_guid_hash_vec, unpack_account_guid_hash_vec,
+    pack_balance_account_guid_hash_vec, unpack_account_guid_hash_vec, AddressBookUpdate,
+    BalanceAccountPolicyUpdate, ConfigUpdate, FullRotation, InitialWalletConfig,
+    ProgramInstruction, RewardSplit, WalletConfigPolicyUpdate,
 };
-use strike_wallet::model::balance_account::BalanceAccountGuidHash;
+use strike_wallet::model::address_book::{
+    AddressBookEntry, AddressBookEntryKind, AddressBookEntryNameHash,
+};
+use strike_wallet::model::approval_group::ApprovalGroup;
+use strike_wallet::model::balance_account::{
+    ApprovalTier, BalanceAccountGuidHash, FinalizeDelayPolicy,
+};
+use strike_wallet::model::fee_policy::FeePolicy;
+use strike_wallet::model::multisig_op::{
+    ApprovalDisposition, BooleanSetting, DispositionReason, MintAuthorityType, SlotUpdateType,
+    WrapDirection,
+};
+use strike_wallet::model::price_oracle::{PriceFeed, PriceOracle};
+use strike_wallet::model::program_parameters::ProgramParameters;
+use strike_wallet::model::rent_return_allowlist::RentReturnAllowlist;
+use strike_wallet::model::session_key_registry::SessionKeyScope;
+use strike_wallet::model::signer::{Signer as WalletSigner, SignerCapabilities};
+use strike_wallet::model::stake_weight_governance::StakeWeightGovernance;
+use strike_wallet::model::wallet::{Approvers, WalletGuidHash};
+use strike_wallet::model::wallet_freeze::WalletFreeze;
+use strike_wallet::model::wallet_replication::{MirroredUpdate, WalletReplication};
+use strike_wallet::model::wrapper_book::{WrapperBook, WrapperEntry};
+use strike_wallet::utils::SlotId;
 
 fn build_account_guid_hash_byte_vec(n: u8) -> (Vec<BalanceAccountGuidHash>, Vec<u8>) {
     let mut buf = Vec::<u8>::new();
@@ -51,3 +79,1581 @@ async fn test_unpack_account_guid_hash_vec() {
             assert_eq!(*hash, *exp_hash);
         });
 }
+
+#[tokio::test]
+async fn test_reward_split_policy_update_round_trip() {
+    let account_guid_hash = BalanceAccountGuidHash::new(&Keypair::new().pubkey().to_bytes());
+    let splits = vec![
+        RewardSplit {
+            account_guid_hash: BalanceAccountGuidHash::new(&Keypair::new().pubkey().to_bytes()),
+            percentage: 70,
+        },
+        RewardSplit {
+            account_guid_hash: BalanceAccountGuidHash::new(&Keypair::new().pubkey().to_bytes()),
+            percentage: 30,
+        },
+    ];
+    let packed = ProgramInstruction::FinalizeRewardSplitPolicyUpdate {
+        account_guid_hash,
+        splits: splits.clone(),
+    }
+    .pack();
+
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::FinalizeRewardSplitPolicyUpdate {
+            account_guid_hash: unpacked_account_guid_hash,
+            splits: unpacked_splits,
+        } => {
+            assert_eq!(unpacked_account_guid_hash, account_guid_hash);
+            assert_eq!(unpacked_splits, splits);
+        }
+        _ => panic!("unexpected instruction"),
+    }
+}
+
+#[tokio::test]
+async fn test_set_mint_authority_round_trip() {
+    let account_guid_hash = BalanceAccountGuidHash::new(&Keypair::new().pubkey().to_bytes());
+    for (authority_type, new_authority) in [
+        (MintAuthorityType::MintTokens, Some(Keypair::new().pubkey())),
+        (MintAuthorityType::FreezeAccount, None),
+    ] {
+        let packed = ProgramInstruction::FinalizeSetMintAuthority {
+            account_guid_hash,
+            authority_type,
+            new_authority,
+        }
+        .pack();
+
+        match ProgramInstruction::unpack(&packed).unwrap() {
+            ProgramInstruction::FinalizeSetMintAuthority {
+                account_guid_hash: unpacked_account_guid_hash,
+                authority_type: unpacked_authority_type,
+                new_authority: unpacked_new_authority,
+            } => {
+                assert_eq!(unpacked_account_guid_hash, account_guid_hash);
+                assert_eq!(unpacked_authority_type, authority_type);
+                assert_eq!(unpacked_new_authority, new_authority);
+            }
+            _ => panic!("unexpected instruction"),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_simulate_balance_account_policy_update_round_trip() {
+    let account_guid_hash = BalanceAccountGuidHash::new(&Keypair::new().pubkey().to_bytes());
+    let update = BalanceAccountPolicyUpdate {
+        approvals_required_for_transfer: 2,
+        approval_timeout_for_transfer: Duration::from_secs(3600),
+        transfer_approvers: vec![SlotId::new(0), SlotId::new(3)],
+        signers_hash: hash(&[1, 2, 3]),
+        finalize_delay_policy: FinalizeDelayPolicy {
+            threshold: 1000,
+            delay: Duration::from_secs(60),
+        },
+        mint_cap: 500,
+        approval_tiers: vec![
+            ApprovalTier {
+                min_amount: 1000,
+                approvals_required: 1,
+                approvers: Approvers::from_enabled_vec(vec![SlotId::new(3)]),
+            },
+            ApprovalTier {
+                min_amount: 10_000,
+                approvals_required: 2,
+                approvers: Approvers::from_enabled_vec(vec![SlotId::new(0), SlotId::new(3)]),
+            },
+        ],
+    };
+    let packed = ProgramInstruction::SimulateBalanceAccountPolicyUpdate {
+        account_guid_hash,
+        update: update.clone(),
+    }
+    .pack();
+
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::SimulateBalanceAccountPolicyUpdate {
+            account_guid_hash: unpacked_account_guid_hash,
+            update: unpacked_update,
+        } => {
+            assert_eq!(unpacked_account_guid_hash, account_guid_hash);
+            assert_eq!(unpacked_update, update);
+        }
+        _ => panic!("unexpected instruction"),
+    }
+}
+
+#[tokio::test]
+async fn test_simulate_config_update_round_trip() {
+    for update in [
+        ConfigUpdate::WalletConfigPolicy(WalletConfigPolicyUpdate {
+            approvals_required_for_config: 2,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            config_approvers: vec![SlotId::new(0), SlotId::new(1)],
+            signers_hash: hash(&[1, 2, 3]),
+            config_policy_timelock: Duration::from_secs(600),
+        }),
+        ConfigUpdate::BalanceAccountPolicy {
+            account_guid_hash: BalanceAccountGuidHash::new(&Keypair::new().pubkey().to_bytes()),
+            update: BalanceAccountPolicyUpdate {
+                approvals_required_for_transfer: 1,
+                approval_timeout_for_transfer: Duration::from_secs(3600),
+                transfer_approvers: vec![SlotId::new(2)],
+                signers_hash: hash(&[4, 5, 6]),
+                finalize_delay_policy: FinalizeDelayPolicy {
+                    threshold: 0,
+                    delay: Duration::from_secs(0),
+                },
+                mint_cap: 0,
+                approval_tiers: vec![],
+            },
+        },
+    ] {
+        let packed = ProgramInstruction::SimulateConfigUpdate {
+            update: update.clone(),
+        }
+        .pack();
+        match ProgramInstruction::unpack(&packed).unwrap() {
+            ProgramInstruction::SimulateConfigUpdate {
+                update: unpacked_update,
+            } => assert_eq!(unpacked_update, update),
+            _ => panic!("unexpected instruction"),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_get_allowed_destinations_round_trip() {
+    let account_guid_hash = BalanceAccountGuidHash::new(&Keypair::new().pubkey().to_bytes());
+    let packed = ProgramInstruction::GetAllowedDestinations {
+        account_guid_hash,
+        start_slot_id: SlotId::new(15),
+    }
+    .pack();
+
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::GetAllowedDestinations {
+            account_guid_hash: unpacked_account_guid_hash,
+            start_slot_id,
+        } => {
+            assert_eq!(unpacked_account_guid_hash, account_guid_hash);
+            assert_eq!(start_slot_id, SlotId::new(15));
+        }
+        _ => panic!("unexpected instruction"),
+    }
+}
+
+#[tokio::test]
+async fn test_init_with_signature_round_trip() {
+    let init = ProgramInstruction::InitSignData {
+        fee_amount: 0,
+        fee_account_guid_hash: None,
+        data: vec![1, 2, 3],
+    }
+    .pack();
+    let packed = ProgramInstruction::InitWithSignature { init: init.clone() }.pack();
+
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::InitWithSignature {
+            init: unpacked_init,
+        } => {
+            assert_eq!(unpacked_init, init);
+            assert!(ProgramInstruction::unpack(&unpacked_init)
+                .unwrap()
+                .is_init());
+        }
+        _ => panic!("unexpected instruction"),
+    }
+    assert!(!ProgramInstruction::unpack(&packed).unwrap().is_init());
+}
+
+#[tokio::test]
+async fn test_wallet_replication_round_trip() {
+    for replication in [
+        None,
+        Some(WalletReplication::Primary {
+            standby: Keypair::new().pubkey(),
+        }),
+        Some(WalletReplication::Standby {
+            primary: Keypair::new().pubkey(),
+        }),
+    ] {
+        let packed = ProgramInstruction::InitWalletReplicationUpdate {
+            fee_amount: 10,
+            fee_account_guid_hash: None,
+            replication,
+        }
+        .pack();
+        match ProgramInstruction::unpack(&packed).unwrap() {
+            ProgramInstruction::InitWalletReplicationUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                replication: unpacked_replication,
+            } => {
+                assert_eq!(fee_amount, 10);
+                assert_eq!(fee_account_guid_hash, None);
+                assert_eq!(unpacked_replication, replication);
+            }
+            _ => panic!("unexpected instruction"),
+        }
+    }
+
+    let updates = [
+        MirroredUpdate::ConfigPolicy(WalletConfigPolicyUpdate {
+            approvals_required_for_config: 2,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            config_approvers: vec![SlotId::new(0), SlotId::new(3)],
+            signers_hash: hash(&[1, 2, 3]),
+            config_policy_timelock: Duration::from_secs(60),
+        }),
+        MirroredUpdate::Signer {
+            slot_update_type: SlotUpdateType::Clear,
+            slot_id: SlotId::new(5),
+            signer: WalletSigner::new(Keypair::new().pubkey()),
+        },
+    ];
+    for update in updates {
+        let packed = ProgramInstruction::ApplyMirroredUpdate {
+            update: update.clone(),
+        }
+        .pack();
+        match ProgramInstruction::unpack(&packed).unwrap() {
+            ProgramInstruction::ApplyMirroredUpdate {
+                update: unpacked_update,
+            } => assert_eq!(unpacked_update, update),
+            _ => panic!("unexpected instruction"),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_balance_account_deletion_round_trip() {
+    let account_guid_hash = BalanceAccountGuidHash::new(&[7; HASH_LEN]);
+    let fee_account_guid_hash = Some(BalanceAccountGuidHash::new(&[8; HASH_LEN]));
+    let packed = ProgramInstruction::InitBalanceAccountDeletion {
+        fee_amount: 10,
+        fee_account_guid_hash,
+        account_guid_hash,
+    }
+    .pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::InitBalanceAccountDeletion {
+            fee_amount,
+            fee_account_guid_hash: unpacked_fee_account_guid_hash,
+            account_guid_hash: unpacked_account_guid_hash,
+        } => {
+            assert_eq!(fee_amount, 10);
+            assert_eq!(unpacked_fee_account_guid_hash, fee_account_guid_hash);
+            assert_eq!(unpacked_account_guid_hash, account_guid_hash);
+        }
+        _ => panic!("unexpected instruction"),
+    }
+
+    let packed = ProgramInstruction::FinalizeBalanceAccountDeletion { account_guid_hash }.pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::FinalizeBalanceAccountDeletion {
+            account_guid_hash: unpacked_account_guid_hash,
+        } => assert_eq!(unpacked_account_guid_hash, account_guid_hash),
+        _ => panic!("unexpected instruction"),
+    }
+}
+
+#[tokio::test]
+async fn test_spending_limit_update_round_trip() {
+    let account_guid_hash = BalanceAccountGuidHash::new(&[7; HASH_LEN]);
+    let token_mint = Keypair::new().pubkey();
+    let packed = ProgramInstruction::InitSpendingLimitUpdate {
+        fee_amount: 10,
+        fee_account_guid_hash: None,
+        account_guid_hash,
+        token_mint,
+        amount: 1000,
+    }
+    .pack();
+    assert_eq!(ProgramInstruction::unpack(&packed).unwrap().pack(), packed);
+
+    let packed = ProgramInstruction::FinalizeSpendingLimitUpdate {
+        account_guid_hash,
+        token_mint,
+        amount: 1000,
+    }
+    .pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::FinalizeSpendingLimitUpdate {
+            account_guid_hash: unpacked_account_guid_hash,
+            token_mint: unpacked_token_mint,
+            amount,
+        } => {
+            assert_eq!(unpacked_account_guid_hash, account_guid_hash);
+            assert_eq!(unpacked_token_mint, token_mint);
+            assert_eq!(amount, 1000);
+        }
+        _ => panic!("unexpected instruction"),
+    }
+}
+
+#[tokio::test]
+async fn test_nudge_round_trip() {
+    let packed = ProgramInstruction::Nudge { idle_slots: 150 }.pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::Nudge { idle_slots } => assert_eq!(idle_slots, 150),
+        _ => panic!("unexpected instruction"),
+    }
+}
+
+#[tokio::test]
+async fn test_cleanup_expired_op_round_trip() {
+    let packed = ProgramInstruction::CleanupExpiredOp {}.pack();
+    assert!(matches!(
+        ProgramInstruction::unpack(&packed).unwrap(),
+        ProgramInstruction::CleanupExpiredOp {}
+    ));
+}
+
+#[tokio::test]
+async fn test_init_with_compute_hint_round_trip() {
+    let init = ProgramInstruction::InitSignData {
+        fee_amount: 0,
+        fee_account_guid_hash: None,
+        data: vec![1, 2, 3],
+    }
+    .pack();
+    let packed = ProgramInstruction::InitWithComputeHint {
+        finalize_compute_units: 250_000,
+        init: init.clone(),
+    }
+    .pack();
+
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::InitWithComputeHint {
+            finalize_compute_units,
+            init: unpacked_init,
+        } => {
+            assert_eq!(finalize_compute_units, 250_000);
+            assert_eq!(unpacked_init, init);
+        }
+        _ => panic!("unexpected instruction"),
+    }
+}
+
+#[tokio::test]
+async fn test_register_token_accounts_round_trip() {
+    let account_guid_hash = BalanceAccountGuidHash::new(&hash(&[7]).to_bytes());
+    let packed = ProgramInstruction::RegisterTokenAccounts { account_guid_hash }.pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::RegisterTokenAccounts {
+            account_guid_hash: unpacked,
+        } => assert_eq!(unpacked, account_guid_hash),
+        _ => panic!("unexpected instruction"),
+    }
+}
+
+#[tokio::test]
+async fn test_signer_capabilities_encoding() {
+    let signers = vec![
+        (SlotId::new(0), WalletSigner::new(Keypair::new().pubkey())),
+        (
+            SlotId::new(2),
+            WalletSigner::with_capabilities(
+                Keypair::new().pubkey(),
+                SignerCapabilities::APPROVE_TRANSFER,
+            ),
+        ),
+    ];
+    let initial_config = InitialWalletConfig {
+        approvals_required_for_config: 1,
+        approval_timeout_for_config: Duration::from_secs(3600),
+        signers: signers.clone(),
+        config_approvers: vec![SlotId::new(0)],
+    };
+    let mut packed = ProgramInstruction::InitWallet {
+        wallet_guid_hash: WalletGuidHash::new(&[1; HASH_LEN]),
+        initial_config: initial_config.clone(),
+    }
+    .pack();
+
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::InitWallet {
+            initial_config: unpacked,
+            ..
+        } => assert_eq!(unpacked, initial_config),
+        _ => panic!("unexpected instruction"),
+    }
+
+    // configs encoded before signers had capabilities give every signer all of them
+    packed.truncate(packed.len() - signers.len());
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::InitWallet {
+            initial_config: unpacked,
+            ..
+        } => assert_eq!(
+            unpacked.signers,
+            signers
+                .iter()
+                .map(|(slot_id, signer)| (*slot_id, WalletSigner::new(signer.key)))
+                .collect::<Vec<_>>()
+        ),
+        _ => panic!("unexpected instruction"),
+    }
+
+    // and so does an update signer instruction without them
+    let signer = signers[1].1;
+    let mut packed = ProgramInstruction::FinalizeUpdateSigner {
+        slot_update_type: SlotUpdateType::SetIfEmpty,
+        slot_id: SlotId::new(2),
+        signer,
+    }
+    .pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::FinalizeUpdateSigner {
+            signer: unpacked, ..
+        } => assert_eq!(unpacked, signer),
+        _ => panic!("unexpected instruction"),
+    }
+
+    packed.truncate(packed.len() - 1);
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::FinalizeUpdateSigner {
+            signer: unpacked, ..
+        } => assert_eq!(unpacked, WalletSigner::new(signer.key)),
+        _ => panic!("unexpected instruction"),
+    }
+}
+
+#[tokio::test]
+async fn test_price_oracle_update_round_trip() {
+    for oracle in [
+        None,
+        Some(PriceOracle {
+            feeds: vec![
+                PriceFeed {
+                    token_mint: Pubkey::default(),
+                    price_account: Keypair::new().pubkey(),
+                    decimals: 9,
+                },
+                PriceFeed {
+                    token_mint: Keypair::new().pubkey(),
+                    price_account: Keypair::new().pubkey(),
+                    decimals: 6,
+                },
+            ],
+            max_staleness: Duration::from_secs(60),
+            max_confidence_bps: 200,
+        }),
+    ] {
+        let packed = ProgramInstruction::InitPriceOracleUpdate {
+            fee_amount: 10,
+            fee_account_guid_hash: None,
+            oracle: oracle.clone(),
+        }
+        .pack();
+        match ProgramInstruction::unpack(&packed).unwrap() {
+            ProgramInstruction::InitPriceOracleUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                oracle: unpacked_oracle,
+            } => {
+                assert_eq!(fee_amount, 10);
+                assert_eq!(fee_account_guid_hash, None);
+                assert_eq!(unpacked_oracle, oracle);
+            }
+            _ => panic!("unexpected instruction"),
+        }
+        let packed = ProgramInstruction::FinalizePriceOracleUpdate {
+            oracle: oracle.clone(),
+        }
+        .pack();
+        match ProgramInstruction::unpack(&packed).unwrap() {
+            ProgramInstruction::FinalizePriceOracleUpdate {
+                oracle: unpacked_oracle,
+            } => assert_eq!(unpacked_oracle, oracle),
+            _ => panic!("unexpected instruction"),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_merkle_address_book_update_round_trip() {
+    let account_guid_hash = BalanceAccountGuidHash::new(&hash(b"account").to_bytes());
+    let root = hash(b"root");
+    let packed = ProgramInstruction::InitMerkleAddressBookUpdate {
+        fee_amount: 10,
+        fee_account_guid_hash: None,
+        account_guid_hash,
+        root,
+    }
+    .pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::InitMerkleAddressBookUpdate {
+            fee_amount,
+            fee_account_guid_hash,
+            account_guid_hash: unpacked_account_guid_hash,
+            root: unpacked_root,
+        } => {
+            assert_eq!(fee_amount, 10);
+            assert_eq!(fee_account_guid_hash, None);
+            assert_eq!(unpacked_account_guid_hash, account_guid_hash);
+            assert_eq!(unpacked_root, root);
+        }
+        _ => panic!("unexpected instruction"),
+    }
+    let packed = ProgramInstruction::FinalizeMerkleAddressBookUpdate {
+        account_guid_hash,
+        root,
+    }
+    .pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::FinalizeMerkleAddressBookUpdate {
+            account_guid_hash: unpacked_account_guid_hash,
+            root: unpacked_root,
+        } => {
+            assert_eq!(unpacked_account_guid_hash, account_guid_hash);
+            assert_eq!(unpacked_root, root);
+        }
+        _ => panic!("unexpected instruction"),
+    }
+}
+
+#[tokio::test]
+async fn test_transfer_memo_round_trip() {
+    let account_guid_hash = BalanceAccountGuidHash::new(&hash(b"account").to_bytes());
+    for memo in [None, Some(vec![]), Some(b"invoice 1234".to_vec())] {
+        let memo_hash = memo.as_ref().map(|memo| hash(memo));
+        let packed = ProgramInstruction::InitTransfer {
+            fee_amount: 0,
+            fee_account_guid_hash: None,
+            account_guid_hash,
+            amount: 100,
+            destination_name_hash: AddressBookEntryNameHash::new(&hash(b"name").to_bytes()),
+            destination_acknowledgement_required: false,
+            deduct_fee_from_amount: false,
+            allow_destination_ata_creation: false,
+            memo_hash,
+            destination_proof: Some(vec![hash(b"a")]),
+        }
+        .pack();
+        match ProgramInstruction::unpack(&packed).unwrap() {
+            ProgramInstruction::InitTransfer {
+                memo_hash: unpacked,
+                destination_proof,
+                ..
+            } => {
+                assert_eq!(unpacked, memo_hash);
+                assert_eq!(destination_proof, Some(vec![hash(b"a")]));
+            }
+            _ => panic!("unexpected instruction"),
+        }
+
+        let packed = ProgramInstruction::FinalizeTransfer {
+            account_guid_hash,
+            amount: 100,
+            token_mint: Pubkey::new_unique(),
+            destination_acknowledgement_required: true,
+            deduct_fee_from_amount: false,
+            allow_destination_ata_creation: true,
+            memo: memo.clone(),
+        }
+        .pack();
+        match ProgramInstruction::unpack(&packed).unwrap() {
+            ProgramInstruction::FinalizeTransfer {
+                memo: unpacked,
+                destination_acknowledgement_required,
+                deduct_fee_from_amount,
+                allow_destination_ata_creation,
+                ..
+            } => {
+                assert_eq!(unpacked, memo);
+                assert!(destination_acknowledgement_required);
+                assert!(!deduct_fee_from_amount);
+                assert!(allow_destination_ata_creation);
+            }
+            _ => panic!("unexpected instruction"),
+        }
+
+        let packed = ProgramInstruction::CancelTransfer {
+            account_guid_hash,
+            amount: 100,
+            token_mint: Pubkey::new_unique(),
+            destination_acknowledgement_required: false,
+            deduct_fee_from_amount: true,
+            allow_destination_ata_creation: false,
+            memo: memo.clone(),
+        }
+        .pack();
+        match ProgramInstruction::unpack(&packed).unwrap() {
+            ProgramInstruction::CancelTransfer {
+                memo: unpacked,
+                deduct_fee_from_amount,
+                ..
+            } => {
+                assert_eq!(unpacked, memo);
+                assert!(deduct_fee_from_amount);
+            }
+            _ => panic!("unexpected instruction"),
+        }
+
+        let packed = ProgramInstruction::VerifyTransfer {
+            account_guid_hash,
+            amount: 100,
+            token_mint: Pubkey::new_unique(),
+            destination_acknowledgement_required: true,
+            deduct_fee_from_amount: false,
+            allow_destination_ata_creation: true,
+            memo: memo.clone(),
+        }
+        .pack();
+        match ProgramInstruction::unpack(&packed).unwrap() {
+            ProgramInstruction::VerifyTransfer {
+                memo: unpacked,
+                destination_acknowledgement_required,
+                allow_destination_ata_creation,
+                ..
+            } => {
+                assert_eq!(unpacked, memo);
+                assert!(destination_acknowledgement_required);
+                assert!(allow_destination_ata_creation);
+            }
+            _ => panic!("unexpected instruction"),
+        }
+
+        let packed = ProgramInstruction::ExecuteTransfer {
+            account_guid_hash,
+            amount: 100,
+            token_mint: Pubkey::new_unique(),
+            destination_acknowledgement_required: false,
+            deduct_fee_from_amount: true,
+            allow_destination_ata_creation: false,
+            memo: memo.clone(),
+        }
+        .pack();
+        match ProgramInstruction::unpack(&packed).unwrap() {
+            ProgramInstruction::ExecuteTransfer {
+                memo: unpacked,
+                deduct_fee_from_amount,
+                ..
+            } => {
+                assert_eq!(unpacked, memo);
+                assert!(deduct_fee_from_amount);
+            }
+            _ => panic!("unexpected instruction"),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_init_transfer_destination_proof_round_trip() {
+    for destination_proof in [None, Some(vec![]), Some(vec![hash(b"a"), hash(b"b")])] {
+        let packed = ProgramInstruction::InitTransfer {
+            fee_amount: 0,
+            fee_account_guid_hash: None,
+            account_guid_hash: BalanceAccountGuidHash::new(&hash(b"account").to_bytes()),
+            amount: 100,
+            destination_name_hash: AddressBookEntryNameHash::new(&hash(b"name").to_bytes()),
+            destination_acknowledgement_required: false,
+            deduct_fee_from_amount: false,
+            allow_destination_ata_creation: false,
+            memo_hash: None,
+            destination_proof: destination_proof.clone(),
+        }
+        .pack();
+        match ProgramInstruction::unpack(&packed).unwrap() {
+            ProgramInstruction::InitTransfer {
+                destination_proof: unpacked,
+                ..
+            } => assert_eq!(unpacked, destination_proof),
+            _ => panic!("unexpected instruction"),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_wallet_freeze_round_trip() {
+    let freeze = WalletFreeze {
+        frozen: true,
+        emergency_quorum: 3,
+    };
+    let packed = ProgramInstruction::InitWalletFreeze {
+        fee_amount: 10,
+        fee_account_guid_hash: None,
+        freeze,
+    }
+    .pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::InitWalletFreeze {
+            fee_amount,
+            fee_account_guid_hash,
+            freeze: unpacked,
+        } => {
+            assert_eq!(fee_amount, 10);
+            assert_eq!(fee_account_guid_hash, None);
+            assert_eq!(unpacked, freeze);
+        }
+        _ => panic!("unexpected instruction"),
+    }
+    let packed = ProgramInstruction::FinalizeWalletFreeze { freeze }.pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::FinalizeWalletFreeze { freeze: unpacked } => {
+            assert_eq!(unpacked, freeze)
+        }
+        _ => panic!("unexpected instruction"),
+    }
+    assert!(matches!(
+        ProgramInstruction::unpack(&ProgramInstruction::EmergencyFreeze {}.pack()).unwrap(),
+        ProgramInstruction::EmergencyFreeze {}
+    ));
+
+    // a frozen flag other than 0 or 1 doesn't unpack
+    let mut packed = ProgramInstruction::FinalizeWalletFreeze { freeze }.pack();
+    packed[1] = 2;
+    assert!(ProgramInstruction::unpack(&packed).is_err());
+}
+
+#[tokio::test]
+async fn test_rent_return_allowlist_update_round_trip() {
+    for allowlist in [
+        RentReturnAllowlist::default(),
+        RentReturnAllowlist {
+            entries: vec![Keypair::new().pubkey(), Keypair::new().pubkey()],
+        },
+    ] {
+        let packed = ProgramInstruction::InitRentReturnAllowlistUpdate {
+            fee_amount: 10,
+            fee_account_guid_hash: None,
+            allowlist: allowlist.clone(),
+        }
+        .pack();
+        match ProgramInstruction::unpack(&packed).unwrap() {
+            ProgramInstruction::InitRentReturnAllowlistUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                allowlist: unpacked,
+            } => {
+                assert_eq!(fee_amount, 10);
+                assert_eq!(fee_account_guid_hash, None);
+                assert_eq!(unpacked, allowlist);
+            }
+            _ => panic!("unexpected instruction"),
+        }
+        let packed = ProgramInstruction::FinalizeRentReturnAllowlistUpdate {
+            allowlist: allowlist.clone(),
+        }
+        .pack();
+        match ProgramInstruction::unpack(&packed).unwrap() {
+            ProgramInstruction::FinalizeRentReturnAllowlistUpdate {
+                allowlist: unpacked,
+            } => assert_eq!(unpacked, allowlist),
+            _ => panic!("unexpected instruction"),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_full_rotation_round_trip() {
+    let rotation = FullRotation {
+        assistant: Keypair::new().pubkey(),
+        rent_return: Keypair::new().pubkey(),
+        signer_keys: vec![
+            (Keypair::new().pubkey(), Keypair::new().pubkey()),
+            (Keypair::new().pubkey(), Keypair::new().pubkey()),
+        ],
+    };
+    let packed = ProgramInstruction::InitFullRotation {
+        fee_amount: 10,
+        fee_account_guid_hash: None,
+        rotation: rotation.clone(),
+    }
+    .pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::InitFullRotation {
+            fee_amount,
+            fee_account_guid_hash,
+            rotation: unpacked,
+        } => {
+            assert_eq!(fee_amount, 10);
+            assert_eq!(fee_account_guid_hash, None);
+            assert_eq!(unpacked, rotation);
+        }
+        _ => panic!("unexpected instruction"),
+    }
+    let packed = ProgramInstruction::FinalizeFullRotation {
+        rotation: rotation.clone(),
+    }
+    .pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::FinalizeFullRotation { rotation: unpacked } => {
+            assert_eq!(unpacked, rotation)
+        }
+        _ => panic!("unexpected instruction"),
+    }
+    // a signer key pair cut short doesn't unpack
+    assert!(ProgramInstruction::unpack(&packed[..packed.len() - 1]).is_err());
+}
+
+#[tokio::test]
+async fn test_update_program_parameters_round_trip() {
+    let parameters = ProgramParameters {
+        is_initialized: true,
+        max_signers: 5,
+        max_dapp_instructions: 4,
+        max_approval_timeout: Duration::from_secs(60 * 60 * 24),
+    };
+    let packed = ProgramInstruction::UpdateProgramParameters { parameters }.pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::UpdateProgramParameters {
+            parameters: unpacked,
+        } => assert_eq!(unpacked, parameters),
+        _ => panic!("unexpected instruction"),
+    }
+}
+
+#[tokio::test]
+async fn test_signer_activation_delay_update_round_trip() {
+    let delay = Duration::from_secs(60 * 60 * 48);
+    let packed = ProgramInstruction::InitSignerActivationDelayUpdate {
+        fee_amount: 10,
+        fee_account_guid_hash: None,
+        delay,
+    }
+    .pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::InitSignerActivationDelayUpdate {
+            fee_amount,
+            fee_account_guid_hash,
+            delay: unpacked,
+        } => {
+            assert_eq!(fee_amount, 10);
+            assert_eq!(fee_account_guid_hash, None);
+            assert_eq!(unpacked, delay);
+        }
+        _ => panic!("unexpected instruction"),
+    }
+    let packed = ProgramInstruction::FinalizeSignerActivationDelayUpdate { delay }.pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::FinalizeSignerActivationDelayUpdate { delay: unpacked } => {
+            assert_eq!(unpacked, delay)
+        }
+        _ => panic!("unexpected instruction"),
+    }
+}
+
+#[tokio::test]
+async fn test_fee_policy_update_round_trip() {
+    let fee_policy = FeePolicy {
+        max_fee_amount: Some(5_000_000),
+    };
+    let packed = ProgramInstruction::InitFeePolicyUpdate {
+        fee_amount: 10,
+        fee_account_guid_hash: None,
+        fee_policy,
+    }
+    .pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::InitFeePolicyUpdate {
+            fee_amount,
+            fee_account_guid_hash,
+            fee_policy: unpacked,
+        } => {
+            assert_eq!(fee_amount, 10);
+            assert_eq!(fee_account_guid_hash, None);
+            assert_eq!(unpacked, fee_policy);
+        }
+        _ => panic!("unexpected instruction"),
+    }
+    for fee_policy in [fee_policy, FeePolicy::default()] {
+        let packed = ProgramInstruction::FinalizeFeePolicyUpdate { fee_policy }.pack();
+        match ProgramInstruction::unpack(&packed).unwrap() {
+            ProgramInstruction::FinalizeFeePolicyUpdate {
+                fee_policy: unpacked,
+            } => assert_eq!(unpacked, fee_policy),
+            _ => panic!("unexpected instruction"),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_set_approval_disposition_reason_round_trip() {
+    let params_hash = hash(&[1]);
+    let reason = DispositionReason {
+        code: 3,
+        hash: Some(hash(b"amount too large")),
+    };
+    for reason in [Some(reason), None] {
+        let packed = ProgramInstruction::SetApprovalDisposition {
+            disposition: ApprovalDisposition::DENY,
+            params_hash,
+            reason,
+        }
+        .pack();
+        match ProgramInstruction::unpack(&packed).unwrap() {
+            ProgramInstruction::SetApprovalDisposition {
+                disposition,
+                params_hash: unpacked_params_hash,
+                reason: unpacked,
+            } => {
+                assert_eq!(disposition, ApprovalDisposition::DENY);
+                assert_eq!(unpacked_params_hash, params_hash);
+                assert_eq!(unpacked, reason);
+            }
+            _ => panic!("unexpected instruction"),
+        }
+    }
+
+    // without a reason, the encoding is the one from before reasons could be given
+    let mut legacy = vec![9, ApprovalDisposition::APPROVE as u8];
+    legacy.extend_from_slice(params_hash.as_ref());
+    assert_eq!(
+        ProgramInstruction::SetApprovalDisposition {
+            disposition: ApprovalDisposition::APPROVE,
+            params_hash,
+            reason: None,
+        }
+        .pack(),
+        legacy
+    );
+}
+
+#[tokio::test]
+async fn test_cleanup_uncreated_balance_account_round_trip() {
+    let account_guid_hash = BalanceAccountGuidHash::new(&hash(&[5]).to_bytes());
+    let packed = ProgramInstruction::CleanupUncreatedBalanceAccount { account_guid_hash }.pack();
+    assert_eq!(packed.len(), 1 + 32);
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::CleanupUncreatedBalanceAccount {
+            account_guid_hash: unpacked,
+        } => assert_eq!(unpacked, account_guid_hash),
+        _ => panic!("unexpected instruction"),
+    }
+}
+
+#[tokio::test]
+async fn test_stake_weight_governance_round_trip() {
+    let governance = StakeWeightGovernance {
+        mint: Pubkey::new_unique(),
+        approval_threshold_bps: 6_667,
+    };
+    for governance in [Some(governance), None] {
+        let packed = ProgramInstruction::InitStakeWeightGovernanceUpdate {
+            fee_amount: 10,
+            fee_account_guid_hash: None,
+            governance,
+        }
+        .pack();
+        match ProgramInstruction::unpack(&packed).unwrap() {
+            ProgramInstruction::InitStakeWeightGovernanceUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                governance: unpacked,
+            } => {
+                assert_eq!(fee_amount, 10);
+                assert_eq!(fee_account_guid_hash, None);
+                assert_eq!(unpacked, governance);
+            }
+            _ => panic!("unexpected instruction"),
+        }
+        let packed = ProgramInstruction::FinalizeStakeWeightGovernanceUpdate { governance }.pack();
+        match ProgramInstruction::unpack(&packed).unwrap() {
+            ProgramInstruction::FinalizeStakeWeightGovernanceUpdate {
+                governance: unpacked,
+            } => assert_eq!(unpacked, governance),
+            _ => panic!("unexpected instruction"),
+        }
+    }
+
+    let init = ProgramInstruction::InitStakeWeightGovernanceUpdate {
+        fee_amount: 0,
+        fee_account_guid_hash: None,
+        governance: None,
+    }
+    .pack();
+    let packed = ProgramInstruction::InitWithStakeWeights {
+        stake_account_count: 3,
+        init: init.clone(),
+    }
+    .pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::InitWithStakeWeights {
+            stake_account_count,
+            init: unpacked,
+        } => {
+            assert_eq!(stake_account_count, 3);
+            assert_eq!(unpacked, init);
+        }
+        _ => panic!("unexpected instruction"),
+    }
+}
+
+#[tokio::test]
+async fn test_init_event_log_round_trip() {
+    let packed = ProgramInstruction::InitEventLog {}.pack();
+    assert_eq!(packed.len(), 1);
+    assert!(matches!(
+        ProgramInstruction::unpack(&packed).unwrap(),
+        ProgramInstruction::InitEventLog {}
+    ));
+}
+
+#[tokio::test]
+async fn test_init_wallet_stats_round_trip() {
+    let packed = ProgramInstruction::InitWalletStats {}.pack();
+    assert_eq!(packed.len(), 1);
+    assert!(matches!(
+        ProgramInstruction::unpack(&packed).unwrap(),
+        ProgramInstruction::InitWalletStats {}
+    ));
+}
+
+#[tokio::test]
+async fn test_address_book_renewal_round_trip() {
+    let entry = AddressBookEntry {
+        address: Pubkey::new_unique(),
+        name_hash: AddressBookEntryNameHash::new(&hash(&[1]).to_bytes()),
+    };
+    let packed = ProgramInstruction::InitAddressBookRenewal {
+        fee_amount: 10,
+        fee_account_guid_hash: None,
+        slot_id: SlotId::new(87),
+        entry,
+        expires_at: 1_700_000_000,
+    }
+    .pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::InitAddressBookRenewal {
+            fee_amount,
+            fee_account_guid_hash,
+            slot_id,
+            entry: unpacked,
+            expires_at,
+        } => {
+            assert_eq!(fee_amount, 10);
+            assert_eq!(fee_account_guid_hash, None);
+            assert_eq!(slot_id, SlotId::new(87));
+            assert_eq!(unpacked, entry);
+            assert_eq!(expires_at, 1_700_000_000);
+        }
+        _ => panic!("unexpected instruction"),
+    }
+
+    let packed = ProgramInstruction::FinalizeAddressBookRenewal {
+        slot_id: SlotId::new(87),
+        entry,
+        expires_at: 1_700_000_000,
+    }
+    .pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::FinalizeAddressBookRenewal {
+            slot_id,
+            entry: unpacked,
+            expires_at,
+        } => {
+            assert_eq!(slot_id, SlotId::new(87));
+            assert_eq!(unpacked, entry);
+            assert_eq!(expires_at, 1_700_000_000);
+        }
+        _ => panic!("unexpected instruction"),
+    }
+    assert!(matches!(
+        ProgramInstruction::unpack(&packed[..packed.len() - 1]),
+        Err(ProgramError::InvalidInstructionData)
+    ));
+}
+
+#[tokio::test]
+async fn test_session_key_round_trip() {
+    let session_key = Pubkey::new_unique();
+    let scope = SessionKeyScope {
+        categories: SessionKeyScope::TRANSFER,
+        max_transfer_amount: 5_000,
+    };
+    let packed = ProgramInstruction::RegisterSessionKey {
+        session_key,
+        scope,
+        expires_at: 1_700_000_000,
+    }
+    .pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::RegisterSessionKey {
+            session_key: unpacked_key,
+            scope: unpacked_scope,
+            expires_at,
+        } => {
+            assert_eq!(unpacked_key, session_key);
+            assert_eq!(unpacked_scope, scope);
+            assert_eq!(expires_at, 1_700_000_000);
+        }
+        _ => panic!("unexpected instruction"),
+    }
+    assert!(matches!(
+        ProgramInstruction::unpack(&packed[..packed.len() - 1]),
+        Err(ProgramError::InvalidInstructionData)
+    ));
+
+    let packed = ProgramInstruction::RevokeSessionKey { session_key }.pack();
+    assert!(matches!(
+        ProgramInstruction::unpack(&packed).unwrap(),
+        ProgramInstruction::RevokeSessionKey { session_key: unpacked } if unpacked == session_key
+    ));
+}
+
+#[tokio::test]
+async fn test_approval_disposition_by_signature_round_trip() {
+    let params_hash = hash(&[1, 2, 3]);
+    let packed = ProgramInstruction::SetApprovalDispositionBySignature {
+        disposition: ApprovalDisposition::DENY,
+        params_hash,
+        expires_at: 1_700_000_000,
+    }
+    .pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::SetApprovalDispositionBySignature {
+            disposition,
+            params_hash: unpacked,
+            expires_at,
+        } => {
+            assert_eq!(disposition, ApprovalDisposition::DENY);
+            assert_eq!(unpacked, params_hash);
+            assert_eq!(expires_at, 1_700_000_000);
+        }
+        _ => panic!("unexpected instruction"),
+    }
+    assert!(matches!(
+        ProgramInstruction::unpack(&packed[..packed.len() - 1]),
+        Err(ProgramError::InvalidInstructionData)
+    ));
+}
+
+#[tokio::test]
+async fn test_approval_group_round_trip() {
+    let group = ApprovalGroup {
+        group_key: Pubkey::new_unique(),
+        threshold: 5,
+        scope: SessionKeyScope {
+            categories: SessionKeyScope::TRANSFER,
+            max_transfer_amount: 10_000,
+        },
+    };
+    for approval_group in [Some(group), None] {
+        let packed = ProgramInstruction::InitApprovalGroupUpdate {
+            fee_amount: 10,
+            fee_account_guid_hash: None,
+            approval_group,
+        }
+        .pack();
+        match ProgramInstruction::unpack(&packed).unwrap() {
+            ProgramInstruction::InitApprovalGroupUpdate {
+                fee_amount,
+                fee_account_guid_hash,
+                approval_group: unpacked,
+            } => {
+                assert_eq!(fee_amount, 10);
+                assert_eq!(fee_account_guid_hash, None);
+                assert_eq!(unpacked, approval_group);
+            }
+            _ => panic!("unexpected instruction"),
+        }
+        let packed = ProgramInstruction::FinalizeApprovalGroupUpdate { approval_group }.pack();
+        match ProgramInstruction::unpack(&packed).unwrap() {
+            ProgramInstruction::FinalizeApprovalGroupUpdate {
+                approval_group: unpacked,
+            } => assert_eq!(unpacked, approval_group),
+            _ => panic!("unexpected instruction"),
+        }
+    }
+
+    let params_hash = hash(&[4, 5, 6]);
+    let packed = ProgramInstruction::SubmitAggregatedApproval {
+        params_hash,
+        expires_at: 1_700_000_000,
+    }
+    .pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::SubmitAggregatedApproval {
+            params_hash: unpacked,
+            expires_at,
+        } => {
+            assert_eq!(unpacked, params_hash);
+            assert_eq!(expires_at, 1_700_000_000);
+        }
+        _ => panic!("unexpected instruction"),
+    }
+    assert!(matches!(
+        ProgramInstruction::unpack(&packed[..packed.len() - 1]),
+        Err(ProgramError::InvalidInstructionData)
+    ));
+}
+
+#[tokio::test]
+async fn test_deposit_addresses_round_trip() {
+    let account_guid_hash = BalanceAccountGuidHash::new(&hash(&[7]).to_bytes());
+    for enabled in [BooleanSetting::On, BooleanSetting::Off] {
+        let packed = ProgramInstruction::InitEnableDepositAddresses {
+            fee_amount: 10,
+            fee_account_guid_hash: None,
+            account_guid_hash,
+            enabled,
+        }
+        .pack();
+        match ProgramInstruction::unpack(&packed).unwrap() {
+            ProgramInstruction::InitEnableDepositAddresses {
+                fee_amount,
+                fee_account_guid_hash,
+                account_guid_hash: unpacked_guid_hash,
+                enabled: unpacked,
+            } => {
+                assert_eq!(fee_amount, 10);
+                assert_eq!(fee_account_guid_hash, None);
+                assert_eq!(unpacked_guid_hash, account_guid_hash);
+                assert_eq!(unpacked, enabled);
+            }
+            _ => panic!("unexpected instruction"),
+        }
+        let packed = ProgramInstruction::FinalizeEnableDepositAddresses {
+            account_guid_hash,
+            enabled,
+        }
+        .pack();
+        match ProgramInstruction::unpack(&packed).unwrap() {
+            ProgramInstruction::FinalizeEnableDepositAddresses {
+                account_guid_hash: unpacked_guid_hash,
+                enabled: unpacked,
+            } => {
+                assert_eq!(unpacked_guid_hash, account_guid_hash);
+                assert_eq!(unpacked, enabled);
+            }
+            _ => panic!("unexpected instruction"),
+        }
+    }
+
+    let finalize = ProgramInstruction::FinalizeWrapUnwrap {
+        account_guid_hash,
+        amount: 1_000,
+        direction: WrapDirection::WRAP,
+    }
+    .pack();
+    let packed = ProgramInstruction::FinalizeWithDepositSweep {
+        deposit_address_indices: vec![0, 3, 254],
+        finalize: finalize.clone(),
+    }
+    .pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::FinalizeWithDepositSweep {
+            deposit_address_indices,
+            finalize: unpacked,
+        } => {
+            assert_eq!(deposit_address_indices, vec![0, 3, 254]);
+            assert_eq!(unpacked, finalize);
+        }
+        _ => panic!("unexpected instruction"),
+    }
+    assert!(matches!(
+        ProgramInstruction::unpack(&packed[..3]),
+        Err(ProgramError::InvalidInstructionData)
+    ));
+}
+
+#[tokio::test]
+async fn test_compact_wallet_round_trip() {
+    let fee_account_guid_hash = Some(BalanceAccountGuidHash::new(&hash(&[3]).to_bytes()));
+    let packed = ProgramInstruction::InitCompactWallet {
+        fee_amount: 25,
+        fee_account_guid_hash,
+    }
+    .pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::InitCompactWallet {
+            fee_amount,
+            fee_account_guid_hash: unpacked,
+        } => {
+            assert_eq!(fee_amount, 25);
+            assert_eq!(unpacked, fee_account_guid_hash);
+        }
+        _ => panic!("unexpected instruction"),
+    }
+    let packed = ProgramInstruction::FinalizeCompactWallet {}.pack();
+    assert!(matches!(
+        ProgramInstruction::unpack(&packed).unwrap(),
+        ProgramInstruction::FinalizeCompactWallet {}
+    ));
+}
+
+#[tokio::test]
+async fn test_insurance_attestation_round_trip() {
+    let attestor = Pubkey::new_unique();
+    let packed = ProgramInstruction::InitInsuranceAttestorUpdate {
+        fee_amount: 5,
+        fee_account_guid_hash: None,
+        attestor,
+    }
+    .pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::InitInsuranceAttestorUpdate {
+            fee_amount,
+            fee_account_guid_hash,
+            attestor: unpacked,
+        } => {
+            assert_eq!(fee_amount, 5);
+            assert_eq!(fee_account_guid_hash, None);
+            assert_eq!(unpacked, attestor);
+        }
+        _ => panic!("unexpected instruction"),
+    }
+    let packed = ProgramInstruction::FinalizeInsuranceAttestorUpdate { attestor }.pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::FinalizeInsuranceAttestorUpdate { attestor: unpacked } => {
+            assert_eq!(unpacked, attestor);
+        }
+        _ => panic!("unexpected instruction"),
+    }
+    let statement_hash = hash(b"statement");
+    let packed = ProgramInstruction::SubmitInsuranceAttestation {
+        statement_hash,
+        coverage_expires_at: 1_731_000_000,
+    }
+    .pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::SubmitInsuranceAttestation {
+            statement_hash: unpacked,
+            coverage_expires_at,
+        } => {
+            assert_eq!(unpacked, statement_hash);
+            assert_eq!(coverage_expires_at, 1_731_000_000);
+        }
+        _ => panic!("unexpected instruction"),
+    }
+    assert!(matches!(
+        ProgramInstruction::unpack(&packed[..packed.len() - 1]),
+        Err(ProgramError::InvalidInstructionData)
+    ));
+    assert!(matches!(
+        ProgramInstruction::unpack(&ProgramInstruction::CheckInsuranceAttestation {}.pack())
+            .unwrap(),
+        ProgramInstruction::CheckInsuranceAttestation {}
+    ));
+}
+
+#[tokio::test]
+async fn test_unwrap_all_round_trip() {
+    let account_guid_hash = BalanceAccountGuidHash::new(&hash(&[4]).to_bytes());
+    let packed = ProgramInstruction::InitWrapUnwrap {
+        fee_amount: 0,
+        fee_account_guid_hash: None,
+        account_guid_hash,
+        amount: 0,
+        direction: WrapDirection::UNWRAP_ALL,
+    }
+    .pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::InitWrapUnwrap {
+            account_guid_hash: unpacked_guid_hash,
+            amount,
+            direction,
+            ..
+        } => {
+            assert_eq!(unpacked_guid_hash, account_guid_hash);
+            assert_eq!(amount, 0);
+            assert_eq!(direction, WrapDirection::UNWRAP_ALL);
+        }
+        _ => panic!("unexpected instruction"),
+    }
+    let packed = ProgramInstruction::FinalizeWrapUnwrap {
+        account_guid_hash,
+        amount: 0,
+        direction: WrapDirection::UNWRAP_ALL,
+    }
+    .pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::FinalizeWrapUnwrap { direction, .. } => {
+            assert_eq!(direction, WrapDirection::UNWRAP_ALL);
+        }
+        _ => panic!("unexpected instruction"),
+    }
+}
+
+#[tokio::test]
+async fn test_wallet_provisioning_round_trip() {
+    let initial_config = InitialWalletConfig {
+        approvals_required_for_config: 1,
+        approval_timeout_for_config: Duration::from_secs(3600),
+        signers: vec![(SlotId::new(0), WalletSigner::new(Keypair::new().pubkey()))],
+        config_approvers: vec![SlotId::new(0)],
+    };
+    let wallet_guid_hash = WalletGuidHash::new(&[7; HASH_LEN]);
+    let packed = ProgramInstruction::InitProvisioningWallet {
+        wallet_guid_hash,
+        initial_config: initial_config.clone(),
+    }
+    .pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::InitProvisioningWallet {
+            wallet_guid_hash: unpacked_guid_hash,
+            initial_config: unpacked,
+        } => {
+            assert_eq!(unpacked_guid_hash, wallet_guid_hash);
+            assert_eq!(unpacked, initial_config);
+        }
+        _ => panic!("unexpected instruction"),
+    }
+    let packed = ProgramInstruction::UpdateProvisioningWallet {
+        config: initial_config.clone(),
+    }
+    .pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::UpdateProvisioningWallet { config } => {
+            assert_eq!(config, initial_config)
+        }
+        _ => panic!("unexpected instruction"),
+    }
+    let packed = ProgramInstruction::ActivateWallet {}.pack();
+    assert!(matches!(
+        ProgramInstruction::unpack(&packed).unwrap(),
+        ProgramInstruction::ActivateWallet {}
+    ));
+}
+
+#[tokio::test]
+async fn test_wrapper_book_and_adapter_wrap_round_trip() {
+    let wrapper_book = WrapperBook {
+        entries: vec![
+            WrapperEntry {
+                mint: Pubkey::new_unique(),
+                adapter_program: Pubkey::new_unique(),
+                adapter_accounts: [Pubkey::new_unique(); WrapperEntry::MAX_ADAPTER_ACCOUNTS],
+            },
+            WrapperEntry {
+                mint: Pubkey::new_unique(),
+                adapter_program: Pubkey::new_unique(),
+                adapter_accounts: [Pubkey::new_unique(); WrapperEntry::MAX_ADAPTER_ACCOUNTS],
+            },
+        ],
+    };
+    let packed = ProgramInstruction::InitWrapperBookUpdate {
+        fee_amount: 0,
+        fee_account_guid_hash: None,
+        wrapper_book: wrapper_book.clone(),
+    }
+    .pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::InitWrapperBookUpdate {
+            wrapper_book: unpacked,
+            ..
+        } => assert_eq!(unpacked, wrapper_book),
+        _ => panic!("unexpected instruction"),
+    }
+
+    let account_guid_hash = BalanceAccountGuidHash::new(&[3; HASH_LEN]);
+    let wrapper_mint = wrapper_book.entries[1].mint;
+    let packed = ProgramInstruction::FinalizeAdapterWrapUnwrap {
+        account_guid_hash,
+        wrapper_mint,
+        amount: 123_456,
+        direction: WrapDirection::UNWRAP,
+    }
+    .pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::FinalizeAdapterWrapUnwrap {
+            account_guid_hash: unpacked_guid_hash,
+            wrapper_mint: unpacked_mint,
+            amount,
+            direction,
+        } => {
+            assert_eq!(unpacked_guid_hash, account_guid_hash);
+            assert_eq!(unpacked_mint, wrapper_mint);
+            assert_eq!(amount, 123_456);
+            assert_eq!(direction, WrapDirection::UNWRAP);
+        }
+        _ => panic!("unexpected instruction"),
+    }
+}
+
+#[tokio::test]
+async fn test_migrate_pending_approvals_round_trip() {
+    let packed = ProgramInstruction::MigratePendingApprovals { approver_count: 2 }.pack();
+    assert!(matches!(
+        ProgramInstruction::unpack(&packed).unwrap(),
+        ProgramInstruction::MigratePendingApprovals { approver_count: 2 }
+    ));
+}
+
+#[tokio::test]
+async fn test_address_book_update_entry_kinds_round_trip() {
+    let entry = AddressBookEntry {
+        address: Pubkey::new_unique(),
+        name_hash: AddressBookEntryNameHash::new(&hash(b"entry").to_bytes()),
+    };
+    let update = AddressBookUpdate {
+        add_address_book_entries: vec![(SlotId::new(1), entry), (SlotId::new(2), entry)],
+        remove_address_book_entries: vec![],
+        deprecate_address_book_entries: vec![],
+        balance_account_whitelist_updates: vec![],
+        address_book_risks: vec![],
+        address_book_expirations: vec![],
+        address_book_entry_kinds: vec![
+            (SlotId::new(1), AddressBookEntryKind::Wallet),
+            (
+                SlotId::new(2),
+                AddressBookEntryKind::TokenAccount {
+                    owner: Pubkey::new_unique(),
+                    token_mint: Pubkey::new_unique(),
+                },
+            ),
+        ],
+    };
+    let packed = ProgramInstruction::InitAddressBookUpdate {
+        fee_amount: 0,
+        fee_account_guid_hash: None,
+        update: update.clone(),
+    }
+    .pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::InitAddressBookUpdate {
+            update: unpacked_update,
+            ..
+        } => assert_eq!(unpacked_update, update),
+        _ => panic!("unexpected instruction"),
+    }
+}
+
+#[tokio::test]
+async fn test_spl_token_accounts_creation_round_trip() {
+    let payer_account_guid_hash = BalanceAccountGuidHash::new(&hash(b"payer").to_bytes());
+    let (account_guid_hashes, _) = build_account_guid_hash_byte_vec(3);
+    let packed = ProgramInstruction::InitSPLTokenAccountsCreation {
+        fee_amount: 10,
+        fee_account_guid_hash: None,
+        payer_account_guid_hash,
+        account_guid_hashes: account_guid_hashes.clone(),
+    }
+    .pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::InitSPLTokenAccountsCreation {
+            fee_amount,
+            fee_account_guid_hash,
+            payer_account_guid_hash: unpacked_payer_account_guid_hash,
+            account_guid_hashes: unpacked_account_guid_hashes,
+        } => {
+            assert_eq!(fee_amount, 10);
+            assert_eq!(fee_account_guid_hash, None);
+            assert_eq!(unpacked_payer_account_guid_hash, payer_account_guid_hash);
+            assert_eq!(unpacked_account_guid_hashes, account_guid_hashes);
+        }
+        _ => panic!("unexpected instruction"),
+    }
+    let packed = ProgramInstruction::ContinueSPLTokenAccountsCreation {
+        payer_account_guid_hash,
+        account_guid_hashes: account_guid_hashes.clone(),
+        batch_size: 2,
+    }
+    .pack();
+    match ProgramInstruction::unpack(&packed).unwrap() {
+        ProgramInstruction::ContinueSPLTokenAccountsCreation {
+            payer_account_guid_hash: unpacked_payer_account_guid_hash,
+            account_guid_hashes: unpacked_account_guid_hashes,
+            batch_size,
+        } => {
+            assert_eq!(unpacked_payer_account_guid_hash, payer_account_guid_hash);
+            assert_eq!(unpacked_account_guid_hashes, account_guid_hashes);
+            assert_eq!(batch_size, 2);
+        }
+        _ => panic!("unexpected instruction"),
+    }
+}
@@ -0,0 +1,200 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use solana_program::instruction::InstructionError::Custom;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program_test::{tokio, BanksClientError};
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::Transaction;
+
+use strike_wallet::error::WalletError;
+use strike_wallet::model::token_account_registry::{RegisteredTokenAccount, TokenAccountRegistry};
+
+async fn register(
+    context: &mut BalanceAccountTestContext,
+    balance_account: &Pubkey,
+    initiator: &Keypair,
+    token_accounts: Vec<Pubkey>,
+) -> Result<(), BanksClientError> {
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[register_token_accounts(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                balance_account,
+                &initiator.pubkey(),
+                &context.pt_context.payer.pubkey(),
+                context.balance_account_guid_hash,
+                token_accounts,
+            )],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer, initiator],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+}
+
+async fn get_registry(context: &mut BalanceAccountTestContext) -> TokenAccountRegistry {
+    let (registry_address, _) = TokenAccountRegistry::find_address(
+        &context.wallet_account.pubkey(),
+        &context.balance_account_guid_hash,
+        &context.program_id,
+    );
+    TokenAccountRegistry::unpack(
+        &context
+            .pt_context
+            .banks_client
+            .get_account(registry_address)
+            .await
+            .unwrap()
+            .unwrap()
+            .data,
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_register_token_accounts() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let spl_context = setup_spl_transfer_test(&mut context, &balance_account, false).await;
+    let assistant = Keypair::from_bytes(&context.assistant_account.to_bytes()).unwrap();
+
+    register(
+        &mut context,
+        &balance_account,
+        &assistant,
+        vec![spl_context.source_token_address],
+    )
+    .await
+    .unwrap();
+
+    let registry = get_registry(&mut context).await;
+    assert_eq!(registry.wallet, context.wallet_account.pubkey());
+    assert_eq!(
+        registry.account_guid_hash,
+        context.balance_account_guid_hash
+    );
+    assert_eq!(
+        registry.token_accounts,
+        vec![RegisteredTokenAccount {
+            token_mint: spl_context.mint.pubkey(),
+            token_account: spl_context.source_token_address,
+            frozen: false,
+        }]
+    );
+
+    // registering it again is a no-op
+    let approver = Keypair::from_bytes(&context.approvers[0].to_bytes()).unwrap();
+    register(
+        &mut context,
+        &balance_account,
+        &approver,
+        vec![spl_context.source_token_address],
+    )
+    .await
+    .unwrap();
+    assert_eq!(get_registry(&mut context).await, registry);
+
+    // registering it once it has been frozen records the freeze
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[spl_token::instruction::freeze_account(
+                &spl_token::id(),
+                &spl_context.source_token_address,
+                &spl_context.mint.pubkey(),
+                &spl_context.mint_authority.pubkey(),
+                &[],
+            )
+            .unwrap()],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer, &spl_context.mint_authority],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+    context.pt_context.last_blockhash =
+        context.pt_context.get_new_latest_blockhash().await.unwrap();
+    register(
+        &mut context,
+        &balance_account,
+        &assistant,
+        vec![spl_context.source_token_address],
+    )
+    .await
+    .unwrap();
+    assert!(get_registry(&mut context).await.token_accounts[0].frozen);
+}
+
+#[tokio::test]
+async fn test_register_token_accounts_not_owned_by_balance_account() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let spl_context = setup_spl_transfer_test(&mut context, &balance_account, false).await;
+    let assistant = Keypair::from_bytes(&context.assistant_account.to_bytes()).unwrap();
+
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                spl_associated_token_account::instruction::create_associated_token_account(
+                    &context.pt_context.payer.pubkey(),
+                    &context.destination.pubkey(),
+                    &spl_context.mint.pubkey(),
+                ),
+            ],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // a token account of someone else
+    assert_instruction_error(
+        register(
+            &mut context,
+            &balance_account,
+            &assistant,
+            vec![spl_context.destination_token_address],
+        )
+        .await,
+        0,
+        Custom(WalletError::InvalidTokenAccount as u32),
+    );
+
+    // an account that isn't a token account at all
+    assert_instruction_error(
+        register(
+            &mut context,
+            &balance_account,
+            &assistant,
+            vec![context.destination.pubkey()],
+        )
+        .await,
+        0,
+        Custom(WalletError::InvalidTokenAccount as u32),
+    );
+
+    // only an initiator can register token accounts
+    let outsider = Keypair::new();
+    assert_instruction_error(
+        register(
+            &mut context,
+            &balance_account,
+            &outsider,
+            vec![spl_context.source_token_address],
+        )
+        .await,
+        0,
+        Custom(WalletError::InvalidApprover as u32),
+    );
+}
@@ -0,0 +1,351 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use solana_program::instruction::InstructionError::Custom;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::pubkey::Pubkey;
+use solana_program::{system_instruction, sysvar};
+use solana_sdk::signature::Keypair;
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::RewardSplit;
+use strike_wallet::model::balance_account::{BalanceAccountGuidHash, RewardSplitPolicy};
+use strike_wallet::model::multisig_op::{ApprovalDisposition, OperationDisposition};
+use strike_wallet::model::wallet::WalletGuidHash;
+use uuid::Uuid;
+use {
+    solana_program_test::tokio,
+    solana_sdk::{signature::Signer as SdkSigner, transaction::Transaction},
+};
+
+// the size of a vote account's state, as allocated by the vote program
+const VOTE_STATE_LEN: usize = 3731;
+const REWARDS: u64 = 1000;
+
+struct VoteRewardsTestContext {
+    test_context: TestContext,
+    wallet: Keypair,
+    assistant: Keypair,
+    approvers: Vec<Keypair>,
+    balance_accounts: Vec<(BalanceAccountGuidHash, Pubkey)>,
+    vote_account: Pubkey,
+}
+
+async fn setup_vote_rewards_test() -> VoteRewardsTestContext {
+    let mut test_context = setup_test(60_000).await;
+
+    let wallet = Keypair::new();
+    let wallet_guid_hash = WalletGuidHash::new(&hash_of(Uuid::new_v4().as_bytes()));
+    let assistant = Keypair::new();
+    let approvers = vec![Keypair::new(), Keypair::new()];
+    create_wallet(
+        &mut test_context,
+        &wallet,
+        &wallet_guid_hash,
+        &assistant,
+        &approvers,
+    )
+    .await;
+    let rent = test_context.banks_client.get_rent().await.unwrap();
+    let balance_accounts: Vec<(BalanceAccountGuidHash, Pubkey)> = create_balance_accounts(
+        &mut test_context,
+        &wallet.pubkey(),
+        &wallet_guid_hash,
+        &assistant,
+        &approvers,
+        3,
+        Some(rent.minimum_balance(0)),
+    )
+    .await
+    .iter()
+    .map(|(guid_hash, (pda, _))| (*guid_hash, *pda))
+    .collect();
+
+    // a vote account withdrawn by the first balance account, holding rewards on top of its rent
+    let vote_account = Keypair::new();
+    let node = Keypair::new();
+    let mut vote_init = 0u32.to_le_bytes().to_vec(); // VoteInstruction::InitializeAccount
+    vote_init.extend_from_slice(node.pubkey().as_ref()); // node
+    vote_init.extend_from_slice(node.pubkey().as_ref()); // authorized voter
+    vote_init.extend_from_slice(balance_accounts[0].1.as_ref()); // authorized withdrawer
+    vote_init.push(0); // commission
+    test_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                system_instruction::create_account(
+                    &test_context.payer.pubkey(),
+                    &vote_account.pubkey(),
+                    rent.minimum_balance(VOTE_STATE_LEN) + REWARDS,
+                    VOTE_STATE_LEN as u64,
+                    &solana_program::vote::program::id(),
+                ),
+                Instruction {
+                    program_id: solana_program::vote::program::id(),
+                    accounts: vec![
+                        AccountMeta::new(vote_account.pubkey(), false),
+                        AccountMeta::new_readonly(sysvar::rent::id(), false),
+                        AccountMeta::new_readonly(sysvar::clock::id(), false),
+                        AccountMeta::new_readonly(node.pubkey(), true),
+                    ],
+                    data: vote_init,
+                },
+            ],
+            Some(&test_context.payer.pubkey()),
+            &[&test_context.payer, &vote_account, &node],
+            test_context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    VoteRewardsTestContext {
+        test_context,
+        wallet,
+        assistant,
+        approvers,
+        balance_accounts,
+        vote_account: vote_account.pubkey(),
+    }
+}
+
+fn splits(context: &VoteRewardsTestContext, percentages: &[(usize, u8)]) -> Vec<RewardSplit> {
+    percentages
+        .iter()
+        .map(|(i, percentage)| RewardSplit {
+            account_guid_hash: context.balance_accounts[*i].0,
+            percentage: *percentage,
+        })
+        .collect()
+}
+
+async fn init_policy_update(
+    context: &mut VoteRewardsTestContext,
+    multisig_op_account: Keypair,
+    splits: &Vec<RewardSplit>,
+) -> Result<(), solana_program_test::BanksClientError> {
+    let instruction = init_reward_split_policy_update(
+        &context.test_context.program_id,
+        &context.wallet.pubkey(),
+        &multisig_op_account.pubkey(),
+        &context.assistant.pubkey(),
+        &context.test_context.payer.pubkey(),
+        context.balance_accounts[0].0,
+        splits,
+    );
+    let assistant = Keypair::from_bytes(&context.assistant.to_bytes()).unwrap();
+    init_multisig_op(
+        &mut context.test_context,
+        multisig_op_account,
+        instruction,
+        &assistant,
+    )
+    .await
+}
+
+async fn set_reward_split_policy(context: &mut VoteRewardsTestContext, splits: &Vec<RewardSplit>) {
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    init_policy_update(context, multisig_op_account, splits)
+        .await
+        .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut context.test_context,
+        &multisig_op_pubkey,
+        context.approvers.iter().collect(),
+    )
+    .await;
+    let instruction = finalize_reward_split_policy_update(
+        &context.test_context.program_id,
+        &context.wallet.pubkey(),
+        &multisig_op_pubkey,
+        &context.test_context.payer.pubkey(),
+        context.balance_accounts[0].0,
+        splits,
+    );
+    finalize_multisig_op(&mut context.test_context, multisig_op_pubkey, instruction).await;
+}
+
+async fn init(
+    context: &mut VoteRewardsTestContext,
+    multisig_op_account: Keypair,
+    vote_account: Pubkey,
+) -> Result<(), solana_program_test::BanksClientError> {
+    let instruction = init_vote_rewards_withdrawal(
+        &context.test_context.program_id,
+        &context.wallet.pubkey(),
+        &multisig_op_account.pubkey(),
+        &vote_account,
+        &context.assistant.pubkey(),
+        &context.test_context.payer.pubkey(),
+        context.balance_accounts[0].0,
+        REWARDS,
+    );
+    let assistant = Keypair::from_bytes(&context.assistant.to_bytes()).unwrap();
+    init_multisig_op(
+        &mut context.test_context,
+        multisig_op_account,
+        instruction,
+        &assistant,
+    )
+    .await
+}
+
+async fn finalize(
+    context: &mut VoteRewardsTestContext,
+    multisig_op_account: Pubkey,
+    split_accounts: &[usize],
+) {
+    let instruction = finalize_vote_rewards_withdrawal(
+        &context.test_context.program_id,
+        &context.wallet.pubkey(),
+        &multisig_op_account,
+        &context.vote_account,
+        &context.balance_accounts[0].1,
+        &context.test_context.payer.pubkey(),
+        context.balance_accounts[0].0,
+        REWARDS,
+        &split_accounts
+            .iter()
+            .map(|i| context.balance_accounts[*i].1)
+            .collect(),
+    );
+    finalize_multisig_op(&mut context.test_context, multisig_op_account, instruction).await;
+}
+
+#[tokio::test]
+async fn test_reward_split_policy_update() {
+    let mut context = setup_vote_rewards_test().await;
+    let vote_account = context.vote_account;
+
+    // a withdrawal can't be initiated before the balance account has a policy
+    assert_instruction_error(
+        init(&mut context, Keypair::new(), vote_account).await,
+        1,
+        Custom(WalletError::InvalidRewardSplit as u32),
+    );
+
+    for percentages in [
+        vec![],
+        vec![(0, 50), (1, 49)],
+        vec![(0, 50), (0, 50)],
+        vec![(0, 100), (1, 0)],
+    ] {
+        let splits = splits(&context, &percentages);
+        assert_instruction_error(
+            init_policy_update(&mut context, Keypair::new(), &splits).await,
+            1,
+            Custom(WalletError::InvalidRewardSplit as u32),
+        );
+    }
+
+    let splits = splits(&context, &[(2, 40), (1, 60)]);
+    set_reward_split_policy(&mut context, &splits).await;
+    let wallet = get_wallet(
+        &mut context.test_context.banks_client,
+        &context.wallet.pubkey(),
+    )
+    .await;
+    let balance_account = wallet
+        .get_balance_account(&context.balance_accounts[0].0)
+        .unwrap();
+    assert_eq!(
+        balance_account.reward_split_policy,
+        RewardSplitPolicy::new([0, 60, 40, 0, 0, 0, 0, 0, 0])
+    );
+
+    // the withdrawal must come from an account owned by the vote program
+    let not_a_vote_account = context.balance_accounts[1].1;
+    assert_instruction_error(
+        init(&mut context, Keypair::new(), not_a_vote_account).await,
+        1,
+        Custom(WalletError::AccountNotRecognized as u32),
+    );
+
+    init(&mut context, Keypair::new(), vote_account)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_vote_rewards_withdrawal_approved() {
+    let mut context = setup_vote_rewards_test().await;
+    let vote_account = context.vote_account;
+    // the authority keeps no share, as the native test runtime can't move lamports of an
+    // account passed twice to the same instruction
+    let splits = splits(&context, &[(2, 30), (1, 70)]);
+    set_reward_split_policy(&mut context, &splits).await;
+
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    init(&mut context, multisig_op_account, vote_account)
+        .await
+        .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut context.test_context,
+        &multisig_op_pubkey,
+        context.approvers.iter().collect(),
+    )
+    .await;
+
+    let balances_before = get_balances(&mut context).await;
+    // split accounts are given in slot order rather than the order the policy was set in
+    finalize(&mut context, multisig_op_pubkey, &[1, 2]).await;
+    let balances_after = get_balances(&mut context).await;
+
+    assert_eq!(balances_after[0], balances_before[0] - REWARDS);
+    for (i, share) in [0, 700, 300].into_iter().enumerate() {
+        assert_eq!(balances_after[i + 1], balances_before[i + 1] + share);
+    }
+}
+
+#[tokio::test]
+async fn test_vote_rewards_withdrawal_denied() {
+    let mut context = setup_vote_rewards_test().await;
+    let vote_account = context.vote_account;
+    let splits = splits(&context, &[(0, 50), (1, 30), (2, 20)]);
+    set_reward_split_policy(&mut context, &splits).await;
+
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    init(&mut context, multisig_op_account, vote_account)
+        .await
+        .unwrap();
+
+    approve_or_deny_n_of_n_multisig_op(
+        &mut context.test_context.banks_client,
+        &context.test_context.program_id,
+        &multisig_op_pubkey,
+        context.approvers.iter().collect(),
+        &context.test_context.payer,
+        context.test_context.recent_blockhash,
+        ApprovalDisposition::DENY,
+        OperationDisposition::DENIED,
+    )
+    .await;
+
+    let balances_before = get_balances(&mut context).await;
+    finalize(&mut context, multisig_op_pubkey, &[0, 1, 2]).await;
+
+    // nothing is withdrawn or distributed
+    assert_eq!(get_balances(&mut context).await, balances_before);
+}
+
+async fn get_balances(context: &mut VoteRewardsTestContext) -> Vec<u64> {
+    let mut balances = Vec::new();
+    for account in std::iter::once(context.vote_account)
+        .chain(context.balance_accounts.iter().map(|(_, pda)| *pda))
+    {
+        balances.push(
+            context
+                .test_context
+                .banks_client
+                .get_balance(account)
+                .await
+                .unwrap(),
+        );
+    }
+    balances
+}
@@ -25,7 +25,7 @@ mod common;
 async fn test_wallet_account_version_mismatch() {
     let program_id = Keypair::new().pubkey();
     let mut pt = ProgramTest::new("strike_wallet", program_id, processor!(Processor::process));
-    pt.set_compute_max_units(25_000);
+    pt.set_compute_max_units(35_000);
 
     let mut pt_context = pt.start_with_context().await;
 
@@ -74,6 +74,7 @@ async fn test_wallet_account_version_mismatch() {
         approval_timeout_for_config: Duration::from_secs(7200),
         config_approvers: vec![SlotId::new(1)],
         signers_hash: hash_signers(&vec![signers[0]]),
+        config_policy_timelock: Wallet::DEFAULT_CONFIG_POLICY_TIMELOCK,
     };
 
     let rent = pt_context.banks_client.get_rent().await.unwrap();
@@ -0,0 +1,120 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use solana_program::program_pack::Pack;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::Transaction;
+
+use strike_wallet::model::wallet_event_log::{WalletEventKind, WalletEventLog};
+use strike_wallet::utils::SlotId;
+
+#[tokio::test]
+async fn test_compact_wallet() {
+    let (mut context, _) = setup_balance_account_tests_and_finalize(None).await;
+    let wallet_address = context.wallet_account.pubkey();
+    let (balance_account_slot_id, balance_account_entry) =
+        context.balance_account_address_book_entry;
+    assert_eq!(balance_account_slot_id, SlotId::new(32));
+
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[init_event_log(
+                &context.program_id,
+                &wallet_address,
+                &context.pt_context.payer.pubkey(),
+            )],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+    let mut packed_remap = Vec::new();
+    get_wallet(&mut context.pt_context.banks_client, &wallet_address)
+        .await
+        .compaction()
+        .pack(&mut packed_remap);
+
+    let rent_return = context.pt_context.payer.pubkey();
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let mut test_context = context.to_test_context();
+    init_multisig_op(
+        &mut test_context,
+        multisig_op_account,
+        init_compact_wallet(
+            &context.program_id,
+            &wallet_address,
+            &multisig_op_pubkey,
+            &context.approvers[0].pubkey(),
+            &rent_return,
+        ),
+        &context.approvers[0],
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut test_context,
+        &multisig_op_pubkey,
+        vec![&context.approvers[0], &context.approvers[1]],
+    )
+    .await;
+    finalize_multisig_op(
+        &mut test_context,
+        multisig_op_pubkey,
+        with_event_log(
+            finalize_compact_wallet(
+                &context.program_id,
+                &wallet_address,
+                &multisig_op_pubkey,
+                &rent_return,
+            ),
+            &wallet_address,
+        ),
+    )
+    .await;
+
+    // the balance account's entry moved down into the first free slot after the two
+    // destinations, and there is nothing left to compact
+    let wallet = get_wallet(&mut context.pt_context.banks_client, &wallet_address).await;
+    assert_eq!(
+        wallet.address_book.filled_slots(),
+        vec![
+            (SlotId::new(0), context.allowed_destination),
+            (SlotId::new(1), wallet.address_book[SlotId::new(1)].unwrap()),
+            (SlotId::new(2), balance_account_entry),
+        ]
+    );
+    assert!(wallet.compaction().is_empty());
+
+    // the remap table is appended to the event log
+    let event_log = WalletEventLog::unpack(
+        context
+            .pt_context
+            .banks_client
+            .get_account(WalletEventLog::find_address(&wallet_address, &context.program_id).0)
+            .await
+            .unwrap()
+            .unwrap()
+            .data
+            .as_slice(),
+    )
+    .unwrap();
+    let logged_remap: Vec<u8> = event_log
+        .events
+        .iter()
+        .filter(|event| event.kind == WalletEventKind::SlotsRemapped)
+        .flat_map(|event| event.op.to_bytes())
+        .collect();
+    assert_eq!(logged_remap[..packed_remap.len()], packed_remap[..]);
+    assert!(logged_remap[packed_remap.len()..]
+        .iter()
+        .all(|byte| *byte == 0));
+}
@@ -12,11 +12,11 @@ use solana_sdk::signature::Keypair;
 use solana_sdk::signer::Signer as SdkSigner;
 use std::time::{Duration, SystemTime};
 use strike_wallet::error::WalletError;
-use strike_wallet::instruction::{InitialWalletConfig, WalletConfigPolicyUpdate};
+use strike_wallet::instruction::{ConfigUpdate, InitialWalletConfig, WalletConfigPolicyUpdate};
 use strike_wallet::model::multisig_op::{
     ApprovalDisposition, ApprovalDispositionRecord, MultisigOpParams, OperationDisposition,
 };
-use strike_wallet::model::wallet::{Approvers, WalletGuidHash};
+use strike_wallet::model::wallet::{Approvers, Wallet, WalletGuidHash};
 use strike_wallet::utils::SlotId;
 use uuid::Uuid;
 
@@ -64,6 +64,7 @@ async fn wallet_config_policy_update() {
         approval_timeout_for_config: Duration::from_secs(7200),
         config_approvers: vec![SlotId::new(1), SlotId::new(2)],
         signers_hash: hash_signers(&vec![signers[1], signers[2]]),
+        config_policy_timelock: Wallet::DEFAULT_CONFIG_POLICY_TIMELOCK,
     };
 
     let multisig_op_account = utils::init_wallet_config_policy_update(
@@ -84,10 +85,14 @@ async fn wallet_config_policy_update() {
             ApprovalDispositionRecord {
                 approver: approvers[0].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                reason: None,
+                stake_weight: 0,
             },
             ApprovalDispositionRecord {
                 approver: approvers[1].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                reason: None,
+                stake_weight: 0,
             },
         ],
         OperationDisposition::NONE,
@@ -114,18 +119,16 @@ async fn wallet_config_policy_update() {
     )
     .await;
 
+    // lowering the approvals required is staged instead of taking effect right away
+    let staged_wallet = get_wallet(&mut context.banks_client, &wallet_account.pubkey()).await;
+    let pending_config_policy = staged_wallet.pending_config_policy.unwrap();
+    assert_eq!(pending_config_policy.to_update(), update);
     let mut expected_wallet = wallet.clone();
-    expected_wallet.approvals_required_for_config = 1;
-    expected_wallet.approval_timeout_for_config = Duration::from_secs(7200);
-    expected_wallet.config_approvers =
-        Approvers::from_enabled_vec(vec![SlotId::new(1), SlotId::new(2)]);
+    expected_wallet.pending_config_policy = Some(pending_config_policy);
+    assert_eq!(expected_wallet, staged_wallet);
 
-    assert_eq!(
-        expected_wallet,
-        get_wallet(&mut context.banks_client, &wallet_account.pubkey()).await
-    );
-
-    // verify updates
+    // verify updates, which supersede the staged one
+    expected_wallet.pending_config_policy = None;
     expected_wallet.approvals_required_for_config = 2;
     expected_wallet.approval_timeout_for_config = Duration::from_secs(14400);
     expected_wallet.config_approvers =
@@ -140,8 +143,9 @@ async fn wallet_config_policy_update() {
             approval_timeout_for_config: Duration::from_secs(14400),
             config_approvers: vec![SlotId::new(0), SlotId::new(1), SlotId::new(2)],
             signers_hash: hash_signers(&vec![signers[0], signers[1], signers[2]]),
+            config_policy_timelock: Wallet::DEFAULT_CONFIG_POLICY_TIMELOCK,
         },
-        vec![&approvers[1], &approvers[2]],
+        vec![&approvers[0], &approvers[1]],
     )
     .await;
 
@@ -162,6 +166,7 @@ async fn wallet_config_policy_update() {
             approval_timeout_for_config: Duration::from_secs(14400),
             config_approvers: vec![SlotId::new(0), SlotId::new(1)],
             signers_hash: hash_signers(&vec![signers[0], signers[1]]),
+            config_policy_timelock: Wallet::DEFAULT_CONFIG_POLICY_TIMELOCK,
         },
         vec![&approvers[0], &approvers[1], &approvers[2]],
     )
@@ -216,6 +221,7 @@ async fn invalid_wallet_config_policy_updates() {
                 approval_timeout_for_config: Duration::from_secs(3200),
                 config_approvers: vec![SlotId::new(0), SlotId::new(1)],
                 signers_hash: hash_signers(&vec![signers[0], signers[1]]),
+                config_policy_timelock: Wallet::DEFAULT_CONFIG_POLICY_TIMELOCK,
             },
         )
         .await,
@@ -234,6 +240,7 @@ async fn invalid_wallet_config_policy_updates() {
                 approval_timeout_for_config: Duration::from_secs(3200),
                 config_approvers: vec![SlotId::new(0), SlotId::new(2)],
                 signers_hash: hash_signers(&vec![signers[0], signers[2]]),
+                config_policy_timelock: Wallet::DEFAULT_CONFIG_POLICY_TIMELOCK,
             },
         )
         .await,
@@ -252,17 +259,114 @@ async fn invalid_wallet_config_policy_updates() {
                 approval_timeout_for_config: Duration::from_secs(3200),
                 config_approvers: vec![SlotId::new(0), SlotId::new(1)],
                 signers_hash: hash_signers(&vec![signers[0], signers[2]]),
+                config_policy_timelock: Wallet::DEFAULT_CONFIG_POLICY_TIMELOCK,
             },
         )
         .await,
         1,
         Custom(WalletError::InvalidSignersHash as u32),
     );
+
+    // verify config approvers must be sorted and free of duplicates
+    for config_approvers in [
+        vec![SlotId::new(1), SlotId::new(0)],
+        vec![SlotId::new(0), SlotId::new(0)],
+    ] {
+        assert_instruction_error(
+            utils::init_wallet_config_policy_update(
+                &mut context,
+                wallet_account.pubkey(),
+                &assistant_account,
+                &WalletConfigPolicyUpdate {
+                    approvals_required_for_config: 1,
+                    approval_timeout_for_config: Duration::from_secs(3200),
+                    config_approvers,
+                    signers_hash: hash_signers(&vec![signers[0], signers[1]]),
+                    config_policy_timelock: Wallet::DEFAULT_CONFIG_POLICY_TIMELOCK,
+                },
+            )
+            .await,
+            1,
+            Custom(WalletError::NonCanonicalParams as u32),
+        );
+    }
+
+    // simulations run the same validation, and leave the wallet as it was
+    let wallet = get_wallet(&mut context.banks_client, &wallet_account.pubkey()).await;
+    assert_instruction_error(
+        simulate_wallet_config_policy_update(
+            &mut context,
+            wallet_account.pubkey(),
+            &WalletConfigPolicyUpdate {
+                approvals_required_for_config: 3,
+                approval_timeout_for_config: Duration::from_secs(3200),
+                config_approvers: vec![SlotId::new(0), SlotId::new(1)],
+                signers_hash: hash_signers(&vec![signers[0], signers[1]]),
+                config_policy_timelock: Wallet::DEFAULT_CONFIG_POLICY_TIMELOCK,
+            },
+        )
+        .await,
+        0,
+        Custom(WalletError::InvalidApproverCount as u32),
+    );
+    simulate_wallet_config_policy_update(
+        &mut context,
+        wallet_account.pubkey(),
+        &WalletConfigPolicyUpdate {
+            approvals_required_for_config: 1,
+            approval_timeout_for_config: Duration::from_secs(3200),
+            config_approvers: vec![SlotId::new(0)],
+            signers_hash: hash_signers(&vec![signers[0]]),
+            config_policy_timelock: Wallet::DEFAULT_CONFIG_POLICY_TIMELOCK,
+        },
+    )
+    .await
+    .unwrap();
+
+    // as do config update previews, which abort once they have logged the resulting policy
+    assert_instruction_error(
+        simulate_config_update(
+            &mut context,
+            wallet_account.pubkey(),
+            &assistant_account,
+            &ConfigUpdate::WalletConfigPolicy(WalletConfigPolicyUpdate {
+                approvals_required_for_config: 3,
+                approval_timeout_for_config: Duration::from_secs(3200),
+                config_approvers: vec![SlotId::new(0), SlotId::new(1)],
+                signers_hash: hash_signers(&vec![signers[0], signers[1]]),
+                config_policy_timelock: Wallet::DEFAULT_CONFIG_POLICY_TIMELOCK,
+            }),
+        )
+        .await,
+        0,
+        Custom(WalletError::InvalidApproverCount as u32),
+    );
+    assert_instruction_error(
+        simulate_config_update(
+            &mut context,
+            wallet_account.pubkey(),
+            &assistant_account,
+            &ConfigUpdate::WalletConfigPolicy(WalletConfigPolicyUpdate {
+                approvals_required_for_config: 1,
+                approval_timeout_for_config: Duration::from_secs(3200),
+                config_approvers: vec![SlotId::new(0)],
+                signers_hash: hash_signers(&vec![signers[0]]),
+                config_policy_timelock: Wallet::DEFAULT_CONFIG_POLICY_TIMELOCK,
+            }),
+        )
+        .await,
+        0,
+        Custom(WalletError::SimulationFinished as u32),
+    );
+    assert_eq!(
+        get_wallet(&mut context.banks_client, &wallet_account.pubkey()).await,
+        wallet
+    );
 }
 
 #[tokio::test]
 async fn wallet_config_policy_update_initiator_approval() {
-    let mut context = setup_test(30_000).await;
+    let (mut context, mut pt_context) = setup_test_with_context(40_000).await;
 
     let wallet_account = Keypair::new();
     let assistant_account = Keypair::new();
@@ -301,6 +405,7 @@ async fn wallet_config_policy_update_initiator_approval() {
         approval_timeout_for_config: Duration::from_secs(3600),
         config_approvers: vec![SlotId::new(0)],
         signers_hash: hash_signers(&vec![signers[0]]),
+        config_policy_timelock: Wallet::DEFAULT_CONFIG_POLICY_TIMELOCK,
     };
 
     let multisig_op_account = utils::init_wallet_config_policy_update(
@@ -319,10 +424,14 @@ async fn wallet_config_policy_update_initiator_approval() {
             ApprovalDispositionRecord {
                 approver: approvers[0].pubkey(),
                 disposition: ApprovalDisposition::APPROVE,
+                reason: None,
+                stake_weight: 0,
             },
             ApprovalDispositionRecord {
                 approver: approvers[1].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                reason: None,
+                stake_weight: 0,
             },
         ],
         OperationDisposition::NONE,
@@ -342,6 +451,11 @@ async fn wallet_config_policy_update_initiator_approval() {
         &update.clone(),
     )
     .await;
+    context.recent_blockhash =
+        advance_clock(&mut pt_context, Wallet::DEFAULT_CONFIG_POLICY_TIMELOCK).await;
+    activate_wallet_config_policy(&mut context, wallet_account.pubkey())
+        .await
+        .unwrap();
 
     let multisig_op_account = utils::init_wallet_config_policy_update(
         &mut context,
@@ -352,6 +466,7 @@ async fn wallet_config_policy_update_initiator_approval() {
             approval_timeout_for_config: Duration::from_secs(7200),
             config_approvers: vec![SlotId::new(0)],
             signers_hash: hash_signers(&vec![signers[0]]),
+            config_policy_timelock: Wallet::DEFAULT_CONFIG_POLICY_TIMELOCK,
         },
     )
     .await
@@ -363,7 +478,138 @@ async fn wallet_config_policy_update_initiator_approval() {
         &vec![ApprovalDispositionRecord {
             approver: approvers[0].pubkey(),
             disposition: ApprovalDisposition::APPROVE,
+            reason: None,
+            stake_weight: 0,
         }],
         OperationDisposition::APPROVED,
     );
 }
+
+#[tokio::test]
+async fn wallet_config_policy_update_timelock() {
+    let (mut context, mut pt_context) = setup_test_with_context(40_000).await;
+
+    let wallet_account = Keypair::new();
+    let assistant_account = Keypair::new();
+
+    let approvers = vec![Keypair::new(), Keypair::new(), Keypair::new()];
+    let signers = vec![
+        approvers[0].pubkey_as_signer(),
+        approvers[1].pubkey_as_signer(),
+        approvers[2].pubkey_as_signer(),
+    ];
+
+    utils::init_wallet(
+        &mut context.banks_client,
+        &context.payer,
+        context.recent_blockhash,
+        &context.program_id,
+        &wallet_account,
+        &assistant_account,
+        WalletGuidHash::new(&hash_of(Uuid::new_v4().as_bytes())),
+        InitialWalletConfig {
+            approvals_required_for_config: 3,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: vec![
+                (SlotId::new(0), signers[0]),
+                (SlotId::new(1), signers[1]),
+                (SlotId::new(2), signers[2]),
+            ],
+            config_approvers: vec![SlotId::new(0), SlotId::new(1), SlotId::new(2)],
+        },
+    )
+    .await
+    .unwrap();
+    let wallet = get_wallet(&mut context.banks_client, &wallet_account.pubkey()).await;
+    assert_eq!(
+        wallet.config_policy_timelock,
+        Wallet::DEFAULT_CONFIG_POLICY_TIMELOCK
+    );
+
+    // nothing to activate yet
+    assert_instruction_error(
+        activate_wallet_config_policy(&mut context, wallet_account.pubkey()).await,
+        0,
+        Custom(WalletError::NoPendingConfigPolicy as u32),
+    );
+
+    // a quorum lowering the approvals required and dropping the timelock has to wait it out
+    let weakened = WalletConfigPolicyUpdate {
+        approvals_required_for_config: 1,
+        approval_timeout_for_config: Duration::from_secs(3600),
+        config_approvers: vec![SlotId::new(0)],
+        signers_hash: hash_signers(&vec![signers[0]]),
+        config_policy_timelock: Duration::from_secs(0),
+    };
+    utils::update_wallet_config_policy(
+        &mut context,
+        wallet_account.pubkey(),
+        &assistant_account,
+        &weakened,
+        approvers.iter().collect(),
+    )
+    .await;
+    let wallet = get_wallet(&mut context.banks_client, &wallet_account.pubkey()).await;
+    assert_eq!(wallet.approvals_required_for_config, 3);
+    assert_eq!(wallet.pending_config_policy.unwrap().to_update(), weakened);
+
+    context.recent_blockhash = advance_clock(&mut pt_context, Duration::from_secs(3600)).await;
+    assert_instruction_error(
+        activate_wallet_config_policy(&mut context, wallet_account.pubkey()).await,
+        0,
+        Custom(WalletError::ConfigPolicyTimelocked as u32),
+    );
+
+    context.recent_blockhash = advance_clock(
+        &mut pt_context,
+        Wallet::DEFAULT_CONFIG_POLICY_TIMELOCK - Duration::from_secs(3600),
+    )
+    .await;
+    activate_wallet_config_policy(&mut context, wallet_account.pubkey())
+        .await
+        .unwrap();
+    let wallet = get_wallet(&mut context.banks_client, &wallet_account.pubkey()).await;
+    assert_eq!(wallet.approvals_required_for_config, 1);
+    assert_eq!(wallet.config_policy_timelock, Duration::from_secs(0));
+    assert_eq!(
+        wallet.config_approvers,
+        Approvers::from_enabled_vec(vec![SlotId::new(0)])
+    );
+    assert_eq!(wallet.pending_config_policy, None);
+
+    // without a timelock, lowering the approvals required takes effect right away
+    utils::update_wallet_config_policy(
+        &mut context,
+        wallet_account.pubkey(),
+        &assistant_account,
+        &WalletConfigPolicyUpdate {
+            approvals_required_for_config: 2,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            config_approvers: vec![SlotId::new(0), SlotId::new(1)],
+            signers_hash: hash_signers(&vec![signers[0], signers[1]]),
+            config_policy_timelock: Duration::from_secs(0),
+        },
+        vec![&approvers[0]],
+    )
+    .await;
+    utils::update_wallet_config_policy(
+        &mut context,
+        wallet_account.pubkey(),
+        &assistant_account,
+        &WalletConfigPolicyUpdate {
+            approvals_required_for_config: 1,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            config_approvers: vec![SlotId::new(1)],
+            signers_hash: hash_signers(&vec![signers[1]]),
+            config_policy_timelock: Duration::from_secs(0),
+        },
+        vec![&approvers[0], &approvers[1]],
+    )
+    .await;
+    let wallet = get_wallet(&mut context.banks_client, &wallet_account.pubkey()).await;
+    assert_eq!(wallet.approvals_required_for_config, 1);
+    assert_eq!(
+        wallet.config_approvers,
+        Approvers::from_enabled_vec(vec![SlotId::new(1)])
+    );
+}
@@ -0,0 +1,275 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use std::time::Duration;
+
+use solana_program::instruction::{Instruction, InstructionError};
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::Transaction;
+
+use strike_wallet::model::multisig_op::ApprovalDisposition;
+use strike_wallet::model::wallet_event_log::{WalletEventKind, WalletEventLog};
+
+async fn init_event_log_for(context: &mut BalanceAccountTestContext) -> Pubkey {
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[init_event_log(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                &context.pt_context.payer.pubkey(),
+            )],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+    WalletEventLog::find_address(&context.wallet_account.pubkey(), &context.program_id).0
+}
+
+async fn get_event_log(
+    context: &mut BalanceAccountTestContext,
+    address: &Pubkey,
+) -> WalletEventLog {
+    WalletEventLog::unpack(
+        context
+            .pt_context
+            .banks_client
+            .get_account(*address)
+            .await
+            .unwrap()
+            .unwrap()
+            .data
+            .as_slice(),
+    )
+    .unwrap()
+}
+
+fn signer_activation_delay_update(
+    context: &BalanceAccountTestContext,
+    multisig_op_account: &Pubkey,
+) -> (Instruction, Instruction) {
+    let wallet = context.wallet_account.pubkey();
+    let rent_return = context.pt_context.payer.pubkey();
+    let delay = Duration::from_secs(60 * 60);
+    (
+        init_signer_activation_delay_update(
+            &context.program_id,
+            &wallet,
+            multisig_op_account,
+            &context.approvers[0].pubkey(),
+            &rent_return,
+            delay,
+        ),
+        finalize_signer_activation_delay_update(
+            &context.program_id,
+            &wallet,
+            multisig_op_account,
+            &rent_return,
+            delay,
+        ),
+    )
+}
+
+async fn set_disposition_with_event_log(
+    context: &mut BalanceAccountTestContext,
+    multisig_op_account: &Pubkey,
+    approver: &Keypair,
+    disposition: ApprovalDisposition,
+) {
+    let params_hash =
+        get_operation_hash(&mut context.pt_context.banks_client, *multisig_op_account).await;
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[with_event_log(
+                set_approval_disposition(
+                    &context.program_id,
+                    multisig_op_account,
+                    &approver.pubkey(),
+                    disposition,
+                    params_hash,
+                ),
+                &context.wallet_account.pubkey(),
+            )],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer, approver],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_event_log_records_op_lifecycle() {
+    let (mut context, _) = setup_balance_account_tests_and_finalize(None).await;
+    let event_log = init_event_log_for(&mut context).await;
+    let log = get_event_log(&mut context, &event_log).await;
+    assert_eq!(log.wallet, context.wallet_account.pubkey());
+    assert_eq!(log.next_sequence, 0);
+    assert!(log.events.is_empty());
+
+    let wallet = context.wallet_account.pubkey();
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let (init, finalize) = signer_activation_delay_update(&context, &multisig_op_pubkey);
+    init_multisig_op(
+        &mut context.to_test_context(),
+        multisig_op_account,
+        with_event_log(init, &wallet),
+        &context.approvers[0],
+    )
+    .await
+    .unwrap();
+    let approver = Keypair::from_bytes(&context.approvers[1].to_bytes()).unwrap();
+    set_disposition_with_event_log(
+        &mut context,
+        &multisig_op_pubkey,
+        &approver,
+        ApprovalDisposition::APPROVE,
+    )
+    .await;
+    finalize_multisig_op(
+        &mut context.to_test_context(),
+        multisig_op_pubkey,
+        with_event_log(finalize, &wallet),
+    )
+    .await;
+
+    let log = get_event_log(&mut context, &event_log).await;
+    assert_eq!(log.next_sequence, 4);
+    assert_eq!(
+        log.events
+            .iter()
+            .map(|event| (event.sequence, event.kind, event.op))
+            .collect::<Vec<_>>(),
+        vec![
+            (0, WalletEventKind::OpInitiated, multisig_op_pubkey),
+            (1, WalletEventKind::OpApproved, multisig_op_pubkey),
+            (2, WalletEventKind::OpExecuted, multisig_op_pubkey),
+            (3, WalletEventKind::ConfigChanged, multisig_op_pubkey),
+        ]
+    );
+
+    // only the instructions the log is supplied to log events
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let (init, finalize) = signer_activation_delay_update(&context, &multisig_op_pubkey);
+    let mut test_context = context.to_test_context();
+    init_multisig_op(
+        &mut test_context,
+        multisig_op_account,
+        init,
+        &context.approvers[0],
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut test_context,
+        &multisig_op_pubkey,
+        vec![&context.approvers[0], &approver],
+    )
+    .await;
+    finalize_multisig_op(
+        &mut test_context,
+        multisig_op_pubkey,
+        with_event_log(finalize, &wallet),
+    )
+    .await;
+
+    let log = get_event_log(&mut context, &event_log).await;
+    assert_eq!(log.next_sequence, 6);
+    assert_eq!(
+        log.events[4..]
+            .iter()
+            .map(|event| (event.kind, event.op))
+            .collect::<Vec<_>>(),
+        vec![
+            (WalletEventKind::OpExecuted, multisig_op_pubkey),
+            (WalletEventKind::ConfigChanged, multisig_op_pubkey),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_event_log_is_created_once() {
+    let (mut context, _) = setup_balance_account_tests_and_finalize(None).await;
+    init_event_log_for(&mut context).await;
+    let blockhash = advance_clock(&mut context.pt_context, Duration::from_secs(1)).await;
+    assert_instruction_error(
+        context
+            .pt_context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[init_event_log(
+                    &context.program_id,
+                    &context.wallet_account.pubkey(),
+                    &context.pt_context.payer.pubkey(),
+                )],
+                Some(&context.pt_context.payer.pubkey()),
+                &[&context.pt_context.payer],
+                blockhash,
+            ))
+            .await,
+        0,
+        InstructionError::AccountAlreadyInitialized,
+    );
+}
+
+#[tokio::test]
+async fn test_event_log_records_expired_op_cleanup() {
+    let (mut context, _) = setup_balance_account_tests_and_finalize(None).await;
+    let event_log = init_event_log_for(&mut context).await;
+
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let (init, _) = signer_activation_delay_update(&context, &multisig_op_pubkey);
+    init_multisig_op(
+        &mut context.to_test_context(),
+        multisig_op_account,
+        init,
+        &context.approvers[0],
+    )
+    .await
+    .unwrap();
+
+    let blockhash = advance_clock(&mut context.pt_context, Duration::from_secs(121)).await;
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[with_event_log(
+                cleanup_expired_op(
+                    &context.program_id,
+                    &multisig_op_pubkey,
+                    &context.pt_context.payer.pubkey(),
+                    Vec::new(),
+                ),
+                &context.wallet_account.pubkey(),
+            )],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer],
+            blockhash,
+        ))
+        .await
+        .unwrap();
+
+    let log = get_event_log(&mut context, &event_log).await;
+    assert_eq!(
+        log.events
+            .iter()
+            .map(|event| (event.kind, event.op))
+            .collect::<Vec<_>>(),
+        vec![(WalletEventKind::OpExpired, multisig_op_pubkey)]
+    );
+}
@@ -0,0 +1,183 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use solana_program::instruction::{Instruction, InstructionError::Custom};
+use solana_program::system_program;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::{Transaction, TransactionError};
+
+use strike_wallet::error::WalletError;
+use strike_wallet::model::wallet_freeze::WalletFreeze;
+
+async fn update_wallet_freeze(context: &mut BalanceAccountTestContext, freeze: WalletFreeze) {
+    let wallet = context.wallet_account.pubkey();
+    let rent_return = context.pt_context.payer.pubkey();
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let mut test_context = context.to_test_context();
+    init_multisig_op(
+        &mut test_context,
+        multisig_op_account,
+        init_wallet_freeze(
+            &context.program_id,
+            &wallet,
+            &multisig_op_pubkey,
+            &context.approvers[0].pubkey(),
+            &rent_return,
+            freeze,
+        ),
+        &context.approvers[0],
+    )
+    .await
+    .unwrap();
+    approve_n_of_n_multisig_op(
+        &mut test_context,
+        &multisig_op_pubkey,
+        vec![&context.approvers[0], &context.approvers[1]],
+    )
+    .await;
+    finalize_multisig_op(
+        &mut test_context,
+        multisig_op_pubkey,
+        finalize_wallet_freeze(
+            &context.program_id,
+            &wallet,
+            &multisig_op_pubkey,
+            &rent_return,
+            freeze,
+        ),
+    )
+    .await;
+}
+
+fn init_transfer_instruction(
+    context: &BalanceAccountTestContext,
+    balance_account: &solana_program::pubkey::Pubkey,
+    multisig_op_account: &Keypair,
+) -> Instruction {
+    init_transfer(
+        &context.program_id,
+        &context.wallet_account.pubkey(),
+        &multisig_op_account.pubkey(),
+        &context.approvers[2].pubkey(),
+        balance_account,
+        &context.destination.pubkey(),
+        context.balance_account_guid_hash,
+        1,
+        context.destination_name_hash,
+        &system_program::id(),
+        &context.pt_context.payer.pubkey(),
+        false,
+    )
+}
+
+async fn emergency_freeze_with(
+    context: &mut BalanceAccountTestContext,
+    signers: &[&Keypair],
+) -> Result<(), TransactionError> {
+    let mut all_signers = vec![&context.pt_context.payer];
+    all_signers.extend_from_slice(signers);
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[emergency_freeze(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                &signers
+                    .iter()
+                    .map(|signer| signer.pubkey())
+                    .collect::<Vec<_>>(),
+            )],
+            Some(&context.pt_context.payer.pubkey()),
+            &all_signers,
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+#[tokio::test]
+async fn test_wallet_freeze() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let approvers: Vec<Keypair> = context
+        .approvers
+        .iter()
+        .map(|approver| Keypair::from_base58_string(&approver.to_base58_string()))
+        .collect();
+
+    // emergency freeze is disabled until a quorum is set
+    assert_eq!(
+        emergency_freeze_with(&mut context, &[&approvers[1], &approvers[2]]).await,
+        Err(TransactionError::InstructionError(
+            0,
+            Custom(WalletError::EmergencyFreezeQuorumNotMet as u32)
+        ))
+    );
+
+    let freeze = WalletFreeze {
+        frozen: false,
+        emergency_quorum: 2,
+    };
+    update_wallet_freeze(&mut context, freeze).await;
+    assert_eq!(
+        get_wallet(
+            &mut context.pt_context.banks_client,
+            &context.wallet_account.pubkey()
+        )
+        .await
+        .freeze,
+        freeze
+    );
+
+    assert_eq!(
+        emergency_freeze_with(&mut context, &[&approvers[0]]).await,
+        Err(TransactionError::InstructionError(
+            0,
+            Custom(WalletError::EmergencyFreezeQuorumNotMet as u32)
+        ))
+    );
+    emergency_freeze_with(&mut context, &[&approvers[0], &approvers[1]])
+        .await
+        .unwrap();
+    assert!(
+        get_wallet(
+            &mut context.pt_context.banks_client,
+            &context.wallet_account.pubkey()
+        )
+        .await
+        .freeze
+        .frozen
+    );
+
+    // no transfers can be initiated while the wallet is frozen
+    let multisig_op_account = Keypair::new();
+    verify_multisig_op_init_fails(
+        &mut context.pt_context.banks_client,
+        context.pt_context.last_blockhash,
+        &context.pt_context.payer,
+        &approvers[2],
+        &multisig_op_account,
+        init_transfer_instruction(&context, &balance_account, &multisig_op_account),
+        Custom(WalletError::WalletFrozen as u32),
+    )
+    .await;
+
+    // config changes still go through, unfreezing included
+    update_wallet_freeze(&mut context, freeze).await;
+    let multisig_op_account = Keypair::new();
+    let init = init_transfer_instruction(&context, &balance_account, &multisig_op_account);
+    init_multisig_op(
+        &mut context.to_test_context(),
+        multisig_op_account,
+        init,
+        &approvers[2],
+    )
+    .await
+    .unwrap();
+}
@@ -0,0 +1,222 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils;
+pub use common::utils::*;
+
+use solana_program::instruction::{Instruction, InstructionError::Custom};
+use solana_program::system_instruction;
+use solana_program_test::{tokio, BanksClientError};
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer as SdkSigner;
+use solana_sdk::transaction::{Transaction, TransactionError};
+use std::time::Duration;
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::{InitialWalletConfig, WalletConfigPolicyUpdate};
+use strike_wallet::model::wallet::{Wallet, WalletGuidHash};
+use strike_wallet::model::wallet_provisioning::WalletProvisioning;
+use strike_wallet::utils::SlotId;
+use uuid::Uuid;
+
+async fn process(
+    context: &mut TestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            instructions,
+            Some(&context.payer.pubkey()),
+            &all_signers,
+            context.recent_blockhash,
+        ))
+        .await
+}
+
+#[tokio::test]
+async fn test_wallet_provisioning() {
+    let mut context = setup_test(40_000).await;
+    let program_id = context.program_id;
+    let payer = context.payer.pubkey();
+    let wallet_account = Keypair::new();
+    let assistant_account = Keypair::new();
+    let creator = Keypair::new();
+    let approvers = vec![Keypair::new(), Keypair::new(), Keypair::new()];
+    let signers = approvers
+        .iter()
+        .map(|approver| approver.pubkey_as_signer())
+        .collect::<Vec<_>>();
+
+    process(
+        &mut context,
+        &[
+            system_instruction::create_account(
+                &payer,
+                &wallet_account.pubkey(),
+                context.rent.minimum_balance(Wallet::LEN),
+                Wallet::LEN as u64,
+                &program_id,
+            ),
+            init_provisioning_wallet(
+                &program_id,
+                &wallet_account.pubkey(),
+                &assistant_account.pubkey(),
+                &payer,
+                &creator.pubkey(),
+                WalletGuidHash::new(&hash_of(Uuid::new_v4().as_bytes())),
+                InitialWalletConfig {
+                    approvals_required_for_config: 1,
+                    approval_timeout_for_config: Duration::from_secs(3600),
+                    signers: vec![(SlotId::new(0), signers[0])],
+                    config_approvers: vec![SlotId::new(0)],
+                },
+            ),
+        ],
+        &[&wallet_account, &assistant_account, &creator],
+    )
+    .await
+    .unwrap();
+    let wallet = get_wallet(&mut context.banks_client, &wallet_account.pubkey()).await;
+    assert_eq!(
+        wallet.provisioning,
+        Some(WalletProvisioning {
+            creator: creator.pubkey()
+        })
+    );
+
+    // only the creator can edit the config, without approvals
+    let config = InitialWalletConfig {
+        approvals_required_for_config: 2,
+        approval_timeout_for_config: Duration::from_secs(7200),
+        signers: vec![
+            (SlotId::new(0), signers[0]),
+            (SlotId::new(1), signers[1]),
+            (SlotId::new(2), signers[2]),
+        ],
+        config_approvers: vec![SlotId::new(0), SlotId::new(1)],
+    };
+    let impostor = Keypair::new();
+    assert_eq!(
+        process(
+            &mut context,
+            &[update_provisioning_wallet(
+                &program_id,
+                &wallet_account.pubkey(),
+                &impostor.pubkey(),
+                config.clone(),
+            )],
+            &[&impostor],
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            Custom(WalletError::InvalidProvisioningCreator as u32)
+        ),
+    );
+    process(
+        &mut context,
+        &[update_provisioning_wallet(
+            &program_id,
+            &wallet_account.pubkey(),
+            &creator.pubkey(),
+            config.clone(),
+        )],
+        &[&creator],
+    )
+    .await
+    .unwrap();
+    let wallet = get_wallet(&mut context.banks_client, &wallet_account.pubkey()).await;
+    assert_eq!(wallet.signers.filled_slots(), config.signers);
+    assert_eq!(wallet.approvals_required_for_config, 2);
+    assert_eq!(
+        wallet.approval_timeout_for_config,
+        Duration::from_secs(7200)
+    );
+
+    // no ops can be started until the wallet is activated
+    let update = WalletConfigPolicyUpdate {
+        approvals_required_for_config: 1,
+        approval_timeout_for_config: Duration::from_secs(3600),
+        config_approvers: vec![SlotId::new(0), SlotId::new(1)],
+        signers_hash: hash_signers(&signers),
+        config_policy_timelock: Wallet::DEFAULT_CONFIG_POLICY_TIMELOCK,
+    };
+    assert_eq!(
+        utils::init_wallet_config_policy_update(
+            &mut context,
+            wallet_account.pubkey(),
+            &assistant_account,
+            &update,
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(1, Custom(WalletError::WalletIsProvisioning as u32)),
+    );
+
+    // which takes the signatures of all the config approvers
+    assert_eq!(
+        process(
+            &mut context,
+            &[activate_wallet(
+                &program_id,
+                &wallet_account.pubkey(),
+                &[approvers[0].pubkey()],
+            )],
+            &[&approvers[0]],
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            Custom(WalletError::ActivationNotSignedByAllApprovers as u32)
+        ),
+    );
+    process(
+        &mut context,
+        &[activate_wallet(
+            &program_id,
+            &wallet_account.pubkey(),
+            &[approvers[0].pubkey(), approvers[1].pubkey()],
+        )],
+        &[&approvers[0], &approvers[1]],
+    )
+    .await
+    .unwrap();
+    let wallet = get_wallet(&mut context.banks_client, &wallet_account.pubkey()).await;
+    assert_eq!(wallet.provisioning, None);
+
+    // after which the creator can't edit the config any more, and ops can be started
+    assert_eq!(
+        process(
+            &mut context,
+            &[update_provisioning_wallet(
+                &program_id,
+                &wallet_account.pubkey(),
+                &creator.pubkey(),
+                config,
+            )],
+            &[&creator],
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        TransactionError::InstructionError(0, Custom(WalletError::WalletNotProvisioning as u32)),
+    );
+    utils::init_wallet_config_policy_update(
+        &mut context,
+        wallet_account.pubkey(),
+        &assistant_account,
+        &update,
+    )
+    .await
+    .unwrap();
+}
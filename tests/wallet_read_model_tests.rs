@@ -0,0 +1,142 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use std::borrow::BorrowMut;
+
+use solana_program::instruction::AccountMeta;
+use solana_program::program_pack::Pack;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::Transaction;
+
+use strike_wallet::instruction::BalanceAccountAddressWhitelistUpdate;
+use strike_wallet::model::multisig_op::{
+    ApprovalDisposition, BooleanSetting, OperationDisposition,
+};
+use strike_wallet::model::wallet_read_model::{EnabledDestination, WalletReadModel};
+use strike_wallet::utils::SlotId;
+
+async fn get_read_model(context: &mut BalanceAccountTestContext) -> WalletReadModel {
+    let (read_model_address, _) =
+        WalletReadModel::find_address(&context.wallet_account.pubkey(), &context.program_id);
+    WalletReadModel::unpack(
+        &context
+            .pt_context
+            .banks_client
+            .get_account(read_model_address)
+            .await
+            .unwrap()
+            .unwrap()
+            .data,
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_wallet_read_model() {
+    let (mut context, _) = setup_balance_account_tests_and_finalize(None).await;
+
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[export_wallet_read_model(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                &context.pt_context.payer.pubkey(),
+            )],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    let wallet = get_wallet(
+        &mut context.pt_context.banks_client,
+        &context.wallet_account.pubkey(),
+    )
+    .await;
+    let read_model = get_read_model(&mut context).await;
+    assert_eq!(
+        read_model,
+        WalletReadModel::of(&context.wallet_account.pubkey(), &wallet)
+    );
+    assert_eq!(
+        read_model.config_approvers,
+        wallet.get_config_approvers_keys()
+    );
+
+    // a config finalize supplied the read model as its last account refreshes it
+    let (slot_id, balance_account) = wallet
+        .get_balance_account_with_slot_id(&context.balance_account_guid_hash)
+        .unwrap();
+    let whitelist_destinations = vec![(SlotId::new(0), context.allowed_destination.clone())];
+    let update = BalanceAccountAddressWhitelistUpdate {
+        allowed_destinations: vec![SlotId::new(0)],
+        destinations_hash: hash_allowed_destinations(&whitelist_destinations),
+    };
+    let initiator = Keypair::from_base58_string(&context.initiator_account.to_base58_string());
+    let multisig_op_account =
+        init_balance_account_address_whitelist_update(&mut context, &initiator, update.clone())
+            .await
+            .unwrap();
+    approve_or_deny_n_of_n_multisig_op(
+        context.pt_context.banks_client.borrow_mut(),
+        &context.program_id,
+        &multisig_op_account,
+        vec![&context.approvers[0], &context.approvers[1]],
+        &context.pt_context.payer,
+        context.pt_context.last_blockhash,
+        ApprovalDisposition::APPROVE,
+        OperationDisposition::APPROVED,
+    )
+    .await;
+
+    let mut finalize = finalize_balance_account_address_whitelist_update_instruction(
+        &context.program_id,
+        &context.wallet_account.pubkey(),
+        &multisig_op_account,
+        &context.pt_context.payer.pubkey(),
+        context.balance_account_guid_hash,
+        update,
+        None,
+    );
+    finalize.accounts.push(AccountMeta::new(
+        WalletReadModel::find_address(&context.wallet_account.pubkey(), &context.program_id).0,
+        false,
+    ));
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[finalize],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    let refreshed = get_read_model(&mut context).await;
+    assert_eq!(
+        refreshed.destinations,
+        vec![EnabledDestination {
+            address: context.allowed_destination.address,
+            balance_account_slots: 1 << slot_id.value,
+        }]
+    );
+    assert_ne!(
+        refreshed.balance_account_digests[slot_id.value],
+        read_model.balance_account_digests[slot_id.value]
+    );
+    assert_eq!(refreshed.config_policy_hash, read_model.config_policy_hash);
+    assert_eq!(
+        refreshed.whitelist_enabled_slots & 1 << slot_id.value != 0,
+        balance_account.whitelist_enabled == BooleanSetting::On
+    );
+}
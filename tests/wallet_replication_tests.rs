@@ -0,0 +1,385 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use std::time::Duration;
+
+use solana_program::instruction::Instruction;
+use solana_program::instruction::InstructionError::Custom;
+use solana_program::pubkey::Pubkey;
+use solana_program_test::{tokio, BanksClientError};
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::Transaction;
+
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::{InitialWalletConfig, WalletConfigPolicyUpdate};
+use strike_wallet::model::multisig_op::SlotUpdateType;
+use strike_wallet::model::signer::Signer;
+use strike_wallet::model::wallet::{Wallet, WalletGuidHash};
+use strike_wallet::model::wallet_replication::{MirroredUpdate, WalletReplication};
+use strike_wallet::utils::SlotId;
+
+struct ReplicationTestContext {
+    test_context: TestContext,
+    approvers: Vec<Keypair>,
+    primary: Pubkey,
+    standby: Pubkey,
+}
+
+/// Sets up two wallets with the same signers and config policy.
+async fn setup() -> ReplicationTestContext {
+    let mut test_context = setup_test(200_000).await;
+    let approvers = vec![Keypair::new(), Keypair::new(), Keypair::new()];
+    let mut wallets = Vec::new();
+    for _ in 0..2 {
+        let wallet_account = Keypair::new();
+        common::utils::init_wallet(
+            &mut test_context.banks_client,
+            &test_context.payer,
+            test_context.recent_blockhash,
+            &test_context.program_id,
+            &wallet_account,
+            &Keypair::new(),
+            WalletGuidHash::new(&hash_of(wallet_account.pubkey().as_ref())),
+            InitialWalletConfig {
+                approvals_required_for_config: 2,
+                approval_timeout_for_config: Duration::from_secs(3600),
+                signers: approvers
+                    .iter()
+                    .enumerate()
+                    .map(|(i, approver)| (SlotId::new(i), approver.pubkey_as_signer()))
+                    .collect(),
+                config_approvers: vec![SlotId::new(0), SlotId::new(1)],
+            },
+        )
+        .await
+        .unwrap();
+        wallets.push(wallet_account.pubkey());
+    }
+    ReplicationTestContext {
+        test_context,
+        approvers,
+        primary: wallets[0],
+        standby: wallets[1],
+    }
+}
+
+async fn process(
+    context: &mut ReplicationTestContext,
+    instruction: Instruction,
+) -> Result<(), BanksClientError> {
+    let test_context = &mut context.test_context;
+    test_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&test_context.payer.pubkey()),
+            &[&test_context.payer],
+            test_context.recent_blockhash,
+        ))
+        .await
+}
+
+/// Initiates the op, has the config approvers approve it, and submits its finalize.
+async fn run_op(
+    context: &mut ReplicationTestContext,
+    multisig_op_account: Keypair,
+    init: Instruction,
+    finalize: Instruction,
+) -> Result<(), BanksClientError> {
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    init_multisig_op(
+        &mut context.test_context,
+        multisig_op_account,
+        init,
+        &context.approvers[0],
+    )
+    .await?;
+    approve_n_of_n_multisig_op(
+        &mut context.test_context,
+        &multisig_op_pubkey,
+        vec![&context.approvers[0], &context.approvers[1]],
+    )
+    .await;
+    process(context, finalize).await
+}
+
+async fn update_replication(
+    context: &mut ReplicationTestContext,
+    wallet: Pubkey,
+    replication: Option<WalletReplication>,
+) -> Result<(), BanksClientError> {
+    let program_id = context.test_context.program_id;
+    let rent_return = context.test_context.payer.pubkey();
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let init = init_wallet_replication_update(
+        &program_id,
+        &wallet,
+        &multisig_op_pubkey,
+        &context.approvers[0].pubkey(),
+        &rent_return,
+        replication,
+    );
+    let finalize = finalize_wallet_replication_update(
+        &program_id,
+        &wallet,
+        &multisig_op_pubkey,
+        &rent_return,
+        replication,
+    );
+    run_op(context, multisig_op_account, init, finalize).await
+}
+
+/// Adds a signer to the primary, passing the standby accounts along if `mirror` is set.
+async fn add_primary_signer(
+    context: &mut ReplicationTestContext,
+    slot_id: SlotId<Signer>,
+    signer: Signer,
+    mirror: bool,
+) -> Result<(), BanksClientError> {
+    let program_id = context.test_context.program_id;
+    let rent_return = context.test_context.payer.pubkey();
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let init = common::instructions::init_update_signer(
+        &program_id,
+        &context.primary,
+        &multisig_op_pubkey,
+        &context.approvers[0].pubkey(),
+        &rent_return,
+        SlotUpdateType::SetIfEmpty,
+        slot_id,
+        signer,
+        None,
+        None,
+    );
+    let mut finalize = finalize_update_signer(
+        &program_id,
+        &context.primary,
+        &multisig_op_pubkey,
+        &rent_return,
+        SlotUpdateType::SetIfEmpty,
+        slot_id,
+        signer,
+        None,
+    );
+    if mirror {
+        finalize.accounts.extend(standby_accounts(
+            &program_id,
+            &context.primary,
+            &context.standby,
+        ));
+    }
+    run_op(context, multisig_op_account, init, finalize).await
+}
+
+async fn update_primary_config_policy(
+    context: &mut ReplicationTestContext,
+    update: &WalletConfigPolicyUpdate,
+) -> Result<(), BanksClientError> {
+    let program_id = context.test_context.program_id;
+    let rent_return = context.test_context.payer.pubkey();
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let init = init_wallet_config_policy_update_instruction(
+        program_id,
+        context.primary,
+        multisig_op_pubkey,
+        context.approvers[0].pubkey(),
+        rent_return,
+        update,
+    );
+    let mut finalize = finalize_wallet_config_policy_update_instruction(
+        program_id,
+        context.primary,
+        multisig_op_pubkey,
+        rent_return,
+        update,
+        None,
+    );
+    finalize.accounts.extend(standby_accounts(
+        &program_id,
+        &context.primary,
+        &context.standby,
+    ));
+    run_op(context, multisig_op_account, init, finalize).await
+}
+
+async fn assert_in_sync(context: &mut ReplicationTestContext) {
+    let primary = get_wallet(&mut context.test_context.banks_client, &context.primary).await;
+    let standby = get_wallet(&mut context.test_context.banks_client, &context.standby).await;
+    assert_eq!(standby.signers, primary.signers);
+    assert_eq!(
+        standby.approvals_required_for_config,
+        primary.approvals_required_for_config
+    );
+    assert_eq!(
+        standby.approval_timeout_for_config,
+        primary.approval_timeout_for_config
+    );
+    assert_eq!(standby.config_approvers, primary.config_approvers);
+    assert_eq!(
+        standby.config_policy_timelock,
+        primary.config_policy_timelock
+    );
+    assert_eq!(standby.pending_config_policy, primary.pending_config_policy);
+}
+
+#[tokio::test]
+async fn test_wallet_replication() {
+    let mut context = setup().await;
+    let (primary, standby) = (context.primary, context.standby);
+
+    // a wallet only becomes a primary once its standby has agreed to mirror it
+    assert_instruction_error(
+        update_replication(
+            &mut context,
+            primary,
+            Some(WalletReplication::Primary { standby }),
+        )
+        .await,
+        0,
+        Custom(WalletError::StandbyWalletNotInSync as u32),
+    );
+    update_replication(
+        &mut context,
+        standby,
+        Some(WalletReplication::Standby { primary }),
+    )
+    .await
+    .unwrap();
+    update_replication(
+        &mut context,
+        primary,
+        Some(WalletReplication::Primary { standby }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        get_wallet(&mut context.test_context.banks_client, &primary)
+            .await
+            .replication,
+        Some(WalletReplication::Primary { standby })
+    );
+
+    // signer and config policy changes of the primary are mirrored into the standby
+    let new_signer = Keypair::new().pubkey_as_signer();
+    add_primary_signer(&mut context, SlotId::new(3), new_signer, true)
+        .await
+        .unwrap();
+    assert!(get_wallet(&mut context.test_context.banks_client, &standby)
+        .await
+        .signers
+        .contains(&vec![(SlotId::new(3), new_signer)]));
+    let signers = get_wallet(&mut context.test_context.banks_client, &primary)
+        .await
+        .get_signers_keys();
+    update_primary_config_policy(
+        &mut context,
+        &WalletConfigPolicyUpdate {
+            approvals_required_for_config: 2,
+            approval_timeout_for_config: Duration::from_secs(7200),
+            config_approvers: vec![SlotId::new(0), SlotId::new(1)],
+            signers_hash: hash_signers(&vec![Signer::new(signers[0]), Signer::new(signers[1])]),
+            config_policy_timelock: Wallet::DEFAULT_CONFIG_POLICY_TIMELOCK,
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        get_wallet(&mut context.test_context.banks_client, &standby)
+            .await
+            .approval_timeout_for_config,
+        Duration::from_secs(7200)
+    );
+    assert_in_sync(&mut context).await;
+
+    // the primary can't finalize a change without mirroring it
+    assert_instruction_error(
+        add_primary_signer(
+            &mut context,
+            SlotId::new(4),
+            Keypair::new().pubkey_as_signer(),
+            false,
+        )
+        .await,
+        0,
+        Custom(WalletError::StandbyWalletNotInSync as u32),
+    );
+
+    // the standby takes no config changes but its primary's
+    let multisig_op_account = Keypair::new();
+    let init = init_wallet_config_policy_update_instruction(
+        context.test_context.program_id,
+        standby,
+        multisig_op_account.pubkey(),
+        context.approvers[0].pubkey(),
+        context.test_context.payer.pubkey(),
+        &WalletConfigPolicyUpdate {
+            approvals_required_for_config: 1,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            config_approvers: vec![SlotId::new(0)],
+            signers_hash: hash_signers(&vec![Signer::new(signers[0])]),
+            config_policy_timelock: Duration::ZERO,
+        },
+    );
+    assert_instruction_error(
+        init_multisig_op(
+            &mut context.test_context,
+            multisig_op_account,
+            init,
+            &context.approvers[0],
+        )
+        .await,
+        1,
+        Custom(WalletError::WalletIsStandby as u32),
+    );
+    let program_id = context.test_context.program_id;
+    assert_instruction_error(
+        process(
+            &mut context,
+            apply_mirrored_update(
+                &program_id,
+                &standby,
+                &WalletReplication::mirror_authority(&primary, &program_id).0,
+                MirroredUpdate::Signer {
+                    slot_update_type: SlotUpdateType::Clear,
+                    slot_id: SlotId::new(3),
+                    signer: new_signer,
+                },
+            ),
+        )
+        .await,
+        0,
+        Custom(WalletError::InvalidMirroredUpdate as u32),
+    );
+    assert_in_sync(&mut context).await;
+
+    // cutting over to the standby leaves the primary unable to mirror its changes, until it
+    // drops its standby as well
+    update_replication(&mut context, standby, None)
+        .await
+        .unwrap();
+    let other_signer = Keypair::new().pubkey_as_signer();
+    assert_instruction_error(
+        add_primary_signer(&mut context, SlotId::new(4), other_signer, true).await,
+        0,
+        Custom(WalletError::InvalidMirroredUpdate as u32),
+    );
+    update_replication(&mut context, primary, None)
+        .await
+        .unwrap();
+    add_primary_signer(&mut context, SlotId::new(5), other_signer, false)
+        .await
+        .unwrap();
+    assert!(
+        !get_wallet(&mut context.test_context.banks_client, &standby)
+            .await
+            .signers
+            .contains(&vec![(SlotId::new(5), other_signer)])
+    );
+}
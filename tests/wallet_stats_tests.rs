@@ -0,0 +1,185 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use std::time::Duration;
+
+use solana_program::instruction::Instruction;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::Transaction;
+
+use strike_wallet::model::multisig_op::{ApprovalDisposition, MultisigOpCode};
+use strike_wallet::model::wallet_stats::{OpTypeStats, WalletStats};
+
+async fn init_wallet_stats_for(context: &mut BalanceAccountTestContext) -> Pubkey {
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[init_wallet_stats(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                &context.pt_context.payer.pubkey(),
+            )],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+    WalletStats::find_address(&context.wallet_account.pubkey(), &context.program_id).0
+}
+
+async fn get_wallet_stats(
+    context: &mut BalanceAccountTestContext,
+    address: &Pubkey,
+) -> WalletStats {
+    WalletStats::unpack(
+        context
+            .pt_context
+            .banks_client
+            .get_account(*address)
+            .await
+            .unwrap()
+            .unwrap()
+            .data
+            .as_slice(),
+    )
+    .unwrap()
+}
+
+fn signer_activation_delay_update(
+    context: &BalanceAccountTestContext,
+    multisig_op_account: &Pubkey,
+) -> (Instruction, Instruction) {
+    let wallet = context.wallet_account.pubkey();
+    let rent_return = context.pt_context.payer.pubkey();
+    let delay = Duration::from_secs(60 * 60);
+    (
+        with_wallet_stats(
+            init_signer_activation_delay_update(
+                &context.program_id,
+                &wallet,
+                multisig_op_account,
+                &context.approvers[0].pubkey(),
+                &rent_return,
+                delay,
+            ),
+            &wallet,
+        ),
+        with_wallet_stats(
+            finalize_signer_activation_delay_update(
+                &context.program_id,
+                &wallet,
+                multisig_op_account,
+                &rent_return,
+                delay,
+            ),
+            &wallet,
+        ),
+    )
+}
+
+async fn set_disposition_with_wallet_stats(
+    context: &mut BalanceAccountTestContext,
+    multisig_op_account: &Pubkey,
+    approver: &Keypair,
+    disposition: ApprovalDisposition,
+) {
+    let params_hash =
+        get_operation_hash(&mut context.pt_context.banks_client, *multisig_op_account).await;
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[with_wallet_stats(
+                set_approval_disposition(
+                    &context.program_id,
+                    multisig_op_account,
+                    &approver.pubkey(),
+                    disposition,
+                    params_hash,
+                ),
+                &context.wallet_account.pubkey(),
+            )],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer, approver],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_wallet_stats_count_ops_by_type() {
+    let (mut context, _) = setup_balance_account_tests_and_finalize(None).await;
+    let stats = init_wallet_stats_for(&mut context).await;
+    assert_eq!(
+        get_wallet_stats(&mut context, &stats).await,
+        WalletStats::new(context.wallet_account.pubkey())
+    );
+
+    // one update is approved and executed
+    let approver = Keypair::from_bytes(&context.approvers[1].to_bytes()).unwrap();
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let (init, finalize) = signer_activation_delay_update(&context, &multisig_op_pubkey);
+    init_multisig_op(
+        &mut context.to_test_context(),
+        multisig_op_account,
+        init,
+        &context.approvers[0],
+    )
+    .await
+    .unwrap();
+    set_disposition_with_wallet_stats(
+        &mut context,
+        &multisig_op_pubkey,
+        &approver,
+        ApprovalDisposition::APPROVE,
+    )
+    .await;
+    finalize_multisig_op(&mut context.to_test_context(), multisig_op_pubkey, finalize).await;
+
+    // and another is denied
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let (init, _) = signer_activation_delay_update(&context, &multisig_op_pubkey);
+    init_multisig_op(
+        &mut context.to_test_context(),
+        multisig_op_account,
+        init,
+        &context.approvers[0],
+    )
+    .await
+    .unwrap();
+    set_disposition_with_wallet_stats(
+        &mut context,
+        &multisig_op_pubkey,
+        &approver,
+        ApprovalDisposition::DENY,
+    )
+    .await;
+
+    let stats = get_wallet_stats(&mut context, &stats).await;
+    assert_eq!(
+        stats.op_type_stats(MultisigOpCode::UpdateSignerActivationDelay),
+        OpTypeStats {
+            initiated: 2,
+            approved: 1,
+            denied: 1,
+            expired: 0,
+        }
+    );
+    assert_eq!(
+        stats.op_type_stats(MultisigOpCode::Transfer),
+        OpTypeStats::default()
+    );
+    assert_eq!(stats.sol_outflow, 0);
+}
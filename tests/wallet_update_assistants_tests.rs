@@ -0,0 +1,303 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use std::borrow::BorrowMut;
+use std::time::Duration;
+
+use solana_program::instruction::InstructionError::Custom;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_instruction;
+use solana_program_test::BanksClientError;
+use strike_wallet::error::WalletError;
+use strike_wallet::instruction::InitialWalletConfig;
+use strike_wallet::model::address_book::{AddressBookEntry, AddressBookEntryNameHash};
+use strike_wallet::model::assistant::Assistant;
+use strike_wallet::model::multisig_op::SlotUpdateType;
+use strike_wallet::model::multisig_op::{ApprovalDisposition, MultisigOp, OperationDisposition};
+use strike_wallet::utils::SlotId;
+use {
+    solana_program::program_pack::Pack,
+    solana_program_test::tokio,
+    solana_sdk::{
+        signature::{Keypair, Signer as SdkSigner},
+        transaction::Transaction,
+    },
+};
+
+async fn init_op(
+    context: &mut WalletTestContext,
+    initiator: &Keypair,
+    instruction: fn(
+        &WalletTestContext,
+        &Pubkey,
+        &Pubkey,
+    ) -> solana_program::instruction::Instruction,
+) -> Result<Pubkey, BanksClientError> {
+    let multisig_op_account = Keypair::new();
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::create_account(
+                &context.payer.pubkey(),
+                &multisig_op_account.pubkey(),
+                context.rent.minimum_balance(MultisigOp::LEN),
+                MultisigOp::LEN as u64,
+                &context.program_id,
+            ),
+            instruction(context, &multisig_op_account.pubkey(), &initiator.pubkey()),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &multisig_op_account, initiator],
+        context.recent_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map(|_| multisig_op_account.pubkey())
+}
+
+fn sign_data(
+    context: &WalletTestContext,
+    multisig_op_account: &Pubkey,
+    initiator: &Pubkey,
+) -> solana_program::instruction::Instruction {
+    init_sign_data_instruction(
+        &context.program_id,
+        &context.wallet_account.pubkey(),
+        multisig_op_account,
+        initiator,
+        &context.payer.pubkey(),
+        &vec![1, 2, 3],
+    )
+}
+
+async fn update_assistant(
+    context: &mut WalletTestContext,
+    approvers: &Vec<Keypair>,
+    slot_update_type: SlotUpdateType,
+    slot_id: usize,
+    assistant: Assistant,
+) {
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::create_account(
+                &context.payer.pubkey(),
+                &multisig_op_pubkey,
+                context.rent.minimum_balance(MultisigOp::LEN),
+                MultisigOp::LEN as u64,
+                &context.program_id,
+            ),
+            init_update_assistant(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                &multisig_op_pubkey,
+                &approvers[0].pubkey(),
+                &context.payer.pubkey(),
+                slot_update_type,
+                SlotId::new(slot_id),
+                assistant,
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &multisig_op_account, &approvers[0]],
+        context.recent_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    approve_or_deny_n_of_n_multisig_op(
+        context.banks_client.borrow_mut(),
+        &context.program_id,
+        &multisig_op_pubkey,
+        approvers.iter().collect(),
+        &context.payer,
+        context.recent_blockhash,
+        ApprovalDisposition::APPROVE,
+        OperationDisposition::APPROVED,
+    )
+    .await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[finalize_update_assistant(
+            &context.program_id,
+            &context.wallet_account.pubkey(),
+            &multisig_op_pubkey,
+            &context.payer.pubkey(),
+            slot_update_type,
+            SlotId::new(slot_id),
+            assistant,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.recent_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+}
+
+async fn add_pending_entry(
+    context: &mut WalletTestContext,
+    assistant: &Keypair,
+    slot_id: usize,
+) -> Result<(), BanksClientError> {
+    let transaction = Transaction::new_signed_with_payer(
+        &[add_pending_address_book_entries(
+            &context.program_id,
+            &context.wallet_account.pubkey(),
+            &assistant.pubkey(),
+            vec![(
+                SlotId::new(slot_id),
+                AddressBookEntry {
+                    address: Keypair::new().pubkey(),
+                    name_hash: AddressBookEntryNameHash::new(&hash_of(&slot_id.to_le_bytes())),
+                },
+            )],
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, assistant],
+        context.recent_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await
+}
+
+#[tokio::test]
+async fn test_assistants_enable_flags_and_rate_limits() {
+    let approvers = vec![Keypair::new(), Keypair::new()];
+    let mut context = setup_wallet_test(
+        40_000,
+        InitialWalletConfig {
+            approvals_required_for_config: 2,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: vec![
+                (SlotId::new(0), approvers[0].pubkey_as_signer()),
+                (SlotId::new(1), approvers[1].pubkey_as_signer()),
+            ],
+            config_approvers: vec![SlotId::new(0), SlotId::new(1)],
+        },
+    )
+    .await;
+
+    // the assistant given at wallet creation occupies the first slot without a rate limit
+    let original = Keypair::from_bytes(&context.assistant_account.to_bytes()).unwrap();
+    let wallet = get_wallet(&mut context.banks_client, &context.wallet_account.pubkey()).await;
+    assert_eq!(
+        wallet.assistants[SlotId::new(0)],
+        Some(Assistant::new(original.pubkey()))
+    );
+
+    let automation = Keypair::new();
+    update_assistant(
+        &mut context,
+        &approvers,
+        SlotUpdateType::SetIfEmpty,
+        1,
+        Assistant::with_rate_limit(automation.pubkey(), true, 2),
+    )
+    .await;
+
+    for _ in 0..2 {
+        init_op(&mut context, &automation, sign_data).await.unwrap();
+    }
+    assert_instruction_error(
+        init_op(&mut context, &automation, sign_data).await,
+        1,
+        Custom(WalletError::AssistantRateLimitExceeded as u32),
+    );
+    let wallet = get_wallet(&mut context.banks_client, &context.wallet_account.pubkey()).await;
+    assert_eq!(
+        wallet.assistants[SlotId::new(1)]
+            .unwrap()
+            .initiations_in_window,
+        2
+    );
+
+    // the other assistant is unaffected, until it is disabled
+    init_op(&mut context, &original, sign_data).await.unwrap();
+    update_assistant(
+        &mut context,
+        &approvers,
+        SlotUpdateType::SetIfEmpty,
+        0,
+        Assistant::with_rate_limit(original.pubkey(), false, 0),
+    )
+    .await;
+    assert_instruction_error(
+        init_op(&mut context, &original, sign_data).await,
+        1,
+        Custom(WalletError::AssistantDisabled as u32),
+    );
+
+    // removing the rate limited assistant revokes it entirely
+    update_assistant(
+        &mut context,
+        &approvers,
+        SlotUpdateType::Clear,
+        1,
+        Assistant::with_rate_limit(automation.pubkey(), true, 2),
+    )
+    .await;
+    assert_instruction_error(
+        init_op(&mut context, &automation, sign_data).await,
+        1,
+        Custom(WalletError::InvalidApprover as u32),
+    );
+}
+
+#[tokio::test]
+async fn test_pending_address_book_entries_count_against_rate_limit() {
+    let approvers = vec![Keypair::new(), Keypair::new()];
+    let mut context = setup_wallet_test(
+        40_000,
+        InitialWalletConfig {
+            approvals_required_for_config: 2,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: vec![
+                (SlotId::new(0), approvers[0].pubkey_as_signer()),
+                (SlotId::new(1), approvers[1].pubkey_as_signer()),
+            ],
+            config_approvers: vec![SlotId::new(0), SlotId::new(1)],
+        },
+    )
+    .await;
+
+    let automation = Keypair::new();
+    update_assistant(
+        &mut context,
+        &approvers,
+        SlotUpdateType::SetIfEmpty,
+        1,
+        Assistant::with_rate_limit(automation.pubkey(), true, 2),
+    )
+    .await;
+
+    // adding pending entries and initiating ops draw on the same window
+    add_pending_entry(&mut context, &automation, 0)
+        .await
+        .unwrap();
+    init_op(&mut context, &automation, sign_data).await.unwrap();
+    assert_instruction_error(
+        add_pending_entry(&mut context, &automation, 1).await,
+        0,
+        Custom(WalletError::AssistantRateLimitExceeded as u32),
+    );
+    let wallet = get_wallet(&mut context.banks_client, &context.wallet_account.pubkey()).await;
+    assert_eq!(
+        wallet.assistants[SlotId::new(1)]
+            .unwrap()
+            .initiations_in_window,
+        2
+    );
+    assert_eq!(wallet.pending_address_book_entries.count_enabled(), 1);
+}
@@ -13,15 +13,20 @@ use solana_program::instruction::InstructionError::Custom;
 use crate::common::utils;
 use common::instructions;
 use strike_wallet::error::WalletError;
-use strike_wallet::instruction::InitialWalletConfig;
+use strike_wallet::instruction::{InitialWalletConfig, WalletConfigPolicyUpdate};
 use strike_wallet::model::multisig_op::{
-    ApprovalDisposition, ApprovalDispositionRecord, OperationDisposition, SlotUpdateType,
+    ApprovalDisposition, ApprovalDispositionRecord, MultisigOp, OperationDisposition,
+    SlotUpdateType,
 };
-use strike_wallet::model::wallet::Signers;
+use strike_wallet::model::signer::{Signer, SignerCapabilities};
+use strike_wallet::model::wallet::{Signers, Wallet};
 use strike_wallet::utils::SlotId;
 use {
-    solana_program_test::tokio,
+    solana_program::pubkey::Pubkey,
+    solana_program::system_instruction,
+    solana_program_test::{tokio, BanksClientError},
     solana_sdk::signature::{Keypair, Signer as SdkSigner},
+    solana_sdk::transaction::Transaction,
 };
 
 #[tokio::test]
@@ -189,7 +194,7 @@ async fn test_signers_update_initiator_approval() {
     let approvers = vec![Keypair::new(), Keypair::new(), Keypair::new()];
 
     let mut context = setup_wallet_test(
-        30_000,
+        40_000,
         InitialWalletConfig {
             approvals_required_for_config: 2,
             approval_timeout_for_config: Duration::from_secs(3600),
@@ -222,17 +227,21 @@ async fn test_signers_update_initiator_approval() {
             ApprovalDispositionRecord {
                 approver: approvers[0].pubkey(),
                 disposition: ApprovalDisposition::APPROVE,
+                reason: None,
+                stake_weight: 0,
             },
             ApprovalDispositionRecord {
                 approver: approvers[1].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                reason: None,
+                stake_weight: 0,
             },
         ],
         OperationDisposition::NONE,
     );
 
     let mut context = setup_wallet_test(
-        30_000,
+        40_000,
         InitialWalletConfig {
             approvals_required_for_config: 1,
             approval_timeout_for_config: Duration::from_secs(3600),
@@ -265,12 +274,442 @@ async fn test_signers_update_initiator_approval() {
             ApprovalDispositionRecord {
                 approver: approvers[0].pubkey(),
                 disposition: ApprovalDisposition::APPROVE,
+                reason: None,
+                stake_weight: 0,
             },
             ApprovalDispositionRecord {
                 approver: approvers[1].pubkey(),
                 disposition: ApprovalDisposition::NONE,
+                reason: None,
+                stake_weight: 0,
             },
         ],
         OperationDisposition::APPROVED,
     );
 }
+
+#[tokio::test]
+async fn test_signer_capabilities() {
+    let approvers = vec![Keypair::new(), Keypair::new(), Keypair::new()];
+    let resigning_signer = Signer::with_capabilities(
+        approvers[2].pubkey(),
+        SignerCapabilities::APPROVE_TRANSFER.union(SignerCapabilities::RESIGN),
+    );
+
+    let mut context = setup_wallet_test(
+        40_000,
+        InitialWalletConfig {
+            approvals_required_for_config: 2,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: vec![
+                (SlotId::new(0), approvers[0].pubkey_as_signer()),
+                (SlotId::new(1), approvers[1].pubkey_as_signer()),
+                (SlotId::new(2), resigning_signer),
+            ],
+            config_approvers: vec![SlotId::new(0), SlotId::new(1)],
+        },
+    )
+    .await;
+
+    // the signer can't initiate anything but its own removal
+    assert_instruction_error(
+        utils::init_update_signer(
+            context.borrow_mut(),
+            &approvers[2],
+            SlotUpdateType::SetIfEmpty,
+            3,
+            Keypair::new().pubkey_as_signer(),
+            None,
+            None,
+        )
+        .await,
+        1,
+        Custom(WalletError::SignerMissingCapability as u32),
+    );
+
+    let multisig_op_account = utils::init_update_signer(
+        context.borrow_mut(),
+        &approvers[2],
+        SlotUpdateType::Clear,
+        2,
+        resigning_signer,
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        get_multisig_op_data(&mut context.banks_client, multisig_op_account)
+            .await
+            .initiator,
+        approvers[2].pubkey()
+    );
+}
+
+async fn init_update_signer_with_op_size(
+    context: &mut WalletTestContext,
+    initiator: &Keypair,
+    signer: Signer,
+    op_size: usize,
+) -> Result<Pubkey, BanksClientError> {
+    let multisig_op_account = Keypair::new();
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                system_instruction::create_account(
+                    &context.payer.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    context.rent.minimum_balance(op_size),
+                    op_size as u64,
+                    &context.program_id,
+                ),
+                instructions::init_update_signer(
+                    &context.program_id,
+                    &context.wallet_account.pubkey(),
+                    &multisig_op_account.pubkey(),
+                    &initiator.pubkey(),
+                    &context.payer.pubkey(),
+                    SlotUpdateType::SetIfEmpty,
+                    SlotId::new(2),
+                    signer,
+                    None,
+                    None,
+                ),
+            ],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &multisig_op_account, initiator],
+            context.recent_blockhash,
+        ))
+        .await
+        .map(|_| multisig_op_account.pubkey())
+}
+
+#[tokio::test]
+async fn test_update_signer_with_sized_op_account() {
+    let approvers = vec![Keypair::new(), Keypair::new(), Keypair::new()];
+    let mut context = setup_wallet_test(
+        40_000,
+        InitialWalletConfig {
+            approvals_required_for_config: 2,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: vec![
+                (SlotId::new(0), approvers[0].pubkey_as_signer()),
+                (SlotId::new(1), approvers[1].pubkey_as_signer()),
+            ],
+            config_approvers: vec![SlotId::new(0), SlotId::new(1)],
+        },
+    )
+    .await;
+    let signer = approvers[2].pubkey_as_signer();
+
+    // the op account has to have room for both config approvers
+    assert_instruction_error(
+        init_update_signer_with_op_size(
+            &mut context,
+            &approvers[0],
+            signer,
+            MultisigOp::len_for(1),
+        )
+        .await,
+        1,
+        Custom(WalletError::MultisigOpAccountTooSmall as u32),
+    );
+    assert_instruction_error(
+        init_update_signer_with_op_size(
+            &mut context,
+            &approvers[0],
+            signer,
+            MultisigOp::len_for(2) + 1,
+        )
+        .await,
+        1,
+        Custom(WalletError::AccountTypeMismatch as u32),
+    );
+
+    let multisig_op_account = init_update_signer_with_op_size(
+        &mut context,
+        &approvers[0],
+        signer,
+        MultisigOp::len_for(2),
+    )
+    .await
+    .unwrap();
+    approve_or_deny_n_of_n_multisig_op(
+        &mut context.banks_client,
+        &context.program_id,
+        &multisig_op_account,
+        vec![&approvers[0], &approvers[1]],
+        &context.payer,
+        context.recent_blockhash,
+        ApprovalDisposition::APPROVE,
+        OperationDisposition::APPROVED,
+    )
+    .await;
+    context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[instructions::finalize_update_signer(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                &multisig_op_account,
+                &context.payer.pubkey(),
+                SlotUpdateType::SetIfEmpty,
+                SlotId::new(2),
+                signer,
+                None,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    assert!(context
+        .banks_client
+        .get_account(multisig_op_account)
+        .await
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        get_wallet(&mut context.banks_client, &context.wallet_account.pubkey())
+            .await
+            .signers[SlotId::new(2)],
+        Some(signer)
+    );
+}
+
+#[tokio::test]
+async fn test_stale_signer_update_is_rejected() {
+    let approvers = vec![
+        Keypair::new(),
+        Keypair::new(),
+        Keypair::new(),
+        Keypair::new(),
+    ];
+    let mut context = setup_wallet_test(
+        40_000,
+        InitialWalletConfig {
+            approvals_required_for_config: 2,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: vec![
+                (SlotId::new(0), approvers[0].pubkey_as_signer()),
+                (SlotId::new(1), approvers[1].pubkey_as_signer()),
+            ],
+            config_approvers: vec![SlotId::new(0), SlotId::new(1)],
+        },
+    )
+    .await;
+    let signer2 = approvers[2].pubkey_as_signer();
+    let signer3 = approvers[3].pubkey_as_signer();
+
+    // both ops are started against the same wallet state
+    let first_op_account = init_update_signer(
+        &mut context,
+        &approvers[0],
+        SlotUpdateType::SetIfEmpty,
+        2,
+        signer2,
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+    let second_op_account = init_update_signer(
+        &mut context,
+        &approvers[0],
+        SlotUpdateType::SetIfEmpty,
+        3,
+        signer3,
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+    for multisig_op_account in [first_op_account, second_op_account] {
+        approve_or_deny_n_of_n_multisig_op(
+            &mut context.banks_client,
+            &context.program_id,
+            &multisig_op_account,
+            vec![&approvers[0], &approvers[1]],
+            &context.payer,
+            context.recent_blockhash,
+            ApprovalDisposition::APPROVE,
+            OperationDisposition::APPROVED,
+        )
+        .await;
+    }
+
+    let finalize = |multisig_op_account: &Pubkey, slot_id: usize, signer: Signer| {
+        Transaction::new_signed_with_payer(
+            &[instructions::finalize_update_signer(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                multisig_op_account,
+                &context.payer.pubkey(),
+                SlotUpdateType::SetIfEmpty,
+                SlotId::new(slot_id),
+                signer,
+                None,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.recent_blockhash,
+        )
+    };
+    let first_finalize = finalize(&first_op_account, 2, signer2);
+    let second_finalize = finalize(&second_op_account, 3, signer3);
+
+    context
+        .banks_client
+        .process_transaction(first_finalize)
+        .await
+        .unwrap();
+    assert_eq!(
+        get_wallet(&mut context.banks_client, &context.wallet_account.pubkey())
+            .await
+            .op_nonce,
+        1
+    );
+
+    // the second op was started before the signers changed
+    assert_instruction_error(
+        context
+            .banks_client
+            .process_transaction(second_finalize)
+            .await,
+        0,
+        Custom(WalletError::StaleOperation as u32),
+    );
+    assert_eq!(
+        get_wallet(&mut context.banks_client, &context.wallet_account.pubkey())
+            .await
+            .signers[SlotId::new(3)],
+        None
+    );
+}
+
+#[tokio::test]
+async fn test_migrate_pending_approvals() {
+    let approvers = vec![Keypair::new(), Keypair::new(), Keypair::new()];
+    let successor = Keypair::new();
+    let signers: Vec<Signer> = approvers
+        .iter()
+        .map(|approver| approver.pubkey_as_signer())
+        .collect();
+
+    let mut context = setup_wallet_test(
+        40_000,
+        InitialWalletConfig {
+            approvals_required_for_config: 2,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            signers: vec![
+                (SlotId::new(0), signers[0]),
+                (SlotId::new(1), signers[1]),
+                (SlotId::new(2), signers[2]),
+            ],
+            config_approvers: vec![SlotId::new(0), SlotId::new(1), SlotId::new(2)],
+        },
+    )
+    .await;
+
+    // an op the signer in slot 2 approves, started before the signer is rotated out
+    let pending_op = utils::init_update_signer(
+        context.borrow_mut(),
+        &approvers[2],
+        SlotUpdateType::SetIfEmpty,
+        3,
+        Keypair::new().pubkey_as_signer(),
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+
+    // the signer stops approving config, and another key takes its slot
+    let mut test_context = TestContext {
+        program_id: context.program_id,
+        banks_client: context.banks_client.clone(),
+        rent: context.rent,
+        payer: Keypair::from_bytes(&context.payer.to_bytes()).unwrap(),
+        recent_blockhash: context.recent_blockhash,
+    };
+    utils::update_wallet_config_policy(
+        &mut test_context,
+        context.wallet_account.pubkey(),
+        &approvers[0],
+        &WalletConfigPolicyUpdate {
+            approvals_required_for_config: 2,
+            approval_timeout_for_config: Duration::from_secs(3600),
+            config_approvers: vec![SlotId::new(0), SlotId::new(1)],
+            signers_hash: hash_signers(&vec![signers[0], signers[1]]),
+            config_policy_timelock: Wallet::DEFAULT_CONFIG_POLICY_TIMELOCK,
+        },
+        vec![&approvers[0], &approvers[1]],
+    )
+    .await;
+    update_signer(
+        context.borrow_mut(),
+        vec![&approvers[0], &approvers[1]],
+        SlotUpdateType::Replace,
+        2,
+        successor.pubkey_as_signer(),
+        Some(Signers::from_vec(vec![
+            (SlotId::new(0), signers[0]),
+            (SlotId::new(1), signers[1]),
+            (SlotId::new(2), successor.pubkey_as_signer()),
+        ])),
+        None,
+        None,
+        None,
+    )
+    .await;
+
+    // the migration takes the signatures of the config approval quorum
+    let migrate = |approvers: &[&Keypair]| {
+        Transaction::new_signed_with_payer(
+            &[instructions::migrate_pending_approvals(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                &approvers
+                    .iter()
+                    .map(|approver| approver.pubkey())
+                    .collect::<Vec<_>>(),
+                &[pending_op],
+            )],
+            Some(&context.payer.pubkey()),
+            &[&[&context.payer], approvers].concat(),
+            context.recent_blockhash,
+        )
+    };
+    let transactions = [
+        migrate(&[&approvers[0]]),
+        migrate(&[&approvers[0], &approvers[1]]),
+    ];
+    assert_instruction_error(
+        context
+            .banks_client
+            .process_transaction(transactions[0].clone())
+            .await,
+        0,
+        Custom(WalletError::ConfigApproverQuorumNotMet as u32),
+    );
+
+    // the approval the retired key gave the pending op is handed on to its successor
+    context
+        .banks_client
+        .process_transaction(transactions[1].clone())
+        .await
+        .unwrap();
+    let multisig_op = get_multisig_op_data(&mut context.banks_client, pending_op).await;
+    assert!(!multisig_op.is_approver(&approvers[2].pubkey()));
+    assert_eq!(
+        multisig_op
+            .disposition_records
+            .iter()
+            .find(|record| record.approver == successor.pubkey())
+            .map(|record| record.disposition),
+        Some(ApprovalDisposition::APPROVE)
+    );
+}
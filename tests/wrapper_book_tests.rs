@@ -0,0 +1,243 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+pub use common::instructions::*;
+pub use common::utils::*;
+
+use solana_program::instruction::{
+    AccountMeta, Instruction, InstructionError, InstructionError::Custom,
+};
+use solana_program::pubkey::Pubkey;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer as SdkSigner};
+use solana_sdk::transaction::{Transaction, TransactionError};
+
+use strike_wallet::error::WalletError;
+use strike_wallet::model::multisig_op::{MultisigOp, WrapDirection};
+use strike_wallet::model::wrapper_book::{WrapperBook, WrapperEntry};
+
+/// Creates a multisig op account and initiates an op in it with `init`, built for that
+/// account, returning the account.
+async fn init_op(
+    context: &mut BalanceAccountTestContext,
+    init: impl FnOnce(&Pubkey) -> Instruction,
+) -> Result<Pubkey, TransactionError> {
+    let multisig_op_account = Keypair::new();
+    let multisig_op_pubkey = multisig_op_account.pubkey();
+    let mut test_context = context.to_test_context();
+    test_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                create_program_owned_account_instruction(
+                    &test_context,
+                    &multisig_op_pubkey,
+                    MultisigOp::LEN,
+                ),
+                init(&multisig_op_pubkey),
+            ],
+            Some(&test_context.payer.pubkey()),
+            &[
+                &test_context.payer,
+                &multisig_op_account,
+                &context.approvers[0],
+            ],
+            test_context.recent_blockhash,
+        ))
+        .await
+        .map_err(|err| err.unwrap())?;
+    Ok(multisig_op_pubkey)
+}
+
+async fn init_wrapper_book_update_op(
+    context: &mut BalanceAccountTestContext,
+    wrapper_book: WrapperBook,
+) -> Result<Pubkey, TransactionError> {
+    let program_id = context.program_id;
+    let wallet_account = context.wallet_account.pubkey();
+    let approver = context.approvers[0].pubkey();
+    init_op(context, |multisig_op_account| {
+        init_wrapper_book_update(
+            &program_id,
+            &wallet_account,
+            multisig_op_account,
+            &approver,
+            &approver,
+            wrapper_book,
+        )
+    })
+    .await
+}
+
+async fn init_adapter_wrap_op(
+    context: &mut BalanceAccountTestContext,
+    balance_account: &Pubkey,
+    wrapper_mint: &Pubkey,
+    direction: WrapDirection,
+) -> Result<Pubkey, TransactionError> {
+    let program_id = context.program_id;
+    let wallet_account = context.wallet_account.pubkey();
+    let approver = context.approvers[0].pubkey();
+    let account_guid_hash = context.balance_account_guid_hash;
+    init_op(context, |multisig_op_account| {
+        init_adapter_wrap_unwrap(
+            &program_id,
+            &wallet_account,
+            multisig_op_account,
+            &approver,
+            &approver,
+            balance_account,
+            &account_guid_hash,
+            wrapper_mint,
+            1_000_000,
+            direction,
+        )
+    })
+    .await
+}
+
+#[tokio::test]
+async fn test_wrapper_book_update() {
+    let (mut context, balance_account) = setup_balance_account_tests_and_finalize(None).await;
+    let mut adapter_accounts = [Pubkey::default(); WrapperEntry::MAX_ADAPTER_ACCOUNTS];
+    adapter_accounts[0] = Pubkey::new_unique();
+    let wrapper = WrapperEntry {
+        mint: Pubkey::new_unique(),
+        adapter_program: Pubkey::new_unique(),
+        adapter_accounts,
+    };
+
+    // nothing but wrapped SOL can be wrapped into before the wrapper book lists the mint
+    assert_eq!(
+        init_adapter_wrap_op(
+            &mut context,
+            &balance_account,
+            &wrapper.mint,
+            WrapDirection::WRAP
+        )
+        .await,
+        Err(TransactionError::InstructionError(
+            1,
+            Custom(WalletError::UnknownWrapperMint as u32)
+        ))
+    );
+
+    // wrapped SOL needs no entry, and a mint can only be listed once
+    for entries in [
+        vec![WrapperEntry {
+            mint: spl_token::native_mint::id(),
+            ..wrapper
+        }],
+        vec![wrapper, wrapper],
+    ] {
+        assert_eq!(
+            init_wrapper_book_update_op(&mut context, WrapperBook { entries }).await,
+            Err(TransactionError::InstructionError(
+                1,
+                Custom(WalletError::InvalidWrapperBook as u32)
+            ))
+        );
+    }
+
+    let wrapper_book = WrapperBook {
+        entries: vec![wrapper],
+    };
+    let multisig_op_account = init_wrapper_book_update_op(&mut context, wrapper_book.clone())
+        .await
+        .unwrap();
+    let mut test_context = context.to_test_context();
+    approve_n_of_n_multisig_op(
+        &mut test_context,
+        &multisig_op_account,
+        vec![&context.approvers[0], &context.approvers[1]],
+    )
+    .await;
+    context
+        .pt_context
+        .banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[finalize_wrapper_book_update(
+                &context.program_id,
+                &context.wallet_account.pubkey(),
+                &multisig_op_account,
+                &context.approvers[0].pubkey(),
+                wrapper_book.clone(),
+            )],
+            Some(&context.pt_context.payer.pubkey()),
+            &[&context.pt_context.payer, &context.approvers[0]],
+            context.pt_context.last_blockhash,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(
+        get_wallet(
+            &mut context.pt_context.banks_client,
+            &context.wallet_account.pubkey()
+        )
+        .await
+        .wrapper_book,
+        wrapper_book
+    );
+
+    // after which the mint can be wrapped into and unwrapped from, but not unwrapped all at once
+    let mut adapter_ops = Vec::new();
+    for direction in [WrapDirection::WRAP, WrapDirection::UNWRAP] {
+        adapter_ops.push(
+            init_adapter_wrap_op(&mut context, &balance_account, &wrapper.mint, direction)
+                .await
+                .unwrap(),
+        );
+    }
+    assert_eq!(
+        init_adapter_wrap_op(
+            &mut context,
+            &balance_account,
+            &wrapper.mint,
+            WrapDirection::UNWRAP_ALL
+        )
+        .await,
+        Err(TransactionError::InstructionError(
+            1,
+            InstructionError::InvalidArgument
+        ))
+    );
+
+    // the adapter is only passed the accounts its wrapper book entry lists
+    for adapter_accounts in [
+        vec![],
+        vec![AccountMeta::new(Pubkey::new_unique(), false)],
+        vec![
+            AccountMeta::new(wrapper.adapter_accounts[0], false),
+            AccountMeta::new(Pubkey::new_unique(), false),
+        ],
+    ] {
+        assert_eq!(
+            context
+                .pt_context
+                .banks_client
+                .process_transaction(Transaction::new_signed_with_payer(
+                    &[finalize_adapter_wrap_unwrap(
+                        &context.program_id,
+                        &adapter_ops[0],
+                        &context.wallet_account.pubkey(),
+                        &balance_account,
+                        &context.approvers[0].pubkey(),
+                        &context.balance_account_guid_hash,
+                        &wrapper,
+                        1_000_000,
+                        WrapDirection::WRAP,
+                        None,
+                        adapter_accounts,
+                    )],
+                    Some(&context.pt_context.payer.pubkey()),
+                    &[&context.pt_context.payer, &context.approvers[0]],
+                    context.pt_context.last_blockhash,
+                ))
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(0, Custom(WalletError::AccountNotRecognized as u32))
+        );
+    }
+}